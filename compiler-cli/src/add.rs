@@ -2,14 +2,40 @@ use camino::{Utf8Path, Utf8PathBuf};
 
 use gleam_core::{
     error::{FileIoAction, FileKind},
+    hex::typosquat::{self, LookAlike},
     Error, Result,
 };
+use hexpm::version::Version;
 
-use crate::{cli, dependencies::UseManifest, fs};
+use crate::{cli, dependencies::UseManifest, fs, http::HttpClient};
 
 pub fn command(packages: Vec<String>, dev: bool) -> Result<()> {
     let paths = crate::find_project_paths()?;
 
+    for look_alike in look_alikes(&packages) {
+        println!(
+            "The package `{}` has been downloaded {} times, and its name is
+close to that of `{}`, which has been downloaded {} times.
+
+Packages are sometimes published with names like those of popular
+packages to trick people into adding them.",
+            look_alike.package.name,
+            look_alike.package.downloads,
+            look_alike.popular.name,
+            look_alike.popular.downloads,
+        );
+        let question = format!(
+            "\nAre you sure you want to add `{}`?",
+            look_alike.package.name
+        );
+        let should_add = cli::confirm(&question)?;
+        println!();
+        if !should_add {
+            println!("Not adding packages.");
+            return Ok(());
+        }
+    }
+
     // Insert the new packages into the manifest and perform dependency
     // resolution to determine suitable versions
     let manifest = crate::dependencies::download(
@@ -35,15 +61,7 @@ pub fn command(packages: Vec<String>, dev: bool) -> Result<()> {
 
         tracing::info!(version=%version, "new_package_version_resolved");
 
-        // Produce a version requirement locked to the major version.
-        // i.e. if 1.2.3 is selected we want >= 1.2.3 and < 2.0.0
-        let range = format!(
-            ">= {}.{}.{} and < {}.0.0",
-            version.major,
-            version.minor,
-            version.patch,
-            version.major + 1
-        );
+        let range = major_version_range(version);
 
         // False positive. This package doesn't use the indexing API correctly.
         #[allow(clippy::indexing_slicing)]
@@ -68,7 +86,52 @@ pub fn command(packages: Vec<String>, dev: bool) -> Result<()> {
     Ok(())
 }
 
-fn read_toml_edit(name: &str) -> Result<toml_edit::Document, Error> {
+/// A version requirement locked to the major version of the given version.
+/// i.e. if 1.2.3 is given we want >= 1.2.3 and < 2.0.0
+pub(crate) fn major_version_range(version: &Version) -> String {
+    format!(
+        ">= {}.{}.{} and < {}.0.0",
+        version.major,
+        version.minor,
+        version.patch,
+        version.major + 1
+    )
+}
+
+/// The packages which are named like much more popular packages on Hex.
+///
+/// The check is only advice, so if Hex cannot be reached the packages are
+/// taken to be fine, and adding them fails later if they cannot be found.
+fn look_alikes(packages: &[String]) -> Vec<LookAlike> {
+    let runtime = tokio::runtime::Runtime::new().expect("Unable to start Tokio async runtime");
+    let config = hexpm::Config::new();
+    let http = HttpClient::new();
+    runtime.block_on(async {
+        let mut popular = vec![];
+        for page in 1..=typosquat::POPULAR_PACKAGE_PAGES {
+            match typosquat::get_popular_packages(page, &config, &http).await {
+                Ok(packages) => popular.extend(packages),
+                Err(error) => {
+                    tracing::warn!(error = ?error, "popular_packages_lookup_failed");
+                    return vec![];
+                }
+            }
+        }
+        let mut look_alikes = vec![];
+        for name in packages {
+            match typosquat::get_downloads(name, &config, &http).await {
+                Ok(Some(package)) => look_alikes.extend(typosquat::look_alike(&package, &popular)),
+                Ok(None) => (),
+                Err(error) => {
+                    tracing::warn!(package = name, error = ?error, "package_downloads_lookup_failed")
+                }
+            }
+        }
+        look_alikes
+    })
+}
+
+pub(crate) fn read_toml_edit(name: &str) -> Result<toml_edit::Document, Error> {
     fs::read(name)?
         .parse::<toml_edit::Document>()
         .map_err(|e| Error::FileIo {
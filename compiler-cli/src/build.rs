@@ -1,7 +1,8 @@
 use std::{sync::Arc, time::Instant};
 
 use gleam_core::{
-    build::{Built, Codegen, Options, ProjectCompiler},
+    analyse::TargetSupport,
+    build::{Built, Codegen, Mode, NullTelemetry, Options, ProjectCompiler, Target, Telemetry},
     manifest::Manifest,
     paths::ProjectPaths,
     Result,
@@ -11,7 +12,7 @@ use crate::{
     build_lock::BuildLock,
     cli,
     dependencies::UseManifest,
-    fs::{self, get_current_directory, get_project_root, ConsoleWarningEmitter},
+    fs::{self, ConsoleWarningEmitter},
 };
 
 pub fn download_dependencies() -> Result<Manifest> {
@@ -20,38 +21,106 @@ pub fn download_dependencies() -> Result<Manifest> {
 }
 
 pub fn main(options: Options, manifest: Manifest) -> Result<Built> {
-    let paths = crate::find_project_paths()?;
+    main_with(crate::find_project_paths()?, options, manifest, false)
+}
+
+/// Compiles the project as `main` does with the given paths, keeping the
+/// modules of the dependencies compiled along with their typed ASTs for
+/// commands that need them.
+pub fn main_keeping_dependency_modules(
+    paths: ProjectPaths,
+    options: Options,
+    manifest: Manifest,
+) -> Result<Built> {
+    main_with(paths, options, manifest, true)
+}
+
+fn main_with(
+    paths: ProjectPaths,
+    options: Options,
+    manifest: Manifest,
+    keep_dependency_modules: bool,
+) -> Result<Built> {
     let perform_codegen = options.codegen;
-    let root_config = crate::config::root_config()?;
-    let telemetry = Box::new(cli::Reporter::new());
-    let io = fs::ProjectIO::new();
     let start = Instant::now();
-    let lock = BuildLock::new_target(
-        &paths,
-        options.mode,
-        options.target.unwrap_or(root_config.target),
-    )?;
-    let current_dir = get_project_root(get_current_directory()?)?;
-
-    tracing::info!("Compiling packages");
-    let result = {
-        let _guard = lock.lock(telemetry.as_ref());
-        let compiler = ProjectCompiler::new(
-            root_config,
-            options,
-            manifest.packages,
-            telemetry,
-            Arc::new(ConsoleWarningEmitter),
-            ProjectPaths::new(current_dir),
-            io,
-        );
-        compiler.compile()?
-    };
+    let telemetry = Box::new(cli::Reporter::new());
+    let built = compile(paths, options, manifest, telemetry, keep_dependency_modules)?;
 
     match perform_codegen {
         Codegen::All | Codegen::DepsOnly => cli::print_compiled(start.elapsed()),
         Codegen::None => cli::print_checked(start.elapsed()),
     };
 
-    Ok(result)
+    Ok(built)
+}
+
+/// Type checks all the modules of the root package for the commands that read
+/// them, such as `gleam lint`, printing progress messages unless `quietly`.
+///
+/// The modules of the package cached by a previous compilation are not type
+/// checked again, so the package is compiled afresh in a directory of its
+/// own, leaving the build cache used by `gleam build` untouched.
+pub fn analyse_root_package(target: Option<Target>, quietly: bool) -> Result<Built> {
+    let paths = crate::find_project_paths()?;
+    let config = crate::config::root_config()?;
+    let target = target.unwrap_or(config.target);
+    let paths = paths
+        .clone()
+        .compiling_into(paths.build_analysis_directory());
+    fs::delete_directory(&paths.build_directory_for_package(Mode::Dev, target, &config.name))?;
+
+    let options = Options {
+        root_target_support: TargetSupport::Enforced,
+        warnings_as_errors: false,
+        verify_artefacts: false,
+        codegen: Codegen::DepsOnly,
+        mode: Mode::Dev,
+        target: Some(target),
+    };
+    let manifest = download_dependencies()?;
+    if quietly {
+        compile(paths, options, manifest, Box::new(NullTelemetry), false)
+    } else {
+        main_with(paths, options, manifest, false)
+    }
+}
+
+/// Compiles the project with the `gleam daemon` for it if one is running, or
+/// else as `main` does.
+pub fn main_or_daemon(options: Options) -> Result<()> {
+    let paths = crate::find_project_paths()?;
+    if crate::daemon::build(&paths, &options)? {
+        return Ok(());
+    }
+    main(options, download_dependencies()?).map(|_| ())
+}
+
+fn compile(
+    paths: ProjectPaths,
+    options: Options,
+    manifest: Manifest,
+    telemetry: Box<dyn Telemetry>,
+    keep_dependency_modules: bool,
+) -> Result<Built> {
+    let root_config = crate::config::root_config()?;
+    let io = fs::ProjectIO::new();
+    let lock = BuildLock::new_target(
+        &paths,
+        options.mode,
+        options.target.unwrap_or(root_config.target),
+    )?;
+    tracing::info!("compiling_packages");
+    let _guard = lock.lock(telemetry.as_ref());
+    let mut compiler = ProjectCompiler::new(
+        root_config,
+        options,
+        manifest.packages,
+        telemetry,
+        Arc::new(ConsoleWarningEmitter),
+        paths,
+        io,
+    );
+    compiler.environment = std::env::vars().collect();
+    compiler.keep_dependency_modules = keep_dependency_modules;
+    compiler.compile()
 }
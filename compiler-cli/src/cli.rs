@@ -1,20 +1,85 @@
 use gleam_core::{
     build::Telemetry,
+    dependency::{Resolution, ResolutionObserver, SourceKind},
+    diagnostic::Theme,
     error::{Error, StandardIoAction},
 };
 use hexpm::version::Version;
 use std::{
     io::{IsTerminal, Write},
-    time::{Duration, Instant},
+    sync::OnceLock,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
-use termcolor::{BufferWriter, Color, ColorChoice, ColorSpec, WriteColor};
+use termcolor::{BufferWriter, ColorChoice, ColorSpec, WriteColor};
+
+/// Whether to use colour in the output, as chosen with the `--color` flag.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum ColourMode {
+    /// Use colour if the output is a terminal
+    #[default]
+    Auto,
+    /// Always use colour
+    Always,
+    /// Never use colour
+    Never,
+}
+
+static COLOUR_MODE: OnceLock<ColourMode> = OnceLock::new();
+static THEME: OnceLock<Theme> = OnceLock::new();
+static MAX_ERRORS: OnceLock<Option<usize>> = OnceLock::new();
+
+/// Sets how output is drawn for the rest of the program. Only the first call
+/// has any effect.
+pub fn set_display(colour: ColourMode, theme: Theme) {
+    let _ = COLOUR_MODE.set(colour);
+    let _ = THEME.set(theme);
+}
+
+/// The theme used to draw diagnostics and progress messages.
+pub fn theme() -> Theme {
+    THEME.get().copied().unwrap_or_default()
+}
+
+/// Sets the most errors shown when the command fails. Only the first call has
+/// any effect.
+pub fn set_max_errors(max_errors: Option<usize>) {
+    let _ = MAX_ERRORS.set(max_errors);
+}
+
+/// The most errors shown when the command fails, if they are limited.
+pub fn max_errors() -> Option<usize> {
+    MAX_ERRORS.get().copied().flatten()
+}
+
+/// How many of the most constrained packages a resolution summary names.
+const MOST_CONSTRAINED_SHOWN: usize = 3;
 
 #[derive(Debug, Default, Clone)]
-pub struct Reporter;
+pub struct Reporter {
+    /// Whether to print a summary of each dependency resolution.
+    resolution_summary: bool,
+}
 
 impl Reporter {
     pub fn new() -> Self {
-        Self
+        Self::default()
+    }
+
+    /// A reporter which also prints a summary of each dependency resolution.
+    pub fn with_resolution_summary() -> Self {
+        Self {
+            resolution_summary: true,
+        }
+    }
+}
+
+impl ResolutionObserver for Reporter {
+    fn package_fetched(&self, name: &str, _source: &SourceKind) {
+        print_resolution_progress(name)
+    }
+
+    fn version_chosen(&self, name: &str, _version: &Version) {
+        print_resolution_progress(name)
     }
 }
 
@@ -31,19 +96,122 @@ impl Telemetry for Reporter {
         print_downloading(name)
     }
 
-    fn packages_downloaded(&self, start: Instant, count: usize) {
-        print_packages_downloaded(start, count)
+    fn package_download_progress(&self, name: &str, downloaded: u64, total: Option<u64>) {
+        print_download_progress(name, downloaded, total)
+    }
+
+    fn package_downloaded(&self, name: &str, size: u64) {
+        print_package_downloaded(name, size)
+    }
+
+    fn packages_downloaded(&self, start: Instant, count: usize, size: u64) {
+        print_packages_downloaded(start, count, size)
     }
 
     fn resolving_package_versions(&self) {
         print_resolving_versions()
     }
 
+    fn package_versions_resolved(&self, start: Instant, resolution: &Resolution) {
+        clear_progress();
+        if self.resolution_summary {
+            print_resolution_summary(start.elapsed(), resolution)
+        }
+    }
+
     fn waiting_for_build_directory_lock(&self) {
         print_waiting_for_build_directory_lock()
     }
 }
 
+/// A reporter which prints nothing but a summary of each dependency
+/// resolution, as a line of JSON, so that its output can be read by other
+/// programs.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct JsonResolutionReporter;
+
+impl ResolutionObserver for JsonResolutionReporter {}
+
+impl Telemetry for JsonResolutionReporter {
+    fn waiting_for_build_directory_lock(&self) {}
+    fn resolving_package_versions(&self) {}
+    fn downloading_package(&self, _name: &str) {}
+    fn package_download_progress(&self, _name: &str, _downloaded: u64, _total: Option<u64>) {}
+    fn package_downloaded(&self, _name: &str, _size: u64) {}
+    fn packages_downloaded(&self, _start: Instant, _count: usize, _size: u64) {}
+    fn compiling_package(&self, _name: &str) {}
+    fn checking_package(&self, _name: &str) {}
+
+    fn package_versions_resolved(&self, start: Instant, resolution: &Resolution) {
+        println!("{}", resolution_summary_json(start.elapsed(), resolution));
+    }
+}
+
+fn print_resolution_summary(duration: Duration, resolution: &Resolution) {
+    let statistics = &resolution.statistics;
+    let fetched: usize = statistics.fetched.values().sum();
+    print_colourful_prefix(
+        "Resolved",
+        &format!(
+            "{} in {}",
+            plural(resolution.packages.len(), "package"),
+            seconds(duration)
+        ),
+    );
+    print_colourful_prefix(
+        "",
+        &format!(
+            "{} fetched, {:.0}% cache hits",
+            plural(fetched, "package"),
+            statistics.cache_hit_rate() * 100.0
+        ),
+    );
+    print_colourful_prefix(
+        "",
+        &format!(
+            "{}, {}",
+            plural(resolution.solver.decisions, "decision"),
+            plural(resolution.solver.backtracks, "backtrack")
+        ),
+    );
+    let most_constrained = resolution.most_constrained(MOST_CONSTRAINED_SHOWN);
+    if !most_constrained.is_empty() {
+        let packages = most_constrained
+            .iter()
+            .map(|(name, requirements)| format!("{name} ({requirements})"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        print_colourful_prefix("", &format!("most constrained: {packages}"));
+    }
+}
+
+fn resolution_summary_json(duration: Duration, resolution: &Resolution) -> serde_json::Value {
+    let statistics = &resolution.statistics;
+    let most_constrained: Vec<_> = resolution
+        .most_constrained(MOST_CONSTRAINED_SHOWN)
+        .into_iter()
+        .map(|(name, requirements)| serde_json::json!({"name": name, "requirements": requirements}))
+        .collect();
+    serde_json::json!({
+        "packages": resolution.packages.len(),
+        "fetched": statistics.fetched.values().sum::<usize>(),
+        "provided": statistics.provided,
+        "cache_hits": statistics.cache_hits,
+        "cache_hit_rate": statistics.cache_hit_rate(),
+        "decisions": resolution.solver.decisions,
+        "backtracks": resolution.solver.backtracks,
+        "duration_ms": duration.as_millis() as u64,
+        "most_constrained": most_constrained,
+    })
+}
+
+fn plural(count: usize, noun: &str) -> String {
+    match count {
+        1 => format!("1 {noun}"),
+        _ => format!("{count} {noun}s"),
+    }
+}
+
 pub fn ask(question: &str) -> Result<String, Error> {
     print!("{question}: ");
     std::io::stdout().flush().expect("ask stdout flush");
@@ -91,6 +259,14 @@ pub fn print_unretired(package: &str, version: &str) {
     print_colourful_prefix("Unretired", &format!("{package} {version}"))
 }
 
+pub fn print_serving_documentation(url: &str) {
+    print_colourful_prefix("Serving", &format!("documentation at {url}"));
+}
+
+pub fn print_rerendering_documentation() {
+    print_colourful_prefix("Rerendering", "documentation");
+}
+
 pub fn print_publishing_documentation() {
     print_colourful_prefix("Publishing", "documentation");
 }
@@ -115,6 +291,10 @@ pub(crate) fn print_exported(text: &str) {
     print_colourful_prefix("Exported", text)
 }
 
+pub(crate) fn print_generated(text: &str) {
+    print_colourful_prefix("Generated", text)
+}
+
 pub(crate) fn print_checking(text: &str) {
     print_colourful_prefix("Checking", text)
 }
@@ -139,17 +319,113 @@ pub(crate) fn print_removed(text: &str) {
     print_colourful_prefix("Removed", text)
 }
 
+pub(crate) fn print_relaxed(text: &str) {
+    print_colourful_prefix("Relaxed", text)
+}
+
+pub(crate) fn print_unlocked(text: &str) {
+    print_colourful_prefix("Unlocked", text)
+}
+
 pub(crate) fn print_generating_documentation() {
     print_colourful_prefix("Generating", "documentation")
 }
 
-fn print_packages_downloaded(start: Instant, count: usize) {
+/// Shows how much of a package has been downloaded on a line that is replaced
+/// by the next message about downloads. Nothing is shown if standard output is
+/// not a terminal.
+fn print_download_progress(name: &str, downloaded: u64, total: Option<u64>) {
+    if !std::io::stdout().is_terminal() {
+        return;
+    }
+    let size = match total {
+        Some(total) => format!("{} of {}", file_size(downloaded), file_size(total)),
+        None => file_size(downloaded),
+    };
+    write_colourful_prefix("Downloading", &format!("{name} {size}"), "\r", "\x1b[K");
+}
+
+/// The frames of the spinner shown while versions are resolved.
+const SPINNER: [char; 4] = ['-', '\\', '|', '/'];
+
+/// Shows the package the solver is working on next to a spinner, on a line
+/// that is replaced by the next message. Nothing is shown if standard output
+/// is not a terminal.
+fn print_resolution_progress(name: &str) {
+    if !std::io::stdout().is_terminal() {
+        return;
+    }
+    // The spinner turns with time rather than with each event, so it moves
+    // at the same speed however quickly the solver works.
+    let millis = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis();
+    let frame = SPINNER
+        .get(millis as usize / 100 % SPINNER.len())
+        .unwrap_or(&'-');
+    write_colourful_prefix("Resolving", &format!("{frame} {name}"), "\r", "\x1b[K");
+}
+
+/// Removes a line of progress that has not been replaced by another message.
+fn clear_progress() {
+    if std::io::stdout().is_terminal() {
+        print!("\r\x1b[K");
+        std::io::stdout()
+            .flush()
+            .expect("clear_progress stdout flush");
+    }
+}
+
+fn print_package_downloaded(name: &str, size: u64) {
+    print_replacing_progress("Downloaded", &format!("{name} ({})", file_size(size)))
+}
+
+fn print_packages_downloaded(start: Instant, count: usize, size: u64) {
     let elapsed = seconds(start.elapsed());
-    let msg = match count {
-        1 => format!("1 package in {elapsed}"),
-        _ => format!("{count} packages in {elapsed}"),
+    let packages = match count {
+        1 => "1 package".into(),
+        _ => format!("{count} packages"),
+    };
+    let msg = match size {
+        0 => format!("{packages} in {elapsed}"),
+        _ => format!("{packages} ({}) in {elapsed}", file_size(size)),
+    };
+    print_replacing_progress("Downloaded", &msg)
+}
+
+/// Prints a message over the progress of a download, if it was shown.
+fn print_replacing_progress(prefix: &str, text: &str) {
+    match std::io::stdout().is_terminal() {
+        true => write_colourful_prefix(prefix, text, "\r", "\x1b[K\n"),
+        false => print_colourful_prefix(prefix, text),
+    }
+}
+
+/// Formats a number of bytes to be read by people, such as `1.5 MiB`.
+pub fn file_size(bytes: u64) -> String {
+    const KIBIBYTE: u64 = 1024;
+    const MEBIBYTE: u64 = 1024 * KIBIBYTE;
+    match bytes {
+        _ if bytes < KIBIBYTE => format!("{bytes} B"),
+        _ if bytes < MEBIBYTE => format!("{:.1} KiB", bytes as f64 / KIBIBYTE as f64),
+        _ => format!("{:.1} MiB", bytes as f64 / MEBIBYTE as f64),
+    }
+}
+
+/// Formats a span of time to be read by people to the nearest minute, such as
+/// `2 hours 5 minutes`.
+pub fn time_span(duration: Duration) -> String {
+    let minutes = duration.as_secs() / 60;
+    let plural = |n: u64, unit: &str| match n {
+        1 => format!("1 {unit}"),
+        _ => format!("{n} {unit}s"),
     };
-    print_colourful_prefix("Downloaded", &msg)
+    match (minutes / 60, minutes % 60) {
+        (0, minutes) => plural(minutes, "minute"),
+        (hours, 0) => plural(hours, "hour"),
+        (hours, minutes) => format!("{} {}", plural(hours, "hour"), plural(minutes, "minute")),
+    }
 }
 
 pub fn seconds(duration: Duration) -> String {
@@ -157,20 +433,21 @@ pub fn seconds(duration: Duration) -> String {
 }
 
 pub fn print_colourful_prefix(prefix: &str, text: &str) {
+    write_colourful_prefix(prefix, text, "", "\n")
+}
+
+fn write_colourful_prefix(prefix: &str, text: &str, start: &str, end: &str) {
     let buffer_writer = stdout_buffer_writer();
     let mut buffer = buffer_writer.buffer();
+    write!(buffer, "{start}").expect("print_green_prefix");
     buffer
-        .set_color(
-            ColorSpec::new()
-                .set_intense(true)
-                .set_fg(Some(Color::Magenta)),
-        )
+        .set_color(&theme().progress_colour())
         .expect("print_green_prefix");
     write!(buffer, "{prefix: >11}").expect("print_green_prefix");
     buffer
         .set_color(&ColorSpec::new())
         .expect("print_green_prefix");
-    writeln!(buffer, " {text}").expect("print_green_prefix");
+    write!(buffer, " {text}{end}").expect("print_green_prefix");
     buffer_writer.print(&buffer).expect("print_green_prefix");
 }
 
@@ -193,6 +470,11 @@ fn colour_forced() -> bool {
 }
 
 fn color_choice() -> ColorChoice {
+    match COLOUR_MODE.get().copied().unwrap_or_default() {
+        ColourMode::Always => return ColorChoice::Always,
+        ColourMode::Never => return ColorChoice::Never,
+        ColourMode::Auto => (),
+    }
     if colour_forced() {
         ColorChoice::Always
     } else if std::io::stderr().is_terminal() {
@@ -33,10 +33,11 @@ pub fn command(options: CompilePackage) -> Result<()> {
             prelude_location: options
                 .javascript_prelude
                 .ok_or_else(|| Error::JavaScriptPreludeRequired)?,
+            platform_location: None,
         },
     };
 
-    tracing::info!("Compiling package");
+    tracing::info!(package = %config.name, "compiling_package");
 
     let mut compiler = PackageCompiler::new(
         &config,
@@ -68,7 +69,7 @@ fn load_libraries(
     ids: &UniqueIdGenerator,
     lib: &Utf8Path,
 ) -> Result<im::HashMap<EcoString, ModuleInterface>> {
-    tracing::info!("Reading precompiled module metadata files");
+    tracing::info!(lib = %lib, "reading_precompiled_module_metadata");
     let mut manifests = im::HashMap::new();
     for lib in fs::read_dir(lib)?.filter_map(Result::ok) {
         let path = lib.path().join(paths::ARTEFACT_DIRECTORY_NAME);
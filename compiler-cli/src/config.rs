@@ -1,13 +1,29 @@
-use camino::Utf8PathBuf;
+use std::sync::{Mutex, OnceLock};
+
+use camino::{Utf8Path, Utf8PathBuf};
 
 use gleam_core::{
-    config::PackageConfig,
+    config::{overlay_path, ConfigOverlay, PackageConfig},
     error::{Error, FileIoAction, FileKind},
     manifest::{Manifest, ManifestPackage, ManifestPackageSource},
     paths::ProjectPaths,
+    warning::WarningEmitterIO,
 };
 
-use crate::fs::{get_current_directory, get_project_root};
+use crate::fs::{get_current_directory, get_project_root, ConsoleWarningEmitter, ProjectIO};
+
+static ENVIRONMENT: OnceLock<Option<String>> = OnceLock::new();
+
+/// Sets the environment, chosen with the `--config-env` flag, whose overlay
+/// is merged over the config of the root package. Only the first call has
+/// any effect.
+pub fn set_environment(env: Option<String>) {
+    let _ = ENVIRONMENT.set(env);
+}
+
+fn environment() -> Option<&'static str> {
+    ENVIRONMENT.get().and_then(Option::as_deref)
+}
 
 pub fn root_config() -> Result<PackageConfig, Error> {
     let dir = get_project_root(get_current_directory()?)?;
@@ -43,31 +59,78 @@ pub fn find_package_config_for_module(
             continue;
         }
 
-        let configuration = read(root.join("gleam.toml"))?;
+        let configuration = read_dependency(root.join("gleam.toml"))?;
         return Ok((configuration, PackageKind::Dependency));
     }
 
     Ok((root_config()?, PackageKind::Root))
 }
 
+/// Get the config for a Gleam dependency.
+pub fn dependency_config(
+    package: &ManifestPackage,
+    project_paths: &ProjectPaths,
+) -> Result<PackageConfig, Error> {
+    read_dependency(package_root(package, project_paths).join("gleam.toml"))
+}
+
 fn package_root(package: &ManifestPackage, project_paths: &ProjectPaths) -> Utf8PathBuf {
     match &package.source {
         ManifestPackageSource::Local { path } => project_paths.root().join(path),
 
         ManifestPackageSource::Hex { .. } | ManifestPackageSource::Git { .. } => {
-            project_paths.build_packages_package(&package.name)
+            project_paths.build_packages_package_source(package)
         }
     }
 }
 
 pub fn read(config_path: Utf8PathBuf) -> Result<PackageConfig, Error> {
     let toml = crate::fs::read(&config_path)?;
-    let config: PackageConfig = toml::from_str(&toml).map_err(|e| Error::FileIo {
-        action: FileIoAction::Parse,
-        kind: FileKind::File,
-        path: config_path,
-        err: Some(e.to_string()),
-    })?;
+    warn_of_unknown_keys(&toml, &config_path);
+    let config = match environment() {
+        None => PackageConfig::parse(&toml, &config_path)?,
+        Some(env) => {
+            let path = overlay_path(&config_path, env);
+            if !path.is_file() {
+                return Err(Error::FileIo {
+                    action: FileIoAction::Read,
+                    kind: FileKind::File,
+                    path,
+                    err: Some("File not found".into()),
+                });
+            }
+            let src = crate::fs::read(&path)?;
+            let overlay = ConfigOverlay {
+                env,
+                path: &path,
+                src: &src,
+            };
+            PackageConfig::parse_with_overlay(&toml, &config_path, &overlay)?
+        }
+    };
+    config.check_gleam_compatibility()?;
+    Ok(config)
+}
+
+/// Prints warnings for the keys of a config that Gleam does not know about.
+/// The config is read many times by a single command, so they are only
+/// printed the first time it is read.
+fn warn_of_unknown_keys(toml: &str, config_path: &Utf8Path) {
+    static WARNED: Mutex<Vec<Utf8PathBuf>> = Mutex::new(Vec::new());
+    let mut warned = WARNED.lock().expect("unknown key warnings lock");
+    if warned.iter().any(|path| path == config_path) {
+        return;
+    }
+    warned.push(config_path.to_path_buf());
+    for warning in PackageConfig::unknown_key_warnings(toml, config_path) {
+        ConsoleWarningEmitter.emit_warning(warning);
+    }
+}
+
+/// Reads the config of a dependency, which unlike that of the root package
+/// may have keys unknown to this version of Gleam.
+fn read_dependency(config_path: Utf8PathBuf) -> Result<PackageConfig, Error> {
+    let config = PackageConfig::read(config_path, &ProjectIO::new())?;
     config.check_gleam_compatibility()?;
     Ok(config)
 }
@@ -100,12 +163,13 @@ mod tests {
             otp_app: None,
             requirements: vec![],
             source: ManifestPackageSource::Hex {
-                outer_checksum: Base16Checksum(vec![]),
+                outer_checksum: Base16Checksum(vec![0xab, 0xcd]),
+                organization: None,
             },
         };
         assert_eq!(
             package_root(&package, &paths),
-            Utf8PathBuf::from("/app/build/packages/the_package")
+            Utf8PathBuf::from("/app/build/packages/the_package-abcd")
         );
     }
 
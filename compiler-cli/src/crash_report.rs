@@ -0,0 +1,191 @@
+//! Crash reports, written to a directory when the compiler panics so that
+//! they can be attached to a bug report. A report holds the panic and its
+//! backtrace, the version of Gleam, the `gleam.toml` and `manifest.toml` of
+//! the project, and the source of the module that was being compiled.
+//!
+//! When `GLEAM_MINIMISE_CRASH` is set the module is also reduced to the top
+//! level definitions needed to reproduce the crash, by compiling the project
+//! again with some of them removed. This happens after the panic has unwound,
+//! so that the lock on the build directory has been released.
+
+use std::{
+    backtrace::Backtrace,
+    process::{Command, Stdio},
+    sync::Mutex,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use camino::{Utf8Path, Utf8PathBuf};
+use gleam_core::crash::{self, CompilingModule};
+
+/// Set to `false` to not write crash reports.
+const REPORT_VARIABLE: &str = "GLEAM_CRASH_REPORT";
+
+/// Set to `true` to minimise the module that was being compiled.
+const MINIMISE_VARIABLE: &str = "GLEAM_MINIMISE_CRASH";
+
+/// The exit code of a Rust program that panicked.
+const PANIC_EXIT_CODE: i32 = 101;
+
+const REPORT_FILE: &str = "report.txt";
+const MODULE_FILE: &str = "module.gleam";
+const MINIMISED_FILE: &str = "minimised.gleam";
+
+/// The report written by the last panic, and the module it was compiling.
+static WRITTEN: Mutex<Option<(Utf8PathBuf, Option<CompilingModule>)>> = Mutex::new(None);
+
+/// Writes a report of the panic, returning the directory it was written to.
+pub fn write(panic: &str) -> Option<Utf8PathBuf> {
+    if std::env::var(REPORT_VARIABLE).is_ok_and(|value| value == "false") {
+        return None;
+    }
+    let backtrace = Backtrace::force_capture();
+    let module = crash::compiling_module();
+    let root = crate::find_project_paths().ok();
+    let seconds = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default();
+    let directory = match &root {
+        Some(paths) => paths.build_directory().join("crash-reports"),
+        None => Utf8PathBuf::from_path_buf(std::env::temp_dir())
+            .ok()?
+            .join("gleam-crash-reports"),
+    }
+    .join(format!("{seconds}-{}", std::process::id()));
+    crate::fs::mkdir(&directory).ok()?;
+
+    let command = std::env::args().skip(1).collect::<Vec<_>>().join(" ");
+    let text = report_text(panic, &command, module.as_ref(), &backtrace.to_string());
+    crate::fs::write(&directory.join(REPORT_FILE), &text).ok()?;
+    if let Some(paths) = &root {
+        for file in [paths.root_config(), paths.manifest()] {
+            if let (Some(name), Ok(contents)) = (file.file_name(), crate::fs::read(&file)) {
+                let _ = crate::fs::write(&directory.join(name), &contents);
+            }
+        }
+    }
+    if let Some(CompilingModule { path, .. }) = &module {
+        if let Ok(code) = crate::fs::read(path) {
+            let _ = crate::fs::write(&directory.join(MODULE_FILE), &code);
+        }
+    }
+
+    *WRITTEN.lock().expect("crash report lock") = Some((directory.clone(), module));
+    Some(directory)
+}
+
+fn report_text(
+    panic: &str,
+    command: &str,
+    module: Option<&CompilingModule>,
+    backtrace: &str,
+) -> String {
+    let module = match module {
+        Some(CompilingModule { name, path }) => format!("{name} ({path})"),
+        None => "none".into(),
+    };
+    format!(
+        "Panic: {panic}
+Gleam version: {version}
+Operating system: {os} ({arch})
+Command: gleam {command}
+Compiling module: {module}
+
+Backtrace:
+{backtrace}",
+        version = env!("CARGO_PKG_VERSION"),
+        os = std::env::consts::OS,
+        arch = std::env::consts::ARCH,
+    )
+}
+
+/// Minimises the module of the last crash report if `GLEAM_MINIMISE_CRASH`
+/// is set, then exits with the exit code of a panic.
+pub fn finish() -> ! {
+    let written = WRITTEN.lock().ok().and_then(|mut written| written.take());
+    let minimise = std::env::var(MINIMISE_VARIABLE).is_ok_and(|value| value == "true");
+    if let (true, Some((directory, Some(module)))) = (minimise, written) {
+        minimise_module(&directory, &module.path);
+    }
+    std::process::exit(PANIC_EXIT_CODE)
+}
+
+fn minimise_module(directory: &Utf8Path, path: &Utf8Path) {
+    let Ok(code) = crate::fs::read(path) else {
+        return;
+    };
+    eprintln!("Minimising {path}, this may take a while...");
+    let still_crashes = |candidate: &str| {
+        let crashed = crate::fs::write(path, candidate).is_ok() && compile_crashes();
+        let _ = crate::fs::write(path, &code);
+        crashed
+    };
+    if !still_crashes(&code) {
+        eprintln!("The crash could not be reproduced, so the module was not minimised.");
+        return;
+    }
+    let minimised = crash::minimise(&code, still_crashes);
+    let path = directory.join(MINIMISED_FILE);
+    if crate::fs::write(&path, &minimised).is_ok() {
+        eprintln!("The minimised module has been written to {path}");
+    }
+}
+
+/// Compiles the project again in a new process, returning whether it panicked.
+/// The command that crashed is used if it only compiles the project, so that
+/// code is not run while minimising.
+fn compile_crashes() -> bool {
+    let Ok(executable) = std::env::current_exe() else {
+        return false;
+    };
+    let args: Vec<String> = match std::env::args().nth(1).as_deref() {
+        Some("build" | "check") => std::env::args().skip(1).collect(),
+        _ => vec!["build".into()],
+    };
+    Command::new(executable)
+        .args(args)
+        .env(REPORT_VARIABLE, "false")
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .is_ok_and(|status| status.code() == Some(PANIC_EXIT_CODE))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn report_of_crash_while_compiling_module() {
+        let module = CompilingModule {
+            name: "wibble/wobble".into(),
+            path: "/app/src/wibble/wobble.gleam".into(),
+        };
+        let text = report_text(
+            "src/type_.rs:10\n\tunreachable",
+            "build --target erlang",
+            Some(&module),
+            "0: gleam::main\n",
+        );
+        let version = env!("CARGO_PKG_VERSION");
+        let os = std::env::consts::OS;
+        let arch = std::env::consts::ARCH;
+        assert_eq!(
+            text,
+            format!(
+                "Panic: src/type_.rs:10
+\tunreachable
+Gleam version: {version}
+Operating system: {os} ({arch})
+Command: gleam build --target erlang
+Compiling module: wibble/wobble (/app/src/wibble/wobble.gleam)
+
+Backtrace:
+0: gleam::main
+"
+            )
+        );
+    }
+}
@@ -0,0 +1,470 @@
+//! `gleam daemon` keeps a compiler running for the project, so that `gleam
+//! build`, `gleam check`, and `gleam test` don't have to load the module
+//! interfaces of the dependencies or analyse unchanged modules again for each
+//! build.
+//!
+//! The daemon listens on the Unix socket `build/daemon.sock`. Each connection
+//! sends one request, a line of JSON with the options of the build, and
+//! receives one response, a line of JSON with the diagnostics of the build.
+//! If no daemon is running the commands compile the project themselves.
+
+use std::{
+    collections::HashMap,
+    io::{BufRead, BufReader, Read, Write},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use gleam_core::{
+    analyse::TargetSupport,
+    build::{Codegen, Mode, NullTelemetry, Options, ProjectCompiler, Target},
+    config::PackageConfig,
+    diagnostic::Diagnostic,
+    manifest::{ManifestPackage, ManifestPackageSource},
+    paths::ProjectPaths,
+    version::COMPILER_VERSION,
+    warning::VectorWarningEmitterIO,
+    Error, Result,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{build_lock::BuildLock, cli, fs::ProjectIO};
+
+/// How long the daemon waits for a client to send its request, or to take the
+/// response, so that a client that stops responding doesn't keep the builds
+/// of other clients waiting. It is also how long a client waits to send its
+/// request.
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Request {
+    /// The version of Gleam that sent the request. The daemon only compiles
+    /// for the same version as itself, as the build caches of other versions
+    /// are not compatible.
+    version: String,
+    mode: Mode,
+    target: Option<Target>,
+    codegen: Codegen,
+    warnings_as_errors: bool,
+    verify_artefacts: bool,
+    /// The environment variables of the client that packages embed with the
+    /// `[env]` section of their gleam.toml. No others are sent.
+    environment: HashMap<String, String>,
+}
+
+impl Request {
+    fn new(paths: &ProjectPaths, options: &Options) -> Self {
+        Self {
+            version: COMPILER_VERSION.into(),
+            mode: options.mode,
+            target: options.target,
+            codegen: options.codegen,
+            warnings_as_errors: options.warnings_as_errors,
+            verify_artefacts: options.verify_artefacts,
+            environment: build_environment(paths),
+        }
+    }
+
+    fn options(&self) -> Options {
+        Options {
+            mode: self.mode,
+            target: self.target,
+            codegen: self.codegen,
+            warnings_as_errors: self.warnings_as_errors,
+            verify_artefacts: self.verify_artefacts,
+            root_target_support: TargetSupport::Enforced,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+enum Response {
+    Compiled {
+        warnings: Vec<Diagnostic>,
+    },
+    Failed {
+        warnings: Vec<Diagnostic>,
+        error: Vec<Diagnostic>,
+    },
+    /// The daemon is for a different version of Gleam, so the client
+    /// compiles the project itself.
+    VersionMismatch {
+        version: String,
+    },
+}
+
+/// The compilers kept between builds, one for each set of build options.
+struct Daemon {
+    paths: ProjectPaths,
+    compilers: Vec<WarmCompiler>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct CompilerKey {
+    mode: Mode,
+    target: Target,
+    codegen: Codegen,
+    warnings_as_errors: bool,
+    verify_artefacts: bool,
+}
+
+struct WarmCompiler {
+    key: CompilerKey,
+    /// The config and dependencies the compiler was made for. If either
+    /// changes a new compiler is made.
+    config: PackageConfig,
+    packages: Vec<ManifestPackage>,
+    compiler: ProjectCompiler<ProjectIO>,
+    warnings: Arc<VectorWarningEmitterIO>,
+}
+
+impl Daemon {
+    fn new(paths: ProjectPaths) -> Self {
+        Self {
+            paths,
+            compilers: vec![],
+        }
+    }
+
+    /// Reads a request from the connection and writes the response to it.
+    fn serve(&mut self, connection: impl Read + Write) -> std::io::Result<()> {
+        let mut reader = BufReader::new(connection);
+        let mut line = String::new();
+        _ = reader.read_line(&mut line)?;
+        let request: Request = serde_json::from_str(&line)?;
+        let response = if request.version == COMPILER_VERSION {
+            tracing::info!(options = ?request.options(), "daemon_compiling");
+            self.compile(&request)
+        } else {
+            Response::VersionMismatch {
+                version: COMPILER_VERSION.into(),
+            }
+        };
+        let mut connection = reader.into_inner();
+        serde_json::to_writer(&mut connection, &response)?;
+        connection.write_all(b"\n")?;
+        connection.flush()
+    }
+
+    fn compile(&mut self, request: &Request) -> Response {
+        let paths = self.paths.clone();
+        let (result, warnings) = match self.compiler(request) {
+            Ok(warm) => (warm.recompile(&paths, request), warm.warnings.take()),
+            Err(error) => (Err(error), vec![]),
+        };
+        let warnings = warnings
+            .iter()
+            .map(|warning| warning.to_diagnostic())
+            .collect();
+        match result {
+            Ok(()) => Response::Compiled { warnings },
+            Err(error) => Response::Failed {
+                warnings,
+                error: error.to_diagnostics(),
+            },
+        }
+    }
+
+    /// The compiler kept for the options of the request. A new one is made if
+    /// there is none yet, if the config or dependencies have changed since it
+    /// was made, or if the build directory has been deleted since.
+    // The warnings are only collected by the daemon's one thread.
+    #[allow(clippy::arc_with_non_send_sync)]
+    fn compiler(&mut self, request: &Request) -> Result<&mut WarmCompiler> {
+        let config = crate::config::read(self.paths.root_config())?;
+        let manifest = crate::build::download_dependencies()?;
+        let options = request.options();
+        let key = CompilerKey {
+            mode: options.mode,
+            target: options.target.unwrap_or(config.target),
+            codegen: options.codegen,
+            warnings_as_errors: options.warnings_as_errors,
+            verify_artefacts: options.verify_artefacts,
+        };
+        let built_before = self
+            .paths
+            .build_gleam_version(key.mode, key.target)
+            .is_file();
+
+        let position = self.compilers.iter().position(|warm| warm.key == key);
+        let reusable = position.is_some_and(|index| {
+            let warm = self.compilers.get(index).expect("Warm compiler");
+            built_before && warm.config == config && warm.packages == manifest.packages
+        });
+        if let (Some(index), true) = (position, reusable) {
+            return Ok(self.compilers.get_mut(index).expect("Warm compiler"));
+        }
+
+        tracing::info!(?key, "daemon_creating_compiler");
+        if let Some(index) = position {
+            _ = self.compilers.swap_remove(index);
+        }
+        let warnings = Arc::new(VectorWarningEmitterIO::default());
+        let compiler = ProjectCompiler::new(
+            config.clone(),
+            options,
+            manifest.packages.clone(),
+            Box::new(NullTelemetry),
+            warnings.clone(),
+            self.paths.clone(),
+            ProjectIO::new(),
+        );
+        self.compilers.push(WarmCompiler {
+            key,
+            config,
+            packages: manifest.packages,
+            compiler,
+            warnings,
+        });
+        Ok(self.compilers.last_mut().expect("Warm compiler"))
+    }
+}
+
+impl WarmCompiler {
+    fn recompile(&mut self, paths: &ProjectPaths, request: &Request) -> Result<()> {
+        let lock = BuildLock::new_target(paths, self.key.mode, self.key.target)?;
+        let _guard = lock.lock(&NullTelemetry)?;
+        self.compiler.environment = request.environment.clone();
+        self.compiler.recompile().map(|_| ())
+    }
+}
+
+/// The environment variables the build reads, which are those named in the
+/// `[env]` section of the gleam.toml of the project or of its dependencies.
+/// The configs of dependencies that have not been downloaded yet can't be
+/// read, but then the daemon can't compile the project either.
+fn build_environment(paths: &ProjectPaths) -> HashMap<String, String> {
+    let io = ProjectIO::new();
+    let mut configs = vec![paths.root_config()];
+    if let Ok(manifest) = crate::dependencies::read_manifest_from_disc(&io, paths) {
+        configs.extend(manifest.packages.iter().map(|package| {
+            let root = match &package.source {
+                ManifestPackageSource::Local { path } => paths.root().join(path),
+                ManifestPackageSource::Git { .. } | ManifestPackageSource::Hex { .. } => {
+                    paths.build_packages_package_source(package)
+                }
+            };
+            root.join("gleam.toml")
+        }));
+    }
+    configs
+        .into_iter()
+        .filter_map(|path| PackageConfig::read(path, &io).ok())
+        .flat_map(|config| config.env.into_values())
+        .filter_map(|variable| {
+            let name = variable.variable().to_string();
+            let value = std::env::var(&name).ok()?;
+            Some((name, value))
+        })
+        .collect()
+}
+
+pub fn command() -> Result<()> {
+    let paths = crate::find_project_paths()?;
+    listen(paths)
+}
+
+#[cfg(unix)]
+fn listen(paths: ProjectPaths) -> Result<()> {
+    use std::os::unix::net::{UnixListener, UnixStream};
+
+    let socket = paths.build_daemon_socket();
+    if socket.exists() {
+        if UnixStream::connect(&socket).is_ok() {
+            return Err(Error::DaemonFailed {
+                reason: format!("A daemon is already running for this project at {socket}"),
+            });
+        }
+        // The socket was left by a daemon that was stopped.
+        crate::fs::delete_file(&socket)?;
+    }
+    crate::fs::mkdir(paths.build_directory())?;
+    let listener = UnixListener::bind(&socket).map_err(|error| Error::DaemonFailed {
+        reason: format!("Listening on {socket} failed: {error}"),
+    })?;
+
+    // The socket is removed when the daemon is stopped so that clients don't
+    // try to connect to it.
+    let socket_to_remove = socket.clone();
+    ctrlc::set_handler(move || {
+        _ = std::fs::remove_file(&socket_to_remove);
+        std::process::exit(0);
+    })
+    .expect("Error setting Ctrl-C handler");
+
+    cli::print_colourful_prefix("Listening", socket.as_str());
+    let mut daemon = Daemon::new(paths);
+    for connection in listener.incoming() {
+        let result = connection.and_then(|connection| {
+            connection.set_read_timeout(Some(TIMEOUT))?;
+            connection.set_write_timeout(Some(TIMEOUT))?;
+            daemon.serve(connection)
+        });
+        if let Err(error) = result {
+            tracing::warn!(error = %error, "daemon_connection_failed");
+        }
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn listen(_paths: ProjectPaths) -> Result<()> {
+    Err(Error::DaemonFailed {
+        reason: "The daemon is only supported on Unix-like operating systems".into(),
+    })
+}
+
+/// Compiles the project with the daemon for it, printing the warnings of the
+/// build. `None` is returned if no daemon is running, or it can't compile
+/// for the options, so the caller compiles the project itself.
+pub fn compile(paths: &ProjectPaths, options: &Options) -> Option<Result<()>> {
+    // A module of a dependency can be run without the root package compiling
+    // for the target, which the daemon doesn't support.
+    if options.root_target_support != TargetSupport::Enforced {
+        return None;
+    }
+    let response = match request(paths, &Request::new(paths, options)) {
+        Ok(response) => response?,
+        Err(error) => {
+            tracing::warn!(error = %error, "daemon_request_failed");
+            return None;
+        }
+    };
+    let (warnings, result) = match response {
+        Response::Compiled { warnings } => (warnings, Ok(())),
+        Response::Failed { warnings, error } => {
+            let error = Error::DaemonCompilation { diagnostics: error };
+            (warnings, Err(error))
+        }
+        Response::VersionMismatch { version } => {
+            tracing::info!(version = %version, "daemon_version_mismatch");
+            return None;
+        }
+    };
+    let buffer_writer = cli::stderr_buffer_writer();
+    for warning in warnings {
+        let mut buffer = buffer_writer.buffer();
+        writeln!(buffer).expect("Writing warning to stderr");
+        warning.write(&mut buffer, cli::theme());
+        buffer_writer
+            .print(&buffer)
+            .expect("Writing warning to stderr");
+    }
+    Some(result)
+}
+
+/// Compiles the project with the daemon for it, as `crate::build::main` would
+/// compile it, if a daemon is running. `false` is returned if none is.
+pub fn build(paths: &ProjectPaths, options: &Options) -> Result<bool> {
+    let start = Instant::now();
+    let Some(result) = compile(paths, options) else {
+        return Ok(false);
+    };
+    result?;
+    match options.codegen {
+        Codegen::All | Codegen::DepsOnly => cli::print_compiled(start.elapsed()),
+        Codegen::None => cli::print_checked(start.elapsed()),
+    };
+    Ok(true)
+}
+
+#[cfg(unix)]
+fn request(paths: &ProjectPaths, request: &Request) -> std::io::Result<Option<Response>> {
+    use std::os::unix::net::UnixStream;
+
+    let socket = paths.build_daemon_socket();
+    if !socket.exists() {
+        return Ok(None);
+    }
+    let Ok(mut connection) = UnixStream::connect(&socket) else {
+        return Ok(None);
+    };
+    // There is no timeout for the response as the build can take any time.
+    connection.set_write_timeout(Some(TIMEOUT))?;
+    serde_json::to_writer(&mut connection, request)?;
+    connection.write_all(b"\n")?;
+    let mut line = String::new();
+    _ = BufReader::new(connection).read_line(&mut line)?;
+    Ok(Some(serde_json::from_str(&line)?))
+}
+
+#[cfg(not(unix))]
+fn request(_paths: &ProjectPaths, _request: &Request) -> std::io::Result<Option<Response>> {
+    Ok(None)
+}
+
+#[cfg(all(test, unix))]
+fn serve_request(request: &Request) -> Response {
+    use std::os::unix::net::UnixStream;
+
+    let (mut client, server) = UnixStream::pair().expect("Socket pair");
+    let daemon = std::thread::spawn(move || {
+        let mut daemon = Daemon::new(ProjectPaths::new("/".into()));
+        daemon.serve(server).expect("Serve request");
+    });
+    serde_json::to_writer(&mut client, request).expect("Write request");
+    client.write_all(b"\n").expect("Write request");
+    daemon.join().expect("Daemon thread");
+    let mut line = String::new();
+    _ = BufReader::new(client)
+        .read_line(&mut line)
+        .expect("Read response");
+    serde_json::from_str(&line).expect("Parse response")
+}
+
+#[cfg(unix)]
+#[test]
+fn version_mismatch() {
+    let options = Options {
+        mode: Mode::Dev,
+        target: None,
+        codegen: Codegen::All,
+        warnings_as_errors: false,
+        verify_artefacts: false,
+        root_target_support: TargetSupport::Enforced,
+    };
+    let request = Request {
+        version: "0.1.0".into(),
+        ..Request::new(&ProjectPaths::new("/".into()), &options)
+    };
+    assert_eq!(
+        serve_request(&request),
+        Response::VersionMismatch {
+            version: COMPILER_VERSION.into()
+        }
+    );
+}
+
+#[test]
+fn no_daemon_running() {
+    let directory = tempfile::tempdir().expect("Temporary directory");
+    let root =
+        camino::Utf8PathBuf::from_path_buf(directory.path().to_path_buf()).expect("Non UTF-8 path");
+    let options = Options {
+        mode: Mode::Dev,
+        target: None,
+        codegen: Codegen::All,
+        warnings_as_errors: false,
+        verify_artefacts: false,
+        root_target_support: TargetSupport::Enforced,
+    };
+    assert!(compile(&ProjectPaths::new(root), &options).is_none());
+}
+
+#[test]
+fn only_variables_of_the_env_section_are_sent() {
+    let directory = tempfile::tempdir().expect("Temporary directory");
+    let root =
+        camino::Utf8PathBuf::from_path_buf(directory.path().to_path_buf()).expect("Non UTF-8 path");
+    crate::fs::write(
+        &root.join("gleam.toml"),
+        "name = \"app\"\nversion = \"1.0.0\"\n\n[env]\npath = \"PATH\"\nunset = \"GLEAM_DAEMON_UNSET\"\n",
+    )
+    .expect("Write config");
+    let path = std::env::var("PATH").expect("PATH is set");
+    assert_eq!(
+        build_environment(&ProjectPaths::new(root)),
+        [("PATH".to_string(), path)].into()
+    );
+}
@@ -0,0 +1,873 @@
+use std::{
+    collections::{BTreeMap, VecDeque},
+    io::{BufRead, BufReader, Read, Write},
+    process::{Child, ChildStdin, Command, Stdio},
+    sync::mpsc::{self, Sender},
+    thread,
+};
+
+use camino::{Utf8Path, Utf8PathBuf};
+use ecow::EcoString;
+use gleam_core::{
+    ast::{Definition, Statement},
+    build::{Mode, Target},
+    error::StandardIoAction,
+    line_numbers::LineNumbers,
+    paths::{ProjectPaths, ARTEFACT_DIRECTORY_NAME},
+    Error, Result,
+};
+use serde_json::{json, Value};
+
+/// The Erlang module that runs the program in the Erlang interpreter and
+/// answers the commands of the debug adapter.
+const HELPER: &str = include_str!("../templates/gleam@@dap.erl");
+
+/// The prefix of the lines the helper module writes to standard output. Other
+/// lines are the output of the program being debugged.
+const MARKER: &str = "@@gleam-dap@@ ";
+
+/// Stack frames are identified by the id of their process and their level in
+/// the stack of that process.
+const FRAMES_PER_THREAD: i64 = 1_000_000;
+
+pub fn main() -> Result<()> {
+    tracing::info!("debug_adapter_starting");
+
+    let paths = crate::find_project_paths()?;
+    let (sender, receiver) = mpsc::channel();
+    let client = sender.clone();
+    let _ = thread::spawn(move || {
+        let mut stdin = BufReader::new(std::io::stdin());
+        while let Ok(Some(message)) = read_message(&mut stdin) {
+            if client.send(Message::Client(message)).is_err() {
+                return;
+            }
+        }
+        let _ = client.send(Message::ClientClosed);
+    });
+
+    let mut server = Server {
+        paths,
+        sender,
+        sequence: 0,
+        debugger: None,
+        module: None,
+        pending: VecDeque::new(),
+    };
+    for message in receiver {
+        if server.handle(message)? == Control::Stop {
+            break;
+        }
+    }
+    server.stop_debugger();
+
+    tracing::info!("debug_adapter_stopped");
+    Ok(())
+}
+
+#[derive(Debug)]
+enum Message {
+    /// A request from the editor.
+    Client(Value),
+    /// The editor closed the connection.
+    ClientClosed,
+    /// A response or event from the helper module.
+    Debugger(Value),
+    /// A line written by the program being debugged.
+    Output {
+        category: &'static str,
+        text: String,
+    },
+    /// The Erlang virtual machine has stopped.
+    DebuggerExited,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Control {
+    Continue,
+    Stop,
+}
+
+#[derive(Debug, Clone)]
+struct Request {
+    sequence: i64,
+    command: String,
+    arguments: Value,
+}
+
+/// A request that is waiting for the helper module to answer, and how to turn
+/// the answer into the body of the response.
+#[derive(Debug)]
+struct Pending {
+    request: Request,
+    reply: Reply,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Reply {
+    Launch,
+    Empty,
+    SetBreakpoints,
+    Threads,
+    StackTrace { thread: i64 },
+    Variables,
+    Continue,
+}
+
+#[derive(Debug)]
+struct Debugger {
+    process: Child,
+    stdin: ChildStdin,
+}
+
+#[derive(Debug)]
+struct Server {
+    paths: ProjectPaths,
+    sender: Sender<Message>,
+    sequence: i64,
+    debugger: Option<Debugger>,
+    /// The module whose `main` function is run, once the program is launched.
+    module: Option<String>,
+    pending: VecDeque<Pending>,
+}
+
+impl Server {
+    fn handle(&mut self, message: Message) -> Result<Control> {
+        match message {
+            Message::Client(message) => {
+                let request = Request {
+                    sequence: field(&message, "seq").as_i64().unwrap_or_default(),
+                    command: field(&message, "command")
+                        .as_str()
+                        .unwrap_or_default()
+                        .into(),
+                    arguments: field(&message, "arguments").clone(),
+                };
+                self.handle_request(request)
+            }
+
+            Message::ClientClosed => Ok(Control::Stop),
+
+            Message::Debugger(message) => {
+                self.handle_debugger_message(message)?;
+                Ok(Control::Continue)
+            }
+
+            Message::Output { category, text } => {
+                self.event(
+                    "output",
+                    json!({ "category": category, "output": format!("{text}\n") }),
+                )?;
+                Ok(Control::Continue)
+            }
+
+            Message::DebuggerExited => {
+                let status = match self.debugger.take() {
+                    Some(mut debugger) => debugger
+                        .process
+                        .wait()
+                        .ok()
+                        .and_then(|status| status.code())
+                        .unwrap_or(1),
+                    None => 0,
+                };
+                for pending in std::mem::take(&mut self.pending) {
+                    self.respond_error(&pending.request, "The program has exited")?;
+                }
+                self.event("exited", json!({ "exitCode": status }))?;
+                self.event("terminated", json!({}))?;
+                Ok(Control::Continue)
+            }
+        }
+    }
+
+    fn handle_request(&mut self, request: Request) -> Result<Control> {
+        let thread = field(&request.arguments, "threadId")
+            .as_i64()
+            .unwrap_or_default();
+        match request.command.as_str() {
+            "initialize" => {
+                let capabilities = json!({ "supportsConfigurationDoneRequest": true });
+                self.respond(&request, capabilities)?;
+            }
+
+            "launch" => {
+                if let Err(error) = self.launch(&request) {
+                    self.stop_debugger();
+                    self.respond_error(&request, &error.pretty_string())?;
+                }
+            }
+
+            "setBreakpoints" => {
+                let lines: Vec<u32> = field(&request.arguments, "breakpoints")
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|breakpoint| field(breakpoint, "line").as_u64())
+                    .map(|line| line as u32)
+                    .collect();
+                let module = field(field(&request.arguments, "source"), "path")
+                    .as_str()
+                    .and_then(|path| self.paths.module_name(Utf8Path::new(path)));
+                match module {
+                    Some(module) if self.debugger.is_some() => {
+                        let command = format!(
+                            "{{breakpoints, {}, [{}]}}.",
+                            erlang_atom(&module.replace('/', "@")),
+                            lines
+                                .iter()
+                                .map(u32::to_string)
+                                .collect::<Vec<_>>()
+                                .join(", ")
+                        );
+                        self.command(request, Reply::SetBreakpoints, &command)?;
+                    }
+                    _ => {
+                        let breakpoints: Vec<_> = lines
+                            .iter()
+                            .map(|line| json!({ "verified": false, "line": line }))
+                            .collect();
+                        self.respond(&request, json!({ "breakpoints": breakpoints }))?;
+                    }
+                }
+            }
+
+            "configurationDone" => match self.module.clone() {
+                Some(module) if self.debugger.is_some() => {
+                    let command = format!(
+                        "{{run, {}, {}}}.",
+                        erlang_atom(&format!("{}@@main", self.package()?)),
+                        erlang_atom(&module.replace('/', "@"))
+                    );
+                    self.command(request, Reply::Empty, &command)?;
+                }
+                _ => self.respond(&request, json!({}))?,
+            },
+
+            "threads" => match self.debugger.is_some() {
+                true => self.command(request, Reply::Threads, "threads.")?,
+                false => self.respond(&request, json!({ "threads": [] }))?,
+            },
+
+            "stackTrace" => {
+                let command = format!("{{stack, {thread}}}.");
+                self.command(request, Reply::StackTrace { thread }, &command)?;
+            }
+
+            "scopes" => {
+                let frame = field(&request.arguments, "frameId")
+                    .as_i64()
+                    .unwrap_or_default();
+                let scopes = json!([{
+                    "name": "Locals",
+                    "presentationHint": "locals",
+                    "variablesReference": frame,
+                    "expensive": false,
+                }]);
+                self.respond(&request, json!({ "scopes": scopes }))?;
+            }
+
+            "variables" => {
+                let reference = field(&request.arguments, "variablesReference")
+                    .as_i64()
+                    .unwrap_or_default();
+                let command = format!(
+                    "{{variables, {}, {}}}.",
+                    reference / FRAMES_PER_THREAD,
+                    reference % FRAMES_PER_THREAD
+                );
+                self.command(request, Reply::Variables, &command)?;
+            }
+
+            "continue" => {
+                let command = format!("{{continue, {thread}}}.");
+                self.command(request, Reply::Continue, &command)?;
+            }
+
+            "next" | "stepIn" | "stepOut" => {
+                let step = match request.command.as_str() {
+                    "next" => "next",
+                    "stepIn" => "step",
+                    _ => "finish",
+                };
+                let command = format!("{{{step}, {thread}}}.");
+                self.command(request, Reply::Empty, &command)?;
+            }
+
+            "disconnect" | "terminate" => {
+                self.stop_debugger();
+                self.respond(&request, json!({}))?;
+                return Ok(Control::Stop);
+            }
+
+            command => {
+                let message = format!("The {command} request is not supported");
+                self.respond_error(&request, &message)?;
+            }
+        }
+        Ok(Control::Continue)
+    }
+
+    /// Compiles the project for Erlang and starts the Erlang virtual machine
+    /// with the modules of the project interpreted, so that it is ready for
+    /// breakpoints to be set before the program is run. The module to run is
+    /// given by the `module` attribute of the launch configuration, and is the
+    /// module named after the package by default.
+    fn launch(&mut self, request: &Request) -> Result<()> {
+        let module = match field(&request.arguments, "module").as_str() {
+            Some(module) => module.to_string(),
+            None => self.package()?.to_string(),
+        };
+        if !crate::run::is_gleam_module(&module) {
+            return Err(Error::InvalidModuleName { module });
+        }
+        self.build()?;
+
+        let package = self.package()?;
+        let package_directory =
+            self.paths
+                .build_directory_for_package(Mode::Dev, Target::Erlang, &package);
+        let helper = package_directory.join("gleam@@dap.erl");
+        crate::fs::write(&helper, HELPER)?;
+
+        let mut modules = vec![];
+        for directory in [self.paths.src_directory(), self.paths.test_directory()] {
+            for path in crate::fs::gleam_files_excluding_gitignore(&directory) {
+                let Some(name) = self.paths.module_name(&path) else {
+                    continue;
+                };
+                let erlang_name = name.replace('/', "@");
+                let source = package_directory
+                    .join(ARTEFACT_DIRECTORY_NAME)
+                    .join(format!("{erlang_name}.erl"));
+                let functions = function_lines(&crate::fs::read(&path)?)
+                    .iter()
+                    .map(FunctionLines::to_erlang)
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                modules.push(format!(
+                    "{{{}, {}, [{functions}]}}",
+                    erlang_atom(&erlang_name),
+                    erlang_string(source.as_str()),
+                ));
+            }
+        }
+
+        let mut args = vec![];
+        let packages = self
+            .paths
+            .build_directory_for_target(Mode::Dev, Target::Erlang);
+        for entry in crate::fs::read_dir(packages)?.filter_map(Result::ok) {
+            args.push("-pa".to_string());
+            args.push(entry.path().join("ebin").into());
+        }
+        args.push("-noshell".into());
+        args.push("-eval".into());
+        args.push(format!(
+            "{{ok, _, Binary}} = compile:file({path}, [binary, report_errors]), \
+             {{module, _}} = code:load_binary('gleam@@dap', {path}, Binary), \
+             'gleam@@dap':start()",
+            path = erlang_string(helper.as_str())
+        ));
+
+        let mut process = Command::new("erl")
+            .args(&args)
+            .current_dir(self.paths.root())
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|error| match error.kind() {
+                std::io::ErrorKind::NotFound => Error::ShellProgramNotFound {
+                    program: "erl".into(),
+                },
+                other => Error::ShellCommand {
+                    program: "erl".into(),
+                    err: Some(other),
+                },
+            })?;
+        let stdin = process.stdin.take().expect("erl stdin");
+        let stdout = process.stdout.take().expect("erl stdout");
+        let stderr = process.stderr.take().expect("erl stderr");
+
+        let sender = self.sender.clone();
+        let _ = thread::spawn(move || {
+            for line in read_lines(stdout) {
+                let message = match line.strip_prefix(MARKER) {
+                    Some(json) => match serde_json::from_str(json) {
+                        Ok(message) => Message::Debugger(message),
+                        Err(_) => continue,
+                    },
+                    None => Message::Output {
+                        category: "stdout",
+                        text: line,
+                    },
+                };
+                if sender.send(message).is_err() {
+                    return;
+                }
+            }
+            let _ = sender.send(Message::DebuggerExited);
+        });
+        let sender = self.sender.clone();
+        let _ = thread::spawn(move || {
+            for text in read_lines(stderr) {
+                let message = Message::Output {
+                    category: "stderr",
+                    text,
+                };
+                if sender.send(message).is_err() {
+                    return;
+                }
+            }
+        });
+
+        self.debugger = Some(Debugger { process, stdin });
+        let command = format!("{{interpret, [{}]}}.", modules.join(", "));
+        self.module = Some(module);
+        self.command(request.clone(), Reply::Launch, &command)
+    }
+
+    /// Builds the project in a separate process, as the progress of the build
+    /// is printed to standard output, which is used for the protocol. The
+    /// output of the build is shown in the debug console instead.
+    fn build(&mut self) -> Result<()> {
+        let gleam = std::env::current_exe().map_err(|error| Error::ShellCommand {
+            program: "gleam".into(),
+            err: Some(error.kind()),
+        })?;
+        let output = Command::new(gleam)
+            .args(["build", "--target", "erlang", "--color", "never"])
+            .current_dir(self.paths.root())
+            .stdin(Stdio::null())
+            .output()
+            .map_err(|error| Error::ShellCommand {
+                program: "gleam".into(),
+                err: Some(error.kind()),
+            })?;
+        let text = String::from_utf8_lossy(&output.stdout).to_string()
+            + &String::from_utf8_lossy(&output.stderr);
+        self.event("output", json!({ "category": "console", "output": text }))?;
+        match output.status.success() {
+            true => Ok(()),
+            false => Err(Error::ShellCommand {
+                program: "gleam build".into(),
+                err: None,
+            }),
+        }
+    }
+
+    fn package(&self) -> Result<EcoString> {
+        Ok(crate::config::root_config()?.name)
+    }
+
+    fn stop_debugger(&mut self) {
+        if let Some(mut debugger) = self.debugger.take() {
+            let _ = writeln!(debugger.stdin, "stop.");
+            let _ = debugger.process.kill();
+            let _ = debugger.process.wait();
+        }
+    }
+
+    /// Sends a command to the helper module, which answers commands in the
+    /// order they are sent.
+    fn command(&mut self, request: Request, reply: Reply, command: &str) -> Result<()> {
+        let Some(debugger) = &mut self.debugger else {
+            return self.respond_error(&request, "The program is not running");
+        };
+        tracing::debug!(command = command, "debug_adapter_command");
+        if writeln!(debugger.stdin, "{command}").is_err() {
+            return self.respond_error(&request, "The program is not running");
+        }
+        self.pending.push_back(Pending { request, reply });
+        Ok(())
+    }
+
+    fn handle_debugger_message(&mut self, message: Value) -> Result<()> {
+        if let Some(event) = field(&message, "event").as_str() {
+            if event == "stopped" {
+                let body = json!({
+                    "reason": field(&message, "reason"),
+                    "threadId": field(&message, "thread"),
+                    "allThreadsStopped": false,
+                });
+                self.event("stopped", body)?;
+            }
+            return Ok(());
+        }
+
+        let Some(Pending { request, reply }) = self.pending.pop_front() else {
+            return Ok(());
+        };
+        if let Some(error) = field(&message, "error").as_str() {
+            return self.respond_error(&request, error);
+        }
+        let response = field(&message, "response");
+        let body = match reply {
+            Reply::Empty => json!({}),
+
+            Reply::Launch => {
+                self.respond(&request, json!({}))?;
+                return self.event("initialized", json!({}));
+            }
+
+            Reply::Continue => json!({ "allThreadsContinued": false }),
+
+            Reply::SetBreakpoints => {
+                let requested = field(&request.arguments, "breakpoints").as_array();
+                let breakpoints: Vec<_> = field(response, "lines")
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .zip(requested.into_iter().flatten())
+                    .map(|(line, requested)| match line.as_u64() {
+                        Some(line) => json!({ "verified": true, "line": line }),
+                        None => json!({ "verified": false, "line": field(requested, "line") }),
+                    })
+                    .collect();
+                json!({ "breakpoints": breakpoints })
+            }
+
+            Reply::Threads => json!({ "threads": field(response, "threads") }),
+
+            Reply::StackTrace { thread } => {
+                let frames: Vec<_> = field(response, "frames")
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .map(|frame| self.stack_frame(thread, frame))
+                    .collect();
+                json!({ "stackFrames": frames, "totalFrames": frames.len() })
+            }
+
+            Reply::Variables => {
+                let bindings = field(response, "variables")
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|variable| {
+                        let name = field(variable, "name").as_str()?;
+                        let value = field(variable, "value").as_str()?;
+                        Some((name, value))
+                    });
+                let variables: Vec<_> = gleam_variables(bindings)
+                    .into_iter()
+                    .map(|(name, value)| {
+                        json!({ "name": name, "value": value, "variablesReference": 0 })
+                    })
+                    .collect();
+                json!({ "variables": variables })
+            }
+        };
+        self.respond(&request, body)
+    }
+
+    fn stack_frame(&self, thread: i64, frame: &Value) -> Value {
+        let module = field(frame, "module")
+            .as_str()
+            .unwrap_or_default()
+            .replace('@', "/");
+        let function = field(frame, "function").as_str().unwrap_or_default();
+        let name = match field(frame, "anonymous").as_bool() {
+            Some(true) => format!("anonymous function in {module}.{function}"),
+            _ => format!("{module}.{function}"),
+        };
+        let source = [self.paths.src_directory(), self.paths.test_directory()]
+            .into_iter()
+            .map(|directory| directory.join(format!("{module}.gleam")))
+            .find(|path| path.exists())
+            .map(|path: Utf8PathBuf| json!({ "name": module, "path": path.as_str() }));
+        json!({
+            "id": thread * FRAMES_PER_THREAD + field(frame, "id").as_i64().unwrap_or_default(),
+            "name": name,
+            "source": source,
+            "line": field(frame, "line"),
+            "column": 1,
+        })
+    }
+
+    fn respond(&mut self, request: &Request, body: Value) -> Result<()> {
+        self.send(json!({
+            "type": "response",
+            "request_seq": request.sequence,
+            "command": request.command,
+            "success": true,
+            "body": body,
+        }))
+    }
+
+    fn respond_error(&mut self, request: &Request, message: &str) -> Result<()> {
+        self.send(json!({
+            "type": "response",
+            "request_seq": request.sequence,
+            "command": request.command,
+            "success": false,
+            "message": message,
+        }))
+    }
+
+    fn event(&mut self, event: &str, body: Value) -> Result<()> {
+        self.send(json!({ "type": "event", "event": event, "body": body }))
+    }
+
+    fn send(&mut self, mut message: Value) -> Result<()> {
+        self.sequence += 1;
+        if let Some(object) = message.as_object_mut() {
+            let _ = object.insert("seq".into(), self.sequence.into());
+        }
+        write_message(&mut std::io::stdout().lock(), &message).map_err(|error| Error::StandardIo {
+            action: StandardIoAction::Write,
+            err: Some(error.kind()),
+        })
+    }
+}
+
+/// Reads a message of the Debug Adapter Protocol, which is a JSON object
+/// preceded by a `Content-Length` header. Returns `None` at the end of the
+/// input.
+fn read_message(reader: &mut impl BufRead) -> std::io::Result<Option<Value>> {
+    let mut length = None;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header)? == 0 {
+            return Ok(None);
+        }
+        let header = header.trim_end();
+        if header.is_empty() && length.is_some() {
+            break;
+        }
+        if let Some(value) = header.strip_prefix("Content-Length:") {
+            length = value.trim().parse::<usize>().ok();
+        }
+    }
+    let mut content = vec![0; length.unwrap_or_default()];
+    reader.read_exact(&mut content)?;
+    serde_json::from_slice(&content)
+        .map(Some)
+        .map_err(|error| std::io::Error::new(std::io::ErrorKind::InvalidData, error))
+}
+
+fn write_message(writer: &mut impl Write, message: &Value) -> std::io::Result<()> {
+    let content = message.to_string();
+    write!(writer, "Content-Length: {}\r\n\r\n{content}", content.len())?;
+    writer.flush()
+}
+
+fn read_lines(reader: impl Read) -> impl Iterator<Item = String> {
+    BufReader::new(reader).split(b'\n').map_while(|line| {
+        let line = line.ok()?;
+        let line = String::from_utf8_lossy(&line);
+        Some(line.trim_end_matches('\r').to_string())
+    })
+}
+
+/// The lines of a Gleam function that the debugger can stop at, which are the
+/// first line of each statement of its body.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct FunctionLines {
+    name: EcoString,
+    arity: usize,
+    first: u32,
+    last: u32,
+    statements: Vec<u32>,
+}
+
+impl FunctionLines {
+    fn to_erlang(&self) -> String {
+        let statements: Vec<_> = self.statements.iter().map(u32::to_string).collect();
+        format!(
+            "{{{}, {}, {}, {}, [{}]}}",
+            erlang_atom(&self.name),
+            self.arity,
+            self.first,
+            self.last,
+            statements.join(", ")
+        )
+    }
+}
+
+/// The lines of the functions of a module that are compiled to Erlang
+/// functions. Modules that cannot be parsed have no lines, as they will not
+/// have been compiled.
+fn function_lines(src: &str) -> Vec<FunctionLines> {
+    let Ok(parsed) = gleam_core::parse::parse_module(src) else {
+        return vec![];
+    };
+    let line_numbers = LineNumbers::new(src);
+    parsed
+        .module
+        .definitions
+        .into_iter()
+        .filter(|definition| definition.target != Some(Target::JavaScript))
+        .filter_map(|definition| match definition.definition {
+            Definition::Function(function) if function.external_erlang.is_none() => Some(function),
+            _ => None,
+        })
+        .map(|function| {
+            // The statements after a `use` are compiled to the body of an
+            // anonymous function, so they belong to the `use` statement.
+            let mut statements = vec![];
+            for statement in function.body.iter() {
+                statements.push(line_numbers.line_number(statement.location().start));
+                if let Statement::Use(_) = statement {
+                    break;
+                }
+            }
+            FunctionLines {
+                name: function.name,
+                arity: function.arguments.len(),
+                first: line_numbers.line_number(function.location.start),
+                last: line_numbers.line_number(function.end_position),
+                statements,
+            }
+        })
+        .collect()
+}
+
+/// The Gleam variables of the bindings of an Erlang stack frame, with their
+/// values. Variables that were discarded or generated by the compiler are left
+/// out, and where a variable has been shadowed only the latest is kept, which
+/// the compiler numbers with a suffix such as `Name@1`.
+fn gleam_variables<'a>(
+    bindings: impl Iterator<Item = (&'a str, &'a str)>,
+) -> Vec<(String, String)> {
+    let mut variables: BTreeMap<String, (u32, String)> = BTreeMap::new();
+    for (name, value) in bindings {
+        if name.starts_with('_') {
+            continue;
+        }
+        let (name, version) = match name.split_once('@') {
+            Some((name, version)) => (name, version.parse().unwrap_or_default()),
+            None => (name, 0),
+        };
+        let mut characters = name.chars();
+        let Some(first) = characters.next() else {
+            continue;
+        };
+        let name = first.to_lowercase().chain(characters).collect::<String>();
+        match variables.get(&name) {
+            Some((latest, _)) if *latest > version => (),
+            _ => {
+                let _ = variables.insert(name, (version, value.into()));
+            }
+        }
+    }
+    variables
+        .into_iter()
+        .map(|(name, (_, value))| (name, value))
+        .collect()
+}
+
+/// The field of a JSON object, which is null if it is missing.
+fn field<'a>(value: &'a Value, name: &str) -> &'a Value {
+    value.get(name).unwrap_or(&Value::Null)
+}
+
+fn erlang_atom(name: &str) -> String {
+    format!("'{}'", name.replace('\\', "\\\\").replace('\'', "\\'"))
+}
+
+fn erlang_string(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_and_write_messages() {
+        let message = json!({ "seq": 1, "type": "request", "command": "initialize" });
+        let mut buffer = vec![];
+        write_message(&mut buffer, &message).expect("write");
+        buffer.extend_from_slice(&buffer.clone());
+        let mut reader = std::io::Cursor::new(buffer);
+        assert_eq!(
+            read_message(&mut reader).expect("read"),
+            Some(message.clone())
+        );
+        assert_eq!(read_message(&mut reader).expect("read"), Some(message));
+        assert_eq!(read_message(&mut reader).expect("read"), None);
+    }
+
+    #[test]
+    fn function_lines_of_statements() {
+        let src = r#"import gleam/io
+
+pub fn main() {
+  let x = 1
+  io.println(
+    "Hello",
+  )
+  x
+}
+
+@external(erlang, "erlang", "abs")
+fn abs(x: Int) -> Int
+
+fn with_use(f) {
+  let y = 2
+  use x <- f()
+  x + y
+}
+"#;
+        assert_eq!(
+            function_lines(src),
+            vec![
+                FunctionLines {
+                    name: "main".into(),
+                    arity: 0,
+                    first: 3,
+                    last: 9,
+                    statements: vec![4, 5, 8],
+                },
+                FunctionLines {
+                    name: "with_use".into(),
+                    arity: 1,
+                    first: 14,
+                    last: 18,
+                    statements: vec![15, 16],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn function_lines_as_erlang() {
+        let function = FunctionLines {
+            name: "main".into(),
+            arity: 2,
+            first: 3,
+            last: 9,
+            statements: vec![4, 5, 8],
+        };
+        assert_eq!(function.to_erlang(), "{'main', 2, 3, 9, [4, 5, 8]}");
+    }
+
+    #[test]
+    fn gleam_variables_of_bindings() {
+        let bindings = [
+            ("Name", "\"Lucy\""),
+            ("Name@1", "\"Nubi\""),
+            ("_pipe", "1"),
+            ("_", "2"),
+            ("Count@2", "3"),
+            ("Count@1", "2"),
+            ("My_var", "Nil"),
+        ];
+        assert_eq!(
+            gleam_variables(bindings.into_iter()),
+            vec![
+                ("count".into(), "3".into()),
+                ("my_var".into(), "Nil".into()),
+                ("name".into(), "\"Nubi\"".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn erlang_terms() {
+        assert_eq!(erlang_atom("app@one"), "'app@one'");
+        assert_eq!(erlang_string(r#"C:\gleam "app""#), r#""C:\\gleam \"app\"""#);
+    }
+}
@@ -1,5 +1,6 @@
 use std::{
     collections::{HashMap, HashSet},
+    sync::{Arc, Mutex, OnceLock},
     time::Instant,
 };
 
@@ -10,24 +11,44 @@ use futures::future;
 use gleam_core::{
     build::{Mode, Target, Telemetry},
     config::PackageConfig,
-    dependency,
+    dependency::{
+        self, NullResolutionObserver, PackageFetchers, ResolutionObserver, ResolveOptions,
+        SourceKind, Suggestion,
+    },
     error::{FileIoAction, FileKind, StandardIoAction},
-    hex::{self, HEXPM_PUBLIC_KEY},
-    io::{HttpClient as _, TarUnpacker, WrappedReader},
-    manifest::{Base16Checksum, Manifest, ManifestPackage, ManifestPackageSource},
+    hex::{
+        self,
+        integrity::{self, Integrity},
+        HEXPM_PUBLIC_KEY,
+    },
+    io::{FileSystemReader, FileSystemWriter, TarUnpacker, WrappedReader},
+    manifest::{self, Base16Checksum, Manifest, ManifestPackage, ManifestPackageSource},
     paths::ProjectPaths,
     requirement::Requirement,
-    Error, Result,
+    warning::WarningEmitterIO,
+    Error, Result, Warning,
 };
 use hexpm::version::Version;
 use itertools::Itertools;
 use same_file::is_same_file;
 use strum::IntoEnumIterator;
 
+mod interactive;
+mod licenses;
+mod module_graph;
+mod retired;
+mod verify;
+
+pub use interactive::update_interactively;
+pub use licenses::licenses;
+pub use module_graph::module_graph;
+pub use retired::warn_about_retired_packages;
+pub use verify::verify;
+
 use crate::{
     build_lock::BuildLock,
     cli,
-    fs::{self, ProjectIO},
+    fs::{self, ConsoleWarningEmitter, ProjectIO},
     http::HttpClient,
 };
 
@@ -36,9 +57,11 @@ pub fn list() -> Result<()> {
     let project = fs::get_project_root(fs::get_current_directory()?)?;
     let paths = ProjectPaths::new(project);
     let config = crate::config::root_config()?;
+    let fetcher = PackageFetcher::new(runtime.handle().clone(), HttpClient::new());
     let (_, manifest) = get_manifest(
         &paths,
-        runtime.handle().clone(),
+        &ProjectIO::new(),
+        &fetcher,
         Mode::Dev,
         &config,
         &cli::Reporter::new(),
@@ -72,6 +95,7 @@ fn list_manifest_format() {
                 requirements: vec![],
                 source: ManifestPackageSource::Hex {
                     outer_checksum: Base16Checksum(vec![1, 2, 3, 4]),
+                    organization: None,
                 },
             },
             ManifestPackage {
@@ -82,6 +106,7 @@ fn list_manifest_format() {
                 requirements: vec!["zzz".into(), "gleam_stdlib".into()],
                 source: ManifestPackageSource::Hex {
                     outer_checksum: Base16Checksum(vec![3, 22]),
+                    organization: None,
                 },
             },
             ManifestPackage {
@@ -92,6 +117,7 @@ fn list_manifest_format() {
                 requirements: vec![],
                 source: ManifestPackageSource::Hex {
                     outer_checksum: Base16Checksum(vec![3, 22]),
+                    organization: None,
                 },
             },
         ],
@@ -112,13 +138,374 @@ pub enum UseManifest {
     No,
 }
 
-pub fn update() -> Result<()> {
+pub fn update(packages: Vec<String>) -> Result<()> {
     let paths = crate::find_project_paths()?;
-    _ = download(&paths, cli::Reporter::new(), None, UseManifest::No)?;
+    if packages.is_empty() {
+        _ = download(&paths, cli::Reporter::new(), None, UseManifest::No)?;
+        return Ok(());
+    }
+
+    // Only the given packages are unlocked, so the rest stay at the versions
+    // and commits they are locked to in the manifest.
+    let io = ProjectIO::new();
+    let reporter = cli::Reporter::new();
+    let runtime = tokio::runtime::Runtime::new().expect("Unable to start Tokio async runtime");
+    let fetcher = PackageFetcher::new(runtime.handle().clone(), HttpClient::new());
+    let config = crate::config::read(paths.root_config())?;
+    let manifest = if io.is_file(&paths.manifest()) {
+        Some(read_manifest_from_disc(&io, &paths)?)
+    } else {
+        None
+    };
+    let mut locks = Locks::new(&config, manifest.as_ref())?;
+    for package in &packages {
+        locks.unlock(package);
+    }
+    let manifest = resolve_versions(&io, &fetcher, Mode::Dev, &paths, &config, &locks, &reporter)?;
+    write_manifest_to_disc(&io, &paths, &manifest)?;
+    for package in &packages {
+        cli::print_unlocked(package);
+    }
+
+    _ = download(&paths, reporter, None, UseManifest::Yes)?;
     Ok(())
 }
 
-pub fn download<Telem: Telemetry>(
+/// The versions of packages locked in the manifest, along with the commits
+/// that git dependencies are pinned to and the Hex organisations that private
+/// packages come from.
+#[derive(Debug, Default)]
+struct Locks {
+    versions: HashMap<EcoString, Version>,
+    git_commits: HashMap<EcoString, EcoString>,
+    organizations: HashMap<EcoString, EcoString>,
+}
+
+impl Locks {
+    fn new(config: &PackageConfig, manifest: Option<&Manifest>) -> Result<Self> {
+        let versions = config.locked(manifest)?;
+        let locked_packages = || {
+            manifest
+                .into_iter()
+                .flat_map(|manifest| &manifest.packages)
+                .filter(|package| versions.contains_key(&package.name))
+        };
+        let git_commits = locked_packages()
+            .filter_map(|package| match &package.source {
+                ManifestPackageSource::Git { commit, .. } => {
+                    Some((package.name.clone(), commit.clone()))
+                }
+                ManifestPackageSource::Hex { .. } | ManifestPackageSource::Local { .. } => None,
+            })
+            .collect();
+        let organizations = locked_packages()
+            .filter_map(|package| match &package.source {
+                ManifestPackageSource::Hex {
+                    organization: Some(organization),
+                    ..
+                } => Some((package.name.clone(), organization.clone())),
+                ManifestPackageSource::Hex { .. }
+                | ManifestPackageSource::Git { .. }
+                | ManifestPackageSource::Local { .. } => None,
+            })
+            .collect();
+        Ok(Self {
+            versions,
+            git_commits,
+            organizations,
+        })
+    }
+
+    fn unlock(&mut self, package: &str) {
+        let _ = self.versions.remove(package);
+        let _ = self.git_commits.remove(package);
+        let _ = self.organizations.remove(package);
+    }
+}
+
+/// Works out changes to the requirements in `gleam.toml` and the locked
+/// versions in `manifest.toml` that would let the dependencies be resolved,
+/// printing them or, if `apply` is true, making them.
+pub fn fix(apply: bool) -> Result<()> {
+    let paths = crate::find_project_paths()?;
+    let io = ProjectIO::new();
+    let reporter = cli::Reporter::new();
+    let runtime = tokio::runtime::Runtime::new().expect("Unable to start Tokio async runtime");
+    let fetcher = PackageFetcher::new(runtime.handle().clone(), HttpClient::new());
+    let config = crate::config::read(paths.root_config())?;
+    let manifest = if io.is_file(&paths.manifest()) {
+        Some(read_manifest_from_disc(&io, &paths)?)
+    } else {
+        None
+    };
+
+    let locks = Locks::new(&config, manifest.as_ref())?;
+    let fixes = match resolve_versions(&io, &fetcher, Mode::Dev, &paths, &config, &locks, &reporter)
+    {
+        Ok(_) => {
+            println!("The dependencies can be resolved, there is nothing to fix.");
+            return Ok(());
+        }
+        Err(Error::DependencyResolutionFailedWithFixes { fixes, .. }) => fixes,
+        Err(error) => return Err(error),
+    };
+
+    if !apply {
+        println!("These changes would let the dependencies be resolved:\n");
+        for fix in &fixes {
+            println!("  - {fix}");
+        }
+        println!("\nRun `gleam deps fix --apply` to make them.");
+        return Ok(());
+    }
+
+    // Relaxed requirements are written to gleam.toml, which also unlocks the
+    // packages as their requirements have changed.
+    let mut gleam_toml = crate::add::read_toml_edit(paths.root_config().as_str())?;
+    let mut unlocked = HashSet::new();
+    for fix in &fixes {
+        match fix {
+            Suggestion::Relax {
+                package,
+                requirement,
+            } => set_requirement(&mut gleam_toml, package, requirement),
+            Suggestion::Unlock { package } => {
+                let _ = unlocked.insert(package.as_str());
+            }
+        }
+    }
+    fs::write(&paths.root_config(), &gleam_toml.to_string())?;
+
+    let config = crate::config::read(paths.root_config())?;
+    let mut locks = Locks::new(&config, manifest.as_ref())?;
+    for package in unlocked {
+        locks.unlock(package);
+    }
+    let manifest = resolve_versions(&io, &fetcher, Mode::Dev, &paths, &config, &locks, &reporter)?;
+    write_manifest_to_disc(&io, &paths, &manifest)?;
+    for fix in &fixes {
+        match fix {
+            Suggestion::Relax {
+                package,
+                requirement,
+            } => cli::print_relaxed(&format!("{package} to {requirement}")),
+            Suggestion::Unlock { package } => cli::print_unlocked(package),
+        }
+    }
+
+    _ = download(&paths, reporter, None, UseManifest::Yes)?;
+    Ok(())
+}
+
+/// The versions published to Hex of each public package required in
+/// `gleam.toml`, for checking the requirements against. Packages that cannot
+/// be looked up, such as when offline, are left out.
+pub fn published_versions(config: &PackageConfig) -> HashMap<EcoString, Vec<Version>> {
+    let runtime = tokio::runtime::Runtime::new().expect("Unable to start Tokio async runtime");
+    let fetcher = PackageFetcher::new(runtime.handle().clone(), HttpClient::new());
+    let mut published = HashMap::new();
+    for (name, requirement) in config.dependencies.iter().chain(&config.dev_dependencies) {
+        let Requirement::Hex {
+            organization: None, ..
+        } = requirement
+        else {
+            continue;
+        };
+        match dependency::PackageFetcher::get_dependencies(&fetcher, name) {
+            Ok(package) => {
+                let versions = package.releases.into_iter().map(|release| release.version);
+                let _ = published.insert(name.clone(), versions.collect());
+            }
+            Err(error) => tracing::debug!(
+                package = name.as_str(),
+                error = error.to_string(),
+                "published_versions_lookup_failed"
+            ),
+        }
+    }
+    published
+}
+
+/// Replaces a `manifest.toml` left with git merge conflicts with a freshly
+/// resolved one. Versions that both sides of the merge agree on stay locked,
+/// and the rest are resolved again against the requirements in `gleam.toml`.
+pub fn resolve_lock_conflict() -> Result<()> {
+    let paths = crate::find_project_paths()?;
+    let io = ProjectIO::new();
+    let reporter = cli::Reporter::new();
+    let manifest_path = paths.manifest();
+    let toml = io.read(&manifest_path)?;
+    let Some((ours, theirs)) = manifest::split_merge_conflicts(&toml) else {
+        println!("manifest.toml has no merge conflicts, there is nothing to resolve.");
+        return Ok(());
+    };
+    let ours = parse_manifest(&ours, &manifest_path)?;
+    let theirs = parse_manifest(&theirs, &manifest_path)?;
+
+    // gleam.toml is read as merged, so any conflicts in it must be resolved
+    // first, and the locks each side still honours are merged.
+    let config = crate::config::read(paths.root_config())?;
+    let ours = Locks::new(&config, Some(&ours))?;
+    let theirs = Locks::new(&config, Some(&theirs))?;
+    let (mut versions, mut unlocked) = merge_locked(ours.versions, theirs.versions);
+    let (git_commits, unpinned) = merge_locked(ours.git_commits, theirs.git_commits);
+    // Packages from a different Hex organisation on each side are not the
+    // same package, so neither side's version is kept.
+    let (organizations, moved) = merge_locked(ours.organizations, theirs.organizations);
+    for package in &moved {
+        let _ = versions.remove(package);
+    }
+    unlocked.extend(unpinned);
+    unlocked.extend(moved);
+    unlocked.sort();
+    unlocked.dedup();
+    let locks = Locks {
+        versions,
+        git_commits,
+        organizations,
+    };
+
+    let runtime = tokio::runtime::Runtime::new().expect("Unable to start Tokio async runtime");
+    let fetcher = PackageFetcher::new(runtime.handle().clone(), HttpClient::new());
+    let manifest = resolve_versions(&io, &fetcher, Mode::Dev, &paths, &config, &locks, &reporter)?;
+    write_manifest_to_disc(&io, &paths, &manifest)?;
+    for package in &unlocked {
+        cli::print_unlocked(package);
+    }
+
+    _ = download(&paths, reporter, None, UseManifest::Yes)?;
+    Ok(())
+}
+
+/// Merges the versions or commits locked on each side of a merge, keeping
+/// those that both sides agree on or that only one side locks. Returns the
+/// merged locks and, sorted, the packages the sides disagree on.
+fn merge_locked<T: PartialEq>(
+    ours: HashMap<EcoString, T>,
+    mut theirs: HashMap<EcoString, T>,
+) -> (HashMap<EcoString, T>, Vec<EcoString>) {
+    let mut locked = HashMap::new();
+    let mut unlocked = Vec::new();
+    for (name, version) in ours {
+        match theirs.remove(&name) {
+            Some(other) if other != version => unlocked.push(name),
+            _ => {
+                let _ = locked.insert(name, version);
+            }
+        }
+    }
+    locked.extend(theirs);
+    unlocked.sort();
+    (locked, unlocked)
+}
+
+#[test]
+fn merge_locked_keeps_agreed_versions() {
+    let version = |version| Version::parse(version).expect("version");
+    let ours = [
+        ("gleam_stdlib".into(), version("0.34.0")),
+        ("gleeunit".into(), version("1.0.0")),
+        ("lustre".into(), version("3.0.0")),
+    ]
+    .into();
+    let theirs = [
+        ("gleam_stdlib".into(), version("0.36.0")),
+        ("gleeunit".into(), version("1.0.0")),
+        ("gleam_json".into(), version("1.0.0")),
+    ]
+    .into();
+    assert_eq!(
+        merge_locked(ours, theirs),
+        (
+            [
+                ("gleeunit".into(), version("1.0.0")),
+                ("lustre".into(), version("3.0.0")),
+                ("gleam_json".into(), version("1.0.0")),
+            ]
+            .into(),
+            vec!["gleam_stdlib".into()]
+        )
+    );
+}
+
+/// Changes the version requirement on a Hex package in `gleam.toml`, whether
+/// it is a dependency or a dev-dependency.
+fn set_requirement(
+    toml: &mut toml_edit::Document,
+    package: &str,
+    requirement: &hexpm::version::Range,
+) {
+    for table in ["dependencies", "dev-dependencies"] {
+        let Some(item) = toml
+            .as_table_mut()
+            .get_mut(table)
+            .and_then(|dependencies| dependencies.as_table_like_mut())
+            .and_then(|dependencies| dependencies.get_mut(package))
+        else {
+            continue;
+        };
+        let version = match item.as_table_like_mut() {
+            Some(requirement_table) => match requirement_table.get_mut("version") {
+                Some(version) => version,
+                None => continue,
+            },
+            None => item,
+        };
+        // Keep the whitespace and comments around the old requirement
+        match version.as_value_mut() {
+            Some(value) => {
+                let decor = value.decor().clone();
+                *value = toml_edit::Value::from(requirement.as_str());
+                *value.decor_mut() = decor;
+            }
+            None => *version = toml_edit::value(requirement.as_str()),
+        }
+    }
+}
+
+#[test]
+fn set_requirement_in_gleam_toml() {
+    let mut toml = r#"name = "app"
+
+[dependencies]
+lustre = "~> 3.0" # the UI
+gleam_stdlib = { version = "~> 0.30" }
+
+[dev-dependencies]
+gleeunit = "~> 1.0"
+"#
+    .parse::<toml_edit::Document>()
+    .expect("gleam.toml");
+    set_requirement(
+        &mut toml,
+        "lustre",
+        &hexpm::version::Range::new(">= 4.0.0 and < 5.0.0".into()),
+    );
+    set_requirement(
+        &mut toml,
+        "gleam_stdlib",
+        &hexpm::version::Range::new(">= 0.34.0 and < 1.0.0".into()),
+    );
+    set_requirement(
+        &mut toml,
+        "gleeunit",
+        &hexpm::version::Range::new(">= 1.1.0 and < 2.0.0".into()),
+    );
+    assert_eq!(
+        toml.to_string(),
+        r#"name = "app"
+
+[dependencies]
+lustre = ">= 4.0.0 and < 5.0.0" # the UI
+gleam_stdlib = { version = ">= 0.34.0 and < 1.0.0" }
+
+[dev-dependencies]
+gleeunit = ">= 1.1.0 and < 2.0.0"
+"#
+    );
+}
+
+pub fn download<Telem: Telemetry + Sync>(
     paths: &ProjectPaths,
     telemetry: Telem,
     new_package: Option<(Vec<String>, bool)>,
@@ -126,6 +513,24 @@ pub fn download<Telem: Telemetry>(
     // manifest which will result in the latest versions of the dependency
     // packages being resolved (not the locked ones).
     use_manifest: UseManifest,
+) -> Result<Manifest> {
+    download_with_concurrency(
+        paths,
+        telemetry,
+        new_package,
+        use_manifest,
+        hex::DEFAULT_DOWNLOAD_CONCURRENCY,
+    )
+}
+
+/// Downloads the dependencies of the project like `download`, with at most
+/// `concurrency` packages downloaded at once.
+pub fn download_with_concurrency<Telem: Telemetry + Sync>(
+    paths: &ProjectPaths,
+    telemetry: Telem,
+    new_package: Option<(Vec<String>, bool)>,
+    use_manifest: UseManifest,
+    concurrency: usize,
 ) -> Result<Manifest> {
     let span = tracing::info_span!("download_deps");
     let _enter = span.enter();
@@ -139,7 +544,7 @@ pub fn download<Telem: Telemetry>(
     let lock = BuildLock::new_packages(paths)?;
     let _guard = lock.lock(&telemetry);
 
-    let fs = ProjectIO::boxed();
+    let io = ProjectIO::new();
 
     // Read the project config
     let mut config = crate::config::read(paths.root_config())?;
@@ -160,50 +565,91 @@ pub fn download<Telem: Telemetry>(
     // Start event loop so we can run async functions to call the Hex API
     let runtime = tokio::runtime::Runtime::new().expect("Unable to start Tokio async runtime");
 
+    let fetcher = PackageFetcher::new(runtime.handle().clone(), HttpClient::new());
+
     // Determine what versions we need
     let (manifest_updated, manifest) = get_manifest(
         paths,
-        runtime.handle().clone(),
+        &io,
+        &fetcher,
         mode,
         &config,
         &telemetry,
         use_manifest,
     )?;
-    let local = LocalPackages::read_from_disc(paths)?;
+    let local = LocalPackages::read_from_disc(&io, paths)?;
 
     // Remove any packages that are no longer required due to gleam.toml changes
-    remove_extra_packages(paths, &local, &manifest, &telemetry)?;
+    {
+        let _guard = BuildLock::lock_all_build(paths, &telemetry)?;
+        remove_extra_packages(&io, paths, &local, &manifest)?;
+    }
 
     // Download them from Hex to the local cache
     runtime.block_on(add_missing_packages(
         paths,
-        fs,
+        io,
+        fetcher.http,
         &manifest,
         &local,
         project_name,
+        concurrency,
         &telemetry,
     ))?;
+    check_out_git_packages(paths, &manifest)?;
 
     if manifest_updated {
         // Record new state of the packages directory
         // TODO: test
         tracing::debug!("writing_manifest_toml");
-        write_manifest_to_disc(paths, &manifest)?;
+        write_manifest_to_disc(&io, paths, &manifest)?;
     }
-    LocalPackages::from_manifest(&manifest).write_to_disc(paths)?;
+    LocalPackages::from_manifest(&manifest).write_to_disc(&io, paths)?;
 
     Ok(manifest)
 }
 
-async fn add_missing_packages<Telem: Telemetry>(
+#[allow(clippy::too_many_arguments)]
+async fn add_missing_packages<IO, Http, Telem>(
     paths: &ProjectPaths,
-    fs: Box<ProjectIO>,
+    io: IO,
+    http: Http,
     manifest: &Manifest,
     local: &LocalPackages,
     project_name: EcoString,
+    concurrency: usize,
     telemetry: &Telem,
-) -> Result<(), Error> {
-    let missing_packages = local.missing_local_packages(manifest, &project_name);
+) -> Result<(), Error>
+where
+    IO: FileSystemReader + FileSystemWriter + Clone + 'static,
+    Http: gleam_core::io::HttpClient + 'static,
+    Telem: Telemetry + Sync,
+{
+    let mut missing_packages = local.missing_local_packages(manifest, &project_name);
+
+    // Packages that have been extracted already are extracted again if their
+    // files have been changed, as the build would otherwise use the changes.
+    for package in &manifest.packages {
+        if !package.is_hex() || package.name == project_name || missing_packages.contains(&package)
+        {
+            continue;
+        }
+        let directory = paths.build_packages_package_source(package);
+        match integrity::check(&io, &directory)? {
+            Integrity::Intact => continue,
+            Integrity::Missing => (),
+            Integrity::Modified { files } => {
+                tracing::info!(package = package.name.as_str(), "package_files_modified");
+                ConsoleWarningEmitter.emit_warning(Warning::ModifiedDependency {
+                    package: package.name.clone(),
+                    files,
+                });
+                // The build artefacts may have been compiled from the changes.
+                remove_build_artefacts(&io, paths, package.application_name())?;
+            }
+        }
+        missing_packages.push(package);
+    }
 
     let mut num_to_download = 0;
     let mut missing_hex_packages = missing_packages
@@ -217,73 +663,125 @@ async fn add_missing_packages<Telem: Telemetry>(
 
     // If we need to download at-least one package
     if missing_hex_packages.peek().is_some() {
-        let http = HttpClient::boxed();
-        let downloader = hex::Downloader::new(fs.clone(), fs, http, Untar::boxed(), paths.clone());
+        let organization_keys = manifest
+            .packages
+            .iter()
+            .filter_map(|package| match &package.source {
+                ManifestPackageSource::Hex {
+                    organization: Some(organization),
+                    ..
+                } => Some((
+                    organization.clone(),
+                    crate::hex::organization_key(organization)?,
+                )),
+                _ => None,
+            })
+            .collect();
+        let downloader = hex::Downloader::new(
+            Box::new(io.clone()),
+            Box::new(io),
+            Box::new(http),
+            Untar::boxed(),
+            paths.clone(),
+        )
+        .with_organization_keys(organization_keys);
         let start = Instant::now();
         telemetry.downloading_package("packages");
-        downloader
-            .download_hex_packages(missing_hex_packages, &project_name)
+        let size = downloader
+            .download_hex_packages(missing_hex_packages, &project_name, concurrency, telemetry)
             .await?;
-        telemetry.packages_downloaded(start, num_to_download);
+        tracing::info!(
+            count = num_to_download,
+            bytes = size,
+            elapsed_ms = start.elapsed().as_millis(),
+            "packages_downloaded"
+        );
+        telemetry.packages_downloaded(start, num_to_download, size);
     }
 
     Ok(())
 }
 
-fn remove_extra_packages<Telem: Telemetry>(
+/// Makes sure each git package is checked out at the commit it is pinned to
+/// in the manifest, checking it out again if it is missing or has changed.
+fn check_out_git_packages(paths: &ProjectPaths, manifest: &Manifest) -> Result<()> {
+    for package in &manifest.packages {
+        if let ManifestPackageSource::Git { repo, commit } = &package.source {
+            let directory = paths.build_packages_package(&package.name);
+            checkout_git_commit(&package.name, repo, commit, &directory)?;
+        }
+    }
+    Ok(())
+}
+
+fn remove_extra_packages<IO: FileSystemReader + FileSystemWriter>(
+    io: &IO,
     paths: &ProjectPaths,
     local: &LocalPackages,
     manifest: &Manifest,
-    telemetry: &Telem,
 ) -> Result<()> {
-    let _guard = BuildLock::lock_all_build(paths, telemetry)?;
-
     for (package_name, version) in local.extra_local_packages(manifest) {
-        // TODO: test
         // Delete the package source
-        let path = paths.build_packages_package(&package_name);
-        if path.exists() {
+        for path in hex::extracted_package_directories(io, paths, &package_name)? {
             tracing::debug!(package=%package_name, version=%version, "removing_unneeded_package");
-            fs::delete_directory(&path)?;
+            io.delete_directory(&path)?;
         }
 
-        // TODO: test
         // Delete any build artefacts for the package
-        for mode in Mode::iter() {
-            for target in Target::iter() {
-                let name = manifest
-                    .packages
-                    .iter()
-                    .find(|p| p.name == package_name)
-                    .map(|p| p.application_name().as_str())
-                    .unwrap_or(package_name.as_str());
-                let path = paths.build_directory_for_package(mode, target, name);
-                if path.exists() {
-                    tracing::debug!(package=%package_name, version=%version, "deleting_build_cache");
-                    fs::delete_directory(&path)?;
-                }
+        let name = manifest
+            .packages
+            .iter()
+            .find(|p| p.name == package_name)
+            .map(|p| p.application_name().as_str())
+            .unwrap_or(package_name.as_str());
+        remove_build_artefacts(io, paths, name)?;
+    }
+    Ok(())
+}
+
+fn remove_build_artefacts<IO: FileSystemReader + FileSystemWriter>(
+    io: &IO,
+    paths: &ProjectPaths,
+    application_name: &str,
+) -> Result<()> {
+    for mode in Mode::iter() {
+        for target in Target::iter() {
+            let path = paths.build_directory_for_package(mode, target, application_name);
+            if io.is_directory(&path) {
+                tracing::debug!(application = application_name, "deleting_build_cache");
+                io.delete_directory(&path)?;
             }
         }
     }
     Ok(())
 }
 
-fn read_manifest_from_disc(paths: &ProjectPaths) -> Result<Manifest> {
+pub(crate) fn read_manifest_from_disc<IO: FileSystemReader>(
+    io: &IO,
+    paths: &ProjectPaths,
+) -> Result<Manifest> {
     tracing::debug!("reading_manifest_toml");
     let manifest_path = paths.manifest();
-    let toml = fs::read(&manifest_path)?;
-    let manifest = toml::from_str(&toml).map_err(|e| Error::FileIo {
+    let toml = io.read(&manifest_path)?;
+    parse_manifest(&toml, &manifest_path)
+}
+
+fn parse_manifest(toml: &str, path: &Utf8Path) -> Result<Manifest> {
+    toml::from_str(toml).map_err(|e| Error::FileIo {
         action: FileIoAction::Parse,
         kind: FileKind::File,
-        path: manifest_path.clone(),
+        path: path.to_path_buf(),
         err: Some(e.to_string()),
-    })?;
-    Ok(manifest)
+    })
 }
 
-fn write_manifest_to_disc(paths: &ProjectPaths, manifest: &Manifest) -> Result<()> {
+fn write_manifest_to_disc<IO: FileSystemWriter>(
+    io: &IO,
+    paths: &ProjectPaths,
+    manifest: &Manifest,
+) -> Result<()> {
     let path = paths.manifest();
-    fs::write(&path, &manifest.to_toml(paths.root()))
+    io.write(&path, &manifest.to_toml(paths.root()))
 }
 
 // This is the container for locally pinned packages, representing the current contents of
@@ -326,14 +824,14 @@ impl LocalPackages {
             .collect()
     }
 
-    pub fn read_from_disc(paths: &ProjectPaths) -> Result<Self> {
+    pub fn read_from_disc<IO: FileSystemReader>(io: &IO, paths: &ProjectPaths) -> Result<Self> {
         let path = paths.build_packages_toml();
-        if !path.exists() {
+        if !io.is_file(&path) {
             return Ok(Self {
                 packages: HashMap::new(),
             });
         }
-        let toml = fs::read(&path)?;
+        let toml = io.read(&path)?;
         toml::from_str(&toml).map_err(|e| Error::FileIo {
             action: FileIoAction::Parse,
             kind: FileKind::File,
@@ -342,10 +840,10 @@ impl LocalPackages {
         })
     }
 
-    pub fn write_to_disc(&self, paths: &ProjectPaths) -> Result<()> {
+    pub fn write_to_disc<IO: FileSystemWriter>(&self, io: &IO, paths: &ProjectPaths) -> Result<()> {
         let path = paths.build_packages_toml();
         let toml = toml::to_string(&self).expect("packages.toml serialization");
-        fs::write(&path, &toml)
+        io.write(&path, &toml)
     }
 
     pub fn from_manifest(manifest: &Manifest) -> Self {
@@ -372,6 +870,7 @@ fn missing_local_packages() {
                 requirements: vec![],
                 source: ManifestPackageSource::Hex {
                     outer_checksum: Base16Checksum(vec![1, 2, 3, 4]),
+                    organization: None,
                 },
             },
             ManifestPackage {
@@ -382,6 +881,7 @@ fn missing_local_packages() {
                 requirements: vec![],
                 source: ManifestPackageSource::Hex {
                     outer_checksum: Base16Checksum(vec![1, 2, 3, 4, 5]),
+                    organization: None,
                 },
             },
             ManifestPackage {
@@ -392,6 +892,7 @@ fn missing_local_packages() {
                 requirements: vec![],
                 source: ManifestPackageSource::Hex {
                     outer_checksum: Base16Checksum(vec![1, 2, 3, 4, 5]),
+                    organization: None,
                 },
             },
         ],
@@ -416,6 +917,7 @@ fn missing_local_packages() {
                 requirements: vec![],
                 source: ManifestPackageSource::Hex {
                     outer_checksum: Base16Checksum(vec![1, 2, 3, 4, 5]),
+                    organization: None,
                 },
             },
             &ManifestPackage {
@@ -426,6 +928,7 @@ fn missing_local_packages() {
                 requirements: vec![],
                 source: ManifestPackageSource::Hex {
                     outer_checksum: Base16Checksum(vec![1, 2, 3, 4, 5]),
+                    organization: None,
                 },
             },
         ]
@@ -453,6 +956,7 @@ fn extra_local_packages() {
                 requirements: vec![],
                 source: ManifestPackageSource::Hex {
                     outer_checksum: Base16Checksum(vec![1, 2, 3, 4, 5]),
+                    organization: None,
                 },
             },
             ManifestPackage {
@@ -463,6 +967,7 @@ fn extra_local_packages() {
                 requirements: vec![],
                 source: ManifestPackageSource::Hex {
                     outer_checksum: Base16Checksum(vec![4, 5]),
+                    organization: None,
                 },
             },
         ],
@@ -477,18 +982,89 @@ fn extra_local_packages() {
     )
 }
 
-fn get_manifest<Telem: Telemetry>(
+#[test]
+fn local_packages_round_trip() {
+    let io = gleam_core::io::memory::InMemoryFileSystem::new();
+    let paths = ProjectPaths::new("/app".into());
+    let read = LocalPackages::read_from_disc(&io, &paths).expect("read missing packages.toml");
+    assert_eq!(read.packages, HashMap::new());
+
+    let local = LocalPackages {
+        packages: [("wibble".into(), Version::new(1, 2, 3))].into(),
+    };
+    local
+        .write_to_disc(&io, &paths)
+        .expect("write packages.toml");
+    let read = LocalPackages::read_from_disc(&io, &paths).expect("read packages.toml");
+    assert_eq!(read.packages, local.packages);
+}
+
+#[test]
+fn remove_extra_packages_deletes_sources_and_build_artefacts() {
+    let io = gleam_core::io::memory::InMemoryFileSystem::new();
+    let paths = ProjectPaths::new("/app".into());
+    let wobble = paths.build_packages_package("wobble").join("gleam.toml");
+    let files = [
+        paths.build_packages_package("wibble").join("gleam.toml"),
+        paths
+            .build_packages_directory()
+            .join("wibble-010203")
+            .join("gleam.toml"),
+        paths
+            .build_directory_for_package(Mode::Dev, Target::Erlang, "wibble")
+            .join("ebin/wibble.app"),
+        paths
+            .build_directory_for_package(Mode::Prod, Target::JavaScript, "wibble")
+            .join("wibble.mjs"),
+        wobble.clone(),
+    ];
+    for path in &files {
+        io.write(path, "").expect("write package file");
+    }
+
+    let local = LocalPackages {
+        packages: [
+            ("wibble".into(), Version::new(1, 0, 0)),
+            ("wobble".into(), Version::new(1, 0, 0)),
+        ]
+        .into(),
+    };
+    let manifest = Manifest {
+        requirements: HashMap::new(),
+        packages: vec![ManifestPackage {
+            name: "wobble".into(),
+            version: Version::new(1, 0, 0),
+            build_tools: ["gleam".into()].into(),
+            otp_app: None,
+            requirements: vec![],
+            source: ManifestPackageSource::Hex {
+                outer_checksum: Base16Checksum(vec![1, 2, 3]),
+                organization: None,
+            },
+        }],
+    };
+    remove_extra_packages(&io, &paths, &local, &manifest).expect("remove extra packages");
+    assert_eq!(io.paths(), vec![wobble]);
+}
+
+fn get_manifest<IO, Http, Telem>(
     paths: &ProjectPaths,
-    runtime: tokio::runtime::Handle,
+    io: &IO,
+    fetcher: &PackageFetcher<Http>,
     mode: Mode,
     config: &PackageConfig,
     telemetry: &Telem,
     use_manifest: UseManifest,
-) -> Result<(bool, Manifest)> {
+) -> Result<(bool, Manifest)>
+where
+    IO: FileSystemReader,
+    Http: gleam_core::io::HttpClient + Clone + 'static,
+    Telem: Telemetry,
+{
     // If there's no manifest (or we have been asked not to use it) then resolve
     // the versions anew
     let should_resolve = match use_manifest {
-        _ if !paths.manifest().exists() => {
+        _ if !io.is_file(&paths.manifest()) => {
             tracing::debug!("manifest_not_present");
             true
         }
@@ -500,15 +1076,24 @@ fn get_manifest<Telem: Telemetry>(
     };
 
     if should_resolve {
-        let manifest = resolve_versions(runtime, mode, paths, config, None, telemetry)?;
+        let manifest = resolve_versions(
+            io,
+            fetcher,
+            mode,
+            paths,
+            config,
+            &Locks::default(),
+            telemetry,
+        )?;
         return Ok((true, manifest));
     }
 
-    let manifest = read_manifest_from_disc(paths)?;
+    let manifest = read_manifest_from_disc(io, paths)?;
 
     // If the config has unchanged since the manifest was written then it is up
     // to date so we can return it unmodified.
     if is_same_requirements(
+        io,
         &manifest.requirements,
         &config.all_dependencies()?,
         paths.root(),
@@ -517,12 +1102,14 @@ fn get_manifest<Telem: Telemetry>(
         Ok((false, manifest))
     } else {
         tracing::debug!("manifest_outdated");
-        let manifest = resolve_versions(runtime, mode, paths, config, Some(&manifest), telemetry)?;
+        let locks = Locks::new(config, Some(&manifest))?;
+        let manifest = resolve_versions(io, fetcher, mode, paths, config, &locks, telemetry)?;
         Ok((true, manifest))
     }
 }
 
-fn is_same_requirements(
+fn is_same_requirements<IO: FileSystemReader>(
+    io: &IO,
     requirements1: &HashMap<EcoString, Requirement>,
     requirements2: &HashMap<EcoString, Requirement>,
     root_path: &Utf8Path,
@@ -532,7 +1119,7 @@ fn is_same_requirements(
     }
 
     for (key, requirement1) in requirements1 {
-        if !same_requirements(requirement1, requirements2.get(key), root_path)? {
+        if !same_requirements(io, requirement1, requirements2.get(key), root_path)? {
             return Ok(false);
         }
     }
@@ -540,15 +1127,28 @@ fn is_same_requirements(
     Ok(true)
 }
 
-fn same_requirements(
+fn same_requirements<IO: FileSystemReader>(
+    io: &IO,
     requirement1: &Requirement,
     requirement2: Option<&Requirement>,
     root_path: &Utf8Path,
 ) -> Result<bool> {
     let (left, right) = match (requirement1, requirement2) {
-        (Requirement::Path { path: path1 }, Some(Requirement::Path { path: path2 })) => {
-            let left = fs::canonicalise(&root_path.join(path1))?;
-            let right = fs::canonicalise(&root_path.join(path2))?;
+        (
+            Requirement::Path {
+                path: path1,
+                features: features1,
+            },
+            Some(Requirement::Path {
+                path: path2,
+                features: features2,
+            }),
+        ) => {
+            if features1 != features2 {
+                return Ok(false);
+            }
+            let left = io.canonicalise(&root_path.join(path1))?;
+            let right = io.canonicalise(&root_path.join(path2))?;
             (left, right)
         }
         (_, Some(requirement2)) => return Ok(requirement1 == requirement2),
@@ -664,120 +1264,369 @@ impl PartialEq for ProvidedPackageSource {
     }
 }
 
-fn resolve_versions<Telem: Telemetry>(
-    runtime: tokio::runtime::Handle,
+// The resolver's own error is large, and kept for suggesting fixes
+#[allow(clippy::result_large_err)]
+fn resolve_versions<IO, Http, Telem>(
+    io: &IO,
+    fetcher: &PackageFetcher<Http>,
     mode: Mode,
     project_paths: &ProjectPaths,
     config: &PackageConfig,
-    manifest: Option<&Manifest>,
+    locks: &Locks,
     telemetry: &Telem,
-) -> Result<Manifest, Error> {
+) -> Result<Manifest, Error>
+where
+    IO: FileSystemReader,
+    Http: gleam_core::io::HttpClient + Clone + 'static,
+    Telem: Telemetry,
+{
+    let start = Instant::now();
     telemetry.resolving_package_versions();
     let dependencies = config.dependencies_for(mode)?;
-    let locked = config.locked(manifest)?;
 
     // Packages which are provided directly instead of downloaded from hex
     let mut provided_packages = HashMap::new();
-    // The version requires of the current project
-    let mut root_requirements = HashMap::new();
-
-    // Populate the provided_packages and root_requirements maps
+    // The version requires of the current project on Hex packages, which
+    // may be changed to fix a failed resolution
+    let mut hex_requirements = HashMap::new();
+    // The version requires of the current project on provided packages
+    let mut provided_requirements = HashMap::new();
+    // The Hex organisation of each package known to be published to one
+    let mut organizations = locks.organizations.clone();
+
+    // Populate the provided_packages and requirements maps
     for (name, requirement) in dependencies.into_iter() {
         let version = match requirement {
-            Requirement::Hex { version } => version,
-            Requirement::Path { path } => provide_local_package(
+            Requirement::Hex {
+                version,
+                organization,
+                ..
+            } => {
+                if let Some(organization) = organization {
+                    let _ = organizations.insert(name.clone(), organization);
+                }
+                let _ = hex_requirements.insert(name, version);
+                continue;
+            }
+            Requirement::Path { path, .. } => provide_local_package(
+                io,
                 name.clone(),
                 &path,
                 project_paths.root(),
                 project_paths,
                 &mut provided_packages,
                 &mut vec![],
+                &locks.git_commits,
+            )?,
+            Requirement::Git { git, reference, .. } => provide_git_package(
+                io,
+                name.clone(),
+                &git,
+                reference.as_deref(),
+                project_paths,
+                &mut provided_packages,
+                &mut vec![],
+                &locks.git_commits,
             )?,
-            Requirement::Git { git } => {
-                provide_git_package(name.clone(), &git, project_paths, &mut provided_packages)?
-            }
         };
-        let _ = root_requirements.insert(name, version);
+        let _ = provided_requirements.insert(name, version);
     }
 
     // Convert provided packages into hex packages for pub-grub resolve
-    let provided_hex_packages = provided_packages
+    let provided_hex_packages: HashMap<_, _> = provided_packages
         .iter()
         .map(|(name, package)| (name.clone(), package.to_hex_package(name)))
         .collect();
 
-    let resolved = dependency::resolve_versions(
-        PackageFetcher::boxed(runtime.clone()),
-        provided_hex_packages,
-        config.name.clone(),
-        root_requirements.into_iter(),
-        &locked,
-    )?;
+    // The registries Hex packages are looked up from, highest priority first
+    let registries = config
+        .registries
+        .iter()
+        .map(|registry| SourceKind::of_repository(Some(registry)))
+        .collect_vec();
+    let registry_organizations = registries.iter().filter_map(|kind| match kind {
+        SourceKind::HexOrganization(organization) => Some(organization),
+        SourceKind::Hex | SourceKind::Git | SourceKind::Path | SourceKind::Custom(_) => None,
+    });
+
+    // Packages of Hex organisations are looked up with the organisation's key.
+    // Organisations with a key of their own may have packages that only the
+    // packages depending on them name.
+    let mut organization_fetchers = HashMap::new();
+    for organization in organizations.values().chain(registry_organizations) {
+        if !organization_fetchers.contains_key(organization) {
+            let api_key = crate::hex::organization_key(organization).ok_or_else(|| {
+                Error::HexOrganizationKeyMissing {
+                    organization: organization.clone(),
+                }
+            })?;
+            let organization_fetcher = fetcher.with_organization(organization, api_key)?;
+            let _ = organization_fetchers.insert(organization.clone(), organization_fetcher);
+        }
+    }
+    for organization in crate::hex::organizations_with_keys() {
+        if organization_fetchers.contains_key(&organization) {
+            continue;
+        }
+        let api_key = crate::hex::organization_key(&organization);
+        if let Some(Ok(organization_fetcher)) =
+            api_key.map(|api_key| fetcher.with_organization(&organization, api_key))
+        {
+            let _ = organization_fetchers.insert(organization, organization_fetcher);
+        }
+    }
+
+    let resolve = |hex_requirements: HashMap<EcoString, hexpm::version::Range>,
+                   locked: &HashMap<EcoString, Version>,
+                   observer: &dyn ResolutionObserver| {
+        let mut fetchers = PackageFetchers::new();
+        let _ = fetchers.register(SourceKind::Hex, Box::new(fetcher.clone()));
+        for (organization, fetcher) in &organization_fetchers {
+            let kind = SourceKind::HexOrganization(organization.clone());
+            let _ = fetchers.register(kind, Box::new(fetcher.clone()));
+        }
+        for (package, organization) in &organizations {
+            let kind = SourceKind::HexOrganization(organization.clone());
+            let _ = fetchers.set_source(package.clone(), kind);
+        }
+        if !registries.is_empty() {
+            // A locked package stays with the registry that served it, which
+            // the manifest records, even if one of higher priority has it too.
+            for package in locked.keys() {
+                if !organizations.contains_key(package) {
+                    let _ = fetchers.set_source(package.clone(), SourceKind::Hex);
+                }
+            }
+            fetchers.set_priority(registries.clone());
+        }
+        dependency::resolve_versions(
+            fetchers,
+            provided_hex_packages.clone(),
+            config.name.clone(),
+            hex_requirements
+                .into_iter()
+                .chain(provided_requirements.clone()),
+            locked,
+            ResolveOptions {
+                strategy: config.resolution_strategy,
+                minimums: config.deps.minimums.clone(),
+                observer,
+            },
+        )
+    };
+    let locked = &locks.versions;
+    let observer = TracingObserver(telemetry);
+    let resolved = resolve(hex_requirements.clone(), locked, &observer).map_err(|error| {
+        // The attempts at fixing the resolution are not shown as progress
+        let fixes =
+            dependency::suggest_fixes(&error, &hex_requirements, locked, |requirements, locked| {
+                resolve(requirements, &locked, &NullResolutionObserver)
+            });
+        Error::dependency_resolution_failed_with_fixes(error, fixes)
+    })?;
+    for warning in &resolved.warnings {
+        tracing::warn!(package = warning.package(), warning = ?warning, "resolution_warning");
+    }
+    telemetry.package_versions_resolved(start, &resolved);
 
     // Convert the hex packages and local packages into manifest packages
-    let manifest_packages = runtime.block_on(future::try_join_all(
-        resolved
-            .into_iter()
-            .map(|(name, version)| lookup_package(name, version, &provided_packages)),
-    ))?;
+    let mut manifest_packages =
+        fetcher
+            .runtime
+            .block_on(future::try_join_all(resolved.packages.into_iter().map(
+                |(name, version)| {
+                    let fetcher = match resolved.sources.get(&name) {
+                        Some(SourceKind::HexOrganization(organization)) => {
+                            organization_fetchers.get(organization).unwrap_or(fetcher)
+                        }
+                        _ => fetcher,
+                    };
+                    lookup_package(name, version, &provided_packages, fetcher)
+                },
+            )))?;
+    manifest_packages.sort_by(|a, b| a.name.cmp(&b.name));
 
     let manifest = Manifest {
         packages: manifest_packages,
         requirements: config.all_dependencies()?,
     };
+    tracing::info!(count = manifest.packages.len(), "versions_resolved");
 
     Ok(manifest)
 }
 
+/// Logs each step the solver takes at the trace level before passing it on,
+/// so that `--log-level trace` shows how versions were resolved,
+/// in JSON too with `--log-format json`.
+struct TracingObserver<'a, Observer>(&'a Observer);
+
+impl<Observer: ResolutionObserver> ResolutionObserver for TracingObserver<'_, Observer> {
+    fn package_fetched(&self, name: &str, source: &SourceKind) {
+        tracing::trace!(package = name, source = ?source, "package_fetched");
+        self.0.package_fetched(name, source);
+    }
+
+    fn version_chosen(&self, name: &str, version: &Version) {
+        tracing::trace!(package = name, version = %version, "version_chosen");
+        self.0.version_chosen(name, version);
+    }
+
+    fn conflict_encountered(&self, name: &str) {
+        tracing::trace!(package = name, "conflict_encountered");
+        self.0.conflict_encountered(name);
+    }
+
+    fn backtracked(&self, name: &str, version: &Version) {
+        tracing::trace!(package = name, version = %version, "backtracked");
+        self.0.backtracked(name, version);
+    }
+}
+
 /// Provide a package from a local project
-fn provide_local_package(
+#[allow(clippy::too_many_arguments)]
+fn provide_local_package<IO: FileSystemReader>(
+    io: &IO,
     package_name: EcoString,
     package_path: &Utf8Path,
     parent_path: &Utf8Path,
     project_paths: &ProjectPaths,
     provided: &mut HashMap<EcoString, ProvidedPackage>,
     parents: &mut Vec<EcoString>,
+    git_commits: &HashMap<EcoString, EcoString>,
 ) -> Result<hexpm::version::Range> {
     let package_path = if package_path.is_absolute() {
         package_path.to_path_buf()
     } else {
-        fs::canonicalise(&parent_path.join(package_path))?
+        io.canonicalise(&parent_path.join(package_path))?
     };
     let package_source = ProvidedPackageSource::Local {
         path: package_path.clone(),
     };
     provide_package(
+        io,
         package_name,
         package_path,
         package_source,
         project_paths,
         provided,
         parents,
+        git_commits,
     )
 }
 
-/// Provide a package from a git repository
-fn provide_git_package(
-    _package_name: EcoString,
-    _repo: &str,
-    _project_paths: &ProjectPaths,
-    _provided: &mut HashMap<EcoString, ProvidedPackage>,
+/// Provide a package from a git repository, checked out into the build
+/// directory at the commit it is pinned to in the manifest, or at the latest
+/// commit of the requested branch, tag, or commit if it is not pinned.
+#[allow(clippy::too_many_arguments)]
+fn provide_git_package<IO: FileSystemReader>(
+    io: &IO,
+    package_name: EcoString,
+    repo: &str,
+    reference: Option<&str>,
+    project_paths: &ProjectPaths,
+    provided: &mut HashMap<EcoString, ProvidedPackage>,
+    parents: &mut Vec<EcoString>,
+    git_commits: &HashMap<EcoString, EcoString>,
 ) -> Result<hexpm::version::Range> {
-    let _git = ProvidedPackageSource::Git {
-        repo: "repo".into(),
-        commit: "commit".into(),
+    // A package already provided from this repository isn't checked out
+    // again, as that would replace the files it was read from.
+    if let Some(package) = provided.get(&package_name) {
+        if let ProvidedPackageSource::Git {
+            repo: provided_repo,
+            ..
+        } = &package.source
+        {
+            if provided_repo == repo {
+                return Ok(hexpm::version::Range::new(format!(
+                    "== {}",
+                    &package.version
+                )));
+            }
+        }
+    }
+
+    let package_path = project_paths.build_packages_package(&package_name);
+    let commit = match git_commits.get(&package_name) {
+        Some(commit) => {
+            checkout_git_commit(&package_name, repo, commit, &package_path)?;
+            commit.clone()
+        }
+        None => {
+            let reference = reference.unwrap_or("HEAD");
+            let checkout_failed = || Error::GitCheckoutFailed {
+                package: package_name.clone(),
+                repo: repo.into(),
+                reference: reference.into(),
+            };
+            if !fs::git_checkout(repo, reference, &package_path)? {
+                return Err(checkout_failed());
+            }
+            fs::git_head(&package_path)?
+                .ok_or_else(checkout_failed)?
+                .into()
+        }
     };
-    Err(Error::GitDependencyUnsupported)
+    tracing::debug!(
+        package = package_name.as_str(),
+        commit = commit.as_str(),
+        "git_package_checked_out"
+    );
+    let package_source = ProvidedPackageSource::Git {
+        repo: repo.into(),
+        commit,
+    };
+    provide_package(
+        io,
+        package_name,
+        package_path,
+        package_source,
+        project_paths,
+        provided,
+        parents,
+        git_commits,
+    )
+}
+
+/// Makes sure the given commit of a git repository is checked out in the
+/// directory, checking it out if something else is there, and verifying that
+/// the commit checked out is the one asked for.
+fn checkout_git_commit(
+    package_name: &EcoString,
+    repo: &str,
+    commit: &EcoString,
+    package_path: &Utf8Path,
+) -> Result<()> {
+    if fs::git_head(package_path)?.as_deref() == Some(commit.as_str()) {
+        return Ok(());
+    }
+    if !fs::git_checkout(repo, commit, package_path)? {
+        return Err(Error::GitCheckoutFailed {
+            package: package_name.clone(),
+            repo: repo.into(),
+            reference: commit.clone(),
+        });
+    }
+    match fs::git_head(package_path)? {
+        Some(found) if found == commit.as_str() => Ok(()),
+        found => Err(Error::GitCommitMismatch {
+            package: package_name.clone(),
+            expected: commit.clone(),
+            found: found.unwrap_or_default().into(),
+        }),
+    }
 }
 
 /// Adds a gleam project located at a specific path to the list of "provided packages"
-fn provide_package(
+#[allow(clippy::too_many_arguments)]
+fn provide_package<IO: FileSystemReader>(
+    io: &IO,
     package_name: EcoString,
     package_path: Utf8PathBuf,
     package_source: ProvidedPackageSource,
     project_paths: &ProjectPaths,
     provided: &mut HashMap<EcoString, ProvidedPackage>,
     parents: &mut Vec<EcoString>,
+    git_commits: &HashMap<EcoString, EcoString>,
 ) -> Result<hexpm::version::Range> {
     // Return early if a package cycle is detected
     if parents.contains(&package_name) {
@@ -809,7 +1658,8 @@ fn provide_package(
         None => (),
     }
     // Load the package
-    let config = crate::config::read(package_path.join("gleam.toml"))?;
+    let config = PackageConfig::read(package_path.join("gleam.toml"), io)?;
+    config.check_gleam_compatibility()?;
     // Check that we are loading the correct project
     if config.name != package_name {
         return Err(Error::WrongDependencyProvided {
@@ -823,21 +1673,30 @@ fn provide_package(
     parents.push(package_name);
     for (name, requirement) in config.dependencies.into_iter() {
         let version = match requirement {
-            Requirement::Hex { version } => version,
-            Requirement::Path { path } => {
+            Requirement::Hex { version, .. } => version,
+            Requirement::Path { path, .. } => {
                 // Recursively walk local packages
                 provide_local_package(
+                    io,
                     name.clone(),
                     &path,
                     &package_path,
                     project_paths,
                     provided,
                     parents,
+                    git_commits,
                 )?
             }
-            Requirement::Git { git } => {
-                provide_git_package(name.clone(), &git, project_paths, provided)?
-            }
+            Requirement::Git { git, reference, .. } => provide_git_package(
+                io,
+                name.clone(),
+                &git,
+                reference.as_deref(),
+                project_paths,
+                provided,
+                parents,
+                git_commits,
+            )?,
         };
         let _ = requirements.insert(name, version);
     }
@@ -861,12 +1720,14 @@ fn provide_wrong_package() {
     let mut provided = HashMap::new();
     let project_paths = crate::project_paths_at_current_directory_without_toml();
     let result = provide_local_package(
+        &ProjectIO::new(),
         "wrong_name".into(),
         Utf8Path::new("./test/hello_world"),
         Utf8Path::new("./"),
         &project_paths,
         &mut provided,
         &mut vec!["root".into(), "subpackage".into()],
+        &HashMap::new(),
     );
     if let Err(Error::WrongDependencyProvided {
         expected, found, ..
@@ -885,22 +1746,26 @@ fn provide_existing_package() {
     let project_paths = crate::project_paths_at_current_directory_without_toml();
 
     let result = provide_local_package(
+        &ProjectIO::new(),
         "hello_world".into(),
         Utf8Path::new("./test/hello_world"),
         Utf8Path::new("./"),
         &project_paths,
         &mut provided,
         &mut vec!["root".into(), "subpackage".into()],
+        &HashMap::new(),
     );
     assert_eq!(result, Ok(hexpm::version::Range::new("== 0.1.0".into())));
 
     let result = provide_local_package(
+        &ProjectIO::new(),
         "hello_world".into(),
         Utf8Path::new("./test/hello_world"),
         Utf8Path::new("./"),
         &project_paths,
         &mut provided,
         &mut vec!["root".into(), "subpackage".into()],
+        &HashMap::new(),
     );
     assert_eq!(result, Ok(hexpm::version::Range::new("== 0.1.0".into())));
 }
@@ -910,16 +1775,19 @@ fn provide_conflicting_package() {
     let mut provided = HashMap::new();
     let project_paths = crate::project_paths_at_current_directory_without_toml();
     let result = provide_local_package(
+        &ProjectIO::new(),
         "hello_world".into(),
         Utf8Path::new("./test/hello_world"),
         Utf8Path::new("./"),
         &project_paths,
         &mut provided,
         &mut vec!["root".into(), "subpackage".into()],
+        &HashMap::new(),
     );
     assert_eq!(result, Ok(hexpm::version::Range::new("== 0.1.0".into())));
 
     let result = provide_package(
+        &ProjectIO::new(),
         "hello_world".into(),
         Utf8PathBuf::from("./test/other"),
         ProvidedPackageSource::Local {
@@ -928,6 +1796,7 @@ fn provide_conflicting_package() {
         &project_paths,
         &mut provided,
         &mut vec!["root".into(), "subpackage".into()],
+        &HashMap::new(),
     );
     if let Err(Error::ProvidedDependencyConflict { package, .. }) = result {
         assert_eq!(package, "hello_world");
@@ -941,12 +1810,14 @@ fn provided_is_absolute() {
     let mut provided = HashMap::new();
     let project_paths = crate::project_paths_at_current_directory_without_toml();
     let result = provide_local_package(
+        &ProjectIO::new(),
         "hello_world".into(),
         Utf8Path::new("./test/hello_world"),
         Utf8Path::new("./"),
         &project_paths,
         &mut provided,
         &mut vec!["root".into(), "subpackage".into()],
+        &HashMap::new(),
     );
     assert_eq!(result, Ok(hexpm::version::Range::new("== 0.1.0".into())));
     let package = provided.get("hello_world").unwrap().clone();
@@ -962,12 +1833,14 @@ fn provided_recursive() {
     let mut provided = HashMap::new();
     let project_paths = crate::project_paths_at_current_directory_without_toml();
     let result = provide_local_package(
+        &ProjectIO::new(),
         "hello_world".into(),
         Utf8Path::new("./test/hello_world"),
         Utf8Path::new("./"),
         &project_paths,
         &mut provided,
         &mut vec!["root".into(), "hello_world".into(), "subpackage".into()],
+        &HashMap::new(),
     );
     assert_eq!(
         result,
@@ -977,18 +1850,98 @@ fn provided_recursive() {
     )
 }
 
+#[test]
+fn provide_git_package_at_pinned_commit() {
+    let directory = tempfile::tempdir().expect("temporary directory");
+    let root = Utf8Path::from_path(directory.path()).expect("utf-8 path");
+    let repo = root.join("repo");
+    let git = |args: &[&str]| {
+        let output = std::process::Command::new("git")
+            .args(["-c", "user.name=Test", "-c", "user.email=test@example.com"])
+            .args(args)
+            .current_dir(&repo)
+            .output()
+            .expect("git");
+        assert!(output.status.success(), "git {args:?}");
+        String::from_utf8(output.stdout).expect("git output")
+    };
+    let commit_version = |version: &str| {
+        fs::write(
+            &repo.join("gleam.toml"),
+            &format!("name = \"wibble\"\nversion = \"{version}\"\n"),
+        )
+        .expect("write gleam.toml");
+        let _ = git(&["add", "gleam.toml"]);
+        let _ = git(&["commit", "--quiet", "-m", version]);
+        git(&["rev-parse", "HEAD"]).trim().to_string()
+    };
+    fs::mkdir(&repo).expect("repo directory");
+    let _ = git(&["init", "--quiet"]);
+    let first = commit_version("0.1.0");
+    let second = commit_version("0.2.0");
+    let paths = ProjectPaths::new(root.join("project"));
+
+    let provide = |git_commits: &HashMap<EcoString, EcoString>| {
+        let mut provided = HashMap::new();
+        let version = provide_git_package(
+            &ProjectIO::new(),
+            "wibble".into(),
+            repo.as_str(),
+            None,
+            &paths,
+            &mut provided,
+            &mut vec![],
+            git_commits,
+        )
+        .expect("provide git package");
+        let source = provided.remove("wibble").expect("provided").source;
+        (version, source)
+    };
+
+    // Pinned to the first commit
+    let pinned = [("wibble".into(), first.as_str().into())].into();
+    assert_eq!(
+        provide(&pinned),
+        (
+            hexpm::version::Range::new("== 0.1.0".into()),
+            ProvidedPackageSource::Git {
+                repo: repo.as_str().into(),
+                commit: first.as_str().into(),
+            }
+        )
+    );
+
+    // Not pinned, so the latest commit
+    assert_eq!(
+        provide(&HashMap::new()),
+        (
+            hexpm::version::Range::new("== 0.2.0".into()),
+            ProvidedPackageSource::Git {
+                repo: repo.as_str().into(),
+                commit: second.as_str().into(),
+            }
+        )
+    );
+}
+
 /// Determine the information to add to the manifest for a specific package
-async fn lookup_package(
+async fn lookup_package<Http: gleam_core::io::HttpClient>(
     name: String,
     version: Version,
     provided: &HashMap<EcoString, ProvidedPackage>,
+    fetcher: &PackageFetcher<Http>,
 ) -> Result<ManifestPackage> {
     match provided.get(name.as_str()) {
         Some(provided_package) => Ok(provided_package.to_manifest_package(name.as_str())),
         None => {
-            let config = hexpm::Config::new();
-            let release =
-                hex::get_package_release(&name, &version, &config, &HttpClient::new()).await?;
+            let release = hex::get_package_release(
+                &name,
+                &version,
+                fetcher.api_key.as_deref(),
+                &fetcher.config,
+                &fetcher.http,
+            )
+            .await?;
             let build_tools = release
                 .meta
                 .build_tools
@@ -1008,22 +1961,55 @@ async fn lookup_package(
                 requirements,
                 source: ManifestPackageSource::Hex {
                     outer_checksum: Base16Checksum(release.outer_checksum),
+                    organization: fetcher.organization.clone(),
                 },
             })
         }
     }
 }
 
-struct PackageFetcher {
+/// The result of looking up a package on Hex, once it has finished.
+type PackageLookup = OnceLock<Result<hexpm::Package, String>>;
+
+#[derive(Debug, Clone)]
+struct PackageFetcher<Http> {
     runtime: tokio::runtime::Handle,
-    http: HttpClient,
+    http: Http,
+    /// The Hex organisation packages are looked up from, if not public Hex.
+    organization: Option<EcoString>,
+    config: hexpm::Config,
+    api_key: Option<String>,
+    /// The lookup of each package asked for, so that a package asked for
+    /// again while it is still being looked up, or after, is only requested
+    /// from Hex once.
+    lookups: Arc<Mutex<HashMap<String, Arc<PackageLookup>>>>,
 }
 
-impl PackageFetcher {
-    pub fn boxed(runtime: tokio::runtime::Handle) -> Box<Self> {
-        Box::new(Self {
+impl<Http> PackageFetcher<Http> {
+    pub fn new(runtime: tokio::runtime::Handle, http: Http) -> Self {
+        Self {
             runtime,
-            http: HttpClient::new(),
+            http,
+            organization: None,
+            config: hexpm::Config::new(),
+            api_key: None,
+            lookups: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// A fetcher that looks packages up from the repository of a Hex
+    /// organisation, using the given API key.
+    pub fn with_organization(&self, organization: &str, api_key: String) -> Result<Self>
+    where
+        Http: Clone,
+    {
+        Ok(Self {
+            runtime: self.runtime.clone(),
+            http: self.http.clone(),
+            organization: Some(organization.into()),
+            config: hex::organization_config(&self.config, organization)?,
+            api_key: Some(api_key),
+            lookups: Arc::new(Mutex::new(HashMap::new())),
         })
     }
 }
@@ -1054,19 +2040,33 @@ impl TarUnpacker for Untar {
     }
 }
 
-impl dependency::PackageFetcher for PackageFetcher {
+impl<Http: gleam_core::io::HttpClient> dependency::PackageFetcher for PackageFetcher<Http> {
     fn get_dependencies(
         &self,
         package: &str,
     ) -> Result<hexpm::Package, Box<dyn std::error::Error>> {
-        tracing::debug!(package = package, "looking_up_hex_package");
-        let config = hexpm::Config::new();
-        let request = hexpm::get_package_request(package, None, &config);
-        let response = self
-            .runtime
-            .block_on(self.http.send(request))
-            .map_err(Box::new)?;
-        hexpm::get_package_response(response, HEXPM_PUBLIC_KEY).map_err(|e| e.into())
+        let lookup = self
+            .lookups
+            .lock()
+            .expect("Package lookups lock")
+            .entry(package.to_string())
+            .or_default()
+            .clone();
+        // Only the first caller looks the package up, anyone else asking for
+        // it waits for that lookup to finish and shares its result.
+        lookup
+            .get_or_init(|| {
+                tracing::debug!(package = package, "looking_up_hex_package");
+                let request =
+                    hexpm::get_package_request(package, self.api_key.as_deref(), &self.config);
+                let response = self
+                    .runtime
+                    .block_on(self.http.send(request))
+                    .map_err(|e| e.to_string())?;
+                hexpm::get_package_response(response, HEXPM_PUBLIC_KEY).map_err(|e| e.to_string())
+            })
+            .clone()
+            .map_err(|e| e.into())
     }
 }
 
@@ -1279,20 +2279,126 @@ fn verified_requirements_equality_with_canonicalized_paths() {
 
     let requirements1 = HashMap::from([(
         EcoString::from("dep1"),
-        Requirement::Path {
-            path: Utf8PathBuf::from(canonical_path.to_str().expect("Path should be valid UTF-8")),
-        },
+        Requirement::path(canonical_path.to_str().expect("Path should be valid UTF-8")),
     )]);
 
     let requirements2 = HashMap::from([(
         EcoString::from("dep1"),
-        Requirement::Path {
-            path: Utf8PathBuf::from(relative_path.to_string()),
-        },
+        Requirement::path(relative_path.as_str()),
     )]);
 
-    assert!(
-        is_same_requirements(&requirements1, &requirements2, &temp_path)
-            .expect("Requirements should be the same")
+    assert!(is_same_requirements(
+        &ProjectIO::new(),
+        &requirements1,
+        &requirements2,
+        &temp_path
+    )
+    .expect("Requirements should be the same"));
+}
+
+#[test]
+fn package_fetcher_looks_up_each_package_once() {
+    use dependency::PackageFetcher as _;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[derive(Debug, Default)]
+    struct CountingHttp {
+        requests: AtomicUsize,
+    }
+
+    #[async_trait::async_trait]
+    impl gleam_core::io::HttpClient for CountingHttp {
+        async fn send(&self, _request: http::Request<Vec<u8>>) -> Result<http::Response<Vec<u8>>> {
+            let _ = self.requests.fetch_add(1, Ordering::SeqCst);
+            // Stay in flight long enough for the other lookup to ask too
+            std::thread::sleep(std::time::Duration::from_millis(50));
+            Ok(http::Response::builder()
+                .status(404)
+                .body(vec![])
+                .expect("response"))
+        }
+
+        async fn send_streaming(
+            &self,
+            _request: http::Request<Vec<u8>>,
+            _on_chunk: &mut (dyn for<'a> FnMut(&'a http::response::Parts, &'a [u8]) -> Result<()>
+                      + Send),
+        ) -> Result<http::response::Parts> {
+            unreachable!("packages are not downloaded")
+        }
+    }
+
+    let runtime = tokio::runtime::Runtime::new().expect("runtime");
+    let fetcher = PackageFetcher::new(runtime.handle().clone(), CountingHttp::default());
+    let errors: Vec<_> = std::thread::scope(|scope| {
+        let lookups: Vec<_> = (0..2)
+            .map(|_| {
+                scope.spawn(|| {
+                    fetcher
+                        .get_dependencies("wibble")
+                        .map_err(|e| e.to_string())
+                })
+            })
+            .collect();
+        lookups
+            .into_iter()
+            .map(|lookup| lookup.join().expect("lookup").expect_err("not found"))
+            .collect()
+    });
+    assert_eq!(errors.first(), errors.get(1));
+    assert!(fetcher.get_dependencies("wibble").is_err());
+    assert_eq!(fetcher.http.requests.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn organization_package_fetcher_uses_organization_repository() {
+    use dependency::PackageFetcher as _;
+
+    /// The URI and `Authorization` header of a request.
+    type Sent = (String, Option<String>);
+
+    #[derive(Debug, Clone, Default)]
+    struct RecordingHttp {
+        requests: Arc<Mutex<Vec<Sent>>>,
+    }
+
+    #[async_trait::async_trait]
+    impl gleam_core::io::HttpClient for RecordingHttp {
+        async fn send(&self, request: http::Request<Vec<u8>>) -> Result<http::Response<Vec<u8>>> {
+            let authorization = request
+                .headers()
+                .get(http::header::AUTHORIZATION)
+                .map(|value| value.to_str().expect("authorization").to_string());
+            self.requests
+                .lock()
+                .expect("requests")
+                .push((request.uri().to_string(), authorization));
+            Ok(http::Response::builder()
+                .status(404)
+                .body(vec![])
+                .expect("response"))
+        }
+
+        async fn send_streaming(
+            &self,
+            _request: http::Request<Vec<u8>>,
+            _on_chunk: &mut (dyn for<'a> FnMut(&'a http::response::Parts, &'a [u8]) -> Result<()>
+                      + Send),
+        ) -> Result<http::response::Parts> {
+            unreachable!("packages are not downloaded")
+        }
+    }
+
+    let runtime = tokio::runtime::Runtime::new().expect("runtime");
+    let fetcher = PackageFetcher::new(runtime.handle().clone(), RecordingHttp::default())
+        .with_organization("acme", "key".into())
+        .expect("organization fetcher");
+    assert!(fetcher.get_dependencies("anvil").is_err());
+    assert_eq!(
+        *fetcher.http.requests.lock().expect("requests"),
+        vec![(
+            "https://repo.hex.pm/repos/acme/packages/anvil".into(),
+            Some("key".into())
+        )]
     );
 }
@@ -0,0 +1,498 @@
+//! `gleam deps update --interactive`, which lists the newer versions of the
+//! locked dependencies, grouped by how much they change, and updates the ones
+//! picked. A direct dependency whose requirement in `gleam.toml` doesn't allow
+//! the newer version has its requirement changed to one that does.
+
+use std::collections::BTreeSet;
+
+use ecow::EcoString;
+use gleam_core::{
+    config::PackageConfig,
+    dependency,
+    io::FileSystemReader,
+    manifest::{Manifest, ManifestPackageSource},
+    requirement::Requirement,
+    Result,
+};
+use hexpm::version::Version;
+use itertools::Itertools;
+
+use crate::{cli, fs, fs::ProjectIO, http::HttpClient};
+
+use super::{read_manifest_from_disc, PackageFetcher};
+
+/// How much a newer version of a package changes from the locked one,
+/// according to semantic versioning.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Impact {
+    /// A new major version, or a new minor version of a package that is
+    /// before version 1.0.0, which may break the project.
+    Major,
+    Minor,
+    Patch,
+}
+
+impl Impact {
+    fn of(current: &Version, latest: &Version) -> Self {
+        if current.major != latest.major || (current.major == 0 && current.minor != latest.minor) {
+            Self::Major
+        } else if current.minor != latest.minor {
+            Self::Minor
+        } else {
+            Self::Patch
+        }
+    }
+
+    fn heading(&self) -> &'static str {
+        match self {
+            Self::Major => "Major updates, which may break the project:",
+            Self::Minor => "Minor updates:",
+            Self::Patch => "Patch updates:",
+        }
+    }
+}
+
+/// A newer version of a locked package.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct AvailableUpdate {
+    package: EcoString,
+    current: Version,
+    latest: Version,
+    impact: Impact,
+    /// Whether the project depends on the package itself, rather than only
+    /// through other packages.
+    direct: bool,
+}
+
+impl AvailableUpdate {
+    /// Where the changes between the two versions can be read, including
+    /// those to the changelog of the package.
+    fn diff_url(&self) -> String {
+        format!(
+            "https://diff.hex.pm/diff/{}/{}..{}",
+            self.package, self.current, self.latest
+        )
+    }
+}
+
+pub fn update_interactively() -> Result<()> {
+    let paths = crate::find_project_paths()?;
+    let io = ProjectIO::new();
+    if !io.is_file(&paths.manifest()) {
+        // Nothing is locked, so everything is updated anyway
+        return super::update(vec![]);
+    }
+    let config = crate::config::read(paths.root_config())?;
+    let manifest = read_manifest_from_disc(&io, &paths)?;
+
+    let runtime = tokio::runtime::Runtime::new().expect("Unable to start Tokio async runtime");
+    let fetcher = PackageFetcher::new(runtime.handle().clone(), HttpClient::new());
+    let updates = available_updates(&fetcher, &config, &manifest);
+    if updates.is_empty() {
+        println!("All dependencies are up to date.");
+        return Ok(());
+    }
+    print!("{}", updates_list(&updates));
+
+    let selected = loop {
+        let answer = cli::ask(
+            "\nUpdates to apply, as numbers separated by spaces, `all`, or nothing to cancel",
+        )?;
+        match parse_selection(&answer, updates.len()) {
+            Ok(selected) => break selected,
+            Err(problem) => println!("{problem}"),
+        }
+    };
+    if selected.is_empty() {
+        println!("No dependencies updated.");
+        return Ok(());
+    }
+    let selected = selected
+        .into_iter()
+        .filter_map(|number| updates.get(number - 1))
+        .collect_vec();
+
+    widen_requirements(&paths.root_config(), &config, &selected)?;
+    super::update(
+        selected
+            .iter()
+            .map(|update| update.package.to_string())
+            .collect(),
+    )?;
+
+    // The other requirements on a package may not allow its newest version
+    let manifest = read_manifest_from_disc(&io, &paths)?;
+    for update in selected {
+        let Some(package) = manifest
+            .packages
+            .iter()
+            .find(|package| package.name == update.package)
+        else {
+            continue;
+        };
+        if package.version == update.latest {
+            continue;
+        }
+        println!(
+            "{} could only be updated to {}, as the other packages that depend on it do not allow {}.",
+            package.name, package.version, update.latest
+        );
+    }
+    Ok(())
+}
+
+/// The newest version of each locked public Hex package that is newer than
+/// the locked one, not retired, and not a pre-release, in the order they are
+/// listed in: grouped by impact, then by name. Packages that cannot be looked
+/// up are left out.
+fn available_updates(
+    fetcher: &impl dependency::PackageFetcher,
+    config: &PackageConfig,
+    manifest: &Manifest,
+) -> Vec<AvailableUpdate> {
+    let mut updates = vec![];
+    for package in &manifest.packages {
+        // Packages of Hex organisations need an API key to be looked up
+        let ManifestPackageSource::Hex {
+            organization: None, ..
+        } = &package.source
+        else {
+            continue;
+        };
+        let hex_package = match fetcher.get_dependencies(&package.name) {
+            Ok(hex_package) => hex_package,
+            Err(error) => {
+                tracing::warn!(
+                    package = package.name.as_str(),
+                    error = error.to_string(),
+                    "update_lookup_failed"
+                );
+                continue;
+            }
+        };
+        let latest = hex_package
+            .releases
+            .iter()
+            .filter(|release| !release.is_retired() && !release.version.is_pre())
+            .map(|release| &release.version)
+            .filter(|version| *version > &package.version)
+            .max();
+        let Some(latest) = latest else {
+            continue;
+        };
+        updates.push(AvailableUpdate {
+            package: package.name.clone(),
+            current: package.version.clone(),
+            latest: latest.clone(),
+            impact: Impact::of(&package.version, latest),
+            direct: config.dependencies.contains_key(&package.name)
+                || config.dev_dependencies.contains_key(&package.name),
+        });
+    }
+    updates.sort_by(|a, b| (a.impact, &a.package).cmp(&(b.impact, &b.package)));
+    updates
+}
+
+/// The updates, numbered from 1 under the heading of their impact.
+fn updates_list(updates: &[AvailableUpdate]) -> String {
+    let mut list = String::new();
+    let mut impact = None;
+    for (index, update) in updates.iter().enumerate() {
+        if impact != Some(update.impact) {
+            impact = Some(update.impact);
+            list.push_str(&format!("\n{}\n", update.impact.heading()));
+        }
+        let indirect = if update.direct { "" } else { " (indirect)" };
+        list.push_str(&format!(
+            "{number:>4}. {package} {current} -> {latest}{indirect}\n      {url}\n",
+            number = index + 1,
+            package = update.package,
+            current = update.current,
+            latest = update.latest,
+            url = update.diff_url(),
+        ));
+    }
+    list
+}
+
+/// The numbers of the updates picked, in order and without repeats. Numbers
+/// start from 1.
+fn parse_selection(answer: &str, count: usize) -> Result<Vec<usize>, String> {
+    let answer = answer.trim();
+    if answer == "all" {
+        return Ok((1..=count).collect());
+    }
+    let mut selected = BTreeSet::new();
+    for word in answer.split(|c: char| c.is_whitespace() || c == ',') {
+        if word.is_empty() {
+            continue;
+        }
+        match word.parse::<usize>() {
+            Ok(number) if (1..=count).contains(&number) => {
+                let _ = selected.insert(number);
+            }
+            _ => return Err(format!("`{word}` is not the number of an update.")),
+        }
+    }
+    Ok(selected.into_iter().collect())
+}
+
+/// Changes the requirement in `gleam.toml` on each directly depended upon
+/// package whose requirement doesn't allow the version it is updated to, so
+/// that it requires that version up to the next major version.
+fn widen_requirements(
+    config_path: &camino::Utf8Path,
+    config: &PackageConfig,
+    updates: &[&AvailableUpdate],
+) -> Result<()> {
+    let mut toml = crate::add::read_toml_edit(config_path.as_str())?;
+    let mut changed = false;
+    for update in updates {
+        let (table, requirement) = match (
+            config.dependencies.get(&update.package),
+            config.dev_dependencies.get(&update.package),
+        ) {
+            (Some(requirement), _) => ("dependencies", requirement),
+            (None, Some(requirement)) => ("dev-dependencies", requirement),
+            (None, None) => continue,
+        };
+        let Requirement::Hex { version, .. } = requirement else {
+            continue;
+        };
+        if version
+            .to_pubgrub()
+            .is_ok_and(|range| range.contains(&update.latest))
+        {
+            continue;
+        }
+        let range = crate::add::major_version_range(&update.latest);
+        // False positive. This package doesn't use the indexing API correctly.
+        #[allow(clippy::indexing_slicing)]
+        {
+            let item = &mut toml[table][update.package.as_str()];
+            match item.as_table_like_mut() {
+                Some(table) => {
+                    if let Some(version) = table.get_mut("version") {
+                        replace_value(version, &range);
+                    }
+                }
+                None => replace_value(item, &range),
+            }
+        }
+        cli::print_colourful_prefix("Changed", &format!("{} to {range}", update.package));
+        changed = true;
+    }
+    if changed {
+        fs::write(config_path, &toml.to_string())?;
+    }
+    Ok(())
+}
+
+/// Replaces a TOML value, keeping the whitespace and comments around it.
+fn replace_value(item: &mut toml_edit::Item, text: &str) {
+    let decor = item.as_value().map(|value| value.decor().clone());
+    *item = toml_edit::value(text);
+    if let (Some(decor), Some(value)) = (decor, item.as_value_mut()) {
+        *value.decor_mut() = decor;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, error::Error as StdError};
+
+    use gleam_core::manifest::{Base16Checksum, ManifestPackage};
+    use hexpm::Release;
+
+    use super::*;
+
+    struct Remote(HashMap<String, hexpm::Package>);
+
+    impl dependency::PackageFetcher for Remote {
+        fn get_dependencies(&self, package: &str) -> Result<hexpm::Package, Box<dyn StdError>> {
+            self.0
+                .get(package)
+                .cloned()
+                .ok_or(Box::new(hexpm::ApiError::NotFound))
+        }
+    }
+
+    fn version(text: &str) -> Version {
+        Version::parse(text).expect("version")
+    }
+
+    fn hex_package(name: &str, versions: &[&str]) -> (String, hexpm::Package) {
+        let releases = versions
+            .iter()
+            .map(|text| Release {
+                version: version(text),
+                requirements: Default::default(),
+                retirement_status: None,
+                outer_checksum: vec![1, 2, 3],
+                meta: (),
+            })
+            .collect();
+        let package = hexpm::Package {
+            name: name.into(),
+            repository: "hexpm".into(),
+            releases,
+        };
+        (name.into(), package)
+    }
+
+    fn locked(name: &str, text: &str) -> ManifestPackage {
+        ManifestPackage {
+            name: name.into(),
+            version: version(text),
+            build_tools: vec!["gleam".into()],
+            otp_app: None,
+            requirements: vec![],
+            source: ManifestPackageSource::Hex {
+                outer_checksum: Base16Checksum(vec![1, 2, 3]),
+                organization: None,
+            },
+        }
+    }
+
+    #[test]
+    fn impact_of_updates() {
+        assert_eq!(
+            Impact::of(&version("1.2.3"), &version("2.0.0")),
+            Impact::Major
+        );
+        assert_eq!(
+            Impact::of(&version("1.2.3"), &version("1.3.0")),
+            Impact::Minor
+        );
+        assert_eq!(
+            Impact::of(&version("1.2.3"), &version("1.2.4")),
+            Impact::Patch
+        );
+        assert_eq!(
+            Impact::of(&version("0.2.3"), &version("0.3.0")),
+            Impact::Major
+        );
+        assert_eq!(
+            Impact::of(&version("0.2.3"), &version("0.2.4")),
+            Impact::Patch
+        );
+    }
+
+    #[test]
+    fn available_updates_are_grouped_by_impact() {
+        let remote = Remote(
+            [
+                hex_package("gleam_json", &["1.0.0", "1.0.1", "1.1.0-rc1"]),
+                hex_package("gleam_stdlib", &["0.30.0", "0.31.0"]),
+                hex_package("lustre", &["4.0.0"]),
+            ]
+            .into(),
+        );
+        let config = PackageConfig {
+            dependencies: [("gleam_json".into(), Requirement::hex("~> 1.0"))].into(),
+            ..Default::default()
+        };
+        let manifest = Manifest {
+            requirements: Default::default(),
+            packages: vec![
+                locked("gleam_json", "1.0.0"),
+                locked("gleam_stdlib", "0.30.0"),
+                locked("lustre", "4.0.0"),
+                // Packages that cannot be looked up are left out
+                locked("wibble", "1.0.0"),
+            ],
+        };
+        let updates = available_updates(&remote, &config, &manifest);
+        assert_eq!(
+            updates,
+            vec![
+                AvailableUpdate {
+                    package: "gleam_stdlib".into(),
+                    current: version("0.30.0"),
+                    latest: version("0.31.0"),
+                    impact: Impact::Major,
+                    direct: false,
+                },
+                AvailableUpdate {
+                    package: "gleam_json".into(),
+                    current: version("1.0.0"),
+                    latest: version("1.0.1"),
+                    impact: Impact::Patch,
+                    direct: true,
+                },
+            ]
+        );
+        assert_eq!(
+            updates_list(&updates),
+            "
+Major updates, which may break the project:
+   1. gleam_stdlib 0.30.0 -> 0.31.0 (indirect)
+      https://diff.hex.pm/diff/gleam_stdlib/0.30.0..0.31.0
+
+Patch updates:
+   2. gleam_json 1.0.0 -> 1.0.1
+      https://diff.hex.pm/diff/gleam_json/1.0.0..1.0.1
+"
+        );
+    }
+
+    #[test]
+    fn selections() {
+        assert_eq!(parse_selection("", 3), Ok(vec![]));
+        assert_eq!(parse_selection("all", 3), Ok(vec![1, 2, 3]));
+        assert_eq!(parse_selection("3, 1 3", 3), Ok(vec![1, 3]));
+        assert_eq!(
+            parse_selection("1 4", 3),
+            Err("`4` is not the number of an update.".into())
+        );
+        assert_eq!(
+            parse_selection("one", 3),
+            Err("`one` is not the number of an update.".into())
+        );
+    }
+
+    #[test]
+    fn requirements_are_widened_to_allow_the_update() {
+        let directory = tempfile::tempdir().expect("temporary directory");
+        let path = camino::Utf8Path::from_path(directory.path())
+            .expect("utf8 path")
+            .join("gleam.toml");
+        let toml = r#"name = "wibble"
+
+[dependencies]
+gleam_json = "~> 1.0"
+gleam_stdlib = "~> 0.30.0" # The standard library
+
+[dev-dependencies]
+gleeunit = { version = "~> 1.0" }
+"#;
+        std::fs::write(&path, toml).expect("write gleam.toml");
+        let config = PackageConfig::read(&path, &ProjectIO::new()).expect("config");
+        let update = |package: &str, latest: &str| AvailableUpdate {
+            package: package.into(),
+            current: version("1.0.0"),
+            latest: version(latest),
+            impact: Impact::Major,
+            direct: true,
+        };
+        let updates = [
+            update("gleam_json", "1.2.0"),
+            update("gleam_stdlib", "0.31.0"),
+            update("gleeunit", "2.0.0"),
+        ];
+
+        widen_requirements(&path, &config, &updates.iter().collect_vec()).expect("widen");
+        assert_eq!(
+            std::fs::read_to_string(&path).expect("read gleam.toml"),
+            r#"name = "wibble"
+
+[dependencies]
+gleam_json = "~> 1.0"
+gleam_stdlib = ">= 0.31.0 and < 1.0.0" # The standard library
+
+[dev-dependencies]
+gleeunit = { version = ">= 2.0.0 and < 3.0.0" }
+"#
+        );
+    }
+}
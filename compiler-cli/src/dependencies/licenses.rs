@@ -0,0 +1,511 @@
+//! The licences of the packages in `manifest.toml`, listed by `gleam deps
+//! licenses` or bundled with `--bundle` into a NOTICE file with the text of
+//! each licence, for shipping alongside an application.
+//!
+//! The licences of Hex packages are read from the tarballs of the resolved
+//! versions in the package cache, so they match what is built. Git and path
+//! packages are read from their directories.
+
+use std::{collections::BTreeMap, io::Read};
+
+use camino::{Utf8Path, Utf8PathBuf};
+use ecow::EcoString;
+use flate2::read::GzDecoder;
+use gleam_core::{
+    io::FileSystemReader,
+    manifest::{Manifest, ManifestPackage, ManifestPackageSource},
+    paths::{self, ProjectPaths},
+    Error, Result,
+};
+use hexpm::version::Version;
+use itertools::Itertools;
+
+use crate::fs::ProjectIO;
+
+use super::read_manifest_from_disc;
+
+/// The heading of packages which do not declare a licence.
+const UNKNOWN_LICENCE: &str = "Unknown";
+
+const RULE: &str =
+    "================================================================================";
+
+pub fn licenses(bundle: bool) -> Result<()> {
+    let paths = crate::find_project_paths()?;
+    let io = ProjectIO::new();
+    let config = crate::config::read(paths.root_config())?;
+    let manifest = read_manifest_from_disc(&io, &paths)?;
+    let notices = read_notices(&io, &paths, &manifest)?;
+    match bundle {
+        true => print!("{}", notice_file(&config.name, &notices)),
+        false => print!("{}", licence_list(&notices)),
+    }
+    Ok(())
+}
+
+/// The licences of a package and the files of its source holding their text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct PackageNotice {
+    name: EcoString,
+    version: Version,
+    licences: Vec<String>,
+    /// The licence files at the top of the package, by file name.
+    texts: BTreeMap<String, String>,
+}
+
+impl PackageNotice {
+    /// The heading the package is grouped under.
+    fn licence(&self) -> String {
+        match self.licences.as_slice() {
+            [] => UNKNOWN_LICENCE.into(),
+            licences => licences.join(", "),
+        }
+    }
+}
+
+fn read_notices<IO: FileSystemReader>(
+    io: &IO,
+    paths: &ProjectPaths,
+    manifest: &Manifest,
+) -> Result<Vec<PackageNotice>> {
+    manifest
+        .packages
+        .iter()
+        .sorted_by(|a, b| a.name.cmp(&b.name))
+        .map(|package| match &package.source {
+            ManifestPackageSource::Hex { .. } => read_hex_notice(io, package),
+            ManifestPackageSource::Git { .. } => {
+                read_directory_notice(io, package, &paths.build_packages_package(&package.name))
+            }
+            ManifestPackageSource::Local { path } => {
+                read_directory_notice(io, package, &paths.root().join(path))
+            }
+        })
+        .collect()
+}
+
+/// Reads the licences from the metadata of the package's tarball, and their
+/// text from the source code within it.
+fn read_hex_notice<IO: FileSystemReader>(
+    io: &IO,
+    package: &ManifestPackage,
+) -> Result<PackageNotice> {
+    let version = package.version.to_string();
+    let path = paths::global_package_cache_package_tarball(&package.name, &version);
+    if !io.is_file(&path) {
+        return Err(Error::PackageNotDownloaded {
+            package: package.name.clone(),
+            version,
+        });
+    }
+    let tarball = io.read_bytes(&path)?;
+    let mut notice = PackageNotice {
+        name: package.name.clone(),
+        version: package.version.clone(),
+        licences: vec![],
+        texts: BTreeMap::new(),
+    };
+    let mut archive = tar::Archive::new(tarball.as_slice());
+    for entry in archive.entries().map_err(Error::expand_tar)? {
+        let entry = entry.map_err(Error::expand_tar)?;
+        let name = entry.path().map_err(Error::expand_tar)?.into_owned();
+        if name.as_os_str() == "metadata.config" {
+            notice.licences = metadata_licences(&read_entry(entry)?);
+        } else if name.as_os_str() == "contents.tar.gz" {
+            let mut contents = tar::Archive::new(GzDecoder::new(entry));
+            for file in contents.entries().map_err(Error::expand_tar)? {
+                let file = file.map_err(Error::expand_tar)?;
+                let name = file.path().map_err(Error::expand_tar)?.into_owned();
+                let Some(name) = name.to_str().filter(|name| is_licence_file(name)) else {
+                    continue;
+                };
+                let name = name.to_string();
+                let _ = notice.texts.insert(name, read_entry(file)?);
+            }
+        }
+    }
+    Ok(notice)
+}
+
+fn read_entry(mut entry: impl Read) -> Result<String> {
+    let mut bytes = vec![];
+    let _ = entry.read_to_end(&mut bytes).map_err(Error::expand_tar)?;
+    Ok(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+/// Reads the licences from the `gleam.toml` of a package checked out from git
+/// or used from a path, and their text from the files beside it.
+fn read_directory_notice<IO: FileSystemReader>(
+    io: &IO,
+    package: &ManifestPackage,
+    directory: &Utf8Path,
+) -> Result<PackageNotice> {
+    let config = directory.join("gleam.toml");
+    let licences = match io.is_file(&config) {
+        true => toml::from_str::<gleam_core::config::PackageConfig>(&io.read(&config)?)
+            .map(|config| {
+                config
+                    .licences
+                    .iter()
+                    .map(|licence| licence.to_string())
+                    .collect()
+            })
+            .unwrap_or_default(),
+        false => vec![],
+    };
+    let mut texts = BTreeMap::new();
+    if io.is_directory(directory) {
+        for entry in io.read_dir(directory)?.into_iter().flatten() {
+            let path: Utf8PathBuf = entry.into_path();
+            let Some(name) = path.file_name().filter(|name| is_licence_file(name)) else {
+                continue;
+            };
+            if io.is_file(&path) {
+                let _ = texts.insert(name.to_string(), io.read(&path)?);
+            }
+        }
+    }
+    Ok(PackageNotice {
+        name: package.name.clone(),
+        version: package.version.clone(),
+        licences,
+        texts,
+    })
+}
+
+/// Whether a path in a package is a file at its top that holds licence text,
+/// such as `LICENSE`, `LICENCE.md`, `COPYING`, or `NOTICE`.
+fn is_licence_file(path: &str) -> bool {
+    let name = path.to_uppercase();
+    !name.contains('/')
+        && ["LICENSE", "LICENCE", "COPYING", "NOTICE"]
+            .iter()
+            .any(|prefix| name.starts_with(prefix))
+}
+
+/// The licences in the `licenses` entry of the Erlang terms of a Hex
+/// package's `metadata.config`, such as
+/// `{<<"licenses">>, [<<"MIT">>, <<"Apache-2.0">>]}.`
+fn metadata_licences(metadata: &str) -> Vec<String> {
+    let Some((_, rest)) = metadata.split_once("<<\"licenses\">>") else {
+        return vec![];
+    };
+    let Some(list) = rest
+        .split_once('[')
+        .and_then(|(_, rest)| rest.split_once(']'))
+        .map(|(list, _)| list)
+    else {
+        return vec![];
+    };
+    list.split("<<\"")
+        .skip(1)
+        .filter_map(|licence| licence.split_once("\">>"))
+        .map(|(licence, _)| licence.to_string())
+        .collect()
+}
+
+/// The packages grouped by their licences.
+fn by_licence(notices: &[PackageNotice]) -> BTreeMap<String, Vec<&PackageNotice>> {
+    let mut groups: BTreeMap<String, Vec<&PackageNotice>> = BTreeMap::new();
+    for notice in notices {
+        groups.entry(notice.licence()).or_default().push(notice);
+    }
+    groups
+}
+
+fn licence_list(notices: &[PackageNotice]) -> String {
+    let mut text = String::new();
+    for (licence, notices) in by_licence(notices) {
+        text.push_str(&format!("{licence}\n"));
+        for notice in notices {
+            text.push_str(&format!("  {} v{}\n", notice.name, notice.version));
+        }
+    }
+    text
+}
+
+/// A NOTICE file naming the licence of each package and holding the text of
+/// its licence files, grouped by licence and then by package.
+fn notice_file(project: &str, notices: &[PackageNotice]) -> String {
+    let mut text = format!(
+        "Third-party notices for {project}\n\n\
+{project} includes the following packages, at the versions in manifest.toml.\n"
+    );
+    for (licence, notices) in by_licence(notices) {
+        text.push_str(&format!("\n{RULE}\n{licence}\n{RULE}\n"));
+        for notice in notices {
+            text.push_str(&format!("\n{} v{}\n", notice.name, notice.version));
+            if notice.texts.is_empty() {
+                text.push_str("\nNo licence text is included in the package.\n");
+            }
+            for (file, licence_text) in &notice.texts {
+                text.push_str(&format!(
+                    "\n--- {file} ---\n\n{}\n",
+                    licence_text.trim_end()
+                ));
+            }
+        }
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use gleam_core::{
+        io::{memory::InMemoryFileSystem, FileSystemWriter},
+        manifest::Base16Checksum,
+    };
+
+    use super::*;
+
+    fn package(name: &str, version: &str, source: ManifestPackageSource) -> ManifestPackage {
+        ManifestPackage {
+            name: name.into(),
+            version: Version::parse(version).expect("version"),
+            build_tools: vec!["gleam".into()],
+            otp_app: None,
+            requirements: vec![],
+            source,
+        }
+    }
+
+    fn hex_source() -> ManifestPackageSource {
+        ManifestPackageSource::Hex {
+            outer_checksum: Base16Checksum(vec![]),
+            organization: None,
+        }
+    }
+
+    fn tar(files: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut builder = tar::Builder::new(vec![]);
+        for (path, data) in files {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(data.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder
+                .append_data(&mut header, path, *data)
+                .expect("append to tarball");
+        }
+        builder.into_inner().expect("tarball")
+    }
+
+    fn hex_tarball(licences: &str, files: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut contents = flate2::write::GzEncoder::new(vec![], flate2::Compression::default());
+        std::io::Write::write_all(&mut contents, &tar(files)).expect("compress contents");
+        let contents = contents.finish().expect("contents");
+        let metadata =
+            format!("{{<<\"name\">>, <<\"wibble\">>}}.\n{{<<\"licenses\">>, [{licences}]}}.\n");
+        tar(&[
+            ("VERSION", b"3"),
+            ("metadata.config", metadata.as_bytes()),
+            ("contents.tar.gz", &contents),
+        ])
+    }
+
+    #[test]
+    fn licences_of_metadata() {
+        assert_eq!(
+            metadata_licences("{<<\"licenses\">>, [<<\"MIT\">>, <<\"Apache-2.0\">>]}."),
+            vec!["MIT".to_string(), "Apache-2.0".to_string()]
+        );
+        assert_eq!(
+            metadata_licences("{<<\"licenses\">>,[<<\"Apache 2.0\">>]}."),
+            vec!["Apache 2.0".to_string()]
+        );
+        assert_eq!(
+            metadata_licences("{<<\"licenses\">>, []}."),
+            Vec::<String>::new()
+        );
+        assert_eq!(
+            metadata_licences("{<<\"name\">>, <<\"wibble\">>}."),
+            Vec::<String>::new()
+        );
+    }
+
+    #[test]
+    fn licence_files() {
+        assert!(is_licence_file("LICENSE"));
+        assert!(is_licence_file("licence.md"));
+        assert!(is_licence_file("COPYING.txt"));
+        assert!(is_licence_file("NOTICE"));
+        assert!(!is_licence_file("src/LICENSE"));
+        assert!(!is_licence_file("README.md"));
+    }
+
+    #[test]
+    fn hex_package_notice_is_read_from_the_tarball_of_its_version() {
+        let io = InMemoryFileSystem::new();
+        let tarball = hex_tarball(
+            "<<\"MIT\">>",
+            &[
+                ("LICENSE", b"MIT licence text\n"),
+                ("src/wibble.gleam", b"pub fn main() { 1 }"),
+                ("src/LICENSE", b"not this one"),
+            ],
+        );
+        io.write_bytes(
+            &paths::global_package_cache_package_tarball("wibble", "1.2.0"),
+            &tarball,
+        )
+        .expect("write tarball");
+
+        let notice =
+            read_hex_notice(&io, &package("wibble", "1.2.0", hex_source())).expect("notice");
+        assert_eq!(
+            notice,
+            PackageNotice {
+                name: "wibble".into(),
+                version: Version::new(1, 2, 0),
+                licences: vec!["MIT".into()],
+                texts: [("LICENSE".into(), "MIT licence text\n".into())].into(),
+            }
+        );
+    }
+
+    #[test]
+    fn hex_package_not_downloaded() {
+        let io = InMemoryFileSystem::new();
+        let result = read_hex_notice(&io, &package("wibble", "1.2.0", hex_source()));
+        assert_eq!(
+            result,
+            Err(Error::PackageNotDownloaded {
+                package: "wibble".into(),
+                version: "1.2.0".into(),
+            })
+        );
+    }
+
+    #[test]
+    fn path_package_notice_is_read_from_its_directory() {
+        let io = InMemoryFileSystem::new();
+        let paths = ProjectPaths::new("/app".into());
+        io.write(
+            Utf8Path::new("/app/wobble/gleam.toml"),
+            "name = \"wobble\"\nversion = \"0.1.0\"\nlicences = [\"Apache-2.0\"]\n",
+        )
+        .expect("write config");
+        io.write(Utf8Path::new("/app/wobble/LICENCE"), "Apache licence text")
+            .expect("write licence");
+        io.write(Utf8Path::new("/app/wobble/README.md"), "# wobble")
+            .expect("write readme");
+        let manifest = Manifest {
+            requirements: Default::default(),
+            packages: vec![package(
+                "wobble",
+                "0.1.0",
+                ManifestPackageSource::Local {
+                    path: "wobble".into(),
+                },
+            )],
+        };
+
+        let notices = read_notices(&io, &paths, &manifest).expect("notices");
+        assert_eq!(
+            notices,
+            vec![PackageNotice {
+                name: "wobble".into(),
+                version: Version::new(0, 1, 0),
+                licences: vec!["Apache-2.0".into()],
+                texts: [("LICENCE".into(), "Apache licence text".into())].into(),
+            }]
+        );
+    }
+
+    fn notices() -> Vec<PackageNotice> {
+        vec![
+            PackageNotice {
+                name: "gleam_stdlib".into(),
+                version: Version::new(0, 38, 0),
+                licences: vec!["Apache-2.0".into()],
+                texts: [("LICENCE".into(), "Apache licence text\n".into())].into(),
+            },
+            PackageNotice {
+                name: "lustre".into(),
+                version: Version::new(4, 2, 1),
+                licences: vec!["MIT".into()],
+                texts: [
+                    ("LICENSE".into(), "MIT licence text".into()),
+                    ("NOTICE".into(), "Lustre notice".into()),
+                ]
+                .into(),
+            },
+            PackageNotice {
+                name: "thoas".into(),
+                version: Version::new(1, 0, 0),
+                licences: vec![],
+                texts: BTreeMap::new(),
+            },
+            PackageNotice {
+                name: "wibble".into(),
+                version: Version::new(1, 2, 0),
+                licences: vec!["Apache-2.0".into()],
+                texts: BTreeMap::new(),
+            },
+        ]
+    }
+
+    #[test]
+    fn licence_list_is_grouped_by_licence() {
+        assert_eq!(
+            licence_list(&notices()),
+            "Apache-2.0
+  gleam_stdlib v0.38.0
+  wibble v1.2.0
+MIT
+  lustre v4.2.1
+Unknown
+  thoas v1.0.0
+"
+        );
+    }
+
+    #[test]
+    fn notice_file_is_grouped_by_licence_and_package() {
+        assert_eq!(
+            notice_file("app", &notices()),
+            format!(
+                "Third-party notices for app
+
+app includes the following packages, at the versions in manifest.toml.
+
+{RULE}
+Apache-2.0
+{RULE}
+
+gleam_stdlib v0.38.0
+
+--- LICENCE ---
+
+Apache licence text
+
+wibble v1.2.0
+
+No licence text is included in the package.
+
+{RULE}
+MIT
+{RULE}
+
+lustre v4.2.1
+
+--- LICENSE ---
+
+MIT licence text
+
+--- NOTICE ---
+
+Lustre notice
+
+{RULE}
+Unknown
+{RULE}
+
+thoas v1.0.0
+
+No licence text is included in the package.
+"
+            )
+        );
+    }
+}
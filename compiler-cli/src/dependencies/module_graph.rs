@@ -0,0 +1,30 @@
+//! The graph of the imports between the project's own modules, printed by
+//! `gleam deps module-graph` for architecture reviews.
+
+use gleam_core::{build::Origin, module_graph::ModuleGraph, Result};
+
+use crate::ModuleGraphFormat;
+
+pub fn module_graph(format: ModuleGraphFormat) -> Result<()> {
+    let paths = crate::find_project_paths()?;
+    let mut graph = ModuleGraph::new();
+
+    for (origin, directory) in [
+        (Origin::Src, paths.src_directory()),
+        (Origin::Test, paths.test_directory()),
+    ] {
+        for path in crate::fs::gleam_files_excluding_gitignore(&directory) {
+            let Some(name) = paths.module_name(&path) else {
+                continue;
+            };
+            let src = crate::fs::read(&path)?;
+            graph.insert(name.into(), origin, &path, &src.into())?;
+        }
+    }
+
+    match format {
+        ModuleGraphFormat::Dot => print!("{}", graph.to_dot()),
+        ModuleGraphFormat::Json => println!("{}", graph.to_json()),
+    }
+    Ok(())
+}
@@ -0,0 +1,187 @@
+//! Checks for versions of Hex packages locked in `manifest.toml` that have
+//! been retired since they were locked. Resolution keeps a locked version
+//! even once it is retired, so without this check nothing would mention the
+//! retirement until the package was next updated.
+
+use gleam_core::{
+    dependency,
+    manifest::{Manifest, ManifestPackageSource},
+    requirement::Requirement,
+    warning::WarningEmitterIO,
+    Warning,
+};
+
+use crate::{fs::ConsoleWarningEmitter, http::HttpClient};
+
+use super::PackageFetcher;
+
+/// Warns about each locked version of a public Hex package that has been
+/// retired. Packages that cannot be looked up, such as when offline, are
+/// skipped.
+pub fn warn_about_retired_packages(manifest: &Manifest) {
+    let runtime = tokio::runtime::Runtime::new().expect("Unable to start Tokio async runtime");
+    let fetcher = PackageFetcher::new(runtime.handle().clone(), HttpClient::new());
+    for warning in retired_package_warnings(&fetcher, manifest) {
+        ConsoleWarningEmitter.emit_warning(warning);
+    }
+}
+
+fn retired_package_warnings(
+    fetcher: &impl dependency::PackageFetcher,
+    manifest: &Manifest,
+) -> Vec<Warning> {
+    let mut warnings = vec![];
+    for package in &manifest.packages {
+        // Packages of Hex organisations need an API key to be looked up
+        let ManifestPackageSource::Hex {
+            organization: None, ..
+        } = &package.source
+        else {
+            continue;
+        };
+        let hex_package = match fetcher.get_dependencies(&package.name) {
+            Ok(hex_package) => hex_package,
+            Err(error) => {
+                tracing::debug!(
+                    package = package.name.as_str(),
+                    error = error.to_string(),
+                    "retirement_lookup_failed"
+                );
+                continue;
+            }
+        };
+        let Some(retirement) = hex_package
+            .releases
+            .iter()
+            .find(|release| release.version == package.version)
+            .and_then(|release| release.retirement_status.as_ref())
+        else {
+            continue;
+        };
+        let requirement = match manifest.requirements.get(&package.name) {
+            Some(Requirement::Hex { version, .. }) => Some(version),
+            _ => None,
+        };
+        let replacement =
+            dependency::newest_compatible_release(&hex_package, &package.version, requirement);
+        let reason = match retirement.message.trim() {
+            "" => retirement.reason.to_str().to_string(),
+            message => format!("{}: {message}", retirement.reason.to_str()),
+        };
+        warnings.push(Warning::RetiredDependency {
+            package: package.name.clone(),
+            version: package.version.to_string(),
+            reason,
+            replacement: replacement.map(|version| version.to_string()),
+        });
+    }
+    warnings
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, error::Error as StdError};
+
+    use gleam_core::manifest::{Base16Checksum, ManifestPackage};
+    use hexpm::{
+        version::{Range, Version},
+        Release, RetirementReason, RetirementStatus,
+    };
+
+    use super::*;
+
+    struct Remote(HashMap<String, hexpm::Package>);
+
+    impl dependency::PackageFetcher for Remote {
+        fn get_dependencies(&self, package: &str) -> Result<hexpm::Package, Box<dyn StdError>> {
+            self.0
+                .get(package)
+                .cloned()
+                .ok_or(Box::new(hexpm::ApiError::NotFound))
+        }
+    }
+
+    fn release(version: &str, retirement_status: Option<RetirementStatus>) -> Release<()> {
+        Release {
+            version: Version::parse(version).expect("version"),
+            requirements: Default::default(),
+            retirement_status,
+            outer_checksum: vec![1, 2, 3],
+            meta: (),
+        }
+    }
+
+    fn locked(name: &str, version: &str, organization: Option<&str>) -> ManifestPackage {
+        ManifestPackage {
+            name: name.into(),
+            version: Version::parse(version).expect("version"),
+            build_tools: vec!["gleam".into()],
+            otp_app: None,
+            requirements: vec![],
+            source: ManifestPackageSource::Hex {
+                outer_checksum: Base16Checksum(vec![1, 2, 3]),
+                organization: organization.map(Into::into),
+            },
+        }
+    }
+
+    fn remote() -> Remote {
+        let security = RetirementStatus {
+            reason: RetirementReason::Security,
+            message: "Leaks secrets".into(),
+        };
+        let package = hexpm::Package {
+            name: "gleam_json".into(),
+            repository: "hexpm".into(),
+            releases: vec![
+                release("1.0.0", Some(security)),
+                release("1.0.1", None),
+                release("1.1.0", None),
+                release("2.0.0", None),
+            ],
+        };
+        Remote([("gleam_json".into(), package)].into())
+    }
+
+    #[test]
+    fn retired_locked_versions_are_warned_about() {
+        let manifest = Manifest {
+            requirements: [(
+                "gleam_json".into(),
+                Requirement::Hex {
+                    version: Range::new("~> 1.0.0".into()),
+                    organization: None,
+                    features: vec![],
+                },
+            )]
+            .into(),
+            packages: vec![
+                locked("gleam_json", "1.0.0", None),
+                // Packages that cannot be looked up are skipped
+                locked("gleam_stdlib", "1.0.0", None),
+            ],
+        };
+        assert_eq!(
+            retired_package_warnings(&remote(), &manifest),
+            vec![Warning::RetiredDependency {
+                package: "gleam_json".into(),
+                version: "1.0.0".into(),
+                reason: "security: Leaks secrets".into(),
+                replacement: Some("1.0.1".into()),
+            }]
+        );
+    }
+
+    #[test]
+    fn versions_that_are_not_retired_are_not_warned_about() {
+        let manifest = Manifest {
+            requirements: Default::default(),
+            packages: vec![
+                locked("gleam_json", "1.0.1", None),
+                // Organisation packages are not looked up
+                locked("gleam_json", "1.0.0", Some("wibble")),
+            ],
+        };
+        assert_eq!(retired_package_warnings(&remote(), &manifest), vec![]);
+    }
+}
@@ -0,0 +1,558 @@
+//! Checks of the dependencies recorded in `manifest.toml` against the package
+//! cache, the build directory, `gleam.toml`, and git, for `gleam deps verify`.
+//! Nothing is downloaded or built, so the checks can be run in release
+//! pipelines to catch dependencies that have drifted or been tampered with.
+
+use std::collections::{BTreeMap, HashSet};
+
+use ecow::EcoString;
+use gleam_core::{
+    config::PackageConfig,
+    hex::integrity::{self, Integrity},
+    io::FileSystemReader,
+    manifest::{Base16Checksum, Manifest, ManifestPackage, ManifestPackageSource},
+    paths::{self, ProjectPaths},
+    requirement::Requirement,
+    Error, Result,
+};
+use itertools::Itertools;
+use sha2::{Digest, Sha256};
+
+use crate::{
+    fs::{self, ProjectIO},
+    provenance::Attestation,
+};
+
+use super::{read_manifest_from_disc, same_requirements};
+
+pub fn verify(provenance: bool) -> Result<()> {
+    let paths = crate::find_project_paths()?;
+    let io = ProjectIO::new();
+    let config = crate::config::read(paths.root_config())?;
+    let manifest = read_manifest_from_disc(&io, &paths)?;
+
+    let mut report = Report::check(&io, &paths, &config, &manifest)?;
+    report.check_git_pins(&paths, &manifest)?;
+    if provenance {
+        report.check_provenance(&io, &paths, &config, &manifest)?;
+    }
+    print!("{}", report.to_text());
+
+    match report.problems() {
+        0 => Ok(()),
+        problems => Err(Error::DependencyVerificationFailed { problems }),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Category {
+    /// Tarballs in the package cache match the checksums in the manifest.
+    Tarballs,
+    /// Packages in the build directory are unchanged since they were
+    /// extracted or checked out.
+    Sources,
+    /// The manifest still satisfies the requirements in `gleam.toml`.
+    Requirements,
+    /// The commits git dependencies are pinned to can still be fetched.
+    GitPins,
+    /// The provenance attestations of Hex packages that have one are signed
+    /// and match the package.
+    Provenance,
+}
+
+impl Category {
+    fn title(&self) -> &'static str {
+        match self {
+            Self::Tarballs => "Tarball checksums",
+            Self::Sources => "Package sources",
+            Self::Requirements => "Requirements",
+            Self::GitPins => "Git pins",
+            Self::Provenance => "Provenance attestations",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Problem {
+    package: EcoString,
+    text: String,
+}
+
+#[derive(Debug, Default)]
+struct Report {
+    checked: BTreeMap<Category, usize>,
+    problems: BTreeMap<Category, Vec<Problem>>,
+}
+
+impl Report {
+    /// Runs every check that can be made without the network.
+    fn check<IO: FileSystemReader>(
+        io: &IO,
+        paths: &ProjectPaths,
+        config: &PackageConfig,
+        manifest: &Manifest,
+    ) -> Result<Self> {
+        let mut report = Self::default();
+        for package in manifest
+            .packages
+            .iter()
+            .sorted_by(|a, b| a.name.cmp(&b.name))
+        {
+            match &package.source {
+                ManifestPackageSource::Hex { outer_checksum, .. } => {
+                    report.check_tarball(io, package, outer_checksum)?;
+                    report.check_extracted_sources(io, paths, package)?;
+                }
+                ManifestPackageSource::Git { commit, .. } => {
+                    report.check_checked_out_sources(paths, package, commit)?;
+                }
+                ManifestPackageSource::Local { .. } => (),
+            }
+        }
+        report.check_requirements(io, paths, config, manifest)?;
+        Ok(report)
+    }
+
+    fn problems(&self) -> usize {
+        self.problems.values().map(Vec::len).sum()
+    }
+
+    fn record(&mut self, category: Category, problem: Option<(&EcoString, String)>) {
+        *self.checked.entry(category).or_default() += 1;
+        if let Some((package, text)) = problem {
+            self.problems.entry(category).or_default().push(Problem {
+                package: package.clone(),
+                text,
+            });
+        }
+    }
+
+    fn check_tarball<IO: FileSystemReader>(
+        &mut self,
+        io: &IO,
+        package: &ManifestPackage,
+        outer_checksum: &Base16Checksum,
+    ) -> Result<()> {
+        let path = paths::global_package_cache_package_tarball(
+            &package.name,
+            &package.version.to_string(),
+        );
+        let problem = if !io.is_file(&path) {
+            Some(format!(
+                "the tarball for v{} is not in the package cache",
+                package.version
+            ))
+        } else {
+            let checksum = Sha256::digest(io.read_bytes(&path)?);
+            (checksum.as_slice() != outer_checksum.0.as_slice()).then(|| {
+                format!(
+                    "the tarball for v{} has checksum {} but manifest.toml has {}",
+                    package.version,
+                    base16::encode_upper(&checksum),
+                    outer_checksum.to_string(),
+                )
+            })
+        };
+        self.record(
+            Category::Tarballs,
+            problem.map(|text| (&package.name, text)),
+        );
+        Ok(())
+    }
+
+    fn check_extracted_sources<IO: FileSystemReader>(
+        &mut self,
+        io: &IO,
+        paths: &ProjectPaths,
+        package: &ManifestPackage,
+    ) -> Result<()> {
+        let directory = paths.build_packages_package_source(package);
+        let problem = match integrity::check(io, &directory)? {
+            Integrity::Intact => None,
+            Integrity::Missing => Some("it has not been downloaded".into()),
+            Integrity::Modified { files } => Some(format!(
+                "these files have been changed: {}",
+                files.iter().join(", ")
+            )),
+        };
+        self.record(Category::Sources, problem.map(|text| (&package.name, text)));
+        Ok(())
+    }
+
+    fn check_checked_out_sources(
+        &mut self,
+        paths: &ProjectPaths,
+        package: &ManifestPackage,
+        commit: &EcoString,
+    ) -> Result<()> {
+        let directory = paths.build_packages_package(&package.name);
+        let problem = match fs::git_head(&directory)? {
+            Some(head) if head == commit.as_str() => None,
+            Some(head) => Some(format!(
+                "commit {head} is checked out but manifest.toml pins {commit}"
+            )),
+            None => Some("it has not been checked out".into()),
+        };
+        self.record(Category::Sources, problem.map(|text| (&package.name, text)));
+        Ok(())
+    }
+
+    fn check_requirements<IO: FileSystemReader>(
+        &mut self,
+        io: &IO,
+        paths: &ProjectPaths,
+        config: &PackageConfig,
+        manifest: &Manifest,
+    ) -> Result<()> {
+        let requirements = config.all_dependencies()?;
+        let packages: BTreeMap<_, _> = manifest
+            .packages
+            .iter()
+            .map(|package| (&package.name, package))
+            .collect();
+
+        for (name, requirement) in requirements.iter().sorted_by_key(|(name, _)| *name) {
+            let recorded = manifest.requirements.get(name);
+            let problem = if !same_requirements(io, requirement, recorded, paths.root())? {
+                Some(
+                    "its requirement in gleam.toml has changed since manifest.toml was written"
+                        .into(),
+                )
+            } else {
+                match packages.get(name) {
+                    None => Some("it is required by gleam.toml but not in manifest.toml".into()),
+                    Some(package) => requirement_problem(requirement, package),
+                }
+            };
+            self.record(Category::Requirements, problem.map(|text| (name, text)));
+        }
+
+        for name in manifest.requirements.keys().sorted() {
+            if !requirements.contains_key(name) {
+                let text = "it is in manifest.toml but no longer required by gleam.toml";
+                self.record(Category::Requirements, Some((name, text.into())));
+            }
+        }
+
+        // Every package the manifest lists as a dependency of another must be
+        // in the manifest too.
+        let mut missing = HashSet::new();
+        for package in packages.values() {
+            for dependency in &package.requirements {
+                if !packages.contains_key(dependency) && missing.insert(dependency) {
+                    let text = format!(
+                        "it is required by {} but not in manifest.toml",
+                        package.name
+                    );
+                    self.record(Category::Requirements, Some((dependency, text)));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks that the commit each git dependency is pinned to can still be
+    /// fetched, which needs the network.
+    fn check_git_pins(&mut self, paths: &ProjectPaths, manifest: &Manifest) -> Result<()> {
+        let scratch = paths.build_directory().join("verify");
+        for package in manifest
+            .packages
+            .iter()
+            .sorted_by(|a, b| a.name.cmp(&b.name))
+        {
+            if let ManifestPackageSource::Git { repo, commit } = &package.source {
+                let problem = (!fs::git_commit_reachable(repo, commit, &scratch)?)
+                    .then(|| format!("commit {commit} can no longer be fetched from {repo}"));
+                self.record(Category::GitPins, problem.map(|text| (&package.name, text)));
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks the attestations of the Hex packages that were published with
+    /// one, and that the packages with a trusted key in `gleam.toml` have
+    /// one. Other packages without an attestation are not counted.
+    fn check_provenance<IO: FileSystemReader>(
+        &mut self,
+        io: &IO,
+        paths: &ProjectPaths,
+        config: &PackageConfig,
+        manifest: &Manifest,
+    ) -> Result<()> {
+        let _ = self.checked.entry(Category::Provenance).or_default();
+        for package in manifest
+            .packages
+            .iter()
+            .sorted_by(|a, b| a.name.cmp(&b.name))
+        {
+            if let ManifestPackageSource::Hex { .. } = &package.source {
+                let directory = paths.build_packages_package_source(package);
+                let version = package.version.to_string();
+                let trusted_key = config.deps.provenance_keys.get(&package.name);
+                let Some(problems) = Attestation::check_package(
+                    io,
+                    &directory,
+                    &package.name,
+                    &version,
+                    trusted_key.map(EcoString::as_str),
+                )?
+                else {
+                    continue;
+                };
+                let problem = (!problems.is_empty()).then(|| problems.join(", "));
+                self.record(
+                    Category::Provenance,
+                    problem.map(|text| (&package.name, text)),
+                );
+            }
+        }
+        Ok(())
+    }
+
+    fn to_text(&self) -> String {
+        let mut text = String::new();
+        for (category, checked) in &self.checked {
+            let problems = self
+                .problems
+                .get(category)
+                .map(Vec::as_slice)
+                .unwrap_or_default();
+            let status = match problems.len() {
+                0 => "ok".into(),
+                1 => "1 problem".into(),
+                count => format!("{count} problems"),
+            };
+            let checks = if *checked == 1 { "check" } else { "checks" };
+            text.push_str(&format!(
+                "{}: {status} ({checked} {checks})\n",
+                category.title()
+            ));
+            for problem in problems {
+                text.push_str(&format!("  - {}: {}\n", problem.package, problem.text));
+            }
+        }
+        text
+    }
+}
+
+/// Why a package in the manifest does not satisfy its requirement, if it
+/// does not.
+fn requirement_problem(requirement: &Requirement, package: &ManifestPackage) -> Option<String> {
+    match (requirement, &package.source) {
+        (Requirement::Hex { version, .. }, ManifestPackageSource::Hex { .. }) => {
+            match version.to_pubgrub() {
+                Ok(range) if range.contains(&package.version) => None,
+                Ok(_) => Some(format!(
+                    "v{} does not satisfy its requirement {version}",
+                    package.version
+                )),
+                Err(_) => Some(format!("its requirement {version} is invalid")),
+            }
+        }
+        (Requirement::Git { git, .. }, ManifestPackageSource::Git { repo, .. }) if git == repo => {
+            None
+        }
+        (Requirement::Path { .. }, ManifestPackageSource::Local { .. }) => None,
+        _ => Some("manifest.toml has it from a different source than gleam.toml".into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, SystemTime};
+
+    use gleam_core::{
+        io::{memory::InMemoryFileSystem, FileSystemWriter},
+        manifest::Base16Checksum,
+    };
+    use hexpm::version::Version;
+
+    use super::*;
+
+    const TARBALL: &[u8] = b"wibble tarball";
+
+    fn hex_package(name: &str, version: &str, requirements: &[&str]) -> ManifestPackage {
+        ManifestPackage {
+            name: name.into(),
+            version: Version::parse(version).expect("version"),
+            build_tools: vec!["gleam".into()],
+            otp_app: None,
+            requirements: requirements.iter().map(|name| (*name).into()).collect(),
+            source: ManifestPackageSource::Hex {
+                outer_checksum: Base16Checksum(Sha256::digest(TARBALL).to_vec()),
+                organization: None,
+            },
+        }
+    }
+
+    /// A project with each package downloaded to the cache and extracted.
+    fn project(manifest: &Manifest) -> (InMemoryFileSystem, ProjectPaths) {
+        let io = InMemoryFileSystem::new();
+        let paths = ProjectPaths::new("/app".into());
+        for package in &manifest.packages {
+            let tarball = paths::global_package_cache_package_tarball(
+                &package.name,
+                &package.version.to_string(),
+            );
+            io.write_bytes(&tarball, TARBALL).expect("write tarball");
+            let directory = paths.build_packages_package_source(package);
+            io.write(&directory.join("gleam.toml"), "name = \"wibble\"")
+                .expect("write package");
+            integrity::write_record(&io, &io, &directory).expect("write record");
+        }
+        (io, paths)
+    }
+
+    fn config(dependencies: &[(&str, &str)]) -> PackageConfig {
+        let mut config = PackageConfig::default();
+        for (name, range) in dependencies {
+            let _ = config
+                .dependencies
+                .insert((*name).into(), Requirement::hex(range));
+        }
+        config
+    }
+
+    fn manifest(requirements: &[(&str, &str)], packages: Vec<ManifestPackage>) -> Manifest {
+        Manifest {
+            requirements: requirements
+                .iter()
+                .map(|(name, range)| ((*name).into(), Requirement::hex(range)))
+                .collect(),
+            packages,
+        }
+    }
+
+    #[test]
+    fn verify_intact_dependencies() {
+        let manifest = manifest(
+            &[("wibble", "~> 1.0")],
+            vec![
+                hex_package("wibble", "1.2.0", &["wobble"]),
+                hex_package("wobble", "0.3.0", &[]),
+            ],
+        );
+        let (io, paths) = project(&manifest);
+        let config = config(&[("wibble", "~> 1.0")]);
+        let report = Report::check(&io, &paths, &config, &manifest).expect("check");
+        assert_eq!(report.problems(), 0);
+        assert_eq!(
+            report.to_text(),
+            "Tarball checksums: ok (2 checks)
+Package sources: ok (2 checks)
+Requirements: ok (1 check)
+"
+        );
+    }
+
+    #[test]
+    fn verify_provenance_of_packages_with_attestation() {
+        let manifest = manifest(
+            &[("wibble", "~> 1.0")],
+            vec![
+                hex_package("wibble", "1.2.0", &["wobble"]),
+                hex_package("wobble", "0.3.0", &[]),
+            ],
+        );
+        let (io, paths) = project(&manifest);
+        let attestation = serde_json::json!({
+            "statement": {
+                "package": "wibble",
+                "version": "1.2.0",
+                "builder": { "id": "local" },
+                "source": { "repository": null, "commit": null },
+                "toolchain": {},
+                "files": { "gleam.toml": "00" },
+            },
+            "public_key": "00",
+            "signature": "00",
+        });
+        let directory =
+            paths.build_packages_package_source(manifest.packages.first().expect("wibble"));
+        io.write(
+            &directory.join(crate::provenance::ATTESTATION_FILE),
+            &attestation.to_string(),
+        )
+        .expect("write attestation");
+
+        let mut config = config(&[("wibble", "~> 1.0")]);
+        config.deps.provenance_keys = [
+            ("wibble".into(), "00".into()),
+            ("wobble".into(), "11".into()),
+        ]
+        .into();
+
+        let mut report = Report::default();
+        report
+            .check_provenance(&io, &paths, &config, &manifest)
+            .expect("check provenance");
+        assert_eq!(
+            report.to_text(),
+            "Provenance attestations: 2 problems (2 checks)
+  - wibble: its signature is not valid, gleam.toml has changed since it was signed
+  - wobble: it has no gleam-provenance.json, but one signed with its trusted key is expected
+"
+        );
+    }
+
+    #[test]
+    fn verify_reports_problems_by_category() {
+        let manifest = manifest(
+            &[("wibble", "~> 1.0"), ("wubble", "~> 2.0")],
+            vec![
+                hex_package("wibble", "1.2.0", &["wobble", "wabble"]),
+                hex_package("wobble", "0.3.0", &[]),
+            ],
+        );
+        let (io, paths) = project(&manifest);
+        let config = config(&[("wibble", "~> 2.0")]);
+
+        let wobble = manifest.packages.get(1).expect("wobble");
+        let tarball = paths::global_package_cache_package_tarball("wobble", "0.3.0");
+        io.write_bytes(&tarball, b"tampered")
+            .expect("write tarball");
+        let source = paths
+            .build_packages_package_source(wobble)
+            .join("gleam.toml");
+        io.write(&source, "name = \"wobble\"")
+            .expect("write package");
+        io.try_set_modification_time(&source, SystemTime::UNIX_EPOCH + Duration::from_secs(1))
+            .expect("set modification time");
+
+        let report = Report::check(&io, &paths, &config, &manifest).expect("check");
+        assert_eq!(report.problems(), 5);
+        assert_eq!(
+            report.to_text(),
+            format!(
+                "Tarball checksums: 1 problem (2 checks)
+  - wobble: the tarball for v0.3.0 has checksum {} but manifest.toml has {}
+Package sources: 1 problem (2 checks)
+  - wobble: these files have been changed: gleam.toml
+Requirements: 3 problems (3 checks)
+  - wibble: its requirement in gleam.toml has changed since manifest.toml was written
+  - wubble: it is in manifest.toml but no longer required by gleam.toml
+  - wabble: it is required by wibble but not in manifest.toml
+",
+                base16::encode_upper(&Sha256::digest(b"tampered")),
+                base16::encode_upper(&Sha256::digest(TARBALL)),
+            )
+        );
+    }
+
+    #[test]
+    fn verify_version_satisfies_requirement() {
+        let package = hex_package("wibble", "1.2.0", &[]);
+        assert_eq!(
+            requirement_problem(&Requirement::hex("~> 1.0"), &package),
+            None
+        );
+        assert_eq!(
+            requirement_problem(&Requirement::hex(">= 1.3.0"), &package),
+            Some("v1.2.0 does not satisfy its requirement >= 1.3.0".into())
+        );
+        assert_eq!(
+            requirement_problem(&Requirement::path("../wibble"), &package),
+            Some("manifest.toml has it from a different source than gleam.toml".into())
+        );
+    }
+}
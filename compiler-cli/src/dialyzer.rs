@@ -0,0 +1,158 @@
+use std::process::{Command, Stdio};
+
+use camino::Utf8Path;
+use ecow::EcoString;
+use gleam_core::{
+    analyse::TargetSupport,
+    build::{Codegen, Mode, Options, Target},
+    erlang::dialyzer::{self, SourceMap},
+    paths::ARTEFACT_DIRECTORY_NAME,
+    Error, Result,
+};
+use itertools::Itertools;
+
+/// The OTP applications every Gleam project on the Erlang target uses.
+const OTP_APPLICATIONS: [&str; 3] = ["erts", "kernel", "stdlib"];
+
+/// Build the project and check it with dialyzer, printing its warnings with
+/// the Gleam functions they are for. The PLT of the dependencies is kept in
+/// the build directory and built again when the dependencies change.
+pub(crate) fn run() -> Result<()> {
+    let paths = crate::find_project_paths()?;
+    let config = crate::config::root_config()?;
+    let mode = Mode::Dev;
+
+    let options = Options {
+        root_target_support: TargetSupport::Enforced,
+        warnings_as_errors: false,
+        verify_artefacts: false,
+        codegen: Codegen::All,
+        mode,
+        target: Some(Target::Erlang),
+    };
+    let manifest = crate::build::download_dependencies()?;
+    let _ = crate::build::main(options, manifest.clone())?;
+
+    // The PLT holds what dialyzer knows of the OTP applications and the
+    // dependencies, which is kept between runs as it is slow to build.
+    let plt_directory = paths.build_directory().join("dialyzer");
+    let plt = plt_directory.join("gleam.plt");
+    let applications_path = plt_directory.join("applications");
+    let applications = OTP_APPLICATIONS
+        .iter()
+        .map(|application| application.to_string())
+        .chain(
+            config
+                .erlang
+                .extra_applications
+                .iter()
+                .map(EcoString::to_string),
+        )
+        .unique()
+        .collect_vec();
+    let dependencies = manifest
+        .packages
+        .iter()
+        .map(|package| {
+            let ebin = paths
+                .build_directory_for_package(mode, Target::Erlang, package.application_name())
+                .join("ebin");
+            (format!("{} {}", package.name, package.version), ebin)
+        })
+        .collect_vec();
+    let fingerprint = applications
+        .iter()
+        .chain(dependencies.iter().map(|(package, _)| package))
+        .join("\n");
+
+    let up_to_date = plt.exists()
+        && crate::fs::read(&applications_path).is_ok_and(|existing| existing == fingerprint);
+    if !up_to_date {
+        crate::cli::print_colourful_prefix("Building", "PLT for dialyzer");
+        crate::fs::mkdir(&plt_directory)?;
+        let mut command = Command::new("dialyzer");
+        let _ = command
+            .arg("--build_plt")
+            .arg("--output_plt")
+            .arg(&plt)
+            .arg("--apps")
+            .args(&applications)
+            .args(dependencies.iter().map(|(_, ebin)| ebin));
+        let _ = dialyzer(command)?;
+        crate::fs::write(&applications_path, &fingerprint)?;
+    }
+
+    crate::cli::print_running("dialyzer");
+    let package = paths.build_directory_for_package(mode, Target::Erlang, &config.name);
+    let mut command = Command::new("dialyzer");
+    let _ = command
+        .arg("--plt")
+        .arg(&plt)
+        .arg("--quiet")
+        .arg("--fullpath")
+        .arg("-r")
+        .arg(package.join("ebin"));
+    let output = dialyzer(command)?;
+
+    let warnings = dialyzer::parse(&output)
+        .into_iter()
+        .map(|mut warning| {
+            if let Ok(path) = warning.path.strip_prefix(paths.root()) {
+                warning.path = path.to_path_buf();
+            }
+            warning
+        })
+        .map(|warning| match source_map(&package, &warning.path) {
+            Some(source_map) => source_map.locate(warning),
+            None => warning,
+        })
+        .collect_vec();
+    if warnings.is_empty() {
+        return Ok(());
+    }
+    print!("{}", dialyzer::to_text(&warnings));
+    Err(Error::DialyzerWarnings {
+        count: warnings.len(),
+    })
+}
+
+/// Runs dialyzer, returning what it printed. It exits with a status of 2 when
+/// it has warnings, which are not a failure to run it.
+fn dialyzer(mut command: Command) -> Result<String> {
+    tracing::info!(command = ?command, "running_os_process");
+    let output = command
+        .stderr(Stdio::inherit())
+        .output()
+        .map_err(|error| match error.kind() {
+            std::io::ErrorKind::NotFound => Error::ShellProgramNotFound {
+                program: "dialyzer".into(),
+            },
+            other => Error::ShellCommand {
+                program: "dialyzer".into(),
+                err: Some(other),
+            },
+        })?;
+    let stdout = String::from_utf8_lossy(&output.stdout).into_owned();
+    match output.status.code() {
+        Some(0 | 2) => Ok(stdout),
+        _ => {
+            print!("{stdout}");
+            Err(Error::ShellCommand {
+                program: "dialyzer".into(),
+                err: None,
+            })
+        }
+    }
+}
+
+/// The source map of the generated Erlang module a warning is for.
+fn source_map(package: &Utf8Path, path: &Utf8Path) -> Option<SourceMap> {
+    let artefacts = package.join(ARTEFACT_DIRECTORY_NAME);
+    let erlang_path = match path.extension() {
+        Some("gleam") => artefacts.join(format!("{}.erl", dialyzer::erlang_module_name(path)?)),
+        Some("erl") => artefacts.join(path.file_name()?),
+        _ => return None,
+    };
+    let erlang = crate::fs::read(erlang_path).ok()?;
+    Some(SourceMap::new(&erlang))
+}
@@ -1,3 +1,5 @@
+mod serve;
+
 use std::time::{Instant, SystemTime};
 
 use camino::{Utf8Path, Utf8PathBuf};
@@ -7,17 +9,70 @@ use gleam_core::{
     analyse::TargetSupport,
     build::{Codegen, Mode, Options, Package},
     config::{DocsPage, PackageConfig},
-    docs::DocContext,
+    docs::{DependencyLinker, DocContext},
     error::Error,
     hex,
     io::HttpClient as _,
+    manifest::ManifestPackageSource,
+    paths::ProjectPaths,
     Result,
 };
 
-pub fn remove(package: String, version: String) -> Result<()> {
+pub use serve::{serve, ServeOptions};
+
+pub fn remove(package: Option<String>, version: String) -> Result<()> {
+    let package = match package {
+        Some(package) => package,
+        None => crate::config::root_config()?.name.to_string(),
+    };
     RemoveCommand::new(package, version).run()
 }
 
+/// Prints the versions of the package that have docs published to HexDocs.
+pub fn list(package: Option<String>) -> Result<()> {
+    let package = match package {
+        Some(package) => package,
+        None => crate::config::root_config()?.name.to_string(),
+    };
+    let releases =
+        get_release_docs(&package)?.ok_or_else(|| Error::hex(hexpm::ApiError::NotFound))?;
+    let published = releases
+        .iter()
+        .filter(|release| release.has_docs)
+        .collect::<Vec<_>>();
+    if published.is_empty() {
+        println!("No docs have been published for {package}.");
+    }
+    for release in published {
+        println!(
+            "v{version}  https://hexdocs.pm/{package}/{version}/",
+            version = release.version
+        );
+    }
+    Ok(())
+}
+
+/// The releases of the package and whether each has docs published, or
+/// nothing if the package has not been published.
+fn get_release_docs(package: &str) -> Result<Option<Vec<hex::docs::ReleaseDocs>>> {
+    let runtime = tokio::runtime::Runtime::new().expect("Unable to start Tokio async runtime");
+    let api_key = std::env::var(crate::hex::API_KEY).ok();
+    let releases = runtime.block_on(hex::docs::get_release_docs(
+        package,
+        api_key
+            .as_deref()
+            .map(str::trim)
+            .filter(|key| !key.is_empty()),
+        &hexpm::Config::new(),
+        &HttpClient::new(),
+    ));
+    match releases {
+        Ok(releases) => Ok(Some(releases)),
+        Err(error) if error == Error::hex(hexpm::ApiError::NotFound) => Ok(None),
+        Err(error) => Err(error),
+    }
+}
+
 struct RemoveCommand {
     package: String,
     version: String,
@@ -57,6 +112,11 @@ impl ApiKeyCommand for RemoveCommand {
 pub struct BuildOptions {
     /// Whether to open the docs after building.
     pub open: bool,
+    /// Whether to also write the docs to an archive that can be read without
+    /// a network connection.
+    pub offline_bundle: bool,
+    /// Whether to also render the docs as a single Markdown file.
+    pub single_page: bool,
 }
 
 pub fn build(options: BuildOptions) -> Result<()> {
@@ -67,17 +127,23 @@ pub fn build(options: BuildOptions) -> Result<()> {
     crate::fs::delete_directory(&paths.build_directory_for_target(Mode::Prod, config.target))?;
 
     let out = paths.build_documentation_directory(&config.name);
-    let mut built = crate::build::main(
-        Options {
-            mode: Mode::Prod,
-            target: None,
-            codegen: Codegen::All,
-            warnings_as_errors: false,
-            root_target_support: TargetSupport::Enforced,
-        },
-        crate::build::download_dependencies()?,
-    )?;
-    let outputs = build_documentation(&config, &mut built.root_package, DocContext::Build)?;
+    let context = if options.offline_bundle {
+        DocContext::OfflineBundle
+    } else {
+        DocContext::Build
+    };
+    let (root_package, dependency_links, mut outputs) = compile_and_render(&config, context)?;
+
+    if options.single_page {
+        outputs.push(gleam_core::docs::generate_single_page_markdown(
+            &paths,
+            &config,
+            root_package.modules.as_slice(),
+            &documentation_pages(&config, &paths),
+            &dependency_links,
+            ProjectIO::new(),
+        ));
+    }
 
     // Write
     crate::fs::delete_directory(&out)?;
@@ -91,6 +157,20 @@ pub fn build(options: BuildOptions) -> Result<()> {
         index_html = index_html
     );
 
+    if options.single_page {
+        println!(
+            "\nThe single page documentation has been rendered to \n{}",
+            out.join(format!("{}.md", config.name))
+        );
+    }
+
+    if options.offline_bundle {
+        let bundle =
+            paths.build_export_documentation_bundle(&config.name, &config.version.to_string());
+        crate::fs::write_bytes(&bundle, &crate::fs::create_tar_archive(outputs)?)?;
+        println!("\nThe offline documentation bundle has been written to \n{bundle}");
+    }
+
     if options.open {
         open_docs(&index_html)?;
     }
@@ -99,6 +179,28 @@ pub fn build(options: BuildOptions) -> Result<()> {
     Ok(())
 }
 
+/// Compiles the project for production and renders the documentation of the
+/// root package.
+fn compile_and_render(
+    config: &PackageConfig,
+    context: DocContext,
+) -> Result<(Package, DependencyLinker, Vec<gleam_core::io::OutputFile>)> {
+    let mut built = crate::build::main(
+        Options {
+            mode: Mode::Prod,
+            target: None,
+            codegen: Codegen::All,
+            warnings_as_errors: false,
+            verify_artefacts: false,
+            root_target_support: TargetSupport::Enforced,
+        },
+        crate::build::download_dependencies()?,
+    )?;
+    let dependency_links = dependency_links()?;
+    let outputs = build_documentation(config, &mut built.root_package, context, &dependency_links)?;
+    Ok((built.root_package, dependency_links, outputs))
+}
+
 /// Opens the indicated path in the default program configured by the system.
 ///
 /// For the docs this will generally be a browser (unless some other program is
@@ -112,25 +214,58 @@ fn open_docs(path: &Utf8Path) -> Result<()> {
     Ok(())
 }
 
+/// Links to the docs of the exact dependency versions the project was built
+/// with, for those whose docs were published to HexDocs. The dependencies
+/// whose docs can't be looked up, such as when offline, are not linked to.
+pub(crate) fn dependency_links() -> Result<DependencyLinker> {
+    let paths = crate::find_project_paths()?;
+    let manifest = crate::dependencies::read_manifest_from_disc(&ProjectIO::new(), &paths)?;
+    let runtime = tokio::runtime::Runtime::new().expect("Unable to start Tokio async runtime");
+    let config = hexpm::Config::new();
+    let http = HttpClient::new();
+    let lookups = manifest
+        .packages
+        .iter()
+        .filter_map(|package| match &package.source {
+            ManifestPackageSource::Hex {
+                organization: None, ..
+            } => Some(async {
+                let releases =
+                    hex::docs::get_release_docs(&package.name, None, &config, &http).await;
+                let version = package.version.to_string();
+                let documented = releases.is_ok_and(|releases| {
+                    (releases.iter()).any(|release| release.version == version && release.has_docs)
+                });
+                documented.then(|| package.name.clone())
+            }),
+            ManifestPackageSource::Hex { .. }
+            | ManifestPackageSource::Git { .. }
+            | ManifestPackageSource::Local { .. } => None,
+        });
+    let documented = runtime
+        .block_on(futures::future::join_all(lookups))
+        .into_iter()
+        .flatten()
+        .collect();
+    Ok(DependencyLinker::new(&manifest, &documented))
+}
+
 pub(crate) fn build_documentation(
     config: &PackageConfig,
     compiled: &mut Package,
     is_hex_publish: DocContext,
+    dependency_links: &DependencyLinker,
 ) -> Result<Vec<gleam_core::io::OutputFile>, Error> {
     compiled.attach_doc_and_module_comments();
     cli::print_generating_documentation();
     let paths = crate::find_project_paths()?;
-    let mut pages = vec![DocsPage {
-        title: "README".into(),
-        path: "index.html".into(),
-        source: paths.readme(), // TODO: support non markdown READMEs. Or a default if there is none.
-    }];
-    pages.extend(config.documentation.pages.iter().cloned());
+    let pages = documentation_pages(config, &paths);
     let mut outputs = gleam_core::docs::generate_html(
         &paths,
         config,
         compiled.modules.as_slice(),
         &pages,
+        dependency_links,
         ProjectIO::new(),
         SystemTime::now(),
         is_hex_publish,
@@ -143,20 +278,36 @@ pub(crate) fn build_documentation(
     Ok(outputs)
 }
 
+fn documentation_pages(config: &PackageConfig, paths: &ProjectPaths) -> Vec<DocsPage> {
+    let mut pages = vec![DocsPage {
+        title: "README".into(),
+        path: "index.html".into(),
+        source: paths.readme(), // TODO: support non markdown READMEs. Or a default if there is none.
+    }];
+    pages.extend(config.documentation.pages.iter().cloned());
+    pages
+}
+
 struct PublishCommand {
     config: PackageConfig,
     archive: Vec<u8>,
 }
 
-pub fn publish() -> Result<()> {
-    PublishCommand::new()?.run()
+pub fn publish(replace: bool) -> Result<()> {
+    PublishCommand::new(replace)?.run()
 }
 
 impl PublishCommand {
-    pub fn new() -> Result<Self> {
+    pub fn new(replace: bool) -> Result<Self> {
         let paths = crate::find_project_paths()?;
         let config = crate::config::root_config()?;
 
+        // Publishing the docs of a version again replaces them, so check they
+        // have not already been published before building them.
+        if !replace {
+            ensure_docs_not_published(&config)?;
+        }
+
         // Reset the build directory so we know the state of the project
         crate::fs::delete_directory(&paths.build_directory_for_target(Mode::Prod, config.target))?;
 
@@ -164,19 +315,39 @@ impl PublishCommand {
             Options {
                 root_target_support: TargetSupport::Enforced,
                 warnings_as_errors: false,
+                verify_artefacts: false,
                 codegen: Codegen::All,
                 mode: Mode::Prod,
                 target: None,
             },
             crate::build::download_dependencies()?,
         )?;
-        let outputs =
-            build_documentation(&config, &mut built.root_package, DocContext::HexPublish)?;
+        let outputs = build_documentation(
+            &config,
+            &mut built.root_package,
+            DocContext::HexPublish,
+            &dependency_links()?,
+        )?;
         let archive = crate::fs::create_tar_archive(outputs)?;
         Ok(Self { config, archive })
     }
 }
 
+fn ensure_docs_not_published(config: &PackageConfig) -> Result<()> {
+    let version = config.version.to_string();
+    let published = get_release_docs(&config.name)?
+        .unwrap_or_default()
+        .iter()
+        .any(|release| release.version == version && release.has_docs);
+    if published {
+        return Err(Error::DocsAlreadyPublished {
+            package: config.name.to_string(),
+            version,
+        });
+    }
+    Ok(())
+}
+
 impl ApiKeyCommand for PublishCommand {
     fn with_api_key(
         &mut self,
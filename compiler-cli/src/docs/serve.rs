@@ -0,0 +1,327 @@
+//! A local preview of the documentation of the project, rendered again
+//! whenever its source changes. Each page served includes a script that
+//! reloads it once a newer rendering is available.
+
+use std::{
+    io::{BufRead, BufReader, Write},
+    net::{TcpListener, TcpStream},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    thread,
+    time::{Duration, SystemTime},
+};
+
+use camino::{Utf8Path, Utf8PathBuf};
+use gleam_core::{build::Mode, docs::DocContext, error::Error, paths::ProjectPaths, Result};
+
+use crate::cli;
+
+/// The path the reload script requests to find the number of the latest
+/// rendering of the docs.
+const VERSION_PATH: &str = "/__gleam/version";
+
+/// How often the source files are checked for changes, and how often the
+/// pages check for a newer rendering.
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+#[derive(Debug)]
+pub struct ServeOptions {
+    /// The port on localhost to serve the docs on.
+    pub port: u16,
+    /// Whether to open the docs after they have first been rendered.
+    pub open: bool,
+}
+
+pub fn serve(options: ServeOptions) -> Result<()> {
+    let paths = crate::find_project_paths()?;
+    let config = crate::config::root_config()?;
+
+    // Reset the build directory so we know the state of the project
+    crate::fs::delete_directory(&paths.build_directory_for_target(Mode::Prod, config.target))?;
+
+    let address = format!("127.0.0.1:{}", options.port);
+    let listener = TcpListener::bind(&address).map_err(|error| Error::FailedToServeDocs {
+        address: address.clone(),
+        error: error.to_string(),
+    })?;
+
+    let out = paths.build_documentation_directory(&config.name);
+    let version = Arc::new(AtomicU64::new(0));
+    let mut sources = source_files(&paths);
+    let _ = render_reporting_errors(&out);
+
+    let server_version = version.clone();
+    let server_out = out.clone();
+    let _ = thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let version = server_version.load(Ordering::SeqCst);
+            let out = server_out.clone();
+            let _ = thread::spawn(move || handle_connection(stream, &out, version));
+        }
+    });
+
+    let url = format!("http://localhost:{}/", options.port);
+    cli::print_serving_documentation(&url);
+    if options.open {
+        super::open_docs(Utf8Path::new(&url))?;
+    }
+
+    loop {
+        thread::sleep(POLL_INTERVAL);
+        let current = source_files(&paths);
+        if current == sources {
+            continue;
+        }
+        sources = current;
+        cli::print_rerendering_documentation();
+        if render_reporting_errors(&out) {
+            let _ = version.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+}
+
+/// Renders the docs to the output directory, printing any error rather than
+/// returning it so that the last rendering continues to be served.
+fn render_reporting_errors(out: &Utf8Path) -> bool {
+    let rendered = crate::config::root_config().and_then(|config| {
+        let (_, _, outputs) = super::compile_and_render(&config, DocContext::Build)?;
+        crate::fs::delete_directory(out)?;
+        crate::fs::write_outputs_under(&outputs, out)
+    });
+    match rendered {
+        Ok(()) => true,
+        Err(error) => {
+            let stderr = cli::stderr_buffer_writer();
+            let mut buffer = stderr.buffer();
+            error.pretty(&mut buffer, cli::theme());
+            stderr.print(&buffer).expect("Docs error writing");
+            false
+        }
+    }
+}
+
+/// The files the docs are rendered from, with the time each was last
+/// modified.
+fn source_files(paths: &ProjectPaths) -> Vec<(Utf8PathBuf, Option<SystemTime>)> {
+    let pages = crate::config::root_config()
+        .map(|config| super::documentation_pages(&config, paths))
+        .unwrap_or_default();
+    let files = walkdir::WalkDir::new(paths.src_directory())
+        .sort_by_file_name()
+        .into_iter()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_file())
+        .filter_map(|entry| Utf8PathBuf::from_path_buf(entry.into_path()).ok())
+        .chain([paths.root_config(), paths.manifest()])
+        .chain(pages.into_iter().map(|page| page.source));
+    files
+        .map(|path| {
+            let modified = path.metadata().and_then(|metadata| metadata.modified());
+            (path, modified.ok())
+        })
+        .collect()
+}
+
+fn handle_connection(stream: TcpStream, out: &Utf8Path, version: u64) -> std::io::Result<()> {
+    let mut reader = BufReader::new(&stream);
+    let mut request_line = String::new();
+    let _ = reader.read_line(&mut request_line)?;
+    // The headers of the request are not needed
+    let mut header = String::new();
+    while reader.read_line(&mut header)? > 0 && !header.trim().is_empty() {
+        header.clear();
+    }
+
+    let response = respond(out, &request_line, version);
+    let mut stream = &stream;
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nCache-Control: no-store\r\nConnection: close\r\n\r\n",
+        response.status,
+        response.content_type,
+        response.body.len()
+    )?;
+    stream.write_all(&response.body)?;
+    stream.flush()
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct Response {
+    status: &'static str,
+    content_type: &'static str,
+    body: Vec<u8>,
+}
+
+impl Response {
+    fn text(status: &'static str, text: &str) -> Self {
+        Self {
+            status,
+            content_type: "text/plain; charset=utf-8",
+            body: text.as_bytes().to_vec(),
+        }
+    }
+}
+
+fn respond(out: &Utf8Path, request_line: &str, version: u64) -> Response {
+    let mut parts = request_line.split_whitespace();
+    let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+        return Response::text("400 Bad Request", "Bad request");
+    };
+    if method != "GET" && method != "HEAD" {
+        return Response::text("405 Method Not Allowed", "Method not allowed");
+    }
+    let target = target.split(['?', '#']).next().unwrap_or_default();
+    if target == VERSION_PATH {
+        return Response::text("200 OK", &version.to_string());
+    }
+    let Some(path) = file_path(out, target) else {
+        return Response::text("404 Not Found", "Not found");
+    };
+    let Ok(body) = std::fs::read(&path) else {
+        return Response::text("404 Not Found", "Not found");
+    };
+    let content_type = content_type(&path);
+    let body = match String::from_utf8(body) {
+        Ok(html) if path.extension() == Some("html") => {
+            with_reload_script(&html, version).into_bytes()
+        }
+        Ok(text) => text.into_bytes(),
+        Err(error) => error.into_bytes(),
+    };
+    Response {
+        status: "200 OK",
+        content_type,
+        body,
+    }
+}
+
+/// The file in the output directory for the requested path, which must not
+/// leave the directory.
+fn file_path(out: &Utf8Path, target: &str) -> Option<Utf8PathBuf> {
+    let mut path = out.to_path_buf();
+    for segment in target.split('/').filter(|segment| !segment.is_empty()) {
+        if segment == "." || segment == ".." || segment.contains('\\') {
+            return None;
+        }
+        path.push(segment);
+    }
+    if target.ends_with('/') || path == out {
+        path.push("index.html");
+    }
+    Some(path)
+}
+
+fn content_type(path: &Utf8Path) -> &'static str {
+    match path.extension() {
+        Some("html") => "text/html; charset=utf-8",
+        Some("css") => "text/css; charset=utf-8",
+        Some("js" | "mjs") => "text/javascript; charset=utf-8",
+        Some("json") => "application/json",
+        Some("md") => "text/markdown; charset=utf-8",
+        Some("svg") => "image/svg+xml",
+        Some("png") => "image/png",
+        Some("ico") => "image/x-icon",
+        Some("woff") => "font/woff",
+        Some("woff2") => "font/woff2",
+        _ => "application/octet-stream",
+    }
+}
+
+/// Adds a script to the page that reloads it once the docs have been rendered
+/// again.
+fn with_reload_script(html: &str, version: u64) -> String {
+    let script = format!(
+        r#"<script>
+  setInterval(async () => {{
+    try {{
+      const response = await fetch("{VERSION_PATH}", {{ cache: "no-store" }});
+      if ((await response.text()) !== "{version}") location.reload();
+    }} catch {{}}
+  }}, {interval});
+</script>
+"#,
+        interval = POLL_INTERVAL.as_millis()
+    );
+    match html.rfind("</body>") {
+        Some(index) => {
+            let (before, after) = html.split_at(index);
+            format!("{before}{script}{after}")
+        }
+        None => format!("{html}{script}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn file_path_of_pages() {
+        let out = Utf8Path::new("/build/docs");
+        assert_eq!(file_path(out, "/"), Some("/build/docs/index.html".into()));
+        assert_eq!(
+            file_path(out, "/wibble/wobble.html"),
+            Some("/build/docs/wibble/wobble.html".into())
+        );
+        assert_eq!(
+            file_path(out, "/wibble/"),
+            Some("/build/docs/wibble/index.html".into())
+        );
+    }
+
+    #[test]
+    fn file_path_cannot_leave_the_output_directory() {
+        let out = Utf8Path::new("/build/docs");
+        assert_eq!(file_path(out, "/../gleam.toml"), None);
+        assert_eq!(file_path(out, "/wibble/../../gleam.toml"), None);
+        assert_eq!(file_path(out, "/..\\gleam.toml"), None);
+    }
+
+    #[test]
+    fn reload_script_is_added_to_the_end_of_the_body() {
+        let html = with_reload_script("<html><body><p>Hi</p></body></html>", 3);
+        assert!(html.starts_with("<html><body><p>Hi</p><script>"));
+        assert!(html.ends_with("</script>\n</body></html>"));
+        assert!(html.contains(r#"!== "3")"#));
+    }
+
+    #[test]
+    fn respond_with_pages_and_version() {
+        let directory = tempfile::tempdir().expect("temporary directory");
+        let out = Utf8Path::from_path(directory.path()).expect("utf8 path");
+        std::fs::write(out.join("index.html"), "<body></body>").expect("write index");
+        std::fs::write(out.join("app.css"), "body {}").expect("write css");
+
+        let page = respond(out, "GET /?query HTTP/1.1\r\n", 7);
+        assert_eq!(page.status, "200 OK");
+        assert_eq!(page.content_type, "text/html; charset=utf-8");
+        assert!(String::from_utf8(page.body)
+            .expect("utf8 body")
+            .contains(r#"!== "7")"#));
+
+        let css = respond(out, "GET /app.css HTTP/1.1\r\n", 7);
+        assert_eq!(
+            css,
+            Response {
+                status: "200 OK",
+                content_type: "text/css; charset=utf-8",
+                body: b"body {}".to_vec(),
+            }
+        );
+
+        assert_eq!(
+            respond(out, "GET /__gleam/version HTTP/1.1\r\n", 7),
+            Response::text("200 OK", "7")
+        );
+        assert_eq!(
+            respond(out, "GET /missing.html HTTP/1.1\r\n", 7).status,
+            "404 Not Found"
+        );
+        assert_eq!(
+            respond(out, "POST / HTTP/1.1\r\n", 7).status,
+            "405 Method Not Allowed"
+        );
+    }
+}
@@ -0,0 +1,220 @@
+//! The example projects in the `examples` directory of a package, which are
+//! type checked by `gleam check --examples` so that they keep compiling as the
+//! package changes.
+//!
+//! An example does not resolve or download its own dependencies. It is
+//! compiled with the packages resolved for the project it is in, along with
+//! the package of that project itself.
+
+use std::sync::Arc;
+
+use camino::Utf8PathBuf;
+use gleam_core::{
+    build::{Options, ProjectCompiler},
+    config::PackageConfig,
+    manifest::{Manifest, ManifestPackage, ManifestPackageSource},
+    paths::ProjectPaths,
+    Error, Result,
+};
+use itertools::Itertools;
+
+use crate::{
+    build_lock::BuildLock,
+    cli,
+    fs::{self, ConsoleWarningEmitter, ProjectIO},
+};
+
+/// Compiles each example of the project with the options, in the order of
+/// their names.
+pub fn check(
+    paths: &ProjectPaths,
+    config: &PackageConfig,
+    manifest: &Manifest,
+    options: impl Fn() -> Options,
+) -> Result<()> {
+    let io = ProjectIO::new();
+    let examples = fs::read_dir(paths.examples_directory())
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.into_path())
+        .filter(|path| path.join("gleam.toml").is_file())
+        .sorted();
+
+    for root in examples {
+        let example = PackageConfig::read(root.join("gleam.toml"), &io)?;
+        let packages = example_packages(paths, config, manifest, &example)?;
+        let options = options();
+        let example_paths = ProjectPaths::new(root);
+        let lock = BuildLock::new_target(
+            &example_paths,
+            options.mode,
+            options.target.unwrap_or(example.target),
+        )?;
+        let telemetry = Box::new(cli::Reporter::new());
+        let _guard = lock.lock(telemetry.as_ref());
+        let mut compiler = ProjectCompiler::new(
+            example,
+            options,
+            packages,
+            telemetry,
+            Arc::new(ConsoleWarningEmitter),
+            example_paths,
+            io,
+        );
+        compiler.environment = std::env::vars().collect();
+        let _ = compiler.compile()?;
+    }
+    Ok(())
+}
+
+/// The packages an example is compiled with: the packages resolved for the
+/// project, read from where the project has them, and the package of the
+/// project itself.
+fn example_packages(
+    paths: &ProjectPaths,
+    config: &PackageConfig,
+    manifest: &Manifest,
+    example: &PackageConfig,
+) -> Result<Vec<ManifestPackage>> {
+    let package = ManifestPackage {
+        name: config.name.clone(),
+        version: config.version.clone(),
+        build_tools: vec!["gleam".into()],
+        otp_app: None,
+        requirements: config.dependencies.keys().cloned().sorted().collect(),
+        source: ManifestPackageSource::Local {
+            path: paths.root().to_path_buf(),
+        },
+    };
+    let packages = manifest
+        .packages
+        .iter()
+        .map(|package| ManifestPackage {
+            source: ManifestPackageSource::Local {
+                path: package_root(paths, package),
+            },
+            ..package.clone()
+        })
+        .chain([package])
+        .collect_vec();
+
+    let required = example
+        .dependencies
+        .keys()
+        .chain(example.dev_dependencies.keys())
+        .sorted();
+    for name in required {
+        if !packages.iter().any(|package| &package.name == name) {
+            return Err(Error::UnresolvedExampleDependency {
+                example: example.name.clone(),
+                package: name.clone(),
+            });
+        }
+    }
+    Ok(packages)
+}
+
+/// Where the project has the source of a package it depends on.
+fn package_root(paths: &ProjectPaths, package: &ManifestPackage) -> Utf8PathBuf {
+    match &package.source {
+        ManifestPackageSource::Local { path } => paths.root().join(path),
+        ManifestPackageSource::Hex { .. } | ManifestPackageSource::Git { .. } => {
+            paths.build_packages_package_source(package)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gleam_core::{manifest::Base16Checksum, requirement::Requirement};
+    use hexpm::version::Version;
+
+    fn hex_package(name: &str) -> ManifestPackage {
+        ManifestPackage {
+            name: name.into(),
+            version: Version::new(1, 0, 0),
+            build_tools: vec!["gleam".into()],
+            otp_app: None,
+            requirements: vec![],
+            source: ManifestPackageSource::Hex {
+                outer_checksum: Base16Checksum(vec![1, 2, 3]),
+                organization: None,
+            },
+        }
+    }
+
+    fn example_depending_on(names: &[&str]) -> PackageConfig {
+        let mut example = PackageConfig {
+            name: "wibble_example".into(),
+            ..Default::default()
+        };
+        for name in names {
+            let _ = example
+                .dependencies
+                .insert((*name).into(), Requirement::hex(">= 1.0.0"));
+        }
+        example
+    }
+
+    #[test]
+    fn example_packages_are_read_from_the_project() {
+        let paths = ProjectPaths::new("/wibble".into());
+        let config = PackageConfig {
+            name: "wibble".into(),
+            ..Default::default()
+        };
+        let manifest = Manifest {
+            requirements: Default::default(),
+            packages: vec![hex_package("gleam_stdlib")],
+        };
+        let example = example_depending_on(&["wibble", "gleam_stdlib"]);
+
+        let packages =
+            example_packages(&paths, &config, &manifest, &example).expect("example packages");
+        let sources = packages
+            .iter()
+            .map(|package| (package.name.as_str(), package.source.clone()))
+            .collect_vec();
+        assert_eq!(
+            sources,
+            vec![
+                (
+                    "gleam_stdlib",
+                    ManifestPackageSource::Local {
+                        path: "/wibble/build/packages/gleam_stdlib-010203".into()
+                    }
+                ),
+                (
+                    "wibble",
+                    ManifestPackageSource::Local {
+                        path: "/wibble".into()
+                    }
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn example_dependencies_must_be_resolved_for_the_project() {
+        let paths = ProjectPaths::new("/wibble".into());
+        let config = PackageConfig {
+            name: "wibble".into(),
+            ..Default::default()
+        };
+        let manifest = Manifest {
+            requirements: Default::default(),
+            packages: vec![],
+        };
+        let example = example_depending_on(&["wibble", "gleam_json"]);
+
+        assert_eq!(
+            example_packages(&paths, &config, &manifest, &example),
+            Err(Error::UnresolvedExampleDependency {
+                example: "wibble_example".into(),
+                package: "gleam_json".into(),
+            })
+        );
+    }
+}
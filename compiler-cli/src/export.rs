@@ -1,9 +1,19 @@
-use camino::Utf8PathBuf;
+use camino::{Utf8Path, Utf8PathBuf};
+use ecow::EcoString;
 use gleam_core::{
     analyse::TargetSupport,
     build::{Codegen, Mode, Options, Target},
-    Result,
+    codegen::STANDARD_APPLICATIONS,
+    config::PackageConfig,
+    manifest::{Manifest, ManifestPackageSource},
+    requirement::Requirement,
+    runtime_config::{self, RuntimeConfig},
+    Error, Result,
 };
+use heck::ToUpperCamelCase;
+use itertools::Itertools;
+
+mod strip;
 
 #[cfg(target_os = "windows")]
 static ENTRYPOINT_FILENAME: &str = "entrypoint.ps1";
@@ -26,7 +36,13 @@ static ENTRYPOINT_TEMPLATE: &str = include_str!("../templates/erlang-shipment-en
 /// - ebin
 /// - include
 /// - priv
-pub(crate) fn erlang_shipment() -> Result<()> {
+///
+/// The TOML files in the project's `config` directory are translated to Erlang
+/// config files in the shipment's `config` directory.
+///
+/// When `strip` is set the shipment is made smaller afterwards, see the
+/// `strip` module.
+pub(crate) fn erlang_shipment(strip: bool) -> Result<()> {
     let paths = crate::find_project_paths()?;
     let target = Target::Erlang;
     let mode = Mode::Prod;
@@ -44,6 +60,7 @@ pub(crate) fn erlang_shipment() -> Result<()> {
         Options {
             root_target_support: TargetSupport::Enforced,
             warnings_as_errors: false,
+            verify_artefacts: false,
             codegen: Codegen::All,
             mode,
             target: Some(target),
@@ -75,6 +92,35 @@ pub(crate) fn erlang_shipment() -> Result<()> {
         }
     }
 
+    // Translate the runtime config files to Erlang config files, loaded by
+    // the entrypoint script when the shipment is started
+    let configs =
+        RuntimeConfig::read_directory(&paths.config_directory(), &crate::fs::ProjectIO::new())?;
+    if !configs.is_empty() {
+        let manifest =
+            crate::dependencies::read_manifest_from_disc(&crate::fs::ProjectIO::new(), &paths)?;
+        let applications = STANDARD_APPLICATIONS
+            .iter()
+            .map(|name| EcoString::from(*name))
+            .chain(
+                manifest
+                    .packages
+                    .iter()
+                    .map(|p| p.application_name().clone()),
+            )
+            .chain(std::iter::once(built.root_package.config.name.clone()))
+            .sorted()
+            .dedup()
+            .collect_vec();
+        let config_directory = out.join(runtime_config::DIRECTORY_NAME);
+        crate::fs::mkdir(&config_directory)?;
+        for config in &configs {
+            config.check_applications(&applications)?;
+            let path = config_directory.join(format!("{}.config", config.name()));
+            crate::fs::write(&path, &config.to_sys_config()?)?;
+        }
+    }
+
     // Write entrypoint script
     let entrypoint = out.join(ENTRYPOINT_FILENAME);
     let text =
@@ -82,8 +128,18 @@ pub(crate) fn erlang_shipment() -> Result<()> {
     crate::fs::write(&entrypoint, &text)?;
     crate::fs::make_executable(&entrypoint)?;
 
+    let report = if strip {
+        Some(strip::strip(&out, &built.root_package.config.name)?)
+    } else {
+        None
+    };
+
     crate::cli::print_exported(&built.root_package.config.name);
 
+    if let Some(report) = report {
+        print_strip_report(&report);
+    }
+
     println!(
         "
 Your Erlang shipment has been generated to {path}.
@@ -98,9 +154,405 @@ the {file} script.
         entrypoint = entrypoint,
     );
 
+    if !configs.is_empty() {
+        println!(
+            "The config in {directory}/{base}.toml is loaded when it starts. To also load
+another config file from {directory}, such as {directory}/prod.toml, set the
+GLEAM_CONFIG environment variable to its name.
+
+    GLEAM_CONFIG=prod {entrypoint} run
+",
+            directory = runtime_config::DIRECTORY_NAME,
+            base = runtime_config::BASE_CONFIG_NAME,
+        );
+    }
+
+    Ok(())
+}
+
+fn print_strip_report(report: &strip::Report) {
+    use crate::cli::file_size;
+    println!(
+        "
+The shipment was stripped from {before} to {after}.",
+        before = file_size(report.size_before),
+        after = file_size(report.size_after()),
+    );
+    if !report.removed_applications.is_empty() {
+        println!(
+            "  - Removed {count} unused applications ({names}), saving {size}",
+            count = report.removed_applications.len(),
+            names = report.removed_applications.iter().join(", "),
+            size = file_size(report.removed_applications_size),
+        );
+    }
+    if report.stripped_beam_files > 0 {
+        println!(
+            "  - Removed debug information from {count} BEAM files, saving {size}",
+            count = report.stripped_beam_files,
+            size = file_size(report.stripped_beam_size),
+        );
+    }
+    if report.linked_priv_files > 0 {
+        println!(
+            "  - Linked {count} duplicate priv files, saving {size}",
+            count = report.linked_priv_files,
+            size = file_size(report.linked_priv_size),
+        );
+    }
+}
+
+/// Generate an Erlang project for the package, with the generated Erlang
+/// source, application metadata, and the `rebar.config` and `mix.exs` files
+/// needed for a rebar3 or Mix project to depend on it from a path or git
+/// checkout without the package being published to Hex.
+pub(crate) fn erlang_project(output: Option<Utf8PathBuf>) -> Result<()> {
+    let paths = crate::find_project_paths()?;
+    let config = crate::config::root_config()?;
+    let out = match output {
+        Some(output) => output,
+        None => {
+            let out = paths.erlang_project_directory();
+            crate::fs::delete_directory(&out)?;
+            out
+        }
+    };
+
+    // Build project in production mode
+    let built = crate::build::main(
+        Options {
+            root_target_support: TargetSupport::Enforced,
+            warnings_as_errors: false,
+            verify_artefacts: false,
+            codegen: Codegen::All,
+            mode: Mode::Prod,
+            target: Some(Target::Erlang),
+        },
+        crate::build::download_dependencies()?,
+    )?;
+    let manifest =
+        crate::dependencies::read_manifest_from_disc(&crate::fs::ProjectIO::new(), &paths)?;
+
+    // Generated Erlang modules, headers, and the .app.src file
+    for (path, contents) in crate::publish::generated_erlang_files(&paths, &built.root_package)? {
+        crate::fs::write(&out.join(path), &contents)?;
+    }
+
+    // Erlang FFI modules and headers
+    let src = Utf8Path::new("src");
+    for file in crate::fs::native_files(&paths.src_directory())? {
+        if matches!(file.extension(), Some("erl" | "hrl")) {
+            let name = file.file_name().expect("erlang_project native file name");
+            crate::fs::write(&out.join(src).join(name), &crate::fs::read(&file)?)?;
+        }
+    }
+
+    let priv_directory = paths.root().join("priv");
+    if priv_directory.is_dir() {
+        crate::fs::mkdir(out.join("priv"))?;
+        crate::fs::copy_dir(&priv_directory, out.join("priv"))?;
+    }
+
+    crate::fs::write(&out.join("rebar.config"), &rebar_config(&config, &manifest))?;
+    crate::fs::write(&out.join("mix.exs"), &mix_exs(&config, &manifest)?)?;
+
+    crate::cli::print_exported(&config.name);
+
+    println!(
+        "
+Your Erlang project has been generated to {out}.
+
+It can be added as a dependency of a rebar3 or Mix project by committing it to
+a git repository, or by referring to its path from a Mix project.
+"
+    );
     Ok(())
 }
 
+/// A dependency of the package as it is written in the rebar3 and Mix
+/// configuration.
+#[derive(Debug)]
+struct ProjectDependency<'a> {
+    name: &'a str,
+    /// The OTP application name, if it differs from the Hex package name.
+    otp_app: Option<&'a str>,
+    /// Gleam packages on Hex are built by rebar3 when the project uses Mix.
+    is_gleam: bool,
+    requirement: &'a Requirement,
+    /// The commit a git dependency is locked to in the manifest.
+    commit: Option<&'a str>,
+}
+
+fn project_dependencies<'a>(
+    config: &'a PackageConfig,
+    manifest: &'a Manifest,
+) -> Vec<ProjectDependency<'a>> {
+    config
+        .dependencies
+        .iter()
+        .sorted_by(|(a, _), (b, _)| a.cmp(b))
+        .map(|(name, requirement)| {
+            let package = manifest.packages.iter().find(|p| &p.name == name);
+            ProjectDependency {
+                name,
+                otp_app: package
+                    .and_then(|p| p.otp_app.as_deref())
+                    .filter(|app| app != name),
+                is_gleam: package.is_some_and(|p| p.build_tools.iter().any(|t| t == "gleam")),
+                requirement,
+                commit: package.and_then(|p| match &p.source {
+                    ManifestPackageSource::Git { commit, .. } => Some(commit.as_str()),
+                    _ => None,
+                }),
+            }
+        })
+        .collect()
+}
+
+fn rebar_config(config: &PackageConfig, manifest: &Manifest) -> String {
+    let mut deps = vec![];
+    let mut comments = vec![];
+    for dependency in project_dependencies(config, manifest) {
+        let app = dependency.otp_app.unwrap_or(dependency.name);
+        if let Requirement::Hex {
+            organization: Some(organization),
+            ..
+        } = dependency.requirement
+        {
+            comments.push(format!(
+                "%% `{}` is from the Hex organization {organization}, whose repository \
+must be configured for rebar3_hex.\n",
+                dependency.name
+            ));
+        }
+        match dependency.requirement {
+            Requirement::Hex { version, .. } if dependency.otp_app.is_some() => deps.push(format!(
+                "    {{{app}, \"{version}\", {{pkg, {}}}}}",
+                dependency.name
+            )),
+            Requirement::Hex { version, .. } => deps.push(format!("    {{{app}, \"{version}\"}}")),
+            Requirement::Git { git, .. } => match dependency.commit {
+                Some(commit) => deps.push(format!(
+                    "    {{{app}, {{git, \"{git}\", {{ref, \"{commit}\"}}}}}}"
+                )),
+                None => deps.push(format!("    {{{app}, {{git, \"{git}\"}}}}")),
+            },
+            Requirement::Path { path, .. } => comments.push(format!(
+                "%% `{}` is a local dependency at {path} and must be added by hand.\n",
+                dependency.name
+            )),
+        }
+    }
+
+    format!(
+        "%% Generated by Gleam for {name} v{version}.\n{comments}
+{{erl_opts, [debug_info]}}.
+
+{{deps, [
+{deps}
+]}}.
+",
+        name = config.name,
+        version = config.version,
+        comments = comments.concat(),
+        deps = deps.join(",\n"),
+    )
+}
+
+fn mix_exs(config: &PackageConfig, manifest: &Manifest) -> Result<String> {
+    let mut deps = vec![];
+    let mut comments = vec![];
+    for dependency in project_dependencies(config, manifest) {
+        let app = dependency.otp_app.unwrap_or(dependency.name);
+        let mut options = vec![];
+        match dependency.requirement {
+            Requirement::Hex {
+                version,
+                organization,
+                ..
+            } => {
+                options.push(format!("\"{version}\""));
+                if dependency.otp_app.is_some() {
+                    options.push(format!("hex: {}", elixir_atom(dependency.name)));
+                }
+                if let Some(organization) = organization {
+                    options.push(format!("organization: \"{organization}\""));
+                }
+            }
+            Requirement::Git { git, .. } => {
+                options.push(format!("git: \"{git}\""));
+                if let Some(commit) = dependency.commit {
+                    options.push(format!("ref: \"{commit}\""));
+                }
+            }
+            Requirement::Path { path, .. } => {
+                comments.push(format!(
+                    "  # `{}` is a local dependency at {path} and must be added by hand.\n",
+                    dependency.name
+                ));
+                continue;
+            }
+        }
+        if dependency.is_gleam {
+            options.push("manager: :rebar3".into());
+        }
+        deps.push(format!(
+            "      {{{}, {}}}",
+            elixir_atom(app),
+            options.join(", ")
+        ));
+    }
+
+    let mut application = vec![];
+    if !config.erlang.extra_applications.is_empty() {
+        application.push(format!(
+            "extra_applications: [{}]",
+            config
+                .erlang
+                .extra_applications
+                .iter()
+                .map(|app| elixir_atom(app))
+                .join(", ")
+        ));
+    }
+    if !config.erlang.included_applications.is_empty() {
+        application.push(format!(
+            "included_applications: [{}]",
+            config
+                .erlang
+                .included_applications
+                .iter()
+                .map(|app| elixir_atom(app))
+                .join(", ")
+        ));
+    }
+    if !config.erlang.registered.is_empty() {
+        application.push(format!(
+            "registered: [{}]",
+            config
+                .erlang
+                .registered
+                .iter()
+                .map(|name| elixir_atom(name))
+                .join(", ")
+        ));
+    }
+    if !config.erlang.env.is_empty() {
+        let env = config
+            .erlang
+            .env
+            .iter()
+            .map(|(key, value)| {
+                let value = elixir_term(value)
+                    .ok_or_else(|| Error::InvalidErlangAppEnv { key: key.clone() })?;
+                Ok(format!("{{{}, {value}}}", elixir_atom(key)))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        application.push(format!("env: [{}]", env.join(", ")));
+    }
+    if let Some(module) = &config.erlang.application_start_module {
+        application.push(format!("mod: {{{}, []}}", elixir_atom(module)));
+    }
+
+    Ok(format!(
+        "# Generated by Gleam for {name} v{version}.
+defmodule {module}.MixProject do
+  use Mix.Project
+
+  def project do
+    [
+      app: {app},
+      version: \"{version}\",
+      language: :erlang,
+      erlc_paths: [\"src\"],
+      erlc_include_path: \"include\",
+      deps: deps()
+    ]
+  end
+
+  def application do
+    [{application}]
+  end
+
+{comments}  defp deps do
+    [
+{deps}
+    ]
+  end
+end
+",
+        name = config.name,
+        version = config.version,
+        module = config.name.to_upper_camel_case(),
+        app = elixir_atom(&config.name),
+        application = application.join(", "),
+        comments = if comments.is_empty() {
+            String::new()
+        } else {
+            comments.concat() + "\n"
+        },
+        deps = deps.join(",\n"),
+    ))
+}
+
+/// Renders a TOML value as an Elixir term, matching the Erlang terms written
+/// to the `.app` file.
+fn elixir_term(value: &toml::Value) -> Option<String> {
+    Some(match value {
+        toml::Value::String(string) => elixir_string(string),
+        toml::Value::Integer(int) => int.to_string(),
+        toml::Value::Float(float) => gleam_core::codegen::erlang_float(*float)?,
+        toml::Value::Boolean(bool) => bool.to_string(),
+        toml::Value::Datetime(datetime) => elixir_string(&datetime.to_string()),
+        toml::Value::Array(values) => {
+            let values: Option<Vec<_>> = values.iter().map(elixir_term).collect();
+            format!("[{}]", values?.join(", "))
+        }
+        toml::Value::Table(table) => {
+            let fields: Option<Vec<_>> = table
+                .iter()
+                .map(|(key, value)| {
+                    Some(format!("{} => {}", elixir_string(key), elixir_term(value)?))
+                })
+                .collect();
+            format!("%{{{}}}", fields?.join(", "))
+        }
+    })
+}
+
+fn elixir_string(string: &str) -> String {
+    let string = string
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace("#{", "\\#{");
+    format!("\"{string}\"")
+}
+
+fn elixir_atom(name: &str) -> EcoString {
+    let is_plain = name.starts_with(|c: char| c.is_ascii_lowercase() || c == '_')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '@');
+    if is_plain {
+        format!(":{name}").into()
+    } else {
+        format!(":\"{name}\"").into()
+    }
+}
+
+pub(crate) fn build_metadata(output: Utf8PathBuf, target: Option<Target>) -> Result<()> {
+    let paths = crate::find_project_paths()?;
+    let config = crate::config::root_config()?;
+    let manifest = crate::build::download_dependencies()?;
+    let io = crate::fs::ProjectIO::new();
+    let target = target.unwrap_or(config.target);
+
+    let metadata =
+        gleam_core::build::build_metadata::generate(&io, paths.root(), &config, &manifest, target)?;
+    let json = serde_json::to_string_pretty(&metadata).expect("JSON build metadata serialisation");
+    crate::fs::write(&output, &json)
+}
+
 pub fn hex_tarball() -> Result<()> {
     let paths = crate::find_project_paths()?;
     let config = crate::config::root_config()?;
@@ -127,6 +579,15 @@ pub fn typescript_prelude() -> Result<()> {
     Ok(())
 }
 
+pub fn package_config_schema() -> Result<()> {
+    let schema = gleam_core::config::schema::json_schema();
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&schema).expect("package config schema JSON")
+    );
+    Ok(())
+}
+
 pub fn package_interface(path: Utf8PathBuf) -> Result<()> {
     // Build the project
     let mut built = crate::build::main(
@@ -135,6 +596,7 @@ pub fn package_interface(path: Utf8PathBuf) -> Result<()> {
             target: None,
             codegen: Codegen::All,
             warnings_as_errors: false,
+            verify_artefacts: false,
             root_target_support: TargetSupport::Enforced,
         },
         crate::build::download_dependencies()?,
@@ -145,3 +607,232 @@ pub fn package_interface(path: Utf8PathBuf) -> Result<()> {
     crate::fs::write_outputs_under(&[out], crate::find_project_paths()?.root())?;
     Ok(())
 }
+
+pub fn project_interface(path: Utf8PathBuf) -> Result<()> {
+    let paths = crate::find_project_paths()?;
+    let manifest = crate::build::download_dependencies()?;
+    let dependencies: Vec<_> = manifest
+        .packages
+        .iter()
+        .filter(|package| package.build_tools.contains(&"gleam".into()))
+        .map(|package| crate::config::dependency_config(package, &paths))
+        .try_collect()?;
+
+    // Build the project
+    let mut built = crate::build::main(
+        Options {
+            mode: Mode::Prod,
+            target: None,
+            codegen: Codegen::All,
+            warnings_as_errors: false,
+            verify_artefacts: false,
+            root_target_support: TargetSupport::Enforced,
+        },
+        manifest,
+    )?;
+    built.root_package.attach_doc_and_module_comments();
+
+    let out = gleam_core::docs::generate_json_project_interface(path, &built, &dependencies);
+    crate::fs::write_outputs_under(&[out], paths.root())?;
+    Ok(())
+}
+
+#[cfg(test)]
+fn manifest_package(
+    name: &str,
+    build_tool: &str,
+    otp_app: Option<&str>,
+    source: ManifestPackageSource,
+) -> gleam_core::manifest::ManifestPackage {
+    gleam_core::manifest::ManifestPackage {
+        name: name.into(),
+        version: hexpm::version::Version::new(1, 0, 0),
+        build_tools: vec![build_tool.into()],
+        otp_app: otp_app.map(Into::into),
+        requirements: vec![],
+        source,
+    }
+}
+
+#[cfg(test)]
+fn hex_source() -> ManifestPackageSource {
+    ManifestPackageSource::Hex {
+        outer_checksum: gleam_core::manifest::Base16Checksum(vec![]),
+        organization: None,
+    }
+}
+
+#[cfg(test)]
+fn project_config() -> (PackageConfig, Manifest) {
+    let config = PackageConfig {
+        name: "my_app".into(),
+        version: hexpm::version::Version::new(1, 2, 3),
+        dependencies: [
+            (
+                "anvil".into(),
+                Requirement::hex_organization("~> 1.0", "acme"),
+            ),
+            (
+                "gleam_stdlib".into(),
+                Requirement::hex(">= 0.34.0 and < 2.0.0"),
+            ),
+            ("jason".into(), Requirement::hex("~> 1.4")),
+            (
+                "wibble".into(),
+                Requirement::git("https://example.com/wibble.git"),
+            ),
+            ("wobble".into(), Requirement::path("../wobble")),
+        ]
+        .into(),
+        erlang: gleam_core::config::ErlangConfig {
+            application_start_module: Some("my_app@application".into()),
+            extra_applications: vec!["inets".into(), "ssl".into()],
+            ..Default::default()
+        },
+        ..PackageConfig::default()
+    };
+    let wibble_source = ManifestPackageSource::Git {
+        repo: "https://example.com/wibble.git".into(),
+        commit: "bd9fe02f72250e6a136967917bcb1bdccaffa3c8".into(),
+    };
+    let anvil_source = ManifestPackageSource::Hex {
+        outer_checksum: gleam_core::manifest::Base16Checksum(vec![]),
+        organization: Some("acme".into()),
+    };
+    let manifest = Manifest {
+        requirements: config.dependencies.clone(),
+        packages: vec![
+            manifest_package("anvil", "gleam", None, anvil_source),
+            manifest_package("gleam_stdlib", "gleam", None, hex_source()),
+            manifest_package("jason", "mix", None, hex_source()),
+            manifest_package("wibble", "gleam", None, wibble_source),
+        ],
+    };
+    (config, manifest)
+}
+
+#[test]
+fn erlang_project_rebar_config() {
+    let (config, manifest) = project_config();
+    assert_eq!(
+        rebar_config(&config, &manifest),
+        r#"%% Generated by Gleam for my_app v1.2.3.
+%% `anvil` is from the Hex organization acme, whose repository must be configured for rebar3_hex.
+%% `wobble` is a local dependency at ../wobble and must be added by hand.
+
+{erl_opts, [debug_info]}.
+
+{deps, [
+    {anvil, "~> 1.0"},
+    {gleam_stdlib, ">= 0.34.0 and < 2.0.0"},
+    {jason, "~> 1.4"},
+    {wibble, {git, "https://example.com/wibble.git", {ref, "bd9fe02f72250e6a136967917bcb1bdccaffa3c8"}}}
+]}.
+"#
+    );
+}
+
+#[test]
+fn erlang_project_mix_exs() {
+    let (config, manifest) = project_config();
+    assert_eq!(
+        mix_exs(&config, &manifest).expect("mix.exs"),
+        r#"# Generated by Gleam for my_app v1.2.3.
+defmodule MyApp.MixProject do
+  use Mix.Project
+
+  def project do
+    [
+      app: :my_app,
+      version: "1.2.3",
+      language: :erlang,
+      erlc_paths: ["src"],
+      erlc_include_path: "include",
+      deps: deps()
+    ]
+  end
+
+  def application do
+    [extra_applications: [:inets, :ssl], mod: {:my_app@application, []}]
+  end
+
+  # `wobble` is a local dependency at ../wobble and must be added by hand.
+
+  defp deps do
+    [
+      {:anvil, "~> 1.0", organization: "acme", manager: :rebar3},
+      {:gleam_stdlib, ">= 0.34.0 and < 2.0.0", manager: :rebar3},
+      {:jason, "~> 1.4"},
+      {:wibble, git: "https://example.com/wibble.git", ref: "bd9fe02f72250e6a136967917bcb1bdccaffa3c8", manager: :rebar3}
+    ]
+  end
+end
+"#
+    );
+}
+
+#[test]
+fn erlang_project_dependency_with_different_otp_app() {
+    let config = PackageConfig {
+        name: "my_app".into(),
+        dependencies: [("elixir_make".into(), Requirement::hex("~> 0.6"))].into(),
+        ..PackageConfig::default()
+    };
+    let manifest = Manifest {
+        requirements: config.dependencies.clone(),
+        packages: vec![manifest_package(
+            "elixir_make",
+            "mix",
+            Some("make"),
+            hex_source(),
+        )],
+    };
+    assert!(rebar_config(&config, &manifest).contains(r#"{make, "~> 0.6", {pkg, elixir_make}}"#));
+    assert!(mix_exs(&config, &manifest)
+        .expect("mix.exs")
+        .contains(r#"{:make, "~> 0.6", hex: :elixir_make}"#));
+}
+
+#[test]
+fn erlang_project_mix_exs_application_metadata() {
+    let toml = r#"
+name = "my_app"
+
+[erlang]
+included_applications = ["mnesia"]
+registered = ["my_app_sup"]
+
+[erlang.env]
+greeting = "Hello, #{name}"
+limits = { burst = 1e100 }
+"#;
+    let config: PackageConfig = toml::from_str(toml).expect("config");
+    let manifest = Manifest {
+        requirements: Default::default(),
+        packages: vec![],
+    };
+    assert!(mix_exs(&config, &manifest).expect("mix.exs").contains(
+        r#"[included_applications: [:mnesia], registered: [:my_app_sup], env: [{:greeting, "Hello, \#{name}"}, {:limits, %{"burst" => 1.0e100}}]]"#
+    ));
+}
+
+#[test]
+fn erlang_project_mix_exs_invalid_env() {
+    let toml = r#"
+name = "my_app"
+
+[erlang.env]
+ratio = nan
+"#;
+    let config: PackageConfig = toml::from_str(toml).expect("config");
+    let manifest = Manifest {
+        requirements: Default::default(),
+        packages: vec![],
+    };
+    assert_eq!(
+        mix_exs(&config, &manifest),
+        Err(Error::InvalidErlangAppEnv {
+            key: "ratio".into()
+        })
+    );
+}
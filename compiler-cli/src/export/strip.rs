@@ -0,0 +1,391 @@
+//! Making an Erlang shipment smaller for `gleam export erlang-shipment
+//! --strip`, such as for a container image:
+//!
+//! - The applications the root application does not depend on, directly or
+//!   through other applications, are removed. Dependencies are read from the
+//!   `applications` and `included_applications` of each `.app` file.
+//! - The chunks of each BEAM file that are not needed to load and run it, such
+//!   as debug information and documentation, are removed, as `beam_lib:strip`
+//!   does.
+//! - Files in `priv` directories with the same contents are replaced with hard
+//!   links to one of them.
+
+use std::{
+    collections::{HashMap, HashSet},
+    sync::OnceLock,
+};
+
+use camino::{Utf8Path, Utf8PathBuf};
+use ecow::EcoString;
+use gleam_core::{
+    error::{FileIoAction, FileKind},
+    Error, Result,
+};
+use itertools::Itertools;
+use regex::Regex;
+use sha2::{Digest, Sha256};
+
+/// The chunks of a BEAM file that are kept when it is stripped.
+const KEPT_CHUNKS: &[&[u8; 4]] = &[
+    b"AtU8", b"Atom", b"Code", b"StrT", b"ImpT", b"ExpT", b"FunT", b"LitT", b"Line", b"Meta",
+    b"Type",
+];
+
+/// What was removed from a shipment by stripping it.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Report {
+    pub size_before: u64,
+    pub removed_applications: Vec<EcoString>,
+    pub removed_applications_size: u64,
+    pub stripped_beam_files: usize,
+    pub stripped_beam_size: u64,
+    pub linked_priv_files: usize,
+    pub linked_priv_size: u64,
+}
+
+impl Report {
+    pub fn size_after(&self) -> u64 {
+        self.size_before
+            - self.removed_applications_size
+            - self.stripped_beam_size
+            - self.linked_priv_size
+    }
+}
+
+/// Strips the shipment in `directory`, which has a directory for each
+/// application, and is run by starting `root_application`.
+pub fn strip(directory: &Utf8Path, root_application: &str) -> Result<Report> {
+    let mut report = Report::default();
+    let applications = application_directories(directory)?;
+    for application in applications.values() {
+        report.size_before += directory_size(application)?;
+    }
+
+    // Unused applications
+    let mut dependencies = HashMap::new();
+    for (name, application) in &applications {
+        let app_file = application.join("ebin").join(format!("{name}.app"));
+        let dependencies_of = if app_file.is_file() {
+            app_dependencies(&crate::fs::read(&app_file)?)
+        } else {
+            vec![]
+        };
+        let _ = dependencies.insert(name.clone(), dependencies_of);
+    }
+    let used = used_applications(root_application, &dependencies);
+    for (name, application) in &applications {
+        if used.contains(name) {
+            continue;
+        }
+        report.removed_applications_size += directory_size(application)?;
+        report.removed_applications.push(name.clone());
+        crate::fs::delete_directory(application)?;
+    }
+    report.removed_applications.sort();
+
+    // Debug information
+    for (name, application) in &applications {
+        if !used.contains(name) {
+            continue;
+        }
+        for path in files(&application.join("ebin"))? {
+            if path.extension() != Some("beam") {
+                continue;
+            }
+            let beam = crate::fs::read_bytes(&path)?;
+            let Some(stripped) = strip_beam(&beam) else {
+                continue;
+            };
+            if stripped.len() < beam.len() {
+                report.stripped_beam_files += 1;
+                report.stripped_beam_size += (beam.len() - stripped.len()) as u64;
+                crate::fs::write_bytes(&path, &stripped)?;
+            }
+        }
+    }
+
+    // Duplicate priv files
+    let mut originals: HashMap<(u64, Vec<u8>), Utf8PathBuf> = HashMap::new();
+    let priv_files = applications
+        .iter()
+        .filter(|(name, _)| used.contains(*name))
+        .map(|(_, application)| files(&application.join("priv")))
+        .flatten_ok()
+        .collect::<Result<Vec<_>>>()?;
+    for path in priv_files {
+        let bytes = crate::fs::read_bytes(&path)?;
+        let key = (bytes.len() as u64, Sha256::digest(&bytes).to_vec());
+        match originals.get(&key) {
+            Some(original) => {
+                crate::fs::delete_file(&path)?;
+                crate::fs::hardlink(original, &path)?;
+                report.linked_priv_files += 1;
+                report.linked_priv_size += key.0;
+            }
+            None => {
+                let _ = originals.insert(key, path);
+            }
+        }
+    }
+
+    Ok(report)
+}
+
+/// The directory of each application in the shipment, by name.
+fn application_directories(directory: &Utf8Path) -> Result<HashMap<EcoString, Utf8PathBuf>> {
+    let mut applications = HashMap::new();
+    for entry in crate::fs::read_dir(directory)?.filter_map(Result::ok) {
+        let path = entry.into_path();
+        // Applications have an ebin directory, unlike the config directory.
+        if !path.join("ebin").is_dir() {
+            continue;
+        }
+        let name = path.file_name().expect("Directory name").into();
+        let _ = applications.insert(name, path);
+    }
+    Ok(applications)
+}
+
+/// The applications that an application depends on, from its `.app` file.
+pub fn app_dependencies(app_file: &str) -> Vec<EcoString> {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    let re = RE.get_or_init(|| {
+        Regex::new(r"\{\s*(?:included_)?applications\s*,\s*\[([^\]]*)\]")
+            .expect("app_dependencies() RE regex")
+    });
+    re.captures_iter(app_file)
+        .filter_map(|captures| captures.get(1))
+        .flat_map(|list| list.as_str().split(','))
+        .map(|name| name.trim().trim_matches('\''))
+        .filter(|name| !name.is_empty())
+        .map(EcoString::from)
+        .collect()
+}
+
+/// The applications the root application depends on, directly or through
+/// other applications, including itself.
+pub fn used_applications(
+    root: &str,
+    dependencies: &HashMap<EcoString, Vec<EcoString>>,
+) -> HashSet<EcoString> {
+    let mut used = HashSet::new();
+    let mut to_visit = vec![EcoString::from(root)];
+    while let Some(application) = to_visit.pop() {
+        if !used.insert(application.clone()) {
+            continue;
+        }
+        if let Some(dependencies) = dependencies.get(&application) {
+            to_visit.extend(dependencies.iter().cloned());
+        }
+    }
+    used
+}
+
+/// Removes the chunks of a BEAM file that are not needed to load it. Returns
+/// `None` if the file is not a BEAM file.
+pub fn strip_beam(beam: &[u8]) -> Option<Vec<u8>> {
+    if beam.get(0..4)? != b"FOR1" || beam.get(8..12)? != b"BEAM" {
+        return None;
+    }
+    let mut chunks = vec![];
+    let mut rest = beam.get(12..)?;
+    while !rest.is_empty() {
+        let id = rest.get(0..4)?;
+        let size = u32::from_be_bytes(rest.get(4..8)?.try_into().ok()?) as usize;
+        // Chunks are padded to a multiple of four bytes.
+        let padded = 8 + size.div_ceil(4) * 4;
+        let chunk = rest.get(..padded).or_else(|| rest.get(..8 + size))?;
+        if KEPT_CHUNKS.iter().any(|kept| kept.as_slice() == id) {
+            chunks.extend_from_slice(chunk);
+            // The last chunk may not be padded in the original file.
+            chunks.resize(chunks.len().div_ceil(4) * 4, 0);
+        }
+        rest = rest.get(chunk.len()..)?;
+    }
+
+    let mut stripped = Vec::with_capacity(chunks.len() + 12);
+    stripped.extend_from_slice(b"FOR1");
+    stripped.extend_from_slice(&(chunks.len() as u32 + 4).to_be_bytes());
+    stripped.extend_from_slice(b"BEAM");
+    stripped.extend_from_slice(&chunks);
+    Some(stripped)
+}
+
+/// The files within a directory, which may not exist.
+fn files(directory: &Utf8Path) -> Result<Vec<Utf8PathBuf>> {
+    if !directory.is_dir() {
+        return Ok(vec![]);
+    }
+    let mut files = vec![];
+    for entry in walkdir::WalkDir::new(directory).sort_by_file_name() {
+        let entry = entry.map_err(|e| Error::FileIo {
+            action: FileIoAction::Read,
+            kind: FileKind::Directory,
+            path: directory.to_path_buf(),
+            err: Some(e.to_string()),
+        })?;
+        if entry.file_type().is_file() {
+            files.push(Utf8PathBuf::from_path_buf(entry.into_path()).expect("Non Utf-8 Path"));
+        }
+    }
+    Ok(files)
+}
+
+fn directory_size(directory: &Utf8Path) -> Result<u64> {
+    let mut size = 0;
+    for path in files(directory)? {
+        size += path
+            .metadata()
+            .map_err(|e| Error::FileIo {
+                action: FileIoAction::ReadMetadata,
+                kind: FileKind::File,
+                path: path.clone(),
+                err: Some(e.to_string()),
+            })?
+            .len();
+    }
+    Ok(size)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chunk(id: &[u8; 4], data: &[u8]) -> Vec<u8> {
+        let mut chunk = id.to_vec();
+        chunk.extend_from_slice(&(data.len() as u32).to_be_bytes());
+        chunk.extend_from_slice(data);
+        chunk.resize(chunk.len().div_ceil(4) * 4, 0);
+        chunk
+    }
+
+    fn beam(chunks: &[Vec<u8>]) -> Vec<u8> {
+        let chunks = chunks.concat();
+        let mut beam = b"FOR1".to_vec();
+        beam.extend_from_slice(&(chunks.len() as u32 + 4).to_be_bytes());
+        beam.extend_from_slice(b"BEAM");
+        beam.extend_from_slice(&chunks);
+        beam
+    }
+
+    #[test]
+    fn debug_chunks_are_stripped() {
+        let atoms = chunk(b"AtU8", b"wibble");
+        let code = chunk(b"Code", b"some code");
+        let debug_info = chunk(b"Dbgi", b"lots of debug information");
+        let docs = chunk(b"Docs", b"documentation");
+        assert_eq!(
+            strip_beam(&beam(&[atoms.clone(), debug_info, code.clone(), docs])),
+            Some(beam(&[atoms, code]))
+        );
+    }
+
+    #[test]
+    fn stripped_beam_is_unchanged_by_stripping() {
+        let beam = beam(&[chunk(b"AtU8", b"wibble"), chunk(b"Code", b"code")]);
+        assert_eq!(strip_beam(&beam), Some(beam));
+    }
+
+    #[test]
+    fn other_files_are_not_stripped() {
+        assert_eq!(strip_beam(b"-module(wibble)."), None);
+        assert_eq!(strip_beam(b""), None);
+        // A chunk that runs past the end of the file
+        let mut truncated = beam(&[chunk(b"Code", b"code")]);
+        truncated.truncate(18);
+        assert_eq!(strip_beam(&truncated), None);
+    }
+
+    #[test]
+    fn app_file_dependencies() {
+        let app = r#"{application, wibble, [
+    {vsn, "1.0.0"},
+    {applications, [gleam_stdlib,
+                    'wobble']},
+    {included_applications, [jiffy]},
+    {description, "Wibble [and] wobble"},
+    {modules, [wibble]},
+    {registered, []}
+]}."#;
+        assert_eq!(
+            app_dependencies(app),
+            vec![
+                EcoString::from("gleam_stdlib"),
+                "wobble".into(),
+                "jiffy".into()
+            ]
+        );
+        assert_eq!(
+            app_dependencies("{application,ranch,[{applications,[kernel,stdlib,ssl]}]}."),
+            vec![EcoString::from("kernel"), "stdlib".into(), "ssl".into()]
+        );
+    }
+
+    #[test]
+    fn shipment_is_stripped() {
+        let tmp = tempfile::tempdir().expect("tempdir");
+        let out = Utf8Path::from_path(tmp.path()).expect("utf8 path");
+        let write = |path: &str, bytes: &[u8]| {
+            let path = out.join(path);
+            crate::fs::mkdir(path.parent().expect("parent")).expect("mkdir");
+            crate::fs::write_bytes(&path, bytes).expect("write");
+        };
+        let code = chunk(b"Code", b"code");
+        let debug_info = chunk(b"Dbgi", b"debug information");
+        write(
+            "app/ebin/app.app",
+            b"{application, app, [{applications, [wibble]}]}.",
+        );
+        write("app/ebin/app.beam", &beam(&[code.clone(), debug_info]));
+        write("app/priv/logo.png", b"logo");
+        write("wibble/ebin/wibble.app", b"{application, wibble, []}.");
+        write("wibble/priv/images/logo.png", b"logo");
+        write(
+            "gleeunit/ebin/gleeunit.app",
+            b"{application, gleeunit, []}.",
+        );
+        write(
+            "gleeunit/ebin/gleeunit.beam",
+            &beam(std::slice::from_ref(&code)),
+        );
+
+        let report = strip(out, "app").expect("strip");
+        assert_eq!(
+            report.removed_applications,
+            vec![EcoString::from("gleeunit")]
+        );
+        assert_eq!(report.stripped_beam_files, 1);
+        assert_eq!(report.linked_priv_files, 1);
+        assert_eq!(report.linked_priv_size, 4);
+        assert!(!out.join("gleeunit").exists());
+        assert_eq!(
+            crate::fs::read_bytes(out.join("app/ebin/app.beam")).expect("read"),
+            beam(&[code])
+        );
+        assert!(same_file::is_same_file(
+            out.join("app/priv/logo.png"),
+            out.join("wibble/priv/images/logo.png")
+        )
+        .expect("same file"));
+    }
+
+    #[test]
+    fn applications_used_by_the_root() {
+        let dependencies = HashMap::from([
+            ("app".into(), vec!["wibble".into(), "kernel".into()]),
+            ("wibble".into(), vec!["wobble".into()]),
+            ("wobble".into(), vec!["wibble".into()]),
+            ("gleeunit".into(), vec!["kernel".into()]),
+        ]);
+        let used = used_applications("app", &dependencies);
+        assert_eq!(
+            used.into_iter().sorted().collect_vec(),
+            vec![
+                EcoString::from("app"),
+                "kernel".into(),
+                "wibble".into(),
+                "wobble".into()
+            ]
+        );
+    }
+}
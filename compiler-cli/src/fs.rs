@@ -21,6 +21,7 @@ use std::{
 };
 
 use camino::{ReadDirUtf8, Utf8Path, Utf8PathBuf};
+use itertools::Itertools;
 
 use crate::{dependencies::UseManifest, lsp::LspLocker};
 
@@ -62,10 +63,6 @@ impl ProjectIO {
     pub fn new() -> Self {
         Self
     }
-
-    pub fn boxed() -> Box<Self> {
-        Box::new(Self::new())
-    }
 }
 
 impl FileSystemReader for ProjectIO {
@@ -76,6 +73,7 @@ impl FileSystemReader for ProjectIO {
         let dir = dir.to_path_buf();
         walkdir::WalkDir::new(dir)
             .follow_links(true)
+            .sort_by_file_name()
             .into_iter()
             .filter_map(Result::ok)
             .filter(|e| e.file_type().is_file())
@@ -92,6 +90,7 @@ impl FileSystemReader for ProjectIO {
         let dir = dir.to_path_buf();
         walkdir::WalkDir::new(dir)
             .follow_links(true)
+            .sort_by_file_name()
             .into_iter()
             .filter_map(Result::ok)
             .filter(|e| e.file_type().is_file())
@@ -247,7 +246,7 @@ pub fn delete_directory(dir: &Utf8Path) -> Result<(), Error> {
 }
 
 pub fn delete_file(file: &Utf8Path) -> Result<(), Error> {
-    tracing::trace!("Deleting file {:?}", file);
+    tracing::trace!(path = ?file, "deleting_file");
     if file.exists() {
         std::fs::remove_file(file).map_err(|e| Error::FileIo {
             action: FileIoAction::Delete,
@@ -256,7 +255,7 @@ pub fn delete_file(file: &Utf8Path) -> Result<(), Error> {
             err: Some(e.to_string()),
         })?;
     } else {
-        tracing::trace!("Did not exist for deletion: {:?}", file);
+        tracing::trace!(path = ?file, "file_to_delete_does_not_exist");
     }
     Ok(())
 }
@@ -374,6 +373,7 @@ pub fn gleam_files_excluding_gitignore(dir: &Utf8Path) -> impl Iterator<Item = U
     ignore::WalkBuilder::new(dir)
         .follow_links(true)
         .require_git(false)
+        .sort_by_file_name(Ord::cmp)
         .filter_entry(|e| !is_gleam_build_dir(e))
         .build()
         .filter_map(Result::ok)
@@ -390,13 +390,15 @@ pub fn native_files(dir: &Utf8Path) -> Result<impl Iterator<Item = Utf8PathBuf>
         .filter(|path| {
             let extension = path.extension().unwrap_or_default();
             matches!(extension, "erl" | "hrl" | "ex" | "js" | "mjs" | "ts")
-        }))
+        })
+        .sorted())
 }
 
 pub fn private_files_excluding_gitignore(dir: &Utf8Path) -> impl Iterator<Item = Utf8PathBuf> + '_ {
     ignore::WalkBuilder::new(dir)
         .follow_links(true)
         .require_git(false)
+        .sort_by_file_name(Ord::cmp)
         .build()
         .filter_map(Result::ok)
         .filter(|e| e.file_type().map(|t| t.is_file()).unwrap_or(false))
@@ -411,7 +413,8 @@ pub fn erlang_files(dir: &Utf8Path) -> Result<impl Iterator<Item = Utf8PathBuf>
         .filter(|path| {
             let extension = path.extension().unwrap_or_default();
             extension == "erl" || extension == "hrl"
-        }))
+        })
+        .sorted())
 }
 
 pub fn create_tar_archive(outputs: Vec<OutputFile>) -> Result<Vec<u8>, Error> {
@@ -561,6 +564,26 @@ pub fn copy_dir(
 ) -> Result<(), Error> {
     tracing::trace!(from=?path, to=?to, "copying_directory");
 
+    // A destination that is a link, such as one made by an earlier version of
+    // Gleam, is replaced rather than having files copied through it into the
+    // directory it points to.
+    let is_link = std::fs::symlink_metadata(to.as_ref())
+        .map(|metadata| metadata.file_type().is_symlink())
+        .unwrap_or(false);
+    if is_link {
+        #[cfg(target_family = "windows")]
+        let result = std::fs::remove_dir(to.as_ref());
+        #[cfg(not(target_family = "windows"))]
+        let result = std::fs::remove_file(to.as_ref());
+        result.map_err(|err| Error::FileIo {
+            action: FileIoAction::Delete,
+            kind: FileKind::File,
+            path: Utf8PathBuf::from(to.as_ref()),
+            err: Some(err.to_string()),
+        })?;
+        mkdir(to.as_ref())?;
+    }
+
     // TODO: include the destination in the error message
     fs_extra::dir::copy(
         path.as_ref(),
@@ -671,6 +694,161 @@ pub fn git_init(path: &Utf8Path) -> Result<(), Error> {
     }
 }
 
+/// The commit checked out in the git repository at the given path, if there
+/// is one there.
+pub fn git_head(path: &Utf8Path) -> Result<Option<String>, Error> {
+    tracing::trace!(path=?path, "reading_git_head");
+
+    // Without this check git would look for a repository in the parent
+    // directories, such as the project's own.
+    if !path.join(".git").exists() {
+        return Ok(None);
+    }
+
+    let result = std::process::Command::new("git")
+        .args(["rev-parse", "HEAD"])
+        .stdin(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .current_dir(path)
+        .output();
+
+    match result {
+        Ok(output) if output.status.success() => {
+            Ok(Some(String::from_utf8_lossy(&output.stdout).trim().into()))
+        }
+        Ok(_) => Ok(None),
+        Err(error) => match error.kind() {
+            io::ErrorKind::NotFound => Err(Error::ShellProgramNotFound {
+                program: "git".into(),
+            }),
+            other => Err(Error::ShellCommand {
+                program: "git".into(),
+                err: Some(other),
+            }),
+        },
+    }
+}
+
+/// Runs git with the given arguments in the given path, returning its output
+/// whether or not it succeeded.
+pub fn git_output(path: &Utf8Path, args: &[&str]) -> Result<std::process::Output, Error> {
+    tracing::trace!(path=?path, args=?args, "running_git");
+
+    std::process::Command::new("git")
+        .args(args)
+        .stdin(std::process::Stdio::null())
+        .current_dir(path)
+        .output()
+        .map_err(|error| match error.kind() {
+            io::ErrorKind::NotFound => Error::ShellProgramNotFound {
+                program: "git".into(),
+            },
+            other => Error::ShellCommand {
+                program: "git".into(),
+                err: Some(other),
+            },
+        })
+}
+
+/// The files that changed in the git repository at the given path since the
+/// given ref, including those that are not yet tracked, relative to the path.
+pub fn git_changed_files(path: &Utf8Path, reference: &str) -> Result<Vec<Utf8PathBuf>, Error> {
+    tracing::trace!(path=?path, reference=reference, "finding_git_changed_files");
+
+    let diff = ["diff", "--name-only", "--relative", reference, "--"];
+    let untracked = ["ls-files", "--others", "--exclude-standard"];
+    let mut files = vec![];
+    for args in [&diff[..], &untracked[..]] {
+        let result = std::process::Command::new("git")
+            .args(args)
+            .stdin(std::process::Stdio::null())
+            .current_dir(path)
+            .output();
+        let output = match result {
+            Ok(output) if output.status.success() => output,
+            Ok(output) => {
+                eprint!("{}", String::from_utf8_lossy(&output.stderr));
+                return Err(Error::ShellCommand {
+                    program: "git".into(),
+                    err: None,
+                });
+            }
+            Err(error) => match error.kind() {
+                io::ErrorKind::NotFound => {
+                    return Err(Error::ShellProgramNotFound {
+                        program: "git".into(),
+                    })
+                }
+                other => {
+                    return Err(Error::ShellCommand {
+                        program: "git".into(),
+                        err: Some(other),
+                    })
+                }
+            },
+        };
+        files.extend(
+            String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .map(Utf8PathBuf::from),
+        );
+    }
+    Ok(files)
+}
+
+/// Checks out a branch, tag, or commit of a git repository into the given
+/// directory, replacing anything already there. Returns false if git could
+/// not check it out.
+pub fn git_checkout(repo: &str, reference: &str, path: &Utf8Path) -> Result<bool, Error> {
+    tracing::trace!(repo=repo, reference=reference, path=?path, "checking_out_git_repository");
+
+    delete_directory(path)?;
+    mkdir(path)?;
+    let io = ProjectIO::new();
+    // `--` stops git reading a repository or reference from a dependency's
+    // `gleam.toml` as an option.
+    let commands = [
+        vec!["init", "--quiet"],
+        vec!["fetch", "--quiet", "--depth", "1", "--", repo, reference],
+        vec!["checkout", "--quiet", "--detach", "FETCH_HEAD"],
+    ];
+    for args in commands {
+        let args: Vec<String> = args.into_iter().map(String::from).collect();
+        if io.exec("git", &args, &[], Some(path), Stdio::Null)? != 0 {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+/// Whether a commit can still be fetched from a git repository, found by
+/// fetching it into a scratch repository at the given path, which is deleted
+/// afterwards.
+pub fn git_commit_reachable(repo: &str, commit: &str, scratch: &Utf8Path) -> Result<bool, Error> {
+    tracing::trace!(
+        repo = repo,
+        commit = commit,
+        "checking_git_commit_reachable"
+    );
+
+    delete_directory(scratch)?;
+    mkdir(scratch)?;
+    let io = ProjectIO::new();
+    let mut reachable = true;
+    for args in [
+        vec!["init", "--quiet"],
+        vec!["fetch", "--quiet", "--depth", "1", "--", repo, commit],
+    ] {
+        let args: Vec<String> = args.into_iter().map(String::from).collect();
+        if io.exec("git", &args, &[], Some(scratch), Stdio::Null)? != 0 {
+            reachable = false;
+            break;
+        }
+    }
+    delete_directory(scratch)?;
+    Ok(reachable)
+}
+
 pub fn canonicalise(path: &Utf8Path) -> Result<Utf8PathBuf, Error> {
     std::fs::canonicalize(path)
         .map_err(|err| Error::FileIo {
@@ -689,7 +867,7 @@ impl WarningEmitterIO for ConsoleWarningEmitter {
     fn emit_warning(&self, warning: Warning) {
         let buffer_writer = crate::cli::stderr_buffer_writer();
         let mut buffer = buffer_writer.buffer();
-        warning.pretty(&mut buffer);
+        warning.pretty(&mut buffer, crate::cli::theme());
         buffer_writer
             .print(&buffer)
             .expect("Writing warning to stderr");
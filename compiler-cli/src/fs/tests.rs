@@ -99,3 +99,61 @@ fn is_gleam_path_test() {
         Utf8Path::new("/some-prefix/")
     ));
 }
+
+#[test]
+fn files_are_listed_in_order() {
+    use gleam_core::io::FileSystemReader;
+
+    let tmp_dir = tempfile::tempdir().unwrap();
+    let path = Utf8Path::from_path(tmp_dir.path()).expect("Non Utf-8 Path");
+    let names = ["wobble.gleam", "b/wibble.gleam", "a.gleam", "wibble.gleam"];
+    for name in names {
+        super::write(&path.join(name), "").unwrap();
+    }
+    for name in ["wobble.erl", "a.hrl", "wibble.mjs"] {
+        super::write(&path.join(name), "").unwrap();
+    }
+
+    let relative = |files: Vec<camino::Utf8PathBuf>| {
+        files
+            .into_iter()
+            .map(|file| file.strip_prefix(path).unwrap().to_string())
+            .collect::<Vec<_>>()
+    };
+    let expected = ["a.gleam", "b/wibble.gleam", "wibble.gleam", "wobble.gleam"];
+    assert_eq!(
+        relative(super::ProjectIO::new().gleam_source_files(path)),
+        expected
+    );
+    assert_eq!(
+        relative(super::gleam_files_excluding_gitignore(path).collect()),
+        expected
+    );
+    assert_eq!(
+        relative(super::native_files(path).unwrap().collect()),
+        ["a.hrl", "wibble.mjs", "wobble.erl"]
+    );
+    assert_eq!(
+        relative(super::erlang_files(path).unwrap().collect()),
+        ["a.hrl", "wobble.erl"]
+    );
+}
+
+#[cfg(not(target_family = "windows"))]
+#[test]
+fn copy_dir_replaces_linked_destination() {
+    let tmp_dir = tempfile::tempdir().unwrap();
+    let path = Utf8Path::from_path(tmp_dir.path()).expect("Non Utf-8 Path");
+    let source = path.join("source");
+    let linked = path.join("linked");
+    let dest = path.join("dest");
+    super::write(&source.join("a.beam"), "a").unwrap();
+    super::mkdir(&linked).unwrap();
+    super::symlink_dir(&linked, &dest).unwrap();
+
+    super::copy_dir(&source, &dest).unwrap();
+    assert!(!dest.is_symlink());
+    assert_eq!(super::read(dest.join("a.beam")).unwrap(), "a");
+    // Nothing was written into the directory the link pointed to
+    assert!(!linked.join("a.beam").exists());
+}
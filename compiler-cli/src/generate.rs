@@ -0,0 +1,127 @@
+use std::{collections::HashSet, process::Command};
+
+use ecow::EcoString;
+use gleam_core::{build::Target, error::Error, paths::ProjectPaths, Result};
+use itertools::Itertools;
+
+/// Generate skeleton Erlang and JavaScript modules for the `@external`
+/// functions of a module in the project, keeping any existing functions.
+pub(crate) fn ffi(module: String, target: Option<Target>) -> Result<()> {
+    let paths = crate::find_project_paths()?;
+    let file_name = format!("{module}.gleam");
+    let (root, path) = [paths.src_directory(), paths.test_directory()]
+        .into_iter()
+        .map(|directory| {
+            let path = directory.join(&file_name);
+            (directory, path)
+        })
+        .find(|(_, path)| path.is_file())
+        .ok_or_else(|| Error::ModuleDoesNotExist {
+            module: module.as_str().into(),
+            suggestion: None,
+        })?;
+
+    let external_modules = match target {
+        Some(Target::JavaScript) => HashSet::new(),
+        Some(Target::Erlang) | None => {
+            let mut modules = otp_modules()?;
+            modules.extend(dependency_modules(&paths));
+            modules
+        }
+    };
+    let src = crate::fs::read(&path)?;
+    let io = crate::fs::ProjectIO::new();
+    let files =
+        gleam_core::ffi_stubs::generate(&io, &root, &path, &src.into(), target, &external_modules)?;
+
+    if files.is_empty() {
+        println!("All the externals of {module} are already implemented.");
+    }
+    for file in files {
+        crate::fs::write_output(&file)?;
+        let path = file.path.strip_prefix(paths.root()).unwrap_or(&file.path);
+        crate::cli::print_generated(path.as_str());
+    }
+    Ok(())
+}
+
+/// The Erlang modules of the downloaded dependency packages: those of their
+/// `.erl` files and the ones their Gleam modules are compiled to.
+fn dependency_modules(paths: &ProjectPaths) -> HashSet<EcoString> {
+    let directory = paths.build_packages_directory();
+    if !directory.is_dir() {
+        return HashSet::new();
+    }
+    crate::fs::private_files_excluding_gitignore(&directory)
+        .filter_map(|path| {
+            let relative = path.strip_prefix(&directory).ok()?;
+            match relative.extension() {
+                Some("erl") => relative.file_stem().map(EcoString::from),
+                Some("gleam") => {
+                    let module = relative.with_extension("");
+                    let mut components = module.iter();
+                    let _package = components.next()?;
+                    (components.next()? == "src").then(|| components.join("@").into())
+                }
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+/// The modules of the OTP applications of the installed Erlang, which
+/// externals can refer to without them being part of the project.
+fn otp_modules() -> Result<HashSet<EcoString>> {
+    let output = Command::new("erl")
+        .args([
+            "-noshell",
+            "-eval",
+            "[io:format(\"~s~n\", [M]) || {M, _, _} <- code:all_available()], halt().",
+        ])
+        .output()
+        .map_err(|error| match error.kind() {
+            std::io::ErrorKind::NotFound => Error::ShellProgramNotFound {
+                program: "erl".into(),
+            },
+            other => Error::ShellCommand {
+                program: "erl".into(),
+                err: Some(other),
+            },
+        })?;
+    if !output.status.success() {
+        return Err(Error::ShellCommand {
+            program: "erl".into(),
+            err: None,
+        });
+    }
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|module| module.trim().into())
+        .filter(|module: &EcoString| !module.is_empty())
+        .collect())
+}
+
+#[test]
+fn modules_of_dependencies() {
+    let directory = tempfile::tempdir().expect("Temporary directory");
+    let root =
+        camino::Utf8PathBuf::from_path_buf(directory.path().to_path_buf()).expect("Non UTF-8 path");
+    let paths = ProjectPaths::new(root);
+    let packages = paths.build_packages_directory();
+    for file in [
+        "gleam_stdlib/src/gleam_stdlib.erl",
+        "gleam_stdlib/src/gleam/list.gleam",
+        "gleam_stdlib/test/gleam/list_test.gleam",
+        "thoas-ba7816bf8f01cfea/src/thoas_decode.erl",
+        "thoas-ba7816bf8f01cfea/README.md",
+    ] {
+        crate::fs::write(&packages.join(file), "").expect("Write file");
+    }
+    assert_eq!(
+        dependency_modules(&paths),
+        ["gleam_stdlib", "gleam@list", "thoas_decode"]
+            .into_iter()
+            .map(EcoString::from)
+            .collect()
+    );
+}
@@ -1,3 +1,6 @@
+use std::time::SystemTime;
+
+use ecow::EcoString;
 use gleam_core::{
     hex::{self, RetirementReason},
     io::HttpClient as _,
@@ -10,7 +13,34 @@ const USER_PROMPT: &str = "https://hex.pm username";
 const USER_KEY: &str = "HEXPM_USER";
 const PASS_PROMPT: &str = "https://hex.pm password";
 const PASS_KEY: &str = "HEXPM_PASS";
-const API_KEY: &str = "HEXPM_API_KEY";
+const OTP_PROMPT: &str = "Two-factor authentication code";
+/// How many times a two-factor authentication code is asked for before
+/// giving up.
+const OTP_ATTEMPTS: usize = 3;
+pub(crate) const API_KEY: &str = "HEXPM_API_KEY";
+const ORGANIZATION_KEY_PREFIX: &str = "HEXPM_ORGANIZATION_KEY_";
+
+/// The API key to use for the packages of a Hex organisation, from the
+/// `HEXPM_ORGANIZATION_KEY_<ORGANIZATION>` environment variable or, if that is
+/// not set, `HEXPM_API_KEY`.
+pub fn organization_key(organization: &str) -> Option<String> {
+    let variable = format!("{ORGANIZATION_KEY_PREFIX}{}", organization.to_uppercase());
+    std::env::var(variable)
+        .or_else(|_| std::env::var(API_KEY))
+        .ok()
+        .map(|key| key.trim().to_string())
+        .filter(|key| !key.is_empty())
+}
+
+/// The Hex organisations that have a key of their own set in the environment.
+pub fn organizations_with_keys() -> Vec<EcoString> {
+    std::env::vars()
+        .filter_map(|(variable, _)| {
+            let organization = variable.strip_prefix(ORGANIZATION_KEY_PREFIX)?;
+            Some(organization.to_lowercase().into())
+        })
+        .collect()
+}
 
 /// A helper trait that handles the provisioning and destruction of a Hex API key.
 pub trait ApiKeyCommand {
@@ -33,10 +63,31 @@ pub trait ApiKeyCommand {
         let username = std::env::var(USER_KEY).or_else(|_| cli::ask(USER_PROMPT))?;
         let password = std::env::var(PASS_KEY).or_else(|_| cli::ask_password(PASS_PROMPT))?;
 
-        // Get API key
-        let api_key = runtime.block_on(hex::create_api_key(
-            &hostname, &username, &password, hex_config, &http,
-        ))?;
+        // Get API key, asking for a two-factor authentication code if the
+        // account needs one. A rejected code can be entered again without
+        // starting the command over.
+        let mut otp = None;
+        let mut attempts = 0;
+        let api_key = loop {
+            let result = runtime.block_on(hex::create_api_key(
+                &hostname,
+                &username,
+                &password,
+                otp.as_deref(),
+                hex_config,
+                &http,
+            ));
+            match result {
+                Err(Error::HexTwoFactorCodeRequired { rejected }) if attempts < OTP_ATTEMPTS => {
+                    if rejected {
+                        println!("That code was not accepted, please try again.");
+                    }
+                    attempts += 1;
+                    otp = Some(cli::ask(OTP_PROMPT)?);
+                }
+                result => break result?,
+            }
+        };
 
         // Perform the API operation but don't exit early if it fails, we want to always
         // remove the API key
@@ -139,16 +190,27 @@ impl ApiKeyCommand for UnretireCommand {
 pub struct RevertCommand {
     package: String,
     version: String,
+    docs: bool,
 }
 
-pub fn revertcommand(package: Option<String>, version: Option<String>) -> Result<()> {
-    RevertCommand::setup(package, version)?.run()?;
+pub fn revertcommand(
+    package: Option<String>,
+    version: Option<String>,
+    docs: bool,
+    yes: bool,
+) -> Result<()> {
+    RevertCommand::setup(package, version, docs, yes)?.run()?;
 
     Ok(())
 }
 
 impl RevertCommand {
-    fn setup(package: Option<String>, version: Option<String>) -> Result<Self> {
+    fn setup(
+        package: Option<String>,
+        version: Option<String>,
+        docs: bool,
+        yes: bool,
+    ) -> Result<Self> {
         let (package, version): (String, String) = match (package, version) {
             (Some(pkg), Some(ver)) => (pkg, ver),
             (None, Some(ver)) => (crate::config::root_config()?.name.to_string(), ver),
@@ -166,11 +228,43 @@ impl RevertCommand {
             }
         };
 
-        let question =
-            "Do you wish to revert ".to_string() + &package + " version " + &version + "?";
-        let should_revert = cli::confirm(&question)?;
-        if should_revert {
-            Ok(Self { version, package })
+        // Hex only lets a release be reverted shortly after it is published,
+        // so check it still can be before asking for credentials.
+        let runtime = tokio::runtime::Runtime::new().expect("Unable to start Tokio async runtime");
+        let api_key = std::env::var(API_KEY).ok();
+        let publication = runtime.block_on(hex::revert::get_publication(
+            &package,
+            &version,
+            api_key
+                .as_deref()
+                .map(str::trim)
+                .filter(|key| !key.is_empty()),
+            &hexpm::Config::new(),
+            &HttpClient::new(),
+        ))?;
+        let now = SystemTime::now();
+        let remaining = publication
+            .revert_deadline()
+            .duration_since(now)
+            .map_err(|_| Error::HexRevertWindowClosed {
+                package: package.clone(),
+                version: version.clone(),
+            })?;
+        let published = now.duration_since(publication.release).unwrap_or_default();
+        println!(
+            "{package} v{version} was published {} ago and can be reverted for {} more.",
+            cli::time_span(published),
+            cli::time_span(remaining),
+        );
+
+        let what = if docs { " and its docs" } else { "" };
+        let question = format!("Do you wish to revert {package} version {version}{what}?");
+        if yes || cli::confirm(&question)? {
+            Ok(Self {
+                version,
+                package,
+                docs,
+            })
         } else {
             println!("Not reverting.");
             std::process::exit(0);
@@ -194,6 +288,18 @@ impl ApiKeyCommand for RevertCommand {
         let response = handle.block_on(http.send(request))?;
         hexpm::revert_release_response(response).map_err(Error::hex)?;
 
+        // Remove the docs too if asked, which may never have been published
+        if self.docs {
+            let request =
+                hexpm::remove_docs_request(&self.package, &self.version, api_key, hex_config)
+                    .map_err(Error::hex)?;
+            let response = handle.block_on(http.send(request))?;
+            match hexpm::remove_docs_response(response) {
+                Ok(()) | Err(hexpm::ApiError::NotFound) => (),
+                Err(e) => return Err(Error::hex(e)),
+            }
+        }
+
         // Done!
         println!(
             "{} {} has been removed from Hex",
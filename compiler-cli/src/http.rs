@@ -1,44 +1,294 @@
 use std::convert::TryInto;
-use std::sync::OnceLock;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
 
 use async_trait::async_trait;
-use gleam_core::{Error, Result};
-use http::{Request, Response};
+use camino::{Utf8Path, Utf8PathBuf};
+use gleam_core::{
+    error::{FileIoAction, FileKind},
+    hex::snapshot,
+    Error, Result,
+};
+use http::{
+    header::RETRY_AFTER, response::Parts, HeaderMap, Method, Request, Response, StatusCode,
+};
 
 static REQWEST_CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
 
-#[derive(Debug)]
+/// Requests made by this process share one limit, however many clients are
+/// made, so that resolving a large dependency graph or downloading many
+/// packages at once does not get us rate limited by Hex.
+static RATE_LIMITER: RateLimiter = RateLimiter::new(Duration::from_millis(50), 20);
+
+/// How many times a request is retried when the server says too many
+/// requests have been made.
+const MAX_RETRIES: u32 = 3;
+
+/// The longest we will wait before retrying a request, whatever the server
+/// asks for.
+const MAX_RETRY_WAIT: Duration = Duration::from_secs(60);
+
+/// The environment variable naming a directory written by `gleam registry
+/// snapshot`. When it is set requests to Hex are answered from the directory
+/// instead of the network.
+const REGISTRY_DIRECTORY: &str = "GLEAM_REGISTRY_DIRECTORY";
+
+#[derive(Debug, Clone, Copy)]
 pub struct HttpClient;
 
 impl HttpClient {
     pub fn new() -> Self {
         Self
     }
-
-    pub fn boxed() -> Box<Self> {
-        Box::new(Self::new())
-    }
 }
 
 #[async_trait]
 impl gleam_core::io::HttpClient for HttpClient {
     async fn send(&self, request: Request<Vec<u8>>) -> Result<Response<Vec<u8>>> {
-        let request = request
-            .try_into()
-            .expect("Unable to convert HTTP request for use by reqwest library");
-        let mut response = REQWEST_CLIENT
-            .get_or_init(reqwest::Client::new)
-            .execute(request)
-            .await
-            .map_err(Error::http)?;
-        let mut builder = Response::builder()
-            .status(response.status())
-            .version(response.version());
-        if let Some(headers) = builder.headers_mut() {
-            std::mem::swap(headers, response.headers_mut());
+        if let Some(directory) = registry_directory() {
+            return from_registry_directory(directory, &request);
+        }
+        let mut response = execute(request).await?;
+        let (parts, ()) = response_parts(&mut response)?.into_parts();
+        let body = response.bytes().await.map_err(Error::http)?.to_vec();
+        Ok(Response::from_parts(parts, body))
+    }
+
+    async fn send_streaming(
+        &self,
+        request: Request<Vec<u8>>,
+        on_chunk: &mut (dyn for<'a> FnMut(&'a Parts, &'a [u8]) -> Result<()> + Send),
+    ) -> Result<Parts> {
+        if let Some(directory) = registry_directory() {
+            let (parts, body) = from_registry_directory(directory, &request)?.into_parts();
+            on_chunk(&parts, &body)?;
+            return Ok(parts);
+        }
+        let mut response = execute(request).await?;
+        let (parts, ()) = response_parts(&mut response)?.into_parts();
+        while let Some(chunk) = response.chunk().await.map_err(Error::http)? {
+            on_chunk(&parts, &chunk)?;
+        }
+        Ok(parts)
+    }
+}
+
+fn registry_directory() -> Option<&'static Utf8Path> {
+    static DIRECTORY: OnceLock<Option<Utf8PathBuf>> = OnceLock::new();
+    DIRECTORY
+        .get_or_init(|| {
+            let directory = std::env::var(REGISTRY_DIRECTORY).ok()?;
+            Some(directory)
+                .filter(|directory| !directory.is_empty())
+                .map(Into::into)
+        })
+        .as_deref()
+}
+
+/// Answers a request to Hex with the file in the snapshot directory that was
+/// written from Hex's response to it, or with a not found response if the
+/// snapshot does not have it. Only reading is possible.
+fn from_registry_directory(
+    directory: &Utf8Path,
+    request: &Request<Vec<u8>>,
+) -> Result<Response<Vec<u8>>> {
+    let path = snapshot::path_for_request(&hexpm::Config::new(), request.uri())
+        .map(|path| directory.join(path));
+    tracing::info!(uri = %request.uri(), path = ?path, "reading_from_registry_directory");
+    let (status, body) = match path {
+        _ if request.method() != Method::GET => (StatusCode::METHOD_NOT_ALLOWED, vec![]),
+        None => (StatusCode::NOT_FOUND, vec![]),
+        Some(path) => match std::fs::read(&path) {
+            Ok(body) => (StatusCode::OK, body),
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => {
+                (StatusCode::NOT_FOUND, vec![])
+            }
+            Err(error) => {
+                return Err(Error::FileIo {
+                    action: FileIoAction::Read,
+                    kind: FileKind::File,
+                    path,
+                    err: Some(error.to_string()),
+                })
+            }
+        },
+    };
+    Response::builder()
+        .status(status)
+        .body(body)
+        .map_err(Error::http)
+}
+
+/// Sends a request once the rate limit allows it, retrying it if the server
+/// responds that too many requests have been made.
+async fn execute(request: Request<Vec<u8>>) -> Result<reqwest::Response> {
+    let request: reqwest::Request = request
+        .try_into()
+        .expect("Unable to convert HTTP request for use by reqwest library");
+    let client = REQWEST_CLIENT.get_or_init(reqwest::Client::new);
+    let mut attempt = 0;
+    loop {
+        RATE_LIMITER.acquire().await;
+        let retry = request
+            .try_clone()
+            .expect("Unable to clone HTTP request with a streaming body");
+        let response = client.execute(retry).await.map_err(Error::http)?;
+        if response.status() != StatusCode::TOO_MANY_REQUESTS || attempt == MAX_RETRIES {
+            return Ok(response);
         }
-        builder
-            .body(response.bytes().await.map_err(Error::http)?.to_vec())
-            .map_err(Error::http)
+        attempt += 1;
+        let wait = retry_after(response.headers())
+            .unwrap_or_else(|| backoff(attempt))
+            .min(MAX_RETRY_WAIT);
+        tracing::info!(
+            url = %request.url(),
+            attempt,
+            wait_ms = wait.as_millis(),
+            "rate_limited_retrying"
+        );
+        // Hold back every other request too, not just this one.
+        RATE_LIMITER.pause_until(Instant::now() + wait);
+    }
+}
+
+/// How long the server asked us to wait before retrying. Only a number of
+/// seconds is understood, as that is what Hex sends.
+fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let seconds = headers
+        .get(RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse()
+        .ok()?;
+    Some(Duration::from_secs(seconds))
+}
+
+/// How long to wait before the given retry if the server did not say.
+fn backoff(attempt: u32) -> Duration {
+    Duration::from_secs(1 << attempt.saturating_sub(1).min(6))
+}
+
+/// The status and headers of a response, taken from the reqwest response so
+/// that its body can still be read.
+fn response_parts(response: &mut reqwest::Response) -> Result<Response<()>> {
+    let mut builder = Response::builder()
+        .status(response.status())
+        .version(response.version());
+    if let Some(headers) = builder.headers_mut() {
+        std::mem::swap(headers, response.headers_mut());
+    }
+    builder.body(()).map_err(Error::http)
+}
+
+/// Spaces requests out to one per `interval`, after allowing a burst of
+/// `burst` requests at once.
+#[derive(Debug)]
+struct RateLimiter {
+    interval: Duration,
+    burst: u32,
+    /// When the next request would be sent if requests were never sent in
+    /// bursts. `None` if no request has been sent yet.
+    next: Mutex<Option<Instant>>,
+}
+
+impl RateLimiter {
+    const fn new(interval: Duration, burst: u32) -> Self {
+        Self {
+            interval,
+            burst,
+            next: Mutex::new(None),
+        }
+    }
+
+    /// Waits until another request may be sent.
+    async fn acquire(&self) {
+        let wait = self.reserve(Instant::now());
+        if !wait.is_zero() {
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    /// Reserves the next slot for a request, returning how long after `now`
+    /// it is.
+    fn reserve(&self, now: Instant) -> Duration {
+        let mut next = self.next.lock().expect("Rate limiter lock");
+        let after = next.map_or(now, |next| next.max(now)) + self.interval;
+        *next = Some(after);
+        let send_at = after
+            .checked_sub(self.interval * self.burst)
+            .map_or(now, |send_at| send_at.max(now));
+        send_at - now
+    }
+
+    /// Holds back all requests until `until`.
+    fn pause_until(&self, until: Instant) {
+        let mut next = self.next.lock().expect("Rate limiter lock");
+        let paused = until + self.interval * self.burst.saturating_sub(1);
+        *next = Some(next.map_or(paused, |next| next.max(paused)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rate_limiter_allows_a_burst_then_spaces_requests() {
+        let limiter = RateLimiter::new(Duration::from_millis(100), 3);
+        let now = Instant::now();
+        let waits: Vec<_> = (0..5).map(|_| limiter.reserve(now)).collect();
+        assert_eq!(
+            waits,
+            vec![
+                Duration::ZERO,
+                Duration::ZERO,
+                Duration::ZERO,
+                Duration::from_millis(100),
+                Duration::from_millis(200),
+            ]
+        );
+
+        // Once enough time has passed the burst is allowed again
+        let later = now + Duration::from_secs(1);
+        assert_eq!(limiter.reserve(later), Duration::ZERO);
+    }
+
+    #[test]
+    fn rate_limiter_pause() {
+        let limiter = RateLimiter::new(Duration::from_millis(100), 3);
+        let now = Instant::now();
+        limiter.pause_until(now + Duration::from_secs(2));
+        assert_eq!(limiter.reserve(now), Duration::from_secs(2));
+        assert_eq!(limiter.reserve(now), Duration::from_millis(2100));
+    }
+
+    #[test]
+    fn retry_after_seconds() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(retry_after(&headers), None);
+        let _ = headers.insert(RETRY_AFTER, "30".parse().expect("header value"));
+        assert_eq!(retry_after(&headers), Some(Duration::from_secs(30)));
+        let _ = headers.insert(
+            RETRY_AFTER,
+            "Wed, 21 Oct 2015 07:28:00 GMT"
+                .parse()
+                .expect("header value"),
+        );
+        assert_eq!(retry_after(&headers), None);
+    }
+
+    #[test]
+    fn backoff_doubles() {
+        let waits: Vec<_> = (1..=4).map(backoff).collect();
+        assert_eq!(
+            waits,
+            vec![
+                Duration::from_secs(1),
+                Duration::from_secs(2),
+                Duration::from_secs(4),
+                Duration::from_secs(8),
+            ]
+        );
     }
 }
@@ -0,0 +1,838 @@
+//! `gleam kernel` runs the cells of a notebook, such as one of Livebook or
+//! Jupyter, in the project, with the cells type checked as any module of the
+//! project would be.
+//!
+//! The kernel speaks JSON-RPC 2.0 over standard input and output, with one
+//! message per line. The methods are:
+//!
+//! - `initialize`, which builds the project. It must be called first, and can
+//!   be called again to pick up changes to the project.
+//! - `check`, which type checks the cell given as the `code` parameter.
+//! - `execute`, which type checks the cell given as the `code` parameter and,
+//!   if it is an expression, evaluates it on an Erlang node.
+//! - `reset`, which forgets the definitions of the cells executed so far.
+//! - `shutdown`, which stops the kernel.
+//!
+//! A cell is either definitions, such as imports, functions, types, and
+//! constants, or an expression, which can be a sequence of statements. The
+//! definitions of executed cells can be used by the cells after them.
+
+use std::{
+    io::{BufRead, BufReader, Write},
+    process::{Child, ChildStdin, ChildStdout, Command, Stdio},
+    sync::Arc,
+};
+
+use camino::{Utf8Path, Utf8PathBuf};
+use ecow::EcoString;
+use gleam_core::{
+    analyse::{
+        expression::{AnalysedModule, ExpressionAnalyzer, ModuleContext, EXPRESSION_FUNCTION},
+        TargetSupport,
+    },
+    build::{Codegen, Mode, NullTelemetry, Options, Origin, ProjectCompiler, Target},
+    config::PackageConfig,
+    diagnostic::Diagnostic,
+    error::StandardIoAction,
+    inspect::ValuePrinter,
+    io::Stdio as SubprocessStdio,
+    line_numbers::LineNumbers,
+    paths::ProjectPaths,
+    type_::{ModuleInterface, Type},
+    version::COMPILER_VERSION,
+    warning::NullWarningEmitterIO,
+    Error, Result,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::{build_lock::BuildLock, fs::ProjectIO};
+
+/// The Erlang module that evaluates the cells on the Erlang node.
+const HELPER: &str = include_str!("../templates/gleam@@kernel.erl");
+
+/// The prefix of the lines the helper module writes to standard output. Other
+/// lines are the output of the cell being evaluated.
+const MARKER: &str = "@@gleam-kernel@@ ";
+
+/// The module the cells are analysed in, which has the definitions of the
+/// cells executed so far.
+const CELL_MODULE: &str = "gleam@@kernel_cell";
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+/// The code of errors of the kernel itself, such as the project failing to
+/// build.
+const KERNEL_ERROR: i64 = -32000;
+
+pub fn main() -> Result<()> {
+    tracing::info!("kernel_starting");
+
+    let mut kernel = Kernel::default();
+    let mut stdout = std::io::stdout().lock();
+    for line in std::io::stdin().lock().lines() {
+        let line = line.map_err(|error| Error::StandardIo {
+            action: StandardIoAction::Read,
+            err: Some(error.kind()),
+        })?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let (response, control) = kernel.handle(&line);
+        if let Some(response) = response {
+            writeln!(stdout, "{response}")
+                .and_then(|_| stdout.flush())
+                .map_err(|error| Error::StandardIo {
+                    action: StandardIoAction::Write,
+                    err: Some(error.kind()),
+                })?;
+        }
+        if control == Control::Stop {
+            break;
+        }
+    }
+    kernel.stop_node();
+
+    tracing::info!("kernel_stopped");
+    Ok(())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Control {
+    Continue,
+    Stop,
+}
+
+#[derive(Debug, Deserialize)]
+struct Request {
+    /// Notifications have no id and are not responded to.
+    #[serde(default)]
+    id: Option<Value>,
+    method: String,
+    #[serde(default)]
+    params: Value,
+}
+
+#[derive(Debug, Deserialize)]
+struct CellParams {
+    code: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+struct ResponseError {
+    code: i64,
+    message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    data: Option<Value>,
+}
+
+impl ResponseError {
+    fn new(code: i64, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            data: None,
+        }
+    }
+}
+
+impl From<Error> for ResponseError {
+    fn from(error: Error) -> Self {
+        let diagnostics = error.to_diagnostics();
+        let message = diagnostics
+            .first()
+            .map(|diagnostic| diagnostic.title.clone())
+            .unwrap_or_else(|| "The kernel failed".into());
+        Self {
+            code: KERNEL_ERROR,
+            message,
+            data: Some(json!({ "diagnostics": diagnostics })),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum CellKind {
+    Definitions,
+    Expression,
+}
+
+/// A type checked cell.
+#[derive(Debug, Clone)]
+struct CheckedCell {
+    kind: CellKind,
+    /// The type of an expression cell without errors.
+    type_: Option<String>,
+    /// The same type, used to print the value of the cell.
+    value_type: Option<Arc<Type>>,
+    diagnostics: Vec<Diagnostic>,
+    /// The cell module with the cell added to it, if there were no errors.
+    module: Option<AnalysedModule>,
+}
+
+impl CheckedCell {
+    fn to_json(&self, evaluation: Option<Evaluation>) -> Value {
+        json!(CellResult {
+            kind: self.kind,
+            type_: self.type_.as_deref(),
+            diagnostics: &self.diagnostics,
+            evaluation,
+        })
+    }
+}
+
+/// The result of the `check` and `execute` methods.
+#[derive(Debug, Serialize)]
+struct CellResult<'a> {
+    kind: CellKind,
+    #[serde(rename = "type")]
+    type_: Option<&'a str>,
+    diagnostics: &'a [Diagnostic],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    evaluation: Option<Evaluation>,
+}
+
+/// The result of evaluating an expression cell on the Erlang node.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+struct Evaluation {
+    /// What the cell wrote to standard output.
+    #[serde(default)]
+    output: String,
+    /// The value of the cell, as Gleam would show it.
+    #[serde(default)]
+    value: Option<String>,
+    /// The value of the cell as encoded by the `gleam@@inspect` module, which
+    /// is printed using the type of the cell in place of `value`.
+    #[serde(default, skip_serializing)]
+    encoded: Option<String>,
+    /// The panic or other exception raised by the cell.
+    #[serde(default)]
+    exception: Option<String>,
+    /// Why the cell could not be evaluated.
+    #[serde(default)]
+    error: Option<String>,
+}
+
+#[derive(Default)]
+struct Kernel {
+    /// The project, once built by the `initialize` method.
+    project: Option<Project>,
+    /// The definitions of the cells executed so far.
+    definitions: Vec<String>,
+    /// The Erlang node, started when the first cell is evaluated.
+    node: Option<ErlangNode>,
+}
+
+struct Project {
+    paths: ProjectPaths,
+    config: PackageConfig,
+    compiler: ProjectCompiler<ProjectIO>,
+}
+
+impl Kernel {
+    /// Handles a message, returning the response to it if it is a request.
+    fn handle(&mut self, line: &str) -> (Option<Value>, Control) {
+        let message: Value = match serde_json::from_str(line) {
+            Ok(message) => message,
+            Err(error) => {
+                let error = ResponseError::new(PARSE_ERROR, error.to_string());
+                return (Some(response(Value::Null, Err(error))), Control::Continue);
+            }
+        };
+        let request: Request = match serde_json::from_value(message) {
+            Ok(request) => request,
+            Err(error) => {
+                let error = ResponseError::new(INVALID_REQUEST, error.to_string());
+                return (Some(response(Value::Null, Err(error))), Control::Continue);
+            }
+        };
+        tracing::info!(method = %request.method, "kernel_request");
+
+        let control = match request.method.as_str() {
+            "shutdown" => Control::Stop,
+            _ => Control::Continue,
+        };
+        let result = self.dispatch(&request.method, request.params);
+        (request.id.map(|id| response(id, result)), control)
+    }
+
+    fn dispatch(&mut self, method: &str, params: Value) -> Result<Value, ResponseError> {
+        match method {
+            "initialize" => self.initialize(),
+            "check" => {
+                let cell = cell_params(params)?;
+                Ok(self.check(&cell.code)?.to_json(None))
+            }
+            "execute" => {
+                let cell = cell_params(params)?;
+                self.execute(&cell.code)
+            }
+            "reset" => {
+                self.definitions.clear();
+                Ok(Value::Null)
+            }
+            "shutdown" => Ok(Value::Null),
+            _ => Err(ResponseError::new(
+                METHOD_NOT_FOUND,
+                format!("Unknown method `{method}`"),
+            )),
+        }
+    }
+
+    /// Builds the project, in a separate process as the progress of the build
+    /// is printed to standard output, which is used for the protocol. The
+    /// modules of the project are then analysed again by the kernel so that
+    /// cells can use them.
+    fn initialize(&mut self) -> Result<Value, ResponseError> {
+        self.stop_node();
+        let paths = crate::find_project_paths()?;
+        build(&paths)?;
+
+        let config = crate::config::root_config()?;
+        let io = ProjectIO::new();
+        let manifest = crate::dependencies::read_manifest_from_disc(&io, &paths)?;
+        let options = Options {
+            mode: Mode::Dev,
+            target: Some(Target::Erlang),
+            codegen: Codegen::None,
+            warnings_as_errors: false,
+            verify_artefacts: false,
+            root_target_support: TargetSupport::Enforced,
+        };
+        let mut compiler = ProjectCompiler::new(
+            config.clone(),
+            options,
+            manifest.packages,
+            Box::new(NullTelemetry),
+            Arc::new(NullWarningEmitterIO),
+            paths.clone(),
+            io,
+        );
+        compiler.subprocess_stdio = SubprocessStdio::Null;
+        {
+            let lock = BuildLock::new_target(&paths, Mode::Dev, Target::Erlang)?;
+            let _guard = lock.lock(&NullTelemetry)?;
+            let _ = compiler.recompile()?;
+        }
+
+        let package = config.name.clone();
+        self.project = Some(Project {
+            paths,
+            config,
+            compiler,
+        });
+        Ok(json!({ "gleam_version": COMPILER_VERSION, "package": package }))
+    }
+
+    fn check(&self, code: &str) -> Result<CheckedCell, ResponseError> {
+        let project = self.project()?;
+        let analyzer = ExpressionAnalyzer {
+            target: Target::Erlang,
+            ids: project.compiler.ids(),
+            importable_modules: project.compiler.get_importable_modules(),
+            package_config: &project.config,
+        };
+        Ok(check_cell(&analyzer, &self.context(), code))
+    }
+
+    fn execute(&mut self, code: &str) -> Result<Value, ResponseError> {
+        let checked = self.check(code)?;
+        let evaluation = match (&checked.module, checked.kind) {
+            (None, _) => None,
+            (Some(_), CellKind::Definitions) => {
+                self.definitions.push(code.into());
+                None
+            }
+            (Some(module), CellKind::Expression) => {
+                let evaluation = self.evaluate(module)?;
+                let mut modules = self.project()?.compiler.get_importable_modules().clone();
+                let _ = modules.insert(CELL_MODULE.into(), module.ast.type_info.clone());
+                Some(print_value(
+                    evaluation,
+                    checked.value_type.as_ref(),
+                    &modules,
+                ))
+            }
+        };
+        Ok(checked.to_json(evaluation))
+    }
+
+    /// Compiles the cell module to Erlang and evaluates the function of the
+    /// expression cell on the Erlang node, starting it if need be.
+    fn evaluate(&mut self, module: &AnalysedModule) -> Result<Evaluation, ResponseError> {
+        let project = self.project()?;
+        let paths = project.paths.clone();
+        let package_directory =
+            paths.build_directory_for_package(Mode::Dev, Target::Erlang, &project.config.name);
+        let erlang = gleam_core::erlang::module(
+            &module.ast,
+            &LineNumbers::new(&module.src),
+            &module.src,
+            &module.path,
+            gleam_core::erlang::Style::Compact,
+        )?;
+        let path = package_directory.join(format!("{CELL_MODULE}.erl"));
+        crate::fs::write(&path, &erlang)?;
+
+        let mut node = match self.node.take() {
+            Some(node) => node,
+            None => ErlangNode::start(&paths, &package_directory)?,
+        };
+        let evaluation = node.evaluate(&path);
+        // A cell can stop the node, in which case a new one is started for
+        // the next cell.
+        if evaluation.is_ok() {
+            self.node = Some(node);
+        } else {
+            node.stop();
+        }
+        Ok(evaluation.unwrap_or_else(|error| Evaluation {
+            error: Some(format!("The Erlang node stopped: {error}")),
+            ..Evaluation::default()
+        }))
+    }
+
+    fn project(&self) -> Result<&Project, ResponseError> {
+        self.project.as_ref().ok_or_else(|| {
+            ResponseError::new(
+                KERNEL_ERROR,
+                "The kernel must be initialized with the `initialize` method first",
+            )
+        })
+    }
+
+    /// The module the next cell is analysed in.
+    fn context(&self) -> ModuleContext {
+        ModuleContext {
+            name: CELL_MODULE.into(),
+            path: Utf8PathBuf::from(format!("{CELL_MODULE}.gleam")),
+            src: self.definitions.join("\n\n").into(),
+            origin: Origin::Src,
+        }
+    }
+
+    fn stop_node(&mut self) {
+        if let Some(node) = self.node.take() {
+            node.stop();
+        }
+    }
+}
+
+/// Type checks a cell in the context module, as definitions if it can be
+/// parsed as such, or else as an expression.
+fn check_cell(
+    analyzer: &ExpressionAnalyzer<'_>,
+    context: &ModuleContext,
+    code: &str,
+) -> CheckedCell {
+    if gleam_core::parse::parse_module(code).is_ok() {
+        let checked = analyzer.check_definitions(code, context);
+        return CheckedCell {
+            kind: CellKind::Definitions,
+            type_: None,
+            value_type: None,
+            diagnostics: checked.diagnostics,
+            module: checked.module,
+        };
+    }
+    let inferred = analyzer.infer(code, context);
+    CheckedCell {
+        kind: CellKind::Expression,
+        type_: inferred.pretty_type(),
+        value_type: inferred.type_,
+        diagnostics: inferred.diagnostics,
+        module: inferred.module,
+    }
+}
+
+/// Prints the encoded value of the cell using its type, so that it is shown as
+/// it would be written in Gleam even if the standard library is not
+/// available to print it.
+fn print_value(
+    evaluation: Evaluation,
+    type_: Option<&Arc<Type>>,
+    modules: &im::HashMap<EcoString, ModuleInterface>,
+) -> Evaluation {
+    let Some(value) = evaluation
+        .encoded
+        .as_deref()
+        .and_then(|encoded| gleam_core::inspect::decode(encoded).ok())
+    else {
+        return evaluation;
+    };
+    Evaluation {
+        value: Some(ValuePrinter::new(modules).print(&value, type_)),
+        encoded: None,
+        ..evaluation
+    }
+}
+
+fn cell_params(params: Value) -> Result<CellParams, ResponseError> {
+    serde_json::from_value(params)
+        .map_err(|error| ResponseError::new(INVALID_PARAMS, error.to_string()))
+}
+
+fn response(id: Value, result: Result<Value, ResponseError>) -> Value {
+    match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err(error) => json!({ "jsonrpc": "2.0", "id": id, "error": error }),
+    }
+}
+
+fn build(paths: &ProjectPaths) -> Result<(), ResponseError> {
+    let gleam = std::env::current_exe().map_err(|error| Error::ShellCommand {
+        program: "gleam".into(),
+        err: Some(error.kind()),
+    })?;
+    let output = Command::new(gleam)
+        .args(["build", "--target", "erlang", "--color", "never"])
+        .current_dir(paths.root())
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|error| Error::ShellCommand {
+            program: "gleam".into(),
+            err: Some(error.kind()),
+        })?;
+    if output.status.success() {
+        return Ok(());
+    }
+    let text = String::from_utf8_lossy(&output.stdout).to_string()
+        + &String::from_utf8_lossy(&output.stderr);
+    Err(ResponseError {
+        code: KERNEL_ERROR,
+        message: "The project could not be built".into(),
+        data: Some(json!({ "output": text })),
+    })
+}
+
+/// The `erl` process the cells are evaluated in, running the helper module.
+struct ErlangNode {
+    process: Child,
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+}
+
+impl ErlangNode {
+    fn start(paths: &ProjectPaths, package_directory: &Utf8Path) -> Result<Self> {
+        let helper = package_directory.join("gleam@@kernel.erl");
+        crate::fs::write(&helper, HELPER)?;
+
+        let mut args = vec![];
+        let packages = paths.build_directory_for_target(Mode::Dev, Target::Erlang);
+        for entry in crate::fs::read_dir(packages)?.filter_map(Result::ok) {
+            args.push("-pa".to_string());
+            args.push(entry.path().join("ebin").into());
+        }
+        args.push("-noshell".into());
+        args.push("-eval".into());
+        args.push(format!(
+            "{{ok, _, Binary}} = compile:file({path}, [binary, report_errors]), \
+             {{module, _}} = code:load_binary('gleam@@kernel', {path}, Binary), \
+             'gleam@@kernel':start(), \
+             halt()",
+            path = erlang_string(helper.as_str())
+        ));
+
+        let mut process = Command::new("erl")
+            .args(&args)
+            .current_dir(paths.root())
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .spawn()
+            .map_err(|error| match error.kind() {
+                std::io::ErrorKind::NotFound => Error::ShellProgramNotFound {
+                    program: "erl".into(),
+                },
+                other => Error::ShellCommand {
+                    program: "erl".into(),
+                    err: Some(other),
+                },
+            })?;
+        let stdin = process.stdin.take().expect("erl stdin");
+        let stdout = BufReader::new(process.stdout.take().expect("erl stdout"));
+        Ok(Self {
+            process,
+            stdin,
+            stdout,
+        })
+    }
+
+    fn evaluate(&mut self, path: &Utf8Path) -> std::io::Result<Evaluation> {
+        writeln!(
+            self.stdin,
+            "{{evaluate, {}, '{CELL_MODULE}', '{EXPRESSION_FUNCTION}'}}.",
+            erlang_string(path.as_str())
+        )?;
+        self.stdin.flush()?;
+        read_evaluation(&mut self.stdout)
+    }
+
+    fn stop(mut self) {
+        drop(self.stdin);
+        let _ = self.process.kill();
+        let _ = self.process.wait();
+    }
+}
+
+/// Reads the output of a cell up to the reply of the helper module.
+fn read_evaluation(reader: &mut impl BufRead) -> std::io::Result<Evaluation> {
+    let mut output = String::new();
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 {
+            return Err(std::io::ErrorKind::UnexpectedEof.into());
+        }
+        let Some((text, reply)) = line.split_once(MARKER) else {
+            output.push_str(&line);
+            continue;
+        };
+        output.push_str(text);
+        let evaluation: Evaluation = serde_json::from_str(reply)?;
+        return Ok(Evaluation {
+            output,
+            ..evaluation
+        });
+    }
+}
+
+fn erlang_string(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use gleam_core::{
+        diagnostic::Level,
+        type_::{build_prelude, string, PRELUDE_MODULE_NAME},
+        uid::UniqueIdGenerator,
+    };
+
+    use super::*;
+
+    fn check(context: &str, code: &str) -> CheckedCell {
+        let ids = UniqueIdGenerator::new();
+        let mut modules = im::HashMap::new();
+        let _ = modules.insert(PRELUDE_MODULE_NAME.into(), build_prelude(&ids));
+        let config = PackageConfig::default();
+        let analyzer = ExpressionAnalyzer {
+            target: Target::Erlang,
+            ids: &ids,
+            importable_modules: &modules,
+            package_config: &config,
+        };
+        let context = ModuleContext {
+            src: context.into(),
+            ..Kernel::default().context()
+        };
+        check_cell(&analyzer, &context, code)
+    }
+
+    fn handle(kernel: &mut Kernel, message: &str) -> Value {
+        kernel.handle(message).0.expect("response")
+    }
+
+    fn error_code(response: &Value) -> Option<i64> {
+        response.pointer("/error/code").and_then(Value::as_i64)
+    }
+
+    #[test]
+    fn check_expression_cell() {
+        let checked = check("", "let x = 1\nx + 2");
+        assert_eq!(checked.kind, CellKind::Expression);
+        assert_eq!(checked.type_, Some("Int".into()));
+        assert_eq!(checked.diagnostics, vec![]);
+        assert!(checked.module.is_some());
+    }
+
+    #[test]
+    fn check_definitions_cell() {
+        let checked = check("", "pub fn double(x) { x * 2 }");
+        assert_eq!(checked.kind, CellKind::Definitions);
+        assert_eq!(checked.type_, None);
+        assert_eq!(checked.diagnostics, vec![]);
+    }
+
+    #[test]
+    fn check_cell_using_earlier_definitions() {
+        let checked = check("pub fn double(x) { x * 2 }", "double(2.0)");
+        assert_eq!(checked.type_, None);
+        let [diagnostic] = checked.diagnostics.as_slice() else {
+            panic!("expected one diagnostic: {:?}", checked.diagnostics);
+        };
+        assert_eq!(diagnostic.level, Level::Error);
+        assert!(checked.module.is_none());
+    }
+
+    #[test]
+    fn cell_json() {
+        let checked = check("", "\"wibble\"");
+        assert_eq!(
+            checked.to_json(None),
+            json!({ "kind": "expression", "type": "String", "diagnostics": [] })
+        );
+    }
+
+    #[test]
+    fn context_has_executed_definitions() {
+        let kernel = Kernel {
+            definitions: vec!["import gleam/io".into(), "pub const x = 1".into()],
+            ..Kernel::default()
+        };
+        let context = kernel.context();
+        assert_eq!(context.name.as_str(), CELL_MODULE);
+        assert_eq!(context.src.as_str(), "import gleam/io\n\npub const x = 1");
+    }
+
+    #[test]
+    fn parse_error() {
+        let response = handle(&mut Kernel::default(), "{");
+        assert_eq!(response.get("id"), Some(&Value::Null));
+        assert_eq!(error_code(&response), Some(PARSE_ERROR));
+    }
+
+    #[test]
+    fn invalid_request() {
+        let response = handle(&mut Kernel::default(), r#"{"jsonrpc": "2.0", "id": 1}"#);
+        assert_eq!(error_code(&response), Some(INVALID_REQUEST));
+    }
+
+    #[test]
+    fn method_not_found() {
+        let response = handle(
+            &mut Kernel::default(),
+            r#"{"jsonrpc": "2.0", "id": 1, "method": "wibble"}"#,
+        );
+        assert_eq!(
+            response,
+            json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "error": { "code": METHOD_NOT_FOUND, "message": "Unknown method `wibble`" }
+            })
+        );
+    }
+
+    #[test]
+    fn invalid_params() {
+        let response = handle(
+            &mut Kernel::default(),
+            r#"{"jsonrpc": "2.0", "id": 1, "method": "check", "params": {}}"#,
+        );
+        assert_eq!(error_code(&response), Some(INVALID_PARAMS));
+    }
+
+    #[test]
+    fn not_initialized() {
+        let response = handle(
+            &mut Kernel::default(),
+            r#"{"jsonrpc": "2.0", "id": "a", "method": "check", "params": {"code": "1"}}"#,
+        );
+        assert_eq!(response.get("id"), Some(&json!("a")));
+        assert_eq!(error_code(&response), Some(KERNEL_ERROR));
+    }
+
+    #[test]
+    fn reset_forgets_definitions() {
+        let mut kernel = Kernel {
+            definitions: vec!["pub const x = 1".into()],
+            ..Kernel::default()
+        };
+        let response = handle(
+            &mut kernel,
+            r#"{"jsonrpc": "2.0", "id": 1, "method": "reset"}"#,
+        );
+        assert_eq!(response.get("result"), Some(&Value::Null));
+        assert!(kernel.definitions.is_empty());
+    }
+
+    #[test]
+    fn notifications_are_not_responded_to() {
+        let (response, control) =
+            Kernel::default().handle(r#"{"jsonrpc": "2.0", "method": "reset"}"#);
+        assert_eq!(response, None);
+        assert_eq!(control, Control::Continue);
+    }
+
+    #[test]
+    fn shutdown() {
+        let (response, control) =
+            Kernel::default().handle(r#"{"jsonrpc": "2.0", "id": 1, "method": "shutdown"}"#);
+        assert_eq!(
+            response,
+            Some(json!({ "jsonrpc": "2.0", "id": 1, "result": null }))
+        );
+        assert_eq!(control, Control::Stop);
+    }
+
+    #[test]
+    fn read_evaluation_with_output() {
+        let mut reader = std::io::Cursor::new(
+            "Hello\nJoe@@gleam-kernel@@ {\"value\":\"Nil\"}\nafter\n".as_bytes(),
+        );
+        assert_eq!(
+            read_evaluation(&mut reader).expect("evaluation"),
+            Evaluation {
+                output: "Hello\nJoe".into(),
+                value: Some("Nil".into()),
+                ..Evaluation::default()
+            }
+        );
+    }
+
+    #[test]
+    fn read_evaluation_exception() {
+        let mut reader =
+            std::io::Cursor::new("@@gleam-kernel@@ {\"exception\":\"wibble\"}\n".as_bytes());
+        assert_eq!(
+            read_evaluation(&mut reader).expect("evaluation"),
+            Evaluation {
+                exception: Some("wibble".into()),
+                ..Evaluation::default()
+            }
+        );
+    }
+
+    #[test]
+    fn print_encoded_value() {
+        let modules = im::hashmap! {
+            PRELUDE_MODULE_NAME.into() => build_prelude(&UniqueIdGenerator::new()),
+        };
+        let evaluation = Evaluation {
+            value: Some("<<\"hi\">>".into()),
+            encoded: Some(r#"{"kind":"bit_array","bytes":[104,105]}"#.into()),
+            ..Evaluation::default()
+        };
+        assert_eq!(
+            print_value(evaluation, Some(&string()), &modules),
+            Evaluation {
+                value: Some("\"hi\"".into()),
+                ..Evaluation::default()
+            }
+        );
+    }
+
+    #[test]
+    fn print_value_without_encoding() {
+        let evaluation = Evaluation {
+            value: Some("Nil".into()),
+            ..Evaluation::default()
+        };
+        assert_eq!(
+            print_value(evaluation.clone(), None, &im::HashMap::new()),
+            evaluation
+        );
+    }
+
+    #[test]
+    fn read_evaluation_node_stopped() {
+        let mut reader = std::io::Cursor::new("Hello\n".as_bytes());
+        assert_eq!(
+            read_evaluation(&mut reader).map_err(|error| error.kind()),
+            Err(std::io::ErrorKind::UnexpectedEof)
+        );
+    }
+}
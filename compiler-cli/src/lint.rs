@@ -0,0 +1,65 @@
+use gleam_core::{lint, Error, Result};
+use itertools::Itertools;
+
+/// Check the project's source and test modules, and the requirements in
+/// `gleam.toml`, against the rules configured under `[lints]` in `gleam.toml`,
+/// printing the lints either as a list or in JSON format. With `fix` the lints
+/// that can be fixed automatically are fixed and only the others are printed.
+pub(crate) fn run(json: bool, fix: bool) -> Result<()> {
+    let paths = crate::find_project_paths()?;
+    let config = crate::config::root_config()?;
+
+    let mut lints = vec![];
+    let toml = crate::fs::read(paths.root_config())?;
+    let published = crate::dependencies::published_versions(&config);
+    let found = lint::find_in_config(&config, &toml, &published);
+    if fix {
+        let (fixable, unfixable): (Vec<_>, Vec<_>) =
+            found.into_iter().partition(|lint| lint.fix.is_some());
+        if !fixable.is_empty() {
+            let toml = lint::apply_fixes(&toml, &fixable.iter().collect_vec());
+            crate::fs::write(&paths.root_config(), &toml)?;
+        }
+        lints.extend(unfixable);
+    } else {
+        lints.extend(found);
+    }
+
+    let built = crate::build::analyse_root_package(None, json)?;
+
+    for module in built
+        .root_package
+        .modules
+        .iter()
+        .sorted_by(|a, b| a.input_path.cmp(&b.input_path))
+    {
+        let path = module
+            .input_path
+            .strip_prefix(paths.root())
+            .unwrap_or(&module.input_path);
+        let found = lint::find(&module.ast, path, &module.code, &config.lints);
+        if !fix {
+            lints.extend(found);
+            continue;
+        }
+
+        let (fixable, unfixable): (Vec<_>, Vec<_>) =
+            found.into_iter().partition(|lint| lint.fix.is_some());
+        if !fixable.is_empty() {
+            let code = lint::apply_fixes(&module.code, &fixable.iter().collect_vec());
+            crate::fs::write(&module.input_path, &code)?;
+        }
+        lints.extend(unfixable);
+    }
+
+    if json {
+        println!("{}", lint::to_json(&lints));
+    } else {
+        print!("{}", lint::to_text(&lints));
+    }
+    if lints.is_empty() {
+        Ok(())
+    } else {
+        Err(Error::LintsFound { count: lints.len() })
+    }
+}
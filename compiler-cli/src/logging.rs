@@ -0,0 +1,230 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde_json::{Map, Value};
+use tracing::{
+    field::{Field, Visit},
+    Event, Subscriber,
+};
+use tracing_subscriber::{
+    fmt::{format::Writer, FmtContext, FormatEvent, FormatFields},
+    registry::LookupSpan,
+    EnvFilter,
+};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum LogFormat {
+    /// Human readable lines
+    #[default]
+    Human,
+    /// One JSON object per line
+    Json,
+}
+
+/// Installs the global logger, which writes to stderr.
+///
+/// The level filter uses the `tracing` directive syntax, e.g. `debug` or
+/// `gleam_core::build=trace`. If none is given the `GLEAM_LOG` environment
+/// variable is used, and if that is not set then human readable logs are
+/// disabled and JSON logs are written at the `info` level.
+///
+pub fn initialise(format: LogFormat, level: Option<String>) {
+    let level = level
+        .or_else(|| std::env::var("GLEAM_LOG").ok())
+        .unwrap_or_else(|| match format {
+            LogFormat::Human => "off".into(),
+            LogFormat::Json => "info".into(),
+        });
+    let filter = EnvFilter::try_new(&level).unwrap_or_else(|_| EnvFilter::new("off"));
+
+    match format {
+        LogFormat::Human => {
+            let enable_colours = std::env::var("GLEAM_LOG_NOCOLOUR").is_err();
+            tracing_subscriber::fmt()
+                .with_writer(std::io::stderr)
+                .with_env_filter(filter)
+                .with_target(false)
+                .with_ansi(enable_colours)
+                .without_time()
+                .init();
+        }
+        LogFormat::Json => {
+            tracing_subscriber::fmt()
+                .with_writer(std::io::stderr)
+                .with_env_filter(filter)
+                .with_ansi(false)
+                .event_format(JsonFormat)
+                .init();
+        }
+    }
+}
+
+/// Formats each event as a single line JSON object:
+///
+/// ```json
+/// {"fields":{"package":"gleam_stdlib","version":"0.38.0"},"level":"INFO","message":"downloading_package_to_cache","spans":["download_deps"],"target":"gleam_core::hex","time":1718000000000}
+/// ```
+///
+/// `time` is the number of milliseconds since the Unix epoch.
+///
+#[derive(Debug, Clone, Copy)]
+struct JsonFormat;
+
+impl<S, N> FormatEvent<S, N> for JsonFormat
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+{
+    fn format_event(
+        &self,
+        ctx: &FmtContext<'_, S, N>,
+        mut writer: Writer<'_>,
+        event: &Event<'_>,
+    ) -> std::fmt::Result {
+        let spans = ctx
+            .event_scope()
+            .into_iter()
+            .flat_map(|scope| scope.from_root())
+            .map(|span| span.name())
+            .collect();
+        writeln!(writer, "{}", json_event(event, spans, now()))
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| u64::try_from(duration.as_millis()).unwrap_or(u64::MAX))
+        .unwrap_or_default()
+}
+
+fn json_event(event: &Event<'_>, spans: Vec<&str>, time: u64) -> Value {
+    let metadata = event.metadata();
+    let mut fields = JsonFields::default();
+    event.record(&mut fields);
+    let mut object = Map::new();
+    let _ = object.insert("time".into(), time.into());
+    let _ = object.insert("level".into(), metadata.level().to_string().into());
+    let _ = object.insert("target".into(), metadata.target().into());
+    let _ = object.insert("spans".into(), spans.into());
+    let _ = object.insert("message".into(), fields.message.into());
+    let _ = object.insert("fields".into(), fields.values.into());
+    Value::Object(object)
+}
+
+#[derive(Debug, Default)]
+struct JsonFields {
+    message: String,
+    values: Map<String, Value>,
+}
+
+impl JsonFields {
+    fn insert(&mut self, field: &Field, value: Value) {
+        if field.name() == "message" {
+            self.message = match value {
+                Value::String(message) => message,
+                value => value.to_string(),
+            };
+        } else {
+            let _ = self.values.insert(field.name().into(), value);
+        }
+    }
+}
+
+impl Visit for JsonFields {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        self.insert(field, format!("{value:?}").into());
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.insert(field, value.into());
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.insert(field, value.into());
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.insert(field, value.into());
+    }
+
+    fn record_u128(&mut self, field: &Field, value: u128) {
+        match u64::try_from(value) {
+            Ok(value) => self.insert(field, value.into()),
+            Err(_) => self.insert(field, value.to_string().into()),
+        }
+    }
+
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.insert(field, value.into());
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.insert(field, value.into());
+    }
+}
+
+#[test]
+fn json_format() {
+    use std::sync::{Arc, Mutex};
+
+    let output = Arc::new(Mutex::new(Vec::new()));
+    let writer = output.clone();
+    let subscriber = tracing_subscriber::fmt()
+        .with_writer(move || SharedWriter(writer.clone()))
+        .with_env_filter(EnvFilter::new("debug"))
+        .event_format(JsonFormat)
+        .finish();
+    tracing::subscriber::with_default(subscriber, || {
+        let span = tracing::info_span!("download_deps");
+        let _enter = span.enter();
+        tracing::info!(
+            package = "wibble",
+            count = 2,
+            elapsed_ms = 15_u128,
+            cached = false,
+            "downloading_package"
+        );
+        tracing::trace!("ignored");
+    });
+
+    let output = output.lock().expect("log output lock");
+    let output = std::str::from_utf8(&output).expect("utf8 log output");
+    let lines = output.lines().collect::<Vec<_>>();
+    assert_eq!(lines.len(), 1);
+    let line = lines.first().expect("log line");
+    let mut event: Value = serde_json::from_str(line).expect("json log line");
+    let time = event
+        .as_object_mut()
+        .and_then(|object| object.remove("time"))
+        .expect("time field");
+    assert!(time.is_u64());
+    assert_eq!(
+        event,
+        serde_json::json!({
+            "level": "INFO",
+            "target": "gleam::logging",
+            "spans": ["download_deps"],
+            "message": "downloading_package",
+            "fields": {"package": "wibble", "count": 2, "elapsed_ms": 15, "cached": false},
+        })
+    );
+}
+
+#[cfg(test)]
+#[derive(Debug)]
+struct SharedWriter(std::sync::Arc<std::sync::Mutex<Vec<u8>>>);
+
+#[cfg(test)]
+impl std::io::Write for SharedWriter {
+    fn write(&mut self, buffer: &[u8]) -> std::io::Result<usize> {
+        self.0
+            .lock()
+            .expect("log output lock")
+            .extend_from_slice(buffer);
+        Ok(buffer.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
@@ -29,7 +29,7 @@ You can exit this program by pressing ctrl+c.
 
     // Run the server and wait for the two threads to end, typically by trigger
     // LSP Exit event.
-    LanguageServer::new(&connection, ProjectIO::new())?.run()?;
+    LanguageServer::new(&connection, ProjectIO::new(), crate::cli::theme())?.run()?;
 
     // Shut down gracefully.
     drop(connection);
@@ -54,35 +54,59 @@ mod build_lock;
 mod cli;
 mod compile_package;
 mod config;
+mod crash_report;
+mod daemon;
+mod dap;
 mod dependencies;
+mod dialyzer;
 mod docs;
+mod examples;
 mod export;
 mod fix;
 mod format;
 mod fs;
+mod generate;
 mod hex;
 mod http;
+mod kernel;
+mod lint;
+mod logging;
 mod lsp;
+mod move_module;
+mod mutate;
 mod new;
+mod organize_imports;
 mod panic;
+mod provenance;
 mod publish;
+mod registry;
+mod release;
 mod remove;
 mod run;
 mod shell;
+mod todos;
+mod wasm;
 
 use config::root_config;
 use dependencies::UseManifest;
 use fs::{get_current_directory, get_project_root};
 pub use gleam_core::error::{Error, Result};
 
+use cli::ColourMode;
 use gleam_core::{
     analyse::TargetSupport,
     build::{Codegen, Mode, Options, Runtime, Target},
+    diagnostic::{
+        catalog::{self, Catalog},
+        Theme,
+    },
     hex::RetirementReason,
     paths::ProjectPaths,
+    test_runner::{self, Shard},
     version::COMPILER_VERSION,
 };
 use hex::ApiKeyCommand as _;
+use logging::LogFormat;
 use std::str::FromStr;
 
 use camino::Utf8PathBuf;
@@ -108,7 +132,39 @@ use strum::VariantNames;
         .usage(styling::AnsiColor::Yellow.on_default())
         .literal(styling::AnsiColor::Green.on_default())
 )]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+
+    /// The format of the log messages written to stderr
+    #[arg(long, global = true, value_enum, default_value_t)]
+    log_format: LogFormat,
+
+    /// Which log messages to write, e.g. `debug` or `gleam_core::hex=trace`.
+    /// Defaults to the `GLEAM_LOG` environment variable
+    #[arg(long, global = true)]
+    log_level: Option<String>,
+
+    /// When to use colour in the output
+    #[arg(long, global = true, value_enum, default_value_t)]
+    color: ColourMode,
+
+    #[arg(
+        long,
+        global = true,
+        default_value_t,
+        help = theme_doc(),
+        value_parser = PossibleValuesParser::new(Theme::VARIANTS).map(|s| Theme::from_str(&s).expect("theme"))
+    )]
+    theme: Theme,
+
+    /// The environment whose overlay of gleam.toml is merged over it, e.g.
+    /// `prod` for gleam.prod.toml
+    #[arg(long, global = true)]
+    config_env: Option<String>,
+}
 
+#[derive(Subcommand, Debug)]
 enum Command {
     /// Build the project
     Build {
@@ -116,14 +172,34 @@ enum Command {
         #[arg(long)]
         warnings_as_errors: bool,
 
-        #[arg(short, long, ignore_case = true, help = target_doc())]
-        target: Option<Target>,
+        /// Warn about generated Erlang and JavaScript files that have been
+        /// edited since they were generated
+        #[arg(long)]
+        verify_artifacts: bool,
+
+        #[arg(short, long, ignore_case = true, help = build_target_doc())]
+        target: Option<BuildTarget>,
+
+        /// Enable unstable features, such as the `wasm` target
+        #[arg(long)]
+        unstable: bool,
+
+        #[command(flatten)]
+        errors: ErrorLimitOptions,
     },
 
     /// Type check the project
     Check {
         #[arg(short, long, ignore_case = true, help = target_doc())]
         target: Option<Target>,
+
+        /// Also type check the example projects in the `examples` directory,
+        /// using the dependencies of this project
+        #[arg(long)]
+        examples: bool,
+
+        #[command(flatten)]
+        errors: ErrorLimitOptions,
     },
 
     /// Publish the project to the Hex package manager
@@ -139,6 +215,29 @@ enum Command {
         replace: bool,
         #[arg(short, long)]
         yes: bool,
+        /// Add a provenance attestation to the package, signed with the key
+        /// in the GLEAM_PROVENANCE_KEY environment variable
+        #[arg(long)]
+        provenance: bool,
+    },
+
+    /// Release a new version of the package
+    ///
+    /// The version in gleam.toml is bumped, which must have a heading in
+    /// CHANGELOG.md. The package is then built to check it could be
+    /// published, and the new version is committed and tagged with git.
+    Release {
+        /// Which part of the version to bump
+        #[arg(value_enum)]
+        bump: release::Bump,
+
+        /// Publish the release to Hex once it has been tagged
+        #[arg(long)]
+        publish: bool,
+
+        /// Publish without asking for confirmation
+        #[arg(short, long, requires = "publish")]
+        yes: bool,
     },
 
     /// Render HTML documentation
@@ -150,12 +249,16 @@ enum Command {
     Deps(Dependencies),
 
     /// Update dependency packages to their latest versions
-    Update,
+    Update(UpdateOptions),
 
     /// Work with the Hex package manager
     #[command(subcommand)]
     Hex(Hex),
 
+    /// Work with copies of the Hex package registry
+    #[command(subcommand)]
+    Registry(Registry),
+
     /// Create a new project
     New(NewOptions),
 
@@ -173,12 +276,77 @@ enum Command {
         #[arg(long)]
         check: bool,
     },
+
+    /// Remove unused imports, add missing ones, and sort them
+    OrganizeImports {
+        /// Files or directories of modules to organize the imports of
+        #[arg(default_value = ".")]
+        files: Vec<String>,
+
+        /// Check if the imports are organized without changing them
+        #[arg(long)]
+        check: bool,
+    },
+
+    /// Move a module and update the imports that refer to it
+    Mv {
+        /// The module to move
+        from: Utf8PathBuf,
+
+        /// The path to move the module to
+        to: Utf8PathBuf,
+    },
+
     /// Rewrite deprecated Gleam code
     Fix,
 
+    /// Check the project against the rules configured under `[lints]` in
+    /// gleam.toml
+    Lint {
+        /// Print the lints in JSON format
+        #[arg(long)]
+        json: bool,
+
+        /// Apply the fixes of the lints that can be fixed automatically
+        #[arg(long)]
+        fix: bool,
+    },
+
+    /// List the `todo` and `panic` expressions in the project
+    Todos {
+        /// Print the list in JSON format
+        #[arg(long)]
+        json: bool,
+    },
+
     /// Start an Erlang shell
     Shell,
 
+    /// Check the project with dialyzer, the Erlang static analyser
+    Dialyzer,
+
+    /// Run the tests against small changes to the project's code, printing
+    /// the changes they do not catch
+    Mutate {
+        #[arg(short, long, ignore_case = true, help = target_doc())]
+        target: Option<Target>,
+
+        /// Only change the code of this module. May be given more than once
+        #[arg(long = "module", value_name = "MODULE")]
+        modules: Vec<String>,
+
+        /// How many seconds the tests may run against each change before
+        /// they are stopped. Defaults to four times as long as they take
+        /// against the unchanged code
+        #[arg(long, value_name = "SECONDS")]
+        timeout: Option<u64>,
+    },
+
+    /// Keep a compiler running for the project, which `gleam build`, `gleam
+    /// check`, and `gleam test` use to avoid loading the dependencies and
+    /// analysing unchanged modules for each build
+    Daemon,
+
     /// Run the project
     #[command(trailing_var_arg = true)]
     Run {
@@ -192,6 +360,18 @@ enum Command {
         #[arg(short, long)]
         module: Option<String>,
 
+        /// Set an environment variable for the program. May be given more
+        /// than once
+        #[arg(long = "env", value_name = "KEY=VALUE", value_parser = run::parse_environment_variable)]
+        environment: Vec<(String, String)>,
+
+        /// The directory to run the program in, instead of the current
+        /// directory
+        #[arg(long)]
+        cwd: Option<Utf8PathBuf>,
+
+        /// Arguments for the program, given after `--` if they start with
+        /// `-`
         arguments: Vec<String>,
     },
 
@@ -204,6 +384,45 @@ enum Command {
         #[arg(long, ignore_case = true, help = runtime_doc())]
         runtime: Option<Runtime>,
 
+        /// Run only the tests of one of a number of shards, such as `2/4` for
+        /// the second of four, with the compiler's test runner
+        #[arg(long, value_name = "INDEX/COUNT")]
+        shard: Option<Shard>,
+
+        /// The number of tests to run at once with the compiler's test
+        /// runner. Defaults to the number of schedulers on Erlang and to 1 on
+        /// JavaScript
+        #[arg(long, value_name = "COUNT", value_parser = clap::value_parser!(u32).range(1..))]
+        parallel: Option<u32>,
+
+        /// The number of times to run a failing test again before it fails,
+        /// with the compiler's test runner. Tests that fail and then pass are
+        /// reported as flaky
+        #[arg(long, value_name = "COUNT")]
+        retries: Option<u32>,
+
+        /// Fail the run if any test is flaky, failing and then passing when
+        /// run again
+        #[arg(long, requires = "retries")]
+        fail_on_flaky: bool,
+
+        /// Run only the tests impacted by the changes since the tests last
+        /// all passed, with the compiler's test runner
+        #[arg(long)]
+        only_changed: bool,
+
+        /// With `--only-changed`, run the tests impacted by the changes since
+        /// the given git ref instead
+        #[arg(long, value_name = "GIT_REF", requires = "only_changed")]
+        since: Option<String>,
+
+        /// Run every test, overriding `--only-changed`
+        #[arg(long)]
+        all: bool,
+
+        #[command(flatten)]
+        errors: ErrorLimitOptions,
+
         arguments: Vec<String>,
     },
 
@@ -240,15 +459,60 @@ enum Command {
     #[command(name = "lsp")]
     LanguageServer,
 
+    /// Run the debug adapter for the Erlang target, to be used by editors
+    #[command(name = "dap")]
+    DebugAdapter,
+
+    /// Run a kernel for notebooks such as Livebook and Jupyter, which type
+    /// checks and evaluates cells of Gleam code in the project
+    Kernel,
+
     /// Export something useful from the Gleam project
     #[command(subcommand)]
     Export(ExportTarget),
+
+    /// Generate code for the Gleam project
+    #[command(subcommand)]
+    Gen(Generate),
+}
+
+fn theme_doc() -> String {
+    format!(
+        "How to draw errors, warnings, and progress messages ({})",
+        Theme::VARIANTS.join("|")
+    )
 }
 
 fn target_doc() -> String {
     format!("The platform to target ({})", Target::VARIANTS.join("|"))
 }
 
+fn build_target_doc() -> String {
+    format!(
+        "The platform to target ({}|wasm). The wasm target is unstable",
+        Target::VARIANTS.join("|")
+    )
+}
+
+/// The targets of `gleam build`, which include the experimental Wasm backend
+/// as well as the targets of the rest of the build tool.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BuildTarget {
+    Target(Target),
+    Wasm,
+}
+
+impl FromStr for BuildTarget {
+    type Err = strum::ParseError;
+
+    fn from_str(target: &str) -> Result<Self, Self::Err> {
+        match target {
+            "wasm" => Ok(Self::Wasm),
+            _ => Target::from_str(target).map(Self::Target),
+        }
+    }
+}
+
 fn runtime_doc() -> String {
     format!("The runtime to target ({})", Runtime::VARIANTS.join("|"))
 }
@@ -256,19 +520,67 @@ fn runtime_doc() -> String {
 #[derive(Subcommand, Debug, Clone)]
 pub enum ExportTarget {
     /// Precompiled Erlang, suitable for deployment
-    ErlangShipment,
+    ErlangShipment {
+        /// Make the shipment smaller by removing applications the project
+        /// does not depend on, debug information in BEAM files, and duplicate
+        /// files in priv directories
+        #[arg(long)]
+        strip: bool,
+    },
+    /// An Erlang project with rebar3 and Mix configuration, suitable for
+    /// depending on from an Erlang or Elixir project without using Hex
+    ErlangProject {
+        #[arg(long = "out")]
+        /// The directory to write the project to. Defaults to
+        /// `build/erlang-project`
+        output: Option<Utf8PathBuf>,
+    },
     /// The package bundled into a tarball, suitable for publishing to Hex
     HexTarball,
     /// The JavaScript prelude module
     JavascriptPrelude,
     /// The TypeScript prelude module
     TypescriptPrelude,
+    /// The JSON Schema of gleam.toml, for editors to validate and complete
+    /// the configuration of packages with
+    PackageConfigSchema,
     /// Information on the modules, functions, and types in the project in JSON format
     PackageInterface {
         #[arg(long = "out", required = true)]
         /// The path to write the JSON file to
         output: Utf8PathBuf,
     },
+    /// Information on the modules, functions, and types in the project and
+    /// all of its dependencies in JSON format, for use by editors and code
+    /// assistants
+    ProjectInterface {
+        #[arg(long = "out", required = true)]
+        /// The path to write the JSON file to
+        output: Utf8PathBuf,
+    },
+    /// A pinned description of the project's dependencies, modules, and
+    /// compilation commands in JSON format, for use by build systems such as
+    /// Nix or Bazel
+    BuildMetadata {
+        #[arg(long = "out", required = true)]
+        /// The path to write the JSON file to
+        output: Utf8PathBuf,
+
+        #[arg(short, long, ignore_case = true, help = target_doc())]
+        target: Option<Target>,
+    },
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum Generate {
+    /// Skeleton Erlang and JavaScript modules for the externals of a module
+    Ffi {
+        /// The name of the Gleam module, for example `my_app/internal`
+        module: String,
+
+        #[arg(short, long, ignore_case = true, help = target_doc())]
+        target: Option<Target>,
+    },
 }
 
 #[derive(Args, Debug, Clone)]
@@ -290,6 +602,51 @@ pub struct NewOptions {
     /// Skip creation of .github/* files
     #[arg(long)]
     pub skip_github: bool,
+
+    /// Also create GitHub workflows that publish to Hex when a version is
+    /// tagged and open pull requests updating the dependencies each week
+    #[arg(long, conflicts_with_all = ["skip_git", "skip_github"])]
+    pub github: bool,
+
+    /// Create a GitLab CI pipeline that tests the project, publishes to Hex
+    /// when a version is tagged, and updates the dependencies on a schedule
+    #[arg(long, conflicts_with = "skip_git")]
+    pub gitlab: bool,
+}
+
+#[derive(Args, Debug, Clone, Copy)]
+pub struct ErrorLimitOptions {
+    /// Show at most this many errors, noting how many more were found
+    #[arg(long, value_name = "COUNT", value_parser = clap::value_parser!(u32).range(1..))]
+    max_errors: Option<u32>,
+
+    /// Show only the first error, noting how many more were found
+    #[arg(long, conflicts_with = "max_errors")]
+    stop_on_first_error: bool,
+}
+
+impl ErrorLimitOptions {
+    fn max_errors(self) -> Option<usize> {
+        match self {
+            Self {
+                stop_on_first_error: true,
+                ..
+            } => Some(1),
+            Self { max_errors, .. } => max_errors.map(|max| max as usize),
+        }
+    }
+}
+
+#[derive(Args, Debug)]
+pub struct UpdateOptions {
+    /// The packages to update, all of them if none are given. A git
+    /// dependency is updated to the latest commit of its branch or tag
+    packages: Vec<String>,
+
+    /// List the newer versions of the dependencies and pick which to update
+    /// to
+    #[arg(long, short, conflicts_with = "packages")]
+    interactive: bool,
 }
 
 #[derive(Args, Debug)]
@@ -332,10 +689,95 @@ enum Dependencies {
     List,
 
     /// Download all dependency packages
-    Download,
+    Download {
+        /// The most packages to download at once
+        #[arg(long, default_value_t = gleam_core::hex::DEFAULT_DOWNLOAD_CONCURRENCY)]
+        concurrency: usize,
+
+        /// Print a summary of how versions were resolved, if they had to be
+        #[arg(long)]
+        summary: bool,
+
+        /// Print only the summary of how versions were resolved, as JSON
+        #[arg(long)]
+        json: bool,
+    },
 
     /// Update dependency packages to their latest versions
-    Update,
+    Update(UpdateOptions),
+
+    /// Suggest changes to gleam.toml that would let the dependencies be
+    /// resolved, if they cannot be
+    Fix {
+        /// Make the suggested changes
+        #[arg(long)]
+        apply: bool,
+    },
+
+    /// Check the dependencies in manifest.toml without building them: that
+    /// tarballs match their checksums, downloaded sources are unmodified,
+    /// gleam.toml's requirements are still satisfied, and git pins can be
+    /// fetched
+    Verify {
+        /// Also check the provenance attestations of the packages that have
+        /// one
+        #[arg(long)]
+        provenance: bool,
+    },
+
+    /// Replace a manifest.toml that has git merge conflicts with a newly
+    /// resolved one, keeping the versions both sides agree on
+    ResolveLockConflict,
+
+    /// Print the graph of the imports between the project's modules
+    ModuleGraph {
+        /// The format to print the graph in
+        #[arg(long, value_enum, default_value_t)]
+        format: ModuleGraphFormat,
+    },
+
+    /// List the licences of the dependency packages
+    Licenses {
+        /// Print a NOTICE file with the text of each package's licence,
+        /// grouped by licence, for shipping with an application
+        #[arg(long)]
+        bundle: bool,
+    },
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum ModuleGraphFormat {
+    /// The DOT language of Graphviz
+    #[default]
+    Dot,
+    /// A JSON array of the modules and their imports
+    Json,
+}
+
+#[derive(Subcommand, Debug)]
+enum Registry {
+    /// Copy the Hex packages of a project to a directory, for using them
+    /// without access to Hex
+    ///
+    /// The directory can be served as a static Hex repository, or its packages
+    /// used by setting GLEAM_REGISTRY_DIRECTORY to the path of the directory.
+    ///
+    /// This command uses these environment variables:
+    ///
+    /// - HEXPM_ORGANIZATION_KEY_<ORGANIZATION>: (optional) The Hex API key to
+    ///   use for the packages of a Hex organisation.
+    /// - HEXPM_API_KEY: (optional) The Hex API key to use for the packages of a
+    ///   Hex organisation without a key of its own.
+    #[command(verbatim_doc_comment)]
+    Snapshot {
+        /// The manifest listing the packages to copy
+        #[arg(long, value_name = "MANIFEST", default_value = "manifest.toml")]
+        packages_from: Utf8PathBuf,
+
+        /// The directory to copy the packages to
+        #[arg(long, value_name = "DIRECTORY")]
+        out: Utf8PathBuf,
+    },
 }
 
 #[derive(Subcommand, Debug)]
@@ -369,8 +811,18 @@ enum Hex {
     #[command(verbatim_doc_comment)]
     Unretire { package: String, version: String },
 
+    /// Create a key to sign provenance attestations with
+    ///
+    /// Set the GLEAM_PROVENANCE_KEY environment variable to the key printed
+    /// to sign the attestation `gleam publish --provenance` adds to a package.
+    /// Keep the key secret, as anyone with it can sign attestations as you.
+    ProvenanceKey,
+
     /// Revert a release from Hex
     ///
+    /// A release can be reverted within an hour of publishing it, or within a
+    /// day of first publishing the package.
+    ///
     /// This command uses this environment variables:
     ///
     /// - HEXPM_USER: (optional) The Hex username to authenticate with.
@@ -382,6 +834,14 @@ enum Hex {
 
         #[arg(long)]
         version: Option<String>,
+
+        /// Also remove the release's documentation from HexDocs
+        #[arg(long)]
+        docs: bool,
+
+        /// Revert without asking for confirmation
+        #[arg(long)]
+        yes: bool,
     },
 }
 
@@ -392,6 +852,27 @@ enum Docs {
         /// Opens the docs in a browser after rendering
         #[arg(long)]
         open: bool,
+
+        /// Also write the docs to a self-contained archive that can be read
+        /// without a network connection
+        #[arg(long)]
+        offline_bundle: bool,
+
+        /// Also render the docs as a single Markdown file
+        #[arg(long)]
+        single_page: bool,
+    },
+
+    /// Render HTML docs and serve them on localhost, rendering them again
+    /// and reloading the browser whenever the project changes
+    Serve {
+        /// The port to serve the docs on
+        #[arg(long, default_value = "8000")]
+        port: u16,
+
+        /// Opens the docs in a browser after rendering
+        #[arg(long)]
+        open: bool,
     },
 
     /// Publish HTML docs to HexDocs
@@ -402,7 +883,19 @@ enum Docs {
     /// - HEXPM_PASS: (optional) The Hex password to authenticate with.
     /// - HEXPM_API_KEY: (optional) A Hex API key to use instead of authenticating.
     #[command(verbatim_doc_comment)]
-    Publish,
+    Publish {
+        /// Replace the docs of this version if they have already been
+        /// published
+        #[arg(long)]
+        replace: bool,
+    },
+
+    /// List the versions of a package that have docs published to HexDocs
+    List {
+        /// The name of the package, defaulting to this project's package
+        #[arg(long)]
+        package: Option<String>,
+    },
 
     /// Remove HTML docs from HexDocs
     ///
@@ -413,9 +906,9 @@ enum Docs {
     /// - HEXPM_API_KEY: (optional) A Hex API key to use instead of authenticating.
     #[command(verbatim_doc_comment)]
     Remove {
-        /// The name of the package
+        /// The name of the package, defaulting to this project's package
         #[arg(long)]
-        package: String,
+        package: Option<String>,
 
         /// The version of the docs to remove
         #[arg(long)]
@@ -424,21 +917,84 @@ enum Docs {
 }
 
 fn main() {
-    initialise_logger();
+    let Cli {
+        command,
+        log_format,
+        log_level,
+        color,
+        theme,
+        config_env,
+    } = Cli::parse();
+    logging::initialise(log_format, log_level);
+    cli::set_display(color, theme);
+    config::set_environment(config_env);
+    install_message_catalog();
     panic::add_handler();
     let stderr = cli::stderr_buffer_writer();
 
-    let result = match Command::parse() {
+    let result =
+        std::panic::catch_unwind(|| run(command)).unwrap_or_else(|_| crash_report::finish());
+
+    match result {
+        Ok(_) => {
+            tracing::info!("command_succeeded");
+        }
+        Err(error) => {
+            tracing::error!(error = ?error, "command_failed");
+            let mut buffer = stderr.buffer();
+            error.pretty_limited(&mut buffer, cli::theme(), cli::max_errors());
+            stderr.print(&buffer).expect("Final result error writing");
+            std::process::exit(1);
+        }
+    }
+}
+
+fn run(command: Command) -> Result<()> {
+    match command {
         Command::Build {
             target,
             warnings_as_errors,
-        } => command_build(target, warnings_as_errors),
+            verify_artifacts,
+            unstable,
+            errors,
+        } => {
+            cli::set_max_errors(errors.max_errors());
+            match target {
+                Some(BuildTarget::Wasm) if unstable => wasm::build(warnings_as_errors),
+                Some(BuildTarget::Wasm) => Err(Error::UnstableFeature {
+                    feature: "The wasm target".into(),
+                }),
+                Some(BuildTarget::Target(target)) => {
+                    command_build(Some(target), warnings_as_errors, verify_artifacts)
+                }
+                None => command_build(None, warnings_as_errors, verify_artifacts),
+            }
+        }
+
+        Command::Check {
+            target,
+            examples,
+            errors,
+        } => {
+            cli::set_max_errors(errors.max_errors());
+            command_check(target, examples)
+        }
 
-        Command::Check { target } => command_check(target),
+        Command::Docs(Docs::Build {
+            open,
+            offline_bundle,
+            single_page,
+        }) => docs::build(docs::BuildOptions {
+            open,
+            offline_bundle,
+            single_page,
+        }),
 
-        Command::Docs(Docs::Build { open }) => docs::build(docs::BuildOptions { open }),
+        Command::Docs(Docs::Serve { port, open }) => docs::serve(docs::ServeOptions { port, open }),
 
-        Command::Docs(Docs::Publish) => docs::publish(),
+        Command::Docs(Docs::Publish { replace }) => docs::publish(replace),
+
+        Command::Docs(Docs::List { package }) => docs::list(package),
 
         Command::Docs(Docs::Remove { package, version }) => docs::remove(package, version),
 
@@ -448,34 +1004,119 @@ fn main() {
             check,
         } => format::run(stdin, check, files),
 
+        Command::OrganizeImports { files, check } => organize_imports::run(check, files),
+
+        Command::Mv { from, to } => move_module::run(from, to),
+
         Command::Fix => fix::run(),
 
+        Command::Lint { json, fix } => lint::run(json, fix),
+
+        Command::Todos { json } => todos::list(json),
+
         Command::Deps(Dependencies::List) => dependencies::list(),
 
-        Command::Deps(Dependencies::Download) => download_dependencies(),
+        Command::Deps(Dependencies::Download {
+            concurrency,
+            summary,
+            json,
+        }) => download_dependencies(concurrency, summary, json),
+
+        Command::Deps(Dependencies::Update(options)) => update(options),
+
+        Command::Deps(Dependencies::Fix { apply }) => dependencies::fix(apply),
+
+        Command::Deps(Dependencies::Verify { provenance }) => dependencies::verify(provenance),
 
-        Command::Deps(Dependencies::Update) => dependencies::update(),
+        Command::Deps(Dependencies::ResolveLockConflict) => dependencies::resolve_lock_conflict(),
+
+        Command::Deps(Dependencies::ModuleGraph { format }) => dependencies::module_graph(format),
+
+        Command::Deps(Dependencies::Licenses { bundle }) => dependencies::licenses(bundle),
+
+        Command::Registry(Registry::Snapshot { packages_from, out }) => {
+            registry::snapshot(&packages_from, &out)
+        }
 
         Command::New(options) => new::create(options, COMPILER_VERSION),
 
         Command::Shell => shell::command(),
 
+        Command::Dialyzer => dialyzer::run(),
+
+        Command::Mutate {
+            target,
+            modules,
+            timeout,
+        } => mutate::run(target, modules, timeout),
+
+        Command::Daemon => daemon::command(),
+
         Command::Run {
             target,
             arguments,
             runtime,
             module,
-        } => run::command(arguments, target, runtime, module, run::Which::Src),
+            environment,
+            cwd,
+        } => {
+            let invocation = run::Invocation {
+                arguments,
+                environment,
+                directory: cwd,
+            };
+            run::command(invocation, target, runtime, module, run::Which::Src)
+        }
 
         Command::Test {
             target,
             arguments,
             runtime,
-        } => run::command(arguments, target, runtime, None, run::Which::Test),
+            shard,
+            parallel,
+            retries,
+            fail_on_flaky,
+            only_changed,
+            since,
+            all,
+            errors,
+        } => {
+            cli::set_max_errors(errors.max_errors());
+            let invocation = run::Invocation {
+                arguments,
+                ..run::Invocation::default()
+            };
+            let changes = match since {
+                _ if all || !only_changed => None,
+                Some(reference) => Some(run::Changes::SinceGitRef(reference)),
+                None => Some(run::Changes::SinceLastPassed),
+            };
+            let which = match (shard, parallel, retries, changes) {
+                (None, None, None, None) => run::Which::Test,
+                (shard, parallel, retries, changes) => run::Which::Tests {
+                    shard,
+                    changes,
+                    options: test_runner::Options {
+                        parallel: parallel.map(|parallel| parallel as usize),
+                        retries: retries.unwrap_or(0),
+                        fail_on_flaky,
+                    },
+                },
+            };
+            run::command(invocation, target, runtime, None, which)
+        }
 
         Command::CompilePackage(opts) => compile_package::command(opts),
 
-        Command::Publish { replace, yes } => publish::command(replace, yes),
+        Command::Publish {
+            replace,
+            yes,
+            provenance,
+        } => publish::command(replace, yes, provenance),
+
+        Command::Release { bump, publish, yes } => {
+            release::command(release::Options { bump, publish, yes })
+        }
 
         Command::PrintConfig => print_config(),
 
@@ -490,67 +1131,87 @@ fn main() {
             hex::UnretireCommand::new(package, version).run()
         }
 
-        Command::Hex(Hex::Revert { package, version }) => hex::revertcommand(package, version),
+        Command::Hex(Hex::ProvenanceKey) => {
+            let (key, public_key) = provenance::generate_key();
+            println!(
+                "GLEAM_PROVENANCE_KEY={key}\n\nPublic key: {public_key}\n\n\
+Packages depending on yours can trust it by adding it to `[deps.provenance_keys]` in gleam.toml."
+            );
+            Ok(())
+        }
+
+        Command::Hex(Hex::Revert {
+            package,
+            version,
+            docs,
+            yes,
+        }) => hex::revertcommand(package, version, docs, yes),
 
         Command::Add { packages, dev } => add::command(packages, dev),
 
         Command::Remove { packages } => remove::command(packages),
 
-        Command::Update => dependencies::update(),
+        Command::Update(options) => update(options),
 
         Command::Clean => clean(),
 
         Command::LanguageServer => lsp::main(),
 
-        Command::Export(ExportTarget::ErlangShipment) => export::erlang_shipment(),
+        Command::DebugAdapter => dap::main(),
+
+        Command::Kernel => kernel::main(),
+
+        Command::Export(ExportTarget::ErlangShipment { strip }) => export::erlang_shipment(strip),
+        Command::Export(ExportTarget::ErlangProject { output }) => export::erlang_project(output),
         Command::Export(ExportTarget::HexTarball) => export::hex_tarball(),
         Command::Export(ExportTarget::JavascriptPrelude) => export::javascript_prelude(),
         Command::Export(ExportTarget::TypescriptPrelude) => export::typescript_prelude(),
+        Command::Export(ExportTarget::PackageConfigSchema) => export::package_config_schema(),
         Command::Export(ExportTarget::PackageInterface { output }) => {
             export::package_interface(output)
         }
-    };
-
-    match result {
-        Ok(_) => {
-            tracing::info!("Successfully completed");
+        Command::Export(ExportTarget::ProjectInterface { output }) => {
+            export::project_interface(output)
         }
-        Err(error) => {
-            tracing::error!(error = ?error, "Failed");
-            let mut buffer = stderr.buffer();
-            error.pretty(&mut buffer);
-            stderr.print(&buffer).expect("Final result error writing");
-            std::process::exit(1);
+        Command::Export(ExportTarget::BuildMetadata { output, target }) => {
+            export::build_metadata(output, target)
         }
+
+        Command::Gen(Generate::Ffi { module, target }) => generate::ffi(module, target),
     }
 }
 
-fn command_check(target: Option<Target>) -> Result<()> {
-    let _ = build::main(
-        Options {
-            root_target_support: TargetSupport::Enforced,
-            warnings_as_errors: false,
-            codegen: Codegen::DepsOnly,
-            mode: Mode::Dev,
-            target,
-        },
-        build::download_dependencies()?,
-    )?;
-    Ok(())
+fn command_check(target: Option<Target>, examples: bool) -> Result<()> {
+    let options = || Options {
+        root_target_support: TargetSupport::Enforced,
+        warnings_as_errors: false,
+        verify_artefacts: false,
+        codegen: Codegen::DepsOnly,
+        mode: Mode::Dev,
+        target,
+    };
+    if !examples {
+        return build::main_or_daemon(options());
+    }
+    let paths = find_project_paths()?;
+    let manifest = build::download_dependencies()?;
+    let _ = build::main(options(), manifest.clone())?;
+    examples::check(&paths, &root_config()?, &manifest, options)
 }
 
-fn command_build(target: Option<Target>, warnings_as_errors: bool) -> Result<()> {
-    let _ = build::main(
-        Options {
-            root_target_support: TargetSupport::Enforced,
-            warnings_as_errors,
-            codegen: Codegen::All,
-            mode: Mode::Dev,
-            target,
-        },
-        build::download_dependencies()?,
-    )?;
-    Ok(())
+fn command_build(
+    target: Option<Target>,
+    warnings_as_errors: bool,
+    verify_artifacts: bool,
+) -> Result<()> {
+    build::main_or_daemon(Options {
+        root_target_support: TargetSupport::Enforced,
+        warnings_as_errors,
+        verify_artefacts: verify_artifacts,
+        codegen: Codegen::All,
+        mode: Mode::Dev,
+        target,
+    })
 }
 
 fn print_config() -> Result<()> {
@@ -564,15 +1225,30 @@ fn clean() -> Result<()> {
     fs::delete_directory(&paths.build_directory())
 }
 
-fn initialise_logger() {
-    let enable_colours = std::env::var("GLEAM_LOG_NOCOLOUR").is_err();
-    tracing_subscriber::fmt()
-        .with_writer(std::io::stderr)
-        .with_env_filter(std::env::var("GLEAM_LOG").unwrap_or_else(|_| "off".into()))
-        .with_target(false)
-        .with_ansi(enable_colours)
-        .without_time()
-        .init();
+/// Loads the translations of diagnostic messages from the catalog file named by
+/// the `GLEAM_MESSAGE_CATALOG` environment variable, if it is set.
+fn install_message_catalog() {
+    let Ok(path) = std::env::var("GLEAM_MESSAGE_CATALOG") else {
+        return;
+    };
+    let path = Utf8PathBuf::from(path);
+    match fs::read(&path).and_then(|src| Catalog::parse(&path, &src)) {
+        Ok(catalog) => {
+            for id in catalog.unknown_ids() {
+                tracing::warn!(id, "unknown_message_catalog_id");
+            }
+            catalog::install(catalog);
+        }
+        Err(error) => {
+            tracing::warn!(error = ?error, "message_catalog_not_loaded");
+            let stderr = cli::stderr_buffer_writer();
+            let mut buffer = stderr.buffer();
+            error.pretty(&mut buffer, cli::theme());
+            stderr
+                .print(&buffer)
+                .expect("Message catalog error writing");
+        }
+    }
 }
 
 fn find_project_paths() -> Result<ProjectPaths> {
@@ -586,8 +1262,32 @@ fn project_paths_at_current_directory_without_toml() -> ProjectPaths {
     ProjectPaths::new(current_dir)
 }
 
-fn download_dependencies() -> Result<()> {
+fn update(options: UpdateOptions) -> Result<()> {
+    if options.interactive {
+        dependencies::update_interactively()
+    } else {
+        dependencies::update(options.packages)
+    }
+}
+
+fn download_dependencies(concurrency: usize, summary: bool, json: bool) -> Result<()> {
     let paths = find_project_paths()?;
-    _ = dependencies::download(&paths, cli::Reporter::new(), None, UseManifest::Yes)?;
+    let use_manifest = UseManifest::Yes;
+    let manifest = if json {
+        dependencies::download_with_concurrency(
+            &paths,
+            cli::JsonResolutionReporter,
+            None,
+            use_manifest,
+            concurrency,
+        )?
+    } else {
+        let reporter = match summary {
+            true => cli::Reporter::with_resolution_summary(),
+            false => cli::Reporter::new(),
+        };
+        dependencies::download_with_concurrency(&paths, reporter, None, use_manifest, concurrency)?
+    };
+    dependencies::warn_about_retired_packages(&manifest);
     Ok(())
 }
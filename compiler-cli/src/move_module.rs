@@ -0,0 +1,90 @@
+use camino::Utf8PathBuf;
+use gleam_core::{
+    analyse::TargetSupport,
+    build::{Codegen, Mode, Options},
+    rename_module, Error, Result,
+};
+
+/// A module whose imports of the moved module have been rewritten.
+#[derive(Debug)]
+struct Rewritten {
+    path: Utf8PathBuf,
+    original: String,
+    rewritten: String,
+}
+
+pub fn run(from: Utf8PathBuf, to: Utf8PathBuf) -> Result<()> {
+    let paths = crate::find_project_paths()?;
+    let current_dir = crate::fs::get_current_directory()?;
+    let from_name = paths
+        .module_name(&current_dir.join(&from))
+        .ok_or_else(|| Error::NotAProjectModule { path: from.clone() })?;
+    let to_name = paths
+        .module_name(&current_dir.join(&to))
+        .ok_or_else(|| Error::NotAProjectModule { path: to.clone() })?;
+    if !crate::run::is_gleam_module(&to_name) {
+        return Err(Error::InvalidModuleName { module: to_name });
+    }
+    if to.exists() {
+        return Err(Error::OutputFilesAlreadyExist {
+            file_names: vec![to],
+        });
+    }
+    let moved = crate::fs::read(&from)?;
+    let manifest = crate::build::download_dependencies()?;
+
+    // The imports are rewritten before any file is changed, so that if a
+    // module cannot be parsed the project is left as it was.
+    let mut rewritten = vec![];
+    for directory in [paths.src_directory(), paths.test_directory()] {
+        for path in crate::fs::gleam_files_excluding_gitignore(&directory) {
+            let original = crate::fs::read(&path)?;
+            let output = rename_module::rename_imports(&original, &path, &from_name, &to_name)?;
+            if output != original {
+                rewritten.push(Rewritten {
+                    path,
+                    original,
+                    rewritten: output,
+                });
+            }
+        }
+    }
+
+    crate::fs::write(&to, &moved)?;
+    crate::fs::delete_file(&from)?;
+    for module in &rewritten {
+        crate::fs::write(&module.path, &module.rewritten)?;
+    }
+
+    // If the project no longer type checks then the move is undone.
+    let checked = crate::build::main(
+        Options {
+            root_target_support: TargetSupport::Enforced,
+            warnings_as_errors: false,
+            verify_artefacts: false,
+            codegen: Codegen::DepsOnly,
+            mode: Mode::Dev,
+            target: None,
+        },
+        manifest,
+    );
+    if let Err(error) = checked {
+        for module in &rewritten {
+            crate::fs::write(&module.path, &module.original)?;
+        }
+        crate::fs::write(&from, &moved)?;
+        crate::fs::delete_file(&to)?;
+        return Err(error);
+    }
+
+    println!(
+        "Moved {from_name} to {to_name}, updating imports in {} {}",
+        rewritten.len(),
+        if rewritten.len() == 1 {
+            "module"
+        } else {
+            "modules"
+        }
+    );
+    Ok(())
+}
@@ -0,0 +1,313 @@
+use std::{
+    process::{Child, Command, Stdio},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use camino::{Utf8Path, Utf8PathBuf};
+use gleam_core::{
+    build::Target,
+    error::{FileIoAction, FileKind},
+    mutation::{self, Mutant, Outcome},
+    Error, Result,
+};
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+
+/// The shortest time the tests may take against a mutant before they are
+/// stopped, for projects whose tests run quickly.
+const MINIMUM_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The file in the build directory the original source of the module being
+/// mutated is kept in while its mutant is tested.
+const ORIGINAL_FILE: &str = "mutate-original.json";
+
+/// Run the tests against each mutant of the project's source modules, or of
+/// the given modules, printing the mutants they do not catch. The source file
+/// of a module is changed while its mutants are tested and restored after
+/// each of them, including when testing one fails or is interrupted.
+pub(crate) fn run(
+    target: Option<Target>,
+    modules: Vec<String>,
+    timeout: Option<u64>,
+) -> Result<()> {
+    let paths = crate::find_project_paths()?;
+    let backup = paths.build_directory().join(ORIGINAL_FILE);
+    restore_interrupted(&backup)?;
+    let config = crate::config::root_config()?;
+    let target = target.unwrap_or(config.target);
+
+    let built = crate::build::analyse_root_package(Some(target), false)?;
+
+    let modules = built
+        .root_package
+        .modules
+        .iter()
+        .filter(|module| module.origin.is_src())
+        .filter(|module| modules.is_empty() || modules.contains(&module.name.to_string()))
+        .sorted_by(|a, b| a.input_path.cmp(&b.input_path))
+        .map(|module| {
+            let path = module
+                .input_path
+                .strip_prefix(paths.root())
+                .unwrap_or(&module.input_path);
+            let mutants = mutation::find(&module.ast, path, &module.code);
+            (module.input_path.clone(), module.code.clone(), mutants)
+        })
+        .collect_vec();
+    let total = modules
+        .iter()
+        .map(|(_, _, mutants)| mutants.len())
+        .sum::<usize>();
+
+    crate::cli::print_running("tests before mutating");
+    let target = target.to_string();
+    let started = Instant::now();
+    if gleam(&["test", "--target", &target], None)? != Some(true) {
+        return Err(Error::MutationTestsFailing);
+    }
+    let timeout = match timeout {
+        Some(seconds) => Duration::from_secs(seconds),
+        None => (started.elapsed() * 4).max(MINIMUM_TIMEOUT),
+    };
+
+    // Stop after the current mutant on ctrl+c, so that its module is restored
+    let interrupted = Arc::new(AtomicBool::new(false));
+    let handler_interrupted = interrupted.clone();
+    ctrlc::set_handler(move || handler_interrupted.store(true, Ordering::SeqCst))
+        .expect("Error setting Ctrl-C handler");
+
+    let mut results = vec![];
+    let mutants = modules
+        .iter()
+        .flat_map(|(input_path, code, mutants)| mutants.iter().map(move |m| (input_path, code, m)));
+    for (index, (input_path, code, mutant)) in mutants.enumerate() {
+        if interrupted.load(Ordering::SeqCst) {
+            break;
+        }
+        let Mutant {
+            path, line, column, ..
+        } = mutant;
+        let progress = format!("{path}:{line}:{column} ({} of {total})", index + 1);
+        crate::cli::print_colourful_prefix("Mutating", &progress);
+
+        let mutated = MutatedFile::write(input_path, code, &backup, mutant)?;
+        let outcome = test_mutant(&target, timeout);
+        mutated.restore()?;
+        results.push((mutant.clone(), outcome?));
+    }
+
+    print!("{}", mutation::to_text(&results));
+    let survived = results
+        .iter()
+        .filter(|(_, outcome)| *outcome == Outcome::Survived)
+        .count();
+    if survived == 0 {
+        Ok(())
+    } else {
+        Err(Error::MutantsSurvived { count: survived })
+    }
+}
+
+/// The original source of a module, as kept in the build directory while a
+/// mutant of it is tested.
+#[derive(Debug, Serialize, Deserialize)]
+struct Original {
+    path: Utf8PathBuf,
+    code: String,
+}
+
+/// A source file changed to a mutant. It is restored when dropped, so that
+/// it is restored however testing the mutant ends, and its original source
+/// is kept in the build directory until then so that a file left mutated by
+/// a run that was killed is restored by the next one.
+struct MutatedFile<'a> {
+    path: &'a Utf8Path,
+    code: &'a str,
+    backup: &'a Utf8Path,
+    restored: bool,
+}
+
+impl<'a> MutatedFile<'a> {
+    fn write(
+        path: &'a Utf8Path,
+        code: &'a str,
+        backup: &'a Utf8Path,
+        mutant: &Mutant,
+    ) -> Result<Self> {
+        let original = Original {
+            path: path.to_path_buf(),
+            code: code.into(),
+        };
+        let json = serde_json::to_string(&original).expect("mutated module to json");
+        crate::fs::write(backup, &json)?;
+        let file = Self {
+            path,
+            code,
+            backup,
+            restored: false,
+        };
+        crate::fs::write(path, &mutation::apply(code, mutant))?;
+        Ok(file)
+    }
+
+    fn restore(mut self) -> Result<()> {
+        crate::fs::write(self.path, self.code)?;
+        self.restored = true;
+        crate::fs::delete_file(self.backup)
+    }
+}
+
+impl Drop for MutatedFile<'_> {
+    fn drop(&mut self) {
+        if !self.restored {
+            let _ = crate::fs::write(self.path, self.code)
+                .and_then(|_| crate::fs::delete_file(self.backup));
+        }
+    }
+}
+
+/// Restores the module left mutated by a run that was stopped before it
+/// could restore it itself.
+fn restore_interrupted(backup: &Utf8Path) -> Result<()> {
+    if !backup.is_file() {
+        return Ok(());
+    }
+    let original: Original =
+        serde_json::from_str(&crate::fs::read(backup)?).map_err(|error| Error::FileIo {
+            action: FileIoAction::Parse,
+            kind: FileKind::File,
+            path: backup.to_path_buf(),
+            err: Some(error.to_string()),
+        })?;
+    crate::cli::print_colourful_prefix("Restoring", original.path.as_str());
+    crate::fs::write(&original.path, &original.code)?;
+    crate::fs::delete_file(backup)
+}
+
+fn test_mutant(target: &str, timeout: Duration) -> Result<Outcome> {
+    // Type checking is enough to tell whether the mutant compiles, and the
+    // tests build it anyway.
+    match gleam(&["check", "--target", target], Some(timeout))? {
+        Some(true) => (),
+        Some(false) => return Ok(Outcome::NotCompiled),
+        None => return Ok(Outcome::TimedOut),
+    }
+    Ok(match gleam(&["test", "--target", target], Some(timeout))? {
+        Some(true) => Outcome::Survived,
+        Some(false) => Outcome::Killed,
+        None => Outcome::TimedOut,
+    })
+}
+
+/// Runs this Gleam executable with the arguments, hiding its output. Returns
+/// whether it succeeded, or `None` if it did not finish in time.
+fn gleam(args: &[&str], timeout: Option<Duration>) -> Result<Option<bool>> {
+    let executable = std::env::current_exe().map_err(|error| Error::ShellCommand {
+        program: "gleam".into(),
+        err: Some(error.kind()),
+    })?;
+    let mut command = Command::new(executable);
+    let _ = command
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+    // The tests run in a process started by Gleam, so they are put in a
+    // process group of their own that can be stopped all at once.
+    #[cfg(unix)]
+    let _ = std::os::unix::process::CommandExt::process_group(&mut command, 0);
+    tracing::info!(command = ?command, "running_os_process");
+    let mut child = command.spawn().map_err(|error| Error::ShellCommand {
+        program: "gleam".into(),
+        err: Some(error.kind()),
+    })?;
+
+    let started = Instant::now();
+    loop {
+        let status = child.try_wait().map_err(|error| Error::ShellCommand {
+            program: "gleam".into(),
+            err: Some(error.kind()),
+        })?;
+        if let Some(status) = status {
+            return Ok(Some(status.success()));
+        }
+        if timeout.is_some_and(|timeout| started.elapsed() > timeout) {
+            stop(&mut child);
+            return Ok(None);
+        }
+        std::thread::sleep(Duration::from_millis(20));
+    }
+}
+
+fn stop(child: &mut Child) {
+    #[cfg(unix)]
+    let _ = Command::new("kill")
+        .arg("-KILL")
+        .arg(format!("-{}", child.id()))
+        .stderr(Stdio::null())
+        .status();
+    let _ = child.kill();
+    let _ = child.wait();
+}
+
+#[cfg(test)]
+mod tests {
+    use gleam_core::mutation::Mutation;
+
+    use super::*;
+
+    fn mutant() -> Mutant {
+        Mutant {
+            mutation: Mutation::FlipComparison,
+            module: "wibble".into(),
+            path: "src/wibble.gleam".into(),
+            line: 1,
+            column: 17,
+            start: 16,
+            end: 17,
+            replacement: ">=".into(),
+            description: "`<` replaced with `>=`".into(),
+        }
+    }
+
+    #[test]
+    fn mutated_file_is_restored_when_dropped() {
+        let directory = tempfile::tempdir().expect("temporary directory");
+        let directory = Utf8Path::from_path(directory.path()).expect("utf8 path");
+        let path = directory.join("src/wibble.gleam");
+        let backup = directory.join("build").join(ORIGINAL_FILE);
+        let code = "pub fn f(a) { a < 1 }";
+        crate::fs::write(&path, code).expect("write module");
+
+        let mutated = MutatedFile::write(&path, code, &backup, &mutant()).expect("mutate");
+        assert_eq!(
+            crate::fs::read(&path).expect("read module"),
+            "pub fn f(a) { a >= 1 }"
+        );
+        drop(mutated);
+        assert_eq!(crate::fs::read(&path).expect("read module"), code);
+        assert!(!backup.exists());
+    }
+
+    #[test]
+    fn module_left_mutated_is_restored() {
+        let directory = tempfile::tempdir().expect("temporary directory");
+        let directory = Utf8Path::from_path(directory.path()).expect("utf8 path");
+        let path = directory.join("src/wibble.gleam");
+        let backup = directory.join("build").join(ORIGINAL_FILE);
+        let code = "pub fn f(a) { a < 1 }";
+        crate::fs::write(&path, code).expect("write module");
+
+        let mut mutated = MutatedFile::write(&path, code, &backup, &mutant()).expect("mutate");
+        // As if Gleam was killed before the module could be restored
+        mutated.restored = true;
+        drop(mutated);
+        restore_interrupted(&backup).expect("restore");
+        assert_eq!(crate::fs::read(&path).expect("read module"), code);
+        assert!(!backup.exists());
+    }
+}
@@ -21,6 +21,11 @@ const ERLANG_OTP_VERSION: &str = "26.0.2";
 const REBAR3_VERSION: &str = "3";
 const ELIXIR_VERSION: &str = "1.15.4";
 
+const GITHUB_RELEASE_TEMPLATE: &str = include_str!("../templates/new/github-release.yml");
+const GITHUB_UPDATE_TEMPLATE: &str = include_str!("../templates/new/github-update.yml");
+const GITHUB_DEPENDABOT_TEMPLATE: &str = include_str!("../templates/new/github-dependabot.yml");
+const GITLAB_CI_TEMPLATE: &str = include_str!("../templates/new/gitlab-ci.yml");
+
 #[derive(
     Debug, Serialize, Deserialize, Display, EnumString, VariantNames, ValueEnum, Clone, Copy,
 )]
@@ -49,6 +54,10 @@ enum FileToCreate {
     TestModule,
     GleamToml,
     GithubCi,
+    GithubRelease,
+    GithubUpdate,
+    GithubDependabot,
+    GitlabCi,
 }
 
 impl FileToCreate {
@@ -66,6 +75,10 @@ impl FileToCreate {
                 .join(Utf8PathBuf::from(format!("{project_name}_test.gleam"))),
             Self::GleamToml => creator.root.join(Utf8PathBuf::from("gleam.toml")),
             Self::GithubCi => creator.workflows.join(Utf8PathBuf::from("test.yml")),
+            Self::GithubRelease => creator.workflows.join(Utf8PathBuf::from("release.yml")),
+            Self::GithubUpdate => creator.workflows.join(Utf8PathBuf::from("update.yml")),
+            Self::GithubDependabot => creator.github.join(Utf8PathBuf::from("dependabot.yml")),
+            Self::GitlabCi => creator.root.join(Utf8PathBuf::from(".gitlab-ci.yml")),
         }
     }
 
@@ -73,6 +86,8 @@ impl FileToCreate {
         let project_name = &creator.project_name;
         let skip_git = creator.options.skip_git;
         let skip_github = creator.options.skip_github;
+        let github = creator.options.github;
+        let gitlab = creator.options.gitlab;
         let gleam_version = creator.gleam_version;
 
         match self {
@@ -189,11 +204,36 @@ jobs:
       - run: gleam format --check src test
 "#,
             )),
-            Self::GithubCi | Self::Gitignore => None,
+
+            Self::GithubRelease if github => {
+                Some(ci_template(GITHUB_RELEASE_TEMPLATE, gleam_version))
+            }
+            Self::GithubUpdate if github => {
+                Some(ci_template(GITHUB_UPDATE_TEMPLATE, gleam_version))
+            }
+            Self::GithubDependabot if github => {
+                Some(ci_template(GITHUB_DEPENDABOT_TEMPLATE, gleam_version))
+            }
+            Self::GitlabCi if gitlab => Some(ci_template(GITLAB_CI_TEMPLATE, gleam_version)),
+
+            Self::GithubCi
+            | Self::GithubRelease
+            | Self::GithubUpdate
+            | Self::GithubDependabot
+            | Self::GitlabCi
+            | Self::Gitignore => None,
         }
     }
 }
 
+/// Fills in the versions of the tools to install in a CI template.
+fn ci_template(template: &str, gleam_version: &str) -> String {
+    template
+        .replace("$GLEAM_VERSION_FROM_GLEAM", gleam_version)
+        .replace("$OTP_VERSION_FROM_GLEAM", ERLANG_OTP_VERSION)
+        .replace("$REBAR3_VERSION_FROM_GLEAM", REBAR3_VERSION)
+}
+
 impl Creator {
     fn new(options: NewOptions, gleam_version: &'static str) -> Result<Self, Error> {
         let project_name = if let Some(name) = options.name.clone() {
@@ -301,7 +341,7 @@ fn validate_root_folder(creator: &Creator) -> Result<(), Error> {
 
     for t in FileToCreate::iter() {
         let full_path = t.location(creator);
-        if full_path.exists() {
+        if t.contents(creator).is_some() && full_path.exists() {
             duplicate_files.push(full_path);
         }
     }
@@ -15,6 +15,8 @@ fn new() {
             name: None,
             skip_git: false,
             skip_github: false,
+            github: false,
+            gitlab: false,
         },
         "1.0.0-gleam",
     )
@@ -33,6 +35,64 @@ fn new() {
     assert!(toml.contains("name = \"my_project\""));
 }
 
+#[test]
+fn new_with_github() {
+    let tmp = tempfile::tempdir().unwrap();
+    let path = Utf8PathBuf::from_path_buf(tmp.path().join("my_project")).expect("Non Utf8 Path");
+
+    let creator = super::Creator::new(
+        super::NewOptions {
+            project_root: path.to_string(),
+            template: super::Template::Lib,
+            name: None,
+            skip_git: false,
+            skip_github: false,
+            github: true,
+            gitlab: false,
+        },
+        "1.0.0-gleam",
+    )
+    .unwrap();
+    creator.run().unwrap();
+
+    assert!(path.join(".github/workflows/test.yml").exists());
+    assert!(path.join(".github/dependabot.yml").exists());
+    assert!(path.join(".github/workflows/update.yml").exists());
+    assert!(!path.join(".gitlab-ci.yml").exists());
+
+    let release = crate::fs::read(path.join(".github/workflows/release.yml")).unwrap();
+    assert!(release.contains("gleam-version: \"1.0.0-gleam\""));
+    assert!(release.contains("gleam publish --yes"));
+    assert!(!release.contains("_FROM_GLEAM"));
+}
+
+#[test]
+fn new_with_gitlab() {
+    let tmp = tempfile::tempdir().unwrap();
+    let path = Utf8PathBuf::from_path_buf(tmp.path().join("my_project")).expect("Non Utf8 Path");
+
+    let creator = super::Creator::new(
+        super::NewOptions {
+            project_root: path.to_string(),
+            template: super::Template::Lib,
+            name: None,
+            skip_git: false,
+            skip_github: true,
+            github: false,
+            gitlab: true,
+        },
+        "1.0.0-gleam",
+    )
+    .unwrap();
+    creator.run().unwrap();
+
+    assert!(!path.join(".github").exists());
+    let pipeline = crate::fs::read(path.join(".gitlab-ci.yml")).unwrap();
+    assert!(pipeline.contains("image: ghcr.io/gleam-lang/gleam:v1.0.0-gleam-erlang-alpine"));
+    assert!(pipeline.contains("gleam update"));
+    assert!(!pipeline.contains("_FROM_GLEAM"));
+}
+
 #[test]
 fn new_with_skip_git() {
     let tmp = tempfile::tempdir().unwrap();
@@ -45,6 +105,8 @@ fn new_with_skip_git() {
             name: None,
             skip_git: true,
             skip_github: false,
+            github: false,
+            gitlab: false,
         },
         "1.0.0-gleam",
     )
@@ -66,6 +128,8 @@ fn new_with_skip_github() {
             name: None,
             skip_git: false,
             skip_github: true,
+            github: false,
+            gitlab: false,
         },
         "1.0.0-gleam",
     )
@@ -90,6 +154,8 @@ fn new_with_skip_git_and_github() {
             name: None,
             skip_git: true,
             skip_github: true,
+            github: false,
+            gitlab: false,
         },
         "1.0.0-gleam",
     )
@@ -114,6 +180,8 @@ fn invalid_path() {
             name: None,
             skip_git: false,
             skip_github: false,
+            github: false,
+            gitlab: false,
         },
         "1.0.0-gleam",
     )
@@ -132,6 +200,8 @@ fn invalid_name() {
             name: Some("-".into()),
             skip_git: false,
             skip_github: false,
+            github: false,
+            gitlab: false,
         },
         "1.0.0-gleam",
     )
@@ -152,6 +222,8 @@ fn existing_directory_no_files() {
             name: None,
             skip_git: true,
             skip_github: true,
+            github: false,
+            gitlab: false,
         },
         "1.0.0-gleam",
     )
@@ -179,6 +251,8 @@ fn existing_directory_with_one_existing_file() {
             name: None,
             skip_git: true,
             skip_github: true,
+            github: false,
+            gitlab: false,
         },
         "1.0.0-gleam",
     )
@@ -202,6 +276,8 @@ fn existing_directory_with_non_generated_file() {
             name: None,
             skip_git: true,
             skip_github: true,
+            github: false,
+            gitlab: false,
         },
         "1.0.0-gleam",
     )
@@ -232,6 +308,8 @@ fn conflict_with_existing_files() {
                 name: None,
                 skip_git: true,
                 skip_github: true,
+                github: false,
+                gitlab: false,
             },
             "1.0.0-gleam",
         )
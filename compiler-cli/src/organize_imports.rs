@@ -0,0 +1,188 @@
+use std::{str::FromStr, sync::Arc};
+
+use camino::{Utf8Path, Utf8PathBuf};
+use ecow::EcoString;
+use gleam_core::{
+    analyse::TargetSupport,
+    build::{Codegen, Mode, Options, Origin, Outcome, ProjectCompiler},
+    error::{FileIoAction, FileKind},
+    organize_imports::ImportOrganizer,
+    paths::ProjectPaths,
+    warning::NullWarningEmitterIO,
+    Error, Result,
+};
+
+use crate::{build_lock::BuildLock, cli};
+
+/// A module of the root package whose imports are to be organized.
+#[derive(Debug)]
+struct Target {
+    name: EcoString,
+    path: Utf8PathBuf,
+    origin: Origin,
+}
+
+pub fn run(check: bool, files: Vec<String>) -> Result<()> {
+    let paths = crate::find_project_paths()?;
+    let config = crate::config::root_config()?;
+    let targets = target_modules(&paths, files)?;
+    let manifest = crate::build::download_dependencies()?;
+
+    let mode = Mode::Dev;
+    let target = config.target;
+    let telemetry = Box::new(cli::Reporter::new());
+    let lock = BuildLock::new_target(&paths, mode, target)?;
+    let _guard = lock.lock(telemetry.as_ref());
+    let options = Options {
+        mode,
+        target: Some(target),
+        codegen: Codegen::None,
+        warnings_as_errors: false,
+        verify_artefacts: false,
+        root_target_support: TargetSupport::NotEnforced,
+    };
+    let mut compiler = ProjectCompiler::new(
+        config.clone(),
+        options,
+        manifest.packages,
+        telemetry,
+        Arc::new(NullWarningEmitterIO),
+        paths.clone(),
+        crate::fs::ProjectIO::new(),
+    );
+    compiler.environment = std::env::vars().collect();
+    compiler.check_gleam_version()?;
+    let _ = compiler.compile_dependencies()?;
+
+    // A module can only be analysed once the modules it imports have been, so
+    // if a module has errors that adding imports fixes then the modules that
+    // import it are organized again once it has been compiled.
+    let mut remaining = targets;
+    let mut unorganized = vec![];
+    let mut errors = vec![];
+    loop {
+        let _ = compiler.compile_root_package();
+        let mut progressed = false;
+        errors.clear();
+        let mut failed = vec![];
+
+        for module in remaining {
+            let src = crate::fs::read(&module.path)?;
+            let organizer = ImportOrganizer {
+                module_name: &module.name,
+                path: &module.path,
+                origin: module.origin,
+                target,
+                mode,
+                package_config: &config,
+                importable_modules: compiler.get_importable_modules(),
+            };
+            let (organized, error) = match organizer.organize(&src) {
+                Outcome::Ok(organized) => (organized, None),
+                Outcome::PartialFailure(organized, error) => (organized, Some(error)),
+                Outcome::TotalFailure(error) => return Err(error),
+            };
+
+            if organized != src {
+                if check {
+                    unorganized.push(module.path.clone());
+                } else {
+                    crate::fs::write(&module.path, &organized)?;
+                    progressed = true;
+                }
+            }
+            if let Some(error) = error {
+                errors.push(error);
+                failed.push(module);
+            }
+        }
+
+        if failed.is_empty() || !progressed {
+            break;
+        }
+        remaining = failed;
+    }
+
+    if !unorganized.is_empty() {
+        return Err(Error::UnorganizedImports { files: unorganized });
+    }
+    match errors.into_iter().next() {
+        Some(error) => Err(error),
+        None => Ok(()),
+    }
+}
+
+/// The modules of the root package in the given files and directories.
+fn target_modules(paths: &ProjectPaths, files: Vec<String>) -> Result<Vec<Target>> {
+    let mut directories = vec![];
+    for (directory, origin) in [
+        (paths.src_directory(), Origin::Src),
+        (paths.test_directory(), Origin::Test),
+    ] {
+        if directory.is_dir() {
+            directories.push((crate::fs::canonicalise(&directory)?, origin));
+        }
+    }
+
+    let mut targets = vec![];
+    for file_path in files {
+        let path = Utf8PathBuf::from_str(&file_path).map_err(|e| Error::FileIo {
+            action: FileIoAction::Open,
+            kind: FileKind::File,
+            path: Utf8PathBuf::from(file_path),
+            err: Some(e.to_string()),
+        })?;
+
+        let files: Vec<_> = if path.is_dir() {
+            crate::fs::gleam_files_excluding_gitignore(&path).collect()
+        } else {
+            vec![path]
+        };
+        for path in files {
+            let canonical = crate::fs::canonicalise(&path)?;
+            let Some((name, origin)) = directories.iter().find_map(|(directory, origin)| {
+                Some((module_name(directory, &canonical)?, *origin))
+            }) else {
+                continue;
+            };
+            if targets.iter().all(|target: &Target| target.name != name) {
+                targets.push(Target { name, path, origin });
+            }
+        }
+    }
+    targets.sort_by(|one, other| one.name.cmp(&other.name));
+    Ok(targets)
+}
+
+fn module_name(directory: &Utf8Path, path: &Utf8Path) -> Option<EcoString> {
+    let relative = path.strip_prefix(directory).ok()?;
+    let name = relative.as_str().strip_suffix(".gleam")?;
+    Some(name.replace('\\', "/").into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn module_name_in_directory() {
+        assert_eq!(
+            module_name(
+                Utf8Path::new("/project/src"),
+                Utf8Path::new("/project/src/my/module.gleam")
+            ),
+            Some("my/module".into())
+        );
+    }
+
+    #[test]
+    fn module_name_outside_directory() {
+        assert_eq!(
+            module_name(
+                Utf8Path::new("/project/src"),
+                Utf8Path::new("/project/test/my/module.gleam")
+            ),
+            None
+        );
+    }
+}
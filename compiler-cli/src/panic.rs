@@ -20,6 +20,18 @@ fn print_compiler_bug_message(info: &PanicInfo<'_>) {
         None => "".into(),
         Some(location) => format!("{}:{}\n\t", location.file(), location.line()),
     };
+    let crash_report = match crate::crash_report::write(&format!("{location}{message}")) {
+        None => "".into(),
+        Some(directory) => format!(
+            "
+A crash report has been written to {directory}
+Please attach its files to your report, once you have checked that they do
+not include any code you cannot share. They can be put in one archive with:
+
+    tar -czf gleam-crash.tar.gz -C {directory} .
+"
+        ),
+    };
 
     let buffer_writer = crate::cli::stderr_buffer_writer();
     let mut buffer = buffer_writer.buffer();
@@ -48,11 +60,12 @@ steps to reproduce the crash that would be a great help.
 
 You may also want to try again with the `GLEAM_LOG=trace` environment
 variable set.
-",
+{crash_report}",
         location = location,
         message = message,
         version = env!("CARGO_PKG_VERSION"),
         os = std::env::consts::OS,
+        crash_report = crash_report,
     )
     .unwrap();
     buffer_writer.print(&buffer).unwrap();
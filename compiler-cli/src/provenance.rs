@@ -0,0 +1,400 @@
+//! Provenance attestations, which record how a package was published: who or
+//! what built it, the commit of the source it was built from, and the
+//! versions of the tools used. `gleam publish --provenance` signs one and adds
+//! it to the files of the package, and `gleam deps verify --provenance` checks
+//! those of the dependencies that have one.
+//!
+//! An attestation is signed with an Ed25519 key, given to `gleam publish` in
+//! the `GLEAM_PROVENANCE_KEY` environment variable. It lists every other file
+//! of the package with its SHA-256 hash, so any change made to the package
+//! after it was signed is caught. The public half of the key is included, but
+//! anyone can sign a package with a key of their own, so an attestation is
+//! only trusted if it is signed with the key given for the package in the
+//! `deps.provenance_keys` table of `gleam.toml`.
+
+use std::collections::BTreeMap;
+
+use camino::{Utf8Path, Utf8PathBuf};
+use ecow::EcoString;
+use gleam_core::{hex::integrity, io::FileSystemReader, Error, Result};
+use itertools::Itertools;
+use ring::{
+    rand::SystemRandom,
+    signature::{self, Ed25519KeyPair, KeyPair},
+};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// The file of a package the attestation is kept in.
+pub const ATTESTATION_FILE: &str = "gleam-provenance.json";
+
+/// The environment variable holding the key attestations are signed with,
+/// as a hex encoded PKCS#8 document.
+const KEY: &str = "GLEAM_PROVENANCE_KEY";
+
+/// What an attestation says about a release of a package.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Statement {
+    pub package: EcoString,
+    pub version: String,
+    pub builder: Builder,
+    pub source: Source,
+    /// The version of each tool used to build the package.
+    pub toolchain: BTreeMap<String, String>,
+    /// The SHA-256 hash of each file of the package, hex encoded.
+    pub files: BTreeMap<Utf8PathBuf, String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Builder {
+    /// The CI job that published the package, or `local` if it was published
+    /// from elsewhere.
+    pub id: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Source {
+    pub repository: Option<String>,
+    pub commit: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Attestation {
+    pub statement: Statement,
+    /// The public key the statement was signed with, hex encoded.
+    pub public_key: String,
+    /// The signature of the statement as JSON, hex encoded.
+    pub signature: String,
+}
+
+impl Statement {
+    /// A statement about the release of the package with the given files,
+    /// built in the current environment.
+    pub fn new(
+        package: &str,
+        version: &str,
+        repository: Option<String>,
+        root: &Utf8Path,
+        files: &[(Utf8PathBuf, Vec<u8>)],
+    ) -> Self {
+        let toolchain = [("gleam", gleam_core::version::COMPILER_VERSION)]
+            .into_iter()
+            .map(|(tool, version)| (tool.into(), version.into()))
+            .collect();
+        Self {
+            package: package.into(),
+            version: version.into(),
+            builder: Builder { id: builder_id() },
+            source: Source {
+                repository,
+                commit: git_commit(root),
+            },
+            toolchain,
+            files: files
+                .iter()
+                .map(|(path, contents)| (path.clone(), hash(contents)))
+                .collect(),
+        }
+    }
+
+    fn to_json(&self) -> Vec<u8> {
+        serde_json::to_vec(self).expect("provenance statement to json")
+    }
+
+    /// Signs the statement with the key in `GLEAM_PROVENANCE_KEY`.
+    pub fn sign(self) -> Result<Attestation> {
+        let key = std::env::var(KEY).map_err(|_| Error::ProvenanceKeyMissing)?;
+        let key = base16::decode(key.trim().as_bytes())
+            .ok()
+            .and_then(|key| Ed25519KeyPair::from_pkcs8_maybe_unchecked(&key).ok())
+            .ok_or(Error::InvalidProvenanceKey)?;
+        Ok(self.sign_with(&key))
+    }
+
+    fn sign_with(self, key: &Ed25519KeyPair) -> Attestation {
+        let signature = key.sign(&self.to_json());
+        Attestation {
+            statement: self,
+            public_key: base16::encode_lower(key.public_key().as_ref()),
+            signature: base16::encode_lower(signature.as_ref()),
+        }
+    }
+}
+
+impl Attestation {
+    pub fn to_json(&self) -> String {
+        let mut json = serde_json::to_string_pretty(self).expect("provenance to json");
+        json.push('\n');
+        json
+    }
+
+    /// Checks the attestation in the directory a release of a package was
+    /// extracted to against the key it is trusted to be signed with,
+    /// returning what is wrong with it if anything is. Returns `None` if the
+    /// package has no attestation and none is expected.
+    pub fn check_package(
+        io: &impl FileSystemReader,
+        directory: &Utf8Path,
+        package: &str,
+        version: &str,
+        trusted_key: Option<&str>,
+    ) -> Result<Option<Vec<String>>> {
+        let path = directory.join(ATTESTATION_FILE);
+        if !io.is_file(&path) {
+            return Ok(trusted_key.map(|_| {
+                vec![format!(
+                    "it has no {ATTESTATION_FILE}, but one signed with its trusted key is expected"
+                )]
+            }));
+        }
+        let attestation: Self = match serde_json::from_str(&io.read(&path)?) {
+            Ok(attestation) => attestation,
+            Err(_) => return Ok(Some(vec![format!("{ATTESTATION_FILE} is not valid")])),
+        };
+
+        let mut problems = vec![];
+        match trusted_key {
+            None => problems.push(format!(
+                "its key {} is not trusted, add it to `deps.provenance_keys` in gleam.toml to trust it",
+                attestation.public_key
+            )),
+            Some(key) if !key.eq_ignore_ascii_case(&attestation.public_key) => {
+                problems.push("it is not signed with its trusted key".into())
+            }
+            Some(key) => problems.extend(attestation.check_signature(key).err()),
+        }
+        let statement = &attestation.statement;
+        if statement.package != package || statement.version != version {
+            problems.push(format!(
+                "it attests to {} v{}",
+                statement.package, statement.version
+            ));
+        }
+        for (file, expected) in &statement.files {
+            let path = directory.join(file);
+            let inside = file
+                .components()
+                .all(|component| matches!(component, camino::Utf8Component::Normal(_)));
+            if !inside {
+                problems.push(format!("{file} is not in the package"));
+            } else if !io.is_file(&path) {
+                problems.push(format!("{file} is missing"));
+            } else if hash(&io.read_bytes(&path)?) != *expected {
+                problems.push(format!("{file} has changed since it was signed"));
+            }
+        }
+        for file in integrity::files(io, directory)?.into_iter().sorted() {
+            if file != ATTESTATION_FILE && !statement.files.contains_key(&file) {
+                problems.push(format!("{file} was not signed"));
+            }
+        }
+        Ok(Some(problems))
+    }
+
+    /// Checks the statement was signed with the given hex encoded public key.
+    fn check_signature(&self, public_key: &str) -> Result<(), String> {
+        let invalid = || "its signature is not valid".to_string();
+        let public_key = base16::decode(public_key.as_bytes()).map_err(|_| invalid())?;
+        let signature = base16::decode(self.signature.as_bytes()).map_err(|_| invalid())?;
+        signature::UnparsedPublicKey::new(&signature::ED25519, public_key)
+            .verify(&self.statement.to_json(), &signature)
+            .map_err(|_| invalid())
+    }
+}
+
+/// Generates a key to sign attestations with, returning it as a hex encoded
+/// PKCS#8 document along with its hex encoded public key.
+pub fn generate_key() -> (String, String) {
+    let document =
+        Ed25519KeyPair::generate_pkcs8(&SystemRandom::new()).expect("generate provenance key");
+    let key = Ed25519KeyPair::from_pkcs8(document.as_ref()).expect("read provenance key");
+    (
+        base16::encode_lower(document.as_ref()),
+        base16::encode_lower(key.public_key().as_ref()),
+    )
+}
+
+fn hash(contents: &[u8]) -> String {
+    base16::encode_lower(&Sha256::digest(contents))
+}
+
+/// The CI job the package is being published by, from the variables the CI
+/// service sets.
+fn builder_id() -> String {
+    let var = |name| std::env::var(name).ok().filter(|value| !value.is_empty());
+    if let (Some(server), Some(repository), Some(run)) = (
+        var("GITHUB_SERVER_URL"),
+        var("GITHUB_REPOSITORY"),
+        var("GITHUB_RUN_ID"),
+    ) {
+        return format!("{server}/{repository}/actions/runs/{run}");
+    }
+    var("CI_JOB_URL").unwrap_or_else(|| "local".into())
+}
+
+/// The commit checked out in the git repository the project is in, if it is
+/// in one.
+fn git_commit(root: &Utf8Path) -> Option<String> {
+    let output = std::process::Command::new("git")
+        .args(["rev-parse", "HEAD"])
+        .stdin(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .current_dir(root)
+        .output()
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).trim().into())
+}
+
+#[cfg(test)]
+mod tests {
+    use gleam_core::io::{memory::InMemoryFileSystem, FileSystemWriter};
+
+    use super::*;
+
+    fn statement() -> Statement {
+        Statement {
+            package: "wibble".into(),
+            version: "1.0.0".into(),
+            builder: Builder { id: "local".into() },
+            source: Source {
+                repository: None,
+                commit: Some("abc123".into()),
+            },
+            toolchain: [("gleam".into(), "1.0.0".into())].into(),
+            files: [("src/wibble.gleam".into(), hash(b"pub fn main() { 1 }"))].into(),
+        }
+    }
+
+    fn key() -> Ed25519KeyPair {
+        let (key, _) = generate_key();
+        Ed25519KeyPair::from_pkcs8(&base16::decode(key.as_bytes()).expect("hex")).expect("key")
+    }
+
+    fn extracted(attestation: &Attestation, source: &str) -> InMemoryFileSystem {
+        let fs = InMemoryFileSystem::new();
+        let directory = Utf8Path::new("/wibble");
+        fs.write(&directory.join("src/wibble.gleam"), source)
+            .expect("write source");
+        fs.write(&directory.join(ATTESTATION_FILE), &attestation.to_json())
+            .expect("write attestation");
+        fs
+    }
+
+    fn check_with_key(
+        fs: &InMemoryFileSystem,
+        package: &str,
+        trusted_key: Option<&str>,
+    ) -> Option<Vec<String>> {
+        Attestation::check_package(fs, Utf8Path::new("/wibble"), package, "1.0.0", trusted_key)
+            .expect("check package")
+    }
+
+    fn check(fs: &InMemoryFileSystem, package: &str) -> Option<Vec<String>> {
+        let attestation: Attestation = serde_json::from_str(
+            &fs.read(&Utf8Path::new("/wibble").join(ATTESTATION_FILE))
+                .expect("read attestation"),
+        )
+        .expect("attestation");
+        check_with_key(fs, package, Some(&attestation.public_key))
+    }
+
+    #[test]
+    fn attestation_of_unchanged_package() {
+        let attestation = statement().sign_with(&key());
+        let fs = extracted(&attestation, "pub fn main() { 1 }");
+        assert_eq!(check(&fs, "wibble"), Some(vec![]));
+    }
+
+    #[test]
+    fn package_without_attestation() {
+        let fs = InMemoryFileSystem::new();
+        assert_eq!(check_with_key(&fs, "wibble", None), None);
+    }
+
+    #[test]
+    fn package_without_expected_attestation() {
+        let fs = InMemoryFileSystem::new();
+        assert_eq!(
+            check_with_key(&fs, "wibble", Some("00")),
+            Some(vec![
+                "it has no gleam-provenance.json, but one signed with its trusted key is expected"
+                    .into()
+            ])
+        );
+    }
+
+    #[test]
+    fn attestation_with_untrusted_key() {
+        let attestation = statement().sign_with(&key());
+        let fs = extracted(&attestation, "pub fn main() { 1 }");
+        assert_eq!(
+            check_with_key(&fs, "wibble", None),
+            Some(vec![format!(
+                "its key {} is not trusted, add it to `deps.provenance_keys` in gleam.toml to trust it",
+                attestation.public_key
+            )])
+        );
+    }
+
+    #[test]
+    fn attestation_signed_with_other_key() {
+        let attestation = statement().sign_with(&key());
+        let fs = extracted(&attestation, "pub fn main() { 1 }");
+        let (_, other_key) = generate_key();
+        assert_eq!(
+            check_with_key(&fs, "wibble", Some(&other_key)),
+            Some(vec!["it is not signed with its trusted key".into()])
+        );
+    }
+
+    #[test]
+    fn attestation_of_package_with_unsigned_file() {
+        let attestation = statement().sign_with(&key());
+        let fs = extracted(&attestation, "pub fn main() { 1 }");
+        fs.write(
+            Utf8Path::new("/wibble/src/wobble.gleam"),
+            "pub fn main() { 2 }",
+        )
+        .expect("write source");
+        assert_eq!(
+            check(&fs, "wibble"),
+            Some(vec!["src/wobble.gleam was not signed".into()])
+        );
+    }
+
+    #[test]
+    fn attestation_of_changed_package() {
+        let attestation = statement().sign_with(&key());
+        let fs = extracted(&attestation, "pub fn main() { 2 }");
+        assert_eq!(
+            check(&fs, "wibble"),
+            Some(vec![
+                "src/wibble.gleam has changed since it was signed".into()
+            ])
+        );
+    }
+
+    #[test]
+    fn attestation_of_other_package() {
+        let attestation = statement().sign_with(&key());
+        let fs = extracted(&attestation, "pub fn main() { 1 }");
+        assert_eq!(
+            check(&fs, "wobble"),
+            Some(vec!["it attests to wibble v1.0.0".into()])
+        );
+    }
+
+    #[test]
+    fn attestation_with_changed_statement() {
+        let mut attestation = statement().sign_with(&key());
+        attestation.statement.builder.id = "https://example.com".into();
+        let fs = extracted(&attestation, "pub fn main() { 1 }");
+        assert_eq!(
+            check(&fs, "wibble"),
+            Some(vec!["its signature is not valid".into()])
+        );
+    }
+}
@@ -6,6 +6,7 @@ use gleam_core::{
     config::{PackageConfig, SpdxLicense},
     docs::DocContext,
     hex,
+    manifest::Manifest,
     paths::{self, ProjectPaths},
     requirement::Requirement,
     Error, Result,
@@ -15,10 +16,15 @@ use itertools::Itertools;
 use sha2::Digest;
 use std::{io::Write, path::PathBuf, time::Instant};
 
-use crate::{build, cli, docs, fs, hex::ApiKeyCommand, http::HttpClient};
+use crate::{
+    build, cli, docs, fs,
+    hex::ApiKeyCommand,
+    http::HttpClient,
+    provenance::{Attestation, Statement, ATTESTATION_FILE},
+};
 
-pub fn command(replace: bool, yes: bool) -> Result<()> {
-    let command = PublishCommand::setup(replace, yes)?;
+pub fn command(replace: bool, yes: bool, provenance: bool) -> Result<()> {
+    let command = PublishCommand::setup(replace, yes, provenance)?;
 
     if let Some(mut command) = command {
         command.run()?;
@@ -34,7 +40,7 @@ pub struct PublishCommand {
 }
 
 impl PublishCommand {
-    pub fn setup(replace: bool, i_am_sure: bool) -> Result<Option<Self>> {
+    pub fn setup(replace: bool, i_am_sure: bool, provenance: bool) -> Result<Option<Self>> {
         let paths = crate::find_project_paths()?;
         let config = crate::config::root_config()?;
 
@@ -49,10 +55,11 @@ impl PublishCommand {
 
         let Tarball {
             mut compile_result,
+            manifest,
             data: package_tarball,
             src_files_added,
             generated_files_added,
-        } = do_build_hex_tarball(&paths, &config)?;
+        } = do_build_hex_tarball(&paths, &config, provenance)?;
 
         check_for_name_squatting(&compile_result)?;
 
@@ -61,6 +68,7 @@ impl PublishCommand {
             &config,
             &mut compile_result,
             DocContext::HexPublish,
+            &docs::dependency_links()?,
         )?)?;
 
         // Ask user if this is correct
@@ -74,8 +82,9 @@ impl PublishCommand {
         for file in src_files_added.iter().sorted() {
             println!("  - {}", file);
         }
-        println!("\nName: {}", config.name);
-        println!("Version: {}", config.version);
+        let files = src_files_added.len() + generated_files_added.len();
+        let size = package_tarball.len() as u64;
+        println!("\n{}", release_summary(&config, &manifest, files, size));
 
         let should_publish = i_am_sure || cli::confirm("\nDo you wish to publish this package?")?;
         if !should_publish {
@@ -92,6 +101,46 @@ impl PublishCommand {
     }
 }
 
+/// The details of a release shown before it is published so they can be
+/// checked: where it is published to, how much it contains, and the versions
+/// of its dependencies it has been built with.
+fn release_summary(config: &PackageConfig, manifest: &Manifest, files: usize, size: u64) -> String {
+    let repository = match &config.organization {
+        Some(organization) => format!("Hex organization {organization}"),
+        None => "Hex".into(),
+    };
+    let mut summary = format!(
+        "Name: {name}
+Version: {version}
+Repository: {repository}
+Files: {files} ({size})
+Dependencies:",
+        name = config.name,
+        version = config.version,
+        size = cli::file_size(size),
+    );
+    if config.dependencies.is_empty() {
+        summary.push_str(" none");
+    }
+    for (name, requirement) in config.dependencies.iter().sorted_by(|a, b| a.0.cmp(b.0)) {
+        let Requirement::Hex { version, .. } = requirement else {
+            continue;
+        };
+        summary.push_str(&format!("\n  - {name} {version}"));
+        if let Some(package) = manifest.packages.iter().find(|p| &p.name == name) {
+            summary.push_str(&format!(", built with v{}", package.version));
+        }
+    }
+    summary
+}
+
+/// Checks that the package could be published, building its release without
+/// publishing it or asking any questions.
+pub fn check(paths: &ProjectPaths, config: &PackageConfig) -> Result<()> {
+    let Tarball { compile_result, .. } = do_build_hex_tarball(paths, config, false)?;
+    check_for_name_squatting(&compile_result)
+}
+
 fn check_for_name_squatting(package: &Package) -> Result<(), Error> {
     if package.modules.len() > 1 {
         return Ok(());
@@ -191,6 +240,13 @@ impl ApiKeyCommand for PublishCommand {
         let start = Instant::now();
         cli::print_publishing(&self.config.name, &self.config.version);
 
+        // Private packages are published to the repository of their Hex
+        // organisation rather than to public Hex.
+        let hex_config = &match &self.config.organization {
+            Some(organization) => hex::organization_config(hex_config, organization)?,
+            None => hex_config.clone(),
+        };
+
         runtime.block_on(hex::publish_package(
             std::mem::take(&mut self.package_tarball),
             self.config.version.to_string(),
@@ -210,10 +266,11 @@ impl ApiKeyCommand for PublishCommand {
             &HttpClient::new(),
         ))?;
         cli::print_published(start.elapsed());
-        println!(
-            "\nView your package at https://hex.pm/packages/{}",
-            &self.config.name
-        );
+        let package_path = match &self.config.organization {
+            Some(organization) => format!("{organization}/{}", self.config.name),
+            None => self.config.name.to_string(),
+        };
+        println!("\nView your package at https://hex.pm/packages/{package_path}");
 
         // Prompt the user to make a git tag if they have not.
         let has_repo = self.config.repository.url().is_some();
@@ -237,17 +294,24 @@ HTML documentation will work:
 
 struct Tarball {
     compile_result: Package,
+    manifest: Manifest,
     data: Vec<u8>,
     src_files_added: Vec<Utf8PathBuf>,
     generated_files_added: Vec<(Utf8PathBuf, String)>,
 }
 
 pub fn build_hex_tarball(paths: &ProjectPaths, config: &PackageConfig) -> Result<Vec<u8>> {
-    let Tarball { data, .. } = do_build_hex_tarball(paths, config)?;
+    let Tarball { data, .. } = do_build_hex_tarball(paths, config, false)?;
     Ok(data)
 }
 
-fn do_build_hex_tarball(paths: &ProjectPaths, config: &PackageConfig) -> Result<Tarball> {
+/// Builds the release tarball of the package, with a signed provenance
+/// attestation among its files if `provenance` is true.
+fn do_build_hex_tarball(
+    paths: &ProjectPaths,
+    config: &PackageConfig,
+    provenance: bool,
+) -> Result<Tarball> {
     let target = config.target;
     check_config_for_publishing(config)?;
 
@@ -255,15 +319,17 @@ fn do_build_hex_tarball(paths: &ProjectPaths, config: &PackageConfig) -> Result<
     fs::delete_directory(&paths.build_directory_for_target(Mode::Prod, target))?;
 
     // Build the project to check that it is valid
+    let manifest = build::download_dependencies()?;
     let built = build::main(
         Options {
             root_target_support: TargetSupport::Enforced,
             warnings_as_errors: false,
+            verify_artefacts: false,
             mode: Mode::Prod,
             target: Some(target),
             codegen: Codegen::All,
         },
-        build::download_dependencies()?,
+        manifest.clone(),
     )?;
 
     // If any of the modules in the package contain a todo then refuse to
@@ -286,11 +352,15 @@ fn do_build_hex_tarball(paths: &ProjectPaths, config: &PackageConfig) -> Result<
     // context: https://discord.com/channels/768594524158427167/768594524158427170/1227250677734969386
 
     // Collect all the files we want to include in the tarball
-    let generated_files = match target {
+    let mut generated_files = match target {
         Target::Erlang => generated_erlang_files(paths, &built.root_package)?,
         Target::JavaScript => vec![],
     };
     let src_files = project_files()?;
+    if provenance {
+        let attestation = provenance_attestation(paths, config, &src_files, &generated_files)?;
+        generated_files.push((ATTESTATION_FILE.into(), attestation.to_json()));
+    }
     let contents_tar_gz = contents_tarball(&src_files, &generated_files)?;
     let version = "3";
     let metadata = metadata_config(&built.root_package.config, &src_files, &generated_files)?;
@@ -301,7 +371,7 @@ fn do_build_hex_tarball(paths: &ProjectPaths, config: &PackageConfig) -> Result<
     hasher.update(metadata.as_bytes());
     hasher.update(contents_tar_gz.as_slice());
     let checksum = base16::encode_upper(&hasher.finalize());
-    tracing::info!(checksum = %checksum, "Generated Hex package inner checksum");
+    tracing::info!(checksum = %checksum, "generated_hex_package_inner_checksum");
 
     // Build tarball
     let mut tarball = Vec::new();
@@ -313,15 +383,39 @@ fn do_build_hex_tarball(paths: &ProjectPaths, config: &PackageConfig) -> Result<
         add_to_tar(&mut tarball, "CHECKSUM", checksum.as_bytes())?;
         tarball.finish().map_err(Error::finish_tar)?;
     }
-    tracing::info!("Generated package Hex release tarball");
+    tracing::info!(bytes = tarball.len(), "generated_hex_release_tarball");
     Ok(Tarball {
         compile_result: built.root_package,
+        manifest,
         data: tarball,
         src_files_added: src_files,
         generated_files_added: generated_files,
     })
 }
 
+fn provenance_attestation(
+    paths: &ProjectPaths,
+    config: &PackageConfig,
+    src_files: &[Utf8PathBuf],
+    generated_files: &[(Utf8PathBuf, String)],
+) -> Result<Attestation> {
+    let mut files = vec![];
+    for path in src_files {
+        files.push((path.clone(), fs::read_bytes(path)?));
+    }
+    for (path, contents) in generated_files {
+        files.push((path.clone(), contents.clone().into_bytes()));
+    }
+    Statement::new(
+        &config.name,
+        &config.version.to_string(),
+        config.repository.url(),
+        paths.root(),
+        &files,
+    )
+    .sign()
+}
+
 fn check_config_for_publishing(config: &PackageConfig) -> Result<()> {
     // These fields are required to publish a Hex package. Hex will reject
     // packages without them.
@@ -345,9 +439,14 @@ fn metadata_config<'a>(
         .dependencies
         .iter()
         .map(|(name, requirement)| match requirement {
-            Requirement::Hex { version } => Ok(ReleaseRequirement {
+            Requirement::Hex {
+                version,
+                organization,
+                ..
+            } => Ok(ReleaseRequirement {
                 name,
                 requirement: version,
+                repository: organization.as_deref(),
             }),
             _ => Err(Error::PublishNonHexDependencies {
                 package: name.to_string(),
@@ -371,7 +470,7 @@ fn metadata_config<'a>(
         build_tools: vec!["gleam"],
     }
     .as_erlang();
-    tracing::info!(contents = ?metadata, "Generated Hex metadata.config");
+    tracing::info!(contents = ?metadata, "generated_hex_metadata_config");
     Ok(metadata)
 }
 
@@ -391,7 +490,7 @@ fn contents_tarball(
         }
         tarball.finish().map_err(Error::finish_tar)?;
     }
-    tracing::info!("Generated contents.tar.gz");
+    tracing::info!(bytes = contents_tar_gz.len(), "generated_contents_tar_gz");
     Ok(contents_tar_gz)
 }
 
@@ -429,7 +528,7 @@ fn project_files() -> Result<Vec<Utf8PathBuf>> {
 }
 
 // TODO: test
-fn generated_erlang_files(
+pub(crate) fn generated_erlang_files(
     paths: &ProjectPaths,
     package: &Package,
 ) -> Result<Vec<(Utf8PathBuf, String)>> {
@@ -474,7 +573,7 @@ where
     W: Write,
 {
     let path = path.as_ref();
-    tracing::info!(file=?path, "Adding file to tarball");
+    tracing::info!(file=?path, "adding_file_to_tarball");
     let mut header = tar::Header::new_gnu();
     header.set_mode(0o600);
     header.set_size(data.len() as u64);
@@ -490,7 +589,7 @@ where
     W: Write,
 {
     let path = path.as_ref();
-    tracing::info!(file=?path, "Adding file to tarball");
+    tracing::info!(file=?path, "adding_file_to_tarball");
     tarball
         .append_path(path)
         .map_err(|e| Error::add_tar(path, e))
@@ -565,17 +664,21 @@ struct ReleaseRequirement<'a> {
     name: &'a str,
     // optional: bool,
     requirement: &'a Range,
-    // Support alternate repositories at a later date.
-    // repository: String,
+    /// The Hex organisation the dependency is published to, if it is private.
+    repository: Option<&'a str>,
 }
 impl<'a> ReleaseRequirement<'a> {
     pub fn as_erlang(&self) -> String {
+        let repository = match self.repository {
+            Some(repository) => format!(",\n    {{<<\"repository\">>, <<\"{repository}\">>}}"),
+            None => String::new(),
+        };
         format!(
             r#"
   {{<<"{app}">>, [
     {{<<"app">>, <<"{app}">>}},
     {{<<"optional">>, false}},
-    {{<<"requirement">>, <<"{requirement}">>}}
+    {{<<"requirement">>, <<"{requirement}">>}}{repository}
   ]}}"#,
             app = self.name,
             requirement = self.requirement,
@@ -618,10 +721,12 @@ fn release_metadata_as_erlang() {
             ReleaseRequirement {
                 name: "wibble",
                 requirement: &req1,
+                repository: None,
             },
             ReleaseRequirement {
                 name: "wobble",
                 requirement: &req2,
+                repository: Some("acme"),
             },
         ],
         build_tools: vec!["gleam", "rebar3"],
@@ -647,7 +752,8 @@ fn release_metadata_as_erlang() {
   {<<"wobble">>, [
     {<<"app">>, <<"wobble">>},
     {<<"optional">>, false},
-    {<<"requirement">>, <<"~> 1.2">>}
+    {<<"requirement">>, <<"~> 1.2">>},
+    {<<"repository">>, <<"acme">>}
   ]}
 ]}.
 {<<"files">>, [
@@ -663,6 +769,68 @@ fn release_metadata_as_erlang() {
     );
 }
 
+#[test]
+fn release_summary_of_package() {
+    let config = PackageConfig {
+        name: "my_app".into(),
+        version: Version::new(1, 2, 3),
+        dependencies: [
+            ("gleam_stdlib".into(), Requirement::hex("~> 0.34")),
+            (
+                "anvil".into(),
+                Requirement::hex_organization(">= 1.0.0", "acme"),
+            ),
+        ]
+        .into(),
+        organization: Some("acme".into()),
+        ..PackageConfig::default()
+    };
+    let manifest = Manifest {
+        requirements: config.dependencies.clone(),
+        packages: vec![gleam_core::manifest::ManifestPackage {
+            name: "gleam_stdlib".into(),
+            version: Version::new(0, 36, 0),
+            build_tools: vec!["gleam".into()],
+            otp_app: None,
+            requirements: vec![],
+            source: gleam_core::manifest::ManifestPackageSource::Hex {
+                outer_checksum: gleam_core::manifest::Base16Checksum(vec![]),
+                organization: None,
+            },
+        }],
+    };
+    assert_eq!(
+        release_summary(&config, &manifest, 4, 2048),
+        "Name: my_app
+Version: 1.2.3
+Repository: Hex organization acme
+Files: 4 (2.0 KiB)
+Dependencies:
+  - anvil >= 1.0.0
+  - gleam_stdlib ~> 0.34, built with v0.36.0"
+    );
+}
+
+#[test]
+fn release_summary_without_dependencies() {
+    let config = PackageConfig {
+        name: "my_app".into(),
+        ..PackageConfig::default()
+    };
+    let manifest = Manifest {
+        requirements: [].into(),
+        packages: vec![],
+    };
+    assert_eq!(
+        release_summary(&config, &manifest, 1, 100),
+        "Name: my_app
+Version: 0.1.0
+Repository: Hex
+Files: 1 (100 B)
+Dependencies: none"
+    );
+}
+
 #[test]
 fn prevent_publish_local_dependency() {
     let mut config = PackageConfig::default();
@@ -0,0 +1,87 @@
+use std::time::Instant;
+
+use camino::{Utf8Path, Utf8PathBuf};
+use futures::{stream, StreamExt, TryStreamExt};
+use gleam_core::{
+    error::{FileIoAction, FileKind},
+    hex::{self, snapshot},
+    io::FileSystemReader,
+    manifest::{Manifest, ManifestPackage, ManifestPackageSource},
+    Error, Result,
+};
+
+use crate::{cli, fs::ProjectIO, http::HttpClient};
+
+/// Writes a snapshot of the Hex packages in the manifest to the `out`
+/// directory, which can be served as a static Hex repository or read from
+/// directly with `GLEAM_REGISTRY_DIRECTORY`.
+pub fn snapshot(packages_from: &Utf8Path, out: &Utf8Path) -> Result<()> {
+    let start = Instant::now();
+    let io = ProjectIO::new();
+    let toml = io.read(packages_from)?;
+    let manifest: Manifest = toml::from_str(&toml).map_err(|error| Error::FileIo {
+        action: FileIoAction::Parse,
+        kind: FileKind::File,
+        path: packages_from.to_path_buf(),
+        err: Some(error.to_string()),
+    })?;
+    let packages: Vec<_> = manifest
+        .packages
+        .iter()
+        .filter(|package| matches!(package.source, ManifestPackageSource::Hex { .. }))
+        .collect();
+
+    let runtime = tokio::runtime::Runtime::new().expect("Unable to start Tokio async runtime");
+    let http = HttpClient::new();
+    let sizes: Vec<u64> = runtime.block_on(
+        stream::iter(packages.iter().map(|package| {
+            let (io, http) = (&io, &http);
+            async move {
+                let (config, api_key) = repository(package)?;
+                let size =
+                    snapshot::snapshot_package(http, io, out, &config, api_key.as_deref(), package)
+                        .await?;
+                cli::print_colourful_prefix(
+                    "Snapshotted",
+                    &format!("{} v{}", package.name, package.version),
+                );
+                Ok::<_, Error>(size)
+            }
+        }))
+        .buffer_unordered(hex::DEFAULT_DOWNLOAD_CONCURRENCY)
+        .try_collect(),
+    )?;
+
+    let size = cli::file_size(sizes.iter().sum());
+    let packages = match packages.len() {
+        1 => "1 package".into(),
+        count => format!("{count} packages"),
+    };
+    let duration = cli::seconds(start.elapsed());
+    cli::print_colourful_prefix("Snapshotted", &format!("{packages} ({size}) in {duration}"));
+    println!("\nThe snapshot has been written to {}", display(out));
+    Ok(())
+}
+
+/// The configuration and API key of the Hex repository the package is in.
+fn repository(package: &ManifestPackage) -> Result<(hexpm::Config, Option<String>)> {
+    match &package.source {
+        ManifestPackageSource::Hex {
+            organization: Some(organization),
+            ..
+        } => {
+            let api_key = crate::hex::organization_key(organization).ok_or_else(|| {
+                Error::HexOrganizationKeyMissing {
+                    organization: organization.clone(),
+                }
+            })?;
+            let config = hex::organization_config(&hexpm::Config::new(), organization)?;
+            Ok((config, Some(api_key)))
+        }
+        _ => Ok((hexpm::Config::new(), None)),
+    }
+}
+
+fn display(path: &Utf8Path) -> Utf8PathBuf {
+    crate::fs::canonicalise(path).unwrap_or_else(|_| path.to_path_buf())
+}
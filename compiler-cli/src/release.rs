@@ -0,0 +1,183 @@
+//! Releasing a new version of the package: bumping its version, checking the
+//! changelog describes the release, checking it could be published, and then
+//! committing and tagging it with git, and publishing it if asked to.
+
+use camino::Utf8Path;
+use gleam_core::{Error, Result};
+use hexpm::version::Version;
+
+use crate::{cli, fs, publish};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Bump {
+    /// For changes that break the public API of the package
+    Major,
+    /// For new features that are backwards compatible
+    Minor,
+    /// For bug fixes that are backwards compatible
+    Patch,
+}
+
+#[derive(Debug)]
+pub struct Options {
+    pub bump: Bump,
+    /// Whether to publish the release to Hex once it has been tagged.
+    pub publish: bool,
+    /// Whether to publish without asking for confirmation.
+    pub yes: bool,
+}
+
+pub fn command(options: Options) -> Result<()> {
+    let paths = crate::find_project_paths()?;
+    let mut config = crate::config::root_config()?;
+    let version = bump(&config.version, options.bump);
+    let tag = format!("v{version}");
+
+    // Check everything that could stop the release before changing anything
+    let changelog_path = paths.changelog();
+    let changelog = fs::read(&changelog_path)?;
+    if !has_changelog_entry(&changelog, &version) {
+        return Err(Error::ChangelogEntryMissing {
+            path: changelog_path,
+            version: version.to_string(),
+        });
+    }
+    let in_git = fs::is_inside_git_work_tree(paths.root())?;
+    if in_git {
+        check_git_ready(paths.root(), &tag)?;
+    }
+    config.version = version.clone();
+    publish::check(&paths, &config)?;
+
+    let mut toml = crate::add::read_toml_edit(paths.root_config().as_str())?;
+    // False positive. This package doesn't use the indexing API correctly.
+    #[allow(clippy::indexing_slicing)]
+    {
+        toml["version"] = toml_edit::value(version.to_string());
+    }
+    fs::write(&paths.root_config(), &toml.to_string())?;
+    cli::print_colourful_prefix("Bumped", &format!("{} to v{version}", config.name));
+
+    if in_git {
+        let _ = git(
+            paths.root(),
+            &["commit", "--quiet", "--message", &tag, "--", "gleam.toml"],
+        )?;
+        let _ = git(
+            paths.root(),
+            &["tag", "--annotate", &tag, "--message", &tag],
+        )?;
+        cli::print_colourful_prefix("Tagged", &tag);
+    } else {
+        println!("The project is not in a git repository, so the release was not tagged.");
+    }
+
+    if options.publish {
+        publish::command(false, options.yes, false)?;
+    }
+
+    if in_git {
+        println!(
+            "
+Push the release and its tag with:
+
+    git push
+    git push origin {tag}"
+        );
+    }
+    Ok(())
+}
+
+fn bump(version: &Version, bump: Bump) -> Version {
+    match bump {
+        Bump::Major => Version::new(version.major + 1, 0, 0),
+        Bump::Minor => Version::new(version.major, version.minor + 1, 0),
+        // The patch release of a pre-release is the version it came before
+        Bump::Patch if !version.pre.is_empty() => {
+            Version::new(version.major, version.minor, version.patch)
+        }
+        Bump::Patch => Version::new(version.major, version.minor, version.patch + 1),
+    }
+}
+
+/// Whether the changelog has a heading for the version, such as `## v1.2.0`
+/// or `## [1.2.0] - 2024-05-01`.
+fn has_changelog_entry(changelog: &str, version: &Version) -> bool {
+    let version = version.to_string();
+    changelog
+        .lines()
+        .filter(|line| line.trim_start().starts_with('#'))
+        .flat_map(|line| line.split(|c: char| c.is_whitespace() || "#[]():".contains(c)))
+        .any(|word| word.strip_prefix('v').unwrap_or(word) == version)
+}
+
+/// Checks the work tree has nothing uncommitted that would be left out of
+/// the release, and that the release has not already been tagged.
+fn check_git_ready(root: &Utf8Path, tag: &str) -> Result<()> {
+    let status = git(root, &["status", "--porcelain"])?;
+    if !status.trim().is_empty() {
+        return Err(Error::ReleaseFromDirtyWorkTree);
+    }
+    let reference = format!("refs/tags/{tag}");
+    let existing = fs::git_output(root, &["rev-parse", "--quiet", "--verify", &reference])?;
+    if existing.status.success() {
+        return Err(Error::GitTagExists { tag: tag.into() });
+    }
+    Ok(())
+}
+
+/// Runs git, returning what it printed, or an error if it failed.
+fn git(root: &Utf8Path, args: &[&str]) -> Result<String> {
+    let output = fs::git_output(root, args)?;
+    if !output.status.success() {
+        eprint!("{}", String::from_utf8_lossy(&output.stderr));
+        return Err(Error::ShellCommand {
+            program: "git".into(),
+            err: None,
+        });
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn version(text: &str) -> Version {
+        Version::parse(text).expect("version")
+    }
+
+    #[test]
+    fn bump_versions() {
+        let current = version("1.2.3");
+        assert_eq!(bump(&current, Bump::Major), version("2.0.0"));
+        assert_eq!(bump(&current, Bump::Minor), version("1.3.0"));
+        assert_eq!(bump(&current, Bump::Patch), version("1.2.4"));
+    }
+
+    #[test]
+    fn bump_pre_release() {
+        let current = version("2.0.0-rc1");
+        assert_eq!(bump(&current, Bump::Patch), version("2.0.0"));
+        assert_eq!(bump(&current, Bump::Minor), version("2.1.0"));
+    }
+
+    #[test]
+    fn changelog_entry_headings() {
+        let version = version("1.2.0");
+        assert!(has_changelog_entry("# Changelog\n\n## v1.2.0\n", &version));
+        assert!(has_changelog_entry("## 1.2.0 - 2024-05-01\n", &version));
+        assert!(has_changelog_entry("## [1.2.0] - 2024-05-01\n", &version));
+    }
+
+    #[test]
+    fn changelog_without_entry() {
+        let version = version("1.2.0");
+        assert!(!has_changelog_entry(
+            "## Unreleased\n\n- Fixed 1.2.0\n",
+            &version
+        ));
+        assert!(!has_changelog_entry("## v1.2.0-rc1\n", &version));
+        assert!(!has_changelog_entry("## v1.12.0\n", &version));
+    }
+}
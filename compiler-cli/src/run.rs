@@ -1,28 +1,102 @@
 use std::sync::OnceLock;
 
-use camino::Utf8PathBuf;
+use camino::{Utf8Path, Utf8PathBuf};
 use ecow::EcoString;
 use gleam_core::{
     analyse::TargetSupport,
     build::{Built, Codegen, Mode, Options, Runtime, Target},
     config::{DenoFlag, PackageConfig},
-    error::Error,
+    error::{Error, FileIoAction, FileKind},
     io::{CommandExecutor, Stdio},
     paths::ProjectPaths,
+    test_runner::{
+        self,
+        changes::{self, Snapshot},
+        Shard, Test,
+    },
     type_::ModuleFunction,
 };
+use itertools::Itertools;
 
 use crate::{config::PackageKind, fs::ProjectIO};
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
 pub enum Which {
     Src,
     Test,
+    /// The test functions of the project, run with the compiler's own test
+    /// runner rather than the `main` function of the test module, so that
+    /// they can be split into shards, run in parallel, or retried.
+    Tests {
+        shard: Option<Shard>,
+        changes: Option<Changes>,
+        options: test_runner::Options,
+    },
+}
+
+/// The changes that select which tests are run, which are those impacted by
+/// them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Changes {
+    /// The changes since the tests last all passed, or every test if they
+    /// have not.
+    SinceLastPassed,
+    /// The changes since a git ref.
+    SinceGitRef(String),
+}
+
+/// What is run: the `main` function of a module, or the given tests with the
+/// test runner.
+enum Entrypoint {
+    Main,
+    Tests {
+        tests: Vec<Test>,
+        options: test_runner::Options,
+    },
+}
+
+/// What the program is run with: the arguments given after `--`, the
+/// environment variables given with `--env`, and the directory given with
+/// `--cwd`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Invocation {
+    pub arguments: Vec<String>,
+    pub environment: Vec<(String, String)>,
+    pub directory: Option<Utf8PathBuf>,
+}
+
+impl Invocation {
+    fn exec(&self, program: &str, args: Vec<String>) -> Result<i32, Error> {
+        let environment = self
+            .environment
+            .iter()
+            .map(|(key, value)| (key.as_str(), value.clone()))
+            .collect_vec();
+        let args = args
+            .into_iter()
+            .chain(self.arguments.iter().cloned())
+            .collect_vec();
+        ProjectIO::new().exec(
+            program,
+            &args,
+            &environment,
+            self.directory.as_deref(),
+            Stdio::Inherit,
+        )
+    }
+}
+
+/// Parses a `KEY=VALUE` environment variable given with `--env`.
+pub fn parse_environment_variable(variable: &str) -> Result<(String, String), String> {
+    match variable.split_once('=') {
+        Some((key, value)) if !key.is_empty() => Ok((key.into(), value.into())),
+        _ => Err(format!("expected KEY=VALUE but got `{variable}`")),
+    }
 }
 
 // TODO: test
 pub fn command(
-    arguments: Vec<String>,
+    invocation: Invocation,
     target: Option<Target>,
     runtime: Option<Runtime>,
     module: Option<String>,
@@ -30,6 +104,19 @@ pub fn command(
 ) -> Result<(), Error> {
     let paths = crate::find_project_paths()?;
 
+    // The directory to run in is checked before building, as otherwise the
+    // program would be reported as not found when it is run.
+    if let Some(directory) = &invocation.directory {
+        if !directory.is_dir() {
+            return Err(Error::FileIo {
+                action: FileIoAction::Open,
+                kind: FileKind::Directory,
+                path: directory.clone(),
+                err: Some("The directory does not exist".into()),
+            });
+        }
+    }
+
     // Validate the module path
     if let Some(mod_path) = &module {
         if !is_gleam_module(mod_path) {
@@ -58,13 +145,14 @@ pub fn command(
     // Determine which module to run
     let module = module.unwrap_or(match which {
         Which::Src => root_config.name.to_string(),
-        Which::Test => format!("{}_test", &root_config.name),
+        Which::Test | Which::Tests { .. } => format!("{}_test", &root_config.name),
     });
 
     let target = target.unwrap_or(mod_config.target);
 
     let options = Options {
         warnings_as_errors: false,
+        verify_artefacts: false,
         codegen: Codegen::All,
         mode: Mode::Dev,
         target: Some(target),
@@ -78,16 +166,52 @@ pub fn command(
         },
     };
 
+    // If a daemon is running it analyses the project, so only the caches it
+    // writes are loaded here.
+    if let Some(result) = crate::daemon::compile(&paths, &options) {
+        result?;
+    }
     let built = crate::build::main(options, manifest)?;
 
-    // A module can not be run if it does not exist or does not have a public main function.
-    let main_function = get_or_suggest_main_function(built, &module, target)?;
+    // The snapshot of the project to save if every test passes, so that the
+    // tests impacted by later changes can be found.
+    let mut snapshot = None;
+
+    let (package, entrypoint) = match which {
+        Which::Tests {
+            shard,
+            changes,
+            options,
+        } => {
+            let tests = test_runner::discover(&built, target);
+            let project = Snapshot::new(&ProjectIO::new(), &paths)?;
+            let tests = match changes {
+                Some(changes) => impacted_tests(&paths, target, &project, changes, tests)?,
+                None => tests,
+            };
+            let tests = match shard {
+                Some(shard) => shard.select(tests),
+                None => {
+                    snapshot = Some(project);
+                    tests
+                }
+            };
+            crate::cli::print_running(&format!("{} tests", tests.len()));
+            let entrypoint = Entrypoint::Tests { tests, options };
+            (root_config.name.clone(), entrypoint)
+        }
+
+        Which::Src | Which::Test => {
+            // A module can not be run if it does not exist or does not have a public main function.
+            let main_function = get_or_suggest_main_function(built, &module, target)?;
+            crate::cli::print_running(&format!("{module}.main"));
+            (main_function.package, Entrypoint::Main)
+        }
+    };
 
     // Don't exit on ctrl+c as it is used by child erlang shell
     ctrlc::set_handler(move || {}).expect("Error setting Ctrl-C handler");
 
-    crate::cli::print_running(&format!("{module}.main"));
-
     // Run the command
     let status = match target {
         Target::Erlang => match runtime {
@@ -95,32 +219,85 @@ pub fn command(
                 target: Target::Erlang,
                 invalid_runtime: r,
             }),
-            _ => run_erlang(&paths, &root_config.name, &module, arguments),
-        },
-        Target::JavaScript => match runtime.unwrap_or(mod_config.javascript.runtime) {
-            Runtime::Deno => run_javascript_deno(
-                &paths,
-                &root_config,
-                &main_function.package,
-                &module,
-                arguments,
-            ),
-            Runtime::NodeJs => {
-                run_javascript_node(&paths, &main_function.package, &module, arguments)
+            _ => {
+                let eval = erlang_entrypoint(&paths, &root_config.name, &module, entrypoint)?;
+                run_erlang(&paths, eval, &invocation)
             }
-            Runtime::Bun => run_javascript_bun(&paths, &main_function.package, &module, arguments),
         },
+        Target::JavaScript => {
+            let entrypoint = write_javascript_entrypoint(&paths, &package, &module, entrypoint)?;
+            match runtime.unwrap_or(mod_config.javascript.runtime) {
+                Runtime::Deno => run_javascript_deno(&root_config, &entrypoint, &invocation),
+                Runtime::NodeJs => run_javascript_node(&entrypoint, &invocation),
+                Runtime::Bun => run_javascript_bun(&entrypoint, &invocation),
+            }
+        }
     }?;
 
+    if let Some(snapshot) = snapshot.filter(|_| status == 0) {
+        crate::fs::write(&paths.build_test_snapshot(target), &snapshot.to_json())?;
+    }
+
     std::process::exit(status);
 }
 
-fn run_erlang(
+/// Selects the tests impacted by the changes, which are all of them if the
+/// tests have not all passed before.
+fn impacted_tests(
+    paths: &ProjectPaths,
+    target: Target,
+    project: &Snapshot,
+    changes: Changes,
+    tests: Vec<Test>,
+) -> Result<Vec<Test>, Error> {
+    let changed = match changes {
+        Changes::SinceLastPassed => {
+            let earlier = crate::fs::read(paths.build_test_snapshot(target))
+                .ok()
+                .and_then(|json| Snapshot::from_json(&json));
+            match earlier {
+                Some(earlier) => project.changed_since(&earlier),
+                None => return Ok(tests),
+            }
+        }
+        Changes::SinceGitRef(reference) => {
+            let files = crate::fs::git_changed_files(paths.root(), &reference)?;
+            project.changed_files(&files)
+        }
+    };
+    Ok(changes::select(tests, &project.impacted(&changed)))
+}
+
+/// The Erlang expression that runs the entrypoint, writing the tests to run
+/// to a file in the build directory of the package for the test runner.
+fn erlang_entrypoint(
     paths: &ProjectPaths,
     package: &str,
     module: &str,
-    arguments: Vec<String>,
-) -> Result<i32, Error> {
+    entrypoint: Entrypoint,
+) -> Result<String, Error> {
+    match entrypoint {
+        Entrypoint::Main => {
+            // gleam modules are separated by `/`. Erlang modules are separated by `@`.
+            let module = module.replace('/', "@");
+            Ok(format!("{package}@@main:run({module})"))
+        }
+
+        Entrypoint::Tests { tests, options } => {
+            let path = paths
+                .build_directory_for_package(Mode::Dev, Target::Erlang, package)
+                .join("gleam@@tests.eterm");
+            crate::fs::write(&path, &test_runner::erlang_tests(&tests))?;
+            let path = path.as_str().replace('\\', "\\\\").replace('"', "\\\"");
+            let options = options.to_erlang();
+            Ok(format!(
+                "'gleam@@test':run('{package}', \"{path}\", {options})"
+            ))
+        }
+    }
+}
+
+fn run_erlang(paths: &ProjectPaths, eval: String, invocation: &Invocation) -> Result<i32, Error> {
     let mut args = vec![];
 
     // Specify locations of Erlang applications
@@ -131,72 +308,63 @@ fn run_erlang(
         args.push(entry.path().join("ebin").into());
     }
 
-    // gleam modules are separated by `/`. Erlang modules are separated by `@`.
-    let module = module.replace('/', "@");
-
     args.push("-eval".into());
-    args.push(format!("{package}@@main:run({module})"));
+    args.push(eval);
 
     // Don't run the Erlang shell
     args.push("-noshell".into());
 
     // Tell the BEAM that any following argument are for the program
     args.push("-extra".into());
-    for argument in arguments.into_iter() {
-        args.push(argument);
-    }
 
-    ProjectIO::new().exec("erl", &args, &[], None, Stdio::Inherit)
+    invocation.exec("erl", args)
 }
 
-fn run_javascript_bun(
-    paths: &ProjectPaths,
-    package: &str,
-    module: &str,
-    arguments: Vec<String>,
-) -> Result<i32, Error> {
-    let mut args = vec!["run".to_string()];
-    let entry = write_javascript_entrypoint(paths, package, module)?;
-
-    args.push(entry.to_string());
-
-    for arg in arguments.into_iter() {
-        args.push(arg);
-    }
+fn run_javascript_bun(entry: &Utf8Path, invocation: &Invocation) -> Result<i32, Error> {
+    let args = vec!["run".to_string(), entry.to_string()];
 
-    ProjectIO::new().exec("bun", &args, &[], None, Stdio::Inherit)
+    invocation.exec("bun", args)
 }
 
-fn run_javascript_node(
-    paths: &ProjectPaths,
-    package: &str,
-    module: &str,
-    arguments: Vec<String>,
-) -> Result<i32, Error> {
-    let mut args = vec![];
-    let entry = write_javascript_entrypoint(paths, package, module)?;
-
-    args.push(entry.to_string());
+fn run_javascript_node(entry: &Utf8Path, invocation: &Invocation) -> Result<i32, Error> {
+    let args = vec![entry.to_string()];
 
-    for argument in arguments.into_iter() {
-        args.push(argument);
-    }
-
-    ProjectIO::new().exec("node", &args, &[], None, Stdio::Inherit)
+    invocation.exec("node", args)
 }
 
 fn write_javascript_entrypoint(
     paths: &ProjectPaths,
     package: &str,
     module: &str,
+    entrypoint: Entrypoint,
 ) -> Result<Utf8PathBuf, Error> {
-    let path = paths
-        .build_directory_for_package(Mode::Dev, Target::JavaScript, package)
-        .to_path_buf()
-        .join("gleam.main.mjs");
+    let directory = paths.build_directory_for_package(Mode::Dev, Target::JavaScript, package);
+
+    if let Entrypoint::Tests { tests, options } = entrypoint {
+        let path = directory.join("gleam.test.mjs");
+        let module = test_runner::javascript_entrypoint(&tests, &options);
+        crate::fs::write(&path, &module)?;
+        return Ok(path);
+    }
+
+    let path = directory.join("gleam.main.mjs");
+    // Errors are printed by the crash formatter written next to the prelude,
+    // which prints errors raised by Gleam code using the formatter set with
+    // `setErrorFormatter` from the prelude, its own being set first, followed
+    // by the stack trace. A `main` function returning a promise is awaited so
+    // that it being rejected is reported in the same way.
     let module = format!(
         r#"import {{ main }} from "./{module}.mjs";
-main();
+import {{ install, formatCrash }} from "../crash.mjs";
+
+install();
+try {{
+  await main();
+}} catch (error) {{
+  console.error(formatCrash(error));
+  if (globalThis.Deno) Deno.exit(1);
+  process.exit(1);
+}}
 "#,
     );
     crate::fs::write(&path, &module)?;
@@ -204,11 +372,9 @@ main();
 }
 
 fn run_javascript_deno(
-    paths: &ProjectPaths,
     config: &PackageConfig,
-    package: &str,
-    module: &str,
-    arguments: Vec<String>,
+    entrypoint: &Utf8Path,
+    invocation: &Invocation,
 ) -> Result<i32, Error> {
     let mut args = vec![];
 
@@ -269,14 +435,9 @@ fn run_javascript_deno(
         );
     }
 
-    let entrypoint = write_javascript_entrypoint(paths, package, module)?;
     args.push(entrypoint.to_string());
 
-    for argument in arguments.into_iter() {
-        args.push(argument);
-    }
-
-    ProjectIO::new().exec("deno", &args, &[], None, Stdio::Inherit)
+    invocation.exec("deno", args)
 }
 
 fn add_deno_flag(args: &mut Vec<String>, flag: &str, flags: &DenoFlag) {
@@ -291,7 +452,7 @@ fn add_deno_flag(args: &mut Vec<String>, flag: &str, flags: &DenoFlag) {
 }
 
 /// Check if a module name is a valid gleam module name.
-fn is_gleam_module(module: &str) -> bool {
+pub(crate) fn is_gleam_module(module: &str) -> bool {
     use regex::Regex;
     static RE: OnceLock<Regex> = OnceLock::new();
 
@@ -350,6 +511,24 @@ fn invalid_module_names() {
     }
 }
 
+#[test]
+fn environment_variables() {
+    assert_eq!(
+        parse_environment_variable("PORT=8080"),
+        Ok(("PORT".into(), "8080".into()))
+    );
+    assert_eq!(
+        parse_environment_variable("QUERY=a=b"),
+        Ok(("QUERY".into(), "a=b".into()))
+    );
+    assert_eq!(
+        parse_environment_variable("EMPTY="),
+        Ok(("EMPTY".into(), "".into()))
+    );
+    assert!(parse_environment_variable("PORT").is_err());
+    assert!(parse_environment_variable("=8080").is_err());
+}
+
 #[test]
 fn valid_module_names() {
     for mod_name in ["valid", "valid/name", "valid/mod/name"] {
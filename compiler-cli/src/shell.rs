@@ -13,6 +13,7 @@ pub fn command() -> Result<(), Error> {
         Options {
             root_target_support: TargetSupport::Enforced,
             warnings_as_errors: false,
+            verify_artefacts: false,
             codegen: Codegen::All,
             mode: Mode::Dev,
             target: Some(Target::Erlang),
@@ -38,7 +39,7 @@ pub fn command() -> Result<(), Error> {
     crate::cli::print_running("Erlang shell");
 
     // Run the shell
-    tracing::info!("Running OS process {:?}", command);
+    tracing::info!(command = ?command, "running_os_process");
     let _ = command.status().map_err(|e| Error::ShellCommand {
         program: "erl".into(),
         err: Some(e.kind()),
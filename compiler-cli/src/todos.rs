@@ -0,0 +1,30 @@
+use gleam_core::{todos, Result};
+use itertools::Itertools;
+
+/// Print the `todo` and `panic` expressions of the project's source and test
+/// modules, either as a list or in JSON format.
+pub(crate) fn list(json: bool) -> Result<()> {
+    let paths = crate::find_project_paths()?;
+    let built = crate::build::analyse_root_package(None, json)?;
+
+    let holes = built
+        .root_package
+        .modules
+        .iter()
+        .sorted_by(|a, b| a.input_path.cmp(&b.input_path))
+        .flat_map(|module| {
+            let path = module
+                .input_path
+                .strip_prefix(paths.root())
+                .unwrap_or(&module.input_path);
+            todos::find(&module.ast, path, &module.code)
+        })
+        .collect_vec();
+
+    if json {
+        println!("{}", todos::to_json(&holes));
+    } else {
+        print!("{}", todos::to_text(&holes));
+    }
+    Ok(())
+}
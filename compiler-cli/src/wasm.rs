@@ -0,0 +1,43 @@
+use gleam_core::{
+    analyse::TargetSupport,
+    build::{Codegen, Mode, Options},
+    Result,
+};
+
+use crate::cli;
+
+/// Compiles the project to a WebAssembly module with the experimental Wasm
+/// backend, writing it in the text format.
+pub fn build(warnings_as_errors: bool) -> Result<()> {
+    let paths = crate::find_project_paths()?;
+    let config = crate::config::root_config()?;
+
+    // The Wasm backend needs the typed ASTs of all the project's modules,
+    // which are not kept in the build cache, so everything is compiled
+    // afresh in a directory of its own.
+    let compile_paths = paths.clone().compiling_into(paths.build_wasm_directory());
+    crate::fs::delete_directory(
+        &compile_paths.build_directory_for_target(Mode::Prod, config.target),
+    )?;
+    let built = crate::build::main_keeping_dependency_modules(
+        compile_paths,
+        Options {
+            mode: Mode::Prod,
+            target: None,
+            codegen: Codegen::None,
+            warnings_as_errors,
+            verify_artefacts: false,
+            root_target_support: TargetSupport::Enforced,
+        },
+        crate::build::download_dependencies()?,
+    )?;
+
+    let wat = gleam_core::wasm::module(
+        &built.root_package.modules,
+        built.compiled_dependency_modules(),
+    )?;
+    let path = paths.build_wasm_module(&config.name);
+    crate::fs::write(&path, &wat)?;
+    cli::print_colourful_prefix("Generated", path.as_str());
+    Ok(())
+}
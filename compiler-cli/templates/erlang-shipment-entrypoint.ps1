@@ -6,8 +6,18 @@ $ScriptCommand = $args[0]
 
 $CodePath = Join-Path -Path $BaseDirectory -ChildPath "\*\ebin" -Resolve
 
+# Load config/config.config and the config named by $env:GLEAM_CONFIG, if any.
+$ConfigArgs = @()
+if (Test-Path (Join-Path -Path $BaseDirectory -ChildPath "config\config.config")) {
+  $ConfigArgs += "-config", (Join-Path -Path $BaseDirectory -ChildPath "config\config")
+}
+if ($env:GLEAM_CONFIG) {
+  $ConfigArgs += "-config", (Join-Path -Path $BaseDirectory -ChildPath "config\$env:GLEAM_CONFIG")
+}
+
 function Run {
   erl `
+    @ConfigArgs `
     -pa $CodePath `
     -eval "$PackageName@@main:run($PackageName)" `
     -noshell `
@@ -15,7 +25,7 @@ function Run {
 }
 
 function Shell {
-  erl -pa $CodePath
+  erl @ConfigArgs -pa $CodePath
 }
 
 switch ($ScriptCommand) {
@@ -32,6 +42,9 @@ switch ($ScriptCommand) {
     Write-Host "commands:"
     Write-Host "  run    Run the project main function"
     Write-Host "  shell  Run an Erlang shell"
+    Write-Host ""
+    Write-Host "environment variables:"
+    Write-Host "  GLEAM_CONFIG  The name of a config file in config\ to load"
     exit 1
   }
 }
@@ -5,8 +5,20 @@ PACKAGE=$PACKAGE_NAME_FROM_GLEAM
 BASE=$(dirname "$0")
 COMMAND="${1-default}"
 
+# Runs erl with the given arguments, loading config/config.config and the
+# config named by $GLEAM_CONFIG, if any.
+erl_with_config() {
+  if [ -n "${GLEAM_CONFIG-}" ]; then
+    set -- -config "$BASE/config/$GLEAM_CONFIG" "$@"
+  fi
+  if [ -f "$BASE/config/config.config" ]; then
+    set -- -config "$BASE/config/config" "$@"
+  fi
+  erl "$@"
+}
+
 run() {
-  erl \
+  erl_with_config \
     -pa "$BASE"/*/ebin \
     -eval "$PACKAGE@@main:run($PACKAGE)" \
     -noshell \
@@ -14,7 +26,7 @@ run() {
 }
 
 shell() {
-  erl -pa "$BASE"/*/ebin
+  erl_with_config -pa "$BASE"/*/ebin
 }
 
 case "$COMMAND" in
@@ -34,5 +46,8 @@ case "$COMMAND" in
     echo "commands:" >&2
     echo "  run    Run the project main function" >&2
     echo "  shell  Run an Erlang shell" >&2
+    echo "" >&2
+    echo "environment variables:" >&2
+    echo "  GLEAM_CONFIG  The name of a config file in config/ to load" >&2
     exit 1
 esac
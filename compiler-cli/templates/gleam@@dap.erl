@@ -0,0 +1,461 @@
+-module('gleam@@dap').
+
+% The Erlang half of `gleam dap`, which runs the program being debugged in the
+% Erlang interpreter. Commands are read from standard input, one Erlang term
+% per line, and each is answered with a line of JSON written to standard output
+% after a marker, as are events such as a process stopping at a breakpoint.
+% Anything else written to standard output is the output of the program.
+%
+% The generated Erlang has a `-file` attribute before each function, so the
+% interpreter reports lines counted from the first line of the Gleam function
+% rather than the lines of the Gleam source. The Gleam lines of the statements
+% of each function are given when modules are interpreted and matched with the
+% expressions of the generated function, so that breakpoints and stepping use
+% Gleam lines.
+
+-export([start/0, attached/2]).
+
+-define(MARKER, "@@gleam-dap@@ ").
+
+start() ->
+    Server = self(),
+    _ = spawn_link(fun() -> read_commands(Server) end),
+    int:auto_attach([break], {?MODULE, attached, [Server]}),
+    loop(#{modules => #{}, breakpoints => #{}, threads => #{}, next_thread => 1}).
+
+read_commands(Server) ->
+    case io:get_line(standard_io, "") of
+        eof ->
+            Server ! {command, stop};
+        {error, _} ->
+            Server ! {command, stop};
+        Line ->
+            Text = unicode:characters_to_list(Line),
+            {ok, Tokens, _} = erl_scan:string(Text),
+            {ok, Command} = erl_parse:parse_term(Tokens),
+            Server ! {command, Command},
+            read_commands(Server)
+    end.
+
+% Called by the interpreter in a new process when a process stops at a
+% breakpoint. The messages of the meta process that interprets the code of the
+% stopped process are forwarded to the server until the process exits.
+attached(Pid, Server) ->
+    {ok, Meta} = int:attached(Pid),
+    Server ! {attached, Pid, Meta},
+    forward(Server, Pid, Meta).
+
+forward(Server, Pid, Meta) ->
+    receive
+        {Meta, {exit_at, _, _, _} = Message} ->
+            Server ! {meta, Pid, Message};
+        {Meta, Message} ->
+            Server ! {meta, Pid, Message},
+            forward(Server, Pid, Meta)
+    end.
+
+loop(State) ->
+    receive
+        {command, Command} ->
+            loop(command(Command, State));
+        {attached, Pid, Meta} ->
+            loop(attach(Pid, Meta, State));
+        {meta, Pid, Message} ->
+            loop(meta(Pid, Message, State))
+    end.
+
+%
+% Commands
+%
+
+command({interpret, Modules}, State) ->
+    Tables = [
+        {Module, interpret(Module, Source, Functions)}
+     || {Module, Source, Functions} <- Modules
+    ],
+    reply(#{}),
+    State#{modules := maps:from_list(Tables)};
+command({breakpoints, Module, Lines}, State) ->
+    #{modules := Modules, breakpoints := Breakpoints} = State,
+    Functions = maps:get(Module, Modules, []),
+    _ = int:no_break(Module),
+    Resolved = [resolve_breakpoint(Functions, Line) || Line <- Lines],
+    Set = [{Function, Line} || {Function, _, Line} <- Resolved, Line =/= none],
+    [int:break(Module, Line) || {_, Line} <- Set],
+    reply(#{lines => [GleamLine || {_, GleamLine, _} <- Resolved]}),
+    State#{breakpoints := Breakpoints#{Module => Set}};
+command({run, Main, Module}, State) ->
+    _ = spawn(Main, run, [Module]),
+    reply(#{}),
+    State;
+command(threads, State = #{threads := Threads}) ->
+    reply(#{
+        threads => [
+            #{id => Id, name => list_to_binary(pid_to_list(Pid))}
+         || {Id, #{pid := Pid}} <- lists:sort(maps:to_list(Threads))
+        ]
+    }),
+    State;
+command({stack, Id}, State) ->
+    with_thread(Id, State, fun(Thread) -> #{frames => frames(Thread, State)} end),
+    State;
+command({variables, Id, Level}, State) ->
+    with_thread(
+        Id,
+        State,
+        fun(#{meta := Meta}) ->
+            Bindings = int:meta(Meta, bindings, Level),
+            #{
+                variables => [
+                    #{name => atom_to_binary(Name), value => inspect(Value)}
+                 || {Name, Value} <- Bindings
+                ]
+            }
+        end
+    ),
+    State;
+command({Step, Id}, State = #{threads := Threads}) when
+    Step =:= continue; Step =:= next; Step =:= step; Step =:= finish
+->
+    case maps:find(Id, Threads) of
+        {ok, Thread = #{meta := Meta, position := Position}} ->
+            int:meta(Meta, Step),
+            reply(#{}),
+            Stepping =
+                case Step of
+                    continue -> none;
+                    _ -> {Step, Position}
+                end,
+            Thread1 = Thread#{stopped := false, stepping := Stepping},
+            State#{threads := Threads#{Id := Thread1}};
+        error ->
+            unknown_thread(),
+            State
+    end;
+command(stop, _State) ->
+    erlang:halt(0).
+
+with_thread(Id, #{threads := Threads}, Fun) ->
+    case maps:find(Id, Threads) of
+        {ok, Thread} -> reply(Fun(Thread));
+        error -> unknown_thread()
+    end.
+
+unknown_thread() ->
+    send(#{error => <<"There is no process with this id">>}).
+
+%
+% Interpreting modules
+%
+
+interpret(Module, Source, Functions) ->
+    Beam = code:which(Module),
+    case is_list(Beam) andalso int:i(Source) of
+        {module, Module} ->
+            {ok, {Module, [{abstract_code, {_, Forms}}]}} =
+                beam_lib:chunks(Beam, [abstract_code]),
+            [line_table(Forms, Function) || Function <- Functions];
+        _ ->
+            []
+    end.
+
+% Pairs the Gleam line of each statement of a function with the line of the
+% first Erlang expression generated for it. If the number of statements and
+% expressions differ only the start of the function is known.
+line_table(Forms, {Name, Arity, First, Last, Lines}) ->
+    Body =
+        case [Clauses || {function, _, N, A, Clauses} <- Forms, N =:= Name, A =:= Arity] of
+            [[{clause, _, _, _, Expressions} | _]] -> Expressions;
+            _ -> []
+        end,
+    Starts = [erl_anno:line(element(2, hd(Statement))) || Statement <- statements(Body)],
+    Pairs =
+        case length(Starts) =:= length(Lines) of
+            true -> lists:zip(Lines, Starts);
+            false -> [{First, Start} || Start <- lists:sublist(Starts, 1)]
+        end,
+    {{Name, Arity}, First, Last, Pairs}.
+
+% Groups the expressions of a function body by the Gleam statement they were
+% generated for. Pipelines and `let assert` assign to variables such as
+% `_pipe` and `_assert_subject` before the expression of the statement.
+statements(Expressions) ->
+    statements(Expressions, [], []).
+
+statements([], [], Statements) ->
+    lists:reverse(Statements);
+statements([], Pending, Statements) ->
+    lists:reverse([lists:reverse(Pending) | Statements]);
+statements([Expression | Rest], Pending, Statements) ->
+    case is_generated_assignment(Expression) of
+        true -> statements(Rest, [Expression | Pending], Statements);
+        false -> statements(Rest, [], [lists:reverse([Expression | Pending]) | Statements])
+    end.
+
+is_generated_assignment({match, _, {var, _, Name}, _}) ->
+    Text = atom_to_list(Name),
+    lists:prefix("_pipe", Text) orelse lists:prefix("_assert_subject", Text);
+is_generated_assignment(_) ->
+    false.
+
+% The statement of a function at or before the given Gleam line, as the
+% function, the Gleam line of the statement, and the Erlang line to break at.
+resolve_breakpoint(Functions, Line) ->
+    Matching = [
+        {Function, Pairs}
+     || {Function, First, Last, Pairs} <- Functions,
+        First =< Line,
+        Line =< Last,
+        Pairs =/= []
+    ],
+    case Matching of
+        [{Function, [First | _] = Pairs} | _] ->
+            {GleamLine, ErlangLine} = last_before(Pairs, Line, First),
+            {Function, GleamLine, ErlangLine};
+        [] ->
+            {none, null, none}
+    end.
+
+last_before([{GleamLine, _} = Pair | Rest], Line, _) when GleamLine =< Line ->
+    last_before(Rest, Line, Pair);
+last_before(_, _, Pair) ->
+    Pair.
+
+gleam_line(Module, Function, Line, #{modules := Modules}) ->
+    case lists:keyfind(Function, 1, maps:get(Module, Modules, [])) of
+        {_, First, _, []} ->
+            First;
+        {_, _, _, [First | _] = Pairs} ->
+            {GleamLine, _} = last_started(Pairs, Line, First),
+            GleamLine;
+        false ->
+            Line
+    end.
+
+last_started([{_, ErlangLine} = Pair | Rest], Line, _) when ErlangLine =< Line ->
+    last_started(Rest, Line, Pair);
+last_started(_, _, Pair) ->
+    Pair.
+
+%
+% Processes stopping
+%
+
+attach(Pid, Meta, State = #{threads := Threads, next_thread := Id}) ->
+    Thread = #{
+        pid => Pid,
+        meta => Meta,
+        position => none,
+        line => 0,
+        stopped => false,
+        stepping => none
+    },
+    State#{threads := Threads#{Id => Thread}, next_thread := Id + 1}.
+
+meta(Pid, {attached, Module, Line, _}, State) when is_integer(Line) ->
+    break_at(Pid, Module, Line, State);
+meta(Pid, {break_at, Module, Line, _}, State) ->
+    break_at(Pid, Module, Line, State);
+meta(Pid, {exit_at, _, _, _}, State = #{threads := Threads}) ->
+    State#{threads := maps:filter(fun(_, #{pid := P}) -> P =/= Pid end, Threads)};
+meta(_, _, State) ->
+    State.
+
+break_at(Pid, Module, Line, State = #{threads := Threads}) ->
+    case [{Id, Thread} || {Id, Thread = #{pid := P}} <- maps:to_list(Threads), P =:= Pid] of
+        [{_, #{stopped := true}}] ->
+            State;
+        [{Id, Thread = #{meta := Meta, stepping := Stepping}}] ->
+            Function = current_function(Meta),
+            Position = {Module, Function, gleam_line(Module, Function, Line, State)},
+            Stop = fun(Reason) ->
+                event(#{event => stopped, reason => Reason, thread => Id}),
+                Thread1 = Thread#{
+                    position := Position,
+                    line := Line,
+                    stopped := true,
+                    stepping := none
+                },
+                State#{threads := Threads#{Id := Thread1}}
+            end,
+            case Stepping of
+                none ->
+                    case is_breakpoint(Module, Function, Line, State) of
+                        true ->
+                            Stop(breakpoint);
+                        false ->
+                            int:meta(Meta, continue),
+                            State
+                    end;
+                % Stepping continues until the process reaches another
+                % line of Gleam code, as a statement can be many
+                % Erlang expressions.
+                {Step, Position} when Step =/= finish ->
+                    int:meta(Meta, Step),
+                    State;
+                {_, _} ->
+                    Stop(step)
+            end;
+        [] ->
+            State
+    end.
+
+% Lines of different functions can be the same as each line of a function is
+% counted from the start of the Gleam function, so a breakpoint only stops the
+% function it was set in.
+is_breakpoint(Module, Function, Line, #{breakpoints := Breakpoints}) ->
+    lists:member({Function, Line}, maps:get(Module, Breakpoints, [])).
+
+current_function(Meta) ->
+    case int:meta(Meta, backtrace, 1) of
+        [{_, {_, Name, Arguments}} | _] -> enclosing_function(Name, length(Arguments));
+        _ -> none
+    end.
+
+% Anonymous functions are named after the function they are defined in, such
+% as '-main/0-fun-0-'.
+enclosing_function(Name, Arity) ->
+    Pattern = "^-(.+)/([0-9]+)-fun-[0-9]+-$",
+    case re:run(atom_to_list(Name), Pattern, [{capture, all_but_first, list}]) of
+        {match, [Outer, OuterArity]} ->
+            enclosing_function(list_to_atom(Outer), list_to_integer(OuterArity));
+        nomatch ->
+            {Name, Arity}
+    end.
+
+frames(#{meta := Meta, line := Line}, State) ->
+    [Top | Rest] = int:meta(Meta, backtrace, all),
+    [frame(Top, Line, State) | [frame(Frame, frame_line(Meta, Frame), State) || Frame <- Rest]].
+
+frame({Level, {Module, Name, Arguments}}, Line, State) ->
+    {Function, Arity} = enclosing_function(Name, length(Arguments)),
+    #{
+        id => Level,
+        module => atom_to_binary(Module),
+        function => atom_to_binary(Function),
+        anonymous => Function =/= Name,
+        line => gleam_line(Module, {Function, Arity}, Line, State)
+    }.
+
+frame_line(Meta, {Level, _}) ->
+    case int:meta(Meta, stack_frame, {up, Level + 1}) of
+        {Level, {_, Line}, _} when is_integer(Line) -> Line;
+        {Level, _, Line} when is_integer(Line) -> Line;
+        _ -> 0
+    end.
+
+%
+% Values
+%
+
+% Formats a value as it would be written in Gleam.
+inspect(Value) ->
+    unicode:characters_to_binary(inspect_value(Value)).
+
+inspect_value(nil) ->
+    "Nil";
+inspect_value(true) ->
+    "True";
+inspect_value(false) ->
+    "False";
+inspect_value(Atom) when is_atom(Atom) ->
+    constructor_name(Atom);
+inspect_value(Int) when is_integer(Int) ->
+    integer_to_list(Int);
+inspect_value(Float) when is_float(Float) ->
+    float_to_list(Float, [short]);
+inspect_value(Binary) when is_binary(Binary) ->
+    case unicode:characters_to_binary(Binary) of
+        Binary -> [$", [escape_character(C) || <<C/utf8>> <= Binary], $"];
+        _ -> io_lib:format("~w", [Binary])
+    end;
+inspect_value(Bits) when is_bitstring(Bits) ->
+    io_lib:format("~w", [Bits]);
+inspect_value(List) when is_list(List) ->
+    case is_proper_list(List) of
+        true -> ["[", join([inspect_value(Element) || Element <- List]), "]"];
+        false -> io_lib:format("//erl(~tp)", [List])
+    end;
+inspect_value({}) ->
+    "#()";
+inspect_value(Tuple) when is_tuple(Tuple), is_atom(element(1, Tuple)) ->
+    [Tag | Fields] = tuple_to_list(Tuple),
+    [constructor_name(Tag), "(", join([inspect_value(Field) || Field <- Fields]), ")"];
+inspect_value(Tuple) when is_tuple(Tuple) ->
+    ["#(", join([inspect_value(Element) || Element <- tuple_to_list(Tuple)]), ")"];
+inspect_value(Map) when is_map(Map) ->
+    Entries = [
+        ["#(", inspect_value(Key), ", ", inspect_value(Value), ")"]
+     || {Key, Value} <- maps:to_list(Map)
+    ],
+    ["dict.from_list([", join(Entries), "])"];
+inspect_value(Fun) when is_function(Fun) ->
+    {arity, Arity} = erlang:fun_info(Fun, arity),
+    ["//fn(", join(lists:duplicate(Arity, "_")), ") { ... }"];
+inspect_value(Other) ->
+    io_lib:format("//erl(~tp)", [Other]).
+
+constructor_name(Atom) ->
+    [string:titlecase(Part) || Part <- string:split(atom_to_list(Atom), "_", all)].
+
+escape_character($") -> "\\\"";
+escape_character($\\) -> "\\\\";
+escape_character($\n) -> "\\n";
+escape_character($\r) -> "\\r";
+escape_character($\t) -> "\\t";
+escape_character(C) -> C.
+
+is_proper_list([]) -> true;
+is_proper_list([_ | Rest]) -> is_proper_list(Rest);
+is_proper_list(_) -> false.
+
+join(Parts) ->
+    lists:join(", ", Parts).
+
+%
+% Messages
+%
+
+reply(Body) ->
+    send(#{response => Body}).
+
+event(Event) ->
+    send(Event).
+
+send(Message) ->
+    io:put_chars(standard_io, [?MARKER, json(Message), "\n"]).
+
+json(null) ->
+    "null";
+json(true) ->
+    "true";
+json(false) ->
+    "false";
+json(Atom) when is_atom(Atom) ->
+    json(atom_to_binary(Atom));
+json(Int) when is_integer(Int) ->
+    integer_to_list(Int);
+json(String) when is_binary(String) ->
+    [$", [json_character(C) || C <- unicode:characters_to_list(String)], $"];
+json(List) when is_list(List) ->
+    ["[", lists:join(",", [json(Value) || Value <- List]), "]"];
+json(Map) when is_map(Map) ->
+    Members = [[json(Key), ":", json(Value)] || {Key, Value} <- maps:to_list(Map)],
+    ["{", lists:join(",", Members), "}"].
+
+% Only ASCII is written, so that the output does not depend on the encoding of
+% standard output.
+json_character($") ->
+    "\\\"";
+json_character($\\) ->
+    "\\\\";
+json_character(C) when C > 16#FFFF ->
+    Offset = C - 16#10000,
+    [json_unicode(16#D800 + (Offset bsr 10)), json_unicode(16#DC00 + (Offset band 16#3FF))];
+json_character(C) when C < 16#20; C > 16#7E ->
+    json_unicode(C);
+json_character(C) ->
+    C.
+
+json_unicode(C) ->
+    io_lib:format("\\u~4.16.0b", [C]).
@@ -0,0 +1,100 @@
+-module('gleam@@kernel').
+
+% The Erlang half of `gleam kernel`, which evaluates the cells of a notebook.
+% Commands are read from standard input, one Erlang term per line, and each is
+% answered with a line of JSON written to standard output after a marker.
+% Anything else written to standard output is the output of the cell being
+% evaluated.
+%
+% Each cell is compiled by the kernel into a module with the definitions of
+% the earlier cells and a function returning the value of the cell, which is
+% loaded in place of the module of the cell before.
+
+-export([start/0]).
+
+-define(MARKER, "@@gleam-kernel@@ ").
+
+start() ->
+    case io:get_line(standard_io, "") of
+        eof ->
+            ok;
+        {error, _} ->
+            ok;
+        Line ->
+            Text = unicode:characters_to_list(Line),
+            {ok, Tokens, _} = erl_scan:string(Text),
+            {ok, Command} = erl_parse:parse_term(Tokens),
+            command(Command),
+            start()
+    end.
+
+command({evaluate, Path, Module, Function}) ->
+    case compile:file(Path, [binary, return_errors]) of
+        {ok, Module, Binary} ->
+            _ = code:purge(Module),
+            {module, Module} = code:load_binary(Module, Path, Binary),
+            reply(evaluate(Module, Function));
+        {error, Errors, _} ->
+            reply(#{error => format("~tp", [Errors])})
+    end.
+
+evaluate(Module, Function) ->
+    try Module:Function() of
+        Value -> maps:merge(#{value => inspect(Value)}, encode(Value))
+    catch
+        Class:Reason:Stacktrace ->
+            #{exception => format_exception(Class, Reason, Stacktrace)}
+    end.
+
+% Values are shown as Gleam would show them if the standard library is
+% available, which it is for most projects.
+inspect(Value) ->
+    case code:ensure_loaded('gleam@string') of
+        {module, _} -> 'gleam@string':inspect(Value);
+        _ -> format("~tp", [Value])
+    end.
+
+% Values are also encoded for the kernel to print using their types, if the
+% project has been built with the module that encodes them.
+encode(Value) ->
+    case code:ensure_loaded('gleam@@inspect') of
+        {module, _} -> #{encoded => 'gleam@@inspect':encode(Value)};
+        _ -> #{}
+    end.
+
+format_exception(error, #{gleam_error := _, message := Message} = Error, _) ->
+    #{module := Module, line := Line} = Error,
+    <<Module/binary, ":", (integer_to_binary(Line))/binary, ": ", Message/binary>>;
+format_exception(Class, Reason, Stacktrace) ->
+    unicode:characters_to_binary(erl_error:format_exception(Class, Reason, Stacktrace)).
+
+format(Format, Arguments) ->
+    unicode:characters_to_binary(io_lib:format(Format, Arguments)).
+
+% The marker may follow output not ending in a newline on the same line.
+reply(Reply) ->
+    Members = [
+        [json_string(atom_to_binary(Key)), ":", json_string(Value)]
+     || {Key, Value} <- maps:to_list(Reply)
+    ],
+    io:put_chars(standard_io, [?MARKER, "{", lists:join(",", Members), "}\n"]).
+
+% Only ASCII is written, so that the output does not depend on the encoding of
+% standard output.
+json_string(String) ->
+    [$", [json_character(C) || C <- unicode:characters_to_list(String)], $"].
+
+json_character($") ->
+    "\\\"";
+json_character($\\) ->
+    "\\\\";
+json_character(C) when C > 16#FFFF ->
+    Offset = C - 16#10000,
+    [json_unicode(16#D800 + (Offset bsr 10)), json_unicode(16#DC00 + (Offset band 16#3FF))];
+json_character(C) when C < 16#20; C > 16#7E ->
+    json_unicode(C);
+json_character(C) ->
+    C.
+
+json_unicode(C) ->
+    io_lib:format("\\u~4.16.0b", [C]).
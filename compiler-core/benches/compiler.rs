@@ -0,0 +1,438 @@
+//! Measures the time taken to parse, type check, and generate code for the
+//! packages in `benches/fixtures` and `test/language`, and to resolve the
+//! dependencies of a project against part of the Hex package index.
+//!
+//! Run with `cargo bench --package gleam-core --bench compiler`.
+//!
+//! Once the benchmarks have run a summary of the results is written as JSON
+//! to `target/criterion/summary.json`, or the path in the
+//! `GLEAM_BENCH_SUMMARY` environment variable. If `GLEAM_BENCH_BASELINE` is
+//! set to the path of a summary from an earlier run the results are compared
+//! with it, and the process exits with an error if any benchmark is slower
+//! by more than `GLEAM_BENCH_THRESHOLD` percent, 10 by default.
+
+#![allow(
+    // TODO: fix
+    clippy::arc_with_non_send_sync,
+)]
+
+use std::{
+    collections::{HashMap, HashSet},
+    error::Error as StdError,
+    sync::Arc,
+    time::SystemTime,
+};
+
+use camino::{Utf8Path, Utf8PathBuf};
+use criterion::{BenchmarkId, Criterion};
+use ecow::EcoString;
+use gleam_core::{
+    analyse::TargetSupport,
+    build::{
+        Mode, Module, NullTelemetry, Outcome, PackageCompiler, StaleTracker, Target,
+        TargetCodegenConfiguration,
+    },
+    codegen::{Erlang, JavaScript, TypeScriptDeclarations},
+    config::PackageConfig,
+    dependency::{self, PackageFetcher, PackageFetchers, SourceKind},
+    io::{memory::InMemoryFileSystem, FileSystemWriter},
+    uid::UniqueIdGenerator,
+    warning::{VectorWarningEmitterIO, WarningEmitter},
+};
+use hexpm::{
+    version::{Range, Version},
+    Dependency, Release,
+};
+use serde::{Deserialize, Serialize};
+
+const DEFAULT_THRESHOLD: f64 = 10.0;
+
+/// A package read from disc into an in-memory file system.
+struct Fixture {
+    name: &'static str,
+    config: PackageConfig,
+    fs: InMemoryFileSystem,
+    sources: Vec<String>,
+}
+
+fn manifest_directory() -> Utf8PathBuf {
+    Utf8PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+}
+
+fn fixtures() -> Vec<Fixture> {
+    let root = manifest_directory();
+    vec![
+        load_fixture(
+            "stdlib_subset",
+            &root.join("benches/fixtures/stdlib_subset"),
+        ),
+        load_fixture("language", &root.join("../test/language")),
+    ]
+}
+
+fn load_fixture(name: &'static str, directory: &Utf8Path) -> Fixture {
+    let toml = std::fs::read_to_string(directory.join("gleam.toml")).expect("read gleam.toml");
+    let config: PackageConfig = toml::from_str(&toml).expect("parse gleam.toml");
+    let fs = InMemoryFileSystem::new();
+    let mut sources = vec![];
+    for directory_name in ["src", "test"] {
+        for entry in walkdir(&directory.join(directory_name)) {
+            let relative = entry.strip_prefix(directory).expect("fixture path");
+            let content = std::fs::read(&entry).expect("read fixture file");
+            fs.write_bytes(&Utf8PathBuf::from("/fixture").join(relative), &content)
+                .expect("write fixture file");
+            if entry.extension() == Some("gleam") {
+                sources.push(String::from_utf8(content).expect("utf8 source"));
+            }
+        }
+    }
+    Fixture {
+        name,
+        config,
+        fs,
+        sources,
+    }
+}
+
+fn walkdir(directory: &Utf8Path) -> Vec<Utf8PathBuf> {
+    let Ok(entries) = directory.read_dir_utf8() else {
+        return vec![];
+    };
+    let mut paths = vec![];
+    for entry in entries {
+        let entry = entry.expect("read fixture directory");
+        let is_directory = entry.file_type().expect("file type").is_dir();
+        let path = entry.into_path();
+        if is_directory {
+            paths.extend(walkdir(&path));
+        } else {
+            paths.push(path);
+        }
+    }
+    paths.sort();
+    paths
+}
+
+fn target_configuration(target: Target) -> TargetCodegenConfiguration {
+    match target {
+        Target::Erlang => TargetCodegenConfiguration::Erlang { app_file: None },
+        Target::JavaScript => TargetCodegenConfiguration::JavaScript {
+            emit_typescript_definitions: false,
+            prelude_location: Utf8PathBuf::from("../prelude.mjs"),
+            platform_location: None,
+        },
+    }
+}
+
+/// Type checks the fixture for the given target without generating any code.
+fn analyse(fixture: &Fixture, target: Target) -> Vec<Module> {
+    let target = target_configuration(target);
+    let root = Utf8PathBuf::from("/fixture");
+    let out = Utf8PathBuf::from("/out/lib").join(fixture.name);
+    let lib = Utf8PathBuf::from("/out/lib");
+    let warnings = WarningEmitter::new(Arc::new(VectorWarningEmitterIO::default()));
+    let mut compiler = PackageCompiler::new(
+        &fixture.config,
+        Mode::Dev,
+        &root,
+        &out,
+        &lib,
+        &target,
+        UniqueIdGenerator::new(),
+        fixture.fs.clone(),
+    );
+    compiler.write_metadata = false;
+    compiler.perform_codegen = false;
+    compiler.copy_native_files = false;
+    let outcome = compiler.compile(
+        &warnings,
+        &mut im::HashMap::new(),
+        &mut im::HashMap::new(),
+        &mut StaleTracker::default(),
+        &mut HashSet::new(),
+        &NullTelemetry,
+    );
+    match outcome {
+        Outcome::Ok(modules) => modules,
+        Outcome::PartialFailure(_, error) | Outcome::TotalFailure(error) => {
+            panic!("{}", error.pretty_string())
+        }
+    }
+}
+
+fn bench_parse(c: &mut Criterion, fixtures: &[Fixture]) {
+    let mut group = c.benchmark_group("parse");
+    for fixture in fixtures {
+        let _ = group.bench_function(fixture.name, |b| {
+            b.iter(|| {
+                for source in &fixture.sources {
+                    let _ = gleam_core::parse::parse_module(source).expect("parse");
+                }
+            })
+        });
+    }
+    group.finish();
+}
+
+fn bench_typecheck(c: &mut Criterion, fixtures: &[Fixture]) {
+    let mut group = c.benchmark_group("typecheck");
+    for fixture in fixtures {
+        let _ = group.bench_function(fixture.name, |b| {
+            b.iter(|| analyse(fixture, Target::Erlang))
+        });
+    }
+    group.finish();
+}
+
+fn bench_codegen(c: &mut Criterion, fixtures: &[Fixture]) {
+    let mut group = c.benchmark_group("codegen");
+    let build = Utf8PathBuf::from("/build");
+    for fixture in fixtures {
+        let modules = analyse(fixture, Target::Erlang);
+        let _ = group.bench_function(BenchmarkId::new("erlang", fixture.name), |b| {
+            b.iter(|| {
+                Erlang::new(&build, &build, false, false)
+                    .render(InMemoryFileSystem::new(), &modules)
+                    .expect("generate Erlang")
+            })
+        });
+
+        let modules = analyse(fixture, Target::JavaScript);
+        let prelude = Utf8PathBuf::from("../prelude.mjs");
+        let generator = JavaScript::new(
+            &build,
+            TypeScriptDeclarations::None,
+            &prelude,
+            None,
+            TargetSupport::Enforced,
+        );
+        let _ = group.bench_function(BenchmarkId::new("javascript", fixture.name), |b| {
+            b.iter(|| {
+                generator
+                    .render(&InMemoryFileSystem::new(), &modules)
+                    .expect("generate JavaScript")
+            })
+        });
+    }
+    group.finish();
+}
+
+/// Part of the Hex package index, read from `benches/fixtures/hex_index.toml`.
+#[derive(Debug, Deserialize)]
+struct PackageIndex {
+    requirements: HashMap<String, String>,
+    packages: HashMap<String, HashMap<String, HashMap<String, String>>>,
+}
+
+impl PackageFetcher for PackageIndex {
+    fn get_dependencies(&self, package: &str) -> Result<hexpm::Package, Box<dyn StdError>> {
+        let releases = self
+            .packages
+            .get(package)
+            .ok_or(Box::new(hexpm::ApiError::NotFound))?;
+        Ok(hexpm::Package {
+            name: package.into(),
+            repository: "hexpm".into(),
+            releases: releases
+                .iter()
+                .map(|(version, requirements)| Release {
+                    version: Version::parse(version).expect("release version"),
+                    requirements: requirements
+                        .iter()
+                        .map(|(name, requirement)| {
+                            let dependency = Dependency {
+                                app: None,
+                                optional: false,
+                                repository: None,
+                                requirement: Range::new(requirement.clone()),
+                            };
+                            (name.clone(), dependency)
+                        })
+                        .collect(),
+                    retirement_status: None,
+                    outer_checksum: vec![],
+                    meta: (),
+                })
+                .collect(),
+        })
+    }
+}
+
+fn package_index() -> PackageIndex {
+    let path = manifest_directory().join("benches/fixtures/hex_index.toml");
+    let toml = std::fs::read_to_string(path).expect("read package index");
+    toml::from_str(&toml).expect("parse package index")
+}
+
+fn bench_dependency_resolution(c: &mut Criterion) {
+    let mut group = c.benchmark_group("dependency_resolution");
+    let _ = group.bench_function("hex_index", |b| {
+        b.iter(|| {
+            let index = package_index();
+            let requirements = index
+                .requirements
+                .iter()
+                .map(|(name, requirement)| {
+                    (
+                        EcoString::from(name.as_str()),
+                        Range::new(requirement.clone()),
+                    )
+                })
+                .collect::<Vec<_>>();
+            let mut fetchers = PackageFetchers::new();
+            let _ = fetchers.register(SourceKind::Hex, Box::new(index));
+            dependency::resolve_versions(
+                fetchers,
+                HashMap::new(),
+                "app".into(),
+                requirements.into_iter(),
+                &HashMap::new(),
+                dependency::ResolveOptions::default(),
+            )
+            .expect("resolve versions")
+            .packages
+        })
+    });
+    group.finish();
+}
+
+/// The result of one benchmark, as written to the summary.
+#[derive(Debug, Serialize, Deserialize)]
+struct BenchmarkSummary {
+    name: String,
+    mean_nanoseconds: f64,
+    median_nanoseconds: f64,
+    standard_deviation_nanoseconds: f64,
+}
+
+#[derive(Debug, Deserialize)]
+struct Estimates {
+    mean: Estimate,
+    median: Estimate,
+    std_dev: Estimate,
+}
+
+#[derive(Debug, Deserialize)]
+struct Estimate {
+    point_estimate: f64,
+}
+
+fn output_directory() -> Utf8PathBuf {
+    if let Ok(directory) = std::env::var("CRITERION_HOME") {
+        return Utf8PathBuf::from(directory);
+    }
+    match std::env::var("CARGO_TARGET_DIR") {
+        Ok(directory) => Utf8PathBuf::from(directory),
+        Err(_) => manifest_directory()
+            .parent()
+            .expect("workspace directory")
+            .join("target"),
+    }
+    .join("criterion")
+}
+
+/// The name of each benchmark, which is also the directory criterion records
+/// its results in.
+fn benchmark_names(fixtures: &[Fixture]) -> Vec<String> {
+    let mut names = vec![];
+    for fixture in fixtures {
+        names.push(format!("parse/{}", fixture.name));
+        names.push(format!("typecheck/{}", fixture.name));
+        names.push(format!("codegen/erlang/{}", fixture.name));
+        names.push(format!("codegen/javascript/{}", fixture.name));
+    }
+    names.push("dependency_resolution/hex_index".into());
+    names.sort();
+    names
+}
+
+/// Reads the estimates criterion recorded for each benchmark, skipping any
+/// that were not run since the given time, such as those excluded by a filter.
+fn summarise(directory: &Utf8Path, names: Vec<String>, since: SystemTime) -> Vec<BenchmarkSummary> {
+    names
+        .into_iter()
+        .filter_map(|name| {
+            let path = directory.join(&name).join("new/estimates.json");
+            if path.metadata().ok()?.modified().ok()? < since {
+                return None;
+            }
+            let json = std::fs::read_to_string(path).ok()?;
+            let estimates: Estimates = serde_json::from_str(&json).ok()?;
+            Some(BenchmarkSummary {
+                name,
+                mean_nanoseconds: estimates.mean.point_estimate,
+                median_nanoseconds: estimates.median.point_estimate,
+                standard_deviation_nanoseconds: estimates.std_dev.point_estimate,
+            })
+        })
+        .collect()
+}
+
+/// Returns a description of each benchmark that is slower than in the
+/// baseline by more than the threshold percentage.
+fn regressions(
+    summaries: &[BenchmarkSummary],
+    baseline: &[BenchmarkSummary],
+    threshold: f64,
+) -> Vec<String> {
+    summaries
+        .iter()
+        .filter_map(|summary| {
+            let previous = baseline
+                .iter()
+                .find(|previous| previous.name == summary.name)?;
+            let change = (summary.mean_nanoseconds - previous.mean_nanoseconds)
+                / previous.mean_nanoseconds
+                * 100.0;
+            (change > threshold).then(|| {
+                format!(
+                    "{}: {:.0}ns -> {:.0}ns ({change:+.1}%)",
+                    summary.name, previous.mean_nanoseconds, summary.mean_nanoseconds
+                )
+            })
+        })
+        .collect()
+}
+
+fn main() {
+    let start = SystemTime::now();
+    let directory = output_directory();
+    let mut criterion = Criterion::default()
+        .output_directory(directory.as_std_path())
+        .configure_from_args();
+
+    let fixtures = fixtures();
+    bench_parse(&mut criterion, &fixtures);
+    bench_typecheck(&mut criterion, &fixtures);
+    bench_codegen(&mut criterion, &fixtures);
+    bench_dependency_resolution(&mut criterion);
+    criterion.final_summary();
+
+    let summaries = summarise(&directory, benchmark_names(&fixtures), start);
+    let summary_path = std::env::var("GLEAM_BENCH_SUMMARY")
+        .map(Utf8PathBuf::from)
+        .unwrap_or_else(|_| directory.join("summary.json"));
+    let json = serde_json::to_string_pretty(&summaries).expect("summary JSON");
+    std::fs::write(&summary_path, json).expect("write summary");
+    println!("Wrote summary to {summary_path}");
+
+    let Ok(baseline_path) = std::env::var("GLEAM_BENCH_BASELINE") else {
+        return;
+    };
+    let baseline = std::fs::read_to_string(&baseline_path).expect("read baseline");
+    let baseline: Vec<BenchmarkSummary> = serde_json::from_str(&baseline).expect("baseline JSON");
+    let threshold = std::env::var("GLEAM_BENCH_THRESHOLD")
+        .ok()
+        .and_then(|threshold| threshold.parse().ok())
+        .unwrap_or(DEFAULT_THRESHOLD);
+    let regressions = regressions(&summaries, &baseline, threshold);
+    if regressions.is_empty() {
+        println!("No benchmarks are more than {threshold}% slower than {baseline_path}");
+        return;
+    }
+    eprintln!("Benchmarks more than {threshold}% slower than {baseline_path}:");
+    for regression in regressions {
+        eprintln!("  {regression}");
+    }
+    std::process::exit(1);
+}
@@ -0,0 +1,9 @@
+-module(gleam_stdlib).
+
+-export([parse_int/1]).
+
+parse_int(String) ->
+    case catch binary_to_integer(String) of
+        Int when is_integer(Int) -> {ok, Int};
+        _ -> {error, nil}
+    end.
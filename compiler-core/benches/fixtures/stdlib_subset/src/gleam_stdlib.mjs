@@ -0,0 +1,17 @@
+import { Ok, Error, Nil } from "./gleam.mjs";
+
+export function parse_int(value) {
+  if (/^[-+]?(\d+)$/.test(value)) {
+    return new Ok(parseInt(value));
+  } else {
+    return new Error(Nil);
+  }
+}
+
+export function to_string(term) {
+  return term.toString();
+}
+
+export function identity(x) {
+  return x;
+}
@@ -0,0 +1,368 @@
+//! Measures the memory used when compiling a large generated package, the
+//! peak memory used when compiling a project with many dependency packages
+//! with and without keeping their typed ASTs, the allocations made when
+//! parsing a package the size of the standard library, and the memory used to
+//! hold repeated names as strings and as interned symbols.
+//!
+//! Run with `cargo bench --package gleam-core --bench memory`, adding
+//! `--features arena` to parse with per-module arenas.
+#![allow(
+    // TODO: fix
+    clippy::arc_with_non_send_sync,
+)]
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    collections::HashSet,
+    fmt::Write,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Instant,
+};
+
+use camino::Utf8PathBuf;
+use ecow::EcoString;
+use gleam_core::{
+    analyse::TargetSupport,
+    build::{
+        Codegen, Mode, NullTelemetry, Options, Outcome, PackageCompiler, ProjectCompiler,
+        StaleTracker, Target, TargetCodegenConfiguration,
+    },
+    config::PackageConfig,
+    intern::Symbol,
+    io::{memory::InMemoryFileSystem, FileSystemWriter},
+    manifest::{Base16Checksum, ManifestPackage, ManifestPackageSource},
+    paths::ProjectPaths,
+    uid::UniqueIdGenerator,
+    warning::{VectorWarningEmitterIO, WarningEmitter},
+};
+
+const MODULES: usize = 200;
+const FUNCTIONS_PER_MODULE: usize = 40;
+const NAME_OCCURRENCES: usize = 500;
+const STDLIB_MODULES: usize = 40;
+const DEPENDENCIES: usize = 10;
+const MODULES_PER_DEPENDENCY: usize = 40;
+
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+static LIVE: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+fn record_allocation(size: usize) {
+    let _ = ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+    let _ = ALLOCATED.fetch_add(size, Ordering::Relaxed);
+    let live = LIVE.fetch_add(size, Ordering::Relaxed) + size;
+    let _ = PEAK.fetch_max(live, Ordering::Relaxed);
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        record_allocation(layout.size());
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        let _ = LIVE.fetch_sub(layout.size(), Ordering::Relaxed);
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let _ = LIVE.fetch_sub(layout.size(), Ordering::Relaxed);
+        record_allocation(new_size);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+#[derive(Debug)]
+struct Usage {
+    allocations: usize,
+    allocated: usize,
+    peak: usize,
+}
+
+/// Runs the function, returning the memory it allocated along with the peak
+/// memory in use while it ran, not counting what was in use before it started.
+fn measure<T>(f: impl FnOnce() -> T) -> (T, Usage) {
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed);
+    let allocated = ALLOCATED.load(Ordering::Relaxed);
+    let live = LIVE.load(Ordering::Relaxed);
+    PEAK.store(live, Ordering::Relaxed);
+    let value = f();
+    let usage = Usage {
+        allocations: ALLOCATIONS.load(Ordering::Relaxed) - allocations,
+        allocated: ALLOCATED.load(Ordering::Relaxed) - allocated,
+        peak: PEAK.load(Ordering::Relaxed) - live,
+    };
+    (value, usage)
+}
+
+fn module_name(index: usize) -> String {
+    format!("bench/generated/module_{index}")
+}
+
+fn module_source(index: usize) -> String {
+    let mut src = String::new();
+    if index > 0 {
+        writeln!(src, "import {}", module_name(index - 1)).expect("write");
+    }
+    writeln!(
+        src,
+        "
+pub type Record{index} {{
+  Record{index}(identifier: Int, description: String, values: List(Float))
+  Empty{index}
+}}
+"
+    )
+    .expect("write");
+    for function in 0..FUNCTIONS_PER_MODULE {
+        writeln!(
+            src,
+            "/// Makes a record when the identifier is greater than {function}.
+pub fn make_{function}(identifier: Int, description: String) -> Record{index} {{
+  case identifier > {function} {{
+    True -> Record{index}(identifier: identifier, description: description, values: [1.0, 2.0])
+    False -> Empty{index}
+  }}
+}}
+"
+        )
+        .expect("write");
+    }
+    if index > 0 {
+        writeln!(
+            src,
+            "pub fn previous(identifier) {{
+  module_{previous}.make_0(identifier, \"wibble\")
+}}",
+            previous = index - 1
+        )
+        .expect("write");
+    }
+    src
+}
+
+fn compile_package() -> Usage {
+    let fs = InMemoryFileSystem::new();
+    for index in 0..MODULES {
+        let path = Utf8PathBuf::from(format!("/bench/src/{}.gleam", module_name(index)));
+        fs.write(&path, &module_source(index))
+            .expect("write source");
+    }
+
+    let config = PackageConfig {
+        name: "bench".into(),
+        ..Default::default()
+    };
+    let target = TargetCodegenConfiguration::JavaScript {
+        emit_typescript_definitions: false,
+        prelude_location: Utf8PathBuf::from("../prelude.mjs"),
+        platform_location: None,
+    };
+    let root = Utf8PathBuf::from("/bench");
+    let out = Utf8PathBuf::from("/out/lib/bench");
+    let lib = Utf8PathBuf::from("/out/lib");
+    let warnings = WarningEmitter::new(Arc::new(VectorWarningEmitterIO::default()));
+
+    let (outcome, usage) = measure(|| {
+        let mut compiler = PackageCompiler::new(
+            &config,
+            Mode::Dev,
+            &root,
+            &out,
+            &lib,
+            &target,
+            UniqueIdGenerator::new(),
+            fs.clone(),
+        );
+        compiler.write_metadata = false;
+        compiler.copy_native_files = false;
+        compiler.compile(
+            &warnings,
+            &mut im::HashMap::new(),
+            &mut im::HashMap::new(),
+            &mut StaleTracker::default(),
+            &mut HashSet::new(),
+            &NullTelemetry,
+        )
+    });
+    match outcome {
+        Outcome::Ok(modules) => assert_eq!(modules.len(), MODULES),
+        Outcome::PartialFailure(_, error) | Outcome::TotalFailure(error) => {
+            panic!("{}", error.pretty_string())
+        }
+    }
+    usage
+}
+
+/// Compiles a project whose root package depends on generated dependency
+/// packages, returning the peak memory in use while compiling.
+fn compile_project(keep_dependency_modules: bool) -> Usage {
+    let fs = InMemoryFileSystem::new();
+    let paths = ProjectPaths::new("/project".into());
+    let mut packages = vec![];
+    let mut root_dependencies = String::new();
+    for dependency in 0..DEPENDENCIES {
+        let name = format!("dependency_{dependency}");
+        let package = ManifestPackage {
+            name: name.clone().into(),
+            version: hexpm::version::Version::new(1, 0, 0),
+            build_tools: vec!["gleam".into()],
+            otp_app: None,
+            requirements: vec![],
+            source: ManifestPackageSource::Hex {
+                outer_checksum: Base16Checksum(vec![0]),
+                organization: None,
+            },
+        };
+        let directory = paths.build_packages_package_source(&package);
+        fs.write(
+            &directory.join("gleam.toml"),
+            &format!("name = \"{name}\"\nversion = \"1.0.0\"\n"),
+        )
+        .expect("write config");
+        for index in 0..MODULES_PER_DEPENDENCY {
+            let module = module_name(index).replace("bench", &name);
+            let source = module_source(index).replace("bench/", &format!("{name}/"));
+            fs.write(&directory.join(format!("src/{module}.gleam")), &source)
+                .expect("write source");
+        }
+        writeln!(root_dependencies, "{name} = \">= 1.0.0\"").expect("write");
+        packages.push(package);
+    }
+    let config = format!(
+        "name = \"project\"\nversion = \"1.0.0\"\ntarget = \"javascript\"\n\n[dependencies]\n{root_dependencies}"
+    );
+    fs.write(&paths.root_config(), &config)
+        .expect("write config");
+    fs.write(
+        &paths.src_directory().join("project.gleam"),
+        "pub fn main() { 1 }",
+    )
+    .expect("write source");
+    let config: PackageConfig = toml::from_str(&config).expect("parse config");
+
+    let options = Options {
+        mode: Mode::Dev,
+        target: Some(Target::JavaScript),
+        codegen: Codegen::All,
+        warnings_as_errors: false,
+        verify_artefacts: false,
+        root_target_support: TargetSupport::Enforced,
+    };
+    let (built, usage) = measure(|| {
+        let mut compiler = ProjectCompiler::new(
+            config,
+            options,
+            packages,
+            Box::new(NullTelemetry),
+            Arc::new(VectorWarningEmitterIO::default()),
+            paths,
+            fs.clone(),
+        );
+        compiler.keep_dependency_modules = keep_dependency_modules;
+        compiler.compile()
+    });
+    let built = built.unwrap_or_else(|error| panic!("{}", error.pretty_string()));
+    let kept = if keep_dependency_modules {
+        DEPENDENCIES * MODULES_PER_DEPENDENCY
+    } else {
+        0
+    };
+    assert_eq!(built.compiled_dependency_modules().len(), kept);
+    usage
+}
+
+fn parse_package() -> Usage {
+    let sources = (0..STDLIB_MODULES).map(module_source).collect::<Vec<_>>();
+    let start = Instant::now();
+    let ((), usage) = measure(|| {
+        for source in &sources {
+            let _ = gleam_core::parse::parse_module(source).expect("parse");
+        }
+    });
+    println!(
+        "parse {STDLIB_MODULES} modules, {} lines: {:?}, {} allocations, {} bytes allocated",
+        sources
+            .iter()
+            .map(|source| source.lines().count())
+            .sum::<usize>(),
+        start.elapsed(),
+        usage.allocations,
+        usage.allocated,
+    );
+    usage
+}
+
+/// The names that appear in the types of the generated package, each held
+/// once for every place it occurs.
+fn names() -> Vec<String> {
+    (0..MODULES)
+        .flat_map(|index| [module_name(index), format!("Record{index}")])
+        .collect()
+}
+
+fn main() {
+    let start = Instant::now();
+    let usage = compile_package();
+    println!(
+        "compile {MODULES} modules: {:?}, {} allocations, {} bytes allocated, {} bytes peak",
+        start.elapsed(),
+        usage.allocations,
+        usage.allocated,
+        usage.peak
+    );
+
+    for keep_dependency_modules in [true, false] {
+        let start = Instant::now();
+        let usage = compile_project(keep_dependency_modules);
+        println!(
+            "compile {DEPENDENCIES} dependencies of {MODULES_PER_DEPENDENCY} modules, {}: {:?}, {} bytes peak",
+            if keep_dependency_modules {
+                "keeping their modules"
+            } else {
+                "dropping their modules"
+            },
+            start.elapsed(),
+            usage.peak
+        );
+    }
+
+    let _ = parse_package();
+
+    let names = names();
+    let (strings, usage) = measure(|| {
+        names
+            .iter()
+            .flat_map(|name| (0..NAME_OCCURRENCES).map(move |_| EcoString::from(name.as_str())))
+            .collect::<Vec<_>>()
+    });
+    println!(
+        "{} names as strings: {} allocations, {} bytes peak",
+        strings.len(),
+        usage.allocations,
+        usage.peak
+    );
+    drop(strings);
+
+    let (symbols, usage) = measure(|| {
+        names
+            .iter()
+            .flat_map(|name| (0..NAME_OCCURRENCES).map(move |_| Symbol::new(name)))
+            .collect::<Vec<_>>()
+    });
+    println!(
+        "{} names as symbols: {} allocations, {} bytes peak",
+        symbols.len(),
+        usage.allocations,
+        usage.peak
+    );
+}
@@ -1,3 +1,4 @@
+pub mod expression;
 mod imports;
 #[cfg(test)]
 mod tests;
@@ -23,6 +24,7 @@ use crate::{
         fields::{FieldMap, FieldMapBuilder},
         hydrator::Hydrator,
         prelude::*,
+        pretty::Alias,
         AccessorsMap, Deprecation, ModuleInterface, PatternConstructor, RecordAccessor, Type,
         TypeConstructor, TypeValueConstructor, TypeValueConstructorField, TypeVariantConstructors,
         ValueConstructor, ValueConstructorVariant,
@@ -198,6 +200,7 @@ impl<'a, A> ModuleAnalyzer<'a, A> {
             self.importable_modules,
             self.warnings,
             self.target_support,
+            self.package_config.warnings,
         );
 
         let statements = GroupedStatements::new(module.into_iter_statements(self.target));
@@ -206,7 +209,13 @@ impl<'a, A> ModuleAnalyzer<'a, A> {
         // Register any modules, types, and values being imported
         // We process imports first so that anything imported can be referenced
         // anywhere in the module.
-        let mut env = Importer::run(self.origin, env, &statements.imports, &mut self.errors);
+        let mut env = Importer::run(
+            self.origin,
+            env,
+            &statements.imports,
+            &self.package_config.import_rules,
+            &mut self.errors,
+        );
 
         // Register types so they can be used in constructors and functions
         // earlier in the module.
@@ -270,6 +279,16 @@ impl<'a, A> ModuleAnalyzer<'a, A> {
             }
         }
 
+        // Type errors print the types they are about using the module's type
+        // aliases where they can, as that is likely how they are written.
+        let aliases = type_aliases(&typed_statements, &env);
+        if !aliases.is_empty() {
+            self.errors = std::mem::take(&mut self.errors)
+                .into_iter()
+                .map(|error| error.with_unify_error_type_aliases(&aliases))
+                .collect();
+        }
+
         // Generate warnings for unused items
         let unused_imports = env.convert_unused_to_warnings();
 
@@ -366,7 +385,7 @@ impl<'a, A> ModuleAnalyzer<'a, A> {
                 documentation: doc.clone(),
                 location,
                 literal: typed_expr.clone(),
-                module: self.module_name.clone(),
+                module: (&self.module_name).into(),
                 implementations,
             },
             type_: type_.clone(),
@@ -418,6 +437,7 @@ impl<'a, A> ModuleAnalyzer<'a, A> {
             deprecation,
             external_erlang,
             external_javascript,
+            behaviour,
             return_type: (),
             implementations: _,
         } = f;
@@ -533,7 +553,7 @@ impl<'a, A> ModuleAnalyzer<'a, A> {
             documentation: doc.clone(),
             name: impl_function,
             field_map,
-            module: impl_module,
+            module: impl_module.into(),
             arity: typed_args.len(),
             location,
             implementations,
@@ -562,6 +582,7 @@ impl<'a, A> ModuleAnalyzer<'a, A> {
             body,
             external_erlang,
             external_javascript,
+            behaviour,
             implementations,
         })
     }
@@ -872,7 +893,7 @@ impl<'a, A> ModuleAnalyzer<'a, A> {
                 arity: constructor.arguments.len() as u16,
                 field_map: field_map.clone(),
                 location: constructor.location,
-                module: self.module_name.clone(),
+                module: (&self.module_name).into(),
                 constructor_index: index as u16,
             };
 
@@ -969,9 +990,9 @@ impl<'a, A> ModuleAnalyzer<'a, A> {
 
         let typ = Arc::new(Type::Named {
             publicity,
-            package: environment.current_package.clone(),
-            module: self.module_name.to_owned(),
-            name: name.clone(),
+            package: environment.current_package.as_str().into(),
+            module: self.module_name.as_str().into(),
+            name: name.as_str().into(),
             args: parameters.clone(),
         });
         let _ = self.hydrators.insert(name.clone(), hydrator);
@@ -1120,6 +1141,7 @@ impl<'a, A> ModuleAnalyzer<'a, A> {
             external_erlang,
             external_javascript,
             deprecation,
+            behaviour: _,
             end_position: _,
             body: _,
             return_type: _,
@@ -1154,7 +1176,7 @@ impl<'a, A> ModuleAnalyzer<'a, A> {
             documentation: documentation.clone(),
             name: impl_function,
             field_map,
-            module: impl_module,
+            module: impl_module.into(),
             arity: args.len(),
             location: *location,
             implementations: *implementations,
@@ -1356,7 +1378,7 @@ fn generalise_module_constant(
         documentation: doc.clone(),
         location,
         literal: *value.clone(),
-        module: module_name.clone(),
+        module: module_name.into(),
         implementations,
     };
     environment.insert_variable(
@@ -1408,6 +1430,7 @@ fn generalise_function(
         return_type,
         external_erlang,
         external_javascript,
+        behaviour,
         implementations,
     } = function;
 
@@ -1429,7 +1452,7 @@ fn generalise_function(
         documentation: doc.clone(),
         name: impl_function,
         field_map,
-        module: impl_module,
+        module: impl_module.into(),
         arity: args.len(),
         location,
         implementations,
@@ -1464,6 +1487,7 @@ fn generalise_function(
         body,
         external_erlang,
         external_javascript,
+        behaviour,
         implementations,
     })
 }
@@ -1594,6 +1618,25 @@ fn get_type_dependencies(typ: &TypeAst) -> Vec<EcoString> {
     deps
 }
 
+/// The type aliases defined in the module which types are printed as.
+fn type_aliases(definitions: &[TypedDefinition], environment: &Environment<'_>) -> Vec<Alias> {
+    definitions
+        .iter()
+        .filter_map(|definition| match definition {
+            Definition::TypeAlias(TypeAlias { alias, .. }) => {
+                let constructor = environment.module_types.get(alias)?;
+                Some(Alias {
+                    name: alias.clone(),
+                    parameters: constructor.parameters.clone(),
+                    type_: constructor.typ.clone(),
+                })
+            }
+            _ => None,
+        })
+        .filter(Alias::is_printable)
+        .collect()
+}
+
 fn sorted_type_aliases(aliases: &Vec<TypeAlias<()>>) -> Result<Vec<&TypeAlias<()>>, Error> {
     let mut deps: Vec<(EcoString, Vec<EcoString>)> = Vec::with_capacity(aliases.len());
 
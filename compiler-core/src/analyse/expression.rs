@@ -0,0 +1,268 @@
+//! Inference of the type of a single expression, as if it were written in a
+//! module of the project. This is what a REPL, a notebook kernel, or an editor
+//! showing the type of a selection needs, without compiling anything.
+
+#[cfg(test)]
+mod tests;
+
+use std::{collections::HashMap, sync::Arc};
+
+use camino::Utf8PathBuf;
+use ecow::EcoString;
+
+use crate::{
+    ast::{Definition, SrcSpan, TypedModule},
+    build::{Origin, Outcome, Target},
+    config::PackageConfig,
+    diagnostic::{Diagnostic, Level, Location},
+    line_numbers::LineNumbers,
+    type_::{pretty::Printer, ModuleInterface, Type},
+    uid::UniqueIdGenerator,
+    warning::{TypeWarningEmitter, VectorWarningEmitterIO, WarningEmitter},
+    Error,
+};
+
+use super::{ModuleAnalyzerConstructor, TargetSupport};
+
+/// The name of the public function the expression is inferred as the body
+/// of, which can be called to evaluate the expression once the analysed
+/// module has been compiled.
+pub const EXPRESSION_FUNCTION: &str = "gleam_expression_to_infer";
+
+/// The module an expression is written in, whose imports and definitions,
+/// public or private, it can refer to.
+#[derive(Debug, Clone)]
+pub struct ModuleContext {
+    pub name: EcoString,
+    pub path: Utf8PathBuf,
+    pub src: EcoString,
+    pub origin: Origin,
+}
+
+impl ModuleContext {
+    /// A module with nothing in it, for expressions which only refer to the
+    /// prelude and to modules they import themselves.
+    pub fn empty(name: EcoString) -> Self {
+        Self {
+            name,
+            path: Utf8PathBuf::new(),
+            src: "".into(),
+            origin: Origin::Src,
+        }
+    }
+}
+
+/// The context module with the analysed code added to it, which can be given
+/// to the code generators.
+#[derive(Debug, Clone)]
+pub struct AnalysedModule {
+    pub ast: TypedModule,
+    pub src: EcoString,
+    pub path: Utf8PathBuf,
+}
+
+/// The inferred type of an expression and the problems found with it.
+#[derive(Debug, Clone)]
+pub struct InferredExpression {
+    /// The type of the expression, if it could be inferred.
+    pub type_: Option<Arc<Type>>,
+    /// The errors and warnings for the expression. Their locations are in the
+    /// expression itself rather than in the source of the module.
+    pub diagnostics: Vec<Diagnostic>,
+    /// The module with the expression as the body of the public function
+    /// [`EXPRESSION_FUNCTION`], if there were no errors.
+    pub module: Option<AnalysedModule>,
+}
+
+impl InferredExpression {
+    /// The type of the expression as it would be written in Gleam.
+    pub fn pretty_type(&self) -> Option<String> {
+        self.type_
+            .as_ref()
+            .map(|type_| Printer::new().pretty_print(type_, 0))
+    }
+}
+
+/// The problems found with definitions added to the end of a module.
+#[derive(Debug, Clone)]
+pub struct CheckedDefinitions {
+    /// The errors and warnings for the definitions, located in the
+    /// definitions themselves as for [`InferredExpression::diagnostics`].
+    pub diagnostics: Vec<Diagnostic>,
+    /// The module with the definitions added to it, if there were no errors.
+    pub module: Option<AnalysedModule>,
+}
+
+/// Infers the types of expressions using what has been analysed of the
+/// project, such as the modules returned by
+/// [`ProjectCompiler::get_importable_modules`](crate::build::ProjectCompiler::get_importable_modules).
+#[derive(Debug)]
+pub struct ExpressionAnalyzer<'a> {
+    pub target: Target,
+    pub ids: &'a UniqueIdGenerator,
+    pub importable_modules: &'a im::HashMap<EcoString, ModuleInterface>,
+    pub package_config: &'a PackageConfig,
+}
+
+impl<'a> ExpressionAnalyzer<'a> {
+    /// Infers the type of an expression, or of a sequence of statements
+    /// ending in one, as if it were the body of a function at the end of the
+    /// context module.
+    pub fn infer(&self, expression: &str, context: &ModuleContext) -> InferredExpression {
+        let prefix = format!("{}\n\npub fn {EXPRESSION_FUNCTION}() {{\n", context.src);
+        let (module, diagnostics) = self.analyse(&prefix, expression, "\n}\n", context);
+        let type_ = module
+            .as_ref()
+            .and_then(|module| expression_type(&module.ast));
+        InferredExpression {
+            type_,
+            diagnostics,
+            module,
+        }
+    }
+
+    /// Checks definitions, such as functions, types, constants, and imports,
+    /// as if they were written at the end of the context module.
+    pub fn check_definitions(
+        &self,
+        definitions: &str,
+        context: &ModuleContext,
+    ) -> CheckedDefinitions {
+        let prefix = format!("{}\n\n", context.src);
+        let (module, diagnostics) = self.analyse(&prefix, definitions, "\n", context);
+        CheckedDefinitions {
+            diagnostics,
+            module,
+        }
+    }
+
+    /// Analyses the context module with the code inserted between the prefix
+    /// and the suffix, returning the diagnostics for the code and the
+    /// analysed module if there were no errors.
+    fn analyse(
+        &self,
+        prefix: &str,
+        code: &str,
+        suffix: &str,
+        context: &ModuleContext,
+    ) -> (Option<AnalysedModule>, Vec<Diagnostic>) {
+        let src: EcoString = format!("{prefix}{code}{suffix}").into();
+        let code_span = SrcSpan::new(prefix.len() as u32, (prefix.len() + code.len()) as u32);
+
+        let mut parsed = match crate::parse::parse_module(&src) {
+            Ok(parsed) => parsed,
+            Err(error) => {
+                let error = Error::Parse {
+                    path: context.path.clone(),
+                    src: src.clone(),
+                    error,
+                };
+                return (None, relocate(error.to_diagnostics(), code, code_span));
+            }
+        };
+        parsed.module.name = context.name.clone();
+
+        let warnings = Arc::new(VectorWarningEmitterIO::new());
+        let emitter = TypeWarningEmitter::new(
+            context.path.clone(),
+            src.clone(),
+            WarningEmitter::new(warnings.clone()),
+        );
+        let outcome = ModuleAnalyzerConstructor::<()> {
+            target: self.target,
+            ids: self.ids,
+            origin: context.origin,
+            importable_modules: self.importable_modules,
+            warnings: &emitter,
+            direct_dependencies: &HashMap::new(),
+            target_support: TargetSupport::NotEnforced,
+            package_config: self.package_config,
+        }
+        .infer_module(parsed.module, LineNumbers::new(&src), context.path.clone());
+
+        let (module, errors) = match outcome {
+            Outcome::Ok(module) => (Some(module), None),
+            Outcome::PartialFailure(module, errors) => (Some(module), Some(errors)),
+            Outcome::TotalFailure(errors) => (None, Some(errors)),
+        };
+        let mut diagnostics = vec![];
+        if let Some(errors) = errors {
+            let error = Error::Type {
+                path: context.path.clone(),
+                src: src.clone(),
+                errors,
+            };
+            diagnostics.extend(error.to_diagnostics());
+        }
+        diagnostics.extend(
+            warnings
+                .take()
+                .iter()
+                .map(|warning| warning.to_diagnostic()),
+        );
+
+        let diagnostics = relocate(diagnostics, code, code_span);
+        let module = module
+            .filter(|_| {
+                !diagnostics
+                    .iter()
+                    .any(|diagnostic| diagnostic.level == Level::Error)
+            })
+            .map(|ast| AnalysedModule {
+                ast,
+                src,
+                path: context.path.clone(),
+            });
+        (module, diagnostics)
+    }
+}
+
+/// The return type of the function the expression is the body of.
+fn expression_type(module: &TypedModule) -> Option<Arc<Type>> {
+    module
+        .definitions
+        .iter()
+        .find_map(|definition| match definition {
+            Definition::Function(function) if function.name.as_str() == EXPRESSION_FUNCTION => {
+                Some(function.return_type.clone())
+            }
+            _ => None,
+        })
+}
+
+/// Keeps the diagnostics about the code, moving their locations to be within
+/// the code alone. Diagnostics about the rest of the module are left out, as
+/// they are reported when the module itself is compiled.
+fn relocate(diagnostics: Vec<Diagnostic>, code: &str, span: SrcSpan) -> Vec<Diagnostic> {
+    let within = |location: &SrcSpan| location.start >= span.start && location.end <= span.end + 1;
+    diagnostics
+        .into_iter()
+        .filter_map(|mut diagnostic| {
+            let Some(location) = diagnostic.location.take() else {
+                return Some(diagnostic);
+            };
+            if !within(&location.label.span) {
+                return None;
+            }
+            let mut location = Location {
+                src: code.into(),
+                ..location
+            };
+            let relative = |label_span: SrcSpan| {
+                let start = label_span.start.saturating_sub(span.start);
+                let end = label_span
+                    .end
+                    .saturating_sub(span.start)
+                    .min(code.len() as u32);
+                SrcSpan::new(start, end.max(start))
+            };
+            location.label.span = relative(location.label.span);
+            location.extra_labels.retain(|label| within(&label.span));
+            for label in &mut location.extra_labels {
+                label.span = relative(label.span);
+            }
+            diagnostic.location = Some(location);
+            Some(diagnostic)
+        })
+        .collect()
+}
@@ -0,0 +1,191 @@
+use crate::{
+    analyse::ModuleAnalyzerConstructor,
+    type_::{build_prelude, PRELUDE_MODULE_NAME},
+    warning::TypeWarningEmitter,
+};
+
+use super::*;
+
+struct Project {
+    ids: UniqueIdGenerator,
+    modules: im::HashMap<EcoString, ModuleInterface>,
+    config: PackageConfig,
+}
+
+impl Project {
+    fn new() -> Self {
+        let ids = UniqueIdGenerator::new();
+        let mut modules = im::HashMap::new();
+        let _ = modules.insert(PRELUDE_MODULE_NAME.into(), build_prelude(&ids));
+        Self {
+            ids,
+            modules,
+            config: PackageConfig::default(),
+        }
+    }
+
+    fn with_module(mut self, name: &str, src: &str) -> Self {
+        let mut parsed = crate::parse::parse_module(src).expect("syntax error");
+        parsed.module.name = name.into();
+        let module = ModuleAnalyzerConstructor::<()> {
+            target: Target::Erlang,
+            ids: &self.ids,
+            origin: Origin::Src,
+            importable_modules: &self.modules,
+            warnings: &TypeWarningEmitter::null(),
+            direct_dependencies: &HashMap::new(),
+            target_support: TargetSupport::NotEnforced,
+            package_config: &self.config,
+        }
+        .infer_module(parsed.module, LineNumbers::new(src), "".into())
+        .expect("should successfully infer");
+        let _ = self.modules.insert(name.into(), module.type_info);
+        self
+    }
+
+    fn infer(&self, expression: &str, context: &ModuleContext) -> InferredExpression {
+        ExpressionAnalyzer {
+            target: Target::Erlang,
+            ids: &self.ids,
+            importable_modules: &self.modules,
+            package_config: &self.config,
+        }
+        .infer(expression, context)
+    }
+
+    fn check_definitions(&self, definitions: &str, context: &ModuleContext) -> CheckedDefinitions {
+        ExpressionAnalyzer {
+            target: Target::Erlang,
+            ids: &self.ids,
+            importable_modules: &self.modules,
+            package_config: &self.config,
+        }
+        .check_definitions(definitions, context)
+    }
+}
+
+fn context(src: &str) -> ModuleContext {
+    ModuleContext {
+        name: "app".into(),
+        path: "src/app.gleam".into(),
+        src: src.into(),
+        origin: Origin::Src,
+    }
+}
+
+#[test]
+fn infer_literal() {
+    let inferred = Project::new().infer("1 + 2", &ModuleContext::empty("app".into()));
+    assert_eq!(inferred.pretty_type(), Some("Int".into()));
+    assert_eq!(inferred.diagnostics, vec![]);
+}
+
+#[test]
+fn infer_generic_function() {
+    let inferred = Project::new().infer("fn(x) { x }", &ModuleContext::empty("app".into()));
+    assert_eq!(inferred.pretty_type(), Some("fn(a) -> a".into()));
+}
+
+#[test]
+fn infer_statements() {
+    let inferred = Project::new().infer(
+        "let x = \"wibble\"\n[x, x]",
+        &ModuleContext::empty("app".into()),
+    );
+    assert_eq!(inferred.pretty_type(), Some("List(String)".into()));
+}
+
+#[test]
+fn infer_with_private_definitions_of_context() {
+    let inferred = Project::new().infer("double(2.0)", &context("fn double(x) { x *. 2.0 }"));
+    assert_eq!(inferred.pretty_type(), Some("Float".into()));
+    // The private function being unused in the module is not reported.
+    assert_eq!(inferred.diagnostics, vec![]);
+}
+
+#[test]
+fn infer_with_imports_of_context() {
+    let project = Project::new().with_module(
+        "wibble",
+        "pub type Wobble { Wobble(Int) }
+pub fn wobble() { Wobble(1) }",
+    );
+    let inferred = project.infer("wibble.wobble()", &context("import wibble\n"));
+    assert_eq!(inferred.pretty_type(), Some("Wobble".into()));
+}
+
+#[test]
+fn infer_type_error() {
+    let expression = "1 + \"wibble\"";
+    let inferred = Project::new().infer(expression, &context("pub fn main() { 1 }"));
+    assert_eq!(inferred.type_, None);
+    let [diagnostic] = inferred.diagnostics.as_slice() else {
+        panic!("expected one diagnostic: {:?}", inferred.diagnostics);
+    };
+    assert_eq!(diagnostic.level, Level::Error);
+    let location = diagnostic.location.as_ref().expect("location");
+    assert_eq!(location.src.as_str(), expression);
+    assert_eq!(location.label.span, SrcSpan::new(4, 12));
+}
+
+#[test]
+fn infer_parse_error() {
+    let inferred = Project::new().infer("1 +", &ModuleContext::empty("app".into()));
+    assert_eq!(inferred.type_, None);
+    assert!(inferred
+        .diagnostics
+        .iter()
+        .any(|diagnostic| diagnostic.level == Level::Error));
+}
+
+#[test]
+fn infer_warning() {
+    let expression = "let x = 1\n2";
+    let inferred = Project::new().infer(expression, &ModuleContext::empty("app".into()));
+    assert_eq!(inferred.pretty_type(), Some("Int".into()));
+    let [diagnostic] = inferred.diagnostics.as_slice() else {
+        panic!("expected one diagnostic: {:?}", inferred.diagnostics);
+    };
+    assert_eq!(diagnostic.level, Level::Warning);
+    let location = diagnostic.location.as_ref().expect("location");
+    assert_eq!(location.label.span, SrcSpan::new(4, 5));
+}
+
+#[test]
+fn infer_gives_module_to_compile() {
+    let inferred = Project::new().infer("1", &context("pub fn one() { 1 }"));
+    let module = inferred.module.expect("module");
+    assert_eq!(module.ast.name.as_str(), "app");
+    assert!(module.src.contains(EXPRESSION_FUNCTION));
+}
+
+#[test]
+fn infer_type_error_gives_no_module() {
+    let inferred = Project::new().infer("1 + 1.0", &ModuleContext::empty("app".into()));
+    assert!(inferred.module.is_none());
+}
+
+#[test]
+fn check_definitions() {
+    let checked = Project::new().check_definitions(
+        "pub fn triple(x) { double(x) + x }",
+        &context("fn double(x) { x * 2 }"),
+    );
+    assert_eq!(checked.diagnostics, vec![]);
+    let module = checked.module.expect("module");
+    assert_eq!(module.ast.definitions.len(), 2);
+}
+
+#[test]
+fn check_definitions_error() {
+    let definitions = "pub fn wibble() { wobble }";
+    let checked = Project::new().check_definitions(definitions, &context("pub fn main() { 1 }"));
+    assert!(checked.module.is_none());
+    let [diagnostic] = checked.diagnostics.as_slice() else {
+        panic!("expected one diagnostic: {:?}", checked.diagnostics);
+    };
+    assert_eq!(diagnostic.level, Level::Error);
+    let location = diagnostic.location.as_ref().expect("location");
+    assert_eq!(location.src.as_str(), definitions);
+    assert_eq!(location.label.span, SrcSpan::new(18, 24));
+}
@@ -3,15 +3,55 @@ use ecow::EcoString;
 use crate::{
     ast::{Import, SrcSpan, UnqualifiedImport},
     build::Origin,
+    config::ImportRule,
     type_::{
-        EntityKind, Environment, Error, ModuleInterface, UnusedModuleAlias, ValueConstructorVariant,
+        EntityKind, Environment, Error, ModuleInterface, UnusedModuleAlias,
+        ValueConstructorVariant, Warning,
     },
 };
 
+/// Names of values that are defined by many modules, so their module is
+/// needed to know what they do.
+const GENERIC_NAMES: &[&str] = &[
+    "all",
+    "any",
+    "append",
+    "concat",
+    "contains",
+    "delete",
+    "each",
+    "filter",
+    "find",
+    "first",
+    "flatten",
+    "fold",
+    "from_list",
+    "get",
+    "insert",
+    "is_empty",
+    "join",
+    "last",
+    "length",
+    "map",
+    "new",
+    "parse",
+    "reverse",
+    "run",
+    "set",
+    "size",
+    "split",
+    "to_list",
+    "to_string",
+    "try",
+    "unwrap",
+    "zip",
+];
+
 #[derive(Debug)]
 pub struct Importer<'context, 'errors> {
     origin: Origin,
     environment: Environment<'context>,
+    import_rules: &'context [ImportRule],
     errors: &'errors mut Vec<Error>,
 }
 
@@ -19,11 +59,13 @@ impl<'context, 'errors> Importer<'context, 'errors> {
     pub fn new(
         origin: Origin,
         environment: Environment<'context>,
+        import_rules: &'context [ImportRule],
         errors: &'errors mut Vec<Error>,
     ) -> Self {
         Self {
             origin,
             environment,
+            import_rules,
             errors,
         }
     }
@@ -32,9 +74,10 @@ impl<'context, 'errors> Importer<'context, 'errors> {
         origin: Origin,
         env: Environment<'context>,
         imports: &'code [Import<()>],
+        import_rules: &'context [ImportRule],
         errors: &'errors mut Vec<Error>,
     ) -> Environment<'context> {
-        let mut importer = Self::new(origin, env, errors);
+        let mut importer = Self::new(origin, env, import_rules, errors);
         for import in imports {
             importer.register_import(import)
         }
@@ -60,6 +103,12 @@ impl<'context, 'errors> Importer<'context, 'errors> {
             return;
         }
 
+        // The import is still registered so that the module's uses of it are
+        // not reported as errors too.
+        if let Err(e) = self.check_import_rules(location, &name) {
+            self.errors.push(e);
+        }
+
         if let Err(e) = self.register_module(import, module_info) {
             self.errors.push(e);
             return;
@@ -72,6 +121,43 @@ impl<'context, 'errors> Importer<'context, 'errors> {
         for value in &import.unqualified_values {
             self.register_unqualified_value(value, module_info);
         }
+
+        self.check_unqualified_imports(import);
+    }
+
+    /// Emits the warnings the package is configured to emit for imports
+    /// that bring many or unclear names into the module's scope.
+    fn check_unqualified_imports(&mut self, import: &Import<()>) {
+        let config = self.environment.warnings_config;
+
+        let count = import.unqualified_values.len() + import.unqualified_types.len();
+        if let Some(max) = config.max_unqualified_imports {
+            if count > max {
+                self.environment
+                    .warnings
+                    .emit(Warning::TooManyUnqualifiedImports {
+                        location: import.location,
+                        module: import.module.clone(),
+                        count,
+                        max,
+                    });
+            }
+        }
+
+        if config.generic_unqualified_imports {
+            for value in &import.unqualified_values {
+                // An alias gives the value a name that is hopefully clearer
+                if value.as_name.is_none() && GENERIC_NAMES.contains(&value.name.as_str()) {
+                    self.environment
+                        .warnings
+                        .emit(Warning::GenericUnqualifiedImport {
+                            location: value.location,
+                            module: import.module.clone(),
+                            name: value.name.clone(),
+                        });
+                }
+            }
+        }
     }
 
     fn register_unqualified_type(&mut self, import: &UnqualifiedImport, module: &ModuleInterface) {
@@ -182,6 +268,34 @@ impl<'context, 'errors> Importer<'context, 'errors> {
         Ok(())
     }
 
+    fn check_import_rules(
+        &mut self,
+        location: SrcSpan,
+        imported_module: &EcoString,
+    ) -> Result<(), Error> {
+        let module = &self.environment.current_module;
+        let Some(rule) = self
+            .import_rules
+            .iter()
+            .find(|rule| rule.forbids(module, imported_module))
+        else {
+            return Ok(());
+        };
+        let imported_pattern = rule
+            .must_not_import
+            .iter()
+            .find(|glob| glob.compile_matcher().is_match(imported_module.as_str()))
+            .map(|glob| glob.glob().into())
+            .unwrap_or_default();
+        Err(Error::ForbiddenImport {
+            location,
+            module: module.clone(),
+            imported_module: imported_module.clone(),
+            modules_pattern: rule.modules.glob().into(),
+            imported_pattern,
+        })
+    }
+
     fn register_module(
         &mut self,
         import: &Import<()>,
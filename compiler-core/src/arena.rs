@@ -0,0 +1,69 @@
+//! Per-module arenas for the short-lived values the parser allocates while
+//! reading a module, such as the text of each token before it is turned into
+//! a name, and the operator and operand stacks used for binary operators.
+//!
+//! With the `arena` feature these are bump allocated from one arena per
+//! module, which is freed all at once when the module has been parsed. This
+//! replaces many small allocations and frees with a few large ones and keeps
+//! the values close together in memory. Without the feature the arena is
+//! empty and values are allocated as usual.
+//!
+//! The syntax tree itself is not allocated in the arena as it outlives the
+//! parser: it is analysed, cached, and held by the language server.
+
+// Without the `arena` feature there is nothing in the arena, but it is not
+// `Copy` as it owns its values with the feature.
+#[allow(missing_copy_implementations)]
+#[derive(Debug, Default)]
+pub struct Arena {
+    #[cfg(feature = "arena")]
+    bump: bumpalo::Bump,
+}
+
+#[cfg(feature = "arena")]
+pub type ArenaString<'a> = bumpalo::collections::String<'a>;
+#[cfg(feature = "arena")]
+pub type ArenaVec<'a, T> = bumpalo::collections::Vec<'a, T>;
+
+#[cfg(not(feature = "arena"))]
+pub type ArenaString<'a> = String;
+#[cfg(not(feature = "arena"))]
+pub type ArenaVec<'a, T> = Vec<T>;
+
+impl Arena {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[cfg(feature = "arena")]
+    pub fn string(&self) -> ArenaString<'_> {
+        bumpalo::collections::String::new_in(&self.bump)
+    }
+
+    #[cfg(feature = "arena")]
+    pub fn vec<T>(&self) -> ArenaVec<'_, T> {
+        bumpalo::collections::Vec::new_in(&self.bump)
+    }
+
+    /// The number of bytes the arena has allocated for its values.
+    #[cfg(feature = "arena")]
+    pub fn allocated_bytes(&self) -> usize {
+        self.bump.allocated_bytes()
+    }
+
+    #[cfg(not(feature = "arena"))]
+    pub fn string(&self) -> ArenaString<'_> {
+        String::new()
+    }
+
+    #[cfg(not(feature = "arena"))]
+    pub fn vec<T>(&self) -> ArenaVec<'_, T> {
+        Vec::new()
+    }
+
+    /// The number of bytes the arena has allocated for its values.
+    #[cfg(not(feature = "arena"))]
+    pub fn allocated_bytes(&self) -> usize {
+        0
+    }
+}
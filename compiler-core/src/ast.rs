@@ -66,10 +66,21 @@ impl TypedModule {
 /// pub fn main(a) { ...}
 /// ```
 ///
+/// Likewise the `@feature("name")` attribute marks a definition as only being
+/// compiled when the named feature of the package is enabled.
+///
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct TargetedDefinition {
     pub definition: UntypedDefinition,
     pub target: Option<Target>,
+    pub feature: Option<DefinitionFeature>,
+}
+
+/// The feature a definition belongs to, given by its `@feature` attribute.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DefinitionFeature {
+    pub name: EcoString,
+    pub location: SrcSpan,
 }
 
 impl TargetedDefinition {
@@ -469,6 +480,9 @@ pub struct Function<T, Expr> {
     pub documentation: Option<EcoString>,
     pub external_erlang: Option<(EcoString, EcoString)>,
     pub external_javascript: Option<(EcoString, EcoString)>,
+    /// The OTP behaviour this function is a callback of, given with the
+    /// `@behaviour` attribute, such as `gen_server`.
+    pub behaviour: Option<EcoString>,
     pub implementations: Implementations,
 }
 
@@ -1307,7 +1321,7 @@ impl TypedClauseGuard {
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Default, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Default, Clone, Copy, serde::Serialize, serde::Deserialize)]
 pub struct SrcSpan {
     pub start: u32,
     pub end: u32,
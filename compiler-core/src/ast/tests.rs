@@ -2,7 +2,7 @@ use std::sync::Arc;
 
 use crate::analyse::TargetSupport;
 use crate::build::Target;
-use crate::config::PackageConfig;
+use crate::config::{PackageConfig, WarningsConfig};
 use crate::line_numbers::LineNumbers;
 use crate::type_::expression::FunctionDefinition;
 use crate::type_::{Deprecation, PRELUDE_MODULE_NAME};
@@ -78,6 +78,7 @@ fn compile_expression(src: &str) -> TypedStatement {
         &modules,
         &emitter,
         TargetSupport::Enforced,
+        WarningsConfig::default(),
     );
 
     // Insert a cat record to use in the tests
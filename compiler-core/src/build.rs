@@ -1,6 +1,12 @@
 #![allow(warnings)]
 
+mod artefacts_manifest;
+pub mod build_metadata;
 mod elixir_libraries;
+pub mod env_module;
+pub(crate) mod erlang_ffi;
+pub mod features;
+pub(crate) mod javascript_ffi;
 mod module_loader;
 mod native_file_copier;
 pub mod package_compiler;
@@ -35,7 +41,13 @@ use itertools::Itertools;
 use serde::{Deserialize, Serialize};
 use std::fmt::Debug;
 use std::time::SystemTime;
-use std::{collections::HashMap, ffi::OsString, fs::DirEntry, iter::Peekable, process};
+use std::{
+    collections::{HashMap, HashSet},
+    ffi::OsString,
+    fs::DirEntry,
+    iter::Peekable,
+    process,
+};
 use strum::{Display, EnumIter, EnumString, EnumVariantNames, VariantNames};
 use vec1::Vec1;
 
@@ -84,7 +96,7 @@ impl Target {
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
 pub enum Codegen {
     All,
     DepsOnly,
@@ -122,11 +134,46 @@ impl Default for Runtime {
     }
 }
 
+/// The platform JavaScript code is written to run on, which decides the
+/// platform shims written alongside the prelude.
+#[derive(Debug, Serialize, Deserialize, Display, EnumString, Clone, Copy, PartialEq, Eq)]
+#[strum(serialize_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum JavaScriptPlatform {
+    /// Node.js and the runtimes compatible with it, such as Deno and Bun.
+    Node,
+    /// Web browsers, which have no file system or environment variables.
+    Browser,
+    /// Any platform, using only what is available everywhere or found when
+    /// the code is run.
+    Neutral,
+}
+
+impl JavaScriptPlatform {
+    /// Whether a package written for this platform can be used by one written
+    /// for the given platform.
+    pub fn is_usable_on(self, platform: Self) -> bool {
+        self == Self::Neutral || self == platform
+    }
+
+    /// The shims module for this platform, written to `platform.mjs`.
+    pub fn shims(self) -> &'static str {
+        match self {
+            Self::Node => crate::javascript::PLATFORM_NODE,
+            Self::Browser => crate::javascript::PLATFORM_BROWSER,
+            Self::Neutral => crate::javascript::PLATFORM_NEUTRAL,
+        }
+    }
+}
+
 #[derive(Debug)]
 pub enum TargetCodegenConfiguration {
     JavaScript {
         emit_typescript_definitions: bool,
         prelude_location: Utf8PathBuf,
+        /// The platform shims module, re-exported from each package's
+        /// `gleam.mjs` as `platform` if one is written alongside the prelude.
+        platform_location: Option<Utf8PathBuf>,
     },
     Erlang {
         app_file: Option<ErlangAppCodegenConfiguration>,
@@ -149,6 +196,10 @@ pub struct ErlangAppCodegenConfiguration {
     /// name, as rebar3 (and Mix?) support this. The .app file must use the OTP
     /// name, not the package name.
     pub package_name_overrides: HashMap<EcoString, EcoString>,
+    /// The OTP application names of all the packages in the project, which
+    /// may be named in `extra_applications` even if they are not direct
+    /// dependencies.
+    pub project_applications: HashSet<EcoString>,
 }
 
 #[derive(
@@ -232,6 +283,16 @@ impl Module {
         self.origin == Origin::Test
     }
 
+    /// The path of the module's source file relative to the root of its
+    /// package.
+    pub fn package_relative_path(&self) -> Utf8PathBuf {
+        let directory = match self.origin {
+            Origin::Src => "src",
+            Origin::Test => "test",
+        };
+        Utf8PathBuf::from(format!("{directory}/{}.gleam", self.name))
+    }
+
     pub fn find_node(&self, byte_index: u32) -> Option<Located<'_>> {
         self.ast.find_node(byte_index)
     }
@@ -372,13 +433,14 @@ pub fn type_constructor_from_modules(
     let type_ = type_::collapse_links(type_);
     match type_.as_ref() {
         type_::Type::Named { name, module, .. } => importable_modules
-            .get(module)
-            .and_then(|i| i.types.get(name)),
+            .get(module.as_str())
+            .and_then(|i| i.types.get(name.as_str())),
         _ => None,
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
 pub enum Origin {
     Src,
     Test,
@@ -422,6 +484,19 @@ impl SourceFingerprint {
     }
 }
 
+/// A hash of the parts of a module's interface that the modules importing it
+/// are analysed against. If it is unchanged the previous analysis of those
+/// modules is still valid.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+pub(crate) struct InterfaceFingerprint(u64);
+
+impl InterfaceFingerprint {
+    pub(crate) fn new(interface: &type_::ModuleInterface, locations: bool) -> crate::Result<Self> {
+        let bytes = crate::metadata::ModuleEncoder::new(interface).encode_interface(locations)?;
+        Ok(InterfaceFingerprint(xxhash_rust::xxh3::xxh3_64(&bytes)))
+    }
+}
+
 /// Like a `Result`, but the operation can partially succeed or fail.
 ///
 #[derive(Debug)]
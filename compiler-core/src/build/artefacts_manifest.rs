@@ -0,0 +1,129 @@
+//! The manifest of the files generated for each module of the root package,
+//! written to `build/.artifacts.json` after each build. Bundlers,
+//! hot-reloaders, and other tools watching the build directory can use it to
+//! find what to reload when a module changes without re-implementing the
+//! build graph.
+
+#[cfg(test)]
+mod tests;
+
+use std::collections::BTreeMap;
+
+use camino::{Utf8Path, Utf8PathBuf};
+use ecow::EcoString;
+use itertools::Itertools;
+use serde::Serialize;
+
+use crate::{
+    build::{InterfaceFingerprint, Mode, Origin, Target},
+    io::FileSystemReader,
+    paths,
+    type_::ModuleInterface,
+    Result,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub(crate) struct ArtefactsManifest {
+    package: EcoString,
+    target: Target,
+    mode: String,
+    modules: BTreeMap<EcoString, ModuleArtefacts>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+struct ModuleArtefacts {
+    source: Utf8PathBuf,
+    origin: Origin,
+    /// Changes only when the module's public interface changes, so the
+    /// modules importing it need to be reloaded only when it does.
+    interface_hash: String,
+    imports: Vec<EcoString>,
+    generated: Vec<Utf8PathBuf>,
+}
+
+impl ArtefactsManifest {
+    pub fn new(package: EcoString, target: Target, mode: Mode) -> Self {
+        Self {
+            package,
+            target,
+            mode: mode.to_string(),
+            modules: BTreeMap::new(),
+        }
+    }
+
+    /// Adds a module of the package, compiled to the `out` directory. The
+    /// paths in the manifest are relative to the project `root`.
+    ///
+    /// The origin is given as it is not kept in the cached interfaces of
+    /// modules.
+    ///
+    pub fn insert(
+        &mut self,
+        io: &impl FileSystemReader,
+        root: &Utf8Path,
+        out: &Utf8Path,
+        interface: &ModuleInterface,
+        origin: Origin,
+        imports: &[EcoString],
+    ) -> Result<()> {
+        let relative = |path: &Utf8Path| {
+            path.strip_prefix(root)
+                .map(Utf8Path::to_path_buf)
+                .unwrap_or_else(|_| path.to_path_buf())
+        };
+        let InterfaceFingerprint(hash) = InterfaceFingerprint::new(interface, false)?;
+        let generated = self
+            .generated_files(out, interface)
+            .into_iter()
+            .filter(|path| io.is_file(path))
+            .map(|path| relative(&path))
+            .sorted()
+            .collect();
+        let module = ModuleArtefacts {
+            source: relative(&interface.src_path),
+            origin,
+            interface_hash: format!("{hash:016x}"),
+            imports: imports.iter().sorted().dedup().cloned().collect(),
+            generated,
+        };
+        _ = self.modules.insert(interface.name.clone(), module);
+        Ok(())
+    }
+
+    /// The files that may have been generated for the module. Record headers
+    /// are only written for some custom types, so the caller checks which
+    /// exist.
+    fn generated_files(&self, out: &Utf8Path, interface: &ModuleInterface) -> Vec<Utf8PathBuf> {
+        match self.target {
+            Target::Erlang => {
+                let erl_name = interface.name.replace("/", "@");
+                let headers = interface
+                    .types_value_constructors
+                    .values()
+                    .flat_map(|constructors| &constructors.variants)
+                    .map(|variant| {
+                        out.join("include")
+                            .join(format!("{erl_name}_{}.hrl", variant.name))
+                    });
+                [
+                    out.join(paths::ARTEFACT_DIRECTORY_NAME)
+                        .join(format!("{erl_name}.erl")),
+                    out.join("ebin").join(format!("{erl_name}.beam")),
+                ]
+                .into_iter()
+                .chain(headers)
+                .collect()
+            }
+            Target::JavaScript => vec![
+                out.join(format!("{}.mjs", interface.name)),
+                out.join(format!("{}.d.mts", interface.name)),
+            ],
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        let mut json = serde_json::to_string_pretty(self).expect("artefacts manifest to json");
+        json.push('\n');
+        json
+    }
+}
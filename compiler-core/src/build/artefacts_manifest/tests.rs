@@ -0,0 +1,102 @@
+use super::ArtefactsManifest;
+use crate::{
+    build::{Mode, Origin, Target},
+    io::{memory::InMemoryFileSystem, FileSystemWriter},
+    line_numbers::LineNumbers,
+    type_::{ModuleInterface, TypeValueConstructor, TypeVariantConstructors},
+};
+
+use camino::{Utf8Path, Utf8PathBuf};
+use ecow::EcoString;
+
+fn interface(name: &str, directory: &str, types: &[&str]) -> ModuleInterface {
+    ModuleInterface {
+        name: name.into(),
+        origin: Origin::Src,
+        package: "app".into(),
+        types: Default::default(),
+        types_value_constructors: types
+            .iter()
+            .map(|name| {
+                let variant = TypeValueConstructor {
+                    name: (*name).into(),
+                    parameters: vec![],
+                };
+                let constructors = TypeVariantConstructors {
+                    type_parameters_ids: vec![],
+                    variants: vec![variant],
+                };
+                ((*name).into(), constructors)
+            })
+            .collect(),
+        values: Default::default(),
+        accessors: Default::default(),
+        unused_imports: Vec::new(),
+        contains_todo: false,
+        line_numbers: LineNumbers::new(""),
+        is_internal: false,
+        src_path: Utf8PathBuf::from(format!("/app/{directory}/{name}.gleam")),
+    }
+}
+
+fn manifest(fs: &InMemoryFileSystem, target: Target, out: &str) -> String {
+    let mut manifest = ArtefactsManifest::new("app".into(), target, Mode::Dev);
+    let modules: [(_, _, &[EcoString]); 3] = [
+        (
+            interface("app", "src", &[]),
+            Origin::Src,
+            &["gleam/io".into(), "app/user".into()],
+        ),
+        (
+            interface("app/user", "src", &["User", "Guest"]),
+            Origin::Src,
+            &[],
+        ),
+        (
+            interface("app_test", "test", &[]),
+            Origin::Test,
+            &["app".into()],
+        ),
+    ];
+    for (interface, origin, imports) in modules {
+        manifest
+            .insert(
+                fs,
+                Utf8Path::new("/app"),
+                Utf8Path::new(out),
+                &interface,
+                origin,
+                imports,
+            )
+            .unwrap();
+    }
+    manifest.to_json()
+}
+
+#[test]
+fn erlang() {
+    let fs = InMemoryFileSystem::new();
+    let out = "/app/build/dev/erlang/app";
+    for path in [
+        "_gleam_artefacts/app.erl",
+        "_gleam_artefacts/app@user.erl",
+        "_gleam_artefacts/app_test.erl",
+        "ebin/app.beam",
+        "ebin/app@user.beam",
+        "ebin/app_test.beam",
+        "include/app@user_User.hrl",
+    ] {
+        fs.write(&Utf8Path::new(out).join(path), "").unwrap();
+    }
+    insta::assert_snapshot!(manifest(&fs, Target::Erlang, out));
+}
+
+#[test]
+fn javascript() {
+    let fs = InMemoryFileSystem::new();
+    let out = "/app/build/dev/javascript/app";
+    for path in ["app.mjs", "app.d.mts", "app/user.mjs", "app/user.d.mts"] {
+        fs.write(&Utf8Path::new(out).join(path), "").unwrap();
+    }
+    insta::assert_snapshot!(manifest(&fs, Target::JavaScript, out));
+}
@@ -0,0 +1,326 @@
+//! A pinned, machine readable description of how a project is built, for use
+//! by external build systems such as Nix or Bazel that want to fetch the
+//! dependencies and run the compilation steps themselves.
+
+#[cfg(test)]
+mod tests;
+
+use std::collections::HashMap;
+
+use camino::{Utf8Path, Utf8PathBuf};
+use ecow::EcoString;
+use itertools::Itertools;
+use serde::Serialize;
+
+use crate::{
+    ast::Definition,
+    build::{
+        project_compiler::{
+            mix_arguments, mix_environment, rebar3_arguments, rebar3_environment,
+            usable_build_tools, BuildTool,
+        },
+        JavaScriptPlatform, Mode, Target,
+    },
+    config::PackageConfig,
+    dep_tree,
+    io::{ordered_map, FileSystemReader},
+    manifest::{Manifest, ManifestPackage, ManifestPackageSource},
+    paths::{self, ProjectPaths},
+    version::COMPILER_VERSION,
+    Error, Result,
+};
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub struct BuildMetadata {
+    name: EcoString,
+    version: EcoString,
+    target: Target,
+    /// The JavaScript platform the project is written for, if given.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    javascript_platform: Option<JavaScriptPlatform>,
+    /// The environment whose overlay of `gleam.toml` was merged over it, if
+    /// any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    config_env: Option<EcoString>,
+    /// The version of the Gleam compiler that produced this description.
+    compiler_version: EcoString,
+    /// Every package in the manifest, pinned to an exact version and source.
+    packages: Vec<PackageMetadata>,
+    /// The order in which the packages must be compiled, ending with the
+    /// project itself.
+    build_order: Vec<EcoString>,
+    /// A map from module name to the module's file and the modules it imports.
+    #[serde(serialize_with = "ordered_map")]
+    modules: HashMap<EcoString, ModuleMetadata>,
+    /// The commands that compile each package, in build order. Paths are
+    /// relative to the root of the project.
+    commands: Vec<CommandMetadata>,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub struct PackageMetadata {
+    name: EcoString,
+    version: EcoString,
+    otp_app: Option<EcoString>,
+    build_tools: Vec<EcoString>,
+    requirements: Vec<EcoString>,
+    source: SourceMetadata,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+pub enum SourceMetadata {
+    Hex {
+        /// The URL of the package tarball in the Hex repository.
+        url: String,
+        /// The SHA-256 checksum of the package tarball, in hexadecimal.
+        sha256: String,
+    },
+    Git {
+        repo: EcoString,
+        commit: EcoString,
+    },
+    Local {
+        path: Utf8PathBuf,
+    },
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub struct ModuleMetadata {
+    path: Utf8PathBuf,
+    /// Either `src` or `test`.
+    origin: &'static str,
+    imports: Vec<EcoString>,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub struct CommandMetadata {
+    package: EcoString,
+    /// The directory the command is run in.
+    directory: Utf8PathBuf,
+    #[serde(serialize_with = "ordered_map")]
+    env: HashMap<&'static str, String>,
+    arguments: Vec<String>,
+}
+
+/// Describes how the project at `root` is built for the given target, using
+/// the versions locked in its manifest.
+///
+pub fn generate<IO: FileSystemReader>(
+    io: &IO,
+    root: &Utf8Path,
+    config: &PackageConfig,
+    manifest: &Manifest,
+    target: Target,
+) -> Result<BuildMetadata> {
+    let packages = manifest
+        .packages
+        .iter()
+        .sorted_by(|a, b| a.name.cmp(&b.name))
+        .collect_vec();
+
+    let mut build_order = dep_tree::toposort_deps(
+        packages
+            .iter()
+            .map(|package| (package.name.clone(), package.requirements.clone()))
+            .collect(),
+    )
+    .map_err(|error| match error {
+        dep_tree::Error::Cycle(packages) => Error::PackageCycle { packages },
+    })?;
+    build_order.push(config.name.clone());
+
+    let mut commands = vec![];
+    for name in &build_order {
+        match packages.iter().find(|package| &package.name == name) {
+            Some(package) => commands.extend(package_command(package, target)?),
+            None => commands.push(gleam_command(name, name, Utf8Path::new("."), target)),
+        }
+    }
+
+    let mut modules = HashMap::new();
+    for (directory, origin) in [("src", "src"), ("test", "test")] {
+        let directory = root.join(directory);
+        for path in io.gleam_source_files(&directory) {
+            let (name, module) = module_metadata(io, root, &directory, &path, origin)?;
+            let _ = modules.insert(name, module);
+        }
+    }
+
+    Ok(BuildMetadata {
+        name: config.name.clone(),
+        version: config.version.to_string().into(),
+        target,
+        javascript_platform: config.javascript.platform,
+        config_env: config.config_env.clone(),
+        compiler_version: COMPILER_VERSION.into(),
+        packages: packages.into_iter().map(package_metadata).collect(),
+        build_order,
+        modules,
+        commands,
+    })
+}
+
+fn package_metadata(package: &ManifestPackage) -> PackageMetadata {
+    let source = match &package.source {
+        ManifestPackageSource::Hex {
+            outer_checksum,
+            organization,
+        } => SourceMetadata::Hex {
+            url: format!(
+                "https://repo.hex.pm/{}tarballs/{}-{}.tar",
+                organization
+                    .as_ref()
+                    .map(|organization| format!("repos/{organization}/"))
+                    .unwrap_or_default(),
+                package.name,
+                package.version
+            ),
+            sha256: outer_checksum.to_string().to_lowercase(),
+        },
+        ManifestPackageSource::Git { repo, commit } => SourceMetadata::Git {
+            repo: repo.clone(),
+            commit: commit.clone(),
+        },
+        ManifestPackageSource::Local { path } => SourceMetadata::Local { path: path.clone() },
+    };
+    PackageMetadata {
+        name: package.name.clone(),
+        version: package.version.to_string().into(),
+        otp_app: package.otp_app.clone(),
+        build_tools: package.build_tools.clone(),
+        requirements: package.requirements.clone(),
+        source,
+    }
+}
+
+fn module_metadata<IO: FileSystemReader>(
+    io: &IO,
+    root: &Utf8Path,
+    directory: &Utf8Path,
+    path: &Utf8Path,
+    origin: &'static str,
+) -> Result<(EcoString, ModuleMetadata)> {
+    let src: EcoString = io.read(path)?.into();
+    let parsed = crate::parse::parse_module(&src).map_err(|error| Error::Parse {
+        path: path.to_path_buf(),
+        src: src.clone(),
+        error,
+    })?;
+    let imports = parsed
+        .module
+        .definitions
+        .iter()
+        .filter_map(|definition| match &definition.definition {
+            Definition::Import(import) => Some(import.module.clone()),
+            _ => None,
+        })
+        .sorted()
+        .dedup()
+        .collect();
+
+    let name = path
+        .strip_prefix(directory)
+        .unwrap_or(path)
+        .with_extension("")
+        .components()
+        .map(|component| component.as_str())
+        .join("/");
+    let module = ModuleMetadata {
+        path: path.strip_prefix(root).unwrap_or(path).to_path_buf(),
+        origin,
+        imports,
+    };
+    Ok((name.into(), module))
+}
+
+/// The command used to compile a dependency, if it needs compiling for the
+/// target. These are the same commands the build tool runs.
+fn package_command(package: &ManifestPackage, target: Target) -> Result<Option<CommandMetadata>> {
+    let paths = ProjectPaths::new(Utf8PathBuf::new());
+    let application = package.application_name();
+    let build_tool = *usable_build_tools(package)?
+        .first()
+        .expect("usable build tools");
+
+    let command = match build_tool {
+        BuildTool::Gleam => {
+            let directory = match &package.source {
+                ManifestPackageSource::Local { path } => path.clone(),
+                _ => paths.build_packages_package_source(package),
+            };
+            gleam_command(&package.name, application, &directory, target)
+        }
+
+        BuildTool::Rebar3 | BuildTool::Mix if target != Target::Erlang => return Ok(None),
+
+        BuildTool::Rebar3 => CommandMetadata {
+            package: package.name.clone(),
+            directory: paths.build_directory_for_package(Mode::Prod, target, application),
+            env: rebar3_environment().into_iter().collect(),
+            arguments: std::iter::once("rebar3".into())
+                .chain(rebar3_arguments())
+                .collect(),
+        },
+
+        BuildTool::Mix => {
+            let directory = paths.build_packages_package_source(package);
+            let up = paths::unnest(&directory);
+            let mix_path = |path: &Utf8Path| up.join(path).to_string();
+            let mix_build_directory = directory.join("_build").join("prod");
+            let ebins = paths.build_packages_ebins_glob(Mode::Prod, target);
+            CommandMetadata {
+                package: package.name.clone(),
+                env: mix_environment(
+                    "prod",
+                    mix_path(&mix_build_directory),
+                    mix_path(&paths.build_mix_home()),
+                )
+                .into_iter()
+                .collect(),
+                arguments: std::iter::once("elixir".into())
+                    .chain(mix_arguments(mix_path(&ebins)))
+                    .collect(),
+                directory,
+            }
+        }
+    };
+    Ok(Some(command))
+}
+
+fn gleam_command(
+    package: &str,
+    application: &str,
+    directory: &Utf8Path,
+    target: Target,
+) -> CommandMetadata {
+    let paths = ProjectPaths::new(Utf8PathBuf::new());
+    let lib = paths.build_directory_for_target(Mode::Prod, target);
+    let out = paths.build_directory_for_package(Mode::Prod, target, application);
+    let mut arguments = vec![
+        "gleam".into(),
+        "compile-package".into(),
+        "--target".into(),
+        target.to_string(),
+        "--package".into(),
+        directory.to_string(),
+        "--out".into(),
+        out.to_string(),
+        "--lib".into(),
+        lib.to_string(),
+    ];
+    if target == Target::JavaScript {
+        arguments.push("--javascript-prelude".into());
+        arguments.push("../prelude.mjs".into());
+    }
+    CommandMetadata {
+        package: package.into(),
+        directory: Utf8PathBuf::from("."),
+        env: HashMap::new(),
+        arguments,
+    }
+}
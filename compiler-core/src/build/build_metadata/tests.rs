@@ -0,0 +1,137 @@
+use super::generate;
+use crate::{
+    build::{JavaScriptPlatform, Target},
+    config::PackageConfig,
+    io::{memory::InMemoryFileSystem, FileSystemWriter},
+    manifest::{Base16Checksum, Manifest, ManifestPackage, ManifestPackageSource},
+    requirement::Requirement,
+};
+
+use camino::{Utf8Path, Utf8PathBuf};
+use hexpm::version::Version;
+
+fn package(name: &str, build_tool: &str, requirements: &[&str]) -> ManifestPackage {
+    ManifestPackage {
+        name: name.into(),
+        version: Version::new(1, 2, 0),
+        build_tools: vec![build_tool.into()],
+        requirements: requirements.iter().map(|r| (*r).into()).collect(),
+        source: ManifestPackageSource::Hex {
+            outer_checksum: Base16Checksum(vec![0xAB, 0xCD, 0xEF]),
+            organization: None,
+        },
+        ..ManifestPackage::default()
+    }
+}
+
+fn project() -> (InMemoryFileSystem, PackageConfig, Manifest) {
+    let fs = InMemoryFileSystem::new();
+    let files = [
+        (
+            "/src/app.gleam",
+            "import gleam/io\nimport app/internal\nimport gleam/io.{println}",
+        ),
+        ("/src/app/internal.gleam", "pub const x = 1"),
+        ("/test/app_test.gleam", "import app\nimport gleeunit"),
+    ];
+    for (path, src) in files {
+        fs.write(Utf8Path::new(path), src)
+            .expect("write gleam module");
+    }
+
+    let config = PackageConfig {
+        name: "app".into(),
+        version: Version::new(0, 1, 0),
+        dependencies: [("gleam_stdlib".into(), Requirement::hex("~> 0.34"))].into(),
+        ..PackageConfig::default()
+    };
+    let manifest = Manifest {
+        requirements: config.dependencies.clone(),
+        packages: vec![
+            package("gleam_stdlib", "gleam", &[]),
+            package("gleeunit", "gleam", &["gleam_stdlib", "thoas"]),
+            package("thoas", "rebar3", &[]),
+            ManifestPackage {
+                otp_app: Some("jason_app".into()),
+                ..package("jason", "mix", &[])
+            },
+            ManifestPackage {
+                source: ManifestPackageSource::Git {
+                    repo: "https://example.com/wibble.git".into(),
+                    commit: "bd9fe02f72250e6a136967917bcb1bdccaffa3c8".into(),
+                },
+                ..package("wibble", "gleam", &["gleam_stdlib"])
+            },
+            ManifestPackage {
+                source: ManifestPackageSource::Local {
+                    path: Utf8PathBuf::from("/home/louis/wobble"),
+                },
+                ..package("wobble", "gleam", &[])
+            },
+        ],
+    };
+    (fs, config, manifest)
+}
+
+#[test]
+fn erlang_build_metadata() {
+    let (fs, config, manifest) = project();
+    let metadata = generate(&fs, Utf8Path::new("/"), &config, &manifest, Target::Erlang)
+        .expect("build metadata");
+    insta::assert_snapshot!(serde_json::to_string_pretty(&metadata).expect("json"));
+}
+
+#[test]
+fn javascript_build_metadata_skips_beam_only_packages() {
+    let (fs, config, manifest) = project();
+    let metadata = generate(
+        &fs,
+        Utf8Path::new("/"),
+        &config,
+        &manifest,
+        Target::JavaScript,
+    )
+    .expect("build metadata");
+    let packages = metadata
+        .commands
+        .iter()
+        .map(|command| command.package.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        packages,
+        vec!["gleam_stdlib", "gleeunit", "wibble", "wobble", "app"]
+    );
+}
+
+#[test]
+fn javascript_build_metadata_records_platform() {
+    let (fs, mut config, manifest) = project();
+    config.javascript.platform = Some(JavaScriptPlatform::Browser);
+    let metadata = generate(
+        &fs,
+        Utf8Path::new("/"),
+        &config,
+        &manifest,
+        Target::JavaScript,
+    )
+    .expect("build metadata");
+    let json = serde_json::to_value(&metadata).expect("json");
+    assert_eq!(
+        json.get("javascript-platform")
+            .and_then(|value| value.as_str()),
+        Some("browser")
+    );
+}
+
+#[test]
+fn build_metadata_records_config_env() {
+    let (fs, mut config, manifest) = project();
+    config.config_env = Some("prod".into());
+    let metadata = generate(&fs, Utf8Path::new("/"), &config, &manifest, Target::Erlang)
+        .expect("build metadata");
+    let json = serde_json::to_value(&metadata).expect("json");
+    assert_eq!(
+        json.get("config-env").and_then(|value| value.as_str()),
+        Some("prod")
+    );
+}
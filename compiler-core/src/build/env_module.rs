@@ -0,0 +1,108 @@
+//! The `[env]` section of gleam.toml lists environment variables whose values
+//! are embedded in the package when it is built, such as the commit a release
+//! was built from:
+//!
+//! ```toml
+//! [env]
+//! commit = "GIT_COMMIT"
+//! build = { variable = "BUILD_NUMBER", default = "dev" }
+//! ```
+//!
+//! Each becomes a constant of a generated `<package>/env` module. A constant
+//! with a default is a `String`, and one without is a `Result(String, Nil)`
+//! that is an error if the variable is not set. Only the variables listed are
+//! read.
+//!
+//! The module is written to the package's build directory and only rewritten
+//! when the values change, so its fingerprint in the build cache accounts for
+//! the values and the modules that use it are recompiled when they change.
+
+#[cfg(test)]
+mod tests;
+
+use std::collections::HashMap;
+
+use ecow::EcoString;
+
+use crate::{config::PackageConfig, parse::lexer::str_to_keyword, Error, Result};
+
+/// The name of the generated module for a package.
+pub fn module_name(package: &str) -> EcoString {
+    format!("{package}/env").into()
+}
+
+/// The values of the environment variables a package embeds, from all the
+/// variables of the environment.
+pub fn variables(
+    config: &PackageConfig,
+    environment: &HashMap<String, String>,
+) -> HashMap<String, String> {
+    config
+        .env
+        .values()
+        .filter_map(|variable| {
+            let name = variable.variable().as_str();
+            let value = environment.get(name)?;
+            Some((name.into(), value.clone()))
+        })
+        .collect()
+}
+
+/// Renders the source of the package's module of environment variables, if
+/// it has any.
+pub fn render(
+    config: &PackageConfig,
+    environment: &HashMap<String, String>,
+) -> Result<Option<String>> {
+    if config.env.is_empty() {
+        return Ok(None);
+    }
+
+    let mut source = String::from(
+        "//// The values of environment variables when the package was built, as
+//// listed in the `[env]` section of gleam.toml.
+",
+    );
+    for (name, variable) in &config.env {
+        if !is_constant_name(name) {
+            return Err(Error::InvalidEnvConstantName { name: name.clone() });
+        }
+        let value = environment.get(variable.variable().as_str());
+        let constant = match (value, variable.default()) {
+            (Some(value), None) => format!(
+                "/// The `{variable}` environment variable, or an error if it was not set.
+pub const {name}: Result(String, Nil) = Ok({value})",
+                variable = variable.variable(),
+                value = string(value),
+            ),
+            (None, None) => format!(
+                "/// The `{variable}` environment variable, or an error if it was not set.
+pub const {name}: Result(String, Nil) = Error(Nil)",
+                variable = variable.variable(),
+            ),
+            (value, Some(default)) => format!(
+                "/// The `{variable}` environment variable, or {default} if it was not set.
+pub const {name}: String = {value}",
+                variable = variable.variable(),
+                default = string(default),
+                value = string(value.map_or(default.as_str(), String::as_str)),
+            ),
+        };
+        source.push('\n');
+        source.push_str(&constant);
+        source.push('\n');
+    }
+    Ok(Some(source))
+}
+
+fn is_constant_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_ascii_lowercase())
+        && chars.all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+        && str_to_keyword(name).is_none()
+}
+
+/// A Gleam string literal of the given value.
+fn string(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
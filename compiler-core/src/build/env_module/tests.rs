@@ -0,0 +1,77 @@
+use std::collections::HashMap;
+
+use super::*;
+
+fn config(toml: &str) -> PackageConfig {
+    toml::from_str(&format!("name = \"wibble\"\n{toml}")).expect("config")
+}
+
+fn environment(variables: &[(&str, &str)]) -> HashMap<String, String> {
+    variables
+        .iter()
+        .map(|(name, value)| ((*name).into(), (*value).into()))
+        .collect()
+}
+
+#[test]
+fn module_of_variables() {
+    let config = config(
+        r#"
+[env]
+commit = "GIT_COMMIT"
+branch = "GIT_BRANCH"
+build = { variable = "BUILD_NUMBER", default = "dev" }
+release = { variable = "RELEASE", default = "none" }
+"#,
+    );
+    let environment = environment(&[("GIT_COMMIT", "abc123"), ("RELEASE", "1.0.0")]);
+    insta::assert_snapshot!(render(&config, &environment)
+        .expect("render")
+        .expect("module"));
+}
+
+#[test]
+fn values_are_escaped() {
+    let config = config("[env]\nmessage = \"MESSAGE\"");
+    let environment = environment(&[("MESSAGE", r#"Say "hello" \o/"#)]);
+    let source = render(&config, &environment)
+        .expect("render")
+        .expect("module");
+    assert!(source.contains(r#"= Ok("Say \"hello\" \\o/")"#));
+}
+
+#[test]
+fn no_module_without_variables() {
+    assert_eq!(render(&config(""), &HashMap::new()), Ok(None));
+}
+
+#[test]
+fn invalid_constant_name() {
+    for name in ["Commit", "1st", "fn", "git-commit"] {
+        let config = config(&format!("[env]\n\"{name}\" = \"GIT_COMMIT\""));
+        assert_eq!(
+            render(&config, &HashMap::new()),
+            Err(Error::InvalidEnvConstantName { name: name.into() })
+        );
+    }
+}
+
+#[test]
+fn only_listed_variables_are_read() {
+    let config = config(
+        r#"
+[env]
+commit = "GIT_COMMIT"
+build = { variable = "BUILD_NUMBER", default = "dev" }
+"#,
+    );
+    let environment = environment(&[
+        ("GIT_COMMIT", "abc123"),
+        ("HEX_API_KEY", "secret"),
+        ("HOME", "/home/lucy"),
+    ]);
+    assert_eq!(
+        variables(&config, &environment),
+        HashMap::from([("GIT_COMMIT".into(), "abc123".into())])
+    );
+}
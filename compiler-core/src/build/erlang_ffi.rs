@@ -0,0 +1,247 @@
+#[cfg(test)]
+mod tests;
+
+use std::{
+    collections::{HashMap, HashSet},
+    sync::OnceLock,
+};
+
+use camino::Utf8Path;
+use ecow::EcoString;
+use itertools::Itertools;
+use regex::Regex;
+
+use crate::{
+    ast::{Definition, TypedFunction},
+    build::Module,
+    error::{Error, UnknownErlangExternalDetails},
+    io::FileSystemReader,
+    Result,
+};
+
+/// The functions an Erlang module makes available to other modules.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Exports {
+    /// The module is compiled with `export_all` so every function is public.
+    All,
+    Functions(HashSet<(EcoString, usize)>),
+}
+
+impl Exports {
+    fn arities(&self, function: &str) -> Vec<usize> {
+        match self {
+            Exports::All => vec![],
+            Exports::Functions(functions) => functions
+                .iter()
+                .filter(|(name, _)| name == function)
+                .map(|(_, arity)| *arity)
+                .sorted()
+                .collect(),
+        }
+    }
+
+    fn contains(&self, function: &str, arity: usize) -> bool {
+        match self {
+            Exports::All => true,
+            Exports::Functions(functions) => {
+                functions.iter().any(|(f, a)| f == function && *a == arity)
+            }
+        }
+    }
+}
+
+/// Checks that the `@external(erlang, ...)` functions of the given modules
+/// refer to functions that are exported by the Erlang modules written in this
+/// package, so a typo results in a compile error rather than an `undef` crash
+/// at runtime.
+///
+/// Externals that refer to modules not defined in this package (such as OTP
+/// modules or modules from other packages) cannot be checked and are skipped.
+///
+pub(crate) fn check_externals<IO: FileSystemReader>(
+    io: &IO,
+    root: &Utf8Path,
+    modules: &[Module],
+) -> Result<()> {
+    let native_modules = package_erlang_exports(io, root)?;
+    if native_modules.is_empty() {
+        return Ok(());
+    }
+
+    for module in modules {
+        for definition in &module.ast.definitions {
+            let Definition::Function(function) = definition else {
+                continue;
+            };
+            check_function(module, function, &native_modules)?;
+        }
+    }
+    Ok(())
+}
+
+fn check_function(
+    module: &Module,
+    function: &TypedFunction,
+    native_modules: &HashMap<EcoString, Exports>,
+) -> Result<()> {
+    let Some((erlang_module, erlang_function)) = &function.external_erlang else {
+        return Ok(());
+    };
+    let Some(exports) = native_modules.get(erlang_module) else {
+        return Ok(());
+    };
+    let arity = function.arguments.len();
+    if exports.contains(erlang_function, arity) {
+        return Ok(());
+    }
+
+    Err(Error::UnknownErlangExternal {
+        details: Box::new(UnknownErlangExternalDetails {
+            module: erlang_module.clone(),
+            function: erlang_function.clone(),
+            arity,
+            exported_arities: exports.arities(erlang_function),
+            location: function.location,
+            path: module.input_path.clone(),
+            src: module.code.clone(),
+        }),
+    })
+}
+
+/// Reads the exports of all the Erlang modules in the `src` and `test`
+/// directories of the package.
+fn package_erlang_exports<IO: FileSystemReader>(
+    io: &IO,
+    root: &Utf8Path,
+) -> Result<HashMap<EcoString, Exports>> {
+    let mut modules = HashMap::new();
+    for directory in [root.join("src"), root.join("test")] {
+        if !io.is_directory(&directory) {
+            continue;
+        }
+        for entry in io.read_dir(&directory)? {
+            let path = entry.expect("erlang_ffi dir_entry").pathbuf;
+            if path.extension() != Some("erl") {
+                continue;
+            }
+            let src = io.read(&path)?;
+            let default_name = path.file_stem().unwrap_or_default();
+            let (name, exports) = parse_exports(&src, default_name);
+            let _ = modules.insert(name, exports);
+        }
+    }
+    Ok(modules)
+}
+
+/// Finds the name and exported functions of an Erlang module from its source.
+/// The file name is used as the module name if there is no `-module`
+/// attribute.
+///
+pub(crate) fn parse_exports(src: &str, default_name: &str) -> (EcoString, Exports) {
+    static MODULE: OnceLock<Regex> = OnceLock::new();
+    static EXPORT: OnceLock<Regex> = OnceLock::new();
+    static COMPILE: OnceLock<Regex> = OnceLock::new();
+    static EXPORT_ALL: OnceLock<Regex> = OnceLock::new();
+
+    let src = strip_comments_and_strings(src);
+
+    let name = MODULE
+        .get_or_init(|| {
+            Regex::new(r"-\s*module\s*\(\s*('(?:[^'\\]|\\.)*'|[a-z][A-Za-z0-9_@]*)\s*\)")
+                .expect("module attribute regex")
+        })
+        .captures(&src)
+        .map(|captures| unquote_atom(&captures[1]))
+        .unwrap_or_else(|| default_name.into());
+
+    let export_all = EXPORT_ALL.get_or_init(|| Regex::new(r"\bexport_all\b").expect("regex"));
+    let is_export_all = COMPILE
+        .get_or_init(|| Regex::new(r"(?s)-\s*compile\s*\((.*?)\)\s*\.").expect("compile regex"))
+        .captures_iter(&src)
+        .any(|captures| export_all.is_match(&captures[1]));
+    if is_export_all {
+        return (name, Exports::All);
+    }
+
+    let functions = EXPORT
+        .get_or_init(|| {
+            Regex::new(r"(?s)-\s*export\s*\(\s*\[(.*?)\]\s*\)").expect("export attribute regex")
+        })
+        .captures_iter(&src)
+        .flat_map(|captures| {
+            captures[1]
+                .split(',')
+                .filter_map(|entry| {
+                    let (function, arity) = entry.trim().rsplit_once('/')?;
+                    let arity = arity.trim().parse().ok()?;
+                    Some((unquote_atom(function.trim()), arity))
+                })
+                .collect_vec()
+        })
+        .collect();
+    (name, Exports::Functions(functions))
+}
+
+fn unquote_atom(atom: &str) -> EcoString {
+    atom.strip_prefix('\'')
+        .and_then(|atom| atom.strip_suffix('\''))
+        .unwrap_or(atom)
+        .into()
+}
+
+/// Removes `%` comments and the contents of strings from Erlang source so
+/// that only the code remains to be searched for attributes. Quoted atoms and
+/// character literals are left untouched.
+fn strip_comments_and_strings(src: &str) -> String {
+    let mut out = String::with_capacity(src.len());
+    let mut chars = src.chars();
+    while let Some(char) = chars.next() {
+        match char {
+            '%' => {
+                for char in chars.by_ref() {
+                    if char == '\n' {
+                        out.push('\n');
+                        break;
+                    }
+                }
+            }
+
+            '"' => {
+                out.push_str("\"\"");
+                while let Some(char) = chars.next() {
+                    if char == '\\' {
+                        let _ = chars.next();
+                    } else if char == '"' {
+                        break;
+                    }
+                }
+            }
+
+            '\'' => {
+                out.push(char);
+                while let Some(char) = chars.next() {
+                    out.push(char);
+                    if char == '\\' {
+                        out.extend(chars.next());
+                    } else if char == '\'' {
+                        break;
+                    }
+                }
+            }
+
+            '$' => {
+                out.push(char);
+                match chars.next() {
+                    Some('\\') => {
+                        out.push('\\');
+                        out.extend(chars.next());
+                    }
+                    next => out.extend(next),
+                }
+            }
+
+            _ => out.push(char),
+        }
+    }
+    out
+}
@@ -0,0 +1,159 @@
+use super::{parse_exports, Exports};
+use crate::{
+    build::{Mode, NullTelemetry, PackageCompiler, StaleTracker, TargetCodegenConfiguration},
+    config::PackageConfig,
+    error::{Error, UnknownErlangExternalDetails},
+    io::{memory::InMemoryFileSystem, FileSystemWriter},
+    uid::UniqueIdGenerator,
+    warning::WarningEmitter,
+};
+use std::collections::HashSet;
+
+use camino::Utf8PathBuf;
+use ecow::EcoString;
+
+fn functions(functions: &[(&str, usize)]) -> Exports {
+    Exports::Functions(
+        functions
+            .iter()
+            .map(|(name, arity)| (EcoString::from(*name), *arity))
+            .collect(),
+    )
+}
+
+#[test]
+fn parse_exports_of_module() {
+    let src = r#"
+-module(wibble_ffi).
+-export([wobble/0, wobble/2]).
+-export([
+    % Not yet: wabble/3
+    'quoted'/1
+]).
+
+wobble() -> "-export([nope/0]).".
+"#;
+    assert_eq!(
+        parse_exports(src, "file_name"),
+        (
+            "wibble_ffi".into(),
+            functions(&[("wobble", 0), ("wobble", 2), ("quoted", 1)])
+        )
+    );
+}
+
+#[test]
+fn parse_exports_without_module_attribute_uses_file_name() {
+    assert_eq!(
+        parse_exports("-export([main/0]).", "file_name"),
+        ("file_name".into(), functions(&[("main", 0)]))
+    );
+}
+
+#[test]
+fn parse_exports_export_all() {
+    let src = "-module(wibble).\n-compile([export_all, nowarn_export_all]).\n";
+    assert_eq!(
+        parse_exports(src, "wibble"),
+        ("wibble".into(), Exports::All)
+    );
+}
+
+#[test]
+fn parse_exports_ignores_commented_out_export_all() {
+    let src = "-module(wibble).\n% -compile(export_all).\n";
+    assert_eq!(
+        parse_exports(src, "wibble"),
+        ("wibble".into(), functions(&[]))
+    );
+}
+
+fn check(gleam: &str, erlang: &str) -> Result<(), Error> {
+    let fs = InMemoryFileSystem::new();
+    fs.write(&Utf8PathBuf::from("/src/app.gleam"), gleam)
+        .expect("write gleam module");
+    fs.write(&Utf8PathBuf::from("/src/app_ffi.erl"), erlang)
+        .expect("write erlang module");
+
+    let config = PackageConfig::default();
+    let target = TargetCodegenConfiguration::Erlang { app_file: None };
+    let root = Utf8PathBuf::from("/");
+    let build = root.join("build");
+    let lib = root.join("lib");
+    let mut compiler = PackageCompiler::new(
+        &config,
+        Mode::Dev,
+        &root,
+        &build,
+        &lib,
+        &target,
+        UniqueIdGenerator::new(),
+        fs.clone(),
+    );
+    compiler.perform_codegen = false;
+    compiler.write_metadata = false;
+    compiler
+        .compile(
+            &WarningEmitter::null(),
+            &mut im::HashMap::new(),
+            &mut im::HashMap::new(),
+            &mut StaleTracker::default(),
+            &mut HashSet::new(),
+            &NullTelemetry,
+        )
+        .into_result()
+        .map(|_| ())
+}
+
+#[test]
+fn known_external_is_accepted() {
+    let result = check(
+        r#"@external(erlang, "app_ffi", "wobble")
+pub fn wobble(a: Int) -> Int"#,
+        "-module(app_ffi).\n-export([wobble/1]).\nwobble(A) -> A.\n",
+    );
+    assert_eq!(result, Ok(()));
+}
+
+#[test]
+fn external_from_other_module_is_not_checked() {
+    let result = check(
+        r#"@external(erlang, "lists", "reverse")
+pub fn reverse(a: List(Int)) -> List(Int)"#,
+        "-module(app_ffi).\n",
+    );
+    assert_eq!(result, Ok(()));
+}
+
+#[test]
+fn external_with_wrong_arity_is_rejected() {
+    let gleam = r#"@external(erlang, "app_ffi", "wobble")
+pub fn wobble(a: Int, b: Int) -> Int"#;
+    let result = check(
+        gleam,
+        "-module(app_ffi).\n-export([wobble/1]).\nwobble(A) -> A.\n",
+    );
+    assert_eq!(
+        result,
+        Err(Error::UnknownErlangExternal {
+            details: Box::new(UnknownErlangExternalDetails {
+                module: "app_ffi".into(),
+                function: "wobble".into(),
+                arity: 2,
+                exported_arities: vec![1],
+                location: crate::ast::SrcSpan::new(39, 68),
+                path: Utf8PathBuf::from("/src/app.gleam"),
+                src: gleam.into(),
+            })
+        })
+    );
+}
+
+#[test]
+fn unknown_external_error_message() {
+    let gleam = r#"@external(erlang, "app_ffi", "wibble")
+pub fn wibble() -> Int"#;
+    let error = check(gleam, "-module(app_ffi).\n-export([wobble/1]).\n")
+        .expect_err("wibble is not exported");
+    insta::assert_snapshot!(error.pretty_string());
+}
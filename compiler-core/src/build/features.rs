@@ -0,0 +1,198 @@
+//! Features are named parts of a package that are only compiled when they are
+//! enabled, declared in the `[features]` section of the package's gleam.toml.
+//!
+//! A feature can own modules, given as globs of module names, and definitions
+//! marked with the `@feature("name")` attribute. It can also enable other
+//! features of the same package, or of a dependency when written
+//! `package/feature`.
+//!
+//! A package's default features are always enabled, and packages enable the
+//! features of their dependencies in their requirements:
+//!
+//! ```toml
+//! [dependencies]
+//! wibble = { version = "~> 1.0", features = ["json"] }
+//! ```
+//!
+//! Each package is compiled once for the whole project, so its features are
+//! unified: a feature is enabled if any package in the project enables it.
+
+#[cfg(test)]
+mod tests;
+
+use std::collections::{BTreeSet, HashMap};
+
+use camino::Utf8Path;
+use ecow::EcoString;
+use globset::{GlobSet, GlobSetBuilder};
+
+use crate::{
+    ast::UntypedModule, config::PackageConfig, error::UnknownFeatureDetails, Error, Result,
+};
+
+use super::Mode;
+
+/// The features enabled for each package of a project.
+pub type EnabledFeatures = HashMap<EcoString, BTreeSet<EcoString>>;
+
+/// Works out which features of each package are enabled, starting from the
+/// default features of each package and the features the root package and
+/// dependencies enable in their requirements.
+///
+/// Packages without a config, such as those built with rebar3 or mix, have no
+/// features.
+pub fn resolve(
+    root: &PackageConfig,
+    mode: Mode,
+    dependencies: &HashMap<EcoString, PackageConfig>,
+) -> Result<EnabledFeatures> {
+    let config = |name: &EcoString| {
+        if name == &root.name {
+            Some(root)
+        } else {
+            dependencies.get(name)
+        }
+    };
+
+    // Each entry is the package enabling the feature, the package the feature
+    // belongs to, and the feature.
+    let mut to_enable = vec![];
+    for package in std::iter::once(root).chain(dependencies.values()) {
+        for feature in &package.default_features {
+            to_enable.push((&package.name, package.name.clone(), feature.clone()));
+        }
+        // Only the root package's dev dependencies are used.
+        let requirements = if package.name == root.name {
+            package.dependencies_for(mode)?
+        } else {
+            package.dependencies.clone()
+        };
+        for (dependency, requirement) in requirements {
+            for feature in requirement.features() {
+                to_enable.push((&package.name, dependency.clone(), feature.clone()));
+            }
+        }
+    }
+
+    let mut enabled = EnabledFeatures::new();
+    while let Some((required_by, package, feature)) = to_enable.pop() {
+        let declared = config(&package).and_then(|config| {
+            let declared = config.features.get(&feature)?;
+            Some((config, declared))
+        });
+        let Some((config, declared)) = declared else {
+            return Err(Error::UnknownFeature {
+                options: config(&package)
+                    .map(|config| config.features.keys().cloned().collect())
+                    .unwrap_or_default(),
+                required_by: required_by.clone(),
+                package,
+                feature,
+            });
+        };
+        if !enabled.entry(package).or_default().insert(feature) {
+            continue;
+        }
+        for enables in &declared.enables {
+            let (package, feature) = match enables.split_once('/') {
+                Some((package, feature)) => (package.into(), feature.into()),
+                None => (config.name.clone(), enables.clone()),
+            };
+            to_enable.push((&config.name, package, feature));
+        }
+    }
+    Ok(enabled)
+}
+
+/// The features of a package, and which of them are enabled in this build.
+#[derive(Debug, Clone)]
+pub struct PackageFeatures {
+    declared: Vec<EcoString>,
+    enabled: BTreeSet<EcoString>,
+    /// The modules of the enabled features.
+    enabled_modules: GlobSet,
+    /// The modules of all the package's features.
+    feature_modules: GlobSet,
+}
+
+impl Default for PackageFeatures {
+    fn default() -> Self {
+        Self::new(&PackageConfig::default(), &BTreeSet::new())
+    }
+}
+
+impl PackageFeatures {
+    pub fn new(config: &PackageConfig, enabled: &BTreeSet<EcoString>) -> Self {
+        let mut enabled_modules = GlobSetBuilder::new();
+        let mut feature_modules = GlobSetBuilder::new();
+        for (name, feature) in &config.features {
+            for glob in &feature.modules {
+                if enabled.contains(name) {
+                    _ = enabled_modules.add(glob.clone());
+                }
+                _ = feature_modules.add(glob.clone());
+            }
+        }
+        Self {
+            declared: config.features.keys().cloned().collect(),
+            enabled: enabled.clone(),
+            enabled_modules: enabled_modules
+                .build()
+                .expect("enabled feature module globs"),
+            feature_modules: feature_modules.build().expect("feature module globs"),
+        }
+    }
+
+    /// Whether a module is compiled, which it is unless it only belongs to
+    /// features that are not enabled.
+    pub fn includes_module(&self, module: &str) -> bool {
+        self.enabled_modules.is_match(module) || !self.feature_modules.is_match(module)
+    }
+
+    /// Removes the definitions that belong to features that are not enabled.
+    pub fn filter_definitions(
+        &self,
+        module: &mut UntypedModule,
+        path: &Utf8Path,
+        src: &EcoString,
+    ) -> Result<()> {
+        let unknown = module
+            .definitions
+            .iter()
+            .filter_map(|definition| definition.feature.as_ref())
+            .find(|feature| !self.declared.contains(&feature.name));
+        if let Some(feature) = unknown {
+            return Err(Error::UnknownFeatureAttribute {
+                details: Box::new(UnknownFeatureDetails {
+                    feature: feature.name.clone(),
+                    options: self.declared.clone(),
+                    location: feature.location,
+                    path: path.to_path_buf(),
+                    src: src.clone(),
+                }),
+            });
+        }
+
+        module
+            .definitions
+            .retain(|definition| match &definition.feature {
+                Some(feature) => self.enabled.contains(&feature.name),
+                None => true,
+            });
+        Ok(())
+    }
+
+    /// A description of the enabled features, which changes whenever the
+    /// enabled features do.
+    pub fn fingerprint(&self) -> String {
+        self.enabled
+            .iter()
+            .map(|feature| format!("{feature}\n"))
+            .collect()
+    }
+
+    /// Whether the package declares any features.
+    pub fn is_empty(&self) -> bool {
+        self.declared.is_empty()
+    }
+}
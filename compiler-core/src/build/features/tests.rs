@@ -0,0 +1,247 @@
+use std::collections::{BTreeSet, HashMap};
+
+use ecow::EcoString;
+use globset::Glob;
+use itertools::Itertools;
+
+use super::*;
+use crate::{config::Feature, requirement::Requirement};
+
+fn config(name: &str, toml: &str) -> PackageConfig {
+    let config: PackageConfig =
+        toml::from_str(&format!("name = \"{name}\"\n{toml}")).expect("config");
+    config
+}
+
+fn features(names: &[&str]) -> BTreeSet<EcoString> {
+    names.iter().map(|name| (*name).into()).collect()
+}
+
+fn resolved(
+    root: &PackageConfig,
+    dependencies: Vec<PackageConfig>,
+) -> Result<Vec<(EcoString, BTreeSet<EcoString>)>> {
+    let dependencies = dependencies
+        .into_iter()
+        .map(|config| (config.name.clone(), config))
+        .collect();
+    let enabled = resolve(root, Mode::Dev, &dependencies)?;
+    Ok(enabled.into_iter().sorted().collect())
+}
+
+#[test]
+fn default_features_are_enabled() {
+    let root = config(
+        "app",
+        r#"
+default_features = ["json"]
+[features.json]
+[features.xml]
+"#,
+    );
+    assert_eq!(
+        resolved(&root, vec![]),
+        Ok(vec![("app".into(), features(&["json"]))])
+    );
+}
+
+#[test]
+fn features_are_unified_across_dependents() {
+    let root = config(
+        "app",
+        r#"
+[dependencies]
+wibble = "~> 1.0"
+codec = { version = "~> 1.0", features = ["json"] }
+"#,
+    );
+    let wibble = config(
+        "wibble",
+        r#"
+[dependencies]
+codec = { version = "~> 1.0", features = ["xml"] }
+"#,
+    );
+    let codec = config(
+        "codec",
+        r#"
+[features.json]
+[features.xml]
+[features.yaml]
+"#,
+    );
+    assert_eq!(
+        resolved(&root, vec![wibble, codec]),
+        Ok(vec![("codec".into(), features(&["json", "xml"]))])
+    );
+}
+
+#[test]
+fn features_enable_other_features() {
+    let root = config(
+        "app",
+        r#"
+default_features = ["full"]
+[features.full]
+enables = ["json", "codec/xml"]
+[features.json]
+enables = ["codec/json"]
+[dependencies]
+codec = "~> 1.0"
+"#,
+    );
+    let codec = config(
+        "codec",
+        r#"
+[features.json]
+[features.xml]
+"#,
+    );
+    assert_eq!(
+        resolved(&root, vec![codec]),
+        Ok(vec![
+            ("app".into(), features(&["full", "json"])),
+            ("codec".into(), features(&["json", "xml"])),
+        ])
+    );
+}
+
+#[test]
+fn dev_dependency_features_are_enabled_for_the_root_package_only() {
+    let root = config(
+        "app",
+        r#"
+[dev-dependencies]
+codec = { version = "~> 1.0", features = ["json"] }
+"#,
+    );
+    let wibble = config(
+        "wibble",
+        r#"
+[dev-dependencies]
+codec = { version = "~> 1.0", features = ["xml"] }
+"#,
+    );
+    let codec = config(
+        "codec",
+        r#"
+[features.json]
+[features.xml]
+"#,
+    );
+    assert_eq!(
+        resolved(&root, vec![wibble, codec]),
+        Ok(vec![("codec".into(), features(&["json"]))])
+    );
+}
+
+#[test]
+fn unknown_dependency_feature() {
+    let mut root = config("app", "");
+    let _ = root.dependencies.insert(
+        "codec".into(),
+        Requirement::hex("~> 1.0").with_features(&["jsno"]),
+    );
+    let codec = config("codec", "[features.json]");
+    assert_eq!(
+        resolved(&root, vec![codec]),
+        Err(Error::UnknownFeature {
+            package: "codec".into(),
+            feature: "jsno".into(),
+            required_by: "app".into(),
+            options: vec!["json".into()],
+        })
+    );
+}
+
+#[test]
+fn unknown_feature_of_package_without_config() {
+    let root = config(
+        "app",
+        r#"
+[dependencies]
+rebar_package = { version = "~> 1.0", features = ["json"] }
+"#,
+    );
+    assert_eq!(
+        resolved(&root, vec![]),
+        Err(Error::UnknownFeature {
+            package: "rebar_package".into(),
+            feature: "json".into(),
+            required_by: "app".into(),
+            options: vec![],
+        })
+    );
+}
+
+#[test]
+fn modules_of_disabled_features_are_not_included() {
+    let mut config = PackageConfig::default();
+    let feature = |globs: &[&str]| Feature {
+        modules: globs
+            .iter()
+            .map(|glob| Glob::new(glob).expect("glob"))
+            .collect(),
+        enables: vec![],
+    };
+    let _ = config
+        .features
+        .insert("json".into(), feature(&["codec/json", "codec/json/*"]));
+    let _ = config
+        .features
+        .insert("xml".into(), feature(&["codec/xml", "codec/shared"]));
+    let _ = config
+        .features
+        .insert("yaml".into(), feature(&["codec/shared"]));
+
+    let package = PackageFeatures::new(&config, &features(&["xml"]));
+    assert!(package.includes_module("main"));
+    assert!(package.includes_module("codec/xml"));
+    assert!(package.includes_module("codec/shared"));
+    assert!(!package.includes_module("codec/json"));
+    assert!(!package.includes_module("codec/json/decode"));
+
+    let package = PackageFeatures::new(&config, &features(&[]));
+    assert!(package.includes_module("main"));
+    assert!(!package.includes_module("codec/shared"));
+}
+
+#[test]
+fn definitions_of_disabled_features_are_removed() {
+    let src: EcoString = r#"
+@feature("json")
+import codec/json
+
+@feature("xml")
+pub fn to_xml() { Nil }
+
+@feature("json")
+pub fn to_json() { Nil }
+
+pub fn main() { Nil }
+"#
+    .into();
+    let mut module = crate::parse::parse_module(&src).expect("parse").module;
+    let config = config("app", "[features.json]\n[features.xml]");
+    let package = PackageFeatures::new(&config, &features(&["json"]));
+    package
+        .filter_definitions(&mut module, Utf8Path::new("src/main.gleam"), &src)
+        .expect("filter");
+    let names: Vec<_> = module
+        .definitions
+        .iter()
+        .map(|definition| match &definition.definition {
+            crate::ast::Definition::Import(import) => import.module.clone(),
+            crate::ast::Definition::Function(function) => function.name.clone(),
+            _ => panic!("unexpected definition"),
+        })
+        .collect();
+    assert_eq!(
+        names,
+        vec![
+            EcoString::from("codec/json"),
+            "to_json".into(),
+            "main".into()
+        ]
+    );
+}
@@ -0,0 +1,357 @@
+#[cfg(test)]
+mod tests;
+
+use std::{
+    collections::{hash_map::Entry, HashMap, HashSet},
+    sync::OnceLock,
+};
+
+use camino::{Utf8Component, Utf8Path, Utf8PathBuf};
+use ecow::EcoString;
+use itertools::Itertools;
+use regex::Regex;
+
+use crate::{
+    ast::{Definition, TypedFunction},
+    build::Module,
+    error::{Error, UnknownJavaScriptExternalDetails, UnknownJavaScriptExternalProblem},
+    io::FileSystemReader,
+    Result,
+};
+
+/// The names a JavaScript module makes available to other modules.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Exports {
+    /// The module re-exports everything from another module with
+    /// `export * from`, so which names it exports cannot be known without
+    /// following the re-export.
+    Unknown,
+    Names(HashSet<EcoString>),
+}
+
+/// Checks that the `@external(javascript, ...)` functions of the given modules
+/// refer to a file in this package that exports a function with the given
+/// name, so a mistake results in a compile error rather than a crash when the
+/// JavaScript is loaded.
+///
+/// Only relative paths to files in the package's `src` and `test` directories
+/// are checked. Externals that refer to packages, to runtime modules such as
+/// `node:fs`, or to files generated by the compiler are skipped.
+///
+pub(crate) fn check_externals<IO: FileSystemReader>(
+    io: &IO,
+    root: &Utf8Path,
+    modules: &[Module],
+) -> Result<()> {
+    let mut exports = HashMap::new();
+    for module in modules {
+        for definition in &module.ast.definitions {
+            let Definition::Function(function) = definition else {
+                continue;
+            };
+            check_function(io, root, module, function, &mut exports)?;
+        }
+    }
+    Ok(())
+}
+
+fn check_function<IO: FileSystemReader>(
+    io: &IO,
+    root: &Utf8Path,
+    module: &Module,
+    function: &TypedFunction,
+    exports: &mut HashMap<Utf8PathBuf, Exports>,
+) -> Result<()> {
+    let Some((path, name)) = &function.external_javascript else {
+        return Ok(());
+    };
+    if !(path.starts_with("./") || path.starts_with("../")) {
+        return Ok(());
+    }
+
+    let directory = module.input_path.parent().unwrap_or(Utf8Path::new(""));
+    let file = crate::paths::normalise(&directory.join(path.as_str()));
+    let problem = match find_file(io, root, file) {
+        Found::Outside => return Ok(()),
+        Found::Missing => UnknownJavaScriptExternalProblem::FileNotFound,
+        Found::File(file) => {
+            let file_exports = match exports.entry(file.clone()) {
+                Entry::Occupied(entry) => entry.into_mut(),
+                Entry::Vacant(entry) => entry.insert(parse_exports(&io.read(&file)?)),
+            };
+            match file_exports {
+                Exports::Unknown => return Ok(()),
+                Exports::Names(names) if names.contains(name) => return Ok(()),
+                Exports::Names(names) => UnknownJavaScriptExternalProblem::NotExported {
+                    exported: names.iter().cloned().sorted().collect(),
+                },
+            }
+        }
+    };
+
+    Err(Error::UnknownJavaScriptExternal {
+        details: Box::new(UnknownJavaScriptExternalDetails {
+            module: path.clone(),
+            function: name.clone(),
+            problem,
+            location: function.location,
+            path: module.input_path.clone(),
+            src: module.code.clone(),
+        }),
+    })
+}
+
+enum Found {
+    File(Utf8PathBuf),
+    Missing,
+    /// The file is not one of the package's own files, such as a module of
+    /// another package or one generated by the compiler, so it is only there
+    /// once the project has been built.
+    Outside,
+}
+
+/// Finds the file an external path refers to. Native files from `src` and
+/// `test` are copied into the same build directory, so a module in one can
+/// refer to a file in the other.
+fn find_file<IO: FileSystemReader>(io: &IO, root: &Utf8Path, file: Utf8PathBuf) -> Found {
+    let (src, test) = (root.join("src"), root.join("test"));
+    let relative = match (file.strip_prefix(&src), file.strip_prefix(&test)) {
+        (Ok(relative), _) | (_, Ok(relative)) => relative.to_path_buf(),
+        (Err(_), Err(_)) => return Found::Outside,
+    };
+    if io.is_file(&file) {
+        return Found::File(file);
+    }
+    for directory in [&src, &test] {
+        let file = directory.join(&relative);
+        if io.is_file(&file) {
+            return Found::File(file);
+        }
+    }
+
+    // The prelude is copied to `gleam.mjs` next to the modules of the
+    // package, and each Gleam module is compiled to a module of the same
+    // name.
+    let generated = relative == "gleam.mjs"
+        || [&src, &test]
+            .iter()
+            .any(|directory| io.is_file(&directory.join(&relative).with_extension("gleam")));
+    if generated {
+        Found::Outside
+    } else {
+        Found::Missing
+    }
+}
+
+/// Finds the names exported by a JavaScript or TypeScript module. This is not
+/// a full parser, it recognises the `export` forms that are used to write FFI
+/// modules.
+///
+pub(crate) fn parse_exports(src: &str) -> Exports {
+    static DECLARATION: OnceLock<Regex> = OnceLock::new();
+    static VARIABLES: OnceLock<Regex> = OnceLock::new();
+    static LIST: OnceLock<Regex> = OnceLock::new();
+    static STAR: OnceLock<Regex> = OnceLock::new();
+    static DEFAULT: OnceLock<Regex> = OnceLock::new();
+
+    let Some(src) = strip_comments_and_strings(src) else {
+        return Exports::Unknown;
+    };
+
+    let is_star = STAR
+        .get_or_init(|| Regex::new(r"\bexport\s*\*\s*from\b").expect("export star regex"))
+        .is_match(&src);
+    if is_star {
+        return Exports::Unknown;
+    }
+
+    let mut names = HashSet::new();
+
+    let declarations = DECLARATION.get_or_init(|| {
+        Regex::new(
+            r"\bexport\s+(?:declare\s+)?(?:async\s+)?(?:function\s*\*?|class)\s*([A-Za-z_$][\w$]*)",
+        )
+        .expect("export declaration regex")
+    });
+    for captures in declarations.captures_iter(&src) {
+        let _ = names.insert(captures[1].into());
+    }
+
+    let variables = VARIABLES.get_or_init(|| {
+        Regex::new(r"\bexport\s+(?:declare\s+)?(?:const|let|var)\s")
+            .expect("export variables regex")
+    });
+    for found in variables.find_iter(&src) {
+        names.extend(declared_names(&src[found.end()..]));
+    }
+
+    let lists = LIST.get_or_init(|| {
+        Regex::new(r"\bexport\s*(?:type\s*)?\{([^}]*)\}").expect("export list regex")
+    });
+    for captures in lists.captures_iter(&src) {
+        for entry in captures[1].split(',') {
+            let name = match entry.split_once(" as ") {
+                Some((_, alias)) => alias.trim(),
+                None => entry.trim(),
+            };
+            if !name.is_empty() {
+                let _ = names.insert(name.into());
+            }
+        }
+    }
+
+    let is_default = DEFAULT
+        .get_or_init(|| Regex::new(r"\bexport\s+default\b").expect("export default regex"))
+        .is_match(&src);
+    if is_default {
+        let _ = names.insert("default".into());
+    }
+
+    Exports::Names(names)
+}
+
+/// The names declared by the declarators of a `const`, `let` or `var`
+/// statement, such as `one` and `two` in `one = 1, two = 2;`. The statement
+/// ends at a semicolon, or at a line break that does not continue an
+/// unfinished expression.
+fn declared_names(src: &str) -> Vec<EcoString> {
+    static NAME: OnceLock<Regex> = OnceLock::new();
+    let name = NAME.get_or_init(|| Regex::new(r"^\s*([A-Za-z_$][\w$]*)").expect("name regex"));
+
+    let mut names = vec![];
+    let mut rest = src;
+    loop {
+        // Destructuring patterns are not followed, as FFI modules do not
+        // export names that way.
+        let Some(captures) = name.captures(rest) else {
+            return names;
+        };
+        names.push(captures[1].into());
+        rest = &rest[captures[0].len()..];
+
+        let mut depth = 0_usize;
+        let mut last = ' ';
+        let mut next = None;
+        for (index, char) in rest.char_indices() {
+            match char {
+                '(' | '[' | '{' => depth += 1,
+                ')' | ']' | '}' => depth = depth.saturating_sub(1),
+                ',' if depth == 0 => {
+                    next = Some(index + 1);
+                    break;
+                }
+                ';' if depth == 0 => break,
+                '\n' if depth == 0 && !"=,+-*/%&|^?:<>!~.(".contains(last) => break,
+                _ => (),
+            }
+            if !char.is_whitespace() {
+                last = char;
+            }
+        }
+        match next {
+            Some(index) => rest = &rest[index..],
+            None => return names,
+        }
+    }
+}
+
+/// Removes comments and the contents of strings and regular expressions from
+/// JavaScript source so that only the code remains to be searched for exports.
+/// Returns `None` if the source ends inside a string or regular expression, as
+/// it was not understood.
+fn strip_comments_and_strings(src: &str) -> Option<String> {
+    let mut out = String::with_capacity(src.len());
+    let mut chars = src.chars().peekable();
+    while let Some(char) = chars.next() {
+        match char {
+            '/' if chars.peek() == Some(&'/') => {
+                for char in chars.by_ref() {
+                    if char == '\n' {
+                        out.push('\n');
+                        break;
+                    }
+                }
+            }
+
+            '/' if chars.peek() == Some(&'*') => {
+                let _ = chars.next();
+                let mut previous = ' ';
+                for char in chars.by_ref() {
+                    if previous == '*' && char == '/' {
+                        break;
+                    }
+                    previous = char;
+                }
+                out.push(' ');
+            }
+
+            '/' if starts_expression(&out) => {
+                out.push_str("//");
+                let mut in_class = false;
+                loop {
+                    match chars.next()? {
+                        '\\' => {
+                            let _ = chars.next();
+                        }
+                        '\n' => return None,
+                        '[' => in_class = true,
+                        ']' => in_class = false,
+                        '/' if !in_class => break,
+                        _ => (),
+                    }
+                }
+            }
+
+            '"' | '\'' | '`' => {
+                let quote = char;
+                out.push(quote);
+                out.push(quote);
+                loop {
+                    match chars.next()? {
+                        '\\' => {
+                            let _ = chars.next();
+                        }
+                        char if char == quote => break,
+                        _ => (),
+                    }
+                }
+            }
+
+            _ => out.push(char),
+        }
+    }
+    Some(out)
+}
+
+/// Whether a `/` following the given code starts a regular expression rather
+/// than being a division.
+fn starts_expression(code: &str) -> bool {
+    let code = code.trim_end();
+    let Some(last) = code.chars().next_back() else {
+        return true;
+    };
+    if last.is_alphanumeric() || last == '_' || last == '$' {
+        let word = code
+            .rsplit(|char: char| !(char.is_alphanumeric() || char == '_' || char == '$'))
+            .next()
+            .unwrap_or_default();
+        return matches!(
+            word,
+            "return"
+                | "typeof"
+                | "instanceof"
+                | "in"
+                | "of"
+                | "new"
+                | "delete"
+                | "void"
+                | "throw"
+                | "case"
+                | "do"
+                | "else"
+                | "yield"
+                | "await"
+        );
+    }
+    !matches!(last, ')' | ']' | '"' | '\'' | '`')
+}
@@ -0,0 +1,249 @@
+use super::{parse_exports, Exports};
+use crate::{
+    build::{Mode, NullTelemetry, PackageCompiler, StaleTracker, TargetCodegenConfiguration},
+    config::PackageConfig,
+    error::{Error, UnknownJavaScriptExternalDetails, UnknownJavaScriptExternalProblem},
+    io::{memory::InMemoryFileSystem, FileSystemWriter},
+    uid::UniqueIdGenerator,
+    warning::WarningEmitter,
+};
+use std::collections::HashSet;
+
+use camino::{Utf8Path, Utf8PathBuf};
+
+fn names(names: &[&str]) -> Exports {
+    Exports::Names(names.iter().map(|name| (*name).into()).collect())
+}
+
+#[test]
+fn parse_exports_of_module() {
+    let src = r#"
+import { thing } from "./other.mjs";
+
+export function wibble() {}
+export async function wobble() {}
+export function* generate() {}
+export const one = 1, two = 2;
+export class Box {}
+// export function commented() {}
+const message = "export function quoted() {}";
+function a() {}
+function b() {}
+export { a, b as renamed };
+"#;
+    assert_eq!(
+        parse_exports(src),
+        names(&["wibble", "wobble", "generate", "one", "two", "Box", "a", "renamed"])
+    );
+}
+
+#[test]
+fn parse_exports_of_declarator_lists() {
+    let src = r#"
+export let first = f(1, 2), second = [3, 4],
+  third = { a: 1, b: 2 };
+export var multiline =
+  5, last
+export const after = 6
+wibble(7, 8)
+"#;
+    assert_eq!(
+        parse_exports(src),
+        names(&["first", "second", "third", "multiline", "last", "after"])
+    );
+}
+
+#[test]
+fn parse_exports_default() {
+    assert_eq!(
+        parse_exports("export default function() {}"),
+        names(&["default"])
+    );
+}
+
+#[test]
+fn parse_exports_reexport_all() {
+    assert_eq!(
+        parse_exports("export * from \"./other.mjs\";"),
+        Exports::Unknown
+    );
+}
+
+#[test]
+fn parse_exports_with_regular_expressions() {
+    let src = r#"
+const quotes = /'/g;
+const escaped = /\/"[/"]/;
+export function unquote(string) {
+  return string.replace(quotes, "").replace(/"/g, "");
+}
+export const half = (a, b) => a / 2 / b;
+export const ratio = (ratio / 2) + 'a';
+"#;
+    assert_eq!(parse_exports(src), names(&["unquote", "half", "ratio"]));
+}
+
+#[test]
+fn parse_exports_of_unterminated_string() {
+    let src = r#"
+export function wibble() { return "wibble }
+"#;
+    assert_eq!(parse_exports(src), Exports::Unknown);
+}
+
+fn check(gleam: &str, files: &[(&str, &str)]) -> Result<(), Error> {
+    let fs = InMemoryFileSystem::new();
+    fs.write(&Utf8PathBuf::from("/src/app.gleam"), gleam)
+        .expect("write gleam module");
+    for (path, src) in files {
+        fs.write(Utf8Path::new(path), src)
+            .expect("write javascript module");
+    }
+
+    let config = PackageConfig::default();
+    let target = TargetCodegenConfiguration::JavaScript {
+        emit_typescript_definitions: false,
+        prelude_location: Utf8PathBuf::from("../prelude.mjs"),
+        platform_location: None,
+    };
+    let root = Utf8PathBuf::from("/");
+    let build = root.join("build");
+    let lib = root.join("lib");
+    let mut compiler = PackageCompiler::new(
+        &config,
+        Mode::Dev,
+        &root,
+        &build,
+        &lib,
+        &target,
+        UniqueIdGenerator::new(),
+        fs,
+    );
+    compiler.perform_codegen = false;
+    compiler.write_metadata = false;
+    compiler
+        .compile(
+            &WarningEmitter::null(),
+            &mut im::HashMap::new(),
+            &mut im::HashMap::new(),
+            &mut StaleTracker::default(),
+            &mut HashSet::new(),
+            &NullTelemetry,
+        )
+        .into_result()
+        .map(|_| ())
+}
+
+const WOBBLE: &str = r#"@external(javascript, "./app_ffi.mjs", "wobble")
+pub fn wobble(a: Int) -> Int"#;
+
+#[test]
+fn known_external_is_accepted() {
+    let result = check(
+        WOBBLE,
+        &[("/src/app_ffi.mjs", "export function wobble(a) { return a }")],
+    );
+    assert_eq!(result, Ok(()));
+}
+
+#[test]
+fn external_in_test_directory_is_found() {
+    let result = check(
+        WOBBLE,
+        &[(
+            "/test/app_ffi.mjs",
+            "export function wobble(a) { return a }",
+        )],
+    );
+    assert_eq!(result, Ok(()));
+}
+
+#[test]
+fn non_relative_external_is_not_checked() {
+    let result = check(
+        r#"@external(javascript, "node:process", "exit")
+pub fn exit(code: Int) -> Nil"#,
+        &[],
+    );
+    assert_eq!(result, Ok(()));
+}
+
+#[test]
+fn external_outside_package_sources_is_not_checked() {
+    let result = check(
+        r#"@external(javascript, "../other_package/other.mjs", "wobble")
+pub fn wobble(a: Int) -> Int"#,
+        &[],
+    );
+    assert_eq!(result, Ok(()));
+}
+
+#[test]
+fn generated_module_is_not_checked() {
+    let result = check(
+        r#"@external(javascript, "./gleam.mjs", "toList")
+pub fn to_list(a: Int) -> Int
+
+@external(javascript, "./other.mjs", "wobble")
+pub fn wobble(a: Int) -> Int"#,
+        &[("/src/other.gleam", "pub fn wobble(a) { a }")],
+    );
+    assert_eq!(result, Ok(()));
+}
+
+#[test]
+fn missing_file_is_rejected() {
+    let result = check(WOBBLE, &[]);
+    assert_eq!(
+        result,
+        Err(Error::UnknownJavaScriptExternal {
+            details: Box::new(UnknownJavaScriptExternalDetails {
+                module: "./app_ffi.mjs".into(),
+                function: "wobble".into(),
+                problem: UnknownJavaScriptExternalProblem::FileNotFound,
+                location: crate::ast::SrcSpan::new(49, 70),
+                path: Utf8PathBuf::from("/src/app.gleam"),
+                src: WOBBLE.into(),
+            })
+        })
+    );
+}
+
+#[test]
+fn missing_export_is_rejected() {
+    let result = check(
+        WOBBLE,
+        &[("/src/app_ffi.mjs", "export function wibble(a) { return a }")],
+    );
+    assert_eq!(
+        result,
+        Err(Error::UnknownJavaScriptExternal {
+            details: Box::new(UnknownJavaScriptExternalDetails {
+                module: "./app_ffi.mjs".into(),
+                function: "wobble".into(),
+                problem: UnknownJavaScriptExternalProblem::NotExported {
+                    exported: vec!["wibble".into()]
+                },
+                location: crate::ast::SrcSpan::new(49, 70),
+                path: Utf8PathBuf::from("/src/app.gleam"),
+                src: WOBBLE.into(),
+            })
+        })
+    );
+}
+
+#[test]
+fn missing_file_error_message() {
+    let error = check(WOBBLE, &[]).expect_err("file does not exist");
+    insta::assert_snapshot!(error.pretty_string());
+}
+
+#[test]
+fn missing_export_error_message() {
+    let error = check(
+        WOBBLE,
+        &[("/src/app_ffi.mjs", "export function wobbel(a) { return a }")],
+    )
+    .expect_err("wobble is not exported");
+    insta::assert_snapshot!(error.pretty_string());
+}
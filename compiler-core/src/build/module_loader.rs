@@ -9,6 +9,7 @@ use ecow::EcoString;
 use serde::{Deserialize, Serialize};
 
 use super::{
+    features::PackageFeatures,
     package_compiler::{module_name, CacheMetadata, CachedModule, Input, UncompiledModule},
     package_loader::CodegenRequired,
     Mode, Origin, SourceFingerprint, Target,
@@ -16,6 +17,7 @@ use super::{
 use crate::{
     error::{FileIoAction, FileKind},
     io::{CommandExecutor, FileSystemReader, FileSystemWriter},
+    parse::incremental::ParseCache,
     warning::WarningEmitter,
     Error, Result,
 };
@@ -31,9 +33,12 @@ pub(crate) struct ModuleLoader<'a, IO> {
     pub source_directory: &'a Utf8Path,
     pub artefact_directory: &'a Utf8Path,
     pub origin: Origin,
+    /// The parse trees retained between compilations by the language server.
+    pub parse_cache: Option<&'a ParseCache>,
     /// The set of modules that have had partial compilation done since the last
     /// successful compilation.
     pub incomplete_modules: &'a HashSet<EcoString>,
+    pub features: &'a PackageFeatures,
 }
 
 impl<'a, IO> ModuleLoader<'a, IO>
@@ -119,6 +124,8 @@ where
     ) -> Result<UncompiledModule, Error> {
         read_source(
             self.io.clone(),
+            self.parse_cache,
+            self.features,
             self.target,
             self.origin,
             path,
@@ -131,6 +138,7 @@ where
     fn cached(&self, name: EcoString, meta: CacheMetadata) -> CachedModule {
         CachedModule {
             dependencies: meta.dependencies,
+            dependency_interfaces: meta.dependency_interfaces,
             source_path: self.source_directory.join(format!("{}.gleam", name)),
             origin: self.origin,
             name,
@@ -139,8 +147,16 @@ where
     }
 }
 
+/// Reads and parses a module. In the language server syntax errors do not stop
+/// the module from being analysed, so the parser recovers from them and the
+/// first error is returned with the module. The parse tree is retained in the
+/// language server's cache so only the changed definitions are parsed again.
+/// Definitions belonging to features that are not enabled are removed.
+#[allow(clippy::too_many_arguments)]
 pub(crate) fn read_source<IO>(
     io: IO,
+    parse_cache: Option<&ParseCache>,
+    features: &PackageFeatures,
     target: Target,
     origin: Origin,
     path: Utf8PathBuf,
@@ -153,13 +169,24 @@ where
 {
     let code: EcoString = io.read(&path)?.into();
 
-    let parsed = crate::parse::parse_module(&code).map_err(|error| Error::Parse {
+    let parse_error = |error| Error::Parse {
         path: path.clone(),
         src: code.clone(),
         error,
-    })?;
+    };
+    let (parsed, parse_error) = match parse_cache {
+        Some(parse_cache) => {
+            let (parsed, errors) = parse_cache.parse(&path, &code);
+            (parsed, errors.into_iter().next().map(parse_error))
+        }
+        None => (
+            crate::parse::parse_module(&code).map_err(parse_error)?,
+            None,
+        ),
+    };
     let mut ast = parsed.module;
     let extra = parsed.extra;
+    features.filter_definitions(&mut ast, &path, &code)?;
     let dependencies = ast.dependencies(target);
 
     ast.name = name.clone();
@@ -173,6 +200,8 @@ where
         name,
         code,
         ast,
+        parse_error,
+        dependency_interfaces: None,
     };
     Ok(module)
 }
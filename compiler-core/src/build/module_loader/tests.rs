@@ -4,7 +4,7 @@ use crate::{
     io::{memory::InMemoryFileSystem, FileSystemWriter},
     line_numbers::LineNumbers,
 };
-use std::time::Duration;
+use std::{sync::OnceLock, time::Duration};
 
 #[test]
 fn no_cache_present() {
@@ -215,6 +215,7 @@ fn write_cache(
         mtime: SystemTime::UNIX_EPOCH + Duration::from_secs(seconds),
         codegen_performed,
         dependencies: vec![],
+        dependency_interfaces: vec![],
         fingerprint: SourceFingerprint::new(source),
         line_numbers,
     };
@@ -228,6 +229,8 @@ fn write_src(fs: &InMemoryFileSystem, source: &str, path: &str, seconds: u64) {
     fs.set_modification_time(&path, SystemTime::UNIX_EPOCH + Duration::from_secs(seconds));
 }
 
+static FEATURES: OnceLock<PackageFeatures> = OnceLock::new();
+
 fn make_loader<'a>(
     warnings: &'a WarningEmitter,
     package_name: &'a EcoString,
@@ -246,6 +249,8 @@ fn make_loader<'a>(
         source_directory: &src,
         artefact_directory: &artefact,
         origin: Origin::Src,
+        parse_cache: None,
         incomplete_modules,
+        features: FEATURES.get_or_init(PackageFeatures::default),
     }
 }
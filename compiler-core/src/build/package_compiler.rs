@@ -1,28 +1,38 @@
 use crate::analyse::{ModuleAnalyzerConstructor, TargetSupport};
 use crate::line_numbers::{self, LineNumbers};
-use crate::type_::PRELUDE_MODULE_NAME;
+use crate::type_::{pretty::Printer, PRELUDE_MODULE_NAME};
 use crate::{
     ast::{SrcSpan, TypedModule, UntypedModule},
     build::{
+        artefacts_manifest::ArtefactsManifest,
         elixir_libraries::ElixirLibraries,
+        env_module, erlang_ffi,
+        features::PackageFeatures,
+        javascript_ffi,
         native_file_copier::NativeFileCopier,
         package_loader::{CodegenRequired, PackageLoader, StaleTracker},
-        Mode, Module, Origin, Outcome, Package, SourceFingerprint, Target,
+        InterfaceFingerprint, Mode, Module, Origin, Outcome, Package, SourceFingerprint, Target,
+    },
+    codegen::{
+        chunks::{self, Chunks},
+        Erlang, ErlangApp, JavaScript, TypeScriptDeclarations,
     },
-    codegen::{Erlang, ErlangApp, JavaScript, TypeScriptDeclarations},
     config::PackageConfig,
     dep_tree, error,
     io::{CommandExecutor, FileSystemReader, FileSystemWriter, Stdio},
-    metadata::ModuleEncoder,
-    parse::extra::ModuleExtra,
-    paths, type_,
+    metadata::{ModuleDecoder, ModuleEncoder},
+    parse::{extra::ModuleExtra, incremental::ParseCache},
+    paths,
+    runtime_config::{self, RuntimeConfig},
+    type_,
     uid::UniqueIdGenerator,
     warning::{TypeWarningEmitter, WarningEmitter},
     Error, Result, Warning,
 };
 use askama::Template;
 use ecow::EcoString;
-use std::collections::HashSet;
+use itertools::Itertools;
+use std::collections::{BTreeSet, HashSet};
 use std::{collections::HashMap, fmt::write, time::SystemTime};
 use vec1::Vec1;
 
@@ -47,6 +57,29 @@ pub struct PackageCompiler<'a, IO> {
     pub compile_beam_bytecode: bool,
     pub subprocess_stdio: Stdio,
     pub target_support: TargetSupport,
+    /// Whether modules with target specific definitions are also type checked
+    /// for the target they are not being compiled for.
+    pub check_other_target: bool,
+    /// Whether the files and functions referred to by externals are checked
+    /// to exist. They are not for dependencies, whose externals cannot be
+    /// fixed by the user.
+    pub check_externals: bool,
+    /// The parse trees retained between compilations by the language server,
+    /// which recovers from syntax errors when parsing.
+    pub parse_cache: Option<ParseCache>,
+    /// The features of the package to compile. By default these are the
+    /// package's default features.
+    pub enabled_features: BTreeSet<EcoString>,
+    /// The values of the environment variables listed in the package's
+    /// `[env]` config.
+    pub environment: HashMap<String, String>,
+    /// Where to write the manifest of the files generated for each module of
+    /// the package, if anywhere.
+    pub artefacts_manifest: Option<Utf8PathBuf>,
+    /// Whether the Erlang is generated to be read by people rather than to
+    /// be compact.
+    pub readable_erlang: bool,
+    pub erlfmt_layout: bool,
 }
 
 impl<'a, IO> PackageCompiler<'a, IO>
@@ -79,6 +112,14 @@ where
             compile_beam_bytecode: true,
             subprocess_stdio: Stdio::Inherit,
             target_support: TargetSupport::NotEnforced,
+            check_other_target: false,
+            check_externals: true,
+            parse_cache: (mode == Mode::Lsp).then(ParseCache::default),
+            enabled_features: config.default_features.iter().cloned().collect(),
+            environment: HashMap::new(),
+            artefacts_manifest: None,
+            readable_erlang: false,
+            erlfmt_layout: false,
         }
     }
 
@@ -103,6 +144,16 @@ where
             return e.into();
         }
 
+        let features = PackageFeatures::new(self.config, &self.enabled_features);
+        if let Err(e) = self.check_features(&features) {
+            return e.into();
+        }
+
+        let generated_directory = self.out.join(paths::GENERATED_DIRECTORY_NAME);
+        if let Err(e) = self.write_env_module(&generated_directory) {
+            return e.into();
+        }
+
         let artefact_directory = self.out.join(paths::ARTEFACT_DIRECTORY_NAME);
         let codegen_required = if self.perform_codegen {
             CodegenRequired::Yes
@@ -122,6 +173,9 @@ where
             stale_modules,
             already_defined_modules,
             incomplete_modules,
+            self.parse_cache.as_ref(),
+            &features,
+            &generated_directory,
         );
         let loaded = match loader.run() {
             Ok(loaded) => loaded,
@@ -144,6 +198,13 @@ where
 
         // Type check the modules that are new or have changed
         tracing::info!(count=%loaded.to_compile.len(), "analysing_modules");
+        let mut analysis_cache = AnalysisCache {
+            io: self.io.clone(),
+            ids: self.ids.clone(),
+            artefact_directory: &artefact_directory,
+            locations: self.mode == Mode::Lsp,
+            fingerprints: HashMap::new(),
+        };
         let outcome = analyse(
             &self.config,
             self.target.target(),
@@ -153,26 +214,85 @@ where
             existing_modules,
             warnings,
             self.target_support,
+            self.check_other_target,
             incomplete_modules,
+            &mut analysis_cache,
         );
         let modules = match outcome {
             Outcome::Ok(modules) => modules,
             Outcome::PartialFailure(_, _) | Outcome::TotalFailure(_) => return outcome,
         };
 
+        let externals_checked = match self.target.target() {
+            _ if !self.check_externals => Ok(()),
+            Target::Erlang => {
+                tracing::debug!("checking_erlang_externals");
+                erlang_ffi::check_externals(&self.io, self.root, &modules)
+            }
+            Target::JavaScript => {
+                tracing::debug!("checking_javascript_externals");
+                javascript_ffi::check_externals(&self.io, self.root, &modules)
+            }
+        };
+        if let Err(error) = externals_checked {
+            return error.into();
+        }
+
         tracing::debug!("performing_code_generation");
 
-        if let Err(error) = self.perform_codegen(&modules) {
+        if let Err(error) = self.perform_codegen(&modules, &loaded.src_imports, warnings) {
+            return error.into();
+        }
+
+        if let Err(error) =
+            self.encode_and_write_metadata(&modules, existing_modules, &mut analysis_cache)
+        {
             return error.into();
         }
 
-        if let Err(error) = self.encode_and_write_metadata(&modules) {
+        if let Err(error) =
+            self.write_artefacts_manifest(existing_modules, &loaded.imports, &loaded.src_imports)
+        {
             return error.into();
         }
 
         Outcome::Ok(modules)
     }
 
+    /// Which modules and definitions are compiled depends on the enabled
+    /// features, so if the package was previously compiled with different
+    /// features then everything compiled then is removed.
+    fn check_features(&self, features: &PackageFeatures) -> Result<()> {
+        if features.is_empty() {
+            return Ok(());
+        }
+        let path = self
+            .out
+            .join(paths::ARTEFACT_DIRECTORY_NAME)
+            .join("features");
+        let fingerprint = features.fingerprint();
+        if self.io.is_file(&path) && self.io.read(&path)? == fingerprint {
+            return Ok(());
+        }
+        tracing::debug!(package = %self.config.name, "features_changed");
+        self.io.delete_directory(self.out)?;
+        self.io.write(&path, &fingerprint)
+    }
+
+    /// Writes the module of the environment variables listed in the package's
+    /// `[env]` config. It is only rewritten when the values change, so the
+    /// modules that use it are only recompiled then.
+    fn write_env_module(&self, directory: &Utf8Path) -> Result<()> {
+        let name = env_module::module_name(&self.config.name);
+        let path = directory.join(format!("{name}.gleam"));
+        match env_module::render(self.config, &self.environment)? {
+            Some(source) if self.io.read(&path).ok().as_deref() == Some(source.as_str()) => Ok(()),
+            Some(source) => self.io.write(&path, &source),
+            None if self.io.is_file(&path) => self.io.delete_file(&path),
+            None => Ok(()),
+        }
+    }
+
     fn compile_erlang_to_beam(&mut self, modules: &HashSet<Utf8PathBuf>) -> Result<(), Error> {
         if modules.is_empty() {
             tracing::debug!("no_erlang_to_compile");
@@ -252,7 +372,12 @@ where
         Ok(())
     }
 
-    fn encode_and_write_metadata(&mut self, modules: &[Module]) -> Result<()> {
+    fn encode_and_write_metadata(
+        &mut self,
+        modules: &[Module],
+        module_types: &im::HashMap<EcoString, type_::ModuleInterface>,
+        analysis_cache: &mut AnalysisCache<'_, IO>,
+    ) -> Result<()> {
         if !self.write_metadata {
             tracing::debug!("package_metadata_writing_disabled");
             return Ok(());
@@ -276,10 +401,14 @@ where
             // Write cache info
             let name = format!("{}.cache_meta", &module_name);
             let path = artefact_dir.join(name);
+            let dependencies = module.dependencies_list();
+            let dependency_interfaces =
+                analysis_cache.dependency_interfaces(&dependencies, module_types)?;
             let info = CacheMetadata {
                 mtime: module.mtime,
                 codegen_performed: self.perform_codegen,
-                dependencies: module.dependencies_list(),
+                dependencies,
+                dependency_interfaces,
                 fingerprint: SourceFingerprint::new(&module.code),
                 line_numbers: module.ast.type_info.line_numbers.clone(),
             };
@@ -288,7 +417,48 @@ where
         Ok(())
     }
 
-    fn perform_codegen(&mut self, modules: &[Module]) -> Result<()> {
+    /// Writes the manifest of the files generated for each module of the
+    /// package, including the modules loaded from the cache.
+    fn write_artefacts_manifest(
+        &self,
+        module_types: &im::HashMap<EcoString, type_::ModuleInterface>,
+        imports: &HashMap<EcoString, Vec<EcoString>>,
+        src_imports: &HashMap<EcoString, Vec<EcoString>>,
+    ) -> Result<()> {
+        let Some(path) = &self.artefacts_manifest else {
+            return Ok(());
+        };
+        tracing::debug!("writing_artefacts_manifest");
+        let mut manifest =
+            ArtefactsManifest::new(self.config.name.clone(), self.target.target(), self.mode);
+        for interface in module_types
+            .values()
+            .filter(|interface| interface.package == self.config.name)
+        {
+            let origin = if src_imports.contains_key(&interface.name) {
+                Origin::Src
+            } else {
+                Origin::Test
+            };
+            let imports = imports.get(&interface.name).map(Vec::as_slice);
+            manifest.insert(
+                &self.io,
+                self.root,
+                self.out,
+                interface,
+                origin,
+                imports.unwrap_or_default(),
+            )?;
+        }
+        self.io.write(path, &manifest.to_json())
+    }
+
+    fn perform_codegen(
+        &mut self,
+        modules: &[Module],
+        imports: &HashMap<EcoString, Vec<EcoString>>,
+        warnings: &WarningEmitter,
+    ) -> Result<()> {
         if !self.perform_codegen {
             tracing::debug!("skipping_codegen");
             return Ok(());
@@ -298,10 +468,14 @@ where
             TargetCodegenConfiguration::JavaScript {
                 emit_typescript_definitions,
                 prelude_location,
+                platform_location,
             } => self.perform_javascript_codegen(
                 modules,
+                imports,
+                warnings,
                 *emit_typescript_definitions,
                 prelude_location,
+                platform_location.as_deref(),
             ),
             TargetCodegenConfiguration::Erlang { app_file } => {
                 self.perform_erlang_codegen(modules, app_file.as_ref())
@@ -341,11 +515,21 @@ where
             tracing::debug!("skipping_entrypoint_generation");
         }
 
+        if self.compile_beam_bytecode && self.write_entrypoint {
+            self.render_erlang_config_module(&build_dir, &mut written)?;
+        }
+
         // NOTE: This must come after `copy_project_native_files` to ensure that
         // we overwrite any precompiled Erlang that was included in the Hex
         // package. Otherwise we will build the potentially outdated precompiled
         // version and not the newly compiled version.
-        Erlang::new(&build_dir, &include_dir).render(io, modules)?;
+        Erlang::new(
+            &build_dir,
+            &include_dir,
+            self.readable_erlang,
+            self.erlfmt_layout,
+        )
+        .render(io, modules)?;
 
         if self.compile_beam_bytecode {
             written.extend(modules.iter().map(Module::compiled_erlang_path));
@@ -359,8 +543,11 @@ where
     fn perform_javascript_codegen(
         &mut self,
         modules: &[Module],
+        imports: &HashMap<EcoString, Vec<EcoString>>,
+        warnings: &WarningEmitter,
         typescript: bool,
         prelude_location: &Utf8Path,
+        platform_location: Option<&Utf8Path>,
     ) -> Result<(), Error> {
         let mut written = HashSet::new();
         let typescript = if typescript {
@@ -369,8 +556,15 @@ where
             TypeScriptDeclarations::None
         };
 
-        JavaScript::new(&self.out, typescript, prelude_location, self.target_support)
-            .render(&self.io, modules)?;
+        JavaScript::new(
+            &self.out,
+            typescript,
+            prelude_location,
+            platform_location,
+            self.target_support,
+        )
+        .render(&self.io, modules)?;
+        self.write_javascript_chunks(imports, warnings, typescript)?;
 
         if self.copy_native_files {
             self.copy_project_native_files(&self.out, &mut written)?;
@@ -381,11 +575,63 @@ where
         Ok(())
     }
 
+    /// Writes the loaders and manifest of the chunks of the entrypoints listed
+    /// in gleam.toml, or removes them if there are none.
+    fn write_javascript_chunks(
+        &self,
+        imports: &HashMap<EcoString, Vec<EcoString>>,
+        warnings: &WarningEmitter,
+        typescript: TypeScriptDeclarations,
+    ) -> Result<()> {
+        let loader = self.out.join(format!("{}.mjs", chunks::LOADER_MODULE));
+        let declarations = self.out.join(format!("{}.d.mts", chunks::LOADER_MODULE));
+        let manifest = self.out.join(chunks::MANIFEST_FILE);
+        let entrypoints = &self.config.javascript.entrypoints;
+        if entrypoints.is_empty() {
+            for path in [loader, declarations, manifest] {
+                if self.io.is_file(&path) {
+                    self.io.delete_file(&path)?;
+                }
+            }
+            return Ok(());
+        }
+
+        let chunks = Chunks::split(entrypoints, imports)?;
+        for (module, entrypoint) in chunks.static_imports(imports) {
+            warnings.emit(Warning::StaticEntrypointImport {
+                module: module.clone(),
+                entrypoint: entrypoint.clone(),
+            });
+        }
+        self.io.write(&loader, &chunks.loader_module())?;
+        if typescript == TypeScriptDeclarations::Emit {
+            self.io
+                .write(&declarations, &chunks.loader_declarations())?;
+        }
+        self.io.write(&manifest, &chunks.manifest())
+    }
+
     fn render_erlang_entrypoint_module(
         &mut self,
         out: &Utf8Path,
         modules_to_compile: &mut HashSet<Utf8PathBuf>,
     ) -> Result<(), Error> {
+        // The entrypoint prints errors with the crash formatter module, which
+        // is written for the root package only as its name is not prefixed
+        // with the name of the package. So are the module encoding values for
+        // tooling and the test runner.
+        for (name, module) in [
+            ("gleam@@crash.erl", crate::erlang::CRASH_MODULE),
+            ("gleam@@inspect.erl", crate::erlang::INSPECT_MODULE),
+            ("gleam@@test.erl", crate::test_runner::ERLANG_MODULE),
+        ] {
+            let path = out.join(name);
+            if !self.io.is_file(&path) {
+                self.io.write(&path, module)?;
+                let _ = modules_to_compile.insert(name.into());
+            }
+        }
+
         let name = format!("{name}@@main.erl", name = self.config.name);
         let path = out.join(&name);
 
@@ -405,9 +651,41 @@ where
         tracing::debug!("erlang_entrypoint_written");
         Ok(())
     }
+
+    /// Writes the module of accessors for the package's application
+    /// environment in `config/config.toml`, if the project has one.
+    fn render_erlang_config_module(
+        &mut self,
+        out: &Utf8Path,
+        modules_to_compile: &mut HashSet<Utf8PathBuf>,
+    ) -> Result<(), Error> {
+        let path = self
+            .root
+            .join(runtime_config::DIRECTORY_NAME)
+            .join(format!("{}.toml", runtime_config::BASE_CONFIG_NAME));
+        if !self.io.is_file(&path) {
+            tracing::debug!("no_runtime_config");
+            return Ok(());
+        }
+
+        let config = RuntimeConfig::read(&path, &self.io)?;
+        let module = config.accessor_module(&self.config.name)?;
+        let name = format!("{name}@@config.erl", name = self.config.name);
+        let path = out.join(&name);
+
+        // Only write and compile the module again if the config has changed.
+        if self.io.read(&path).ok().as_deref() == Some(module.as_str()) {
+            tracing::debug!("erlang_config_module_unchanged");
+            return Ok(());
+        }
+        self.io.write(&path, &module)?;
+        let _ = modules_to_compile.insert(name.into());
+        tracing::debug!("erlang_config_module_written");
+        Ok(())
+    }
 }
 
-fn analyse(
+fn analyse<IO>(
     package_config: &PackageConfig,
     target: Target,
     mode: Mode,
@@ -416,8 +694,13 @@ fn analyse(
     module_types: &mut im::HashMap<EcoString, type_::ModuleInterface>,
     warnings: &WarningEmitter,
     target_support: TargetSupport,
+    check_other_target: bool,
     incomplete_modules: &mut HashSet<EcoString>,
-) -> Outcome<Vec<Module>, Error> {
+    analysis_cache: &mut AnalysisCache<'_, IO>,
+) -> Outcome<Vec<Module>, Error>
+where
+    IO: FileSystemReader,
+{
     let mut modules = Vec::with_capacity(parsed_modules.len() + 1);
     let direct_dependencies = package_config.dependencies_for(mode).expect("Package deps");
 
@@ -428,22 +711,46 @@ fn analyse(
     // place.
     let _ = module_types.insert(PRELUDE_MODULE_NAME.into(), type_::build_prelude(ids));
 
-    for UncompiledModule {
-        name,
-        code,
-        ast,
-        path,
-        mtime,
-        origin,
-        package,
-        dependencies,
-        extra,
-    } in parsed_modules
-    {
-        tracing::debug!(module = ?name, "Type checking");
+    // The interfaces of the modules of this package as seen from the other
+    // target, for modules that have target specific definitions.
+    let mut other_target_interfaces = HashMap::new();
+
+    for module in parsed_modules {
+        // A module is compiled again when its dependencies are, but if their
+        // interfaces are the same as the ones it was last analysed against
+        // then its previous analysis can be used instead.
+        match analysis_cache.reusable_interface(&module, module_types) {
+            Ok(Some(interface)) => {
+                tracing::debug!(module = ?module.name, "reusing_previous_analysis");
+                let _ = module_types.insert(module.name, interface);
+                continue;
+            }
+            Ok(None) => (),
+            Err(error) => return error.into(),
+        }
+
+        let UncompiledModule {
+            name,
+            code,
+            ast,
+            path,
+            mtime,
+            origin,
+            package,
+            dependencies,
+            extra,
+            parse_error,
+            dependency_interfaces: _,
+        } = module;
+        tracing::debug!(module = ?name, "type_checking");
+        let _compiling = crate::crash::compiling(&name, &path);
 
         let line_numbers = LineNumbers::new(&code);
 
+        let other_target_ast = (check_other_target
+            && ast.definitions.iter().any(|d| d.target.is_some()))
+        .then(|| ast.clone());
+
         let analysis = crate::analyse::ModuleAnalyzerConstructor {
             target,
             ids,
@@ -456,8 +763,47 @@ fn analyse(
         }
         .infer_module(ast, line_numbers, path.clone());
 
-        match analysis {
-            Outcome::Ok(ast) => {
+        // A syntax error is reported in preference to any type errors, as
+        // they are likely to be caused by the part of the module that could
+        // not be parsed.
+        let type_error = |errors| Error::Type {
+            path: path.clone(),
+            src: code.clone(),
+            errors,
+        };
+        let (ast, error) = match analysis {
+            Outcome::Ok(ast) => (ast, parse_error),
+            Outcome::PartialFailure(ast, errors) => {
+                (ast, Some(parse_error.unwrap_or_else(|| type_error(errors))))
+            }
+            Outcome::TotalFailure(errors) => {
+                return Outcome::TotalFailure(parse_error.unwrap_or_else(|| type_error(errors)))
+            }
+        };
+
+        match error {
+            None => {
+                if let Some(other_target_ast) = other_target_ast {
+                    let other_target = OtherTarget {
+                        target: other_target_of(target),
+                        ids,
+                        origin,
+                        package_config,
+                        direct_dependencies: &direct_dependencies,
+                        warnings,
+                        path: &path,
+                        src: &code,
+                    };
+                    let importable_modules = module_types
+                        .clone()
+                        .union(other_target_interfaces.clone().into());
+                    if let Some(interface) =
+                        other_target.check(other_target_ast, &ast, &importable_modules)
+                    {
+                        let _ = other_target_interfaces.insert(name.clone(), interface);
+                    }
+                }
+
                 // Module has compiled successfully. Make sure it isn't marked as incomplete.
                 let _ = incomplete_modules.remove(&name.clone());
                 // Register the types from this module so they can be imported into
@@ -477,12 +823,7 @@ fn analyse(
                 });
             }
 
-            Outcome::PartialFailure(ast, errors) => {
-                let error = Error::Type {
-                    path: path.clone(),
-                    src: code.clone(),
-                    errors,
-                };
+            Some(error) => {
                 // Mark as incomplete so that this module isn't reloaded from cache.
                 let _ = incomplete_modules.insert(name.clone());
                 // Register the partially type checked module data so that it can be
@@ -500,18 +841,135 @@ fn analyse(
                 // WARNING: This cannot be used for code generation as the code has errors.
                 return Outcome::PartialFailure(modules, error);
             }
+        };
+    }
 
-            Outcome::TotalFailure(errors) => {
-                return Outcome::TotalFailure(Error::Type {
-                    path: path.clone(),
-                    src: code.clone(),
+    Outcome::Ok(modules)
+}
+
+fn other_target_of(target: Target) -> Target {
+    match target {
+        Target::Erlang => Target::JavaScript,
+        Target::JavaScript => Target::Erlang,
+    }
+}
+
+/// Type checks a module with target specific definitions for the target it is
+/// not being compiled for. The dependencies of the package are only compiled
+/// for the current target, so problems are reported as warnings rather than
+/// errors, as the other target's view of a dependency may differ.
+struct OtherTarget<'a, A> {
+    target: Target,
+    ids: &'a UniqueIdGenerator,
+    origin: Origin,
+    package_config: &'a PackageConfig,
+    direct_dependencies: &'a HashMap<EcoString, A>,
+    warnings: &'a WarningEmitter,
+    path: &'a Utf8PathBuf,
+    src: &'a EcoString,
+}
+
+impl<A> OtherTarget<'_, A> {
+    /// Type checks the module for the other target, warning about any errors
+    /// and about public definitions whose types differ from those of the
+    /// module as compiled. Returns the module's interface for the other
+    /// target if it type checks.
+    fn check(
+        &self,
+        ast: UntypedModule,
+        compiled: &TypedModule,
+        importable_modules: &im::HashMap<EcoString, type_::ModuleInterface>,
+    ) -> Option<type_::ModuleInterface> {
+        // Warnings about the code shared by both targets have already been
+        // emitted when the module was compiled.
+        let null_warnings = WarningEmitter::null();
+        let analysis = crate::analyse::ModuleAnalyzerConstructor {
+            target: self.target,
+            ids: self.ids,
+            origin: self.origin,
+            importable_modules,
+            warnings: &TypeWarningEmitter::new(self.path.clone(), self.src.clone(), null_warnings),
+            direct_dependencies: self.direct_dependencies,
+            target_support: TargetSupport::NotEnforced,
+            package_config: self.package_config,
+        }
+        .infer_module(ast, LineNumbers::new(self.src), self.path.clone());
+
+        let other = match analysis {
+            Outcome::Ok(other) => other,
+            Outcome::PartialFailure(_, errors) | Outcome::TotalFailure(errors) => {
+                let error = Error::Type {
+                    path: self.path.clone(),
+                    src: self.src.clone(),
                     errors,
-                })
+                };
+                self.warnings.emit(Warning::OtherTargetError {
+                    target: self.target,
+                    error: Box::new(error),
+                });
+                return None;
             }
         };
+
+        self.check_signatures(&compiled.type_info, &other.type_info);
+        Some(other.type_info)
     }
 
-    Outcome::Ok(modules)
+    fn check_signatures(&self, compiled: &type_::ModuleInterface, other: &type_::ModuleInterface) {
+        let print = |type_: &type_::Type| Printer::new().pretty_print(type_, 0);
+        let values = compiled
+            .values
+            .iter()
+            .filter(|(_, value)| value.publicity.is_public())
+            .sorted_by_key(|(name, _)| *name);
+        for (name, value) in values {
+            let Some(other_value) = other.values.get(name) else {
+                continue;
+            };
+            let (type_, other_type) = (print(&value.type_), print(&other_value.type_));
+            if type_ != other_type {
+                self.emit_mismatch(name, value.variant.definition_location(), type_, other_type);
+            }
+        }
+
+        let types = compiled
+            .types
+            .iter()
+            .filter(|(_, type_)| type_.publicity.is_public())
+            .sorted_by_key(|(name, _)| *name);
+        for (name, type_) in types {
+            let Some(other_type) = other.types.get(name) else {
+                continue;
+            };
+            if type_.parameters.len() != other_type.parameters.len() {
+                self.emit_mismatch(
+                    name,
+                    type_.origin,
+                    print(&type_.typ),
+                    print(&other_type.typ),
+                );
+            }
+        }
+    }
+
+    fn emit_mismatch(
+        &self,
+        name: &EcoString,
+        location: SrcSpan,
+        type_: String,
+        other_type: String,
+    ) {
+        self.warnings.emit(Warning::TargetSignatureMismatch {
+            path: self.path.clone(),
+            src: self.src.clone(),
+            location,
+            name: name.clone(),
+            target: other_target_of(self.target),
+            type_,
+            other_target: self.target,
+            other_type,
+        });
+    }
 }
 
 pub(crate) fn module_name(package_path: &Utf8Path, full_module_path: &Utf8Path) -> EcoString {
@@ -554,6 +1012,13 @@ impl Input {
         }
     }
 
+    pub fn origin(&self) -> Origin {
+        match self {
+            Input::New(m) => m.origin,
+            Input::Cached(m) => m.origin,
+        }
+    }
+
     pub fn dependencies(&self) -> Vec<EcoString> {
         match self {
             Input::New(m) => m.dependencies.iter().map(|(n, _)| n.clone()).collect(),
@@ -583,6 +1048,7 @@ pub(crate) struct CachedModule {
     pub name: EcoString,
     pub origin: Origin,
     pub dependencies: Vec<EcoString>,
+    pub dependency_interfaces: Vec<(EcoString, InterfaceFingerprint)>,
     pub source_path: Utf8PathBuf,
     pub line_numbers: LineNumbers,
 }
@@ -592,6 +1058,9 @@ pub(crate) struct CacheMetadata {
     pub mtime: SystemTime,
     pub codegen_performed: bool,
     pub dependencies: Vec<EcoString>,
+    /// The fingerprints of the interfaces of the dependencies the module was
+    /// analysed against.
+    pub dependency_interfaces: Vec<(EcoString, InterfaceFingerprint)>,
     pub fingerprint: SourceFingerprint,
     pub line_numbers: LineNumbers,
 }
@@ -610,6 +1079,12 @@ impl CacheMetadata {
 pub(crate) struct Loaded {
     pub to_compile: Vec<UncompiledModule>,
     pub cached: Vec<type_::ModuleInterface>,
+    /// The modules each src module of the package imports, including those
+    /// that were loaded from the cache.
+    pub src_imports: HashMap<EcoString, Vec<EcoString>>,
+    /// The modules each module of the package imports, including those that
+    /// were loaded from the cache.
+    pub imports: HashMap<EcoString, Vec<EcoString>>,
 }
 
 #[derive(Debug, PartialEq, Eq)]
@@ -623,6 +1098,93 @@ pub(crate) struct UncompiledModule {
     pub dependencies: Vec<(EcoString, SrcSpan)>,
     pub ast: UntypedModule,
     pub extra: ModuleExtra,
+    /// A syntax error the parser recovered from. The module can be analysed
+    /// for the language server but cannot be compiled.
+    pub parse_error: Option<Error>,
+    /// The fingerprints of the interfaces of the dependencies the module was
+    /// last analysed against, if it has a cache that is being invalidated
+    /// only because its dependencies are being compiled again.
+    pub dependency_interfaces: Option<Vec<(EcoString, InterfaceFingerprint)>>,
+}
+
+/// The previous analyses of the modules in the package. Analysing a module is
+/// treated as a query keyed by the fingerprints of its source and of the
+/// interfaces of the modules it imports, so when these are all unchanged the
+/// interface in the cache is used instead of analysing the module again.
+#[derive(Debug)]
+struct AnalysisCache<'a, IO> {
+    io: IO,
+    ids: UniqueIdGenerator,
+    artefact_directory: &'a Utf8Path,
+    /// The language server keeps the typed ASTs of the modules it does not
+    /// analyse again, and these contain the locations of the definitions they
+    /// import, so changes to those locations must be detected too.
+    locations: bool,
+    /// The fingerprints of the module interfaces computed so far in this
+    /// compilation.
+    fingerprints: HashMap<EcoString, InterfaceFingerprint>,
+}
+
+impl<IO> AnalysisCache<'_, IO>
+where
+    IO: FileSystemReader,
+{
+    fn fingerprint(
+        &mut self,
+        name: &EcoString,
+        module_types: &im::HashMap<EcoString, type_::ModuleInterface>,
+    ) -> Result<Option<InterfaceFingerprint>> {
+        if let Some(fingerprint) = self.fingerprints.get(name) {
+            return Ok(Some(*fingerprint));
+        }
+        let Some(interface) = module_types.get(name) else {
+            return Ok(None);
+        };
+        let fingerprint = InterfaceFingerprint::new(interface, self.locations)?;
+        let _ = self.fingerprints.insert(name.clone(), fingerprint);
+        Ok(Some(fingerprint))
+    }
+
+    fn dependency_interfaces(
+        &mut self,
+        dependencies: &[EcoString],
+        module_types: &im::HashMap<EcoString, type_::ModuleInterface>,
+    ) -> Result<Vec<(EcoString, InterfaceFingerprint)>> {
+        let mut interfaces = Vec::with_capacity(dependencies.len());
+        for name in dependencies {
+            if let Some(fingerprint) = self.fingerprint(name, module_types)? {
+                interfaces.push((name.clone(), fingerprint));
+            }
+        }
+        Ok(interfaces)
+    }
+
+    /// Returns the cached interface of the module if the interfaces of its
+    /// dependencies are the same as when it was last analysed.
+    fn reusable_interface(
+        &mut self,
+        module: &UncompiledModule,
+        module_types: &im::HashMap<EcoString, type_::ModuleInterface>,
+    ) -> Result<Option<type_::ModuleInterface>> {
+        let Some(dependency_interfaces) = &module.dependency_interfaces else {
+            return Ok(None);
+        };
+        for (name, fingerprint) in dependency_interfaces {
+            if self.fingerprint(name, module_types)? != Some(*fingerprint) {
+                return Ok(None);
+            }
+        }
+        let path = self
+            .artefact_directory
+            .join(module.name.replace("/", "@").as_ref())
+            .with_extension("cache");
+        let bytes = self.io.read_bytes(&path)?;
+        let mut interface = ModuleDecoder::new(self.ids.clone()).read(bytes.as_slice())?;
+        // The origin is not kept in the cache, so it is taken from the
+        // directory the module was found in.
+        interface.origin = module.origin;
+        Ok(Some(interface))
+    }
 }
 
 #[derive(Template)]
@@ -17,15 +17,19 @@ use crate::{
     build::{module_loader::ModuleLoader, package_compiler::module_name, Module, Origin},
     config::PackageConfig,
     dep_tree,
-    error::{FileIoAction, FileKind},
+    error::{CycleImport, FileIoAction, FileKind},
     io::{CommandExecutor, FileSystemReader, FileSystemWriter},
-    metadata, type_,
+    line_numbers::LineNumbers,
+    metadata,
+    parse::incremental::ParseCache,
+    type_,
     uid::UniqueIdGenerator,
     warning::WarningEmitter,
     Error, Result,
 };
 
 use super::{
+    features::PackageFeatures,
     module_loader::read_source,
     package_compiler::{CacheMetadata, CachedModule, Input, Loaded, UncompiledModule},
     Mode, Target,
@@ -61,6 +65,11 @@ pub struct PackageLoader<'a, IO> {
     stale_modules: &'a mut StaleTracker,
     already_defined_modules: &'a mut im::HashMap<EcoString, Utf8PathBuf>,
     incomplete_modules: &'a HashSet<EcoString>,
+    parse_cache: Option<&'a ParseCache>,
+    features: &'a PackageFeatures,
+    /// The directory of modules generated for the package, such as the
+    /// module of environment variables.
+    generated_directory: &'a Utf8Path,
 }
 
 impl<'a, IO> PackageLoader<'a, IO>
@@ -80,6 +89,9 @@ where
         stale_modules: &'a mut StaleTracker,
         already_defined_modules: &'a mut im::HashMap<EcoString, Utf8PathBuf>,
         incomplete_modules: &'a HashSet<EcoString>,
+        parse_cache: Option<&'a ParseCache>,
+        features: &'a PackageFeatures,
+        generated_directory: &'a Utf8Path,
     ) -> Self {
         Self {
             io,
@@ -94,6 +106,9 @@ where
             stale_modules,
             already_defined_modules,
             incomplete_modules,
+            parse_cache,
+            features,
+            generated_directory,
         }
     }
 
@@ -113,12 +128,28 @@ where
             // mostly to aid debugging.
             .sorted_by(|(a, _), (b, _)| a.cmp(b))
             .collect();
-        let sequence = dep_tree::toposort_deps(deps).map_err(convert_deps_tree_error)?;
+        let imports: HashMap<_, _> = inputs
+            .values()
+            .map(|input| (input.name().clone(), input.dependencies()))
+            .collect();
+        // Test modules can't be imported by src modules, so they are not part
+        // of the JavaScript chunks that src modules are split into.
+        let src_imports = inputs
+            .values()
+            .filter(|input| input.origin() == Origin::Src)
+            .map(|input| (input.name().clone(), input.dependencies()))
+            .collect();
+        let sequence = dep_tree::toposort_deps(deps)
+            .map_err(|error| self.convert_deps_tree_error(error, &inputs))?;
 
         // Now that we have loaded sources and caches we check to see if any of
         // the caches need to be invalidated because their dependencies have
         // changed.
-        let mut loaded = Loaded::default();
+        let mut loaded = Loaded {
+            imports,
+            src_imports,
+            ..Loaded::default()
+        };
         for name in sequence {
             let input = inputs
                 .remove(&name)
@@ -161,7 +192,11 @@ where
             .join(info.name.replace("/", "@").as_ref())
             .with_extension("cache");
         let bytes = self.io.read_bytes(&path)?;
-        metadata::ModuleDecoder::new(self.ids.clone()).read(bytes.as_slice())
+        let mut module = metadata::ModuleDecoder::new(self.ids.clone()).read(bytes.as_slice())?;
+        // The origin is not kept in the cache, so it is taken from the
+        // directory the module was found in.
+        module.origin = info.origin;
+        Ok(module)
     }
 
     pub fn is_gleam_path(&self, path: &Utf8Path, dir: &Utf8Path) -> bool {
@@ -184,6 +219,62 @@ where
         )
     }
 
+    fn convert_deps_tree_error(
+        &self,
+        error: dep_tree::Error,
+        inputs: &HashMap<EcoString, Input>,
+    ) -> Error {
+        match error {
+            dep_tree::Error::Cycle(mut modules) => {
+                // Each module of the cycle is imported by the one after it,
+                // so they are reversed to have each one import the next.
+                modules.reverse();
+                let imports = modules
+                    .iter()
+                    .zip(modules.iter().cycle().skip(1))
+                    .filter_map(|(module, imported)| {
+                        self.cycle_import(inputs.get(module)?, imported)
+                    })
+                    .collect();
+                Error::ImportCycle { modules, imports }
+            }
+        }
+    }
+
+    /// Finds the statement a module in an import cycle imports the next one
+    /// with. Modules loaded from the cache are parsed again to find it.
+    fn cycle_import(&self, input: &Input, imported: &EcoString) -> Option<CycleImport> {
+        let (code, location) = match input {
+            Input::New(module) => {
+                let (_, location) = module
+                    .dependencies
+                    .iter()
+                    .find(|(name, _)| name == imported)?;
+                (module.code.clone(), *location)
+            }
+            Input::Cached(module) => {
+                let code = EcoString::from(self.io.read(&module.source_path).ok()?);
+                let parsed = crate::parse::parse_module(&code).ok()?;
+                let (_, location) = parsed
+                    .module
+                    .dependencies(self.target)
+                    .into_iter()
+                    .find(|(name, _)| name == imported)?;
+                (code, location)
+            }
+        };
+        let statement = code
+            .get(location.start as usize..location.end as usize)?
+            .lines()
+            .next()?;
+        let path = input.source_path();
+        Some(CycleImport {
+            path: path.strip_prefix(self.root).unwrap_or(path).to_path_buf(),
+            line: LineNumbers::new(&code).line_number(location.start),
+            statement: statement.into(),
+        })
+    }
+
     fn read_sources_and_caches(&self) -> Result<HashMap<EcoString, Input>> {
         let span = tracing::info_span!("load");
         let _enter = span.enter();
@@ -201,7 +292,9 @@ where
             artefact_directory: self.artefact_directory,
             source_directory: &src,
             origin: Origin::Src,
+            parse_cache: self.parse_cache,
             incomplete_modules: self.incomplete_modules,
+            features: self.features,
         };
 
         // Src
@@ -216,6 +309,19 @@ where
                 continue;
             }
 
+            // Modules that belong to features that are not enabled are not
+            // compiled.
+            if !self.features.includes_module(&module_name(&src, &path)) {
+                continue;
+            }
+
+            let input = loader.load(path)?;
+            inputs.insert(input)?;
+        }
+
+        // Generated
+        loader.source_directory = self.generated_directory;
+        for path in self.io.gleam_source_files(self.generated_directory) {
             let input = loader.load(path)?;
             inputs.insert(input)?;
         }
@@ -231,6 +337,9 @@ where
                     self.warnings.emit(crate::Warning::InvalidSource { path });
                     continue;
                 }
+                if !self.features.includes_module(&module_name(&test, &path)) {
+                    continue;
+                }
                 let input = loader.load(path)?;
                 inputs.insert(input)?;
             }
@@ -244,7 +353,11 @@ where
         // This would most commonly happen for modules like "user" and
         // "code". Emit an error so this never happens.
         if self.target.is_erlang() {
-            for input in inputs.collection.values() {
+            let inputs = inputs
+                .collection
+                .values()
+                .sorted_by(|a, b| a.name().cmp(b.name()));
+            for input in inputs {
                 ensure_gleam_module_does_not_overwrite_standard_erlang_module(&input)?;
             }
         }
@@ -254,15 +367,19 @@ where
 
     fn load_and_parse(&self, cached: CachedModule) -> Result<UncompiledModule> {
         let mtime = self.io.modification_time(&cached.source_path)?;
-        read_source(
+        let mut module = read_source(
             self.io.clone(),
+            self.parse_cache,
+            self.features,
             self.target,
             cached.origin,
             cached.source_path,
             cached.name,
             self.package_name.clone(),
             mtime,
-        )
+        )?;
+        module.dependency_interfaces = Some(cached.dependency_interfaces);
+        Ok(module)
     }
 }
 
@@ -1547,12 +1664,6 @@ fn ensure_gleam_module_does_not_overwrite_standard_erlang_module(input: &Input)
     })
 }
 
-fn convert_deps_tree_error(e: dep_tree::Error) -> Error {
-    match e {
-        dep_tree::Error::Cycle(modules) => Error::ImportCycle { modules },
-    }
-}
-
 #[derive(Debug, Default)]
 pub struct StaleTracker(HashSet<EcoString>);
 
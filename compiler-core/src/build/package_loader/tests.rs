@@ -16,6 +16,7 @@ use std::time::Duration;
 struct LoaderTestOutput {
     to_compile: Vec<EcoString>,
     cached: Vec<EcoString>,
+    cached_origins: Vec<Origin>,
     warnings: Vec<Warning>,
 }
 
@@ -35,6 +36,7 @@ fn write_cache(fs: &InMemoryFileSystem, name: &str, seconds: u64, deps: Vec<EcoS
         mtime,
         codegen_performed: true,
         dependencies: deps,
+        dependency_interfaces: vec![],
         fingerprint: SourceFingerprint::new(src),
         line_numbers: line_numbers.clone(),
     };
@@ -81,11 +83,15 @@ fn run_loader(fs: InMemoryFileSystem, root: &Utf8Path, artefact: &Utf8Path) -> L
         stale_modules: &mut StaleTracker::default(),
         already_defined_modules: &mut defined,
         incomplete_modules: &mut HashSet::new(),
+        parse_cache: None,
+        features: &PackageFeatures::default(),
+        generated_directory: Utf8Path::new("/generated"),
     };
     let loaded = loader.run().unwrap();
 
     LoaderTestOutput {
         to_compile: loaded.to_compile.into_iter().map(|m| m.name).collect(),
+        cached_origins: loaded.cached.iter().map(|m| m.origin).collect(),
         cached: loaded.cached.into_iter().map(|m| m.name).collect(),
         warnings: warnings.take(),
     }
@@ -164,6 +170,21 @@ fn reading_cache() {
     assert_eq!(loaded.cached, vec![EcoString::from("one")]);
 }
 
+#[test]
+fn reading_cache_of_test_module() {
+    let fs = InMemoryFileSystem::new();
+    let root = Utf8Path::new("/");
+    let artefact = Utf8Path::new("/artefact");
+
+    write_src(&fs, "/test/one_test.gleam", 0, TEST_SOURCE_1);
+    write_cache(&fs, "one_test", 0, vec![], TEST_SOURCE_1);
+
+    let loaded = run_loader(fs, root, artefact);
+    assert!(loaded.to_compile.is_empty());
+    assert_eq!(loaded.cached, vec![EcoString::from("one_test")]);
+    assert_eq!(loaded.cached_origins, vec![Origin::Test]);
+}
+
 #[test]
 fn module_is_stale_if_cache_older() {
     let fs = InMemoryFileSystem::new();
@@ -1,16 +1,27 @@
 use crate::{
     analyse::TargetSupport,
     build::{
-        package_compiler, package_compiler::PackageCompiler, package_loader::StaleTracker,
-        project_compiler, telemetry::Telemetry, Mode, Module, Origin, Package, Target,
+        env_module,
+        features::{self, EnabledFeatures},
+        package_compiler,
+        package_compiler::PackageCompiler,
+        package_loader::StaleTracker,
+        project_compiler,
+        telemetry::Telemetry,
+        JavaScriptPlatform, Mode, Module, Origin, Package, Target,
+    },
+    codegen::{
+        self,
+        banner::{self, Verification},
+        ErlangApp,
     },
-    codegen::{self, ErlangApp},
     config::PackageConfig,
     dep_tree,
     error::{FileIoAction, FileKind},
     io::{CommandExecutor, FileSystemReader, FileSystemWriter, Stdio},
     manifest::{ManifestPackage, ManifestPackageSource},
     metadata,
+    parse::incremental::ParseCache,
     paths::{self, ProjectPaths},
     type_::{self, ModuleFunction},
     uid::UniqueIdGenerator,
@@ -50,6 +61,9 @@ pub struct Options {
     pub target: Option<Target>,
     pub codegen: Codegen,
     pub warnings_as_errors: bool,
+    /// Whether to warn about generated files in the build directory that
+    /// have been edited since they were generated.
+    pub verify_artefacts: bool,
     pub root_target_support: TargetSupport,
 }
 
@@ -74,6 +88,19 @@ impl Built {
             }),
         }
     }
+
+    /// The dependency modules compiled by this build, if the compiler was set
+    /// to keep them. Modules loaded from the build cache are not included, as
+    /// their ASTs are not kept.
+    pub fn compiled_dependency_modules(&self) -> &[Module] {
+        &self.compiled_dependency_modules
+    }
+
+    /// The interfaces of every module that can be imported by the root
+    /// package, including those of its dependencies.
+    pub fn module_interfaces(&self) -> impl Iterator<Item = &type_::ModuleInterface> {
+        self.module_interfaces.values()
+    }
 }
 
 #[derive(Debug)]
@@ -87,6 +114,15 @@ pub struct ProjectCompiler<IO> {
     /// The set of modules that have had partial compilation done since the last
     /// successful compilation.
     incomplete_modules: HashSet<EcoString>,
+    /// The parse trees of the root package's modules, kept between
+    /// compilations in the language server so that only the definitions that
+    /// changed are parsed again.
+    parse_cache: ParseCache,
+    /// The features enabled for each package, unified across the project.
+    enabled_features: EnabledFeatures,
+    /// Whether the dependency packages have been compiled, so that a compiler
+    /// kept between builds does not compile or load them again.
+    dependencies_compiled: bool,
     warnings: WarningEmitter,
     telemetry: Box<dyn Telemetry>,
     options: Options,
@@ -96,6 +132,16 @@ pub struct ProjectCompiler<IO> {
     /// We may want to silence subprocess stdout if we are running in LSP mode.
     /// The language server talks over stdio so printing would break that.
     pub subprocess_stdio: Stdio,
+    /// The environment variables of the build, which packages can embed
+    /// with the `[env]` section of their gleam.toml.
+    pub environment: HashMap<String, String>,
+    /// Whether the modules of the dependency packages are kept once compiled,
+    /// so that they can be returned along with their typed ASTs. Only their
+    /// interfaces are needed to compile the packages that depend on them, so
+    /// by default each package's modules are dropped once its code has been
+    /// generated, keeping the memory used on large projects from growing with
+    /// the number of packages.
+    pub keep_dependency_modules: bool,
 }
 
 // TODO: test that tests cannot be imported into src
@@ -124,9 +170,14 @@ where
             defined_modules: im::HashMap::new(),
             stale_modules: StaleTracker::default(),
             incomplete_modules: HashSet::new(),
+            parse_cache: ParseCache::default(),
+            enabled_features: EnabledFeatures::new(),
+            dependencies_compiled: false,
             ids: UniqueIdGenerator::new(),
             warnings: WarningEmitter::new(warning_emitter),
             subprocess_stdio: Stdio::Inherit,
+            environment: HashMap::new(),
+            keep_dependency_modules: false,
             telemetry,
             packages,
             options,
@@ -140,6 +191,12 @@ where
         &self.importable_modules
     }
 
+    /// The generator of the ids of the types analysed by the compiler, which
+    /// must also be used to analyse code that uses those types.
+    pub fn ids(&self) -> &UniqueIdGenerator {
+        &self.ids
+    }
+
     pub fn mode(&self) -> Mode {
         self.options.mode
     }
@@ -151,6 +208,28 @@ where
     /// Compiles all packages in the project and returns the compiled
     /// information from the root package
     pub fn compile(mut self) -> Result<Built> {
+        let (root_package, compiled_dependency_modules) = self.compile_project()?;
+        Ok(Built {
+            root_package,
+            module_interfaces: self.importable_modules,
+            compiled_dependency_modules,
+        })
+    }
+
+    /// Compiles the root package again, reusing the dependency packages
+    /// compiled by an earlier call. This is used by `gleam daemon`, which
+    /// keeps a compiler between builds so the module interfaces of the
+    /// dependencies are not loaded again for each one.
+    ///
+    /// The dependencies are not checked for changes, so a new compiler must
+    /// be made if the manifest changes.
+    ///
+    pub fn recompile(&mut self) -> Result<Package> {
+        self.compile_project()
+            .map(|(root_package, _dependencies)| root_package)
+    }
+
+    fn compile_project(&mut self) -> Result<(Package, Vec<Module>)> {
         // We make sure the stale module tracker is empty before we start, to
         // avoid mistakenly thinking a module is stale due to outdated state
         // from a previous build. A ProjectCompiler instance is re-used by the
@@ -161,11 +240,22 @@ where
         // verify that this version is appropriate.
         self.check_gleam_version()?;
 
+        // Generated files that have been edited are checked for before they
+        // are regenerated.
+        if self.options.verify_artefacts {
+            self.verify_artefacts()?;
+        }
+
         // The JavaScript target requires a prelude module to be written.
         self.write_prelude()?;
 
         // Dependencies are compiled first.
-        let compiled_dependency_modules = self.compile_dependencies()?;
+        let compiled_dependency_modules = if self.dependencies_compiled {
+            vec![]
+        } else {
+            self.compile_dependencies()?
+        };
+        self.dependencies_compiled = true;
 
         // We reset the warning count as we don't want to fail the build if a
         // dependency has warnings, only if the root package does.
@@ -180,11 +270,60 @@ where
             });
         }
 
-        Ok(Built {
-            root_package,
-            module_interfaces: self.importable_modules,
-            compiled_dependency_modules,
-        })
+        Ok((root_package, compiled_dependency_modules))
+    }
+
+    /// Warns about the generated Erlang and JavaScript files of the build
+    /// directory for the target that have been edited since they were
+    /// generated, according to the banner at the top of each.
+    fn verify_artefacts(&self) -> Result<()> {
+        let build_directory = self
+            .paths
+            .build_directory_for_target(self.mode(), self.target());
+        let mut modified = vec![];
+        let mut directories = vec![build_directory];
+        while let Some(directory) = directories.pop() {
+            if !self.io.is_directory(&directory) {
+                continue;
+            }
+            for entry in self.io.read_dir(&directory)? {
+                let path = entry
+                    .map_err(|e| Error::FileIo {
+                        action: FileIoAction::Read,
+                        kind: FileKind::Directory,
+                        path: directory.clone(),
+                        err: Some(e.to_string()),
+                    })?
+                    .into_path();
+                if self.io.is_directory(&path) {
+                    directories.push(path);
+                    continue;
+                }
+                let generated = [".erl", ".hrl", ".mjs", ".d.mts"]
+                    .iter()
+                    .any(|extension| path.as_str().ends_with(extension));
+                if !generated {
+                    continue;
+                }
+                let bytes = self.io.read_bytes(&path)?;
+                let Ok(contents) = std::str::from_utf8(&bytes) else {
+                    continue;
+                };
+                if banner::verify(contents) == Verification::Modified {
+                    let path = path
+                        .strip_prefix(self.paths.root())
+                        .map(Utf8Path::to_path_buf)
+                        .unwrap_or(path);
+                    modified.push(path);
+                }
+            }
+        }
+        if !modified.is_empty() {
+            modified.sort();
+            self.warnings
+                .emit(Warning::ModifiedArtefacts { files: modified });
+        }
+        Ok(())
     }
 
     pub fn compile_root_package(&mut self) -> Outcome<Package, Error> {
@@ -202,9 +341,18 @@ where
             .paths
             .build_directory_for_target(self.mode(), self.target());
         let version_path = self.paths.build_gleam_version(self.mode(), self.target());
+        // How the Erlang is laid out is recorded with the version so that
+        // changing it regenerates the cached modules too.
+        let mut build_version = COMPILER_VERSION.to_string();
+        if self.config.erlang.readable_output {
+            build_version.push_str(" readable-erlang");
+        }
+        if self.config.erlang.erlfmt_layout {
+            build_version.push_str(" erlfmt-layout");
+        }
         if self.io.is_file(&version_path) {
             let version = self.io.read(&version_path)?;
-            if version == COMPILER_VERSION {
+            if version == build_version {
                 return Ok(());
             }
         }
@@ -216,7 +364,7 @@ where
         // Recreate build directory with new updated version file
         self.io.mkdir(&build_path)?;
         self.io
-            .write(&version_path, COMPILER_VERSION)
+            .write(&version_path, &build_version)
             .map_err(|e| Error::FileIo {
                 action: FileIoAction::WriteTo,
                 kind: FileKind::File,
@@ -226,12 +374,15 @@ where
     }
 
     pub fn compile_dependencies(&mut self) -> Result<Vec<Module>, Error> {
+        self.resolve_features()?;
         let sequence = order_packages(&self.packages)?;
         let mut modules = vec![];
 
         for name in sequence {
             let compiled = self.load_cache_or_compile_package(&name)?;
-            modules.extend(compiled);
+            if self.keep_dependency_modules {
+                modules.extend(compiled);
+            }
         }
 
         Ok(modules)
@@ -253,12 +404,47 @@ where
             self.io.write(&path, crate::javascript::PRELUDE)?;
         }
 
+        // Write the crash formatter used by `gleam run` and `gleam test`
+        let path = build.join("crash.mjs");
+        if !self.io.is_file(&path) {
+            self.io.write(&path, crate::javascript::CRASH)?;
+        }
+
+        // Write the test runner used by `gleam test --shard` and `--parallel`
+        let path = build.join("test_runner.mjs");
+        if !self.io.is_file(&path) {
+            self.io
+                .write(&path, crate::test_runner::JAVASCRIPT_MODULE)?;
+        }
+
+        // Write the encoder of values used by tooling such as `gleam kernel`
+        let path = build.join("inspect.mjs");
+        if !self.io.is_file(&path) {
+            self.io.write(&path, crate::javascript::INSPECT)?;
+        }
+
+        // Write the shims for the platform the project is written for,
+        // replacing those for any other platform
+        let platform = self
+            .config
+            .javascript
+            .platform
+            .unwrap_or(JavaScriptPlatform::Neutral);
+        let path = build.join("platform.mjs");
+        if self.io.read(&path).ok().as_deref() != Some(platform.shims()) {
+            self.io.write(&path, platform.shims())?;
+        }
+
         // Write the TypeScript prelude, if asked for
         if self.config.javascript.typescript_declarations {
             let path = build.join("prelude.d.mts");
             if !self.io.is_file(&path) {
                 self.io.write(&path, crate::javascript::PRELUDE_TS_DEF)?;
             }
+            let path = build.join("platform.d.mts");
+            if !self.io.is_file(&path) {
+                self.io.write(&path, crate::javascript::PLATFORM_TS_DEF)?;
+            }
         }
 
         Ok(())
@@ -331,7 +517,7 @@ where
         // Print that work is being done
         self.telemetry.compiling_package(package_name);
 
-        let package = self.paths.build_packages_package(package_name);
+        let package = self.paths.build_packages_package_source(package);
         let build_packages = self.paths.build_directory_for_target(mode, target);
         let ebins = self.paths.build_packages_ebins_glob(mode, target);
         let rebar3_path = |path: &Utf8Path| format!("../{}", path);
@@ -340,18 +526,8 @@ where
         self.io.mkdir(&package_build)?;
         self.io.copy_dir(&package, &package_build)?;
 
-        let env = [
-            ("ERL_LIBS", "../*/ebin".into()),
-            ("REBAR_BARE_COMPILER_OUTPUT_DIR", "./".into()),
-            ("REBAR_PROFILE", "prod".into()),
-            ("TERM", "dumb".into()),
-        ];
-        let args = [
-            "bare".into(),
-            "compile".into(),
-            "--paths".into(),
-            "../*/ebin".into(),
-        ];
+        let env = rebar3_environment();
+        let args = rebar3_arguments();
         let status = self.io.exec(
             REBAR_EXECUTABLE,
             &args,
@@ -381,6 +557,21 @@ where
             .paths
             .build_directory_for_package(mode, target, application_name);
 
+        // Earlier versions linked the compiled application to Mix's build
+        // directory inside the package's source. The link is removed so the
+        // application is compiled and copied into the build directory instead.
+        let build_dir = self.paths.build_directory_for_target(mode, target);
+        if self.io.is_directory(&dest)
+            && self.io.canonicalise(&dest)?
+                != self
+                    .io
+                    .canonicalise(&build_dir)?
+                    .join(application_name.as_str())
+        {
+            tracing::debug!(%package_name, "removing_linked_mix_package");
+            self.io.delete_directory(&dest)?;
+        }
+
         // TODO: test
         if self.io.is_directory(&dest) {
             tracing::debug!(%package_name, "using_precompiled_mix_package");
@@ -402,10 +593,10 @@ where
         // Print that work is being done
         self.telemetry.compiling_package(package_name);
 
-        let build_dir = self.paths.build_directory_for_target(mode, target);
-        let project_dir = self.paths.build_packages_package(package_name);
+        let project_dir = self.paths.build_packages_package_source(package);
         let mix_build_dir = project_dir.join("_build").join(mix_target);
         let mix_build_lib_dir = mix_build_dir.join("lib");
+        let mix_home = self.paths.build_mix_home();
         let up = paths::unnest(&project_dir);
         let mix_path = |path: &Utf8Path| up.join(path).to_string();
         let ebins = self.paths.build_packages_ebins_glob(mode, target);
@@ -413,6 +604,10 @@ where
         // Elixir core libs must be loaded
         ElixirLibraries::make_available(&self.io, &build_dir, self.subprocess_stdio)?;
 
+        // Mix and Hex get a home of their own so that anything the user has
+        // installed globally cannot change how the package is compiled
+        self.io.mkdir(&mix_home)?;
+
         // Prevent Mix.Compilers.ApplicationTracer warnings
         // mix would make this if it didn't exist, but we make it anyway as
         // we need to link the compiled dependencies into there
@@ -428,22 +623,8 @@ where
             }
         }
 
-        let env = [
-            ("MIX_BUILD_PATH", mix_path(&mix_build_dir)),
-            ("MIX_ENV", mix_target.into()),
-            ("MIX_QUIET", "1".into()),
-            ("TERM", "dumb".into()),
-        ];
-        let args = [
-            "-pa".into(),
-            mix_path(&ebins),
-            "-S".into(),
-            "mix".into(),
-            "compile".into(),
-            "--no-deps-check".into(),
-            "--no-load-deps".into(),
-            "--no-protocol-consolidation".into(),
-        ];
+        let env = mix_environment(mix_target, mix_path(&mix_build_dir), mix_path(&mix_home));
+        let args = mix_arguments(mix_path(&ebins));
         let status = self.io.exec(
             ELIXIR_EXECUTABLE,
             &args,
@@ -453,11 +634,19 @@ where
         )?;
 
         if status == 0 {
+            // Copy the compiled application into the build directory so it
+            // does not depend on Mix's build directory, which is deleted when
+            // the package is downloaded again.
             // TODO: unit test
-            let source = mix_build_dir.join("lib").join(application_name.as_str());
-            if self.io.is_directory(&source) && !self.io.is_directory(&dest) {
-                tracing::debug!("linking_{}_to_build", application_name);
-                self.io.symlink_dir(&source, &dest)?;
+            let source = mix_build_lib_dir.join(application_name.as_str());
+            tracing::debug!("copying_{}_to_build", application_name);
+            for directory in ["ebin", "priv", "include"] {
+                let source = source.join(directory);
+                if self.io.is_directory(&source) {
+                    let dest = dest.join(directory);
+                    self.io.mkdir(&dest)?;
+                    self.io.copy_dir(&source, &dest)?;
+                }
             }
             Ok(())
         } else {
@@ -468,12 +657,24 @@ where
         }
     }
 
-    fn compile_gleam_dep_package(
-        &mut self,
-        package: &ManifestPackage,
-    ) -> Result<Vec<Module>, Error> {
+    /// Works out the features of each package to enable, which depend on the
+    /// configs of all the Gleam packages in the project.
+    fn resolve_features(&mut self) -> Result<()> {
+        let mut configs = HashMap::new();
+        for package in self.packages.values() {
+            if package.build_tools.iter().any(|tool| tool == "gleam") {
+                let config_path = self.package_root(package)?.join("gleam.toml");
+                let config = PackageConfig::read(config_path, &self.io)?;
+                let _ = configs.insert(package.name.clone(), config);
+            }
+        }
+        self.enabled_features = features::resolve(&self.config, self.mode(), &configs)?;
+        Ok(())
+    }
+
+    fn package_root(&self, package: &ManifestPackage) -> Result<Utf8PathBuf> {
         // TODO: Test
-        let package_root = match &package.source {
+        Ok(match &package.source {
             // If the path is relative it is relative to the root of the
             // project, not to the current working directory. The language server
             // could have the working directory and the project root in different
@@ -488,15 +689,44 @@ where
             // Hex and Git packages are downloaded into the project's build
             // directory.
             ManifestPackageSource::Git { .. } | ManifestPackageSource::Hex { .. } => {
-                self.paths.build_packages_package(&package.name)
+                self.paths.build_packages_package_source(package)
             }
-        };
+        })
+    }
+
+    fn compile_gleam_dep_package(
+        &mut self,
+        package: &ManifestPackage,
+    ) -> Result<Vec<Module>, Error> {
+        let package_root = self.package_root(package)?;
         let config_path = package_root.join("gleam.toml");
         let config = PackageConfig::read(config_path, &self.io)?;
+        self.check_javascript_platform(&config);
         self.compile_gleam_package(&config, false, package_root)
             .into_result()
     }
 
+    /// Warns if a dependency is written for a different JavaScript platform
+    /// than the project.
+    fn check_javascript_platform(&self, dependency: &PackageConfig) {
+        if !self.target().is_javascript() {
+            return;
+        }
+        let (Some(platform), Some(dependency_platform)) = (
+            self.config.javascript.platform,
+            dependency.javascript.platform,
+        ) else {
+            return;
+        };
+        if !dependency_platform.is_usable_on(platform) {
+            self.warnings.emit(Warning::JavaScriptPlatformMismatch {
+                package: dependency.name.clone(),
+                package_platform: dependency_platform,
+                platform,
+            });
+        }
+    }
+
     fn compile_gleam_package(
         &mut self,
         config: &PackageConfig,
@@ -520,10 +750,16 @@ where
                         Some((p.name.clone(), overriden.clone()))
                     })
                     .collect();
+                let project_applications = self
+                    .packages
+                    .values()
+                    .map(|p| p.application_name().clone())
+                    .collect();
                 super::TargetCodegenConfiguration::Erlang {
                     app_file: Some(ErlangAppCodegenConfiguration {
                         include_dev_deps: is_root,
                         package_name_overrides,
+                        project_applications,
                     }),
                 }
             }
@@ -532,6 +768,7 @@ where
                 emit_typescript_definitions: self.config.javascript.typescript_declarations,
                 // This path is relative to each package output directory
                 prelude_location: Utf8PathBuf::from("../prelude.mjs"),
+                platform_location: Some(Utf8PathBuf::from("../platform.mjs")),
             },
         };
 
@@ -547,9 +784,25 @@ where
         );
         compiler.write_metadata = true;
         compiler.write_entrypoint = is_root;
+        compiler.check_other_target = is_root;
+        compiler.check_externals = is_root;
+        compiler.environment = env_module::variables(config, &self.environment);
+        compiler.enabled_features = self
+            .enabled_features
+            .get(&config.name)
+            .cloned()
+            .unwrap_or_default();
         compiler.perform_codegen = self.options.codegen.should_codegen(is_root);
         compiler.compile_beam_bytecode = self.options.codegen.should_codegen(is_root);
         compiler.subprocess_stdio = self.subprocess_stdio;
+        compiler.readable_erlang = self.config.erlang.readable_output;
+        compiler.erlfmt_layout = self.config.erlang.erlfmt_layout;
+        if is_root && mode != Mode::Lsp && compiler.perform_codegen {
+            compiler.artefacts_manifest = Some(self.paths.build_artefacts_manifest());
+        }
+        if mode == Mode::Lsp {
+            compiler.parse_cache = Some(self.parse_cache.clone());
+        }
         compiler.target_support = if is_root {
             // When compiling the root package it is context specific as to whether we need to
             // enforce that all functions have an implementation for the current target.
@@ -607,6 +860,61 @@ fn convert_deps_tree_error(e: dep_tree::Error) -> Error {
     }
 }
 
+/// The environment variables rebar3 is run with when compiling a dependency
+/// that has been copied into the build directory.
+pub(crate) fn rebar3_environment() -> Vec<(&'static str, String)> {
+    vec![
+        ("ERL_LIBS", "../*/ebin".into()),
+        ("REBAR_BARE_COMPILER_OUTPUT_DIR", "./".into()),
+        ("REBAR_PROFILE", "prod".into()),
+        ("TERM", "dumb".into()),
+    ]
+}
+
+pub(crate) fn rebar3_arguments() -> Vec<String> {
+    vec![
+        "bare".into(),
+        "compile".into(),
+        "--paths".into(),
+        "../*/ebin".into(),
+    ]
+}
+
+pub(crate) fn mix_arguments(ebins: String) -> Vec<String> {
+    vec![
+        "-pa".into(),
+        ebins,
+        "-S".into(),
+        "mix".into(),
+        "compile".into(),
+        "--no-deps-check".into(),
+        "--no-load-deps".into(),
+        "--no-protocol-consolidation".into(),
+    ]
+}
+
+/// The environment variables Mix is run with when compiling a dependency.
+///
+/// `MIX_HOME` and `HEX_HOME` point to a directory in the build directory so
+/// that archives and configuration from the user's global Mix installation are
+/// not used, and Hex is put into offline mode as the dependencies of the
+/// package have already been downloaded and compiled by Gleam.
+pub(crate) fn mix_environment(
+    mix_target: &str,
+    mix_build_path: String,
+    mix_home: String,
+) -> Vec<(&'static str, String)> {
+    vec![
+        ("MIX_BUILD_PATH", mix_build_path),
+        ("MIX_ENV", mix_target.into()),
+        ("MIX_HOME", mix_home.clone()),
+        ("MIX_QUIET", "1".into()),
+        ("HEX_HOME", mix_home),
+        ("HEX_OFFLINE", "1".into()),
+        ("TERM", "dumb".into()),
+    ]
+}
+
 #[derive(Debug, PartialEq, Clone, Copy)]
 pub(crate) enum BuildTool {
     Gleam,
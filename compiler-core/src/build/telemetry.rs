@@ -3,13 +3,25 @@ use std::{
     time::{Duration, Instant},
 };
 
-use crate::Warning;
+use crate::{
+    dependency::{Resolution, ResolutionObserver},
+    Warning,
+};
 
-pub trait Telemetry: Debug {
+/// Told about what the build tool is doing, so it can be shown to the user.
+/// It is also told about each step taken while resolving versions.
+pub trait Telemetry: Debug + ResolutionObserver {
     fn waiting_for_build_directory_lock(&self);
     fn resolving_package_versions(&self);
+    /// Called once versions have been resolved, with the time resolution
+    /// started.
+    fn package_versions_resolved(&self, start: Instant, resolution: &Resolution);
     fn downloading_package(&self, name: &str);
-    fn packages_downloaded(&self, start: Instant, count: usize);
+    /// Called as the tarball of a package is downloaded, with the number of
+    /// bytes received so far and the size of the tarball if it is known.
+    fn package_download_progress(&self, name: &str, downloaded: u64, total: Option<u64>);
+    fn package_downloaded(&self, name: &str, size: u64);
+    fn packages_downloaded(&self, start: Instant, count: usize, size: u64);
     fn compiling_package(&self, name: &str);
     fn checking_package(&self, name: &str);
 }
@@ -17,11 +29,16 @@ pub trait Telemetry: Debug {
 #[derive(Debug, Clone, Copy)]
 pub struct NullTelemetry;
 
+impl ResolutionObserver for NullTelemetry {}
+
 impl Telemetry for NullTelemetry {
     fn waiting_for_build_directory_lock(&self) {}
     fn resolving_package_versions(&self) {}
+    fn package_versions_resolved(&self, _start: Instant, _resolution: &Resolution) {}
     fn downloading_package(&self, _name: &str) {}
     fn compiling_package(&self, _name: &str) {}
     fn checking_package(&self, _name: &str) {}
-    fn packages_downloaded(&self, _start: Instant, _count: usize) {}
+    fn package_download_progress(&self, _name: &str, _downloaded: u64, _total: Option<u64>) {}
+    fn package_downloaded(&self, _name: &str, _size: u64) {}
+    fn packages_downloaded(&self, _start: Instant, _count: usize, _size: u64) {}
 }
@@ -1,6 +1,6 @@
 use crate::{manifest::ManifestPackage, Error};
 
-use super::project_compiler::{usable_build_tools, BuildTool};
+use super::project_compiler::{mix_environment, usable_build_tools, BuildTool};
 
 #[test]
 fn usable_build_tool_unknown() {
@@ -55,3 +55,30 @@ fn usable_build_tool_mix_then_rebar3() {
         Ok(vec![BuildTool::Mix, BuildTool::Rebar3])
     )
 }
+
+#[test]
+fn mix_environment_is_isolated_from_global_installation() {
+    assert_eq!(
+        mix_environment("prod", "../../_build/prod".into(), "../../.mix".into()),
+        vec![
+            ("MIX_BUILD_PATH", "../../_build/prod".into()),
+            ("MIX_ENV", "prod".into()),
+            ("MIX_HOME", "../../.mix".into()),
+            ("MIX_QUIET", "1".into()),
+            ("HEX_HOME", "../../.mix".into()),
+            ("HEX_OFFLINE", "1".into()),
+            ("TERM", "dumb".into()),
+        ]
+    )
+}
+
+#[test]
+fn javascript_platform_usable_on() {
+    use super::JavaScriptPlatform::{Browser, Neutral, Node};
+    assert!(Neutral.is_usable_on(Node));
+    assert!(Neutral.is_usable_on(Browser));
+    assert!(Node.is_usable_on(Node));
+    assert!(!Node.is_usable_on(Browser));
+    assert!(!Node.is_usable_on(Neutral));
+    assert!(!Browser.is_usable_on(Node));
+}
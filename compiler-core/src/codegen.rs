@@ -1,3 +1,9 @@
+pub mod banner;
+pub mod chunks;
+
+#[cfg(test)]
+mod tests;
+
 use crate::{
     analyse::TargetSupport,
     build::{ErlangAppCodegenConfiguration, Module},
@@ -6,26 +12,41 @@ use crate::{
     io::FileSystemWriter,
     javascript,
     line_numbers::LineNumbers,
-    Result,
+    Error, Result,
 };
+use ecow::EcoString;
 use itertools::Itertools;
 use std::fmt::Debug;
 
 use camino::Utf8Path;
 
+use self::banner::Banner;
+
 /// A code generator that creates a .erl Erlang module and record header files
 /// for each Gleam module in the package.
 #[derive(Debug)]
 pub struct Erlang<'a> {
     build_directory: &'a Utf8Path,
     include_directory: &'a Utf8Path,
+    /// Whether the Erlang is generated to be read by people rather than to be
+    /// compact.
+    readable: bool,
+    /// Whether the Erlang is laid out as erlfmt would.
+    erlfmt_layout: bool,
 }
 
 impl<'a> Erlang<'a> {
-    pub fn new(build_directory: &'a Utf8Path, include_directory: &'a Utf8Path) -> Self {
+    pub fn new(
+        build_directory: &'a Utf8Path,
+        include_directory: &'a Utf8Path,
+        readable: bool,
+        erlfmt_layout: bool,
+    ) -> Self {
         Self {
             build_directory,
             include_directory,
+            readable,
+            erlfmt_layout,
         }
     }
 
@@ -35,9 +56,16 @@ impl<'a> Erlang<'a> {
         modules: &[Module],
     ) -> Result<()> {
         for module in modules {
+            let _compiling = crate::crash::compiling(&module.name, &module.input_path);
             let erl_name = module.name.replace("/", "@");
-            self.erlang_module(&writer, module, &erl_name)?;
-            self.erlang_record_headers(&writer, module, &erl_name)?;
+            let banner = Banner {
+                comment: "%%",
+                source_path: &module.package_relative_path(),
+                source: &module.code,
+                options: "target=erlang",
+            };
+            self.erlang_module(&writer, module, &erl_name, &banner)?;
+            self.erlang_record_headers(&writer, module, &erl_name, &banner)?;
         }
         Ok(())
     }
@@ -47,13 +75,28 @@ impl<'a> Erlang<'a> {
         writer: &Writer,
         module: &Module,
         erl_name: &str,
+        banner: &Banner<'_>,
     ) -> Result<()> {
         let name = format!("{erl_name}.erl");
         let path = self.build_directory.join(&name);
         let line_numbers = LineNumbers::new(&module.code);
-        let output = erlang::module(&module.ast, &line_numbers);
-        tracing::debug!(name = ?name, "Generated Erlang module");
-        writer.write(&path, &output?)
+        let source_path = module.package_relative_path();
+        let style = if self.readable {
+            erlang::Style::Readable {
+                extra: &module.extra,
+            }
+        } else {
+            erlang::Style::Compact
+        };
+        let output = erlang::module(
+            &module.ast,
+            &line_numbers,
+            &module.code,
+            &source_path,
+            style,
+        );
+        tracing::debug!(name = ?name, "generated_erlang_module");
+        writer.write(&path, &banner.prepend(&self.layout(output?)))
     }
 
     fn erlang_record_headers<Writer: FileSystemWriter>(
@@ -61,14 +104,26 @@ impl<'a> Erlang<'a> {
         writer: &Writer,
         module: &Module,
         erl_name: &str,
+        banner: &Banner<'_>,
     ) -> Result<()> {
         for (name, text) in erlang::records(&module.ast) {
             let name = format!("{erl_name}_{name}.hrl");
-            tracing::debug!(name = ?name, "Generated Erlang header");
-            writer.write(&self.include_directory.join(name), &text)?;
+            tracing::debug!(name = ?name, "generated_erlang_header");
+            writer.write(
+                &self.include_directory.join(name),
+                &banner.prepend(&self.layout(text)),
+            )?;
         }
         Ok(())
     }
+
+    fn layout(&self, code: String) -> String {
+        if self.erlfmt_layout {
+            erlang::erlfmt::format(&code)
+        } else {
+            code
+        }
+    }
 }
 
 /// A code generator that creates a .app Erlang application file for the package
@@ -96,6 +151,8 @@ impl<'a> ErlangApp<'a> {
             format!("    {{{key}, {value}}},\n")
         }
 
+        self.check_applications(config)?;
+
         let path = self.output_directory.join(format!("{}.app", &config.name));
 
         let start_module = config
@@ -128,13 +185,45 @@ impl<'a> ErlangApp<'a> {
             .sorted()
             .join(",\n                    ");
 
+        let included_applications = match config.erlang.included_applications.as_slice() {
+            [] => String::new(),
+            names => tuple(
+                "included_applications",
+                &format!("[{}]", names.iter().map(|name| atom(name)).join(", ")),
+            ),
+        };
+
+        let env = if config.erlang.env.is_empty() {
+            String::new()
+        } else {
+            let env = config
+                .erlang
+                .env
+                .iter()
+                .map(|(key, value)| {
+                    let value = erlang_term(value)
+                        .ok_or_else(|| Error::InvalidErlangAppEnv { key: key.clone() })?;
+                    Ok(format!("{{{}, {value}}}", atom(key)))
+                })
+                .collect::<Result<Vec<_>>>()?
+                .join(",\n           ");
+            tuple("env", &format!("[{env}]"))
+        };
+
+        let registered = config
+            .erlang
+            .registered
+            .iter()
+            .map(|name| atom(name))
+            .join(", ");
+
         let text = format!(
             r#"{{application, {package}, [
 {start_module}    {{vsn, "{version}"}},
     {{applications, [{applications}]}},
-    {{description, "{description}"}},
+{included_applications}{env}    {{description, "{description}"}},
     {{modules, [{modules}]}},
-    {{registered, []}}
+    {{registered, [{registered}]}}
 ]}}.
 "#,
             applications = applications,
@@ -147,6 +236,150 @@ impl<'a> ErlangApp<'a> {
 
         writer.write(&path, &text)
     }
+
+    /// Checks the applications named in the Erlang config are ones that the
+    /// application can start, being either a package in the project or an
+    /// application that comes with Erlang or Elixir.
+    fn check_applications(&self, config: &PackageConfig) -> Result<()> {
+        let fields = [
+            ("extra_applications", &config.erlang.extra_applications),
+            (
+                "included_applications",
+                &config.erlang.included_applications,
+            ),
+        ];
+        for (field, names) in fields {
+            for name in names {
+                let known = STANDARD_APPLICATIONS.contains(&name.as_str())
+                    || self.config.project_applications.contains(name)
+                    || config.dependencies.contains_key(name)
+                    || config.dev_dependencies.contains_key(name)
+                    || self
+                        .config
+                        .package_name_overrides
+                        .values()
+                        .any(|app| app == name);
+                if !known {
+                    return Err(Error::UnknownErlangApplication {
+                        field: field.into(),
+                        name: name.clone(),
+                        options: self.known_applications(config),
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn known_applications(&self, config: &PackageConfig) -> Vec<EcoString> {
+        STANDARD_APPLICATIONS
+            .iter()
+            .map(|name| EcoString::from(*name))
+            .chain(self.config.project_applications.iter().cloned())
+            .chain(config.dependencies.keys().cloned())
+            .chain(config.dev_dependencies.keys().cloned())
+            .sorted()
+            .dedup()
+            .collect()
+    }
+}
+
+/// The applications that come with Erlang/OTP and Elixir, which can be named
+/// in `extra_applications` without being a dependency.
+pub const STANDARD_APPLICATIONS: &[&str] = &[
+    // Erlang/OTP
+    "asn1",
+    "common_test",
+    "compiler",
+    "crypto",
+    "debugger",
+    "dialyzer",
+    "diameter",
+    "edoc",
+    "eldap",
+    "erl_interface",
+    "erts",
+    "et",
+    "eunit",
+    "ftp",
+    "inets",
+    "jinterface",
+    "kernel",
+    "megaco",
+    "mnesia",
+    "observer",
+    "odbc",
+    "os_mon",
+    "parsetools",
+    "public_key",
+    "reltool",
+    "runtime_tools",
+    "sasl",
+    "snmp",
+    "ssh",
+    "ssl",
+    "stdlib",
+    "syntax_tools",
+    "tftp",
+    "tools",
+    "wx",
+    "xmerl",
+    // Elixir
+    "eex",
+    "elixir",
+    "ex_unit",
+    "iex",
+    "logger",
+    "mix",
+];
+
+/// Renders a name as a quoted Erlang atom.
+pub(crate) fn atom(name: &str) -> String {
+    format!("'{}'", name.replace('\\', "\\\\").replace('\'', "\\'"))
+}
+
+/// Renders a TOML value as an Erlang term. Strings become UTF-8 binaries and
+/// tables become maps with binary keys, as Gleam code would expect. Returns
+/// `None` for floats that Erlang can't represent, such as `nan`.
+pub fn erlang_term(value: &toml::Value) -> Option<String> {
+    Some(match value {
+        toml::Value::String(string) => binary(string),
+        toml::Value::Integer(int) => int.to_string(),
+        toml::Value::Float(float) => erlang_float(*float)?,
+        toml::Value::Boolean(bool) => bool.to_string(),
+        toml::Value::Datetime(datetime) => binary(&datetime.to_string()),
+        toml::Value::Array(values) => {
+            let values: Option<Vec<_>> = values.iter().map(erlang_term).collect();
+            format!("[{}]", values?.join(", "))
+        }
+        toml::Value::Table(table) => {
+            let fields: Option<Vec<_>> = table
+                .iter()
+                .map(|(key, value)| Some(format!("{} => {}", binary(key), erlang_term(value)?)))
+                .collect();
+            format!("#{{{}}}", fields?.join(", "))
+        }
+    })
+}
+
+fn binary(string: &str) -> String {
+    let string = string.replace('\\', "\\\\").replace('"', "\\\"");
+    format!("<<\"{string}\"/utf8>>")
+}
+
+/// Renders a float so Erlang reads it as a float, which needs a fraction
+/// before any exponent.
+pub fn erlang_float(float: f64) -> Option<String> {
+    if !float.is_finite() {
+        return None;
+    }
+    let float = format!("{float:?}");
+    Some(match float.split_once('e') {
+        Some((mantissa, exponent)) if !mantissa.contains('.') => {
+            format!("{mantissa}.0e{exponent}")
+        }
+        _ => float,
+    })
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -159,6 +392,7 @@ pub enum TypeScriptDeclarations {
 pub struct JavaScript<'a> {
     output_directory: &'a Utf8Path,
     prelude_location: &'a Utf8Path,
+    platform_location: Option<&'a Utf8Path>,
     typescript: TypeScriptDeclarations,
     target_support: TargetSupport,
 }
@@ -168,10 +402,12 @@ impl<'a> JavaScript<'a> {
         output_directory: &'a Utf8Path,
         typescript: TypeScriptDeclarations,
         prelude_location: &'a Utf8Path,
+        platform_location: Option<&'a Utf8Path>,
         target_support: TargetSupport,
     ) -> Self {
         Self {
             prelude_location,
+            platform_location,
             output_directory,
             target_support,
             typescript,
@@ -179,19 +415,33 @@ impl<'a> JavaScript<'a> {
     }
 
     pub fn render(&self, writer: &impl FileSystemWriter, modules: &[Module]) -> Result<()> {
+        let options = match self.typescript {
+            TypeScriptDeclarations::None => "target=javascript typescript-declarations=false",
+            TypeScriptDeclarations::Emit => "target=javascript typescript-declarations=true",
+        };
         for module in modules {
+            let _compiling = crate::crash::compiling(&module.name, &module.input_path);
             let js_name = module.name.clone();
+            let banner = Banner {
+                comment: "//",
+                source_path: &module.package_relative_path(),
+                source: &module.code,
+                options,
+            };
             if self.typescript == TypeScriptDeclarations::Emit {
-                self.ts_declaration(writer, module, &js_name)?;
+                self.ts_declaration(writer, module, &js_name, &banner)?;
             }
-            self.js_module(writer, module, &js_name)?
+            self.js_module(writer, module, &js_name, &banner)?
         }
         self.write_prelude(writer)?;
         Ok(())
     }
 
     fn write_prelude(&self, writer: &impl FileSystemWriter) -> Result<()> {
-        let rexport = format!("export * from \"{}\";\n", self.prelude_location);
+        let mut rexport = format!("export * from \"{}\";\n", self.prelude_location);
+        if let Some(platform) = self.platform_location {
+            rexport.push_str(&format!("export * as platform from \"{platform}\";\n"));
+        }
         writer.write(&self.output_directory.join("gleam.mjs"), &rexport)?;
 
         if self.typescript == TypeScriptDeclarations::Emit {
@@ -207,12 +457,13 @@ impl<'a> JavaScript<'a> {
         writer: &impl FileSystemWriter,
         module: &Module,
         js_name: &str,
+        banner: &Banner<'_>,
     ) -> Result<()> {
         let name = format!("{js_name}.d.mts");
         let path = self.output_directory.join(name);
         let output = javascript::ts_declaration(&module.ast, &module.input_path, &module.code);
-        tracing::debug!(name = ?js_name, "Generated TS declaration");
-        writer.write(&path, &output?)
+        tracing::debug!(name = ?js_name, "generated_typescript_declaration");
+        writer.write(&path, &banner.prepend(&output?))
     }
 
     fn js_module(
@@ -220,6 +471,7 @@ impl<'a> JavaScript<'a> {
         writer: &impl FileSystemWriter,
         module: &Module,
         js_name: &str,
+        banner: &Banner<'_>,
     ) -> Result<()> {
         let name = format!("{js_name}.mjs");
         let path = self.output_directory.join(name);
@@ -232,7 +484,7 @@ impl<'a> JavaScript<'a> {
             self.target_support,
             self.typescript,
         );
-        tracing::debug!(name = ?js_name, "Generated js module");
-        writer.write(&path, &output?)
+        tracing::debug!(name = ?js_name, "generated_javascript_module");
+        writer.write(&path, &banner.prepend(&output?))
     }
 }
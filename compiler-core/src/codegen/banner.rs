@@ -0,0 +1,88 @@
+//! The comment at the top of each Erlang and JavaScript file generated from a
+//! Gleam module, saying where the file came from and how it was generated:
+//!
+//! ```erlang
+//! %% Generated by Gleam from src/wibble.gleam, do not edit.
+//! %% source-hash: 6d6f6e6b6579626f
+//! %% options: target=erlang
+//! %% output-hash: 9a3e0c64ef1d2c5b
+//! ```
+//!
+//! The banner is the same each time the same source is compiled with the same
+//! options, so builds are reproducible. The compiler version is left out so
+//! that upgrading the compiler does not change every generated file. The output hash is of the
+//! rest of the file, so edits made to the file after it was generated can be
+//! detected.
+
+#[cfg(test)]
+mod tests;
+
+use camino::Utf8Path;
+
+/// The number of lines of a banner.
+const LINES: usize = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Banner<'a> {
+    /// The start of a line comment in the generated language.
+    pub comment: &'static str,
+    /// The path of the Gleam module within its package.
+    pub source_path: &'a Utf8Path,
+    pub source: &'a str,
+    /// The code generation options used, written as `key=value` pairs.
+    pub options: &'a str,
+}
+
+impl Banner<'_> {
+    /// The generated output, with the banner before it.
+    pub fn prepend(&self, output: &str) -> String {
+        let comment = self.comment;
+        format!(
+            "{comment} Generated by Gleam from {path}, do not edit.
+{comment} source-hash: {source_hash}
+{comment} options: {options}
+{comment} output-hash: {output_hash}
+{output}",
+            path = self.source_path,
+            source_hash = hash(self.source),
+            options = self.options,
+            output_hash = hash(output),
+        )
+    }
+}
+
+/// Whether a file is as it was when it was generated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verification {
+    /// The file has no banner, so it was not generated from a Gleam module.
+    NotGenerated,
+    Unmodified,
+    Modified,
+}
+
+/// Checks a generated file against the output hash in its banner.
+pub fn verify(contents: &str) -> Verification {
+    let banner: Vec<_> = contents.split_inclusive('\n').take(LINES).collect();
+    let output = contents
+        .get(banner.iter().map(|line| line.len()).sum::<usize>()..)
+        .unwrap_or_default();
+    let output_hash = match banner.as_slice() {
+        [first, _, _, last] if first.contains(" Generated by Gleam ") => last
+            .trim_end()
+            .split_once(" output-hash: ")
+            .map(|(_, hash)| hash),
+        _ => None,
+    };
+    let Some(output_hash) = output_hash else {
+        return Verification::NotGenerated;
+    };
+    if hash(output) == output_hash {
+        Verification::Unmodified
+    } else {
+        Verification::Modified
+    }
+}
+
+fn hash(text: &str) -> String {
+    format!("{:016x}", xxhash_rust::xxh3::xxh3_64(text.as_bytes()))
+}
@@ -0,0 +1,60 @@
+use super::*;
+
+const OUTPUT: &str = "-module(wibble).\n\n-export([main/0]).\n\nmain() ->\n    nil.\n";
+
+fn banner(source: &str) -> Banner<'_> {
+    Banner {
+        comment: "%%",
+        source_path: Utf8Path::new("src/wibble.gleam"),
+        source,
+        options: "target=erlang",
+    }
+}
+
+#[test]
+fn banner_is_prepended() {
+    let generated = banner("pub fn main() { Nil }").prepend(OUTPUT);
+    let lines: Vec<_> = generated.lines().take(LINES).collect();
+    assert_eq!(
+        lines.first(),
+        Some(&"%% Generated by Gleam from src/wibble.gleam, do not edit.")
+    );
+    assert!(generated.ends_with(OUTPUT));
+    assert!(lines.contains(&"%% options: target=erlang"));
+}
+
+#[test]
+fn banner_is_reproducible() {
+    let source = "pub fn main() { Nil }";
+    assert_eq!(
+        banner(source).prepend(OUTPUT),
+        banner(source).prepend(OUTPUT)
+    );
+}
+
+#[test]
+fn banner_depends_on_the_source() {
+    assert_ne!(
+        banner("pub fn main() { Nil }").prepend(OUTPUT),
+        banner("pub fn main() { 1 }").prepend(OUTPUT)
+    );
+}
+
+#[test]
+fn generated_file_is_unmodified() {
+    let generated = banner("pub fn main() { Nil }").prepend(OUTPUT);
+    assert_eq!(verify(&generated), Verification::Unmodified);
+}
+
+#[test]
+fn edited_file_is_modified() {
+    let generated = banner("pub fn main() { Nil }").prepend(OUTPUT);
+    let edited = generated.replace("nil.", "io:format(\"hello\").");
+    assert_eq!(verify(&edited), Verification::Modified);
+}
+
+#[test]
+fn file_without_banner_is_not_generated() {
+    assert_eq!(verify(OUTPUT), Verification::NotGenerated);
+    assert_eq!(verify(""), Verification::NotGenerated);
+}
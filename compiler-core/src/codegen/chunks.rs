@@ -0,0 +1,170 @@
+//! The JavaScript output of a package can be split into chunks that are
+//! loaded when they are needed, such as the pages of a web application that
+//! are rarely visited. Each chunk starts at an entrypoint module listed in
+//! gleam.toml:
+//!
+//! ```toml
+//! [javascript]
+//! entrypoints = ["app/admin", "app/reports"]
+//! ```
+//!
+//! A chunk holds the modules its entrypoint imports, directly or through
+//! other modules, stopping at other entrypoints. Modules in more than one
+//! chunk are shared between them.
+//!
+//! The `_chunks.mjs` module has a function for each entrypoint that loads it
+//! with a dynamic `import()`, which bundlers split the output at. The modules
+//! of each chunk are listed in `_chunks.json`, for bundlers that are told
+//! which modules go in which chunk.
+
+#[cfg(test)]
+mod tests;
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use ecow::EcoString;
+use itertools::Itertools;
+
+use crate::{Error, Result};
+
+/// The name of the module of loaders, without an extension. It starts with an
+/// underscore so it can't be the name of a Gleam module.
+pub const LOADER_MODULE: &str = "_chunks";
+
+/// The name of the file listing the modules of each chunk.
+pub const MANIFEST_FILE: &str = "_chunks.json";
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Chunks {
+    /// The modules of each entrypoint's chunk, including the entrypoint.
+    pub chunks: BTreeMap<EcoString, BTreeSet<EcoString>>,
+    /// The modules that are in more than one chunk.
+    pub shared: BTreeSet<EcoString>,
+}
+
+impl Chunks {
+    /// Splits the modules of a package into the chunks of its entrypoints,
+    /// given the modules each module imports.
+    pub fn split(
+        entrypoints: &[EcoString],
+        imports: &HashMap<EcoString, Vec<EcoString>>,
+    ) -> Result<Self> {
+        let mut chunks = Self::default();
+        for entrypoint in entrypoints {
+            if !imports.contains_key(entrypoint) {
+                return Err(Error::UnknownJavaScriptEntrypoint {
+                    module: entrypoint.clone(),
+                    options: imports.keys().cloned().sorted().collect(),
+                });
+            }
+            let mut modules = BTreeSet::new();
+            let mut to_visit = vec![entrypoint];
+            while let Some(module) = to_visit.pop() {
+                // Imports of modules of other packages are not followed, and
+                // other entrypoints are the start of their own chunks.
+                let Some(module_imports) = imports.get(module) else {
+                    continue;
+                };
+                if module != entrypoint && entrypoints.contains(module) {
+                    continue;
+                }
+                if modules.insert(module.clone()) {
+                    to_visit.extend(module_imports);
+                }
+            }
+            let _ = chunks.chunks.insert(entrypoint.clone(), modules);
+        }
+
+        chunks.shared = chunks
+            .chunks
+            .values()
+            .flatten()
+            .duplicates()
+            .cloned()
+            .collect();
+        for modules in chunks.chunks.values_mut() {
+            modules.retain(|module| !chunks.shared.contains(module));
+        }
+        Ok(chunks)
+    }
+
+    /// The imports of entrypoints by modules outside of their chunks. As these
+    /// imports are not dynamic the entrypoint is loaded along with the
+    /// importing module, rather than when it is needed.
+    pub fn static_imports<'a>(
+        &self,
+        imports: &'a HashMap<EcoString, Vec<EcoString>>,
+    ) -> Vec<(&'a EcoString, &'a EcoString)> {
+        imports
+            .iter()
+            .flat_map(|(module, module_imports)| {
+                module_imports.iter().map(move |import| (module, import))
+            })
+            .filter(|(module, import)| module != import && self.chunks.contains_key(*import))
+            .sorted()
+            .collect()
+    }
+
+    /// The source of the `_chunks.mjs` module.
+    pub fn loader_module(&self) -> String {
+        let mut module = String::from(
+            "// Loaders of the chunks of the entrypoints listed in gleam.toml. Each one
+// loads its entrypoint with a dynamic import, which bundlers split the
+// output at.
+",
+        );
+        for entrypoint in self.chunks.keys() {
+            module.push_str(&format!(
+                "
+export function {loader}() {{
+  return import(\"./{entrypoint}.mjs\");
+}}
+",
+                loader = loader_name(entrypoint),
+            ));
+        }
+        module
+    }
+
+    /// The source of the `_chunks.d.mts` TypeScript declaration file.
+    pub fn loader_declarations(&self) -> String {
+        self.chunks
+            .keys()
+            .map(|entrypoint| {
+                format!(
+                    "export function {loader}(): Promise<typeof import(\"./{entrypoint}.mjs\")>;\n",
+                    loader = loader_name(entrypoint),
+                )
+            })
+            .collect()
+    }
+
+    /// The contents of `_chunks.json`, with the paths of the files of each
+    /// chunk relative to the package's output directory.
+    pub fn manifest(&self) -> String {
+        let file = |module: &EcoString| format!("{module}.mjs");
+        let manifest = serde_json::json!({
+            "chunks": self
+                .chunks
+                .iter()
+                .map(|(entrypoint, modules)| {
+                    let chunk = serde_json::json!({
+                        "loader": loader_name(entrypoint),
+                        "files": modules.iter().map(file).collect_vec(),
+                    });
+                    (entrypoint.to_string(), chunk)
+                })
+                .collect::<serde_json::Map<_, _>>(),
+            "shared": self.shared.iter().map(file).collect_vec(),
+        });
+        let mut json = serde_json::to_string_pretty(&manifest).expect("chunk manifest to json");
+        json.push('\n');
+        json
+    }
+}
+
+/// The name of the function of `_chunks.mjs` that loads an entrypoint.
+/// Module names can't contain `$`, so it doesn't make names the same.
+pub fn loader_name(entrypoint: &str) -> String {
+    format!("load_{}", entrypoint.replace('/', "$"))
+}
@@ -0,0 +1,94 @@
+use super::*;
+
+fn imports(modules: &[(&str, &[&str])]) -> HashMap<EcoString, Vec<EcoString>> {
+    modules
+        .iter()
+        .map(|(module, imports)| {
+            let imports = imports.iter().map(|import| (*import).into()).collect();
+            ((*module).into(), imports)
+        })
+        .collect()
+}
+
+fn names(names: &[&str]) -> BTreeSet<EcoString> {
+    names.iter().map(|name| (*name).into()).collect()
+}
+
+fn app() -> HashMap<EcoString, Vec<EcoString>> {
+    imports(&[
+        ("app", &["app/router", "gleam/list"]),
+        ("app/router", &["app/admin", "app/ui"]),
+        ("app/admin", &["app/admin/view", "app/ui"]),
+        ("app/admin/view", &["gleam/list"]),
+        ("app/reports", &["app/ui", "app/chart"]),
+        ("app/chart", &[]),
+        ("app/ui", &[]),
+        ("app_test", &["app"]),
+    ])
+}
+
+#[test]
+fn modules_are_split_into_chunks() {
+    let entrypoints = ["app".into(), "app/admin".into(), "app/reports".into()];
+    let chunks = Chunks::split(&entrypoints, &app()).expect("split");
+    assert_eq!(
+        chunks.chunks,
+        BTreeMap::from([
+            ("app".into(), names(&["app", "app/router"])),
+            ("app/admin".into(), names(&["app/admin", "app/admin/view"])),
+            ("app/reports".into(), names(&["app/chart", "app/reports"])),
+        ])
+    );
+    assert_eq!(chunks.shared, names(&["app/ui"]));
+}
+
+#[test]
+fn static_imports_of_entrypoints() {
+    let entrypoints = ["app".into(), "app/admin".into()];
+    let imports = app();
+    let chunks = Chunks::split(&entrypoints, &imports).expect("split");
+    assert_eq!(
+        chunks.static_imports(&imports),
+        vec![
+            (&"app/router".into(), &"app/admin".into()),
+            (&"app_test".into(), &"app".into()),
+        ]
+    );
+}
+
+#[test]
+fn unknown_entrypoint() {
+    let entrypoints = ["app/admni".into()];
+    let imports = imports(&[("app", &[]), ("app/admin", &[])]);
+    assert_eq!(
+        Chunks::split(&entrypoints, &imports),
+        Err(Error::UnknownJavaScriptEntrypoint {
+            module: "app/admni".into(),
+            options: vec!["app".into(), "app/admin".into()],
+        })
+    );
+}
+
+#[test]
+fn loader_module() {
+    let entrypoints = ["app/admin".into(), "app/reports".into()];
+    let chunks = Chunks::split(&entrypoints, &app()).expect("split");
+    insta::assert_snapshot!(chunks.loader_module());
+}
+
+#[test]
+fn loader_declarations() {
+    let entrypoints = ["app/admin".into()];
+    let chunks = Chunks::split(&entrypoints, &app()).expect("split");
+    assert_eq!(
+        chunks.loader_declarations(),
+        "export function load_app$admin(): Promise<typeof import(\"./app/admin.mjs\")>;\n"
+    );
+}
+
+#[test]
+fn manifest() {
+    let entrypoints = ["app/admin".into(), "app/reports".into()];
+    let chunks = Chunks::split(&entrypoints, &app()).expect("split");
+    insta::assert_snapshot!(chunks.manifest());
+}
@@ -0,0 +1,46 @@
+use super::*;
+use crate::io::{memory::InMemoryFileSystem, FileSystemReader};
+
+fn render(platform_location: Option<&Utf8Path>) -> InMemoryFileSystem {
+    let fs = InMemoryFileSystem::new();
+    JavaScript::new(
+        Utf8Path::new("/build/app"),
+        TypeScriptDeclarations::Emit,
+        Utf8Path::new("../prelude.mjs"),
+        platform_location,
+        TargetSupport::Enforced,
+    )
+    .render(&fs, &[])
+    .expect("render");
+    fs
+}
+
+fn read(fs: &InMemoryFileSystem, path: &str) -> String {
+    fs.read(Utf8Path::new(path)).expect("read")
+}
+
+#[test]
+fn prelude_is_reexported() {
+    let fs = render(None);
+    assert_eq!(
+        read(&fs, "/build/app/gleam.mjs"),
+        "export * from \"../prelude.mjs\";\n"
+    );
+    assert_eq!(
+        read(&fs, "/build/app/gleam.d.mts"),
+        "export * from \"../prelude.d.mts\";\n"
+    );
+}
+
+#[test]
+fn platform_shims_are_reexported() {
+    let fs = render(Some(Utf8Path::new("../platform.mjs")));
+    assert_eq!(
+        read(&fs, "/build/app/gleam.mjs"),
+        "export * from \"../prelude.mjs\";\nexport * as platform from \"../platform.mjs\";\n"
+    );
+    assert_eq!(
+        read(&fs, "/build/app/gleam.d.mts"),
+        "export * from \"../prelude.d.mts\";\nexport * as platform from \"../platform.d.mts\";\n"
+    );
+}
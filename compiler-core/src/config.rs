@@ -1,23 +1,26 @@
+use crate::dependency::ResolutionStrategy;
 use crate::error::{FileIoAction, FileKind};
 use crate::io::FileSystemReader;
 use crate::manifest::Manifest;
 use crate::requirement::Requirement;
 use crate::version::COMPILER_VERSION;
-use crate::{Error, Result};
+use crate::{Error, Result, Warning};
 use camino::{Utf8Path, Utf8PathBuf};
 use ecow::EcoString;
 use globset::{Glob, GlobSetBuilder};
 use hexpm::version::Version;
 use http::Uri;
 use serde::Deserialize;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fmt::{self};
 use std::marker::PhantomData;
 
+pub mod schema;
+
 #[cfg(test)]
 use crate::manifest::ManifestPackage;
 
-use crate::build::{Mode, Runtime, Target};
+use crate::build::{JavaScriptPlatform, Mode, Runtime, Target};
 
 fn default_version() -> Version {
     Version::parse("0.1.0").expect("default version")
@@ -97,6 +100,38 @@ pub struct PackageConfig {
     pub target: Target,
     #[serde(default)]
     pub internal_modules: Option<Vec<Glob>>,
+    /// The features that are enabled whenever the package is built.
+    #[serde(default)]
+    pub default_features: Vec<EcoString>,
+    #[serde(default)]
+    pub features: BTreeMap<EcoString, Feature>,
+    /// The environment variables embedded in the package's `env` module,
+    /// keyed by the name of the constant they are given to.
+    #[serde(default)]
+    pub env: BTreeMap<EcoString, EnvVariable>,
+    #[serde(default)]
+    pub resolution_strategy: ResolutionStrategy,
+    #[serde(default)]
+    pub deps: DepsConfig,
+    #[serde(default)]
+    pub organization: Option<EcoString>,
+    /// The registries Hex packages are looked up from, highest priority
+    /// first, each written as the repository of a dependency is: `hexpm` for
+    /// Hex itself or the name of a Hex organisation. Only Hex when empty.
+    #[serde(default)]
+    pub registries: Vec<EcoString>,
+    #[serde(default)]
+    pub warnings: WarningsConfig,
+    #[serde(default)]
+    pub lints: LintsConfig,
+    /// Rules of which of the package's modules may import which other
+    /// modules, enforced when the package is compiled.
+    #[serde(default)]
+    pub import_rules: Vec<ImportRule>,
+    /// The environment whose overlay of `gleam.toml`, such as
+    /// `gleam.prod.toml` for `prod`, was merged over it, if any.
+    #[serde(skip)]
+    pub config_env: Option<EcoString>,
 }
 
 impl PackageConfig {
@@ -119,20 +154,109 @@ impl PackageConfig {
         Ok(deps)
     }
 
+    /// Reads the config of a dependency package. Unknown keys are allowed,
+    /// as the config may have been written for another version of Gleam.
     pub fn read<FS: FileSystemReader, P: AsRef<Utf8Path>>(
         path: P,
         fs: &FS,
     ) -> Result<PackageConfig, Error> {
         let toml = fs.read(path.as_ref())?;
-        let config: PackageConfig = toml::from_str(&toml).map_err(|e| Error::FileIo {
+        Self::parse_validated(&toml, path.as_ref(), schema::UnknownKeys::Ignore)
+    }
+
+    /// Parses the config of the root package, after validating it against
+    /// the schema of `gleam.toml`. Unknown keys are not errors, see
+    /// [`PackageConfig::unknown_key_warnings`].
+    pub fn parse(toml: &str, path: &Utf8Path) -> Result<PackageConfig, Error> {
+        Self::parse_validated(toml, path, schema::UnknownKeys::Ignore)
+    }
+
+    /// The warnings for the keys of the config of the root package that
+    /// Gleam does not know about. They are ignored when it is parsed, so
+    /// that keys added by other tools or versions of Gleam do not stop the
+    /// package from building, but are often misspellings of known keys.
+    pub fn unknown_key_warnings(toml: &str, path: &Utf8Path) -> Vec<Warning> {
+        schema::validate(toml, schema::UnknownKeys::Report)
+            .into_iter()
+            .filter_map(|problem| match problem.kind {
+                schema::ProblemKind::UnknownKey { hint } => Some(Warning::UnknownConfigKey {
+                    path: path.to_path_buf(),
+                    src: toml.into(),
+                    location: problem.location,
+                    key: problem.key,
+                    hint,
+                }),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Parses the config of the root package with the overlay for an
+    /// environment merged over it. Tables are merged key by key, and any
+    /// other value in the overlay replaces the one in `gleam.toml`.
+    pub fn parse_with_overlay(
+        toml: &str,
+        path: &Utf8Path,
+        overlay: &ConfigOverlay<'_>,
+    ) -> Result<PackageConfig, Error> {
+        // Both files are validated on their own so that problems are
+        // reported with their locations in the file they are in.
+        let _ = Self::parse(toml, path)?;
+        let problems = schema::validate_overlay(overlay.src);
+        if !problems.is_empty() {
+            return Err(Error::InvalidPackageConfig {
+                path: overlay.path.to_path_buf(),
+                src: overlay.src.into(),
+                problems,
+            });
+        }
+
+        let parse_error = |path: &Utf8Path, error: toml::de::Error| Error::FileIo {
             action: FileIoAction::Parse,
             kind: FileKind::File,
-            path: path.as_ref().to_path_buf(),
-            err: Some(e.to_string()),
+            path: path.to_path_buf(),
+            err: Some(error.to_string()),
+        };
+        let mut value: toml::Value =
+            toml::from_str(toml).map_err(|error| parse_error(path, error))?;
+        let overlay_value: toml::Value =
+            toml::from_str(overlay.src).map_err(|error| parse_error(overlay.path, error))?;
+        merge_overlay(&mut value, overlay_value);
+        // The config borrows from its source so the merged value is written
+        // back out to be deserialised.
+        let merged = toml::to_string(&value).map_err(|error| Error::FileIo {
+            action: FileIoAction::Parse,
+            kind: FileKind::File,
+            path: overlay.path.to_path_buf(),
+            err: Some(error.to_string()),
         })?;
+        let mut config: PackageConfig =
+            toml::from_str(&merged).map_err(|error| parse_error(overlay.path, error))?;
+        config.config_env = Some(overlay.env.into());
         Ok(config)
     }
 
+    fn parse_validated(
+        toml: &str,
+        path: &Utf8Path,
+        unknown_keys: schema::UnknownKeys,
+    ) -> Result<PackageConfig, Error> {
+        let problems = schema::validate(toml, unknown_keys);
+        if !problems.is_empty() {
+            return Err(Error::InvalidPackageConfig {
+                path: path.to_path_buf(),
+                src: toml.into(),
+                problems,
+            });
+        }
+        toml::from_str(toml).map_err(|e| Error::FileIo {
+            action: FileIoAction::Parse,
+            kind: FileKind::File,
+            path: path.to_path_buf(),
+            err: Some(e.to_string()),
+        })
+    }
+
     /// Get the locked packages for the current config and a given (optional)
     /// manifest of previously locked packages.
     ///
@@ -144,13 +268,23 @@ impl PackageConfig {
     /// outdated deps are removed from the manifest and not locked to the
     /// previously selected versions.
     ///
+    /// Packages locked to a version below their minimum version are not
+    /// considered locked either, so that they are updated past it.
+    ///
     pub fn locked(&self, manifest: Option<&Manifest>) -> Result<HashMap<EcoString, Version>> {
-        match manifest {
-            None => Ok(HashMap::new()),
+        let mut locked = match manifest {
+            None => HashMap::new(),
             Some(manifest) => {
-                StalePackageRemover::fresh_and_locked(&self.all_dependencies()?, manifest)
+                StalePackageRemover::fresh_and_locked(&self.all_dependencies()?, manifest)?
             }
-        }
+        };
+        locked.retain(|name, version| {
+            self.deps
+                .minimums
+                .get(name)
+                .is_none_or(|minimum| &*version >= minimum)
+        });
+        Ok(locked)
     }
 
     /// Determines whether the given module should be hidden in the docs or not
@@ -159,6 +293,11 @@ impl PackageConfig {
     /// to determine modules that should not be shown in the package's documentation
     pub fn is_internal_module(&self, module: &str) -> bool {
         let package = &self.name;
+        // The module of environment variables is specific to the build, so
+        // it is never documented.
+        if !self.env.is_empty() && module == crate::build::env_module::module_name(package) {
+            return true;
+        }
         match &self.internal_modules {
             Some(globs) => {
                 let mut builder = GlobSetBuilder::new();
@@ -207,6 +346,36 @@ impl PackageConfig {
     }
 }
 
+/// An overlay of `gleam.toml` for an environment, such as `gleam.prod.toml`
+/// for `prod`.
+#[derive(Debug, Clone, Copy)]
+pub struct ConfigOverlay<'a> {
+    pub env: &'a str,
+    pub path: &'a Utf8Path,
+    pub src: &'a str,
+}
+
+/// The path of the overlay of the given `gleam.toml` for an environment.
+pub fn overlay_path(config_path: &Utf8Path, env: &str) -> Utf8PathBuf {
+    config_path.with_file_name(format!("gleam.{env}.toml"))
+}
+
+fn merge_overlay(value: &mut toml::Value, overlay: toml::Value) {
+    match (value, overlay) {
+        (toml::Value::Table(table), toml::Value::Table(overlay)) => {
+            for (key, overlay) in overlay {
+                match table.get_mut(&key) {
+                    Some(value) => merge_overlay(value, overlay),
+                    None => {
+                        let _ = table.insert(key, overlay);
+                    }
+                }
+            }
+        }
+        (value, overlay) => *value = overlay,
+    }
+}
+
 #[derive(Debug)]
 struct StalePackageRemover<'a> {
     // These are the packages for which the requirement or their parents
@@ -474,6 +643,24 @@ fn locked_unlock_new() {
     )
 }
 
+#[test]
+fn locked_below_minimum_is_unlocked() {
+    let mut config = PackageConfig::default();
+    config.dependencies = [("prod1".into(), Requirement::hex("~> 1.0"))].into();
+    config.deps.minimums = [locked_version("shared", "1.2.0")].into();
+    let manifest = Manifest {
+        requirements: config.all_dependencies().unwrap(),
+        packages: vec![
+            manifest_package("prod1", "1.1.0", &["shared"]),
+            manifest_package("shared", "1.1.0", &[]),
+        ],
+    };
+    assert_eq!(
+        config.locked(Some(&manifest)).unwrap(),
+        [locked_version("prod1", "1.1.0")].into()
+    );
+}
+
 #[test]
 fn default_internal_modules() {
     // When no internal modules are specified then we default to
@@ -590,6 +777,7 @@ fn manifest_package(
         requirements: requirements.iter().map(|e| (*e).into()).collect(),
         source: crate::manifest::ManifestPackageSource::Hex {
             outer_checksum: Base16Checksum(vec![]),
+            organization: None,
         },
     }
 }
@@ -615,17 +803,45 @@ impl Default for PackageConfig {
             licences: Default::default(),
             links: Default::default(),
             internal_modules: Default::default(),
+            default_features: Default::default(),
+            features: Default::default(),
+            env: Default::default(),
+            resolution_strategy: Default::default(),
+            deps: Default::default(),
+            organization: Default::default(),
+            registries: Default::default(),
+            warnings: Default::default(),
+            lints: Default::default(),
+            import_rules: Default::default(),
             target: Target::Erlang,
+            config_env: None,
         }
     }
 }
 
-#[derive(Deserialize, Debug, PartialEq, Eq, Default, Clone)]
+#[derive(Deserialize, Debug, PartialEq, Default, Clone)]
 pub struct ErlangConfig {
     #[serde(default)]
     pub application_start_module: Option<EcoString>,
     #[serde(default)]
     pub extra_applications: Vec<EcoString>,
+    /// Applications that are loaded, but not started, by this application.
+    #[serde(default)]
+    pub included_applications: Vec<EcoString>,
+    /// The names of the processes this application registers.
+    #[serde(default)]
+    pub registered: Vec<EcoString>,
+    /// The application's environment, available with `application:get_env`.
+    #[serde(default)]
+    pub env: BTreeMap<EcoString, toml::Value>,
+    /// Whether the Erlang is generated to be read by people, such as when
+    /// auditing it or migrating away from Gleam, rather than to be compact.
+    #[serde(default)]
+    pub readable_output: bool,
+    /// Whether the Erlang is laid out as erlfmt would, so that diffs of
+    /// generated code that is committed are small.
+    #[serde(default)]
+    pub erlfmt_layout: bool,
 }
 
 #[derive(Deserialize, Debug, PartialEq, Default, Clone)]
@@ -634,6 +850,13 @@ pub struct JavaScriptConfig {
     pub typescript_declarations: bool,
     #[serde(default = "default_javascript_runtime")]
     pub runtime: Runtime,
+    /// The platform the package is written for, if it is specific to one.
+    #[serde(default)]
+    pub platform: Option<JavaScriptPlatform>,
+    /// The modules that start chunks of the JavaScript output which are
+    /// loaded with dynamic imports, so bundlers can load them lazily.
+    #[serde(default)]
+    pub entrypoints: Vec<EcoString>,
     #[serde(default, rename = "deno")]
     pub deno: DenoConfig,
 }
@@ -791,6 +1014,156 @@ pub struct DocsPage {
     pub source: Utf8PathBuf,
 }
 
+/// A named part of a package that is only compiled when the feature is
+/// enabled, by the package itself or by a package that depends on it.
+#[derive(Deserialize, Default, Debug, PartialEq, Eq, Clone)]
+pub struct Feature {
+    /// The modules that belong to the feature.
+    #[serde(default)]
+    pub modules: Vec<Glob>,
+    /// The other features enabled by this feature. These are features of this
+    /// package, or of a dependency when written `package/feature`.
+    #[serde(default)]
+    pub enables: Vec<EcoString>,
+}
+
+/// Configuration of the warnings emitted when the package is compiled.
+#[derive(Deserialize, Default, Debug, PartialEq, Eq, Clone, Copy)]
+pub struct WarningsConfig {
+    #[serde(default)]
+    pub discarded_values: DiscardedValues,
+    /// The most types and values a module can import unqualified from
+    /// another module without a warning, if there is a limit.
+    #[serde(default)]
+    pub max_unqualified_imports: Option<usize>,
+    /// Whether to warn about unqualified imports of values with names so
+    /// generic that they are unclear without their module, such as `map`.
+    #[serde(default)]
+    pub generic_unqualified_imports: bool,
+}
+
+/// The values that a warning is emitted for when they are the result of an
+/// expression that is discarded, rather than assigned or returned.
+#[derive(Deserialize, Default, Debug, PartialEq, Eq, Clone, Copy)]
+pub enum DiscardedValues {
+    /// Any value other than `Nil`.
+    #[default]
+    #[serde(rename = "all")]
+    All,
+    /// Only `Result` values, as their errors are then not handled.
+    #[serde(rename = "results")]
+    Results,
+}
+
+/// A rule that modules matching a glob pattern must not import modules
+/// matching any of a set of other patterns, for keeping the layers of a large
+/// codebase apart.
+///
+/// ```toml
+/// [[import_rules]]
+/// modules = "app/domain/*"
+/// must_not_import = ["app/web/*"]
+/// ```
+#[derive(Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct ImportRule {
+    pub modules: Glob,
+    pub must_not_import: Vec<Glob>,
+}
+
+impl ImportRule {
+    pub fn forbids(&self, module: &str, imported: &str) -> bool {
+        self.modules.compile_matcher().is_match(module)
+            && self
+                .must_not_import
+                .iter()
+                .any(|glob| glob.compile_matcher().is_match(imported))
+    }
+}
+
+/// Configuration of how the dependencies of the package are resolved.
+#[derive(Deserialize, Default, Debug, PartialEq, Eq, Clone)]
+pub struct DepsConfig {
+    /// The lowest version of each package that may be used, whether the
+    /// package is a direct dependency or not, such as to keep out versions
+    /// with a known vulnerability.
+    #[serde(default)]
+    pub minimums: HashMap<EcoString, Version>,
+    /// The public key each package's provenance attestations are expected to
+    /// be signed with, hex encoded.
+    #[serde(default)]
+    pub provenance_keys: HashMap<EcoString, EcoString>,
+}
+
+/// Configuration of the rules checked by `gleam lint`.
+#[derive(Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct LintsConfig {
+    /// Whether to check that names follow the Gleam conventions, such as
+    /// acronyms being capitalised as words in `HttpClient`.
+    #[serde(default = "default_true")]
+    pub naming_conventions: bool,
+    /// The most lines a function can span, if there is a limit.
+    #[serde(default)]
+    pub max_function_length: Option<u32>,
+    /// The functions that should not be used, written as the module and the
+    /// function such as `gleam/io.debug`.
+    #[serde(default)]
+    pub discouraged_functions: Vec<EcoString>,
+    /// Whether to check that every `todo` has a message saying what is left
+    /// to be done.
+    #[serde(default = "default_true")]
+    pub todo_without_message: bool,
+    /// Whether to check that the version requirements on Hex packages are
+    /// bounded above, allow a published version, and do not pin libraries
+    /// to one version.
+    #[serde(default = "default_true")]
+    pub requirement_ranges: bool,
+}
+
+impl Default for LintsConfig {
+    fn default() -> Self {
+        Self {
+            naming_conventions: true,
+            max_function_length: None,
+            discouraged_functions: vec![],
+            todo_without_message: true,
+            requirement_ranges: true,
+        }
+    }
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// An environment variable whose value is embedded in the package when it is
+/// built, either written as just the variable's name or as a table giving the
+/// value to use when it is not set.
+#[derive(Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(untagged)]
+pub enum EnvVariable {
+    Name(EcoString),
+    Table {
+        variable: EcoString,
+        #[serde(default)]
+        default: Option<EcoString>,
+    },
+}
+
+impl EnvVariable {
+    pub fn variable(&self) -> &EcoString {
+        match self {
+            Self::Name(variable) | Self::Table { variable, .. } => variable,
+        }
+    }
+
+    pub fn default(&self) -> Option<&EcoString> {
+        match self {
+            Self::Name(_) => None,
+            Self::Table { default, .. } => default.as_ref(),
+        }
+    }
+}
+
 #[derive(Deserialize, Debug, PartialEq, Eq, Clone)]
 pub struct Link {
     pub title: String,
@@ -865,20 +1238,24 @@ mod package_name {
 
     static PACKAGE_NAME_PATTERN: OnceLock<Regex> = OnceLock::new();
 
+    pub(super) const ERROR: &str =
+        "Package names may only container lowercase letters, numbers, and underscores";
+
+    pub(super) fn is_valid(name: &str) -> bool {
+        PACKAGE_NAME_PATTERN
+            .get_or_init(|| Regex::new("^[a-z][a-z0-9_]*$").expect("Package name regex"))
+            .is_match(name)
+    }
+
     pub fn deserialize<'de, D>(deserializer: D) -> Result<EcoString, D::Error>
     where
         D: Deserializer<'de>,
     {
         let name: &str = serde::de::Deserialize::deserialize(deserializer)?;
-        if PACKAGE_NAME_PATTERN
-            .get_or_init(|| Regex::new("^[a-z][a-z0-9_]*$").expect("Package name regex"))
-            .is_match(name)
-        {
+        if is_valid(name) {
             Ok(name.into())
         } else {
-            let error =
-                "Package names may only container lowercase letters, numbers, and underscores";
-            Err(serde::de::Error::custom(error))
+            Err(serde::de::Error::custom(ERROR))
         }
     }
 }
@@ -908,3 +1285,279 @@ name = "1"
         "Package names may only container lowercase letters, numbers, and underscores for key `name` at line 1 column 1"
     )
 }
+
+#[test]
+fn features() {
+    let input = r#"
+name = "wibble"
+default_features = ["json"]
+
+[features.json]
+modules = ["wibble/json", "wibble/json/*"]
+enables = ["gleam_json/decode"]
+
+[features.xml]
+"#;
+    let config = toml::from_str::<PackageConfig>(input).expect("config");
+    assert_eq!(config.default_features, vec![EcoString::from("json")]);
+    assert_eq!(
+        config.features.get("json"),
+        Some(&Feature {
+            modules: vec![
+                Glob::new("wibble/json").expect("glob"),
+                Glob::new("wibble/json/*").expect("glob"),
+            ],
+            enables: vec!["gleam_json/decode".into()],
+        })
+    );
+    assert_eq!(config.features.get("xml"), Some(&Feature::default()));
+}
+
+#[test]
+fn env() {
+    let input = r#"
+name = "wibble"
+
+[env]
+commit = "GIT_COMMIT"
+build = { variable = "BUILD_NUMBER", default = "dev" }
+"#;
+    let config = toml::from_str::<PackageConfig>(input).expect("config");
+    assert_eq!(
+        config.env.get("commit"),
+        Some(&EnvVariable::Name("GIT_COMMIT".into()))
+    );
+    assert_eq!(
+        config.env.get("build"),
+        Some(&EnvVariable::Table {
+            variable: "BUILD_NUMBER".into(),
+            default: Some("dev".into()),
+        })
+    );
+    assert!(config.is_internal_module("wibble/env"));
+}
+
+#[test]
+fn resolution_strategy() {
+    let input = r#"
+name = "wibble"
+resolution_strategy = "fewest-packages"
+"#;
+    assert_eq!(
+        toml::from_str::<PackageConfig>(input)
+            .expect("config")
+            .resolution_strategy,
+        ResolutionStrategy::FewestPackages
+    );
+    assert_eq!(
+        toml::from_str::<PackageConfig>("name = \"wibble\"")
+            .expect("config")
+            .resolution_strategy,
+        ResolutionStrategy::FewestVersions
+    );
+}
+
+#[test]
+fn deps_minimums() {
+    let input = r#"
+name = "wibble"
+
+[deps.minimums]
+gleam_json = "1.0.1"
+"#;
+    assert_eq!(
+        PackageConfig::parse(input, Utf8Path::new("gleam.toml"))
+            .expect("config")
+            .deps
+            .minimums,
+        [locked_version("gleam_json", "1.0.1")].into()
+    );
+    assert!(PackageConfig::parse(
+        "name = \"wibble\"\n[deps.minimums]\ngleam_json = \">= 1.0.1\"",
+        Utf8Path::new("gleam.toml")
+    )
+    .is_err());
+}
+
+#[test]
+fn warnings_discarded_values() {
+    let input = r#"
+name = "wibble"
+
+[warnings]
+discarded_values = "results"
+"#;
+    assert_eq!(
+        toml::from_str::<PackageConfig>(input)
+            .expect("config")
+            .warnings
+            .discarded_values,
+        DiscardedValues::Results
+    );
+    assert_eq!(
+        toml::from_str::<PackageConfig>("name = \"wibble\"")
+            .expect("config")
+            .warnings
+            .discarded_values,
+        DiscardedValues::All
+    );
+}
+
+#[test]
+fn warnings_unqualified_imports() {
+    let input = r#"
+name = "wibble"
+
+[warnings]
+max_unqualified_imports = 3
+generic_unqualified_imports = true
+"#;
+    let warnings = toml::from_str::<PackageConfig>(input)
+        .expect("config")
+        .warnings;
+    assert_eq!(warnings.max_unqualified_imports, Some(3));
+    assert!(warnings.generic_unqualified_imports);
+
+    let warnings = toml::from_str::<PackageConfig>("name = \"wibble\"")
+        .expect("config")
+        .warnings;
+    assert_eq!(warnings.max_unqualified_imports, None);
+    assert!(!warnings.generic_unqualified_imports);
+}
+
+#[test]
+fn organization() {
+    let input = r#"
+name = "wibble"
+organization = "acme"
+"#;
+    assert_eq!(
+        toml::from_str::<PackageConfig>(input)
+            .expect("config")
+            .organization,
+        Some("acme".into())
+    );
+}
+
+#[test]
+fn registries() {
+    let input = r#"
+name = "wibble"
+registries = ["acme", "hexpm"]
+"#;
+    assert_eq!(
+        toml::from_str::<PackageConfig>(input)
+            .expect("config")
+            .registries,
+        vec![EcoString::from("acme"), EcoString::from("hexpm")]
+    );
+}
+
+#[test]
+fn javascript_platform() {
+    let input = r#"
+name = "wibble"
+
+[javascript]
+runtime = "deno"
+platform = "browser"
+"#;
+    let config = toml::from_str::<PackageConfig>(input).expect("config");
+    assert_eq!(
+        config.javascript.platform,
+        Some(JavaScriptPlatform::Browser)
+    );
+    assert_eq!(config.javascript.runtime, Runtime::Deno);
+    assert_eq!(
+        toml::from_str::<PackageConfig>("name = \"wibble\"")
+            .expect("config")
+            .javascript
+            .platform,
+        None
+    );
+}
+
+#[test]
+fn overlay_is_merged() {
+    let base = r#"
+name = "wibble"
+
+[dependencies]
+gleam_stdlib = ">= 1.0.0 and < 2.0.0"
+
+[javascript]
+runtime = "deno"
+typescript_declarations = true
+"#;
+    let src = r#"
+[dependencies]
+wobble = ">= 1.0.0 and < 2.0.0"
+
+[javascript]
+typescript_declarations = false
+"#;
+    let path = overlay_path(Utf8Path::new("/app/gleam.toml"), "prod");
+    assert_eq!(path, Utf8PathBuf::from("/app/gleam.prod.toml"));
+    let overlay = ConfigOverlay {
+        env: "prod",
+        path: &path,
+        src,
+    };
+    let config =
+        PackageConfig::parse_with_overlay(base, Utf8Path::new("/app/gleam.toml"), &overlay)
+            .expect("config");
+    assert!(config.dependencies.contains_key("gleam_stdlib"));
+    assert!(config.dependencies.contains_key("wobble"));
+    assert_eq!(config.javascript.runtime, Runtime::Deno);
+    assert!(!config.javascript.typescript_declarations);
+    assert_eq!(config.config_env, Some("prod".into()));
+}
+
+#[test]
+fn overlay_cannot_set_name() {
+    let overlay = ConfigOverlay {
+        env: "dev",
+        path: Utf8Path::new("/app/gleam.dev.toml"),
+        src: "name = \"wobble\"",
+    };
+    let result = PackageConfig::parse_with_overlay(
+        "name = \"wibble\"",
+        Utf8Path::new("/app/gleam.toml"),
+        &overlay,
+    );
+    assert!(matches!(
+        result,
+        Err(Error::InvalidPackageConfig { path, .. }) if path == "/app/gleam.dev.toml"
+    ));
+}
+
+#[test]
+fn import_rules() {
+    let input = r#"
+name = "wibble"
+
+[[import_rules]]
+modules = "wibble/domain/*"
+must_not_import = ["wibble/web/*", "gleam/http"]
+"#;
+    let config = toml::from_str::<PackageConfig>(input).expect("config");
+    let [rule] = config.import_rules.as_slice() else {
+        panic!("expected one import rule");
+    };
+    assert!(rule.forbids("wibble/domain/user", "wibble/web/router"));
+    assert!(rule.forbids("wibble/domain/user", "gleam/http"));
+    assert!(!rule.forbids("wibble/domain/user", "wibble/domain/order"));
+    assert!(!rule.forbids("wibble/web/router", "wibble/domain/user"));
+}
+
+#[test]
+fn unknown_keys_are_warnings() {
+    let input = "name = \"wibble\"\ntool = \"other\"\n\n[tools.lustre]\nport = 1234\n";
+    let path = Utf8Path::new("gleam.toml");
+    assert!(PackageConfig::parse(input, path).is_ok());
+    let warnings = PackageConfig::unknown_key_warnings(input, path);
+    assert!(matches!(
+        warnings.as_slice(),
+        [Warning::UnknownConfigKey { key, .. }] if key == "tool"
+    ));
+}
@@ -0,0 +1,1101 @@
+//! The schema of `gleam.toml`, which the config of a package is validated
+//! against before it is deserialised, so that unknown keys, values of the
+//! wrong type, and invalid versions are reported with their locations in the
+//! file. The schema is also exported as a JSON Schema for editors.
+
+#[cfg(test)]
+mod tests;
+
+use std::fmt;
+
+use ecow::EcoString;
+use hexpm::version::{Range, Version};
+use itertools::Itertools;
+use serde::de::{self, Deserialize, Deserializer, MapAccess, SeqAccess, Visitor};
+use serde_json::json;
+use toml::Spanned;
+
+use crate::{ast::SrcSpan, error::edit_distance};
+
+/// The expected shape of a value in `gleam.toml`.
+#[derive(Debug, Clone, Copy)]
+pub enum Schema {
+    String(Format),
+    Boolean,
+    /// A non-negative integer, as all the integers in `gleam.toml` are
+    /// counts.
+    Integer,
+    /// One of the given strings.
+    Enum(&'static [&'static str]),
+    Array(&'static Schema),
+    /// A table with the given keys.
+    Table(&'static [Field]),
+    /// A table with any keys, with values of the given schema.
+    Map(&'static Schema),
+    /// A value of one of the given schemas, which must each be for a
+    /// different type of TOML value.
+    OneOf(&'static [Schema]),
+    /// Any value, such as the environment of an Erlang application.
+    Any,
+}
+
+/// What a string in `gleam.toml` must be.
+#[derive(Debug, Clone, Copy)]
+pub enum Format {
+    Text,
+    PackageName,
+    Version,
+    VersionRange,
+    Licence,
+    Uri,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Field {
+    pub name: &'static str,
+    /// Other names the key can be written with.
+    pub aliases: &'static [&'static str],
+    pub required: bool,
+    pub description: &'static str,
+    pub schema: Schema,
+}
+
+impl Field {
+    const fn new(name: &'static str, description: &'static str, schema: Schema) -> Self {
+        Self {
+            name,
+            aliases: &[],
+            required: false,
+            description,
+            schema,
+        }
+    }
+
+    const fn required(self) -> Self {
+        Self {
+            required: true,
+            ..self
+        }
+    }
+
+    const fn aliases(self, aliases: &'static [&'static str]) -> Self {
+        Self { aliases, ..self }
+    }
+
+    fn is_named(&self, key: &str) -> bool {
+        self.name == key || self.aliases.contains(&key)
+    }
+}
+
+const NAMES: Schema = Schema::Array(&Schema::String(Format::Text));
+
+const REQUIREMENT: Schema = Schema::OneOf(&[
+    Schema::String(Format::VersionRange),
+    Schema::Table(&[
+        Field::new(
+            "version",
+            "The range of versions of the package to use, from Hex.",
+            Schema::String(Format::VersionRange),
+        ),
+        Field::new(
+            "organization",
+            "The Hex organisation the package is privately published to.",
+            Schema::String(Format::Text),
+        ),
+        Field::new(
+            "path",
+            "The path of a local package to use.",
+            Schema::String(Format::Text),
+        ),
+        Field::new(
+            "git",
+            "The URL of a git repository to use the package from.",
+            Schema::String(Format::Text),
+        ),
+        Field::new(
+            "ref",
+            "The branch, tag, or commit of the git repository to use.",
+            Schema::String(Format::Text),
+        ),
+        Field::new(
+            "features",
+            "The features of the package to enable, in addition to its default features.",
+            NAMES,
+        ),
+    ]),
+]);
+
+const DENO_FLAG: Schema = Schema::OneOf(&[Schema::Boolean, NAMES]);
+
+const DOCUMENTATION: Field = Field::new(
+    "documentation",
+    "The documentation of the package.",
+    Schema::Table(&[Field::new(
+        "pages",
+        "Extra pages of documentation, written in Markdown.",
+        Schema::Array(&Schema::Table(&[
+            Field::new(
+                "title",
+                "The title of the page.",
+                Schema::String(Format::Text),
+            )
+            .required(),
+            Field::new(
+                "path",
+                "The path of the page in the documentation.",
+                Schema::String(Format::Text),
+            )
+            .required(),
+            Field::new(
+                "source",
+                "The path of the Markdown file of the page.",
+                Schema::String(Format::Text),
+            )
+            .required(),
+        ])),
+    )]),
+)
+.aliases(&["docs"]);
+
+const DEPENDENCIES: Field = Field::new(
+    "dependencies",
+    "The packages the package depends on.",
+    Schema::Map(&REQUIREMENT),
+);
+
+const DEV_DEPENDENCIES: Field = Field::new(
+    "dev-dependencies",
+    "The packages the package depends on for development only, such as in tests.",
+    Schema::Map(&REQUIREMENT),
+);
+
+const ERLANG: Field = Field::new(
+    "erlang",
+    "The configuration of the Erlang target.",
+    Schema::Table(&[
+        Field::new(
+            "application_start_module",
+            "The module whose `start` function starts the OTP application.",
+            Schema::String(Format::Text),
+        ),
+        Field::new(
+            "extra_applications",
+            "The OTP applications to start in addition to the dependencies.",
+            NAMES,
+        ),
+        Field::new(
+            "included_applications",
+            "The OTP applications loaded, but not started, by this application.",
+            NAMES,
+        ),
+        Field::new(
+            "registered",
+            "The names of the processes the application registers.",
+            NAMES,
+        ),
+        Field::new(
+            "env",
+            "The environment of the application, available with `application:get_env`.",
+            Schema::Map(&Schema::Any),
+        ),
+        Field::new(
+            "readable_output",
+            "Whether the Erlang is generated to be read by people rather than to be compact.",
+            Schema::Boolean,
+        ),
+        Field::new(
+            "erlfmt_layout",
+            "Whether the generated Erlang is laid out as erlfmt would.",
+            Schema::Boolean,
+        ),
+    ]),
+);
+
+const JAVASCRIPT: Field = Field::new(
+    "javascript",
+    "The configuration of the JavaScript target.",
+    Schema::Table(&[
+        Field::new(
+            "typescript_declarations",
+            "Whether to emit TypeScript declaration files.",
+            Schema::Boolean,
+        ),
+        Field::new(
+            "runtime",
+            "The JavaScript runtime to run the package with.",
+            Schema::Enum(&["nodejs", "node", "deno", "bun"]),
+        ),
+        Field::new(
+            "platform",
+            "The platform the package is written for, if it is specific to one.",
+            Schema::Enum(&["node", "browser", "neutral"]),
+        ),
+        Field::new(
+            "entrypoints",
+            "The modules starting chunks of the output that are loaded lazily.",
+            NAMES,
+        ),
+        Field::new(
+            "deno",
+            "The permissions of the Deno runtime.",
+            Schema::Table(&[
+                Field::new(
+                    "allow_env",
+                    "Allow access to environment variables.",
+                    DENO_FLAG,
+                ),
+                Field::new(
+                    "allow_sys",
+                    "Allow access to system information.",
+                    Schema::Boolean,
+                ),
+                Field::new(
+                    "allow_hrtime",
+                    "Allow high resolution time measurement.",
+                    Schema::Boolean,
+                ),
+                Field::new("allow_net", "Allow network access.", DENO_FLAG),
+                Field::new(
+                    "allow_ffi",
+                    "Allow loading dynamic libraries.",
+                    Schema::Boolean,
+                ),
+                Field::new("allow_read", "Allow file system read access.", DENO_FLAG),
+                Field::new("allow_run", "Allow running subprocesses.", DENO_FLAG),
+                Field::new("allow_write", "Allow file system write access.", DENO_FLAG),
+                Field::new("allow_all", "Allow all permissions.", Schema::Boolean),
+                Field::new("unstable", "Enable unstable Deno APIs.", Schema::Boolean),
+                Field::new(
+                    "location",
+                    "The value of `globalThis.location`.",
+                    Schema::String(Format::Uri),
+                ),
+            ]),
+        ),
+    ]),
+);
+
+const TARGET: Field = Field::new(
+    "target",
+    "The target the package is compiled to by default.",
+    Schema::Enum(&["erlang", "erl", "javascript", "js"]),
+);
+
+const INTERNAL_MODULES: Field = Field::new(
+    "internal_modules",
+    "Glob patterns of the modules that are hidden from the documentation.",
+    NAMES,
+);
+
+const DEFAULT_FEATURES: Field = Field::new(
+    "default_features",
+    "The features that are enabled whenever the package is built.",
+    NAMES,
+);
+
+/// The schema of `gleam.toml`.
+pub static PACKAGE_CONFIG: Schema = Schema::Table(PACKAGE_CONFIG_FIELDS);
+
+const PACKAGE_CONFIG_FIELDS: &[Field] = &[
+    Field::new(
+        "name",
+        "The name of the package.",
+        Schema::String(Format::PackageName),
+    )
+    .required(),
+    Field::new(
+        "version",
+        "The version of the package.",
+        Schema::String(Format::Version),
+    ),
+    Field::new(
+        "gleam",
+        "The range of versions of Gleam the package can be compiled with.",
+        Schema::String(Format::VersionRange),
+    ),
+    Field::new(
+        "licences",
+        "The SPDX identifiers of the licences of the package.",
+        Schema::Array(&Schema::String(Format::Licence)),
+    )
+    .aliases(&["licenses"]),
+    Field::new(
+        "description",
+        "A short description of the package.",
+        Schema::String(Format::Text),
+    ),
+    DOCUMENTATION,
+    DEPENDENCIES,
+    DEV_DEPENDENCIES,
+    Field::new(
+        "repository",
+        "Where the source code of the package is hosted.",
+        Schema::Table(&[
+            Field::new(
+                "type",
+                "The service hosting the repository.",
+                Schema::Enum(&[
+                    "github",
+                    "gitlab",
+                    "bitbucket",
+                    "codeberg",
+                    "gitea",
+                    "forgejo",
+                    "sourcehut",
+                    "custom",
+                    "none",
+                ]),
+            )
+            .required(),
+            Field::new(
+                "user",
+                "The user or organisation owning the repository.",
+                Schema::String(Format::Text),
+            ),
+            Field::new(
+                "repo",
+                "The name of the repository.",
+                Schema::String(Format::Text),
+            ),
+            Field::new(
+                "host",
+                "The host of a Gitea or Forgejo instance.",
+                Schema::String(Format::Text),
+            ),
+            Field::new(
+                "url",
+                "The URL of a repository of the `custom` type.",
+                Schema::String(Format::Text),
+            ),
+        ]),
+    ),
+    Field::new(
+        "links",
+        "Links to show in the documentation and on Hex.",
+        Schema::Array(&Schema::Table(&[
+            Field::new(
+                "title",
+                "The text of the link.",
+                Schema::String(Format::Text),
+            )
+            .required(),
+            Field::new("href", "The URL of the link.", Schema::String(Format::Uri)).required(),
+        ])),
+    ),
+    ERLANG,
+    JAVASCRIPT,
+    TARGET,
+    INTERNAL_MODULES,
+    DEFAULT_FEATURES,
+    Field::new(
+        "features",
+        "Parts of the package that are only compiled when enabled.",
+        Schema::Map(&Schema::Table(&[
+            Field::new(
+                "modules",
+                "Glob patterns of the modules that belong to the feature.",
+                NAMES,
+            ),
+            Field::new(
+                "enables",
+                "The other features enabled by the feature.",
+                NAMES,
+            ),
+        ])),
+    ),
+    Field::new(
+        "env",
+        "Environment variables embedded in the package's `env` module when it is built.",
+        Schema::Map(&Schema::OneOf(&[
+            Schema::String(Format::Text),
+            Schema::Table(&[
+                Field::new(
+                    "variable",
+                    "The name of the environment variable.",
+                    Schema::String(Format::Text),
+                )
+                .required(),
+                Field::new(
+                    "default",
+                    "The value to use when the variable is not set.",
+                    Schema::String(Format::Text),
+                ),
+            ]),
+        ])),
+    ),
+    Field::new(
+        "resolution_strategy",
+        "How to pick between the versions of a package that satisfy its requirements.",
+        Schema::Enum(&["fewest-versions", "fewest-packages"]),
+    ),
+    Field::new(
+        "deps",
+        "The configuration of how the dependencies of the package are resolved.",
+        Schema::Table(&[
+            Field::new(
+                "minimums",
+                "The lowest version of each package that may be used, even as an indirect dependency.",
+                Schema::Map(&Schema::String(Format::Version)),
+            ),
+            Field::new(
+                "provenance_keys",
+                "The public key each package's provenance attestations are expected to be signed with.",
+                Schema::Map(&Schema::String(Format::Text)),
+            ),
+        ]),
+    ),
+    Field::new(
+        "organization",
+        "The Hex organisation to publish the package to privately.",
+        Schema::String(Format::Text),
+    ),
+    Field::new(
+        "registries",
+        "The registries to look Hex packages up from, highest priority first: `hexpm` or the name of a Hex organisation.",
+        NAMES,
+    ),
+    Field::new(
+        "warnings",
+        "The configuration of the warnings emitted when the package is compiled.",
+        Schema::Table(&[
+            Field::new(
+                "discarded_values",
+                "The discarded values to warn about.",
+                Schema::Enum(&["all", "results"]),
+            ),
+            Field::new(
+                "max_unqualified_imports",
+                "The most values and types a module can import unqualified from another module.",
+                Schema::Integer,
+            ),
+            Field::new(
+                "generic_unqualified_imports",
+                "Whether to warn about unqualified imports of values with generic names.",
+                Schema::Boolean,
+            ),
+        ]),
+    ),
+    Field::new(
+        "import_rules",
+        "Rules of which of the package's modules may import which other modules.",
+        Schema::Array(&Schema::Table(&[
+            Field::new(
+                "modules",
+                "A glob pattern of the modules the rule applies to.",
+                Schema::String(Format::Text),
+            )
+            .required(),
+            Field::new(
+                "must_not_import",
+                "Glob patterns of the modules that they must not import.",
+                NAMES,
+            )
+            .required(),
+        ])),
+    ),
+    Field::new(
+        "lints",
+        "The configuration of the rules checked by `gleam lint`.",
+        Schema::Table(&[
+            Field::new(
+                "naming_conventions",
+                "Whether to check that names follow the Gleam conventions.",
+                Schema::Boolean,
+            ),
+            Field::new(
+                "max_function_length",
+                "The most lines a function can span.",
+                Schema::Integer,
+            ),
+            Field::new(
+                "discouraged_functions",
+                "The functions that should not be used, such as `gleam/io.debug`.",
+                Schema::Array(&Schema::String(Format::Text)),
+            ),
+            Field::new(
+                "todo_without_message",
+                "Whether to check that every `todo` has a message.",
+                Schema::Boolean,
+            ),
+            Field::new(
+                "requirement_ranges",
+                "Whether to check the version requirements on Hex packages.",
+                Schema::Boolean,
+            ),
+        ]),
+    ),
+    Field::new(
+        "tools",
+        "The configuration of other tools, in a table for each tool. It is not checked by Gleam.",
+        Schema::Map(&Schema::Any),
+    ),
+];
+
+/// The schema of an overlay of `gleam.toml` for an environment, such as
+/// `gleam.prod.toml`, which can change how the package is built but not what
+/// the package is.
+pub static OVERLAY: Schema = Schema::Table(&[
+    DEPENDENCIES,
+    DEV_DEPENDENCIES,
+    TARGET,
+    ERLANG,
+    JAVASCRIPT,
+    DOCUMENTATION,
+    INTERNAL_MODULES,
+    DEFAULT_FEATURES,
+]);
+
+impl Schema {
+    /// The schema as a JSON Schema, for editors and other tooling.
+    pub fn to_json_schema(&self) -> serde_json::Value {
+        match self {
+            Schema::String(format) => {
+                let mut schema = json!({ "type": "string" });
+                if let Some(object) = schema.as_object_mut() {
+                    match format {
+                        Format::PackageName => {
+                            let _ = object.insert("pattern".into(), json!("^[a-z][a-z0-9_]*$"));
+                        }
+                        Format::Uri => {
+                            let _ = object.insert("format".into(), json!("uri"));
+                        }
+                        Format::Text | Format::Version | Format::VersionRange | Format::Licence => {
+                        }
+                    }
+                }
+                schema
+            }
+            Schema::Boolean => json!({ "type": "boolean" }),
+            Schema::Integer => json!({ "type": "integer", "minimum": 0 }),
+            Schema::Enum(values) => json!({ "enum": values }),
+            Schema::Array(items) => json!({ "type": "array", "items": items.to_json_schema() }),
+            Schema::Table(fields) => {
+                let mut properties = serde_json::Map::new();
+                for field in fields.iter() {
+                    let mut schema = field.schema.to_json_schema();
+                    if let Some(object) = schema.as_object_mut() {
+                        let _ = object.insert("description".into(), json!(field.description));
+                    }
+                    for name in std::iter::once(&field.name).chain(field.aliases) {
+                        let _ = properties.insert((*name).into(), schema.clone());
+                    }
+                }
+                let required = fields
+                    .iter()
+                    .filter(|field| field.required)
+                    .map(|field| field.name)
+                    .collect_vec();
+                json!({
+                    "type": "object",
+                    "properties": properties,
+                    "required": required,
+                    "additionalProperties": false,
+                })
+            }
+            Schema::Map(values) => {
+                json!({ "type": "object", "additionalProperties": values.to_json_schema() })
+            }
+            Schema::OneOf(schemas) => {
+                json!({ "anyOf": schemas.iter().map(Schema::to_json_schema).collect_vec() })
+            }
+            Schema::Any => json!({}),
+        }
+    }
+
+    /// What the schema expects, such as "a string or a table".
+    fn expected(&self) -> String {
+        match self {
+            Schema::String(_) | Schema::Enum(_) => "a string".into(),
+            Schema::Boolean => "a boolean".into(),
+            Schema::Integer => "an integer".into(),
+            Schema::Array(_) => "an array".into(),
+            Schema::Table(_) | Schema::Map(_) => "a table".into(),
+            Schema::OneOf(schemas) => schemas.iter().map(Schema::expected).join(" or "),
+            Schema::Any => "any value".into(),
+        }
+    }
+
+    fn accepts_type_of(&self, value: &Value) -> bool {
+        match (self, value) {
+            (Schema::String(_) | Schema::Enum(_), Value::String(_))
+            | (Schema::Boolean, Value::Boolean)
+            | (Schema::Integer, Value::Integer(_))
+            | (Schema::Array(_), Value::Array(_))
+            | (Schema::Table(_) | Schema::Map(_), Value::Table(_))
+            | (Schema::Any, _) => true,
+            (Schema::OneOf(schemas), _) => {
+                schemas.iter().any(|schema| schema.accepts_type_of(value))
+            }
+            _ => false,
+        }
+    }
+}
+
+/// The JSON Schema of `gleam.toml`.
+pub fn json_schema() -> serde_json::Value {
+    let mut schema = PACKAGE_CONFIG.to_json_schema();
+    if let Some(object) = schema.as_object_mut() {
+        let _ = object.insert(
+            "$schema".into(),
+            json!("http://json-schema.org/draft-07/schema#"),
+        );
+        let _ = object.insert("title".into(), json!("gleam.toml"));
+        let _ = object.insert(
+            "description".into(),
+            json!("The configuration of a Gleam package."),
+        );
+    }
+    schema
+}
+
+/// A way in which a `gleam.toml` file does not match the schema.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Problem {
+    pub location: SrcSpan,
+    /// The dotted path of the key with the problem, such as
+    /// `javascript.runtime`. It is empty for the whole file.
+    pub key: EcoString,
+    pub kind: ProblemKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProblemKind {
+    /// The file is not valid TOML.
+    Syntax {
+        error: String,
+    },
+    UnknownKey {
+        hint: Option<String>,
+    },
+    MissingKey {
+        name: EcoString,
+    },
+    TypeMismatch {
+        expected: String,
+        found: &'static str,
+    },
+    InvalidVersion {
+        version: EcoString,
+        error: String,
+    },
+    InvalidVersionRange {
+        range: EcoString,
+        error: String,
+    },
+    InvalidValue {
+        error: String,
+        hint: Option<String>,
+    },
+}
+
+/// Whether unknown keys are reported. They are not for the configs of
+/// dependencies, which may have been written for other versions of Gleam.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnknownKeys {
+    Report,
+    Ignore,
+}
+
+/// Validates the source of a `gleam.toml` file against the schema.
+pub fn validate(src: &str, unknown_keys: UnknownKeys) -> Vec<Problem> {
+    validate_against(&PACKAGE_CONFIG, src, unknown_keys)
+}
+
+/// Validates the source of an overlay of `gleam.toml` against the schema of
+/// overlays. Keys that can only be set in `gleam.toml` itself are reported
+/// as such.
+pub fn validate_overlay(src: &str) -> Vec<Problem> {
+    let mut problems = validate_against(&OVERLAY, src, UnknownKeys::Report);
+    for problem in &mut problems {
+        if let ProblemKind::UnknownKey { hint } = &mut problem.kind {
+            if PACKAGE_CONFIG_FIELDS
+                .iter()
+                .any(|field| field.is_named(&problem.key))
+            {
+                *hint = Some("This key can only be set in gleam.toml.".into());
+            }
+        }
+    }
+    problems
+}
+
+fn validate_against(schema: &Schema, src: &str, unknown_keys: UnknownKeys) -> Vec<Problem> {
+    let value = match toml::from_str::<Spanned<Value>>(src) {
+        Ok(value) => value,
+        Err(error) => {
+            let offset = error
+                .line_col()
+                .map(|(line, column)| line_column_offset(src, line, column))
+                .unwrap_or(0);
+            return vec![Problem {
+                location: SrcSpan::new(offset, offset),
+                key: "".into(),
+                kind: ProblemKind::Syntax {
+                    error: syntax_error_message(&error),
+                },
+            }];
+        }
+    };
+    let mut validator = Validator {
+        unknown_keys,
+        path: vec![],
+        problems: vec![],
+    };
+    validator.check(schema, value.get_ref(), SrcSpan::new(0, 0));
+    validator.problems
+}
+
+struct Validator {
+    unknown_keys: UnknownKeys,
+    /// The keys of the value being checked.
+    path: Vec<EcoString>,
+    problems: Vec<Problem>,
+}
+
+impl Validator {
+    /// Checks the value at the given location against the schema.
+    fn check(&mut self, schema: &Schema, value: &Value, location: SrcSpan) {
+        if !schema.accepts_type_of(value) {
+            return self.report(
+                location,
+                ProblemKind::TypeMismatch {
+                    expected: schema.expected(),
+                    found: value.description(),
+                },
+            );
+        }
+
+        match (schema, value) {
+            (Schema::String(format), Value::String(string)) => {
+                self.check_format(*format, string, location)
+            }
+            (Schema::Enum(options), Value::String(string))
+                if !options.contains(&string.as_str()) =>
+            {
+                let options = options
+                    .iter()
+                    .map(|option| EcoString::from(*option))
+                    .collect_vec();
+                self.report(
+                    location,
+                    ProblemKind::InvalidValue {
+                        error: format!(
+                            "Expected one of {}.",
+                            options
+                                .iter()
+                                .map(|option| format!("`{option}`"))
+                                .join(", ")
+                        ),
+                        hint: closest(string, &options),
+                    },
+                )
+            }
+            (Schema::Integer, Value::Integer(integer)) if *integer < 0 => self.report(
+                location,
+                ProblemKind::InvalidValue {
+                    error: "This must not be negative.".into(),
+                    hint: None,
+                },
+            ),
+            (Schema::Array(items), Value::Array(elements)) => {
+                for element in elements {
+                    self.check(items, element.get_ref(), value_span(element, location));
+                }
+            }
+            (Schema::Table(fields), Value::Table(entries)) => {
+                self.check_table(fields, entries, location)
+            }
+            (Schema::Map(values), Value::Table(entries)) => {
+                for (key, value) in entries {
+                    self.path.push(key.get_ref().as_str().into());
+                    self.check(values, value.get_ref(), value_span(value, span(key)));
+                    let _ = self.path.pop();
+                }
+            }
+            (Schema::OneOf(schemas), _) => {
+                if let Some(schema) = schemas.iter().find(|schema| schema.accepts_type_of(value)) {
+                    self.check(schema, value, location);
+                }
+            }
+            _ => (),
+        }
+    }
+
+    fn check_table(
+        &mut self,
+        fields: &[Field],
+        entries: &[(Spanned<String>, Spanned<Value>)],
+        location: SrcSpan,
+    ) {
+        for (key, value) in entries {
+            self.path.push(key.get_ref().as_str().into());
+            match fields.iter().find(|field| field.is_named(key.get_ref())) {
+                Some(field) => {
+                    self.check(&field.schema, value.get_ref(), value_span(value, span(key)))
+                }
+                None if self.unknown_keys == UnknownKeys::Report => {
+                    let names = fields
+                        .iter()
+                        .flat_map(|field| std::iter::once(&field.name).chain(field.aliases))
+                        .map(|name| EcoString::from(*name))
+                        .collect_vec();
+                    self.report(
+                        span(key),
+                        ProblemKind::UnknownKey {
+                            hint: closest(key.get_ref(), &names),
+                        },
+                    )
+                }
+                None => (),
+            }
+            let _ = self.path.pop();
+        }
+
+        for field in fields.iter().filter(|field| field.required) {
+            if !entries.iter().any(|(key, _)| field.is_named(key.get_ref())) {
+                self.report(
+                    location,
+                    ProblemKind::MissingKey {
+                        name: field.name.into(),
+                    },
+                );
+            }
+        }
+    }
+
+    fn check_format(&mut self, format: Format, string: &str, location: SrcSpan) {
+        let kind = match format {
+            Format::Text => None,
+            Format::PackageName => {
+                (!super::package_name::is_valid(string)).then(|| ProblemKind::InvalidValue {
+                    error: super::package_name::ERROR.into(),
+                    hint: None,
+                })
+            }
+            Format::Version => {
+                Version::parse(string)
+                    .err()
+                    .map(|error| ProblemKind::InvalidVersion {
+                        version: string.into(),
+                        error: error.to_string(),
+                    })
+            }
+            Format::VersionRange => Range::new(string.into()).to_pubgrub().err().map(|error| {
+                ProblemKind::InvalidVersionRange {
+                    range: string.into(),
+                    error: error.to_string(),
+                }
+            }),
+            Format::Licence => {
+                spdx::license_id(string)
+                    .is_none()
+                    .then(|| ProblemKind::InvalidValue {
+                        error: format!("`{string}` is not a valid SPDX licence identifier."),
+                        hint: spdx::imprecise_license_id(string)
+                            .map(|(licence, _)| format!("Did you mean `{}`?", licence.name)),
+                    })
+            }
+            Format::Uri => match string.parse::<http::Uri>() {
+                Ok(uri) if uri.scheme().is_some() && uri.host().is_some() => None,
+                Ok(_) => Some(ProblemKind::InvalidValue {
+                    error: "The URL must have a scheme and a host.".into(),
+                    hint: Some(format!("Did you mean `https://{string}`?")),
+                }),
+                Err(error) => Some(ProblemKind::InvalidValue {
+                    error: format!("This is not a valid URL: {error}."),
+                    hint: None,
+                }),
+            },
+        };
+        if let Some(kind) = kind {
+            self.report(location, kind);
+        }
+    }
+
+    fn report(&mut self, location: SrcSpan, kind: ProblemKind) {
+        self.problems.push(Problem {
+            location,
+            key: self.path.join(".").into(),
+            kind,
+        });
+    }
+}
+
+/// A hint naming the option closest to the given name, if any is close
+/// enough to be a likely typo of it.
+fn closest(name: &str, options: &[EcoString]) -> Option<String> {
+    let limit = std::cmp::max(name.chars().count() / 3, 1);
+    options
+        .iter()
+        .filter_map(|option| Some((edit_distance(name, option, limit)?, option)))
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, option)| format!("Did you mean `{option}`?"))
+}
+
+/// The location of a value. Tables written with a header and the tables of
+/// arrays of tables have no location, so the given location is used in its
+/// place, which is that of their key.
+fn value_span(value: &Spanned<Value>, default: SrcSpan) -> SrcSpan {
+    match value.span() {
+        (0, 0) => default,
+        _ => span(value),
+    }
+}
+
+fn span<T>(spanned: &Spanned<T>) -> SrcSpan {
+    let (start, end) = spanned.span();
+    SrcSpan::new(start as u32, end as u32)
+}
+
+/// The byte offset of the zero-indexed line and column of an error from the
+/// TOML parser.
+fn line_column_offset(src: &str, line: usize, column: usize) -> u32 {
+    let line_start: usize = src.split_inclusive('\n').take(line).map(str::len).sum();
+    let offset = src
+        .get(line_start..)
+        .unwrap_or_default()
+        .char_indices()
+        .nth(column)
+        .map(|(index, _)| line_start + index)
+        .unwrap_or(src.len());
+    offset as u32
+}
+
+/// The message of a TOML syntax error, without the line and column it
+/// includes, as the error is shown at its location.
+fn syntax_error_message(error: &toml::de::Error) -> String {
+    let message = error.to_string();
+    match message.rsplit_once(" at line ") {
+        Some((message, _)) => message.into(),
+        None => message,
+    }
+}
+
+/// A TOML value, keeping the locations of its keys and values.
+#[derive(Debug)]
+enum Value {
+    String(String),
+    Integer(i64),
+    Float,
+    Boolean,
+    Datetime,
+    Array(Vec<Spanned<Value>>),
+    Table(Vec<(Spanned<String>, Spanned<Value>)>),
+}
+
+impl Value {
+    fn description(&self) -> &'static str {
+        match self {
+            Value::String(_) => "a string",
+            Value::Integer(_) => "an integer",
+            Value::Float => "a float",
+            Value::Boolean => "a boolean",
+            Value::Datetime => "a date",
+            Value::Array(_) => "an array",
+            Value::Table(_) => "a table",
+        }
+    }
+}
+
+// The TOML deserialiser gives the locations of keys and values when they are
+// deserialised as `Spanned`, which asks for a struct with this name and these
+// fields. Datetimes are given as a table with this key instead, which can only
+// be told apart from other keys by asking for the key in the same way.
+const SPANNED: &str = "$__toml_private_Spanned";
+const SPANNED_FIELDS: [&str; 3] = [
+    "$__toml_private_start",
+    "$__toml_private_end",
+    "$__toml_private_value",
+];
+const DATETIME: &str = "$__toml_private_datetime";
+
+impl<'de> Deserialize<'de> for Value {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(ValueVisitor)
+    }
+}
+
+struct ValueVisitor;
+
+impl<'de> Visitor<'de> for ValueVisitor {
+    type Value = Value;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str("a TOML value")
+    }
+
+    fn visit_bool<E>(self, _value: bool) -> Result<Value, E> {
+        Ok(Value::Boolean)
+    }
+
+    fn visit_i64<E>(self, value: i64) -> Result<Value, E> {
+        Ok(Value::Integer(value))
+    }
+
+    fn visit_f64<E>(self, _value: f64) -> Result<Value, E> {
+        Ok(Value::Float)
+    }
+
+    fn visit_str<E>(self, value: &str) -> Result<Value, E> {
+        Ok(Value::String(value.into()))
+    }
+
+    fn visit_string<E>(self, value: String) -> Result<Value, E> {
+        Ok(Value::String(value))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Value, A::Error> {
+        let mut elements = vec![];
+        while let Some(element) = seq.next_element()? {
+            elements.push(element);
+        }
+        Ok(Value::Array(elements))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Value, A::Error> {
+        let mut entries = vec![];
+        while let Some(key) = map.next_key::<Key>()? {
+            match key {
+                Key::Datetime => {
+                    let _: String = map.next_value()?;
+                    return Ok(Value::Datetime);
+                }
+                Key::Key(key) => entries.push((key, map.next_value()?)),
+            }
+        }
+        Ok(Value::Table(entries))
+    }
+}
+
+enum Key {
+    Key(Spanned<String>),
+    Datetime,
+}
+
+impl<'de> Deserialize<'de> for Key {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_struct(SPANNED, &SPANNED_FIELDS, KeyVisitor)
+    }
+}
+
+struct KeyVisitor;
+
+impl<'de> Visitor<'de> for KeyVisitor {
+    type Value = Key;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str("a TOML key")
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<Key, E> {
+        if value == DATETIME {
+            Ok(Key::Datetime)
+        } else {
+            Err(E::custom("expected a key with a location"))
+        }
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<Key, A::Error> {
+        Spanned::deserialize(de::value::MapAccessDeserializer::new(map)).map(Key::Key)
+    }
+}
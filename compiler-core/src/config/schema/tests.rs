@@ -0,0 +1,296 @@
+use super::{json_schema, validate, validate_overlay, Problem, ProblemKind, UnknownKeys};
+use crate::{ast::SrcSpan, config::PackageConfig, Error};
+
+use camino::Utf8Path;
+
+fn problems(src: &str) -> Vec<Problem> {
+    validate(src, UnknownKeys::Report)
+}
+
+fn located(src: &str, problem: &Problem) -> String {
+    src.get(problem.location.start as usize..problem.location.end as usize)
+        .unwrap_or_default()
+        .into()
+}
+
+fn error(src: &str) -> String {
+    match PackageConfig::parse(src, Utf8Path::new("gleam.toml")) {
+        Ok(_) => panic!("expected the config to be invalid"),
+        Err(error) => error.pretty_string(),
+    }
+}
+
+#[test]
+fn valid_config() {
+    let src = r#"
+name = "wibble"
+version = "1.0.0"
+gleam = ">= 1.0.0"
+licences = ["Apache-2.0"]
+description = "Wibble"
+target = "javascript"
+internal_modules = ["wibble/internal/*"]
+
+[repository]
+type = "github"
+user = "gleam-lang"
+repo = "wibble"
+
+[[links]]
+title = "Website"
+href = "https://gleam.run"
+
+[[documentation.pages]]
+title = "Guide"
+path = "guide.html"
+source = "./guide.md"
+
+[dependencies]
+gleam_stdlib = ">= 0.34.0 and < 2.0.0"
+local = { path = "../local", features = ["json"] }
+
+[dev-dependencies]
+gleeunit = { version = "~> 1.0" }
+
+[erlang]
+extra_applications = ["inets"]
+env = { started = 1979-05-27, nested = { count = 1 } }
+
+[javascript]
+typescript_declarations = true
+runtime = "deno"
+
+[javascript.deno]
+allow_net = ["example.com"]
+allow_env = true
+
+[env]
+api_key = "API_KEY"
+port = { variable = "PORT", default = "8000" }
+
+[warnings]
+max_unqualified_imports = 3
+"#;
+    assert_eq!(problems(src), vec![]);
+}
+
+#[test]
+fn aliases_are_allowed() {
+    let src = r#"
+name = "wibble"
+licenses = ["MIT"]
+
+[docs]
+pages = []
+"#;
+    assert_eq!(problems(src), vec![]);
+}
+
+#[test]
+fn unknown_key() {
+    let src = "name = \"wibble\"\nverison = \"1.0.0\"\n";
+    let [problem] = problems(src).try_into().expect("one problem");
+    assert_eq!(located(src, &problem), "verison");
+    assert_eq!(problem.key, "verison");
+    assert_eq!(
+        problem.kind,
+        ProblemKind::UnknownKey {
+            hint: Some("Did you mean `version`?".into())
+        }
+    );
+}
+
+#[test]
+fn unknown_key_in_header_table() {
+    let src = "name = \"wibble\"\n\n[javascript]\nruntme = \"deno\"\n";
+    let [problem] = problems(src).try_into().expect("one problem");
+    assert_eq!(located(src, &problem), "runtme");
+    assert_eq!(problem.key, "javascript.runtme");
+}
+
+#[test]
+fn unknown_keys_can_be_ignored() {
+    let src = "name = \"wibble\"\nwobble = 1\n";
+    assert_eq!(validate(src, UnknownKeys::Ignore), vec![]);
+}
+
+#[test]
+fn tools_can_have_any_config() {
+    let src = "name = \"wibble\"\n\n[tools.lustre]\nport = 1234\n\n[tools.lustre.html]\ntitle = \"Wibble\"\n";
+    assert_eq!(problems(src), vec![]);
+}
+
+#[test]
+fn type_mismatch() {
+    let src = "name = \"wibble\"\nlicences = \"MIT\"\n";
+    let [problem] = problems(src).try_into().expect("one problem");
+    assert_eq!(located(src, &problem), "\"MIT\"");
+    assert_eq!(
+        problem.kind,
+        ProblemKind::TypeMismatch {
+            expected: "an array".into(),
+            found: "a string",
+        }
+    );
+}
+
+#[test]
+fn type_mismatch_of_header_table_is_at_its_key() {
+    let src = "name = \"wibble\"\n\n[description]\ntext = \"Wibble\"\n";
+    let [problem] = problems(src).try_into().expect("one problem");
+    assert_eq!(located(src, &problem), "description");
+}
+
+#[test]
+fn type_mismatch_of_one_of() {
+    let src = "name = \"wibble\"\n\n[dependencies]\nwobble = 1\n";
+    let [problem] = problems(src).try_into().expect("one problem");
+    assert_eq!(located(src, &problem), "1");
+    assert_eq!(problem.key, "dependencies.wobble");
+    assert_eq!(
+        problem.kind,
+        ProblemKind::TypeMismatch {
+            expected: "a string or a table".into(),
+            found: "an integer",
+        }
+    );
+}
+
+#[test]
+fn invalid_version_range() {
+    let src = "name = \"wibble\"\n\n[dependencies]\nwobble = { version = \">= 1.0 and nope\" }\n";
+    let [problem] = problems(src).try_into().expect("one problem");
+    assert_eq!(located(src, &problem), "\">= 1.0 and nope\"");
+    assert!(matches!(
+        problem.kind,
+        ProblemKind::InvalidVersionRange { .. }
+    ));
+}
+
+#[test]
+fn invalid_version() {
+    let src = "name = \"wibble\"\nversion = \"1.0\"\n";
+    let [problem] = problems(src).try_into().expect("one problem");
+    assert_eq!(located(src, &problem), "\"1.0\"");
+    assert!(matches!(problem.kind, ProblemKind::InvalidVersion { .. }));
+}
+
+#[test]
+fn invalid_enum_value() {
+    let src = "name = \"wibble\"\ntarget = \"erlnag\"\n";
+    let [problem] = problems(src).try_into().expect("one problem");
+    assert_eq!(
+        problem.kind,
+        ProblemKind::InvalidValue {
+            error: "Expected one of `erlang`, `erl`, `javascript`, `js`.".into(),
+            hint: Some("Did you mean `erlang`?".into()),
+        }
+    );
+}
+
+#[test]
+fn invalid_licence() {
+    let src = "name = \"wibble\"\nlicences = [\"apache 2.0\"]\n";
+    let [problem] = problems(src).try_into().expect("one problem");
+    assert_eq!(located(src, &problem), "\"apache 2.0\"");
+    assert_eq!(
+        problem.kind,
+        ProblemKind::InvalidValue {
+            error: "`apache 2.0` is not a valid SPDX licence identifier.".into(),
+            hint: Some("Did you mean `Apache-2.0`?".into()),
+        }
+    );
+}
+
+#[test]
+fn invalid_package_name() {
+    let src = "name = \"Wibble\"\n";
+    let [problem] = problems(src).try_into().expect("one problem");
+    assert_eq!(located(src, &problem), "\"Wibble\"");
+}
+
+#[test]
+fn missing_keys() {
+    let src = "version = \"1.0.0\"\n\n[[links]]\ntitle = \"Website\"\n";
+    let problems = problems(src);
+    assert_eq!(
+        problems
+            .iter()
+            .map(|problem| (problem.key.as_str(), &problem.kind))
+            .collect::<Vec<_>>(),
+        vec![
+            (
+                "links",
+                &ProblemKind::MissingKey {
+                    name: "href".into()
+                }
+            ),
+            (
+                "",
+                &ProblemKind::MissingKey {
+                    name: "name".into()
+                }
+            ),
+        ]
+    );
+}
+
+#[test]
+fn syntax_error() {
+    let src = "name = \"wibble\"\nversion = \n";
+    let [problem] = problems(src).try_into().expect("one problem");
+    assert_eq!(problem.location, SrcSpan::new(26, 26));
+    assert!(matches!(problem.kind, ProblemKind::Syntax { .. }));
+}
+
+#[test]
+fn problems_are_reported_together() {
+    let src = r#"name = "wibble"
+version = "1.0"
+target = "erlnag"
+
+[dependencies]
+wobble = "~> nope"
+"#;
+    insta::assert_snapshot!(error(src));
+}
+
+#[test]
+fn parse_error_is_invalid_package_config() {
+    let result = PackageConfig::parse("nmae = \"wibble\"", Utf8Path::new("gleam.toml"));
+    assert!(matches!(result, Err(Error::InvalidPackageConfig { .. })));
+}
+
+#[test]
+fn json_schema_properties() {
+    let schema = json_schema();
+    assert_eq!(
+        schema.pointer("/required"),
+        Some(&serde_json::json!(["name"]))
+    );
+    assert_eq!(
+        schema.pointer("/properties/licenses/items/type"),
+        Some(&serde_json::json!("string"))
+    );
+    assert_eq!(
+        schema.pointer("/properties/dependencies/additionalProperties/anyOf/1/properties/ref/type"),
+        Some(&serde_json::json!("string"))
+    );
+    assert_eq!(
+        schema.pointer("/properties/target/enum/0"),
+        Some(&serde_json::json!("erlang"))
+    );
+}
+
+#[test]
+fn overlay_cannot_set_root_only_keys() {
+    let src = "name = \"wibble\"\n\n[javascript]\ntypescript_declarations = true\n";
+    let [problem] = validate_overlay(src).try_into().expect("one problem");
+    assert_eq!(located(src, &problem), "name");
+    assert_eq!(
+        problem.kind,
+        ProblemKind::UnknownKey {
+            hint: Some("This key can only be set in gleam.toml.".into())
+        }
+    );
+}
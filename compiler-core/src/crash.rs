@@ -0,0 +1,143 @@
+//! Support for the crash reports written when the compiler panics. The module
+//! being compiled is recorded while it is analysed and while code is
+//! generated for it, so that the report can include its source, and the
+//! source can be reduced to the top level definitions needed to reproduce the
+//! crash.
+
+#[cfg(test)]
+mod tests;
+
+use std::{cell::RefCell, ops::Range};
+
+use camino::{Utf8Path, Utf8PathBuf};
+use ecow::EcoString;
+
+thread_local! {
+    static COMPILING: RefCell<Option<CompilingModule>> = const { RefCell::new(None) };
+}
+
+/// A module that is being compiled on the current thread.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompilingModule {
+    pub name: EcoString,
+    pub path: Utf8PathBuf,
+}
+
+/// Records that the module is being compiled on the current thread until the
+/// returned guard is dropped.
+///
+pub fn compiling(name: &EcoString, path: &Utf8Path) -> CompilingGuard {
+    let module = CompilingModule {
+        name: name.clone(),
+        path: path.to_path_buf(),
+    };
+    let previous = COMPILING.with(|compiling| compiling.replace(Some(module)));
+    CompilingGuard { previous }
+}
+
+/// The module being compiled on the current thread, if any.
+///
+pub fn compiling_module() -> Option<CompilingModule> {
+    COMPILING.with(|compiling| compiling.borrow().clone())
+}
+
+#[derive(Debug)]
+pub struct CompilingGuard {
+    previous: Option<CompilingModule>,
+}
+
+impl Drop for CompilingGuard {
+    fn drop(&mut self) {
+        let previous = self.previous.take();
+        COMPILING.with(|compiling| *compiling.borrow_mut() = previous);
+    }
+}
+
+/// Removes the top level definitions of the module that are not needed for
+/// `still_crashes` to return true, by removing ever smaller groups of them in
+/// turn and keeping each removal that the crash survives.
+///
+/// Each definition is removed along with the code between it and the previous
+/// definition, such as its documentation and attributes. The module is
+/// returned unchanged if it cannot be parsed.
+///
+pub fn minimise(code: &str, mut still_crashes: impl FnMut(&str) -> bool) -> String {
+    let Some((header, chunks)) = top_level_chunks(code) else {
+        return code.into();
+    };
+    let render = |kept: &[usize]| {
+        let mut minimised = code.get(..header).unwrap_or_default().to_string();
+        minimised.extend(
+            kept.iter()
+                .filter_map(|index| chunks.get(*index))
+                .filter_map(|chunk| code.get(chunk.clone())),
+        );
+        minimised
+    };
+
+    let mut kept: Vec<usize> = (0..chunks.len()).collect();
+    let mut granularity = 2;
+    while kept.len() >= 2 {
+        let size = kept.len().div_ceil(granularity);
+        let reduced = kept.chunks(size).find_map(|removed| {
+            let candidate: Vec<usize> = kept
+                .iter()
+                .filter(|index| !removed.contains(index))
+                .copied()
+                .collect();
+            still_crashes(&render(&candidate)).then_some(candidate)
+        });
+        match reduced {
+            Some(candidate) => {
+                kept = candidate;
+                granularity = (granularity - 1).max(2);
+            }
+            None if granularity >= kept.len() => break,
+            None => granularity = (granularity * 2).min(kept.len()),
+        }
+    }
+    render(&kept)
+}
+
+/// The offset the first top level definition of the module starts at, and
+/// the byte range of each definition, which runs up to the start of the next
+/// one. A definition starts at the first of the attributes and comments on
+/// the lines directly above it.
+fn top_level_chunks(code: &str) -> Option<(usize, Vec<Range<usize>>)> {
+    let parsed = crate::parse::parse_module(code).ok()?;
+    let mut starts: Vec<usize> = parsed
+        .module
+        .definitions
+        .iter()
+        .map(|targeted| definition_start(code, targeted.definition.location().start as usize))
+        .collect();
+    starts.sort_unstable();
+    starts.dedup();
+    let header = starts.first().copied().unwrap_or(code.len());
+    let ends = starts.iter().skip(1).copied().chain([code.len()]);
+    let chunks = starts
+        .iter()
+        .zip(ends)
+        .map(|(start, end)| *start..end)
+        .collect();
+    Some((header, chunks))
+}
+
+fn definition_start(code: &str, location: usize) -> usize {
+    let mut start = code
+        .get(..location)
+        .and_then(|before| before.rfind('\n'))
+        .map_or(0, |newline| newline + 1);
+    while start > 0 {
+        let previous = code
+            .get(..start - 1)
+            .and_then(|before| before.rfind('\n'))
+            .map_or(0, |newline| newline + 1);
+        let line = code.get(previous..start).unwrap_or_default().trim();
+        if !(line.starts_with('@') || line.starts_with("//")) {
+            break;
+        }
+        start = previous;
+    }
+    start
+}
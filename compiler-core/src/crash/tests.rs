@@ -0,0 +1,69 @@
+use super::{compiling, compiling_module, minimise};
+
+use camino::Utf8Path;
+use ecow::EcoString;
+
+const MODULE: &str = r#"import gleam/io
+
+/// The answer.
+pub const answer = 42
+
+pub type Wibble {
+  Wibble(Int)
+}
+
+pub fn wibble() {
+  Wibble(answer)
+}
+
+@deprecated("Use wibble")
+pub fn wobble() {
+  io.println("wobble")
+}
+
+pub fn main() {
+  wibble()
+}
+"#;
+
+#[test]
+fn minimise_to_one_definition() {
+    let minimised = minimise(MODULE, |code| code.contains("pub fn wobble"));
+    insta::assert_snapshot!(minimised);
+}
+
+#[test]
+fn minimise_to_several_definitions() {
+    let minimised = minimise(MODULE, |code| {
+        code.contains("pub type Wibble") && code.contains("pub fn main")
+    });
+    insta::assert_snapshot!(minimised);
+}
+
+#[test]
+fn minimise_keeps_module_that_always_crashes_only_with_everything() {
+    let minimised = minimise(MODULE, |code| code == MODULE);
+    assert_eq!(minimised, MODULE);
+}
+
+#[test]
+fn minimise_module_that_cannot_be_parsed() {
+    let code = "pub fn main( {";
+    assert_eq!(minimise(code, |_| true), code);
+}
+
+#[test]
+fn compiling_module_is_restored() {
+    let outer: EcoString = "outer".into();
+    let inner: EcoString = "inner".into();
+    assert_eq!(compiling_module(), None);
+    let _outer = compiling(&outer, Utf8Path::new("src/outer.gleam"));
+    {
+        let _inner = compiling(&inner, Utf8Path::new("src/inner.gleam"));
+        assert_eq!(
+            compiling_module().map(|module| module.name),
+            Some(inner.clone())
+        );
+    }
+    assert_eq!(compiling_module().map(|module| module.name), Some(outer));
+}
@@ -1,15 +1,24 @@
-use std::{borrow::Borrow, cell::RefCell, collections::HashMap, error::Error as StdError};
+use std::{
+    borrow::Borrow,
+    cell::RefCell,
+    collections::{BTreeSet, HashMap, HashSet},
+    error::Error as StdError,
+    fmt::Display,
+};
 
-use crate::{Error, Result};
+use crate::Result;
 
+use debug_ignore::DebugIgnore;
 use ecow::EcoString;
 use hexpm::{
     version::{Range, Version},
     Dependency, Release,
 };
+use itertools::Itertools;
 use pubgrub::{
     error::PubGrubError,
-    solver::{choose_package_with_fewest_versions, Dependencies},
+    report::{DerivationTree, External},
+    solver::Dependencies,
     type_aliases::Map,
 };
 
@@ -19,20 +28,234 @@ pub type ResolutionError = PubGrubError<String, Version>;
 
 type PubgrubRange = pubgrub::range::Range<Version>;
 
+/// The outcome of resolving the versions of a package's dependencies.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Resolution {
+    /// The version selected for each package, not including the root package.
+    pub packages: PackageVersions,
+    /// The requirements placed on each selected package, and where they come
+    /// from.
+    pub requirements: HashMap<String, Vec<RequiredBy>>,
+    /// The kind of source the selected release of each package was looked
+    /// up from. Packages that were provided up front are not included.
+    pub sources: HashMap<String, SourceKind>,
+    pub statistics: FetchStatistics,
+    pub solver: SolverStatistics,
+    pub warnings: Vec<ResolutionWarning>,
+}
+
+impl Resolution {
+    /// The selected packages with the most requirements placed on them, most
+    /// first, along with how many requirements there are. At most `count`
+    /// packages are returned.
+    pub fn most_constrained(&self, count: usize) -> Vec<(&str, usize)> {
+        let mut packages: Vec<_> = self
+            .requirements
+            .iter()
+            .filter(|(name, _)| self.packages.contains_key(name.as_str()))
+            .map(|(name, required_by)| (name.as_str(), required_by.len()))
+            .collect();
+        packages.sort_by(|(a_name, a), (b_name, b)| b.cmp(a).then_with(|| a_name.cmp(b_name)));
+        packages.truncate(count);
+        packages
+    }
+}
+
+/// A requirement on a package, made by a release of another package or by the
+/// root package.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequiredBy {
+    pub package: String,
+    pub version: Version,
+    pub requirement: Range,
+}
+
+/// How packages were looked up while resolving versions.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FetchStatistics {
+    /// How many packages were looked up with the fetcher for each kind of
+    /// source.
+    pub fetched: HashMap<SourceKind, usize>,
+    /// How many packages were provided up front and so never looked up.
+    pub provided: usize,
+    /// How many times a package was needed by the solver and was already
+    /// known, so did not have to be looked up.
+    pub cache_hits: usize,
+}
+
+impl FetchStatistics {
+    /// The share of the times a package was needed that it was already known,
+    /// from 0 to 1.
+    pub fn cache_hit_rate(&self) -> f64 {
+        let misses: usize = self.fetched.values().sum();
+        match self.cache_hits + misses {
+            0 => 0.0,
+            total => self.cache_hits as f64 / total as f64,
+        }
+    }
+}
+
+/// The work the solver did to resolve versions.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SolverStatistics {
+    /// How many times a version of a package was picked.
+    pub decisions: usize,
+    /// How many times a version of a package was picked again, after the
+    /// solver went back on an earlier pick of it.
+    pub backtracks: usize,
+}
+
+/// Receives events from the solver as it resolves versions, such as to show
+/// its progress. Each method does nothing unless it is implemented.
+pub trait ResolutionObserver {
+    /// A package was looked up, from the given kind of source.
+    fn package_fetched(&self, _name: &str, _source: &SourceKind) {}
+    /// A version of a package was picked.
+    fn version_chosen(&self, _name: &str, _version: &Version) {}
+    /// No version of a package satisfies all the requirements on it, so the
+    /// solver must go back on an earlier pick.
+    fn conflict_encountered(&self, _name: &str) {}
+    /// A version of a package was picked again, after the solver went back on
+    /// an earlier pick of it.
+    fn backtracked(&self, _name: &str, _version: &Version) {}
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct NullResolutionObserver;
+
+impl ResolutionObserver for NullResolutionObserver {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ResolutionWarning {
+    /// A retired version was selected because it is locked.
+    RetiredVersionLocked {
+        package: String,
+        version: Version,
+        retirement: hexpm::RetirementStatus,
+    },
+    /// A pre-release version was selected.
+    PreReleaseSelected { package: String, version: Version },
+}
+
+impl ResolutionWarning {
+    pub fn package(&self) -> &str {
+        match self {
+            ResolutionWarning::RetiredVersionLocked { package, .. }
+            | ResolutionWarning::PreReleaseSelected { package, .. } => package,
+        }
+    }
+}
+
+/// The newest release of a package that a locked version of it could be
+/// updated to in place of a retired one. It must not be retired or a
+/// pre-release, must be newer than the locked version and compatible with it
+/// according to semantic versioning, and must satisfy the requirement on the
+/// package, if there is one.
+pub fn newest_compatible_release(
+    package: &hexpm::Package,
+    locked: &Version,
+    requirement: Option<&Range>,
+) -> Option<Version> {
+    let requirement = match requirement.map(Range::to_pubgrub) {
+        Some(Ok(range)) => Some(range),
+        Some(Err(_)) => return None,
+        None => None,
+    };
+    let compatible = |version: &Version| match locked.major {
+        0 => version.major == 0 && version.minor == locked.minor,
+        major => version.major == major,
+    };
+    package
+        .releases
+        .iter()
+        .filter(|release| !release.is_retired() && !release.version.is_pre())
+        .map(|release| &release.version)
+        .filter(|version| *version > locked && compatible(version))
+        .filter(|version| {
+            requirement
+                .as_ref()
+                .is_none_or(|range| range.contains(version))
+        })
+        .max()
+        .cloned()
+}
+
+/// How to pick between the versions of a package that satisfy its
+/// requirements.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Deserialize)]
+pub enum ResolutionStrategy {
+    /// Pick the newest version. Packages with the fewest versions to choose
+    /// from are picked first.
+    #[default]
+    #[serde(rename = "fewest-versions")]
+    FewestVersions,
+    /// Pick the version that depends on the fewest packages that nothing
+    /// picked so far depends on, preferring the newest of those. This keeps
+    /// the dependencies of an application small, but may pick older versions.
+    #[serde(rename = "fewest-packages")]
+    FewestPackages,
+}
+
+/// Options for `resolve_versions`. The defaults pick the newest versions,
+/// with no minimum versions and no observer.
+pub struct ResolveOptions<'a> {
+    pub strategy: ResolutionStrategy,
+    /// A version of a package below its minimum version is never picked,
+    /// whether the package is a dependency of the root package or of another
+    /// package. This keeps out versions that are known to be unsafe even when
+    /// the requirements on the package would allow them.
+    pub minimums: HashMap<EcoString, Version>,
+    /// Told about each step the solver takes as it goes.
+    pub observer: &'a dyn ResolutionObserver,
+}
+
+impl Default for ResolveOptions<'_> {
+    fn default() -> Self {
+        Self {
+            strategy: ResolutionStrategy::default(),
+            minimums: HashMap::new(),
+            observer: &NullResolutionObserver,
+        }
+    }
+}
+
+impl std::fmt::Debug for ResolveOptions<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ResolveOptions")
+            .field("strategy", &self.strategy)
+            .field("minimums", &self.minimums)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Picks a version of each of the root package's dependencies, and of theirs,
+/// that satisfies all the requirements on it.
+///
+/// This does not depend on the rest of the build tool, so it can be used by
+/// other tools which report the resolution or its failure in their own way.
+///
+// The error is the solver's own so that callers get everything it knows.
+#[allow(clippy::result_large_err)]
 pub fn resolve_versions<Requirements>(
-    package_fetcher: Box<dyn PackageFetcher>,
+    package_fetchers: PackageFetchers,
     provided_packages: HashMap<EcoString, hexpm::Package>,
     root_name: EcoString,
     dependencies: Requirements,
     locked: &HashMap<EcoString, Version>,
-) -> Result<PackageVersions>
+    options: ResolveOptions<'_>,
+) -> Result<Resolution, ResolutionError>
 where
     Requirements: Iterator<Item = (EcoString, Range)>,
 {
+    let ResolveOptions {
+        strategy,
+        minimums,
+        observer,
+    } = options;
+    let minimums = &minimums;
     tracing::info!("resolving_versions");
     let root_version = Version::new(0, 0, 0);
-    let requirements =
-        root_dependencies(dependencies, locked).map_err(Error::dependency_resolution_failed)?;
+    let requirements = root_dependencies(dependencies, locked, minimums)?;
 
     // Creating a map of all the required packages that have exact versions specified
     let exact_deps = &requirements
@@ -53,17 +276,286 @@ where
         }],
     };
 
-    let packages = pubgrub::solver::resolve(
-        &DependencyProvider::new(package_fetcher, provided_packages, root, locked, exact_deps),
-        root_name.as_str().into(),
-        root_version,
-    )
-    .map_err(Error::dependency_resolution_failed)?
-    .into_iter()
-    .filter(|(name, _)| name.as_str() != root_name.as_str())
-    .collect();
+    let provided: Vec<_> = provided_packages.keys().cloned().collect();
+    let provider = DependencyProvider::new(
+        package_fetchers,
+        strategy,
+        provided_packages,
+        root,
+        locked,
+        exact_deps,
+        minimums,
+        observer,
+    );
+    let selected = pubgrub::solver::resolve(&provider, root_name.as_str().into(), root_version)
+        .map_err(|error| explain_minimums(error, minimums))?;
+
+    let packages = provider.packages.borrow();
+    let mut requirements: HashMap<String, Vec<RequiredBy>> = HashMap::new();
+    let mut warnings = vec![];
+    // The solver's map of selections is iterated in name order so the
+    // requirements and warnings are listed in the same order on every run.
+    for (name, version) in selected.iter().sorted_by(|a, b| a.0.cmp(b.0)) {
+        let Some(release) = packages
+            .get(name.as_str())
+            .into_iter()
+            .flat_map(|package| package.releases.iter())
+            .find(|release| &release.version == version)
+        else {
+            continue;
+        };
+        for (dependency, requirement) in &release.requirements {
+            requirements
+                .entry(dependency.clone())
+                .or_default()
+                .push(RequiredBy {
+                    package: name.clone(),
+                    version: version.clone(),
+                    requirement: requirement.requirement.clone(),
+                });
+        }
+        if name.as_str() == root_name.as_str() {
+            continue;
+        }
+        if let Some(retirement) = &release.retirement_status {
+            warnings.push(ResolutionWarning::RetiredVersionLocked {
+                package: name.clone(),
+                version: version.clone(),
+                retirement: retirement.clone(),
+            });
+        }
+        if version.is_pre() {
+            warnings.push(ResolutionWarning::PreReleaseSelected {
+                package: name.clone(),
+                version: version.clone(),
+            });
+        }
+    }
+    requirements.retain(|name, _| selected.contains_key(name));
+    for required_by in requirements.values_mut() {
+        required_by.sort_by(|a, b| a.package.cmp(&b.package));
+    }
+    warnings.sort_by(|a, b| a.package().cmp(b.package()));
+
+    let mut release_sources = provider.release_sources.take();
+    let sources = selected
+        .iter()
+        .filter_map(|(name, version)| {
+            let source = release_sources.get_mut(name)?.remove(version)?;
+            Some((name.clone(), source))
+        })
+        .collect();
+
+    let statistics = FetchStatistics {
+        fetched: provider.fetched.take(),
+        provided: provided
+            .iter()
+            .filter(|name| selected.contains_key(name.as_str()))
+            .count(),
+        cache_hits: provider.cache_hits.take(),
+    };
+    let solver = SolverStatistics {
+        decisions: provider.decisions.take(),
+        backtracks: provider.backtracks.take(),
+    };
+
+    let packages = selected
+        .into_iter()
+        .filter(|(name, _)| name.as_str() != root_name.as_str())
+        .collect();
 
-    Ok(packages)
+    Ok(Resolution {
+        packages,
+        requirements,
+        sources,
+        statistics,
+        solver,
+        warnings,
+    })
+}
+
+/// A change to the requirements of the root package, or to its locked
+/// versions, that would let versions be resolved.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Suggestion {
+    /// Change the requirement on a package in `gleam.toml`.
+    Relax { package: String, requirement: Range },
+    /// Let a locked package be resolved to a different version.
+    Unlock { package: String },
+}
+
+impl Display for Suggestion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Suggestion::Relax {
+                package,
+                requirement,
+            } => write!(f, "relax {package} to {requirement}"),
+            Suggestion::Unlock { package } => write!(f, "unlock {package}"),
+        }
+    }
+}
+
+/// Works out changes that would let versions be resolved after resolution
+/// failed with `error`.
+///
+/// Only the packages involved in the conflict are considered. Unlocking them
+/// is suggested first, as that leaves `gleam.toml` as it is, and then
+/// relaxing the root package's requirements on them to allow the version that
+/// would be picked without them. Each suggestion is checked by resolving the
+/// versions again with `resolve`, which is given the requirements and locked
+/// versions to use.
+///
+/// `requirements` are the requirements that may be changed. Requirements that
+/// cannot be, such as those on path dependencies, are left for `resolve` to
+/// add.
+///
+pub fn suggest_fixes<Resolve>(
+    error: &ResolutionError,
+    requirements: &HashMap<EcoString, Range>,
+    locked: &HashMap<EcoString, Version>,
+    mut resolve: Resolve,
+) -> Vec<Suggestion>
+where
+    Resolve: FnMut(
+        HashMap<EcoString, Range>,
+        HashMap<EcoString, Version>,
+    ) -> Result<Resolution, ResolutionError>,
+{
+    let mut conflicting = match error {
+        ResolutionError::NoSolution(tree) => conflicting_packages(tree),
+        ResolutionError::Failure(_) => BTreeSet::new(),
+        _ => return vec![],
+    };
+    // Locked versions the root package's requirements no longer allow
+    for (name, range) in requirements {
+        let allows_locked = match (locked.get(name), range.to_pubgrub()) {
+            (Some(version), Ok(range)) => range.contains(version),
+            _ => true,
+        };
+        if !allows_locked {
+            let _ = conflicting.insert(name.to_string());
+        }
+    }
+
+    let unlock = |packages: &[String]| {
+        let mut locked = locked.clone();
+        for package in packages {
+            let _ = locked.remove(package.as_str());
+        }
+        locked
+    };
+
+    // Try unlocking each conflicting package. If none is enough alone, unlock
+    // all of them, along with any locked packages in the conflicts that
+    // remain, until the conflicts are gone.
+    let mut unlockable: Vec<_> = conflicting
+        .iter()
+        .filter(|name| locked.contains_key(name.as_str()))
+        .cloned()
+        .collect();
+    let mut suggestions: Vec<_> = unlockable
+        .iter()
+        .filter(|package| {
+            resolve(requirements.clone(), unlock(std::slice::from_ref(*package))).is_ok()
+        })
+        .map(|package| Suggestion::Unlock {
+            package: package.to_string(),
+        })
+        .collect();
+    while suggestions.is_empty() && !unlockable.is_empty() {
+        match resolve(requirements.clone(), unlock(&unlockable)) {
+            Ok(_) => {
+                unlockable.sort();
+                suggestions = unlockable
+                    .iter()
+                    .map(|package| Suggestion::Unlock {
+                        package: package.to_string(),
+                    })
+                    .collect();
+            }
+            Err(ResolutionError::NoSolution(tree)) => {
+                let more: Vec<_> = conflicting_packages(&tree)
+                    .into_iter()
+                    .filter(|name| locked.contains_key(name.as_str()) && !unlockable.contains(name))
+                    .collect();
+                if more.is_empty() {
+                    break;
+                }
+                unlockable.extend(more);
+            }
+            Err(_) => break,
+        }
+    }
+    if !suggestions.is_empty() {
+        return suggestions;
+    }
+
+    // Try relaxing each requirement, and then all of them at once
+    let relaxable: Vec<_> = conflicting
+        .iter()
+        .filter(|name| requirements.contains_key(name.as_str()))
+        .cloned()
+        .collect();
+    let mut relax = |packages: &[String]| {
+        let mut relaxed = requirements.clone();
+        for package in packages {
+            let _ = relaxed.insert(package.as_str().into(), Range::new(">= 0.0.0".into()));
+        }
+        let resolution = resolve(relaxed.clone(), unlock(packages)).ok()?;
+        let mut suggestions = vec![];
+        for package in packages {
+            let version = resolution.packages.get(package.as_str())?;
+            let requirement = Range::new(format!(
+                ">= {version} and < {major}.0.0",
+                major = version.major + 1
+            ));
+            let _ = relaxed.insert(package.as_str().into(), requirement.clone());
+            suggestions.push(Suggestion::Relax {
+                package: package.to_string(),
+                requirement,
+            });
+        }
+        // Check the suggested requirements work, not just unbounded ones
+        let _ = resolve(relaxed, unlock(packages)).ok()?;
+        Some(suggestions)
+    };
+    let mut suggestions: Vec<_> = relaxable
+        .iter()
+        .filter_map(|package| relax(std::slice::from_ref(package)))
+        .flatten()
+        .collect();
+    if suggestions.is_empty() && relaxable.len() > 1 {
+        suggestions = relax(&relaxable).unwrap_or_default();
+    }
+    suggestions
+}
+
+/// The packages involved in a failure to resolve versions, in order of name.
+pub fn conflicting_packages(tree: &DerivationTree<String, Version>) -> BTreeSet<String> {
+    fn collect<'a>(tree: &'a DerivationTree<String, Version>, packages: &mut BTreeSet<&'a String>) {
+        match tree {
+            DerivationTree::External(external) => match external {
+                External::NotRoot(package, _)
+                | External::NoVersions(package, _)
+                | External::UnavailableDependencies(package, _) => {
+                    let _ = packages.insert(package);
+                }
+                External::FromDependencyOf(package, _, dependency, _) => {
+                    let _ = packages.insert(package);
+                    let _ = packages.insert(dependency);
+                }
+            },
+            DerivationTree::Derived(derived) => {
+                collect(&derived.cause1, packages);
+                collect(&derived.cause2, packages);
+            }
+        }
+    }
+
+    let mut packages = BTreeSet::new();
+    collect(tree, &mut packages);
+    packages.into_iter().cloned().collect()
 }
 
 // If the string would parse to an exact version then return the version
@@ -88,6 +580,7 @@ fn parse_exact_version(ver: &str) -> Option<Version> {
 fn root_dependencies<Requirements>(
     base_requirements: Requirements,
     locked: &HashMap<EcoString, Version>,
+    minimums: &HashMap<EcoString, Version>,
 ) -> Result<HashMap<String, Dependency>, ResolutionError>
 where
     Requirements: Iterator<Item = (EcoString, Range)>,
@@ -127,10 +620,17 @@ where
             // If the version was locked we verify that the requirement is
             // compatible with the locked version.
             Some(locked_version) => {
-                let compatible = range
-                    .to_pubgrub()
-                    .map_err(|e| ResolutionError::Failure(format!("Failed to parse range {}", e)))?
-                    .contains(locked_version);
+                // An exact version only allows that version, as the range
+                // pubgrub makes for it would allow a pre-release's release.
+                let compatible = match parse_exact_version(range.as_str()) {
+                    Some(exact) => &exact == locked_version,
+                    None => range
+                        .to_pubgrub()
+                        .map_err(|e| {
+                            ResolutionError::Failure(format!("Failed to parse range {}", e))
+                        })?
+                        .contains(locked_version),
+                };
                 if !compatible {
                     return Err(ResolutionError::Failure(format!(
                         "{package} is specified with the requirement `{requirement}`, \
@@ -144,42 +644,284 @@ but it is locked to {version}, which is incompatible.",
         };
     }
 
+    // Versions below the minimum version of a package are never picked, so
+    // a lock or requirement that only allows older versions can't be met.
+    for (name, minimum) in minimums.iter().sorted_by(|a, b| a.0.cmp(b.0)) {
+        if let Some(version) = locked.get(name).filter(|version| *version < minimum) {
+            return Err(ResolutionError::Failure(format!(
+                "{name} is locked to {version}, which is below its minimum version {minimum}."
+            )));
+        }
+        let Some(dependency) = requirements.get(name.as_str()) else {
+            continue;
+        };
+        let range = dependency
+            .requirement
+            .to_pubgrub()
+            .map_err(|e| ResolutionError::Failure(format!("Failed to parse range {}", e)))?;
+        if range.intersection(&PubgrubRange::higher_than(minimum.clone())) == PubgrubRange::none() {
+            return Err(ResolutionError::Failure(format!(
+                "{name} is specified with the requirement `{requirement}`, \
+but its minimum version is {minimum}, which is incompatible.",
+                requirement = dependency.requirement,
+            )));
+        }
+    }
+
     Ok(requirements)
 }
 
+/// Explains a failed resolution in terms of the minimum versions of packages
+/// if any of the packages in conflict have one, as the solver doesn't know
+/// why the older versions of those packages were unavailable.
+fn explain_minimums(
+    error: ResolutionError,
+    minimums: &HashMap<EcoString, Version>,
+) -> ResolutionError {
+    let ResolutionError::NoSolution(tree) = &error else {
+        return error;
+    };
+    let mut tree = tree.clone();
+    tree.collapse_no_versions();
+    let conflicting = conflicting_packages(&tree);
+    let below_minimums = conflicting
+        .iter()
+        .filter_map(|name| {
+            let minimum = minimums.get(name.as_str())?;
+            Some(format!("- {name} >= {minimum}"))
+        })
+        .join("\n");
+    if below_minimums.is_empty() {
+        return error;
+    }
+    ResolutionError::Failure(format!(
+        "No versions of the dependencies satisfy both their requirements and the \
+minimum versions of these packages:
+
+{below_minimums}
+
+The conflicting packages are:
+
+{conflicting}",
+        conflicting = conflicting
+            .iter()
+            .map(|name| format!("- {name}"))
+            .join("\n")
+    ))
+}
+
 pub trait PackageFetcher {
     fn get_dependencies(&self, package: &str) -> Result<hexpm::Package, Box<dyn StdError>>;
 }
 
+/// The kind of source a package comes from, which decides the fetcher used to
+/// look it up.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum SourceKind {
+    Hex,
+    /// The repository of a Hex organisation, which holds the packages it
+    /// publishes privately.
+    HexOrganization(EcoString),
+    Git,
+    Path,
+    /// A source identified by the scheme of a URL, such as `artifacts` for a
+    /// dependency with the repository `artifacts://packages.example.com`.
+    Custom(EcoString),
+}
+
+impl SourceKind {
+    /// The kind of source of a dependency with the given repository. A
+    /// repository that is a URL is a custom source named by its scheme, and
+    /// any other name than `hexpm` is a Hex organisation, which may also be
+    /// written as `hexpm:<organisation>`.
+    pub fn of_repository(repository: Option<&str>) -> Self {
+        match repository {
+            None | Some("" | "hexpm") => Self::Hex,
+            Some(repository) => match repository.split_once("://") {
+                Some((scheme, _)) if !scheme.is_empty() => Self::Custom(scheme.into()),
+                _ => {
+                    let organization = repository.strip_prefix("hexpm:").unwrap_or(repository);
+                    Self::HexOrganization(organization.into())
+                }
+            },
+        }
+    }
+}
+
+impl Display for SourceKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SourceKind::Hex => f.write_str("Hex"),
+            SourceKind::HexOrganization(organization) => {
+                write!(f, "Hex organization `{organization}`")
+            }
+            SourceKind::Git => f.write_str("git"),
+            SourceKind::Path => f.write_str("path"),
+            SourceKind::Custom(scheme) => write!(f, "`{scheme}`"),
+        }
+    }
+}
+
+/// The fetchers used to look up packages while resolving versions, one for
+/// each kind of source.
+///
+/// A package is looked up from the source it was given with `set_source`, or
+/// otherwise from the source implied by the repository of the dependency on
+/// it, which is Hex unless the repository names a Hex organisation or is a
+/// URL.
+///
+/// When a priority has been given with `set_priority` a package implied to
+/// come from Hex is instead looked up from each of the sources in that order.
+/// A release found in more than one of them is taken from the first.
+#[derive(Debug, Default)]
+pub struct PackageFetchers {
+    fetchers: HashMap<SourceKind, DebugIgnore<Box<dyn PackageFetcher>>>,
+    sources: HashMap<EcoString, SourceKind>,
+    priority: Vec<SourceKind>,
+}
+
+impl PackageFetchers {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers the fetcher for a kind of source, returning the fetcher it
+    /// replaces if there was one.
+    pub fn register(
+        &mut self,
+        kind: SourceKind,
+        fetcher: Box<dyn PackageFetcher>,
+    ) -> Option<Box<dyn PackageFetcher>> {
+        self.fetchers
+            .insert(kind, DebugIgnore(fetcher))
+            .map(|fetcher| fetcher.0)
+    }
+
+    /// Looks the package up from the given kind of source, whatever the
+    /// dependencies on it say.
+    pub fn set_source(&mut self, package: EcoString, kind: SourceKind) -> Option<SourceKind> {
+        self.sources.insert(package, kind)
+    }
+
+    /// Looks packages implied to come from Hex up from each of the given
+    /// kinds of source, highest priority first.
+    pub fn set_priority(&mut self, kinds: Vec<SourceKind>) {
+        self.priority = kinds;
+    }
+
+    /// The kinds of source the package is looked up from, in order, if the
+    /// dependency on it implies the given kind.
+    fn sources<'a>(&'a self, package: &str, kind: &'a SourceKind) -> Vec<&'a SourceKind> {
+        match self.sources.get(package) {
+            Some(kind) => vec![kind],
+            None if *kind == SourceKind::Hex && !self.priority.is_empty() => {
+                self.priority.iter().collect()
+            }
+            None => vec![kind],
+        }
+    }
+
+    /// Looks the package up from each of its sources, returning what each
+    /// one that has the package knows of it. A source not having the package
+    /// is only an error if none of them have it.
+    fn get_dependencies<'a>(
+        &'a self,
+        package: &str,
+        kind: &'a SourceKind,
+    ) -> Result<Vec<(&'a SourceKind, hexpm::Package)>, Box<dyn StdError>> {
+        let mut found = vec![];
+        let mut not_found = None;
+        for kind in self.sources(package, kind) {
+            let Some(fetcher) = self.fetchers.get(kind) else {
+                return Err(format!(
+                    "No package fetcher is registered for {kind} packages, which `{package}` is"
+                )
+                .into());
+            };
+            match fetcher.get_dependencies(package) {
+                Ok(found_package) => found.push((kind, found_package)),
+                Err(error) if is_not_found(error.as_ref()) => not_found = Some(error),
+                Err(error) => return Err(error),
+            }
+        }
+        match not_found {
+            Some(error) if found.is_empty() => Err(error),
+            _ => Ok(found),
+        }
+    }
+}
+
+fn is_not_found(error: &(dyn StdError + 'static)) -> bool {
+    matches!(
+        error.downcast_ref::<hexpm::ApiError>(),
+        Some(hexpm::ApiError::NotFound)
+    )
+}
+
 struct DependencyProvider<'a> {
     packages: RefCell<HashMap<EcoString, hexpm::Package>>,
-    remote: Box<dyn PackageFetcher>,
+    remote: PackageFetchers,
+    /// The kind of source of each package, from the repository of the first
+    /// dependency on it that was seen.
+    sources: RefCell<HashMap<String, SourceKind>>,
+    /// The kind of source each release of each package was looked up from.
+    release_sources: RefCell<HashMap<String, HashMap<Version, SourceKind>>>,
+    /// How many packages have been looked up from each kind of source.
+    fetched: RefCell<HashMap<SourceKind, usize>>,
+    /// How many times a package was needed and was already known.
+    cache_hits: RefCell<usize>,
+    /// The packages a version has been picked for so far.
+    decided: RefCell<HashSet<String>>,
+    decisions: RefCell<usize>,
+    backtracks: RefCell<usize>,
+    strategy: ResolutionStrategy,
+    /// Every package depended upon by the versions picked so far.
+    introduced: RefCell<HashSet<String>>,
     locked: &'a HashMap<EcoString, Version>,
     // Map of packages where an exact version was requested
     // We need this because by default pubgrub checks exact version by checking if a version is between the exact
     // and the version 1 bump ahead. That default breaks on prerelease builds since a bump includes the whole patch
     exact_only: &'a HashMap<String, Version>,
+    /// The lowest version of each package that may be picked.
+    minimums: &'a HashMap<EcoString, Version>,
+    observer: &'a dyn ResolutionObserver,
 }
 
 impl<'a> DependencyProvider<'a> {
+    #[allow(clippy::too_many_arguments)]
     fn new(
-        remote: Box<dyn PackageFetcher>,
+        remote: PackageFetchers,
+        strategy: ResolutionStrategy,
         mut packages: HashMap<EcoString, hexpm::Package>,
         root: hexpm::Package,
         locked: &'a HashMap<EcoString, Version>,
         exact_only: &'a HashMap<String, Version>,
+        minimums: &'a HashMap<EcoString, Version>,
+        observer: &'a dyn ResolutionObserver,
     ) -> Self {
         let _ = packages.insert(root.name.as_str().into(), root);
         Self {
             packages: RefCell::new(packages),
             locked,
             remote,
+            sources: RefCell::new(HashMap::new()),
+            release_sources: RefCell::new(HashMap::new()),
+            fetched: RefCell::new(HashMap::new()),
+            cache_hits: RefCell::new(0),
+            decided: RefCell::new(HashSet::new()),
+            decisions: RefCell::new(0),
+            backtracks: RefCell::new(0),
+            strategy,
+            introduced: RefCell::new(HashSet::new()),
             exact_only,
+            minimums,
+            observer,
         }
     }
 
     /// Download information about the package from the registry into the local
-    /// store. Does nothing if the packages are already known.
+    /// store. Does nothing but count a cache hit if the package is already
+    /// known.
     ///
     /// Package versions are sorted from newest to oldest, with all pre-releases
     /// at the end to ensure that a non-prerelease version will be picked first
@@ -192,11 +934,49 @@ impl<'a> DependencyProvider<'a> {
         name: &str,
     ) -> Result<(), Box<dyn StdError>> {
         let mut packages = self.packages.borrow_mut();
-        if packages.get(name).is_none() {
-            let mut package = self.remote.get_dependencies(name)?;
-            // Sort the packages from newest to oldest, pres after all others
-            package.releases.sort_by(|a, b| a.version.cmp(&b.version));
-            package.releases.reverse();
+        if packages.contains_key(name) {
+            *self.cache_hits.borrow_mut() += 1;
+        } else {
+            let source = self
+                .sources
+                .borrow()
+                .get(name)
+                .cloned()
+                .unwrap_or(SourceKind::Hex);
+            let mut found = self.remote.get_dependencies(name, &source)?.into_iter();
+            let (kind, mut package) = found
+                .next()
+                .ok_or_else(|| format!("No source has the package `{name}`"))?;
+            let mut release_sources: HashMap<_, _> = package
+                .releases
+                .iter()
+                .map(|release| (release.version.clone(), kind.clone()))
+                .collect();
+            *self.fetched.borrow_mut().entry(kind.clone()).or_default() += 1;
+            self.observer.package_fetched(name, kind);
+            // Releases already found in a source of higher priority are kept
+            for (kind, other) in found {
+                *self.fetched.borrow_mut().entry(kind.clone()).or_default() += 1;
+                for release in other.releases {
+                    if !release_sources.contains_key(&release.version) {
+                        let _ = release_sources.insert(release.version.clone(), kind.clone());
+                        package.releases.push(release);
+                    }
+                }
+            }
+            let _ = self
+                .release_sources
+                .borrow_mut()
+                .insert(name.into(), release_sources);
+            // Sort the packages from newest to oldest, pres after all others.
+            // Versions that differ only in build metadata have the same
+            // precedence, so they are ordered by it to not depend on the
+            // order the registry listed them in.
+            package.releases.sort_by(|a, b| {
+                b.version
+                    .cmp(&a.version)
+                    .then_with(|| a.version.build.cmp(&b.version.build))
+            });
             let (pre, mut norm): (_, Vec<_>) = package
                 .releases
                 .into_iter()
@@ -222,29 +1002,71 @@ impl<'a> pubgrub::solver::DependencyProvider<PackageName, Version> for Dependenc
                 Ok(pair)
             })
             .collect::<Result<_, _>>()?;
+        let introduced = self.introduced.borrow();
         let list_available_versions = |name: &String| {
             let name = name.as_str();
             let exact_package = self.exact_only.get(name);
-            self.packages
+            let mut releases: Vec<_> = self
+                .packages
                 .borrow()
                 .get(name)
-                .cloned()
                 .into_iter()
-                .flat_map(move |p| {
-                    p.releases
-                        .into_iter()
-                        // if an exact version of a package is specified then we only want to allow that version as available
-                        .filter(move |release| match exact_package {
-                            Some(ver) => ver == &release.version,
-                            _ => true,
-                        })
+                .flat_map(|p| p.releases.iter())
+                // if an exact version of a package is specified then we only want to allow that version as available
+                .filter(|release| match exact_package {
+                    Some(ver) => ver == &release.version,
+                    _ => true,
                 })
-                .map(|p| p.version)
+                .cloned()
+                .collect();
+            if self.strategy == ResolutionStrategy::FewestPackages {
+                // The sort is stable, so versions bringing in the same number
+                // of packages stay newest first, with pre-releases last.
+                releases.sort_by_key(|release| {
+                    let new_packages = release
+                        .requirements
+                        .keys()
+                        .filter(|name| !introduced.contains(*name))
+                        .count();
+                    (release.version.is_pre(), new_packages)
+                });
+            }
+            releases.into_iter().map(|p| p.version)
         };
-        Ok(choose_package_with_fewest_versions(
-            list_available_versions,
-            potential_packages.into_iter(),
-        ))
+        // Pick the package with the fewest versions to choose from. Packages
+        // with as many are picked by name rather than by the order the solver
+        // lists them in, which comes from a hash map.
+        let (name, range) = potential_packages
+            .into_iter()
+            .map(|(name, range)| {
+                let count = list_available_versions(name.borrow())
+                    .filter(|version| range.borrow().contains(version))
+                    .count();
+                (count, name, range)
+            })
+            .min_by(|(a_count, a_name, _), (b_count, b_name, _)| {
+                a_count
+                    .cmp(b_count)
+                    .then_with(|| a_name.borrow().cmp(b_name.borrow()))
+            })
+            .map(|(_, name, range)| (name, range))
+            .expect("potential_packages gave us an empty iterator");
+        let version =
+            list_available_versions(name.borrow()).find(|version| range.borrow().contains(version));
+        match &version {
+            Some(version) => {
+                *self.decisions.borrow_mut() += 1;
+                self.observer.version_chosen(name.borrow(), version);
+                // The solver only asks about packages without a picked version,
+                // so one picked before has had that pick undone.
+                if !self.decided.borrow_mut().insert(name.borrow().clone()) {
+                    *self.backtracks.borrow_mut() += 1;
+                    self.observer.backtracked(name.borrow(), version);
+                }
+            }
+            None => self.observer.conflict_encountered(name.borrow()),
+        }
+        Ok((name, version))
     }
 
     fn get_dependencies(
@@ -269,15 +1091,90 @@ impl<'a> pubgrub::solver::DependencyProvider<PackageName, Version> for Dependenc
             return Ok(Dependencies::Unknown);
         }
 
+        // Versions below the minimum version of the package are never used
+        if self
+            .minimums
+            .get(name.as_str())
+            .is_some_and(|minimum| version < minimum)
+        {
+            return Ok(Dependencies::Unknown);
+        }
+
+        // The requirements are a hash map, so they are added in name order
+        // for the solver to see them in the same order on every run.
+        let requirements = release
+            .requirements
+            .iter()
+            .sorted_by(|a, b| a.0.cmp(b.0))
+            .map(|(name, d)| (name.clone(), d.clone()))
+            .collect_vec();
+        drop(packages);
+
         let mut deps: Map<String, PubgrubRange> = Default::default();
-        for (name, d) in &release.requirements {
-            let range = d.requirement.to_pubgrub()?;
-            let _ = deps.insert(name.clone(), range);
+        for (name, d) in requirements {
+            let _ = self.introduced.borrow_mut().insert(name.clone());
+            let _ = self
+                .sources
+                .borrow_mut()
+                .entry(name.clone())
+                .or_insert_with(|| SourceKind::of_repository(d.repository.as_deref()));
+            // The versions of the dependency are needed to align its range.
+            // If they cannot be fetched the error is reported when the
+            // solver comes to choose a version of it.
+            let _ = self.ensure_package_fetched(&name);
+            let packages = self.packages.borrow();
+            let versions = packages
+                .get(name.as_str())
+                .into_iter()
+                .flat_map(|package| package.releases.iter())
+                .map(|release| &release.version);
+            let range = align_to_decisions(d.requirement.to_pubgrub()?, versions);
+            // A version that requires no version at all of a package can
+            // never be used, rather than being an error.
+            if range == PubgrubRange::none() {
+                return Ok(Dependencies::Unknown);
+            }
+            let _ = deps.insert(name, range);
         }
         Ok(Dependencies::Known(deps))
     }
 }
 
+/// pubgrub takes a decision on a version to stand for the range from it up
+/// to its next patch, and panics if a requirement's range later turns out to
+/// include only part of that range, as `< 1.0.0` does for `1.0.0-rc1`. The
+/// range is widened or narrowed so that it holds the whole range of each
+/// version that could be decided on or none of it, which makes no difference
+/// when no other version of the package falls within that range.
+///
+/// When another version does fall within it, such as `1.0.0` within the range
+/// of `1.0.0-rc1`, the range is left as it is, as any change to it would also
+/// change whether that other version is allowed.
+fn align_to_decisions<'a>(
+    range: PubgrubRange,
+    versions: impl Iterator<Item = &'a Version> + Clone,
+) -> PubgrubRange {
+    let mut aligned = range.clone();
+    for version in versions.clone() {
+        let decision = PubgrubRange::exact(version.clone());
+        let alone = versions
+            .clone()
+            .all(|other| other == version || !decision.contains(other));
+        if !alone {
+            continue;
+        }
+        aligned = if range.contains(version) {
+            aligned.union(&decision)
+        } else {
+            aligned.intersection(&decision.negate())
+        };
+    }
+    aligned
+}
+
+#[cfg(test)]
+mod property_tests;
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -295,7 +1192,7 @@ mod tests {
         }
     }
 
-    fn make_remote() -> Box<Remote> {
+    fn make_remote() -> PackageFetchers {
         let mut deps = HashMap::new();
         let _ = deps.insert(
             "gleam_stdlib".into(),
@@ -433,74 +1330,114 @@ mod tests {
                 ],
             },
         );
-        Box::new(Remote { deps })
+        fetchers(Remote { deps })
     }
 
-    #[test]
-    fn resolution_with_locked() {
-        let locked_stdlib = ("gleam_stdlib".into(), Version::parse("0.1.0").unwrap());
-        let result = resolve_versions(
-            make_remote(),
-            HashMap::new(),
-            "app".into(),
-            vec![("gleam_stdlib".into(), Range::new("~> 0.1".into()))].into_iter(),
-            &vec![locked_stdlib].into_iter().collect(),
-        )
-        .unwrap();
-        assert_eq!(
-            result,
-            vec![("gleam_stdlib".into(), Version::parse("0.1.0").unwrap())]
-                .into_iter()
-                .collect()
-        );
+    fn fetchers(hex: Remote) -> PackageFetchers {
+        let mut fetchers = PackageFetchers::new();
+        let _ = fetchers.register(SourceKind::Hex, Box::new(hex));
+        fetchers
     }
 
-    #[test]
-    fn resolution_without_deps() {
-        let result = resolve_versions(
-            make_remote(),
-            HashMap::new(),
-            "app".into(),
-            vec![].into_iter(),
-            &vec![].into_iter().collect(),
-        )
-        .unwrap();
-        assert_eq!(result, vec![].into_iter().collect())
+    fn release(version: &str, requirements: &[(&str, &str, Option<&str>)]) -> Release<()> {
+        Release {
+            version: Version::try_from(version).expect("version"),
+            requirements: requirements
+                .iter()
+                .map(|(name, requirement, repository)| {
+                    (
+                        (*name).into(),
+                        Dependency {
+                            app: None,
+                            optional: false,
+                            repository: repository.map(Into::into),
+                            requirement: Range::new((*requirement).into()),
+                        },
+                    )
+                })
+                .collect(),
+            retirement_status: None,
+            outer_checksum: vec![1, 2, 3],
+            meta: (),
+        }
+    }
+
+    fn package(name: &str, releases: Vec<Release<()>>) -> (String, hexpm::Package) {
+        let package = hexpm::Package {
+            name: name.into(),
+            repository: "hexpm".into(),
+            releases,
+        };
+        (name.into(), package)
     }
 
     #[test]
-    fn resolution_1_dep() {
-        let result = resolve_versions(
-            make_remote(),
-            HashMap::new(),
-            "app".into(),
-            vec![("gleam_stdlib".into(), Range::new("~> 0.1".into()))].into_iter(),
-            &vec![].into_iter().collect(),
-        )
-        .unwrap();
+    fn source_kind_of_repository() {
+        assert_eq!(SourceKind::of_repository(None), SourceKind::Hex);
+        assert_eq!(SourceKind::of_repository(Some("hexpm")), SourceKind::Hex);
         assert_eq!(
-            result,
-            vec![("gleam_stdlib".into(), Version::try_from("0.3.0").unwrap())]
-                .into_iter()
-                .collect()
+            SourceKind::of_repository(Some("hexpm:my_org")),
+            SourceKind::HexOrganization("my_org".into())
+        );
+        assert_eq!(
+            SourceKind::of_repository(Some("my_org")),
+            SourceKind::HexOrganization("my_org".into())
+        );
+        assert_eq!(
+            SourceKind::of_repository(Some("artifacts://packages.example.com")),
+            SourceKind::Custom("artifacts".into())
         );
     }
 
     #[test]
-    fn resolution_with_nested_deps() {
+    fn resolution_with_custom_source() {
+        let hex = Remote {
+            deps: [package("wibble", vec![release("1.0.0", &[])])].into(),
+        };
+        let artifacts = Remote {
+            deps: [
+                package(
+                    "internal",
+                    vec![release(
+                        "1.0.0",
+                        &[
+                            ("wibble", "~> 1.0", Some("hexpm")),
+                            ("secret", "~> 2.0", Some("artifacts://store")),
+                        ],
+                    )],
+                ),
+                package("secret", vec![release("2.1.0", &[])]),
+            ]
+            .into(),
+        };
+        let mut fetchers = fetchers(hex);
+        let _ = fetchers.register(SourceKind::Custom("artifacts".into()), Box::new(artifacts));
+        let _ = fetchers.set_source("internal".into(), SourceKind::Custom("artifacts".into()));
+
         let result = resolve_versions(
-            make_remote(),
+            fetchers,
             HashMap::new(),
             "app".into(),
-            vec![("gleam_otp".into(), Range::new("~> 0.1".into()))].into_iter(),
+            vec![("internal".into(), Range::new("~> 1.0".into()))].into_iter(),
             &vec![].into_iter().collect(),
+            ResolveOptions::default(),
         )
-        .unwrap();
+        .expect("resolve versions");
         assert_eq!(
-            result,
+            result.packages,
             vec![
-                ("gleam_otp".into(), Version::try_from("0.2.0").unwrap()),
-                ("gleam_stdlib".into(), Version::try_from("0.3.0").unwrap())
+                (
+                    "internal".into(),
+                    Version::try_from("1.0.0").expect("version")
+                ),
+                (
+                    "secret".into(),
+                    Version::try_from("2.1.0").expect("version")
+                ),
+                (
+                    "wibble".into(),
+                    Version::try_from("1.0.0").expect("version")
+                ),
             ]
             .into_iter()
             .collect()
@@ -508,17 +1445,268 @@ mod tests {
     }
 
     #[test]
-    fn resolution_locked_to_older_version() {
+    fn resolution_with_hex_organization() {
+        let hex = Remote {
+            deps: [package("wibble", vec![release("1.0.0", &[])])].into(),
+        };
+        let acme = Remote {
+            deps: [
+                package(
+                    "anvil",
+                    vec![release(
+                        "1.0.0",
+                        &[
+                            ("wibble", "~> 1.0", None),
+                            ("rocket", "~> 2.0", Some("acme")),
+                        ],
+                    )],
+                ),
+                package("rocket", vec![release("2.1.0", &[])]),
+            ]
+            .into(),
+        };
+        let acme_source = SourceKind::HexOrganization("acme".into());
+        let mut fetchers = fetchers(hex);
+        let _ = fetchers.register(acme_source.clone(), Box::new(acme));
+        let _ = fetchers.set_source("anvil".into(), acme_source.clone());
+
+        let result = resolve_versions(
+            fetchers,
+            HashMap::new(),
+            "app".into(),
+            vec![("anvil".into(), Range::new("~> 1.0".into()))].into_iter(),
+            &vec![].into_iter().collect(),
+            ResolveOptions::default(),
+        )
+        .expect("resolve versions");
+        assert_eq!(
+            result.sources,
+            [
+                ("anvil".into(), acme_source.clone()),
+                ("rocket".into(), acme_source.clone()),
+                ("wibble".into(), SourceKind::Hex),
+            ]
+            .into()
+        );
+        assert_eq!(
+            result.statistics.fetched,
+            [(acme_source, 2), (SourceKind::Hex, 1)].into()
+        );
+    }
+
+    #[test]
+    fn resolution_with_registry_priority() {
+        let hex = Remote {
+            deps: [
+                package("wibble", vec![release("1.0.0", &[]), release("1.1.0", &[])]),
+                package("wobble", vec![release("2.0.0", &[])]),
+            ]
+            .into(),
+        };
+        let acme = Remote {
+            deps: [package(
+                "wibble",
+                vec![
+                    release("1.0.0", &[("wobble", "~> 2.0", None)]),
+                    release("1.2.0", &[("wobble", "~> 2.0", None)]),
+                ],
+            )]
+            .into(),
+        };
+        let acme_source = SourceKind::HexOrganization("acme".into());
+        let mut fetchers = fetchers(hex);
+        let _ = fetchers.register(acme_source.clone(), Box::new(acme));
+        fetchers.set_priority(vec![acme_source.clone(), SourceKind::Hex]);
+
+        let resolve = |fetchers, requirement: &str| {
+            resolve_versions(
+                fetchers,
+                HashMap::new(),
+                "app".into(),
+                vec![("wibble".into(), Range::new(requirement.into()))].into_iter(),
+                &vec![].into_iter().collect(),
+                ResolveOptions::default(),
+            )
+            .expect("resolve versions")
+        };
+        let result = resolve(fetchers, "~> 1.0");
+        assert_eq!(
+            result.packages,
+            [
+                ("wibble".into(), Version::new(1, 2, 0)),
+                ("wobble".into(), Version::new(2, 0, 0)),
+            ]
+            .into()
+        );
+        // The package missing from the first source is found in the second
+        assert_eq!(
+            result.sources,
+            [
+                ("wibble".into(), acme_source.clone()),
+                ("wobble".into(), SourceKind::Hex),
+            ]
+            .into()
+        );
+        assert_eq!(
+            result.statistics.fetched,
+            [(acme_source, 1), (SourceKind::Hex, 2)].into()
+        );
+    }
+
+    #[test]
+    fn resolution_with_registry_priority_takes_release_from_first() {
+        let hex = Remote {
+            deps: [package(
+                "wibble",
+                vec![release("1.0.0", &[]), release("1.1.0", &[])],
+            )]
+            .into(),
+        };
+        let acme = Remote {
+            deps: [package("wibble", vec![release("1.0.0", &[])])].into(),
+        };
+        let acme_source = SourceKind::HexOrganization("acme".into());
+        let resolve = |priority: Vec<SourceKind>, requirement: &str| {
+            let hex = Remote {
+                deps: hex.deps.clone(),
+            };
+            let acme = Remote {
+                deps: acme.deps.clone(),
+            };
+            let mut fetchers = fetchers(hex);
+            let _ = fetchers.register(acme_source.clone(), Box::new(acme));
+            fetchers.set_priority(priority);
+            resolve_versions(
+                fetchers,
+                HashMap::new(),
+                "app".into(),
+                vec![("wibble".into(), Range::new(requirement.into()))].into_iter(),
+                &vec![].into_iter().collect(),
+                ResolveOptions::default(),
+            )
+            .expect("resolve versions")
+        };
+
+        let sources = |resolution: Resolution| resolution.sources["wibble"].clone();
+        let acme_first = vec![acme_source.clone(), SourceKind::Hex];
+        let hex_first = vec![SourceKind::Hex, acme_source.clone()];
+        assert_eq!(sources(resolve(acme_first.clone(), "1.0.0")), acme_source);
+        assert_eq!(sources(resolve(hex_first, "1.0.0")), SourceKind::Hex);
+        assert_eq!(sources(resolve(acme_first, "1.1.0")), SourceKind::Hex);
+    }
+
+    #[test]
+    fn resolution_without_fetcher_for_source() {
+        let mut fetchers = fetchers(Remote {
+            deps: HashMap::new(),
+        });
+        let _ = fetchers.set_source("wibble".into(), SourceKind::Git);
+        let error = resolve_versions(
+            fetchers,
+            HashMap::new(),
+            "app".into(),
+            vec![("wibble".into(), Range::new("~> 1.0".into()))].into_iter(),
+            &vec![].into_iter().collect(),
+            ResolveOptions::default(),
+        )
+        .expect_err("no git fetcher");
+        match error {
+            ResolutionError::ErrorChoosingPackageVersion(error) => assert_eq!(
+                error.to_string(),
+                "No package fetcher is registered for git packages, which `wibble` is"
+            ),
+            _ => panic!("wrong error: {}", error),
+        }
+    }
+
+    #[test]
+    fn resolution_with_locked() {
+        let locked_stdlib = ("gleam_stdlib".into(), Version::parse("0.1.0").unwrap());
+        let result = resolve_versions(
+            make_remote(),
+            HashMap::new(),
+            "app".into(),
+            vec![("gleam_stdlib".into(), Range::new("~> 0.1".into()))].into_iter(),
+            &vec![locked_stdlib].into_iter().collect(),
+            ResolveOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(
+            result.packages,
+            vec![("gleam_stdlib".into(), Version::parse("0.1.0").unwrap())]
+                .into_iter()
+                .collect()
+        );
+    }
+
+    #[test]
+    fn resolution_without_deps() {
+        let result = resolve_versions(
+            make_remote(),
+            HashMap::new(),
+            "app".into(),
+            vec![].into_iter(),
+            &vec![].into_iter().collect(),
+            ResolveOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(result.packages, vec![].into_iter().collect())
+    }
+
+    #[test]
+    fn resolution_1_dep() {
+        let result = resolve_versions(
+            make_remote(),
+            HashMap::new(),
+            "app".into(),
+            vec![("gleam_stdlib".into(), Range::new("~> 0.1".into()))].into_iter(),
+            &vec![].into_iter().collect(),
+            ResolveOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(
+            result.packages,
+            vec![("gleam_stdlib".into(), Version::try_from("0.3.0").unwrap())]
+                .into_iter()
+                .collect()
+        );
+    }
+
+    #[test]
+    fn resolution_with_nested_deps() {
+        let result = resolve_versions(
+            make_remote(),
+            HashMap::new(),
+            "app".into(),
+            vec![("gleam_otp".into(), Range::new("~> 0.1".into()))].into_iter(),
+            &vec![].into_iter().collect(),
+            ResolveOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(
+            result.packages,
+            vec![
+                ("gleam_otp".into(), Version::try_from("0.2.0").unwrap()),
+                ("gleam_stdlib".into(), Version::try_from("0.3.0").unwrap())
+            ]
+            .into_iter()
+            .collect()
+        );
+    }
+
+    #[test]
+    fn resolution_locked_to_older_version() {
         let result = resolve_versions(
             make_remote(),
             HashMap::new(),
             "app".into(),
             vec![("gleam_otp".into(), Range::new("~> 0.1.0".into()))].into_iter(),
             &vec![].into_iter().collect(),
+            ResolveOptions::default(),
         )
         .unwrap();
         assert_eq!(
-            result,
+            result.packages,
             vec![
                 ("gleam_otp".into(), Version::try_from("0.1.0").unwrap()),
                 ("gleam_stdlib".into(), Version::try_from("0.3.0").unwrap())
@@ -536,10 +1724,11 @@ mod tests {
             "app".into(),
             vec![("package_with_retired".into(), Range::new("> 0.0.0".into()))].into_iter(),
             &vec![].into_iter().collect(),
+            ResolveOptions::default(),
         )
         .unwrap();
         assert_eq!(
-            result,
+            result.packages,
             vec![(
                 "package_with_retired".into(),
                 // Uses the older version that hasn't been retired
@@ -550,6 +1739,113 @@ mod tests {
         );
     }
 
+    fn minimums_remote() -> PackageFetchers {
+        fetchers(Remote {
+            deps: [
+                package(
+                    "wibble",
+                    vec![
+                        release("1.0.0", &[("wobble", ">= 1.0.0", None)]),
+                        release("2.0.0", &[("wobble", "== 1.0.0", None)]),
+                    ],
+                ),
+                package("wobble", vec![release("1.0.0", &[]), release("1.1.0", &[])]),
+            ]
+            .into(),
+        })
+    }
+
+    #[test]
+    fn resolution_minimum_of_indirect_dependency() {
+        let result = resolve_versions(
+            minimums_remote(),
+            HashMap::new(),
+            "app".into(),
+            vec![("wibble".into(), Range::new(">= 1.0.0".into()))].into_iter(),
+            &HashMap::new(),
+            ResolveOptions {
+                minimums: [("wobble".into(), Version::new(1, 1, 0))].into(),
+                ..ResolveOptions::default()
+            },
+        )
+        .expect("resolve versions");
+        assert_eq!(
+            result.packages,
+            [
+                // The newest version of wibble only allows wobble 1.0.0
+                ("wibble".into(), Version::new(1, 0, 0)),
+                ("wobble".into(), Version::new(1, 1, 0)),
+            ]
+            .into()
+        );
+    }
+
+    #[test]
+    fn resolution_minimum_conflicting_with_indirect_requirement() {
+        let error = resolve_versions(
+            minimums_remote(),
+            HashMap::new(),
+            "app".into(),
+            vec![("wibble".into(), Range::new(">= 2.0.0".into()))].into_iter(),
+            &HashMap::new(),
+            ResolveOptions {
+                minimums: [("wobble".into(), Version::new(1, 1, 0))].into(),
+                ..ResolveOptions::default()
+            },
+        )
+        .expect_err("should fail to resolve");
+        let ResolutionError::Failure(message) = error else {
+            panic!("unexpected error {error:?}");
+        };
+        assert!(message.contains("- wobble >= 1.1.0"), "{message}");
+    }
+
+    #[test]
+    fn resolution_minimum_conflicting_with_root_requirement() {
+        let error = resolve_versions(
+            minimums_remote(),
+            HashMap::new(),
+            "app".into(),
+            vec![("wobble".into(), Range::new("< 1.1.0".into()))].into_iter(),
+            &HashMap::new(),
+            ResolveOptions {
+                minimums: [("wobble".into(), Version::new(1, 1, 0))].into(),
+                ..ResolveOptions::default()
+            },
+        )
+        .expect_err("should fail to resolve");
+        let ResolutionError::Failure(message) = error else {
+            panic!("unexpected error {error:?}");
+        };
+        assert_eq!(
+            message,
+            "wobble is specified with the requirement `< 1.1.0`, but its minimum version is 1.1.0, which is incompatible."
+        );
+    }
+
+    #[test]
+    fn resolution_minimum_above_locked_version() {
+        let error = resolve_versions(
+            minimums_remote(),
+            HashMap::new(),
+            "app".into(),
+            vec![("wobble".into(), Range::new(">= 1.0.0".into()))].into_iter(),
+            &[("wobble".into(), Version::new(1, 0, 0))].into(),
+            ResolveOptions {
+                minimums: [("wobble".into(), Version::new(1, 1, 0))].into(),
+                ..ResolveOptions::default()
+            },
+        )
+        .expect_err("should fail to resolve");
+        let ResolutionError::Failure(message) = error else {
+            panic!("unexpected error {error:?}");
+        };
+        assert_eq!(
+            message,
+            "wobble is locked to 1.0.0, which is below its minimum version 1.1.0."
+        );
+    }
+
     #[test]
     fn resolution_retired_versions_can_be_used_if_locked() {
         let result = resolve_versions(
@@ -560,10 +1856,11 @@ mod tests {
             &vec![("package_with_retired".into(), Version::new(0, 2, 0))]
                 .into_iter()
                 .collect(),
+            ResolveOptions::default(),
         )
         .unwrap();
         assert_eq!(
-            result,
+            result.packages,
             vec![(
                 "package_with_retired".into(),
                 // Uses the locked version even though it's retired
@@ -582,10 +1879,11 @@ mod tests {
             "app".into(),
             vec![("gleam_otp".into(), Range::new("~> 0.3.0-rc1".into()))].into_iter(),
             &vec![].into_iter().collect(),
+            ResolveOptions::default(),
         )
         .unwrap();
         assert_eq!(
-            result,
+            result.packages,
             vec![
                 ("gleam_stdlib".into(), Version::try_from("0.3.0").unwrap()),
                 ("gleam_otp".into(), Version::try_from("0.3.0-rc2").unwrap()),
@@ -603,10 +1901,11 @@ mod tests {
             "app".into(),
             vec![("gleam_otp".into(), Range::new("0.3.0-rc1".into()))].into_iter(),
             &vec![].into_iter().collect(),
+            ResolveOptions::default(),
         )
         .unwrap();
         assert_eq!(
-            result,
+            result.packages,
             vec![
                 ("gleam_stdlib".into(), Version::try_from("0.3.0").unwrap()),
                 ("gleam_otp".into(), Version::try_from("0.3.0-rc1").unwrap()),
@@ -624,6 +1923,7 @@ mod tests {
             "app".into(),
             vec![("unknown".into(), Range::new("~> 0.1".into()))].into_iter(),
             &vec![].into_iter().collect(),
+            ResolveOptions::default(),
         )
         .unwrap_err();
     }
@@ -636,6 +1936,7 @@ mod tests {
             "app".into(),
             vec![("gleam_stdlib".into(), Range::new("~> 99.0".into()))].into_iter(),
             &vec![].into_iter().collect(),
+            ResolveOptions::default(),
         )
         .unwrap_err();
     }
@@ -650,18 +1951,41 @@ mod tests {
             &vec![("gleam_stdlib".into(), Version::new(0, 2, 0))]
                 .into_iter()
                 .collect(),
+            ResolveOptions::default(),
         )
         .unwrap_err();
 
         match err {
-        Error::DependencyResolutionFailed(msg) => assert_eq!(
+        ResolutionError::Failure(msg) => assert_eq!(
             msg,
-            "An unrecoverable error happened while solving dependencies: gleam_stdlib is specified with the requirement `~> 0.1.0`, but it is locked to 0.2.0, which is incompatible."
+            "gleam_stdlib is specified with the requirement `~> 0.1.0`, but it is locked to 0.2.0, which is incompatible."
         ),
         _ => panic!("wrong error: {}", err),
         }
     }
 
+    #[test]
+    fn resolution_locked_version_doesnt_satisfy_exact_prerelease() {
+        let err = resolve_versions(
+            make_remote(),
+            HashMap::new(),
+            "app".into(),
+            vec![("gleam_otp".into(), Range::new("== 0.3.0-rc1".into()))].into_iter(),
+            &vec![("gleam_otp".into(), Version::new(0, 3, 0))]
+                .into_iter()
+                .collect(),
+            ResolveOptions::default(),
+        )
+        .expect_err("locked version is not the exact version");
+        match err {
+            ResolutionError::Failure(msg) => assert_eq!(
+                msg,
+                "gleam_otp is specified with the requirement `== 0.3.0-rc1`, but it is locked to 0.3.0, which is incompatible."
+            ),
+            _ => panic!("wrong error: {}", err),
+        }
+    }
+
     #[test]
     fn resolution_with_exact_dep() {
         let result = resolve_versions(
@@ -670,16 +1994,449 @@ mod tests {
             "app".into(),
             vec![("gleam_stdlib".into(), Range::new("0.1.0".into()))].into_iter(),
             &vec![].into_iter().collect(),
+            ResolveOptions::default(),
         )
         .unwrap();
         assert_eq!(
-            result,
+            result.packages,
             vec![("gleam_stdlib".into(), Version::try_from("0.1.0").unwrap())]
                 .into_iter()
                 .collect()
         );
     }
 
+    #[test]
+    fn resolution_records_requirements() {
+        let result = resolve_versions(
+            make_remote(),
+            HashMap::new(),
+            "app".into(),
+            vec![
+                ("gleam_otp".into(), Range::new("~> 0.1".into())),
+                ("gleam_stdlib".into(), Range::new("~> 0.3".into())),
+            ]
+            .into_iter(),
+            &vec![].into_iter().collect(),
+            ResolveOptions::default(),
+        )
+        .expect("resolve versions");
+        assert_eq!(
+            result.requirements.get("gleam_stdlib"),
+            Some(&vec![
+                RequiredBy {
+                    package: "app".into(),
+                    version: Version::new(0, 0, 0),
+                    requirement: Range::new("~> 0.3".into()),
+                },
+                RequiredBy {
+                    package: "gleam_otp".into(),
+                    version: Version::new(0, 2, 0),
+                    requirement: Range::new(">= 0.1.0".into()),
+                },
+            ])
+        );
+        assert_eq!(result.requirements.get("app"), None);
+    }
+
+    #[test]
+    fn resolution_statistics() {
+        let provided = [package("wibble", vec![release("1.0.0", &[])])]
+            .into_iter()
+            .map(|(name, package)| (name.into(), package))
+            .collect();
+        let result = resolve_versions(
+            make_remote(),
+            provided,
+            "app".into(),
+            vec![
+                ("gleam_otp".into(), Range::new("~> 0.1".into())),
+                ("wibble".into(), Range::new("== 1.0.0".into())),
+            ]
+            .into_iter(),
+            &vec![].into_iter().collect(),
+            ResolveOptions::default(),
+        )
+        .expect("resolve versions");
+        assert_eq!(
+            result.statistics,
+            FetchStatistics {
+                fetched: [(SourceKind::Hex, 2)].into(),
+                provided: 1,
+                cache_hits: 10,
+            }
+        );
+        assert_eq!(
+            result.solver,
+            SolverStatistics {
+                decisions: 4,
+                backtracks: 0,
+            }
+        );
+    }
+
+    /// wibble 2.0.0 needs a version of wubble that no version of wobble allows,
+    /// so the solver has to go back on picking it.
+    fn backtracking_remote() -> HashMap<String, hexpm::Package> {
+        [
+            package(
+                "wibble",
+                vec![
+                    release("2.0.0", &[("wubble", ">= 2.0.0", None)]),
+                    release("1.0.0", &[("wubble", ">= 1.0.0", None)]),
+                ],
+            ),
+            package(
+                "wobble",
+                vec![
+                    release("1.2.0", &[("wubble", "< 2.0.0", None)]),
+                    release("1.1.0", &[("wubble", "< 2.0.0", None)]),
+                    release("1.0.0", &[("wubble", "< 2.0.0", None)]),
+                ],
+            ),
+            package("wubble", vec![release("2.0.0", &[]), release("1.0.0", &[])]),
+        ]
+        .into()
+    }
+
+    #[test]
+    fn resolution_solver_statistics() {
+        let deps = backtracking_remote();
+        let result = resolve_versions(
+            fetchers(Remote { deps }),
+            HashMap::new(),
+            "app".into(),
+            vec![
+                ("wibble".into(), Range::new("> 0.0.0".into())),
+                ("wobble".into(), Range::new("> 0.0.0".into())),
+            ]
+            .into_iter(),
+            &vec![].into_iter().collect(),
+            ResolveOptions::default(),
+        )
+        .expect("resolve versions");
+        assert_eq!(result.packages.get("wibble"), Some(&Version::new(1, 0, 0)));
+        assert!(result.solver.backtracks > 0);
+        assert!(result.solver.decisions > result.packages.len());
+        assert_eq!(
+            result.most_constrained(2),
+            vec![("wubble", 2), ("wibble", 1)]
+        );
+    }
+
+    #[derive(Default)]
+    struct Recorder {
+        events: RefCell<Vec<String>>,
+    }
+
+    impl ResolutionObserver for Recorder {
+        fn package_fetched(&self, name: &str, source: &SourceKind) {
+            self.events
+                .borrow_mut()
+                .push(format!("fetched {name} from {source:?}"));
+        }
+
+        fn version_chosen(&self, name: &str, version: &Version) {
+            self.events
+                .borrow_mut()
+                .push(format!("chose {name} {version}"));
+        }
+
+        fn conflict_encountered(&self, name: &str) {
+            self.events.borrow_mut().push(format!("conflict {name}"));
+        }
+
+        fn backtracked(&self, name: &str, version: &Version) {
+            self.events
+                .borrow_mut()
+                .push(format!("backtracked {name} {version}"));
+        }
+    }
+
+    #[test]
+    fn resolution_observer_events() {
+        let observer = Recorder::default();
+        let result = resolve_versions(
+            fetchers(Remote {
+                deps: backtracking_remote(),
+            }),
+            HashMap::new(),
+            "app".into(),
+            vec![
+                ("wibble".into(), Range::new("> 0.0.0".into())),
+                ("wobble".into(), Range::new("> 0.0.0".into())),
+            ]
+            .into_iter(),
+            &HashMap::new(),
+            ResolveOptions {
+                observer: &observer,
+                ..ResolveOptions::default()
+            },
+        )
+        .expect("resolve versions");
+        let events = observer.events.take();
+        let count = |prefix: &str| events.iter().filter(|e| e.starts_with(prefix)).count();
+        assert_eq!(count("chose "), result.solver.decisions);
+        assert_eq!(count("backtracked "), result.solver.backtracks);
+        assert_eq!(
+            events.get(..8),
+            Some(
+                &[
+                    "chose app 0.0.0",
+                    "fetched wibble from Hex",
+                    "fetched wobble from Hex",
+                    "chose wibble 2.0.0",
+                    "fetched wubble from Hex",
+                    "chose wubble 2.0.0",
+                    "chose wobble 1.2.0",
+                    "chose wobble 1.1.0",
+                ]
+                .map(String::from)[..]
+            )
+        );
+        assert!(events.contains(&"conflict wobble".into()));
+    }
+
+    #[test]
+    fn resolution_warnings() {
+        let result = resolve_versions(
+            make_remote(),
+            HashMap::new(),
+            "app".into(),
+            vec![
+                ("gleam_otp".into(), Range::new("~> 0.3.0-rc1".into())),
+                ("package_with_retired".into(), Range::new("> 0.0.0".into())),
+            ]
+            .into_iter(),
+            &vec![("package_with_retired".into(), Version::new(0, 2, 0))]
+                .into_iter()
+                .collect(),
+            ResolveOptions::default(),
+        )
+        .expect("resolve versions");
+        assert_eq!(
+            result.warnings,
+            vec![
+                ResolutionWarning::PreReleaseSelected {
+                    package: "gleam_otp".into(),
+                    version: Version::try_from("0.3.0-rc2").expect("version"),
+                },
+                ResolutionWarning::RetiredVersionLocked {
+                    package: "package_with_retired".into(),
+                    version: Version::new(0, 2, 0),
+                    retirement: hexpm::RetirementStatus {
+                        reason: hexpm::RetirementReason::Security,
+                        message: "It's bad".into(),
+                    },
+                },
+            ]
+        );
+    }
+
+    fn wibble_remote() -> PackageFetchers {
+        fetchers(Remote {
+            deps: [
+                package(
+                    "wibble",
+                    vec![
+                        release("1.0.0", &[]),
+                        release("1.1.0", &[("wobble", "~> 1.0", None)]),
+                    ],
+                ),
+                package("wobble", vec![release("1.0.0", &[])]),
+            ]
+            .into(),
+        })
+    }
+
+    #[test]
+    fn resolution_fewest_versions_picks_newest() {
+        let result = resolve_versions(
+            wibble_remote(),
+            HashMap::new(),
+            "app".into(),
+            vec![("wibble".into(), Range::new("~> 1.0".into()))].into_iter(),
+            &vec![].into_iter().collect(),
+            ResolveOptions::default(),
+        )
+        .expect("resolve versions");
+        assert_eq!(
+            result.packages,
+            vec![
+                ("wibble".into(), Version::new(1, 1, 0)),
+                ("wobble".into(), Version::new(1, 0, 0)),
+            ]
+            .into_iter()
+            .collect()
+        );
+    }
+
+    #[test]
+    fn resolution_fewest_packages_avoids_new_dependencies() {
+        let result = resolve_versions(
+            wibble_remote(),
+            HashMap::new(),
+            "app".into(),
+            vec![("wibble".into(), Range::new("~> 1.0".into()))].into_iter(),
+            &vec![].into_iter().collect(),
+            ResolveOptions {
+                strategy: ResolutionStrategy::FewestPackages,
+                ..ResolveOptions::default()
+            },
+        )
+        .expect("resolve versions");
+        assert_eq!(
+            result.packages,
+            vec![("wibble".into(), Version::new(1, 0, 0))]
+                .into_iter()
+                .collect()
+        );
+    }
+
+    #[test]
+    fn resolution_fewest_packages_picks_newest_without_new_dependencies() {
+        // `wobble` is already a dependency, so the newest `wibble` adds nothing
+        let result = resolve_versions(
+            wibble_remote(),
+            HashMap::new(),
+            "app".into(),
+            vec![
+                ("wibble".into(), Range::new("~> 1.0".into())),
+                ("wobble".into(), Range::new("~> 1.0".into())),
+            ]
+            .into_iter(),
+            &vec![].into_iter().collect(),
+            ResolveOptions {
+                strategy: ResolutionStrategy::FewestPackages,
+                ..ResolveOptions::default()
+            },
+        )
+        .expect("resolve versions");
+        assert_eq!(
+            result.packages,
+            vec![
+                ("wibble".into(), Version::new(1, 1, 0)),
+                ("wobble".into(), Version::new(1, 0, 0)),
+            ]
+            .into_iter()
+            .collect()
+        );
+    }
+
+    fn conflicting_remote() -> PackageFetchers {
+        fetchers(Remote {
+            deps: [
+                package(
+                    "wibble",
+                    vec![
+                        release("1.0.0", &[("wobble", "~> 1.0", None)]),
+                        release("2.0.0", &[("wobble", "~> 2.0", None)]),
+                    ],
+                ),
+                package("wobble", vec![release("1.0.0", &[]), release("2.0.0", &[])]),
+            ]
+            .into(),
+        })
+    }
+
+    fn fix_conflict(requirements: &[(&str, &str)], locked: &[(&str, Version)]) -> Vec<Suggestion> {
+        let requirements: HashMap<EcoString, Range> = requirements
+            .iter()
+            .map(|(name, range)| ((*name).into(), Range::new((*range).into())))
+            .collect();
+        let locked: HashMap<EcoString, Version> = locked
+            .iter()
+            .map(|(name, version)| ((*name).into(), version.clone()))
+            .collect();
+        #[allow(clippy::result_large_err)]
+        let resolve = |requirements: HashMap<EcoString, Range>,
+                       locked: HashMap<EcoString, Version>| {
+            resolve_versions(
+                conflicting_remote(),
+                HashMap::new(),
+                "app".into(),
+                requirements.into_iter(),
+                &locked,
+                ResolveOptions::default(),
+            )
+        };
+        let error = resolve(requirements.clone(), locked.clone()).expect_err("conflict");
+        suggest_fixes(&error, &requirements, &locked, resolve)
+    }
+
+    #[test]
+    fn suggest_relaxing_requirements() {
+        assert_eq!(
+            fix_conflict(&[("wibble", "~> 1.0"), ("wobble", "~> 2.0")], &[]),
+            vec![
+                Suggestion::Relax {
+                    package: "wibble".into(),
+                    requirement: Range::new(">= 2.0.0 and < 3.0.0".into()),
+                },
+                Suggestion::Relax {
+                    package: "wobble".into(),
+                    requirement: Range::new(">= 1.0.0 and < 2.0.0".into()),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn suggest_unlocking_packages() {
+        assert_eq!(
+            fix_conflict(
+                &[("wibble", ">= 2.0.0")],
+                &[
+                    ("wibble", Version::new(1, 0, 0)),
+                    ("wobble", Version::new(1, 0, 0))
+                ],
+            ),
+            vec![
+                Suggestion::Unlock {
+                    package: "wibble".into()
+                },
+                Suggestion::Unlock {
+                    package: "wobble".into()
+                },
+            ]
+        );
+        assert_eq!(
+            fix_conflict(
+                &[("wibble", ">= 1.0.0"), ("wobble", ">= 2.0.0")],
+                &[
+                    ("wibble", Version::new(1, 0, 0)),
+                    ("wobble", Version::new(1, 0, 0))
+                ],
+            ),
+            vec![
+                Suggestion::Unlock {
+                    package: "wibble".into()
+                },
+                Suggestion::Unlock {
+                    package: "wobble".into()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn suggestion_display() {
+        assert_eq!(
+            Suggestion::Relax {
+                package: "lustre".into(),
+                requirement: Range::new(">= 4.0.0 and < 5.0.0".into()),
+            }
+            .to_string(),
+            "relax lustre to >= 4.0.0 and < 5.0.0"
+        );
+        assert_eq!(
+            Suggestion::Unlock {
+                package: "gleam_stdlib".into()
+            }
+            .to_string(),
+            "unlock gleam_stdlib"
+        );
+    }
+
     #[test]
     fn parse_exact_version_test() {
         assert_eq!(
@@ -697,4 +2454,59 @@ mod tests {
         assert_eq!(parse_exact_version("~> 1.0.0"), None);
         assert_eq!(parse_exact_version(">= 1.0.0"), None);
     }
+
+    fn retired(version: &str) -> Release<()> {
+        Release {
+            retirement_status: Some(hexpm::RetirementStatus {
+                reason: hexpm::RetirementReason::Security,
+                message: "It's bad".into(),
+            }),
+            ..release(version, &[])
+        }
+    }
+
+    #[test]
+    fn newest_compatible_release_skips_retired_and_incompatible() {
+        let (_, package) = package(
+            "gleam_stdlib",
+            vec![
+                release("1.0.0", &[]),
+                release("1.1.0", &[]),
+                release("1.2.0", &[]),
+                retired("1.3.0"),
+                release("1.4.0-rc1", &[]),
+                release("2.0.0", &[]),
+            ],
+        );
+        let locked = Version::parse("1.0.0").unwrap();
+        assert_eq!(
+            newest_compatible_release(&package, &locked, None),
+            Some(Version::parse("1.2.0").unwrap())
+        );
+        let requirement = Range::new("< 1.2.0".into());
+        assert_eq!(
+            newest_compatible_release(&package, &locked, Some(&requirement)),
+            Some(Version::parse("1.1.0").unwrap())
+        );
+    }
+
+    #[test]
+    fn newest_compatible_release_of_zero_major_version() {
+        let (_, package) = package(
+            "gleam_stdlib",
+            vec![
+                release("0.1.0", &[]),
+                release("0.1.1", &[]),
+                release("0.2.0", &[]),
+            ],
+        );
+        assert_eq!(
+            newest_compatible_release(&package, &Version::parse("0.1.0").unwrap(), None),
+            Some(Version::parse("0.1.1").unwrap())
+        );
+        assert_eq!(
+            newest_compatible_release(&package, &Version::parse("0.2.0").unwrap(), None),
+            None
+        );
+    }
 }
@@ -0,0 +1,591 @@
+//! Checks of version resolution against randomly generated package universes.
+//!
+//! Each case generates a handful of packages with random versions,
+//! requirements, retirements, and locked versions, resolves them, and checks
+//! the outcome with an oracle. A solution must satisfy every constraint, and
+//! a failure is checked to be genuine by searching every possible assignment
+//! of versions for one that would have satisfied them.
+//!
+//! A failing case prints its seed, which can be used with `check_seed` to
+//! reproduce it.
+//!
+//! pubgrub represents a single version as the range up to its next patch,
+//! which for Hex versions also holds pre-releases of that patch. The
+//! generator keeps clear of the cases where that includes another version of
+//! the package.
+
+use std::collections::HashMap;
+
+use ecow::EcoString;
+use hexpm::{
+    version::{Range, Version},
+    Dependency, Release, RetirementReason, RetirementStatus,
+};
+use itertools::Itertools;
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+
+use super::{
+    conflicting_packages, parse_exact_version, resolve_versions, PackageFetcher, PackageFetchers,
+    PackageVersions, ResolutionError, ResolutionStrategy, ResolveOptions, SourceKind,
+};
+
+const CASES: u64 = 500;
+
+/// The seed of the universe resolved by `resolution_is_deterministic`, and
+/// how many packages it has.
+const LARGE_UNIVERSE_SEED: u64 = 10;
+const LARGE_UNIVERSE_PACKAGES: usize = 80;
+
+/// How many times the large universe is resolved, each time given to the
+/// resolver in a different order.
+const DETERMINISM_RUNS: u64 = 10;
+
+type PubgrubRange = pubgrub::range::Range<Version>;
+
+#[derive(Debug, Clone)]
+struct Universe {
+    packages: HashMap<String, hexpm::Package>,
+    requirements: Vec<(EcoString, Range)>,
+    locked: HashMap<EcoString, Version>,
+}
+
+impl PackageFetcher for Universe {
+    fn get_dependencies(
+        &self,
+        package: &str,
+    ) -> Result<hexpm::Package, Box<dyn std::error::Error>> {
+        self.packages
+            .get(package)
+            .cloned()
+            .ok_or(Box::new(hexpm::ApiError::NotFound))
+    }
+}
+
+fn generate_version(random: &mut StdRng) -> Version {
+    let mut version = Version::new(
+        random.gen_range(0..3),
+        random.gen_range(0..3),
+        random.gen_range(0..2),
+    );
+    // A pre-release of 0.0.0 sorts below the lowest version Hex ranges can
+    // hold, so it could never satisfy a requirement.
+    if version != Version::new(0, 0, 0) && random.gen_bool(0.1) {
+        version = Version::parse(&format!("{version}-rc1")).expect("pre-release version");
+    }
+    version
+}
+
+fn generate_range(random: &mut StdRng, versions: &[Version]) -> Range {
+    let version = versions.choose(random).expect("package with no versions");
+    let spec = match random.gen_range(0..5) {
+        0 => format!(">= {version}"),
+        1 => format!("~> {}.{}", version.major, version.minor),
+        2 => format!("== {version}"),
+        3 => format!(">= {version} and < {}.0.0", version.major + 1),
+        _ => format!("< {version}"),
+    };
+    Range::new(spec)
+}
+
+fn generate_universe(random: &mut StdRng) -> Universe {
+    let names: Vec<String> = (0..random.gen_range(1..6))
+        .map(|i| format!("package_{i}"))
+        .collect();
+    let mut versions: HashMap<&str, Vec<Version>> = HashMap::new();
+    for name in &names {
+        let mut package_versions: Vec<_> = (0..random.gen_range(1..5))
+            .map(|_| generate_version(random))
+            .collect();
+        package_versions.sort();
+        package_versions.dedup();
+        // pubgrub takes a version to stand for everything up to its next
+        // patch, which would include a pre-release of that patch. Keep
+        // pre-releases clear of the other versions so they can't be
+        // confused with them.
+        let releases = package_versions.clone();
+        package_versions.retain(|version| {
+            !version.is_pre()
+                || !releases.iter().any(|other| {
+                    other != version
+                        && other.major == version.major
+                        && other.minor == version.minor
+                        && (other.patch == version.patch || other.patch + 1 == version.patch)
+                })
+        });
+        let _ = versions.insert(name, package_versions);
+    }
+
+    let mut packages = HashMap::new();
+    for name in &names {
+        let releases = versions
+            .get(name.as_str())
+            .expect("package versions")
+            .iter()
+            .map(|version| {
+                let requirements = names
+                    .iter()
+                    .filter(|dependency| *dependency != name)
+                    .filter_map(|dependency| {
+                        if !random.gen_bool(0.3) {
+                            return None;
+                        }
+                        let versions = versions.get(dependency.as_str()).expect("versions");
+                        let dependency_on = Dependency {
+                            requirement: generate_range(random, versions),
+                            optional: false,
+                            app: None,
+                            repository: None,
+                        };
+                        Some((dependency.clone(), dependency_on))
+                    })
+                    .collect();
+                let retirement_status = random.gen_bool(0.1).then(|| RetirementStatus {
+                    reason: RetirementReason::Other,
+                    message: "retired".into(),
+                });
+                Release {
+                    version: version.clone(),
+                    requirements,
+                    retirement_status,
+                    outer_checksum: vec![],
+                    meta: (),
+                }
+            })
+            .collect();
+        let package = hexpm::Package {
+            name: name.clone(),
+            repository: "hexpm".into(),
+            releases,
+        };
+        let _ = packages.insert(name.clone(), package);
+    }
+
+    let requirements = names
+        .iter()
+        .filter_map(|name| {
+            if !random.gen_bool(0.5) {
+                return None;
+            }
+            let versions = versions.get(name.as_str()).expect("versions");
+            Some((name.as_str().into(), generate_range(random, versions)))
+        })
+        .collect();
+    let locked = names
+        .iter()
+        .filter_map(|name| {
+            if !random.gen_bool(0.2) {
+                return None;
+            }
+            let versions = versions.get(name.as_str()).expect("versions");
+            let version = versions.choose(random).expect("version");
+            Some((name.as_str().into(), version.clone()))
+        })
+        .collect();
+
+    Universe {
+        packages,
+        requirements,
+        locked,
+    }
+}
+
+/// A range that allows at least one of the versions.
+fn generate_satisfiable_range(random: &mut StdRng, versions: &[Version]) -> Range {
+    let version = versions.choose(random).expect("package with no versions");
+    let spec = match random.gen_range(0..8) {
+        0 => format!("~> {}.{}", version.major, version.minor),
+        1 => format!(">= {version} and < {}.0.0", version.major + 1),
+        _ => format!(">= {version}"),
+    };
+    Range::new(spec)
+}
+
+/// A universe of many packages, for checking that resolution picks the same
+/// versions however it is run. Packages only depend on packages named after
+/// them, so there are no cycles, and there are no pre-releases or
+/// retirements, so it usually has a solution that takes some backtracking to
+/// find.
+fn generate_large_universe(random: &mut StdRng) -> Universe {
+    let names: Vec<String> = (0..LARGE_UNIVERSE_PACKAGES)
+        .map(|i| format!("package_{i:02}"))
+        .collect();
+    let versions: Vec<Vec<Version>> = names
+        .iter()
+        .map(|_| {
+            let mut versions: Vec<_> = (0..random.gen_range(1..8))
+                .map(|_| {
+                    Version::new(
+                        random.gen_range(0..3),
+                        random.gen_range(0..4),
+                        random.gen_range(0..3),
+                    )
+                })
+                .collect();
+            versions.sort();
+            versions.dedup();
+            versions
+        })
+        .collect();
+    let mut packages = HashMap::new();
+    let mut requirements = vec![];
+    for (index, (name, package_versions)) in names.iter().zip(&versions).enumerate() {
+        let mut releases = vec![];
+        for version in package_versions {
+            let mut dependencies = HashMap::new();
+            for (dependency, versions) in names.iter().zip(&versions).skip(index + 1) {
+                if !random.gen_bool(0.05) {
+                    continue;
+                }
+                let dependency_on = Dependency {
+                    requirement: generate_satisfiable_range(random, versions),
+                    optional: false,
+                    app: None,
+                    repository: None,
+                };
+                let _ = dependencies.insert(dependency.clone(), dependency_on);
+            }
+            releases.push(Release {
+                version: version.clone(),
+                requirements: dependencies,
+                retirement_status: None,
+                outer_checksum: vec![],
+                meta: (),
+            });
+        }
+        let package = hexpm::Package {
+            name: name.clone(),
+            repository: "hexpm".into(),
+            releases,
+        };
+        let _ = packages.insert(name.clone(), package);
+        if index < LARGE_UNIVERSE_PACKAGES / 4 {
+            let range = generate_satisfiable_range(random, package_versions);
+            requirements.push((name.as_str().into(), range));
+        }
+    }
+
+    Universe {
+        packages,
+        requirements,
+        locked: HashMap::new(),
+    }
+}
+
+/// Resolves the universe with its packages, their releases, and the root
+/// package's requirements given to the resolver in an order shuffled by the
+/// seed, returning the versions picked in name order, or the packages in
+/// conflict if there is no solution.
+fn resolve_shuffled(
+    universe: &Universe,
+    strategy: ResolutionStrategy,
+    seed: u64,
+) -> Result<Vec<(String, Version)>, Vec<String>> {
+    let random = &mut StdRng::seed_from_u64(seed);
+    let mut packages: Vec<_> = universe.packages.values().cloned().collect();
+    packages.shuffle(random);
+    for package in &mut packages {
+        package.releases.shuffle(random);
+    }
+    let mut requirements = universe.requirements.clone();
+    requirements.shuffle(random);
+    let shuffled = Universe {
+        packages: packages
+            .into_iter()
+            .map(|package| (package.name.clone(), package))
+            .collect(),
+        requirements,
+        locked: universe.locked.clone(),
+    };
+
+    let mut fetchers = PackageFetchers::new();
+    let _ = fetchers.register(SourceKind::Hex, Box::new(shuffled.clone()));
+    match resolve_versions(
+        fetchers,
+        HashMap::new(),
+        "root".into(),
+        shuffled.requirements.into_iter(),
+        &shuffled.locked,
+        ResolveOptions {
+            strategy,
+            ..ResolveOptions::default()
+        },
+    ) {
+        Ok(resolution) => Ok(resolution.packages.into_iter().sorted().collect()),
+        Err(ResolutionError::NoSolution(mut tree)) => {
+            tree.collapse_no_versions();
+            Err(conflicting_packages(&tree).into_iter().collect())
+        }
+        Err(error) => panic!("resolution failed: {error}"),
+    }
+}
+
+/// The constraints a solution must satisfy, with ranges parsed up front.
+struct Constraints<'a> {
+    universe: &'a Universe,
+    requirements: Vec<(&'a str, PubgrubRange)>,
+    /// The requirements of each release, by package and version.
+    dependencies: HashMap<(&'a str, &'a Version), Vec<(&'a str, PubgrubRange)>>,
+}
+
+impl<'a> Constraints<'a> {
+    fn new(universe: &'a Universe) -> Self {
+        let requirements = universe
+            .requirements
+            .iter()
+            .map(|(name, range)| (name.as_str(), range.to_pubgrub().expect("root range")))
+            .collect();
+        let dependencies = universe
+            .packages
+            .values()
+            .flat_map(|package| {
+                package.releases.iter().map(|release| {
+                    let dependencies = release
+                        .requirements
+                        .iter()
+                        .map(|(name, dependency)| {
+                            let range = dependency.requirement.to_pubgrub().expect("range");
+                            (name.as_str(), range)
+                        })
+                        .collect();
+                    ((package.name.as_str(), &release.version), dependencies)
+                })
+            })
+            .collect();
+        Self {
+            universe,
+            requirements,
+            dependencies,
+        }
+    }
+
+    fn release(&self, name: &str, version: &Version) -> Option<&'a Release<()>> {
+        self.universe
+            .packages
+            .get(name)?
+            .releases
+            .iter()
+            .find(|release| &release.version == version)
+    }
+
+    /// Why the versions do not satisfy the constraints, if they do not.
+    fn violation(&self, solution: &PackageVersions) -> Option<String> {
+        for (name, version) in &self.universe.locked {
+            if solution.get(name.as_str()) != Some(version) {
+                return Some(format!("{name} is not at its locked version {version}"));
+            }
+        }
+
+        for (name, range) in &self.requirements {
+            let Some(version) = solution.get(*name) else {
+                return Some(format!("{name} is required but not selected"));
+            };
+            if !range.contains(version) {
+                return Some(format!("{name} {version} is not in the root range {range}"));
+            }
+        }
+
+        // An exact root requirement allows only that version, pre-releases
+        // included.
+        for (name, range) in &self.universe.requirements {
+            let exact = parse_exact_version(range.as_str());
+            if exact.is_some() && solution.get(name.as_str()) != exact.as_ref() {
+                return Some(format!("{name} is not at its exact version {range}"));
+            }
+        }
+
+        for (name, version) in solution {
+            let Some(release) = self.release(name, version) else {
+                return Some(format!("{name} {version} does not exist"));
+            };
+            let locked = self.universe.locked.get(name.as_str()) == Some(version);
+            if release.is_retired() && !locked {
+                return Some(format!("{name} {version} is retired"));
+            }
+            let dependencies = self
+                .dependencies
+                .get(&(name.as_str(), version))
+                .expect("dependencies");
+            for (dependency, range) in dependencies {
+                match solution.get(*dependency) {
+                    Some(selected) if range.contains(selected) => (),
+                    Some(selected) => {
+                        return Some(format!(
+                        "{name} {version} requires {dependency} {range} but {selected} is selected"
+                    ))
+                    }
+                    None => {
+                        return Some(format!(
+                            "{name} {version} requires {dependency} but it is not selected"
+                        ))
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    /// Whether every selected package is needed by the root package.
+    fn all_needed(&self, solution: &PackageVersions) -> bool {
+        let mut needed: Vec<&str> = self
+            .requirements
+            .iter()
+            .map(|(name, _)| *name)
+            .chain(self.universe.locked.keys().map(|name| name.as_str()))
+            .collect();
+        let mut seen = std::collections::HashSet::new();
+        while let Some(name) = needed.pop() {
+            if !seen.insert(name) {
+                continue;
+            }
+            let Some(version) = solution.get(name) else {
+                continue;
+            };
+            if let Some(dependencies) = self.dependencies.get(&(name, version)) {
+                needed.extend(dependencies.iter().map(|(dependency, _)| *dependency));
+            }
+        }
+        solution.keys().all(|name| seen.contains(name.as_str()))
+    }
+
+    /// Searches every assignment of versions to packages for one that
+    /// satisfies the constraints.
+    fn find_solution(&self) -> Option<PackageVersions> {
+        let mut names: Vec<&String> = self.universe.packages.keys().collect();
+        names.sort();
+        let mut choices = vec![0; names.len()];
+        loop {
+            let solution: PackageVersions = names
+                .iter()
+                .zip(&choices)
+                .filter(|(_, choice)| **choice > 0)
+                .map(|(name, choice)| {
+                    let releases = &self.universe.packages.get(*name).expect("package").releases;
+                    let release = releases.get(choice - 1).expect("release");
+                    ((*name).clone(), release.version.clone())
+                })
+                .collect();
+            if self.violation(&solution).is_none() {
+                return Some(solution);
+            }
+
+            // Move on to the next assignment, counting in mixed radix
+            let mut position = 0;
+            loop {
+                let name = names.get(position)?;
+                let releases = self
+                    .universe
+                    .packages
+                    .get(*name)
+                    .expect("package")
+                    .releases
+                    .len();
+                let choice = choices.get_mut(position).expect("choice");
+                if *choice < releases {
+                    *choice += 1;
+                    break;
+                }
+                *choice = 0;
+                position += 1;
+            }
+        }
+    }
+}
+
+#[allow(clippy::result_large_err)]
+fn check(universe: &Universe, strategy: ResolutionStrategy) -> Result<(), String> {
+    let mut fetchers = PackageFetchers::new();
+    let _ = fetchers.register(SourceKind::Hex, Box::new(universe.clone()));
+    let result = resolve_versions(
+        fetchers,
+        HashMap::new(),
+        "root".into(),
+        universe.requirements.clone().into_iter(),
+        &universe.locked,
+        ResolveOptions {
+            strategy,
+            ..ResolveOptions::default()
+        },
+    );
+    let constraints = Constraints::new(universe);
+    match result {
+        Ok(resolution) => {
+            if let Some(violation) = constraints.violation(&resolution.packages) {
+                return Err(format!(
+                    "the solution {:?} is invalid: {violation}",
+                    resolution.packages
+                ));
+            }
+            if !constraints.all_needed(&resolution.packages) {
+                return Err(format!(
+                    "the solution {:?} has packages that are not needed",
+                    resolution.packages
+                ));
+            }
+            Ok(())
+        }
+        Err(error) => match constraints.find_solution() {
+            Some(solution) => Err(format!(
+                "resolution failed with {error:?} but {solution:?} is a solution"
+            )),
+            None => Ok(()),
+        },
+    }
+}
+
+fn check_seed(seed: u64) {
+    let universe = generate_universe(&mut StdRng::seed_from_u64(seed));
+    for strategy in [
+        ResolutionStrategy::FewestVersions,
+        ResolutionStrategy::FewestPackages,
+    ] {
+        if let Err(problem) = check(&universe, strategy) {
+            panic!("seed {seed} with {strategy:?}: {problem}\n\n{universe:#?}");
+        }
+    }
+}
+
+#[test]
+fn resolution_is_deterministic() {
+    let universe = generate_large_universe(&mut StdRng::seed_from_u64(LARGE_UNIVERSE_SEED));
+    for strategy in [
+        ResolutionStrategy::FewestVersions,
+        ResolutionStrategy::FewestPackages,
+    ] {
+        let first = resolve_shuffled(&universe, strategy, 0);
+        for seed in 1..DETERMINISM_RUNS {
+            assert_eq!(
+                resolve_shuffled(&universe, strategy, seed),
+                first,
+                "run {seed} with {strategy:?} picked differently"
+            );
+        }
+        // The solution is recorded so that a platform that picks differently
+        // fails too.
+        let solution = match first {
+            Ok(packages) => packages
+                .iter()
+                .map(|(name, version)| format!("{name} {version}"))
+                .join("\n"),
+            Err(conflicting) => format!("no solution: {}", conflicting.join(", ")),
+        };
+        insta::assert_snapshot!(
+            format!("resolution_is_deterministic_{strategy:?}"),
+            solution
+        );
+    }
+}
+
+/// A pre-release locked in the manifest was once decided on as the range up to
+/// its next patch, which a requirement of `~> 0.1` later split in two,
+/// panicking pubgrub.
+#[test]
+fn pre_release_decision_is_not_split() {
+    check_seed(535);
+}
+
+#[test]
+fn resolution_satisfies_constraints() {
+    for seed in 0..CASES {
+        check_seed(seed);
+    }
+}
@@ -1,25 +1,105 @@
+pub mod catalog;
+#[cfg(test)]
+mod tests;
+
 use camino::Utf8PathBuf;
 
 pub use codespan_reporting::diagnostic::{LabelStyle, Severity};
-use codespan_reporting::{diagnostic::Label as CodespanLabel, files::SimpleFile};
+use codespan_reporting::{
+    diagnostic::Label as CodespanLabel,
+    files::SimpleFile,
+    term::{Chars, Config, Styles},
+};
 use ecow::EcoString;
-use termcolor::Buffer;
+use serde::{Deserialize, Serialize};
+use strum::{Display, EnumString, VariantNames};
+use termcolor::{Buffer, Color, ColorSpec};
 
 use crate::ast::SrcSpan;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Level {
     Error,
     Warning,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+/// How diagnostics and progress messages are drawn in the terminal.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Display, EnumString, VariantNames)]
+#[strum(serialize_all = "kebab-case")]
+pub enum Theme {
+    /// Bright colours, for terminals with a dark background.
+    #[default]
+    Dark,
+    /// Darker colours, for terminals with a light background.
+    Light,
+    /// The dark theme drawn with ASCII characters only, for terminals and
+    /// fonts without box drawing characters.
+    NoUnicode,
+}
+
+impl Theme {
+    fn config(self) -> Config {
+        match self {
+            Theme::Dark => Config::default(),
+            Theme::NoUnicode => Config {
+                chars: Chars::ascii(),
+                ..Config::default()
+            },
+            Theme::Light => {
+                let header = ColorSpec::new().set_bold(true).clone();
+                let label = |colour| ColorSpec::new().set_fg(Some(colour)).clone();
+                Config {
+                    styles: Styles {
+                        header_bug: header.clone().set_fg(Some(Color::Red)).clone(),
+                        header_error: header.clone().set_fg(Some(Color::Red)).clone(),
+                        header_warning: header.clone().set_fg(Some(Color::Magenta)).clone(),
+                        header_note: header.clone().set_fg(Some(Color::Green)).clone(),
+                        header_help: header.clone().set_fg(Some(Color::Blue)).clone(),
+                        header_message: header,
+                        primary_label_bug: label(Color::Red),
+                        primary_label_error: label(Color::Red),
+                        primary_label_warning: label(Color::Magenta),
+                        primary_label_note: label(Color::Green),
+                        primary_label_help: label(Color::Blue),
+                        secondary_label: label(Color::Blue),
+                        line_number: label(Color::Blue),
+                        source_border: label(Color::Blue),
+                        note_bullet: label(Color::Blue),
+                    },
+                    ..Config::default()
+                }
+            }
+        }
+    }
+
+    /// The colour used for the prefix of progress messages such as
+    /// "Compiling".
+    pub fn progress_colour(self) -> ColorSpec {
+        match self {
+            Theme::Dark | Theme::NoUnicode => ColorSpec::new()
+                .set_intense(true)
+                .set_fg(Some(Color::Magenta))
+                .clone(),
+            Theme::Light => ColorSpec::new().set_fg(Some(Color::Magenta)).clone(),
+        }
+    }
+
+    fn level_colour(self, level: Level) -> Color {
+        match (self, level) {
+            (_, Level::Error) => Color::Red,
+            (Theme::Dark | Theme::NoUnicode, Level::Warning) => Color::Yellow,
+            (Theme::Light, Level::Warning) => Color::Magenta,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Label {
     pub text: Option<String>,
     pub span: SrcSpan,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Location {
     pub src: EcoString,
     pub path: Utf8PathBuf,
@@ -34,7 +114,7 @@ impl Location {
 }
 
 // TODO: split this into locationed diagnostics and locationless diagnostics
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Diagnostic {
     pub title: String,
     pub text: String,
@@ -44,11 +124,11 @@ pub struct Diagnostic {
 }
 
 impl Diagnostic {
-    pub fn write(&self, buffer: &mut Buffer) {
+    pub fn write(&self, buffer: &mut Buffer, theme: Theme) {
         use std::io::Write;
         match &self.location {
-            Some(location) => self.write_span(location, buffer),
-            None => self.write_title(buffer),
+            Some(location) => self.write_span(location, buffer, theme),
+            None => self.write_title(buffer, theme),
         };
 
         if !self.text.is_empty() {
@@ -60,7 +140,15 @@ impl Diagnostic {
         }
     }
 
-    fn write_span(&self, location: &Location, buffer: &mut Buffer) {
+    /// Renders the diagnostic without colour, for places that only support
+    /// plain text.
+    pub fn to_plain_string(&self, theme: Theme) -> String {
+        let mut buffer = Buffer::no_color();
+        self.write(&mut buffer, theme);
+        String::from_utf8(buffer.into_inner()).expect("Diagnostic printing produced invalid utf8")
+    }
+
+    fn write_span(&self, location: &Location, buffer: &mut Buffer, theme: Theme) {
         let file = SimpleFile::new(location.path.to_string(), location.src.as_str());
         let labels = location
             .labels()
@@ -85,18 +173,18 @@ impl Diagnostic {
         let diagnostic = codespan_reporting::diagnostic::Diagnostic::new(severity)
             .with_message(&self.title)
             .with_labels(labels);
-        let config = codespan_reporting::term::Config::default();
-        codespan_reporting::term::emit(buffer, &config, &file, &diagnostic)
+        codespan_reporting::term::emit(buffer, &theme.config(), &file, &diagnostic)
             .expect("write_diagnostic");
     }
 
-    fn write_title(&self, buffer: &mut Buffer) {
+    fn write_title(&self, buffer: &mut Buffer, theme: Theme) {
         use std::io::Write;
-        use termcolor::{Color, ColorSpec, WriteColor};
-        let (kind, colour) = match self.level {
-            Level::Error => ("error", Color::Red),
-            Level::Warning => ("warning", Color::Yellow),
+        use termcolor::WriteColor;
+        let kind = match self.level {
+            Level::Error => "error",
+            Level::Warning => "warning",
         };
+        let colour = theme.level_colour(self.level);
         buffer
             .set_color(ColorSpec::new().set_bold(true).set_fg(Some(colour)))
             .expect("write_title_color1");
@@ -0,0 +1,240 @@
+//! The text of diagnostics, as templates that can be translated.
+//!
+//! Each template has an identifier and the English text, which may refer to
+//! values given when the template is rendered using `{name}` placeholders. A
+//! catalog of translations can be installed at runtime, mapping identifiers to
+//! translated templates. Templates missing from the catalog, and translations
+//! that use placeholders the English text does not, are rendered in English.
+//!
+//! A catalog is a TOML file with one key per template:
+//!
+//! ```toml
+//! module-does-not-exist = "Le module `{module}` est introuvable."
+//! ```
+//!
+//! Only some diagnostics have been moved to templates so far: the errors of
+//! running a module, creating a project, and publishing, and the warnings
+//! about dependencies, generated files, JavaScript entrypoints and platforms,
+//! target specific definitions, and unknown configuration keys. Type errors,
+//! parse errors, and the remaining diagnostics of `error.rs` and `warning.rs`
+//! are still written in English with `format!`, and are not translated.
+
+#[cfg(test)]
+mod tests;
+
+use std::{collections::HashMap, fmt::Display, sync::OnceLock};
+
+use camino::Utf8Path;
+
+use crate::{
+    error::{FileIoAction, FileKind},
+    Error, Result,
+};
+
+static CATALOG: OnceLock<Catalog> = OnceLock::new();
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Template {
+    pub id: &'static str,
+    pub text: &'static str,
+}
+
+impl Template {
+    /// Renders the template using the installed catalog, substituting the
+    /// given values for their placeholders.
+    pub fn render(&self, values: &[(&str, &dyn Display)]) -> String {
+        match CATALOG.get() {
+            Some(catalog) => catalog.render(self, values),
+            None => substitute(self.text, values),
+        }
+    }
+}
+
+/// Translated templates, keyed by the identifier of the template.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Catalog {
+    templates: HashMap<String, String>,
+}
+
+impl Catalog {
+    pub fn parse(path: &Utf8Path, src: &str) -> Result<Self> {
+        let templates = toml::from_str(src).map_err(|e| Error::FileIo {
+            action: FileIoAction::Parse,
+            kind: FileKind::File,
+            path: path.to_path_buf(),
+            err: Some(e.to_string()),
+        })?;
+        Ok(Self { templates })
+    }
+
+    /// The identifiers in the catalog that do not belong to any template,
+    /// typically because the template has been renamed or removed.
+    pub fn unknown_ids(&self) -> Vec<&str> {
+        let mut ids = self
+            .templates
+            .keys()
+            .map(String::as_str)
+            .filter(|id| !TEMPLATES.iter().any(|template| &template.id == id))
+            .collect::<Vec<_>>();
+        ids.sort_unstable();
+        ids
+    }
+
+    pub fn render(&self, template: &Template, values: &[(&str, &dyn Display)]) -> String {
+        match self.templates.get(template.id) {
+            Some(text)
+                if placeholders(text)
+                    .all(|name| placeholders(template.text).any(|n| n == name)) =>
+            {
+                substitute(text, values)
+            }
+            _ => substitute(template.text, values),
+        }
+    }
+}
+
+/// Installs the catalog used to render all diagnostics. Only the first call
+/// has any effect.
+pub fn install(catalog: Catalog) {
+    let _ = CATALOG.set(catalog);
+}
+
+/// Renders a catalog containing the English text of every template, to be
+/// used as the starting point for a translation.
+pub fn english_catalog() -> String {
+    let mut templates = TEMPLATES.to_vec();
+    templates.sort_by_key(|template| template.id);
+    templates
+        .iter()
+        .map(|template| {
+            let text = toml::Value::String(template.text.into());
+            format!("{} = {text}\n", template.id)
+        })
+        .collect()
+}
+
+fn placeholders(text: &str) -> impl Iterator<Item = &str> {
+    text.split('{')
+        .skip(1)
+        .filter_map(|rest| rest.split_once('}'))
+        .map(|(name, _)| name)
+}
+
+fn substitute(text: &str, values: &[(&str, &dyn Display)]) -> String {
+    let mut output = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('{') {
+        let (before, after) = rest.split_at(start);
+        output.push_str(before);
+        let value = after.get(1..).and_then(|after| {
+            let (name, remaining) = after.split_once('}')?;
+            let (_, value) = values.iter().find(|(n, _)| *n == name)?;
+            Some((value, remaining))
+        });
+        match value {
+            Some((value, remaining)) => {
+                output.push_str(&value.to_string());
+                rest = remaining;
+            }
+            None => {
+                output.push('{');
+                rest = after.get(1..).unwrap_or_default();
+            }
+        }
+    }
+    output.push_str(rest);
+    output
+}
+
+macro_rules! templates {
+    ($($name:ident = $id:literal => $text:literal,)*) => {
+        $(pub const $name: Template = Template { id: $id, text: $text };)*
+
+        /// Every template, for building catalogs.
+        pub const TEMPLATES: &[Template] = &[$($name),*];
+    };
+}
+
+templates! {
+    INVALID_MODULE_NAME_TITLE = "invalid-module-name-title" => "Invalid module name",
+    INVALID_MODULE_NAME = "invalid-module-name" => "`{module}` is not a valid module name.
+Module names can only contain lowercase letters, underscore, and
+forward slash and must not end with a slash.",
+
+    MODULE_DOES_NOT_EXIST_TITLE = "module-does-not-exist-title" => "Module does not exist",
+    MODULE_DOES_NOT_EXIST = "module-does-not-exist" => "Module `{module}` was not found.",
+    MODULE_DOES_NOT_EXIST_SUGGESTION = "module-does-not-exist-suggestion" => "Did you mean `{suggestion}`?",
+    MODULE_DOES_NOT_EXIST_CREATE = "module-does-not-exist-create" => "Try creating the file `src/{module}.gleam`.",
+
+    NO_MAIN_FUNCTION_TITLE = "no-main-function-title" => "Module does not have a main function",
+    NO_MAIN_FUNCTION = "no-main-function" => "`{module}` does not have a main function so the module can not be run.",
+    NO_MAIN_FUNCTION_HINT = "no-main-function-hint" => "Add a public `main` function to to `src/{module}.gleam`.",
+
+    MAIN_TARGET_NOT_SUPPORTED_TITLE = "main-target-not-supported-title" => "Target not supported",
+    MAIN_TARGET_NOT_SUPPORTED = "main-target-not-supported" => "`{module}` has a main function, but it does not support the {target} target, so it cannot be run.",
+
+    MAIN_WRONG_ARITY_TITLE = "main-wrong-arity-title" => "Main function has wrong arity",
+    MAIN_WRONG_ARITY = "main-wrong-arity" => "`{module}:main` should have an arity of 0 to be run but its arity is {arity}.",
+    MAIN_WRONG_ARITY_HINT = "main-wrong-arity-hint" => "Change the function signature of main to `pub fn main() {}`.",
+
+    PROJECT_ROOT_EXISTS_TITLE = "project-root-exists-title" => "Project folder already exists",
+    PROJECT_ROOT_EXISTS = "project-root-exists" => "Project folder root:\n\n  {path}",
+
+    CANNOT_PUBLISH_TODO_TITLE = "cannot-publish-todo-title" => "Cannot publish unfinished code",
+    CANNOT_PUBLISH_TODO = "cannot-publish-todo" => "These modules contain todo expressions and cannot be published:
+
+{modules}
+
+Please remove them and try again.
+",
+    MODIFIED_DEPENDENCY_TITLE = "modified-dependency-title" => "Dependency files changed",
+    MODIFIED_DEPENDENCY = "modified-dependency" => "These files of the `{package}` package have been changed since it was
+downloaded:
+
+{files}
+
+The package has been extracted again from the downloaded copy, undoing the
+changes.",
+    MODIFIED_DEPENDENCY_HINT = "modified-dependency-hint" => "The packages in `build/packages` are managed by Gleam. To change a dependency, copy it outside of the build directory and depend on the copy by its path.",
+    MODIFIED_ARTEFACTS_TITLE = "modified-artefacts-title" => "Generated files changed",
+    MODIFIED_ARTEFACTS = "modified-artefacts" => "These generated files have been edited since they were generated:
+
+{files}
+
+Edits to generated files are lost when they are generated again.",
+    MODIFIED_ARTEFACTS_HINT = "modified-artefacts-hint" => "Make the changes to the Gleam source instead, or move the code to an Erlang or JavaScript file in the `src` directory.",
+    STATIC_ENTRYPOINT_IMPORT_TITLE = "static-entrypoint-import-title" => "Entrypoint imported statically",
+    STATIC_ENTRYPOINT_IMPORT = "static-entrypoint-import" => "The `{module}` module imports `{entrypoint}`, which is listed in the
+JavaScript entrypoints of gleam.toml. It will be loaded along with
+`{module}` rather than in its own chunk when it is needed.",
+    STATIC_ENTRYPOINT_IMPORT_HINT = "static-entrypoint-import-hint" => "Load it with the `{loader}` function of `_chunks.mjs` instead.",
+    JAVASCRIPT_PLATFORM_MISMATCH_TITLE = "javascript-platform-mismatch-title" => "Dependency written for another platform",
+    JAVASCRIPT_PLATFORM_MISMATCH = "javascript-platform-mismatch" => "The `{package}` package is written for the {package_platform} JavaScript
+platform, but this project is written for the {platform} platform. It may
+use features that are not available on the {platform} platform.",
+    OTHER_TARGET_ERROR_TITLE = "other-target-error-title" => "{title} on the {target} target",
+    OTHER_TARGET_ERROR = "other-target-error" => "This module has target specific definitions, and does not type check for
+the {target} target:
+
+{text}",
+    TARGET_SIGNATURE_MISMATCH_TITLE = "target-signature-mismatch-title" => "Public definition differs between targets",
+    TARGET_SIGNATURE_MISMATCH = "target-signature-mismatch" => "`{name}` has this type on the {target} target:
+
+    {type}
+
+But it has this type on the {other_target} target:
+
+    {other_type}",
+    TARGET_SIGNATURE_MISMATCH_HINT = "target-signature-mismatch-hint" => "Code using this module will only work on one of the targets. Give the definition the same type on both.",
+    RETIRED_DEPENDENCY_TITLE = "retired-dependency-title" => "Retired dependency version",
+    RETIRED_DEPENDENCY = "retired-dependency" => "Version {version} of the `{package}` package is locked in the manifest, but
+has since been retired by its maintainers.
+
+Reason: {reason}",
+    RETIRED_DEPENDENCY_HINT = "retired-dependency-hint" => "Run `gleam update {package}` to update to version {replacement}, the newest compatible version that has not been retired.",
+    RETIRED_DEPENDENCY_WITHOUT_REPLACEMENT_HINT = "retired-dependency-without-replacement-hint" => "There is no newer compatible version that has not been retired. A new major version of the package, or another package, may be needed.",
+    UNKNOWN_CONFIG_KEY_TITLE = "unknown-config-key-title" => "Unknown key in {file}",
+    UNKNOWN_CONFIG_KEY = "unknown-config-key" => "`{key}` is not a key Gleam knows about, so it is ignored.",
+    UNKNOWN_CONFIG_KEY_LABEL = "unknown-config-key-label" => "Unknown key",
+    UNKNOWN_CONFIG_KEY_HINT = "unknown-config-key-hint" => "The configuration of other tools can be written in a `[tools.<name>]` table.",
+}
@@ -0,0 +1,89 @@
+use super::{
+    english_catalog, Catalog, MAIN_WRONG_ARITY, MAIN_WRONG_ARITY_HINT, MODULE_DOES_NOT_EXIST,
+    TEMPLATES,
+};
+
+use camino::Utf8Path;
+
+fn catalog(src: &str) -> Catalog {
+    Catalog::parse(Utf8Path::new("fr.toml"), src).expect("valid catalog")
+}
+
+#[test]
+fn template_ids_are_unique() {
+    let mut ids = TEMPLATES
+        .iter()
+        .map(|template| template.id)
+        .collect::<Vec<_>>();
+    ids.sort_unstable();
+    ids.dedup();
+    assert_eq!(ids.len(), TEMPLATES.len());
+}
+
+#[test]
+fn render_without_translation() {
+    assert_eq!(
+        MODULE_DOES_NOT_EXIST.render(&[("module", &"wibble/wobble")]),
+        "Module `wibble/wobble` was not found."
+    );
+}
+
+#[test]
+fn render_translation() {
+    let catalog = catalog(r#"module-does-not-exist = "Le module `{module}` est introuvable.""#);
+    assert_eq!(
+        catalog.render(&MODULE_DOES_NOT_EXIST, &[("module", &"wibble")]),
+        "Le module `wibble` est introuvable."
+    );
+}
+
+#[test]
+fn render_translation_with_unknown_placeholder_uses_english() {
+    let catalog = catalog(r#"module-does-not-exist = "Le module `{name}` est introuvable.""#);
+    assert_eq!(
+        catalog.render(&MODULE_DOES_NOT_EXIST, &[("module", &"wibble")]),
+        "Module `wibble` was not found."
+    );
+}
+
+#[test]
+fn render_keeps_braces_without_values() {
+    assert_eq!(
+        MAIN_WRONG_ARITY_HINT.render(&[]),
+        "Change the function signature of main to `pub fn main() {}`."
+    );
+    assert_eq!(
+        MAIN_WRONG_ARITY.render(&[("module", &"app"), ("arity", &2)]),
+        "`app:main` should have an arity of 0 to be run but its arity is 2."
+    );
+}
+
+#[test]
+fn unknown_ids() {
+    let catalog = catalog(
+        r#"
+module-does-not-exist = "Le module `{module}` est introuvable."
+module-is-missing = "?"
+"#,
+    );
+    assert_eq!(catalog.unknown_ids(), vec!["module-is-missing"]);
+}
+
+#[test]
+fn invalid_catalog() {
+    assert!(Catalog::parse(Utf8Path::new("fr.toml"), "module-does-not-exist = 1").is_err());
+}
+
+#[test]
+fn english_catalog_round_trips() {
+    let src = english_catalog();
+    let catalog = catalog(&src);
+    assert!(catalog.unknown_ids().is_empty());
+    for template in TEMPLATES {
+        assert_eq!(
+            catalog.templates.get(template.id).map(String::as_str),
+            Some(template.text)
+        );
+    }
+    insta::assert_snapshot!(src);
+}
@@ -0,0 +1,62 @@
+use super::{Diagnostic, Label, Level, Location, Theme};
+use crate::ast::SrcSpan;
+
+use termcolor::Buffer;
+
+fn diagnostic() -> Diagnostic {
+    Diagnostic {
+        title: "Unknown variable".into(),
+        text: "The name `wibble` is not in scope here.".into(),
+        level: Level::Warning,
+        location: Some(Location {
+            src: "pub fn main() { wibble }".into(),
+            path: "src/app.gleam".into(),
+            label: Label {
+                text: Some("Did you mean `wobble`?".into()),
+                span: SrcSpan { start: 16, end: 22 },
+            },
+            extra_labels: vec![],
+        }),
+        hint: Some("Define it with `let`.".into()),
+    }
+}
+
+#[test]
+fn no_unicode_theme_uses_ascii() {
+    let rendered = diagnostic().to_plain_string(Theme::NoUnicode);
+    assert!(rendered.is_ascii());
+    insta::assert_snapshot!(rendered);
+}
+
+#[test]
+fn dark_theme_uses_box_drawing() {
+    insta::assert_snapshot!(diagnostic().to_plain_string(Theme::Dark));
+}
+
+#[test]
+fn light_theme_avoids_intense_colours() {
+    let render = |theme| {
+        let mut buffer = Buffer::ansi();
+        diagnostic().write(&mut buffer, theme);
+        String::from_utf8(buffer.into_inner()).expect("utf8 diagnostic")
+    };
+    // Yellow and bright colours are hard to read on a light background
+    let dark = render(Theme::Dark);
+    assert!(dark.contains("\x1b[33m"));
+    let light = render(Theme::Light);
+    assert!(!light.contains("\x1b[33m"));
+    assert!(!light.contains("\x1b[38;5;"));
+    assert!(light.contains("\x1b[35m"));
+}
+
+#[test]
+fn title_without_location_uses_theme_colour() {
+    let diagnostic = Diagnostic {
+        location: None,
+        ..diagnostic()
+    };
+    let mut buffer = Buffer::ansi();
+    diagnostic.write(&mut buffer, Theme::Light);
+    let rendered = String::from_utf8(buffer.into_inner()).expect("utf8 diagnostic");
+    assert!(rendered.contains("\x1b[35mwarning"));
+}
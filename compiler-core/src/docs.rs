@@ -1,4 +1,7 @@
+mod dependency_links;
 mod source_links;
+
+pub use dependency_links::DependencyLinker;
 #[cfg(test)]
 mod tests;
 
@@ -10,12 +13,12 @@ use crate::{
     ast::{
         CustomType, Definition, Function, ModuleConstant, Publicity, TypeAlias, TypedDefinition,
     },
-    build::{Module, Package},
+    build::{Built, Module, Package},
     config::{DocsPage, PackageConfig},
     docs::source_links::SourceLinker,
     format,
     io::{Content, FileSystemReader, OutputFile},
-    package_interface::PackageInterface,
+    package_interface::{PackageInterface, ProjectInterface},
     paths::ProjectPaths,
     pretty,
     type_::Deprecation,
@@ -33,13 +36,18 @@ const MAX_COLUMNS: isize = 65;
 pub enum DocContext {
     HexPublish,
     Build,
+    /// Docs bundled into an archive to be read without a network connection,
+    /// so nothing served by HexDocs can be relied upon.
+    OfflineBundle,
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn generate_html<IO: FileSystemReader>(
     paths: &ProjectPaths,
     config: &PackageConfig,
     analysed: &[Module],
     docs_pages: &[DocsPage],
+    dependency_links: &DependencyLinker,
     fs: IO,
     rendering_timestamp: SystemTime,
     is_hex_publish: DocContext,
@@ -83,9 +91,11 @@ pub fn generate_html<IO: FileSystemReader>(
                 path: format!("https://hex.pm/packages/{0}", config.name).to_string(),
             }])
             .collect(),
-        DocContext::Build => doc_links.chain(repo_link).collect(),
+        DocContext::Build | DocContext::OfflineBundle => doc_links.chain(repo_link).collect(),
     };
 
+    let offline = is_hex_publish == DocContext::OfflineBundle;
+
     let mut files = vec![];
 
     let mut search_indexes = vec![];
@@ -132,6 +142,7 @@ pub fn generate_html<IO: FileSystemReader>(
             content: rendered_content,
             rendering_timestamp: &rendering_timestamp,
             unnest: &unnest,
+            offline,
         };
 
         files.push(OutputFile {
@@ -164,7 +175,7 @@ pub fn generate_html<IO: FileSystemReader>(
             .definitions
             .iter()
             .filter(|statement| !statement.is_internal())
-            .flat_map(|statement| function(&source_links, statement))
+            .flat_map(|statement| function(&source_links, dependency_links, statement))
             .sorted()
             .collect();
 
@@ -173,7 +184,7 @@ pub fn generate_html<IO: FileSystemReader>(
             .definitions
             .iter()
             .filter(|statement| !statement.is_internal())
-            .flat_map(|statement| type_(&source_links, statement))
+            .flat_map(|statement| type_(&source_links, dependency_links, statement))
             .sorted()
             .collect();
 
@@ -182,7 +193,7 @@ pub fn generate_html<IO: FileSystemReader>(
             .definitions
             .iter()
             .filter(|statement| !statement.is_internal())
-            .flat_map(|statement| constant(&source_links, statement))
+            .flat_map(|statement| constant(&source_links, dependency_links, statement))
             .sorted()
             .collect();
 
@@ -269,6 +280,7 @@ pub fn generate_html<IO: FileSystemReader>(
             types,
             constants,
             rendering_timestamp: &rendering_timestamp,
+            offline,
         };
 
         files.push(OutputFile {
@@ -446,6 +458,96 @@ pub fn generate_html<IO: FileSystemReader>(
     files
 }
 
+/// Renders the documentation of the whole package as a single Markdown file,
+/// for reading without a browser or feeding to other tools.
+///
+pub fn generate_single_page_markdown<IO: FileSystemReader>(
+    paths: &ProjectPaths,
+    config: &PackageConfig,
+    analysed: &[Module],
+    docs_pages: &[DocsPage],
+    dependency_links: &DependencyLinker,
+    fs: IO,
+) -> OutputFile {
+    let mut content = format!("# {} v{}\n", config.name, config.version);
+
+    for page in docs_pages {
+        let page_content = fs.read(&page.source).unwrap_or_default();
+        content.push_str(&format!("\n{}\n", page_content.trim_end()));
+    }
+
+    let modules = analysed
+        .iter()
+        .filter(|module| !module.is_test())
+        .filter(|module| !config.is_internal_module(&module.name))
+        .sorted_by(|a, b| a.name.cmp(&b.name));
+
+    for module in modules {
+        let source_links = SourceLinker::new(paths, config, module);
+        let definitions = || {
+            module
+                .ast
+                .definitions
+                .iter()
+                .filter(|statement| !statement.is_internal())
+        };
+
+        content.push_str(&format!("\n## {}\n", module.name));
+        let documentation = module.ast.documentation.iter().join("\n");
+        if !documentation.is_empty() {
+            content.push_str(&format!("\n{}\n", strip_comment_prefix(&documentation)));
+        }
+
+        let types = definitions()
+            .flat_map(|statement| type_(&source_links, dependency_links, statement))
+            .sorted();
+        for typ in types {
+            single_page_member(
+                &mut content,
+                typ.name,
+                &typ.definition,
+                &typ.raw_documentation,
+            );
+        }
+
+        let constants = definitions()
+            .flat_map(|statement| constant(&source_links, dependency_links, statement))
+            .sorted();
+        for constant in constants {
+            single_page_member(
+                &mut content,
+                constant.name,
+                &constant.definition,
+                &constant.raw_documentation,
+            );
+        }
+
+        let functions = definitions()
+            .flat_map(|statement| function(&source_links, dependency_links, statement))
+            .sorted();
+        for function in functions {
+            single_page_member(
+                &mut content,
+                function.name,
+                &function.signature,
+                &function.raw_documentation,
+            );
+        }
+    }
+
+    OutputFile {
+        path: Utf8PathBuf::from(format!("{}.md", config.name)),
+        content: Content::Text(content),
+    }
+}
+
+fn single_page_member(content: &mut String, name: &str, definition: &str, documentation: &str) {
+    content.push_str(&format!("\n### {name}\n\n```gleam\n{definition}\n```\n"));
+    if !documentation.is_empty() {
+        content.push_str(&format!("\n{}\n", documentation.trim_end()));
+    }
+}
+
 pub fn generate_json_package_interface(path: Utf8PathBuf, package: &Package) -> OutputFile {
     OutputFile {
         path,
@@ -456,6 +558,24 @@ pub fn generate_json_package_interface(path: Utf8PathBuf, package: &Package) ->
     }
 }
 
+pub fn generate_json_project_interface(
+    path: Utf8PathBuf,
+    built: &Built,
+    dependencies: &[PackageConfig],
+) -> OutputFile {
+    OutputFile {
+        path,
+        content: Content::Text(
+            serde_json::to_string(&ProjectInterface::new(
+                &built.root_package,
+                dependencies,
+                built.module_interfaces(),
+            ))
+            .expect("JSON project interface serialisation"),
+        ),
+    }
+}
+
 fn page_unnest(path: &str) -> String {
     let unnest = path
         .strip_prefix('/')
@@ -511,6 +631,7 @@ fn import_synonyms(parent: &str, child: &str) -> String {
 
 fn function<'a>(
     source_links: &SourceLinker,
+    dependency_links: &DependencyLinker,
     statement: &'a TypedDefinition,
 ) -> Option<DocsFunction<'a>> {
     let mut formatter = format::Formatter::new();
@@ -529,12 +650,15 @@ fn function<'a>(
             name,
             documentation: markdown_documentation(doc),
             text_documentation: text_documentation(doc),
+            raw_documentation: raw_documentation(doc),
             signature: print(
                 formatter
                     .docs_fn_signature(Publicity::Public, name, args, ret.clone(), location)
                     .group(),
             ),
             source_url: source_links.url(*location),
+            dependency_links: dependency_links
+                .links(args.iter().map(|arg| &arg.type_).chain([ret])),
             deprecation_message: match deprecation {
                 Deprecation::NotDeprecated => "".to_string(),
                 Deprecation::Deprecated { message } => message.to_string(),
@@ -555,6 +679,10 @@ fn text_documentation(doc: &Option<EcoString>) -> String {
     raw_text.replace("```gleam", "").replace("```", "")
 }
 
+fn raw_documentation(doc: &Option<EcoString>) -> String {
+    doc.as_deref().map(strip_comment_prefix).unwrap_or_default()
+}
+
 fn markdown_documentation(doc: &Option<EcoString>) -> String {
     doc.as_deref()
         .map(|doc| render_markdown(doc, MarkdownSource::Comment))
@@ -574,12 +702,7 @@ enum MarkdownSource {
 fn render_markdown(text: &str, source: MarkdownSource) -> String {
     let text = match source {
         MarkdownSource::Standalone => text.into(),
-        // Doc comments start with "///\s", which can confuse the markdown parser
-        // and prevent tables from rendering correctly, so remove that first space.
-        MarkdownSource::Comment => text
-            .split('\n')
-            .map(|s| s.strip_prefix(' ').unwrap_or(s))
-            .join("\n"),
+        MarkdownSource::Comment => strip_comment_prefix(text),
     };
 
     let mut s = String::with_capacity(text.len() * 3 / 2);
@@ -588,7 +711,19 @@ fn render_markdown(text: &str, source: MarkdownSource) -> String {
     s
 }
 
-fn type_<'a>(source_links: &SourceLinker, statement: &'a TypedDefinition) -> Option<Type<'a>> {
+// Doc comments start with "///\s", which can confuse the markdown parser
+// and prevent tables from rendering correctly, so remove that first space.
+fn strip_comment_prefix(text: &str) -> String {
+    text.split('\n')
+        .map(|s| s.strip_prefix(' ').unwrap_or(s))
+        .join("\n")
+}
+
+fn type_<'a>(
+    source_links: &SourceLinker,
+    dependency_links: &DependencyLinker,
+    statement: &'a TypedDefinition,
+) -> Option<Type<'a>> {
     let mut formatter = format::Formatter::new();
 
     match statement {
@@ -600,6 +735,7 @@ fn type_<'a>(source_links: &SourceLinker, statement: &'a TypedDefinition) -> Opt
             definition: print(formatter.custom_type(ct)),
             documentation: markdown_documentation(&ct.documentation),
             text_documentation: text_documentation(&ct.documentation),
+            raw_documentation: raw_documentation(&ct.documentation),
             deprecation_message: match &ct.deprecation {
                 Deprecation::NotDeprecated => "".to_string(),
                 Deprecation::Deprecated { message } => message.to_string(),
@@ -624,6 +760,12 @@ fn type_<'a>(source_links: &SourceLinker, statement: &'a TypedDefinition) -> Opt
                 })
                 .collect(),
             source_url: source_links.url(ct.location),
+            dependency_links: dependency_links.links(
+                ct.constructors
+                    .iter()
+                    .flat_map(|constructor| &constructor.arguments)
+                    .map(|argument| &argument.type_),
+            ),
             opaque: ct.opaque,
         }),
 
@@ -645,8 +787,10 @@ fn type_<'a>(source_links: &SourceLinker, statement: &'a TypedDefinition) -> Opt
             ),
             documentation: markdown_documentation(doc),
             text_documentation: text_documentation(doc),
+            raw_documentation: raw_documentation(doc),
             constructors: vec![],
             source_url: source_links.url(*location),
+            dependency_links: vec![],
             deprecation_message: match deprecation {
                 Deprecation::NotDeprecated => "".to_string(),
                 Deprecation::Deprecated { message } => message.to_string(),
@@ -658,11 +802,11 @@ fn type_<'a>(source_links: &SourceLinker, statement: &'a TypedDefinition) -> Opt
             publicity: Publicity::Public,
             alias: name,
             type_ast: typ,
+            type_,
             documentation: doc,
             parameters: args,
             location,
             deprecation,
-            ..
         }) => Some(Type {
             name,
             definition: print(
@@ -672,8 +816,10 @@ fn type_<'a>(source_links: &SourceLinker, statement: &'a TypedDefinition) -> Opt
             ),
             documentation: markdown_documentation(doc),
             text_documentation: text_documentation(doc),
+            raw_documentation: raw_documentation(doc),
             constructors: vec![],
             source_url: source_links.url(*location),
+            dependency_links: dependency_links.links([type_]),
             deprecation_message: match deprecation {
                 Deprecation::NotDeprecated => "".to_string(),
                 Deprecation::Deprecated { message } => message.to_string(),
@@ -687,6 +833,7 @@ fn type_<'a>(source_links: &SourceLinker, statement: &'a TypedDefinition) -> Opt
 
 fn constant<'a>(
     source_links: &SourceLinker,
+    dependency_links: &DependencyLinker,
     statement: &'a TypedDefinition,
 ) -> Option<Constant<'a>> {
     let mut formatter = format::Formatter::new();
@@ -703,7 +850,9 @@ fn constant<'a>(
             definition: print(formatter.docs_const_expr(Publicity::Public, name, value)),
             documentation: markdown_documentation(doc),
             text_documentation: text_documentation(doc),
+            raw_documentation: raw_documentation(doc),
             source_url: source_links.url(*location),
+            dependency_links: dependency_links.links([&value.type_()]),
         }),
 
         _ => None,
@@ -714,7 +863,7 @@ fn print(doc: pretty::Document<'_>) -> String {
     doc.to_pretty_string(MAX_COLUMNS)
 }
 
-#[derive(PartialEq, Eq, PartialOrd, Ord, Clone)]
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Debug)]
 struct Link {
     name: String,
     path: String,
@@ -726,7 +875,9 @@ struct DocsFunction<'a> {
     signature: String,
     documentation: String,
     text_documentation: String,
+    raw_documentation: String,
     source_url: String,
+    dependency_links: Vec<Link>,
     deprecation_message: String,
 }
 
@@ -751,7 +902,9 @@ struct Type<'a> {
     documentation: String,
     constructors: Vec<TypeConstructor>,
     text_documentation: String,
+    raw_documentation: String,
     source_url: String,
+    dependency_links: Vec<Link>,
     deprecation_message: String,
     opaque: bool,
 }
@@ -762,7 +915,9 @@ struct Constant<'a> {
     definition: String,
     documentation: String,
     text_documentation: String,
+    raw_documentation: String,
     source_url: String,
+    dependency_links: Vec<Link>,
 }
 
 #[derive(Template)]
@@ -779,6 +934,7 @@ struct PageTemplate<'a> {
     modules: &'a [Link],
     content: String,
     rendering_timestamp: &'a str,
+    offline: bool,
 }
 
 #[derive(Template)]
@@ -799,6 +955,7 @@ struct ModuleTemplate<'a> {
     constants: Vec<Constant<'a>>,
     documentation: String,
     rendering_timestamp: &'a str,
+    offline: bool,
 }
 
 #[derive(Serialize, PartialEq, Eq, PartialOrd, Ord, Clone)]
@@ -0,0 +1,112 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+
+use ecow::EcoString;
+use itertools::Itertools;
+
+use crate::{
+    manifest::{Manifest, ManifestPackageSource},
+    type_::{Type, TypeVar},
+};
+
+use super::Link;
+
+/// Links the types of dependencies used in a package's signatures to their
+/// documentation on HexDocs, for the exact version of the dependency that was
+/// resolved in the manifest.
+///
+#[derive(Debug)]
+pub struct DependencyLinker {
+    /// The HexDocs base url of each dependency whose resolved version was
+    /// published to Hex with docs. Other dependencies, such as git and path
+    /// dependencies and those of Hex organisations, have no docs on HexDocs
+    /// so they are left out and their types are rendered without a link.
+    docs_urls: HashMap<EcoString, String>,
+}
+
+impl DependencyLinker {
+    /// Links to the docs of the dependencies in `documented`, the names of
+    /// the Hex packages whose versions in the manifest have docs published.
+    pub fn new(manifest: &Manifest, documented: &HashSet<EcoString>) -> Self {
+        let docs_urls = manifest
+            .packages
+            .iter()
+            .filter(|package| documented.contains(&package.name))
+            .filter_map(|package| match package.source {
+                ManifestPackageSource::Hex {
+                    organization: None, ..
+                } => Some((
+                    package.name.clone(),
+                    format!("https://hexdocs.pm/{}/{}", package.name, package.version),
+                )),
+                ManifestPackageSource::Hex { .. }
+                | ManifestPackageSource::Git { .. }
+                | ManifestPackageSource::Local { .. } => None,
+            })
+            .collect();
+        Self { docs_urls }
+    }
+
+    /// The url of the documentation for a type defined in the given package,
+    /// if that package's docs are available on HexDocs.
+    pub(super) fn url(&self, package: &str, module: &str, name: &str) -> Option<String> {
+        self.docs_urls
+            .get(package)
+            .map(|base| format!("{base}/{module}.html#{name}"))
+    }
+
+    /// Links to all the types from other packages that appear in the given
+    /// types, in the order they first appear, without duplicates.
+    pub(super) fn links<'a>(&self, types: impl IntoIterator<Item = &'a Arc<Type>>) -> Vec<Link> {
+        let mut named = vec![];
+        for type_ in types {
+            collect_named_types(type_, &mut named);
+        }
+        named
+            .into_iter()
+            .unique()
+            .filter_map(|(package, module, name)| {
+                let path = self.url(&package, &module, &name)?;
+                let name = format!("{}.{name}", module.rsplit('/').next().unwrap_or(&module));
+                Some(Link { name, path })
+            })
+            .collect()
+    }
+}
+
+fn collect_named_types(type_: &Type, acc: &mut Vec<(EcoString, EcoString, EcoString)>) {
+    match type_ {
+        Type::Named {
+            package,
+            module,
+            name,
+            args,
+            ..
+        } => {
+            acc.push(((*package).into(), (*module).into(), (*name).into()));
+            for arg in args {
+                collect_named_types(arg, acc);
+            }
+        }
+
+        Type::Fn { args, retrn } => {
+            for arg in args {
+                collect_named_types(arg, acc);
+            }
+            collect_named_types(retrn, acc);
+        }
+
+        Type::Var { type_ } => match &*type_.borrow() {
+            TypeVar::Link { type_ } => collect_named_types(type_, acc),
+            TypeVar::Unbound { .. } | TypeVar::Generic { .. } => (),
+        },
+
+        Type::Tuple { elems } => {
+            for elem in elems {
+                collect_named_types(elem, acc);
+            }
+        }
+    }
+}
@@ -1,11 +1,20 @@
-use std::{collections::HashSet, time::SystemTime};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+    time::SystemTime,
+};
 
 use crate::{
-    build::{Mode, NullTelemetry, PackageCompiler, StaleTracker, TargetCodegenConfiguration},
+    ast::Publicity,
+    build::{
+        Mode, Module, NullTelemetry, PackageCompiler, StaleTracker, TargetCodegenConfiguration,
+    },
     config::{DocsPage, PackageConfig},
-    docs::DocContext,
+    docs::{dependency_links::DependencyLinker, DocContext, Link},
     io::{memory::InMemoryFileSystem, FileSystemWriter},
+    manifest::{Base16Checksum, Manifest, ManifestPackage, ManifestPackageSource},
     paths::ProjectPaths,
+    type_::Type,
     uid::UniqueIdGenerator,
     version::COMPILER_VERSION,
     warning::WarningEmitter,
@@ -14,29 +23,13 @@ use camino::Utf8PathBuf;
 use ecow::EcoString;
 use itertools::Itertools;
 
-fn compile_with_markdown_pages(
-    config: PackageConfig,
-    modules: Vec<(&str, &str)>,
-    markdown_pages: Vec<(&str, &str)>,
-) -> EcoString {
+fn compile_modules(config: &PackageConfig, modules: Vec<(&str, &str)>) -> Vec<Module> {
     let fs = InMemoryFileSystem::new();
     for (name, src) in modules {
         fs.write(&Utf8PathBuf::from(format!("/src/{name}")), src)
             .unwrap();
     }
 
-    // We're saving the pages under a different `InMemoryFileSystem` for these
-    // tests so we don't have to juggle with borrows and lifetimes.
-    // The package compiler is going to take ownership of `fs` but later
-    // `generate_html` also needs a `FileSystemReader` to go and read the
-    // markdown pages' content.
-    let pages_fs = InMemoryFileSystem::new();
-    for (title, src) in markdown_pages.iter() {
-        pages_fs
-            .write(&Utf8PathBuf::from(format!("{title}.md")), src)
-            .unwrap();
-    }
-
     let ids = UniqueIdGenerator::new();
     let mut type_manifests = im::HashMap::new();
     let mut defined_modules = im::HashMap::new();
@@ -46,9 +39,8 @@ fn compile_with_markdown_pages(
     let root = Utf8PathBuf::from("/");
     let build = root.join("build");
     let lib = root.join("lib");
-    let paths = ProjectPaths::new(root.clone());
     let mut compiler =
-        PackageCompiler::new(&config, Mode::Dev, &root, &build, &lib, &target, ids, fs);
+        PackageCompiler::new(config, Mode::Dev, &root, &build, &lib, &target, ids, fs);
     compiler.write_entrypoint = false;
     compiler.write_metadata = false;
     compiler.compile_beam_bytecode = true;
@@ -66,7 +58,36 @@ fn compile_with_markdown_pages(
     for module in &mut modules {
         module.attach_doc_and_module_comments();
     }
+    modules
+}
+
+fn empty_manifest() -> Manifest {
+    Manifest {
+        requirements: HashMap::new(),
+        packages: vec![],
+    }
+}
+
+fn compile_with_markdown_pages(
+    config: PackageConfig,
+    modules: Vec<(&str, &str)>,
+    markdown_pages: Vec<(&str, &str)>,
+) -> EcoString {
+    let modules = compile_modules(&config, modules);
+
+    // We're saving the pages under a different `InMemoryFileSystem` for these
+    // tests so we don't have to juggle with borrows and lifetimes.
+    // The package compiler is going to take ownership of `fs` but later
+    // `generate_html` also needs a `FileSystemReader` to go and read the
+    // markdown pages' content.
+    let pages_fs = InMemoryFileSystem::new();
+    for (title, src) in markdown_pages.iter() {
+        pages_fs
+            .write(&Utf8PathBuf::from(format!("{title}.md")), src)
+            .unwrap();
+    }
 
+    let paths = ProjectPaths::new(Utf8PathBuf::from("/"));
     let docs_pages = markdown_pages
         .into_iter()
         .map(|(title, _)| DocsPage {
@@ -81,6 +102,7 @@ fn compile_with_markdown_pages(
         &config,
         &modules,
         &docs_pages,
+        &DependencyLinker::new(&empty_manifest(), &HashSet::new()),
         pages_fs,
         SystemTime::UNIX_EPOCH,
         DocContext::HexPublish,
@@ -270,3 +292,166 @@ fn markdown_code_from_module_comment_is_trimmed() {
     )];
     insta::assert_snapshot!(compile(config, modules));
 }
+
+#[test]
+fn single_page_markdown() {
+    let config = PackageConfig {
+        name: "my_package".into(),
+        ..Default::default()
+    };
+    let modules = compile_modules(
+        &config,
+        vec![(
+            "app.gleam",
+            r#"
+//// The app module.
+
+/// A wibble.
+///
+/// ```gleam
+/// Wibble(1)
+/// ```
+pub type Wibble {
+  Wibble(Int)
+}
+
+/// The default wobble.
+pub const wobble = 1
+
+/// Makes a wibble.
+pub fn new(n: Int) -> Wibble {
+  Wibble(n)
+}
+
+@internal
+pub fn hidden() { 1 }
+"#,
+        )],
+    );
+
+    let pages_fs = InMemoryFileSystem::new();
+    pages_fs
+        .write(&Utf8PathBuf::from("README.md"), "Hello, Joe!\n")
+        .expect("write README");
+    let pages = vec![DocsPage {
+        title: "README".into(),
+        path: "index.html".into(),
+        source: "README.md".into(),
+    }];
+
+    let output = super::generate_single_page_markdown(
+        &ProjectPaths::new(Utf8PathBuf::from("/")),
+        &config,
+        &modules,
+        &pages,
+        &DependencyLinker::new(&empty_manifest(), &HashSet::new()),
+        pages_fs,
+    );
+    assert_eq!(
+        output.path,
+        Utf8PathBuf::from(format!("{}.md", config.name))
+    );
+    insta::assert_snapshot!(output.content.text().expect("text output"));
+}
+
+fn manifest_package(name: &str, version: &str, source: ManifestPackageSource) -> ManifestPackage {
+    ManifestPackage {
+        name: name.into(),
+        version: hexpm::version::Version::parse(version).expect("valid version"),
+        build_tools: vec!["gleam".into()],
+        otp_app: None,
+        requirements: vec![],
+        source,
+    }
+}
+
+fn named_type(package: &str, module: &str, name: &str, args: Vec<Arc<Type>>) -> Arc<Type> {
+    Arc::new(Type::Named {
+        publicity: Publicity::Public,
+        package: package.into(),
+        module: module.into(),
+        name: name.into(),
+        args,
+    })
+}
+
+#[test]
+fn dependency_types_link_to_resolved_hexdocs_version() {
+    let manifest = Manifest {
+        requirements: HashMap::new(),
+        packages: vec![
+            manifest_package(
+                "gleam_stdlib",
+                "0.38.0",
+                ManifestPackageSource::Hex {
+                    outer_checksum: Base16Checksum(vec![]),
+                    organization: None,
+                },
+            ),
+            manifest_package(
+                "wibble",
+                "1.0.0",
+                ManifestPackageSource::Local {
+                    path: "../wibble".into(),
+                },
+            ),
+        ],
+    };
+    let linker = DependencyLinker::new(&manifest, &["gleam_stdlib".into()].into());
+
+    let option = named_type(
+        "gleam_stdlib",
+        "gleam/option",
+        "Option",
+        vec![named_type("wibble", "wibble", "Wibble", vec![])],
+    );
+    let dict = named_type("gleam_stdlib", "gleam/dict", "Dict", vec![option.clone()]);
+    let int = named_type("", "gleam", "Int", vec![]);
+    let own = named_type("my_package", "my_package", "Thing", vec![]);
+
+    assert_eq!(
+        linker.links([&dict, &option, &int, &own]),
+        vec![
+            Link {
+                name: "dict.Dict".into(),
+                path: "https://hexdocs.pm/gleam_stdlib/0.38.0/gleam/dict.html#Dict".into(),
+            },
+            Link {
+                name: "option.Option".into(),
+                path: "https://hexdocs.pm/gleam_stdlib/0.38.0/gleam/option.html#Option".into(),
+            },
+        ]
+    );
+}
+
+#[test]
+fn dependency_types_without_published_docs_are_not_linked() {
+    let manifest = Manifest {
+        requirements: HashMap::new(),
+        packages: vec![
+            manifest_package(
+                "wobble",
+                "2.0.0",
+                ManifestPackageSource::Hex {
+                    outer_checksum: Base16Checksum(vec![]),
+                    organization: None,
+                },
+            ),
+            manifest_package(
+                "private",
+                "1.0.0",
+                ManifestPackageSource::Hex {
+                    outer_checksum: Base16Checksum(vec![]),
+                    organization: Some("acme".into()),
+                },
+            ),
+        ],
+    };
+    // Organisation packages have no docs on HexDocs even if published with
+    // them
+    let linker = DependencyLinker::new(&manifest, &["private".into()].into());
+
+    let wobble = named_type("wobble", "wobble", "Wobble", vec![]);
+    let private = named_type("private", "private", "Secret", vec![]);
+    assert_eq!(linker.links([&wobble, &private]), vec![]);
+}
@@ -0,0 +1,577 @@
+//! A stable interface for Rust programs that embed the Gleam compiler, such as
+//! linters, documentation generators, and build systems.
+//!
+//! The functions and types defined in this module follow semantic versioning:
+//! they will not be changed or removed without a new major version of this
+//! crate. Everything else in the crate is internal to the Gleam compiler and
+//! may change in any release, so none of its types appear in the functions
+//! defined here: they take and return types of their own instead.
+//!
+//! These functions don't access the file system, run processes, or use the
+//! network. The caller provides the files and package information they need,
+//! so they can be used from a sandbox.
+//!
+//! ```
+//! use camino::Utf8Path;
+//! use gleam_core::embed;
+//!
+//! let src = "pub fn main() { 1 + 1 }";
+//! let formatted = embed::format_source(Utf8Path::new("src/app.gleam"), src)
+//!     .expect("the source is valid Gleam");
+//! assert_eq!(formatted, "pub fn main() {\n  1 + 1\n}\n");
+//! ```
+
+#[cfg(test)]
+mod tests;
+
+use std::{
+    collections::{HashMap, HashSet},
+    error::Error as StdError,
+    fmt,
+    sync::Arc,
+};
+
+use camino::{Utf8Path, Utf8PathBuf};
+use ecow::EcoString;
+use hexpm::version::{Range, Version};
+use termcolor::Buffer;
+
+use crate::{
+    analyse::TargetSupport,
+    ast::Definition as AstDefinition,
+    build::{Mode, NullTelemetry, PackageCompiler, StaleTracker, TargetCodegenConfiguration},
+    config::PackageConfig,
+    dependency::{self, PackageFetcher, PackageFetchers, ResolutionWarning, SourceKind},
+    diagnostic::Theme,
+    io::{memory::InMemoryFileSystem, FileSystemWriter},
+    parse::Parsed,
+    requirement::Requirement as ConfigRequirement,
+    type_::ModuleInterface,
+    uid::UniqueIdGenerator,
+    warning::{VectorWarningEmitterIO, WarningEmitter},
+};
+
+/// A problem found in a package, as `gleam` would report it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub title: String,
+    /// The file the problem was found in, if it is in one.
+    pub path: Option<Utf8PathBuf>,
+    /// The whole diagnostic as it would be printed, without colour.
+    pub message: String,
+}
+
+impl Diagnostic {
+    fn new(diagnostic: crate::diagnostic::Diagnostic) -> Self {
+        let mut buffer = Buffer::no_color();
+        diagnostic.write(&mut buffer, Theme::default());
+        Self {
+            message: String::from_utf8(buffer.into_inner())
+                .expect("Diagnostic printing produced invalid utf8"),
+            title: diagnostic.title,
+            path: diagnostic.location.map(|location| location.path),
+        }
+    }
+}
+
+/// The error returned when a package cannot be parsed, analysed, or have its
+/// dependencies resolved, made of one diagnostic for each problem found.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Error {
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl Error {
+    fn new(error: crate::Error) -> Self {
+        Self {
+            diagnostics: error
+                .to_diagnostics()
+                .into_iter()
+                .map(Diagnostic::new)
+                .collect(),
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for diagnostic in &self.diagnostics {
+            writeln!(f, "{}", diagnostic.message)?;
+        }
+        Ok(())
+    }
+}
+
+impl StdError for Error {}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// A parsed Gleam module.
+#[derive(Debug)]
+pub struct ParsedModule {
+    parsed: Parsed,
+}
+
+impl ParsedModule {
+    /// The names of the modules imported, in the order they are imported.
+    pub fn imports(&self) -> Vec<String> {
+        self.parsed
+            .module
+            .definitions
+            .iter()
+            .filter_map(|definition| match &definition.definition {
+                AstDefinition::Import(import) => Some(import.module.to_string()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// The functions, types, and constants defined in the module, in the
+    /// order they are defined.
+    pub fn definitions(&self) -> Vec<Definition> {
+        self.parsed
+            .module
+            .definitions
+            .iter()
+            .filter_map(|definition| {
+                let (kind, name, publicity) = match &definition.definition {
+                    AstDefinition::Function(function) => {
+                        (DefinitionKind::Function, &function.name, function.publicity)
+                    }
+                    AstDefinition::CustomType(type_) => {
+                        (DefinitionKind::Type, &type_.name, type_.publicity)
+                    }
+                    AstDefinition::TypeAlias(alias) => {
+                        (DefinitionKind::TypeAlias, &alias.alias, alias.publicity)
+                    }
+                    AstDefinition::ModuleConstant(constant) => {
+                        (DefinitionKind::Constant, &constant.name, constant.publicity)
+                    }
+                    AstDefinition::Import(_) => return None,
+                };
+                Some(Definition {
+                    name: name.to_string(),
+                    kind,
+                    public: publicity.is_public(),
+                })
+            })
+            .collect()
+    }
+}
+
+/// A function, type, or constant defined in a module.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Definition {
+    pub name: String,
+    pub kind: DefinitionKind,
+    pub public: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum DefinitionKind {
+    Function,
+    Type,
+    TypeAlias,
+    Constant,
+}
+
+/// Parses the source of a Gleam module. The path is used in error messages.
+pub fn parse_module(path: &Utf8Path, src: &str) -> Result<ParsedModule> {
+    crate::parse::parse_module(src)
+        .map(|parsed| ParsedModule { parsed })
+        .map_err(|error| {
+            Error::new(crate::Error::Parse {
+                path: path.to_path_buf(),
+                src: src.into(),
+                error,
+            })
+        })
+}
+
+/// Formats the source of a Gleam module, as `gleam format` would. The path is
+/// used in error messages.
+pub fn format_source(path: &Utf8Path, src: &str) -> Result<String> {
+    let mut formatted = String::new();
+    crate::format::pretty(&mut formatted, &src.into(), path).map_err(Error::new)?;
+    Ok(formatted)
+}
+
+/// The configuration of a package, read from its `gleam.toml`.
+#[derive(Debug, Clone)]
+pub struct Config {
+    config: PackageConfig,
+}
+
+impl Config {
+    /// Parses the contents of a `gleam.toml` file. The path is used in error
+    /// messages.
+    pub fn parse(path: &Utf8Path, toml: &str) -> Result<Self> {
+        PackageConfig::parse(toml, path)
+            .map(|config| Self { config })
+            .map_err(Error::new)
+    }
+
+    pub fn name(&self) -> &str {
+        &self.config.name
+    }
+}
+
+/// The platform modules are type checked for, which decides the externals
+/// they can use.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Target {
+    Erlang,
+    JavaScript,
+}
+
+/// The type checked modules of a package, along with any warnings that were
+/// emitted while analysing them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AnalysedPackage {
+    pub modules: Vec<AnalysedModule>,
+    pub warnings: Vec<Diagnostic>,
+}
+
+/// A type checked module, in the order modules were analysed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AnalysedModule {
+    pub name: String,
+    /// The path of the module's source file.
+    pub path: Utf8PathBuf,
+    /// Whether the module is in the `test` directory of its package.
+    pub test: bool,
+    /// The names of the modules it imports.
+    pub imports: Vec<String>,
+}
+
+/// Type checks packages without generating any code.
+///
+/// The modules of each package analysed are remembered so that they can be
+/// imported by the packages analysed after it, so dependencies must be
+/// analysed before the packages that depend on them.
+#[derive(Debug)]
+pub struct PackageAnalyser {
+    target: Target,
+    ids: UniqueIdGenerator,
+    importable_modules: im::HashMap<EcoString, ModuleInterface>,
+    defined_modules: im::HashMap<EcoString, Utf8PathBuf>,
+}
+
+impl PackageAnalyser {
+    pub fn new(target: Target) -> Self {
+        Self {
+            target,
+            ids: UniqueIdGenerator::new(),
+            importable_modules: im::HashMap::new(),
+            defined_modules: im::HashMap::new(),
+        }
+    }
+
+    /// Type checks the package with the given config, located at `root`.
+    ///
+    /// `files` holds the contents of the package's files by their path
+    /// within `root`: the Gleam modules of its `src` and `test` directories,
+    /// and the Erlang and JavaScript modules their externals refer to.
+    pub fn analyse(
+        &mut self,
+        config: &Config,
+        root: &Utf8Path,
+        files: &HashMap<Utf8PathBuf, String>,
+    ) -> Result<AnalysedPackage> {
+        let io = InMemoryFileSystem::new();
+        for (path, contents) in files {
+            io.write(&root.join(path), contents).map_err(Error::new)?;
+        }
+        let target = match self.target {
+            Target::Erlang => TargetCodegenConfiguration::Erlang { app_file: None },
+            Target::JavaScript => TargetCodegenConfiguration::JavaScript {
+                emit_typescript_definitions: false,
+                prelude_location: Utf8PathBuf::from("../prelude.mjs"),
+                platform_location: None,
+            },
+        };
+        let out = root.join("build");
+        let lib = out.join("lib");
+        let mut compiler = PackageCompiler::new(
+            &config.config,
+            Mode::Dev,
+            root,
+            &out,
+            &lib,
+            &target,
+            self.ids.clone(),
+            io,
+        );
+        compiler.write_metadata = false;
+        compiler.perform_codegen = false;
+        compiler.write_entrypoint = false;
+        compiler.copy_native_files = false;
+        compiler.target_support = TargetSupport::NotEnforced;
+
+        let warnings = Arc::new(VectorWarningEmitterIO::new());
+        let modules = compiler
+            .compile(
+                &WarningEmitter::new(warnings.clone()),
+                &mut self.importable_modules,
+                &mut self.defined_modules,
+                &mut StaleTracker::default(),
+                &mut HashSet::new(),
+                &NullTelemetry,
+            )
+            .into_result()
+            .map_err(Error::new)?;
+
+        Ok(AnalysedPackage {
+            modules: modules
+                .into_iter()
+                .map(|module| AnalysedModule {
+                    test: module.is_test(),
+                    imports: (module.dependencies.iter())
+                        .map(|(name, _)| name.to_string())
+                        .collect(),
+                    name: module.name.to_string(),
+                    path: module.input_path,
+                })
+                .collect(),
+            warnings: (warnings.take().iter())
+                .map(|warning| Diagnostic::new(warning.to_diagnostic()))
+                .collect(),
+        })
+    }
+}
+
+/// The releases of a package, as known to a package source.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Package {
+    pub name: String,
+    pub releases: Vec<Release>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Release {
+    pub version: Version,
+    /// The packages the release depends on, by name.
+    pub requirements: HashMap<String, Requirement>,
+    /// Whether the release is retired, so it is only picked if it is locked.
+    pub retired: bool,
+}
+
+/// A requirement of a release on another package.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Requirement {
+    pub range: Range,
+    /// Whether the package is only needed if something else requires it.
+    pub optional: bool,
+    /// The URL of the repository the package is in, if not Hex.
+    pub repository: Option<String>,
+}
+
+impl Package {
+    fn into_hex(self) -> hexpm::Package {
+        hexpm::Package {
+            name: self.name,
+            repository: "hexpm".into(),
+            releases: (self.releases.into_iter())
+                .map(|release| hexpm::Release {
+                    version: release.version,
+                    requirements: (release.requirements.into_iter())
+                        .map(|(name, requirement)| {
+                            let dependency = hexpm::Dependency {
+                                requirement: requirement.range,
+                                optional: requirement.optional,
+                                app: None,
+                                repository: requirement.repository,
+                            };
+                            (name, dependency)
+                        })
+                        .collect(),
+                    retirement_status: release.retired.then(|| hexpm::RetirementStatus {
+                        reason: hexpm::RetirementReason::Other,
+                        message: String::new(),
+                    }),
+                    outer_checksum: vec![],
+                    meta: (),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Somewhere information about packages can be looked up, such as the Hex
+/// API or an internal artifact store.
+pub trait PackageSource {
+    /// The releases of the package with the given name and the requirements
+    /// of each.
+    fn package(&self, name: &str) -> std::result::Result<Package, Box<dyn StdError>>;
+}
+
+struct SourceFetcher<S>(S);
+
+impl<S: PackageSource> PackageFetcher for SourceFetcher<S> {
+    fn get_dependencies(
+        &self,
+        package: &str,
+    ) -> std::result::Result<hexpm::Package, Box<dyn StdError>> {
+        self.0.package(package).map(Package::into_hex)
+    }
+}
+
+/// The sources packages are looked up from while resolving versions, one for
+/// each kind of source a dependency's repository can name.
+#[derive(Debug, Default)]
+pub struct PackageSources {
+    fetchers: PackageFetchers,
+}
+
+impl PackageSources {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Looks packages published to Hex up from the given source.
+    pub fn hex(&mut self, source: impl PackageSource + 'static) {
+        self.register(SourceKind::Hex, source);
+    }
+
+    /// Looks packages of the given Hex organisation up from the given source.
+    pub fn hex_organization(&mut self, organization: &str, source: impl PackageSource + 'static) {
+        self.register(SourceKind::HexOrganization(organization.into()), source);
+    }
+
+    /// Looks packages whose dependencies have a repository URL with the given
+    /// scheme up from the given source, such as `artifacts` for
+    /// `artifacts://packages.example.com`.
+    pub fn custom(&mut self, scheme: &str, source: impl PackageSource + 'static) {
+        self.register(SourceKind::Custom(scheme.into()), source);
+    }
+
+    fn register(&mut self, kind: SourceKind, source: impl PackageSource + 'static) {
+        let _ = self
+            .fetchers
+            .register(kind, Box::new(SourceFetcher(source)));
+    }
+}
+
+/// The versions picked for the dependencies of a package.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Resolution {
+    /// The version picked for each package, not including the root package.
+    pub versions: HashMap<String, Version>,
+    /// The packages placing a requirement on each package picked, along with
+    /// the requirement they place on it.
+    pub required_by: HashMap<String, Vec<(String, Range)>>,
+    /// Warnings about the versions picked, such as a retired version being
+    /// kept because it is locked.
+    pub warnings: Vec<String>,
+}
+
+/// Picks a version of each of the package's dependencies, including its
+/// development dependencies, that satisfies all the requirements.
+///
+/// Information about Hex packages is looked up from the given source. Path
+/// and git dependencies are not downloaded, the caller must give their
+/// package information in `provided`. Versions in `locked` are kept if
+/// possible.
+pub fn resolve_dependencies(
+    source: impl PackageSource + 'static,
+    config: &Config,
+    provided: HashMap<String, Package>,
+    locked: &HashMap<String, Version>,
+) -> Result<HashMap<String, Version>> {
+    let mut sources = PackageSources::new();
+    sources.hex(source);
+    resolve_dependencies_with_sources(sources, config, provided, locked)
+        .map(|resolution| resolution.versions)
+}
+
+/// Picks versions of the package's dependencies like `resolve_dependencies`,
+/// looking up each package from the source for its kind. This lets packages
+/// be fetched from places other than Hex, such as an internal artifact store.
+///
+/// Dependencies of the package on packages of a Hex organisation are looked
+/// up from the source given for that organisation.
+///
+/// The whole resolution is returned, including where each requirement came
+/// from and any warnings about the versions picked.
+pub fn resolve_dependencies_with_sources(
+    sources: PackageSources,
+    config: &Config,
+    provided: HashMap<String, Package>,
+    locked: &HashMap<String, Version>,
+) -> Result<Resolution> {
+    let config = &config.config;
+    let provided: HashMap<EcoString, hexpm::Package> = (provided.into_iter())
+        .map(|(name, package)| (name.into(), package.into_hex()))
+        .collect();
+    let locked: HashMap<EcoString, Version> = (locked.iter())
+        .map(|(name, version)| (name.into(), version.clone()))
+        .collect();
+    let mut fetchers = sources.fetchers;
+    let mut requirements = HashMap::new();
+    for (name, requirement) in config.all_dependencies().map_err(Error::new)? {
+        let range = match requirement {
+            ConfigRequirement::Hex {
+                version,
+                organization,
+                ..
+            } => {
+                if let Some(organization) = organization {
+                    let kind = SourceKind::HexOrganization(organization);
+                    let _ = fetchers.set_source(name.clone(), kind);
+                }
+                version
+            }
+            ConfigRequirement::Path { .. } | ConfigRequirement::Git { .. } => {
+                let release = provided
+                    .get(&name)
+                    .and_then(|package| package.releases.first())
+                    .ok_or_else(|| {
+                        Error::new(crate::Error::DependencyResolutionFailed(format!(
+                            "No package information was provided for `{name}`"
+                        )))
+                    })?;
+                Range::new(format!("== {}", release.version))
+            }
+        };
+        let _ = requirements.insert(name, range);
+    }
+
+    let resolution = dependency::resolve_versions(
+        fetchers,
+        provided,
+        config.name.clone(),
+        requirements.into_iter(),
+        &locked,
+        dependency::ResolveOptions {
+            strategy: config.resolution_strategy,
+            ..dependency::ResolveOptions::default()
+        },
+    )
+    .map_err(|error| Error::new(crate::Error::dependency_resolution_failed(error)))?;
+
+    Ok(Resolution {
+        versions: resolution.packages,
+        required_by: (resolution.requirements.into_iter())
+            .map(|(package, required_by)| {
+                let required_by = (required_by.into_iter())
+                    .map(|required| (required.package, required.requirement))
+                    .collect();
+                (package, required_by)
+            })
+            .collect(),
+        warnings: resolution.warnings.iter().map(warning_message).collect(),
+    })
+}
+
+fn warning_message(warning: &ResolutionWarning) -> String {
+    match warning {
+        ResolutionWarning::RetiredVersionLocked {
+            package, version, ..
+        } => format!("{package} {version} is retired but was kept as it is locked"),
+        ResolutionWarning::PreReleaseSelected { package, version } => {
+            format!("{package} {version} is a pre-release")
+        }
+    }
+}
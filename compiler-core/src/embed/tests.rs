@@ -0,0 +1,212 @@
+use super::{
+    format_source, parse_module, resolve_dependencies, resolve_dependencies_with_sources, Config,
+    Definition, DefinitionKind, Package, PackageAnalyser, PackageSource, PackageSources, Release,
+    Target,
+};
+use std::{collections::HashMap, error::Error as StdError};
+
+use camino::{Utf8Path, Utf8PathBuf};
+use hexpm::version::{Range, Version};
+
+#[test]
+fn parse_module_returns_definitions() {
+    let parsed = parse_module(
+        Utf8Path::new("src/app.gleam"),
+        "import wibble\npub fn main() {}",
+    )
+    .expect("valid module");
+    assert_eq!(parsed.imports(), vec!["wibble"]);
+    assert_eq!(
+        parsed.definitions(),
+        vec![Definition {
+            name: "main".into(),
+            kind: DefinitionKind::Function,
+            public: true,
+        }]
+    );
+}
+
+#[test]
+fn parse_module_error_has_path() {
+    let error =
+        parse_module(Utf8Path::new("src/app.gleam"), "pub fn main(").expect_err("invalid module");
+    let [diagnostic] = error.diagnostics.as_slice() else {
+        panic!("Expected one diagnostic, got {error:?}")
+    };
+    assert_eq!(diagnostic.path, Some(Utf8PathBuf::from("src/app.gleam")));
+    assert!(diagnostic.message.contains("src/app.gleam:1:12"));
+}
+
+#[test]
+fn format_source_formats() {
+    assert_eq!(
+        format_source(Utf8Path::new("src/app.gleam"), "pub fn main() { 1 }"),
+        Ok("pub fn main() {\n  1\n}\n".into())
+    );
+}
+
+fn config(toml: &str) -> Config {
+    Config::parse(Utf8Path::new("gleam.toml"), toml).expect("valid config")
+}
+
+fn files(files: &[(&str, &str)]) -> HashMap<Utf8PathBuf, String> {
+    files
+        .iter()
+        .map(|(path, contents)| ((*path).into(), (*contents).into()))
+        .collect()
+}
+
+#[test]
+fn config_parse_error_has_path() {
+    let error = Config::parse(Utf8Path::new("gleam.toml"), "name = ").expect_err("invalid config");
+    let [diagnostic] = error.diagnostics.as_slice() else {
+        panic!("Expected one diagnostic, got {error:?}")
+    };
+    assert!(diagnostic.message.contains("gleam.toml"));
+}
+
+#[test]
+fn analyse_packages_in_dependency_order() {
+    let mut analyser = PackageAnalyser::new(Target::Erlang);
+    let wibble = config("name = \"wibble\"\nversion = \"1.0.0\"\n");
+    let analysed = analyser
+        .analyse(
+            &wibble,
+            Utf8Path::new("/wibble"),
+            &files(&[("src/wibble.gleam", "pub fn id(x) { x }")]),
+        )
+        .expect("analyse dependency");
+    assert_eq!(analysed.modules.len(), 1);
+
+    let app = config(
+        "name = \"app\"\nversion = \"1.0.0\"\n\n[dependencies]\nwibble = { path = \"../wibble\" }\n",
+    );
+    let analysed = analyser
+        .analyse(
+            &app,
+            Utf8Path::new("/app"),
+            &files(&[
+                (
+                    "src/app.gleam",
+                    "import wibble\nimport gleam/list\npub fn main() { wibble.id(1) }",
+                ),
+                ("src/gleam/list.gleam", ""),
+            ]),
+        )
+        .expect("analyse root package");
+    let names = analysed
+        .modules
+        .iter()
+        .map(|module| module.name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(names, vec!["gleam/list", "app"]);
+    assert_eq!(analysed.modules[1].imports, vec!["wibble", "gleam/list"]);
+    // The unused `gleam/list` import is reported rather than printed
+    let [warning] = analysed.warnings.as_slice() else {
+        panic!("Expected one warning, got {:?}", analysed.warnings)
+    };
+    assert_eq!(warning.path, Some(Utf8PathBuf::from("/app/src/app.gleam")));
+}
+
+struct Remote {
+    packages: HashMap<String, Package>,
+}
+
+impl PackageSource for Remote {
+    fn package(&self, package: &str) -> Result<Package, Box<dyn StdError>> {
+        self.packages
+            .get(package)
+            .cloned()
+            .ok_or(Box::new(hexpm::ApiError::NotFound))
+    }
+}
+
+fn hex_package(name: &str, versions: &[Version]) -> Package {
+    Package {
+        name: name.into(),
+        releases: versions
+            .iter()
+            .map(|version| Release {
+                version: version.clone(),
+                requirements: [].into(),
+                retired: false,
+            })
+            .collect(),
+    }
+}
+
+#[test]
+fn resolve_dependencies_with_provided_packages() {
+    let remote = Remote {
+        packages: [(
+            "gleam_stdlib".into(),
+            hex_package(
+                "gleam_stdlib",
+                &[Version::new(0, 33, 0), Version::new(0, 34, 1)],
+            ),
+        )]
+        .into(),
+    };
+    let config = config(
+        "name = \"app\"\nversion = \"1.0.0\"\n\n[dependencies]\ngleam_stdlib = \"~> 0.33\"\nwibble = { path = \"../wibble\" }\n",
+    );
+    let provided = [(
+        "wibble".into(),
+        hex_package("wibble", &[Version::new(2, 0, 0)]),
+    )]
+    .into();
+
+    let versions =
+        resolve_dependencies(remote, &config, provided, &HashMap::new()).expect("resolve versions");
+    assert_eq!(
+        versions,
+        [
+            ("gleam_stdlib".into(), Version::new(0, 34, 1)),
+            ("wibble".into(), Version::new(2, 0, 0)),
+        ]
+        .into()
+    );
+}
+
+#[test]
+fn resolve_dependencies_requires_provided_packages() {
+    let remote = Remote {
+        packages: HashMap::new(),
+    };
+    let config = config(
+        "name = \"app\"\nversion = \"1.0.0\"\n\n[dependencies]\nwibble = { path = \"../wibble\" }\n",
+    );
+    let error = resolve_dependencies(remote, &config, HashMap::new(), &HashMap::new())
+        .expect_err("wibble was not provided");
+    assert!(error
+        .to_string()
+        .contains("No package information was provided for `wibble`"));
+}
+
+#[test]
+fn resolve_dependencies_with_sources_reports_requirements() {
+    let mut sources = PackageSources::new();
+    sources.hex(Remote {
+        packages: [(
+            "gleam_stdlib".into(),
+            hex_package("gleam_stdlib", &[Version::new(0, 34, 1)]),
+        )]
+        .into(),
+    });
+    let config = config(
+        "name = \"app\"\nversion = \"1.0.0\"\n\n[dependencies]\ngleam_stdlib = \"~> 0.33\"\n",
+    );
+
+    let resolution =
+        resolve_dependencies_with_sources(sources, &config, HashMap::new(), &HashMap::new())
+            .expect("resolve versions");
+    assert_eq!(
+        resolution.versions,
+        [("gleam_stdlib".into(), Version::new(0, 34, 1))].into()
+    );
+    assert_eq!(
+        resolution.required_by["gleam_stdlib"],
+        vec![("app".into(), Range::new("~> 0.33".into()))]
+    );
+    assert!(resolution.warnings.is_empty());
+}
@@ -2,6 +2,8 @@
 // functions with a load of arguments. See the JavaScript code generator and the
 // formatter for examples.
 
+pub mod dialyzer;
+pub mod erlfmt;
 mod pattern;
 #[cfg(test)]
 mod tests;
@@ -13,6 +15,7 @@ use crate::{
     ast::{CustomType, Function, Import, ModuleConstant, TypeAlias, *},
     docvec,
     line_numbers::LineNumbers,
+    parse::extra::{Comment, ModuleExtra},
     pretty::*,
     type_::{
         ModuleValueConstructor, PatternConstructor, Type, TypeVar, ValueConstructor,
@@ -20,6 +23,7 @@ use crate::{
     },
     Result,
 };
+use camino::Utf8Path;
 use ecow::EcoString;
 use heck::ToSnakeCase;
 use im::HashSet;
@@ -33,6 +37,35 @@ use vec1::Vec1;
 const INDENT: isize = 4;
 const MAX_COLUMNS: isize = 80;
 
+/// The module that formats the errors of programs run by `gleam run` and
+/// `gleam test`, compiled with the entrypoint module of the root package.
+pub const CRASH_MODULE: &str = include_str!("../templates/gleam@@crash.erl");
+
+/// The module that encodes values for tooling, as described in the `inspect`
+/// module, compiled with the entrypoint module of the root package.
+pub const INSPECT_MODULE: &str = include_str!("../templates/gleam@@inspect.erl");
+
+/// How the Erlang of a module is laid out.
+#[derive(Debug, Clone, Copy)]
+pub enum Style<'a> {
+    /// Compact Erlang, which is only meant to be compiled.
+    Compact,
+    /// Erlang meant to be read by people, such as when auditing it or
+    /// migrating away from Gleam. The comments of the Gleam source are kept,
+    /// `case` clauses are laid out as is usual in Erlang, and the results of
+    /// the steps of a pipeline are named after the functions called.
+    Readable {
+        /// The comments of the Gleam source.
+        extra: &'a ModuleExtra,
+    },
+}
+
+impl Style<'_> {
+    fn is_readable(&self) -> bool {
+        matches!(self, Self::Readable { .. })
+    }
+}
+
 fn module_name_to_erlang(module: &str) -> Document<'_> {
     Document::String(module.replace('/', "@"))
 }
@@ -46,39 +79,101 @@ struct Env<'a> {
     module: &'a str,
     function: &'a str,
     line_numbers: &'a LineNumbers,
+    src: &'a str,
     current_scope_vars: im::HashMap<String, usize>,
     erl_function_scope_vars: im::HashMap<String, usize>,
+    style: Style<'a>,
+    /// The position in the Gleam source before which comments have already
+    /// been written to readable Erlang.
+    comment_cursor: u32,
+    /// The Erlang variable holding the value being piped in readable Erlang,
+    /// which is named after the value rather than `_pipe`.
+    pipe_variable: Option<String>,
 }
 
 impl<'env> Env<'env> {
-    pub fn new(module: &'env str, function: &'env str, line_numbers: &'env LineNumbers) -> Self {
+    pub fn new(
+        module: &'env str,
+        function: &'env str,
+        line_numbers: &'env LineNumbers,
+        src: &'env str,
+        style: Style<'env>,
+    ) -> Self {
         let vars: im::HashMap<_, _> = std::iter::once(("_".into(), 0)).collect();
         Self {
             current_scope_vars: vars.clone(),
             erl_function_scope_vars: vars,
             line_numbers,
+            src,
             function,
             module,
+            style,
+            comment_cursor: 0,
+            pipe_variable: None,
         }
     }
 
     pub fn local_var_name<'a>(&mut self, name: &str) -> Document<'a> {
+        if name == PIPE_VARIABLE {
+            if let Some(variable) = &self.pipe_variable {
+                return Document::String(variable.clone());
+            }
+        }
+        Document::String(self.erlang_variable(name))
+    }
+
+    fn erlang_variable(&mut self, name: &str) -> String {
         match self.current_scope_vars.get(name) {
             None => {
                 let _ = self.current_scope_vars.insert(name.to_string(), 0);
                 let _ = self.erl_function_scope_vars.insert(name.to_string(), 0);
-                Document::String(variable_name(name))
+                variable_name(name)
             }
-            Some(0) => Document::String(variable_name(name)),
+            Some(0) => variable_name(name),
             Some(n) => {
                 use std::fmt::Write;
                 let mut name = variable_name(name);
                 write!(name, "@{n}").expect("pushing number suffix to name");
-                Document::String(name)
+                name
             }
         }
     }
 
+    fn next_erlang_variable(&mut self, name: &str) -> String {
+        let next = self.erl_function_scope_vars.get(name).map_or(0, |i| i + 1);
+        let _ = self.erl_function_scope_vars.insert(name.to_string(), next);
+        let _ = self.current_scope_vars.insert(name.to_string(), next);
+        self.erlang_variable(name)
+    }
+
+    /// The comments of the Gleam source before the position that have not
+    /// yet been written, as Erlang comments each followed by a line. Only
+    /// readable Erlang has comments.
+    fn comments_before<'a>(&mut self, position: u32, prefix: &'a str) -> Vec<Document<'a>> {
+        let Style::Readable { extra } = self.style else {
+            return vec![];
+        };
+        let cursor = self.comment_cursor;
+        let documents = extra
+            .comments
+            .iter()
+            .filter(|span| span.start >= cursor && span.start < position)
+            .map(|span| {
+                let comment = self
+                    .src
+                    .get(span.start as usize..span.end as usize)
+                    .unwrap_or_default();
+                docvec![
+                    prefix,
+                    Document::String(comment.trim_end().to_string()),
+                    line()
+                ]
+            })
+            .collect();
+        self.comment_cursor = self.comment_cursor.max(position);
+        documents
+    }
+
     pub fn next_local_var_name<'a>(&mut self, name: &str) -> Document<'a> {
         let next = self.erl_function_scope_vars.get(name).map_or(0, |i| i + 1);
         let _ = self.erl_function_scope_vars.insert(name.to_string(), next);
@@ -146,13 +241,25 @@ pub fn record_definition(name: &str, fields: &[(&str, Arc<Type>)]) -> String {
     .to_pretty_string(MAX_COLUMNS)
 }
 
-pub fn module<'a>(module: &'a TypedModule, line_numbers: &'a LineNumbers) -> Result<String> {
-    Ok(module_document(module, line_numbers)?.to_pretty_string(MAX_COLUMNS))
+/// Generates the Erlang for a module. `path` is the path of the module's
+/// source file relative to the root of its package, which is used in `-file`
+/// attributes so that stack traces refer to the Gleam source.
+pub fn module<'a>(
+    module: &'a TypedModule,
+    line_numbers: &'a LineNumbers,
+    src: &'a str,
+    path: &'a Utf8Path,
+    style: Style<'a>,
+) -> Result<String> {
+    Ok(module_document(module, line_numbers, src, path, style)?.to_pretty_string(MAX_COLUMNS))
 }
 
 fn module_document<'a>(
     module: &'a TypedModule,
     line_numbers: &'a LineNumbers,
+    src: &'a str,
+    path: &'a Utf8Path,
+    style: Style<'a>,
 ) -> Result<Document<'a>> {
     let mut exports = vec![];
     let mut type_defs = vec![];
@@ -164,6 +271,18 @@ fn module_document<'a>(
         .append(").")
         .append(line());
 
+    // Readable Erlang keeps the module's documentation as a comment.
+    let header = match style {
+        Style::Readable { extra } => {
+            let documentation = extra.module_comments.iter().map(|span| {
+                let text = Comment::from((span, src)).content.trim_end();
+                docvec!["%%%", Document::String(text.to_string()), line()]
+            });
+            concat(documentation).append(header)
+        }
+        Style::Compact => header,
+    };
+
     // We need to know which private functions are referenced in importable
     // constants so that we can export them anyway in the generated Erlang.
     // This is because otherwise when the constant is used in another module it
@@ -207,23 +326,69 @@ fn module_document<'a>(
             .append(lines(2)),
     };
 
+    // The OTP behaviours the module's functions are callbacks of, so that
+    // dialyzer and other Erlang tooling can check the callbacks.
+    let behaviours = module
+        .definitions
+        .iter()
+        .filter_map(|definition| match definition {
+            Definition::Function(Function {
+                behaviour: Some(behaviour),
+                implementations,
+                ..
+            }) if implementations.supports(Target::Erlang) => Some(behaviour),
+            _ => None,
+        })
+        .sorted()
+        .dedup()
+        .map(|behaviour| docvec!["-behaviour(", atom(behaviour), ").", line()])
+        .collect_vec();
+    let behaviours = if behaviours.is_empty() {
+        nil()
+    } else {
+        concat(behaviours).append(line())
+    };
+
     let type_defs = if type_defs.is_empty() {
         nil()
     } else {
         join(type_defs, lines(2)).append(lines(2))
     };
 
+    // The positions where the definitions end, so the comments before a
+    // function can be told apart from those of the definition before it.
+    let definition_ends = module
+        .definitions
+        .iter()
+        .map(|definition| match definition {
+            Definition::Function(function) => function.end_position,
+            Definition::CustomType(custom_type) => custom_type.end_position,
+            Definition::TypeAlias(_) | Definition::Import(_) | Definition::ModuleConstant(_) => {
+                definition.location().end
+            }
+        })
+        .sorted()
+        .collect_vec();
+
     let statements = join(
-        module
-            .definitions
-            .iter()
-            .flat_map(|s| module_statement(s, &module.name, line_numbers)),
+        module.definitions.iter().flat_map(|s| {
+            module_statement(
+                s,
+                &module.name,
+                line_numbers,
+                src,
+                path,
+                style,
+                &definition_ends,
+            )
+        }),
         lines(2),
     );
 
     Ok(header
         .append("-compile([no_auto_import, nowarn_unused_vars, nowarn_unused_function, nowarn_nomatch]).")
         .append(lines(2))
+        .append(behaviours)
         .append(exports)
         .append(type_defs)
         .append(statements)
@@ -244,8 +409,12 @@ fn register_imports(
             name,
             arguments: args,
             implementations,
+            behaviour,
             ..
-        }) if publicity.is_importable() || overridden_publicity.contains(name) => {
+        }) if publicity.is_importable()
+            || overridden_publicity.contains(name)
+            || behaviour.is_some() =>
+        {
             // If the function isn't for this target then don't attempt to export it
             if implementations.supports(Target::Erlang) {
                 exports.push(atom_string(name.to_string()).append("/").append(args.len()))
@@ -343,6 +512,10 @@ fn module_statement<'a>(
     statement: &'a TypedDefinition,
     module: &'a str,
     line_numbers: &'a LineNumbers,
+    src: &'a str,
+    path: &'a Utf8Path,
+    style: Style<'a>,
+    definition_ends: &[u32],
 ) -> Option<Document<'a>> {
     match statement {
         Definition::TypeAlias(TypeAlias { .. })
@@ -350,7 +523,15 @@ fn module_statement<'a>(
         | Definition::Import(Import { .. })
         | Definition::ModuleConstant(ModuleConstant { .. }) => None,
 
-        Definition::Function(function) => module_function(function, module, line_numbers),
+        Definition::Function(function) => module_function(
+            function,
+            module,
+            line_numbers,
+            src,
+            path,
+            style,
+            definition_ends,
+        ),
     }
 }
 
@@ -358,10 +539,18 @@ fn module_function<'a>(
     function: &'a TypedFunction,
     module: &'a str,
     line_numbers: &'a LineNumbers,
+    src: &'a str,
+    path: &'a Utf8Path,
+    style: Style<'a>,
+    definition_ends: &[u32],
 ) -> Option<Document<'a>> {
     // Private external functions don't need to render anything, the underlying
-    // Erlang implementation is used directly at the call site.
-    if function.external_erlang.is_some() && function.publicity.is_private() {
+    // Erlang implementation is used directly at the call site. Callbacks of a
+    // behaviour are always rendered as the behaviour calls them by name.
+    if function.external_erlang.is_some()
+        && function.publicity.is_private()
+        && function.behaviour.is_none()
+    {
         return None;
     }
 
@@ -371,7 +560,25 @@ fn module_function<'a>(
         return None;
     }
 
-    let mut env = Env::new(module, &function.name, line_numbers);
+    let mut env = Env::new(module, &function.name, line_numbers, src, style);
+
+    // The comments between the previous definition and this function, and its
+    // documentation, are written before it in readable Erlang.
+    env.comment_cursor = definition_ends
+        .iter()
+        .copied()
+        .filter(|end| *end <= function.location.start)
+        .max()
+        .unwrap_or(0);
+    let mut comments = env.comments_before(function.location.start, "%%");
+    if let (true, Some(documentation)) = (style.is_readable(), &function.documentation) {
+        comments.extend(
+            documentation
+                .lines()
+                .map(|text| docvec!["%%", Document::String(text.trim_end().to_string()), line()]),
+        );
+    }
+    env.comment_cursor = function.location.end;
     let var_usages = collect_type_var_usages(
         HashMap::new(),
         std::iter::once(&function.return_type).chain(function.arguments.iter().map(|a| &a.type_)),
@@ -391,7 +598,25 @@ fn module_function<'a>(
         .map(|(module, function)| docvec![atom(module), ":", atom(function), arguments.clone()])
         .unwrap_or_else(|| statement_sequence(&function.body, &mut env));
 
-    let doc = spec
+    // The attribute sets the line of the function head to that of the Gleam
+    // function. An attribute cannot be written inside the function, so empty
+    // lines are added before each statement of the body to keep it on the line
+    // of the Gleam statement, so the lines in stack traces are those of the
+    // code that was running wherever the Erlang is not longer than the Gleam.
+    let function_line = line_numbers.line_number(function.location.start);
+    let file = docvec![
+        "-file(\"",
+        path.as_str(),
+        "\", ",
+        function_line,
+        ").",
+        number_next_line(function_line),
+        line()
+    ];
+
+    let doc = concat(comments)
+        .append(spec)
+        .append(file)
         .append(atom_string(function.name.to_string()))
         .append(arguments)
         .append(" ->")
@@ -459,7 +684,7 @@ fn atom(value: &str) -> Document<'_> {
     }
 }
 
-fn escape_atom_string(value: String) -> String {
+pub(crate) fn escape_atom_string(value: String) -> String {
     if is_erlang_reserved_word(&value) {
         // Escape because of keyword collision
         format!("'{value}'")
@@ -501,6 +726,17 @@ fn string(value: &str) -> Document<'_> {
     string_inner(value).surround("<<\"", "\"/utf8>>")
 }
 
+/// A string of text such as source code, rather than the contents of a Gleam
+/// string literal, which has its escape sequences written as they are in Gleam.
+fn text_string<'a>(value: &str) -> Document<'a> {
+    let escaped = value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r");
+    Document::String(escaped).surround("<<\"", "\"/utf8>>")
+}
+
 fn string_length_utf8_bytes(str: &EcoString) -> usize {
     convert_string_escape_chars(str).len()
 }
@@ -745,22 +981,53 @@ fn block<'a>(statements: &'a Vec1<TypedStatement>, env: &mut Env<'a>) -> Documen
 fn statement_sequence<'a>(statements: &'a [TypedStatement], env: &mut Env<'a>) -> Document<'a> {
     let count = statements.len();
     let mut documents = Vec::with_capacity(count * 3);
+    let mut has_comments = false;
     for (i, expression) in statements.iter().enumerate() {
+        let location = expression.location();
+        let comments = env.comments_before(location.start, "%");
+        has_comments = has_comments || !comments.is_empty();
+        if i > 0 {
+            // Each statement is kept on the line of the Gleam statement, see
+            // `module_function`.
+            let line = env.line_numbers.line_number(statement_start(expression));
+            documents.push(line_until(line.saturating_sub(comments.len() as u32)));
+        }
+        documents.extend(comments);
         documents.push(statement(expression, env).group());
+        env.comment_cursor = env.comment_cursor.max(location.end);
 
         if i + 1 < count {
             // This isn't the final expression so add the delimeters
             documents.push(",".to_doc());
-            documents.push(line());
         }
     }
-    if count == 1 {
+    if count == 1 && !has_comments {
         documents.to_doc()
     } else {
         documents.to_doc().force_break()
     }
 }
 
+/// Where the code of a statement starts, which for some expressions, such as
+/// record access, is before the start of their location.
+fn statement_start(statement: &TypedStatement) -> u32 {
+    match statement {
+        Statement::Expression(expression) => expression_start(expression),
+        Statement::Assignment(_) | Statement::Use(_) => statement.location().start,
+    }
+}
+
+fn expression_start(expression: &TypedExpr) -> u32 {
+    let start = expression.location().start;
+    match expression {
+        TypedExpr::RecordAccess { record: inner, .. }
+        | TypedExpr::TupleIndex { tuple: inner, .. }
+        | TypedExpr::Call { fun: inner, .. }
+        | TypedExpr::BinOp { left: inner, .. } => start.min(expression_start(inner)),
+        _ => start,
+    }
+}
+
 fn float_div<'a>(left: &'a TypedExpr, right: &'a TypedExpr, env: &mut Env<'a>) -> Document<'a> {
     if right.non_zero_compile_time_number() {
         return binop_exprs(left, "/", right, env);
@@ -861,7 +1128,13 @@ fn binop_documents<'a>(left: Document<'a>, op: &'static str, right: Document<'a>
         .append(right)
 }
 
-fn let_assert<'a>(value: &'a TypedExpr, pat: &'a TypedPattern, env: &mut Env<'a>) -> Document<'a> {
+fn let_assert<'a>(assignment: &'a TypedAssignment, env: &mut Env<'a>) -> Document<'a> {
+    let TypedAssignment {
+        location,
+        value,
+        pattern: pat,
+        ..
+    } = assignment;
     let mut vars: Vec<&str> = vec![];
     let body = maybe_block_expr(value, env);
     let (subject_var, subject_definition) = if value.is_var() {
@@ -873,6 +1146,13 @@ fn let_assert<'a>(value: &'a TypedExpr, pat: &'a TypedPattern, env: &mut Env<'a>
     };
     let check_pattern = pattern::to_doc_discarding_all(pat, &mut vars, env);
     let assign_pattern = pattern::to_doc(pat, &mut vars, env);
+    let expression = env
+        .src
+        .get(location.start as usize..location.end as usize)
+        .unwrap_or_default();
+    let value_type = crate::type_::pretty::Printer::new()
+        .print(&value.type_())
+        .to_pretty_string(isize::MAX);
     let clauses = docvec![
         check_pattern.clone(),
         " -> ",
@@ -887,7 +1167,15 @@ fn let_assert<'a>(value: &'a TypedExpr, pat: &'a TypedPattern, env: &mut Env<'a>
                 "let_assert",
                 &string("Assertion pattern match failed"),
                 pat.location(),
-                vec![("value", env.local_var_name(ASSERT_FAIL_VARIABLE))],
+                vec![
+                    ("value", env.local_var_name(ASSERT_FAIL_VARIABLE)),
+                    ("expression", text_string(expression)),
+                    ("value_type", text_string(&value_type)),
+                    ("start", location.start.to_doc()),
+                    ("'end'", location.end.to_doc()),
+                    ("pattern_start", pat.location().start.to_doc()),
+                    ("pattern_end", pat.location().end.to_doc()),
+                ],
                 env,
             )
             .nest(INDENT)
@@ -1084,6 +1372,7 @@ fn clause<'a>(clause: &'a TypedClause, env: &mut Env<'a>) -> Document<'a> {
     let mut then_doc = None;
     let initial_erlang_vars = env.erl_function_scope_vars.clone();
     let mut end_erlang_vars = im::HashMap::new();
+    let separator = clause_separator(env);
 
     let doc = join(
         std::iter::once(pat)
@@ -1105,13 +1394,20 @@ fn clause<'a>(clause: &'a TypedClause, env: &mut Env<'a>) -> Document<'a> {
                     end_erlang_vars = env.erl_function_scope_vars.clone();
                 }
 
+                // Readable Erlang puts short clause bodies on the same line
+                // as their patterns.
+                let body_break = if env.style.is_readable() && is_simple(then) {
+                    break_("", " ")
+                } else {
+                    line()
+                };
                 patterns_doc.append(
                     guard
                         .append(" ->")
-                        .append(line().append(then_doc.clone()).nest(INDENT).group()),
+                        .append(body_break.append(then_doc.clone()).nest(INDENT).group()),
                 )
             }),
-        ";".to_doc().append(lines(2)),
+        separator,
     );
 
     env.erl_function_scope_vars = end_erlang_vars;
@@ -1253,6 +1549,7 @@ fn clause_guard<'a>(guard: &'a TypedClauseGuard, env: &mut Env<'a>) -> Document<
 }
 
 fn clauses<'a>(cs: &'a [TypedClause], env: &mut Env<'a>) -> Document<'a> {
+    let separator = clause_separator(env);
     join(
         cs.iter().map(|c| {
             let vars = env.current_scope_vars.clone();
@@ -1260,10 +1557,43 @@ fn clauses<'a>(cs: &'a [TypedClause], env: &mut Env<'a>) -> Document<'a> {
             env.current_scope_vars = vars; // Reset the known variables now the clauses' scope has ended
             erl
         }),
-        ";".to_doc().append(lines(2)),
+        separator,
     )
 }
 
+/// Whether the expression is one that can be written on the same line as the
+/// pattern of a clause when it is short enough, rather than one that is
+/// always written over many lines such as a `case`.
+fn is_simple(expression: &TypedExpr) -> bool {
+    match expression {
+        TypedExpr::Int { .. }
+        | TypedExpr::Float { .. }
+        | TypedExpr::String { .. }
+        | TypedExpr::Var { .. }
+        | TypedExpr::List { .. }
+        | TypedExpr::Call { .. }
+        | TypedExpr::BinOp { .. }
+        | TypedExpr::RecordAccess { .. }
+        | TypedExpr::ModuleSelect { .. }
+        | TypedExpr::Tuple { .. }
+        | TypedExpr::TupleIndex { .. }
+        | TypedExpr::NegateBool { .. }
+        | TypedExpr::NegateInt { .. } => true,
+
+        _ => false,
+    }
+}
+
+/// Clauses are separated by an empty line, or by just a new line as is usual
+/// in readable Erlang.
+fn clause_separator<'a>(env: &Env<'_>) -> Document<'a> {
+    if env.style.is_readable() {
+        ";".to_doc().append(line())
+    } else {
+        ";".to_doc().append(lines(2))
+    }
+}
+
 fn case<'a>(subjects: &'a [TypedExpr], cs: &'a [TypedClause], env: &mut Env<'a>) -> Document<'a> {
     let subjects_doc = if subjects.len() == 1 {
         let subject = subjects
@@ -1634,6 +1964,10 @@ fn pipeline<'a>(
     finally: &'a TypedExpr,
     env: &mut Env<'a>,
 ) -> Document<'a> {
+    if env.style.is_readable() {
+        return readable_pipeline(assignments, finally, env);
+    }
+
     let mut documents = Vec::with_capacity((assignments.len() + 1) * 3);
 
     for a in assignments {
@@ -1646,10 +1980,59 @@ fn pipeline<'a>(
     documents.to_doc()
 }
 
+/// A pipeline in readable Erlang, where a variable at the start of the
+/// pipeline is used as it is rather than being copied, and the result of each
+/// step is named after the function called rather than `_pipe`.
+fn readable_pipeline<'a>(
+    assignments: &'a [Assignment<Arc<Type>, TypedExpr>],
+    finally: &'a TypedExpr,
+    env: &mut Env<'a>,
+) -> Document<'a> {
+    let outer_pipe_variable = env.pipe_variable.clone();
+    let mut documents = Vec::with_capacity((assignments.len() + 1) * 3);
+
+    for assignment in assignments {
+        let value = assignment.value.as_ref();
+        if let TypedExpr::Var {
+            name, constructor, ..
+        } = value
+        {
+            if constructor.is_local_variable() {
+                let variable = match env.local_var_name(name) {
+                    Document::String(variable) => variable,
+                    _ => env.erlang_variable(name),
+                };
+                env.pipe_variable = Some(variable);
+                continue;
+            }
+        }
+
+        let value_doc = maybe_block_expr(value, env).group();
+        let name = match value {
+            TypedExpr::Call { fun, .. } => match fun.as_ref() {
+                TypedExpr::ModuleSelect { label, .. } | TypedExpr::Var { name: label, .. } => {
+                    format!("_{label}")
+                }
+                _ => PIPE_VARIABLE.to_string(),
+            },
+            _ => PIPE_VARIABLE.to_string(),
+        };
+        let variable = env.next_erlang_variable(&name);
+        env.pipe_variable = Some(variable.clone());
+        documents.push(docvec![Document::String(variable), " = ", value_doc]);
+        documents.push(','.to_doc());
+        documents.push(line());
+    }
+
+    documents.push(expr(finally, env));
+    env.pipe_variable = outer_pipe_variable;
+    documents.to_doc()
+}
+
 fn assignment<'a>(assignment: &'a TypedAssignment, env: &mut Env<'a>) -> Document<'a> {
     match assignment.kind {
         AssignmentKind::Let => let_(&assignment.value, &assignment.pattern, env),
-        AssignmentKind::Assert { .. } => let_assert(&assignment.value, &assignment.pattern, env),
+        AssignmentKind::Assert { .. } => let_assert(assignment, env),
     }
 }
 
@@ -1704,7 +2087,7 @@ fn incrementing_args_list(arity: usize) -> String {
     Itertools::intersperse(arguments, ", ".into()).collect()
 }
 
-fn variable_name(name: &str) -> String {
+pub(crate) fn variable_name(name: &str) -> String {
     let mut chars = name.chars();
     let first_char = chars.next();
     let first_uppercased = first_char.into_iter().flat_map(char::to_uppercase);
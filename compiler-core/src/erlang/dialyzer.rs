@@ -0,0 +1,175 @@
+//! Reading the warnings of dialyzer, the Erlang static analyser run by
+//! `gleam dialyzer`, and finding the Gleam functions they are for.
+//!
+//! The generated Erlang has a `-file` attribute before each function giving
+//! the Gleam file and line the function starts on. Dialyzer reports warnings
+//! for the lines after one of these attributes as if they were in the Gleam
+//! file, counting on from the line of the function, so the lines it reports
+//! are only accurate for the first line of each function. The `-file`
+//! attributes in the Erlang module are used to find the function instead.
+
+use std::{fmt::Write, sync::OnceLock};
+
+use camino::{Utf8Path, Utf8PathBuf};
+use ecow::EcoString;
+use regex::Regex;
+
+/// A warning of dialyzer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Warning {
+    /// The path of the file, either a Gleam module relative to the root of
+    /// its package or an Erlang module.
+    pub path: Utf8PathBuf,
+    pub line: u32,
+    pub message: String,
+    /// The name of the Gleam function the warning is for, when it is known.
+    pub function: Option<EcoString>,
+}
+
+/// Parses the warnings printed by dialyzer, which each start with the file
+/// and line they are for and may continue on the lines after it.
+///
+pub fn parse(output: &str) -> Vec<Warning> {
+    let mut warnings: Vec<Warning> = vec![];
+    for text in output.lines() {
+        match warning_pattern().captures(text) {
+            Some(captures) => warnings.push(Warning {
+                path: Utf8PathBuf::from(&captures["path"]),
+                line: captures["line"].parse().unwrap_or(0),
+                message: captures["message"].trim_end().to_string(),
+                function: None,
+            }),
+            None => match warnings.last_mut() {
+                Some(warning) if !text.trim().is_empty() => {
+                    warning.message.push('\n');
+                    warning.message.push_str(text.trim());
+                }
+                Some(_) | None => (),
+            },
+        }
+    }
+    warnings
+}
+
+fn warning_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| {
+        Regex::new(r"^(?P<path>(?:[A-Za-z]:)?[^\s:][^:]*):(?P<line>\d+)(?::\d+)?: (?P<message>.*)$")
+            .expect("dialyzer warning regex")
+    })
+}
+
+/// Where the Gleam functions of a generated Erlang module start, read from
+/// the `-file` attributes written before each of them.
+#[derive(Debug, Default)]
+pub struct SourceMap {
+    functions: Vec<Function>,
+}
+
+#[derive(Debug)]
+struct Function {
+    name: EcoString,
+    /// The line of the Erlang module the function head is on.
+    erlang_line: u32,
+    /// The path of the Gleam module and the line the function starts on.
+    path: Utf8PathBuf,
+    line: u32,
+}
+
+impl SourceMap {
+    pub fn new(erlang: &str) -> Self {
+        let mut functions = vec![];
+        let mut lines = erlang.lines().zip(1..);
+        while let Some((text, _)) = lines.next() {
+            let Some(captures) = file_attribute_pattern().captures(text) else {
+                continue;
+            };
+            let Some((head, erlang_line)) = lines.next() else {
+                break;
+            };
+            let name = head.split('(').next().unwrap_or_default();
+            functions.push(Function {
+                name: name.trim_matches('\'').into(),
+                erlang_line,
+                path: Utf8PathBuf::from(&captures["path"]),
+                line: captures["line"].parse().unwrap_or(0),
+            });
+        }
+        Self { functions }
+    }
+
+    /// Moves the warning to the start of the Gleam function it is for, if it
+    /// is for a line of the Erlang module or one of the lines dialyzer
+    /// reports as being in the Gleam module.
+    ///
+    pub fn locate(&self, mut warning: Warning) -> Warning {
+        let function = if warning.path.extension() == Some("gleam") {
+            self.functions
+                .iter()
+                .filter(|function| function.path == warning.path && function.line <= warning.line)
+                .max_by_key(|function| function.line)
+        } else {
+            self.functions
+                .iter()
+                .filter(|function| function.erlang_line <= warning.line)
+                .max_by_key(|function| function.erlang_line)
+        };
+        if let Some(function) = function {
+            warning.path = function.path.clone();
+            warning.line = function.line;
+            warning.function = Some(function.name.clone());
+        }
+        warning
+    }
+}
+
+fn file_attribute_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| {
+        Regex::new(r#"^-file\("(?P<path>[^"]*)", (?P<line>\d+)\)\.$"#)
+            .expect("file attribute regex")
+    })
+}
+
+/// The name of the Erlang module generated for a Gleam module, from the path
+/// of the Gleam module relative to the root of its package.
+///
+pub fn erlang_module_name(path: &Utf8Path) -> Option<String> {
+    let module = path
+        .strip_prefix("src")
+        .or_else(|_| path.strip_prefix("test"))
+        .or_else(|_| path.strip_prefix("dev"))
+        .ok()?
+        .as_str()
+        .strip_suffix(".gleam")?;
+    Some(module.replace(['/', '\\'], "@"))
+}
+
+/// Renders warnings as a list for printing in a terminal, with a summary of
+/// how many there are.
+///
+pub fn to_text(warnings: &[Warning]) -> String {
+    let mut text = String::new();
+    for warning in warnings {
+        let Warning {
+            path,
+            line,
+            message,
+            function,
+        } = warning;
+        match function {
+            Some(function) => writeln!(text, "{path}:{line} in `{function}`"),
+            None => writeln!(text, "{path}:{line}"),
+        }
+        .expect("write location");
+        for message_line in message.lines() {
+            writeln!(text, "  {message_line}").expect("write message");
+        }
+        text.push('\n');
+    }
+    match warnings.len() {
+        1 => text.push_str("1 warning\n"),
+        count => writeln!(text, "{count} warnings").expect("write summary"),
+    }
+    text
+}
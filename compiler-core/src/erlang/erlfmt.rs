@@ -0,0 +1,176 @@
+//! A pass over generated Erlang that lays it out as erlfmt would, for projects
+//! that commit the generated code and want its diffs to be small.
+//!
+//! The pass only changes whitespace and the order of exports, so what the
+//! code does is unchanged:
+//!
+//! - Attributes and records that fit within the print width are written on
+//!   one line, otherwise with one element per line.
+//! - Exports are sorted by name and arity, so the order of definitions in the
+//!   Gleam module or in the generated code does not show up in diffs.
+//! - Trailing whitespace is removed and runs of blank lines are collapsed to a
+//!   single one, except inside strings and quoted atoms.
+//!
+
+use itertools::Itertools;
+
+/// The default `print_width` of erlfmt.
+const PRINT_WIDTH: usize = 100;
+const INDENT: &str = "    ";
+
+/// Lays out generated Erlang as erlfmt would.
+///
+pub fn format(code: &str) -> String {
+    let lines = code.lines().collect_vec();
+    let mut formatted = String::with_capacity(code.len());
+    let mut literal = None;
+    let mut after_blank_line = true;
+    let mut index = 0;
+
+    while let Some(line) = lines.get(index) {
+        index += 1;
+
+        if let Some(quote) = literal {
+            literal = scan(line, Some(quote));
+            formatted.push_str(line);
+            formatted.push('\n');
+            after_blank_line = false;
+            continue;
+        }
+
+        if let Some((attribute, end)) = attribute(&lines, index - 1) {
+            formatted.push_str(&attribute);
+            formatted.push('\n');
+            after_blank_line = false;
+            index = end + 1;
+            continue;
+        }
+
+        literal = scan(line, None);
+        let line = if literal.is_some() {
+            line
+        } else {
+            line.trim_end()
+        };
+        if line.is_empty() {
+            if !after_blank_line {
+                formatted.push('\n');
+            }
+            after_blank_line = true;
+            continue;
+        }
+        formatted.push_str(line);
+        formatted.push('\n');
+        after_blank_line = false;
+    }
+
+    let length = formatted.trim_end().len();
+    formatted.truncate(length);
+    formatted.push('\n');
+    formatted
+}
+
+/// Returns the quote character of the string or quoted atom the line ends
+/// inside of, if any, given the one it starts inside of.
+fn scan(line: &str, mut literal: Option<char>) -> Option<char> {
+    let mut characters = line.chars();
+    while let Some(character) = characters.next() {
+        match (literal, character) {
+            (_, '\\') => {
+                let _ = characters.next();
+            }
+            (Some(quote), _) if character == quote => literal = None,
+            (Some(_), _) => (),
+            (None, '"' | '\'') => literal = Some(character),
+            (None, '$') => {
+                // A character literal, which may be an escape sequence
+                if characters.next() == Some('\\') {
+                    let _ = characters.next();
+                }
+            }
+            (None, '%') => return None,
+            (None, _) => (),
+        }
+    }
+    literal
+}
+
+/// Lays out the export, compile or record attribute starting at the line with
+/// the given index, returning it and the index of the line it ends on.
+fn attribute(lines: &[&str], start: usize) -> Option<(String, usize)> {
+    let first = lines.get(start)?;
+    let (name, _) = first.strip_prefix('-')?.split_once('(')?;
+    if !matches!(name, "export" | "export_type" | "compile" | "record") {
+        return None;
+    }
+    let end = (start..lines.len()).find(|&index| {
+        lines
+            .get(index)
+            .is_some_and(|line| line.trim_end().ends_with(")."))
+    })?;
+    let form = lines.get(start..=end)?.join("\n");
+    let arguments = form
+        .strip_prefix('-')?
+        .strip_prefix(name)?
+        .strip_prefix('(')?
+        .trim_end()
+        .strip_suffix(").")?;
+
+    let attribute = match name {
+        "record" => {
+            let (record, fields) = arguments.split_once(',')?;
+            let fields = fields.trim().strip_prefix('{')?.strip_suffix('}')?;
+            layout(&format!("-record({record}, {{"), elements(fields), "}).")
+        }
+        _ => {
+            let list = arguments.trim().strip_prefix('[')?.strip_suffix(']')?;
+            let mut elements = elements(list);
+            if name != "compile" {
+                elements.sort_by_key(|export| match export.rsplit_once('/') {
+                    Some((name, arity)) => (name.to_string(), arity.parse::<usize>().unwrap_or(0)),
+                    None => (export.clone(), 0),
+                });
+            }
+            layout(&format!("-{name}(["), elements, "]).")
+        }
+    };
+    Some((attribute, end))
+}
+
+/// Splits the elements of a list or tuple at the commas that are not nested
+/// in brackets, with the whitespace within each one collapsed.
+fn elements(text: &str) -> Vec<String> {
+    let mut elements = vec![];
+    let mut depth = 0;
+    let mut element = String::new();
+    for character in text.chars() {
+        match character {
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' | '}' => depth -= 1,
+            ',' if depth == 0 => {
+                elements.push(std::mem::take(&mut element));
+                continue;
+            }
+            _ => (),
+        }
+        element.push(character);
+    }
+    elements.push(element);
+    elements
+        .into_iter()
+        .map(|element| element.split_whitespace().join(" "))
+        .filter(|element| !element.is_empty())
+        .collect()
+}
+
+fn layout(open: &str, elements: Vec<String>, close: &str) -> String {
+    let line = format!("{open}{}{close}", elements.join(", "));
+    if line.len() <= PRINT_WIDTH || elements.is_empty() {
+        return line;
+    }
+    let elements = elements
+        .iter()
+        .map(|element| format!("{INDENT}{element}"))
+        .join(",\n");
+    format!("{open}\n{elements}\n{close}")
+}
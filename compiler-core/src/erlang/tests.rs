@@ -3,17 +3,21 @@ use crate::config::PackageConfig;
 use crate::type_::PRELUDE_MODULE_NAME;
 use crate::{
     build::{Origin, Target},
-    erlang::module,
+    erlang::{module, Style},
     line_numbers::LineNumbers,
     uid::UniqueIdGenerator,
     warning::TypeWarningEmitter,
 };
+use camino::Utf8Path;
 
+mod behaviours;
 mod bit_arrays;
 mod case;
 mod conditional_compilation;
 mod consts;
 mod custom_types;
+mod dialyzer;
+mod erlfmt;
 mod external_fn;
 mod functions;
 mod guards;
@@ -22,6 +26,7 @@ mod numbers;
 mod panic;
 mod patterns;
 mod pipes;
+mod readable;
 mod records;
 mod reserved;
 mod strings;
@@ -30,6 +35,19 @@ mod use_;
 mod variables;
 
 pub fn compile_test_project(src: &str, dep: Option<(&str, &str, &str)>) -> String {
+    compile_test_project_with_style(src, dep, false)
+}
+
+/// Compiles the module to Erlang meant to be read by people.
+pub fn compile_readable_test_project(src: &str) -> String {
+    compile_test_project_with_style(src, None, true)
+}
+
+fn compile_test_project_with_style(
+    src: &str,
+    dep: Option<(&str, &str, &str)>,
+    readable: bool,
+) -> String {
     let mut modules = im::HashMap::new();
     let ids = UniqueIdGenerator::new();
     // DUPE: preludeinsertion
@@ -67,6 +85,7 @@ pub fn compile_test_project(src: &str, dep: Option<(&str, &str, &str)>) -> Strin
     let parsed = crate::parse::parse_module(src).expect("syntax error");
     let mut config = PackageConfig::default();
     config.name = "thepackage".into();
+    let extra = parsed.extra;
     let mut ast = parsed.module;
     ast.name = "my/mod".into();
     let line_numbers = LineNumbers::new(src);
@@ -83,7 +102,19 @@ pub fn compile_test_project(src: &str, dep: Option<(&str, &str, &str)>) -> Strin
     .infer_module(ast, line_numbers, "".into())
     .expect("should successfully infer root Erlang");
     let line_numbers = LineNumbers::new(src);
-    module(&ast, &line_numbers).unwrap()
+    let style = if readable {
+        Style::Readable { extra: &extra }
+    } else {
+        Style::Compact
+    };
+    module(
+        &ast,
+        &line_numbers,
+        src,
+        Utf8Path::new("src/my/mod.gleam"),
+        style,
+    )
+    .unwrap()
 }
 
 #[macro_export]
@@ -632,3 +663,18 @@ pub fn main() {
 "
     );
 }
+
+#[test]
+fn statements_are_kept_on_the_lines_of_the_gleam_code() {
+    assert_erl!(
+        r#"
+pub fn main() {
+  let x = 1
+
+
+  let y = case x { 1 -> 2 _ -> 3 }
+  y
+}
+"#
+    );
+}
@@ -0,0 +1,74 @@
+use crate::assert_erl;
+
+#[test]
+fn behaviour_is_declared() {
+    assert_erl!(
+        r#"
+@behaviour("gen_server")
+pub fn init(args: Int) -> Int {
+  args
+}
+
+@behaviour("gen_server")
+pub fn handle_call(request: Int, from: Int, state: Int) -> Int {
+  state
+}
+"#
+    );
+}
+
+#[test]
+fn private_callbacks_are_exported() {
+    assert_erl!(
+        r#"
+@behaviour("application")
+fn start(type_: Int, args: Int) -> Int {
+  args
+}
+
+@behaviour("application")
+fn stop(state: Int) -> Nil {
+  Nil
+}
+"#
+    );
+}
+
+#[test]
+fn external_callbacks_are_generated() {
+    assert_erl!(
+        r#"
+@behaviour("gen_server")
+@external(erlang, "my_server", "init")
+fn init(args: Int) -> Int
+"#
+    );
+}
+
+#[test]
+fn many_behaviours() {
+    assert_erl!(
+        r#"
+@behaviour("supervisor")
+pub fn init(args: Int) -> Int {
+  args
+}
+
+@behaviour("application")
+pub fn start(type_: Int, args: Int) -> Int {
+  args
+}
+"#
+    );
+}
+
+#[test]
+fn behaviour_of_other_target_is_not_declared() {
+    assert_erl!(
+        r#"
+@behaviour("gen_server")
+@external(javascript, "./server.mjs", "init")
+pub fn init(args: Int) -> Int
+"#
+    );
+}
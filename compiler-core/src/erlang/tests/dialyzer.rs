@@ -0,0 +1,121 @@
+use crate::erlang::dialyzer::{erlang_module_name, parse, to_text, SourceMap, Warning};
+use crate::erlang::tests::compile_test_project;
+
+use camino::{Utf8Path, Utf8PathBuf};
+
+const SRC: &str = r#"pub fn main() {
+  wibble(1)
+}
+
+fn wibble(x: Int) -> Int {
+  let y = x + 1
+  y * 2
+}
+"#;
+
+#[test]
+fn parse_warnings() {
+    let output = "src/my/mod.gleam:5:1: Function wibble/1 has no local return
+src/my/mod.gleam:7:5: The pattern
+          'ok' can never match the type
+          integer()
+_gleam_artefacts/my@mod.erl:3:2: Function main/0 has no local return
+";
+    assert_eq!(
+        parse(output),
+        vec![
+            Warning {
+                path: "src/my/mod.gleam".into(),
+                line: 5,
+                message: "Function wibble/1 has no local return".into(),
+                function: None,
+            },
+            Warning {
+                path: "src/my/mod.gleam".into(),
+                line: 7,
+                message: "The pattern\n'ok' can never match the type\ninteger()".into(),
+                function: None,
+            },
+            Warning {
+                path: "_gleam_artefacts/my@mod.erl".into(),
+                line: 3,
+                message: "Function main/0 has no local return".into(),
+                function: None,
+            },
+        ]
+    );
+}
+
+#[test]
+fn parse_warnings_without_columns() {
+    let warnings = parse("my@mod.erl:12: Function main/0 has no local return\n");
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings.first().map(|warning| warning.line), Some(12));
+}
+
+#[test]
+fn locate_warning_reported_in_gleam_module() {
+    let map = SourceMap::new(&compile_test_project(SRC, None));
+    // The second line of the body of `wibble` is reported as being a few
+    // lines on from the start of the function.
+    let warning = map.locate(Warning {
+        path: "src/my/mod.gleam".into(),
+        line: 7,
+        message: "".into(),
+        function: None,
+    });
+    assert_eq!(warning.path, Utf8PathBuf::from("src/my/mod.gleam"));
+    assert_eq!(warning.line, 5);
+    assert_eq!(warning.function.as_deref(), Some("wibble"));
+}
+
+#[test]
+fn locate_warning_reported_in_erlang_module() {
+    let erlang = compile_test_project(SRC, None);
+    let line = erlang
+        .lines()
+        .position(|line| line.starts_with("main()"))
+        .expect("main function") as u32
+        + 2;
+    let warning = SourceMap::new(&erlang).locate(Warning {
+        path: "my@mod.erl".into(),
+        line,
+        message: "".into(),
+        function: None,
+    });
+    assert_eq!(warning.path, Utf8PathBuf::from("src/my/mod.gleam"));
+    assert_eq!(warning.line, 1);
+    assert_eq!(warning.function.as_deref(), Some("main"));
+}
+
+#[test]
+fn module_names() {
+    assert_eq!(
+        erlang_module_name(Utf8Path::new("src/my/mod.gleam")).as_deref(),
+        Some("my@mod")
+    );
+    assert_eq!(
+        erlang_module_name(Utf8Path::new("test/my_test.gleam")).as_deref(),
+        Some("my_test")
+    );
+    assert_eq!(erlang_module_name(Utf8Path::new("src/native.erl")), None);
+}
+
+#[test]
+fn text() {
+    let warnings = vec![
+        Warning {
+            path: "src/my/mod.gleam".into(),
+            line: 5,
+            message: "The pattern\n'ok' can never match the type\ninteger()".into(),
+            function: Some("wibble".into()),
+        },
+        Warning {
+            path: "src/native.erl".into(),
+            line: 3,
+            message: "Function main/0 has no local return".into(),
+            function: None,
+        },
+    ];
+    insta::assert_snapshot!(to_text(&warnings));
+}
@@ -0,0 +1,63 @@
+use crate::erlang::tests::compile_test_project;
+use crate::erlang::{erlfmt::format, record_definition};
+use crate::type_;
+
+#[test]
+fn module() {
+    let src = r#"pub type Wibble {
+  Wibble(name: String, count: Int)
+}
+
+pub fn wobble() { 1 }
+
+pub fn add(x: Int, y: Int) -> Int { x + y }
+
+pub fn add_all(a: Int, b: Int, c: Int) -> Int { a + b + c }
+"#;
+    insta::assert_snapshot!(format(&compile_test_project(src, None)));
+}
+
+#[test]
+fn long_exports_are_one_per_line() {
+    let src = r#"pub fn first_function_with_a_long_name() { 1 }
+pub fn second_function_with_a_long_name() { 2 }
+pub fn third_function_with_a_long_name() { 3 }
+pub fn fourth_function_with_a_long_name() { 4 }
+"#;
+    insta::assert_snapshot!(format(&compile_test_project(src, None)));
+}
+
+#[test]
+fn long_record_fields_are_one_per_line() {
+    insta::assert_snapshot!(format(&record_definition(
+        "PetCat",
+        &[
+            ("name", type_::string()),
+            ("is_cute", type_::bool()),
+            ("favourite_toys", type_::list(type_::string())),
+            ("owner", type_::tuple(vec![type_::string(), type_::int()])),
+        ]
+    )));
+}
+
+#[test]
+fn short_records_are_on_one_line() {
+    assert_eq!(
+        format("-record(pet_cat, {\n    name :: binary(),\n    is_cute :: boolean()\n}).\n"),
+        "-record(pet_cat, {name :: binary(), is_cute :: boolean()}).\n"
+    );
+}
+
+#[test]
+fn whitespace_is_normalised() {
+    assert_eq!(
+        format("\n\n-module(wibble).  \n\n\n\nwobble() ->   \n    1.\n\n\n"),
+        "-module(wibble).\n\nwobble() ->\n    1.\n"
+    );
+}
+
+#[test]
+fn strings_are_unchanged() {
+    let code = "wobble() ->\n    <<\"one  \n\n\n  two\"/utf8>>.\n\nwubble() ->\n    '%  '.\n";
+    assert_eq!(format(code), code);
+}
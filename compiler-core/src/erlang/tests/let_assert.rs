@@ -45,6 +45,17 @@ fn variable_rewrites() {
     );
 }
 
+#[test]
+fn source_of_multiline_assert_is_escaped() {
+    assert_erl!(
+        r#"pub fn go(x) {
+  let assert #("\\wibble", Ok(y)) =
+    x
+  y
+}"#
+    );
+}
+
 // TODO: patterns that are just vars don't render a case expression
 // #[test]
 // fn just_pattern() {
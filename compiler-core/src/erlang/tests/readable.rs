@@ -0,0 +1,100 @@
+use crate::erlang::tests::compile_readable_test_project;
+
+#[test]
+fn comments_are_kept() {
+    let src = r#"//// The module of wibbles.
+
+// A constant used below.
+const limit = 10
+
+// Whether the wibble is small.
+/// Returns `True` if the wibble is at most the limit.
+pub fn is_small(wibble: Int) -> Bool {
+  // Compare with the limit
+  let small = wibble <= limit
+  // and return it.
+  small
+}
+"#;
+    insta::assert_snapshot!(compile_readable_test_project(src));
+}
+
+#[test]
+fn comments_in_nested_blocks() {
+    let src = r#"pub fn main(x: Int) {
+  case x {
+    0 -> {
+      // Nothing to do.
+      Nil
+    }
+    _ -> Nil
+  }
+}
+"#;
+    insta::assert_snapshot!(compile_readable_test_project(src));
+}
+
+#[test]
+fn case_clauses_are_compact() {
+    let src = r#"pub fn describe(x: Int) -> String {
+  case x {
+    0 -> "zero"
+    1 | 2 -> "small"
+    _ -> "big"
+  }
+}
+"#;
+    insta::assert_snapshot!(compile_readable_test_project(src));
+}
+
+#[test]
+fn pipeline_steps_are_named_after_functions() {
+    let src = r#"fn double(x: Int) -> Int { x * 2 }
+fn add(x: Int, y: Int) -> Int { x + y }
+
+pub fn main(x: Int) -> Int {
+  x
+  |> double
+  |> add(1)
+  |> double
+}
+"#;
+    insta::assert_snapshot!(compile_readable_test_project(src));
+}
+
+#[test]
+fn pipeline_starting_with_a_call() {
+    let src = r#"fn double(x: Int) -> Int { x * 2 }
+
+pub fn main() -> Int {
+  double(1)
+  |> double
+}
+"#;
+    insta::assert_snapshot!(compile_readable_test_project(src));
+}
+
+#[test]
+fn nested_pipelines() {
+    let src = r#"fn add(x: Int, y: Int) -> Int { x + y }
+
+pub fn main(x: Int, y: Int) -> Int {
+  x
+  |> add(y |> add(1))
+  |> add(2)
+}
+"#;
+    insta::assert_snapshot!(compile_readable_test_project(src));
+}
+
+#[test]
+fn case_clauses_that_are_not_simple() {
+    let src = r#"pub fn main(x: Int) -> Int {
+  case x {
+    0 -> 1
+    _ -> panic as "unexpected"
+  }
+}
+"#;
+    insta::assert_snapshot!(compile_readable_test_project(src));
+}
@@ -1,8 +1,9 @@
 #![allow(clippy::unwrap_used, clippy::expect_used)]
 use crate::build::{Outcome, Runtime, Target};
-use crate::diagnostic::{Diagnostic, Label, Location};
-use crate::type_::error::RecordVariants;
+use crate::config::schema::ProblemKind;
+use crate::diagnostic::{catalog, Diagnostic, Label, Location, Theme};
 use crate::type_::error::{MissingAnnotation, UnknownTypeHint};
+use crate::type_::error::{ProblemKey, RecordVariants};
 use crate::type_::{error::PatternMatchKind, FieldAccessUsage};
 use crate::{ast::BinOp, parse::error::ParseErrorType, type_::Type};
 use crate::{
@@ -14,10 +15,8 @@ use crate::{
 use ecow::EcoString;
 use hexpm::version::ResolutionError;
 use itertools::Itertools;
-use pubgrub::package::Package;
-use pubgrub::report::DerivationTree;
-use pubgrub::version::Version;
-use std::collections::HashSet;
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
 use std::env;
 use std::fmt::Debug;
 use std::io::Write;
@@ -47,6 +46,50 @@ pub struct UnknownImportDetails {
     pub modules: Vec<EcoString>,
 }
 
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct UnknownErlangExternalDetails {
+    pub module: EcoString,
+    pub function: EcoString,
+    pub arity: usize,
+    pub exported_arities: Vec<usize>,
+    pub location: crate::ast::SrcSpan,
+    pub path: Utf8PathBuf,
+    pub src: EcoString,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct UnknownJavaScriptExternalDetails {
+    pub module: EcoString,
+    pub function: EcoString,
+    pub problem: UnknownJavaScriptExternalProblem,
+    pub location: crate::ast::SrcSpan,
+    pub path: Utf8PathBuf,
+    pub src: EcoString,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct UnknownFeatureDetails {
+    pub feature: EcoString,
+    pub options: Vec<EcoString>,
+    pub location: crate::ast::SrcSpan,
+    pub path: Utf8PathBuf,
+    pub src: EcoString,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct WasmUnsupportedDetails {
+    pub feature: EcoString,
+    pub location: crate::ast::SrcSpan,
+    pub path: Utf8PathBuf,
+    pub src: EcoString,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum UnknownJavaScriptExternalProblem {
+    FileNotFound,
+    NotExported { exported: Vec<EcoString> },
+}
+
 #[derive(Debug, Eq, PartialEq, Error, Clone)]
 pub enum Error {
     #[error("failed to parse Gleam source code")]
@@ -81,7 +124,10 @@ pub enum Error {
     DuplicateSourceFile { file: String },
 
     #[error("cyclical module imports")]
-    ImportCycle { modules: Vec<EcoString> },
+    ImportCycle {
+        modules: Vec<EcoString>,
+        imports: Vec<CycleImport>,
+    },
 
     #[error("cyclical package dependencies")]
     PackageCycle { packages: Vec<EcoString> },
@@ -109,6 +155,24 @@ pub enum Error {
     #[error("source code incorrectly formatted")]
     Format { problem_files: Vec<Unformatted> },
 
+    #[error("imports not organized")]
+    UnorganizedImports { files: Vec<Utf8PathBuf> },
+
+    #[error("{count} lints found")]
+    LintsFound { count: usize },
+
+    #[error("{count} dialyzer warnings")]
+    DialyzerWarnings { count: usize },
+
+    #[error("the tests fail before mutating the code")]
+    MutationTestsFailing,
+
+    #[error("{count} mutants survived")]
+    MutantsSurvived { count: usize },
+
+    #[error("{path} is not a module of the project")]
+    NotAProjectModule { path: Utf8PathBuf },
+
     #[error("Hex error: {0}")]
     Hex(String),
 
@@ -160,6 +224,13 @@ pub enum Error {
     #[error("{input} is not a valid version. {error}")]
     InvalidVersionFormat { input: String, error: String },
 
+    #[error("{path} does not match the schema of gleam.toml")]
+    InvalidPackageConfig {
+        path: Utf8PathBuf,
+        src: EcoString,
+        problems: Vec<crate::config::schema::Problem>,
+    },
+
     #[error("project root already exists")]
     ProjectRootAlreadyExist { path: String },
 
@@ -202,8 +273,37 @@ file_names.iter().map(|x| x.as_str()).join(", "))]
     #[error("{0}")]
     Http(String),
 
-    #[error("Git dependencies are currently unsupported")]
-    GitDependencyUnsupported,
+    #[error("Failed to check out {reference} of git repository {repo}")]
+    GitCheckoutFailed {
+        package: EcoString,
+        repo: EcoString,
+        reference: EcoString,
+    },
+
+    #[error("{problems} problems were found verifying the dependencies")]
+    DependencyVerificationFailed { problems: usize },
+
+    #[error("Git dependency {package} is at commit {found} rather than {expected}")]
+    GitCommitMismatch {
+        package: EcoString,
+        expected: EcoString,
+        found: EcoString,
+    },
+
+    #[error("Invalid Hex organization name {organization}")]
+    InvalidHexOrganization { organization: EcoString },
+
+    #[error("No API key for the Hex organization {organization}")]
+    HexOrganizationKeyMissing { organization: EcoString },
+
+    #[error("no provenance signing key was given")]
+    ProvenanceKeyMissing,
+
+    #[error("the provenance signing key is invalid")]
+    InvalidProvenanceKey,
+
+    #[error("Package {package} v{version} has not been downloaded")]
+    PackageNotDownloaded { package: EcoString, version: String },
 
     #[error("Failed to create canonical path for package {0}")]
     DependencyCanonicalizationFailed(String),
@@ -211,6 +311,12 @@ file_names.iter().map(|x| x.as_str()).join(", "))]
     #[error("Dependency tree resolution failed: {0}")]
     DependencyResolutionFailed(String),
 
+    #[error("Dependency tree resolution failed: {error}")]
+    DependencyResolutionFailedWithFixes {
+        error: String,
+        fixes: Vec<crate::dependency::Suggestion>,
+    },
+
     #[error("The package {0} is listed in dependencies and dev-dependencies")]
     DuplicateDependency(EcoString),
 
@@ -246,6 +352,9 @@ file_names.iter().map(|x| x.as_str()).join(", "))]
     #[error("Opening docs at {path} failed: {error}")]
     FailedToOpenDocs { path: Utf8PathBuf, error: String },
 
+    #[error("Serving docs on {address} failed: {error}")]
+    FailedToServeDocs { address: String, error: String },
+
     #[error("The package {package} requires a Gleam version satisfying {required_version} and you are using v{gleam_version}")]
     IncompatibleCompilerVersion {
         package: String,
@@ -273,6 +382,98 @@ file_names.iter().map(|x| x.as_str()).join(", "))]
 
     #[error("Version already published")]
     HexPublishReplaceRequired { version: String },
+
+    #[error("Hex requires a two-factor authentication code")]
+    HexTwoFactorCodeRequired { rejected: bool },
+
+    #[error("The release can no longer be reverted")]
+    HexRevertWindowClosed { package: String, version: String },
+
+    #[error("The docs of {package} v{version} have already been published")]
+    DocsAlreadyPublished { package: String, version: String },
+
+    #[error("The example {example} depends on {package} which this package does not")]
+    UnresolvedExampleDependency {
+        example: EcoString,
+        package: EcoString,
+    },
+
+    #[error("{path} has no entry for version {version}")]
+    ChangelogEntryMissing { path: Utf8PathBuf, version: String },
+
+    #[error("The git work tree has uncommitted changes")]
+    ReleaseFromDirtyWorkTree,
+
+    #[error("The git tag {tag} already exists")]
+    GitTagExists { tag: String },
+
+    #[error("Unknown Erlang application {name}")]
+    UnknownErlangApplication {
+        field: EcoString,
+        name: EcoString,
+        options: Vec<EcoString>,
+    },
+
+    #[error("Invalid Erlang application environment value for {key}")]
+    InvalidErlangAppEnv { key: EcoString },
+
+    #[error("Unknown application {name} in runtime config {path}")]
+    UnknownRuntimeConfigApplication {
+        path: Utf8PathBuf,
+        name: EcoString,
+        options: Vec<EcoString>,
+    },
+
+    #[error("Unknown feature {feature} of package {package}")]
+    UnknownFeature {
+        package: EcoString,
+        feature: EcoString,
+        /// The package that enables the feature.
+        required_by: EcoString,
+        options: Vec<EcoString>,
+    },
+
+    #[error("Invalid env constant name {name}")]
+    InvalidEnvConstantName { name: EcoString },
+
+    #[error("Unknown JavaScript entrypoint {module}")]
+    UnknownJavaScriptEntrypoint {
+        module: EcoString,
+        options: Vec<EcoString>,
+    },
+
+    #[error("Unknown feature in @feature attribute")]
+    UnknownFeatureAttribute {
+        // Boxed to prevent this variant from being overly large
+        details: Box<UnknownFeatureDetails>,
+    },
+
+    #[error("{feature} is unstable")]
+    UnstableFeature { feature: EcoString },
+
+    #[error("compilation by the daemon failed")]
+    DaemonCompilation { diagnostics: Vec<Diagnostic> },
+
+    #[error("the daemon failed: {reason}")]
+    DaemonFailed { reason: String },
+
+    #[error("Unsupported by the Wasm target")]
+    WasmUnsupported {
+        // Boxed to prevent this variant from being overly large
+        details: Box<WasmUnsupportedDetails>,
+    },
+
+    #[error("unknown Erlang external function")]
+    UnknownErlangExternal {
+        // Boxed to prevent this variant from being overly large
+        details: Box<UnknownErlangExternalDetails>,
+    },
+
+    #[error("unknown JavaScript external function")]
+    UnknownJavaScriptExternal {
+        // Boxed to prevent this variant from being overly large
+        details: Box<UnknownJavaScriptExternalDetails>,
+    },
 }
 
 impl Error {
@@ -309,39 +510,11 @@ impl Error {
     }
 
     pub fn dependency_resolution_failed(error: ResolutionError) -> Error {
-        fn collect_conflicting_packages<'dt, P: Package, V: Version>(
-            derivation_tree: &'dt DerivationTree<P, V>,
-            conflicting_packages: &mut HashSet<&'dt P>,
-        ) {
-            match derivation_tree {
-                DerivationTree::External(external) => match external {
-                    pubgrub::report::External::NotRoot(package, _) => {
-                        let _ = conflicting_packages.insert(package);
-                    }
-                    pubgrub::report::External::NoVersions(package, _) => {
-                        let _ = conflicting_packages.insert(package);
-                    }
-                    pubgrub::report::External::UnavailableDependencies(package, _) => {
-                        let _ = conflicting_packages.insert(package);
-                    }
-                    pubgrub::report::External::FromDependencyOf(package, _, dep_package, _) => {
-                        let _ = conflicting_packages.insert(package);
-                        let _ = conflicting_packages.insert(dep_package);
-                    }
-                },
-                DerivationTree::Derived(derived) => {
-                    collect_conflicting_packages(&derived.cause1, conflicting_packages);
-                    collect_conflicting_packages(&derived.cause2, conflicting_packages);
-                }
-            }
-        }
-
         Self::DependencyResolutionFailed(match error {
             ResolutionError::NoSolution(mut derivation_tree) => {
                 derivation_tree.collapse_no_versions();
 
-                let mut conflicting_packages = HashSet::new();
-                collect_conflicting_packages(&derivation_tree, &mut conflicting_packages);
+                let conflicting_packages = crate::dependency::conflicting_packages(&derivation_tree);
 
                 let report = format!("{}\n\n{}",
                     String::from("Unable to find compatible versions for the version constraints in your gleam.toml. The conflicting packages are:"),
@@ -383,6 +556,20 @@ impl Error {
         })
     }
 
+    /// Like `dependency_resolution_failed`, suggesting changes that would let
+    /// the dependencies be resolved if there are any.
+    pub fn dependency_resolution_failed_with_fixes(
+        error: ResolutionError,
+        fixes: Vec<crate::dependency::Suggestion>,
+    ) -> Error {
+        match Self::dependency_resolution_failed(error) {
+            Self::DependencyResolutionFailed(error) if !fixes.is_empty() => {
+                Self::DependencyResolutionFailedWithFixes { error, fixes }
+            }
+            error => error,
+        }
+    }
+
     pub fn expand_tar<E>(error: E) -> Error
     where
         E: std::error::Error,
@@ -493,7 +680,7 @@ impl FileKind {
 }
 
 // https://github.com/rust-lang/rust/blob/03994e498df79aa1f97f7bbcfd52d57c8e865049/compiler/rustc_span/src/edit_distance.rs
-fn edit_distance(a: &str, b: &str, limit: usize) -> Option<usize> {
+pub(crate) fn edit_distance(a: &str, b: &str, limit: usize) -> Option<usize> {
     let mut a = &a.chars().collect::<Vec<_>>()[..];
     let mut b = &b.chars().collect::<Vec<_>>()[..];
 
@@ -642,6 +829,7 @@ fn did_you_mean(name: &str, options: &[EcoString]) -> Option<String> {
     // such as the test on `type_vars_must_be_declared`.
     if let Some(exact_match) = options
         .iter()
+        .sorted()
         .find(|&option| option.eq_ignore_ascii_case(name))
     {
         return Some(format!("Did you mean `{}`?", exact_match));
@@ -666,15 +854,30 @@ fn did_you_mean(name: &str, options: &[EcoString]) -> Option<String> {
 impl Error {
     pub fn pretty_string(&self) -> String {
         let mut nocolor = Buffer::no_color();
-        self.pretty(&mut nocolor);
+        self.pretty(&mut nocolor, Theme::default());
         String::from_utf8(nocolor.into_inner()).expect("Error printing produced invalid utf8")
     }
 
-    pub fn pretty(&self, buffer: &mut Buffer) {
-        for diagnostic in self.to_diagnostics() {
-            diagnostic.write(buffer);
+    pub fn pretty(&self, buffer: &mut Buffer, theme: Theme) {
+        self.pretty_limited(buffer, theme, None)
+    }
+
+    /// Writes at most `max_errors` of the diagnostics of the error, followed
+    /// by a note saying how many were not written.
+    pub fn pretty_limited(&self, buffer: &mut Buffer, theme: Theme, max_errors: Option<usize>) {
+        let diagnostics = self.to_diagnostics();
+        let shown = max_errors.unwrap_or(usize::MAX).min(diagnostics.len());
+        let suppressed = diagnostics.len() - shown;
+        for diagnostic in diagnostics.into_iter().take(shown) {
+            diagnostic.write(buffer, theme);
             writeln!(buffer).expect("write new line after diagnostic");
         }
+        match suppressed {
+            0 => (),
+            1 => writeln!(buffer, "1 more error was not shown.").expect("write suppressed count"),
+            _ => writeln!(buffer, "{suppressed} more errors were not shown.")
+                .expect("write suppressed count"),
+        }
     }
 
     pub fn to_diagnostics(&self) -> Vec<Diagnostic> {
@@ -750,12 +953,8 @@ This prefix is intended for official Gleam packages only.",
             }
 
             Error::InvalidModuleName { module } => vec![Diagnostic {
-                title: "Invalid module name".into(),
-                text: format!(
-                    "`{module}` is not a valid module name.
-Module names can only contain lowercase letters, underscore, and
-forward slash and must not end with a slash."
-                ),
+                title: catalog::INVALID_MODULE_NAME_TITLE.render(&[]),
+                text: catalog::INVALID_MODULE_NAME.render(&[("module", module)]),
                 level: Level::Error,
                 location: None,
                 hint: None,
@@ -763,12 +962,14 @@ forward slash and must not end with a slash."
 
             Error::ModuleDoesNotExist { module, suggestion } => {
                 let hint = match suggestion {
-                    Some(suggestion) => format!("Did you mean `{suggestion}`?"),
-                    None => format!("Try creating the file `src/{module}.gleam`."),
+                    Some(suggestion) => {
+                        catalog::MODULE_DOES_NOT_EXIST_SUGGESTION.render(&[("suggestion", suggestion)])
+                    }
+                    None => catalog::MODULE_DOES_NOT_EXIST_CREATE.render(&[("module", module)]),
                 };
                 vec![Diagnostic {
-                    title: "Module does not exist".into(),
-                    text: format!("Module `{module}` was not found."),
+                    title: catalog::MODULE_DOES_NOT_EXIST_TITLE.render(&[]),
+                    text: catalog::MODULE_DOES_NOT_EXIST.render(&[("module", module)]),
                     level: Level::Error,
                     location: None,
                     hint: Some(hint),
@@ -776,23 +977,18 @@ forward slash and must not end with a slash."
             }
 
             Error::ModuleDoesNotHaveMainFunction { module } => vec![Diagnostic {
-                title: "Module does not have a main function".into(),
-                text: format!(
-                    "`{module}` does not have a main function so the module can not be run."
-                ),
+                title: catalog::NO_MAIN_FUNCTION_TITLE.render(&[]),
+                text: catalog::NO_MAIN_FUNCTION.render(&[("module", module)]),
                 level: Level::Error,
                 location: None,
-                hint: Some(format!(
-                    "Add a public `main` function to \
-to `src/{module}.gleam`."
-                )),
+                hint: Some(catalog::NO_MAIN_FUNCTION_HINT.render(&[("module", module)])),
             }],
 
             Error::MainFunctionDoesNotSupportTarget { module, target } => vec![Diagnostic {
-                title: "Target not supported".into(),
-                text: wrap_format!(
-                    "`{module}` has a main function, but it does not support the {target} \
-target, so it cannot be run."
+                title: catalog::MAIN_TARGET_NOT_SUPPORTED_TITLE.render(&[]),
+                text: wrap(
+                    &catalog::MAIN_TARGET_NOT_SUPPORTED
+                        .render(&[("module", module), ("target", target)]),
                 ),
                 level: Level::Error,
                 location: None,
@@ -800,18 +996,16 @@ target, so it cannot be run."
             }],
 
             Error::MainFunctionHasWrongArity { module, arity } => vec![Diagnostic {
-                title: "Main function has wrong arity".into(),
-                text: format!(
-                    "`{module}:main` should have an arity of 0 to be run but its arity is {arity}."
-                ),
+                title: catalog::MAIN_WRONG_ARITY_TITLE.render(&[]),
+                text: catalog::MAIN_WRONG_ARITY.render(&[("module", module), ("arity", arity)]),
                 level: Level::Error,
                 location: None,
-                hint: Some("Change the function signature of main to `pub fn main() {}`.".into()),
+                hint: Some(catalog::MAIN_WRONG_ARITY_HINT.render(&[])),
             }],
 
             Error::ProjectRootAlreadyExist { path } => vec![Diagnostic {
-                title: "Project folder already exists".into(),
-                text: format!("Project folder root:\n\n  {path}"),
+                title: catalog::PROJECT_ROOT_EXISTS_TITLE.render(&[]),
+                text: catalog::PROJECT_ROOT_EXISTS.render(&[("path", path)]),
                 level: Level::Error,
                 hint: None,
                 location: None,
@@ -842,19 +1036,14 @@ If you want to overwrite these files, delete them and run the command again.
             }],
 
             Error::CannotPublishTodo { unfinished } => vec![Diagnostic {
-                title: "Cannot publish unfinished code".into(),
-                text: format!(
-                    "These modules contain todo expressions and cannot be published:
-
-{}
-
-Please remove them and try again.
-",
-                    unfinished
+                title: catalog::CANNOT_PUBLISH_TODO_TITLE.render(&[]),
+                text: catalog::CANNOT_PUBLISH_TODO.render(&[(
+                    "modules",
+                    &unfinished
                         .iter()
                         .map(|name| format!("  - {}", name.as_str()))
-                        .join("\n")
-                ),
+                        .join("\n"),
+                )]),
                 level: Level::Error,
                 hint: None,
                 location: None,
@@ -912,7 +1101,7 @@ your app.src file \"{app_ver}\"."
                 let mut text = format!("The program `{program}` was not found. Is it installed?");
 
                 match program.as_str() {
-                    "erl" | "erlc" | "escript" => text.push_str(
+                    "erl" | "erlc" | "escript" | "dialyzer" => text.push_str(
                         "
 Documentation for installing Erlang can be viewed here:
 https://gleam.run/getting-started/installing/",
@@ -921,18 +1110,28 @@ https://gleam.run/getting-started/installing/",
                         "
 Documentation for installing rebar3 can be viewed here:
 https://gleam.run/getting-started/installing/",
+                    ),
+                    "elixir" | "elixir.bat" => text.push_str(
+                        "
+Elixir is needed to compile dependencies that are built with Mix.
+Documentation for installing Elixir can be viewed here:
+https://elixir-lang.org/install.html",
                     ),
                     _ => (),
                 }
                 match (program.as_str(), env::consts::OS) {
                     // TODO: Further suggestions for other OSes?
-                    ("erl" | "erlc" | "escript", "macos") => text.push_str(
+                    ("erl" | "erlc" | "escript" | "dialyzer", "macos") => text.push_str(
                         "
 You can also install Erlang via homebrew using \"brew install erlang\"",
                     ),
                     ("rebar3", "macos") => text.push_str(
                         "
 You can also install rebar3 via homebrew using \"brew install rebar3\"",
+                    ),
+                    ("elixir", "macos") => text.push_str(
+                        "
+You can also install Elixir via homebrew using \"brew install elixir\"",
                     ),
                     _ => (),
                 };
@@ -1158,10 +1357,10 @@ Second: {second}"
                 }]
             }
 
-            Error::Type { path, src, errors: error } => error
+            Error::Type { path, src, errors: error } => group_repeated_problems(error
                 .iter()
                 .map(|error| {
-                    match error {
+                    let diagnostic = match error {
                 TypeError::SrcImportingTest {
                     location,
                     src_module,
@@ -1191,6 +1390,36 @@ modules cannot import them. Perhaps move the `{test_module}` module to the src d
                     }
                 }
 
+                TypeError::ForbiddenImport {
+                    location,
+                    module,
+                    imported_module,
+                    modules_pattern,
+                    imported_pattern,
+                } => {
+                    let text = wrap_format!(
+                        "The module `{module}` is importing the module `{imported_module}`, \
+but the import rules in gleam.toml say that modules matching `{modules_pattern}` \
+must not import modules matching `{imported_pattern}`.",
+                    );
+
+                    Diagnostic {
+                        title: "Forbidden import".into(),
+                        text,
+                        hint: None,
+                        level: Level::Error,
+                        location: Some(Location {
+                            label: Label {
+                                text: Some("Forbidden by an import rule".into()),
+                                span: *location,
+                            },
+                            path: path.clone(),
+                            src: src.clone(),
+                            extra_labels: vec![],
+                        }),
+                    }
+                }
+
                 TypeError::UnknownLabels {
                     unknown,
                     valid,
@@ -1481,10 +1710,16 @@ Hint: Add some type annotations and try again."
                     let mut printer = Printer::new();
 
                     // Give a hint about what type this value has.
-                    let mut text = format!(
-                        "The value being accessed has this type:\n\n{}\n",
-                        printer.pretty_print(typ, 4)
-                    );
+                    let mut text = match usage {
+                        FieldAccessUsage::RecordUpdate => format!(
+                            "The record being updated has this type:\n\n{}\n",
+                            printer.pretty_print(typ, 4)
+                        ),
+                        FieldAccessUsage::MethodCall | FieldAccessUsage::Other => format!(
+                            "The value being accessed has this type:\n\n{}\n",
+                            printer.pretty_print(typ, 4)
+                        ),
+                    };
 
                     // Give a hint about what record fields this value has, if any.
                     if fields.is_empty() {
@@ -1498,15 +1733,52 @@ Hint: Add some type annotations and try again."
                     }
 
                     match variants {
-                        RecordVariants::HasVariants => {
-                            let msg = wrap(
-                                "Note: The field you are trying to \
-access might not be consistently present or positioned across the custom \
-type's variants, preventing reliable access. Ensure the field exists in the \
-same position and has the same type in all variants to enable direct accessor syntax.",
-                            );
+                        RecordVariants::HasVariants {
+                            record_location,
+                            with_field,
+                            without_field,
+                        } => {
+                            let msg = if without_field.is_empty() {
+                                wrap(&format!(
+                                    "Note: Every variant of this type has the `{label}` \
+field, but it is not in the same position with the same type in each of them, \
+so it cannot be accessed directly."
+                                ))
+                            } else {
+                                wrap(&format!(
+                                    "Note: Only {with} of this type's variants \
+{have} a `{label}` field. The value could be {without}, so the field cannot be \
+accessed directly.",
+                                    with = with_field.iter().map(|v| format!("`{v}`")).join(", "),
+                                    have = if with_field.len() == 1 { "has" } else { "have" },
+                                    without = without_field
+                                        .iter()
+                                        .map(|(v, _)| format!("`{v}`"))
+                                        .join(" or "),
+                                ))
+                            };
                             text.push_str("\n\n");
                             text.push_str(&msg);
+
+                            // Suggest a case expression that gets the field
+                            // from the variants that have it.
+                            let subject = src
+                                .get(record_location.start as usize..record_location.end as usize)
+                                .unwrap_or("value");
+                            text.push_str(
+                                "\n\nHint: A case expression can be used to get the field:\n\n",
+                            );
+                            text.push_str(&format!("    case {subject} {{\n"));
+                            for variant in with_field {
+                                text.push_str(&format!(
+                                    "      {variant}({label}: {label}, ..) -> {label}\n"
+                                ));
+                            }
+                            for (variant, arity) in without_field {
+                                let fields = if *arity == 0 { "" } else { "(..)" };
+                                text.push_str(&format!("      {variant}{fields} -> todo\n"));
+                            }
+                            text.push_str("    }");
                         }
                         RecordVariants::NoVariants => (),
                     }
@@ -1525,7 +1797,7 @@ to call a method on this value you may want to use the function syntax instead."
                             text.push_str(label);
                             text.push_str("(value)");
                         }
-                        FieldAccessUsage::Other => (),
+                        FieldAccessUsage::RecordUpdate | FieldAccessUsage::Other => (),
                     }
 
                     let label = did_you_mean(label, fields)
@@ -1553,9 +1825,13 @@ to call a method on this value you may want to use the function syntax instead."
                     given,
                     situation: Some(UnifyErrorSituation::Operator(op)),
                     rigid_type_names: annotated_names,
+                    type_aliases,
                 } => {
                     let mut printer = Printer::new();
                     printer.with_names(annotated_names.clone());
+                    printer.with_aliases(type_aliases.clone());
+                    let (expected, given_text, notes) =
+                        print_mismatched_types(&mut printer, expected, given);
                     let mut text = format!(
                         "The {op} operator expects arguments of this type:
 
@@ -1563,10 +1839,8 @@ to call a method on this value you may want to use the function syntax instead."
 
 But this argument has this type:
 
-{given}\n",
+{given_text}{notes}\n",
                         op = op.name(),
-                        expected = printer.pretty_print(expected, 4),
-                        given = printer.pretty_print(given, 4),
                     );
                     if let Some(hint) = hint_alternative_operator(op, given) {
                         text.push('\n');
@@ -1594,36 +1868,57 @@ But this argument has this type:
                     location,
                     expected,
                     given,
-                    situation: Some(UnifyErrorSituation::PipeTypeMismatch),
+                    situation:
+                        Some(UnifyErrorSituation::PipeTypeMismatch {
+                            stage,
+                            types,
+                            suggested_map,
+                        }),
                     rigid_type_names: annotated_names,
+                    type_aliases,
                 } => {
-                    // Remap the pipe function type into just the type expected by the pipe.
-                    let expected = expected
-                        .fn_types()
-                        .and_then(|(args, _)| args.first().cloned());
-
-                    // Remap the argument as well, if it's a function.
-                    let given = given
-                        .fn_types()
-                        .and_then(|(args, _)| args.first().cloned())
-                        .unwrap_or_else(|| given.clone());
-
                     let mut printer = Printer::new();
                     printer.with_names(annotated_names.clone());
-                    let text = format!(
+                    printer.with_aliases(type_aliases.clone());
+                    let (expected, given, notes) =
+                        print_mismatched_types(&mut printer, expected, given);
+                    let mut text = format!(
                         "The argument is:
 
 {given}
 
 But function expects:
 
-{expected}",
-                        expected = expected
-                            .map(|v| printer.pretty_print(&v, 4))
-                            .unwrap_or_else(|| "    No arguments".into()),
-                        given = printer.pretty_print(&given, 4)
+{expected}{notes}"
                     );
 
+                    // Show the types sent between the earlier stages so it's
+                    // clear where the mismatched type came from.
+                    if *stage > 1 {
+                        text.push_str(&format!(
+                            "\n\nThis is stage {stage} of the pipeline. \
+The types sent through it so far are:\n\n"
+                        ));
+                        text.push_str(
+                            &types
+                                .iter()
+                                .enumerate()
+                                .map(|(i, type_)| {
+                                    let arrow = if i == 0 { "" } else { "|> " };
+                                    format!("    {arrow}{}", printer.pretty_print(type_, 0))
+                                })
+                                .join("\n"),
+                        );
+                    }
+
+                    if let Some(map) = suggested_map {
+                        text.push_str("\n\n");
+                        text.push_str(&wrap(&format!(
+                            "Hint: The function expects the contents of the piped \
+value. It can be applied to them with `{map}`."
+                        )));
+                    }
+
                     Diagnostic {
                         title: "Type mismatch".into(),
                         text,
@@ -1647,9 +1942,11 @@ But function expects:
                     given,
                     situation,
                     rigid_type_names: annotated_names,
+                    type_aliases,
                 } => {
                     let mut printer = Printer::new();
                     printer.with_names(annotated_names.clone());
+                    printer.with_aliases(type_aliases.clone());
                     let mut text = if let Some(description) = situation.as_ref().and_then(|s| s.description()) {
                         let mut text = description.to_string();
                         text.push('\n');
@@ -1658,10 +1955,13 @@ But function expects:
                     } else {
                         "".into()
                     };
+                    let (expected, given, notes) =
+                        print_mismatched_types(&mut printer, expected, given);
                     text.push_str("Expected type:\n\n");
-                    text.push_str(&printer.pretty_print(expected, 4));
+                    text.push_str(&expected);
                     text.push_str("\n\nFound type:\n\n");
-                    text.push_str(&printer.pretty_print(given, 4));
+                    text.push_str(&given);
+                    text.push_str(&notes);
                     Diagnostic {
                         title: "Type mismatch".into(),
                         text,
@@ -2904,9 +3204,10 @@ See: https://tour.gleam.run/advanced-features/use/");
                         }),
                     }
                 },
-            }
+            };
+                    (error.problem_key(), diagnostic)
                 })
-                .collect_vec(),
+                .collect_vec()),
 
             Error::Parse { path, src, error } => {
                 let (label, extra) = error.details();
@@ -2938,11 +3239,23 @@ See: https://tour.gleam.run/advanced-features/use/");
                 }]
             }
 
-            Error::ImportCycle { modules } => {
+            Error::ImportCycle { modules, imports } => {
                 let mut text = "The import statements for these modules form a cycle:
 "
                 .into();
                 write_cycle(&mut text, modules);
+                if !imports.is_empty() {
+                    text.push_str("\nThe cycle is formed by these imports:\n\n");
+                    for import in imports {
+                        let CycleImport {
+                            path,
+                            line,
+                            statement,
+                        } = import;
+                        text.push_str(&format!("    {path}:{line}: {statement}\n"));
+                    }
+                    text.push('\n');
+                }
                 text.push_str(
                     "Gleam doesn't support dependency cycles like these, please break the
 cycle to continue.",
@@ -3044,6 +3357,94 @@ but it cannot be found."
                 }]
             }
 
+            Error::UnorganizedImports { files } => {
+                let files: Vec<_> = files.iter().map(|p| format!("  - {p}")).sorted().collect();
+                let mut text = files.iter().join("\n");
+                text.push('\n');
+                vec![Diagnostic {
+                    title: "These files have imports that have not been organized".into(),
+                    text,
+                    hint: Some("Run `gleam organize-imports` to organize them.".into()),
+                    location: None,
+                    level: Level::Error,
+                }]
+            }
+
+            Error::LintsFound { count } => {
+                let text = match count {
+                    1 => "1 lint was found in the project.".into(),
+                    _ => format!("{count} lints were found in the project."),
+                };
+                vec![Diagnostic {
+                    title: "Lints found".into(),
+                    text,
+                    hint: None,
+                    location: None,
+                    level: Level::Error,
+                }]
+            }
+
+            Error::DialyzerWarnings { count } => {
+                let text = match count {
+                    1 => "Dialyzer found 1 problem in the project.".into(),
+                    _ => format!("Dialyzer found {count} problems in the project."),
+                };
+                vec![Diagnostic {
+                    title: "Dialyzer warnings".into(),
+                    text,
+                    hint: None,
+                    location: None,
+                    level: Level::Error,
+                }]
+            }
+
+            Error::MutationTestsFailing => vec![Diagnostic {
+                title: "Tests failing".into(),
+                text: "The tests of the project fail before any change is made to its code, \
+so they cannot show which changes they catch."
+                    .into(),
+                hint: Some("Run `gleam test` and fix the failing tests.".into()),
+                location: None,
+                level: Level::Error,
+            }],
+
+            Error::MutantsSurvived { count } => {
+                let text = match count {
+                    1 => "The tests pass with 1 change made to the code of the project.".into(),
+                    _ => format!(
+                        "The tests pass with {count} changes made to the code of the project."
+                    ),
+                };
+                vec![Diagnostic {
+                    title: "Mutants survived".into(),
+                    text,
+                    hint: None,
+                    location: None,
+                    level: Level::Error,
+                }]
+            }
+
+            Error::NotAProjectModule { path } => vec![Diagnostic {
+                title: "Not a module of this project".into(),
+                text: format!(
+                    "`{path}` is not a Gleam module in the `src` or `test` directory
+of this project."
+                ),
+                hint: None,
+                location: None,
+                level: Level::Error,
+            }],
+
+            Error::DaemonCompilation { diagnostics } => diagnostics.clone(),
+
+            Error::DaemonFailed { reason } => vec![Diagnostic {
+                title: "Daemon failure".into(),
+                text: format!("The `gleam daemon` for this project failed:\n\n    {reason}"),
+                hint: None,
+                location: None,
+                level: Level::Error,
+            }],
+
             Error::ForbiddenWarnings { count } => {
                 let word_warning = match count {
                     1 => "warning",
@@ -3131,6 +3532,89 @@ The error from the parser was:
                 }]
             }
 
+            Error::InvalidPackageConfig {
+                path,
+                src,
+                problems,
+            } => {
+                let file = path.file_name().unwrap_or("gleam.toml");
+                problems
+                .iter()
+                .map(|problem| {
+                    let key = &problem.key;
+                    let (title, text, label, hint) = match &problem.kind {
+                        ProblemKind::Syntax { error } => (
+                            "Invalid TOML".into(),
+                            format!("The {file} file could not be parsed: {error}."),
+                            "Syntax error".to_string(),
+                            None,
+                        ),
+                        ProblemKind::UnknownKey { hint } => (
+                            format!("Unknown key in {file}"),
+                            format!("`{key}` is not a key Gleam knows about."),
+                            "Unknown key".to_string(),
+                            hint.clone(),
+                        ),
+                        ProblemKind::MissingKey { name } => {
+                            let table = if key.is_empty() {
+                                "The package".into()
+                            } else {
+                                format!("`{key}`")
+                            };
+                            (
+                                format!("Missing key in {file}"),
+                                format!("{table} must have the `{name}` key."),
+                                format!("Missing `{name}`"),
+                                None,
+                            )
+                        }
+                        ProblemKind::TypeMismatch { expected, found } => (
+                            format!("Invalid value in {file}"),
+                            format!("`{key}` must be {expected}, but it is {found}."),
+                            format!("Expected {expected}"),
+                            None,
+                        ),
+                        ProblemKind::InvalidVersion { version, error } => (
+                            format!("Invalid version in {file}"),
+                            format!("`{version}` is not a valid version: {error}"),
+                            "Invalid version".to_string(),
+                            Some("Versions are written as `major.minor.patch`, such as `1.0.0`.".into()),
+                        ),
+                        ProblemKind::InvalidVersionRange { range, error } => (
+                            format!("Invalid version requirement in {file}"),
+                            format!("`{range}` is not a valid version requirement: {error}"),
+                            "Invalid version requirement".to_string(),
+                            Some(
+                                "Version requirements are written such as `>= 1.0.0 and < 2.0.0`."
+                                    .into(),
+                            ),
+                        ),
+                        ProblemKind::InvalidValue { error, hint } => (
+                            format!("Invalid value in {file}"),
+                            format!("`{key}` is invalid. {error}"),
+                            "Invalid value".to_string(),
+                            hint.clone(),
+                        ),
+                    };
+                    Diagnostic {
+                        title,
+                        text,
+                        hint,
+                        level: Level::Error,
+                        location: Some(Location {
+                            label: Label {
+                                text: Some(label),
+                                span: problem.location,
+                            },
+                            path: path.clone(),
+                            src: src.clone(),
+                            extra_labels: vec![],
+                        }),
+                    }
+                })
+                .collect()
+            }
+
             Error::DependencyCanonicalizationFailed(package) => {
                 let text = format!("Local package `{package}` has no canonical path");
 
@@ -3144,31 +3628,148 @@ The error from the parser was:
             }
 
             Error::DependencyResolutionFailed(error) => {
-                let text = format!(
-                    "An error occurred while determining what dependency packages and
-versions should be downloaded.
-The error from the version resolver library was:
+                vec![dependency_resolution_failed_diagnostic(error, None)]
+            }
 
-{}",
-                    wrap(error)
+            Error::DependencyResolutionFailedWithFixes { error, fixes } => {
+                let fixes = fixes.iter().map(|fix| format!("  - {fix}")).join("\n");
+                let hint = format!(
+                    "These changes would let the dependencies be resolved:
+
+{fixes}
+
+Run `gleam deps fix --apply` to make them."
                 );
+                vec![dependency_resolution_failed_diagnostic(error, Some(hint))]
+            }
+
+            Error::GitCheckoutFailed {
+                package,
+                repo,
+                reference,
+            } => vec![Diagnostic {
+                title: "Failed to fetch git dependency".into(),
+                text: format!(
+                    "`{reference}` of the git repository {repo} could not be checked out \
+for the `{package}` package."
+                ),
+                hint: Some(
+                    "Check the repository exists and that the branch, tag, or commit \
+is in it. If a commit pinned in manifest.toml is no longer in the repository \
+run `gleam deps update` with the package to update it."
+                        .into(),
+                ),
+                location: None,
+                level: Level::Error,
+            }],
+
+            Error::DependencyVerificationFailed { problems } => {
+                let text = match problems {
+                    1 => "1 problem was found with the dependencies in manifest.toml.".into(),
+                    _ => format!(
+                        "{problems} problems were found with the dependencies in manifest.toml."
+                    ),
+                };
                 vec![Diagnostic {
-                    title: "Dependency resolution failed".into(),
+                    title: "Dependency verification failed".into(),
                     text,
-                    hint: None,
+                    hint: Some(
+                        "See the report above. `gleam deps download` replaces packages \
+that are missing or have been modified."
+                            .into(),
+                    ),
                     location: None,
                     level: Level::Error,
                 }]
             }
 
-            Error::GitDependencyUnsupported => vec![Diagnostic {
-                title: "Git dependencies are not currently supported".into(),
-                text: "Please remove all git dependencies from the gleam.toml file".into(),
+            Error::GitCommitMismatch {
+                package,
+                expected,
+                found,
+            } => vec![Diagnostic {
+                title: "Git dependency commit mismatch".into(),
+                text: format!(
+                    "The `{package}` package is pinned to commit {expected} in \
+manifest.toml but commit {found} was checked out."
+                ),
+                hint: Some(format!(
+                    "Run `gleam deps update {package}` to pin it to its latest commit."
+                )),
+                location: None,
+                level: Level::Error,
+            }],
+
+            Error::InvalidHexOrganization { organization } => vec![Diagnostic {
+                title: "Invalid Hex organization".into(),
+                text: wrap_format!(
+                    "`{organization}` is not a valid name for a Hex organization. \
+Organization names may only contain lowercase letters, numbers, and \
+underscores."
+                ),
                 hint: None,
                 location: None,
                 level: Level::Error,
             }],
 
+            Error::HexOrganizationKeyMissing { organization } => {
+                let variable = format!("HEXPM_ORGANIZATION_KEY_{}", organization.to_uppercase());
+                vec![Diagnostic {
+                    title: "Missing Hex organization key".into(),
+                    text: wrap_format!(
+                        "Packages of the Hex organization `{organization}` can only be \
+used with an API key, but none was found."
+                    ),
+                    hint: Some(format!(
+                        "Set the {variable} or HEXPM_API_KEY environment variable to \
+a key with access to the organization."
+                    )),
+                    location: None,
+                    level: Level::Error,
+                }]
+            }
+
+            Error::ProvenanceKeyMissing => vec![Diagnostic {
+                title: "Missing provenance key".into(),
+                text: wrap(
+                    "A provenance attestation is signed with a key given in the \
+GLEAM_PROVENANCE_KEY environment variable, but it is not set.",
+                ),
+                hint: Some(
+                    "Create a key with `gleam hex provenance-key` and set the \
+GLEAM_PROVENANCE_KEY environment variable to it."
+                        .into(),
+                ),
+                location: None,
+                level: Level::Error,
+            }],
+
+            Error::InvalidProvenanceKey => vec![Diagnostic {
+                title: "Invalid provenance key".into(),
+                text: wrap(
+                    "The GLEAM_PROVENANCE_KEY environment variable is not a hex \
+encoded Ed25519 key in PKCS#8 format.",
+                ),
+                hint: Some(
+                    "Create a key with `gleam hex provenance-key` and set the \
+GLEAM_PROVENANCE_KEY environment variable to it."
+                        .into(),
+                ),
+                location: None,
+                level: Level::Error,
+            }],
+
+            Error::PackageNotDownloaded { package, version } => vec![Diagnostic {
+                title: "Package not downloaded".into(),
+                text: wrap_format!(
+                    "The tarball of `{package}` v{version}, which manifest.toml \
+requires, is not in the package cache."
+                ),
+                hint: Some("Run `gleam deps download` to download it.".into()),
+                location: None,
+                level: Level::Error,
+            }],
+
             Error::WrongDependencyProvided {
                 path,
                 expected,
@@ -3301,6 +3902,21 @@ issue in our tracker: https://github.com/gleam-lang/gleam/issues",
                 }]
             }
 
+            Error::FailedToServeDocs { address, error } => {
+                let text = format!(
+                    "An error occurred while trying to serve the docs on {address}:
+
+    {error}"
+                );
+                vec![Diagnostic {
+                    title: "Failed to serve docs".into(),
+                    text,
+                    hint: Some("Use the `--port` flag to serve the docs on another port.".into()),
+                    level: Level::Error,
+                    location: None,
+                }]
+            }
+
             Error::IncompatibleCompilerVersion {
                 package,
                 required_version,
@@ -3384,6 +4000,344 @@ or you can publish it using a different version number"),
                 location: None,
                 hint: Some("Please add the --replace flag if you want to replace the release.".into()),
             }],
+
+            Error::HexTwoFactorCodeRequired { rejected } => {
+                let text = if *rejected {
+                    "The two-factor authentication code given was not accepted by Hex."
+                } else {
+                    "Hex requires a two-factor authentication code to sign in to this \
+account."
+                };
+                vec![Diagnostic {
+                    title: "Two-factor authentication failed".into(),
+                    text: text.into(),
+                    hint: Some(
+                        "Enter the current code from your authenticator app, or set \
+HEXPM_API_KEY to an existing API key."
+                            .into(),
+                    ),
+                    level: Level::Error,
+                    location: None,
+                }]
+            }
+
+            Error::HexRevertWindowClosed { package, version } => vec![Diagnostic {
+                title: "Release can no longer be reverted".into(),
+                text: wrap_format!("{package} v{version} was published too long ago \
+to be reverted. Hex only lets a release be reverted within an hour of \
+publishing it, or within a day of first publishing the package."),
+                level: Level::Error,
+                location: None,
+                hint: Some(format!("Use `gleam hex retire {package} {version}` to \
+discourage people from using it instead.")),
+            }],
+
+            Error::DocsAlreadyPublished { package, version } => vec![Diagnostic {
+                title: "Docs already published".into(),
+                text: wrap_format!("The docs of {package} v{version} have already \
+been published to HexDocs."),
+                level: Level::Error,
+                location: None,
+                hint: Some("Use `gleam docs publish --replace` to replace them.".into()),
+            }],
+
+            Error::ChangelogEntryMissing { path, version } => vec![Diagnostic {
+                title: "Changelog entry missing".into(),
+                text: wrap_format!("{path} has no heading for version {version}, so \
+the changes in this release have not been written down."),
+                level: Level::Error,
+                location: None,
+                hint: Some(format!("Add a heading such as `## v{version}` above the \
+changes in this release, or rename the heading of the unreleased changes.")),
+            }],
+
+            Error::ReleaseFromDirtyWorkTree => vec![Diagnostic {
+                title: "Uncommitted changes".into(),
+                text: wrap("The git work tree has changes that have not been \
+committed. A release is made from the last commit, so these changes would \
+not be part of it."),
+                level: Level::Error,
+                location: None,
+                hint: Some("Commit or stash the changes, then try again.".into()),
+            }],
+
+            Error::GitTagExists { tag } => vec![Diagnostic {
+                title: "Release already tagged".into(),
+                text: wrap_format!("The git tag `{tag}` already exists, so this \
+version has already been released."),
+                level: Level::Error,
+                location: None,
+                hint: None,
+            }],
+
+            Error::UnresolvedExampleDependency { example, package } => vec![Diagnostic {
+                title: "Unresolved example dependency".into(),
+                text: wrap_format!("The example `{example}` depends on the package \
+`{package}`, but it is not one of the packages resolved for this project. \
+Examples are compiled with the dependencies of the project they are in."),
+                level: Level::Error,
+                location: None,
+                hint: Some(format!("Add `{package}` to the dev-dependencies of this project.")),
+            }],
+
+            Error::UnknownErlangApplication {
+                field,
+                name,
+                options,
+            } => vec![Diagnostic {
+                title: "Unknown Erlang application".into(),
+                text: wrap_format!(
+                    "The application `{name}` in `erlang.{field}` of gleam.toml \
+is not a package in this project or an application that comes with Erlang \
+or Elixir."
+                ),
+                level: Level::Error,
+                location: None,
+                hint: did_you_mean(name, options).or_else(|| {
+                    Some(format!(
+                        "If `{name}` is provided by a package, add it to the \
+dependencies in gleam.toml."
+                    ))
+                }),
+            }],
+
+            Error::InvalidErlangAppEnv { key } => vec![Diagnostic {
+                title: "Invalid Erlang application environment".into(),
+                text: wrap_format!(
+                    "The value of `{key}` in `erlang.env` of gleam.toml can't be \
+represented in Erlang, which has no infinite or NaN floats."
+                ),
+                level: Level::Error,
+                location: None,
+                hint: None,
+            }],
+
+            Error::UnknownRuntimeConfigApplication {
+                path,
+                name,
+                options,
+            } => vec![Diagnostic {
+                title: "Unknown application in config".into(),
+                text: wrap_format!(
+                    "The config file {path} configures the application `{name}`, \
+which is not a package in this project or an application that comes with \
+Erlang or Elixir."
+                ),
+                level: Level::Error,
+                location: None,
+                hint: did_you_mean(name, options),
+            }],
+
+            Error::UnknownFeature {
+                package,
+                feature,
+                required_by,
+                options,
+            } => {
+                let text = if package == required_by {
+                    wrap_format!(
+                        "The package `{package}` enables the feature `{feature}`, \
+but its gleam.toml does not define a feature with that name."
+                    )
+                } else {
+                    wrap_format!(
+                        "The package `{required_by}` enables the feature \
+`{feature}` of its dependency `{package}`, but `{package}` does not define a \
+feature with that name."
+                    )
+                };
+                vec![Diagnostic {
+                    title: "Unknown feature".into(),
+                    text,
+                    level: Level::Error,
+                    location: None,
+                    hint: did_you_mean(feature, options),
+                }]
+            }
+
+            Error::InvalidEnvConstantName { name } => vec![Diagnostic {
+                title: "Invalid env constant name".into(),
+                text: wrap_format!(
+                    "The `{name}` key in the `[env]` section of gleam.toml can't \
+be used as the name of a Gleam constant."
+                ),
+                level: Level::Error,
+                location: None,
+                hint: Some(
+                    "Constant names start with a lowercase letter and contain \
+only lowercase letters, numbers, and underscores."
+                        .into(),
+                ),
+            }],
+
+            Error::UnknownJavaScriptEntrypoint { module, options } => vec![Diagnostic {
+                title: "Unknown JavaScript entrypoint".into(),
+                text: wrap_format!(
+                    "The `entrypoints` of the `[javascript]` section of \
+gleam.toml include `{module}`, but the package has no module with that name."
+                ),
+                level: Level::Error,
+                location: None,
+                hint: did_you_mean(module, options),
+            }],
+
+            Error::UnknownFeatureAttribute { details } => {
+                let UnknownFeatureDetails {
+                    feature,
+                    options,
+                    location,
+                    path,
+                    src,
+                } = details.as_ref();
+                vec![Diagnostic {
+                    title: "Unknown feature".into(),
+                    text: wrap_format!(
+                        "This definition belongs to the feature `{feature}`, \
+but the package's gleam.toml does not define a feature with that name."
+                    ),
+                    hint: did_you_mean(feature, options).or_else(|| {
+                        Some(format!(
+                            "Define the feature with a `[features.{feature}]` \
+table in gleam.toml."
+                        ))
+                    }),
+                    level: Level::Error,
+                    location: Some(Location {
+                        label: Label {
+                            text: None,
+                            span: *location,
+                        },
+                        path: path.clone(),
+                        src: src.clone(),
+                        extra_labels: vec![],
+                    }),
+                }]
+            }
+
+            Error::UnstableFeature { feature } => vec![Diagnostic {
+                title: "Unstable feature".into(),
+                text: wrap_format!(
+                    "{feature} is unstable, so it may change or be removed in \
+a future version of Gleam."
+                ),
+                level: Level::Error,
+                location: None,
+                hint: Some("Run the command with the `--unstable` flag to use it.".into()),
+            }],
+
+            Error::WasmUnsupported { details } => {
+                let WasmUnsupportedDetails {
+                    feature,
+                    location,
+                    path,
+                    src,
+                } = details.as_ref();
+                vec![Diagnostic {
+                    title: "Unsupported by the Wasm target".into(),
+                    text: wrap_format!(
+                        "The Wasm target is experimental and doesn't support \
+{feature} yet."
+                    ),
+                    hint: None,
+                    level: Level::Error,
+                    location: Some(Location {
+                        label: Label {
+                            text: None,
+                            span: *location,
+                        },
+                        path: path.clone(),
+                        src: src.clone(),
+                        extra_labels: vec![],
+                    }),
+                }]
+            }
+
+            Error::UnknownErlangExternal { details } => {
+                let UnknownErlangExternalDetails {
+                    module,
+                    function,
+                    arity,
+                    exported_arities,
+                    location,
+                    path,
+                    src,
+                } = details.as_ref();
+                let text = wrap_format!(
+                    "This function is implemented by the Erlang function \
+`{module}:{function}/{arity}`, but the `{module}` module does not export \
+a function with that name and arity, so calling it would crash at runtime."
+                );
+                let hint = match exported_arities.as_slice() {
+                    [] => None,
+                    arities => Some(format!(
+                        "The module exports {}.",
+                        arities
+                            .iter()
+                            .map(|arity| format!("`{function}/{arity}`"))
+                            .join(", ")
+                    )),
+                };
+                vec![Diagnostic {
+                    title: "Unknown Erlang external function".into(),
+                    text,
+                    hint,
+                    level: Level::Error,
+                    location: Some(Location {
+                        label: Label {
+                            text: None,
+                            span: *location,
+                        },
+                        path: path.clone(),
+                        src: src.clone(),
+                        extra_labels: vec![],
+                    }),
+                }]
+            }
+
+            Error::UnknownJavaScriptExternal { details } => {
+                let UnknownJavaScriptExternalDetails {
+                    module,
+                    function,
+                    problem,
+                    location,
+                    path,
+                    src,
+                } = details.as_ref();
+                let (title, text, hint) = match problem {
+                    UnknownJavaScriptExternalProblem::FileNotFound => (
+                        "JavaScript external file not found",
+                        wrap_format!(
+                            "This function is implemented by the JavaScript \
+function `{function}` from `{module}`, but that file could not be found."
+                        ),
+                        None,
+                    ),
+                    UnknownJavaScriptExternalProblem::NotExported { exported } => (
+                        "Unknown JavaScript external function",
+                        wrap_format!(
+                            "This function is implemented by the JavaScript \
+function `{function}` from `{module}`, but that file does not export \
+anything named `{function}`."
+                        ),
+                        did_you_mean(function, exported),
+                    ),
+                };
+                vec![Diagnostic {
+                    title: title.into(),
+                    text,
+                    hint,
+                    level: Level::Error,
+                    location: Some(Location {
+                        label: Label {
+                            text: None,
+                            span: *location,
+                        },
+                        path: path.clone(),
+                        src: src.clone(),
+                        extra_labels: vec![],
+                    }),
+                }]
+            }
         }
     }
 }
@@ -3430,6 +4384,84 @@ fn write_cycle(buffer: &mut String, cycle: &[EcoString]) {
     buffer.push_str("    └─────┘\n");
 }
 
+/// The most locations of a repeated problem that are labelled on its
+/// diagnostic, besides the first one.
+const REPEATED_PROBLEM_LABELS: usize = 3;
+
+/// Reports the diagnostics of errors caused by the same problem as a single
+/// diagnostic at the first location of the problem, labelling a few of the
+/// other locations and saying how many times the problem occurs.
+fn group_repeated_problems(diagnostics: Vec<(Option<ProblemKey>, Diagnostic)>) -> Vec<Diagnostic> {
+    let mut grouped: Vec<(Diagnostic, usize)> = Vec::with_capacity(diagnostics.len());
+    let mut first_of_problem = HashMap::new();
+    for (key, diagnostic) in diagnostics {
+        let first = key.and_then(|key| match first_of_problem.entry(key) {
+            Entry::Occupied(entry) => grouped.get_mut(*entry.get()),
+            Entry::Vacant(entry) => {
+                let _ = entry.insert(grouped.len());
+                None
+            }
+        });
+        let Some((first, occurrences)) = first else {
+            grouped.push((diagnostic, 1));
+            continue;
+        };
+        *occurrences += 1;
+        if let (Some(location), Some(repeated)) = (&mut first.location, diagnostic.location) {
+            if location.extra_labels.len() < REPEATED_PROBLEM_LABELS {
+                location.extra_labels.push(Label {
+                    text: None,
+                    span: repeated.label.span,
+                });
+            }
+        }
+    }
+
+    grouped
+        .into_iter()
+        .map(|(mut diagnostic, occurrences)| {
+            if occurrences > 1 {
+                if !diagnostic.text.is_empty() {
+                    diagnostic.text.push_str("\n\n");
+                }
+                diagnostic.text.push_str(&format!(
+                    "This problem occurs {occurrences} times in this module."
+                ));
+            }
+            diagnostic
+        })
+        .collect()
+}
+
+/// Prints the expected and given types of a type mismatch, along with notes
+/// on what the type aliases used to print only one of them expand to, as the
+/// alias could hide how the types differ.
+fn print_mismatched_types(
+    printer: &mut Printer,
+    expected: &Type,
+    given: &Type,
+) -> (String, String, String) {
+    let expected = printer.pretty_print(expected, 4);
+    let expected_aliases = printer.take_used_aliases();
+    let given = printer.pretty_print(given, 4);
+    let given_aliases = printer.take_used_aliases();
+
+    let mut notes = String::new();
+    let only_expected = expected_aliases
+        .iter()
+        .filter(|alias| !given_aliases.contains(alias));
+    let only_given = given_aliases
+        .iter()
+        .filter(|alias| !expected_aliases.contains(alias));
+    for alias in only_expected.chain(only_given) {
+        if let Some(expansion) = printer.alias_expansion(alias) {
+            notes.push_str("\n\n");
+            notes.push_str(&wrap(&format!("Note: {expansion}.")));
+        }
+    }
+    (expected, given, notes)
+}
+
 fn hint_alternative_operator(op: &BinOp, given: &Type) -> Option<String> {
     match op {
         BinOp::AddInt if given.is_float() => Some(hint_numeric_message("+.", "Float")),
@@ -3468,6 +4500,16 @@ functions from the `gleam/string` module.",
     )
 }
 
+/// One of the import statements that form an import cycle.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CycleImport {
+    /// The path of the importing module, relative to the package root.
+    pub path: Utf8PathBuf,
+    pub line: u32,
+    /// The first line of the import statement.
+    pub statement: EcoString,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Unformatted {
     pub source: Utf8PathBuf,
@@ -3476,6 +4518,24 @@ pub struct Unformatted {
     pub output: String,
 }
 
+fn dependency_resolution_failed_diagnostic(error: &str, hint: Option<String>) -> Diagnostic {
+    let text = format!(
+        "An error occurred while determining what dependency packages and
+versions should be downloaded.
+The error from the version resolver library was:
+
+{}",
+        wrap(error)
+    );
+    Diagnostic {
+        title: "Dependency resolution failed".into(),
+        text,
+        hint,
+        location: None,
+        level: Level::Error,
+    }
+}
+
 pub fn wrap(text: &str) -> String {
     textwrap::fill(text, std::cmp::min(75, textwrap::termwidth()))
 }
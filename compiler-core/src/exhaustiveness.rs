@@ -781,8 +781,8 @@ impl<'a> Compiler<'a> {
     ///
     fn instantiated_custom_type_info(
         &self,
-        module: &EcoString,
-        name: &EcoString,
+        module: &str,
+        name: &str,
         type_arguments: &[Arc<Type>],
     ) -> Result<Vec<TypeValueConstructor>, UnknownTypeConstructorError> {
         let constructors = self.environment.get_constructors_for_type(module, name)?;
@@ -863,9 +863,9 @@ impl ConstructorSpecialiser {
                 args,
             } => Type::Named {
                 publicity: *publicity,
-                package: package.clone(),
-                module: module.clone(),
-                name: name.clone(),
+                package: *package,
+                module: *module,
+                name: *name,
                 args: args.iter().map(|a| self.specialise_type(a)).collect(),
             },
 
@@ -0,0 +1,389 @@
+//! Generation of skeleton Erlang and JavaScript modules for the `@external`
+//! functions of a Gleam module, used by `gleam gen ffi`.
+
+#[cfg(test)]
+mod tests;
+
+use std::{collections::HashSet, sync::OnceLock};
+
+use camino::{Utf8Path, Utf8PathBuf};
+use ecow::EcoString;
+use itertools::Itertools;
+use regex::Regex;
+
+use crate::{
+    ast::{Arg, ArgNames, Definition, UntypedFunction},
+    build::{
+        erlang_ffi::{self, Exports as ErlangExports},
+        javascript_ffi::{self, Exports as JavaScriptExports},
+        Target,
+    },
+    erlang::{escape_atom_string, variable_name},
+    io::{Content, FileSystemReader, OutputFile},
+    javascript::maybe_escape_identifier_string,
+    Error, Result,
+};
+
+/// A function that is to be implemented in a native module.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Stub {
+    name: EcoString,
+    arguments: Vec<EcoString>,
+}
+
+/// Generates the native modules needed by the `@external` functions of the
+/// Gleam module at the given path.
+///
+/// Functions that already exist are left untouched, missing functions are
+/// appended to the existing file with a body that raises an error. Only the
+/// files that need to change are returned.
+///
+/// Erlang modules are written next to the Gleam module. Externals that refer
+/// to the modules given in `external_modules`, such as those of OTP and of the
+/// package's dependencies, and JavaScript externals that do not use a relative
+/// path or refer to a file outside of `root`, the directory of sources the
+/// Gleam module is in, are skipped as they are not part of this package.
+pub fn generate<IO: FileSystemReader>(
+    io: &IO,
+    root: &Utf8Path,
+    path: &Utf8Path,
+    src: &EcoString,
+    target: Option<Target>,
+    external_modules: &HashSet<EcoString>,
+) -> Result<Vec<OutputFile>> {
+    let parsed = crate::parse::parse_module(src).map_err(|error| Error::Parse {
+        path: path.to_path_buf(),
+        src: src.clone(),
+        error,
+    })?;
+
+    let functions = parsed
+        .module
+        .definitions
+        .iter()
+        .filter_map(|definition| match &definition.definition {
+            Definition::Function(function) => Some(function),
+            _ => None,
+        })
+        .collect_vec();
+
+    let directory = path.parent().unwrap_or(Utf8Path::new(""));
+    let mut files = vec![];
+    if target != Some(Target::JavaScript) {
+        for (module, stubs) in erlang_stubs(&functions, external_modules) {
+            let path = directory.join(format!("{module}.erl"));
+            files.extend(erlang_file(io, path, &module, &stubs)?);
+        }
+    }
+    if target != Some(Target::Erlang) {
+        for (file, stubs) in javascript_stubs(root, directory, &functions) {
+            files.extend(javascript_file(io, file, &stubs)?);
+        }
+    }
+    Ok(files)
+}
+
+fn stub(name: &EcoString, arguments: &[Arg<()>]) -> Stub {
+    let arguments = arguments
+        .iter()
+        .enumerate()
+        .map(|(index, argument)| match &argument.names {
+            ArgNames::Named { name } | ArgNames::NamedLabelled { name, .. } => name.clone(),
+            ArgNames::Discard { name } | ArgNames::LabelledDiscard { name, .. } => {
+                if name == "_" {
+                    format!("_arg{index}").into()
+                } else {
+                    name.clone()
+                }
+            }
+        })
+        .collect();
+    Stub {
+        name: name.clone(),
+        arguments,
+    }
+}
+
+/// Groups the Erlang externals by the module they refer to, in the order they
+/// first appear in the Gleam module.
+fn erlang_stubs(
+    functions: &[&UntypedFunction],
+    external_modules: &HashSet<EcoString>,
+) -> Vec<(EcoString, Vec<Stub>)> {
+    let mut modules: Vec<(EcoString, Vec<Stub>)> = vec![];
+    for function in functions {
+        let Some((module, name)) = &function.external_erlang else {
+            continue;
+        };
+        if external_modules.contains(module) {
+            continue;
+        }
+        let stub = stub(name, &function.arguments);
+        match modules.iter_mut().find(|(m, _)| m == module) {
+            Some((_, stubs)) => push_unique(stubs, stub),
+            None => modules.push((module.clone(), vec![stub])),
+        }
+    }
+    modules
+}
+
+/// Groups the JavaScript externals by the file they refer to, in the order
+/// they first appear in the Gleam module.
+fn javascript_stubs(
+    root: &Utf8Path,
+    directory: &Utf8Path,
+    functions: &[&UntypedFunction],
+) -> Vec<(Utf8PathBuf, Vec<Stub>)> {
+    let mut files: Vec<(Utf8PathBuf, Vec<Stub>)> = vec![];
+    for function in functions {
+        let Some((path, name)) = &function.external_javascript else {
+            continue;
+        };
+        if !(path.starts_with("./") || path.starts_with("../")) {
+            continue;
+        }
+        let file = crate::paths::normalise(&directory.join(path.as_str()));
+        if !file.starts_with(root) {
+            continue;
+        }
+        let stub = stub(name, &function.arguments);
+        match files.iter_mut().find(|(f, _)| f == &file) {
+            Some((_, stubs)) => push_unique(stubs, stub),
+            None => files.push((file, vec![stub])),
+        }
+    }
+    files
+}
+
+fn push_unique(stubs: &mut Vec<Stub>, stub: Stub) {
+    let exists = stubs
+        .iter()
+        .any(|s| s.name == stub.name && s.arguments.len() == stub.arguments.len());
+    if !exists {
+        stubs.push(stub);
+    }
+}
+
+fn erlang_file<IO: FileSystemReader>(
+    io: &IO,
+    path: Utf8PathBuf,
+    module: &str,
+    stubs: &[Stub],
+) -> Result<Option<OutputFile>> {
+    let existing = if io.is_file(&path) {
+        Some(io.read(&path)?)
+    } else {
+        None
+    };
+
+    let missing = match &existing {
+        None => stubs.iter().collect_vec(),
+        Some(src) => match erlang_ffi::parse_exports(src, module).1 {
+            ErlangExports::All => vec![],
+            ErlangExports::Functions(exported) => stubs
+                .iter()
+                .filter(|stub| !exported.contains(&(stub.name.clone(), stub.arguments.len())))
+                .collect(),
+        },
+    };
+    if missing.is_empty() {
+        return Ok(None);
+    }
+
+    let export = format!(
+        "-export([{}]).\n",
+        missing
+            .iter()
+            .map(|stub| format!(
+                "{}/{}",
+                escape_atom_string(stub.name.to_string()),
+                stub.arguments.len()
+            ))
+            .join(", ")
+    );
+
+    // Functions that are defined but not exported only need to be exported.
+    let definitions = missing
+        .iter()
+        .filter(|stub| {
+            !existing
+                .as_deref()
+                .is_some_and(|src| erlang_defines(src, stub))
+        })
+        .map(|stub| erlang_function(stub))
+        .join("\n");
+
+    let content = match existing {
+        None => format!(
+            "-module({}).\n\n{export}\n{definitions}",
+            escape_atom_string(module.to_string())
+        ),
+        Some(src) => {
+            let (head, tail) = src.split_at(erlang_export_position(&src));
+            let mut content = format!("{head}{export}{tail}");
+            if !definitions.is_empty() {
+                if !content.ends_with('\n') {
+                    content.push('\n');
+                }
+                content.push('\n');
+                content.push_str(&definitions);
+            }
+            content
+        }
+    };
+
+    Ok(Some(OutputFile {
+        content: Content::Text(content),
+        path,
+    }))
+}
+
+fn erlang_function(stub: &Stub) -> String {
+    let name = escape_atom_string(stub.name.to_string());
+    let arguments = stub
+        .arguments
+        .iter()
+        .map(|argument| variable_name(argument))
+        .join(", ");
+    format!(
+        "{name}({arguments}) ->\n    erlang:error({{todo, <<\"{}/{} is not yet implemented\">>}}).\n",
+        stub.name,
+        stub.arguments.len()
+    )
+}
+
+/// The position after the `-module` attribute, where a new `-export`
+/// attribute can be inserted.
+fn erlang_export_position(src: &str) -> usize {
+    static MODULE: OnceLock<Regex> = OnceLock::new();
+    MODULE
+        .get_or_init(|| Regex::new(r"(?m)^-\s*module\s*\(.*?\)\s*\.[^\n]*\n?").expect("regex"))
+        .find(src)
+        .map(|found| found.end())
+        .unwrap_or(0)
+}
+
+/// Whether the Erlang source has a clause of the stub's function, with the
+/// same name and number of arguments.
+fn erlang_defines(src: &str, stub: &Stub) -> bool {
+    let name = regex::escape(&escape_atom_string(stub.name.to_string()));
+    Regex::new(&format!(r"(?m)^{name}\s*\("))
+        .expect("function definition regex")
+        .find_iter(src)
+        .any(|head| erlang_arity(&src[head.end()..]) == Some(stub.arguments.len()))
+}
+
+/// The number of arguments of a function head, given the source following
+/// its opening parenthesis, or `None` if the parenthesis is never closed.
+fn erlang_arity(src: &str) -> Option<usize> {
+    let mut depth = 0;
+    let mut commas = 0;
+    let mut empty = true;
+    let mut chars = src.chars().peekable();
+    while let Some(char) = chars.next() {
+        match char {
+            ')' | ']' | '}' if depth == 0 => return Some(if empty { 0 } else { commas + 1 }),
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' | '}' => depth -= 1,
+            '<' if chars.peek() == Some(&'<') => {
+                let _ = chars.next();
+                depth += 1;
+            }
+            '>' if chars.peek() == Some(&'>') && depth > 0 => {
+                let _ = chars.next();
+                depth -= 1;
+            }
+            ',' if depth == 0 => commas += 1,
+            '$' => {
+                let _ = chars.next();
+            }
+            '"' | '\'' => {
+                while let Some(next) = chars.next() {
+                    match next {
+                        '\\' => {
+                            let _ = chars.next();
+                        }
+                        _ if next == char => break,
+                        _ => (),
+                    }
+                }
+            }
+            '%' => {
+                for next in chars.by_ref() {
+                    if next == '\n' {
+                        break;
+                    }
+                }
+                continue;
+            }
+            _ if char.is_whitespace() => continue,
+            _ => (),
+        }
+        empty = false;
+    }
+    None
+}
+
+fn javascript_file<IO: FileSystemReader>(
+    io: &IO,
+    path: Utf8PathBuf,
+    stubs: &[Stub],
+) -> Result<Option<OutputFile>> {
+    let existing = if io.is_file(&path) {
+        Some(io.read(&path)?)
+    } else {
+        None
+    };
+
+    let missing = match existing.as_deref().map(javascript_ffi::parse_exports) {
+        None => stubs.iter().collect_vec(),
+        Some(JavaScriptExports::Unknown) => vec![],
+        Some(JavaScriptExports::Names(exported)) => stubs
+            .iter()
+            .filter(|stub| !exported.contains(&stub.name))
+            .collect(),
+    };
+    if missing.is_empty() {
+        return Ok(None);
+    }
+
+    let functions = missing
+        .iter()
+        .map(|stub| javascript_function(stub))
+        .join("\n");
+    let mut content = existing.unwrap_or_default();
+    if !content.is_empty() {
+        if !content.ends_with('\n') {
+            content.push('\n');
+        }
+        content.push('\n');
+    }
+    content.push_str(&functions);
+
+    Ok(Some(OutputFile {
+        content: Content::Text(content),
+        path,
+    }))
+}
+
+fn javascript_function(stub: &Stub) -> String {
+    let arguments = stub
+        .arguments
+        .iter()
+        .map(|argument| maybe_escape_identifier_string(argument))
+        .join(", ");
+    let body = format!(
+        "({arguments}) {{\n  throw new Error(\"todo: {} is not yet implemented\");\n}}\n",
+        stub.name
+    );
+    // A reserved word can't name a function, but it can name an export
+    let function = maybe_escape_identifier_string(&stub.name);
+    if function == stub.name {
+        format!("export function {function}{body}")
+    } else {
+        format!(
+            "function {function}{body}export {{ {function} as {} }};\n",
+            stub.name
+        )
+    }
+}
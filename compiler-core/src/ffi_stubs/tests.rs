@@ -0,0 +1,205 @@
+use super::generate;
+use crate::{
+    build::Target,
+    io::{memory::InMemoryFileSystem, Content, FileSystemWriter},
+};
+
+use std::collections::HashSet;
+
+use camino::Utf8Path;
+use itertools::Itertools;
+
+fn stubs(gleam: &str, files: &[(&str, &str)], target: Option<Target>) -> String {
+    let fs = InMemoryFileSystem::new();
+    for (path, src) in files {
+        fs.write(Utf8Path::new(path), src)
+            .expect("write native module");
+    }
+    generate(
+        &fs,
+        Utf8Path::new("/src"),
+        Utf8Path::new("/src/app.gleam"),
+        &gleam.into(),
+        target,
+        &["lists".into()].into(),
+    )
+    .expect("generate stubs")
+    .into_iter()
+    .map(|file| {
+        let Content::Text(text) = file.content else {
+            panic!("Unexpected binary file {}", file.path)
+        };
+        format!("//// {}\n{text}", file.path)
+    })
+    .join("\n")
+}
+
+const EXTERNALS: &str = r#"
+@external(erlang, "app_ffi", "wibble")
+@external(javascript, "./app_ffi.mjs", "wibble")
+pub fn wibble(a: Int, label _: Int, _ignored: Int) -> Int
+
+@external(erlang, "app_ffi", "wobble")
+@external(javascript, "./app_ffi.mjs", "wobble")
+pub fn wobble(label name: String, class: String) -> String
+
+@external(erlang, "lists", "reverse")
+@external(javascript, "./other.mjs", "reverse")
+pub fn reverse(list: List(a)) -> List(a)
+
+@external(javascript, "node:process", "exit")
+pub fn exit(code: Int) -> Nil
+"#;
+
+#[test]
+fn new_files() {
+    insta::assert_snapshot!(stubs(EXTERNALS, &[], None));
+}
+
+#[test]
+fn erlang_only() {
+    insta::assert_snapshot!(stubs(EXTERNALS, &[], Some(Target::Erlang)));
+}
+
+#[test]
+fn javascript_only() {
+    insta::assert_snapshot!(stubs(EXTERNALS, &[], Some(Target::JavaScript)));
+}
+
+#[test]
+fn existing_files_are_extended() {
+    let erlang = "-module(app_ffi).
+-export([wibble/3]).
+
+wibble(A, B, C) -> A + B + C.
+
+wobble(Name, Class) -> <<Name/binary, Class/binary>>.
+";
+    let javascript = "export function wibble(a, b, c) {
+  return a + b + c;
+}";
+    insta::assert_snapshot!(stubs(
+        EXTERNALS,
+        &[
+            ("/src/app_ffi.erl", erlang),
+            ("/src/app_ffi.mjs", javascript)
+        ],
+        None
+    ));
+}
+
+#[test]
+fn complete_files_are_not_changed() {
+    let erlang = "-module(app_ffi).\n-export([wibble/3, wobble/2]).\n";
+    let javascript = "export { wibble, wobble, reverse };";
+    assert_eq!(
+        stubs(
+            EXTERNALS,
+            &[
+                ("/src/app_ffi.erl", erlang),
+                ("/src/app_ffi.mjs", javascript),
+                ("/src/other.mjs", javascript)
+            ],
+            None
+        ),
+        ""
+    );
+}
+
+#[test]
+fn javascript_files_outside_sources_are_not_written() {
+    let gleam = r#"
+@external(javascript, "../other.mjs", "reverse")
+pub fn reverse(list: List(a)) -> List(a)
+"#;
+    assert_eq!(stubs(gleam, &[], Some(Target::JavaScript)), "");
+}
+
+#[test]
+fn javascript_files_in_parent_directories_of_sources_are_written() {
+    let gleam = r#"
+@external(javascript, "../other.mjs", "reverse")
+pub fn reverse(list: List(a)) -> List(a)
+"#;
+    let files = generate(
+        &InMemoryFileSystem::new(),
+        Utf8Path::new("/src"),
+        Utf8Path::new("/src/app/inner.gleam"),
+        &gleam.into(),
+        Some(Target::JavaScript),
+        &HashSet::new(),
+    )
+    .expect("generate stubs");
+    let paths = files.iter().map(|file| file.path.as_str()).collect_vec();
+    assert_eq!(paths, vec!["/src/other.mjs"]);
+}
+
+#[test]
+fn modules_not_in_otp_are_generated() {
+    let gleam = r#"
+@external(erlang, "lists", "reverse")
+pub fn reverse(list: List(a)) -> List(a)
+"#;
+    let files = generate(
+        &InMemoryFileSystem::new(),
+        Utf8Path::new("/src"),
+        Utf8Path::new("/src/app.gleam"),
+        &gleam.into(),
+        Some(Target::Erlang),
+        &HashSet::new(),
+    )
+    .expect("generate stubs");
+    let paths = files.iter().map(|file| file.path.as_str()).collect_vec();
+    assert_eq!(paths, vec!["/src/lists.erl"]);
+}
+
+#[test]
+fn functions_of_another_arity_are_defined() {
+    let gleam = r#"
+@external(erlang, "app_ffi", "wobble")
+pub fn wobble(a: Int, b: Int) -> Int
+"#;
+    let erlang = "-module(app_ffi).
+-export([wobble/1]).
+
+wobble({A, B}) -> <<A/binary, \"),\"/utf8>>; % (
+wobble(A) -> A.
+";
+    insta::assert_snapshot!(stubs(
+        gleam,
+        &[("/src/app_ffi.erl", erlang)],
+        Some(Target::Erlang)
+    ));
+}
+
+#[test]
+fn functions_defined_with_the_arity_are_only_exported() {
+    let gleam = r#"
+@external(erlang, "app_ffi", "wobble")
+pub fn wobble(a: Int, b: Int) -> Int
+"#;
+    let erlang = "-module(app_ffi).
+
+wobble(A) -> A.
+wobble([A, B], {C, D}) -> A + B + C + D.
+";
+    assert_eq!(
+        stubs(gleam, &[("/src/app_ffi.erl", erlang)], Some(Target::Erlang)),
+        "//// /src/app_ffi.erl
+-module(app_ffi).
+-export([wobble/2]).
+
+wobble(A) -> A.
+wobble([A, B], {C, D}) -> A + B + C + D.
+"
+    );
+}
+
+#[test]
+fn reserved_words_are_exported_under_their_own_name() {
+    let gleam = r#"
+@external(javascript, "./app_ffi.mjs", "delete")
+pub fn delete(key: String) -> Nil
+"#;
+    insta::assert_snapshot!(stubs(gleam, &[], Some(Target::JavaScript)));
+}
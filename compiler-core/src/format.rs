@@ -143,10 +143,21 @@ impl<'comments> Formatter<'comments> {
 
     fn targeted_definition<'a>(&mut self, definition: &'a TargetedDefinition) -> Document<'a> {
         let target = definition.target;
+        let feature = &definition.feature;
         let definition = &definition.definition;
         let start = definition.location().start;
         let comments = self.pop_comments(start);
         let document = self.documented_definition(definition);
+        let document = match feature {
+            None => document,
+            Some(feature) => docvec![
+                "@feature(",
+                self.string(&feature.name),
+                ")",
+                line(),
+                document
+            ],
+        };
         let document = match target {
             None => document,
             Some(Target::Erlang) => docvec!["@target(erlang)", line(), document],
@@ -741,6 +752,14 @@ impl<'comments> Formatter<'comments> {
             None => attributes,
         };
 
+        // @behaviour attribute
+        let attributes = match function.behaviour.as_ref() {
+            Some(behaviour) => {
+                attributes.append(docvec!["@behaviour(\"", behaviour, "\")", line()])
+            }
+            None => attributes,
+        };
+
         // Fn name and args
         let args = function
             .arguments
@@ -17,3 +17,18 @@ type Z {
 "
     );
 }
+
+#[test]
+fn feature() {
+    assert_format!(
+        r#"@feature("json")
+import gleam/json
+
+@target(erlang)
+@feature("json")
+pub fn to_json() {
+  json.null()
+}
+"#
+    );
+}
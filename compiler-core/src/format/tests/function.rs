@@ -21,6 +21,28 @@ pub fn main() -> Nil
     );
 }
 
+#[test]
+fn behaviour() {
+    assert_format!(
+        r#"@behaviour("gen_server")
+pub fn init(args: Int) -> Int {
+  args
+}
+"#
+    );
+}
+
+#[test]
+fn behaviour_external() {
+    assert_format!(
+        r#"@deprecated("use something else instead")
+@external(erlang, "server", "init")
+@behaviour("gen_server")
+pub fn init(args: Int) -> Int
+"#
+    );
+}
+
 #[test]
 fn anonymous_function_as_final_function_argument() {
     assert_format!(
@@ -1,16 +1,30 @@
-use camino::Utf8Path;
+pub mod docs;
+pub mod integrity;
+pub mod revert;
+pub mod snapshot;
+#[cfg(test)]
+mod tests;
+pub mod typosquat;
+
+use std::collections::HashMap;
+
+use camino::{Utf8Path, Utf8PathBuf};
 use debug_ignore::DebugIgnore;
+use ecow::EcoString;
 use flate2::read::GzDecoder;
-use futures::future;
+use futures::{stream, StreamExt};
 use hexpm::{version::Version, ApiError};
+use http::StatusCode;
 use tar::Archive;
 
 use crate::{
+    build::Telemetry,
     io::{FileSystemReader, FileSystemWriter, HttpClient, TarUnpacker},
     manifest::{ManifestPackage, ManifestPackageSource},
     paths::{self, ProjectPaths},
     Error, Result,
 };
+use integrity::Integrity;
 
 pub const HEXPM_PUBLIC_KEY: &[u8] = b"-----BEGIN PUBLIC KEY-----
 MIIBIjANBgkqhkiG9w0BAQEFAAOCAQ8AMIIBCgKCAQEApqREcFDt5vV21JVe2QNB
@@ -23,10 +37,40 @@ J1i2xWFndWa6nfFnRxZmCStCOZWYYPlaxr+FZceFbpMwzTNs4g3d4tLNUcbKAIH4
 -----END PUBLIC KEY-----
 ";
 
+/// The number of packages downloaded at once unless another limit is given.
+pub const DEFAULT_DOWNLOAD_CONCURRENCY: usize = 8;
+
+/// How much of a tarball is downloaded between reports of the progress of the
+/// download.
+const PROGRESS_INTERVAL: u64 = 64 * 1024;
+
 fn key_name(hostname: &str) -> String {
     format!("gleam-{hostname}")
 }
 
+/// The configuration for the repository of a Hex organisation, which holds
+/// the packages it publishes privately, on the Hex instance of `config`.
+pub fn organization_config(config: &hexpm::Config, organization: &str) -> Result<hexpm::Config> {
+    let valid = !organization.is_empty()
+        && organization
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_');
+    if !valid {
+        return Err(Error::InvalidHexOrganization {
+            organization: organization.into(),
+        });
+    }
+    let repository = |base: &http::Uri| {
+        format!("{base}repos/{organization}/")
+            .parse()
+            .expect("Hex organization URI")
+    };
+    Ok(hexpm::Config {
+        api_base: repository(&config.api_base),
+        repository_base: repository(&config.repository_base),
+    })
+}
+
 pub async fn publish_package<Http: HttpClient>(
     release_tarball: Vec<u8>,
     version: String,
@@ -35,7 +79,7 @@ pub async fn publish_package<Http: HttpClient>(
     replace: bool,
     http: &Http,
 ) -> Result<()> {
-    tracing::info!("Publishing package, replace: {}", replace);
+    tracing::info!(version = version.as_str(), replace, "publishing_package");
     let request = hexpm::publish_package_request(release_tarball, api_key, config, replace);
     let response = http.send(request).await?;
     hexpm::publish_package_response(response).map_err(|e| match e {
@@ -101,31 +145,79 @@ pub async fn unretire_release<Http: HttpClient>(
     hexpm::unretire_release_response(response).map_err(Error::hex)
 }
 
+/// Creates an API key, authenticating with a username and password.
+///
+/// If the account has two-factor authentication enabled Hex also requires the
+/// code from the authenticator app, given as `otp`. Without a code, or with a
+/// code Hex rejects, `Error::HexTwoFactorCodeRequired` is returned so the
+/// caller can ask for one and try again.
 pub async fn create_api_key<Http: HttpClient>(
     hostname: &str,
     username: &str,
     password: &str,
+    otp: Option<&str>,
     config: &hexpm::Config,
     http: &Http,
 ) -> Result<String> {
-    tracing::info!("Creating API key with Hex");
-    let request = hexpm::create_api_key_request(username, password, &key_name(hostname), config);
+    tracing::info!(two_factor_code = otp.is_some(), "creating_hex_api_key");
+    let mut request =
+        hexpm::create_api_key_request(username, password, &key_name(hostname), config);
+    if let Some(otp) = otp {
+        let otp = http::HeaderValue::from_str(otp)
+            .map_err(|_| Error::HexTwoFactorCodeRequired { rejected: true })?;
+        let _ = request.headers_mut().insert("x-hex-otp", otp);
+    }
     let response = http.send(request).await?;
+    if let Some(rejected) = two_factor_challenge(&response) {
+        return Err(Error::HexTwoFactorCodeRequired { rejected });
+    }
     hexpm::create_api_key_response(response).map_err(Error::hex)
 }
 
+/// Whether Hex refused a request because it needs a two-factor authentication
+/// code, and if so whether a code was given but rejected.
+fn two_factor_challenge(response: &http::Response<Vec<u8>>) -> Option<bool> {
+    if response.status() != StatusCode::UNAUTHORIZED {
+        return None;
+    }
+    let challenge = response
+        .headers()
+        .get(http::header::WWW_AUTHENTICATE)?
+        .to_str()
+        .ok()?;
+    if !challenge.contains("totp") {
+        return None;
+    }
+    Some(challenge.contains("invalid"))
+}
+
 pub async fn remove_api_key<Http: HttpClient>(
     hostname: &str,
     config: &hexpm::Config,
     auth_key: &str,
     http: &Http,
 ) -> Result<()> {
-    tracing::info!("Deleting API key from Hex");
+    tracing::info!("deleting_hex_api_key");
     let request = hexpm::remove_api_key_request(&key_name(hostname), auth_key, config);
     let response = http.send(request).await?;
     hexpm::remove_api_key_response(response).map_err(Error::hex)
 }
 
+/// The size of the whole tarball being downloaded, given by the
+/// `Content-Range` header of a resumed download or the `Content-Length`
+/// header otherwise.
+fn download_size(parts: &http::response::Parts) -> Option<u64> {
+    let header = |name| parts.headers.get(name)?.to_str().ok();
+    match parts.status {
+        StatusCode::PARTIAL_CONTENT => header(http::header::CONTENT_RANGE)?
+            .rsplit_once('/')?
+            .1
+            .parse()
+            .ok(),
+        _ => header(http::header::CONTENT_LENGTH)?.parse().ok(),
+    }
+}
+
 #[derive(Debug)]
 pub struct Downloader {
     fs_reader: DebugIgnore<Box<dyn FileSystemReader>>,
@@ -133,6 +225,8 @@ pub struct Downloader {
     http: DebugIgnore<Box<dyn HttpClient>>,
     untar: DebugIgnore<Box<dyn TarUnpacker>>,
     hex_config: hexpm::Config,
+    /// The API key used for the packages of each Hex organisation.
+    organization_keys: HashMap<EcoString, String>,
     paths: ProjectPaths,
 }
 
@@ -150,32 +244,60 @@ impl Downloader {
             http: DebugIgnore(http),
             untar: DebugIgnore(untar),
             hex_config: hexpm::Config::new(),
+            organization_keys: HashMap::new(),
             paths,
         }
     }
 
+    /// Sets the API keys used to download the packages of Hex organisations.
+    pub fn with_organization_keys(mut self, keys: HashMap<EcoString, String>) -> Self {
+        self.organization_keys = keys;
+        self
+    }
+
+    /// The configuration and API key used to download a package from the Hex
+    /// repository it was published to.
+    fn repository(&self, package: &ManifestPackage) -> Result<(hexpm::Config, Option<&str>)> {
+        match &package.source {
+            ManifestPackageSource::Hex {
+                organization: Some(organization),
+                ..
+            } => {
+                let key = self.organization_keys.get(organization).ok_or_else(|| {
+                    Error::HexOrganizationKeyMissing {
+                        organization: organization.clone(),
+                    }
+                })?;
+                let config = organization_config(&self.hex_config, organization)?;
+                Ok((config, Some(key.as_str())))
+            }
+            _ => Ok((self.hex_config.clone(), None)),
+        }
+    }
+
+    /// Downloads the tarball of a package to the global cache if it is not
+    /// there already, returning its size if it was downloaded.
     pub async fn ensure_package_downloaded(
         &self,
         package: &ManifestPackage,
-    ) -> Result<bool, Error> {
-        let outer_checksum = if let ManifestPackageSource::Hex { outer_checksum } = &package.source
-        {
-            outer_checksum
-        } else {
-            panic!("Attempt to download non-hex package from hex")
-        };
+        telemetry: &(dyn Telemetry + Sync),
+    ) -> Result<Option<u64>, Error> {
+        let outer_checksum =
+            if let ManifestPackageSource::Hex { outer_checksum, .. } = &package.source {
+                outer_checksum
+            } else {
+                panic!("Attempt to download non-hex package from hex")
+            };
 
-        let tarball_path = paths::global_package_cache_package_tarball(
-            &package.name,
-            &package.version.to_string(),
-        );
+        let version = package.version.to_string();
+        let tarball_path = paths::global_package_cache_package_tarball(&package.name, &version);
         if self.fs_reader.is_file(&tarball_path) {
             tracing::info!(
                 package = package.name.as_str(),
                 version = %package.version,
                 "package_in_cache"
             );
-            return Ok(false);
+            return Ok(None);
         }
         tracing::info!(
             package = &package.name.as_str(),
@@ -183,47 +305,175 @@ impl Downloader {
             "downloading_package_to_cache"
         );
 
-        let request = hexpm::get_package_tarball_request(
+        let partial_path =
+            paths::global_package_cache_package_partial_tarball(&package.name, &version);
+        let checksum = &outer_checksum.0;
+        let tarball = match self
+            .download_tarball(package, checksum, &partial_path, telemetry)
+            .await?
+        {
+            Some(tarball) => tarball,
+            // The part kept from an earlier download could not be used, so
+            // the tarball is downloaded again from the start.
+            None => {
+                self.fs_writer.delete_file(&partial_path)?;
+                self.download_tarball(package, checksum, &partial_path, telemetry)
+                    .await?
+                    .ok_or_else(|| Error::DownloadPackageError {
+                        package_name: package.name.to_string(),
+                        package_version: version.clone(),
+                        error: "the tarball could not be downloaded".into(),
+                    })?
+            }
+        };
+        self.fs_writer.write_bytes(&tarball_path, &tarball)?;
+        self.fs_writer.delete_file(&partial_path)?;
+
+        let size = tarball.len() as u64;
+        telemetry.package_downloaded(&package.name, size);
+        Ok(Some(size))
+    }
+
+    /// Downloads the tarball of a package, continuing from the part kept from
+    /// an earlier download if there is one. If the connection is lost the part
+    /// received so far is kept to be continued from later.
+    ///
+    /// Returns `None` if a download was continued but the part kept could not
+    /// be used, either because Hex could not send the rest of it or because
+    /// the whole tarball did not match its checksum.
+    async fn download_tarball(
+        &self,
+        package: &ManifestPackage,
+        checksum: &[u8],
+        partial_path: &Utf8Path,
+        telemetry: &(dyn Telemetry + Sync),
+    ) -> Result<Option<Vec<u8>>> {
+        let mut downloaded = if self.fs_reader.is_file(partial_path) {
+            self.fs_reader.read_bytes(partial_path)?
+        } else {
+            vec![]
+        };
+
+        let (config, api_key) = self.repository(package)?;
+        let mut request = hexpm::get_package_tarball_request(
             &package.name,
             &package.version.to_string(),
-            None,
-            &self.hex_config,
+            api_key,
+            &config,
         );
-        let response = self.http.send(request).await?;
+        if !downloaded.is_empty() {
+            tracing::info!(
+                package = package.name.as_str(),
+                version = %package.version,
+                bytes = downloaded.len(),
+                "resuming_package_download"
+            );
+            let range = http::HeaderValue::from_str(&format!("bytes={}-", downloaded.len()))
+                .expect("Range header value");
+            let _ = request.headers_mut().insert(http::header::RANGE, range);
+        }
 
-        let tarball =
-            hexpm::get_package_tarball_response(response, &outer_checksum.0).map_err(|error| {
-                Error::DownloadPackageError {
-                    package_name: package.name.to_string(),
-                    package_version: package.version.to_string(),
-                    error: error.to_string(),
+        let mut status = None;
+        let mut received = vec![];
+        let mut reported = 0;
+        let resumed_from = downloaded.len() as u64;
+        let result = self
+            .http
+            .send_streaming(request, &mut |parts, chunk| {
+                status = Some(parts.status);
+                received.extend_from_slice(chunk);
+                if parts.status.is_success() {
+                    let progress = match parts.status {
+                        StatusCode::PARTIAL_CONTENT => resumed_from + received.len() as u64,
+                        _ => received.len() as u64,
+                    };
+                    if progress >= reported + PROGRESS_INTERVAL {
+                        reported = progress;
+                        telemetry.package_download_progress(
+                            &package.name,
+                            progress,
+                            download_size(parts),
+                        );
+                    }
                 }
-            })?;
-        self.fs_writer.write_bytes(&tarball_path, &tarball)?;
-        Ok(true)
+                Ok(())
+            })
+            .await;
+
+        let mut parts = match result {
+            Ok(parts) => parts,
+            Err(error) => {
+                match status {
+                    Some(StatusCode::PARTIAL_CONTENT) => {
+                        downloaded.extend(received);
+                        self.fs_writer.write_bytes(partial_path, &downloaded)?;
+                    }
+                    Some(StatusCode::OK) if !received.is_empty() => {
+                        self.fs_writer.write_bytes(partial_path, &received)?;
+                    }
+                    _ => (),
+                }
+                return Err(error);
+            }
+        };
+
+        let resumed = parts.status == StatusCode::PARTIAL_CONTENT;
+        let tarball = match parts.status {
+            StatusCode::PARTIAL_CONTENT => {
+                parts.status = StatusCode::OK;
+                downloaded.extend(received);
+                downloaded
+            }
+            StatusCode::RANGE_NOT_SATISFIABLE => return Ok(None),
+            _ => received,
+        };
+        let response = http::Response::from_parts(parts, tarball);
+        match hexpm::get_package_tarball_response(response, checksum) {
+            Ok(tarball) => Ok(Some(tarball)),
+            Err(_) if resumed => Ok(None),
+            Err(error) => Err(Error::DownloadPackageError {
+                package_name: package.name.to_string(),
+                package_version: package.version.to_string(),
+                error: error.to_string(),
+            }),
+        }
     }
 
+    /// Downloads a package if it is not in the global cache and extracts it to
+    /// the build directory, returning the size of the download if there was
+    /// one.
     pub async fn ensure_package_in_build_directory(
         &self,
         package: &ManifestPackage,
-    ) -> Result<bool> {
-        let _ = self.ensure_package_downloaded(package).await?;
-        self.extract_package_from_cache(&package.name, &package.version)
+        telemetry: &(dyn Telemetry + Sync),
+    ) -> Result<Option<u64>> {
+        let size = self.ensure_package_downloaded(package, telemetry).await?;
+        let _ = self.extract_package_from_cache(package)?;
+        Ok(size)
     }
 
     // It would be really nice if this was async but the library is sync
-    pub fn extract_package_from_cache(&self, name: &str, version: &Version) -> Result<bool> {
+    pub fn extract_package_from_cache(&self, package: &ManifestPackage) -> Result<bool> {
+        let name = package.name.as_str();
         let contents_path = Utf8Path::new("contents.tar.gz");
-        let destination = self.paths.build_packages_package(name);
+        let destination = self.paths.build_packages_package_source(package);
 
-        // If the directory already exists then there's nothing for us to do
-        if self.fs_reader.is_directory(&destination) {
+        // If the package has been extracted already and its files have not
+        // been changed then there's nothing for us to do
+        if integrity::check(&**self.fs_reader, &destination)? == Integrity::Intact {
             tracing::info!(package = name, "Package already in build directory");
             return Ok(false);
         }
 
+        // Remove any other release of the package, and whatever is left of
+        // this one
+        for directory in extracted_package_directories(&**self.fs_reader, &self.paths, name)? {
+            self.fs_writer.delete_directory(&directory)?;
+        }
+
         tracing::info!(package = name, "writing_package_to_target");
-        let tarball = paths::global_package_cache_package_tarball(name, &version.to_string());
+        let tarball =
+            paths::global_package_cache_package_tarball(name, &package.version.to_string());
         let reader = self.fs_reader.reader(&tarball)?;
         let mut archive = Archive::new(reader);
 
@@ -241,7 +491,14 @@ impl Downloader {
                 // that was partially written so that we don't mistakenly think
                 // the operation succeeded next time we run.
                 return match result {
-                    Ok(()) => Ok(true),
+                    Ok(()) => {
+                        integrity::write_record(
+                            &**self.fs_reader,
+                            &**self.fs_writer,
+                            &destination,
+                        )?;
+                        Ok(true)
+                    }
                     Err(err) => {
                         self.fs_writer.delete_directory(&destination)?;
                         Err(err)
@@ -255,24 +512,67 @@ impl Downloader {
         })
     }
 
+    /// Downloads and extracts packages, with at most `concurrency` downloads
+    /// at once, returning the total size of the tarballs downloaded.
     pub async fn download_hex_packages<'a, Packages: Iterator<Item = &'a ManifestPackage>>(
         &self,
         packages: Packages,
         project_name: &str,
-    ) -> Result<()> {
+        concurrency: usize,
+        telemetry: &(dyn Telemetry + Sync),
+    ) -> Result<u64> {
         let futures = packages
             .filter(|package| project_name != package.name)
-            .map(|package| self.ensure_package_in_build_directory(package));
+            .map(|package| self.ensure_package_in_build_directory(package, telemetry));
 
         // Run the futures to download the packages concurrently
-        let results = future::join_all(futures).await;
+        let results: Vec<_> = stream::iter(futures)
+            .buffer_unordered(concurrency.max(1))
+            .collect()
+            .await;
 
-        // Count the number of packages downloaded while checking for errors
+        // Add up the size of the packages downloaded while checking for errors
+        let mut size = 0;
         for result in results {
-            let _ = result?;
+            size += result?.unwrap_or_default();
+        }
+        Ok(size)
+    }
+}
+
+/// The directories in the build directory that releases of a package have
+/// been extracted to.
+pub fn extracted_package_directories(
+    io: &dyn FileSystemReader,
+    paths: &ProjectPaths,
+    name: &str,
+) -> Result<Vec<Utf8PathBuf>> {
+    let packages = paths.build_packages_directory();
+    if !io.is_directory(&packages) {
+        return Ok(vec![]);
+    }
+    let prefix = format!("{name}-");
+    let mut directories = vec![];
+    for entry in io.read_dir(&packages)?.into_iter().flatten() {
+        let path = entry.into_path();
+        let Some(child) = path
+            .strip_prefix(&packages)
+            .ok()
+            .and_then(|relative| relative.components().next())
+        else {
+            continue;
+        };
+        // Hex package names cannot contain `-`, so this cannot match the
+        // directory of another package.
+        let child = child.as_str();
+        if child == name || child.starts_with(&prefix) {
+            let directory = packages.join(child);
+            if io.is_directory(&directory) && !directories.contains(&directory) {
+                directories.push(directory);
+            }
         }
-        Ok(())
     }
+    Ok(directories)
 }
 
 pub async fn publish_documentation<Http: HttpClient>(
@@ -293,6 +593,7 @@ pub async fn publish_documentation<Http: HttpClient>(
 pub async fn get_package_release<Http: HttpClient>(
     name: &str,
     version: &Version,
+    api_key: Option<&str>,
     config: &hexpm::Config,
     http: &Http,
 ) -> Result<hexpm::Release<hexpm::ReleaseMeta>> {
@@ -302,7 +603,7 @@ pub async fn get_package_release<Http: HttpClient>(
         version = version.as_str(),
         "looking_up_package_release"
     );
-    let request = hexpm::get_package_release_request(name, &version, None, config);
+    let request = hexpm::get_package_release_request(name, &version, api_key, config);
     let response = http.send(request).await?;
     hexpm::get_package_release_response(response).map_err(Error::hex)
 }
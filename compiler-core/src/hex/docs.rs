@@ -0,0 +1,78 @@
+//! The documentation published to HexDocs for the releases of a package.
+//!
+//! Hex lets the docs of a release be published again at any time, replacing
+//! the docs that were published before, and lets them be removed.
+
+#[cfg(test)]
+mod tests;
+
+use hexpm::ApiError;
+use http::StatusCode;
+
+use crate::{io::HttpClient, Error, Result};
+
+/// A release of a package and whether it has docs published to HexDocs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReleaseDocs {
+    pub version: String,
+    pub has_docs: bool,
+}
+
+/// Looks up which releases of a package have docs published, newest first.
+pub async fn get_release_docs<Http: HttpClient>(
+    name: &str,
+    api_key: Option<&str>,
+    config: &hexpm::Config,
+    http: &Http,
+) -> Result<Vec<ReleaseDocs>> {
+    tracing::info!(name = name, "looking_up_release_docs");
+    let mut request = http::Request::get(format!("{}packages/{name}", config.api_base))
+        .header("accept", "application/json")
+        .header("user-agent", concat!("gleam/", env!("CARGO_PKG_VERSION")));
+    if let Some(api_key) = api_key {
+        request = request.header("authorization", api_key);
+    }
+    let request = request
+        .body(vec![])
+        .map_err(|e| Error::Hex(e.to_string()))?;
+    let response = http.send(request).await?;
+    match response.status() {
+        StatusCode::OK => parse_release_docs(response.body()),
+        StatusCode::NOT_FOUND => Err(Error::hex(ApiError::NotFound)),
+        StatusCode::TOO_MANY_REQUESTS => Err(Error::hex(ApiError::RateLimited)),
+        status => Err(Error::hex(ApiError::UnexpectedResponse(
+            status,
+            String::from_utf8_lossy(response.body()).to_string(),
+        ))),
+    }
+}
+
+/// Reads which releases have docs from the description of the package given
+/// by the Hex API, ordered from the newest version to the oldest.
+fn parse_release_docs(body: &[u8]) -> Result<Vec<ReleaseDocs>> {
+    #[derive(serde::Deserialize)]
+    struct Package {
+        releases: Vec<Release>,
+    }
+
+    #[derive(serde::Deserialize)]
+    struct Release {
+        version: String,
+        #[serde(default)]
+        has_docs: bool,
+    }
+
+    let package: Package = serde_json::from_slice(body).map_err(Error::hex)?;
+    let mut releases: Vec<ReleaseDocs> = package
+        .releases
+        .into_iter()
+        .map(|release| ReleaseDocs {
+            version: release.version,
+            has_docs: release.has_docs,
+        })
+        .collect();
+    releases.sort_by_cached_key(|release| {
+        std::cmp::Reverse(hexpm::version::Version::parse(&release.version).ok())
+    });
+    Ok(releases)
+}
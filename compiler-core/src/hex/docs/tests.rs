@@ -0,0 +1,42 @@
+use super::*;
+
+#[test]
+fn parse_release_docs_newest_first() {
+    let body = br#"{
+        "name": "wibble",
+        "releases": [
+            {"version": "1.2.0", "has_docs": false},
+            {"version": "1.10.0", "has_docs": true},
+            {"version": "1.0.0", "has_docs": true}
+        ]
+    }"#;
+    assert_eq!(
+        parse_release_docs(body).expect("releases"),
+        vec![
+            ReleaseDocs {
+                version: "1.10.0".into(),
+                has_docs: true,
+            },
+            ReleaseDocs {
+                version: "1.2.0".into(),
+                has_docs: false,
+            },
+            ReleaseDocs {
+                version: "1.0.0".into(),
+                has_docs: true,
+            },
+        ]
+    );
+}
+
+#[test]
+fn parse_release_docs_without_docs_field() {
+    let body = br#"{"releases": [{"version": "1.0.0"}]}"#;
+    assert_eq!(
+        parse_release_docs(body).expect("releases"),
+        vec![ReleaseDocs {
+            version: "1.0.0".into(),
+            has_docs: false,
+        }]
+    );
+}
@@ -0,0 +1,151 @@
+//! Records of the files extracted from the tarball of a package, used to
+//! detect when they have been changed since.
+//!
+//! Each file is recorded with its modification time and a hash of its
+//! contents. Checking the files only reads those whose modification time has
+//! changed, so unchanged packages can be checked on every build.
+
+#[cfg(test)]
+mod tests;
+
+use std::{collections::BTreeMap, time::SystemTime};
+
+use camino::{Utf8Path, Utf8PathBuf};
+
+use crate::{
+    error::{FileIoAction, FileKind},
+    io::{FileSystemReader, FileSystemWriter},
+    Error, Result,
+};
+
+/// The name of the file the record is kept in, within the directory of the
+/// package.
+const RECORD_FILE: &str = ".gleam-integrity.toml";
+
+/// The directory Mix compiles a package into, which is expected to change.
+const MIX_BUILD_DIRECTORY: &str = "_build";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Integrity {
+    /// The files are the same as when they were extracted.
+    Intact,
+    /// The package has not been extracted, or there is no usable record of
+    /// its files.
+    Missing,
+    /// These files have been changed, added, or deleted since the package was
+    /// extracted.
+    Modified { files: Vec<Utf8PathBuf> },
+}
+
+#[derive(Debug, Default, serde::Serialize, serde::Deserialize)]
+struct Record {
+    files: BTreeMap<Utf8PathBuf, FileRecord>,
+}
+
+#[derive(Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+struct FileRecord {
+    /// Nanoseconds since the Unix epoch.
+    modified: u64,
+    hash: String,
+}
+
+/// Records the files of a package that has just been extracted to
+/// `directory`.
+pub fn write_record(
+    reader: &dyn FileSystemReader,
+    writer: &dyn FileSystemWriter,
+    directory: &Utf8Path,
+) -> Result<()> {
+    let mut record = Record::default();
+    for path in files(reader, directory)? {
+        let full_path = directory.join(&path);
+        let file = FileRecord {
+            modified: modified(reader, &full_path)?,
+            hash: hash(&reader.read_bytes(&full_path)?),
+        };
+        let _ = record.files.insert(path, file);
+    }
+    let toml = toml::to_string(&record).expect("integrity record serialization");
+    writer.write(&directory.join(RECORD_FILE), &toml)
+}
+
+/// Checks whether the files of the package extracted to `directory` have
+/// changed since they were recorded.
+pub fn check(reader: &dyn FileSystemReader, directory: &Utf8Path) -> Result<Integrity> {
+    let record_path = directory.join(RECORD_FILE);
+    if !reader.is_file(&record_path) {
+        return Ok(Integrity::Missing);
+    }
+    // A record that cannot be read is no better than no record at all.
+    let Ok(mut record) = toml::from_str::<Record>(&reader.read(&record_path)?) else {
+        return Ok(Integrity::Missing);
+    };
+
+    let mut changed = vec![];
+    for path in files(reader, directory)? {
+        let full_path = directory.join(&path);
+        let unchanged = match record.files.remove(&path) {
+            None => false,
+            Some(file) if file.modified == modified(reader, &full_path)? => true,
+            // The file has been touched, but it may still have the same
+            // contents.
+            Some(file) => file.hash == hash(&reader.read_bytes(&full_path)?),
+        };
+        if !unchanged {
+            changed.push(path);
+        }
+    }
+    // Any files left in the record have been deleted.
+    changed.extend(record.files.into_keys());
+
+    if changed.is_empty() {
+        Ok(Integrity::Intact)
+    } else {
+        changed.sort();
+        Ok(Integrity::Modified { files: changed })
+    }
+}
+
+/// The paths of the files in `directory`, relative to it, leaving out the
+/// record itself and the build directory of Mix.
+pub fn files(reader: &dyn FileSystemReader, directory: &Utf8Path) -> Result<Vec<Utf8PathBuf>> {
+    let mut files = vec![];
+    let mut directories = vec![directory.to_path_buf()];
+    while let Some(current) = directories.pop() {
+        for entry in reader.read_dir(&current)? {
+            let path = entry
+                .map_err(|e| Error::FileIo {
+                    action: FileIoAction::Read,
+                    kind: FileKind::Directory,
+                    path: current.clone(),
+                    err: Some(e.to_string()),
+                })?
+                .into_path();
+            let relative = path
+                .strip_prefix(directory)
+                .expect("path within package directory")
+                .to_path_buf();
+            if relative == Utf8Path::new(RECORD_FILE) || relative.starts_with(MIX_BUILD_DIRECTORY) {
+                continue;
+            }
+            if reader.is_file(&path) {
+                files.push(relative);
+            } else if reader.is_directory(&path) {
+                directories.push(path);
+            }
+        }
+    }
+    Ok(files)
+}
+
+fn modified(reader: &dyn FileSystemReader, path: &Utf8Path) -> Result<u64> {
+    let time = reader.modification_time(path)?;
+    let since_epoch = time
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default();
+    Ok(since_epoch.as_nanos() as u64)
+}
+
+fn hash(bytes: &[u8]) -> String {
+    format!("{:016x}", xxhash_rust::xxh3::xxh3_64(bytes))
+}
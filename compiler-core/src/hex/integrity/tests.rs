@@ -0,0 +1,98 @@
+use std::time::{Duration, SystemTime};
+
+use camino::{Utf8Path, Utf8PathBuf};
+
+use super::*;
+use crate::io::memory::InMemoryFileSystem;
+
+const DIRECTORY: &str = "/app/build/packages/wibble-0102";
+
+fn extracted_package() -> InMemoryFileSystem {
+    let fs = InMemoryFileSystem::new();
+    for (path, content) in [
+        ("gleam.toml", "name = \"wibble\""),
+        ("src/wibble.gleam", "pub fn main() { 1 }"),
+        ("src/wibble/wobble.gleam", "pub fn wobble() { 2 }"),
+    ] {
+        fs.write(&Utf8Path::new(DIRECTORY).join(path), content)
+            .expect("write");
+    }
+    write_record(&fs, &fs, DIRECTORY.into()).expect("write record");
+    fs
+}
+
+fn edit(fs: &InMemoryFileSystem, path: &str, content: &str) {
+    let path = Utf8Path::new(DIRECTORY).join(path);
+    fs.write(&path, content).expect("write");
+    fs.set_modification_time(&path, SystemTime::UNIX_EPOCH + Duration::from_secs(1));
+}
+
+fn modified(files: &[&str]) -> Integrity {
+    Integrity::Modified {
+        files: files.iter().map(Utf8PathBuf::from).collect(),
+    }
+}
+
+#[test]
+fn not_extracted() {
+    let fs = InMemoryFileSystem::new();
+    assert_eq!(check(&fs, DIRECTORY.into()), Ok(Integrity::Missing));
+}
+
+#[test]
+fn extracted_without_record() {
+    let fs = extracted_package();
+    fs.delete_file(&Utf8Path::new(DIRECTORY).join(RECORD_FILE))
+        .expect("delete");
+    assert_eq!(check(&fs, DIRECTORY.into()), Ok(Integrity::Missing));
+}
+
+#[test]
+fn unreadable_record() {
+    let fs = extracted_package();
+    fs.write(&Utf8Path::new(DIRECTORY).join(RECORD_FILE), "files = 1")
+        .expect("write");
+    assert_eq!(check(&fs, DIRECTORY.into()), Ok(Integrity::Missing));
+}
+
+#[test]
+fn intact() {
+    let fs = extracted_package();
+    assert_eq!(check(&fs, DIRECTORY.into()), Ok(Integrity::Intact));
+}
+
+#[test]
+fn touched_without_changes() {
+    let fs = extracted_package();
+    edit(&fs, "src/wibble.gleam", "pub fn main() { 1 }");
+    assert_eq!(check(&fs, DIRECTORY.into()), Ok(Integrity::Intact));
+}
+
+#[test]
+fn file_changed() {
+    let fs = extracted_package();
+    edit(&fs, "src/wibble/wobble.gleam", "pub fn wobble() { 3 }");
+    assert_eq!(
+        check(&fs, DIRECTORY.into()),
+        Ok(modified(&["src/wibble/wobble.gleam"]))
+    );
+}
+
+#[test]
+fn files_added_and_deleted() {
+    let fs = extracted_package();
+    edit(&fs, "src/wibble/wubble.gleam", "pub fn wubble() { 4 }");
+    fs.delete_file(&Utf8Path::new(DIRECTORY).join("gleam.toml"))
+        .expect("delete");
+    assert_eq!(
+        check(&fs, DIRECTORY.into()),
+        Ok(modified(&["gleam.toml", "src/wibble/wubble.gleam"]))
+    );
+}
+
+#[test]
+fn mix_build_directory_is_ignored() {
+    let fs = extracted_package();
+    edit(&fs, "_build/prod/lib/wibble/ebin/wibble.beam", "");
+    assert_eq!(check(&fs, DIRECTORY.into()), Ok(Integrity::Intact));
+}
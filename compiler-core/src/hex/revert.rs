@@ -0,0 +1,136 @@
+//! The window after publishing in which Hex lets a release be reverted,
+//! deleting it as if it had never been published.
+//!
+//! A release can be reverted for an hour after it is published, or for a day
+//! after its package was first published if that is later, so mistakes in a
+//! new package can be fixed for longer.
+
+#[cfg(test)]
+mod tests;
+
+use std::time::{Duration, SystemTime};
+
+use hexpm::ApiError;
+use http::StatusCode;
+
+use crate::{io::HttpClient, Error, Result};
+
+/// How long after a release is published that it can be reverted.
+pub const RELEASE_WINDOW: Duration = Duration::from_secs(60 * 60);
+
+/// How long after a package is first published that its releases can be
+/// reverted.
+pub const NEW_PACKAGE_WINDOW: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// When a release, and the package it belongs to, were published.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Publication {
+    pub package: SystemTime,
+    pub release: SystemTime,
+}
+
+impl Publication {
+    /// The time after which Hex no longer lets the release be reverted.
+    pub fn revert_deadline(&self) -> SystemTime {
+        (self.release + RELEASE_WINDOW).max(self.package + NEW_PACKAGE_WINDOW)
+    }
+}
+
+/// Looks up when a release and its package were published.
+pub async fn get_publication<Http: HttpClient>(
+    name: &str,
+    version: &str,
+    api_key: Option<&str>,
+    config: &hexpm::Config,
+    http: &Http,
+) -> Result<Publication> {
+    tracing::info!(name = name, version = version, "looking_up_publication");
+    let mut request = http::Request::get(format!("{}packages/{name}", config.api_base))
+        .header("accept", "application/json")
+        .header("user-agent", concat!("gleam/", env!("CARGO_PKG_VERSION")));
+    if let Some(api_key) = api_key {
+        request = request.header("authorization", api_key);
+    }
+    let request = request
+        .body(vec![])
+        .map_err(|e| Error::Hex(e.to_string()))?;
+    let response = http.send(request).await?;
+    match response.status() {
+        StatusCode::OK => parse_publication(response.body(), version),
+        StatusCode::NOT_FOUND => Err(Error::hex(ApiError::NotFound)),
+        StatusCode::TOO_MANY_REQUESTS => Err(Error::hex(ApiError::RateLimited)),
+        status => Err(Error::hex(ApiError::UnexpectedResponse(
+            status,
+            String::from_utf8_lossy(response.body()).to_string(),
+        ))),
+    }
+}
+
+/// Reads when a release and its package were published from the description
+/// of the package given by the Hex API.
+fn parse_publication(body: &[u8], version: &str) -> Result<Publication> {
+    #[derive(serde::Deserialize)]
+    struct Package {
+        inserted_at: String,
+        releases: Vec<Release>,
+    }
+
+    #[derive(serde::Deserialize)]
+    struct Release {
+        version: String,
+        inserted_at: String,
+    }
+
+    let package: Package = serde_json::from_slice(body).map_err(Error::hex)?;
+    let release = package
+        .releases
+        .iter()
+        .find(|release| release.version == version)
+        .ok_or_else(|| Error::hex(ApiError::NotFound))?;
+    let timestamp = |timestamp: &str| {
+        parse_timestamp(timestamp)
+            .ok_or_else(|| Error::Hex(format!("Invalid timestamp {timestamp}")))
+    };
+    Ok(Publication {
+        package: timestamp(&package.inserted_at)?,
+        release: timestamp(&release.inserted_at)?,
+    })
+}
+
+/// Parses a UTC timestamp as written by the Hex API, such as
+/// `2024-05-01T12:34:56.123456Z`. Fractions of a second are ignored.
+fn parse_timestamp(timestamp: &str) -> Option<SystemTime> {
+    let timestamp = timestamp.strip_suffix('Z').unwrap_or(timestamp);
+    let (date, time) = timestamp.split_once('T')?;
+    let time = time.split_once('.').map_or(time, |(time, _)| time);
+    let number = |part: Option<&str>| part?.parse::<i64>().ok();
+
+    let mut date = date.split('-');
+    let (year, month, day) = (
+        number(date.next())?,
+        number(date.next())?,
+        number(date.next())?,
+    );
+    let mut time = time.split(':');
+    let (hour, minute, second) = (
+        number(time.next())?,
+        number(time.next())?,
+        number(time.next())?,
+    );
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) || hour > 23 || minute > 59 {
+        return None;
+    }
+
+    // The number of days since 1970-01-01 of the date in the proleptic
+    // Gregorian calendar, counting years from March so leap days come last.
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = era * 146_097 + day_of_era - 719_468;
+
+    let seconds = days * 86_400 + hour * 3_600 + minute * 60 + second;
+    let seconds = u64::try_from(seconds).ok()?;
+    Some(SystemTime::UNIX_EPOCH + Duration::from_secs(seconds))
+}
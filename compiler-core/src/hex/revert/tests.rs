@@ -0,0 +1,85 @@
+use std::time::{Duration, SystemTime};
+
+use super::*;
+
+fn at(seconds: u64) -> SystemTime {
+    SystemTime::UNIX_EPOCH + Duration::from_secs(seconds)
+}
+
+#[test]
+fn parse_timestamp_epoch() {
+    assert_eq!(parse_timestamp("1970-01-01T00:00:00Z"), Some(at(0)));
+}
+
+#[test]
+fn parse_timestamp_with_fraction() {
+    assert_eq!(
+        parse_timestamp("2024-05-01T12:34:56.123456Z"),
+        Some(at(1_714_566_896))
+    );
+}
+
+#[test]
+fn parse_timestamp_leap_day() {
+    assert_eq!(
+        parse_timestamp("2024-02-29T00:00:00Z"),
+        Some(at(1_709_164_800))
+    );
+}
+
+#[test]
+fn parse_timestamp_invalid() {
+    assert_eq!(parse_timestamp("2024-05-01"), None);
+    assert_eq!(parse_timestamp("2024-13-01T00:00:00Z"), None);
+    assert_eq!(parse_timestamp("2024-05-01T25:00:00Z"), None);
+    assert_eq!(parse_timestamp("yesterday"), None);
+}
+
+#[test]
+fn revert_deadline_of_new_package() {
+    let publication = Publication {
+        package: at(0),
+        release: at(60 * 60),
+    };
+    assert_eq!(publication.revert_deadline(), at(24 * 60 * 60));
+}
+
+#[test]
+fn revert_deadline_of_old_package() {
+    let publication = Publication {
+        package: at(0),
+        release: at(7 * 24 * 60 * 60),
+    };
+    assert_eq!(
+        publication.revert_deadline(),
+        at(7 * 24 * 60 * 60 + 60 * 60)
+    );
+}
+
+#[test]
+fn parse_publication_finds_release() {
+    let body = br#"{
+        "name": "wibble",
+        "inserted_at": "1970-01-01T00:00:00.000000Z",
+        "releases": [
+            {"version": "1.1.0", "inserted_at": "1970-01-02T00:00:00.000000Z"},
+            {"version": "1.0.0", "inserted_at": "1970-01-01T00:00:00.000000Z"}
+        ]
+    }"#;
+    assert_eq!(
+        parse_publication(body, "1.1.0").expect("publication"),
+        Publication {
+            package: at(0),
+            release: at(24 * 60 * 60),
+        }
+    );
+}
+
+#[test]
+fn parse_publication_unknown_release() {
+    let body = br#"{"inserted_at": "1970-01-01T00:00:00Z", "releases": []}"#;
+    assert_eq!(
+        parse_publication(body, "1.0.0"),
+        Err(Error::hex(ApiError::NotFound))
+    );
+}
@@ -0,0 +1,116 @@
+//! Snapshots of the parts of a Hex registry that a project needs, written by
+//! `gleam registry snapshot` so that its dependencies can be resolved and
+//! downloaded without access to Hex, such as in an air-gapped network.
+//!
+//! A snapshot is a directory with the layout of a Hex repository: the signed
+//! metadata of each package is in `packages/<name>` and its tarballs are in
+//! `tarballs/<name>-<version>.tar`, with the packages of a Hex organisation
+//! under `repos/<organisation>/`. The responses of the Hex API used to look up
+//! releases are kept under `api/`. The files are kept as Hex sent them, so
+//! the signatures and checksums are checked as usual when they are used.
+
+use camino::{Utf8Path, Utf8PathBuf};
+use http::StatusCode;
+
+use crate::{
+    io::{FileSystemWriter, HttpClient},
+    manifest::{ManifestPackage, ManifestPackageSource},
+    Error, Result,
+};
+
+/// The directory of a snapshot that the responses of the Hex API are kept in.
+const API_DIRECTORY: &str = "api";
+
+/// The path of the file in a snapshot that answers a request to Hex, relative
+/// to the root of the snapshot. `config` is the configuration of public Hex,
+/// whose URLs those of organisations start with.
+///
+/// Returns `None` if the request is not to Hex, or if its path could name a
+/// file outside of the snapshot.
+///
+pub fn path_for_request(config: &hexpm::Config, uri: &http::Uri) -> Option<Utf8PathBuf> {
+    let uri = uri.to_string();
+    let repository = config.repository_base.to_string();
+    let api = config.api_base.to_string();
+    let mut path = Utf8PathBuf::new();
+    let rest = match uri.strip_prefix(&repository) {
+        Some(rest) => rest,
+        None => {
+            path.push(API_DIRECTORY);
+            uri.strip_prefix(&api)?
+        }
+    };
+    let rest = rest.split(['?', '#']).next().unwrap_or_default();
+    for segment in rest.split('/') {
+        if segment.is_empty() || segment == "." || segment == ".." || segment.contains('\\') {
+            return None;
+        }
+        path.push(segment);
+    }
+    Some(path)
+}
+
+/// Writes the files that resolving and downloading the package need to the
+/// snapshot in `directory`: the metadata of the package, the release used,
+/// and its tarball, which must match the checksum in the manifest. Returns
+/// the size of the tarball.
+///
+/// `config` and `api_key` are those of the repository the package is in,
+/// which is public Hex unless the package is private to an organisation.
+///
+pub async fn snapshot_package<Http: HttpClient>(
+    http: &Http,
+    writer: &dyn FileSystemWriter,
+    directory: &Utf8Path,
+    config: &hexpm::Config,
+    api_key: Option<&str>,
+    package: &ManifestPackage,
+) -> Result<u64> {
+    let ManifestPackageSource::Hex { outer_checksum, .. } = &package.source else {
+        return Ok(0);
+    };
+    let name = package.name.as_str();
+    let version = package.version.to_string();
+    let error = |error: String| Error::DownloadPackageError {
+        package_name: name.into(),
+        package_version: version.clone(),
+        error,
+    };
+    tracing::info!(
+        package = name,
+        version = version.as_str(),
+        "snapshotting_package"
+    );
+
+    let requests = [
+        hexpm::get_package_request(name, api_key, config),
+        hexpm::get_package_release_request(name, &version, api_key, config),
+    ];
+    for request in requests {
+        let path = file_path(directory, &request).ok_or_else(|| error(unexpected(&request)))?;
+        let response = http.send(request).await?;
+        if response.status() != StatusCode::OK {
+            return Err(error(format!("Hex responded with {}", response.status())));
+        }
+        writer.write_bytes(&path, response.body())?;
+    }
+
+    let request = hexpm::get_package_tarball_request(name, &version, api_key, config);
+    let path = file_path(directory, &request).ok_or_else(|| error(unexpected(&request)))?;
+    let response = http.send(request).await?;
+    let tarball = hexpm::get_package_tarball_response(response, &outer_checksum.0)
+        .map_err(|e| error(e.to_string()))?;
+    writer.write_bytes(&path, &tarball)?;
+    Ok(tarball.len() as u64)
+}
+
+fn file_path(directory: &Utf8Path, request: &http::Request<Vec<u8>>) -> Option<Utf8PathBuf> {
+    path_for_request(&hexpm::Config::new(), request.uri()).map(|path| directory.join(path))
+}
+
+fn unexpected(request: &http::Request<Vec<u8>>) -> String {
+    format!(
+        "{} is not a Hex URL that can be kept in a snapshot",
+        request.uri()
+    )
+}
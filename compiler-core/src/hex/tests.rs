@@ -0,0 +1,480 @@
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+};
+
+use async_trait::async_trait;
+use camino::{Utf8Path, Utf8PathBuf};
+use http::{response::Parts, Request, Response, StatusCode};
+
+use super::*;
+use crate::{
+    build::NullTelemetry,
+    io::{memory::InMemoryFileSystem, WrappedReader},
+    manifest::Base16Checksum,
+};
+
+// The SHA-256 checksum of "abc".
+const ABC_CHECKSUM: &str = "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad";
+
+#[derive(Debug)]
+struct FakeResponse {
+    status: StatusCode,
+    headers: Vec<(&'static str, String)>,
+    chunks: Vec<&'static [u8]>,
+    /// Whether the connection is lost after the chunks are sent.
+    lost: bool,
+}
+
+impl FakeResponse {
+    fn new(status: StatusCode, chunks: Vec<&'static [u8]>) -> Self {
+        Self {
+            status,
+            headers: vec![],
+            chunks,
+            lost: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct FakeHttpClient {
+    responses: Arc<Mutex<VecDeque<FakeResponse>>>,
+    /// The `Range` header of each request sent.
+    ranges: Arc<Mutex<Vec<Option<String>>>>,
+    requests: Arc<Mutex<Vec<Sent>>>,
+    /// The two-factor authentication code of each request sent.
+    otps: Arc<Mutex<Vec<Option<String>>>>,
+}
+
+/// The URI and `Authorization` header of a request sent.
+type Sent = (String, Option<String>);
+
+impl FakeHttpClient {
+    fn new(responses: Vec<FakeResponse>) -> Self {
+        Self {
+            responses: Arc::new(Mutex::new(responses.into())),
+            ranges: Arc::new(Mutex::new(vec![])),
+            requests: Arc::new(Mutex::new(vec![])),
+            otps: Arc::new(Mutex::new(vec![])),
+        }
+    }
+
+    fn otps(&self) -> Vec<Option<String>> {
+        self.otps.lock().expect("otps").clone()
+    }
+
+    fn requests(&self) -> Vec<Sent> {
+        self.requests.lock().expect("requests").clone()
+    }
+
+    fn ranges(&self) -> Vec<Option<String>> {
+        self.ranges.lock().expect("ranges").clone()
+    }
+
+    fn next_response(&self, request: &Request<Vec<u8>>) -> FakeResponse {
+        let range = request
+            .headers()
+            .get(http::header::RANGE)
+            .map(|value| value.to_str().expect("range").to_string());
+        self.ranges.lock().expect("ranges").push(range);
+        let authorization = request
+            .headers()
+            .get(http::header::AUTHORIZATION)
+            .map(|value| value.to_str().expect("authorization").to_string());
+        self.requests
+            .lock()
+            .expect("requests")
+            .push((request.uri().to_string(), authorization));
+        let otp = request
+            .headers()
+            .get("x-hex-otp")
+            .map(|value| value.to_str().expect("otp").to_string());
+        self.otps.lock().expect("otps").push(otp);
+        self.responses
+            .lock()
+            .expect("responses")
+            .pop_front()
+            .expect("no response left")
+    }
+}
+
+#[async_trait]
+impl HttpClient for FakeHttpClient {
+    async fn send(&self, request: Request<Vec<u8>>) -> Result<Response<Vec<u8>>> {
+        let response = self.next_response(&request);
+        let mut builder = Response::builder().status(response.status);
+        for (name, value) in response.headers {
+            builder = builder.header(name, value);
+        }
+        Ok(builder.body(response.chunks.concat()).expect("response"))
+    }
+
+    async fn send_streaming(
+        &self,
+        request: Request<Vec<u8>>,
+        on_chunk: &mut (dyn for<'a> FnMut(&'a Parts, &'a [u8]) -> Result<()> + Send),
+    ) -> Result<Parts> {
+        let response = self.next_response(&request);
+        let mut builder = Response::builder().status(response.status);
+        for (name, value) in response.headers {
+            builder = builder.header(name, value);
+        }
+        let (parts, ()) = builder.body(()).expect("response").into_parts();
+        for chunk in response.chunks {
+            on_chunk(&parts, chunk)?;
+        }
+        if response.lost {
+            return Err(Error::Http("connection lost".into()));
+        }
+        Ok(parts)
+    }
+}
+
+#[derive(Debug)]
+struct NoTar;
+
+impl TarUnpacker for NoTar {
+    fn io_result_entries<'a>(
+        &self,
+        _archive: &'a mut Archive<WrappedReader>,
+    ) -> std::io::Result<tar::Entries<'a, WrappedReader>> {
+        unreachable!("tarballs are not unpacked in these tests")
+    }
+
+    fn io_result_unpack(
+        &self,
+        _path: &Utf8Path,
+        _archive: Archive<GzDecoder<tar::Entry<'_, WrappedReader>>>,
+    ) -> std::io::Result<()> {
+        unreachable!("tarballs are not unpacked in these tests")
+    }
+}
+
+fn package() -> ManifestPackage {
+    ManifestPackage {
+        name: "abc".into(),
+        version: Version::new(1, 0, 0),
+        build_tools: vec!["gleam".into()],
+        otp_app: None,
+        requirements: vec![],
+        source: ManifestPackageSource::Hex {
+            outer_checksum: Base16Checksum(
+                base16::decode(ABC_CHECKSUM.as_bytes()).expect("checksum"),
+            ),
+            organization: None,
+        },
+    }
+}
+
+fn organization_package() -> ManifestPackage {
+    let mut package = package();
+    if let ManifestPackageSource::Hex { organization, .. } = &mut package.source {
+        *organization = Some("acme".into());
+    }
+    package
+}
+
+fn downloader(fs: &InMemoryFileSystem, http: &FakeHttpClient) -> Downloader {
+    Downloader::new(
+        Box::new(fs.clone()),
+        Box::new(fs.clone()),
+        Box::new(http.clone()),
+        Box::new(NoTar),
+        ProjectPaths::new("/project".into()),
+    )
+}
+
+fn download(fs: &InMemoryFileSystem, http: &FakeHttpClient) -> Result<Option<u64>> {
+    let downloader = downloader(fs, http);
+    futures::executor::block_on(downloader.ensure_package_downloaded(&package(), &NullTelemetry))
+}
+
+fn tarball_path() -> Utf8PathBuf {
+    paths::global_package_cache_package_tarball("abc", "1.0.0")
+}
+
+fn partial_path() -> Utf8PathBuf {
+    paths::global_package_cache_package_partial_tarball("abc", "1.0.0")
+}
+
+#[test]
+fn download_package() {
+    let fs = InMemoryFileSystem::new();
+    let http = FakeHttpClient::new(vec![FakeResponse::new(StatusCode::OK, vec![b"a", b"bc"])]);
+    assert_eq!(download(&fs, &http).expect("download"), Some(3));
+    assert_eq!(fs.read_bytes(&tarball_path()).expect("tarball"), b"abc");
+    assert!(!fs.is_file(&partial_path()));
+    assert_eq!(http.ranges(), vec![None]);
+}
+
+#[test]
+fn download_organization_package() {
+    let fs = InMemoryFileSystem::new();
+    let http = FakeHttpClient::new(vec![FakeResponse::new(StatusCode::OK, vec![b"abc"])]);
+    let downloader =
+        downloader(&fs, &http).with_organization_keys([("acme".into(), "key".into())].into());
+    let downloaded = futures::executor::block_on(
+        downloader.ensure_package_downloaded(&organization_package(), &NullTelemetry),
+    );
+    assert_eq!(downloaded.expect("download"), Some(3));
+    assert_eq!(
+        http.requests(),
+        vec![(
+            "https://repo.hex.pm/repos/acme/tarballs/abc-1.0.0.tar".into(),
+            Some("key".into())
+        )]
+    );
+}
+
+#[test]
+fn download_organization_package_without_key() {
+    let fs = InMemoryFileSystem::new();
+    let http = FakeHttpClient::new(vec![]);
+    let downloaded = futures::executor::block_on(
+        downloader(&fs, &http).ensure_package_downloaded(&organization_package(), &NullTelemetry),
+    );
+    assert_eq!(
+        downloaded,
+        Err(Error::HexOrganizationKeyMissing {
+            organization: "acme".into()
+        })
+    );
+    assert_eq!(http.requests(), vec![]);
+}
+
+fn create_key(http: &FakeHttpClient, otp: Option<&str>) -> Result<String> {
+    futures::executor::block_on(create_api_key(
+        "laptop",
+        "louis",
+        "password",
+        otp,
+        &hexpm::Config::new(),
+        http,
+    ))
+}
+
+fn two_factor_challenge(error: &str) -> FakeResponse {
+    FakeResponse {
+        headers: vec![(
+            "www-authenticate",
+            format!("Basic realm=\"hex\", error=\"{error}\""),
+        )],
+        ..FakeResponse::new(StatusCode::UNAUTHORIZED, vec![])
+    }
+}
+
+#[test]
+fn create_api_key_requiring_two_factor_code() {
+    let http = FakeHttpClient::new(vec![
+        two_factor_challenge("totp_required"),
+        FakeResponse::new(StatusCode::CREATED, vec![br#"{"secret": "key"}"#]),
+    ]);
+    assert_eq!(
+        create_key(&http, None),
+        Err(Error::HexTwoFactorCodeRequired { rejected: false })
+    );
+    assert_eq!(create_key(&http, Some("123456")), Ok("key".into()));
+    assert_eq!(http.otps(), vec![None, Some("123456".into())]);
+}
+
+#[test]
+fn create_api_key_with_rejected_two_factor_code() {
+    let http = FakeHttpClient::new(vec![two_factor_challenge("invalid_totp")]);
+    assert_eq!(
+        create_key(&http, Some("000000")),
+        Err(Error::HexTwoFactorCodeRequired { rejected: true })
+    );
+}
+
+#[test]
+fn create_api_key_with_wrong_password() {
+    let http = FakeHttpClient::new(vec![FakeResponse::new(StatusCode::UNAUTHORIZED, vec![])]);
+    assert_eq!(
+        create_key(&http, None),
+        Err(Error::hex(ApiError::InvalidCredentials))
+    );
+}
+
+#[test]
+fn organization_config_urls() {
+    let config = organization_config(&hexpm::Config::new(), "acme").expect("config");
+    assert_eq!(
+        config.api_base.to_string(),
+        "https://hex.pm/api/repos/acme/"
+    );
+    assert_eq!(
+        config.repository_base.to_string(),
+        "https://repo.hex.pm/repos/acme/"
+    );
+}
+
+#[test]
+fn organization_config_invalid_name() {
+    assert_eq!(
+        organization_config(&hexpm::Config::new(), "../acme").map(|_| ()),
+        Err(Error::InvalidHexOrganization {
+            organization: "../acme".into()
+        })
+    );
+}
+
+#[test]
+fn cached_package_is_not_downloaded() {
+    let fs = InMemoryFileSystem::new();
+    fs.write_bytes(&tarball_path(), b"abc").expect("write");
+    let http = FakeHttpClient::new(vec![]);
+    assert_eq!(download(&fs, &http).expect("download"), None);
+    assert_eq!(http.ranges(), Vec::<Option<String>>::new());
+}
+
+#[test]
+fn lost_connection_keeps_partial_download() {
+    let fs = InMemoryFileSystem::new();
+    let http = FakeHttpClient::new(vec![FakeResponse {
+        lost: true,
+        ..FakeResponse::new(StatusCode::OK, vec![b"a"])
+    }]);
+    assert!(download(&fs, &http).is_err());
+    assert_eq!(fs.read_bytes(&partial_path()).expect("partial"), b"a");
+    assert!(!fs.is_file(&tarball_path()));
+}
+
+#[test]
+fn partial_download_is_resumed() {
+    let fs = InMemoryFileSystem::new();
+    fs.write_bytes(&partial_path(), b"a").expect("write");
+    let http = FakeHttpClient::new(vec![FakeResponse {
+        headers: vec![("content-range", "bytes 1-2/3".into())],
+        ..FakeResponse::new(StatusCode::PARTIAL_CONTENT, vec![b"bc"])
+    }]);
+    assert_eq!(download(&fs, &http).expect("download"), Some(3));
+    assert_eq!(fs.read_bytes(&tarball_path()).expect("tarball"), b"abc");
+    assert!(!fs.is_file(&partial_path()));
+    assert_eq!(http.ranges(), vec![Some("bytes=1-".into())]);
+}
+
+#[test]
+fn resumed_download_with_wrong_checksum_starts_again() {
+    let fs = InMemoryFileSystem::new();
+    fs.write_bytes(&partial_path(), b"x").expect("write");
+    let http = FakeHttpClient::new(vec![
+        FakeResponse::new(StatusCode::PARTIAL_CONTENT, vec![b"bc"]),
+        FakeResponse::new(StatusCode::OK, vec![b"abc"]),
+    ]);
+    assert_eq!(download(&fs, &http).expect("download"), Some(3));
+    assert_eq!(fs.read_bytes(&tarball_path()).expect("tarball"), b"abc");
+    assert_eq!(http.ranges(), vec![Some("bytes=1-".into()), None]);
+}
+
+#[test]
+fn unsatisfiable_range_starts_again() {
+    let fs = InMemoryFileSystem::new();
+    fs.write_bytes(&partial_path(), b"abcd").expect("write");
+    let http = FakeHttpClient::new(vec![
+        FakeResponse::new(StatusCode::RANGE_NOT_SATISFIABLE, vec![]),
+        FakeResponse::new(StatusCode::OK, vec![b"abc"]),
+    ]);
+    assert_eq!(download(&fs, &http).expect("download"), Some(3));
+    assert_eq!(http.ranges(), vec![Some("bytes=4-".into()), None]);
+}
+
+#[test]
+fn extracted_package_directories_of_a_package() {
+    let fs = InMemoryFileSystem::new();
+    let paths = ProjectPaths::new("/app".into());
+    let packages = paths.build_packages_directory();
+    for directory in ["wibble", "wibble-0102", "wibble-0304", "wibble_wobble-0102"] {
+        fs.write(&packages.join(directory).join("gleam.toml"), "")
+            .expect("write");
+    }
+    fs.write(&paths.build_packages_toml(), "").expect("write");
+    let mut directories =
+        extracted_package_directories(&fs, &paths, "wibble").expect("directories");
+    directories.sort();
+    assert_eq!(
+        directories,
+        vec![
+            packages.join("wibble"),
+            packages.join("wibble-0102"),
+            packages.join("wibble-0304"),
+        ]
+    );
+}
+
+#[test]
+fn snapshot_path_for_request() {
+    let config = hexpm::Config::new();
+    let path = |uri: &str| snapshot::path_for_request(&config, &uri.parse().expect("uri"));
+    assert_eq!(
+        path("https://repo.hex.pm/packages/abc"),
+        Some("packages/abc".into())
+    );
+    assert_eq!(
+        path("https://repo.hex.pm/repos/acme/tarballs/abc-1.0.0.tar"),
+        Some("repos/acme/tarballs/abc-1.0.0.tar".into())
+    );
+    assert_eq!(
+        path("https://hex.pm/api/packages/abc/releases/1.0.0"),
+        Some("api/packages/abc/releases/1.0.0".into())
+    );
+    assert_eq!(path("https://example.com/packages/abc"), None);
+    assert_eq!(path("https://repo.hex.pm/packages/../../etc"), None);
+    assert_eq!(path("https://repo.hex.pm/packages//abc"), None);
+}
+
+#[test]
+fn snapshot_package() {
+    let fs = InMemoryFileSystem::new();
+    let http = FakeHttpClient::new(vec![
+        FakeResponse::new(StatusCode::OK, vec![b"metadata"]),
+        FakeResponse::new(StatusCode::OK, vec![b"release"]),
+        FakeResponse::new(StatusCode::OK, vec![b"abc"]),
+    ]);
+    let config = organization_config(&hexpm::Config::new(), "acme").expect("config");
+    let size = futures::executor::block_on(snapshot::snapshot_package(
+        &http,
+        &fs,
+        Utf8Path::new("/snapshot"),
+        &config,
+        Some("key"),
+        &organization_package(),
+    ))
+    .expect("snapshot");
+
+    assert_eq!(size, 3);
+    let read = |path: &str| String::from_utf8(fs.read_bytes(Utf8Path::new(path)).expect(path));
+    assert_eq!(
+        read("/snapshot/repos/acme/packages/abc").as_deref(),
+        Ok("metadata")
+    );
+    assert_eq!(
+        read("/snapshot/api/repos/acme/packages/abc/releases/1.0.0").as_deref(),
+        Ok("release")
+    );
+    assert_eq!(
+        read("/snapshot/repos/acme/tarballs/abc-1.0.0.tar").as_deref(),
+        Ok("abc")
+    );
+}
+
+#[test]
+fn snapshot_package_with_wrong_checksum() {
+    let fs = InMemoryFileSystem::new();
+    let http = FakeHttpClient::new(vec![
+        FakeResponse::new(StatusCode::OK, vec![b"metadata"]),
+        FakeResponse::new(StatusCode::OK, vec![b"release"]),
+        FakeResponse::new(StatusCode::OK, vec![b"not abc"]),
+    ]);
+    let result = futures::executor::block_on(snapshot::snapshot_package(
+        &http,
+        &fs,
+        Utf8Path::new("/snapshot"),
+        &hexpm::Config::new(),
+        None,
+        &package(),
+    ));
+
+    assert!(matches!(result, Err(Error::DownloadPackageError { .. })));
+    assert!(!fs.is_file(Utf8Path::new("/snapshot/tarballs/abc-1.0.0.tar")));
+}
@@ -0,0 +1,143 @@
+//! Checks that a package about to be added is not named like a much more
+//! popular package, as packages are sometimes published with a name one or
+//! two letters away from a popular one to trick people into installing them.
+
+#[cfg(test)]
+mod tests;
+
+use ecow::EcoString;
+use hexpm::ApiError;
+use http::StatusCode;
+
+use crate::{error::edit_distance, io::HttpClient, Error, Result};
+
+/// How many times more downloads a package with a similar name must have for
+/// a package to be thought to be imitating it.
+pub const POPULARITY_RATIO: u64 = 10;
+
+/// How many pages of the most downloaded packages on Hex are compared with,
+/// each of 100 packages.
+pub const POPULAR_PACKAGE_PAGES: u32 = 2;
+
+/// A package and how many times it has been downloaded in total.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PackageDownloads {
+    pub name: EcoString,
+    pub downloads: u64,
+}
+
+/// A package named like a much more popular one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LookAlike {
+    pub package: PackageDownloads,
+    pub popular: PackageDownloads,
+}
+
+/// The most popular of the given packages that the package looks like it is
+/// imitating, if any: one whose name is one or two edits away from its name
+/// and which has many times more downloads.
+///
+/// Short names are only compared with names one edit away, as most short
+/// names are two edits away from some other package.
+pub fn look_alike(package: &PackageDownloads, popular: &[PackageDownloads]) -> Option<LookAlike> {
+    let limit = match package.name.chars().count() {
+        0..=5 => 1,
+        _ => 2,
+    };
+    popular
+        .iter()
+        .filter(|candidate| candidate.name != package.name)
+        .filter(|candidate| {
+            candidate.downloads >= package.downloads.max(1).saturating_mul(POPULARITY_RATIO)
+        })
+        .filter(|candidate| edit_distance(&package.name, &candidate.name, limit).is_some())
+        .max_by_key(|candidate| candidate.downloads)
+        .map(|candidate| LookAlike {
+            package: package.clone(),
+            popular: candidate.clone(),
+        })
+}
+
+/// Looks up how many times a package has been downloaded, or `None` if there
+/// is no such package.
+pub async fn get_downloads<Http: HttpClient>(
+    name: &str,
+    config: &hexpm::Config,
+    http: &Http,
+) -> Result<Option<PackageDownloads>> {
+    tracing::info!(name = name, "looking_up_package_downloads");
+    let url = format!("{}packages/{name}", config.api_base);
+    match get_json(&url, http).await? {
+        None => Ok(None),
+        Some(body) => parse_package(&body).map(Some),
+    }
+}
+
+/// Looks up a page of the packages on Hex with the most downloads, starting
+/// from page 1.
+pub async fn get_popular_packages<Http: HttpClient>(
+    page: u32,
+    config: &hexpm::Config,
+    http: &Http,
+) -> Result<Vec<PackageDownloads>> {
+    tracing::info!(page = page, "looking_up_popular_packages");
+    let url = format!("{}packages?sort=downloads&page={page}", config.api_base);
+    match get_json(&url, http).await? {
+        None => Err(Error::hex(ApiError::NotFound)),
+        Some(body) => parse_packages(&body),
+    }
+}
+
+async fn get_json<Http: HttpClient>(url: &str, http: &Http) -> Result<Option<Vec<u8>>> {
+    let request = http::Request::get(url)
+        .header("accept", "application/json")
+        .header("user-agent", concat!("gleam/", env!("CARGO_PKG_VERSION")))
+        .body(vec![])
+        .map_err(|e| Error::Hex(e.to_string()))?;
+    let response = http.send(request).await?;
+    match response.status() {
+        StatusCode::OK => Ok(Some(response.into_body())),
+        StatusCode::NOT_FOUND => Ok(None),
+        StatusCode::TOO_MANY_REQUESTS => Err(Error::hex(ApiError::RateLimited)),
+        status => Err(Error::hex(ApiError::UnexpectedResponse(
+            status,
+            String::from_utf8_lossy(response.body()).to_string(),
+        ))),
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct Package {
+    name: EcoString,
+    #[serde(default)]
+    downloads: Downloads,
+}
+
+#[derive(Default, serde::Deserialize)]
+struct Downloads {
+    #[serde(default)]
+    all: u64,
+}
+
+impl From<Package> for PackageDownloads {
+    fn from(package: Package) -> Self {
+        Self {
+            name: package.name,
+            downloads: package.downloads.all,
+        }
+    }
+}
+
+/// Reads a package and its downloads from its description given by the Hex
+/// API.
+fn parse_package(body: &[u8]) -> Result<PackageDownloads> {
+    let package: Package = serde_json::from_slice(body).map_err(Error::hex)?;
+    Ok(package.into())
+}
+
+/// Reads packages and their downloads from a list of packages given by the
+/// Hex API.
+fn parse_packages(body: &[u8]) -> Result<Vec<PackageDownloads>> {
+    let packages: Vec<Package> = serde_json::from_slice(body).map_err(Error::hex)?;
+    Ok(packages.into_iter().map(Into::into).collect())
+}
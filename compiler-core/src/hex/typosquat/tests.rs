@@ -0,0 +1,102 @@
+use super::*;
+
+fn package(name: &str, downloads: u64) -> PackageDownloads {
+    PackageDownloads {
+        name: name.into(),
+        downloads,
+    }
+}
+
+fn popular() -> Vec<PackageDownloads> {
+    vec![
+        package("gleam_json", 500_000),
+        package("gleam_http", 800_000),
+        package("lustre", 200_000),
+        package("jason", 300_000_000),
+    ]
+}
+
+#[test]
+fn look_alike_one_edit_away() {
+    assert_eq!(
+        look_alike(&package("gleam_jsno", 12), &popular()),
+        Some(LookAlike {
+            package: package("gleam_jsno", 12),
+            popular: package("gleam_json", 500_000),
+        })
+    );
+}
+
+#[test]
+fn look_alike_two_edits_away() {
+    assert_eq!(
+        look_alike(&package("gleam_jon", 0), &popular()).map(|found| found.popular),
+        Some(package("gleam_json", 500_000))
+    );
+}
+
+#[test]
+fn look_alike_prefers_most_downloaded() {
+    assert_eq!(
+        look_alike(&package("gleam_htjp", 3), &popular()).map(|found| found.popular),
+        Some(package("gleam_http", 800_000))
+    );
+}
+
+#[test]
+fn look_alike_short_name_two_edits_away() {
+    assert_eq!(look_alike(&package("jsn", 10), &popular()), None);
+    assert_eq!(
+        look_alike(&package("jasonxy", 10), &popular()).map(|found| found.popular),
+        Some(package("jason", 300_000_000))
+    );
+}
+
+#[test]
+fn look_alike_not_much_less_popular() {
+    assert_eq!(look_alike(&package("lustra", 50_000), &popular()), None);
+}
+
+#[test]
+fn look_alike_same_package() {
+    assert_eq!(
+        look_alike(&package("gleam_json", 500_000), &popular()),
+        None
+    );
+}
+
+#[test]
+fn look_alike_far_away() {
+    assert_eq!(look_alike(&package("wibble", 10), &popular()), None);
+}
+
+#[test]
+fn parse_package_downloads() {
+    let body = br#"{
+        "name": "gleam_json",
+        "downloads": {"all": 512, "recent": 20},
+        "releases": []
+    }"#;
+    assert_eq!(
+        parse_package(body).expect("package"),
+        package("gleam_json", 512)
+    );
+}
+
+#[test]
+fn parse_package_without_downloads() {
+    let body = br#"{"name": "wibble"}"#;
+    assert_eq!(parse_package(body).expect("package"), package("wibble", 0));
+}
+
+#[test]
+fn parse_packages_downloads() {
+    let body = br#"[
+        {"name": "jason", "downloads": {"all": 300}},
+        {"name": "lustre", "downloads": {"all": 20, "week": 1}}
+    ]"#;
+    assert_eq!(
+        parse_packages(body).expect("packages"),
+        vec![package("jason", 300), package("lustre", 20)]
+    );
+}
@@ -0,0 +1,441 @@
+//! The encoding of Gleam values at runtime used by tooling, such as `gleam
+//! kernel`, to show the values of programs, and the printing of the decoded
+//! values as Gleam would write them.
+//!
+//! Values are encoded as JSON by the `encode/1` function of the
+//! `gleam@@inspect` Erlang module and the `encode` function of the
+//! `inspect.mjs` JavaScript module, which are written with the entrypoint
+//! module and the prelude respectively. Each value is an object with a `kind`
+//! field, describing the value as the target represents it. Some Gleam types
+//! are represented in the same way, such as a `String` and a `BitArray` on
+//! Erlang, or an `Int` and a `Float` on JavaScript, so the type of the value
+//! is used to print it if it is known.
+
+#[cfg(test)]
+mod tests;
+
+use std::{collections::HashMap, sync::Arc};
+
+use ecow::EcoString;
+use heck::{ToSnakeCase, ToUpperCamelCase};
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+
+use crate::type_::{
+    collapse_links, is_prelude_module, ModuleInterface, Type, TypeValueConstructor, TypeVar,
+    ValueConstructorVariant,
+};
+
+/// A Gleam value as it is represented at runtime.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum RuntimeValue {
+    /// An Erlang integer, as text as it can be larger than any Rust integer.
+    Int {
+        value: EcoString,
+    },
+    /// An Erlang float.
+    Float {
+        value: f64,
+    },
+    /// A JavaScript number, which is an `Int` or a `Float`.
+    Number {
+        value: f64,
+    },
+    /// A JavaScript string.
+    String {
+        value: EcoString,
+    },
+    /// A JavaScript boolean.
+    Bool {
+        value: bool,
+    },
+    /// JavaScript's `undefined`, which is `Nil`.
+    Undefined,
+    /// An Erlang atom, which is a `Bool`, `Nil`, or a constructor without
+    /// fields.
+    Atom {
+        name: EcoString,
+    },
+    /// An Erlang binary, which is a `String` or a `BitArray`, or a
+    /// JavaScript `BitArray`.
+    BitArray {
+        bytes: Vec<u8>,
+    },
+    /// A JavaScript `UtfCodepoint`. On Erlang they are integers.
+    UtfCodepoint {
+        value: u32,
+    },
+    List {
+        elements: Vec<RuntimeValue>,
+    },
+    /// An Erlang tuple, which is a tuple or a record, or a JavaScript array,
+    /// which is a tuple.
+    Tuple {
+        elements: Vec<RuntimeValue>,
+    },
+    /// A JavaScript record.
+    Record {
+        name: EcoString,
+        fields: Vec<RecordField>,
+    },
+    /// An Erlang map or a JavaScript `Dict`, given as pairs of keys and
+    /// values.
+    Map {
+        entries: Vec<(RuntimeValue, RuntimeValue)>,
+    },
+    Function {
+        arity: usize,
+    },
+    /// Any other value, as the target shows it.
+    Other {
+        text: EcoString,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordField {
+    pub label: Option<EcoString>,
+    pub value: RuntimeValue,
+}
+
+/// Decodes a value encoded by the `gleam@@inspect` Erlang module or the
+/// `inspect.mjs` JavaScript module.
+pub fn decode(json: &str) -> Result<RuntimeValue, serde_json::Error> {
+    serde_json::from_str(json)
+}
+
+/// Prints runtime values as Gleam would write them, using the custom types
+/// of the given modules to print records with the names and labels of their
+/// constructors.
+#[derive(Debug)]
+pub struct ValuePrinter<'a> {
+    modules: &'a im::HashMap<EcoString, ModuleInterface>,
+}
+
+impl<'a> ValuePrinter<'a> {
+    pub fn new(modules: &'a im::HashMap<EcoString, ModuleInterface>) -> Self {
+        Self { modules }
+    }
+
+    /// Prints the value, which is of the given type if it is known.
+    pub fn print(&self, value: &RuntimeValue, type_: Option<&Arc<Type>>) -> String {
+        let type_ = type_.map(|type_| collapse_links(type_.clone()));
+        match type_.as_deref() {
+            Some(Type::Named {
+                module, name, args, ..
+            }) => self
+                .print_named(value, module, name, args)
+                .unwrap_or_else(|| self.print_untyped(value)),
+            Some(Type::Tuple { elems }) => match value {
+                RuntimeValue::Tuple { elements } if elements.len() == elems.len() => {
+                    let elements = elements
+                        .iter()
+                        .zip(elems)
+                        .map(|(element, type_)| self.print(element, Some(type_)));
+                    format!("#({})", elements.format(", "))
+                }
+                _ => self.print_untyped(value),
+            },
+            Some(Type::Fn { .. } | Type::Var { .. }) | None => self.print_untyped(value),
+        }
+    }
+
+    fn print_named(
+        &self,
+        value: &RuntimeValue,
+        module: &str,
+        name: &str,
+        args: &[Arc<Type>],
+    ) -> Option<String> {
+        if is_prelude_module(module) {
+            if let Some(printed) = self.print_prelude(value, name, args) {
+                return Some(printed);
+            }
+        }
+        if let (RuntimeValue::Map { entries }, [key, value]) = (value, args) {
+            let entries = entries.iter().map(|(k, v)| {
+                format!(
+                    "#({}, {})",
+                    self.print(k, Some(key)),
+                    self.print(v, Some(value))
+                )
+            });
+            return Some(format!("dict.from_list([{}])", entries.format(", ")));
+        }
+
+        let constructors = self
+            .modules
+            .get(module)?
+            .types_value_constructors
+            .get(name)?;
+        let (constructor, fields) = match value {
+            RuntimeValue::Atom { name } => {
+                let constructor = constructors.variants.iter().find(|constructor| {
+                    constructor.parameters.is_empty() && constructor.name.to_snake_case() == *name
+                })?;
+                (constructor, vec![])
+            }
+            RuntimeValue::Tuple { elements } => {
+                let (RuntimeValue::Atom { name: tag }, fields) = elements.split_first()? else {
+                    return None;
+                };
+                let constructor = constructors.variants.iter().find(|constructor| {
+                    constructor.parameters.len() == fields.len()
+                        && constructor.name.to_snake_case() == *tag
+                })?;
+                let labels = self.labels(module, constructor);
+                let fields = fields
+                    .iter()
+                    .enumerate()
+                    .map(|(index, value)| (labels.get(&index).cloned(), value))
+                    .collect();
+                (constructor, fields)
+            }
+            RuntimeValue::Record { name, fields } => {
+                let constructor = constructors
+                    .variants
+                    .iter()
+                    .find(|constructor| constructor.name == *name)?;
+                let fields = fields
+                    .iter()
+                    .map(|field| (field.label.clone(), &field.value))
+                    .collect();
+                (constructor, fields)
+            }
+            _ => return None,
+        };
+
+        if fields.is_empty() {
+            return Some(constructor.name.to_string());
+        }
+        let generics: HashMap<u64, Arc<Type>> = constructors
+            .type_parameters_ids
+            .iter()
+            .copied()
+            .zip(args.iter().cloned())
+            .collect();
+        let fields = fields
+            .into_iter()
+            .enumerate()
+            .map(|(index, (label, value))| {
+                let type_ = constructor
+                    .parameters
+                    .get(index)
+                    .map(|parameter| specialise(&parameter.type_, &generics));
+                let value = self.print(value, type_.as_ref());
+                match label {
+                    Some(label) => format!("{label}: {value}"),
+                    None => value,
+                }
+            });
+        Some(format!("{}({})", constructor.name, fields.format(", ")))
+    }
+
+    fn print_prelude(
+        &self,
+        value: &RuntimeValue,
+        name: &str,
+        args: &[Arc<Type>],
+    ) -> Option<String> {
+        let printed = match (name, value) {
+            ("Int", RuntimeValue::Int { value }) => value.to_string(),
+            ("Int", RuntimeValue::Number { value }) => print_int(*value),
+            ("Float", RuntimeValue::Float { value } | RuntimeValue::Number { value }) => {
+                print_float(*value)
+            }
+            ("String", RuntimeValue::String { value }) => print_string(value),
+            ("String", RuntimeValue::BitArray { bytes }) => {
+                print_string(std::str::from_utf8(bytes).ok()?)
+            }
+            ("BitArray", RuntimeValue::BitArray { bytes }) => print_bit_array(bytes),
+            ("Bool", RuntimeValue::Bool { value }) => print_bool(*value).into(),
+            ("Bool", RuntimeValue::Atom { name }) if name == "true" => "True".into(),
+            ("Bool", RuntimeValue::Atom { name }) if name == "false" => "False".into(),
+            ("Nil", _) => "Nil".into(),
+            ("UtfCodepoint", RuntimeValue::UtfCodepoint { value }) => print_codepoint(*value)?,
+            ("UtfCodepoint", RuntimeValue::Int { value }) => print_codepoint(value.parse().ok()?)?,
+            ("List", RuntimeValue::List { elements }) => {
+                let element_type = args.first();
+                let elements = elements
+                    .iter()
+                    .map(|element| self.print(element, element_type));
+                format!("[{}]", elements.format(", "))
+            }
+            _ => return None,
+        };
+        Some(printed)
+    }
+
+    /// Prints a value whose type is not known, guessing which Gleam value it
+    /// is from how it is represented.
+    fn print_untyped(&self, value: &RuntimeValue) -> String {
+        match value {
+            RuntimeValue::Int { value } => value.to_string(),
+            RuntimeValue::Float { value } => print_float(*value),
+            RuntimeValue::Number { value } if value.fract() == 0.0 => print_int(*value),
+            RuntimeValue::Number { value } => print_float(*value),
+            RuntimeValue::String { value } => print_string(value),
+            RuntimeValue::Bool { value } => print_bool(*value).into(),
+            RuntimeValue::Undefined => "Nil".into(),
+            RuntimeValue::Atom { name } => print_atom(name),
+            RuntimeValue::BitArray { bytes } => match std::str::from_utf8(bytes) {
+                Ok(string) => print_string(string),
+                Err(_) => print_bit_array(bytes),
+            },
+            RuntimeValue::UtfCodepoint { value } => {
+                print_codepoint(*value).unwrap_or_else(|| value.to_string())
+            }
+            RuntimeValue::List { elements } => {
+                let elements = elements.iter().map(|element| self.print_untyped(element));
+                format!("[{}]", elements.format(", "))
+            }
+            RuntimeValue::Tuple { elements } => match elements.split_first() {
+                Some((RuntimeValue::Atom { name }, fields)) if !fields.is_empty() => {
+                    let fields = fields.iter().map(|field| self.print_untyped(field));
+                    format!("{}({})", print_atom(name), fields.format(", "))
+                }
+                _ => {
+                    let elements = elements.iter().map(|element| self.print_untyped(element));
+                    format!("#({})", elements.format(", "))
+                }
+            },
+            RuntimeValue::Record { name, fields } if fields.is_empty() => name.to_string(),
+            RuntimeValue::Record { name, fields } => {
+                let fields = fields.iter().map(|field| {
+                    let value = self.print_untyped(&field.value);
+                    match &field.label {
+                        Some(label) => format!("{label}: {value}"),
+                        None => value,
+                    }
+                });
+                format!("{name}({})", fields.format(", "))
+            }
+            RuntimeValue::Map { entries } => {
+                let entries = entries.iter().map(|(key, value)| {
+                    format!(
+                        "#({}, {})",
+                        self.print_untyped(key),
+                        self.print_untyped(value)
+                    )
+                });
+                format!("dict.from_list([{}])", entries.format(", "))
+            }
+            RuntimeValue::Function { arity } => {
+                format!("//fn({}) {{ ... }}", vec!["_"; *arity].join(", "))
+            }
+            RuntimeValue::Other { text } => text.to_string(),
+        }
+    }
+
+    /// The labels of the fields of a constructor, by their position.
+    fn labels(
+        &self,
+        module: &str,
+        constructor: &TypeValueConstructor,
+    ) -> HashMap<usize, EcoString> {
+        let Some(value) = self
+            .modules
+            .get(module)
+            .and_then(|module| module.values.get(&constructor.name))
+        else {
+            return HashMap::new();
+        };
+        match &value.variant {
+            ValueConstructorVariant::Record {
+                field_map: Some(field_map),
+                ..
+            } => field_map
+                .fields
+                .iter()
+                .map(|(label, index)| (*index as usize, EcoString::from(label.as_str())))
+                .collect(),
+            _ => HashMap::new(),
+        }
+    }
+}
+
+/// Replaces the generic type variables of the type of a field of a
+/// constructor with the arguments of the type of the record.
+fn specialise(type_: &Arc<Type>, generics: &HashMap<u64, Arc<Type>>) -> Arc<Type> {
+    match type_.as_ref() {
+        Type::Named {
+            publicity,
+            package,
+            module,
+            name,
+            args,
+        } => Arc::new(Type::Named {
+            publicity: *publicity,
+            package: *package,
+            module: *module,
+            name: *name,
+            args: args.iter().map(|arg| specialise(arg, generics)).collect(),
+        }),
+        Type::Fn { args, retrn } => Arc::new(Type::Fn {
+            args: args.iter().map(|arg| specialise(arg, generics)).collect(),
+            retrn: specialise(retrn, generics),
+        }),
+        Type::Tuple { elems } => Arc::new(Type::Tuple {
+            elems: elems
+                .iter()
+                .map(|elem| specialise(elem, generics))
+                .collect(),
+        }),
+        Type::Var { type_: variable } => match &*variable.borrow() {
+            TypeVar::Generic { id } => generics.get(id).cloned().unwrap_or_else(|| type_.clone()),
+            TypeVar::Link { type_ } => specialise(type_, generics),
+            TypeVar::Unbound { .. } => type_.clone(),
+        },
+    }
+}
+
+fn print_int(value: f64) -> String {
+    format!("{value:.0}")
+}
+
+fn print_float(value: f64) -> String {
+    format!("{value:?}")
+}
+
+fn print_bool(value: bool) -> &'static str {
+    if value {
+        "True"
+    } else {
+        "False"
+    }
+}
+
+fn print_atom(name: &str) -> String {
+    match name {
+        "nil" => "Nil".into(),
+        "true" => "True".into(),
+        "false" => "False".into(),
+        _ => name.to_upper_camel_case(),
+    }
+}
+
+fn print_string(string: &str) -> String {
+    let mut printed = String::with_capacity(string.len() + 2);
+    printed.push('"');
+    for character in string.chars() {
+        match character {
+            '"' => printed.push_str("\\\""),
+            '\\' => printed.push_str("\\\\"),
+            '\n' => printed.push_str("\\n"),
+            '\r' => printed.push_str("\\r"),
+            '\t' => printed.push_str("\\t"),
+            _ => printed.push(character),
+        }
+    }
+    printed.push('"');
+    printed
+}
+
+fn print_bit_array(bytes: &[u8]) -> String {
+    format!("<<{}>>", bytes.iter().format(", "))
+}
+
+fn print_codepoint(value: u32) -> Option<String> {
+    char::from_u32(value).map(|character| format!("//utfcodepoint({character})"))
+}
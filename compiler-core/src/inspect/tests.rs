@@ -0,0 +1,171 @@
+use super::{decode, RecordField, RuntimeValue, ValuePrinter};
+use crate::{
+    type_::{build_prelude, tests::compile_module},
+    uid::UniqueIdGenerator,
+};
+
+/// Prints the encoded value as the type of the constant `value` of the module.
+fn print(src: &str, json: &str) -> String {
+    let module = compile_module("app", src, None, vec![]).expect("compile module");
+    let type_ = module
+        .type_info
+        .values
+        .get("value")
+        .expect("value constant")
+        .type_
+        .clone();
+    let modules = im::hashmap! {
+        "gleam".into() => build_prelude(&UniqueIdGenerator::new()),
+        "app".into() => module.type_info,
+    };
+    let value = decode(json).expect("decode value");
+    ValuePrinter::new(&modules).print(&value, Some(&type_))
+}
+
+fn print_untyped(json: &str) -> String {
+    let modules = im::HashMap::new();
+    let value = decode(json).expect("decode value");
+    ValuePrinter::new(&modules).print(&value, None)
+}
+
+#[test]
+fn decode_record() {
+    let json = r#"{"kind":"record","name":"Ok","fields":[{"label":null,"value":{"kind":"number","value":1}}]}"#;
+    assert_eq!(
+        decode(json).expect("decode value"),
+        RuntimeValue::Record {
+            name: "Ok".into(),
+            fields: vec![RecordField {
+                label: None,
+                value: RuntimeValue::Number { value: 1.0 },
+            }],
+        }
+    );
+}
+
+#[test]
+fn decode_unknown_kind() {
+    assert!(decode(r#"{"kind":"wibble"}"#).is_err());
+}
+
+#[test]
+fn erlang_string() {
+    let json = r#"{"kind":"bit_array","bytes":[104,105,10]}"#;
+    assert_eq!(print(r#"pub const value = "hi\n""#, json), r#""hi\n""#);
+}
+
+#[test]
+fn erlang_bit_array() {
+    let json = r#"{"kind":"bit_array","bytes":[104,105]}"#;
+    assert_eq!(
+        print("pub const value = <<104, 105>>", json),
+        "<<104, 105>>"
+    );
+}
+
+#[test]
+fn erlang_big_int() {
+    let json = r#"{"kind":"int","value":"123456789012345678901234567890"}"#;
+    assert_eq!(
+        print("pub const value = 1", json),
+        "123456789012345678901234567890"
+    );
+}
+
+#[test]
+fn erlang_bool_and_nil() {
+    let json = r#"{"kind":"list","elements":[{"kind":"atom","name":"true"}]}"#;
+    assert_eq!(print("pub const value = [True]", json), "[True]");
+    assert_eq!(
+        print("pub const value = Nil", r#"{"kind":"atom","name":"nil"}"#),
+        "Nil"
+    );
+}
+
+#[test]
+fn erlang_records() {
+    let src = r#"
+pub type Animal(a) {
+  Cat(name: String, data: a)
+  HouseCat
+}
+
+pub const value = [Cat("Nubi", 1.0), HouseCat]
+"#;
+    let json = r#"{"kind":"list","elements":[
+        {"kind":"tuple","elements":[
+            {"kind":"atom","name":"cat"},
+            {"kind":"bit_array","bytes":[78,117,98,105]},
+            {"kind":"float","value":1.0}
+        ]},
+        {"kind":"atom","name":"house_cat"}
+    ]}"#;
+    assert_eq!(
+        print(src, json),
+        r#"[Cat(name: "Nubi", data: 1.0), HouseCat]"#
+    );
+}
+
+#[test]
+fn erlang_prelude_records() {
+    let json = r#"{"kind":"tuple","elements":[
+        {"kind":"tuple","elements":[{"kind":"atom","name":"ok"},{"kind":"int","value":"1"}]},
+        {"kind":"atom","name":"nil"}
+    ]}"#;
+    assert_eq!(
+        print(
+            "pub const value: #(Result(Int, Nil), Nil) = #(Ok(1), Nil)",
+            json
+        ),
+        "#(Ok(1), Nil)"
+    );
+}
+
+#[test]
+fn javascript_numbers() {
+    let json = r#"{"kind":"tuple","elements":[
+        {"kind":"number","value":1},
+        {"kind":"number","value":2}
+    ]}"#;
+    assert_eq!(print("pub const value = #(1, 2.0)", json), "#(1, 2.0)");
+}
+
+#[test]
+fn javascript_records() {
+    let src = r#"
+pub type Box {
+  Box(Int, label: String)
+}
+
+pub const value = Ok(Box(1, "a"))
+"#;
+    let json = r#"{"kind":"record","name":"Ok","fields":[{"label":null,"value":
+        {"kind":"record","name":"Box","fields":[
+            {"label":null,"value":{"kind":"number","value":1}},
+            {"label":"label","value":{"kind":"string","value":"a"}}
+        ]}
+    }]}"#;
+    assert_eq!(print(src, json), r#"Ok(Box(1, label: "a"))"#);
+}
+
+#[test]
+fn untyped_values() {
+    let json = r#"{"kind":"tuple","elements":[
+        {"kind":"atom","name":"some_error"},
+        {"kind":"bit_array","bytes":[255]},
+        {"kind":"function","arity":2},
+        {"kind":"undefined"}
+    ]}"#;
+    assert_eq!(
+        print_untyped(json),
+        "SomeError(<<255>>, //fn(_, _) { ... }, Nil)"
+    );
+}
+
+#[test]
+fn untyped_map() {
+    let json = r#"{"kind":"map","entries":[
+        [{"kind":"int","value":"1"},{"kind":"float","value":1.5}]
+    ]}"#;
+    assert_eq!(print_untyped(json), "dict.from_list([#(1, 1.5)])");
+}
@@ -0,0 +1,190 @@
+//! Interned strings for the names used throughout the compiler, such as
+//! module names, type names, and labels.
+//!
+//! Each distinct name is allocated once, for the lifetime of the process, and
+//! every `Symbol` with that text points to the same allocation. This means a
+//! symbol is a copyable pointer-sized value and two symbols can be compared
+//! without looking at their text.
+
+#[cfg(test)]
+mod tests;
+
+use ecow::EcoString;
+use std::{
+    collections::HashSet,
+    fmt,
+    hash::{Hash, Hasher},
+    ops::Deref,
+    sync::{Mutex, OnceLock},
+};
+
+static INTERNER: OnceLock<Mutex<HashSet<&'static str>>> = OnceLock::new();
+
+#[derive(Clone, Copy)]
+pub struct Symbol(&'static str);
+
+impl Symbol {
+    pub fn new(text: &str) -> Self {
+        let mut names = INTERNER
+            .get_or_init(Default::default)
+            .lock()
+            .expect("Symbol interner lock");
+        match names.get(text) {
+            Some(interned) => Symbol(interned),
+            None => {
+                let interned: &'static str = Box::leak(text.into());
+                let _ = names.insert(interned);
+                Symbol(interned)
+            }
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        self.0
+    }
+}
+
+impl PartialEq for Symbol {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self.0, other.0)
+    }
+}
+
+impl Eq for Symbol {}
+
+impl Hash for Symbol {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        std::ptr::hash(self.0, state)
+    }
+}
+
+// Symbols are ordered by their text rather than their address so that sorting
+// them gives the same order in every compilation.
+impl PartialOrd for Symbol {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Symbol {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.0.cmp(other.0)
+    }
+}
+
+impl Deref for Symbol {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.0
+    }
+}
+
+impl AsRef<str> for Symbol {
+    fn as_ref(&self) -> &str {
+        self.0
+    }
+}
+
+impl fmt::Debug for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.0, f)
+    }
+}
+
+impl fmt::Display for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self.0, f)
+    }
+}
+
+impl Default for Symbol {
+    fn default() -> Self {
+        Symbol::new("")
+    }
+}
+
+impl From<&str> for Symbol {
+    fn from(text: &str) -> Self {
+        Symbol::new(text)
+    }
+}
+
+impl From<&String> for Symbol {
+    fn from(text: &String) -> Self {
+        Symbol::new(text)
+    }
+}
+
+impl From<String> for Symbol {
+    fn from(text: String) -> Self {
+        Symbol::new(&text)
+    }
+}
+
+impl From<&EcoString> for Symbol {
+    fn from(text: &EcoString) -> Self {
+        Symbol::new(text)
+    }
+}
+
+impl From<EcoString> for Symbol {
+    fn from(text: EcoString) -> Self {
+        Symbol::new(&text)
+    }
+}
+
+impl From<Symbol> for EcoString {
+    fn from(symbol: Symbol) -> Self {
+        EcoString::from(symbol.0)
+    }
+}
+
+impl PartialEq<str> for Symbol {
+    fn eq(&self, other: &str) -> bool {
+        self.0 == other
+    }
+}
+
+impl PartialEq<&str> for Symbol {
+    fn eq(&self, other: &&str) -> bool {
+        self.0 == *other
+    }
+}
+
+impl PartialEq<EcoString> for Symbol {
+    fn eq(&self, other: &EcoString) -> bool {
+        self.0 == other.as_str()
+    }
+}
+
+impl PartialEq<Symbol> for str {
+    fn eq(&self, other: &Symbol) -> bool {
+        self == other.0
+    }
+}
+
+impl PartialEq<Symbol> for &str {
+    fn eq(&self, other: &Symbol) -> bool {
+        *self == other.0
+    }
+}
+
+impl PartialEq<Symbol> for EcoString {
+    fn eq(&self, other: &Symbol) -> bool {
+        self.as_str() == other.0
+    }
+}
+
+impl serde::Serialize for Symbol {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.0)
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for Symbol {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let text = <std::borrow::Cow<'de, str>>::deserialize(deserializer)?;
+        Ok(Symbol::new(&text))
+    }
+}
@@ -0,0 +1,54 @@
+use super::Symbol;
+use ecow::EcoString;
+use std::collections::HashMap;
+
+#[test]
+fn same_text_same_symbol() {
+    let one = Symbol::new("gleam/option");
+    let two = Symbol::from(EcoString::from("gleam/option"));
+    assert_eq!(one, two);
+    assert!(std::ptr::eq(one.as_str(), two.as_str()));
+}
+
+#[test]
+fn different_text_different_symbol() {
+    assert_ne!(Symbol::new("Option"), Symbol::new("Result"));
+    assert_ne!(Symbol::new("wibble"), Symbol::new("wibble/wobble"));
+}
+
+#[test]
+fn compares_with_strings() {
+    let symbol = Symbol::new("Int");
+    assert_eq!(symbol, "Int");
+    assert_eq!("Int", symbol);
+    assert_eq!(symbol, EcoString::from("Int"));
+    assert_eq!(EcoString::from(symbol), "Int");
+}
+
+#[test]
+fn ordered_by_text() {
+    let mut symbols = vec![
+        Symbol::new("wobble"),
+        Symbol::new("wibble"),
+        Symbol::new("wubble"),
+    ];
+    symbols.sort();
+    assert_eq!(symbols, vec!["wibble", "wobble", "wubble"]);
+}
+
+#[test]
+fn usable_as_map_key() {
+    let mut map = HashMap::new();
+    let _ = map.insert(Symbol::new("name"), 1);
+    assert_eq!(map.get(&Symbol::from(String::from("name"))), Some(&1));
+}
+
+#[test]
+fn debug_and_display() {
+    let symbol = Symbol::new("Wibble");
+    assert_eq!(
+        format!("{symbol:?}"),
+        format!("{:?}", EcoString::from("Wibble"))
+    );
+    assert_eq!(symbol.to_string(), "Wibble");
+}
@@ -279,6 +279,17 @@ impl Reader for WrappedReader {
 pub trait HttpClient {
     async fn send(&self, request: http::Request<Vec<u8>>)
         -> Result<http::Response<Vec<u8>>, Error>;
+
+    /// Sends a request, passing the body of the response to `on_chunk` in parts
+    /// as it is received rather than returning it, so that the progress of a
+    /// download can be shown and the part received kept if the connection is
+    /// lost.
+    async fn send_streaming(
+        &self,
+        request: http::Request<Vec<u8>>,
+        on_chunk: &mut (dyn for<'a> FnMut(&'a http::response::Parts, &'a [u8]) -> Result<(), Error>
+                  + Send),
+    ) -> Result<http::response::Parts, Error>;
 }
 
 pub trait TarUnpacker {
@@ -83,8 +83,10 @@ impl InMemoryFileSystem {
 
 impl FileSystemWriter for InMemoryFileSystem {
     fn delete_directory(&self, path: &Utf8Path) -> Result<(), Error> {
-        let mut files = self.files.deref().borrow_mut();
-        let _ = files.remove(path);
+        self.files
+            .deref()
+            .borrow_mut()
+            .retain(|file_path, _| !file_path.starts_with(path));
         Ok(())
     }
 
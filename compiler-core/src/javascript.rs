@@ -24,7 +24,13 @@ use self::import::{Imports, Member};
 const INDENT: isize = 2;
 
 pub const PRELUDE: &str = include_str!("../templates/prelude.mjs");
+pub const CRASH: &str = include_str!("../templates/crash.mjs");
+pub const INSPECT: &str = include_str!("../templates/inspect.mjs");
 pub const PRELUDE_TS_DEF: &str = include_str!("../templates/prelude.d.mts");
+pub const PLATFORM_NODE: &str = include_str!("../templates/platform.node.mjs");
+pub const PLATFORM_BROWSER: &str = include_str!("../templates/platform.browser.mjs");
+pub const PLATFORM_NEUTRAL: &str = include_str!("../templates/platform.neutral.mjs");
+pub const PLATFORM_TS_DEF: &str = include_str!("../templates/platform.d.mts");
 
 pub type Output<'a> = Result<Document<'a>, Error>;
 
@@ -37,6 +43,7 @@ pub enum JavaScriptCodegenTarget {
 #[derive(Debug)]
 pub struct Generator<'a> {
     line_numbers: &'a LineNumbers,
+    src: &'a str,
     module: &'a TypedModule,
     tracker: UsageTracker,
     module_scope: im::HashMap<EcoString, usize>,
@@ -48,6 +55,7 @@ pub struct Generator<'a> {
 impl<'a> Generator<'a> {
     pub fn new(
         line_numbers: &'a LineNumbers,
+        src: &'a str,
         module: &'a TypedModule,
         target_support: TargetSupport,
         typescript: TypeScriptDeclarations,
@@ -57,6 +65,7 @@ impl<'a> Generator<'a> {
         Self {
             current_module_name_segments_count,
             line_numbers,
+            src,
             module,
             tracker: UsageTracker::default(),
             module_scope: Default::default(),
@@ -486,6 +495,7 @@ impl<'a> Generator<'a> {
         let mut generator = expression::Generator::new(
             self.module.name.clone(),
             self.line_numbers,
+            self.src,
             function.name.clone(),
             argument_names,
             &mut self.tracker,
@@ -552,7 +562,7 @@ pub fn module(
     target_support: TargetSupport,
     typescript: TypeScriptDeclarations,
 ) -> Result<String, crate::Error> {
-    let document = Generator::new(line_numbers, module, target_support, typescript)
+    let document = Generator::new(line_numbers, src, module, target_support, typescript)
         .compile()
         .map_err(|error| crate::Error::JavaScript {
             path: path.to_path_buf(),
@@ -725,7 +735,7 @@ fn is_usable_js_identifier(word: &str) -> bool {
     )
 }
 
-fn maybe_escape_identifier_string(word: &str) -> String {
+pub(crate) fn maybe_escape_identifier_string(word: &str) -> String {
     if is_usable_js_identifier(word) {
         word.to_string()
     } else {
@@ -32,6 +32,7 @@ impl Position {
 pub(crate) struct Generator<'module> {
     module_name: EcoString,
     line_numbers: &'module LineNumbers,
+    src: &'module str,
     function_name: Option<EcoString>,
     function_arguments: Vec<Option<&'module EcoString>>,
     current_scope_vars: im::HashMap<EcoString, usize>,
@@ -51,6 +52,7 @@ impl<'module> Generator<'module> {
     pub fn new(
         module_name: EcoString,
         line_numbers: &'module LineNumbers,
+        src: &'module str,
         function_name: EcoString,
         function_arguments: Vec<Option<&'module EcoString>>,
         tracker: &'module mut UsageTracker,
@@ -71,6 +73,7 @@ impl<'module> Generator<'module> {
             tracker,
             module_name,
             line_numbers,
+            src,
             function_name,
             function_arguments,
             tail_recursion_used: false,
@@ -491,7 +494,7 @@ impl<'module> Generator<'module> {
     fn assignment<'a>(&mut self, assignment: &'a TypedAssignment) -> Output<'a> {
         let TypedAssignment {
             pattern,
-            kind,
+            kind: _,
             value,
             annotation: _,
             location: _,
@@ -541,8 +544,7 @@ impl<'module> Generator<'module> {
             nil()
         };
 
-        let compiled =
-            self.pattern_into_assignment_doc(compiled, subject, pattern.location(), *kind)?;
+        let compiled = self.pattern_into_assignment_doc(compiled, subject, assignment)?;
         // If there is a subject name given create a variable to hold it for
         // use in patterns
         let doc = match subject_assignment {
@@ -654,12 +656,37 @@ impl<'module> Generator<'module> {
         Ok(docvec![subject_assignments, doc].force_break())
     }
 
-    fn assignment_no_match<'a>(&mut self, location: SrcSpan, subject: Document<'a>) -> Output<'a> {
+    fn assignment_no_match<'a>(
+        &mut self,
+        assignment: &'a TypedAssignment,
+        subject: Document<'a>,
+    ) -> Output<'a> {
+        let TypedAssignment {
+            location,
+            value,
+            pattern,
+            ..
+        } = assignment;
+        let expression = self
+            .src
+            .get(location.start as usize..location.end as usize)
+            .unwrap_or_default();
+        let value_type = crate::type_::pretty::Printer::new()
+            .print(&value.type_())
+            .to_pretty_string(isize::MAX);
         Ok(self.throw_error(
             "assignment_no_match",
             &string("Assignment pattern did not match"),
-            location,
-            [("value", subject)],
+            pattern.location(),
+            [
+                ("value", subject),
+                ("expression", text_string(expression)),
+                ("value_type", text_string(&value_type)),
+                ("start", location.start.to_doc()),
+                ("end", location.end.to_doc()),
+                ("pattern_start", pattern.location().start.to_doc()),
+                ("pattern_end", pattern.location().end.to_doc()),
+            ],
         ))
     }
 
@@ -1029,8 +1056,7 @@ impl<'module> Generator<'module> {
         &mut self,
         compiled_pattern: CompiledPattern<'a>,
         subject: Document<'a>,
-        location: SrcSpan,
-        kind: AssignmentKind,
+        assignment: &'a TypedAssignment,
     ) -> Output<'a> {
         let any_assignments = !compiled_pattern.assignments.is_empty();
         let assignments = Self::pattern_assignments_doc(compiled_pattern.assignments);
@@ -1038,9 +1064,9 @@ impl<'module> Generator<'module> {
         // If it's an assert then it is likely that the pattern is inexhaustive. When a value is
         // provided that does not get matched the code needs to throw an exception, which is done
         // by the pattern_checks_or_throw_doc method.
-        if kind.is_assert() && !compiled_pattern.checks.is_empty() {
+        if assignment.kind.is_assert() && !compiled_pattern.checks.is_empty() {
             let checks =
-                self.pattern_checks_or_throw_doc(compiled_pattern.checks, subject, location)?;
+                self.pattern_checks_or_throw_doc(compiled_pattern.checks, subject, assignment)?;
 
             if !any_assignments {
                 Ok(checks)
@@ -1056,7 +1082,7 @@ impl<'module> Generator<'module> {
         &mut self,
         checks: Vec<pattern::Check<'a>>,
         subject: Document<'a>,
-        location: SrcSpan,
+        assignment: &'a TypedAssignment,
     ) -> Output<'a> {
         let checks = self.pattern_checks_doc(checks, false);
         Ok(docvec![
@@ -1064,7 +1090,7 @@ impl<'module> Generator<'module> {
             docvec![break_("", ""), checks].nest(INDENT),
             break_("", ""),
             ") {",
-            docvec![line(), self.assignment_no_match(location, subject)?].nest(INDENT),
+            docvec![line(), self.assignment_no_match(assignment, subject)?].nest(INDENT),
             line(),
             "}",
         ]
@@ -1362,6 +1388,17 @@ pub fn string(value: &str) -> Document<'_> {
     }
 }
 
+/// A string of text such as source code, rather than the contents of a Gleam
+/// string literal, which has its escape sequences written as they are in Gleam.
+fn text_string<'a>(value: &str) -> Document<'a> {
+    let escaped = value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r");
+    Document::String(escaped).surround("\"", "\"")
+}
+
 pub fn array<'a, Elements: IntoIterator<Item = Output<'a>>>(elements: Elements) -> Output<'a> {
     let elements = Itertools::intersperse(elements.into_iter(), Ok(break_(",", ", ")))
         .collect::<Result<Vec<_>, _>>()?;
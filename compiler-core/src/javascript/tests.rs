@@ -147,7 +147,7 @@ pub fn compile_js(src: &str, deps: Vec<(&str, &str, &str)>) -> String {
         &ast,
         &line_numbers,
         Utf8Path::new(""),
-        &"".into(),
+        &src.into(),
         TargetSupport::NotEnforced,
         TypeScriptDeclarations::None,
     )
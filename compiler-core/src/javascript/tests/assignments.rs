@@ -185,6 +185,19 @@ pub fn main() {
     );
 }
 
+#[test]
+fn source_of_multiline_assert_is_escaped() {
+    assert_js!(
+        r#"
+pub fn main(x) {
+  let assert #("\\wibble", Ok(y)) =
+    x
+  y
+}
+"#
+    );
+}
+
 // https://github.com/gleam-lang/gleam/issues/2931
 #[test]
 fn keyword_assignment() {
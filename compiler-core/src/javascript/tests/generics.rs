@@ -54,3 +54,16 @@ fn task_typescript() {
     pub type Task(a) = fn() -> Promise(a)"#,
     );
 }
+
+#[test]
+fn many_generics_typescript() {
+    let src = r#"pub type Wibble(a, b, c, d, e, f) {
+  Wibble(a: a, b: b, c: c, d: d, e: e, f: f)
+}
+"#;
+    let output = crate::javascript::tests::compile_ts(src, vec![]);
+    for _ in 0..10 {
+        assert_eq!(crate::javascript::tests::compile_ts(src, vec![]), output);
+    }
+    insta::assert_snapshot!(insta::internals::AutoName, output, src);
+}
@@ -58,6 +58,7 @@ fn name_with_generics<'a>(
     let generic_usages = collect_generic_usages(HashMap::new(), types);
     let generic_names: Vec<Document<'_>> = generic_usages
         .keys()
+        .sorted()
         .map(|id| id_to_type_var(*id))
         .collect();
 
@@ -6,9 +6,10 @@ use lsp_types::{CodeAction, CodeActionKind, CodeActionParams, TextEdit, Url};
 use crate::{
     ast::{self, visit::Visit as _, SrcSpan},
     build,
+    config::DiscardedValues,
     line_numbers::LineNumbers,
     parse::extra::ModuleExtra,
-    type_::Type,
+    type_::{Type, ValueConstructor, ValueConstructorVariant},
 };
 
 use super::{engine::overlaps, src_span_to_lsp_range};
@@ -264,3 +265,363 @@ impl<'a> RedundantTupleInCaseSubject<'a> {
         edits
     }
 }
+
+/// Code action to explicitly discard the value of an expression whose result
+/// would otherwise be silently thrown away, which the compiler warns about.
+///
+/// # Basic example:
+///
+/// ```gleam
+/// pub fn main() {
+///   file.write("Hello!", to: "wibble.txt")
+///   Nil
+/// }
+/// ```
+///
+/// Becomes:
+///
+/// ```gleam
+/// pub fn main() {
+///   let _ = file.write("Hello!", to: "wibble.txt")
+///   Nil
+/// }
+/// ```
+///
+/// If the discarded value is a `Result` the code action to assert that it is
+/// `Ok` with `let assert Ok(_) =` is offered as well.
+pub struct DiscardedValue<'a> {
+    line_numbers: LineNumbers,
+    params: &'a CodeActionParams,
+    module: &'a ast::TypedModule,
+    discarded_values: DiscardedValues,
+    discarded: Vec<(SrcSpan, bool)>,
+}
+
+impl<'ast> ast::visit::Visit<'ast> for DiscardedValue<'_> {
+    fn visit_typed_function(&mut self, fun: &'ast ast::TypedFunction) {
+        self.check_statements(&fun.body);
+        ast::visit::visit_typed_function(self, fun)
+    }
+
+    fn visit_typed_expr_block(
+        &mut self,
+        location: &'ast SrcSpan,
+        statements: &'ast [ast::TypedStatement],
+    ) {
+        self.check_statements(statements);
+        ast::visit::visit_typed_expr_block(self, location, statements)
+    }
+
+    fn visit_typed_expr_fn(
+        &mut self,
+        location: &'ast SrcSpan,
+        typ: &'ast Arc<Type>,
+        is_capture: &'ast bool,
+        args: &'ast [ast::TypedArg],
+        body: &'ast [ast::TypedStatement],
+        return_annotation: &'ast Option<ast::TypeAst>,
+    ) {
+        self.check_statements(body);
+        ast::visit::visit_typed_expr_fn(
+            self,
+            location,
+            typ,
+            is_capture,
+            args,
+            body,
+            return_annotation,
+        )
+    }
+}
+
+impl<'a> DiscardedValue<'a> {
+    pub fn new(
+        module: &'a build::Module,
+        params: &'a CodeActionParams,
+        discarded_values: DiscardedValues,
+    ) -> Self {
+        Self {
+            line_numbers: LineNumbers::new(&module.code),
+            params,
+            module: &module.ast,
+            discarded_values,
+            discarded: vec![],
+        }
+    }
+
+    pub fn code_actions(mut self) -> Vec<CodeAction> {
+        self.visit_typed_module(self.module);
+
+        let mut actions = vec![];
+        for (location, is_result) in self.discarded {
+            let position = src_span_to_lsp_range(
+                SrcSpan::new(location.start, location.start),
+                &self.line_numbers,
+            );
+            let insert = |text: &str| {
+                vec![TextEdit {
+                    range: position,
+                    new_text: text.to_string(),
+                }]
+            };
+            let uri = &self.params.text_document.uri;
+
+            CodeActionBuilder::new("Assign unused value to `_`")
+                .kind(CodeActionKind::QUICKFIX)
+                .changes(uri.clone(), insert("let _ = "))
+                .preferred(!is_result)
+                .push_to(&mut actions);
+
+            if is_result {
+                CodeActionBuilder::new("Assert the result is `Ok`")
+                    .kind(CodeActionKind::QUICKFIX)
+                    .changes(uri.clone(), insert("let assert Ok(_) = "))
+                    .preferred(false)
+                    .push_to(&mut actions);
+            }
+        }
+
+        actions
+    }
+
+    fn check_statements(&mut self, statements: &[ast::TypedStatement]) {
+        // The last statement is the returned value so it is never discarded
+        let Some((_, discarded)) = statements.split_last() else {
+            return;
+        };
+
+        for statement in discarded {
+            let ast::Statement::Expression(expression) = statement else {
+                continue;
+            };
+
+            // Literals and constructors are better deleted than assigned
+            if expression.is_literal() || expression.is_pure_value_constructor() {
+                continue;
+            }
+
+            let type_ = expression.type_();
+            let is_result = type_.is_result();
+            let warns = match self.discarded_values {
+                DiscardedValues::All => is_result || type_.is_meaningful(),
+                DiscardedValues::Results => is_result,
+            };
+            if !warns {
+                continue;
+            }
+
+            let location = expression.location();
+            let range = src_span_to_lsp_range(location, &self.line_numbers);
+            if overlaps(self.params.range, range) {
+                self.discarded.push((location, is_result));
+            }
+        }
+    }
+}
+
+/// Code action to refer to a function imported unqualified through its module
+/// instead, rewriting everywhere it is used and removing it from the list of
+/// unqualified imports.
+///
+/// # Basic example:
+///
+/// ```gleam
+/// import gleam/list.{map}
+///
+/// pub fn main() {
+///   map([1, 2], double)
+/// }
+/// ```
+///
+/// Becomes:
+///
+/// ```gleam
+/// import gleam/list
+///
+/// pub fn main() {
+///   list.map([1, 2], double)
+/// }
+/// ```
+///
+/// The code action is only available for functions, as constants and record
+/// constructors can be used in guards and patterns.
+pub struct QualifyImportedValue<'a> {
+    line_numbers: LineNumbers,
+    code: &'a EcoString,
+    params: &'a CodeActionParams,
+    module: &'a ast::TypedModule,
+}
+
+impl<'a> QualifyImportedValue<'a> {
+    pub fn new(module: &'a build::Module, params: &'a CodeActionParams) -> Self {
+        Self {
+            line_numbers: LineNumbers::new(&module.code),
+            code: &module.code,
+            params,
+            module: &module.ast,
+        }
+    }
+
+    pub fn code_actions(self) -> Vec<CodeAction> {
+        let mut actions = vec![];
+        for definition in &self.module.definitions {
+            let ast::Definition::Import(import) = definition else {
+                continue;
+            };
+            // A module imported as `_` cannot be referred to
+            let Some(qualifier) = import.used_name() else {
+                continue;
+            };
+
+            for value in &import.unqualified_values {
+                let range = src_span_to_lsp_range(value.location, &self.line_numbers);
+                if !overlaps(self.params.range, range) {
+                    continue;
+                }
+
+                let mut references = ValueReferences {
+                    module: &import.module,
+                    name: value.used_name(),
+                    locations: vec![],
+                    only_functions: true,
+                };
+                references.visit_typed_module(self.module);
+                if references.locations.is_empty() || !references.only_functions {
+                    continue;
+                }
+
+                let mut edits = references
+                    .locations
+                    .into_iter()
+                    .map(|location| TextEdit {
+                        range: src_span_to_lsp_range(location, &self.line_numbers),
+                        new_text: format!("{qualifier}.{}", value.name),
+                    })
+                    .collect::<Vec<_>>();
+                edits.push(TextEdit {
+                    range: src_span_to_lsp_range(
+                        self.unqualified_import_span(import, value),
+                        &self.line_numbers,
+                    ),
+                    new_text: "".into(),
+                });
+                edits.sort_by_key(|edit| edit.range.start);
+
+                CodeActionBuilder::new(&format!("Qualify `{}`", value.used_name()))
+                    .kind(CodeActionKind::QUICKFIX)
+                    .changes(self.params.text_document.uri.clone(), edits)
+                    .preferred(false)
+                    .push_to(&mut actions);
+            }
+        }
+        actions
+    }
+
+    /// The span to delete to remove an item from an import's unqualified
+    /// items, including the separating comma, or the braces if it is the only
+    /// item.
+    fn unqualified_import_span(
+        &self,
+        import: &ast::Import<EcoString>,
+        item: &ast::UnqualifiedImport,
+    ) -> SrcSpan {
+        let mut items = import
+            .unqualified_values
+            .iter()
+            .chain(&import.unqualified_types)
+            .map(|item| item.location)
+            .collect::<Vec<_>>();
+        items.sort_by_key(|location| location.start);
+        let index = items
+            .iter()
+            .position(|location| *location == item.location)
+            .expect("unqualified import item");
+
+        let previous = index.checked_sub(1).and_then(|index| items.get(index));
+        match (previous, items.get(index + 1)) {
+            (_, Some(next)) => SrcSpan::new(item.location.start, next.start),
+            (Some(previous), None) => SrcSpan::new(previous.end, item.location.end),
+            (None, None) => {
+                let before = self.code.get(..item.location.start as usize).unwrap_or("");
+                let start = before
+                    .rfind('{')
+                    .and_then(|brace| before[..brace].rfind('.'))
+                    .unwrap_or(item.location.start as usize);
+                let after = self.code.get(item.location.end as usize..).unwrap_or("");
+                let end = after
+                    .find('}')
+                    .map(|brace| item.location.end as usize + brace + 1)
+                    .unwrap_or(item.location.end as usize);
+                SrcSpan::new(start as u32, end as u32)
+            }
+        }
+    }
+}
+
+/// Finds the places a value imported unqualified is referred to by name.
+struct ValueReferences<'a> {
+    module: &'a EcoString,
+    name: &'a EcoString,
+    locations: Vec<SrcSpan>,
+    only_functions: bool,
+}
+
+impl ValueReferences<'_> {
+    fn register(&mut self, location: SrcSpan, name: &EcoString, constructor: &ValueConstructor) {
+        if name != self.name {
+            return;
+        }
+        match &constructor.variant {
+            ValueConstructorVariant::ModuleFn { module, .. } if module == self.module => {
+                self.locations.push(location)
+            }
+            ValueConstructorVariant::ModuleConstant { module, .. }
+            | ValueConstructorVariant::Record { module, .. }
+                if module == self.module =>
+            {
+                self.only_functions = false
+            }
+            _ => (),
+        }
+    }
+
+    fn visit_constant(&mut self, constant: &ast::TypedConstant) {
+        match constant {
+            ast::Constant::Var {
+                location,
+                module: None,
+                name,
+                constructor: Some(constructor),
+                ..
+            } => self.register(*location, name, constructor),
+            ast::Constant::Tuple { elements, .. } | ast::Constant::List { elements, .. } => {
+                elements
+                    .iter()
+                    .for_each(|element| self.visit_constant(element))
+            }
+            ast::Constant::Record { args, .. } => {
+                args.iter().for_each(|arg| self.visit_constant(&arg.value))
+            }
+            _ => (),
+        }
+    }
+}
+
+impl<'ast> ast::visit::Visit<'ast> for ValueReferences<'_> {
+    fn visit_typed_definition(&mut self, def: &'ast ast::TypedDefinition) {
+        if let ast::Definition::ModuleConstant(constant) = def {
+            self.visit_constant(&constant.value);
+        }
+        ast::visit::visit_typed_definition(self, def)
+    }
+
+    fn visit_typed_expr_var(
+        &mut self,
+        location: &'ast SrcSpan,
+        constructor: &'ast ValueConstructor,
+        name: &'ast EcoString,
+    ) {
+        self.register(*location, name, constructor);
+        ast::visit::visit_typed_expr_var(self, location, constructor, name)
+    }
+}
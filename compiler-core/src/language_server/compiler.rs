@@ -67,6 +67,7 @@ where
 
         let options = build::Options {
             warnings_as_errors: false,
+            verify_artefacts: false,
             mode: Mode::Lsp,
             target: None,
             codegen: build::Codegen::None,
@@ -85,6 +86,9 @@ where
         // To avoid the Erlang compiler printing to stdout (and thus
         // violating LSP which is currently using stdout) we silence it.
         project_compiler.subprocess_stdio = Stdio::Null;
+        // The paths of the compiled dependency modules are needed to reset
+        // their diagnostics.
+        project_compiler.keep_dependency_modules = true;
 
         Ok(Self {
             locker: locker.into(),
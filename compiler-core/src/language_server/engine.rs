@@ -25,7 +25,9 @@ use std::sync::Arc;
 use strum::IntoEnumIterator;
 
 use super::{
-    code_action::{CodeActionBuilder, RedundantTupleInCaseSubject},
+    code_action::{
+        CodeActionBuilder, DiscardedValue, QualifyImportedValue, RedundantTupleInCaseSubject,
+    },
     src_span_to_lsp_range, DownloadDependencies, MakeLocker,
 };
 
@@ -258,6 +260,19 @@ where
 
             code_action_unused_imports(module, &params, &mut actions);
             actions.extend(RedundantTupleInCaseSubject::new(module, &params).code_actions());
+            actions.extend(QualifyImportedValue::new(module, &params).code_actions());
+            actions.extend(
+                DiscardedValue::new(
+                    module,
+                    &params,
+                    this.compiler
+                        .project_compiler
+                        .config
+                        .warnings
+                        .discarded_values,
+                )
+                .code_actions(),
+            );
 
             Ok(if actions.is_empty() {
                 None
@@ -972,7 +987,7 @@ fn delete_line(span: &SrcSpan, line_numbers: &LineNumbers) -> bool {
     })
 }
 
-fn get_expr_qualified_name(expression: &TypedExpr) -> Option<(&EcoString, &EcoString)> {
+fn get_expr_qualified_name(expression: &TypedExpr) -> Option<(&str, &EcoString)> {
     match expression {
         TypedExpr::Var {
             name, constructor, ..
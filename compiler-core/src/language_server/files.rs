@@ -11,6 +11,7 @@ use crate::{
 };
 
 use camino::{Utf8Path, Utf8PathBuf};
+use lsp_types as lsp;
 
 // A proxy intended for `LanguageServer` to use when files are modified in
 // memory but not yet saved to disc by the client.
@@ -48,11 +49,51 @@ where
         write_result
     }
 
+    /// Applies edits from the client to the text of a file, writing the result
+    /// to the memory cache. Edits without a range replace the whole text.
+    pub fn edit_mem_cache(
+        &mut self,
+        path: &Utf8Path,
+        changes: &[lsp::TextDocumentContentChangeEvent],
+    ) -> Result<()> {
+        let mut text = self.read(path)?;
+        for change in changes {
+            match change.range {
+                Some(range) => {
+                    let start = byte_index(&text, range.start);
+                    let end = byte_index(&text, range.end).max(start);
+                    text.replace_range(start..end, &change.text);
+                }
+                None => text = change.text.clone(),
+            }
+        }
+        self.write_mem_cache(path, &text)
+    }
+
     pub fn delete_mem_cache(&self, path: &Utf8Path) -> Result<()> {
         self.edit_cache.delete_directory(path)
     }
 }
 
+// Converts a position sent by the client, where the character is counted in
+// UTF-16 code units, to a byte index into the text. Positions past the end of
+// a line are at the end of that line.
+fn byte_index(text: &str, position: lsp::Position) -> usize {
+    let mut line = 0;
+    let mut character = 0;
+    for (index, char) in text.char_indices() {
+        if line == position.line {
+            if character >= position.character || char == '\n' {
+                return index;
+            }
+            character += char.len_utf16() as u32;
+        } else if char == '\n' {
+            line += 1;
+        }
+    }
+    text.len()
+}
+
 // All write operations goes to disk (for mem-cache use the dedicated `_mem_cache` methods)
 impl<IO> FileSystemWriter for FileSystemProxy<IO>
 where
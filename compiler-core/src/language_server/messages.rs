@@ -58,6 +58,12 @@ impl Request {
 pub enum Notification {
     /// A Gleam file has been modified in memory, and the new text is provided.
     SourceFileChangedInMemory { path: Utf8PathBuf, text: String },
+    /// A Gleam file has been edited in memory, and the changes to its text are
+    /// provided.
+    SourceFileEditedInMemory {
+        path: Utf8PathBuf,
+        changes: Vec<lsp::TextDocumentContentChangeEvent>,
+    },
     /// A Gleam file has been saved or closed in the editor.
     SourceFileMatchesDisc { path: Utf8PathBuf },
     /// gleam.toml has changed.
@@ -79,9 +85,9 @@ impl Notification {
             }
             "textDocument/didChange" => {
                 let params = cast_notification::<DidChangeTextDocument>(notification);
-                let notification = Notification::SourceFileChangedInMemory {
+                let notification = Notification::SourceFileEditedInMemory {
                     path: super::path(&params.text_document.uri),
-                    text: params.content_changes.into_iter().last()?.text,
+                    changes: params.content_changes,
                 };
                 Some(Message::Notification(notification))
             }
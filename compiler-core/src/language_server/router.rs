@@ -1,6 +1,6 @@
 use crate::{
     build::SourceFingerprint,
-    error::{FileIoAction, FileKind},
+    config::PackageConfig,
     io::{CommandExecutor, FileSystemReader, FileSystemWriter},
     language_server::{
         engine::LanguageServerEngine, files::FileSystemProxy, progress::ProgressReporter,
@@ -129,12 +129,7 @@ where
         let config_path = paths.root_config();
         let modification_time = io.modification_time(&config_path)?;
         let toml = io.read(&config_path)?;
-        let config = toml::from_str(&toml).map_err(|e| Error::FileIo {
-            action: FileIoAction::Parse,
-            kind: FileKind::File,
-            path: config_path,
-            err: Some(e.to_string()),
-        })?;
+        let config = PackageConfig::parse(&toml, &config_path)?;
         let engine = LanguageServerEngine::new(config, progress_reporter, io, paths)?;
         let project = Project {
             engine,
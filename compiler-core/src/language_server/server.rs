@@ -3,7 +3,7 @@ use super::{
     progress::ConnectionProgressReporter,
 };
 use crate::{
-    diagnostic::{Diagnostic, Level},
+    diagnostic::{Diagnostic, Level, Theme},
     io::{CommandExecutor, FileSystemReader, FileSystemWriter},
     language_server::{
         engine::{self, LanguageServerEngine},
@@ -42,6 +42,9 @@ pub struct LanguageServer<'a, IO> {
     router: Router<IO, ConnectionProgressReporter<'a>>,
     changed_projects: HashSet<Utf8PathBuf>,
     io: FileSystemProxy<IO>,
+    /// Used to render the messages shown for diagnostics that do not belong
+    /// to a file.
+    theme: Theme,
 }
 
 impl<'a, IO> LanguageServer<'a, IO>
@@ -53,7 +56,7 @@ where
         + MakeLocker
         + Clone,
 {
-    pub fn new(connection: &'a lsp_server::Connection, io: IO) -> Result<Self> {
+    pub fn new(connection: &'a lsp_server::Connection, io: IO, theme: Theme) -> Result<Self> {
         let initialise_params = initialisation_handshake(connection);
         let reporter = ConnectionProgressReporter::new(connection, &initialise_params);
         let io = FileSystemProxy::new(io);
@@ -65,6 +68,7 @@ where
             outside_of_project_feedback: FeedbackBookKeeper::default(),
             router,
             io,
+            theme,
         })
     }
 
@@ -123,6 +127,9 @@ where
             Notification::SourceFileChangedInMemory { path, text } => {
                 self.cache_file_in_memory(path, text)
             }
+            Notification::SourceFileEditedInMemory { path, changes } => {
+                self.edit_file_in_memory(path, changes)
+            }
             Notification::ConfigFileChanged { path } => self.watched_files_changed(path),
         };
         self.publish_feedback(feedback);
@@ -210,7 +217,7 @@ where
                     Level::Error => lsp::MessageType::ERROR,
                     Level::Warning => lsp::MessageType::WARNING,
                 },
-                message: message.text,
+                message: message.to_plain_string(self.theme),
             };
             let notification = lsp_server::Notification {
                 method: "window/showMessage".into(),
@@ -327,6 +334,18 @@ where
         Feedback::none()
     }
 
+    fn edit_file_in_memory(
+        &mut self,
+        path: Utf8PathBuf,
+        changes: Vec<lsp::TextDocumentContentChangeEvent>,
+    ) -> Feedback {
+        self.project_changed(&path);
+        if let Err(error) = self.io.edit_mem_cache(&path, &changes) {
+            return self.outside_of_project_feedback.error(error);
+        }
+        Feedback::none()
+    }
+
     fn discard_in_memory_cache(&mut self, path: Utf8PathBuf) -> Feedback {
         self.project_changed(&path);
         if let Err(error) = self.io.delete_mem_cache(&path) {
@@ -363,7 +382,7 @@ fn initialisation_handshake(connection: &lsp_server::Connection) -> InitializePa
         text_document_sync: Some(lsp::TextDocumentSyncCapability::Options(
             lsp::TextDocumentSyncOptions {
                 open_close: Some(true),
-                change: Some(lsp::TextDocumentSyncKind::FULL),
+                change: Some(lsp::TextDocumentSyncKind::INCREMENTAL),
                 will_save: None,
                 will_save_wait_until: None,
                 save: Some(lsp::TextDocumentSyncSaveOptions::SaveOptions(
@@ -430,7 +449,8 @@ fn diagnostic_to_lsp(diagnostic: Diagnostic) -> Vec<lsp::Diagnostic> {
         Level::Warning => lsp::DiagnosticSeverity::WARNING,
     };
     let hint = diagnostic.hint;
-    let mut text = diagnostic.title;
+    let title = diagnostic.title;
+    let mut text = title.clone();
 
     if let Some(label) = diagnostic
         .location
@@ -455,6 +475,17 @@ fn diagnostic_to_lsp(diagnostic: Diagnostic) -> Vec<lsp::Diagnostic> {
         .location
         .expect("Diagnostic given to LSP without location");
     let line_numbers = LineNumbers::new(&location.src);
+    let related_information = location
+        .extra_labels
+        .iter()
+        .map(|label| lsp::DiagnosticRelatedInformation {
+            location: lsp::Location {
+                uri: path_to_uri(location.path.clone()),
+                range: src_span_to_lsp_range(label.span, &line_numbers),
+            },
+            message: label.text.clone().unwrap_or_else(|| title.clone()),
+        })
+        .collect::<Vec<_>>();
 
     let main = lsp::Diagnostic {
         range: src_span_to_lsp_range(location.label.span, &line_numbers),
@@ -463,7 +494,7 @@ fn diagnostic_to_lsp(diagnostic: Diagnostic) -> Vec<lsp::Diagnostic> {
         code_description: None,
         source: None,
         message: text,
-        related_information: None,
+        related_information: (!related_information.is_empty()).then_some(related_information),
         tags: None,
         data: None,
     };
@@ -2,6 +2,7 @@ mod action;
 mod compilation;
 mod completion;
 mod definition;
+mod edit;
 mod hover;
 
 use std::{
@@ -11,7 +12,7 @@ use std::{
 };
 
 use ecow::EcoString;
-use hexpm::version::{Range, Version};
+use hexpm::version::Version;
 
 use camino::{Utf8Path, Utf8PathBuf};
 use lsp_types::{Position, TextDocumentIdentifier, TextDocumentPositionParams, Url};
@@ -93,21 +94,17 @@ impl LanguageServerTestIO {
     }
 
     pub fn hex_dep_module(&self, dep: &str, name: &str, code: &str) -> Utf8PathBuf {
-        let dep_dir = self.paths.build_packages_package(dep).join("src");
+        let dep_dir = self
+            .paths
+            .build_packages_package_source(&hex_package(dep))
+            .join("src");
         let path = dep_dir.join(name).with_extension("gleam");
         self.module(&path, code);
         path
     }
 
     pub fn add_hex_package(&mut self, name: &str) {
-        self.manifest.packages.push(ManifestPackage {
-            name: name.into(),
-            source: ManifestPackageSource::Hex {
-                outer_checksum: Base16Checksum(vec![]),
-            },
-            build_tools: vec!["gleam".into()],
-            ..Default::default()
-        });
+        self.manifest.packages.push(hex_package(name));
     }
 
     fn module(&self, path: &Utf8Path, code: &str) {
@@ -120,6 +117,18 @@ impl LanguageServerTestIO {
     }
 }
 
+fn hex_package(name: &str) -> ManifestPackage {
+    ManifestPackage {
+        name: name.into(),
+        source: ManifestPackageSource::Hex {
+            outer_checksum: Base16Checksum(vec![0xab, 0xcd]),
+            organization: None,
+        },
+        build_tools: vec!["gleam".into()],
+        ..Default::default()
+    }
+}
+
 impl FileSystemReader for LanguageServerTestIO {
     fn gleam_source_files(&self, dir: &Utf8Path) -> Vec<Utf8PathBuf> {
         self.io.gleam_source_files(dir)
@@ -288,11 +297,9 @@ fn add_package_from_manifest<B>(
     _ = compiler.config.dependencies.insert(
         package.name.clone(),
         match package.source {
-            ManifestPackageSource::Hex { .. } => Requirement::Hex {
-                version: Range::new("1.0.0".into()),
-            },
-            ManifestPackageSource::Local { ref path } => Requirement::Path { path: path.into() },
-            ManifestPackageSource::Git { ref repo, .. } => Requirement::Git { git: repo.clone() },
+            ManifestPackageSource::Hex { .. } => Requirement::hex("1.0.0"),
+            ManifestPackageSource::Local { ref path } => Requirement::path(path.as_str()),
+            ManifestPackageSource::Git { ref repo, .. } => Requirement::git(repo),
         },
     );
     write_toml_from_manifest(engine, toml_path, package);
@@ -307,11 +314,9 @@ fn add_dev_package_from_manifest<B>(
     _ = compiler.config.dev_dependencies.insert(
         package.name.clone(),
         match package.source {
-            ManifestPackageSource::Hex { .. } => Requirement::Hex {
-                version: Range::new("1.0.0".into()),
-            },
-            ManifestPackageSource::Local { ref path } => Requirement::Path { path: path.into() },
-            ManifestPackageSource::Git { ref repo, .. } => Requirement::Git { git: repo.clone() },
+            ManifestPackageSource::Hex { .. } => Requirement::hex("1.0.0"),
+            ManifestPackageSource::Local { ref path } => Requirement::path(path.as_str()),
+            ManifestPackageSource::Git { ref repo, .. } => Requirement::git(repo),
         },
     );
     write_toml_from_manifest(engine, toml_path, package);
@@ -451,39 +456,28 @@ impl<'a> TestProject<'a> {
             let _ = io.test_module(name, code);
         });
         for package in &io.manifest.packages {
-            let toml_path = engine.paths.build_packages_package_config(&package.name);
+            let toml_path = engine
+                .paths
+                .build_packages_package_source(package)
+                .join("gleam.toml");
             add_package_from_manifest(&mut engine, toml_path, package.clone());
         }
 
         // Add an indirect dependency manifest
-        let toml_path = engine.paths.build_packages_package_config("indirect_hex");
-        write_toml_from_manifest(
-            &mut engine,
-            toml_path,
-            ManifestPackage {
-                name: "indirect_hex".into(),
-                source: ManifestPackageSource::Hex {
-                    outer_checksum: Base16Checksum(vec![]),
-                },
-                build_tools: vec!["gleam".into()],
-                ..Default::default()
-            },
-        );
+        let package = hex_package("indirect_hex");
+        let toml_path = engine
+            .paths
+            .build_packages_package_source(&package)
+            .join("gleam.toml");
+        write_toml_from_manifest(&mut engine, toml_path, package);
 
         // Add a dev dependency
-        let toml_path = engine.paths.build_packages_package_config("dev_hex");
-        add_dev_package_from_manifest(
-            &mut engine,
-            toml_path,
-            ManifestPackage {
-                name: "dev_hex".into(),
-                source: ManifestPackageSource::Hex {
-                    outer_checksum: Base16Checksum(vec![]),
-                },
-                build_tools: vec!["gleam".into()],
-                ..Default::default()
-            },
-        );
+        let package = hex_package("dev_hex");
+        let toml_path = engine
+            .paths
+            .build_packages_package_source(&package)
+            .join("gleam.toml");
+        add_dev_package_from_manifest(&mut engine, toml_path, package);
 
         engine
     }
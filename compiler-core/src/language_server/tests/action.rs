@@ -54,6 +54,9 @@ fn engine_response(src: &str, line: u32) -> engine::Response<Option<Vec<lsp_type
 
 const REMOVE_UNUSED_IMPORTS_TITLE: &str = "Remove unused imports";
 const REMOVE_REDUNDANT_TUPLES: &str = "Remove redundant tuples";
+const ASSIGN_UNUSED_VALUE: &str = "Assign unused value to `_`";
+const ASSERT_RESULT_IS_OK: &str = "Assert the result is `Ok`";
+const QUALIFY_IS_OK: &str = "Qualify `is_ok`";
 
 fn apply_first_code_action_with_title(src: &str, line: u32, title: &str) -> String {
     let response = engine_response(src, line)
@@ -85,18 +88,19 @@ fn apply_code_edit(
 ) -> String {
     let mut result = src.to_string();
     let line_numbers = LineNumbers::new(src);
-    let mut offset = 0;
+    let mut offset: i64 = 0;
     for (change_url, change) in changes {
         if url != change_url {
             panic!("Unknown url {}", change_url)
         }
         for edit in change {
-            let start =
-                line_numbers.byte_index(edit.range.start.line, edit.range.start.character) - offset;
-            let end =
-                line_numbers.byte_index(edit.range.end.line, edit.range.end.character) - offset;
+            let start = line_numbers.byte_index(edit.range.start.line, edit.range.start.character)
+                as i64
+                + offset;
+            let end = line_numbers.byte_index(edit.range.end.line, edit.range.end.character) as i64
+                + offset;
             let range = (start as usize)..(end as usize);
-            offset += end - start - edit.new_text.len() as u32;
+            offset += edit.new_text.len() as i64 - (end - start);
             result.replace_range(range, &edit.new_text);
         }
     }
@@ -299,6 +303,189 @@ pub fn main() {
     );
 }
 
+#[test]
+fn test_assign_discarded_value() {
+    let code = "
+pub fn main() {
+  wibble()
+  Nil
+}
+
+fn wibble() { 1 }
+";
+
+    let expected = "
+pub fn main() {
+  let _ = wibble()
+  Nil
+}
+
+fn wibble() { 1 }
+";
+
+    assert_eq!(
+        apply_first_code_action_with_title(code, 3, ASSIGN_UNUSED_VALUE),
+        expected
+    );
+}
+
+#[test]
+fn test_assert_discarded_result_is_ok() {
+    let code = "
+pub fn main() {
+  wibble()
+  Nil
+}
+
+fn wibble() { Ok(1) }
+";
+
+    let expected = "
+pub fn main() {
+  let assert Ok(_) = wibble()
+  Nil
+}
+
+fn wibble() { Ok(1) }
+";
+
+    assert_eq!(
+        apply_first_code_action_with_title(code, 3, ASSERT_RESULT_IS_OK),
+        expected
+    );
+}
+
+#[test]
+fn test_assign_discarded_value_in_block() {
+    let code = "
+pub fn main() {
+  {
+    wibble()
+    Nil
+  }
+}
+
+fn wibble() { \"wobble\" }
+";
+
+    let expected = "
+pub fn main() {
+  {
+    let _ = wibble()
+    Nil
+  }
+}
+
+fn wibble() { \"wobble\" }
+";
+
+    assert_eq!(
+        apply_first_code_action_with_title(code, 5, ASSIGN_UNUSED_VALUE),
+        expected
+    );
+}
+
+#[test]
+fn test_no_action_for_discarded_nil() {
+    let code = "
+pub fn main() {
+  wibble()
+  Nil
+}
+
+fn wibble() { Nil }
+";
+
+    assert!(engine_response(code, 3)
+        .result
+        .expect("ok response")
+        .is_none());
+}
+
+#[test]
+fn test_qualify_only_unqualified_import() {
+    let code = "
+import result.{is_ok}
+
+pub fn main() {
+  is_ok()
+}
+";
+
+    let expected = "
+import result
+
+pub fn main() {
+  result.is_ok()
+}
+";
+
+    assert_eq!(
+        apply_first_code_action_with_title(code, 5, QUALIFY_IS_OK),
+        expected
+    );
+}
+
+#[test]
+fn test_qualify_one_of_many_unqualified_imports() {
+    let code = "
+import result.{all, is_ok, is_err} as res
+
+const checker = is_ok
+
+pub fn main() {
+  all()
+  is_err()
+  is_ok()
+  checker
+}
+";
+
+    let expected = "
+import result.{all, is_err} as res
+
+const checker = res.is_ok
+
+pub fn main() {
+  all()
+  is_err()
+  res.is_ok()
+  checker
+}
+";
+
+    assert_eq!(
+        apply_first_code_action_with_title(code, 10, QUALIFY_IS_OK),
+        expected
+    );
+}
+
+#[test]
+fn test_qualify_last_unqualified_import() {
+    let code = "
+import result.{all, is_ok}
+
+pub fn main() {
+  all()
+  is_ok
+}
+";
+
+    let expected = "
+import result.{all}
+
+pub fn main() {
+  all()
+  result.is_ok
+}
+";
+
+    assert_eq!(
+        apply_first_code_action_with_title(code, 6, QUALIFY_IS_OK),
+        expected
+    );
+}
+
 /* TODO: implement qualified unused location
 #[test]
 fn test_remove_unused_qualified_action() {
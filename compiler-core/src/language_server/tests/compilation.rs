@@ -37,12 +37,14 @@ fn compile_error_in_src() {
     let io = LanguageServerTestIO::new();
     let mut engine = setup_engine(&io);
 
-    _ = io.src_module("app/error", "pub type Error {");
+    let path = io.src_module("app/error", "pub type Error {");
 
+    // The parser recovers from the syntax error, so the module is still
+    // analysed.
     let response = engine.compile_please();
     assert!(response.result.is_err());
     assert!(response.warnings.is_empty());
-    assert_eq!(response.compilation, Compilation::Yes(vec![]));
+    assert_eq!(response.compilation, Compilation::Yes(vec![path]));
 
     drop(engine);
     let actions = io.into_actions();
@@ -69,12 +71,14 @@ fn compile_error_in_test() {
     let io = LanguageServerTestIO::new();
     let mut engine = setup_engine(&io);
 
-    _ = io.test_module("app/error", "pub type Error {");
+    let path = io.test_module("app/error", "pub type Error {");
 
+    // The parser recovers from the syntax error, so the module is still
+    // analysed.
     let response = engine.compile_please();
     assert!(response.result.is_err());
     assert!(response.warnings.is_empty());
-    assert_eq!(response.compilation, Compilation::Yes(vec![]));
+    assert_eq!(response.compilation, Compilation::Yes(vec![path]));
 
     drop(engine);
     let actions = io.into_actions();
@@ -212,3 +216,71 @@ fn dep_compile_recompile() {
         ]
     )
 }
+
+#[test]
+fn compile_after_edits() {
+    let io = LanguageServerTestIO::new();
+    let mut engine = setup_engine(&io);
+
+    let src = "pub fn main() {\n  1\n}\n\npub fn wibble() {\n  2\n}\n";
+    let path = io.src_module("app", src);
+    assert!(engine.compile_please().result.is_ok());
+
+    // The module's retained parse tree is updated with each edit.
+    let _ = io.src_module("app", &src.replace("1", "1 +"));
+    assert!(engine.compile_please().result.is_err());
+
+    let _ = io.src_module("app", &src.replace("1", "1 + 1"));
+    let response = engine.compile_please();
+    assert!(response.result.is_ok());
+    assert_eq!(response.compilation, Compilation::Yes(vec![path]));
+}
+
+#[test]
+fn dependent_module_analysis_is_reused() {
+    let io = LanguageServerTestIO::new();
+    let mut engine = setup_engine(&io);
+
+    let src = "pub fn wibble() { 1 }\n\nfn wobble() { 1 }\n";
+    let wibble = io.src_module("app/wibble", src);
+    let app = io.src_module(
+        "app",
+        "import app/wibble\npub fn main() { wibble.wibble() }",
+    );
+    let response = engine.compile_please();
+    assert!(response.result.is_ok());
+    assert_eq!(
+        response.compilation,
+        Compilation::Yes(vec![wibble.clone(), app.clone()])
+    );
+
+    // The interface of the imported module is unchanged, so the previous
+    // analysis of the importing module is used.
+    let _ = io.src_module(
+        "app/wibble",
+        &src.replace("wobble() { 1 }", "wobble() { 2 }"),
+    );
+    let response = engine.compile_please();
+    assert!(response.result.is_ok());
+    assert_eq!(response.compilation, Compilation::Yes(vec![wibble.clone()]));
+    assert!(engine.compiler.modules.contains_key("app"));
+
+    // The imported function has moved, so the importing module is analysed
+    // again for the language server to have its new location.
+    let _ = io.src_module("app/wibble", &format!("\n{src}"));
+    let response = engine.compile_please();
+    assert!(response.result.is_ok());
+    assert_eq!(
+        response.compilation,
+        Compilation::Yes(vec![wibble.clone(), app.clone()])
+    );
+
+    // The type of the imported function has changed.
+    let _ = io.src_module(
+        "app/wibble",
+        &src.replace("wibble() { 1 }", "wibble() { 1.0 }"),
+    );
+    let response = engine.compile_please();
+    assert!(response.result.is_ok());
+    assert_eq!(response.compilation, Compilation::Yes(vec![wibble, app]));
+}
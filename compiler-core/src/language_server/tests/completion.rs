@@ -1653,3 +1653,53 @@ pub fn main() {
         prelude_type_completions(),
     );
 }
+
+#[test]
+fn completions_in_function_with_syntax_error() {
+    let code = "
+import dep
+
+pub fn main() {
+  let x = 1
+  dep.
+
+pub fn wibble() {
+  Nil
+}
+";
+    let dep = "
+pub fn wobble() {
+  Nil
+}
+";
+
+    assert_eq!(
+        completion(
+            TestProject::for_source(code).add_module("dep", dep),
+            Position::new(4, 2)
+        ),
+        vec![
+            CompletionItem {
+                label: "dep.wobble".into(),
+                kind: Some(CompletionItemKind::FUNCTION),
+                detail: Some("fn() -> Nil".into()),
+                documentation: None,
+                ..Default::default()
+            },
+            CompletionItem {
+                label: "main".into(),
+                kind: Some(CompletionItemKind::FUNCTION),
+                detail: Some("fn() -> a".into()),
+                documentation: None,
+                ..Default::default()
+            },
+            CompletionItem {
+                label: "wibble".into(),
+                kind: Some(CompletionItemKind::FUNCTION),
+                detail: Some("fn() -> Nil".into()),
+                documentation: None,
+                ..Default::default()
+            },
+        ]
+    );
+}
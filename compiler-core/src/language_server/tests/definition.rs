@@ -353,7 +353,7 @@ fn main() {
             uri: Url::from_file_path(Utf8PathBuf::from(if cfg!(target_family = "windows") {
                 r"\\?\C:\build\packages\hex\src\example_module.gleam"
             } else {
-                "/build/packages/hex/src/example_module.gleam"
+                "/build/packages/hex-abcd/src/example_module.gleam"
             }))
             .unwrap(),
             range: Range {
@@ -389,7 +389,7 @@ fn main() {
             uri: Url::from_file_path(Utf8PathBuf::from(if cfg!(target_family = "windows") {
                 r"\\?\C:\build\packages\hex\src\example_module.gleam"
             } else {
-                "/build/packages/hex/src/example_module.gleam"
+                "/build/packages/hex-abcd/src/example_module.gleam"
             }))
             .unwrap(),
             range: Range {
@@ -434,7 +434,7 @@ fn main() {
             uri: Url::from_file_path(Utf8PathBuf::from(if cfg!(target_family = "windows") {
                 r"\\?\C:\build\packages\hex\src\example_module.gleam"
             } else {
-                "/build/packages/hex/src/example_module.gleam"
+                "/build/packages/hex-abcd/src/example_module.gleam"
             }))
             .unwrap(),
             range: Range {
@@ -463,7 +463,7 @@ fn main() {
             uri: Url::from_file_path(Utf8PathBuf::from(if cfg!(target_family = "windows") {
                 r"\\?\C:\build\packages\hex\src\example_module.gleam"
             } else {
-                "/build/packages/hex/src/example_module.gleam"
+                "/build/packages/hex-abcd/src/example_module.gleam"
             }))
             .unwrap(),
             range: Range {
@@ -580,7 +580,7 @@ fn main() {
             uri: Url::from_file_path(Utf8PathBuf::from(if cfg!(target_family = "windows") {
                 r"\\?\C:\build\packages\hex\src\example_module.gleam"
             } else {
-                "/build/packages/hex/src/example_module.gleam"
+                "/build/packages/hex-abcd/src/example_module.gleam"
             }))
             .unwrap(),
             range: Range {
@@ -693,7 +693,7 @@ fn make_var() -> example_module.Rec {
             uri: Url::from_file_path(Utf8PathBuf::from(if cfg!(target_family = "windows") {
                 r"\\?\C:\build\packages\hex\src\example_module.gleam"
             } else {
-                "/build/packages/hex/src/example_module.gleam"
+                "/build/packages/hex-abcd/src/example_module.gleam"
             }))
             .unwrap(),
             range: Range {
@@ -784,7 +784,7 @@ fn make_var() -> example_module.Wabble(example_module.Wibble(example_module.Wobb
             uri: Url::from_file_path(Utf8PathBuf::from(if cfg!(target_family = "windows") {
                 r"\\?\C:\build\packages\hex\src\example_module.gleam"
             } else {
-                "/build/packages/hex/src/example_module.gleam"
+                "/build/packages/hex-abcd/src/example_module.gleam"
             }))
             .unwrap(),
             range: Range {
@@ -0,0 +1,84 @@
+use lsp_types::{Range, TextDocumentContentChangeEvent};
+
+use super::*;
+
+fn edit(src: &str, changes: Vec<TextDocumentContentChangeEvent>) -> String {
+    let path = Utf8Path::new("/src/app.gleam");
+    let mut io = FileSystemProxy::new(InMemoryFileSystem::new());
+    io.write_mem_cache(path, src).expect("write");
+    io.edit_mem_cache(path, &changes).expect("edit");
+    io.read(path).expect("read")
+}
+
+fn change(start: (u32, u32), end: (u32, u32), text: &str) -> TextDocumentContentChangeEvent {
+    TextDocumentContentChangeEvent {
+        range: Some(Range::new(
+            Position::new(start.0, start.1),
+            Position::new(end.0, end.1),
+        )),
+        range_length: None,
+        text: text.into(),
+    }
+}
+
+#[test]
+fn edit_insert() {
+    assert_eq!(
+        edit(
+            "fn main() {\n  1\n}\n",
+            vec![change((1, 3), (1, 3), " + 2")]
+        ),
+        "fn main() {\n  1 + 2\n}\n"
+    );
+}
+
+#[test]
+fn edit_replace_across_lines() {
+    assert_eq!(
+        edit(
+            "fn main() {\n  1\n}\n",
+            vec![change((0, 10), (2, 0), "{ 2 ")]
+        ),
+        "fn main() { 2 }\n"
+    );
+}
+
+#[test]
+fn edit_several_changes_in_order() {
+    assert_eq!(
+        edit(
+            "let x = 1",
+            vec![
+                change((0, 4), (0, 5), "wibble"),
+                change((0, 0), (0, 3), "const")
+            ]
+        ),
+        "const wibble = 1"
+    );
+}
+
+#[test]
+fn edit_counts_utf16_code_units() {
+    assert_eq!(
+        edit("\"🙂é\" x", vec![change((0, 3), (0, 4), "ê")]),
+        "\"🙂ê\" x"
+    );
+}
+
+#[test]
+fn edit_past_end_of_line() {
+    assert_eq!(
+        edit("a\nb\n", vec![change((0, 10), (0, 10), "c")]),
+        "ac\nb\n"
+    );
+}
+
+#[test]
+fn edit_whole_text() {
+    let change = TextDocumentContentChangeEvent {
+        range: None,
+        range_length: None,
+        text: "wobble".into(),
+    };
+    assert_eq!(edit("wibble", vec![change]), "wobble");
+}
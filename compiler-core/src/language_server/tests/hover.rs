@@ -867,3 +867,35 @@ fn valid() { Nil }
         })
     );
 }
+
+#[test]
+fn hover_in_function_with_syntax_error() {
+    let code = "
+fn add_2(x) {
+  x + 2
+}
+
+pub fn main() {
+  let x = add_2(1)
+  case x {
+    1 ->
+}
+";
+
+    assert_eq!(
+        hover(TestProject::for_source(code), Position::new(6, 6)),
+        Some(Hover {
+            contents: HoverContents::Scalar(MarkedString::String(
+                "```gleam
+Int
+```
+"
+                .to_string()
+            )),
+            range: Some(Range {
+                start: Position::new(6, 6),
+                end: Position::new(6, 7)
+            }),
+        })
+    );
+}
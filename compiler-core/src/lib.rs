@@ -58,35 +58,50 @@
 extern crate pretty_assertions;
 
 pub mod analyse;
+pub mod arena;
 pub mod ast;
 pub mod bit_array;
 pub mod build;
 pub mod codegen;
 pub mod config;
+pub mod crash;
 pub mod dependency;
 pub mod diagnostic;
 pub mod docs;
+pub mod embed;
 pub mod erlang;
 pub mod error;
+pub mod ffi_stubs;
 pub mod fix;
 pub mod format;
 pub mod hex;
+pub mod inspect;
+pub mod intern;
 pub mod io;
 pub mod javascript;
 pub mod language_server;
 pub mod line_numbers;
+pub mod lint;
 pub mod manifest;
 pub mod metadata;
+pub mod module_graph;
+pub mod mutation;
+pub mod organize_imports;
 pub mod package_interface;
 pub mod parse;
 pub mod paths;
 pub mod pretty;
+pub mod rename_module;
 pub mod requirement;
+pub mod runtime_config;
 pub mod strings;
+pub mod test_runner;
+pub mod todos;
 pub mod type_;
 pub mod uid;
 pub mod version;
 pub mod warning;
+pub mod wasm;
 
 pub(crate) mod ast_folder;
 mod call_graph;
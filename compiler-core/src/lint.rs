@@ -0,0 +1,334 @@
+//! Checks of a project's code that are not needed for it to compile but keep
+//! it consistent, used by `gleam lint` with the rules configured under
+//! `[lints]` in `gleam.toml`.
+
+mod requirements;
+#[cfg(test)]
+mod tests;
+
+pub use requirements::find as find_in_config;
+
+use std::{fmt::Write, sync::Arc};
+
+use camino::{Utf8Path, Utf8PathBuf};
+use ecow::EcoString;
+use itertools::Itertools;
+use serde::Serialize;
+
+use crate::{
+    ast::{
+        visit::{self, Visit},
+        Definition, SrcSpan, TypedExpr, TypedFunction, TypedModule,
+    },
+    config::LintsConfig,
+    line_numbers::LineNumbers,
+    type_::{ModuleValueConstructor, Type},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Rule {
+    NamingConventions,
+    MaxFunctionLength,
+    DiscouragedFunctions,
+    TodoWithoutMessage,
+    RequirementRanges,
+}
+
+impl Rule {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::NamingConventions => "naming_conventions",
+            Self::MaxFunctionLength => "max_function_length",
+            Self::DiscouragedFunctions => "discouraged_functions",
+            Self::TodoWithoutMessage => "todo_without_message",
+            Self::RequirementRanges => "requirement_ranges",
+        }
+    }
+}
+
+/// A change to a module's source code that resolves a lint without changing
+/// what the code does.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Fix {
+    /// The byte offsets of the code to replace.
+    pub start: u32,
+    pub end: u32,
+    pub replacement: EcoString,
+}
+
+/// A place in a module, or in `gleam.toml`, where a rule is broken.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Lint {
+    pub rule: Rule,
+    /// The module the lint is in, or empty for a lint of `gleam.toml`.
+    pub module: EcoString,
+    pub path: Utf8PathBuf,
+    pub line: u32,
+    pub column: u32,
+    pub message: String,
+    pub fix: Option<Fix>,
+}
+
+/// Finds the lints of a module, in the order they appear in its source code.
+///
+pub fn find(module: &TypedModule, path: &Utf8Path, code: &str, config: &LintsConfig) -> Vec<Lint> {
+    let mut linter = Linter {
+        module: &module.name,
+        path,
+        code,
+        config,
+        line_numbers: LineNumbers::new(code),
+        function: "".into(),
+        lints: vec![],
+    };
+    linter.check_definitions(module);
+    linter.visit_typed_module(module);
+    linter
+        .lints
+        .into_iter()
+        .sorted_by_key(|lint| (lint.line, lint.column))
+        .collect()
+}
+
+/// Applies the fixes of the lints to the source code of the module, or the
+/// `gleam.toml`, they were found in.
+///
+pub fn apply_fixes(code: &str, lints: &[&Lint]) -> String {
+    let mut code = code.to_string();
+    let fixes = lints
+        .iter()
+        .filter_map(|lint| lint.fix.as_ref())
+        .sorted_by_key(|fix| std::cmp::Reverse(fix.start));
+    for fix in fixes {
+        code.replace_range(fix.start as usize..fix.end as usize, &fix.replacement);
+    }
+    code
+}
+
+/// Renders lints as a list for printing in a terminal, with a summary of how
+/// many there are and how many can be fixed automatically.
+///
+pub fn to_text(lints: &[Lint]) -> String {
+    let mut text = String::new();
+    for lint in lints {
+        let Lint {
+            rule,
+            path,
+            line,
+            column,
+            message,
+            ..
+        } = lint;
+        let rule = rule.as_str();
+        writeln!(text, "{path}:{line}:{column} {rule}: {message}").expect("write lint");
+    }
+
+    if !lints.is_empty() {
+        text.push('\n');
+    }
+    let fixable = lints.iter().filter(|lint| lint.fix.is_some()).count();
+    match lints.len() {
+        1 => text.push_str("1 lint"),
+        count => write!(text, "{count} lints").expect("write summary"),
+    }
+    if fixable > 0 {
+        write!(text, ", {fixable} fixable with `gleam lint --fix`").expect("write fixable");
+    }
+    text.push('\n');
+    text
+}
+
+/// Renders lints as a JSON array, for editors and continuous integration.
+///
+pub fn to_json(lints: &[Lint]) -> String {
+    serde_json::to_string_pretty(lints).expect("lints to json")
+}
+
+struct Linter<'a> {
+    module: &'a EcoString,
+    path: &'a Utf8Path,
+    code: &'a str,
+    config: &'a LintsConfig,
+    line_numbers: LineNumbers,
+    function: EcoString,
+    lints: Vec<Lint>,
+}
+
+impl Linter<'_> {
+    fn register(&mut self, rule: Rule, location: SrcSpan, message: String, fix: Option<Fix>) {
+        let position = self.line_numbers.line_and_column_number(location.start);
+        self.lints.push(Lint {
+            rule,
+            module: self.module.clone(),
+            path: self.path.to_path_buf(),
+            line: position.line,
+            column: position.column,
+            message,
+            fix,
+        });
+    }
+
+    fn check_definitions(&mut self, module: &TypedModule) {
+        for definition in &module.definitions {
+            match definition {
+                Definition::Function(function) => {
+                    self.check_name(&function.name, function.location);
+                    self.check_function_length(function);
+                }
+                Definition::ModuleConstant(constant) => {
+                    self.check_name(&constant.name, constant.location);
+                }
+                Definition::TypeAlias(alias) => {
+                    self.check_upname(&alias.alias, alias.location);
+                }
+                Definition::CustomType(custom_type) => {
+                    self.check_upname(&custom_type.name, custom_type.location);
+                    for constructor in &custom_type.constructors {
+                        self.check_upname(&constructor.name, constructor.location);
+                        for argument in &constructor.arguments {
+                            if let Some(label) = &argument.label {
+                                self.check_name(label, argument.location);
+                            }
+                        }
+                    }
+                }
+                Definition::Import(import) => {
+                    for value in &import.unqualified_values {
+                        self.check_function_use(&import.module, &value.name, value.location);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Checks a name of a value, which is written in snake case with a single
+    /// underscore between words.
+    fn check_name(&mut self, name: &EcoString, location: SrcSpan) {
+        if !self.config.naming_conventions || !(name.contains("__") || name.ends_with('_')) {
+            return;
+        }
+        let suggestion = name.split('_').filter(|word| !word.is_empty()).join("_");
+        let message = format!(
+            "`{name}` should be written `{suggestion}`, with single underscores between words"
+        );
+        self.register(Rule::NamingConventions, location, message, None);
+    }
+
+    /// Checks a name of a type or constructor, which is written in pascal case
+    /// with acronyms capitalised as words, such as `HttpClient`.
+    fn check_upname(&mut self, name: &EcoString, location: SrcSpan) {
+        if !self.config.naming_conventions {
+            return;
+        }
+        let characters = name.chars().collect_vec();
+        let suggestion: String = characters
+            .iter()
+            .enumerate()
+            .map(|(index, character)| {
+                let after_upper = index
+                    .checked_sub(1)
+                    .and_then(|index| characters.get(index))
+                    .is_some_and(char::is_ascii_uppercase);
+                let before_lower = characters
+                    .get(index + 1)
+                    .is_some_and(char::is_ascii_lowercase);
+                if after_upper && !before_lower {
+                    character.to_ascii_lowercase()
+                } else {
+                    *character
+                }
+            })
+            .collect();
+        if suggestion == name.as_str() {
+            return;
+        }
+        let message = format!(
+            "`{name}` should be written `{suggestion}`, with acronyms capitalised as words"
+        );
+        self.register(Rule::NamingConventions, location, message, None);
+    }
+
+    fn check_function_length(&mut self, function: &TypedFunction) {
+        let Some(max) = self.config.max_function_length else {
+            return;
+        };
+        let start = self.line_numbers.line_number(function.location.start);
+        let end = self.line_numbers.line_number(function.end_position);
+        let length = end - start + 1;
+        if length > max {
+            let name = &function.name;
+            let message =
+                format!("`{name}` is {length} lines long, more than the limit of {max} lines");
+            self.register(Rule::MaxFunctionLength, function.location, message, None);
+        }
+    }
+
+    fn check_function_use(&mut self, module: &str, name: &str, location: SrcSpan) {
+        let discouraged = self
+            .config
+            .discouraged_functions
+            .iter()
+            .any(|function| function.rsplit_once('.') == Some((module, name)));
+        if discouraged {
+            let message = format!("`{module}.{name}` is discouraged in this project");
+            self.register(Rule::DiscouragedFunctions, location, message, None);
+        }
+    }
+}
+
+impl<'ast> Visit<'ast> for Linter<'_> {
+    fn visit_typed_function(&mut self, fun: &'ast TypedFunction) {
+        self.function = fun.name.clone();
+        visit::visit_typed_function(self, fun);
+    }
+
+    fn visit_typed_expr_module_select(
+        &mut self,
+        location: &'ast SrcSpan,
+        typ: &'ast Arc<Type>,
+        label: &'ast EcoString,
+        module_name: &'ast EcoString,
+        module_alias: &'ast EcoString,
+        constructor: &'ast ModuleValueConstructor,
+    ) {
+        if let ModuleValueConstructor::Fn { .. } = constructor {
+            self.check_function_use(module_name, label, *location);
+        }
+        visit::visit_typed_expr_module_select(
+            self,
+            location,
+            typ,
+            label,
+            module_name,
+            module_alias,
+            constructor,
+        );
+    }
+
+    fn visit_typed_expr_todo(
+        &mut self,
+        location: &'ast SrcSpan,
+        message: &'ast Option<Box<TypedExpr>>,
+        type_: &'ast Arc<Type>,
+    ) {
+        // A function without a body is also typed as a `todo`, which is not
+        // written in the source code.
+        let written = self
+            .code
+            .get(location.start as usize..location.end as usize)
+            == Some("todo");
+        if self.config.todo_without_message && message.is_none() && written {
+            // A message only changes what is printed when the `todo` is
+            // evaluated, so it is safe to add one.
+            let fix = Fix {
+                start: location.end,
+                end: location.end,
+                replacement: format!(" as \"implement {}\"", self.function).into(),
+            };
+            let message = "This `todo` has no message saying what is left to be done".into();
+            self.register(Rule::TodoWithoutMessage, *location, message, Some(fix));
+        }
+        visit::visit_typed_expr_todo(self, location, message, type_);
+    }
+}
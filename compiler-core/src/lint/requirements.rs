@@ -0,0 +1,208 @@
+//! Checks of the version requirements on Hex packages written in
+//! `gleam.toml`, for ranges that are likely to cause trouble later even
+//! though the dependencies can be resolved today.
+
+use std::collections::HashMap;
+
+use camino::Utf8PathBuf;
+use ecow::EcoString;
+use hexpm::version::{Range, Version};
+use itertools::Itertools;
+
+use super::{Fix, Lint, Rule};
+use crate::{config::PackageConfig, line_numbers::LineNumbers, requirement::Requirement};
+
+/// Finds the lints of the requirements on Hex packages in `gleam.toml`, in
+/// the order they appear in it. `toml` is the text of `gleam.toml` and
+/// `published` the versions of each package published to Hex, where they
+/// could be looked up.
+///
+pub fn find(
+    config: &PackageConfig,
+    toml: &str,
+    published: &HashMap<EcoString, Vec<Version>>,
+) -> Vec<Lint> {
+    if !config.lints.requirement_ranges {
+        return vec![];
+    }
+    // A package that is started as an application is not depended upon by
+    // other packages, so its exact versions cannot conflict with theirs.
+    let library = config.erlang.application_start_module.is_none();
+    let line_numbers = LineNumbers::new(toml);
+    let tables = [
+        ("dependencies", &config.dependencies, library),
+        ("dev-dependencies", &config.dev_dependencies, false),
+    ];
+    let mut lints = vec![];
+    for (table, dependencies, library) in tables {
+        for (name, requirement) in dependencies.iter().sorted_by(|a, b| a.0.cmp(b.0)) {
+            let Requirement::Hex { version, .. } = requirement else {
+                continue;
+            };
+            let versions = published.get(name).map(Vec::as_slice).unwrap_or_default();
+            let Some((message, replacement)) = check(name, version, versions, library) else {
+                continue;
+            };
+            let span = requirement_span(toml, table, name);
+            let position = line_numbers.line_and_column_number(span.map_or(0, |(start, _)| start));
+            let fix = span
+                .zip(replacement)
+                .map(|((start, end), replacement)| Fix {
+                    start,
+                    end,
+                    replacement,
+                });
+            lints.push(Lint {
+                rule: Rule::RequirementRanges,
+                module: "".into(),
+                path: Utf8PathBuf::from("gleam.toml"),
+                line: position.line,
+                column: position.column,
+                message,
+                fix,
+            });
+        }
+    }
+    lints.sort_by_key(|lint| (lint.line, lint.column));
+    lints
+}
+
+/// Checks a single requirement, returning what is wrong with it and the
+/// range to write in its place, if one can be suggested.
+fn check(
+    name: &str,
+    range: &Range,
+    published: &[Version],
+    library: bool,
+) -> Option<(String, Option<EcoString>)> {
+    let requirement = range.as_str().trim();
+
+    if let Ok(allowed) = range.to_pubgrub() {
+        if let Some(newest) = published.iter().max() {
+            if !published.iter().any(|version| allowed.contains(version)) {
+                let message = format!(
+                    "`{requirement}` does not allow any published version of `{name}`, the newest of which is {newest}"
+                );
+                return Some((message, None));
+            }
+        }
+    }
+
+    // Ranges combining others with `or` are left as they were written
+    if requirement.contains(" or ") {
+        return None;
+    }
+    let clauses = requirement
+        .split(" and ")
+        .map(clause)
+        .collect::<Option<Vec<_>>>()?;
+
+    match clauses.as_slice() {
+        // `~> 0.3` allows every version below 1.0.0, but each minor version
+        // before 1.0.0 may have breaking changes.
+        [("~>", version)] if version.split('.').count() == 2 && version.starts_with("0.") => {
+            let version = Version::parse(&format!("{version}.0")).ok()?;
+            let message = format!(
+                "`{requirement}` allows every version of `{name}` below 1.0.0, including ones with breaking changes"
+            );
+            Some((message, Some(recommended_range(&version))))
+        }
+
+        [("==", version)] if library => {
+            let version = Version::parse(version).ok()?;
+            let message = format!(
+                "`{requirement}` pins `{name}` to one version, so packages depending on this one cannot use any other"
+            );
+            Some((message, Some(recommended_range(&version))))
+        }
+
+        _ if clauses
+            .iter()
+            .all(|(operator, _)| *operator == ">=" || *operator == ">") =>
+        {
+            let message = format!(
+                "`{requirement}` has no upper bound, so it allows future versions of `{name}` with breaking changes"
+            );
+            let replacement = clauses
+                .iter()
+                .filter(|(operator, _)| *operator == ">=")
+                .filter_map(|(_, version)| Version::parse(version).ok())
+                .max()
+                .map(|version| recommended_range(&version));
+            Some((message, replacement))
+        }
+
+        _ => None,
+    }
+}
+
+/// Splits a clause of a requirement such as `>= 1.2.0` into its operator and
+/// its version. A version without an operator is an exact version.
+fn clause(text: &str) -> Option<(&str, &str)> {
+    let text = text.trim();
+    let operator = [">=", "<=", "==", "!=", "~>", ">", "<"]
+        .into_iter()
+        .find(|operator| text.starts_with(operator));
+    match operator {
+        Some(operator) => Some((operator, text.get(operator.len()..)?.trim())),
+        None if text.starts_with(|c: char| c.is_ascii_digit()) => Some(("==", text)),
+        None => None,
+    }
+}
+
+/// The range allowing a version and every later version without breaking
+/// changes, such as `>= 1.2.0 and < 2.0.0`. Before 1.0.0 each minor version
+/// may break, so `0.3.1` gives `>= 0.3.1 and < 0.4.0`.
+fn recommended_range(version: &Version) -> EcoString {
+    let Version {
+        major,
+        minor,
+        patch,
+        ..
+    } = version;
+    let breaking = match major {
+        0 => format!("0.{}.0", minor + 1),
+        _ => format!("{}.0.0", major + 1),
+    };
+    format!(">= {major}.{minor}.{patch} and < {breaking}").into()
+}
+
+/// The byte offsets of the text of the requirement on a package within the
+/// quotes it is written in, whether it is written as a string, as the
+/// `version` of an inline table, or in a table of its own.
+fn requirement_span(toml: &str, table: &str, name: &str) -> Option<(u32, u32)> {
+    let own_table = format!("{table}.{name}");
+    let mut current = "";
+    let mut offset = 0;
+    for line in toml.split_inclusive('\n') {
+        let line_start = offset;
+        offset += line.len();
+        let trimmed = line.trim();
+        if trimmed.starts_with('[') {
+            current = trimmed.trim_matches(|c| c == '[' || c == ']').trim();
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let value_start = line_start + key.len() + 1;
+        let key = key.trim().trim_matches('"');
+        let quote = if current == table && key == name {
+            match value.trim_start().starts_with('{') {
+                true => value.find("version").and_then(|index| {
+                    let after = value.get(index..)?;
+                    Some(index + after.find('"')?)
+                })?,
+                false => value.find('"')?,
+            }
+        } else if current == own_table && key == "version" {
+            value.find('"')?
+        } else {
+            continue;
+        };
+        let start = value_start + quote + 1;
+        let end = start + toml.get(start..)?.find('"')?;
+        return Some((start as u32, end as u32));
+    }
+    None
+}
@@ -0,0 +1,213 @@
+use super::{apply_fixes, find, find_in_config, to_json, to_text, Lint, Rule};
+use crate::{
+    config::{LintsConfig, PackageConfig},
+    type_::tests::compile_module,
+};
+
+use std::collections::HashMap;
+
+use camino::Utf8Path;
+use ecow::EcoString;
+use hexpm::version::Version;
+
+const IO: &str = r#"
+pub fn debug(value: a) -> a { value }
+pub fn println(string: String) -> Nil { Nil }
+"#;
+
+fn lints_with_config(src: &str, config: &LintsConfig) -> Vec<Lint> {
+    let module = compile_module("app", src, None, vec![("thepackage", "gleam/io", IO)])
+        .expect("compile module");
+    find(&module, Utf8Path::new("src/app.gleam"), src, config)
+}
+
+fn lints(src: &str) -> Vec<Lint> {
+    lints_with_config(src, &LintsConfig::default())
+}
+
+fn rules(lints: &[Lint]) -> Vec<Rule> {
+    lints.iter().map(|lint| lint.rule).collect()
+}
+
+#[test]
+fn naming_conventions() {
+    let src = r#"
+pub type HTTPClient {
+  JSONClient(base__url: String)
+  Client
+}
+
+pub type IO =
+  Int
+
+pub const max_retries_ = 3
+
+fn fetch__all() {
+  Nil
+}
+"#;
+    insta::assert_snapshot!(to_text(&lints(src)));
+}
+
+#[test]
+fn naming_conventions_can_be_disabled() {
+    let config = LintsConfig {
+        naming_conventions: false,
+        ..Default::default()
+    };
+    let src = "pub type HTTPClient\n\npub fn fetch__all() { Nil }\n";
+    assert_eq!(lints_with_config(src, &config), vec![]);
+}
+
+#[test]
+fn max_function_length() {
+    let config = LintsConfig {
+        max_function_length: Some(3),
+        ..Default::default()
+    };
+    let src = r#"
+pub fn short() {
+  1
+}
+
+pub fn long() {
+  let x = 1
+  let y = 2
+  x + y
+}
+"#;
+    insta::assert_snapshot!(to_text(&lints_with_config(src, &config)));
+}
+
+#[test]
+fn discouraged_functions() {
+    let config = LintsConfig {
+        discouraged_functions: vec!["gleam/io.debug".into()],
+        ..Default::default()
+    };
+    let src = r#"
+import gleam/io.{debug}
+
+pub fn main() {
+  io.println("Hello")
+  io.debug(1)
+  debug(2)
+}
+"#;
+    insta::assert_snapshot!(to_text(&lints_with_config(src, &config)));
+}
+
+#[test]
+fn todo_without_message() {
+    let src = r#"
+pub fn main() {
+  todo
+}
+
+pub fn wibble() {
+  todo as "finish wibble"
+}
+
+pub fn wobble() -> Int {}
+"#;
+    let lints = lints(src);
+    assert_eq!(rules(&lints), vec![Rule::TodoWithoutMessage]);
+    insta::assert_snapshot!(to_text(&lints));
+}
+
+#[test]
+fn todo_without_message_fix() {
+    let src = "pub fn main() {\n  todo\n}\n";
+    let lints = lints(src);
+    assert_eq!(
+        apply_fixes(src, &lints.iter().collect::<Vec<_>>()),
+        "pub fn main() {\n  todo as \"implement main\"\n}\n"
+    );
+}
+
+#[test]
+fn todo_without_message_can_be_disabled() {
+    let config = LintsConfig {
+        todo_without_message: false,
+        ..Default::default()
+    };
+    assert_eq!(lints_with_config("pub fn main() { todo }", &config), vec![]);
+}
+
+#[test]
+fn no_lints() {
+    insta::assert_snapshot!(to_text(&lints("pub fn main() { 1 }")));
+}
+
+#[test]
+fn json() {
+    let src = "pub fn main() {\n  todo\n}\n";
+    insta::assert_snapshot!(to_json(&lints(src)));
+}
+
+fn config_lints(toml: &str, published: &[(&str, &str)]) -> Vec<Lint> {
+    let config = PackageConfig::parse(toml, Utf8Path::new("gleam.toml")).expect("config");
+    let mut versions: HashMap<EcoString, Vec<Version>> = HashMap::new();
+    for (name, version) in published {
+        versions
+            .entry((*name).into())
+            .or_default()
+            .push(Version::parse(version).expect("version"));
+    }
+    find_in_config(&config, toml, &versions)
+}
+
+const REQUIREMENTS: &str = r#"name = "app"
+
+[dependencies]
+gleam_stdlib = ">= 0.34.0"
+gleam_json = "1.0.1"
+gleam_http = { version = "~> 0.3" }
+gleam_otp = ">= 0.10.0 and < 1.0.0"
+gleam_erlang = "~> 2.0"
+
+[dev-dependencies]
+gleeunit = "== 1.0.0"
+
+[dev-dependencies.birdie]
+version = ">= 1.0.0"
+"#;
+
+#[test]
+fn requirement_ranges() {
+    let published = [("gleam_erlang", "0.25.0"), ("gleam_otp", "0.10.0")];
+    let lints = config_lints(REQUIREMENTS, &published);
+    insta::assert_snapshot!(to_text(&lints));
+}
+
+#[test]
+fn requirement_ranges_fix() {
+    let lints = config_lints(REQUIREMENTS, &[]);
+    insta::assert_snapshot!(apply_fixes(REQUIREMENTS, &lints.iter().collect::<Vec<_>>()));
+}
+
+#[test]
+fn requirement_ranges_exact_versions_of_applications() {
+    let toml = r#"name = "app"
+
+[dependencies]
+gleam_json = "1.0.1"
+
+[erlang]
+application_start_module = "app/application"
+"#;
+    assert_eq!(config_lints(toml, &[]), vec![]);
+}
+
+#[test]
+fn requirement_ranges_can_be_disabled() {
+    let toml = r#"name = "app"
+
+[dependencies]
+gleam_stdlib = ">= 0.34.0"
+
+[lints]
+requirement_ranges = false
+"#;
+    assert_eq!(config_lints(toml, &[]), vec![]);
+}
@@ -79,10 +79,18 @@ impl Manifest {
             }
 
             match source {
-                ManifestPackageSource::Hex { outer_checksum } => {
+                ManifestPackageSource::Hex {
+                    outer_checksum,
+                    organization,
+                } => {
                     buffer.push_str(r#", source = "hex", outer_checksum = ""#);
                     buffer.push_str(&outer_checksum.to_string());
                     buffer.push('"');
+                    if let Some(organization) = organization {
+                        buffer.push_str(r#", organization = ""#);
+                        buffer.push_str(organization);
+                        buffer.push('"');
+                    }
                 }
                 ManifestPackageSource::Git { repo, commit } => {
                     buffer.push_str(r#", source = "git", repo = ""#);
@@ -186,13 +194,65 @@ impl ManifestPackage {
 #[serde(tag = "source")]
 pub enum ManifestPackageSource {
     #[serde(rename = "hex")]
-    Hex { outer_checksum: Base16Checksum },
+    Hex {
+        outer_checksum: Base16Checksum,
+        /// The Hex organisation the package is privately published to, if it
+        /// is not a public package.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        organization: Option<EcoString>,
+    },
     #[serde(rename = "git")]
-    Git { repo: EcoString, commit: EcoString },
+    Git {
+        #[serde(deserialize_with = "crate::requirement::deserialize_git_argument")]
+        repo: EcoString,
+        #[serde(deserialize_with = "crate::requirement::deserialize_git_argument")]
+        commit: EcoString,
+    },
     #[serde(rename = "local")]
     Local { path: Utf8PathBuf }, // should be the canonical path
 }
 
+/// Splits a `manifest.toml` left with git merge conflict markers into the
+/// file as it is on each side of the merge. Returns `None` if there are no
+/// conflicts.
+///
+/// The common ancestor section written by the `diff3` conflict style is
+/// dropped.
+pub fn split_merge_conflicts(toml: &str) -> Option<(String, String)> {
+    enum Section {
+        Both,
+        Ours,
+        Base,
+        Theirs,
+    }
+
+    let mut ours = String::new();
+    let mut theirs = String::new();
+    let mut section = Section::Both;
+    let mut conflicted = false;
+    for line in toml.split_inclusive('\n') {
+        match section {
+            _ if line.starts_with("<<<<<<<") => {
+                conflicted = true;
+                section = Section::Ours;
+            }
+            Section::Ours if line.starts_with("|||||||") => section = Section::Base,
+            Section::Ours | Section::Base if line.starts_with("=======") => {
+                section = Section::Theirs
+            }
+            Section::Theirs if line.starts_with(">>>>>>>") => section = Section::Both,
+            Section::Both => {
+                ours.push_str(line);
+                theirs.push_str(line);
+            }
+            Section::Ours => ours.push_str(line),
+            Section::Base => (),
+            Section::Theirs => theirs.push_str(line),
+        }
+    }
+    conflicted.then_some((ours, theirs))
+}
+
 fn sorted_vec<S, T>(value: &[T], serializer: S) -> Result<S::Ok, S::Error>
 where
     S: serde::Serializer,
@@ -250,6 +310,7 @@ mod tests {
                     requirements: vec![],
                     source: ManifestPackageSource::Hex {
                         outer_checksum: Base16Checksum(vec![1, 22]),
+                        organization: None,
                     },
                 },
                 ManifestPackage {
@@ -260,6 +321,7 @@ mod tests {
                     requirements: vec!["zzz".into(), "gleam_stdlib".into()],
                     source: ManifestPackageSource::Hex {
                         outer_checksum: Base16Checksum(vec![3, 22]),
+                        organization: None,
                     },
                 },
                 ManifestPackage {
@@ -270,6 +332,7 @@ mod tests {
                     requirements: vec![],
                     source: ManifestPackageSource::Hex {
                         outer_checksum: Base16Checksum(vec![3, 22]),
+                        organization: None,
                     },
                 },
                 ManifestPackage {
@@ -301,6 +364,7 @@ mod tests {
                     requirements: vec!["gleam_stdlib".into()],
                     source: ManifestPackageSource::Hex {
                         outer_checksum: Base16Checksum(vec![3, 46]),
+                        organization: None,
                     },
                 },
             ],
@@ -332,6 +396,47 @@ zzz = { version = "> 0.0.0" }
         );
     }
 
+    #[test]
+    fn manifest_toml_format_with_organization() {
+        let manifest = Manifest {
+            requirements: [(
+                "anvil".into(),
+                Requirement::hex_organization("~> 1.0", "acme"),
+            )]
+            .into(),
+            packages: vec![ManifestPackage {
+                name: "anvil".into(),
+                version: Version::new(1, 0, 0),
+                build_tools: ["gleam".into()].into(),
+                otp_app: None,
+                requirements: vec![],
+                source: ManifestPackageSource::Hex {
+                    outer_checksum: Base16Checksum(vec![1, 22]),
+                    organization: Some("acme".into()),
+                },
+            }],
+        };
+
+        let buffer = manifest.to_toml(HOME.into());
+        assert_eq!(
+            buffer,
+            r#"# This file was generated by Gleam
+# You typically do not need to edit this file
+
+packages = [
+  { name = "anvil", version = "1.0.0", build_tools = ["gleam"], requirements = [], source = "hex", outer_checksum = "0116", organization = "acme" },
+]
+
+[requirements]
+anvil = { version = "~> 1.0", organization = "acme" }
+"#
+        );
+        assert_eq!(
+            toml::from_str::<Manifest>(&buffer).expect("manifest"),
+            manifest
+        );
+    }
+
     #[cfg(windows)]
     #[test]
     fn manifest_toml_format_with_unc() {
@@ -360,6 +465,7 @@ zzz = { version = "> 0.0.0" }
                     requirements: vec![],
                     source: ManifestPackageSource::Hex {
                         outer_checksum: Base16Checksum(vec![1, 22]),
+                        organization: None,
                     },
                 },
                 ManifestPackage {
@@ -370,6 +476,7 @@ zzz = { version = "> 0.0.0" }
                     requirements: vec!["zzz".into(), "gleam_stdlib".into()],
                     source: ManifestPackageSource::Hex {
                         outer_checksum: Base16Checksum(vec![3, 22]),
+                        organization: None,
                     },
                 },
                 ManifestPackage {
@@ -380,6 +487,7 @@ zzz = { version = "> 0.0.0" }
                     requirements: vec![],
                     source: ManifestPackageSource::Hex {
                         outer_checksum: Base16Checksum(vec![3, 22]),
+                        organization: None,
                     },
                 },
                 ManifestPackage {
@@ -411,6 +519,7 @@ zzz = { version = "> 0.0.0" }
                     requirements: vec!["gleam_stdlib".into()],
                     source: ManifestPackageSource::Hex {
                         outer_checksum: Base16Checksum(vec![3, 46]),
+                        organization: None,
                     },
                 },
             ],
@@ -452,8 +561,62 @@ zzz = { version = "> 0.0.0" }
                 version: Version::new(1, 0, 0),
                 source: ManifestPackageSource::Hex {
                     outer_checksum: Base16Checksum(vec![]),
+                    organization: None,
                 },
             }
         }
     }
+
+    #[test]
+    fn split_merge_conflicts_without_conflicts() {
+        assert_eq!(split_merge_conflicts("packages = []\n"), None);
+    }
+
+    #[test]
+    fn split_merge_conflicts_into_each_side() {
+        let toml = r#"packages = [
+<<<<<<< HEAD
+  { name = "gleam_stdlib", version = "0.34.0" },
+=======
+  { name = "gleam_stdlib", version = "0.36.0" },
+  { name = "gleam_json", version = "1.0.0" },
+>>>>>>> feature
+  { name = "gleeunit", version = "1.0.0" },
+]
+"#;
+        assert_eq!(
+            split_merge_conflicts(toml),
+            Some((
+                r#"packages = [
+  { name = "gleam_stdlib", version = "0.34.0" },
+  { name = "gleeunit", version = "1.0.0" },
+]
+"#
+                .into(),
+                r#"packages = [
+  { name = "gleam_stdlib", version = "0.36.0" },
+  { name = "gleam_json", version = "1.0.0" },
+  { name = "gleeunit", version = "1.0.0" },
+]
+"#
+                .into()
+            ))
+        );
+    }
+
+    #[test]
+    fn split_merge_conflicts_drops_common_ancestor() {
+        let toml = r#"<<<<<<< HEAD
+a = 1
+||||||| base
+a = 0
+=======
+a = 2
+>>>>>>> feature
+"#;
+        assert_eq!(
+            split_merge_conflicts(toml),
+            Some(("a = 1\n".into(), "a = 2\n".into()))
+        );
+    }
 }
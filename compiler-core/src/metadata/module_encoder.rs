@@ -1,4 +1,5 @@
 use ecow::EcoString;
+use itertools::Itertools;
 
 use crate::{
     ast::{
@@ -19,6 +20,8 @@ pub struct ModuleEncoder<'a> {
     data: &'a type_::ModuleInterface,
     next_type_var_id: u64,
     type_var_id_map: HashMap<u64, u64>,
+    interface_only: bool,
+    locations: bool,
 }
 
 impl<'a> ModuleEncoder<'a> {
@@ -27,9 +30,20 @@ impl<'a> ModuleEncoder<'a> {
             data,
             next_type_var_id: 0,
             type_var_id_map: HashMap::new(),
+            interface_only: false,
+            locations: true,
         }
     }
 
+    /// Encode only the parts of the module interface that are used when
+    /// analysing the modules that import it, for use as a fingerprint of the
+    /// interface. Source locations are left out unless `locations` is true.
+    pub fn encode_interface(mut self, locations: bool) -> crate::Result<Vec<u8>> {
+        self.interface_only = true;
+        self.locations = locations;
+        self.encode()
+    }
+
     pub fn encode(mut self) -> crate::Result<Vec<u8>> {
         let span = tracing::info_span!("metadata");
         let _enter = span.enter();
@@ -47,8 +61,10 @@ impl<'a> ModuleEncoder<'a> {
         self.set_module_values(&mut module);
         self.set_module_accessors(&mut module);
         self.set_module_types_constructors(&mut module);
-        self.set_unused_imports(&mut module);
-        self.set_line_numbers(&mut module);
+        if !self.interface_only {
+            self.set_unused_imports(&mut module);
+            self.set_line_numbers(&mut module);
+        }
 
         capnp::serialize_packed::write_message(&mut buffer, &message).expect("capnp encode");
         Ok(buffer)
@@ -75,11 +91,17 @@ impl<'a> ModuleEncoder<'a> {
     }
 
     fn set_module_accessors(&mut self, module: &mut module::Builder<'_>) {
-        tracing::trace!("Writing module metadata accessors");
+        tracing::trace!("writing_module_metadata_accessors");
         let mut builder = module
             .reborrow()
             .init_accessors(self.data.accessors.len() as u32);
-        for (i, (key, map)) in self.data.accessors.iter().enumerate() {
+        for (i, (key, map)) in self
+            .data
+            .accessors
+            .iter()
+            .sorted_by_key(|(key, _)| *key)
+            .enumerate()
+        {
             let mut property = builder.reborrow().get(i as u32);
             property.set_key(key);
             self.build_accessors_map(property.init_value(), map);
@@ -93,7 +115,12 @@ impl<'a> ModuleEncoder<'a> {
     ) {
         self.build_type(builder.reborrow().init_type(), &accessors.type_);
         let mut builder = builder.init_accessors(accessors.accessors.len() as u32);
-        for (i, (name, accessor)) in accessors.accessors.iter().enumerate() {
+        for (i, (name, accessor)) in accessors
+            .accessors
+            .iter()
+            .sorted_by_key(|(name, _)| *name)
+            .enumerate()
+        {
             let mut property = builder.reborrow().get(i as u32);
             property.set_key(name);
             self.build_record_accessor(property.init_value(), accessor)
@@ -111,9 +138,15 @@ impl<'a> ModuleEncoder<'a> {
     }
 
     fn set_module_types(&mut self, module: &mut module::Builder<'_>) {
-        tracing::trace!("Writing module metadata types");
+        tracing::trace!("writing_module_metadata_types");
         let mut types = module.reborrow().init_types(self.data.types.len() as u32);
-        for (i, (name, type_)) in self.data.types.iter().enumerate() {
+        for (i, (name, type_)) in self
+            .data
+            .types
+            .iter()
+            .sorted_by_key(|(name, _)| *name)
+            .enumerate()
+        {
             let mut property = types.reborrow().get(i as u32);
             property.set_key(name);
             self.build_type_constructor(property.init_value(), type_)
@@ -121,11 +154,17 @@ impl<'a> ModuleEncoder<'a> {
     }
 
     fn set_module_types_constructors(&mut self, module: &mut module::Builder<'_>) {
-        tracing::trace!("Writing module metadata types to constructors mapping");
+        tracing::trace!("writing_module_metadata_types_constructors");
         let mut types_constructors = module
             .reborrow()
             .init_types_constructors(self.data.types_value_constructors.len() as u32);
-        for (i, (name, data)) in self.data.types_value_constructors.iter().enumerate() {
+        for (i, (name, data)) in self
+            .data
+            .types_value_constructors
+            .iter()
+            .sorted_by_key(|(name, _)| *name)
+            .enumerate()
+        {
             let mut property = types_constructors.reborrow().get(i as u32);
             property.set_key(name);
             self.build_type_variant_constructors(property.init_value(), data)
@@ -153,9 +192,15 @@ impl<'a> ModuleEncoder<'a> {
     }
 
     fn set_module_values(&mut self, module: &mut module::Builder<'_>) {
-        tracing::trace!("Writing module metadata values");
+        tracing::trace!("writing_module_metadata_values");
         let mut values = module.reborrow().init_values(self.data.values.len() as u32);
-        for (i, (name, value)) in self.data.values.iter().enumerate() {
+        for (i, (name, value)) in self
+            .data
+            .values
+            .iter()
+            .sorted_by_key(|(name, _)| *name)
+            .enumerate()
+        {
             let mut property = values.reborrow().get(i as u32);
             property.set_key(name);
             self.build_value_constructor(property.init_value(), value)
@@ -237,6 +282,9 @@ impl<'a> ModuleEncoder<'a> {
     }
 
     fn build_src_span(&mut self, mut builder: src_span::Builder<'_>, span: SrcSpan) {
+        if !self.locations {
+            return;
+        }
         builder.set_start(span.start);
         builder.set_end(span.end);
     }
@@ -326,7 +374,12 @@ impl<'a> ModuleEncoder<'a> {
     fn build_field_map(&mut self, mut builder: field_map::Builder<'_>, field_map: &FieldMap) {
         builder.set_arity(field_map.arity);
         let mut builder = builder.init_fields(field_map.fields.len() as u32);
-        for (i, (name, &position)) in field_map.fields.iter().enumerate() {
+        for (i, (name, &position)) in field_map
+            .fields
+            .iter()
+            .sorted_by_key(|(name, _)| *name)
+            .enumerate()
+        {
             let mut field = builder.reborrow().get(i as u32);
             field.set_key(name);
             field.init_value().set_value(position);
@@ -1411,3 +1411,97 @@ fn type_variable_ids_in_constructors_are_shared() {
 
     assert_eq!(roundtrip(&module).types_value_constructors, expected);
 }
+
+fn module_fns_module(names: &[&str], location: SrcSpan, src: &str) -> ModuleInterface {
+    let values = names.iter().map(|name| {
+        let value = ValueConstructor {
+            publicity: Publicity::Public,
+            deprecation: Deprecation::NotDeprecated,
+            type_: type_::fn_(vec![type_::generic_var(1)], type_::generic_var(2)),
+            variant: ValueConstructorVariant::ModuleFn {
+                documentation: None,
+                name: (*name).into(),
+                field_map: None,
+                module: "a".into(),
+                arity: 1,
+                location,
+                implementations: Implementations {
+                    gleam: true,
+                    uses_erlang_externals: false,
+                    uses_javascript_externals: false,
+                    can_run_on_erlang: true,
+                    can_run_on_javascript: true,
+                },
+            },
+        };
+        ((*name).into(), value)
+    });
+    ModuleInterface {
+        is_internal: false,
+        contains_todo: false,
+        package: "some_package".into(),
+        origin: Origin::Src,
+        name: "a".into(),
+        types: HashMap::new(),
+        types_value_constructors: HashMap::new(),
+        unused_imports: Vec::new(),
+        accessors: HashMap::new(),
+        values: values.collect(),
+        line_numbers: LineNumbers::new(src),
+        src_path: "some_path".into(),
+    }
+}
+
+#[test]
+fn encoding_does_not_depend_on_insertion_order() {
+    let names = [
+        "one", "two", "three", "four", "five", "six", "seven", "eight",
+    ];
+    let mut reversed = names;
+    reversed.reverse();
+    let location = SrcSpan { start: 1, end: 10 };
+    let module = module_fns_module(&names, location, "");
+    let reversed = module_fns_module(&reversed, location, "");
+
+    assert_eq!(
+        ModuleEncoder::new(&module).encode().expect("encode"),
+        ModuleEncoder::new(&reversed).encode().expect("encode")
+    );
+}
+
+#[test]
+fn interface_encoding_without_locations() {
+    let module = module_fns_module(&["one"], SrcSpan { start: 1, end: 10 }, "");
+    let moved = module_fns_module(&["one"], SrcSpan { start: 5, end: 14 }, "\n\n");
+
+    assert_eq!(
+        ModuleEncoder::new(&module)
+            .encode_interface(false)
+            .expect("encode"),
+        ModuleEncoder::new(&moved)
+            .encode_interface(false)
+            .expect("encode")
+    );
+    assert_ne!(
+        ModuleEncoder::new(&module)
+            .encode_interface(true)
+            .expect("encode"),
+        ModuleEncoder::new(&moved)
+            .encode_interface(true)
+            .expect("encode")
+    );
+}
+
+#[test]
+fn interface_encoding_is_unchanged_by_roundtrip() {
+    let module = module_fns_module(&["one", "two"], SrcSpan { start: 1, end: 10 }, "");
+
+    assert_eq!(
+        ModuleEncoder::new(&module)
+            .encode_interface(true)
+            .expect("encode"),
+        ModuleEncoder::new(&roundtrip(&module))
+            .encode_interface(true)
+            .expect("encode")
+    );
+}
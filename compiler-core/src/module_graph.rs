@@ -0,0 +1,113 @@
+//! The graph of the imports between the modules of a project, printed by
+//! `gleam deps module-graph` for reviewing the project's architecture.
+
+#[cfg(test)]
+mod tests;
+
+use std::{collections::HashSet, fmt::Write};
+
+use camino::Utf8Path;
+use ecow::EcoString;
+use itertools::Itertools;
+use serde::Serialize;
+
+use crate::{ast::Definition, build::Origin, Error, Result};
+
+/// A module of the project and the modules of the project it imports.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct GraphModule {
+    pub name: EcoString,
+    pub origin: Origin,
+    pub imports: Vec<EcoString>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ModuleGraph {
+    modules: Vec<GraphModule>,
+}
+
+impl ModuleGraph {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a module to the graph, parsing its source to find its imports.
+    /// Imports for any target are included.
+    ///
+    pub fn insert(
+        &mut self,
+        name: EcoString,
+        origin: Origin,
+        path: &Utf8Path,
+        src: &EcoString,
+    ) -> Result<()> {
+        let parsed = crate::parse::parse_module(src).map_err(|error| Error::Parse {
+            path: path.to_path_buf(),
+            src: src.clone(),
+            error,
+        })?;
+        let imports = parsed
+            .module
+            .definitions
+            .iter()
+            .filter_map(|definition| match &definition.definition {
+                Definition::Import(import) => Some(import.module.clone()),
+                _ => None,
+            })
+            .sorted()
+            .dedup()
+            .collect();
+        self.modules.push(GraphModule {
+            name,
+            origin,
+            imports,
+        });
+        Ok(())
+    }
+
+    /// The modules of the graph sorted by name, with only their imports of
+    /// other modules of the project.
+    ///
+    pub fn modules(&self) -> Vec<GraphModule> {
+        let names: HashSet<_> = self.modules.iter().map(|module| &module.name).collect();
+        self.modules
+            .iter()
+            .sorted_by(|a, b| a.name.cmp(&b.name))
+            .map(|module| GraphModule {
+                imports: module
+                    .imports
+                    .iter()
+                    .filter(|import| names.contains(import))
+                    .cloned()
+                    .collect(),
+                ..module.clone()
+            })
+            .collect()
+    }
+
+    /// Renders the graph in the DOT language of Graphviz, with an edge from
+    /// each module to the modules it imports. Test modules are drawn dashed.
+    ///
+    pub fn to_dot(&self) -> String {
+        let mut dot = "digraph modules {\n    node [shape=box];\n".to_string();
+        for module in self.modules() {
+            let name = &module.name;
+            let style = match module.origin {
+                Origin::Src => "",
+                Origin::Test => " [style=dashed]",
+            };
+            writeln!(dot, "    \"{name}\"{style};").expect("write module");
+            for import in &module.imports {
+                writeln!(dot, "    \"{name}\" -> \"{import}\";").expect("write import");
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// Renders the graph as a JSON array of the modules and their imports.
+    ///
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(&self.modules()).expect("module graph to json")
+    }
+}
@@ -0,0 +1,58 @@
+use super::ModuleGraph;
+use crate::build::Origin;
+
+use camino::Utf8Path;
+
+fn graph(modules: &[(&str, Origin, &str)]) -> ModuleGraph {
+    let mut graph = ModuleGraph::new();
+    for (name, origin, src) in modules {
+        graph
+            .insert(
+                (*name).into(),
+                *origin,
+                Utf8Path::new(&format!("src/{name}.gleam")),
+                &(*src).into(),
+            )
+            .expect("insert module");
+    }
+    graph
+}
+
+const MODULES: &[(&str, Origin, &str)] = &[
+    (
+        "app",
+        Origin::Src,
+        "import app/router\nimport app/db\nimport gleam/io",
+    ),
+    ("app/router", Origin::Src, "import app/db\nimport app/db"),
+    (
+        "app/db",
+        Origin::Src,
+        "@target(erlang)\nimport app/db/erlang\nimport gleam/dynamic",
+    ),
+    ("app/db/erlang", Origin::Src, "pub fn connect() { Nil }"),
+    ("app_test", Origin::Test, "import app\nimport gleeunit"),
+];
+
+#[test]
+fn dot() {
+    insta::assert_snapshot!(graph(MODULES).to_dot());
+}
+
+#[test]
+fn json() {
+    insta::assert_snapshot!(graph(MODULES).to_json());
+}
+
+#[test]
+fn syntax_error() {
+    let mut graph = ModuleGraph::new();
+    assert!(graph
+        .insert(
+            "app".into(),
+            Origin::Src,
+            Utf8Path::new("src/app.gleam"),
+            &"import".into()
+        )
+        .is_err());
+}
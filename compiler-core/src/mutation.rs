@@ -0,0 +1,365 @@
+//! Mutation testing, used by `gleam mutate`. Small changes called mutants are
+//! made to the code of the project one at a time, and the tests are run
+//! against each of them. A mutant the tests still pass against survives,
+//! showing code whose behaviour the tests do not check.
+//!
+//! The mutants are found by walking the typed AST of each module, so that only
+//! changes that keep the code well typed are made, such as flipping a
+//! comparison of two numbers. Each one is applied as an edit to the source of
+//! the module, which is then compiled again along with the modules that
+//! depend on it.
+
+#[cfg(test)]
+mod tests;
+
+use std::{fmt::Write, sync::Arc};
+
+use camino::{Utf8Path, Utf8PathBuf};
+use ecow::EcoString;
+use itertools::Itertools;
+
+use crate::{
+    ast::{
+        visit::{self, Visit},
+        BinOp, SrcSpan, TypedClause, TypedExpr, TypedModule,
+    },
+    line_numbers::LineNumbers,
+    type_::{Type, ValueConstructor, ValueConstructorVariant},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mutation {
+    /// A comparison replaced with its opposite, such as `<` with `>=`.
+    FlipComparison,
+    /// The bodies of the two clauses of a `case` expression swapped.
+    SwapBranches,
+    /// A literal replaced with another value of the same type.
+    ReplaceConstant,
+}
+
+impl Mutation {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::FlipComparison => "flip_comparison",
+            Self::SwapBranches => "swap_branches",
+            Self::ReplaceConstant => "replace_constant",
+        }
+    }
+}
+
+/// A change to the source code of a module.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mutant {
+    pub mutation: Mutation,
+    pub module: EcoString,
+    pub path: Utf8PathBuf,
+    pub line: u32,
+    pub column: u32,
+    /// The byte offsets of the code to replace.
+    pub start: u32,
+    pub end: u32,
+    pub replacement: EcoString,
+    pub description: String,
+}
+
+/// What the tests did when run against a mutant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    /// The tests failed, so they catch the change.
+    Killed,
+    /// The tests passed, so they do not catch the change.
+    Survived,
+    /// The tests did not finish in time, such as when the change made a
+    /// recursive function loop forever. This counts as the change being
+    /// caught.
+    TimedOut,
+    /// The mutated code did not compile, such as when the bodies of swapped
+    /// branches use variables bound by their patterns. These mutants are
+    /// left out of the score.
+    NotCompiled,
+}
+
+/// Finds the mutants of a module, in the order they appear in its source
+/// code.
+///
+pub fn find(module: &TypedModule, path: &Utf8Path, code: &str) -> Vec<Mutant> {
+    let mut finder = MutantFinder {
+        module: &module.name,
+        path,
+        code,
+        line_numbers: LineNumbers::new(code),
+        mutants: vec![],
+    };
+    finder.visit_typed_module(module);
+    finder
+        .mutants
+        .into_iter()
+        .sorted_by_key(|mutant| (mutant.start, mutant.end))
+        .collect()
+}
+
+/// Applies the mutant to the source code of the module it was found in.
+///
+pub fn apply(code: &str, mutant: &Mutant) -> String {
+    let mut code = code.to_string();
+    code.replace_range(
+        mutant.start as usize..mutant.end as usize,
+        &mutant.replacement,
+    );
+    code
+}
+
+/// Renders the mutants that survived as a list for printing in a terminal,
+/// with a summary of the outcomes and the mutation score: the percentage of
+/// the mutants that compiled that the tests caught.
+///
+pub fn to_text(results: &[(Mutant, Outcome)]) -> String {
+    let mut text = String::new();
+    let survived = results
+        .iter()
+        .filter(|(_, outcome)| *outcome == Outcome::Survived)
+        .map(|(mutant, _)| mutant)
+        .collect_vec();
+    for mutant in &survived {
+        let Mutant {
+            mutation,
+            path,
+            line,
+            column,
+            description,
+            ..
+        } = mutant;
+        let mutation = mutation.as_str();
+        writeln!(text, "{path}:{line}:{column} {mutation}: {description}").expect("write mutant");
+    }
+    if !survived.is_empty() {
+        text.push('\n');
+    }
+
+    let count = |wanted: Outcome| {
+        results
+            .iter()
+            .filter(|(_, outcome)| *outcome == wanted)
+            .count()
+    };
+    let killed = count(Outcome::Killed);
+    let timed_out = count(Outcome::TimedOut);
+    let not_compiled = count(Outcome::NotCompiled);
+    match results.len() {
+        1 => text.push_str("1 mutant: "),
+        total => write!(text, "{total} mutants: ").expect("write total"),
+    }
+    writeln!(
+        text,
+        "{killed} killed, {} survived, {timed_out} timed out, {not_compiled} did not compile",
+        survived.len()
+    )
+    .expect("write summary");
+    let viable = results.len() - not_compiled;
+    if let Some(score) = ((killed + timed_out) * 100).checked_div(viable) {
+        writeln!(text, "Mutation score: {score}%").expect("write score");
+    }
+    text
+}
+
+struct MutantFinder<'a> {
+    module: &'a EcoString,
+    path: &'a Utf8Path,
+    code: &'a str,
+    line_numbers: LineNumbers,
+    mutants: Vec<Mutant>,
+}
+
+impl MutantFinder<'_> {
+    fn register(
+        &mut self,
+        mutation: Mutation,
+        location: SrcSpan,
+        replacement: EcoString,
+        description: String,
+    ) {
+        let position = self.line_numbers.line_and_column_number(location.start);
+        self.mutants.push(Mutant {
+            mutation,
+            module: self.module.clone(),
+            path: self.path.to_path_buf(),
+            line: position.line,
+            column: position.column,
+            start: location.start,
+            end: location.end,
+            replacement,
+            description,
+        });
+    }
+
+    fn source(&self, location: SrcSpan) -> Option<&str> {
+        self.code
+            .get(location.start as usize..location.end as usize)
+    }
+
+    /// Replaces a literal that is written in the source code, rather than
+    /// added by the compiler when desugaring.
+    fn replace_constant(&mut self, location: SrcSpan, replacement: &str) {
+        let Some(original) = self.source(location) else {
+            return;
+        };
+        if original == replacement {
+            return;
+        }
+        let description = format!("`{original}` replaced with `{replacement}`");
+        self.register(
+            Mutation::ReplaceConstant,
+            location,
+            replacement.into(),
+            description,
+        );
+    }
+}
+
+/// The opposite of a comparison, which is true whenever it is false.
+fn flipped(operator: BinOp) -> Option<BinOp> {
+    match operator {
+        BinOp::Eq => Some(BinOp::NotEq),
+        BinOp::NotEq => Some(BinOp::Eq),
+        BinOp::LtInt => Some(BinOp::GtEqInt),
+        BinOp::GtEqInt => Some(BinOp::LtInt),
+        BinOp::LtEqInt => Some(BinOp::GtInt),
+        BinOp::GtInt => Some(BinOp::LtEqInt),
+        BinOp::LtFloat => Some(BinOp::GtEqFloat),
+        BinOp::GtEqFloat => Some(BinOp::LtFloat),
+        BinOp::LtEqFloat => Some(BinOp::GtFloat),
+        BinOp::GtFloat => Some(BinOp::LtEqFloat),
+        BinOp::And
+        | BinOp::Or
+        | BinOp::AddInt
+        | BinOp::AddFloat
+        | BinOp::SubInt
+        | BinOp::SubFloat
+        | BinOp::MultInt
+        | BinOp::MultFloat
+        | BinOp::DivInt
+        | BinOp::DivFloat
+        | BinOp::RemainderInt
+        | BinOp::Concatenate => None,
+    }
+}
+
+impl<'ast> Visit<'ast> for MutantFinder<'_> {
+    fn visit_typed_expr_bin_op(
+        &mut self,
+        location: &'ast SrcSpan,
+        typ: &'ast Arc<Type>,
+        name: &'ast BinOp,
+        left: &'ast TypedExpr,
+        right: &'ast TypedExpr,
+    ) {
+        // The operator is found in the code between the two operands.
+        let between = SrcSpan::new(left.location().end, right.location().start);
+        let operator = flipped(*name).and_then(|flipped| {
+            let offset = self.source(between)?.find(name.name())?;
+            let start = between.start + offset as u32;
+            let end = start + name.name().len() as u32;
+            Some((SrcSpan::new(start, end), flipped))
+        });
+        if let Some((operator, flipped)) = operator {
+            let description = format!("`{}` replaced with `{}`", name.name(), flipped.name());
+            self.register(
+                Mutation::FlipComparison,
+                operator,
+                flipped.name().into(),
+                description,
+            );
+        }
+        visit::visit_typed_expr_bin_op(self, location, typ, name, left, right);
+    }
+
+    fn visit_typed_expr_case(
+        &mut self,
+        location: &'ast SrcSpan,
+        typ: &'ast Arc<Type>,
+        subjects: &'ast [TypedExpr],
+        clauses: &'ast [TypedClause],
+    ) {
+        if let [first, second] = clauses {
+            let (first, second) = (first.then.location(), second.then.location());
+            let swapped = SrcSpan::new(first.start, second.end);
+            let between = SrcSpan::new(first.end, second.start);
+            if let (Some(first_code), Some(between), Some(second_code)) = (
+                self.source(first),
+                self.source(between),
+                self.source(second),
+            ) {
+                let replacement = format!("{second_code}{between}{first_code}");
+                let description = "the bodies of the two clauses swapped".into();
+                self.register(
+                    Mutation::SwapBranches,
+                    swapped,
+                    replacement.into(),
+                    description,
+                );
+            }
+        }
+        visit::visit_typed_expr_case(self, location, typ, subjects, clauses);
+    }
+
+    fn visit_typed_expr_int(
+        &mut self,
+        location: &'ast SrcSpan,
+        typ: &'ast Arc<Type>,
+        value: &'ast EcoString,
+    ) {
+        let replacement = if value.trim_start_matches(['0', '_']).is_empty() {
+            "1"
+        } else {
+            "0"
+        };
+        self.replace_constant(*location, replacement);
+        visit::visit_typed_expr_int(self, location, typ, value);
+    }
+
+    fn visit_typed_expr_float(
+        &mut self,
+        location: &'ast SrcSpan,
+        typ: &'ast Arc<Type>,
+        value: &'ast EcoString,
+    ) {
+        let is_zero = value
+            .chars()
+            .take_while(|character| !matches!(character, 'e' | 'E'))
+            .all(|character| matches!(character, '0' | '_' | '.'));
+        let replacement = if is_zero { "1.0" } else { "0.0" };
+        self.replace_constant(*location, replacement);
+        visit::visit_typed_expr_float(self, location, typ, value);
+    }
+
+    fn visit_typed_expr_string(
+        &mut self,
+        location: &'ast SrcSpan,
+        typ: &'ast Arc<Type>,
+        value: &'ast EcoString,
+    ) {
+        let replacement = if value.is_empty() {
+            "\"mutant\""
+        } else {
+            "\"\""
+        };
+        self.replace_constant(*location, replacement);
+        visit::visit_typed_expr_string(self, location, typ, value);
+    }
+
+    fn visit_typed_expr_var(
+        &mut self,
+        location: &'ast SrcSpan,
+        constructor: &'ast ValueConstructor,
+        name: &'ast EcoString,
+    ) {
+        let is_bool = constructor.type_.is_bool()
+            && matches!(constructor.variant, ValueConstructorVariant::Record { .. });
+        match name.as_str() {
+            "True" if is_bool => self.replace_constant(*location, "False"),
+            "False" if is_bool => self.replace_constant(*location, "True"),
+            _ => (),
+        }
+        visit::visit_typed_expr_var(self, location, constructor, name);
+    }
+}
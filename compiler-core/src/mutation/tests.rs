@@ -0,0 +1,116 @@
+use super::{apply, find, to_text, Mutant, Mutation, Outcome};
+use crate::type_::tests::compile_module;
+
+use camino::Utf8Path;
+use itertools::Itertools;
+
+fn mutants(src: &str) -> Vec<Mutant> {
+    let module = compile_module("app", src, None, vec![]).expect("compile module");
+    find(&module, Utf8Path::new("src/app.gleam"), src)
+}
+
+/// Each mutant of the module, with the line of code it changes as it is
+/// after the change.
+fn mutated(src: &str) -> String {
+    mutants(src)
+        .iter()
+        .map(|mutant| {
+            let code = apply(src, mutant);
+            let line = code
+                .lines()
+                .nth(mutant.line as usize - 1)
+                .expect("mutated line");
+            format!("{}: {}", mutant.mutation.as_str(), line.trim())
+        })
+        .join("\n")
+}
+
+#[test]
+fn flip_comparisons() {
+    let src = r#"
+pub fn compare(a: Int, b: Int, c: Float) {
+  #(a < b, a <= b, a > b, a >= b, a == b, a != b, c <. 1.5)
+}
+"#;
+    insta::assert_snapshot!(mutated(src));
+}
+
+#[test]
+fn arithmetic_is_not_flipped() {
+    let src = r#"
+pub fn add(a, b) {
+  a + b
+}
+"#;
+    assert_eq!(mutants(src), vec![]);
+}
+
+#[test]
+fn swap_branches() {
+    let src = r#"
+pub fn sign(a: Int) {
+  case a < 0 {
+    True -> "negative"
+    False -> {
+      "positive"
+    }
+  }
+}
+"#;
+    insta::assert_snapshot!(mutated(src));
+}
+
+#[test]
+fn case_with_more_clauses_is_not_swapped() {
+    let src = r#"
+pub fn name(a: Int) {
+  case a {
+    _ if a < 0 -> a
+    0 -> a
+    _ -> a
+  }
+}
+"#;
+    assert!(mutants(src)
+        .iter()
+        .all(|mutant| mutant.mutation != Mutation::SwapBranches));
+}
+
+#[test]
+fn replace_constants() {
+    let src = r#"
+pub fn constants() {
+  #(0, 1_000, 0.0, 2.5, "", "hello", True, False)
+}
+"#;
+    insta::assert_snapshot!(mutated(src));
+}
+
+#[test]
+fn report() {
+    let src = r#"
+pub fn is_adult(age: Int) {
+  age >= 18
+}
+"#;
+    let mutants = mutants(src);
+    let outcomes = [Outcome::Survived, Outcome::Killed];
+    let results = mutants.into_iter().zip(outcomes).collect_vec();
+    insta::assert_snapshot!(to_text(&results));
+}
+
+#[test]
+fn report_leaves_out_mutants_that_did_not_compile() {
+    let src = r#"
+pub fn swap(result: Result(Int, Int)) {
+  case result {
+    Ok(a) -> a
+    Error(_) -> 0
+  }
+}
+"#;
+    let mutants = mutants(src);
+    let outcomes = [Outcome::NotCompiled, Outcome::TimedOut];
+    let results = mutants.into_iter().zip(outcomes).collect_vec();
+    insta::assert_snapshot!(to_text(&results));
+}
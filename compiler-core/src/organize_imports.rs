@@ -0,0 +1,568 @@
+//! Organizes the imports of a module, as done by `gleam organize-imports`.
+//!
+//! Imports that are not used are removed, imports are added for references
+//! to modules, types, and values that are not in scope but that only one
+//! importable module could provide, and the imports are sorted into a single
+//! block at the top of the module. The rest of the module is left as it is.
+
+#[cfg(test)]
+mod tests;
+
+use std::sync::Arc;
+
+use camino::Utf8Path;
+use ecow::EcoString;
+use itertools::Itertools;
+
+use crate::{
+    analyse::TargetSupport,
+    ast::{AssignName, Definition, SrcSpan, UntypedImport},
+    build::{Mode, Origin, Outcome, Target},
+    config::PackageConfig,
+    line_numbers::LineNumbers,
+    type_::{self, ModuleInterface, PRELUDE_MODULE_NAME},
+    uid::UniqueIdGenerator,
+    warning::{TypeWarningEmitter, VectorWarningEmitterIO, WarningEmitter},
+    Error, Warning,
+};
+
+/// Adding an import can leave another one unused, and removing the last
+/// unqualified import from an import can leave its module unused, so a module
+/// may be analysed a few times before its imports are organized.
+const MAX_ROUNDS: usize = 4;
+
+#[derive(Debug)]
+pub struct ImportOrganizer<'a> {
+    pub module_name: &'a EcoString,
+    pub path: &'a Utf8Path,
+    pub origin: Origin,
+    pub target: Target,
+    pub mode: Mode,
+    pub package_config: &'a PackageConfig,
+    pub importable_modules: &'a im::HashMap<EcoString, ModuleInterface>,
+}
+
+/// An import to add for a reference that is not in scope.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum MissingImport {
+    Module { module: EcoString },
+    Type { module: EcoString, name: EcoString },
+    Value { module: EcoString, name: EcoString },
+}
+
+impl ImportOrganizer<'_> {
+    /// Returns the module's source with its imports organized.
+    ///
+    /// Unused imports are only removed from modules that type check, as when
+    /// there are errors some uses of the imports may not have been seen. If
+    /// errors remain once any missing imports have been added the organized
+    /// source is returned along with them.
+    pub fn organize(&self, src: &str) -> Outcome<String, Error> {
+        let mut src = src.to_string();
+        let mut error = None;
+
+        for _ in 0..MAX_ROUNDS {
+            let parsed = match crate::parse::parse_module(&src) {
+                Ok(parsed) => parsed,
+                Err(parse_error) => {
+                    let error = Error::Parse {
+                        path: self.path.to_path_buf(),
+                        src: src.into(),
+                        error: parse_error,
+                    };
+                    return Outcome::TotalFailure(error);
+                }
+            };
+            let imports = parsed
+                .module
+                .definitions
+                .iter()
+                .filter_map(|definition| match &definition.definition {
+                    Definition::Import(import) => Some(import.clone()),
+                    _ => None,
+                })
+                .collect_vec();
+
+            let mut module = parsed.module;
+            module.name = self.module_name.clone();
+            let (outcome, warnings) = self.analyse(module, &src);
+
+            let (unused, missing) = match &outcome {
+                Outcome::Ok(_) => (unused_imports(&warnings), vec![]),
+                Outcome::PartialFailure(_, errors) | Outcome::TotalFailure(errors) => {
+                    (vec![], self.missing_imports(errors, &imports))
+                }
+            };
+            error = match outcome {
+                Outcome::Ok(_) => None,
+                Outcome::PartialFailure(_, errors) | Outcome::TotalFailure(errors) => {
+                    Some(Error::Type {
+                        path: self.path.to_path_buf(),
+                        src: src.as_str().into(),
+                        errors,
+                    })
+                }
+            };
+
+            let organized = match rewrite(&src, self.path, &imports, &unused, &missing) {
+                Ok(organized) => organized,
+                Err(error) => return Outcome::TotalFailure(error),
+            };
+            if organized == src {
+                break;
+            }
+            src = organized;
+        }
+
+        match error {
+            None => Outcome::Ok(src),
+            Some(error) => Outcome::PartialFailure(src, error),
+        }
+    }
+
+    fn analyse(
+        &self,
+        module: crate::ast::UntypedModule,
+        src: &str,
+    ) -> (
+        Outcome<crate::ast::TypedModule, vec1::Vec1<type_::Error>>,
+        Vec<type_::Warning>,
+    ) {
+        let emitted = Arc::new(VectorWarningEmitterIO::new());
+        let warnings = TypeWarningEmitter::new(
+            self.path.to_path_buf(),
+            src.into(),
+            WarningEmitter::new(emitted.clone()),
+        );
+        let direct_dependencies = self
+            .package_config
+            .dependencies_for(self.mode)
+            .unwrap_or_default();
+        let outcome = crate::analyse::ModuleAnalyzerConstructor {
+            target: self.target,
+            ids: &UniqueIdGenerator::new(),
+            origin: self.origin,
+            importable_modules: self.importable_modules,
+            warnings: &warnings,
+            direct_dependencies: &direct_dependencies,
+            target_support: TargetSupport::NotEnforced,
+            package_config: self.package_config,
+        }
+        .infer_module(module, LineNumbers::new(src), self.path.to_path_buf());
+
+        let warnings = emitted
+            .take()
+            .into_iter()
+            .filter_map(|warning| match warning {
+                Warning::Type { warning, .. } => Some(warning),
+                Warning::InvalidSource { .. }
+                | Warning::ModifiedDependency { .. }
+                | Warning::ModifiedArtefacts { .. }
+                | Warning::StaticEntrypointImport { .. }
+                | Warning::JavaScriptPlatformMismatch { .. }
+                | Warning::OtherTargetError { .. }
+                | Warning::TargetSignatureMismatch { .. }
+                | Warning::UnknownConfigKey { .. }
+                | Warning::RetiredDependency { .. } => None,
+            })
+            .collect();
+        (outcome, warnings)
+    }
+
+    /// The imports that would bring the unknown modules, types, and values in
+    /// the errors into scope, where only one module could provide them.
+    fn missing_imports(
+        &self,
+        errors: &[type_::Error],
+        imports: &[UntypedImport],
+    ) -> Vec<MissingImport> {
+        // If an imported module could not be found then the names it would
+        // have brought into scope are unknown too, so nothing is added.
+        let unknown_import = errors.iter().any(|error| match error {
+            type_::Error::UnknownModule { location, .. } => {
+                imports.iter().any(|import| import.location == *location)
+            }
+            _ => false,
+        });
+        if unknown_import {
+            return vec![];
+        }
+
+        errors
+            .iter()
+            .filter_map(|error| match error {
+                type_::Error::UnknownModule { name, .. } => {
+                    let module = self.only_module(|module| {
+                        module.name.split('/').next_back() == Some(name.as_str())
+                    })?;
+                    Some(MissingImport::Module { module })
+                }
+
+                type_::Error::UnknownType { name, .. } => {
+                    let module = self.only_module(|module| match module.types.get(name) {
+                        Some(type_) => self.can_import(module, type_.publicity),
+                        None => false,
+                    })?;
+                    Some(MissingImport::Type {
+                        module,
+                        name: name.clone(),
+                    })
+                }
+
+                type_::Error::UnknownVariable { name, .. } => {
+                    let module = self.only_module(|module| match module.values.get(name) {
+                        Some(value) => self.can_import(module, value.publicity),
+                        None => false,
+                    })?;
+                    Some(MissingImport::Value {
+                        module,
+                        name: name.clone(),
+                    })
+                }
+
+                _ => None,
+            })
+            .unique()
+            .collect()
+    }
+
+    /// The name of the only module that this module may import which
+    /// satisfies the predicate, if there is exactly one.
+    fn only_module(&self, predicate: impl Fn(&ModuleInterface) -> bool) -> Option<EcoString> {
+        self.importable_modules
+            .values()
+            .filter(|module| self.may_import(module) && predicate(module))
+            .map(|module| module.name.clone())
+            .exactly_one()
+            .ok()
+    }
+
+    fn may_import(&self, module: &ModuleInterface) -> bool {
+        if module.name == *self.module_name || module.name == PRELUDE_MODULE_NAME {
+            return false;
+        }
+        // Modules in `src` cannot import ones in `test`.
+        if self.origin.is_src() && !module.origin.is_src() {
+            return false;
+        }
+        if module.package == self.package_config.name {
+            return true;
+        }
+        // Only the public modules of direct dependencies can be imported from
+        // other packages.
+        let dependencies = self
+            .package_config
+            .dependencies_for(self.mode)
+            .unwrap_or_default();
+        !module.is_internal && dependencies.contains_key(&module.package)
+    }
+
+    fn can_import(&self, module: &ModuleInterface, publicity: crate::ast::Publicity) -> bool {
+        if module.package == self.package_config.name {
+            publicity.is_importable()
+        } else {
+            publicity.is_public()
+        }
+    }
+}
+
+/// The locations of the unused imports, unqualified imports, and import
+/// aliases in the warnings.
+fn unused_imports(warnings: &[type_::Warning]) -> Vec<SrcSpan> {
+    warnings
+        .iter()
+        .filter_map(|warning| match warning {
+            type_::Warning::UnusedImportedModule { location, .. }
+            | type_::Warning::UnusedImportedModuleAlias { location, .. }
+            | type_::Warning::UnusedImportedValue { location, .. }
+            | type_::Warning::UnusedType {
+                location,
+                imported: true,
+                ..
+            }
+            | type_::Warning::UnusedConstructor {
+                location,
+                imported: true,
+                ..
+            } => Some(*location),
+            _ => None,
+        })
+        .collect()
+}
+
+/// An import as it will be written once the imports have been organized.
+#[derive(Debug)]
+struct OrganizedImport {
+    module: EcoString,
+    alias: Option<AssignName>,
+    /// The alias of the import is removed if it is unused, unless that would
+    /// give the module the same name as another import.
+    unused_alias: Option<AssignName>,
+    types: Vec<(EcoString, Option<EcoString>)>,
+    values: Vec<(EcoString, Option<EcoString>)>,
+    /// Any comments on the lines before the import, which are kept with it.
+    comments: Vec<String>,
+}
+
+impl OrganizedImport {
+    fn new(module: EcoString) -> Self {
+        Self {
+            module,
+            alias: None,
+            unused_alias: None,
+            types: vec![],
+            values: vec![],
+            comments: vec![],
+        }
+    }
+
+    fn used_name(&self) -> Option<&str> {
+        match &self.alias {
+            Some(AssignName::Variable(name)) => Some(name),
+            Some(AssignName::Discard(_)) => None,
+            None => self.module.split('/').next_back(),
+        }
+    }
+
+    fn to_source(&self) -> String {
+        let mut source = String::new();
+        for comment in &self.comments {
+            source.push_str(comment);
+            source.push('\n');
+        }
+        source.push_str("import ");
+        source.push_str(&self.module);
+
+        let unqualified = |(name, alias): &(EcoString, Option<EcoString>)| match alias {
+            Some(alias) => format!("{name} as {alias}"),
+            None => name.to_string(),
+        };
+        let types = self
+            .types
+            .iter()
+            .map(|type_| format!("type {}", unqualified(type_)));
+        let values = self.values.iter().map(unqualified);
+        let unqualified = types.chain(values).join(", ");
+        if !unqualified.is_empty() {
+            source.push_str(".{");
+            source.push_str(&unqualified);
+            source.push('}');
+        }
+
+        if let Some(alias) = &self.alias {
+            source.push_str(" as ");
+            source.push_str(alias.name());
+        }
+        source
+    }
+}
+
+/// Rewrites the imports of the module, removing the unused ones and adding
+/// the missing ones, and sorts them into a single block in place of the
+/// first run of imports in the module.
+fn rewrite(
+    src: &str,
+    path: &Utf8Path,
+    imports: &[UntypedImport],
+    unused: &[SrcSpan],
+    missing: &[MissingImport],
+) -> Result<String, Error> {
+    if imports.is_empty() && missing.is_empty() {
+        return Ok(src.to_string());
+    }
+
+    let imports = imports
+        .iter()
+        .sorted_by_key(|import| import.location.start)
+        .collect_vec();
+
+    // The first run of imports is those separated only by blank lines and
+    // comments. The organized imports replace it, and any imports after it
+    // are removed.
+    let mut run_end = 0;
+    let mut organized = Vec::with_capacity(imports.len() + missing.len());
+    for (index, import) in imports.iter().enumerate() {
+        let gap = src
+            .get(run_end..import.location.start as usize)
+            .unwrap_or_default();
+        let in_first_run = index == 0
+            || imports
+                .get(index - 1)
+                .map(|previous| previous.location.end as usize)
+                == Some(run_end)
+                && gap.lines().all(|line| {
+                    let line = line.trim();
+                    line.is_empty() || line.starts_with("//")
+                });
+        let mut comments = vec![];
+        if in_first_run {
+            if index > 0 {
+                comments = gap
+                    .lines()
+                    .map(str::trim)
+                    .filter(|line| !line.is_empty())
+                    .map(String::from)
+                    .collect();
+            }
+            run_end = import.location.end as usize;
+        }
+
+        if unused.contains(&import.location) {
+            continue;
+        }
+        let unqualified = |items: &[crate::ast::UnqualifiedImport]| {
+            items
+                .iter()
+                .filter(|item| !unused.contains(&item.location))
+                .map(|item| (item.name.clone(), item.as_name.clone()))
+                .collect_vec()
+        };
+        let alias = import.as_name.as_ref().map(|(name, _)| name.clone());
+        let alias_is_unused = import
+            .alias_location()
+            .is_some_and(|location| unused.contains(&location));
+        let (alias, unused_alias) = match alias_is_unused {
+            true => (None, alias),
+            false => (alias, None),
+        };
+        organized.push(OrganizedImport {
+            module: import.module.clone(),
+            alias,
+            unused_alias,
+            types: unqualified(&import.unqualified_types),
+            values: unqualified(&import.unqualified_values),
+            comments,
+        });
+    }
+    let first_run_start = imports.first().map(|import| import.location.start as usize);
+
+    for missing in missing {
+        let (module, type_, value) = match missing {
+            MissingImport::Module { module } => (module, None, None),
+            MissingImport::Type { module, name } => (module, Some(name), None),
+            MissingImport::Value { module, name } => (module, None, Some(name)),
+        };
+        let index = match organized.iter().position(|import| import.module == *module) {
+            Some(index) => index,
+            None => {
+                organized.push(OrganizedImport::new(module.clone()));
+                organized.len() - 1
+            }
+        };
+        let Some(import) = organized.get_mut(index) else {
+            continue;
+        };
+        if let Some(name) = type_ {
+            import.types.push((name.clone(), None));
+        }
+        if let Some(name) = value {
+            import.values.push((name.clone(), None));
+        }
+    }
+
+    // Removing an alias gives the module its default name, which is kept
+    // unless another import already uses it.
+    for index in 0..organized.len() {
+        let Some(import) = organized.get(index) else {
+            continue;
+        };
+        if import.unused_alias.is_none() {
+            continue;
+        }
+        let name = import.used_name().map(EcoString::from);
+        let clashes = organized
+            .iter()
+            .enumerate()
+            .any(|(other, import)| other != index && import.used_name() == name.as_deref());
+        if let Some(import) = organized.get_mut(index) {
+            if clashes {
+                import.alias = import.unused_alias.take();
+            }
+        }
+    }
+
+    organized.sort_by(|one, other| one.module.cmp(&other.module));
+    let block_src: EcoString = organized
+        .iter()
+        .map(OrganizedImport::to_source)
+        .join("\n")
+        .into();
+    let mut block = String::new();
+    if !block_src.is_empty() {
+        crate::format::pretty(&mut block, &block_src, path)?;
+    }
+
+    let mut output = String::with_capacity(src.len() + block.len());
+    match first_run_start {
+        Some(start) => {
+            output.push_str(src.get(..start).unwrap_or_default());
+            output.push_str(block.trim_end_matches('\n'));
+            // If all the imports have been removed so are the blank lines
+            // that separated them from the rest of the module.
+            let mut rest_start = run_end;
+            if block.is_empty() {
+                while src.get(rest_start..rest_start + 1) == Some("\n") {
+                    rest_start += 1;
+                }
+            }
+            push_without_imports(&mut output, src, rest_start, &imports);
+        }
+
+        None => {
+            let start = import_position(src);
+            let (before, after) = src.split_at(start);
+            output.push_str(before);
+            if !before.is_empty() && !before.ends_with("\n\n") {
+                output.push('\n');
+            }
+            output.push_str(&block);
+            if !after.is_empty() {
+                output.push('\n');
+            }
+            output.push_str(after.trim_start_matches('\n'));
+        }
+    }
+    Ok(output)
+}
+
+/// Pushes the module from `start` onwards, leaving out any imports along
+/// with the blank lines that separated them from the code around them.
+fn push_without_imports(output: &mut String, src: &str, start: usize, imports: &[&UntypedImport]) {
+    let mut position = start;
+    let mut removed = false;
+    for import in imports
+        .iter()
+        .filter(|import| import.location.start as usize >= start)
+    {
+        removed = true;
+        output.push_str(
+            src.get(position..import.location.start as usize)
+                .unwrap_or_default(),
+        );
+        position = import.location.end as usize;
+        if src.get(position..position + 1) == Some("\n") {
+            position += 1;
+        }
+        while output.ends_with("\n\n") && src.get(position..position + 1) == Some("\n") {
+            position += 1;
+        }
+    }
+    output.push_str(src.get(position..).unwrap_or_default());
+    while removed && output.ends_with("\n\n") {
+        let _ = output.pop();
+    }
+}
+
+/// Where to add imports to a module that has none: after any module
+/// documentation at the start of the module.
+fn import_position(src: &str) -> usize {
+    let mut position = 0;
+    for line in src.split_inclusive('\n') {
+        let trimmed = line.trim();
+        if !trimmed.is_empty() && !trimmed.starts_with("////") {
+            break;
+        }
+        position += line.len();
+    }
+    position
+}
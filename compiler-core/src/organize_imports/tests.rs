@@ -0,0 +1,396 @@
+use camino::Utf8Path;
+use ecow::EcoString;
+use globset::GlobBuilder;
+
+use crate::{
+    analyse::TargetSupport,
+    build::{Mode, Origin, Outcome, Target},
+    config::PackageConfig,
+    line_numbers::LineNumbers,
+    requirement::Requirement,
+    type_::{ModuleInterface, PRELUDE_MODULE_NAME},
+    uid::UniqueIdGenerator,
+    warning::TypeWarningEmitter,
+};
+
+use super::ImportOrganizer;
+
+macro_rules! assert_organized {
+    ($src:expr $(,)?) => {{
+        let output = $crate::organize_imports::tests::organize($src);
+        insta::assert_snapshot!(insta::internals::AutoName, output, $src);
+    }};
+}
+
+macro_rules! assert_unchanged {
+    ($src:expr $(,)?) => {{
+        let output = $crate::organize_imports::tests::organize($src);
+        assert_eq!(output, $src);
+    }};
+}
+
+fn root_config() -> PackageConfig {
+    PackageConfig {
+        name: "my_package".into(),
+        dependencies: [("gleam_stdlib".into(), Requirement::hex(">= 1.0.0"))].into(),
+        ..PackageConfig::default()
+    }
+}
+
+fn dependency_config(name: &str) -> PackageConfig {
+    PackageConfig {
+        name: name.into(),
+        internal_modules: Some(vec![GlobBuilder::new("*/internal")
+            .build()
+            .expect("internals glob")]),
+        ..PackageConfig::default()
+    }
+}
+
+fn analyse(
+    modules: &mut im::HashMap<EcoString, ModuleInterface>,
+    ids: &UniqueIdGenerator,
+    config: &PackageConfig,
+    name: &str,
+    src: &str,
+) {
+    let mut ast = crate::parse::parse_module(src)
+        .expect("syntax error")
+        .module;
+    ast.name = name.into();
+    let module = crate::analyse::ModuleAnalyzerConstructor::<()> {
+        target: Target::Erlang,
+        ids,
+        origin: Origin::Src,
+        importable_modules: modules,
+        warnings: &TypeWarningEmitter::null(),
+        direct_dependencies: &std::collections::HashMap::new(),
+        target_support: TargetSupport::NotEnforced,
+        package_config: config,
+    }
+    .infer_module(ast, LineNumbers::new(src), "".into())
+    .expect("should successfully infer");
+    let _ = modules.insert(name.into(), module.type_info);
+}
+
+/// The modules that can be imported: some from the root package, some from a
+/// direct dependency, and one from a package that is not a direct dependency.
+fn importable_modules() -> im::HashMap<EcoString, ModuleInterface> {
+    let ids = UniqueIdGenerator::new();
+    let mut modules = im::HashMap::new();
+    let _ = modules.insert(
+        PRELUDE_MODULE_NAME.into(),
+        crate::type_::build_prelude(&ids),
+    );
+    let stdlib = dependency_config("gleam_stdlib");
+    analyse(
+        &mut modules,
+        &ids,
+        &stdlib,
+        "gleam/list",
+        "pub fn map(list: List(a), f: fn(a) -> b) -> List(b) { todo }
+pub fn length(list: List(a)) -> Int { 0 }",
+    );
+    analyse(
+        &mut modules,
+        &ids,
+        &stdlib,
+        "gleam/option",
+        "pub type Option(a) { Some(a) None }
+pub fn unwrap(option: Option(a), default: a) -> a { default }",
+    );
+    analyse(
+        &mut modules,
+        &ids,
+        &stdlib,
+        "gleam/string",
+        "pub fn length(string: String) -> Int { 0 }",
+    );
+    analyse(
+        &mut modules,
+        &ids,
+        &stdlib,
+        "gleam/internal",
+        "pub fn secret() -> Int { 0 }",
+    );
+    analyse(
+        &mut modules,
+        &ids,
+        &dependency_config("other"),
+        "other/thing",
+        "pub fn transitive() -> Int { 0 }",
+    );
+    analyse(
+        &mut modules,
+        &ids,
+        &root_config(),
+        "my/helpers",
+        "pub type Wibble { Wibble }
+pub fn helper() -> Int { 0 }",
+    );
+    modules
+}
+
+pub fn organize(src: &str) -> String {
+    let importable_modules = importable_modules();
+    let config = root_config();
+    let organizer = ImportOrganizer {
+        module_name: &"my/module".into(),
+        path: Utf8Path::new("src/my/module.gleam"),
+        origin: Origin::Src,
+        target: Target::Erlang,
+        mode: Mode::Dev,
+        package_config: &config,
+        importable_modules: &importable_modules,
+    };
+    match organizer.organize(src) {
+        Outcome::Ok(src) | Outcome::PartialFailure(src, _) => src,
+        Outcome::TotalFailure(error) => panic!("{}", error.pretty_string()),
+    }
+}
+
+#[test]
+fn organized_imports_are_unchanged() {
+    assert_unchanged!(
+        r#"import gleam/list
+import gleam/option.{type Option, Some}
+
+pub fn main(x: Option(Int)) {
+  list.map([x, Some(1)], fn(x) { x })
+}
+"#
+    );
+}
+
+#[test]
+fn module_without_imports_is_unchanged() {
+    assert_unchanged!("pub fn main() {\n  1\n}\n");
+}
+
+#[test]
+fn unused_import_is_removed() {
+    assert_organized!(
+        r#"import gleam/list
+import gleam/string
+
+pub fn main() {
+  list.length([])
+}
+"#
+    );
+}
+
+#[test]
+fn all_unused_imports_are_removed() {
+    assert_organized!(
+        r#"import gleam/list
+import gleam/string
+
+pub fn main() {
+  1
+}
+"#
+    );
+}
+
+#[test]
+fn unused_unqualified_imports_are_removed() {
+    assert_organized!(
+        r#"import gleam/option.{type Option, None, Some, unwrap}
+
+pub fn main(x: Option(Int)) {
+  unwrap(x, 1)
+}
+"#
+    );
+}
+
+#[test]
+fn import_is_removed_when_its_unqualified_imports_are_unused() {
+    assert_organized!(
+        r#"import gleam/list.{map}
+
+pub fn main() {
+  1
+}
+"#
+    );
+}
+
+#[test]
+fn unused_alias_is_removed() {
+    assert_organized!(
+        r#"import gleam/option.{Some} as opt
+
+pub fn main() {
+  Some(1)
+}
+"#
+    );
+}
+
+#[test]
+fn unused_alias_is_kept_if_removing_it_would_clash() {
+    assert_organized!(
+        r#"import gleam/list.{map} as l
+import gleam/string as list
+
+pub fn main() {
+  #(map([], fn(x) { x }), list.length(""))
+}
+"#
+    );
+}
+
+#[test]
+fn imports_are_sorted_into_one_block() {
+    assert_organized!(
+        r#"import gleam/string
+
+import gleam/option
+import gleam/list
+
+pub fn main() {
+  #(list.length([]), string.length(""), option.None)
+}
+"#
+    );
+}
+
+#[test]
+fn comments_are_kept_with_their_import() {
+    assert_organized!(
+        r#"import gleam/string
+// Used for the list
+import gleam/list
+
+pub fn main() {
+  #(list.length([]), string.length(""))
+}
+"#
+    );
+}
+
+#[test]
+fn imports_after_other_definitions_are_moved() {
+    assert_organized!(
+        r#"import gleam/string
+
+pub fn main() {
+  #(list.length([]), string.length(""))
+}
+
+import gleam/list
+"#
+    );
+}
+
+#[test]
+fn missing_module_is_imported() {
+    assert_organized!(
+        r#"import gleam/string
+
+pub fn main() {
+  #(list.length([]), string.length(""))
+}
+"#
+    );
+}
+
+#[test]
+fn missing_import_is_added_to_module_without_imports() {
+    assert_organized!(
+        r#"//// This is a module
+
+pub fn main() {
+  list.length([])
+}
+"#
+    );
+}
+
+#[test]
+fn missing_value_is_imported() {
+    assert_organized!(
+        r#"pub fn main() {
+  map([1], fn(x) { x })
+}
+"#
+    );
+}
+
+#[test]
+fn missing_type_and_constructor_are_imported() {
+    assert_organized!(
+        r#"pub fn main() -> Option(Int) {
+  Some(1)
+}
+"#
+    );
+}
+
+#[test]
+fn missing_value_is_added_to_existing_import() {
+    assert_organized!(
+        r#"import gleam/option.{Some}
+
+pub fn main() {
+  unwrap(Some(1), 2)
+}
+"#
+    );
+}
+
+#[test]
+fn missing_values_from_the_root_package_are_imported() {
+    assert_organized!(
+        r#"pub fn main() {
+  #(helper(), Wibble)
+}
+"#
+    );
+}
+
+#[test]
+fn ambiguous_values_are_not_imported() {
+    // `length` is defined in both `gleam/list` and `gleam/string`.
+    assert_unchanged!(
+        r#"pub fn main() {
+  length([])
+}
+"#
+    );
+}
+
+#[test]
+fn internal_modules_of_dependencies_are_not_imported() {
+    assert_unchanged!(
+        r#"pub fn main() {
+  secret()
+}
+"#
+    );
+}
+
+#[test]
+fn modules_of_indirect_dependencies_are_not_imported() {
+    assert_unchanged!(
+        r#"pub fn main() {
+  transitive()
+}
+"#
+    );
+}
+
+#[test]
+fn unused_imports_are_kept_when_there_are_other_errors() {
+    assert_organized!(
+        r#"import gleam/string
+import gleam/list
+
+pub fn main() {
+  list.length(1)
+}
+"#
+    );
+}
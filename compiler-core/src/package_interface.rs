@@ -9,12 +9,28 @@ mod tests;
 
 use crate::{
     ast::{CustomType, Definition, Function, ModuleConstant, Publicity, TypeAlias},
+    config::PackageConfig,
     io::ordered_map,
-    type_::{expression::Implementations, Deprecation, Type, TypeVar},
+    type_::{
+        self, expression::Implementations, Deprecation, FieldMap, Type, TypeVar,
+        ValueConstructorVariant,
+    },
 };
 
 use crate::build::{Module, Package};
 
+/// The public interface of a project's root package and each of its
+/// dependencies that gets serialised as a json object.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub struct ProjectInterface {
+    /// The name of the root package.
+    root: EcoString,
+    /// A map from package name to its interface, including the root package.
+    #[serde(serialize_with = "ordered_map")]
+    packages: HashMap<EcoString, PackageInterface>,
+}
+
 /// The public interface of a package that gets serialised as a json object.
 #[derive(Serialize, Debug)]
 #[serde(rename_all = "kebab-case")]
@@ -357,6 +373,53 @@ pub struct ParameterInterface {
     type_: TypeInterface,
 }
 
+impl ProjectInterface {
+    /// Dependencies are compiled from their cached module interfaces rather
+    /// than their syntax trees, so their modules' documentation is not
+    /// included.
+    pub fn new<'a>(
+        root: &Package,
+        dependencies: &[PackageConfig],
+        module_interfaces: impl IntoIterator<Item = &'a type_::ModuleInterface>,
+    ) -> ProjectInterface {
+        let mut packages: HashMap<_, _> = dependencies
+            .iter()
+            .map(|config| {
+                let interface = PackageInterface {
+                    name: config.name.clone(),
+                    version: config.version.to_string().into(),
+                    gleam_version_constraint: config.gleam_version.clone(),
+                    modules: HashMap::new(),
+                };
+                (config.name.clone(), interface)
+            })
+            .collect();
+
+        for module in module_interfaces {
+            if module.is_internal {
+                continue;
+            }
+            // The root package's modules come from its syntax tree, and the
+            // prelude does not belong to any package.
+            let Some(package) = packages.get_mut(&module.package) else {
+                continue;
+            };
+            let _ = package
+                .modules
+                .insert(module.name.clone(), ModuleInterface::from_interface(module));
+        }
+
+        let _ = packages.insert(
+            root.config.name.clone(),
+            PackageInterface::from_package(root),
+        );
+        ProjectInterface {
+            root: root.config.name.clone(),
+            packages,
+        }
+    }
+}
+
 impl PackageInterface {
     pub fn from_package(package: &Package) -> PackageInterface {
         PackageInterface {
@@ -496,6 +559,7 @@ impl ModuleInterface {
                     return_annotation: _,
                     external_erlang: _,
                     external_javascript: _,
+                    behaviour: _,
                 }) => {
                     let mut id_map = IdMap::new();
                     let _ = functions.insert(
@@ -537,6 +601,176 @@ impl ModuleInterface {
             functions,
         }
     }
+
+    /// Builds the interface of a module from its compiled interface, as used
+    /// for dependencies. Compiled interfaces do not hold the module's
+    /// documentation, so it is left empty.
+    fn from_interface(interface: &type_::ModuleInterface) -> ModuleInterface {
+        let mut types = HashMap::new();
+        let mut type_aliases = HashMap::new();
+        let mut constants = HashMap::new();
+        let mut functions = HashMap::new();
+
+        for (name, type_) in &interface.types {
+            if !type_.publicity.is_public() {
+                continue;
+            }
+            let deprecation = DeprecationInterface::from_deprecation(&type_.deprecation);
+
+            // Type aliases are the only types without a list of constructors.
+            let Some(type_constructors) = interface.types_value_constructors.get(name) else {
+                let _ = type_aliases.insert(
+                    name.clone(),
+                    TypeAliasInterface {
+                        documentation: trim_documentation(&type_.documentation),
+                        deprecation,
+                        parameters: type_.parameters.len(),
+                        alias: TypeInterface::from_type(type_.typ.as_ref()),
+                    },
+                );
+                continue;
+            };
+
+            let mut id_map = IdMap::new();
+            for parameter in &type_.parameters {
+                id_map.add_type_variable_id(parameter.as_ref());
+            }
+
+            // The constructors of opaque types are private, so they are left
+            // out here.
+            let constructors = type_constructors
+                .variants
+                .iter()
+                .filter_map(|variant| {
+                    let constructor = interface.values.get(&variant.name)?;
+                    let ValueConstructorVariant::Record {
+                        documentation,
+                        field_map,
+                        ..
+                    } = &constructor.variant
+                    else {
+                        return None;
+                    };
+                    if !constructor.publicity.is_public() {
+                        return None;
+                    }
+                    Some(TypeConstructorInterface {
+                        documentation: trim_documentation(documentation),
+                        name: variant.name.clone(),
+                        parameters: variant
+                            .parameters
+                            .iter()
+                            .enumerate()
+                            .map(|(index, parameter)| ParameterInterface {
+                                label: label_at(field_map, index),
+                                type_: from_type_helper(&parameter.type_, &mut id_map),
+                            })
+                            .collect(),
+                    })
+                })
+                .collect();
+
+            let _ = types.insert(
+                name.clone(),
+                TypeDefinitionInterface {
+                    documentation: trim_documentation(&type_.documentation),
+                    deprecation,
+                    parameters: type_.parameters.len(),
+                    constructors,
+                },
+            );
+        }
+
+        for (name, value) in &interface.values {
+            if !value.publicity.is_public() {
+                continue;
+            }
+            let deprecation = DeprecationInterface::from_deprecation(&value.deprecation);
+            match &value.variant {
+                ValueConstructorVariant::ModuleConstant {
+                    documentation,
+                    implementations,
+                    ..
+                } => {
+                    let _ = constants.insert(
+                        name.clone(),
+                        ConstantInterface {
+                            documentation: trim_documentation(documentation),
+                            deprecation,
+                            implementations: ImplementationsInterface::from_implementations(
+                                implementations,
+                            ),
+                            type_: TypeInterface::from_type(value.type_.as_ref()),
+                        },
+                    );
+                }
+
+                ValueConstructorVariant::ModuleFn {
+                    documentation,
+                    implementations,
+                    field_map,
+                    ..
+                } => {
+                    let Some((arguments, return_type)) = value.type_.fn_types() else {
+                        continue;
+                    };
+                    let mut id_map = IdMap::new();
+                    let _ = functions.insert(
+                        name.clone(),
+                        FunctionInterface {
+                            documentation: trim_documentation(documentation),
+                            deprecation,
+                            implementations: ImplementationsInterface::from_implementations(
+                                implementations,
+                            ),
+                            parameters: arguments
+                                .iter()
+                                .enumerate()
+                                .map(|(index, argument)| ParameterInterface {
+                                    label: label_at(field_map, index),
+                                    type_: from_type_helper(argument, &mut id_map),
+                                })
+                                .collect(),
+                            return_: from_type_helper(&return_type, &mut id_map),
+                        },
+                    );
+                }
+
+                // Record constructors are included with their types.
+                ValueConstructorVariant::Record { .. } => {}
+
+                // These are never defined at the top level of a module.
+                ValueConstructorVariant::LocalVariable { .. }
+                | ValueConstructorVariant::LocalConstant { .. } => {}
+            }
+        }
+
+        ModuleInterface {
+            documentation: vec![],
+            types,
+            type_aliases,
+            constants,
+            functions,
+        }
+    }
+}
+
+/// Documentation in a compiled module interface ends with a newline, which is
+/// not present in the documentation taken from a syntax tree.
+fn trim_documentation(documentation: &Option<EcoString>) -> Option<EcoString> {
+    documentation
+        .as_ref()
+        .map(|documentation| documentation.trim_end_matches('\n').into())
+}
+
+/// The label of the argument at the given position, if it has one.
+fn label_at(field_map: &Option<FieldMap>, index: usize) -> Option<EcoString> {
+    field_map
+        .as_ref()?
+        .fields
+        .iter()
+        .find(|(_, position)| **position as usize == index)
+        .map(|(label, _)| (*label).into())
 }
 
 impl TypeInterface {
@@ -597,9 +831,9 @@ fn from_type_helper(type_: &Type, id_map: &mut IdMap) -> TypeInterface {
             package,
             ..
         } => TypeInterface::Named {
-            name: name.clone(),
-            package: package.clone(),
-            module: module.clone(),
+            name: (*name).into(),
+            package: (*package).into(),
+            module: (*module).into(),
             parameters: args
                 .iter()
                 .map(|arg| from_type_helper(arg.as_ref(), id_map))
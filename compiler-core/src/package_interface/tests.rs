@@ -14,7 +14,7 @@ use crate::{
     warning::TypeWarningEmitter,
 };
 
-use super::PackageInterface;
+use super::{ModuleInterface, PackageInterface, ProjectInterface};
 
 #[macro_export]
 macro_rules! assert_package_interface_with_name {
@@ -130,6 +130,103 @@ pub fn compile_package(
     serde_json::to_string_pretty(&PackageInterface::from_package(&package)).expect("to json")
 }
 
+macro_rules! assert_project_interface {
+    ($dep_name:expr, $dep_src:expr $(,)?) => {{
+        let output = $crate::package_interface::tests::compile_project($dep_name, $dep_src);
+        insta::assert_snapshot!(insta::internals::AutoName, output, $dep_src);
+    }};
+}
+
+fn analyse_dependency(
+    name: &str,
+    src: &str,
+    modules: &im::HashMap<EcoString, crate::type_::ModuleInterface>,
+    ids: &UniqueIdGenerator,
+) -> crate::type_::ModuleInterface {
+    let parsed = crate::parse::parse_module(src).expect("dep syntax error");
+    let mut ast = parsed.module;
+    ast.name = name.into();
+    crate::analyse::ModuleAnalyzerConstructor::<()> {
+        target: Target::Erlang,
+        ids,
+        origin: Origin::Src,
+        importable_modules: modules,
+        warnings: &TypeWarningEmitter::null(),
+        direct_dependencies: &std::collections::HashMap::new(),
+        target_support: TargetSupport::Enforced,
+        package_config: &dependency_config(),
+    }
+    .infer_module(ast, LineNumbers::new(src), "".into())
+    .expect("should successfully infer")
+    .type_info
+}
+
+fn dependency_config() -> PackageConfig {
+    PackageConfig {
+        name: "wibble".into(),
+        version: hexpm::version::Version::new(1, 2, 3),
+        gleam_version: Some(">= 1.0.0".into()),
+        internal_modules: Some(vec![GlobBuilder::new("wibble/internal")
+            .build()
+            .expect("internals glob")]),
+        ..PackageConfig::default()
+    }
+}
+
+/// Compiles a module of a dependency and returns the interface of the project
+/// with an empty root package.
+pub fn compile_project(dep_name: &str, dep_src: &str) -> String {
+    let ids = UniqueIdGenerator::new();
+    let mut modules = im::HashMap::new();
+    let _ = modules.insert(
+        PRELUDE_MODULE_NAME.into(),
+        crate::type_::build_prelude(&ids),
+    );
+    let dependency = analyse_dependency(dep_name, dep_src, &modules, &ids);
+    let _ = modules.insert(dep_name.into(), dependency);
+
+    let mut root = package_from_module(module_from_src("pub fn main() { 1 }"));
+    root.modules.clear();
+    let interface = ProjectInterface::new(&root, &[dependency_config()], modules.values());
+    serde_json::to_string_pretty(&interface).expect("to json")
+}
+
+fn module_from_src(src: &str) -> Module {
+    let ids = UniqueIdGenerator::new();
+    let mut modules = im::HashMap::new();
+    let _ = modules.insert(
+        PRELUDE_MODULE_NAME.into(),
+        crate::type_::build_prelude(&ids),
+    );
+    let parsed = crate::parse::parse_module(src).expect("syntax error");
+    let mut ast = parsed.module;
+    ast.name = "wibble/wobble".into();
+    let ast = crate::analyse::ModuleAnalyzerConstructor::<()> {
+        target: Target::Erlang,
+        ids: &ids,
+        origin: Origin::Src,
+        importable_modules: &modules,
+        warnings: &TypeWarningEmitter::null(),
+        direct_dependencies: &std::collections::HashMap::new(),
+        target_support: TargetSupport::Enforced,
+        package_config: &dependency_config(),
+    }
+    .infer_module(ast, LineNumbers::new(src), "".into())
+    .expect("should successfully infer");
+    let mut module = Module {
+        name: "wibble/wobble".into(),
+        code: src.into(),
+        mtime: SystemTime::UNIX_EPOCH,
+        input_path: "wibble".into(),
+        origin: Origin::Src,
+        ast,
+        extra: parsed.extra,
+        dependencies: vec![],
+    };
+    module.attach_doc_and_module_comments();
+    module
+}
+
 fn package_from_module(module: Module) -> Package {
     Package {
         config: PackageConfig {
@@ -155,9 +252,20 @@ fn package_from_module(module: Module) -> Package {
             erlang: ErlangConfig::default(),
             javascript: JavaScriptConfig::default(),
             target: Target::Erlang,
+            resolution_strategy: Default::default(),
+            deps: Default::default(),
+            organization: None,
+            registries: vec![],
+            warnings: Default::default(),
+            lints: Default::default(),
+            import_rules: Default::default(),
             internal_modules: Some(vec![GlobBuilder::new("internals/*")
                 .build()
                 .expect("internals glob")]),
+            default_features: vec![],
+            features: Default::default(),
+            env: Default::default(),
+            config_env: None,
         },
         modules: vec![module],
     }
@@ -293,3 +401,89 @@ pub type Box(a, b) {
 pub fn internal_modules_are_not_exported() {
     assert_package_interface_with_name!("internals/internal_module", "pub fn main() { 1 }");
 }
+
+#[test]
+pub fn dependency_definitions() {
+    assert_project_interface!(
+        "wibble/wobble",
+        r#"
+/// A box
+pub type Box(a) {
+  /// A box with a label
+  Box(label: String, a)
+  Empty
+}
+
+pub opaque type Secret {
+  Secret(String)
+}
+
+@deprecated("Use Box instead")
+pub type Boxes(a) = List(Box(a))
+
+/// The answer
+pub const answer = 42
+
+type Private {
+  Private
+}
+
+fn private() { Private }
+
+@internal
+pub fn internal() { private() Nil }
+
+/// Puts a value in a box
+pub fn box(value a: b, with label: String) -> Box(b) {
+  Box(label, a)
+}
+"#
+    );
+}
+
+#[test]
+pub fn dependency_internal_modules_are_not_exported() {
+    assert_project_interface!("wibble/internal", "pub fn main() { 1 }");
+}
+
+// The interface of a dependency is built from its compiled module interface
+// rather than its syntax tree, so it should match the interface of the same
+// module in the root package, other than the module's documentation.
+#[test]
+pub fn dependency_interface_matches_root_interface() {
+    let src = r#"
+pub type Box(a, b) {
+  /// A box
+  Box(b, Int)
+  OtherBox(message: String, a: a)
+}
+
+pub opaque type Secret(a) {
+  Secret(a)
+}
+
+pub type Pair(a) = #(a, a)
+
+@deprecated("Use a Box")
+pub const pair: Pair(Int) = #(1, 2)
+
+@external(erlang, "wibble", "wobble")
+pub fn wobble(a: a, label b: fn(b) -> a) -> Box(a, b)
+
+/// Wobbles
+pub fn main(x: Pair(a)) -> Pair(a) { x }
+"#;
+    let ids = UniqueIdGenerator::new();
+    let mut modules = im::HashMap::new();
+    let _ = modules.insert(
+        PRELUDE_MODULE_NAME.into(),
+        crate::type_::build_prelude(&ids),
+    );
+    let from_interface =
+        ModuleInterface::from_interface(&analyse_dependency("wibble/wobble", src, &modules, &ids));
+    let from_module = ModuleInterface::from_module(&module_from_src(src));
+    assert_eq!(
+        serde_json::to_string_pretty(&from_interface).expect("to json"),
+        serde_json::to_string_pretty(&from_module).expect("to json"),
+    );
+}
@@ -51,19 +51,21 @@
 //
 pub mod error;
 pub mod extra;
+pub mod incremental;
 pub mod lexer;
-mod token;
+pub(crate) mod token;
 
 use crate::analyse::Inferred;
+use crate::arena::{Arena, ArenaVec};
 use crate::ast::{
     Arg, ArgNames, AssignName, Assignment, AssignmentKind, BinOp, BitArrayOption, BitArraySegment,
-    CallArg, Clause, ClauseGuard, Constant, CustomType, Definition, Function, HasLocation, Import,
-    Module, ModuleConstant, Pattern, Publicity, RecordConstructor, RecordConstructorArg,
-    RecordUpdateSpread, SrcSpan, Statement, TargetedDefinition, TodoKind, TypeAlias, TypeAst,
-    TypeAstConstructor, TypeAstFn, TypeAstHole, TypeAstTuple, TypeAstVar, UnqualifiedImport,
-    UntypedArg, UntypedClause, UntypedClauseGuard, UntypedConstant, UntypedDefinition, UntypedExpr,
-    UntypedModule, UntypedPattern, UntypedRecordUpdateArg, UntypedStatement, Use, UseAssignment,
-    CAPTURE_VARIABLE,
+    CallArg, Clause, ClauseGuard, Constant, CustomType, Definition, DefinitionFeature, Function,
+    HasLocation, Import, Module, ModuleConstant, Pattern, Publicity, RecordConstructor,
+    RecordConstructorArg, RecordUpdateSpread, SrcSpan, Statement, TargetedDefinition, TodoKind,
+    TypeAlias, TypeAst, TypeAstConstructor, TypeAstFn, TypeAstHole, TypeAstTuple, TypeAstVar,
+    UnqualifiedImport, UntypedArg, UntypedClause, UntypedClauseGuard, UntypedConstant,
+    UntypedDefinition, UntypedExpr, UntypedModule, UntypedPattern, UntypedRecordUpdateArg,
+    UntypedStatement, Use, UseAssignment, CAPTURE_VARIABLE,
 };
 use crate::build::Target;
 use crate::parse::extra::ModuleExtra;
@@ -112,15 +114,19 @@ enum InternalAttribute {
 #[derive(Debug, Default)]
 struct Attributes {
     target: Option<Target>,
+    feature: Option<DefinitionFeature>,
     deprecated: Deprecation,
     external_erlang: Option<(EcoString, EcoString)>,
     external_javascript: Option<(EcoString, EcoString)>,
     internal: InternalAttribute,
+    behaviour: Option<EcoString>,
 }
 
 impl Attributes {
     fn has_function_only(&self) -> bool {
-        self.external_erlang.is_some() || self.external_javascript.is_some()
+        self.external_erlang.is_some()
+            || self.external_javascript.is_some()
+            || self.behaviour.is_some()
     }
 }
 
@@ -128,20 +134,36 @@ impl Attributes {
 // Public Interface
 //
 pub fn parse_module(src: &str) -> Result<Parsed, ParseError> {
-    let lex = lexer::make_tokenizer(src);
-    let mut parser = Parser::new(lex);
+    let arena = Arena::new();
+    let lex = lexer::make_tokenizer(src, &arena);
+    let mut parser = Parser::new(lex, &arena);
     let mut parsed = parser.parse_module()?;
     parsed.extra = parser.extra;
     Ok(parsed)
 }
 
+/// Parses a module, recovering from syntax errors so that the definitions that
+/// could be parsed are still returned along with all the errors found. This is
+/// used by the language server so that a module that is being edited can still
+/// be analysed.
+///
+/// Each definition is expected to start at the beginning of a line, as it does
+/// in formatted code, and a definition that contains an error is skipped up to
+/// the start of the next one. A module function keeps its head and the
+/// statements before the error in its body.
+///
+pub fn parse_module_with_recovery(src: &str) -> (Parsed, Vec<ParseError>) {
+    incremental::RetainedModule::new(src).parsed()
+}
+
 //
 // Test Interface
 //
 #[cfg(test)]
 pub fn parse_statement_sequence(src: &str) -> Result<Vec1<UntypedStatement>, ParseError> {
-    let lex = lexer::make_tokenizer(src);
-    let mut parser = Parser::new(lex);
+    let arena = Arena::new();
+    let lex = lexer::make_tokenizer(src, &arena);
+    let mut parser = Parser::new(lex, &arena);
     let expr = parser.parse_statement_seq();
     let expr = parser.ensure_no_errors_or_remaining_input(expr)?;
     if let Some((e, _)) = expr {
@@ -156,8 +178,9 @@ pub fn parse_statement_sequence(src: &str) -> Result<Vec1<UntypedStatement>, Par
 //
 #[cfg(test)]
 pub fn parse_const_value(src: &str) -> Result<Constant<(), ()>, ParseError> {
-    let lex = lexer::make_tokenizer(src);
-    let mut parser = Parser::new(lex);
+    let arena = Arena::new();
+    let lex = lexer::make_tokenizer(src, &arena);
+    let mut parser = Parser::new(lex, &arena);
     let expr = parser.parse_const_value();
     let expr = parser.ensure_no_errors_or_remaining_input(expr)?;
     if let Some(e) = expr {
@@ -170,33 +193,106 @@ pub fn parse_const_value(src: &str) -> Result<Constant<(), ()>, ParseError> {
 //
 // Parser
 //
+
+/// The deepest that expressions, patterns, types, and constants can be nested
+/// within each other.
+const MAX_NESTING: u32 = 256;
+
+// When less than this much stack remains a new segment is allocated for the
+// parser to continue in.
+const STACK_RED_ZONE: usize = 128 * 1024;
+const STACK_SEGMENT_SIZE: usize = 2 * 1024 * 1024;
+
 #[derive(Debug)]
-pub struct Parser<T: Iterator<Item = LexResult>> {
+pub struct Parser<'a, T: Iterator<Item = LexResult>> {
     tokens: T,
+    arena: &'a Arena,
     lex_errors: Vec<LexicalError>,
     tok0: Option<Spanned>,
     tok1: Option<Spanned>,
     extra: ModuleExtra,
     doc_comments: VecDeque<(u32, String)>,
+    recovery: Option<Recovery>,
+    nesting: u32,
 }
-impl<T> Parser<T>
+
+/// The state of a parser that is recovering from errors, see
+/// `parse_module_with_recovery`.
+#[derive(Debug, Default)]
+struct Recovery {
+    /// The first token of the next definition. It is held back so that the
+    /// definition being parsed ends before it, even if it is incomplete.
+    next_definition: Option<Spanned>,
+    /// Whether the last definition keyword at the start of a line was an
+    /// attribute, in which case the definition it belongs to follows it.
+    after_attribute: bool,
+    /// Errors in definitions that were kept despite being incomplete.
+    errors: Vec<ParseError>,
+    /// The end of the last token that was not held back, if any.
+    last_token_end: Option<u32>,
+}
+
+impl<'a, T> Parser<'a, T>
 where
     T: Iterator<Item = LexResult>,
 {
-    pub fn new(input: T) -> Self {
+    pub fn new(input: T, arena: &'a Arena) -> Self {
         let mut parser = Parser {
             tokens: input,
+            arena,
             lex_errors: vec![],
             tok0: None,
             tok1: None,
             extra: ModuleExtra::new(),
             doc_comments: VecDeque::new(),
+            recovery: None,
+            nesting: 0,
         };
         parser.advance();
         parser.advance();
         parser
     }
 
+    // Creates a parser that recovers from errors, stopping at the start of
+    // the next definition, see `incremental::RetainedModule`.
+    fn with_recovery(input: T, arena: &'a Arena) -> Self {
+        let mut parser = Parser {
+            tokens: input,
+            arena,
+            lex_errors: vec![],
+            tok0: None,
+            tok1: None,
+            extra: ModuleExtra::new(),
+            doc_comments: VecDeque::new(),
+            recovery: Some(Recovery::default()),
+            nesting: 0,
+        };
+        parser.advance();
+        parser.advance();
+        parser
+    }
+
+    // Parses an expression, pattern, type, or constant that may be nested
+    // within another. The stack is grown as needed so that deeply nested
+    // source does not overflow it, and source nested more deeply than
+    // `MAX_NESTING` is rejected so that later passes do not overflow either.
+    fn nested<A>(
+        &mut self,
+        parse: impl FnOnce(&mut Self) -> Result<A, ParseError>,
+    ) -> Result<A, ParseError> {
+        if self.nesting >= MAX_NESTING {
+            let location = match &self.tok0 {
+                Some((start, _, end)) => SrcSpan::new(*start, *end),
+                None => SrcSpan::new(0, 0),
+            };
+            return parse_error(ParseErrorType::TooDeeplyNested, location);
+        }
+        self.nesting += 1;
+        let result = stacker::maybe_grow(STACK_RED_ZONE, STACK_SEGMENT_SIZE, || parse(self));
+        self.nesting -= 1;
+        result
+    }
+
     fn parse_module(&mut self) -> Result<Parsed, ParseError> {
         let definitions = Parser::series_of(self, &Parser::parse_definition, None);
         let definitions = self.ensure_no_errors_or_remaining_input(definitions)?;
@@ -212,6 +308,29 @@ where
         })
     }
 
+    // Whether a token is the start of a new definition, and so should be held
+    // back when recovering from errors. Definitions start at the beginning of
+    // a line, and are only split from the attributes before them if those are
+    // not followed by a definition keyword. The first token always belongs to
+    // the definition being parsed.
+    fn starts_definition(&mut self, (start, token, _): &Spanned) -> bool {
+        let Some(recovery) = self.recovery.as_mut() else {
+            return false;
+        };
+        let line_start = *start == 0 || self.extra.new_lines.last() == Some(&(start - 1));
+        let attribute = matches!(token, Token::At);
+        let definition = matches!(
+            token,
+            Token::Pub | Token::Fn | Token::Type | Token::Import | Token::Const
+        );
+        if !line_start || !(attribute || definition) {
+            return false;
+        }
+        let starts_definition = !recovery.after_attribute && recovery.last_token_end.is_some();
+        recovery.after_attribute = attribute;
+        starts_definition
+    }
+
     // The way the parser is currently implemented, it cannot exit immediately while advancing
     // the token stream upon seeing a LexError. That is to avoid having to put `?` all over the
     // place and instead we collect LexErrors in `self.lex_errors` and attempt to continue parsing.
@@ -317,11 +436,13 @@ where
             (Some(definition), _) if definition.is_function() => Ok(Some(TargetedDefinition {
                 definition,
                 target: attributes.target,
+                feature: attributes.feature,
             })),
 
             (Some(definition), None) => Ok(Some(TargetedDefinition {
                 definition,
                 target: attributes.target,
+                feature: attributes.feature,
             })),
 
             (_, Some(location)) if attributes.has_function_only() => {
@@ -331,6 +452,7 @@ where
             (Some(definition), _) => Ok(Some(TargetedDefinition {
                 definition,
                 target: attributes.target,
+                feature: attributes.feature,
             })),
 
             (_, Some(location)) => parse_error(ParseErrorType::ExpectedDefinition, location),
@@ -350,8 +472,8 @@ where
     //   unit op unit pipe unit(call) pipe unit(call)
     fn parse_expression(&mut self) -> Result<Option<UntypedExpr>, ParseError> {
         // uses the simple operator parser algorithm
-        let mut opstack = vec![];
-        let mut estack = vec![];
+        let mut opstack = self.arena.vec();
+        let mut estack = self.arena.vec();
         let mut last_op_start = 0;
         let mut last_op_end = 0;
         loop {
@@ -417,6 +539,10 @@ where
     //   A(a.., label: tuple(1))
     //   { expression_sequence }
     fn parse_expression_unit(&mut self) -> Result<Option<UntypedExpr>, ParseError> {
+        self.nested(Self::parse_expression_unit_inner)
+    }
+
+    fn parse_expression_unit_inner(&mut self) -> Result<Option<UntypedExpr>, ParseError> {
         let mut expr = match self.tok0.take() {
             Some((start, Token::String { value }, end)) => {
                 self.advance();
@@ -958,6 +1084,10 @@ where
 
     // The left side of an "=" or a "->"
     fn parse_pattern(&mut self) -> Result<Option<UntypedPattern>, ParseError> {
+        self.nested(Self::parse_pattern_inner)
+    }
+
+    fn parse_pattern_inner(&mut self) -> Result<Option<UntypedPattern>, ParseError> {
         let pattern = match self.tok0.take() {
             // Pattern::Var or Pattern::Constructor start
             Some((start, Token::Name { name }, end)) => {
@@ -1272,8 +1402,8 @@ where
         nested: bool,
     ) -> Result<Option<UntypedClauseGuard>, ParseError> {
         if self.maybe_one(&Token::If).is_some() || nested {
-            let mut opstack = vec![];
-            let mut estack = vec![];
+            let mut opstack = self.arena.vec();
+            let mut estack = self.arena.vec();
             let mut last_op_start = 0;
             let mut last_op_end = 0;
             loop {
@@ -1330,6 +1460,12 @@ where
     // a || b
     // a < b || b < c
     fn parse_case_clause_guard_unit(&mut self) -> Result<Option<UntypedClauseGuard>, ParseError> {
+        self.nested(Self::parse_case_clause_guard_unit_inner)
+    }
+
+    fn parse_case_clause_guard_unit_inner(
+        &mut self,
+    ) -> Result<Option<UntypedClauseGuard>, ParseError> {
         match self.tok0.take() {
             Some((start, Token::Bang, _)) => {
                 self.advance();
@@ -1579,9 +1715,14 @@ where
         let return_annotation = self.parse_type_annotation(&Token::RArrow)?;
 
         let (body, end, end_position) = match self.maybe_one(&Token::LeftBrace) {
-            Some(_) => {
-                let some_body = self.parse_statement_seq()?;
-                let (_, rbr_e) = self.expect_one(&Token::RightBrace)?;
+            Some((_, lbr_e)) => {
+                let (some_body, rbr_e) = if self.recovery.is_some() && !is_anon {
+                    self.parse_recovering_function_body(lbr_e)
+                } else {
+                    let some_body = self.parse_statement_seq()?;
+                    let (_, rbr_e) = self.expect_one(&Token::RightBrace)?;
+                    (some_body, rbr_e)
+                };
                 let end = return_annotation
                     .as_ref()
                     .map(|l| l.location().end)
@@ -1626,6 +1767,7 @@ where
             deprecation: std::mem::take(&mut attributes.deprecated),
             external_erlang: attributes.external_erlang.take(),
             external_javascript: attributes.external_javascript.take(),
+            behaviour: attributes.behaviour.take(),
             implementations: Implementations {
                 gleam: true,
                 can_run_on_erlang: true,
@@ -1636,6 +1778,54 @@ where
         })))
     }
 
+    // Parses the body of a module function when recovering from errors. If the
+    // body contains an error then the statements before it are kept, followed
+    // by a placeholder for the rest, and the remainder of the definition is
+    // skipped.
+    fn parse_recovering_function_body(
+        &mut self,
+        start: u32,
+    ) -> (Option<(Vec1<UntypedStatement>, u32)>, u32) {
+        let mut statements = vec![];
+        let error = loop {
+            match self.parse_statement() {
+                Ok(Some(statement)) => statements.push(statement),
+                Ok(None) => match self.expect_one(&Token::RightBrace) {
+                    Ok((_, end)) => {
+                        let body = Vec1::try_from_vec(statements).ok().map(|body| {
+                            let end = body.last().location().end;
+                            (body, end)
+                        });
+                        return (body, end);
+                    }
+                    Err(error) => break error,
+                },
+                Err(error) => break error,
+            }
+        };
+
+        let mut end = statements
+            .last()
+            .map(|statement| statement.location().end)
+            .unwrap_or(start);
+        while self.tok0.is_some() || self.tok1.is_some() {
+            if let Some((_, _, token_end)) = self.next_tok() {
+                end = token_end;
+            }
+        }
+        let placeholder_start = error.location.start.max(start);
+        statements.push(Statement::Expression(UntypedExpr::Placeholder {
+            location: SrcSpan::new(placeholder_start, placeholder_start),
+        }));
+        if let Some(recovery) = self.recovery.as_mut() {
+            recovery.errors.push(error);
+        }
+        let body = Vec1::try_from_vec(statements)
+            .ok()
+            .map(|body| (body, placeholder_start));
+        (body, end)
+    }
+
     fn publicity(
         &self,
         public: bool,
@@ -1975,6 +2165,10 @@ where
 
     // Parse the type part of a type annotation, same as `parse_type_annotation` minus the ":"
     fn parse_type(&mut self) -> Result<Option<TypeAst>, ParseError> {
+        self.nested(Self::parse_type_inner)
+    }
+
+    fn parse_type_inner(&mut self) -> Result<Option<TypeAst>, ParseError> {
         match self.tok0.take() {
             // Type hole
             Some((start, Token::DiscardName { name }, end)) => {
@@ -2296,6 +2490,10 @@ where
     //   True
     //   [1,2,3]
     fn parse_const_value(&mut self) -> Result<Option<UntypedConstant>, ParseError> {
+        self.nested(Self::parse_const_value_inner)
+    }
+
+    fn parse_const_value_inner(&mut self) -> Result<Option<UntypedConstant>, ParseError> {
         match self.tok0.take() {
             Some((start, Token::String { value }, end)) => {
                 self.advance();
@@ -2410,9 +2608,10 @@ where
                         },
                         SrcSpan { start, end },
                     ),
-                    None => {
-                        parse_error(ParseErrorType::UnexpectedEof, SrcSpan { start: 0, end: 0 })
-                    }
+                    None => self.unexpected_end(ParseErrorType::UnexpectedToken {
+                        expected: vec!["UpName".into(), "Name".into()],
+                        hint: None,
+                    }),
                 }
             }
 
@@ -2744,7 +2943,7 @@ where
                 ),
                 _ => parse_error(ParseErrorType::ExpectedName, SrcSpan { start, end }),
             },
-            None => parse_error(ParseErrorType::UnexpectedEof, SrcSpan { start: 0, end: 0 }),
+            None => self.unexpected_end(ParseErrorType::ExpectedName),
         }
     }
 
@@ -2763,7 +2962,7 @@ where
                     parse_error(ParseErrorType::ExpectedUpName, SrcSpan { start, end })
                 }
             }
-            None => parse_error(ParseErrorType::UnexpectedEof, SrcSpan { start: 0, end: 0 }),
+            None => self.unexpected_end(ParseErrorType::ExpectedUpName),
         }
     }
 
@@ -2879,7 +3078,10 @@ where
     // Error on the next token or EOF
     fn next_tok_unexpected<A>(&mut self, expected: Vec<EcoString>) -> Result<A, ParseError> {
         match self.next_tok() {
-            None => parse_error(ParseErrorType::UnexpectedEof, SrcSpan { start: 0, end: 0 }),
+            None => self.unexpected_end(ParseErrorType::UnexpectedToken {
+                expected,
+                hint: None,
+            }),
 
             Some((start, _, end)) => parse_error(
                 ParseErrorType::UnexpectedToken {
@@ -2891,6 +3093,20 @@ where
         }
     }
 
+    // Error on reaching the end of the input. When recovering from errors this
+    // is usually the start of the next definition, so the given error is
+    // reported there instead.
+    fn unexpected_end<A>(&self, error: ParseErrorType) -> Result<A, ParseError> {
+        match self
+            .recovery
+            .as_ref()
+            .and_then(|recovery| recovery.next_definition.as_ref())
+        {
+            Some((start, _, end)) => parse_error(error, SrcSpan::new(*start, *end)),
+            None => parse_error(ParseErrorType::UnexpectedEof, SrcSpan { start: 0, end: 0 }),
+        }
+    }
+
     // Moves the token stream forward
     fn advance(&mut self) {
         let _ = self.next_tok();
@@ -2903,6 +3119,14 @@ where
         let mut previous_newline = None;
         let mut nxt;
         loop {
+            if self
+                .recovery
+                .as_ref()
+                .is_some_and(|recovery| recovery.next_definition.is_some())
+            {
+                nxt = None;
+                break;
+            }
             match self.tokens.next() {
                 // gather and skip extra
                 Some(Ok((start, Token::CommentNormal, end))) => {
@@ -2937,7 +3161,18 @@ where
                     break;
                 }
 
+                Some(Ok(tok)) if self.starts_definition(&tok) => {
+                    if let Some(recovery) = self.recovery.as_mut() {
+                        recovery.next_definition = Some(tok);
+                    }
+                    nxt = None;
+                    break;
+                }
+
                 Some(Ok(tok)) => {
+                    if let Some(recovery) = self.recovery.as_mut() {
+                        recovery.last_token_end = Some(tok.2);
+                    }
                     nxt = Some(tok);
                     break;
                 }
@@ -3012,7 +3247,15 @@ where
                 let _ = self.expect_one(&Token::LeftParen)?;
                 self.parse_deprecated_attribute(start, end, attributes)
             }
+            "feature" => {
+                let _ = self.expect_one(&Token::LeftParen)?;
+                self.parse_feature_attribute(start, end, attributes)
+            }
             "internal" => self.parse_internal_attribute(start, end, attributes),
+            "behaviour" => {
+                let _ = self.expect_one(&Token::LeftParen)?;
+                self.parse_behaviour_attribute(start, end, attributes)
+            }
             _ => parse_error(ParseErrorType::UnknownAttribute, SrcSpan { start, end }),
         }?;
 
@@ -3093,6 +3336,39 @@ where
         Ok(end)
     }
 
+    fn parse_feature_attribute(
+        &mut self,
+        start: u32,
+        end: u32,
+        attributes: &mut Attributes,
+    ) -> Result<u32, ParseError> {
+        if attributes.feature.is_some() {
+            return parse_error(ParseErrorType::DuplicateAttribute, SrcSpan::new(start, end));
+        }
+        let (_, name, _) = self.expect_string()?;
+        let (_, end) = self.expect_one(&Token::RightParen)?;
+        attributes.feature = Some(DefinitionFeature {
+            name,
+            location: SrcSpan::new(start, end),
+        });
+        Ok(end)
+    }
+
+    fn parse_behaviour_attribute(
+        &mut self,
+        start: u32,
+        end: u32,
+        attributes: &mut Attributes,
+    ) -> Result<u32, ParseError> {
+        if attributes.behaviour.is_some() {
+            return parse_error(ParseErrorType::DuplicateAttribute, SrcSpan::new(start, end));
+        }
+        let (_, behaviour, _) = self.expect_string()?;
+        let (_, end) = self.expect_one(&Token::RightParen)?;
+        attributes.behaviour = Some(behaviour);
+        Ok(end)
+    }
+
     fn parse_internal_attribute(
         &mut self,
         start: u32,
@@ -3129,9 +3405,9 @@ fn concat_pattern_variable_left_hand_side_error<T>(start: u32, end: u32) -> Resu
 /// Simple-Precedence-Parser, handle seeing an operator or end
 fn handle_op<A>(
     next_op: Option<(Spanned, u8)>,
-    opstack: &mut Vec<(Spanned, u8)>,
-    estack: &mut Vec<A>,
-    do_reduce: &impl Fn(Spanned, &mut Vec<A>),
+    opstack: &mut ArenaVec<'_, (Spanned, u8)>,
+    estack: &mut ArenaVec<'_, A>,
+    do_reduce: &impl Fn(Spanned, &mut ArenaVec<'_, A>),
 ) -> Option<A> {
     let mut next_op = next_op;
     loop {
@@ -3209,7 +3485,7 @@ fn tok_to_binop(t: &Token) -> Option<BinOp> {
     }
 }
 /// Simple-Precedence-Parser, perform reduction for expression
-fn do_reduce_expression(op: Spanned, estack: &mut Vec<UntypedExpr>) {
+fn do_reduce_expression(op: Spanned, estack: &mut ArenaVec<'_, UntypedExpr>) {
     match (estack.pop(), estack.pop()) {
         (Some(er), Some(el)) => {
             let new_e = expr_op_reduction(op, el, er);
@@ -3220,7 +3496,7 @@ fn do_reduce_expression(op: Spanned, estack: &mut Vec<UntypedExpr>) {
 }
 
 /// Simple-Precedence-Parser, perform reduction for clause guard
-fn do_reduce_clause_guard(op: Spanned, estack: &mut Vec<UntypedClauseGuard>) {
+fn do_reduce_clause_guard(op: Spanned, estack: &mut ArenaVec<'_, UntypedClauseGuard>) {
     match (estack.pop(), estack.pop()) {
         (Some(er), Some(el)) => {
             let new_e = clause_guard_reduction(op, el, er);
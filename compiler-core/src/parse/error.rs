@@ -232,6 +232,12 @@ utf16_codepoint, utf32_codepoint, signed, unsigned, big, little, native, size, u
                     "See: https://tour.gleam.run/flow-control/case-expressions/".into(),
                 ],
             ),
+            ParseErrorType::TooDeeplyNested => (
+                "This is nested too deeply",
+                vec![
+                    "Hint: Move some of the nested code into variables or functions.".into(),
+                ],
+            ),
         }
     }
 }
@@ -288,6 +294,7 @@ pub enum ParseErrorType {
     ExpectedFunctionBody,       // let x = fn()
     RedundantInternalAttribute, // for a private definition marked as internal
     InvalidModuleTypePattern,   // for patterns that have a dot like: `name.thing`
+    TooDeeplyNested,            // [[[[...]]]] nested more deeply than the compiler supports
 }
 
 impl LexicalError {
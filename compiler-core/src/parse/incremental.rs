@@ -0,0 +1,1002 @@
+//! Parse trees that are kept between edits of a module so that the language
+//! server only has to parse the part of the module that changed.
+//!
+//! A module is split into chunks, each starting where a definition starts at
+//! the beginning of a line. These are the places the parser recovers from
+//! syntax errors at (see `parse_module_with_recovery`), so each chunk can be
+//! parsed on its own. Everything in a chunk is parsed relative to its start,
+//! so a chunk after an edit is reused as it is and only moved when the parse
+//! tree of the module is built.
+
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+use camino::{Utf8Path, Utf8PathBuf};
+use vec1::Vec1;
+
+use crate::arena::Arena;
+use crate::ast::{
+    Arg, Assignment, AssignmentKind, BitArrayOption, BitArraySegment, CallArg, Clause, ClauseGuard,
+    Constant, CustomType, Definition, Function, Import, Module, ModuleConstant, Pattern,
+    RecordConstructor, RecordConstructorArg, RecordUpdateSpread, SrcSpan, Statement,
+    TargetedDefinition, TypeAlias, TypeAst, TypeAstConstructor, TypeAstFn, TypeAstHole,
+    TypeAstTuple, TypeAstVar, UnqualifiedImport, UntypedExpr, UntypedRecordUpdateArg, Use,
+    UseAssignment,
+};
+
+use super::{
+    error::{LexicalError, ParseError, ParseErrorType},
+    extra::ModuleExtra,
+    lexer, Parsed, Parser,
+};
+
+#[cfg(test)]
+mod tests;
+
+/// The retained parse trees of the modules of a package, kept between
+/// compilations by the language server.
+#[derive(Debug, Clone, Default)]
+pub struct ParseCache {
+    modules: Rc<RefCell<HashMap<Utf8PathBuf, RetainedModule>>>,
+}
+
+impl ParseCache {
+    /// Parses a module, recovering from syntax errors and reusing what was
+    /// parsed the last time the module at the same path was parsed.
+    pub fn parse(&self, path: &Utf8Path, src: &str) -> (Parsed, Vec<ParseError>) {
+        let mut modules = self.modules.borrow_mut();
+        match modules.get_mut(path) {
+            Some(module) => {
+                module.update(src);
+                module.parsed()
+            }
+            None => {
+                let module = RetainedModule::new(src);
+                let parsed = module.parsed();
+                let _ = modules.insert(path.to_path_buf(), module);
+                parsed
+            }
+        }
+    }
+}
+
+/// A parsed module that can be updated with a new version of its source,
+/// parsing again only the definitions that were changed.
+#[derive(Debug, Clone)]
+pub struct RetainedModule {
+    src: String,
+    chunks: Vec<Chunk>,
+}
+
+#[derive(Debug, Clone)]
+struct Chunk {
+    /// Where the chunk starts in the module. Other than for the first chunk,
+    /// this is the end of the line with the last token of the chunk before.
+    start: u32,
+    /// The number of bytes of the source in the chunk.
+    length: u32,
+    /// The number of bytes of the source that were read to parse the chunk.
+    /// This includes the first token of the next chunk, as that is where the
+    /// chunk ends.
+    read: u32,
+    /// The location of the first token of the next chunk, if there is one.
+    next: Option<SrcSpan>,
+    definitions: Vec<TargetedDefinition>,
+    errors: Vec<ParseError>,
+    extra: ModuleExtra,
+    /// Documentation comments from the chunks before that were not used by a
+    /// definition there, so they are used by the first definition here.
+    documentation: Vec<String>,
+    /// Documentation comments that were not used by any definition so far.
+    remaining_documentation: Vec<String>,
+}
+
+impl RetainedModule {
+    pub fn new(src: &str) -> Self {
+        let mut module = Self {
+            src: src.into(),
+            chunks: vec![],
+        };
+        module.parse_from(0, vec![], vec![], 0);
+        module
+    }
+
+    /// Updates the module to a new version of its source. The chunks before
+    /// the changed part of the source are kept, and the chunks after it are
+    /// reused once parsing the changed chunks reaches the start of one again.
+    pub fn update(&mut self, src: &str) {
+        if self.src == src {
+            return;
+        }
+        let old = std::mem::replace(&mut self.src, src.into());
+        let prefix = old
+            .bytes()
+            .zip(src.bytes())
+            .take_while(|(old, new)| old == new)
+            .count();
+        let suffix = old
+            .bytes()
+            .rev()
+            .zip(src.bytes().rev())
+            .take(old.len().min(src.len()) - prefix)
+            .take_while(|(old, new)| old == new)
+            .count();
+        let changed_start = prefix as u32;
+        let old_changed_end = (old.len() - suffix) as u32;
+        let changed_end = (src.len() - suffix) as u32;
+
+        // Chunks that were parsed without reading the changed source are kept
+        // as they are, and the ones after the change are moved.
+        let first_changed = self
+            .chunks
+            .iter()
+            .position(|chunk| chunk.start + chunk.read >= changed_start)
+            .unwrap_or(self.chunks.len());
+        let following = self
+            .chunks
+            .split_off(first_changed)
+            .into_iter()
+            .filter(|chunk| chunk.start >= old_changed_end)
+            .map(|mut chunk| {
+                chunk.start = chunk.start - old_changed_end + changed_end;
+                chunk
+            })
+            .collect();
+        let (start, documentation) = match self.chunks.last() {
+            Some(chunk) => (
+                chunk.start + chunk.length,
+                chunk.remaining_documentation.clone(),
+            ),
+            None => (0, vec![]),
+        };
+        self.parse_from(start, documentation, following, changed_end);
+    }
+
+    /// Builds the parse tree of the module, along with all the errors found
+    /// in it.
+    pub fn parsed(&self) -> (Parsed, Vec<ParseError>) {
+        let mut definitions = vec![];
+        let mut errors = vec![];
+        let mut extra = ModuleExtra::new();
+        for chunk in &self.chunks {
+            let offset = chunk.start;
+            definitions.extend(chunk.definitions.iter().cloned().map(|mut definition| {
+                definition.shift(offset);
+                definition
+            }));
+            errors.extend(chunk.errors.iter().cloned().map(|mut error| {
+                error.shift(offset);
+                error
+            }));
+            let mut chunk_extra = chunk.extra.clone();
+            chunk_extra.shift(offset);
+            extra.module_comments.extend(chunk_extra.module_comments);
+            extra.doc_comments.extend(chunk_extra.doc_comments);
+            extra.comments.extend(chunk_extra.comments);
+            extra.empty_lines.extend(chunk_extra.empty_lines);
+            extra.new_lines.extend(chunk_extra.new_lines);
+        }
+        let module = Module {
+            name: "".into(),
+            documentation: vec![],
+            type_info: (),
+            definitions,
+        };
+        (Parsed { module, extra }, errors)
+    }
+
+    // Parses chunks from the given position to the end of the module, or
+    // until the next chunk starts where one of the following chunks does
+    // after the end of the changed source.
+    fn parse_from(
+        &mut self,
+        mut start: u32,
+        mut documentation: Vec<String>,
+        following: Vec<Chunk>,
+        changed_end: u32,
+    ) {
+        let mut following = following.into_iter().peekable();
+        loop {
+            let chunk = parse_chunk(&self.src, start, documentation);
+            let next_start = chunk.start + chunk.length;
+            let has_next = chunk.next.is_some();
+            documentation = chunk.remaining_documentation.clone();
+            self.chunks.push(chunk);
+            if !has_next {
+                break;
+            }
+            if next_start >= changed_end {
+                while following
+                    .peek()
+                    .is_some_and(|chunk| chunk.start < next_start)
+                {
+                    let _ = following.next();
+                }
+                if following.peek().is_some_and(|chunk| {
+                    chunk.start == next_start && chunk.documentation == documentation
+                }) {
+                    self.chunks.extend(following);
+                    break;
+                }
+            }
+            start = next_start;
+        }
+        self.join_split_definition();
+    }
+
+    // A definition keyword at the start of a line can still be part of the
+    // definition before it in code that is not formatted, in which case
+    // splitting the module there causes an error at the keyword. If the rest
+    // of the module parses without splitting it, it is kept as one chunk.
+    fn join_split_definition(&mut self) {
+        let Some((index, chunk)) = self
+            .chunks
+            .iter()
+            .enumerate()
+            .find(|(_, chunk)| !chunk.errors.is_empty())
+        else {
+            return;
+        };
+        let first_error = chunk.errors.first().map(|error| error.location);
+        if chunk.next.is_none() || chunk.next != first_error {
+            return;
+        }
+        if let Some(joined) = parse_rest(&self.src, chunk.start, chunk.documentation.clone()) {
+            self.chunks.truncate(index);
+            self.chunks.push(joined);
+        }
+    }
+}
+
+fn parser_for<'a>(
+    src: &'a str,
+    arena: &'a Arena,
+    start: u32,
+    documentation: &[String],
+    recover: bool,
+) -> Parser<'a, impl Iterator<Item = lexer::LexResult> + 'a> {
+    let tokens = lexer::make_tokenizer(src.get(start as usize..).unwrap_or_default(), arena);
+    let mut parser = if recover {
+        Parser::with_recovery(tokens, arena)
+    } else {
+        Parser::new(tokens, arena)
+    };
+    // The chunk starts with the newline before its first token, so these are
+    // always before any definition in it.
+    for content in documentation.iter().rev() {
+        parser.doc_comments.push_front((0, content.clone()));
+    }
+    parser
+}
+
+// Parses the definitions from the given position up to the start of the next
+// definition at the beginning of a line, recovering from errors.
+fn parse_chunk(src: &str, start: u32, documentation: Vec<String>) -> Chunk {
+    let arena = Arena::new();
+    let mut parser = parser_for(src, &arena, start, &documentation, true);
+    let mut definitions = vec![];
+    let mut errors = vec![];
+    loop {
+        match parser.parse_definition() {
+            Ok(Some(definition)) => definitions.push(definition),
+            Ok(None) => {
+                errors.extend(parser.tok0.as_ref().map(|(start, _, end)| ParseError {
+                    error: ParseErrorType::UnexpectedToken {
+                        expected: vec!["An import, const, type, or function.".into()],
+                        hint: None,
+                    },
+                    location: SrcSpan::new(*start, *end),
+                }));
+                break;
+            }
+            Err(error) => {
+                errors.push(error);
+                break;
+            }
+        }
+    }
+    // Skip the rest of the chunk after an error.
+    while parser.tok0.is_some() || parser.tok1.is_some() {
+        parser.advance();
+    }
+
+    let recovery = parser.recovery.take().unwrap_or_default();
+    errors.extend(recovery.errors);
+    // The lexer cannot continue after an error, so only the first one is
+    // meaningful and any errors after it are caused by it.
+    if let Some(error) = parser.lex_errors.first() {
+        errors.retain(|parse_error| {
+            parse_error.error != ParseErrorType::UnexpectedEof
+                && parse_error.location.start < error.location.start
+        });
+        errors.push(ParseError {
+            error: ParseErrorType::LexError {
+                error: error.clone(),
+            },
+            location: error.location,
+        });
+    }
+    errors.sort_by_key(|error| error.location.start);
+
+    let next = recovery
+        .next_definition
+        .map(|(start, _, end)| SrcSpan::new(start, end));
+    let rest = (src.len() - start as usize) as u32;
+    let (length, read) = match next {
+        // The chunk ends at the end of the line with its last token, so the
+        // comments before the next definition belong to the next chunk.
+        Some(next) => {
+            let length = recovery
+                .last_token_end
+                .and_then(|end| parser.extra.new_lines.iter().find(|line| **line >= end))
+                .copied()
+                .unwrap_or(next.start);
+            (length, next.end)
+        }
+        None => (rest, rest),
+    };
+
+    let mut extra = parser.extra;
+    extra.module_comments.retain(|span| span.start < length);
+    extra.doc_comments.retain(|span| span.start < length);
+    extra.comments.retain(|span| span.start < length);
+    extra.empty_lines.retain(|line| *line < length);
+    extra.new_lines.retain(|line| *line < length);
+    let remaining_documentation = parser
+        .doc_comments
+        .into_iter()
+        .filter(|(start, _)| *start < length)
+        .map(|(_, content)| content)
+        .collect();
+
+    Chunk {
+        start,
+        length,
+        read,
+        next,
+        definitions,
+        errors,
+        extra,
+        documentation,
+        remaining_documentation,
+    }
+}
+
+// Parses the rest of the module from the given position as a single chunk,
+// without recovering from errors.
+fn parse_rest(src: &str, start: u32, documentation: Vec<String>) -> Option<Chunk> {
+    let arena = Arena::new();
+    let mut parser = parser_for(src, &arena, start, &documentation, false);
+    let parsed = parser.parse_module().ok()?;
+    let rest = (src.len() - start as usize) as u32;
+    Some(Chunk {
+        start,
+        length: rest,
+        read: rest,
+        next: None,
+        definitions: parsed.module.definitions,
+        errors: vec![],
+        extra: parser.extra,
+        documentation,
+        remaining_documentation: vec![],
+    })
+}
+
+/// Moves something parsed relative to the start of a chunk to its position in
+/// the module.
+trait Shift {
+    fn shift(&mut self, offset: u32);
+}
+
+impl Shift for SrcSpan {
+    fn shift(&mut self, offset: u32) {
+        self.start += offset;
+        self.end += offset;
+    }
+}
+
+impl<T: Shift> Shift for Option<T> {
+    fn shift(&mut self, offset: u32) {
+        if let Some(value) = self {
+            value.shift(offset);
+        }
+    }
+}
+
+impl<T: Shift> Shift for Box<T> {
+    fn shift(&mut self, offset: u32) {
+        self.as_mut().shift(offset);
+    }
+}
+
+impl<T: Shift> Shift for Vec<T> {
+    fn shift(&mut self, offset: u32) {
+        self.iter_mut().for_each(|value| value.shift(offset));
+    }
+}
+
+impl<T: Shift> Shift for Vec1<T> {
+    fn shift(&mut self, offset: u32) {
+        self.iter_mut().for_each(|value| value.shift(offset));
+    }
+}
+
+impl Shift for ModuleExtra {
+    fn shift(&mut self, offset: u32) {
+        self.module_comments.shift(offset);
+        self.doc_comments.shift(offset);
+        self.comments.shift(offset);
+        self.empty_lines.iter_mut().for_each(|line| *line += offset);
+        self.new_lines.iter_mut().for_each(|line| *line += offset);
+    }
+}
+
+impl Shift for ParseError {
+    fn shift(&mut self, offset: u32) {
+        self.location.shift(offset);
+        if let ParseErrorType::LexError { error } = &mut self.error {
+            error.shift(offset);
+        }
+    }
+}
+
+impl Shift for LexicalError {
+    fn shift(&mut self, offset: u32) {
+        self.location.shift(offset);
+    }
+}
+
+impl Shift for TargetedDefinition {
+    fn shift(&mut self, offset: u32) {
+        if let Some(feature) = &mut self.feature {
+            feature.location.shift(offset);
+        }
+        match &mut self.definition {
+            Definition::Function(function) => function.shift(offset),
+            Definition::TypeAlias(alias) => alias.shift(offset),
+            Definition::CustomType(custom_type) => custom_type.shift(offset),
+            Definition::Import(import) => import.shift(offset),
+            Definition::ModuleConstant(constant) => constant.shift(offset),
+        }
+    }
+}
+
+impl Shift for Function<(), UntypedExpr> {
+    fn shift(&mut self, offset: u32) {
+        self.location.shift(offset);
+        self.end_position += offset;
+        self.arguments.shift(offset);
+        self.body.shift(offset);
+        self.return_annotation.shift(offset);
+    }
+}
+
+impl Shift for TypeAlias<()> {
+    fn shift(&mut self, offset: u32) {
+        self.location.shift(offset);
+        self.type_ast.shift(offset);
+    }
+}
+
+impl Shift for CustomType<()> {
+    fn shift(&mut self, offset: u32) {
+        self.location.shift(offset);
+        self.end_position += offset;
+        self.constructors.shift(offset);
+    }
+}
+
+impl Shift for RecordConstructor<()> {
+    fn shift(&mut self, offset: u32) {
+        self.location.shift(offset);
+        self.arguments.shift(offset);
+    }
+}
+
+impl Shift for RecordConstructorArg<()> {
+    fn shift(&mut self, offset: u32) {
+        self.location.shift(offset);
+        self.ast.shift(offset);
+    }
+}
+
+impl Shift for Import<()> {
+    fn shift(&mut self, offset: u32) {
+        self.location.shift(offset);
+        if let Some((_, location)) = &mut self.as_name {
+            location.shift(offset);
+        }
+        self.unqualified_values.shift(offset);
+        self.unqualified_types.shift(offset);
+    }
+}
+
+impl Shift for UnqualifiedImport {
+    fn shift(&mut self, offset: u32) {
+        self.location.shift(offset);
+    }
+}
+
+impl Shift for ModuleConstant<(), ()> {
+    fn shift(&mut self, offset: u32) {
+        self.location.shift(offset);
+        self.annotation.shift(offset);
+        self.value.shift(offset);
+    }
+}
+
+impl Shift for Arg<()> {
+    fn shift(&mut self, offset: u32) {
+        self.location.shift(offset);
+        self.annotation.shift(offset);
+    }
+}
+
+impl Shift for TypeAst {
+    fn shift(&mut self, offset: u32) {
+        match self {
+            TypeAst::Constructor(TypeAstConstructor {
+                location,
+                arguments,
+                ..
+            }) => {
+                location.shift(offset);
+                arguments.shift(offset);
+            }
+            TypeAst::Fn(TypeAstFn {
+                location,
+                arguments,
+                return_,
+            }) => {
+                location.shift(offset);
+                arguments.shift(offset);
+                return_.shift(offset);
+            }
+            TypeAst::Var(TypeAstVar { location, .. })
+            | TypeAst::Hole(TypeAstHole { location, .. }) => location.shift(offset),
+            TypeAst::Tuple(TypeAstTuple { location, elems }) => {
+                location.shift(offset);
+                elems.shift(offset);
+            }
+        }
+    }
+}
+
+impl Shift for Statement<(), UntypedExpr> {
+    fn shift(&mut self, offset: u32) {
+        match self {
+            Statement::Expression(expression) => expression.shift(offset),
+            Statement::Assignment(assignment) => assignment.shift(offset),
+            Statement::Use(use_) => use_.shift(offset),
+        }
+    }
+}
+
+impl Shift for Assignment<(), UntypedExpr> {
+    fn shift(&mut self, offset: u32) {
+        self.location.shift(offset);
+        self.value.shift(offset);
+        self.pattern.shift(offset);
+        if let AssignmentKind::Assert { location } = &mut self.kind {
+            location.shift(offset);
+        }
+        self.annotation.shift(offset);
+    }
+}
+
+impl Shift for Use {
+    fn shift(&mut self, offset: u32) {
+        self.call.shift(offset);
+        self.location.shift(offset);
+        self.assignments_location.shift(offset);
+        self.assignments.shift(offset);
+    }
+}
+
+impl Shift for UseAssignment {
+    fn shift(&mut self, offset: u32) {
+        self.location.shift(offset);
+        self.pattern.shift(offset);
+        self.annotation.shift(offset);
+    }
+}
+
+impl<A: Shift> Shift for CallArg<A> {
+    fn shift(&mut self, offset: u32) {
+        self.location.shift(offset);
+        self.value.shift(offset);
+    }
+}
+
+impl<A: Shift> Shift for BitArraySegment<A, ()> {
+    fn shift(&mut self, offset: u32) {
+        self.location.shift(offset);
+        self.value.shift(offset);
+        self.options.shift(offset);
+    }
+}
+
+impl<A: Shift> Shift for BitArrayOption<A> {
+    fn shift(&mut self, offset: u32) {
+        match self {
+            BitArrayOption::Size {
+                location, value, ..
+            } => {
+                location.shift(offset);
+                value.shift(offset);
+            }
+            BitArrayOption::Bytes { location }
+            | BitArrayOption::Int { location }
+            | BitArrayOption::Float { location }
+            | BitArrayOption::Bits { location }
+            | BitArrayOption::Utf8 { location }
+            | BitArrayOption::Utf16 { location }
+            | BitArrayOption::Utf32 { location }
+            | BitArrayOption::Utf8Codepoint { location }
+            | BitArrayOption::Utf16Codepoint { location }
+            | BitArrayOption::Utf32Codepoint { location }
+            | BitArrayOption::Signed { location }
+            | BitArrayOption::Unsigned { location }
+            | BitArrayOption::Big { location }
+            | BitArrayOption::Little { location }
+            | BitArrayOption::Native { location }
+            | BitArrayOption::Unit { location, .. } => location.shift(offset),
+        }
+    }
+}
+
+impl Shift for UntypedExpr {
+    fn shift(&mut self, offset: u32) {
+        match self {
+            UntypedExpr::Int { location, .. }
+            | UntypedExpr::Float { location, .. }
+            | UntypedExpr::String { location, .. }
+            | UntypedExpr::Var { location, .. }
+            | UntypedExpr::Placeholder { location } => location.shift(offset),
+
+            UntypedExpr::Block {
+                location,
+                statements,
+            } => {
+                location.shift(offset);
+                statements.shift(offset);
+            }
+
+            UntypedExpr::Fn {
+                location,
+                end_of_head_byte_index,
+                arguments,
+                body,
+                return_annotation,
+                ..
+            } => {
+                location.shift(offset);
+                *end_of_head_byte_index += offset;
+                arguments.shift(offset);
+                body.shift(offset);
+                return_annotation.shift(offset);
+            }
+
+            UntypedExpr::List {
+                location,
+                elements,
+                tail,
+            } => {
+                location.shift(offset);
+                elements.shift(offset);
+                tail.shift(offset);
+            }
+
+            UntypedExpr::Call {
+                location,
+                fun,
+                arguments,
+            } => {
+                location.shift(offset);
+                fun.shift(offset);
+                arguments.shift(offset);
+            }
+
+            UntypedExpr::BinOp {
+                location,
+                left,
+                right,
+                ..
+            } => {
+                location.shift(offset);
+                left.shift(offset);
+                right.shift(offset);
+            }
+
+            UntypedExpr::PipeLine { expressions } => expressions.shift(offset),
+
+            UntypedExpr::Case {
+                location,
+                subjects,
+                clauses,
+            } => {
+                location.shift(offset);
+                subjects.shift(offset);
+                clauses.shift(offset);
+            }
+
+            UntypedExpr::FieldAccess {
+                location,
+                label_location,
+                container,
+                ..
+            } => {
+                location.shift(offset);
+                label_location.shift(offset);
+                container.shift(offset);
+            }
+
+            UntypedExpr::Tuple { location, elems } => {
+                location.shift(offset);
+                elems.shift(offset);
+            }
+
+            UntypedExpr::TupleIndex {
+                location, tuple, ..
+            } => {
+                location.shift(offset);
+                tuple.shift(offset);
+            }
+
+            UntypedExpr::Todo {
+                location, message, ..
+            }
+            | UntypedExpr::Panic { location, message } => {
+                location.shift(offset);
+                message.shift(offset);
+            }
+
+            UntypedExpr::BitArray { location, segments } => {
+                location.shift(offset);
+                segments.shift(offset);
+            }
+
+            UntypedExpr::RecordUpdate {
+                location,
+                constructor,
+                spread,
+                arguments,
+            } => {
+                location.shift(offset);
+                constructor.shift(offset);
+                spread.shift(offset);
+                arguments.shift(offset);
+            }
+
+            UntypedExpr::NegateBool { location, value }
+            | UntypedExpr::NegateInt { location, value } => {
+                location.shift(offset);
+                value.shift(offset);
+            }
+        }
+    }
+}
+
+impl Shift for RecordUpdateSpread {
+    fn shift(&mut self, offset: u32) {
+        self.base.shift(offset);
+        self.location.shift(offset);
+    }
+}
+
+impl Shift for UntypedRecordUpdateArg {
+    fn shift(&mut self, offset: u32) {
+        self.location.shift(offset);
+        self.value.shift(offset);
+    }
+}
+
+impl Shift for Clause<UntypedExpr, (), ()> {
+    fn shift(&mut self, offset: u32) {
+        self.location.shift(offset);
+        self.pattern.shift(offset);
+        self.alternative_patterns.shift(offset);
+        self.guard.shift(offset);
+        self.then.shift(offset);
+    }
+}
+
+impl Shift for ClauseGuard<(), ()> {
+    fn shift(&mut self, offset: u32) {
+        match self {
+            ClauseGuard::Equals {
+                location,
+                left,
+                right,
+            }
+            | ClauseGuard::NotEquals {
+                location,
+                left,
+                right,
+            }
+            | ClauseGuard::GtInt {
+                location,
+                left,
+                right,
+            }
+            | ClauseGuard::GtEqInt {
+                location,
+                left,
+                right,
+            }
+            | ClauseGuard::LtInt {
+                location,
+                left,
+                right,
+            }
+            | ClauseGuard::LtEqInt {
+                location,
+                left,
+                right,
+            }
+            | ClauseGuard::GtFloat {
+                location,
+                left,
+                right,
+            }
+            | ClauseGuard::GtEqFloat {
+                location,
+                left,
+                right,
+            }
+            | ClauseGuard::LtFloat {
+                location,
+                left,
+                right,
+            }
+            | ClauseGuard::LtEqFloat {
+                location,
+                left,
+                right,
+            }
+            | ClauseGuard::Or {
+                location,
+                left,
+                right,
+            }
+            | ClauseGuard::And {
+                location,
+                left,
+                right,
+            } => {
+                location.shift(offset);
+                left.shift(offset);
+                right.shift(offset);
+            }
+
+            ClauseGuard::Not {
+                location,
+                expression,
+            } => {
+                location.shift(offset);
+                expression.shift(offset);
+            }
+
+            ClauseGuard::Var { location, .. } => location.shift(offset),
+
+            ClauseGuard::TupleIndex {
+                location, tuple, ..
+            } => {
+                location.shift(offset);
+                tuple.shift(offset);
+            }
+
+            ClauseGuard::FieldAccess {
+                location,
+                container,
+                ..
+            } => {
+                location.shift(offset);
+                container.shift(offset);
+            }
+
+            ClauseGuard::ModuleSelect {
+                location, literal, ..
+            } => {
+                location.shift(offset);
+                literal.shift(offset);
+            }
+
+            ClauseGuard::Constant(constant) => constant.shift(offset),
+        }
+    }
+}
+
+impl Shift for Constant<(), ()> {
+    fn shift(&mut self, offset: u32) {
+        match self {
+            Constant::Int { location, .. }
+            | Constant::Float { location, .. }
+            | Constant::String { location, .. }
+            | Constant::Var { location, .. }
+            | Constant::Invalid { location, .. } => location.shift(offset),
+
+            Constant::Tuple { location, elements }
+            | Constant::List {
+                location, elements, ..
+            } => {
+                location.shift(offset);
+                elements.shift(offset);
+            }
+
+            Constant::Record { location, args, .. } => {
+                location.shift(offset);
+                args.shift(offset);
+            }
+
+            Constant::BitArray { location, segments } => {
+                location.shift(offset);
+                segments.shift(offset);
+            }
+        }
+    }
+}
+
+impl Shift for Pattern<()> {
+    fn shift(&mut self, offset: u32) {
+        match self {
+            Pattern::Int { location, .. }
+            | Pattern::Float { location, .. }
+            | Pattern::String { location, .. }
+            | Pattern::Variable { location, .. }
+            | Pattern::VarUsage { location, .. }
+            | Pattern::Discard { location, .. } => location.shift(offset),
+
+            Pattern::Assign {
+                location, pattern, ..
+            } => {
+                location.shift(offset);
+                pattern.shift(offset);
+            }
+
+            Pattern::List {
+                location,
+                elements,
+                tail,
+                ..
+            } => {
+                location.shift(offset);
+                elements.shift(offset);
+                tail.shift(offset);
+            }
+
+            Pattern::Constructor {
+                location,
+                arguments,
+                ..
+            } => {
+                location.shift(offset);
+                arguments.shift(offset);
+            }
+
+            Pattern::Tuple { location, elems } => {
+                location.shift(offset);
+                elems.shift(offset);
+            }
+
+            Pattern::BitArray { location, segments } => {
+                location.shift(offset);
+                segments.shift(offset);
+            }
+
+            Pattern::StringPrefix {
+                location,
+                left_location,
+                left_side_assignment,
+                right_location,
+                ..
+            } => {
+                location.shift(offset);
+                left_location.shift(offset);
+                if let Some((_, location)) = left_side_assignment {
+                    location.shift(offset);
+                }
+                right_location.shift(offset);
+            }
+        }
+    }
+}
@@ -0,0 +1,197 @@
+use super::RetainedModule;
+use crate::ast::Definition;
+use crate::parse::{error::ParseError, Parsed};
+
+use pretty_assertions::assert_eq;
+
+const MODULE: &str = r#"//// A module
+
+import gleam/io
+import gleam/list.{map} as l
+
+/// The answer
+pub const answer: Int = 42
+
+// A comment
+pub type Wibble(a) {
+  /// A wobble
+  Wobble(a: a, b: Int)
+  Wubble
+}
+
+@deprecated("Use main2")
+pub fn main() {
+  let x = case answer {
+    42 if answer > 1 -> <<1:size(8), "a":utf8>>
+    _ -> <<>>
+  }
+  use y <- list.each([1, 2, 3])
+  io.debug(#(x, y).0)
+}
+
+fn wibble(a: Int, b b: Float) -> fn(Int) -> #(Int, Float) {
+  fn(c) { #(a + c, b) }
+  // Trailing comment
+}
+"#;
+
+fn assert_same(left: (Parsed, Vec<ParseError>), right: (Parsed, Vec<ParseError>)) {
+    assert_eq!(
+        format!("{:#?}", left.0.module),
+        format!("{:#?}", right.0.module)
+    );
+    assert_eq!(left.0.extra, right.0.extra);
+    assert_eq!(left.1, right.1);
+}
+
+fn assert_same_as_full_parse(src: &str) {
+    let parsed = crate::parse::parse_module(src).expect("should parse");
+    assert_same(RetainedModule::new(src).parsed(), (parsed, vec![]));
+}
+
+// Checks that updating the module to each version of the source gives the
+// same result as parsing that version from scratch.
+fn assert_updates(first: &str, versions: &[&str]) {
+    let mut module = RetainedModule::new(first);
+    for version in versions {
+        module.update(version);
+        assert_same(module.parsed(), RetainedModule::new(version).parsed());
+    }
+}
+
+#[test]
+fn same_as_full_parse() {
+    assert_same_as_full_parse(MODULE);
+}
+
+#[test]
+fn same_as_full_parse_with_windows_newlines() {
+    assert_same_as_full_parse(&MODULE.replace('\n', "\r\n"));
+}
+
+#[test]
+fn same_as_full_parse_with_definitions_on_one_line() {
+    assert_same_as_full_parse("fn a() { 1 } fn b() { 2 }\nfn c() { 3 }");
+}
+
+#[test]
+fn same_as_full_parse_with_unused_documentation() {
+    assert_same_as_full_parse(
+        "fn main() {\n  /// Not used here\n  1\n}\n\n/// Used\npub fn wibble() { 2 }\n",
+    );
+}
+
+#[test]
+fn same_as_full_parse_with_definition_keyword_in_function() {
+    assert_same_as_full_parse("fn main() {\n  list.map(x,\nfn(a) { a })\n}\n\nfn wibble() { 1 }\n");
+}
+
+#[test]
+fn one_chunk_per_definition() {
+    let module = RetainedModule::new(MODULE);
+    assert_eq!(module.chunks.len(), 6);
+}
+
+#[test]
+fn chunks_after_change_are_reused() {
+    let mut module = RetainedModule::new(MODULE);
+    let reused = module
+        .chunks
+        .iter()
+        .map(|chunk| chunk.definitions.as_ptr())
+        .collect::<Vec<_>>();
+
+    module.update(&MODULE.replace("Wubble", "Wubble\n  Wabble"));
+
+    let definitions = module
+        .chunks
+        .iter()
+        .map(|chunk| chunk.definitions.as_ptr())
+        .collect::<Vec<_>>();
+    assert_eq!(definitions.len(), reused.len());
+    assert_eq!(definitions.get(..3), reused.get(..3));
+    assert_eq!(definitions.get(4..), reused.get(4..));
+
+    let changed = module
+        .chunks
+        .get(3)
+        .and_then(|chunk| chunk.definitions.first())
+        .map(|definition| &definition.definition);
+    match changed {
+        Some(Definition::CustomType(custom_type)) => {
+            assert_eq!(custom_type.constructors.len(), 3)
+        }
+        _ => panic!("expected a custom type"),
+    }
+}
+
+#[test]
+fn update_typing_function() {
+    let src = "pub fn main() {\n  1\n}\n\npub fn wibble() {\n  2\n}\n";
+    let typed = "fn wobble(a) {\n  a + 1\n}\n\n";
+    let (before, after) = src.split_at(src.find("pub fn wibble").expect("wibble"));
+    let versions = (0..=typed.len())
+        .map(|length| {
+            let typed = typed.chars().take(length).collect::<String>();
+            format!("{before}{typed}{after}")
+        })
+        .collect::<Vec<_>>();
+    let versions = versions.iter().map(String::as_str).collect::<Vec<_>>();
+    assert_updates(src, &versions);
+}
+
+#[test]
+fn update_deleting_each_character() {
+    let versions = (0..MODULE.len())
+        .filter(|index| MODULE.is_char_boundary(*index))
+        .map(|index| {
+            let mut version = MODULE.to_string();
+            let _ = version.remove(index);
+            version
+        })
+        .collect::<Vec<_>>();
+    for version in versions {
+        assert_updates(MODULE, &[&version, MODULE]);
+    }
+}
+
+#[test]
+fn update_adding_definition_keyword() {
+    assert_updates(
+        MODULE,
+        &[
+            &MODULE.replace("// Trailing comment", "pub fn"),
+            &MODULE.replace("// Trailing comment", "\npub fn"),
+            MODULE,
+        ],
+    );
+}
+
+#[test]
+fn update_removing_definition_keyword() {
+    assert_updates(
+        MODULE,
+        &[
+            &MODULE.replace("pub type", "type"),
+            &MODULE.replace("pub type", "ype"),
+            MODULE,
+        ],
+    );
+}
+
+#[test]
+fn update_documentation() {
+    assert_updates(
+        MODULE,
+        &[
+            &MODULE.replace("// A comment", "/// A comment"),
+            &MODULE.replace("  // Trailing comment", "  /// Trailing comment"),
+            MODULE,
+        ],
+    );
+}
+
+#[test]
+fn update_with_lex_error() {
+    assert_updates(MODULE, &[&MODULE.replace("a + c", "a # c"), MODULE]);
+}
@@ -1,3 +1,4 @@
+use crate::arena::{Arena, ArenaString};
 use crate::ast::SrcSpan;
 use crate::parse::error::{LexicalError, LexicalErrorType};
 use crate::parse::token::Token;
@@ -6,8 +7,9 @@ use std::char;
 use super::error::InvalidUnicodeEscapeError;
 
 #[derive(Debug)]
-pub struct Lexer<T: Iterator<Item = (u32, char)>> {
+pub struct Lexer<'a, T: Iterator<Item = (u32, char)>> {
     chars: T,
+    arena: &'a Arena,
     pending: Vec<Spanned>,
     chr0: Option<char>,
     chr1: Option<char>,
@@ -47,10 +49,13 @@ pub fn str_to_keyword(word: &str) -> Option<Token> {
     }
 }
 
-pub fn make_tokenizer(source: &str) -> impl Iterator<Item = LexResult> + '_ {
+pub fn make_tokenizer<'a>(
+    source: &'a str,
+    arena: &'a Arena,
+) -> impl Iterator<Item = LexResult> + 'a {
     let chars = source.char_indices().map(|(i, c)| (i as u32, c));
     let nlh = NewlineHandler::new(chars);
-    Lexer::new(nlh)
+    Lexer::new(nlh, arena)
 }
 
 // The newline handler is an iterator which collapses different newline
@@ -109,13 +114,14 @@ where
     }
 }
 
-impl<T> Lexer<T>
+impl<'a, T> Lexer<'a, T>
 where
     T: Iterator<Item = (u32, char)>,
 {
-    pub fn new(input: T) -> Self {
+    pub fn new(input: T, arena: &'a Arena) -> Self {
         let mut lxr = Lexer {
             chars: input,
+            arena,
             pending: Vec::new(),
             location: 0,
             chr0: None,
@@ -466,7 +472,7 @@ where
     // Lexer helper functions:
     // this can be either a reserved word, or a name
     fn lex_name(&mut self) -> LexResult {
-        let mut name = String::new();
+        let mut name = self.arena.string();
         let start_pos = self.get_pos();
 
         while self.is_name_continuation() {
@@ -481,7 +487,9 @@ where
             let end_pos = self.get_pos();
             if name.starts_with('_') {
                 return Err(LexicalError {
-                    error: LexicalErrorType::BadDiscardName { name },
+                    error: LexicalErrorType::BadDiscardName {
+                        name: name.as_str().into(),
+                    },
                     location: SrcSpan {
                         start: start_pos,
                         end: end_pos,
@@ -489,7 +497,9 @@ where
                 });
             } else {
                 return Err(LexicalError {
-                    error: LexicalErrorType::BadName { name },
+                    error: LexicalErrorType::BadName {
+                        name: name.as_str().into(),
+                    },
                     location: SrcSpan {
                         start: start_pos,
                         end: end_pos,
@@ -503,14 +513,26 @@ where
         if let Some(tok) = str_to_keyword(&name) {
             Ok((start_pos, tok, end_pos))
         } else if name.starts_with('_') {
-            Ok((start_pos, Token::DiscardName { name: name.into() }, end_pos))
+            Ok((
+                start_pos,
+                Token::DiscardName {
+                    name: name.as_str().into(),
+                },
+                end_pos,
+            ))
         } else {
-            Ok((start_pos, Token::Name { name: name.into() }, end_pos))
+            Ok((
+                start_pos,
+                Token::Name {
+                    name: name.as_str().into(),
+                },
+                end_pos,
+            ))
         }
     }
     // A type name or constructor
     fn lex_upname(&mut self) -> LexResult {
-        let mut name = String::new();
+        let mut name = self.arena.string();
         let start_pos = self.get_pos();
 
         while self.is_upname_continuation() {
@@ -524,7 +546,9 @@ where
             }
             let end_pos = self.get_pos();
             return Err(LexicalError {
-                error: LexicalErrorType::BadUpname { name },
+                error: LexicalErrorType::BadUpname {
+                    name: name.as_str().into(),
+                },
                 location: SrcSpan {
                     start: start_pos,
                     end: end_pos,
@@ -537,7 +561,13 @@ where
         if let Some(tok) = str_to_keyword(&name) {
             Ok((start_pos, tok, end_pos))
         } else {
-            Ok((start_pos, Token::UpName { name: name.into() }, end_pos))
+            Ok((
+                start_pos,
+                Token::UpName {
+                    name: name.as_str().into(),
+                },
+                end_pos,
+            ))
         }
     }
 
@@ -607,7 +637,7 @@ where
             Ok((
                 start_pos,
                 Token::Int {
-                    value: value.into(),
+                    value: value.as_str().into(),
                 },
                 end_pos,
             ))
@@ -626,7 +656,7 @@ where
 
     fn lex_decimal_or_int_number(&mut self, can_lex_decimal: bool) -> Spanned {
         let start_pos = self.get_pos();
-        let mut value = String::new();
+        let mut value = self.arena.string();
         // consume negative sign
         if self.chr0 == Some('-') {
             value.push(self.next_char().expect("lex_normal_number negative"));
@@ -654,7 +684,7 @@ where
             (
                 start_pos,
                 Token::Float {
-                    value: value.into(),
+                    value: value.as_str().into(),
                 },
                 end_pos,
             )
@@ -663,7 +693,7 @@ where
             (
                 start_pos,
                 Token::Int {
-                    value: value.into(),
+                    value: value.as_str().into(),
                 },
                 end_pos,
             )
@@ -687,8 +717,8 @@ where
     // Consume a sequence of numbers with the given radix,
     // the digits can be decorated with underscores
     // like this: '1_2_3_4' == '1234'
-    fn radix_run(&mut self, radix: u32) -> String {
-        let mut value_text = String::new();
+    fn radix_run(&mut self, radix: u32) -> ArenaString<'a> {
+        let mut value_text = self.arena.string();
 
         loop {
             if let Some(c) = self.take_number(radix) {
@@ -745,7 +775,7 @@ where
             }
             _ => Kind::Comment,
         };
-        let mut content = String::new();
+        let mut content = self.arena.string();
         let start_pos = self.get_pos();
         while Some('\n') != self.chr0 {
             match self.chr0 {
@@ -757,7 +787,9 @@ where
         let end_pos = self.get_pos();
         let token = match kind {
             Kind::Comment => Token::CommentNormal,
-            Kind::Doc => Token::CommentDoc { content },
+            Kind::Doc => Token::CommentDoc {
+                content: content.as_str().into(),
+            },
             Kind::ModuleDoc => Token::CommentModule,
         };
         (start_pos, token, end_pos)
@@ -767,7 +799,7 @@ where
         let start_pos = self.get_pos();
         // advance past the first quote
         let _ = self.next_char();
-        let mut string_content = String::new();
+        let mut string_content = self.arena.string();
 
         loop {
             match self.next_char() {
@@ -796,7 +828,7 @@ where
                                 }
 
                                 // All digits inside \u{...}.
-                                let mut hex_digits = String::new();
+                                let mut hex_digits = self.arena.string();
 
                                 loop {
                                     let _ = self.next_char();
@@ -910,7 +942,7 @@ where
         let end_pos = self.get_pos();
 
         let tok = Token::String {
-            value: string_content.into(),
+            value: string_content.as_str().into(),
         };
 
         Ok((start_pos, tok, end_pos))
@@ -988,7 +1020,7 @@ where
     }
 }
 
-impl<T> Iterator for Lexer<T>
+impl<T> Iterator for Lexer<'_, T>
 where
     T: Iterator<Item = (u32, char)>,
 {
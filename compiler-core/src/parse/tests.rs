@@ -1,3 +1,4 @@
+use crate::arena::Arena;
 use crate::ast::SrcSpan;
 use crate::parse::error::{
     InvalidUnicodeEscapeError, LexicalError, LexicalErrorType, ParseError, ParseErrorType,
@@ -657,6 +658,38 @@ pub fn main() -> Nil {
     );
 }
 
+#[test]
+fn multiple_feature_attributes() {
+    assert_module_error!(
+        r#"
+@feature("json")
+@feature("xml")
+pub fn main() -> Nil { Nil }
+"#
+    );
+}
+
+#[test]
+fn multiple_behaviour_attributes() {
+    assert_module_error!(
+        r#"
+@behaviour("gen_server")
+@behaviour("gen_statem")
+pub fn init(args: Int) -> Int { args }
+"#
+    );
+}
+
+#[test]
+fn behaviour_attribute_with_non_fn_definition() {
+    assert_module_error!(
+        r#"
+@behaviour("gen_server")
+pub type State
+"#
+    );
+}
+
 #[test]
 fn attributes_with_no_definition() {
     assert_module_error!(
@@ -1077,7 +1110,7 @@ const a = A(\"a\", let)
 #[test]
 fn newline_tokens() {
     assert_eq!(
-        make_tokenizer("1\n\n2\n").collect_vec(),
+        make_tokenizer("1\n\n2\n", &Arena::new()).collect_vec(),
         [
             Ok((0, Token::Int { value: "1".into() }, 1)),
             Ok((1, Token::NewLine, 2)),
@@ -1087,3 +1120,209 @@ fn newline_tokens() {
         ]
     );
 }
+
+macro_rules! assert_recovered_module {
+    ($src:expr) => {
+        let result = $crate::parse::tests::recover_module($src);
+        insta::assert_snapshot!(insta::internals::AutoName, result, $src);
+    };
+}
+
+// The definitions that were recovered from the module, followed by the errors.
+pub fn recover_module(src: &str) -> String {
+    use crate::ast::Definition;
+
+    let (parsed, errors) = crate::parse::parse_module_with_recovery(src);
+    let definitions =
+        parsed
+            .module
+            .definitions
+            .iter()
+            .map(|definition| match &definition.definition {
+                Definition::Function(function) => {
+                    format!("fn {} ({} statements)", function.name, function.body.len())
+                }
+                Definition::TypeAlias(alias) => format!("type {}", alias.alias),
+                Definition::CustomType(custom_type) => format!("type {}", custom_type.name),
+                Definition::Import(import) => format!("import {}", import.module),
+                Definition::ModuleConstant(constant) => format!("const {}", constant.name),
+            });
+    let errors = errors.into_iter().map(|error| {
+        crate::error::Error::Parse {
+            src: src.into(),
+            path: Utf8PathBuf::from("/src/parse/error.gleam"),
+            error,
+        }
+        .pretty_string()
+    });
+    definitions.chain(errors).join("\n")
+}
+
+#[test]
+fn recovery_missing_closing_brace() {
+    assert_recovered_module!(
+        "
+pub fn main() {
+  let x = 1
+  wibble(x
+
+pub fn wibble(x) {
+  x
+}
+"
+    );
+}
+
+#[test]
+fn recovery_missing_closing_brace_before_attribute() {
+    assert_recovered_module!(
+        "
+pub fn main() {
+  let x = 1
+
+@external(erlang, \"wibble\", \"wobble\")
+pub fn wobble() -> Int
+"
+    );
+}
+
+#[test]
+fn recovery_stray_comma() {
+    assert_recovered_module!(
+        "
+import gleam/io
+
+pub fn main() {
+  io.println(\"Hello\")
+  wibble(1,, 2)
+}
+
+type Wibble {
+  Wibble
+}
+"
+    );
+}
+
+#[test]
+fn recovery_incomplete_case() {
+    assert_recovered_module!(
+        "
+pub fn main() {
+  let x = 1
+  case x {
+    1 ->
+}
+
+const wobble = 2
+"
+    );
+}
+
+#[test]
+fn recovery_unexpected_token_between_definitions() {
+    assert_recovered_module!(
+        "
+pub fn main() {
+  1
+}
+}
+
+pub fn wibble() {
+  2
+}
+"
+    );
+}
+
+#[test]
+fn recovery_multiple_errors() {
+    assert_recovered_module!(
+        "
+pub fn main() {
+  1 +
+}
+
+pub fn wibble( {
+  2
+}
+
+pub fn wobble() {
+  3
+}
+"
+    );
+}
+
+#[test]
+fn recovery_lex_error() {
+    assert_recovered_module!(
+        "
+pub fn main() {
+  1
+}
+
+pub fn wibble() {
+  ;
+}
+
+pub fn wobble() {
+  3
+}
+"
+    );
+}
+
+#[test]
+fn recovery_not_needed() {
+    let src = "pub fn main() {\n  1\n}\nfn wibble() {\n  2\n}\n";
+    let (parsed, errors) = crate::parse::parse_module_with_recovery(src);
+    assert!(errors.is_empty());
+    assert_eq!(
+        format!("{:#?}", parsed.module),
+        format!(
+            "{:#?}",
+            crate::parse::parse_module(src)
+                .expect("should parse")
+                .module
+        )
+    );
+}
+
+// Inputs found by the fuzz targets in `fuzz/` which crashed the lexer or
+// parser. Any that still do will fail this test.
+#[test]
+fn fuzz_regressions() {
+    let directory = Utf8PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../fuzz/regressions");
+    for entry in std::fs::read_dir(&directory).expect("read regressions directory") {
+        let path = entry.expect("read regressions entry").path();
+        let bytes = std::fs::read(&path).expect("read regression");
+        let Ok(src) = std::str::from_utf8(&bytes) else {
+            continue;
+        };
+        let arena = Arena::new();
+        let _ = make_tokenizer(src, &arena).count();
+        let _ = crate::parse::parse_module(src);
+        let _ = crate::parse::parse_module_with_recovery(src);
+    }
+}
+
+#[test]
+fn deeply_nested_expression() {
+    let src = format!("{}{}", "[".repeat(300), "]".repeat(300));
+    let error = crate::parse::parse_statement_sequence(&src).expect_err("should not parse");
+    assert_eq!(error.error, ParseErrorType::TooDeeplyNested);
+}
+
+#[test]
+fn deeply_nested_type() {
+    let src = format!("type Wibble = {}Int{}", "#(".repeat(300), ")".repeat(300));
+    let error = crate::parse::parse_module(&src).expect_err("should not parse");
+    assert_eq!(error.error, ParseErrorType::TooDeeplyNested);
+}
+
+#[test]
+fn nested_within_limit() {
+    let src = format!("{}1{}", "{ ".repeat(200), " }".repeat(200));
+    assert!(crate::parse::parse_statement_sequence(&src).is_ok());
+}
@@ -1,17 +1,38 @@
-use crate::build::{Mode, Target};
+use crate::{
+    build::{Mode, Target},
+    manifest::{ManifestPackage, ManifestPackageSource},
+};
 
-use camino::{Utf8Path, Utf8PathBuf};
+use camino::{Utf8Component, Utf8Path, Utf8PathBuf};
 
 pub const ARTEFACT_DIRECTORY_NAME: &str = "_gleam_artefacts";
 
+/// The directory of Gleam modules generated when a package is built.
+pub const GENERATED_DIRECTORY_NAME: &str = "_gleam_generated";
+
 #[derive(Debug, Clone)]
 pub struct ProjectPaths {
     root: Utf8PathBuf,
+    /// The directory packages are compiled into, if not the build directory.
+    compiled_directory: Option<Utf8PathBuf>,
 }
 
 impl ProjectPaths {
     pub fn new(root: Utf8PathBuf) -> Self {
-        Self { root }
+        Self {
+            root,
+            compiled_directory: None,
+        }
+    }
+
+    /// Returns the paths of the project with its packages compiled into the
+    /// given directory, so a build that needs different artefacts doesn't
+    /// replace those of `gleam build`.
+    pub fn compiling_into(self, directory: Utf8PathBuf) -> Self {
+        Self {
+            compiled_directory: Some(directory),
+            ..self
+        }
     }
 
     pub fn at_filesystem_root() -> Self {
@@ -36,6 +57,10 @@ impl ProjectPaths {
         self.root.join("README.md")
     }
 
+    pub fn changelog(&self) -> Utf8PathBuf {
+        self.root.join("CHANGELOG.md")
+    }
+
     pub fn manifest(&self) -> Utf8PathBuf {
         self.root.join("manifest.toml")
     }
@@ -48,10 +73,36 @@ impl ProjectPaths {
         self.root.join("test")
     }
 
+    /// The directory of example projects that use the package.
+    pub fn examples_directory(&self) -> Utf8PathBuf {
+        self.root.join("examples")
+    }
+
+    pub fn config_directory(&self) -> Utf8PathBuf {
+        self.root.join(crate::runtime_config::DIRECTORY_NAME)
+    }
+
     pub fn build_directory(&self) -> Utf8PathBuf {
         self.root.join("build")
     }
 
+    /// The manifest of the files generated for each module of the root
+    /// package by the last build.
+    pub fn build_artefacts_manifest(&self) -> Utf8PathBuf {
+        self.build_directory().join(".artifacts.json")
+    }
+
+    /// The snapshot of the modules of the root package the tests last passed
+    /// against on the given target.
+    pub fn build_test_snapshot(&self, target: Target) -> Utf8PathBuf {
+        self.build_directory().join(format!(".tests.{target}.json"))
+    }
+
+    /// The socket the `gleam daemon` of the project listens on.
+    pub fn build_daemon_socket(&self) -> Utf8PathBuf {
+        self.build_directory().join("daemon.sock")
+    }
+
     pub fn build_packages_directory(&self) -> Utf8PathBuf {
         self.build_directory().join("packages")
     }
@@ -64,6 +115,28 @@ impl ProjectPaths {
         self.build_packages_directory().join(package_name)
     }
 
+    /// The directory the source of a downloaded package is extracted to.
+    /// Packages from Hex are put in a directory named after the checksum of
+    /// their tarball, so the source of a different release is never mistaken
+    /// for theirs.
+    pub fn build_packages_package_source(&self, package: &ManifestPackage) -> Utf8PathBuf {
+        match &package.source {
+            ManifestPackageSource::Hex { outer_checksum, .. } => {
+                let hash: String = outer_checksum
+                    .0
+                    .iter()
+                    .take(8)
+                    .map(|byte| format!("{byte:02x}"))
+                    .collect();
+                self.build_packages_directory()
+                    .join(format!("{}-{hash}", package.name))
+            }
+            ManifestPackageSource::Git { .. } | ManifestPackageSource::Local { .. } => {
+                self.build_packages_package(&package.name)
+            }
+        }
+    }
+
     // build_deps_package_config
     pub fn build_packages_package_config(&self, package_name: &str) -> Utf8PathBuf {
         self.build_packages_package(package_name).join("gleam.toml")
@@ -74,20 +147,58 @@ impl ProjectPaths {
             .join(format!("{package_name}-{version}.tar"))
     }
 
+    pub fn build_export_documentation_bundle(
+        &self,
+        package_name: &str,
+        version: &str,
+    ) -> Utf8PathBuf {
+        self.build_directory()
+            .join(format!("{package_name}-{version}-docs.tar.gz"))
+    }
+
+    /// The directory Mix and Hex use as their home when compiling
+    /// dependencies, kept apart from the directories of compiled packages.
+    pub fn build_mix_home(&self) -> Utf8PathBuf {
+        self.build_directory().join(".mix")
+    }
+
     pub fn build_directory_for_mode(&self, mode: Mode) -> Utf8PathBuf {
-        self.build_directory().join(mode.to_string())
+        self.compiled_directory
+            .clone()
+            .unwrap_or_else(|| self.build_directory())
+            .join(mode.to_string())
     }
 
     pub fn erlang_shipment_directory(&self) -> Utf8PathBuf {
         self.build_directory().join("erlang-shipment")
     }
 
+    pub fn erlang_project_directory(&self) -> Utf8PathBuf {
+        self.build_directory().join("erlang-project")
+    }
+
     pub fn build_documentation_directory(&self, package: &str) -> Utf8PathBuf {
         self.build_directory_for_mode(Mode::Dev)
             .join("docs")
             .join(package)
     }
 
+    /// The directory of the `gleam build --target wasm` output, which also
+    /// holds the packages compiled for it.
+    pub fn build_wasm_directory(&self) -> Utf8PathBuf {
+        self.build_directory().join("wasm")
+    }
+
+    pub fn build_wasm_module(&self, package: &str) -> Utf8PathBuf {
+        self.build_wasm_directory().join(format!("{package}.wat"))
+    }
+
+    /// The directory packages are compiled into for the commands that only
+    /// read the type checked modules of the project, such as `gleam lint`.
+    pub fn build_analysis_directory(&self) -> Utf8PathBuf {
+        self.build_directory().join("analysis")
+    }
+
     pub fn build_directory_for_target(&self, mode: Mode, target: Target) -> Utf8PathBuf {
         self.build_directory_for_mode(mode).join(target.to_string())
     }
@@ -120,12 +231,49 @@ impl ProjectPaths {
         self.build_directory_for_target(mode, target)
             .join("gleam_version")
     }
+
+    /// The name of the module at the given absolute path, if it is a Gleam
+    /// file in the `src` or `test` directory of the project. The path does not
+    /// have to exist.
+    pub fn module_name(&self, path: &Utf8Path) -> Option<String> {
+        let path = normalise(path);
+        let relative = [self.src_directory(), self.test_directory()]
+            .iter()
+            .find_map(|directory| path.strip_prefix(directory).ok())?;
+        let name = relative.as_str().strip_suffix(".gleam")?;
+        Some(name.replace('\\', "/"))
+    }
+}
+
+/// Removes `.` and `..` components from the path without accessing the file
+/// system, as the path may not exist yet.
+pub fn normalise(path: &Utf8Path) -> Utf8PathBuf {
+    let mut normalised = Utf8PathBuf::new();
+    for component in path.components() {
+        match component {
+            Utf8Component::CurDir => (),
+            Utf8Component::ParentDir => {
+                let _ = normalised.pop();
+            }
+            component => normalised.push(component),
+        }
+    }
+    normalised
 }
 
 pub fn global_package_cache_package_tarball(package_name: &str, version: &str) -> Utf8PathBuf {
     global_packages_cache().join(format!("{package_name}-{version}.tar"))
 }
 
+/// Where the part of a package tarball downloaded so far is kept, so that an
+/// interrupted download can be resumed.
+pub fn global_package_cache_package_partial_tarball(
+    package_name: &str,
+    version: &str,
+) -> Utf8PathBuf {
+    global_packages_cache().join(format!("{package_name}-{version}.tar.partial"))
+}
+
 fn global_packages_cache() -> Utf8PathBuf {
     default_global_gleam_cache()
         .join("hex")
@@ -163,4 +311,72 @@ fn paths() {
 
     assert!(global_package_cache_package_tarball("elli", "1.0.0")
         .ends_with("hex/hexpm/packages/elli-1.0.0.tar"));
+
+    assert!(
+        global_package_cache_package_partial_tarball("gleam_stdlib", "0.17.1")
+            .ends_with("hex/hexpm/packages/gleam_stdlib-0.17.1.tar.partial")
+    );
+}
+
+#[test]
+fn build_packages_package_source() {
+    let paths = ProjectPaths::new("/app".into());
+    let package = ManifestPackage {
+        name: "gleam_stdlib".into(),
+        source: ManifestPackageSource::Hex {
+            outer_checksum: crate::manifest::Base16Checksum(vec![
+                0xba, 0x78, 0x16, 0xbf, 0x8f, 0x01, 0xcf, 0xea, 0x41, 0x41,
+            ]),
+            organization: None,
+        },
+        ..Default::default()
+    };
+    assert_eq!(
+        paths.build_packages_package_source(&package),
+        Utf8PathBuf::from("/app/build/packages/gleam_stdlib-ba7816bf8f01cfea")
+    );
+}
+
+#[test]
+fn compiling_into_another_directory() {
+    let paths = ProjectPaths::new("/app".into()).compiling_into("/app/build/wasm".into());
+    assert_eq!(
+        paths.build_directory_for_package(Mode::Prod, Target::Erlang, "app"),
+        Utf8PathBuf::from("/app/build/wasm/prod/erlang/app")
+    );
+    assert_eq!(
+        paths.build_packages_package_config("gleam_stdlib"),
+        Utf8PathBuf::from("/app/build/packages/gleam_stdlib/gleam.toml")
+    );
+}
+
+#[test]
+fn module_name_in_src() {
+    let paths = ProjectPaths::new("/project".into());
+    assert_eq!(
+        paths.module_name(Utf8Path::new("/project/src/./one/../two/three.gleam")),
+        Some("two/three".into())
+    );
+}
+
+#[test]
+fn module_name_in_test() {
+    let paths = ProjectPaths::new("/project".into());
+    assert_eq!(
+        paths.module_name(Utf8Path::new("/project/test/one_test.gleam")),
+        Some("one_test".into())
+    );
+}
+
+#[test]
+fn module_name_outside_project() {
+    let paths = ProjectPaths::new("/project".into());
+    assert_eq!(
+        paths.module_name(Utf8Path::new("/project/build/one.gleam")),
+        None
+    );
+    assert_eq!(
+        paths.module_name(Utf8Path::new("/project/src/one.erl")),
+        None
+    );
 }
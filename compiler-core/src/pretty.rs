@@ -11,6 +11,17 @@
 //!
 //! - `ForcedBreak` from Elixir.
 //! - `FlexBreak` from Elixir.
+//!
+//! ## Width
+//!
+//! The width of text is the number of columns it takes up in a terminal or
+//! editor, rather than its length in bytes, so lines with wide characters
+//! such as emoji or CJK characters are not made too long, and lines with
+//! multi-byte characters are not broken early.
+//!
+//! Text can span multiple lines, such as multi-line string literals. Only its
+//! first line has to fit on the current line, and the text that follows it
+//! starts where its last line ends.
 #![allow(clippy::wrong_self_convention)]
 
 #[cfg(test)]
@@ -18,8 +29,9 @@ mod tests;
 
 use ecow::EcoString;
 use itertools::Itertools;
+use unicode_width::UnicodeWidthChar;
 
-use crate::{io::Utf8Writer, Result};
+use crate::{intern::Symbol, io::Utf8Writer, Result};
 
 #[macro_export]
 macro_rules! docvec {
@@ -63,6 +75,18 @@ impl<'a> Documentable<'a> for &EcoString {
     }
 }
 
+impl<'a> Documentable<'a> for Symbol {
+    fn to_doc(self) -> Document<'a> {
+        Document::Str(self.as_str())
+    }
+}
+
+impl<'a> Documentable<'a> for &Symbol {
+    fn to_doc(self) -> Document<'a> {
+        Document::Str(self.as_str())
+    }
+}
+
 impl<'a> Documentable<'a> for isize {
     fn to_doc(self) -> Document<'a> {
         Document::String(format!("{self}"))
@@ -140,11 +164,50 @@ pub fn join<'a>(
     concat(Itertools::intersperse(docs.into_iter(), separator))
 }
 
+/// The number of columns text takes up when printed. Control characters are
+/// counted as one column each, as they are in bytes.
+fn text_width(text: &str) -> isize {
+    text.chars()
+        .map(|char| char.width().unwrap_or(1) as isize)
+        .sum()
+}
+
+/// Whether text fits on the current line. Only the first line of multi-line
+/// text has to fit, as the rest starts on new lines.
+fn text_fits(limit: isize, current_width: isize, text: &str) -> Result<isize, bool> {
+    match text.split_once('\n') {
+        Some((first_line, _)) => Err(current_width + text_width(first_line) <= limit),
+        None => Ok(current_width + text_width(text)),
+    }
+}
+
+/// The number of line breaks in a piece of text.
+fn lines_in(text: &str) -> u32 {
+    text.matches('\n').count() as u32
+}
+
+/// The width of the current line after text is printed.
+fn width_after_text(width: isize, text: &str) -> isize {
+    match text.rsplit_once('\n') {
+        Some((_, last_line)) => text_width(last_line),
+        None => width + text_width(text),
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Document<'a> {
     /// A mandatory linebreak
     Line(usize),
 
+    /// A mandatory linebreak, followed by as many empty lines as are needed
+    /// for the next line to have the given number. Lines are only numbered
+    /// after a `LineNumber`, before which this is a single linebreak.
+    LineUntil(u32),
+
+    /// Gives the next line the given number, counting on from which
+    /// `LineUntil` adds empty lines. Nothing is rendered.
+    LineNumber(u32),
+
     /// Forces the breaks of the wrapped document to be considered as not
     /// fitting on a single line. Used in combination with a `Group` it can be
     /// used to force its `Break`s to always break.
@@ -286,7 +349,9 @@ fn fits(
             // document has a bit that fits in the current line; if it didn't
             // fit (that is, it exceeded the maximum allowed width) the loop
             // would have been broken by one of the earlier checks.
-            Document::Line(_) => return true,
+            Document::Line(_) | Document::LineUntil(_) => return true,
+
+            Document::LineNumber(_) => (),
 
             // If the nesting level is increased we go on checking the wrapped
             // document and increase its indentation level based on the nesting
@@ -317,9 +382,20 @@ fn fits(
 
             // When we run into a string we increase the current_width; looping
             // back we will check if we've exceeded the maximum allowed width.
-            Document::Str(s) => current_width += s.len() as isize,
-            Document::String(s) => current_width += s.len() as isize,
-            Document::EcoString(s) => current_width += s.len() as isize,
+            // If the string spans multiple lines we're done, as with a `Line`,
+            // once we know whether its first line fits.
+            Document::Str(s) => match text_fits(limit, current_width, s) {
+                Ok(width) => current_width = width,
+                Err(fits) => return fits,
+            },
+            Document::String(s) => match text_fits(limit, current_width, s) {
+                Ok(width) => current_width = width,
+                Err(fits) => return fits,
+            },
+            Document::EcoString(s) => match text_fits(limit, current_width, s) {
+                Ok(width) => current_width = width,
+                Err(fits) => return fits,
+            },
 
             // If we get to a break we need to first see if it has to be
             // rendered as its unbroken or broken string, depending on the mode.
@@ -335,7 +411,7 @@ fn fits(
                 // If the break is not broken then it will be rendered inline as
                 // its unbroken string, so we treat it exactly as if it were a
                 // normal string.
-                Mode::Unbroken | Mode::ForcedUnbroken => current_width += unbroken.len() as isize,
+                Mode::Unbroken | Mode::ForcedUnbroken => current_width += text_width(unbroken),
             },
 
             // The `NextBreakFits` can alter the current mode to `ForcedBroken`
@@ -386,6 +462,9 @@ fn format(
     mut width: isize,
     mut docs: im::Vector<(isize, Mode, &Document<'_>)>,
 ) -> Result<()> {
+    // The number of the line being written, once a `LineNumber` has been seen.
+    let mut line_number: Option<u32> = None;
+
     // As long as there are documents to print we'll take each one by one and
     // output the corresponding string to the given writer.
     //
@@ -406,12 +485,30 @@ fn format(
                 for _ in 0..*i {
                     writer.str_write("\n")?;
                 }
+                line_number = line_number.map(|number| number + *i as u32);
+                for _ in 0..indent {
+                    writer.str_write(" ")?;
+                }
+                width = indent;
+            }
+
+            // Empty lines are added until the next line has the wanted
+            // number, without indenting them.
+            Document::LineUntil(wanted) => {
+                writer.str_write("\n")?;
+                line_number = line_number.map(|number| number + 1);
+                while let Some(number) = line_number.filter(|number| number < wanted) {
+                    writer.str_write("\n")?;
+                    line_number = Some(number + 1);
+                }
                 for _ in 0..indent {
                     writer.str_write(" ")?;
                 }
                 width = indent;
             }
 
+            Document::LineNumber(number) => line_number = Some(number.saturating_sub(1)),
+
             // Flex breaks are NOT conditional to the mode: if the mode is
             // already `Unbroken`, then the break is left unbroken (like strict
             // breaks); any other mode is ignored.
@@ -423,7 +520,7 @@ fn format(
                 unbroken,
                 kind: BreakKind::Flex,
             } => {
-                let unbroken_width = width + unbroken.len() as isize;
+                let unbroken_width = width + text_width(unbroken);
                 // Every time we need to check again if the remaining piece can
                 // fit. If it does, the flexible break is not broken.
                 if mode == Mode::Unbroken || fits(limit, unbroken_width, docs.clone()) {
@@ -432,6 +529,7 @@ fn format(
                 } else {
                     writer.str_write(broken)?;
                     writer.str_write("\n")?;
+                    line_number = line_number.map(|number| number + 1);
                     for _ in 0..indent {
                         writer.str_write(" ")?;
                     }
@@ -457,6 +555,7 @@ fn format(
                 Mode::Broken | Mode::ForcedBroken => {
                     writer.str_write(broken)?;
                     writer.str_write("\n")?;
+                    line_number = line_number.map(|number| number + 1);
                     for _ in 0..indent {
                         writer.str_write(" ")?;
                     }
@@ -467,24 +566,27 @@ fn format(
                 // also updating the width of the current line.
                 Mode::Unbroken | Mode::ForcedUnbroken => {
                     writer.str_write(unbroken)?;
-                    width += unbroken.len() as isize
+                    width += text_width(unbroken)
                 }
             },
 
             // Strings are printed as they are and the current width is
             // increased accordingly.
             Document::String(s) => {
-                width += s.len() as isize;
+                width = width_after_text(width, s);
+                line_number = line_number.map(|number| number + lines_in(s));
                 writer.str_write(s)?;
             }
 
             Document::EcoString(s) => {
-                width += s.len() as isize;
+                width = width_after_text(width, s);
+                line_number = line_number.map(|number| number + lines_in(s));
                 writer.str_write(s)?;
             }
 
             Document::Str(s) => {
-                width += s.len() as isize;
+                width = width_after_text(width, s);
+                line_number = line_number.map(|number| number + lines_in(s));
                 writer.str_write(s)?;
             }
 
@@ -563,6 +665,14 @@ pub fn lines<'a>(i: usize) -> Document<'a> {
     Document::Line(i)
 }
 
+pub fn line_until<'a>(line: u32) -> Document<'a> {
+    Document::LineUntil(line)
+}
+
+pub fn number_next_line<'a>(line: u32) -> Document<'a> {
+    Document::LineNumber(line)
+}
+
 pub fn break_<'a>(broken: &'a str, unbroken: &'a str) -> Document<'a> {
     Document::Break {
         broken,
@@ -647,6 +757,8 @@ impl<'a> Document<'a> {
         use Document::*;
         match self {
             Line(n) => *n == 0,
+            LineUntil(_) => false,
+            LineNumber(_) => true,
             EcoString(s) => s.is_empty(),
             String(s) => s.is_empty(),
             Str(s) => s.is_empty(),
@@ -315,3 +315,144 @@ fn set_nesting() {
     let doc = Vec(vec!["foo".to_doc(), break_("", " "), "bar".to_doc()]).group();
     assert_eq!("foo\nbar", doc.set_nesting(0).nest(2).to_pretty_string(1));
 }
+
+#[test]
+fn wide_characters_width_test() {
+    // Each of these characters takes up two columns, so the group doesn't
+    // fit even though it is only 6 characters long.
+    let doc = concat(["日本語".to_doc(), break_("", " "), "日本語".to_doc()]).group();
+    assert_eq!("日本語\n日本語", doc.clone().to_pretty_string(12));
+    assert_eq!("日本語 日本語", doc.to_pretty_string(13));
+
+    // Multi-byte characters that take up a single column don't cause lines to
+    // be broken early.
+    let doc = concat(["ééééé".to_doc(), break_("", " "), "üüüüü".to_doc()]).group();
+    assert_eq!("ééééé üüüüü", doc.to_pretty_string(11));
+
+    let doc = concat(["🚀".to_doc(), break_("", " "), "🚀".to_doc()]).group();
+    assert_eq!("🚀\n🚀", doc.clone().to_pretty_string(4));
+    assert_eq!("🚀 🚀", doc.to_pretty_string(5));
+}
+
+#[test]
+fn multi_line_text_width_test() {
+    // Only the first line of multi-line text has to fit on the current line.
+    let doc = concat([
+        "x =".to_doc(),
+        break_("", " "),
+        "\"a\nvery long second line\"".to_doc(),
+    ])
+    .group();
+    assert_eq!("x = \"a\nvery long second line\"", doc.to_pretty_string(10));
+
+    // The text following multi-line text starts where its last line ends.
+    let doc = concat([
+        "\"a very long first line\nb\"".to_doc(),
+        concat([break_("", " "), "<>".to_doc(), " \"c\"".to_doc()]).group(),
+    ]);
+    assert_eq!(
+        "\"a very long first line\nb\" <> \"c\"",
+        doc.to_pretty_string(10)
+    );
+}
+
+#[test]
+fn line_until_test() {
+    // Without numbered lines it is a single line break
+    let doc = concat(["a".to_doc(), line_until(5), "b".to_doc()]);
+    assert_eq!("a\nb", doc.to_pretty_string(80));
+
+    // Empty lines are added until the line has the given number
+    let doc = concat([
+        "a".to_doc(),
+        number_next_line(10),
+        line(),
+        "b".to_doc(),
+        concat([line_until(13), "c".to_doc()]).nest(2),
+    ]);
+    assert_eq!("a\nb\n\n\n  c", doc.to_pretty_string(80));
+
+    // Lines in text are counted, and a line that is already past the given
+    // number gets a single line break
+    let doc = concat([
+        number_next_line(1),
+        "\"a\nb\"".to_doc(),
+        line_until(2),
+        "c".to_doc(),
+    ]);
+    assert_eq!("\"a\nb\"\nc", doc.to_pretty_string(80));
+}
+
+#[test]
+fn pipeline_layout_test() {
+    let pipeline = |steps: &[&'static str]| {
+        concat(steps.iter().enumerate().map(|(i, step)| {
+            if i == 0 {
+                step.to_doc()
+            } else {
+                line().append("|> ").append(*step)
+            }
+        }))
+        .force_break()
+        .group()
+    };
+
+    assert_eq!(
+        "list\n|> map(f)\n|> filter(g)",
+        pipeline(&["list", "map(f)", "filter(g)"]).to_pretty_string(80)
+    );
+
+    // A pipeline nested in a group that fits is still broken
+    let doc = concat([
+        "let x =".to_doc(),
+        line().append(pipeline(&["list", "map(f)"])).nest(2),
+    ])
+    .group();
+    assert_eq!("let x =\n  list\n  |> map(f)", doc.to_pretty_string(80));
+}
+
+#[test]
+fn binary_operator_layout_test() {
+    let operands = ["first_value", "second_value", "third_value"];
+    let doc = join(
+        operands.iter().map(|operand| operand.to_doc()),
+        break_("", " ").append("+ "),
+    )
+    .nest_if_broken(2)
+    .group();
+
+    assert_eq!(
+        "first_value + second_value + third_value",
+        doc.clone().to_pretty_string(80)
+    );
+    assert_eq!(
+        "first_value\n  + second_value\n  + third_value",
+        doc.to_pretty_string(20)
+    );
+}
+
+#[test]
+fn string_concatenation_layout_test() {
+    let strings = ["\"Hello, \"", "\"wonderful \"", "\"world!\""];
+    let doc = join(
+        strings.iter().map(|string| string.to_doc()),
+        flex_break("", " ").append("<> "),
+    )
+    .nest(2)
+    .group();
+
+    assert_eq!(
+        "\"Hello, \" <> \"wonderful \" <> \"world!\"",
+        doc.clone().to_pretty_string(80)
+    );
+    // Flexible breaks are only broken when the next piece doesn't fit, so as
+    // many strings as fit are kept on each line.
+    assert_eq!(
+        "\"Hello, \" <> \"wonderful \"\n  <> \"world!\"",
+        doc.clone().to_pretty_string(28)
+    );
+    assert_eq!(
+        "\"Hello, \"\n  <> \"wonderful \"\n  <> \"world!\"",
+        doc.to_pretty_string(12)
+    );
+}
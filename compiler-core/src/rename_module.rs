@@ -0,0 +1,125 @@
+//! Rewrites the imports of a module that has been renamed, as done by
+//! `gleam mv`.
+//!
+//! Only the module name in each import is changed, so any unqualified imports
+//! and aliases are kept. When an import has no alias and the last segment of
+//! the module name changes, an alias of the old last segment is added if the
+//! module is referred to by that name, so qualified references to it keep
+//! working, or if the new name would clash with another import.
+
+#[cfg(test)]
+mod tests;
+
+use camino::Utf8Path;
+use ecow::EcoString;
+use itertools::Itertools;
+
+use crate::{
+    arena::Arena,
+    ast::{Definition, SrcSpan},
+    parse::{lexer, token::Token},
+    Error, Result,
+};
+
+/// Returns the source with imports of the `from` module changed to import the
+/// `to` module instead.
+pub fn rename_imports(src: &str, path: &Utf8Path, from: &str, to: &str) -> Result<String> {
+    let parsed = crate::parse::parse_module(src).map_err(|error| Error::Parse {
+        path: path.to_path_buf(),
+        src: src.into(),
+        error,
+    })?;
+
+    let old_name = last_segment(from);
+    let new_name = last_segment(to);
+    let imports = parsed
+        .module
+        .definitions
+        .iter()
+        .filter_map(|definition| match &definition.definition {
+            Definition::Import(import) => Some(import),
+            _ => None,
+        })
+        .collect_vec();
+    let import_locations = imports.iter().map(|import| import.location).collect_vec();
+    let mut edits = vec![];
+
+    for import in &imports {
+        if import.module != from {
+            continue;
+        }
+        let Some(location) = module_name_location(src, import.location) else {
+            continue;
+        };
+        edits.push((location, EcoString::from(to)));
+
+        // The alias is also needed if the new name would clash with the name
+        // of another import.
+        let clashes = imports
+            .iter()
+            .any(|other| other.module != from && other.used_name().as_deref() == Some(new_name));
+        if import.as_name.is_none()
+            && old_name != new_name
+            && (clashes || is_referenced(src, &import_locations, old_name))
+        {
+            let end = import.location.end;
+            let alias = EcoString::from(format!(" as {old_name}"));
+            edits.push((SrcSpan { start: end, end }, alias));
+        }
+    }
+
+    let mut output = src.to_string();
+    for (location, text) in edits
+        .into_iter()
+        .sorted_by_key(|(location, _)| location.start)
+        .rev()
+    {
+        output.replace_range(location.start as usize..location.end as usize, &text);
+    }
+    Ok(output)
+}
+
+fn last_segment(module: &str) -> &str {
+    module.rsplit('/').next().unwrap_or(module)
+}
+
+/// The location of the module name of the import at the given location,
+/// from the start of its first segment to the end of its last.
+fn module_name_location(src: &str, import: SrcSpan) -> Option<SrcSpan> {
+    let text = src.get(import.start as usize..import.end as usize)?;
+    let arena = Arena::new();
+    let mut location: Option<SrcSpan> = None;
+
+    for token in lexer::make_tokenizer(text, &arena).skip(1) {
+        let (start, token, end) = token.ok()?;
+        match token {
+            Token::Name { .. } => {
+                let start = location.map_or(start, |location| location.start);
+                location = Some(SrcSpan { start, end });
+            }
+            Token::Slash | Token::CommentNormal | Token::NewLine => (),
+            _ => break,
+        }
+    }
+
+    location.map(|location| SrcSpan {
+        start: import.start + location.start,
+        end: import.start + location.end,
+    })
+}
+
+/// Whether the name is followed by a `.` anywhere in the source outside of
+/// its imports. This also finds accesses to fields of variables with the same
+/// name, in which case an alias that is not needed may be added.
+fn is_referenced(src: &str, imports: &[SrcSpan], name: &str) -> bool {
+    let arena = Arena::new();
+    let referenced = lexer::make_tokenizer(src, &arena)
+        .map_while(|token| token.ok())
+        .filter(|(start, _, _)| !imports.iter().any(|import| import.contains(*start)))
+        .tuple_windows()
+        .any(|((_, first, _), (_, second, _))| match (first, second) {
+            (Token::Name { name: first }, Token::Dot) => first == name,
+            _ => false,
+        });
+    referenced
+}
@@ -0,0 +1,148 @@
+use camino::Utf8Path;
+
+use super::rename_imports;
+
+macro_rules! assert_renamed {
+    ($from:expr, $to:expr, $src:expr $(,)?) => {{
+        let output = rename_imports($src, Utf8Path::new("src/my/module.gleam"), $from, $to)
+            .expect("should rename");
+        insta::assert_snapshot!(insta::internals::AutoName, output, $src);
+    }};
+}
+
+macro_rules! assert_unchanged {
+    ($from:expr, $to:expr, $src:expr $(,)?) => {{
+        let output = rename_imports($src, Utf8Path::new("src/my/module.gleam"), $from, $to)
+            .expect("should rename");
+        assert_eq!(output, $src);
+    }};
+}
+
+#[test]
+fn import_is_renamed() {
+    assert_renamed!(
+        "wibble/wobble",
+        "wibble/wobble2",
+        r#"import wibble/wobble
+
+pub fn main() {
+  wobble.main()
+}
+"#
+    );
+}
+
+#[test]
+fn import_with_unqualified_values_is_renamed() {
+    assert_renamed!(
+        "wibble/wobble",
+        "wibble/wubble",
+        r#"import wibble/wobble.{type Wobble, Wobble, wobble}
+
+pub fn main() -> Wobble {
+  wobble(Wobble)
+}
+"#
+    );
+}
+
+#[test]
+fn import_with_alias_is_renamed() {
+    assert_renamed!(
+        "wibble/wobble",
+        "wubble",
+        r#"import wibble/wobble as w
+
+pub fn main() {
+  w.main()
+}
+"#
+    );
+}
+
+#[test]
+fn alias_is_added_when_module_is_referenced() {
+    assert_renamed!(
+        "wibble/wobble",
+        "wibble/wubble",
+        r#"import wibble/wobble.{type Wobble}
+
+pub fn main() -> Wobble {
+  wobble.new()
+}
+"#
+    );
+}
+
+#[test]
+fn alias_is_not_added_when_module_is_not_referenced() {
+    assert_renamed!(
+        "wibble/wobble",
+        "wubble",
+        r#"import wibble/wobble.{wobble}
+import other.{wobble as other_wobble}
+
+pub fn main() {
+  wobble(other_wobble)
+}
+"#
+    );
+}
+
+#[test]
+fn alias_is_added_when_new_name_would_clash() {
+    assert_renamed!(
+        "wibble/wobble",
+        "wibble/list",
+        r#"import gleam/list
+import wibble/wobble.{wobble}
+
+pub fn main() {
+  list.map([], wobble)
+}
+"#
+    );
+}
+
+#[test]
+fn import_split_over_lines_is_renamed() {
+    assert_renamed!(
+        "wibble/wobble",
+        "wubble/wobble",
+        r#"import wibble/wobble.{
+  wobble,
+}
+
+pub fn main() {
+  wobble.wobble(wobble)
+}
+"#
+    );
+}
+
+#[test]
+fn other_imports_are_unchanged() {
+    assert_unchanged!(
+        "wibble/wobble",
+        "wubble/wobble",
+        r#"import wibble
+import wibble/wobble/wubble
+import wobble
+
+pub fn main() {
+  wobble.main()
+}
+"#
+    );
+}
+
+#[test]
+fn syntax_error() {
+    let result = rename_imports(
+        "import wibble/wobble\npub fn main( {",
+        Utf8Path::new("src/my/module.gleam"),
+        "wibble/wobble",
+        "wubble",
+    );
+    assert!(result.is_err());
+}
@@ -13,38 +13,139 @@ use serde::Deserialize;
 #[derive(Deserialize, Debug, PartialEq, Eq, Clone)]
 #[serde(untagged, remote = "Self")]
 pub enum Requirement {
-    Hex { version: Range },
-    Path { path: Utf8PathBuf },
-    Git { git: EcoString },
+    Hex {
+        version: Range,
+        /// The Hex organisation the package is privately published to, if it
+        /// is not a public package.
+        #[serde(default)]
+        organization: Option<EcoString>,
+        /// The features of the package to enable, in addition to its default
+        /// features.
+        #[serde(default)]
+        features: Vec<EcoString>,
+    },
+    Path {
+        path: Utf8PathBuf,
+        /// The features of the package to enable, in addition to its default
+        /// features.
+        #[serde(default)]
+        features: Vec<EcoString>,
+    },
+    Git {
+        git: EcoString,
+        /// The branch, tag, or commit to use, the default branch if not set.
+        #[serde(default, rename = "ref")]
+        reference: Option<EcoString>,
+        /// The features of the package to enable, in addition to its default
+        /// features.
+        #[serde(default)]
+        features: Vec<EcoString>,
+    },
 }
 
 impl Requirement {
     pub fn hex(range: &str) -> Requirement {
         Requirement::Hex {
             version: Range::new(range.to_string()),
+            organization: None,
+            features: vec![],
+        }
+    }
+
+    pub fn hex_organization(range: &str, organization: &str) -> Requirement {
+        Requirement::Hex {
+            version: Range::new(range.to_string()),
+            organization: Some(organization.into()),
+            features: vec![],
         }
     }
 
     pub fn path(path: &str) -> Requirement {
-        Requirement::Path { path: path.into() }
+        Requirement::Path {
+            path: path.into(),
+            features: vec![],
+        }
     }
 
     pub fn git(url: &str) -> Requirement {
-        Requirement::Git { git: url.into() }
+        Requirement::Git {
+            git: url.into(),
+            reference: None,
+            features: vec![],
+        }
     }
 
-    pub fn to_toml(&self, root_path: &Utf8Path) -> String {
-        match self {
-            Requirement::Hex { version: range } => {
-                format!(r#"{{ version = "{}" }}"#, range)
+    pub fn git_ref(url: &str, reference: &str) -> Requirement {
+        Requirement::Git {
+            git: url.into(),
+            reference: Some(reference.into()),
+            features: vec![],
+        }
+    }
+
+    /// The same requirement, enabling the given features of the package.
+    pub fn with_features(mut self, enabled: &[&str]) -> Requirement {
+        match &mut self {
+            Requirement::Hex { features, .. }
+            | Requirement::Path { features, .. }
+            | Requirement::Git { features, .. } => {
+                *features = enabled.iter().map(|feature| (*feature).into()).collect()
             }
-            Requirement::Path { path } => {
+        }
+        self
+    }
+
+    /// The features of the package to enable, in addition to its default
+    /// features.
+    pub fn features(&self) -> &[EcoString] {
+        match self {
+            Requirement::Hex { features, .. }
+            | Requirement::Path { features, .. }
+            | Requirement::Git { features, .. } => features,
+        }
+    }
+
+    pub fn to_toml(&self, root_path: &Utf8Path) -> String {
+        let source = match self {
+            Requirement::Hex {
+                version: range,
+                organization: None,
+                ..
+            } => format!(r#"version = "{}""#, range),
+            Requirement::Hex {
+                version: range,
+                organization: Some(organization),
+                ..
+            } => format!(
+                r#"version = "{}", organization = "{}""#,
+                range, organization
+            ),
+            Requirement::Path { path, .. } => {
                 format!(
-                    r#"{{ path = "{}" }}"#,
+                    r#"path = "{}""#,
                     make_relative(root_path, path).as_str().replace('\\', "/")
                 )
             }
-            Requirement::Git { git: url } => format!(r#"{{ git = "{}" }}"#, url),
+            Requirement::Git {
+                git: url,
+                reference: None,
+                ..
+            } => format!(r#"git = "{}""#, url),
+            Requirement::Git {
+                git: url,
+                reference: Some(reference),
+                ..
+            } => format!(r#"git = "{}", ref = "{}""#, url, reference),
+        };
+        match self.features() {
+            [] => format!("{{ {source} }}"),
+            features => {
+                let features = features.iter().map(|feature| format!(r#""{feature}""#));
+                format!(
+                    "{{ {source}, features = [{}] }}",
+                    features.collect::<Vec<_>>().join(", ")
+                )
+            }
         }
     }
 }
@@ -56,11 +157,47 @@ impl Serialize for Requirement {
     where
         S: Serializer,
     {
-        let mut map = serializer.serialize_map(Some(1))?;
+        let entries = match self {
+            Requirement::Git {
+                reference: Some(_), ..
+            }
+            | Requirement::Hex {
+                organization: Some(_),
+                ..
+            } => 2,
+            _ => 1,
+        };
+        let entries = if self.features().is_empty() {
+            entries
+        } else {
+            entries + 1
+        };
+        let mut map = serializer.serialize_map(Some(entries))?;
         match self {
-            Requirement::Hex { version: range } => map.serialize_entry("version", range)?,
-            Requirement::Path { path } => map.serialize_entry("path", path)?,
-            Requirement::Git { git: url } => map.serialize_entry("git", url)?,
+            Requirement::Hex {
+                version: range,
+                organization,
+                ..
+            } => {
+                map.serialize_entry("version", range)?;
+                if let Some(organization) = organization {
+                    map.serialize_entry("organization", organization)?;
+                }
+            }
+            Requirement::Path { path, .. } => map.serialize_entry("path", path)?,
+            Requirement::Git {
+                git: url,
+                reference,
+                ..
+            } => {
+                map.serialize_entry("git", url)?;
+                if let Some(reference) = reference {
+                    map.serialize_entry("ref", reference)?;
+                }
+            }
+        }
+        if !self.features().is_empty() {
+            map.serialize_entry("features", self.features())?;
         }
         map.end()
     }
@@ -99,10 +236,41 @@ impl<'de> Visitor<'de> for RequirementVisitor {
     where
         M: MapAccess<'de>,
     {
-        Requirement::deserialize(de::value::MapAccessDeserializer::new(visitor))
+        let requirement = Requirement::deserialize(de::value::MapAccessDeserializer::new(visitor))?;
+        if let Requirement::Git { git, reference, .. } = &requirement {
+            check_git_argument(git)?;
+            if let Some(reference) = reference {
+                check_git_argument(reference)?;
+            }
+        }
+        Ok(requirement)
+    }
+}
+
+/// Rejects a repository or reference that git would read as an option when
+/// it is passed on the command line. They come from the `gleam.toml` of
+/// dependencies, so are not trusted.
+fn check_git_argument<E: de::Error>(value: &str) -> Result<(), E> {
+    if value.starts_with('-') {
+        Err(E::custom(format!(
+            "git repositories and references must not start with `-`: {value}"
+        )))
+    } else {
+        Ok(())
     }
 }
 
+/// Deserialises a repository or commit of a git package, rejecting any that
+/// git would read as an option.
+pub(crate) fn deserialize_git_argument<'de, D>(deserializer: D) -> Result<EcoString, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let value = EcoString::deserialize(deserializer)?;
+    check_git_argument(&value)?;
+    Ok(value)
+}
+
 impl<'de> Deserialize<'de> for Requirement {
     fn deserialize<D>(deserializer: D) -> Result<Requirement, D::Error>
     where
@@ -125,6 +293,9 @@ mod tests {
             hex = { version = "~> 1.0.0" }
             local = { path = "/path/to/package" }
             github = { git = "https://github.com/gleam-lang/otp.git" }
+            tagged = { git = "https://github.com/gleam-lang/otp.git", ref = "v0.10.0" }
+            private = { version = "~> 1.0", organization = "acme" }
+            featured = { version = "~> 1.0", features = ["json", "xml"] }
         "#;
         let deps: HashMap<String, Requirement> = toml::from_str(toml).unwrap();
         assert_eq!(deps["short"], Requirement::hex("~> 0.5"));
@@ -134,5 +305,38 @@ mod tests {
             deps["github"],
             Requirement::git("https://github.com/gleam-lang/otp.git")
         );
+        assert_eq!(
+            deps.get("tagged"),
+            Some(&Requirement::git_ref(
+                "https://github.com/gleam-lang/otp.git",
+                "v0.10.0"
+            ))
+        );
+        assert_eq!(
+            deps.get("private"),
+            Some(&Requirement::hex_organization("~> 1.0", "acme"))
+        );
+        assert_eq!(
+            deps.get("featured"),
+            Some(&Requirement::hex("~> 1.0").with_features(&["json", "xml"]))
+        );
+    }
+
+    #[test]
+    fn git_arguments_starting_with_a_dash_are_rejected() {
+        let toml =
+            r#"wibble = { git = "https://example.com/wibble.git", ref = "--upload-pack=evil" }"#;
+        assert!(toml::from_str::<HashMap<String, Requirement>>(toml).is_err());
+        let toml = r#"wibble = { git = "--upload-pack=evil" }"#;
+        assert!(toml::from_str::<HashMap<String, Requirement>>(toml).is_err());
+    }
+
+    #[test]
+    fn requirement_with_features_to_toml() {
+        let requirement = Requirement::path("/app/wibble").with_features(&["json"]);
+        assert_eq!(
+            requirement.to_toml(Utf8Path::new("/app")),
+            r#"{ path = "wibble", features = ["json"] }"#
+        );
     }
 }
@@ -0,0 +1,198 @@
+//! Runtime configuration for Erlang applications, written as TOML files in the
+//! `config` directory of a project.
+//!
+//! Each top level table of a config file is the environment of an OTP
+//! application, so `[my_app] port = 8080` becomes the `port` key of the
+//! `my_app` application's environment. The files are translated to Erlang
+//! `sys.config` files when exporting an Erlang shipment, and loaded when the
+//! shipment is started. Values are translated as they are for the `env` of
+//! `gleam.toml`, with strings as binaries and tables as maps, so they suit
+//! Gleam applications rather than those expecting atoms.
+//!
+//! The keys of the project's own application in `config/config.toml` are also
+//! given accessor functions in a generated `<package>@@config` module, which
+//! return the value from the application environment or the value in the file
+//! if it has not been set.
+
+#[cfg(test)]
+mod tests;
+
+use std::collections::BTreeMap;
+
+use camino::{Utf8Path, Utf8PathBuf};
+use ecow::EcoString;
+use itertools::Itertools;
+
+use crate::{
+    codegen::{atom, erlang_term},
+    error::{FileIoAction, FileKind},
+    io::FileSystemReader,
+    Error, Result,
+};
+
+/// The name of the directory of config files in a project.
+pub const DIRECTORY_NAME: &str = "config";
+
+/// The name of the config file that is always loaded, and whose values are
+/// the defaults of the accessor module.
+pub const BASE_CONFIG_NAME: &str = "config";
+
+/// A config file, giving the environment of each OTP application it
+/// configures.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct RuntimeConfig {
+    pub path: Utf8PathBuf,
+    pub applications: BTreeMap<EcoString, BTreeMap<EcoString, toml::Value>>,
+}
+
+impl RuntimeConfig {
+    pub fn read<FS: FileSystemReader>(path: &Utf8Path, fs: &FS) -> Result<Self> {
+        let toml = fs.read(path)?;
+        Self::parse(path, &toml)
+    }
+
+    pub fn parse(path: &Utf8Path, toml: &str) -> Result<Self> {
+        let applications = toml::from_str(toml).map_err(|e| Error::FileIo {
+            action: FileIoAction::Parse,
+            kind: FileKind::File,
+            path: path.to_path_buf(),
+            err: Some(e.to_string()),
+        })?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            applications,
+        })
+    }
+
+    /// The config files in a config directory, in order of their names.
+    pub fn read_directory<FS: FileSystemReader>(
+        directory: &Utf8Path,
+        fs: &FS,
+    ) -> Result<Vec<Self>> {
+        if !fs.is_directory(directory) {
+            return Ok(vec![]);
+        }
+        fs.read_dir(directory)?
+            .into_iter()
+            .filter_map(|entry| Some(entry.ok()?.into_path()))
+            .filter(|path| path.extension() == Some("toml"))
+            .sorted()
+            .map(|path| Self::read(&path, fs))
+            .collect()
+    }
+
+    /// The name of the config, as given to `GLEAM_CONFIG` to load it.
+    pub fn name(&self) -> &str {
+        self.path.file_stem().unwrap_or_default()
+    }
+
+    /// Checks each application configured is one of the given applications.
+    pub fn check_applications(&self, applications: &[EcoString]) -> Result<()> {
+        match self
+            .applications
+            .keys()
+            .find(|name| !applications.contains(name))
+        {
+            None => Ok(()),
+            Some(name) => Err(Error::UnknownRuntimeConfigApplication {
+                path: self.path.clone(),
+                name: name.clone(),
+                options: applications.to_vec(),
+            }),
+        }
+    }
+
+    /// Renders the config as an Erlang `sys.config` file.
+    pub fn to_sys_config(&self) -> Result<String> {
+        let applications = self
+            .applications
+            .iter()
+            .map(|(application, env)| {
+                let env = env
+                    .iter()
+                    .map(|(key, value)| Ok(format!("    {{{}, {}}}", atom(key), term(key, value)?)))
+                    .collect::<Result<Vec<_>>>()?;
+                Ok(format!(
+                    "  {{{}, [\n{}\n  ]}}",
+                    atom(application),
+                    env.join(",\n")
+                ))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(format!(
+            "%% Generated by Gleam from {path}\n[\n{applications}\n].\n",
+            path = self.project_path(),
+            applications = applications.join(",\n"),
+        ))
+    }
+
+    /// Renders the Erlang module with an accessor function for each key of the
+    /// given application's environment.
+    pub fn accessor_module(&self, application: &str) -> Result<String> {
+        let module = format!("{application}@@config");
+        let env = self.applications.get(application);
+        let env = env.into_iter().flatten();
+
+        let mut exports = vec![];
+        let mut functions = vec![];
+        for (key, value) in env {
+            exports.push(format!("{}/0", atom(key)));
+            functions.push(format!(
+                "%% {key}() -> {type_}\n{name}() ->\n    application:get_env({app}, {name}, {value}).\n",
+                type_ = gleam_type(value),
+                name = atom(key),
+                app = atom(application),
+                value = term(key, value)?,
+            ));
+        }
+
+        Ok(format!(
+            "-module({quoted_module}).
+%% Generated by Gleam from {path}.
+%%
+%% Each function returns a key of the {application} application's
+%% environment, or the value in {path} if it has not been set,
+%% such as by the config files of an Erlang shipment. The Gleam type of each
+%% value is given above its function, and it can be called from Gleam with an
+%% external function:
+%%
+%%     @external(erlang, \"{module}\", \"key\")
+%%     pub fn key() -> Type
+
+-export([{exports}]).
+{functions}",
+            quoted_module = atom(&module),
+            path = self.project_path(),
+            exports = exports.join(", "),
+            functions = functions.iter().map(|f| format!("\n{f}")).join(""),
+        ))
+    }
+
+    /// The path of the config file within the project.
+    fn project_path(&self) -> String {
+        let name = self.path.file_name().unwrap_or_default();
+        format!("{DIRECTORY_NAME}/{name}")
+    }
+}
+
+fn term(key: &EcoString, value: &toml::Value) -> Result<String> {
+    erlang_term(value).ok_or_else(|| Error::InvalidErlangAppEnv { key: key.clone() })
+}
+
+/// The Gleam type a TOML value has once it is translated to Erlang.
+fn gleam_type(value: &toml::Value) -> String {
+    match value {
+        toml::Value::String(_) | toml::Value::Datetime(_) => "String".into(),
+        toml::Value::Integer(_) => "Int".into(),
+        toml::Value::Float(_) => "Float".into(),
+        toml::Value::Boolean(_) => "Bool".into(),
+        toml::Value::Array(values) => {
+            let types: Vec<_> = values.iter().map(gleam_type).unique().collect();
+            match types.as_slice() {
+                [type_] => format!("List({type_})"),
+                _ => "List(Dynamic)".into(),
+            }
+        }
+        toml::Value::Table(_) => "Dict(String, Dynamic)".into(),
+    }
+}
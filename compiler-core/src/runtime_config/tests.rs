@@ -0,0 +1,90 @@
+use camino::Utf8Path;
+
+use super::*;
+use crate::io::{memory::InMemoryFileSystem, FileSystemWriter};
+
+const CONFIG: &str = r#"
+[my_app]
+greeting = "Hello, Joe!"
+port = 8080
+hosts = ["localhost", "example.com"]
+limits = { requests = 100 }
+
+[wibble]
+verbose = true
+"#;
+
+fn config() -> RuntimeConfig {
+    RuntimeConfig::parse(Utf8Path::new("/app/config/config.toml"), CONFIG).expect("parse")
+}
+
+#[test]
+fn sys_config() {
+    insta::assert_snapshot!(config().to_sys_config().expect("sys.config"));
+}
+
+#[test]
+fn accessor_module() {
+    insta::assert_snapshot!(config().accessor_module("my_app").expect("module"));
+}
+
+#[test]
+fn accessor_module_without_application() {
+    insta::assert_snapshot!(config().accessor_module("other_app").expect("module"));
+}
+
+#[test]
+fn invalid_value() {
+    let config = RuntimeConfig::parse(Utf8Path::new("config/config.toml"), "[my_app]\nratio = inf")
+        .expect("parse");
+    assert_eq!(
+        config.to_sys_config(),
+        Err(Error::InvalidErlangAppEnv {
+            key: "ratio".into()
+        })
+    );
+}
+
+#[test]
+fn application_must_be_table() {
+    assert!(RuntimeConfig::parse(Utf8Path::new("config/config.toml"), "port = 1").is_err());
+}
+
+#[test]
+fn check_applications() {
+    let config = config();
+    assert_eq!(
+        config.check_applications(&["my_app".into(), "wibble".into()]),
+        Ok(())
+    );
+    assert_eq!(
+        config.check_applications(&["my_app".into()]),
+        Err(Error::UnknownRuntimeConfigApplication {
+            path: "/app/config/config.toml".into(),
+            name: "wibble".into(),
+            options: vec!["my_app".into()],
+        })
+    );
+}
+
+#[test]
+fn read_directory() {
+    let fs = InMemoryFileSystem::new();
+    for (path, content) in [
+        ("/app/config/prod.toml", "[my_app]\nport = 80"),
+        ("/app/config/config.toml", CONFIG),
+        ("/app/config/README.md", "Not a config"),
+    ] {
+        fs.write(Utf8Path::new(path), content).expect("write");
+    }
+    let configs = RuntimeConfig::read_directory(Utf8Path::new("/app/config"), &fs).expect("read");
+    let names: Vec<_> = configs.iter().map(RuntimeConfig::name).collect();
+    assert_eq!(names, vec!["config", "prod"]);
+}
+
+#[test]
+fn read_missing_directory() {
+    let fs = InMemoryFileSystem::new();
+    let configs = RuntimeConfig::read_directory(Utf8Path::new("/app/config"), &fs).expect("read");
+    assert!(configs.is_empty());
+}
@@ -0,0 +1,192 @@
+//! The test runner of `gleam test` when the tests are split into shards, run
+//! in parallel, or retried. The compiler finds the test functions of the
+//! project and selects those of the shard, and a runner written for each
+//! target runs them a number at a time, running failing tests again up to the
+//! number of retries, and gathers their results into a single report.
+//!
+//! Without these options `gleam test` runs the `main` function of the
+//! project's test module instead, which usually runs the tests with a test
+//! framework such as gleeunit.
+//!
+//! ## Property tests
+//!
+//! A property testing library reports the counterexample of a failing
+//! property by raising a Gleam error with the `property` variant, in the same
+//! way as `panic` and `let assert`. Besides the `message`, `module`,
+//! `function` and `line` of the property, the error has these fields, all of
+//! which are optional:
+//!
+//! - `counterexample`: the shrunk input the property fails for.
+//! - `original`: the input the property first failed for.
+//! - `shrinks`: the number of steps taken to shrink the input, an `Int`.
+//! - `seed`: the seed the inputs were generated with, an `Int`.
+//! - `failure`: the error the property failed with for the counterexample.
+//!
+//! The crash formatter shows the inputs as Gleam values, and the failure with
+//! the module and line it was raised at, in the report of the runner as well
+//! as when running the `main` function of the test module.
+
+pub mod changes;
+#[cfg(test)]
+mod tests;
+
+use std::{fmt::Write, str::FromStr};
+
+use ecow::EcoString;
+use itertools::Itertools;
+
+use crate::build::{Built, Origin, Target};
+
+/// The Erlang module that runs the tests, compiled with the entrypoint module
+/// of the root package.
+pub const ERLANG_MODULE: &str = include_str!("../templates/gleam@@test.erl");
+
+/// The JavaScript module that runs the tests, written next to the prelude.
+pub const JAVASCRIPT_MODULE: &str = include_str!("../templates/test_runner.mjs");
+
+/// A test function of the project.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Test {
+    pub module: EcoString,
+    pub function: EcoString,
+}
+
+/// One of a number of parts the tests are split into, so that each can be run
+/// on a different machine. It is written as `2/4` for the second of four.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Shard {
+    /// The number of the shard, starting from 1.
+    pub index: u32,
+    pub count: u32,
+}
+
+/// How the runner runs the tests, passed to it by the entrypoint.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Options {
+    /// The number of tests to run at once, or the default of the runner when
+    /// not given.
+    pub parallel: Option<usize>,
+    /// The number of times a failing test is run again before it fails. A
+    /// test that fails and then passes when run again is reported as flaky.
+    pub retries: u32,
+    /// Whether the run fails when any test is flaky.
+    pub fail_on_flaky: bool,
+}
+
+impl Options {
+    /// The options as an Erlang map, where a `parallel` of 0 runs as many
+    /// tests at once as there are schedulers.
+    pub fn to_erlang(&self) -> String {
+        format!(
+            "#{{parallel => {}, retries => {}, fail_on_flaky => {}}}",
+            self.parallel.unwrap_or(0),
+            self.retries,
+            self.fail_on_flaky
+        )
+    }
+
+    /// The options as a JavaScript object.
+    pub fn to_javascript(&self) -> String {
+        format!(
+            "{{ parallel: {}, retries: {}, failOnFlaky: {} }}",
+            self.parallel.unwrap_or(1),
+            self.retries,
+            self.fail_on_flaky
+        )
+    }
+}
+
+impl Shard {
+    /// Selects the tests of the shard. Tests are given to the shards in turn
+    /// so that the tests of each module are spread across all of them.
+    pub fn select(&self, tests: Vec<Test>) -> Vec<Test> {
+        tests
+            .into_iter()
+            .zip((0..self.count).cycle())
+            .filter(|(_, shard)| *shard + 1 == self.index)
+            .map(|(test, _)| test)
+            .collect()
+    }
+}
+
+impl FromStr for Shard {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("expected a shard such as `2/4` but got `{text}`");
+        let (index, count) = text.split_once('/').ok_or_else(invalid)?;
+        let index: u32 = index.trim().parse().map_err(|_| invalid())?;
+        let count: u32 = count.trim().parse().map_err(|_| invalid())?;
+        if index == 0 || index > count {
+            return Err(format!(
+                "the shard must be between 1 and the number of shards, but got `{text}`"
+            ));
+        }
+        Ok(Self { index, count })
+    }
+}
+
+/// Finds the test functions of the root package, which are the public
+/// functions with no arguments whose names end with `_test` in the modules of
+/// its `test` directory, ordered by module and name.
+///
+pub fn discover(built: &Built, target: Target) -> Vec<Test> {
+    let package = &built.root_package.config.name;
+    built
+        .module_interfaces()
+        .filter(|module| &module.package == package && module.origin == Origin::Test)
+        .flat_map(|module| {
+            module
+                .test_functions(target)
+                .into_iter()
+                .map(|function| Test {
+                    module: module.name.clone(),
+                    function,
+                })
+        })
+        .sorted()
+        .collect()
+}
+
+/// The tests to run, read by the Erlang runner with `file:consult`.
+///
+pub fn erlang_tests(tests: &[Test]) -> String {
+    let mut text = String::new();
+    for Test { module, function } in tests {
+        let module = module.replace("/", "@");
+        writeln!(text, "{{'{module}', '{function}'}}.").expect("write test");
+    }
+    text
+}
+
+/// The JavaScript module that imports the test modules and runs the tests
+/// with the runner.
+///
+pub fn javascript_entrypoint(tests: &[Test], options: &Options) -> String {
+    let modules = tests
+        .iter()
+        .map(|test| &test.module)
+        .unique()
+        .enumerate()
+        .map(|(index, module)| (module, index))
+        .collect_vec();
+    let mut text = String::from("import { run } from \"../test_runner.mjs\";\n");
+    for (module, index) in &modules {
+        writeln!(text, "import * as $module{index} from \"./{module}.mjs\";").expect("import");
+    }
+    text.push_str("\nrun([\n");
+    for Test { module, function } in tests {
+        let index = modules
+            .iter()
+            .find(|(name, _)| *name == module)
+            .map(|(_, index)| index)
+            .expect("test module imported");
+        writeln!(
+            text,
+            "  [\"{module}\", \"{function}\", $module{index}.{function}],"
+        )
+        .expect("write test");
+    }
+    writeln!(text, "], {});", options.to_javascript()).expect("write run");
+    text
+}
@@ -0,0 +1,182 @@
+//! Finding the tests impacted by changes to the project, for
+//! `gleam test --only-changed`.
+//!
+//! After each run in which every test passes a snapshot of the modules of the
+//! root package is saved, taken from the artefacts manifest written by the
+//! build. The modules that changed are those whose source or interface hash
+//! differs from the snapshot, or whose source files changed since a git ref,
+//! and the tests impacted are those of the test modules that import a changed
+//! module, directly or through other modules.
+
+use std::collections::{BTreeMap, HashSet};
+
+use camino::{Utf8Path, Utf8PathBuf};
+use ecow::EcoString;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::{FileIoAction, FileKind},
+    io::FileSystemReader,
+    paths::ProjectPaths,
+    Error, Result,
+};
+
+use super::Test;
+
+/// The modules of the root package the tests were run against.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Snapshot {
+    /// A hash of the `gleam.toml` and `manifest.toml` of the project, as any
+    /// change to the dependencies could change what the tests do.
+    dependencies_hash: String,
+    modules: BTreeMap<EcoString, ModuleSnapshot>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct ModuleSnapshot {
+    /// The path of the source file, relative to the root of the project.
+    source: Utf8PathBuf,
+    source_hash: String,
+    /// The interface of a module can change without its source changing,
+    /// such as when a type it infers comes from a dependency.
+    interface_hash: String,
+    imports: Vec<EcoString>,
+}
+
+/// The parts of the artefacts manifest written by the build that the
+/// snapshot is taken from.
+#[derive(Debug, Deserialize)]
+struct ArtefactsManifest {
+    modules: BTreeMap<EcoString, ModuleArtefacts>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ModuleArtefacts {
+    source: Utf8PathBuf,
+    interface_hash: String,
+    imports: Vec<EcoString>,
+}
+
+impl Snapshot {
+    /// Takes a snapshot of the project as it was last built.
+    ///
+    pub fn new(io: &impl FileSystemReader, paths: &ProjectPaths) -> Result<Self> {
+        let manifest_path = paths.build_artefacts_manifest();
+        let manifest: ArtefactsManifest =
+            serde_json::from_str(&io.read(&manifest_path)?).map_err(|error| Error::FileIo {
+                kind: FileKind::File,
+                action: FileIoAction::Parse,
+                path: manifest_path.clone(),
+                err: Some(error.to_string()),
+            })?;
+
+        let mut dependencies = io.read(&paths.root_config())?;
+        // A project without dependencies may not have a manifest.
+        if let Ok(manifest) = io.read(&paths.manifest()) {
+            dependencies.push_str(&manifest);
+        }
+
+        let mut modules = BTreeMap::new();
+        for (name, module) in manifest.modules {
+            let source = io.read(&paths.root().join(&module.source))?;
+            let snapshot = ModuleSnapshot {
+                source: module.source,
+                source_hash: hash(&source),
+                interface_hash: module.interface_hash,
+                imports: module.imports,
+            };
+            let _ = modules.insert(name, snapshot);
+        }
+
+        Ok(Self {
+            dependencies_hash: hash(&dependencies),
+            modules,
+        })
+    }
+
+    pub fn to_json(&self) -> String {
+        let mut json = serde_json::to_string_pretty(self).expect("test snapshot to json");
+        json.push('\n');
+        json
+    }
+
+    /// Reads a saved snapshot, returning `None` if it cannot be read, such as
+    /// when it was saved by a different version of Gleam.
+    ///
+    pub fn from_json(json: &str) -> Option<Self> {
+        serde_json::from_str(json).ok()
+    }
+
+    /// The modules that changed since the earlier snapshot: the new modules
+    /// and those whose source or interface changed. Every module has changed
+    /// if the dependencies have.
+    ///
+    pub fn changed_since(&self, earlier: &Snapshot) -> HashSet<EcoString> {
+        let all_changed = self.dependencies_hash != earlier.dependencies_hash;
+        self.modules
+            .iter()
+            .filter(|(name, module)| match earlier.modules.get(*name) {
+                _ if all_changed => true,
+                Some(earlier) => {
+                    earlier.source_hash != module.source_hash
+                        || earlier.interface_hash != module.interface_hash
+                }
+                None => true,
+            })
+            .map(|(name, _)| name.clone())
+            .collect()
+    }
+
+    /// The modules whose source files are among the changed files, given
+    /// relative to the root of the project. Every module has changed if the
+    /// `gleam.toml` or `manifest.toml` has.
+    ///
+    pub fn changed_files(&self, files: &[Utf8PathBuf]) -> HashSet<EcoString> {
+        let config_changed = files
+            .iter()
+            .any(|file| file == Utf8Path::new("gleam.toml") || file == "manifest.toml");
+        self.modules
+            .iter()
+            .filter(|(_, module)| config_changed || files.contains(&module.source))
+            .map(|(name, _)| name.clone())
+            .collect()
+    }
+
+    /// The modules impacted by the changed modules, which are the changed
+    /// modules and those that import one, directly or through other modules.
+    ///
+    pub fn impacted(&self, changed: &HashSet<EcoString>) -> HashSet<EcoString> {
+        let mut impacted = changed.clone();
+        loop {
+            let importers = self
+                .modules
+                .iter()
+                .filter(|(name, module)| {
+                    !impacted.contains(*name)
+                        && module
+                            .imports
+                            .iter()
+                            .any(|import| impacted.contains(import))
+                })
+                .map(|(name, _)| name.clone())
+                .collect::<Vec<_>>();
+            if importers.is_empty() {
+                return impacted;
+            }
+            impacted.extend(importers);
+        }
+    }
+}
+
+/// Selects the tests of the impacted modules.
+///
+pub fn select(tests: Vec<Test>, impacted: &HashSet<EcoString>) -> Vec<Test> {
+    tests
+        .into_iter()
+        .filter(|test| impacted.contains(&test.module))
+        .collect()
+}
+
+fn hash(text: &str) -> String {
+    format!("{:016x}", xxhash_rust::xxh3::xxh3_64(text.as_bytes()))
+}
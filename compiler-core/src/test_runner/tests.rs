@@ -0,0 +1,276 @@
+use std::collections::HashSet;
+
+use camino::Utf8PathBuf;
+use ecow::EcoString;
+
+use super::{
+    changes::{self, Snapshot},
+    erlang_tests, javascript_entrypoint, Options, Shard, Test,
+};
+use crate::{
+    build::Target,
+    io::{memory::InMemoryFileSystem, FileSystemWriter},
+    paths::ProjectPaths,
+    type_::tests::compile_module,
+};
+
+fn test(module: &str, function: &str) -> Test {
+    Test {
+        module: module.into(),
+        function: function.into(),
+    }
+}
+
+fn tests() -> Vec<Test> {
+    vec![
+        test("app_test", "one_test"),
+        test("app_test", "two_test"),
+        test("app/parser_test", "three_test"),
+        test("app/parser_test", "four_test"),
+        test("app/parser_test", "five_test"),
+    ]
+}
+
+#[test]
+fn test_functions() {
+    let src = r#"
+pub fn one_test() { Nil }
+pub fn two_test() { Nil }
+fn private_test() { Nil }
+pub fn helper() { Nil }
+pub fn with_argument_test(x: Int) { x }
+pub const constant_test = 1
+"#;
+    let module = compile_module("app_test", src, None, vec![]).expect("compile module");
+    assert_eq!(
+        module.type_info.test_functions(Target::Erlang),
+        vec!["one_test", "two_test"]
+    );
+}
+
+#[test]
+fn parse_shard() {
+    assert_eq!("2/4".parse(), Ok(Shard { index: 2, count: 4 }));
+    assert_eq!("1/1".parse(), Ok(Shard { index: 1, count: 1 }));
+    assert!("0/4".parse::<Shard>().is_err());
+    assert!("5/4".parse::<Shard>().is_err());
+    assert!("2".parse::<Shard>().is_err());
+    assert!("a/b".parse::<Shard>().is_err());
+}
+
+#[test]
+fn shards_select_tests_in_turn() {
+    let first = Shard { index: 1, count: 2 }.select(tests());
+    let second = Shard { index: 2, count: 2 }.select(tests());
+    assert_eq!(
+        first,
+        vec![
+            test("app_test", "one_test"),
+            test("app/parser_test", "three_test"),
+            test("app/parser_test", "five_test"),
+        ]
+    );
+    assert_eq!(
+        second,
+        vec![
+            test("app_test", "two_test"),
+            test("app/parser_test", "four_test"),
+        ]
+    );
+}
+
+#[test]
+fn shards_can_be_empty() {
+    assert_eq!(Shard { index: 6, count: 6 }.select(tests()), vec![]);
+}
+
+#[test]
+fn erlang() {
+    insta::assert_snapshot!(erlang_tests(&tests()));
+}
+
+#[test]
+fn javascript() {
+    let options = Options {
+        parallel: Some(8),
+        retries: 0,
+        fail_on_flaky: false,
+    };
+    insta::assert_snapshot!(javascript_entrypoint(&tests(), &options));
+}
+
+#[test]
+fn erlang_options() {
+    assert_eq!(
+        Options::default().to_erlang(),
+        "#{parallel => 0, retries => 0, fail_on_flaky => false}"
+    );
+    let options = Options {
+        parallel: Some(4),
+        retries: 2,
+        fail_on_flaky: true,
+    };
+    assert_eq!(
+        options.to_erlang(),
+        "#{parallel => 4, retries => 2, fail_on_flaky => true}"
+    );
+}
+
+#[test]
+fn javascript_options() {
+    assert_eq!(
+        Options::default().to_javascript(),
+        "{ parallel: 1, retries: 0, failOnFlaky: false }"
+    );
+    let options = Options {
+        parallel: Some(4),
+        retries: 2,
+        fail_on_flaky: true,
+    };
+    assert_eq!(
+        options.to_javascript(),
+        "{ parallel: 4, retries: 2, failOnFlaky: true }"
+    );
+}
+
+const ARTEFACTS_MANIFEST: &str = r#"{
+  "package": "app",
+  "target": "erlang",
+  "mode": "dev",
+  "modules": {
+    "app": {
+      "source": "src/app.gleam",
+      "origin": "src",
+      "interface_hash": "0000000000000001",
+      "imports": ["app/user", "gleam/io"],
+      "generated": []
+    },
+    "app/user": {
+      "source": "src/app/user.gleam",
+      "origin": "src",
+      "interface_hash": "0000000000000002",
+      "imports": [],
+      "generated": []
+    },
+    "app_test": {
+      "source": "test/app_test.gleam",
+      "origin": "test",
+      "interface_hash": "0000000000000003",
+      "imports": ["app"],
+      "generated": []
+    },
+    "app/user_test": {
+      "source": "test/app/user_test.gleam",
+      "origin": "test",
+      "interface_hash": "0000000000000004",
+      "imports": ["app/user"],
+      "generated": []
+    }
+  }
+}"#;
+
+fn project() -> (InMemoryFileSystem, ProjectPaths) {
+    let fs = InMemoryFileSystem::new();
+    let paths = ProjectPaths::new("/app".into());
+    let files = [
+        (paths.build_artefacts_manifest(), ARTEFACTS_MANIFEST),
+        (paths.root_config(), "name = \"app\""),
+        (paths.root().join("src/app.gleam"), "pub fn main() { 1 }"),
+        (
+            paths.root().join("src/app/user.gleam"),
+            "pub fn name() { 2 }",
+        ),
+        (
+            paths.root().join("test/app_test.gleam"),
+            "pub fn a_test() { 3 }",
+        ),
+        (
+            paths.root().join("test/app/user_test.gleam"),
+            "pub fn b_test() { 4 }",
+        ),
+    ];
+    for (path, text) in files {
+        fs.write(&path, text).expect("write file");
+    }
+    (fs, paths)
+}
+
+fn modules(names: &[&str]) -> HashSet<EcoString> {
+    names.iter().map(|name| EcoString::from(*name)).collect()
+}
+
+#[test]
+fn nothing_changed() {
+    let (fs, paths) = project();
+    let snapshot = Snapshot::new(&fs, &paths).expect("snapshot");
+    assert_eq!(snapshot.changed_since(&snapshot), modules(&[]));
+}
+
+#[test]
+fn snapshot_round_trips() {
+    let (fs, paths) = project();
+    let snapshot = Snapshot::new(&fs, &paths).expect("snapshot");
+    assert_eq!(Snapshot::from_json(&snapshot.to_json()), Some(snapshot));
+    assert_eq!(Snapshot::from_json("{}"), None);
+}
+
+#[test]
+fn changed_modules_impact_the_modules_that_import_them() {
+    let (fs, paths) = project();
+    let earlier = Snapshot::new(&fs, &paths).expect("snapshot");
+    let path = paths.root().join("src/app/user.gleam");
+    fs.write(&path, "pub fn name() { 5 }").expect("write file");
+    let snapshot = Snapshot::new(&fs, &paths).expect("snapshot");
+
+    let changed = snapshot.changed_since(&earlier);
+    assert_eq!(changed, modules(&["app/user"]));
+    assert_eq!(
+        snapshot.impacted(&changed),
+        modules(&["app/user", "app", "app_test", "app/user_test"])
+    );
+}
+
+#[test]
+fn changed_test_modules_impact_only_themselves() {
+    let (fs, paths) = project();
+    let earlier = Snapshot::new(&fs, &paths).expect("snapshot");
+    let path = paths.root().join("test/app_test.gleam");
+    fs.write(&path, "pub fn a_test() { 6 }")
+        .expect("write file");
+    let snapshot = Snapshot::new(&fs, &paths).expect("snapshot");
+
+    let changed = snapshot.changed_since(&earlier);
+    assert_eq!(snapshot.impacted(&changed), modules(&["app_test"]));
+    let tests = vec![test("app_test", "a_test"), test("app/user_test", "b_test")];
+    assert_eq!(
+        changes::select(tests, &snapshot.impacted(&changed)),
+        vec![test("app_test", "a_test")]
+    );
+}
+
+#[test]
+fn changed_dependencies_change_every_module() {
+    let (fs, paths) = project();
+    let earlier = Snapshot::new(&fs, &paths).expect("snapshot");
+    fs.write(&paths.manifest(), "packages = []")
+        .expect("write file");
+    let snapshot = Snapshot::new(&fs, &paths).expect("snapshot");
+
+    assert_eq!(
+        snapshot.changed_since(&earlier),
+        modules(&["app/user", "app", "app_test", "app/user_test"])
+    );
+}
+
+#[test]
+fn changed_files() {
+    let (fs, paths) = project();
+    let snapshot = Snapshot::new(&fs, &paths).expect("snapshot");
+    let files = [Utf8PathBuf::from("src/app.gleam"), "README.md".into()];
+    assert_eq!(snapshot.changed_files(&files), modules(&["app"]));
+    let files = [Utf8PathBuf::from("gleam.toml")];
+    assert_eq!(
+        snapshot.changed_files(&files),
+        modules(&["app/user", "app", "app_test", "app/user_test"])
+    );
+}
@@ -0,0 +1,194 @@
+//! Listing of the `todo` and `panic` expressions of a project, used by
+//! `gleam todos` to track how much of it is still to be written.
+
+#[cfg(test)]
+mod tests;
+
+use std::{fmt::Write, sync::Arc};
+
+use camino::{Utf8Path, Utf8PathBuf};
+use ecow::EcoString;
+use itertools::Itertools;
+use serde::Serialize;
+
+use crate::{
+    ast::{
+        visit::{self, Visit},
+        SrcSpan, TypedExpr, TypedFunction, TypedModule,
+    },
+    line_numbers::LineNumbers,
+    type_::{pretty::Printer, Type},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HoleKind {
+    Todo,
+    Panic,
+}
+
+impl HoleKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Todo => "todo",
+            Self::Panic => "panic",
+        }
+    }
+}
+
+/// A `todo` or `panic` expression in a module.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Hole {
+    pub kind: HoleKind,
+    pub module: EcoString,
+    pub path: Utf8PathBuf,
+    pub line: u32,
+    pub column: u32,
+    /// The module function the expression is in, including when it is in an
+    /// anonymous function defined there.
+    pub function: EcoString,
+    /// The type inferred for the expression, which for a `todo` is the type
+    /// of the code that is yet to be written.
+    #[serde(rename = "type")]
+    pub type_: EcoString,
+    pub message: Option<EcoString>,
+}
+
+/// Finds the `todo` and `panic` expressions of a module, in the order they
+/// appear in its source code.
+///
+pub fn find(module: &TypedModule, path: &Utf8Path, code: &str) -> Vec<Hole> {
+    let mut finder = HoleFinder {
+        module: &module.name,
+        path,
+        code,
+        line_numbers: LineNumbers::new(code),
+        function: "".into(),
+        holes: vec![],
+    };
+    finder.visit_typed_module(module);
+    finder
+        .holes
+        .into_iter()
+        .sorted_by_key(|hole| (hole.line, hole.column))
+        .collect()
+}
+
+/// Renders holes as a list for printing in a terminal, with a summary of how
+/// many there are of each kind.
+///
+pub fn to_text(holes: &[Hole]) -> String {
+    let mut text = String::new();
+    for hole in holes {
+        let Hole {
+            kind,
+            path,
+            line,
+            column,
+            function,
+            type_,
+            message,
+            ..
+        } = hole;
+        let kind = kind.as_str();
+        write!(
+            text,
+            "{path}:{line}:{column} {kind} in `{function}` of type `{type_}`"
+        )
+        .expect("write hole");
+        if let Some(message) = message {
+            write!(text, ": {message}").expect("write message");
+        }
+        text.push('\n');
+    }
+
+    let count = |kind| holes.iter().filter(|hole| hole.kind == kind).count();
+    let plural = |count: usize, word: &str| match count {
+        1 => format!("1 {word}"),
+        _ => format!("{count} {word}s"),
+    };
+    if !holes.is_empty() {
+        text.push('\n');
+    }
+    writeln!(
+        text,
+        "{} and {}",
+        plural(count(HoleKind::Todo), "todo"),
+        plural(count(HoleKind::Panic), "panic")
+    )
+    .expect("write summary");
+    text
+}
+
+/// Renders holes as a JSON array, for tools tracking the completeness of a
+/// project.
+///
+pub fn to_json(holes: &[Hole]) -> String {
+    serde_json::to_string_pretty(holes).expect("holes to json")
+}
+
+struct HoleFinder<'a> {
+    module: &'a EcoString,
+    path: &'a Utf8Path,
+    code: &'a str,
+    line_numbers: LineNumbers,
+    function: EcoString,
+    holes: Vec<Hole>,
+}
+
+impl HoleFinder<'_> {
+    fn register(
+        &mut self,
+        kind: HoleKind,
+        location: SrcSpan,
+        message: &Option<Box<TypedExpr>>,
+        type_: &Type,
+    ) {
+        let position = self.line_numbers.line_and_column_number(location.start);
+        let message = message.as_deref().map(|message| match message {
+            TypedExpr::String { value, .. } => value.clone(),
+            _ => self
+                .code
+                .get(message.location().start as usize..message.location().end as usize)
+                .unwrap_or_default()
+                .into(),
+        });
+        self.holes.push(Hole {
+            kind,
+            module: self.module.clone(),
+            path: self.path.to_path_buf(),
+            line: position.line,
+            column: position.column,
+            function: self.function.clone(),
+            type_: Printer::new().pretty_print(type_, 0).into(),
+            message,
+        });
+    }
+}
+
+impl<'ast> Visit<'ast> for HoleFinder<'_> {
+    fn visit_typed_function(&mut self, fun: &'ast TypedFunction) {
+        self.function = fun.name.clone();
+        visit::visit_typed_function(self, fun);
+    }
+
+    fn visit_typed_expr_todo(
+        &mut self,
+        location: &'ast SrcSpan,
+        message: &'ast Option<Box<TypedExpr>>,
+        type_: &'ast Arc<Type>,
+    ) {
+        self.register(HoleKind::Todo, *location, message, type_);
+        visit::visit_typed_expr_todo(self, location, message, type_);
+    }
+
+    fn visit_typed_expr_panic(
+        &mut self,
+        location: &'ast SrcSpan,
+        message: &'ast Option<Box<TypedExpr>>,
+        type_: &'ast Arc<Type>,
+    ) {
+        self.register(HoleKind::Panic, *location, message, type_);
+        visit::visit_typed_expr_panic(self, location, message, type_);
+    }
+}
@@ -0,0 +1,63 @@
+use super::{find, to_json, to_text, Hole};
+use crate::type_::tests::compile_module;
+
+use camino::Utf8Path;
+
+fn holes(src: &str) -> Vec<Hole> {
+    let module = compile_module("app", src, None, vec![]).expect("compile module");
+    find(&module, Utf8Path::new("src/app.gleam"), src)
+}
+
+#[test]
+fn todos_and_panics() {
+    let src = r#"
+pub fn main() -> Int {
+  let x = wibble(1)
+  todo as "finish main"
+}
+
+fn wibble(x: Int) -> String {
+  case x {
+    0 -> panic
+    _ -> todo
+  }
+}
+"#;
+    insta::assert_snapshot!(to_text(&holes(src)));
+}
+
+#[test]
+fn hole_in_anonymous_function() {
+    let src = r#"
+pub fn main() {
+  let f = fn(x: Int) -> Float { todo }
+  f
+}
+"#;
+    insta::assert_snapshot!(to_text(&holes(src)));
+}
+
+#[test]
+fn message_that_is_not_a_literal() {
+    let src = r#"
+pub fn main(name: String) {
+  panic as { "unknown " <> name }
+}
+"#;
+    insta::assert_snapshot!(to_text(&holes(src)));
+}
+
+#[test]
+fn no_holes() {
+    insta::assert_snapshot!(to_text(&holes("pub fn main() { 1 }")));
+}
+
+#[test]
+fn json() {
+    let src = r#"
+pub fn main() -> Int {
+  todo as "finish main"
+}
+"#;
+    insta::assert_snapshot!(to_json(&holes(src)));
+}
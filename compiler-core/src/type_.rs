@@ -27,6 +27,7 @@ use crate::{
     },
     bit_array,
     build::{Origin, Target},
+    intern::Symbol,
     line_numbers::LineNumbers,
     type_::expression::Implementations,
 };
@@ -56,9 +57,9 @@ pub enum Type {
     ///
     Named {
         publicity: Publicity,
-        package: EcoString,
-        module: EcoString,
-        name: EcoString,
+        package: Symbol,
+        module: Symbol,
+        name: Symbol,
         args: Vec<Arc<Type>>,
     },
 
@@ -169,6 +170,13 @@ impl Type {
         }
     }
 
+    /// Whether a value of this type carries some information, so discarding
+    /// it is likely a mistake. `Nil` doesn't, and type variables are the types
+    /// of expressions such as `panic` that don't return a value at all.
+    pub fn is_meaningful(&self) -> bool {
+        !self.is_nil() && !self.is_type_variable()
+    }
+
     pub fn is_bit_array(&self) -> bool {
         match self {
             Self::Named { module, name, .. } if "BitArray" == name && is_prelude_module(module) => {
@@ -215,9 +223,9 @@ impl Type {
         }
     }
 
-    pub fn named_type_name(&self) -> Option<(EcoString, EcoString)> {
+    pub fn named_type_name(&self) -> Option<(Symbol, Symbol)> {
         match self {
-            Self::Named { module, name, .. } => Some((module.clone(), name.clone())),
+            Self::Named { module, name, .. } => Some((*module, *name)),
             Self::Var { type_ } => type_.borrow().named_type_name(),
             _ => None,
         }
@@ -375,7 +383,7 @@ pub enum ValueConstructorVariant {
     ModuleConstant {
         documentation: Option<EcoString>,
         location: SrcSpan,
-        module: EcoString,
+        module: Symbol,
         literal: Constant<Arc<Type>, EcoString>,
         implementations: Implementations,
     },
@@ -389,7 +397,7 @@ pub enum ValueConstructorVariant {
     ModuleFn {
         name: EcoString,
         field_map: Option<FieldMap>,
-        module: EcoString,
+        module: Symbol,
         arity: usize,
         location: SrcSpan,
         documentation: Option<EcoString>,
@@ -402,7 +410,7 @@ pub enum ValueConstructorVariant {
         arity: u16,
         field_map: Option<FieldMap>,
         location: SrcSpan,
-        module: EcoString,
+        module: Symbol,
         constructors_count: u16,
         constructor_index: u16,
         documentation: Option<EcoString>,
@@ -413,7 +421,7 @@ impl ValueConstructorVariant {
     fn to_module_value_constructor(
         &self,
         type_: Arc<Type>,
-        module_name: &EcoString,
+        module_name: Symbol,
         function_name: &EcoString,
     ) -> ModuleValueConstructor {
         match self {
@@ -453,7 +461,7 @@ impl ValueConstructorVariant {
 
             Self::LocalVariable { location, .. } => ModuleValueConstructor::Fn {
                 name: function_name.clone(),
-                module: module_name.clone(),
+                module: module_name,
                 documentation: None,
                 location: *location,
             },
@@ -466,7 +474,7 @@ impl ValueConstructorVariant {
                 ..
             } => ModuleValueConstructor::Fn {
                 name: name.clone(),
-                module: module.clone(),
+                module: *module,
                 documentation: documentation.clone(),
                 location: *location,
             },
@@ -551,7 +559,7 @@ pub enum ModuleValueConstructor {
         ///     @external(erlang, "other", "whoop")
         ///     pub fn wibble() -> Nil
         ///
-        module: EcoString,
+        module: Symbol,
         name: EcoString,
         documentation: Option<EcoString>,
     },
@@ -729,6 +737,27 @@ impl ModuleInterface {
         })
     }
 
+    /// The names of the module's test functions, which are public functions
+    /// with no arguments whose names end with `_test`, in alphabetical order.
+    pub fn test_functions(&self, target: Target) -> Vec<EcoString> {
+        self.values
+            .iter()
+            .filter(|(name, value)| {
+                let is_test = matches!(
+                    &value.variant,
+                    ValueConstructorVariant::ModuleFn {
+                        arity: 0,
+                        implementations,
+                        ..
+                    } if implementations.supports(target)
+                );
+                is_test && name.ends_with("_test") && value.publicity.is_importable()
+            })
+            .map(|(name, _)| name.clone())
+            .sorted()
+            .collect_vec()
+    }
+
     pub fn public_value_names(&self) -> Vec<EcoString> {
         self.values
             .iter()
@@ -753,7 +782,7 @@ pub struct PatternConstructor {
     pub name: EcoString,
     pub field_map: Option<FieldMap>,
     pub documentation: Option<EcoString>,
-    pub module: Option<EcoString>,
+    pub module: Option<Symbol>,
     pub location: SrcSpan,
     pub constructor_index: u16,
 }
@@ -884,7 +913,7 @@ impl TypeVar {
         }
     }
 
-    pub fn named_type_name(&self) -> Option<(EcoString, EcoString)> {
+    pub fn named_type_name(&self) -> Option<(Symbol, Symbol)> {
         match self {
             Self::Link { type_ } => type_.named_type_name(),
             Self::Unbound { .. } | Self::Generic { .. } => None,
@@ -1129,9 +1158,9 @@ pub fn generalise(t: Arc<Type>) -> Arc<Type> {
             let args = args.iter().map(|t| generalise(t.clone())).collect();
             Arc::new(Type::Named {
                 publicity: *publicity,
-                module: module.clone(),
-                package: package.clone(),
-                name: name.clone(),
+                module: *module,
+                package: *package,
+                name: *name,
                 args,
             })
         }
@@ -1149,6 +1178,8 @@ pub fn generalise(t: Arc<Type>) -> Arc<Type> {
 pub enum FieldAccessUsage {
     /// Used as `thing.field()`
     MethodCall,
+    /// Used as `Record(..thing, field: value)`
+    RecordUpdate,
     /// Used as `thing.field`
     Other,
 }
@@ -2,11 +2,13 @@ use crate::{
     analyse::TargetSupport,
     ast::{Publicity, PIPE_VARIABLE},
     build::Target,
+    config::WarningsConfig,
     uid::UniqueIdGenerator,
     warning::TypeWarningEmitter,
 };
 
 use super::*;
+use itertools::Itertools;
 use std::collections::HashMap;
 
 #[derive(Debug)]
@@ -59,12 +61,16 @@ pub struct Environment<'a> {
     /// compilation target.
     pub target_support: TargetSupport,
 
+    /// The configuration of the package's optional warnings.
+    pub warnings_config: WarningsConfig,
+
     /// Whether a `todo` expression has been encountered in this module.
     /// This is used by the build tool to refuse to publish packages that are unfinished.
     pub todo_encountered: bool,
 }
 
 impl<'a> Environment<'a> {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         ids: UniqueIdGenerator,
         current_package: EcoString,
@@ -73,6 +79,7 @@ impl<'a> Environment<'a> {
         importable_modules: &'a im::HashMap<EcoString, ModuleInterface>,
         warnings: &'a TypeWarningEmitter,
         target_support: TargetSupport,
+        warnings_config: WarningsConfig,
     ) -> Self {
         let prelude = importable_modules
             .get(PRELUDE_MODULE_NAME)
@@ -98,6 +105,7 @@ impl<'a> Environment<'a> {
             warnings,
             entity_usages: vec![HashMap::new()],
             target_support,
+            warnings_config,
             todo_encountered: false,
         }
     }
@@ -341,7 +349,7 @@ impl<'a> Environment<'a> {
         Ok(t)
     }
 
-    fn unknown_type_hint(&self, type_name: &EcoString) -> UnknownTypeHint {
+    fn unknown_type_hint(&self, type_name: &str) -> UnknownTypeHint {
         match self.scope.contains_key(type_name) {
             true => UnknownTypeHint::ValueInScopeWithSameName,
             false => UnknownTypeHint::AlternativeTypes(self.module_types.keys().cloned().collect()),
@@ -352,10 +360,10 @@ impl<'a> Environment<'a> {
     ///
     pub fn get_constructors_for_type(
         &self,
-        module: &EcoString,
-        name: &EcoString,
+        module: &str,
+        name: &str,
     ) -> Result<&TypeVariantConstructors, UnknownTypeConstructorError> {
-        let module = if module.is_empty() || *module == self.current_module {
+        let module = if module.is_empty() || module == self.current_module {
             None
         } else {
             Some(module)
@@ -363,7 +371,7 @@ impl<'a> Environment<'a> {
         match module {
             None => self.module_types_constructors.get(name).ok_or_else(|| {
                 UnknownTypeConstructorError::Type {
-                    name: name.clone(),
+                    name: name.into(),
                     hint: self.unknown_type_hint(name),
                 }
             }),
@@ -371,13 +379,13 @@ impl<'a> Environment<'a> {
             Some(m) => {
                 let module = self.importable_modules.get(m).ok_or_else(|| {
                     UnknownTypeConstructorError::Module {
-                        name: name.clone(),
+                        name: name.into(),
                         imported_modules: self.importable_modules.keys().cloned().collect(),
                     }
                 })?;
                 module.types_value_constructors.get(name).ok_or_else(|| {
                     UnknownTypeConstructorError::ModuleType {
-                        name: name.clone(),
+                        name: name.into(),
                         module_name: module.name.clone(),
                         type_constructors: module.public_type_names(),
                         imported_type_as_value: false,
@@ -450,9 +458,9 @@ impl<'a> Environment<'a> {
                     .collect();
                 Arc::new(Type::Named {
                     publicity: *publicity,
-                    name: name.clone(),
-                    package: package.clone(),
-                    module: module.clone(),
+                    name: *name,
+                    package: *package,
+                    module: *module,
                     args,
                 })
             }
@@ -556,7 +564,12 @@ impl<'a> Environment<'a> {
         self.handle_unused(unused);
 
         let mut locations = Vec::new();
-        for (name, location) in self.unused_modules.clone().into_iter() {
+        let unused_modules = self
+            .unused_modules
+            .clone()
+            .into_iter()
+            .sorted_by_key(|(_, location)| location.start);
+        for (name, location) in unused_modules {
             self.warnings.emit(Warning::UnusedImportedModule {
                 name: name.clone(),
                 location,
@@ -564,7 +577,11 @@ impl<'a> Environment<'a> {
             locations.push(location);
         }
 
-        for (name, info) in self.unused_module_aliases.iter() {
+        let unused_module_aliases = self
+            .unused_module_aliases
+            .iter()
+            .sorted_by_key(|(_, info)| info.location.start);
+        for (name, info) in unused_module_aliases {
             if !self.unused_modules.contains_key(name) {
                 self.warnings.emit(Warning::UnusedImportedModuleAlias {
                     alias: name.clone(),
@@ -578,7 +595,13 @@ impl<'a> Environment<'a> {
     }
 
     fn handle_unused(&mut self, unused: HashMap<EcoString, (EntityKind, SrcSpan, bool)>) {
-        for (name, (kind, location, _)) in unused.into_iter().filter(|(_, (_, _, used))| !used) {
+        // The warnings are emitted in the order of the code they are for, as
+        // the order of a hash map changes between runs.
+        let unused = unused
+            .into_iter()
+            .filter(|(_, (_, _, used))| !used)
+            .sorted_by_key(|(_, (_, location, _))| location.start);
+        for (name, (kind, location, _)) in unused {
             let warning = match kind {
                 EntityKind::ImportedType => Warning::UnusedType {
                     name,
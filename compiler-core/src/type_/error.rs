@@ -14,6 +14,7 @@ use pretty_assertions::assert_eq;
 
 use super::{
     expression::{ArgumentKind, CallKind},
+    pretty::Alias,
     FieldAccessUsage,
 };
 
@@ -23,9 +24,18 @@ pub struct UnknownType {
     pub name: EcoString,
 }
 
-#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+#[derive(Debug, Eq, PartialEq, Clone)]
 pub enum RecordVariants {
-    HasVariants,
+    /// The field is defined for some of the variants of the custom type, but
+    /// it can't be accessed as it is not in the same position with the same
+    /// type in all of them.
+    HasVariants {
+        record_location: SrcSpan,
+        /// The variants that have the field.
+        with_field: Vec<EcoString>,
+        /// The variants that don't have the field, with their arity.
+        without_field: Vec<(EcoString, usize)>,
+    },
     NoVariants,
 }
 
@@ -37,6 +47,18 @@ pub enum Error {
         test_module: crate::error::Name,
     },
 
+    /// A module imports a module that an import rule of the package's
+    /// `gleam.toml` forbids it from importing.
+    ForbiddenImport {
+        location: SrcSpan,
+        module: crate::error::Name,
+        imported_module: crate::error::Name,
+        /// The patterns of the rule's `modules` and of its `must_not_import`
+        /// that the modules matched.
+        modules_pattern: EcoString,
+        imported_pattern: EcoString,
+    },
+
     BitArraySegmentError {
         error: crate::bit_array::ErrorType,
         location: SrcSpan,
@@ -126,6 +148,8 @@ pub enum Error {
         expected: Arc<Type>,
         given: Arc<Type>,
         rigid_type_names: im::HashMap<u64, EcoString>,
+        /// The type aliases of the module, used to print the types.
+        type_aliases: Vec<Alias>,
     },
 
     RecursiveType {
@@ -469,6 +493,30 @@ pub enum Warning {
         location: SrcSpan,
     },
 
+    /// The value of an expression other than the last in a block is not
+    /// used, and it isn't `Nil`.
+    DiscardedValue {
+        location: SrcSpan,
+        typ: Arc<Type>,
+    },
+
+    /// More types and values are imported unqualified from a module than the
+    /// package's configured limit.
+    TooManyUnqualifiedImports {
+        location: SrcSpan,
+        module: EcoString,
+        count: usize,
+        max: usize,
+    },
+
+    /// A value with a name that is unclear without its module is imported
+    /// unqualified, and the package is configured to warn about this.
+    GenericUnqualifiedImport {
+        location: SrcSpan,
+        module: EcoString,
+        name: EcoString,
+    },
+
     NoFieldsRecordUpdate {
         location: SrcSpan,
     },
@@ -667,6 +715,7 @@ impl Error {
     pub fn start_location(&self) -> u32 {
         match self {
             Error::SrcImportingTest { location, .. }
+            | Error::ForbiddenImport { location, .. }
             | Error::BitArraySegmentError { location, .. }
             | Error::UnknownVariable { location, .. }
             | Error::UnknownType { location, .. }
@@ -744,6 +793,19 @@ impl Error {
         }
     }
 
+    pub fn with_unify_error_type_aliases(mut self, aliases: &[Alias]) -> Self {
+        match self {
+            Error::CouldNotUnify {
+                ref mut type_aliases,
+                ..
+            } => {
+                *type_aliases = aliases.to_vec();
+                self
+            }
+            _ => self,
+        }
+    }
+
     pub fn with_unify_error_rigid_names(mut self, new_names: &im::HashMap<u64, EcoString>) -> Self {
         match self {
             Error::CouldNotUnify {
@@ -756,6 +818,24 @@ impl Error {
             _ => self,
         }
     }
+
+    /// Errors with the same problem key are caused by the same underlying
+    /// problem, such as a missing module or a misspelt name, and are reported
+    /// as a single diagnostic.
+    pub fn problem_key(&self) -> Option<ProblemKey> {
+        match self {
+            Error::UnknownVariable { name, .. } => Some(("value", "".into(), name.clone())),
+            Error::UnknownType { name, .. } => Some(("type", "".into(), name.clone())),
+            Error::UnknownModule { name, .. } => Some(("module", name.clone(), "".into())),
+            Error::UnknownModuleType {
+                name, module_name, ..
+            } => Some(("type", module_name.clone(), name.clone())),
+            Error::UnknownModuleValue {
+                name, module_name, ..
+            } => Some(("value", module_name.clone(), name.clone())),
+            _ => None,
+        }
+    }
 }
 
 impl Warning {
@@ -768,6 +848,10 @@ impl Warning {
     }
 }
 
+/// The kind, module, and name of what a problem is about. See
+/// `Error::problem_key`.
+pub type ProblemKey = (&'static str, EcoString, EcoString);
+
 #[derive(Debug, PartialEq, Eq)]
 pub enum UnknownValueConstructorError {
     Variable {
@@ -1070,7 +1154,19 @@ pub enum UnifyErrorSituation {
     /// annotation.
     ReturnAnnotationMismatch,
 
-    PipeTypeMismatch,
+    /// A value sent through a `|>` pipe was not of the type expected by the
+    /// function of the pipeline stage it was sent to.
+    PipeTypeMismatch {
+        /// The position of the stage in the pipeline, the first function the
+        /// initial value is piped into being stage 1.
+        stage: usize,
+        /// The types of the values sent out of each of the stages before this
+        /// one, starting with the initial value.
+        types: Vec<Arc<Type>>,
+        /// A standard library function such as `list.map` that could be used
+        /// to apply the function to the contents of the piped value instead.
+        suggested_map: Option<&'static str>,
+    },
 
     /// The operands of a binary operator were incorrect.
     Operator(BinOp),
@@ -1088,9 +1184,7 @@ pub enum UnifyErrorSituation {
     ListTailMismatch,
 
     /// When two functions cannot be unified.
-    FunctionsMismatch {
-        reason: FunctionsMismatchReason,
-    },
+    FunctionsMismatch { reason: FunctionsMismatchReason },
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -1121,7 +1215,7 @@ one, but all case clauses must return the same type.",
                 "The type of this returned value doesn't match the return type
 annotation of this function.",
             ),
-            Self::PipeTypeMismatch => {
+            Self::PipeTypeMismatch { .. } => {
                 Some("This function cannot handle the argument sent through the (|>) pipe:")
             }
             Self::Operator(_op) => None,
@@ -1229,6 +1323,7 @@ impl UnifyError {
                 given,
                 situation: note,
                 rigid_type_names: im::hashmap![],
+                type_aliases: vec![],
             },
 
             Self::ExtraVarInAlternativePattern { name } => {
@@ -1281,6 +1376,7 @@ impl UnifyError {
                     given: other.clone(),
                     situation: None,
                     rigid_type_names: im::hashmap![],
+                    type_aliases: vec![],
                 },
 
                 FunctionsMismatchReason::Arity {
@@ -11,6 +11,7 @@ use crate::{
         USE_ASSIGNMENT_VARIABLE,
     },
     build::Target,
+    config::DiscardedValues,
     exhaustiveness,
 };
 use id_arena::Arena;
@@ -497,6 +498,8 @@ impl<'a, 'b> ExprTyper<'a, 'b> {
     /// Emit a warning if the given expressions should not be discarded.
     /// e.g. because it's a literal (why was it made in the first place?)
     /// e.g. because it's of the `Result` type (errors should be handled)
+    /// e.g. because it has a value other than `Nil`, unless the package is
+    /// configured to only warn about discarded `Result`s
     fn expression_discarded(&mut self, discarded: &TypedExpr) {
         if discarded.is_literal() {
             self.environment.warnings.emit(Warning::UnusedLiteral {
@@ -512,6 +515,15 @@ impl<'a, 'b> ExprTyper<'a, 'b> {
             self.environment.warnings.emit(Warning::UnusedValue {
                 location: discarded.location(),
             })
+        } else if self.environment.warnings_config.discarded_values == DiscardedValues::All
+            && discarded.type_().is_meaningful()
+            // If the expression always panics no value is ever produced
+            && !self.previous_panics
+        {
+            self.environment.warnings.emit(Warning::DiscardedValue {
+                location: discarded.location(),
+                typ: discarded.type_(),
+            })
         }
     }
 
@@ -1820,15 +1832,17 @@ impl<'a, 'b> ExprTyper<'a, 'b> {
 
         let constructor = match &constructor.variant {
             variant @ ValueConstructorVariant::ModuleFn { name, module, .. } => {
-                variant.to_module_value_constructor(Arc::clone(&type_), module, name)
+                variant.to_module_value_constructor(Arc::clone(&type_), *module, name)
             }
 
             variant @ (ValueConstructorVariant::LocalVariable { .. }
             | ValueConstructorVariant::ModuleConstant { .. }
             | ValueConstructorVariant::LocalConstant { .. }
-            | ValueConstructorVariant::Record { .. }) => {
-                variant.to_module_value_constructor(Arc::clone(&type_), &module_name, &label)
-            }
+            | ValueConstructorVariant::Record { .. }) => variant.to_module_value_constructor(
+                Arc::clone(&type_),
+                (&module_name).into(),
+                &label,
+            ),
         };
 
         Ok(TypedExpr::ModuleSelect {
@@ -1887,41 +1901,26 @@ impl<'a, 'b> ExprTyper<'a, 'b> {
             });
         }
 
-        let has_variants = match &*record_type {
-            Type::Named { name, .. } => self
-                .environment
-                .module_types_constructors
-                .get(name)
-                .map_or(RecordVariants::NoVariants, |type_variant_constructors| {
-                    if type_variant_constructors.variants.len() > 1 {
-                        RecordVariants::HasVariants
-                    } else {
-                        RecordVariants::NoVariants
-                    }
-                }),
-            _ => RecordVariants::NoVariants,
-        };
-
         let unknown_field = |fields| Error::UnknownRecordField {
             usage,
             typ: record_type.clone(),
             location,
             label: label.clone(),
             fields,
-            variants: has_variants,
+            variants: self.record_variants(&record_type, record_location, &label),
         };
         let accessors = match collapse_links(record_type.clone()).as_ref() {
             // A type in the current module which may have fields
             Type::Named { module, name, .. } if module == &self.environment.current_module => {
-                self.environment.accessors.get(name)
+                self.environment.accessors.get(name.as_str())
             }
 
             // A type in another module which may have fields
             Type::Named { module, name, .. } => self
                 .environment
                 .importable_modules
-                .get(module)
-                .and_then(|module| module.accessors.get(name)),
+                .get(module.as_str())
+                .and_then(|module| module.accessors.get(name.as_str())),
 
             _something_without_fields => return Err(unknown_field(vec![])),
         }
@@ -1944,6 +1943,79 @@ impl<'a, 'b> ExprTyper<'a, 'b> {
         Ok((index, label, typ))
     }
 
+    /// Whether a field that could not be accessed is defined for some of the
+    /// variants of a custom type, and so could be got using a case expression.
+    fn record_variants(
+        &self,
+        record_type: &Type,
+        record_location: SrcSpan,
+        label: &EcoString,
+    ) -> RecordVariants {
+        let Some((module, name)) = record_type.named_type_name() else {
+            return RecordVariants::NoVariants;
+        };
+        let Ok(constructors) = self
+            .environment
+            .get_constructors_for_type(module.as_str(), name.as_str())
+        else {
+            return RecordVariants::NoVariants;
+        };
+        if constructors.variants.len() < 2 {
+            return RecordVariants::NoVariants;
+        }
+
+        let is_current_module = module.as_str() == self.environment.current_module;
+        let values = if is_current_module {
+            Some(&self.environment.module_values)
+        } else {
+            self.environment
+                .importable_modules
+                .get(module.as_str())
+                .map(|module| &module.values)
+        };
+        // Constructors from other modules are qualified with the alias they
+        // are imported with, if they are.
+        let qualifier = self
+            .environment
+            .imported_modules
+            .iter()
+            .find(|(_, (_, imported))| !is_current_module && imported.name == module.as_str())
+            .map(|(alias, _)| alias);
+
+        let mut with_field = vec![];
+        let mut without_field = vec![];
+        for variant in &constructors.variants {
+            let has_field = values
+                .and_then(|values| values.get(&variant.name))
+                .is_some_and(|value| match &value.variant {
+                    ValueConstructorVariant::Record {
+                        field_map: Some(field_map),
+                        ..
+                    } => field_map.fields.contains_key(&Symbol::from(label)),
+                    _ => false,
+                });
+            let name = match qualifier {
+                Some(alias) => format!("{alias}.{}", variant.name).into(),
+                None => variant.name.clone(),
+            };
+            if has_field {
+                with_field.push(name);
+            } else {
+                without_field.push((name, variant.parameters.len()));
+            }
+        }
+
+        if with_field.is_empty() {
+            RecordVariants::NoVariants
+        } else {
+            RecordVariants::HasVariants {
+                record_location,
+                with_field,
+                without_field,
+            }
+        }
+    }
+
     fn infer_record_update(
         &mut self,
         constructor: UntypedExpr,
@@ -2026,7 +2098,7 @@ impl<'a, 'b> ExprTyper<'a, 'b> {
                         spread.clone(),
                         label.clone(),
                         *location,
-                        FieldAccessUsage::Other,
+                        FieldAccessUsage::RecordUpdate,
                     )?;
 
                     // Check that the update argument unifies with the corresponding
@@ -2036,7 +2108,7 @@ impl<'a, 'b> ExprTyper<'a, 'b> {
                     unify(spread_field.type_(), value.type_())
                         .map_err(|e| convert_unify_error(e, value.location()))?;
 
-                    match field_map.fields.get(label) {
+                    match field_map.fields.get(&Symbol::from(label)) {
                         None => panic!(
                             "Failed to lookup record field after successfully inferring that field",
                         ),
@@ -1,5 +1,8 @@
 use super::Error;
-use crate::ast::{CallArg, SrcSpan};
+use crate::{
+    ast::{CallArg, SrcSpan},
+    intern::Symbol,
+};
 use ecow::EcoString;
 use itertools::Itertools;
 use std::collections::{HashMap, HashSet};
@@ -7,7 +10,7 @@ use std::collections::{HashMap, HashSet};
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct FieldMap {
     pub arity: u32,
-    pub fields: HashMap<EcoString, u32>,
+    pub fields: HashMap<Symbol, u32>,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -21,8 +24,8 @@ impl FieldMap {
         }
     }
 
-    pub fn insert(&mut self, label: EcoString, index: u32) -> Result<(), DuplicateField> {
-        match self.fields.insert(label, index) {
+    pub fn insert(&mut self, label: impl Into<Symbol>, index: u32) -> Result<(), DuplicateField> {
+        match self.fields.insert(label.into(), index) {
             Some(_) => Err(DuplicateField),
             None => Ok(()),
         }
@@ -89,7 +92,7 @@ impl FieldMap {
                 }
             };
 
-            let position = match self.fields.get(label) {
+            let position = match self.fields.get(&Symbol::from(label)) {
                 None => {
                     unknown_labels.push((label.clone(), location));
                     i += 1;
@@ -120,7 +123,7 @@ impl FieldMap {
             Ok(())
         } else {
             Err(Error::UnknownLabels {
-                valid: self.fields.keys().cloned().collect(),
+                valid: self.fields.keys().map(|label| (*label).into()).collect(),
                 unknown: unknown_labels,
                 supplied: seen_labels.into_iter().collect(),
             })
@@ -128,13 +131,16 @@ impl FieldMap {
     }
 
     pub fn incorrect_arity_labels<A>(&self, args: &[CallArg<A>]) -> Vec<EcoString> {
-        let given: HashSet<_> = args.iter().filter_map(|arg| arg.label.as_ref()).collect();
+        let given: HashSet<_> = args
+            .iter()
+            .filter_map(|arg| arg.label.as_ref().map(Symbol::from))
+            .collect();
 
         self.fields
             .keys()
             .filter(|f| !given.contains(f))
-            .cloned()
             .sorted()
+            .map(|f| (*f).into())
             .collect()
     }
 }
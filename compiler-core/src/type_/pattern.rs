@@ -358,6 +358,7 @@ impl<'a, 'b> PatternTyper<'a, 'b> {
                     situation: None,
                     location,
                     rigid_type_names: hashmap![],
+                    type_aliases: vec![],
                 }),
             },
 
@@ -405,6 +406,7 @@ impl<'a, 'b> PatternTyper<'a, 'b> {
                         situation: None,
                         location,
                         rigid_type_names: hashmap![],
+                        type_aliases: vec![],
                     })
                 }
             },
@@ -524,7 +526,7 @@ impl<'a, 'b> PatternTyper<'a, 'b> {
                         documentation: documentation.clone(),
                         name: name.clone(),
                         field_map: cons.field_map().cloned(),
-                        module: Some(module.clone()),
+                        module: Some(*module),
                         location: *location,
                         constructor_index: *constructor_index,
                     },
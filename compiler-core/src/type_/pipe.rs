@@ -225,17 +225,10 @@ impl<'a, 'b, 'c> PipeTyper<'a, 'b, 'c> {
             fun: Box::new(function),
         };
         let args = vec![self.untyped_left_hand_value_variable_call_argument()];
-        // TODO: use `.with_unify_error_situation(UnifyErrorSituation::PipeTypeMismatch)`
-        // This will require the typing of the arguments to be lifted up out of
-        // the function below. If it is not we don't know if the error comes
-        // from incorrect usage of the pipe or if it originates from the
-        // argument expressions.
-        let (function, args, typ) = self.expr_typer.do_infer_call_with_known_fun(
-            function,
-            args,
-            location,
-            CallKind::Function,
-        )?;
+        let (function, args, typ) = self
+            .expr_typer
+            .do_infer_call_with_known_fun(function, args, location, CallKind::Function)
+            .map_err(|e| self.convert_pipe_argument_error(e, location))?;
         Ok(TypedExpr::Call {
             location,
             typ,
@@ -251,18 +244,12 @@ impl<'a, 'b, 'c> PipeTyper<'a, 'b, 'c> {
         mut arguments: Vec<CallArg<UntypedExpr>>,
         location: SrcSpan,
     ) -> Result<TypedExpr, Error> {
+        let function_location = function.location();
         arguments.insert(0, self.untyped_left_hand_value_variable_call_argument());
-        // TODO: use `.with_unify_error_situation(UnifyErrorSituation::PipeTypeMismatch)`
-        // This will require the typing of the arguments to be lifted up out of
-        // the function below. If it is not we don't know if the error comes
-        // from incorrect usage of the pipe or if it originates from the
-        // argument expressions.
-        let (fun, args, typ) = self.expr_typer.do_infer_call_with_known_fun(
-            function,
-            arguments,
-            location,
-            CallKind::Function,
-        )?;
+        let (fun, args, typ) = self
+            .expr_typer
+            .do_infer_call_with_known_fun(function, arguments, location, CallKind::Function)
+            .map_err(|e| self.convert_pipe_argument_error(e, function_location))?;
         Ok(TypedExpr::Call {
             location,
             typ,
@@ -280,14 +267,13 @@ impl<'a, 'b, 'c> PipeTyper<'a, 'b, 'c> {
             function.type_(),
             fn_(vec![self.argument_type.clone()], return_type.clone()),
         )
-        .map_err(|e| {
-            let is_pipe_mismatch = self.check_if_pipe_type_mismatch(&e);
-            let error = convert_unify_error(e, function.location());
-            if is_pipe_mismatch {
-                error.with_unify_error_situation(UnifyErrorSituation::PipeTypeMismatch)
-            } else {
-                error
+        .map_err(|e| match self.check_if_pipe_type_mismatch(&e) {
+            // Report the mismatch between the function's argument and the
+            // piped value, rather than between the two function types.
+            Some((expected, given)) => {
+                self.pipe_type_mismatch(function.location(), expected, given)
             }
+            None => convert_unify_error(e, function.location()),
         })?;
 
         Ok(TypedExpr::Call {
@@ -298,22 +284,91 @@ impl<'a, 'b, 'c> PipeTyper<'a, 'b, 'c> {
         })
     }
 
-    fn check_if_pipe_type_mismatch(&mut self, error: &UnifyError) -> bool {
+    /// If the function could not be unified because of its first argument,
+    /// returns the type of that argument and the type of the piped value.
+    fn check_if_pipe_type_mismatch(
+        &mut self,
+        error: &UnifyError,
+    ) -> Option<(Arc<Type>, Arc<Type>)> {
         let types = match error {
             UnifyError::CouldNotUnify {
                 expected, given, ..
             } => (expected.as_ref(), given.as_ref()),
-            _ => return false,
+            _ => return None,
         };
 
         match types {
             (Type::Fn { args: a, .. }, Type::Fn { args: b, .. }) if a.len() == b.len() => {
                 match (a.first(), b.first()) {
-                    (Some(a), Some(b)) => unify(a.clone(), b.clone()).is_err(),
-                    _ => false,
+                    (Some(a), Some(b)) if unify(a.clone(), b.clone()).is_err() => {
+                        Some((a.clone(), b.clone()))
+                    }
+                    _ => None,
                 }
             }
-            _ => false,
+            _ => None,
         }
     }
+
+    /// An error with the argument that the value sent through the pipe is
+    /// passed as is reported as a mismatch with the piped type, rather than
+    /// at the location of the previous stage of the pipeline.
+    fn convert_pipe_argument_error(&mut self, error: Error, function_location: SrcSpan) -> Error {
+        match error {
+            Error::CouldNotUnify {
+                location,
+                expected,
+                given,
+                ..
+            } if location == self.argument_location => {
+                self.pipe_type_mismatch(function_location, expected, given)
+            }
+            error => error,
+        }
+    }
+
+    fn pipe_type_mismatch(
+        &mut self,
+        location: SrcSpan,
+        expected: Arc<Type>,
+        given: Arc<Type>,
+    ) -> Error {
+        // The assignments hold the value sent out of each of the previous
+        // stages, so this stage is one after the last of them.
+        let stage = self.assignments.len();
+        let types = self
+            .assignments
+            .iter()
+            .map(|assignment| assignment.value.type_())
+            .collect();
+        let suggested_map = suggested_map(&expected, &given);
+        UnifyError::CouldNotUnify {
+            situation: Some(UnifyErrorSituation::PipeTypeMismatch {
+                stage,
+                types,
+                suggested_map,
+            }),
+            expected,
+            given,
+        }
+        .into_error(location)
+    }
+}
+
+/// If the piped value is a list, result, or option containing a value of the
+/// type the function expects, the standard library function that can be used
+/// to apply the function to its contents.
+fn suggested_map(expected: &Arc<Type>, given: &Arc<Type>) -> Option<&'static str> {
+    let (map, element) = match collapse_links(given.clone()).as_ref() {
+        Type::Named {
+            module, name, args, ..
+        } => match (module.as_str(), name.as_str(), args.first()) {
+            (PRELUDE_MODULE_NAME, "List", Some(element)) => ("list.map", element.clone()),
+            (PRELUDE_MODULE_NAME, "Result", Some(element)) => ("result.map", element.clone()),
+            ("gleam/option", "Option", Some(element)) => ("option.map", element.clone()),
+            _ => return None,
+        },
+        _ => return None,
+    };
+    unify(expected.clone(), element).ok().map(|_| map)
 }
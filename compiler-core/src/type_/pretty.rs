@@ -1,5 +1,6 @@
-use super::{Type, TypeVar};
+use super::{collapse_links, Type, TypeVar};
 use crate::{
+    ast::Publicity,
     docvec,
     pretty::{nil, *},
 };
@@ -16,12 +17,39 @@ use pretty_assertions::assert_eq;
 
 const INDENT: isize = 2;
 
+/// A type alias defined in the module, which types it expands to are printed
+/// as instead.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Alias {
+    pub name: EcoString,
+    /// The generic type variables of the alias's parameters.
+    pub parameters: Vec<Arc<Type>>,
+    /// The type the alias expands to.
+    pub type_: Arc<Type>,
+}
+
+impl Alias {
+    /// Only aliases of types with some structure to them are printed, as an
+    /// alias such as `type Id = Int` would otherwise be printed for every
+    /// `Int` in the module.
+    pub fn is_printable(&self) -> bool {
+        match self.type_.as_ref() {
+            Type::Named { args, .. } => !args.is_empty(),
+            Type::Fn { .. } | Type::Tuple { .. } => true,
+            Type::Var { .. } => false,
+        }
+    }
+}
+
 #[derive(Debug, Default)]
 pub struct Printer {
     names: im::HashMap<u64, EcoString>,
     uid: u64,
     // A mapping of printd type names to the module that they are defined in.
     printed_types: im::HashMap<EcoString, EcoString>,
+    aliases: Vec<Alias>,
+    // The names of the aliases printed since they were last taken.
+    used_aliases: Vec<EcoString>,
 }
 
 impl Printer {
@@ -33,6 +61,34 @@ impl Printer {
         self.names = names;
     }
 
+    pub fn with_aliases(&mut self, aliases: Vec<Alias>) {
+        self.aliases = aliases;
+    }
+
+    /// The names of the aliases printed since this was last called.
+    pub fn take_used_aliases(&mut self) -> Vec<EcoString> {
+        std::mem::take(&mut self.used_aliases)
+    }
+
+    /// A description of the type an alias expands to, such as
+    /// `` `Pair(a)` is an alias of `#(a, a)` ``.
+    pub fn alias_expansion(&self, name: &str) -> Option<String> {
+        let alias = self.aliases.iter().find(|alias| alias.name == name)?;
+        // A new printer is used so that the alias isn't printed as itself, and
+        // the names of its parameters don't depend on the types printed before.
+        let mut printer = Printer::new();
+        let head = printer.print(&Type::Named {
+            publicity: Publicity::Public,
+            package: "".into(),
+            module: "".into(),
+            name: name.into(),
+            args: alias.parameters.clone(),
+        });
+        let head = head.to_pretty_string(80);
+        let expansion = printer.pretty_print(&alias.type_, 0);
+        Some(format!("`{head}` is an alias of `{expansion}`"))
+    }
+
     /// Render a Type as a well formatted string.
     ///
     pub fn pretty_print(&mut self, typ: &Type, initial_indent: usize) -> String {
@@ -51,6 +107,10 @@ impl Printer {
     // Is this possible? The lifetime would have to go through the Arc<Refcell<Type>>
     // for TypeVar::Link'd types.
     pub fn print<'a>(&mut self, typ: &Type) -> Document<'a> {
+        if let Some(doc) = self.print_as_alias(typ) {
+            return doc;
+        }
+
         match typ {
             Type::Named {
                 name, args, module, ..
@@ -58,7 +118,7 @@ impl Printer {
                 let doc = if self.name_clashes_if_unqualified(name, module) {
                     qualify_type_name(module, name)
                 } else {
-                    let _ = self.printed_types.insert(name.clone(), module.clone());
+                    let _ = self.printed_types.insert((*name).into(), (*module).into());
                     name.to_doc()
                 };
                 if args.is_empty() {
@@ -87,7 +147,41 @@ impl Printer {
         }
     }
 
-    fn name_clashes_if_unqualified(&mut self, type_: &EcoString, module: &str) -> bool {
+    fn print_as_alias<'a>(&mut self, typ: &Type) -> Option<Document<'a>> {
+        let (name, args) = self.aliases.iter().find_map(|alias| {
+            let mut bindings = im::HashMap::new();
+            if !matches_alias(&alias.type_, typ, &mut bindings) {
+                return None;
+            }
+            let args: Option<Vec<_>> = alias
+                .parameters
+                .iter()
+                .map(|parameter| match parameter.as_ref() {
+                    Type::Var { type_ } => match &*type_.borrow() {
+                        TypeVar::Generic { id } => bindings.get(id).cloned(),
+                        _ => None,
+                    },
+                    _ => None,
+                })
+                .collect();
+            Some((alias.name.clone(), args?))
+        })?;
+
+        if !self.used_aliases.contains(&name) {
+            self.used_aliases.push(name.clone());
+        }
+        let _ = self.printed_types.insert(name.clone(), "".into());
+        let doc = name.to_doc();
+        Some(if args.is_empty() {
+            doc
+        } else {
+            doc.append("(")
+                .append(self.args_to_gleam_doc(&args))
+                .append(")")
+        })
+    }
+
+    fn name_clashes_if_unqualified(&mut self, type_: &str, module: &str) -> bool {
         match self.printed_types.get(type_) {
             None => false,
             Some(previous_module) if module == previous_module => false,
@@ -156,6 +250,137 @@ impl Printer {
     }
 }
 
+/// Whether a type is the type an alias expands to, binding the alias's
+/// parameters to the types they are given as.
+fn matches_alias(alias: &Type, typ: &Type, bindings: &mut im::HashMap<u64, Arc<Type>>) -> bool {
+    if let Type::Var { type_ } = alias {
+        if let TypeVar::Link { type_ } = &*type_.borrow() {
+            return matches_alias(type_, typ, bindings);
+        }
+    }
+    if let Type::Var { type_ } = typ {
+        if let TypeVar::Link { type_ } = &*type_.borrow() {
+            return matches_alias(alias, type_, bindings);
+        }
+    }
+
+    match (alias, typ) {
+        (Type::Var { type_: parameter }, _) => match &*parameter.borrow() {
+            TypeVar::Generic { id } => match bindings.get(id) {
+                Some(bound) => is_same_type(bound, &Arc::new(typ.clone())),
+                None => {
+                    let _ = bindings.insert(*id, Arc::new(typ.clone()));
+                    true
+                }
+            },
+            _ => false,
+        },
+
+        (
+            Type::Named {
+                module, name, args, ..
+            },
+            Type::Named {
+                module: other_module,
+                name: other_name,
+                args: other_args,
+                ..
+            },
+        ) => {
+            module == other_module
+                && name == other_name
+                && args.len() == other_args.len()
+                && args
+                    .iter()
+                    .zip(other_args)
+                    .all(|(arg, other)| matches_alias(arg, other, bindings))
+        }
+
+        (
+            Type::Fn { args, retrn },
+            Type::Fn {
+                args: other_args,
+                retrn: other_retrn,
+            },
+        ) => {
+            args.len() == other_args.len()
+                && args
+                    .iter()
+                    .zip(other_args)
+                    .all(|(arg, other)| matches_alias(arg, other, bindings))
+                && matches_alias(retrn, other_retrn, bindings)
+        }
+
+        (Type::Tuple { elems }, Type::Tuple { elems: other_elems }) => {
+            elems.len() == other_elems.len()
+                && elems
+                    .iter()
+                    .zip(other_elems)
+                    .all(|(elem, other)| matches_alias(elem, other, bindings))
+        }
+
+        _ => false,
+    }
+}
+
+fn is_same_type(one: &Arc<Type>, other: &Arc<Type>) -> bool {
+    match (
+        collapse_links(one.clone()).as_ref(),
+        collapse_links(other.clone()).as_ref(),
+    ) {
+        (Type::Var { type_ }, Type::Var { type_: other }) => {
+            match (&*type_.borrow(), &*other.borrow()) {
+                (
+                    TypeVar::Unbound { id } | TypeVar::Generic { id },
+                    TypeVar::Unbound { id: other } | TypeVar::Generic { id: other },
+                ) => id == other,
+                _ => false,
+            }
+        }
+        (
+            Type::Named {
+                module, name, args, ..
+            },
+            Type::Named {
+                module: other_module,
+                name: other_name,
+                args: other_args,
+                ..
+            },
+        ) => {
+            module == other_module
+                && name == other_name
+                && args.len() == other_args.len()
+                && args
+                    .iter()
+                    .zip(other_args)
+                    .all(|(arg, other)| is_same_type(arg, other))
+        }
+        (
+            Type::Fn { args, retrn },
+            Type::Fn {
+                args: other_args,
+                retrn: other_retrn,
+            },
+        ) => {
+            args.len() == other_args.len()
+                && args
+                    .iter()
+                    .zip(other_args)
+                    .all(|(arg, other)| is_same_type(arg, other))
+                && is_same_type(retrn, other_retrn)
+        }
+        (Type::Tuple { elems }, Type::Tuple { elems: other_elems }) => {
+            elems.len() == other_elems.len()
+                && elems
+                    .iter()
+                    .zip(other_elems)
+                    .all(|(elem, other)| is_same_type(elem, other))
+        }
+        _ => false,
+    }
+}
+
 fn qualify_type_name(module: &str, type_name: &str) -> Document<'static> {
     let type_name = Document::String(type_name.to_string());
     docvec![Document::String(module.to_string()), ".", type_name]
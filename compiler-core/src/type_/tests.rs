@@ -3,7 +3,7 @@ use crate::{
     analyse::TargetSupport,
     ast::{TypedModule, TypedStatement, UntypedExpr, UntypedModule},
     build::{Origin, Outcome, Target},
-    config::PackageConfig,
+    config::{PackageConfig, WarningsConfig},
     error::Error,
     type_::{build_prelude, expression::FunctionDefinition, pretty::Printer},
     uid::UniqueIdGenerator,
@@ -165,25 +165,58 @@ macro_rules! assert_with_module_error {
 }
 
 fn get_warnings(src: &str, deps: Vec<DependencyModule<'_>>) -> Vec<Warning> {
+    get_warnings_with_config(src, deps, WarningsConfig::default())
+}
+
+fn get_warnings_with_config(
+    src: &str,
+    deps: Vec<DependencyModule<'_>>,
+    warnings_config: WarningsConfig,
+) -> Vec<Warning> {
     let warnings = VectorWarningEmitterIO::default();
-    _ = compile_module("test_module", src, Some(Arc::new(warnings.clone())), deps).unwrap();
+    _ = compile_module_with_warnings_config(
+        "test_module",
+        src,
+        Some(Arc::new(warnings.clone())),
+        deps,
+        Target::Erlang,
+        TargetSupport::NotEnforced,
+        warnings_config,
+    )
+    .unwrap();
     warnings
         .take()
         .into_iter()
         .map(|warning| match warning {
             crate::Warning::Type { warning, .. } => warning,
             crate::Warning::InvalidSource { .. } => panic!("Invalid module file name"),
+            crate::Warning::ModifiedDependency { .. } => panic!("Modified dependency"),
+            crate::Warning::ModifiedArtefacts { .. } => panic!("Modified artefacts"),
+            crate::Warning::StaticEntrypointImport { .. } => panic!("Static entrypoint import"),
+            crate::Warning::JavaScriptPlatformMismatch { .. } => panic!("Platform mismatch"),
+            crate::Warning::OtherTargetError { .. } => panic!("Other target error"),
+            crate::Warning::TargetSignatureMismatch { .. } => panic!("Target mismatch"),
+            crate::Warning::UnknownConfigKey { .. } => panic!("Unknown config key"),
+            crate::Warning::RetiredDependency { .. } => panic!("Retired dependency"),
         })
         .collect_vec()
 }
 
 fn get_printed_warnings(src: &str, deps: Vec<DependencyModule<'_>>) -> String {
-    let warnings = get_warnings(src, deps);
+    get_printed_warnings_with_config(src, deps, WarningsConfig::default())
+}
+
+fn get_printed_warnings_with_config(
+    src: &str,
+    deps: Vec<DependencyModule<'_>>,
+    warnings_config: WarningsConfig,
+) -> String {
+    let warnings = get_warnings_with_config(src, deps, warnings_config);
     let mut nocolor = termcolor::Buffer::no_color();
     for warning in warnings {
         let path = Utf8PathBuf::from("/src/warning/wrn.gleam");
         let warning = warning.into_warning(path, src.into());
-        warning.pretty(&mut nocolor);
+        warning.pretty(&mut nocolor, crate::diagnostic::Theme::default());
     }
     String::from_utf8(nocolor.into_inner()).expect("Error printing produced invalid utf8")
 }
@@ -280,6 +313,7 @@ fn compile_statement_sequence(
             &modules,
             &TypeWarningEmitter::null(),
             TargetSupport::Enforced,
+            WarningsConfig::default(),
         ),
         FunctionDefinition {
             has_body: true,
@@ -375,6 +409,50 @@ pub fn compile_module_with_opts(
     dep: Vec<DependencyModule<'_>>,
     target: Target,
     target_support: TargetSupport,
+) -> Result<TypedModule, Vec<crate::type_::Error>> {
+    compile_module_with_warnings_config(
+        module_name,
+        src,
+        warnings,
+        dep,
+        target,
+        target_support,
+        WarningsConfig::default(),
+    )
+}
+
+fn compile_module_with_warnings_config(
+    module_name: &str,
+    src: &str,
+    warnings: Option<Arc<dyn WarningEmitterIO>>,
+    dep: Vec<DependencyModule<'_>>,
+    target: Target,
+    target_support: TargetSupport,
+    warnings_config: WarningsConfig,
+) -> Result<TypedModule, Vec<crate::type_::Error>> {
+    let config = PackageConfig {
+        warnings: warnings_config,
+        ..Default::default()
+    };
+    compile_module_with_config(
+        module_name,
+        src,
+        warnings,
+        dep,
+        target,
+        target_support,
+        config,
+    )
+}
+
+pub fn compile_module_with_config(
+    module_name: &str,
+    src: &str,
+    warnings: Option<Arc<dyn WarningEmitterIO>>,
+    dep: Vec<DependencyModule<'_>>,
+    target: Target,
+    target_support: TargetSupport,
+    mut config: PackageConfig,
 ) -> Result<TypedModule, Vec<crate::type_::Error>> {
     let ids = UniqueIdGenerator::new();
     let mut modules = im::HashMap::new();
@@ -422,7 +500,6 @@ pub fn compile_module_with_opts(
     let parsed = crate::parse::parse_module(src).expect("syntax error");
     let mut ast = parsed.module;
     ast.name = module_name.into();
-    let mut config = PackageConfig::default();
     config.name = "thepackage".into();
     let inference_result = crate::analyse::ModuleAnalyzerConstructor::<()> {
         target,
@@ -469,6 +546,31 @@ pub fn module_error_with_target(
     error.pretty_string()
 }
 
+/// The error of a module of the package with the given config.
+pub fn module_error_with_config(
+    module_name: &str,
+    src: &str,
+    deps: Vec<DependencyModule<'_>>,
+    config: PackageConfig,
+) -> String {
+    let error = compile_module_with_config(
+        module_name,
+        src,
+        None,
+        deps,
+        Target::Erlang,
+        TargetSupport::NotEnforced,
+        config,
+    )
+    .expect_err("should infer an error");
+    let error = Error::Type {
+        src: src.into(),
+        path: Utf8PathBuf::from("/src/one/two.gleam"),
+        errors: Vec1::try_from_vec(error).expect("should have at least one error"),
+    };
+    error.pretty_string()
+}
+
 pub fn syntax_error(src: &str) -> String {
     let error = crate::parse::parse_module(src).expect_err("should trigger an error when parsing");
     let error = Error::Parse {
@@ -488,7 +590,7 @@ fn field_map_reorder_test() {
 
     struct Case {
         arity: u32,
-        fields: HashMap<EcoString, u32>,
+        fields: HashMap<Symbol, u32>,
         args: Vec<CallArg<UntypedExpr>>,
         expected_result: Result<(), crate::type_::Error>,
         expected_args: Vec<CallArg<UntypedExpr>>,
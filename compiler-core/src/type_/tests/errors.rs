@@ -337,6 +337,94 @@ fn pipe_mismatch_error() {
     );
 }
 
+#[test]
+fn pipe_mismatch_error_in_later_stage() {
+    assert_module_error!(
+        r#"
+pub fn main() {
+  1
+  |> to_float
+  |> to_string
+  |> length
+}
+
+fn to_float(x: Int) -> Float { todo }
+fn to_string(x: Float) -> String { todo }
+fn length(x: List(a)) -> Int { todo }
+"#
+    );
+}
+
+#[test]
+fn pipe_mismatch_error_with_inserted_argument() {
+    assert_module_error!(
+        r#"
+pub fn main() {
+  "Hello"
+  |> add(1)
+}
+
+fn add(x: Int, y: Int) -> Int { x + y }
+"#
+    );
+}
+
+#[test]
+fn pipe_mismatch_error_in_returned_function() {
+    assert_module_error!(
+        r#"
+pub fn main() {
+  "Hello"
+  |> adder(1)
+}
+
+fn adder(x: Int) -> fn(Int) -> Int { fn(y) { x + y } }
+"#
+    );
+}
+
+#[test]
+fn pipe_mismatch_error_argument_error_is_not_a_pipe_error() {
+    assert_module_error!(
+        r#"
+pub fn main() {
+  1
+  |> add("Hello")
+}
+
+fn add(x: Int, y: Int) -> Int { x + y }
+"#
+    );
+}
+
+#[test]
+fn pipe_mismatch_error_suggests_list_map() {
+    assert_module_error!(
+        r#"
+pub fn main() {
+  [1, 2, 3]
+  |> double
+}
+
+fn double(x: Int) -> Int { x * 2 }
+"#
+    );
+}
+
+#[test]
+fn pipe_mismatch_error_suggests_result_map() {
+    assert_module_error!(
+        r#"
+pub fn main() {
+  Ok(1)
+  |> add(1)
+}
+
+fn add(x: Int, y: Int) -> Int { x + y }
+"#
+    );
+}
+
 #[test]
 fn case_tuple_guard() {
     assert_error!("case #(1, 2, 3) { x if x == #(1, 1.0) -> 1 }");
@@ -590,6 +678,34 @@ pub fn get_title(person: Person) { person.title }"
     );
 }
 
+#[test]
+fn field_not_in_variant_without_fields() {
+    assert_module_error!(
+        "
+pub type Person {
+    Teacher(name: String, title: String)
+    Student(name: String)
+    Nobody
+}
+pub fn get_title(person: Person) { person.title }"
+    );
+}
+
+#[test]
+fn field_not_in_all_variants_of_imported_type() {
+    assert_with_module_error!(
+        (
+            "school",
+            "pub type Person {
+  Teacher(name: String, title: String)
+  Student(name: String)
+}"
+        ),
+        "import school
+pub fn get_title(person: school.Person) { person.title }",
+    );
+}
+
 #[test]
 fn field_not_in_any_variant() {
     assert_module_error!(
@@ -1906,3 +2022,48 @@ fn leak_multiple_private_types() {
         "
     );
 }
+
+#[test]
+fn repeated_unknown_module_is_reported_once() {
+    assert_module_error!(
+        "
+pub fn one() { wibble.one() }
+pub fn two() { wibble.two() }
+pub fn three() { wibble.three() }
+pub fn four() { wibble.four() }
+pub fn five() { wibble.five() }
+pub fn six() { wibble.six() }
+"
+    );
+}
+
+#[test]
+fn repeated_unknown_variable_is_reported_once() {
+    assert_module_error!(
+        "
+pub fn one() { wobble }
+pub fn two() { wobble + 1 }
+pub fn three() { wibble }
+"
+    );
+}
+
+#[test]
+fn errors_beyond_the_limit_are_not_shown() {
+    let src = "
+pub fn one() { wibble }
+pub fn two() { wobble }
+pub fn three() { wubble }
+";
+    let errors =
+        super::compile_module("themodule", src, None, vec![]).expect_err("should infer an error");
+    let error = crate::Error::Type {
+        src: src.into(),
+        path: "/src/one/two.gleam".into(),
+        errors: vec1::Vec1::try_from_vec(errors).expect("should have at least one error"),
+    };
+    let mut buffer = termcolor::Buffer::no_color();
+    error.pretty_limited(&mut buffer, Default::default(), Some(1));
+    let output = String::from_utf8(buffer.into_inner()).expect("utf8 output");
+    insta::assert_snapshot!(output);
+}
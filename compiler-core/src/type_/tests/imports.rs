@@ -1,4 +1,10 @@
-use crate::{assert_infer_with_module, assert_module_error, assert_with_module_error};
+use crate::{
+    assert_infer_with_module, assert_module_error, assert_with_module_error,
+    config::{ImportRule, PackageConfig},
+    type_::tests::{compile_module_with_config, module_error_with_config},
+};
+
+use globset::Glob;
 
 // https://github.com/gleam-lang/gleam/issues/1760
 #[test]
@@ -312,3 +318,41 @@ pub fn main() -> Integer {
 }"
     );
 }
+
+fn import_rules_config() -> PackageConfig {
+    PackageConfig {
+        import_rules: vec![ImportRule {
+            modules: Glob::new("app/domain/*").expect("glob"),
+            must_not_import: vec![
+                Glob::new("app/web/*").expect("glob"),
+                Glob::new("gleam/http").expect("glob"),
+            ],
+        }],
+        ..Default::default()
+    }
+}
+
+#[test]
+fn import_forbidden_by_import_rule() {
+    let output = module_error_with_config(
+        "app/domain/user",
+        "import app/web/router\npub fn main() { router.main() }",
+        vec![("thepackage", "app/web/router", "pub fn main() { 1 }")],
+        import_rules_config(),
+    );
+    insta::assert_snapshot!(output);
+}
+
+#[test]
+fn import_allowed_by_import_rules() {
+    let result = compile_module_with_config(
+        "app/web/router",
+        "import app/domain/user\npub fn main() { user.main() }",
+        None,
+        vec![("thepackage", "app/domain/user", "pub fn main() { 1 }")],
+        crate::build::Target::Erlang,
+        crate::analyse::TargetSupport::NotEnforced,
+        import_rules_config(),
+    );
+    assert!(result.is_ok());
+}
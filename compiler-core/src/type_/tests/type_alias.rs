@@ -137,3 +137,70 @@ fn example(a: X) {
 "#
     );
 }
+
+#[test]
+fn mismatch_is_printed_with_alias() {
+    assert_module_error!(
+        r#"
+type Headers =
+  List(#(String, String))
+
+fn send(headers: Headers) -> Nil {
+  todo
+}
+
+pub fn main() {
+  send([#("accept", 1)])
+}
+"#
+    );
+}
+
+#[test]
+fn mismatch_is_printed_with_parameterised_alias() {
+    assert_module_error!(
+        r#"
+type Pair(a) =
+  #(a, a)
+
+fn swap(pair: Pair(a)) -> Pair(a) {
+  #(pair.1, pair.0)
+}
+
+pub fn main() {
+  swap(#(1, 2.0))
+}
+"#
+    );
+}
+
+#[test]
+fn mismatch_is_printed_with_alias_used_in_both_types() {
+    assert_module_error!(
+        r#"
+type Pair(a) =
+  #(a, a)
+
+pub fn main() {
+  let x: Pair(Int) = #(1, 2)
+  let y: Pair(Float) = x
+  y
+}
+"#
+    );
+}
+
+#[test]
+fn alias_of_named_type_without_arguments_is_not_printed() {
+    assert_module_error!(
+        r#"
+type Id =
+  Int
+
+pub fn main() {
+  let x: Id = "one"
+  x
+}
+"#
+    );
+}
@@ -1,5 +1,6 @@
 use super::*;
 use crate::ast::TodoKind;
+use crate::config::WarningsConfig;
 use crate::{assert_no_warnings, assert_warning, assert_warnings_with_imports};
 
 #[test]
@@ -177,6 +178,55 @@ fn unused_list() {
     );
 }
 
+#[test]
+fn discarded_function_call_value() {
+    assert_warning!(
+        "
+pub fn wibble() { 1 }
+pub fn main() {
+  wibble()
+  Nil
+}"
+    );
+}
+
+#[test]
+fn discarded_case_value() {
+    assert_warning!(
+        r#"
+pub fn main(x) {
+  case x {
+    True -> "wibble"
+    False -> "wobble"
+  }
+  Nil
+}"#
+    );
+}
+
+#[test]
+fn discarded_nil_value_raises_no_warnings() {
+    assert_no_warnings!(
+        "
+pub fn wibble() { Nil }
+pub fn main() {
+  wibble()
+  Nil
+}"
+    );
+}
+
+#[test]
+fn discarded_generic_value_raises_no_warnings() {
+    assert_no_warnings!(
+        "
+pub fn main(f) {
+  f()
+  Nil
+}"
+    );
+}
+
 #[test]
 fn record_update_warnings_test() {
     // Some fields are given in a record update do not emit warnings
@@ -1473,9 +1523,10 @@ fn unused_pipeline_not_ending_with_variant_raises_no_warnings() {
         r#"
 pub type Wibble(a) { Wibble(a) }
 pub fn wibble(a) { a }
+pub fn wobble(_) { Nil }
 
 pub fn main() {
-  1 |> wibble |> wibble
+  1 |> wibble |> wobble
   1
 }
 "#
@@ -1729,7 +1780,7 @@ fn no_unreachable_warning_if_at_least_a_branch_is_reachable() {
           let n = 1
           case n {
             0 -> panic
-            _ -> 1
+            _ -> Nil
           }
           1
         }"
@@ -2012,3 +2063,106 @@ fn warnings_for_matches_on_literal_values_that_are_not_like_an_if_2() {
         "#
     );
 }
+
+const UNQUALIFIED_IMPORTS_MODULE: &str = "
+pub type Wibble { Wibble }
+pub fn map(x) { x }
+pub fn new() { Wibble }
+pub fn wobble() { 1 }
+";
+
+#[test]
+fn too_many_unqualified_imports() {
+    let config = WarningsConfig {
+        max_unqualified_imports: Some(2),
+        ..Default::default()
+    };
+    let src = "
+import wibble.{type Wibble, Wibble, map}
+pub fn main() -> Wibble { map(Wibble) }
+";
+    let output = get_printed_warnings_with_config(
+        src,
+        vec![("thepackage", "wibble", UNQUALIFIED_IMPORTS_MODULE)],
+        config,
+    );
+    insta::assert_snapshot!(insta::internals::AutoName, output, src);
+}
+
+#[test]
+fn unqualified_imports_within_limit_raise_no_warnings() {
+    let config = WarningsConfig {
+        max_unqualified_imports: Some(3),
+        ..Default::default()
+    };
+    let src = "
+import wibble.{type Wibble, Wibble, map}
+pub fn main() -> Wibble { map(Wibble) }
+";
+    let warnings = get_warnings_with_config(
+        src,
+        vec![("thepackage", "wibble", UNQUALIFIED_IMPORTS_MODULE)],
+        config,
+    );
+    assert_eq!(warnings, vec![]);
+}
+
+#[test]
+fn generic_unqualified_import() {
+    let config = WarningsConfig {
+        generic_unqualified_imports: true,
+        ..Default::default()
+    };
+    let src = "
+import wibble.{map, new as new_wibble, wobble}
+pub fn main() { let _ = map(wobble()) new_wibble() }
+";
+    let output = get_printed_warnings_with_config(
+        src,
+        vec![("thepackage", "wibble", UNQUALIFIED_IMPORTS_MODULE)],
+        config,
+    );
+    insta::assert_snapshot!(insta::internals::AutoName, output, src);
+}
+
+#[test]
+fn generic_unqualified_import_raises_no_warnings_by_default() {
+    assert_no_warnings!(
+        ("thepackage", "wibble", "pub fn map(x) { x }"),
+        "
+import wibble.{map}
+pub fn main() { map(1) }
+"
+    );
+}
+
+#[test]
+fn unused_warnings_are_in_source_order() {
+    let src = "
+import wibble.{type Wibble, Wobble, wobble}
+type Private { Private }
+const private = 1
+fn unused() { Nil }
+pub fn main() {
+  let a = 1
+  let b = 2
+  let c = 3
+  let d = 4
+  Nil
+}
+";
+    let deps = || {
+        vec![(
+            "thepackage",
+            "wibble",
+            "pub type Wibble { Wobble } pub fn wobble() { Nil }",
+        )]
+    };
+    let output = get_printed_warnings(src, deps());
+    // The unused entities are kept in hash maps, whose order changes each
+    // time one is made.
+    for _ in 0..10 {
+        assert_eq!(get_printed_warnings(src, deps()), output);
+    }
+    insta::assert_snapshot!(insta::internals::AutoName, output, src);
+}
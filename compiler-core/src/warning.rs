@@ -1,16 +1,20 @@
 use crate::{
-    ast::TodoKind,
-    diagnostic::{self, Diagnostic, Location},
+    ast::{SrcSpan, TodoKind},
+    build::{JavaScriptPlatform, Target},
+    codegen::chunks,
+    diagnostic::{self, catalog, Diagnostic, Location, Theme},
     error::wrap,
     type_::{
         self,
         error::{LiteralCollectionKind, PanicPosition, TodoOrPanic},
         pretty::Printer,
     },
+    Error,
 };
 use camino::Utf8PathBuf;
 use debug_ignore::DebugIgnore;
 use ecow::EcoString;
+use itertools::Itertools;
 use std::sync::atomic::AtomicUsize;
 use std::{
     io::Write,
@@ -151,6 +155,68 @@ pub enum Warning {
     InvalidSource {
         path: Utf8PathBuf,
     },
+    /// The files of a package extracted to the build directory had been
+    /// changed, so the package was extracted again.
+    ModifiedDependency {
+        package: EcoString,
+        files: Vec<Utf8PathBuf>,
+    },
+    /// Generated files in the build directory have been edited since they
+    /// were generated.
+    ModifiedArtefacts {
+        files: Vec<Utf8PathBuf>,
+    },
+    /// A module statically imports a JavaScript entrypoint, so the entrypoint
+    /// is loaded along with the module rather than when it is needed.
+    StaticEntrypointImport {
+        module: EcoString,
+        entrypoint: EcoString,
+    },
+    /// A dependency is written for a JavaScript platform other than the one
+    /// the project is written for.
+    JavaScriptPlatformMismatch {
+        package: EcoString,
+        package_platform: JavaScriptPlatform,
+        platform: JavaScriptPlatform,
+    },
+    /// A module with target specific definitions does not type check for the
+    /// target other than the one it is being compiled for.
+    OtherTargetError {
+        target: Target,
+        error: Box<Error>,
+    },
+    /// A version of a dependency locked in the manifest has been retired on
+    /// Hex since it was locked.
+    RetiredDependency {
+        package: EcoString,
+        version: String,
+        /// The reason given for retiring it, along with any message.
+        reason: String,
+        /// The newest compatible version that has not been retired, if any.
+        replacement: Option<String>,
+    },
+    /// A public definition of a module with target specific definitions has a
+    /// different type on each target.
+    TargetSignatureMismatch {
+        path: Utf8PathBuf,
+        src: EcoString,
+        location: SrcSpan,
+        name: EcoString,
+        target: Target,
+        type_: String,
+        other_target: Target,
+        other_type: String,
+    },
+    /// The config of the root package has a key that Gleam does not know
+    /// about, which is ignored.
+    UnknownConfigKey {
+        path: Utf8PathBuf,
+        src: EcoString,
+        location: SrcSpan,
+        /// The dotted path of the key, such as `javascript.runtme`.
+        key: EcoString,
+        hint: Option<String>,
+    },
 }
 
 impl Warning {
@@ -168,6 +234,148 @@ only lowercase alphanumeric characters or underscores."
                     "Rename `{path}` to be valid, or remove this file from the project source."
                 )),
             },
+            Warning::ModifiedDependency { package, files } => Diagnostic {
+                title: catalog::MODIFIED_DEPENDENCY_TITLE.render(&[]),
+                text: catalog::MODIFIED_DEPENDENCY.render(&[
+                    ("package", package),
+                    (
+                        "files",
+                        &files.iter().map(|path| format!("  - {path}")).join("\n"),
+                    ),
+                ]),
+                level: diagnostic::Level::Warning,
+                location: None,
+                hint: Some(catalog::MODIFIED_DEPENDENCY_HINT.render(&[])),
+            },
+            Warning::ModifiedArtefacts { files } => Diagnostic {
+                title: catalog::MODIFIED_ARTEFACTS_TITLE.render(&[]),
+                text: catalog::MODIFIED_ARTEFACTS.render(&[(
+                    "files",
+                    &files.iter().map(|path| format!("  - {path}")).join("\n"),
+                )]),
+                level: diagnostic::Level::Warning,
+                location: None,
+                hint: Some(catalog::MODIFIED_ARTEFACTS_HINT.render(&[])),
+            },
+            Warning::StaticEntrypointImport { module, entrypoint } => Diagnostic {
+                title: catalog::STATIC_ENTRYPOINT_IMPORT_TITLE.render(&[]),
+                text: catalog::STATIC_ENTRYPOINT_IMPORT
+                    .render(&[("module", module), ("entrypoint", entrypoint)]),
+                level: diagnostic::Level::Warning,
+                location: None,
+                hint: Some(
+                    catalog::STATIC_ENTRYPOINT_IMPORT_HINT
+                        .render(&[("loader", &chunks::loader_name(entrypoint))]),
+                ),
+            },
+            Warning::JavaScriptPlatformMismatch {
+                package,
+                package_platform,
+                platform,
+            } => Diagnostic {
+                title: catalog::JAVASCRIPT_PLATFORM_MISMATCH_TITLE.render(&[]),
+                text: catalog::JAVASCRIPT_PLATFORM_MISMATCH.render(&[
+                    ("package", package),
+                    ("package_platform", package_platform),
+                    ("platform", platform),
+                ]),
+                level: diagnostic::Level::Warning,
+                location: None,
+                hint: None,
+            },
+            Warning::OtherTargetError { target, error } => {
+                let diagnostic = error.to_diagnostics().into_iter().next();
+                let diagnostic = diagnostic.unwrap_or_else(|| Diagnostic {
+                    title: String::new(),
+                    text: error.to_string(),
+                    level: diagnostic::Level::Warning,
+                    location: None,
+                    hint: None,
+                });
+                Diagnostic {
+                    title: catalog::OTHER_TARGET_ERROR_TITLE
+                        .render(&[("title", &diagnostic.title), ("target", target)]),
+                    text: catalog::OTHER_TARGET_ERROR
+                        .render(&[("text", &diagnostic.text), ("target", target)]),
+                    level: diagnostic::Level::Warning,
+                    ..diagnostic
+                }
+            }
+            Warning::RetiredDependency {
+                package,
+                version,
+                reason,
+                replacement,
+            } => Diagnostic {
+                title: catalog::RETIRED_DEPENDENCY_TITLE.render(&[]),
+                text: catalog::RETIRED_DEPENDENCY.render(&[
+                    ("package", package),
+                    ("version", version),
+                    ("reason", reason),
+                ]),
+                level: diagnostic::Level::Warning,
+                location: None,
+                hint: Some(match replacement {
+                    Some(replacement) => catalog::RETIRED_DEPENDENCY_HINT
+                        .render(&[("package", package), ("replacement", replacement)]),
+                    None => catalog::RETIRED_DEPENDENCY_WITHOUT_REPLACEMENT_HINT.render(&[]),
+                }),
+            },
+            Warning::TargetSignatureMismatch {
+                path,
+                src,
+                location,
+                name,
+                target,
+                type_,
+                other_target,
+                other_type,
+            } => Diagnostic {
+                title: catalog::TARGET_SIGNATURE_MISMATCH_TITLE.render(&[]),
+                text: catalog::TARGET_SIGNATURE_MISMATCH.render(&[
+                    ("name", name),
+                    ("target", target),
+                    ("type", type_),
+                    ("other_target", other_target),
+                    ("other_type", other_type),
+                ]),
+                level: diagnostic::Level::Warning,
+                location: Some(Location {
+                    src: src.clone(),
+                    path: path.to_path_buf(),
+                    label: diagnostic::Label {
+                        text: None,
+                        span: *location,
+                    },
+                    extra_labels: vec![],
+                }),
+                hint: Some(catalog::TARGET_SIGNATURE_MISMATCH_HINT.render(&[])),
+            },
+            Warning::UnknownConfigKey {
+                path,
+                src,
+                location,
+                key,
+                hint,
+            } => Diagnostic {
+                title: catalog::UNKNOWN_CONFIG_KEY_TITLE
+                    .render(&[("file", &path.file_name().unwrap_or("gleam.toml"))]),
+                text: catalog::UNKNOWN_CONFIG_KEY.render(&[("key", key)]),
+                level: diagnostic::Level::Warning,
+                location: Some(Location {
+                    src: src.clone(),
+                    path: path.to_path_buf(),
+                    label: diagnostic::Label {
+                        text: Some(catalog::UNKNOWN_CONFIG_KEY_LABEL.render(&[])),
+                        span: *location,
+                    },
+                    extra_labels: vec![],
+                }),
+                hint: Some(
+                    hint.clone()
+                        .unwrap_or_else(|| catalog::UNKNOWN_CONFIG_KEY_HINT.render(&[])),
+                ),
+            },
             Self::Type { path, warning, src } => match warning {
                 type_::Warning::Todo {
                     kind,
@@ -692,6 +900,86 @@ can already tell which branch is going to match with this value.",
                     }),
                 },
 
+                type_::Warning::DiscardedValue { location, typ } => {
+                    let mut printer = Printer::new();
+                    Diagnostic {
+                        title: "Unused value".into(),
+                        text: format!(
+                            "The value of this expression is discarded. It has this type:\n\n{}\n",
+                            printer.pretty_print(typ, 4)
+                        ),
+                        hint: Some(
+                            "If you are sure you don't need it you can assign it to `_`.".into(),
+                        ),
+                        level: diagnostic::Level::Warning,
+                        location: Some(Location {
+                            path: path.to_path_buf(),
+                            src: src.clone(),
+                            label: diagnostic::Label {
+                                text: Some("This value is never used".into()),
+                                span: *location,
+                            },
+                            extra_labels: Vec::new(),
+                        }),
+                    }
+                }
+
+                type_::Warning::TooManyUnqualifiedImports {
+                    location,
+                    module,
+                    count,
+                    max,
+                } => Diagnostic {
+                    title: "Too many unqualified imports".into(),
+                    text: wrap(&format!(
+                        "{count} types and values are imported unqualified from \
+`{module}`, but this package allows at most {max}. Code using many unqualified \
+imports is harder to read as it is not clear where each name comes from."
+                    )),
+                    hint: Some(format!(
+                        "Refer to some of them through the module instead, as in `{}.name`.",
+                        module.rsplit('/').next().unwrap_or(module)
+                    )),
+                    level: diagnostic::Level::Warning,
+                    location: Some(Location {
+                        path: path.to_path_buf(),
+                        src: src.clone(),
+                        label: diagnostic::Label {
+                            text: None,
+                            span: *location,
+                        },
+                        extra_labels: Vec::new(),
+                    }),
+                },
+
+                type_::Warning::GenericUnqualifiedImport {
+                    location,
+                    module,
+                    name,
+                } => {
+                    let module_name = module.rsplit('/').next().unwrap_or(module);
+                    Diagnostic {
+                        title: "Generic unqualified import".into(),
+                        text: wrap(&format!(
+                            "The name `{name}` is used by many modules, so it is not \
+clear which one it comes from when imported unqualified."
+                        )),
+                        hint: Some(format!(
+                            "Refer to it through the module instead, as `{module_name}.{name}`."
+                        )),
+                        level: diagnostic::Level::Warning,
+                        location: Some(Location {
+                            path: path.to_path_buf(),
+                            src: src.clone(),
+                            label: diagnostic::Label {
+                                text: Some(format!("This is imported from `{module}`")),
+                                span: *location,
+                            },
+                            extra_labels: Vec::new(),
+                        }),
+                    }
+                }
+
                 type_::Warning::InternalTypeLeak { location, leaked } => {
                     let mut printer = Printer::new();
 
@@ -834,16 +1122,16 @@ Your code will crash before reaching this point.",
         }
     }
 
-    pub fn pretty(&self, buffer: &mut Buffer) {
+    pub fn pretty(&self, buffer: &mut Buffer, theme: Theme) {
         buffer
             .write_all(b"\n")
             .expect("error pretty buffer write space before");
-        self.to_diagnostic().write(buffer);
+        self.to_diagnostic().write(buffer, theme);
     }
 
     pub fn to_pretty_string(&self) -> String {
         let mut nocolor = Buffer::no_color();
-        self.pretty(&mut nocolor);
+        self.pretty(&mut nocolor, Theme::default());
         String::from_utf8(nocolor.into_inner()).expect("Warning printing produced invalid utf8")
     }
 }
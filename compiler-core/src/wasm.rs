@@ -0,0 +1,1426 @@
+//! An experimental backend that compiles Gleam to a WebAssembly module in the
+//! text format, using the garbage collection proposal for Gleam's values. It
+//! is used by `gleam build --target wasm --unstable`.
+//!
+//! All values have the type `(ref null eq)` so that generic functions can work
+//! on values of any type:
+//!
+//! - An `Int` is a `$int` struct holding an `i64`, and a `Float` is a `$float`
+//!   struct holding an `f64`.
+//! - A `String` is a `$string` array of the bytes of its UTF-8 encoding.
+//! - `True` and `False` are the `i31` values 1 and 0, and `Nil` is 0.
+//! - The empty list is null, and other lists are `$cons` structs holding the
+//!   first element and the rest of the list.
+//! - A record is a `$record_N` struct holding the index of its constructor
+//!   within its custom type followed by its `N` fields. Each `$record_N` type
+//!   is a subtype of `$record_{N-1}`, so a field can be read from any record
+//!   that has it. Tuples are records with the constructor index 0.
+//!
+//! Only part of the language is supported so far. Anonymous functions,
+//! functions used as values, bit arrays, record updates, clause guards, string
+//! prefix patterns, and external functions are errors.
+
+#[cfg(test)]
+mod tests;
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt::Write;
+
+use ecow::EcoString;
+use itertools::Itertools;
+
+use crate::{
+    ast::{
+        ArgNames, AssignmentKind, BinOp, Clause, Definition, Pattern, SrcSpan, Statement,
+        TypedAssignment, TypedConstant, TypedExpr, TypedFunction, TypedPattern, TypedStatement,
+    },
+    build::{Module, Origin},
+    error::WasmUnsupportedDetails,
+    strings::convert_string_escape_chars,
+    type_::{prelude::PRELUDE_MODULE_NAME, ModuleValueConstructor, Type, ValueConstructorVariant},
+    Error, Result,
+};
+
+/// Compiles the public functions of the root package's modules, and the
+/// functions they call, to a WebAssembly module in the text format.
+///
+/// The modules of dependencies must be freshly compiled so that their typed
+/// ASTs are available, as they are not kept in the build cache.
+pub fn module(root: &[Module], dependencies: &[Module]) -> Result<String> {
+    let mut program = Program::new(root.iter().chain(dependencies));
+    let mut exports = String::new();
+    for module in root.iter().filter(|module| module.origin == Origin::Src) {
+        for function in public_functions(module) {
+            program.enqueue(&module.name, &function.name);
+            exports.push_str(&export(module, function));
+        }
+    }
+    program.compile_queued()?;
+    Ok(program.into_wat(&exports))
+}
+
+fn public_functions(module: &Module) -> impl Iterator<Item = &TypedFunction> {
+    module
+        .ast
+        .definitions
+        .iter()
+        .filter_map(|definition| match definition {
+            Definition::Function(function) if function.publicity.is_public() => Some(function),
+            _ => None,
+        })
+}
+
+/// Functions of which the arguments and return value are all ints, floats, or
+/// bools are exported with those as plain numbers so they are easy to call
+/// from the host. Other functions are exported as they are.
+fn export(module: &Module, function: &TypedFunction) -> String {
+    let name = format!("{}.{}", module.name, function.name);
+    let arguments: Option<Vec<_>> = function
+        .arguments
+        .iter()
+        .map(|argument| Scalar::of(&argument.type_))
+        .collect();
+    let (Some(arguments), Some(result)) = (arguments, Scalar::of(&function.return_type)) else {
+        return format!("  (export \"{name}\" (func ${name}))\n");
+    };
+
+    let mut wat = format!("  (func (export \"{name}\")");
+    for argument in &arguments {
+        write!(wat, " (param {})", argument.value_type()).expect("write wat");
+    }
+    writeln!(wat, " (result {})", result.value_type()).expect("write wat");
+    for (index, argument) in arguments.iter().enumerate() {
+        writeln!(wat, "    local.get {index}").expect("write wat");
+        for instruction in argument.boxing() {
+            writeln!(wat, "    {instruction}").expect("write wat");
+        }
+    }
+    writeln!(wat, "    call ${name}").expect("write wat");
+    for instruction in result.unboxing() {
+        writeln!(wat, "    {instruction}").expect("write wat");
+    }
+    wat.push_str("  )\n");
+    wat
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Scalar {
+    Int,
+    Float,
+    Bool,
+}
+
+impl Scalar {
+    fn of(type_: &Type) -> Option<Self> {
+        if type_.is_int() {
+            Some(Self::Int)
+        } else if type_.is_float() {
+            Some(Self::Float)
+        } else if type_.is_bool() {
+            Some(Self::Bool)
+        } else {
+            None
+        }
+    }
+
+    fn value_type(self) -> &'static str {
+        match self {
+            Self::Int => "i64",
+            Self::Float => "f64",
+            Self::Bool => "i32",
+        }
+    }
+
+    fn boxing(self) -> &'static [&'static str] {
+        match self {
+            Self::Int => &["struct.new $int"],
+            Self::Float => &["struct.new $float"],
+            Self::Bool => &["ref.i31"],
+        }
+    }
+
+    fn unboxing(self) -> &'static [&'static str] {
+        match self {
+            Self::Int => &["ref.cast (ref $int)", "struct.get $int 0"],
+            Self::Float => &["ref.cast (ref $float)", "struct.get $float 0"],
+            Self::Bool => &["ref.cast (ref i31)", "i31.get_u"],
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Program<'a> {
+    modules: HashMap<&'a str, &'a Module>,
+    /// The index of each constructor within its custom type, by the module
+    /// that defines the type and the name of the constructor.
+    constructor_indices: HashMap<(&'a str, &'a str), u16>,
+    /// The bytes of each string literal, stored in a data segment.
+    strings: Vec<Vec<u8>>,
+    /// The largest number of fields of the records used.
+    max_arity: usize,
+    queue: VecDeque<(EcoString, EcoString)>,
+    queued: HashSet<(EcoString, EcoString)>,
+    functions: String,
+}
+
+impl<'a> Program<'a> {
+    fn new(modules: impl Iterator<Item = &'a Module>) -> Self {
+        let mut constructor_indices = HashMap::from([
+            ((PRELUDE_MODULE_NAME, "Ok"), 0),
+            ((PRELUDE_MODULE_NAME, "Error"), 1),
+        ]);
+        let modules: HashMap<_, _> = modules
+            .map(|module| (module.name.as_str(), module))
+            .collect();
+        for module in modules.values().copied() {
+            for definition in &module.ast.definitions {
+                let Definition::CustomType(type_) = definition else {
+                    continue;
+                };
+                for (index, constructor) in type_.constructors.iter().enumerate() {
+                    let index = u16::try_from(index).expect("constructor index");
+                    let _ = constructor_indices
+                        .insert((module.name.as_str(), constructor.name.as_str()), index);
+                }
+            }
+        }
+        Self {
+            modules,
+            constructor_indices,
+            strings: Vec::new(),
+            max_arity: 0,
+            queue: VecDeque::new(),
+            queued: HashSet::new(),
+            functions: String::new(),
+        }
+    }
+
+    fn enqueue(&mut self, module: &str, function: &str) {
+        let key = (EcoString::from(module), EcoString::from(function));
+        if self.queued.insert(key.clone()) {
+            self.queue.push_back(key);
+        }
+    }
+
+    fn compile_queued(&mut self) -> Result<()> {
+        while let Some((module_name, function_name)) = self.queue.pop_front() {
+            let module = *self
+                .modules
+                .get(module_name.as_str())
+                .expect("queued function's module");
+            let function = module
+                .ast
+                .definitions
+                .iter()
+                .find_map(|definition| match definition {
+                    Definition::Function(function) if function.name == function_name => {
+                        Some(function)
+                    }
+                    _ => None,
+                })
+                .expect("queued function");
+            let wat = Generator::new(self, module).function(function)?;
+            self.functions.push_str(&wat);
+        }
+        Ok(())
+    }
+
+    /// The index of the data segment holding a string.
+    fn string(&mut self, value: &str) -> (usize, usize) {
+        let bytes = value.as_bytes();
+        let index = match self.strings.iter().position(|string| string == bytes) {
+            Some(index) => index,
+            None => {
+                self.strings.push(bytes.to_vec());
+                self.strings.len() - 1
+            }
+        };
+        (index, bytes.len())
+    }
+
+    fn record_type(&mut self, arity: usize) -> String {
+        self.max_arity = self.max_arity.max(arity);
+        format!("$record_{arity}")
+    }
+
+    fn into_wat(self, exports: &str) -> String {
+        let mut wat = String::from(
+            "(module
+  (type $int (struct (field i64)))
+  (type $float (struct (field f64)))
+  (type $string (array (mut i8)))
+  (type $cons (struct (field (ref null eq)) (field (ref null eq))))
+  (type $record_0 (sub (struct (field i32))))
+",
+        );
+        for arity in 1..=self.max_arity {
+            let fields = " (field (ref null eq))".repeat(arity);
+            writeln!(
+                wat,
+                "  (type $record_{arity} (sub $record_{previous} (struct (field i32){fields})))",
+                previous = arity - 1,
+            )
+            .expect("write wat");
+        }
+        for (index, string) in self.strings.iter().enumerate() {
+            writeln!(wat, "  (data $string_{index} \"{}\")", data_string(string))
+                .expect("write wat");
+        }
+        wat.push_str(exports);
+        wat.push_str(&self.functions);
+        wat.push_str(RUNTIME);
+        wat.push_str(&equal_function(self.max_arity));
+        wat.push_str(")\n");
+        wat
+    }
+}
+
+/// Helper functions used by the generated code.
+const RUNTIME: &str = "  (func $gleam.string_concat (param $a (ref null eq)) (param $b (ref null eq)) (result (ref null eq))
+    (local $left (ref null $string))
+    (local $right (ref null $string))
+    (local $result (ref null $string))
+    (local.set $left (ref.cast (ref $string) (local.get $a)))
+    (local.set $right (ref.cast (ref $string) (local.get $b)))
+    (local.set $result
+      (array.new_default $string
+        (i32.add (array.len (local.get $left)) (array.len (local.get $right)))))
+    (array.copy $string $string
+      (local.get $result) (i32.const 0)
+      (local.get $left) (i32.const 0) (array.len (local.get $left)))
+    (array.copy $string $string
+      (local.get $result) (array.len (local.get $left))
+      (local.get $right) (i32.const 0) (array.len (local.get $right)))
+    (local.get $result)
+  )
+  (func $gleam.string_equal (param $a (ref null eq)) (param $b (ref null eq)) (result i32)
+    (local $left (ref null $string))
+    (local $right (ref null $string))
+    (local $index i32)
+    (local.set $left (ref.cast (ref $string) (local.get $a)))
+    (local.set $right (ref.cast (ref $string) (local.get $b)))
+    (if (i32.ne (array.len (local.get $left)) (array.len (local.get $right)))
+      (then (return (i32.const 0))))
+    (block $done
+      (loop $next
+        (br_if $done (i32.eq (local.get $index) (array.len (local.get $left))))
+        (if (i32.ne
+              (array.get_u $string (local.get $left) (local.get $index))
+              (array.get_u $string (local.get $right) (local.get $index)))
+          (then (return (i32.const 0))))
+        (local.set $index (i32.add (local.get $index) (i32.const 1)))
+        (br $next)))
+    (i32.const 1)
+  )
+";
+
+/// The structural equality of two values of the same type. Records are
+/// compared field by field, for each number of fields used in the program.
+fn equal_function(max_arity: usize) -> String {
+    let mut wat = String::from(
+        "  (func $gleam.equal (param $a (ref null eq)) (param $b (ref null eq)) (result i32)
+    (if (ref.eq (local.get $a) (local.get $b))
+      (then (return (i32.const 1))))
+    (if (i32.or (ref.is_null (local.get $a)) (ref.is_null (local.get $b)))
+      (then (return (i32.const 0))))
+    (if (ref.test (ref i31) (local.get $a))
+      (then (return (i32.const 0))))
+    (if (ref.test (ref $int) (local.get $a))
+      (then (return (i64.eq
+        (struct.get $int 0 (ref.cast (ref $int) (local.get $a)))
+        (struct.get $int 0 (ref.cast (ref $int) (local.get $b)))))))
+    (if (ref.test (ref $float) (local.get $a))
+      (then (return (f64.eq
+        (struct.get $float 0 (ref.cast (ref $float) (local.get $a)))
+        (struct.get $float 0 (ref.cast (ref $float) (local.get $b)))))))
+    (if (ref.test (ref $string) (local.get $a))
+      (then (return (call $gleam.string_equal (local.get $a) (local.get $b)))))
+    (if (ref.test (ref $cons) (local.get $a))
+      (then
+        (if (i32.eqz (call $gleam.equal
+              (struct.get $cons 0 (ref.cast (ref $cons) (local.get $a)))
+              (struct.get $cons 0 (ref.cast (ref $cons) (local.get $b)))))
+          (then (return (i32.const 0))))
+        (return (call $gleam.equal
+          (struct.get $cons 1 (ref.cast (ref $cons) (local.get $a)))
+          (struct.get $cons 1 (ref.cast (ref $cons) (local.get $b)))))))
+    (if (i32.ne
+          (struct.get $record_0 0 (ref.cast (ref $record_0) (local.get $a)))
+          (struct.get $record_0 0 (ref.cast (ref $record_0) (local.get $b))))
+      (then (return (i32.const 0))))
+",
+    );
+    // Records with the same constructor have the same number of fields, so
+    // the first test that passes, starting from the most fields, is exact.
+    for arity in (1..=max_arity).rev() {
+        let fields = (1..=arity)
+            .map(|field| {
+                format!(
+                    "
+        (call $gleam.equal
+          (struct.get $record_{arity} {field} (ref.cast (ref $record_{arity}) (local.get $a)))
+          (struct.get $record_{arity} {field} (ref.cast (ref $record_{arity}) (local.get $b))))"
+                )
+            })
+            .reduce(|left, right| format!("\n        (i32.and{left}{right})"))
+            .unwrap_or_default();
+        writeln!(
+            wat,
+            "    (if (ref.test (ref $record_{arity}) (local.get $a))
+      (then (return{fields})))"
+        )
+        .expect("write wat");
+    }
+    wat.push_str("    (i32.const 1)\n  )\n");
+    wat
+}
+
+/// Writes bytes as the contents of a string in the text format.
+fn data_string(bytes: &[u8]) -> String {
+    let mut string = String::with_capacity(bytes.len());
+    for byte in bytes {
+        match byte {
+            b'"' | b'\\' => write!(string, "\\{}", *byte as char),
+            0x20..=0x7e => write!(string, "{}", *byte as char),
+            _ => write!(string, "\\{byte:02x}"),
+        }
+        .expect("write string");
+    }
+    string
+}
+
+/// Parses the value of an int literal, which may have underscores and a base
+/// prefix.
+fn int_value(value: &str) -> Option<i64> {
+    let value = value.replace('_', "");
+    let (negative, digits) = match value.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, value.as_str()),
+    };
+    let (radix, digits) = match digits.get(..2) {
+        Some("0x" | "0X") => (16, digits.get(2..)?),
+        Some("0o" | "0O") => (8, digits.get(2..)?),
+        Some("0b" | "0B") => (2, digits.get(2..)?),
+        _ => (10, digits),
+    };
+    let magnitude = i128::from(u64::from_str_radix(digits, radix).ok()?);
+    i64::try_from(if negative { -magnitude } else { magnitude }).ok()
+}
+
+fn float_value(value: &str) -> Option<f64> {
+    value.replace('_', "").parse().ok()
+}
+
+/// The parts of a function being generated, which keeps track of its locals.
+#[derive(Debug)]
+struct Generator<'a, 'program> {
+    program: &'program mut Program<'a>,
+    module: &'a Module,
+    /// The names of the parameters and locals declared so far.
+    names: HashSet<String>,
+    locals: Vec<(String, &'static str)>,
+    /// The local holding each variable in scope. Later variables shadow
+    /// earlier ones with the same name.
+    scope: Vec<(EcoString, String)>,
+    labels: usize,
+    code: String,
+    depth: usize,
+}
+
+/// The variables bound by a pattern. The alternative patterns of a clause bind
+/// the same variables, so they share their locals.
+#[derive(Debug, Default)]
+struct Bindings {
+    locals: Vec<(EcoString, String)>,
+}
+
+impl<'a, 'program> Generator<'a, 'program> {
+    fn new(program: &'program mut Program<'a>, module: &'a Module) -> Self {
+        Self {
+            program,
+            module,
+            names: HashSet::new(),
+            locals: Vec::new(),
+            scope: Vec::new(),
+            labels: 0,
+            code: String::new(),
+            depth: 1,
+        }
+    }
+
+    fn function(mut self, function: &'a TypedFunction) -> Result<String> {
+        let name = format!("${}.{}", self.module.name, function.name);
+        if is_external(function) {
+            return Err(self.unsupported("external functions".into(), function.location));
+        }
+
+        let mut parameters = String::new();
+        for argument in &function.arguments {
+            let parameter = match &argument.names {
+                ArgNames::Named { name } | ArgNames::NamedLabelled { name, .. } => {
+                    let parameter = self.name(name);
+                    self.scope.push((name.clone(), parameter.clone()));
+                    parameter
+                }
+                ArgNames::Discard { .. } | ArgNames::LabelledDiscard { .. } => self.name("_"),
+            };
+            write!(parameters, " (param {parameter} (ref null eq))").expect("write wat");
+        }
+        self.statements(&function.body)?;
+
+        let mut wat = format!("  (func {name}{parameters} (result (ref null eq))\n");
+        for (local, type_) in &self.locals {
+            writeln!(wat, "    (local {local} {type_})").expect("write wat");
+        }
+        wat.push_str(&self.code);
+        wat.push_str("  )\n");
+        Ok(wat)
+    }
+
+    fn unsupported(&self, feature: EcoString, location: SrcSpan) -> Error {
+        Error::WasmUnsupported {
+            details: Box::new(WasmUnsupportedDetails {
+                feature,
+                location,
+                path: self.module.input_path.clone(),
+                src: self.module.code.clone(),
+            }),
+        }
+    }
+
+    /// A name for a parameter or local that no other one of the function has.
+    fn name(&mut self, name: &str) -> String {
+        let mut local = format!("${name}");
+        let mut suffix = 1;
+        while self.names.contains(&local) {
+            suffix += 1;
+            local = format!("${name}.{suffix}");
+        }
+        let _ = self.names.insert(local.clone());
+        local
+    }
+
+    fn local(&mut self, name: &str, type_: &'static str) -> String {
+        let local = self.name(name);
+        self.locals.push((local.clone(), type_));
+        local
+    }
+
+    fn label(&mut self, name: &str) -> String {
+        self.labels += 1;
+        format!("${name}.{}", self.labels)
+    }
+
+    fn line(&mut self, instruction: impl AsRef<str>) {
+        for _ in 0..=self.depth {
+            self.code.push_str("  ");
+        }
+        self.code.push_str(instruction.as_ref());
+        self.code.push('\n');
+    }
+
+    fn open(&mut self, instruction: impl AsRef<str>) {
+        self.line(instruction);
+        self.depth += 1;
+    }
+
+    fn close(&mut self) {
+        self.depth -= 1;
+        self.line("end");
+    }
+
+    fn else_(&mut self) {
+        self.depth -= 1;
+        self.line("else");
+        self.depth += 1;
+    }
+
+    fn statements(&mut self, statements: &'a [TypedStatement]) -> Result<()> {
+        let scope = self.scope.len();
+        for (index, statement) in statements.iter().enumerate() {
+            let is_last = index + 1 == statements.len();
+            match statement {
+                Statement::Expression(expression) => {
+                    self.expression(expression)?;
+                    if !is_last {
+                        self.line("drop");
+                    }
+                }
+                Statement::Assignment(assignment) => {
+                    let value = self.assignment(assignment)?;
+                    if is_last {
+                        self.line(format!("local.get {value}"));
+                    }
+                }
+                Statement::Use(use_) => {
+                    return Err(self.unsupported("use expressions".into(), use_.location))
+                }
+            }
+        }
+        self.scope.truncate(scope);
+        Ok(())
+    }
+
+    /// Binds the variables of an assignment's pattern, returning the local
+    /// holding the assigned value.
+    fn assignment(&mut self, assignment: &'a TypedAssignment) -> Result<String> {
+        self.expression(&assignment.value)?;
+        if let Pattern::Variable { name, .. } = &assignment.pattern {
+            let local = self.local(name, "(ref null eq)");
+            self.line(format!("local.set {local}"));
+            self.scope.push((name.clone(), local.clone()));
+            return Ok(local);
+        }
+        let value = self.local("value", "(ref null eq)");
+        self.line(format!("local.set {value}"));
+
+        // A `let` pattern always matches, and a `let assert` pattern that
+        // doesn't match panics.
+        let matched = self.label("matched");
+        let failed = self.label("failed");
+        let mut bindings = Bindings::default();
+        self.open(format!("block {matched}"));
+        self.open(format!("block {failed}"));
+        self.pattern(&assignment.pattern, &value, &failed, &mut bindings)?;
+        self.line(format!("br {matched}"));
+        self.close();
+        match assignment.kind {
+            AssignmentKind::Let | AssignmentKind::Assert { .. } => self.line("unreachable"),
+        }
+        self.close();
+        self.scope.extend(bindings.locals);
+        Ok(value)
+    }
+
+    fn expression(&mut self, expression: &'a TypedExpr) -> Result<()> {
+        match expression {
+            TypedExpr::Int {
+                value, location, ..
+            } => self.int(value, *location),
+            TypedExpr::Float {
+                value, location, ..
+            } => self.float(value, *location),
+            TypedExpr::String { value, .. } => {
+                self.string(value);
+                Ok(())
+            }
+
+            TypedExpr::Block { statements, .. } => self.statements(statements),
+
+            TypedExpr::Pipeline {
+                assignments,
+                finally,
+                ..
+            } => {
+                let scope = self.scope.len();
+                for assignment in assignments {
+                    let _ = self.assignment(assignment)?;
+                }
+                self.expression(finally)?;
+                self.scope.truncate(scope);
+                Ok(())
+            }
+
+            TypedExpr::Var {
+                constructor,
+                name,
+                location,
+            } => match &constructor.variant {
+                ValueConstructorVariant::LocalVariable { .. } => {
+                    let local = self
+                        .scope
+                        .iter()
+                        .rev()
+                        .find(|(variable, _)| variable == name)
+                        .map(|(_, local)| local.clone())
+                        .expect("local variable in scope");
+                    self.line(format!("local.get {local}"));
+                    Ok(())
+                }
+                ValueConstructorVariant::ModuleConstant { literal, .. }
+                | ValueConstructorVariant::LocalConstant { literal } => self.constant(literal),
+                ValueConstructorVariant::Record {
+                    name,
+                    arity: 0,
+                    constructor_index,
+                    ..
+                } => self.record::<&TypedExpr>(name, &constructor.type_, *constructor_index, &[]),
+                ValueConstructorVariant::ModuleFn { .. }
+                | ValueConstructorVariant::Record { .. } => {
+                    Err(self.unsupported("functions used as values".into(), *location))
+                }
+            },
+
+            TypedExpr::ModuleSelect {
+                constructor,
+                location,
+                ..
+            } => match constructor {
+                ModuleValueConstructor::Constant { literal, .. } => self.constant(literal),
+                ModuleValueConstructor::Record {
+                    name,
+                    arity: 0,
+                    type_,
+                    ..
+                } => {
+                    let index = self.constructor_index(type_, name, *location)?;
+                    self.record::<&TypedExpr>(name, type_, index, &[])
+                }
+                ModuleValueConstructor::Record { .. } | ModuleValueConstructor::Fn { .. } => {
+                    Err(self.unsupported("functions used as values".into(), *location))
+                }
+            },
+
+            TypedExpr::List { elements, tail, .. } => {
+                // Each element is pushed in order, then the lists are built
+                // from the end.
+                for element in elements {
+                    self.expression(element)?;
+                }
+                match tail {
+                    Some(tail) => self.expression(tail)?,
+                    None => self.line("ref.null none"),
+                }
+                for _ in elements {
+                    self.line("struct.new $cons");
+                }
+                Ok(())
+            }
+
+            TypedExpr::Tuple { elems, .. } => {
+                self.line("i32.const 0");
+                for element in elems {
+                    self.expression(element)?;
+                }
+                let type_ = self.program.record_type(elems.len());
+                self.line(format!("struct.new {type_}"));
+                Ok(())
+            }
+
+            TypedExpr::Call {
+                fun,
+                args,
+                location,
+                ..
+            } => self.call(fun, args.iter().map(|arg| &arg.value), *location),
+
+            TypedExpr::BinOp {
+                name, left, right, ..
+            } => self.bin_op(*name, left, right),
+
+            TypedExpr::Case {
+                subjects, clauses, ..
+            } => self.case(subjects, clauses),
+
+            TypedExpr::RecordAccess { index, record, .. } => {
+                self.expression(record)?;
+                self.field(*index as usize + 1);
+                Ok(())
+            }
+
+            TypedExpr::TupleIndex { index, tuple, .. } => {
+                self.expression(tuple)?;
+                self.field(*index as usize + 1);
+                Ok(())
+            }
+
+            TypedExpr::Todo { .. } | TypedExpr::Panic { .. } => {
+                self.line("unreachable");
+                Ok(())
+            }
+
+            TypedExpr::NegateBool { value, .. } => {
+                self.expression(value)?;
+                self.line("ref.cast (ref i31)");
+                self.line("i31.get_u");
+                self.line("i32.eqz");
+                self.line("ref.i31");
+                Ok(())
+            }
+
+            TypedExpr::NegateInt { value, .. } => {
+                self.line("i64.const 0");
+                self.unboxed(value, Scalar::Int)?;
+                self.line("i64.sub");
+                self.line("struct.new $int");
+                Ok(())
+            }
+
+            TypedExpr::Fn { location, .. } => {
+                Err(self.unsupported("anonymous functions".into(), *location))
+            }
+            TypedExpr::BitArray { location, .. } => {
+                Err(self.unsupported("bit arrays".into(), *location))
+            }
+            TypedExpr::RecordUpdate { location, .. } => {
+                Err(self.unsupported("record updates".into(), *location))
+            }
+        }
+    }
+
+    fn int(&mut self, value: &str, location: SrcSpan) -> Result<()> {
+        let value = self.int_const(value, location)?;
+        self.line(value);
+        self.line("struct.new $int");
+        Ok(())
+    }
+
+    fn int_const(&self, value: &str, location: SrcSpan) -> Result<String> {
+        match int_value(value) {
+            Some(value) => Ok(format!("i64.const {value}")),
+            None => Err(self.unsupported("ints that don't fit in 64 bits".into(), location)),
+        }
+    }
+
+    fn float(&mut self, value: &str, location: SrcSpan) -> Result<()> {
+        let value = self.float_const(value, location)?;
+        self.line(value);
+        self.line("struct.new $float");
+        Ok(())
+    }
+
+    fn float_const(&self, value: &str, location: SrcSpan) -> Result<String> {
+        match float_value(value) {
+            Some(value) => Ok(format!("f64.const {value:?}")),
+            None => Err(self.unsupported("floats that don't fit in 64 bits".into(), location)),
+        }
+    }
+
+    fn boolean(&mut self, value: bool) {
+        self.line(format!("i32.const {}", u8::from(value)));
+        self.line("ref.i31");
+    }
+
+    fn string(&mut self, value: &EcoString) {
+        let (index, length) = self.program.string(&convert_string_escape_chars(value));
+        self.line("i32.const 0");
+        self.line(format!("i32.const {length}"));
+        self.line(format!("array.new_data $string $string_{index}"));
+    }
+
+    fn unbox(&mut self, scalar: Scalar) {
+        for instruction in scalar.unboxing() {
+            self.line(instruction);
+        }
+    }
+
+    /// Reads a field of the record on the stack, counting its constructor
+    /// index as field 0.
+    fn field(&mut self, field: usize) {
+        let type_ = self.program.record_type(field);
+        self.line(format!("ref.cast (ref {type_})"));
+        self.line(format!("struct.get {type_} {field}"));
+    }
+
+    fn constructor_index(&self, type_: &Type, name: &str, location: SrcSpan) -> Result<u16> {
+        let type_ = type_.return_type().unwrap_or_else(|| type_.clone().into());
+        type_
+            .named_type_name()
+            .and_then(|(module, _)| {
+                self.program
+                    .constructor_indices
+                    .get(&(module.as_ref(), name))
+                    .copied()
+            })
+            .ok_or_else(|| {
+                self.unsupported(
+                    "records of types from modules that were not compiled".into(),
+                    location,
+                )
+            })
+    }
+
+    /// Builds a record. Bools and `Nil` are records in Gleam but are
+    /// represented as `i31` values.
+    fn record<Field>(
+        &mut self,
+        name: &str,
+        type_: &Type,
+        index: u16,
+        fields: &[Field],
+    ) -> Result<()>
+    where
+        Field: RecordField<'a>,
+    {
+        let type_ = type_.return_type().unwrap_or_else(|| type_.clone().into());
+        if type_.is_bool() {
+            self.boolean(name == "True");
+            return Ok(());
+        }
+        if type_.is_nil() {
+            self.boolean(false);
+            return Ok(());
+        }
+        self.line(format!("i32.const {index}"));
+        for field in fields {
+            field.generate(self)?;
+        }
+        let type_ = self.program.record_type(fields.len());
+        self.line(format!("struct.new {type_}"));
+        Ok(())
+    }
+
+    fn call(
+        &mut self,
+        fun: &'a TypedExpr,
+        args: impl Iterator<Item = &'a TypedExpr>,
+        location: SrcSpan,
+    ) -> Result<()> {
+        let args: Vec<_> = args.collect();
+        let (module, name) = match fun {
+            TypedExpr::Var { constructor, .. } => match &constructor.variant {
+                ValueConstructorVariant::ModuleFn { module, name, .. } => (*module, name),
+                ValueConstructorVariant::Record {
+                    name,
+                    constructor_index,
+                    ..
+                } => return self.record(name, &constructor.type_, *constructor_index, &args),
+                ValueConstructorVariant::LocalVariable { .. }
+                | ValueConstructorVariant::ModuleConstant { .. }
+                | ValueConstructorVariant::LocalConstant { .. } => {
+                    return Err(self.unsupported("calls to function values".into(), location))
+                }
+            },
+            TypedExpr::ModuleSelect {
+                constructor,
+                location: select_location,
+                ..
+            } => match constructor {
+                ModuleValueConstructor::Fn { module, name, .. } => (*module, name),
+                ModuleValueConstructor::Record { name, type_, .. } => {
+                    let index = self.constructor_index(type_, name, *select_location)?;
+                    return self.record(name, type_, index, &args);
+                }
+                ModuleValueConstructor::Constant { .. } => {
+                    return Err(self.unsupported("calls to function values".into(), location))
+                }
+            },
+            _ => return Err(self.unsupported("calls to function values".into(), location)),
+        };
+
+        if !self.program.modules.contains_key(module.as_ref()) {
+            return Err(self.unsupported("external functions".into(), location));
+        }
+        for arg in args {
+            self.expression(arg)?;
+        }
+        self.program.enqueue(&module, name);
+        self.line(format!("call ${module}.{name}"));
+        Ok(())
+    }
+
+    fn bin_op(&mut self, name: BinOp, left: &'a TypedExpr, right: &'a TypedExpr) -> Result<()> {
+        let (operands, instruction, result) = match name {
+            BinOp::And | BinOp::Or => {
+                self.expression(left)?;
+                self.unbox(Scalar::Bool);
+                self.open("if (result (ref null eq))");
+                match name {
+                    BinOp::And => self.expression(right)?,
+                    _ => self.boolean(true),
+                }
+                self.else_();
+                match name {
+                    BinOp::And => self.boolean(false),
+                    _ => self.expression(right)?,
+                }
+                self.close();
+                return Ok(());
+            }
+
+            BinOp::Eq | BinOp::NotEq => {
+                self.expression(left)?;
+                self.expression(right)?;
+                self.line("call $gleam.equal");
+                if name == BinOp::NotEq {
+                    self.line("i32.eqz");
+                }
+                self.line("ref.i31");
+                return Ok(());
+            }
+
+            BinOp::Concatenate => {
+                self.expression(left)?;
+                self.expression(right)?;
+                self.line("call $gleam.string_concat");
+                return Ok(());
+            }
+
+            BinOp::DivInt | BinOp::RemainderInt | BinOp::DivFloat => {
+                return self.division(name, left, right)
+            }
+
+            BinOp::LtInt => (Scalar::Int, "i64.lt_s", Scalar::Bool),
+            BinOp::LtEqInt => (Scalar::Int, "i64.le_s", Scalar::Bool),
+            BinOp::GtInt => (Scalar::Int, "i64.gt_s", Scalar::Bool),
+            BinOp::GtEqInt => (Scalar::Int, "i64.ge_s", Scalar::Bool),
+            BinOp::LtFloat => (Scalar::Float, "f64.lt", Scalar::Bool),
+            BinOp::LtEqFloat => (Scalar::Float, "f64.le", Scalar::Bool),
+            BinOp::GtFloat => (Scalar::Float, "f64.gt", Scalar::Bool),
+            BinOp::GtEqFloat => (Scalar::Float, "f64.ge", Scalar::Bool),
+            BinOp::AddInt | BinOp::SubInt | BinOp::MultInt => {
+                (Scalar::Int, arithmetic_instruction(name), Scalar::Int)
+            }
+            BinOp::AddFloat | BinOp::SubFloat | BinOp::MultFloat => {
+                (Scalar::Float, arithmetic_instruction(name), Scalar::Float)
+            }
+        };
+        self.unboxed(left, operands)?;
+        self.unboxed(right, operands)?;
+        self.line(instruction);
+        for instruction in result.boxing() {
+            self.line(instruction);
+        }
+        Ok(())
+    }
+
+    /// Generates an int or float expression, leaving it unboxed. The results
+    /// of literals and arithmetic are never boxed to start with.
+    fn unboxed(&mut self, expression: &'a TypedExpr, scalar: Scalar) -> Result<()> {
+        match (expression, scalar) {
+            (
+                TypedExpr::Int {
+                    value, location, ..
+                },
+                Scalar::Int,
+            ) => {
+                let value = self.int_const(value, *location)?;
+                self.line(value);
+            }
+            (
+                TypedExpr::Float {
+                    value, location, ..
+                },
+                Scalar::Float,
+            ) => {
+                let value = self.float_const(value, *location)?;
+                self.line(value);
+            }
+            (
+                TypedExpr::BinOp {
+                    name: name @ (BinOp::AddInt | BinOp::SubInt | BinOp::MultInt),
+                    left,
+                    right,
+                    ..
+                },
+                Scalar::Int,
+            )
+            | (
+                TypedExpr::BinOp {
+                    name: name @ (BinOp::AddFloat | BinOp::SubFloat | BinOp::MultFloat),
+                    left,
+                    right,
+                    ..
+                },
+                Scalar::Float,
+            ) => {
+                self.unboxed(left, scalar)?;
+                self.unboxed(right, scalar)?;
+                self.line(arithmetic_instruction(*name));
+            }
+            _ => {
+                self.expression(expression)?;
+                self.unbox(scalar);
+            }
+        }
+        Ok(())
+    }
+
+    /// Division by zero is zero in Gleam, rather than trapping.
+    fn division(&mut self, name: BinOp, left: &'a TypedExpr, right: &'a TypedExpr) -> Result<()> {
+        let (scalar, is_zero, zero, instruction) = match name {
+            BinOp::DivInt => (Scalar::Int, "i64.eqz", "i64.const 0", "i64.div_s"),
+            BinOp::RemainderInt => (Scalar::Int, "i64.eqz", "i64.const 0", "i64.rem_s"),
+            _ => (Scalar::Float, "f64.eq", "f64.const 0", "f64.div"),
+        };
+        let type_ = scalar.value_type();
+        let dividend = self.local("dividend", type_);
+        let divisor = self.local("divisor", type_);
+        self.unboxed(left, scalar)?;
+        self.line(format!("local.set {dividend}"));
+        self.unboxed(right, scalar)?;
+        self.line(format!("local.set {divisor}"));
+        self.line(format!("local.get {divisor}"));
+        if scalar == Scalar::Float {
+            self.line("f64.const 0");
+        }
+        self.line(is_zero);
+        self.open(format!("if (result {type_})"));
+        self.line(zero);
+        self.else_();
+        self.line(format!("local.get {dividend}"));
+        self.line(format!("local.get {divisor}"));
+        self.line(instruction);
+        self.close();
+        for instruction in scalar.boxing() {
+            self.line(instruction);
+        }
+        Ok(())
+    }
+
+    fn case(
+        &mut self,
+        subjects: &'a [TypedExpr],
+        clauses: &'a [Clause<TypedExpr, std::sync::Arc<Type>, EcoString>],
+    ) -> Result<()> {
+        let mut subject_locals = Vec::with_capacity(subjects.len());
+        for subject in subjects {
+            self.expression(subject)?;
+            let local = self.local("subject", "(ref null eq)");
+            self.line(format!("local.set {local}"));
+            subject_locals.push(local);
+        }
+
+        let case = self.label("case");
+        self.open(format!("block {case} (result (ref null eq))"));
+        for clause in clauses {
+            if let Some(guard) = &clause.guard {
+                return Err(self.unsupported("case clause guards".into(), guard.location()));
+            }
+            let next = self.label("next");
+            let mut bindings = Bindings::default();
+            self.open(format!("block {next}"));
+            if clause.alternative_patterns.is_empty() {
+                self.multi_pattern(&clause.pattern, &subject_locals, &next, &mut bindings)?;
+            } else {
+                let matched = self.label("matched");
+                self.open(format!("block {matched}"));
+                for patterns in std::iter::once(&clause.pattern).chain(&clause.alternative_patterns)
+                {
+                    let alternative = self.label("alternative");
+                    self.open(format!("block {alternative}"));
+                    self.multi_pattern(patterns, &subject_locals, &alternative, &mut bindings)?;
+                    self.line(format!("br {matched}"));
+                    self.close();
+                }
+                self.line(format!("br {next}"));
+                self.close();
+            }
+            let scope = self.scope.len();
+            self.scope.extend(bindings.locals);
+            self.expression(&clause.then)?;
+            self.scope.truncate(scope);
+            self.line(format!("br {case}"));
+            self.close();
+        }
+        // Case expressions are exhaustive, so one of the clauses matches.
+        self.line("unreachable");
+        self.close();
+        Ok(())
+    }
+
+    fn multi_pattern(
+        &mut self,
+        patterns: &'a [TypedPattern],
+        subjects: &[String],
+        failed: &str,
+        bindings: &mut Bindings,
+    ) -> Result<()> {
+        for (pattern, subject) in patterns.iter().zip_eq(subjects) {
+            self.pattern(pattern, subject, failed, bindings)?;
+        }
+        Ok(())
+    }
+
+    /// Checks that the value of a local matches a pattern, branching to the
+    /// `failed` label if it doesn't, and binds the pattern's variables.
+    fn pattern(
+        &mut self,
+        pattern: &'a TypedPattern,
+        subject: &str,
+        failed: &str,
+        bindings: &mut Bindings,
+    ) -> Result<()> {
+        match pattern {
+            Pattern::Int {
+                value, location, ..
+            } => {
+                self.line(format!("local.get {subject}"));
+                self.unbox(Scalar::Int);
+                let value = self.int_const(value, *location)?;
+                self.line(value);
+                self.line("i64.ne");
+                self.line(format!("br_if {failed}"));
+            }
+
+            Pattern::Float {
+                value, location, ..
+            } => {
+                self.line(format!("local.get {subject}"));
+                self.unbox(Scalar::Float);
+                let value = self.float_const(value, *location)?;
+                self.line(value);
+                self.line("f64.ne");
+                self.line(format!("br_if {failed}"));
+            }
+
+            Pattern::String { value, .. } => {
+                self.line(format!("local.get {subject}"));
+                self.string(value);
+                self.line("call $gleam.string_equal");
+                self.line("i32.eqz");
+                self.line(format!("br_if {failed}"));
+            }
+
+            Pattern::Variable { name, .. } => self.bind(name, subject, bindings),
+
+            Pattern::Assign { name, pattern, .. } => {
+                self.bind(name, subject, bindings);
+                self.pattern(pattern, subject, failed, bindings)?;
+            }
+
+            Pattern::Discard { .. } => (),
+
+            Pattern::List { elements, tail, .. } => {
+                let mut list = subject.to_string();
+                for element in elements {
+                    self.line(format!("local.get {list}"));
+                    self.line("ref.is_null");
+                    self.line(format!("br_if {failed}"));
+                    self.sub_pattern(element, "head", failed, bindings, |generator| {
+                        generator.line(format!("local.get {list}"));
+                        generator.line("ref.cast (ref $cons)");
+                        generator.line("struct.get $cons 0");
+                    })?;
+                    let rest = self.local("rest", "(ref null eq)");
+                    self.line(format!("local.get {list}"));
+                    self.line("ref.cast (ref $cons)");
+                    self.line("struct.get $cons 1");
+                    self.line(format!("local.set {rest}"));
+                    list = rest;
+                }
+                match tail {
+                    Some(tail) => self.pattern(tail, &list, failed, bindings)?,
+                    None => {
+                        self.line(format!("local.get {list}"));
+                        self.line("ref.is_null");
+                        self.line("i32.eqz");
+                        self.line(format!("br_if {failed}"));
+                    }
+                }
+            }
+
+            Pattern::Constructor {
+                name,
+                arguments,
+                constructor,
+                type_,
+                ..
+            } => {
+                if type_.is_bool() {
+                    self.line(format!("local.get {subject}"));
+                    self.unbox(Scalar::Bool);
+                    self.line(format!("i32.const {}", u8::from(name == "True")));
+                    self.line("i32.ne");
+                    self.line(format!("br_if {failed}"));
+                } else if !type_.is_nil() {
+                    let index = constructor
+                        .expect_ref("inferred pattern constructor")
+                        .constructor_index;
+                    self.line(format!("local.get {subject}"));
+                    self.field(0);
+                    self.line(format!("i32.const {index}"));
+                    self.line("i32.ne");
+                    self.line(format!("br_if {failed}"));
+                    self.fields(
+                        arguments.iter().map(|argument| &argument.value),
+                        subject,
+                        failed,
+                        bindings,
+                    )?;
+                }
+            }
+
+            Pattern::Tuple { elems, .. } => self.fields(elems.iter(), subject, failed, bindings)?,
+
+            Pattern::VarUsage { location, .. } | Pattern::BitArray { location, .. } => {
+                return Err(self.unsupported("bit array patterns".into(), *location))
+            }
+            Pattern::StringPrefix { location, .. } => {
+                return Err(self.unsupported("string prefix patterns".into(), *location))
+            }
+        }
+        Ok(())
+    }
+
+    fn fields(
+        &mut self,
+        patterns: impl Iterator<Item = &'a TypedPattern>,
+        subject: &str,
+        failed: &str,
+        bindings: &mut Bindings,
+    ) -> Result<()> {
+        for (index, pattern) in patterns.enumerate() {
+            self.sub_pattern(pattern, "field", failed, bindings, |generator| {
+                generator.line(format!("local.get {subject}"));
+                generator.field(index + 1);
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Matches a part of a value against a pattern. The part is loaded by
+    /// `load` straight into the variable of a variable pattern, rather than
+    /// being copied from another local, and not at all for a discard pattern.
+    fn sub_pattern(
+        &mut self,
+        pattern: &'a TypedPattern,
+        name: &str,
+        failed: &str,
+        bindings: &mut Bindings,
+        load: impl FnOnce(&mut Self),
+    ) -> Result<()> {
+        let local = match pattern {
+            Pattern::Discard { .. } => return Ok(()),
+            Pattern::Variable { name, .. } => self.binding(name, bindings),
+            _ => self.local(name, "(ref null eq)"),
+        };
+        load(self);
+        self.line(format!("local.set {local}"));
+        match pattern {
+            Pattern::Variable { .. } => Ok(()),
+            _ => self.pattern(pattern, &local, failed, bindings),
+        }
+    }
+
+    fn bind(&mut self, name: &EcoString, subject: &str, bindings: &mut Bindings) {
+        let local = self.binding(name, bindings);
+        self.line(format!("local.get {subject}"));
+        self.line(format!("local.set {local}"));
+    }
+
+    /// The local a pattern binds a variable to.
+    fn binding(&mut self, name: &EcoString, bindings: &mut Bindings) -> String {
+        match bindings
+            .locals
+            .iter()
+            .find(|(variable, _)| variable == name)
+        {
+            Some((_, local)) => local.clone(),
+            None => {
+                let local = self.local(name, "(ref null eq)");
+                bindings.locals.push((name.clone(), local.clone()));
+                local
+            }
+        }
+    }
+
+    fn constant(&mut self, constant: &'a TypedConstant) -> Result<()> {
+        match constant {
+            TypedConstant::Int {
+                value, location, ..
+            } => self.int(value, *location),
+            TypedConstant::Float {
+                value, location, ..
+            } => self.float(value, *location),
+            TypedConstant::String { value, .. } => {
+                self.string(value);
+                Ok(())
+            }
+            TypedConstant::Tuple { elements, .. } => {
+                self.line("i32.const 0");
+                for element in elements {
+                    self.constant(element)?;
+                }
+                let type_ = self.program.record_type(elements.len());
+                self.line(format!("struct.new {type_}"));
+                Ok(())
+            }
+            TypedConstant::List { elements, .. } => {
+                for element in elements {
+                    self.constant(element)?;
+                }
+                self.line("ref.null none");
+                for _ in elements {
+                    self.line("struct.new $cons");
+                }
+                Ok(())
+            }
+            TypedConstant::Record {
+                tag,
+                args,
+                typ,
+                location,
+                ..
+            } => {
+                let index = if typ.is_bool() || typ.is_nil() {
+                    0
+                } else {
+                    self.constructor_index(typ, tag, *location)?
+                };
+                let args: Vec<_> = args.iter().map(|arg| &arg.value).collect();
+                self.record(tag, typ, index, &args)
+            }
+            TypedConstant::Var {
+                constructor: Some(constructor),
+                location,
+                ..
+            } => match &constructor.variant {
+                ValueConstructorVariant::ModuleConstant { literal, .. }
+                | ValueConstructorVariant::LocalConstant { literal } => self.constant(literal),
+                ValueConstructorVariant::Record {
+                    name,
+                    arity: 0,
+                    constructor_index,
+                    ..
+                } => self.record::<&TypedExpr>(name, &constructor.type_, *constructor_index, &[]),
+                ValueConstructorVariant::Record { .. }
+                | ValueConstructorVariant::ModuleFn { .. }
+                | ValueConstructorVariant::LocalVariable { .. } => {
+                    Err(self.unsupported("functions used as values".into(), *location))
+                }
+            },
+            TypedConstant::BitArray { location, .. } => {
+                Err(self.unsupported("bit arrays".into(), *location))
+            }
+            TypedConstant::Var { location, .. } | TypedConstant::Invalid { location, .. } => {
+                panic!("invalid constant at {location:?} after type checking")
+            }
+        }
+    }
+}
+
+/// A field of a record being built, which is an expression or a constant.
+trait RecordField<'a> {
+    fn generate(&self, generator: &mut Generator<'a, '_>) -> Result<()>;
+}
+
+impl<'a> RecordField<'a> for &'a TypedExpr {
+    fn generate(&self, generator: &mut Generator<'a, '_>) -> Result<()> {
+        generator.expression(self)
+    }
+}
+
+impl<'a> RecordField<'a> for &'a TypedConstant {
+    fn generate(&self, generator: &mut Generator<'a, '_>) -> Result<()> {
+        generator.constant(self)
+    }
+}
+
+fn arithmetic_instruction(name: BinOp) -> &'static str {
+    match name {
+        BinOp::AddInt => "i64.add",
+        BinOp::SubInt => "i64.sub",
+        BinOp::MultInt => "i64.mul",
+        BinOp::AddFloat => "f64.add",
+        BinOp::SubFloat => "f64.sub",
+        _ => "f64.mul",
+    }
+}
+
+/// Whether a function is implemented only by external functions, rather than
+/// having a Gleam body. Such functions are parsed with a body that panics,
+/// ending where the function's head ends.
+fn is_external(function: &TypedFunction) -> bool {
+    if function.external_erlang.is_none() && function.external_javascript.is_none() {
+        return false;
+    }
+    match function.body.as_slice() {
+        [Statement::Expression(TypedExpr::Panic {
+            location,
+            message: None,
+            ..
+        })] => location.end == function.end_position,
+        _ => false,
+    }
+}
@@ -0,0 +1,326 @@
+use std::time::SystemTime;
+
+use camino::Utf8PathBuf;
+
+use super::*;
+use crate::{
+    analyse::TargetSupport, build::Target, config::PackageConfig, line_numbers::LineNumbers,
+    uid::UniqueIdGenerator, warning::TypeWarningEmitter,
+};
+
+fn compile_modules(sources: &[(&str, &str)]) -> Result<String> {
+    let ids = UniqueIdGenerator::new();
+    let mut importable_modules = im::HashMap::new();
+    let _ = importable_modules.insert(
+        PRELUDE_MODULE_NAME.into(),
+        crate::type_::build_prelude(&ids),
+    );
+    let config = PackageConfig {
+        name: "thepackage".into(),
+        ..Default::default()
+    };
+
+    // The last module is the root package's, and the others are its
+    // dependencies.
+    let mut modules = Vec::new();
+    for (name, src) in sources {
+        let mut ast = crate::parse::parse_module(src)
+            .expect("syntax error")
+            .module;
+        ast.name = (*name).into();
+        let ast = crate::analyse::ModuleAnalyzerConstructor::<()> {
+            target: Target::Erlang,
+            ids: &ids,
+            origin: Origin::Src,
+            importable_modules: &importable_modules,
+            warnings: &TypeWarningEmitter::null(),
+            direct_dependencies: &HashMap::new(),
+            target_support: TargetSupport::NotEnforced,
+            package_config: &config,
+        }
+        .infer_module(ast, LineNumbers::new(src), "".into())
+        .expect("should successfully infer");
+        let _ = importable_modules.insert((*name).into(), ast.type_info.clone());
+        modules.push(Module {
+            name: (*name).into(),
+            code: (*src).into(),
+            mtime: SystemTime::UNIX_EPOCH,
+            input_path: Utf8PathBuf::from(format!("src/{name}.gleam")),
+            origin: Origin::Src,
+            ast,
+            extra: Default::default(),
+            dependencies: vec![],
+        });
+    }
+    let root = modules.split_off(modules.len() - 1);
+    module(&root, &modules)
+}
+
+fn compile(src: &str) -> String {
+    let output = compile_modules(&[("my/mod", src)]).expect("compile to wasm");
+    validate(&output);
+    output
+}
+
+// Every module the tests generate is assembled and checked by wasmparser, so
+// the snapshots can't record a module the engines would refuse to load.
+fn validate(wat: &str) {
+    let binary = wat::parse_str(wat).expect("generated text should assemble");
+    let _ = wasmparser::Validator::new_with_features(wasmparser::WasmFeatures::all())
+        .validate_all(&binary)
+        .expect("generated module should be valid");
+}
+
+macro_rules! assert_wat {
+    ($src:expr $(,)?) => {{
+        let output = compile($src);
+        insta::assert_snapshot!(insta::internals::AutoName, output, $src);
+    }};
+}
+
+#[test]
+fn int_value_bases() {
+    assert_eq!(int_value("1_000"), Some(1000));
+    assert_eq!(int_value("-42"), Some(-42));
+    assert_eq!(int_value("0xFF"), Some(255));
+    assert_eq!(int_value("0o17"), Some(15));
+    assert_eq!(int_value("0b101"), Some(5));
+    assert_eq!(int_value("-9223372036854775808"), Some(i64::MIN));
+    assert_eq!(int_value("9223372036854775808"), None);
+}
+
+#[test]
+fn data_string_escapes() {
+    assert_eq!(
+        data_string("a\"b\\c\n£".as_bytes()),
+        "a\\\"b\\\\c\\0a\\c2\\a3"
+    );
+}
+
+#[test]
+fn arithmetic() {
+    assert_wat!(
+        r#"
+pub fn add(a: Int, b: Int) -> Int {
+  a + b * 2 - 0x10
+}
+
+pub fn divide(a: Float, b: Float) -> Float {
+  a /. b
+}
+
+pub fn remainder(a: Int, b: Int) -> Int {
+  a % b
+}
+
+pub fn negate(a: Int) -> Int {
+  -a
+}
+"#
+    );
+}
+
+#[test]
+fn strings() {
+    assert_wat!(
+        r#"
+pub fn greet(name: String) -> String {
+  "Hello, " <> name <> "!\n"
+}
+
+pub fn is_joe(name: String) -> Bool {
+  case name {
+    "Joe" -> True
+    _ -> False
+  }
+}
+"#
+    );
+}
+
+#[test]
+fn lists() {
+    assert_wat!(
+        r#"
+pub fn sum(list: List(Int)) -> Int {
+  case list {
+    [] -> 0
+    [first, ..rest] -> first + sum(rest)
+  }
+}
+
+pub fn numbers() -> Int {
+  sum([1, 2, 3, ..[4]])
+}
+"#
+    );
+}
+
+#[test]
+fn custom_types() {
+    assert_wat!(
+        r#"
+pub type Shape {
+  Circle(radius: Float)
+  Rectangle(width: Float, height: Float)
+  Point
+}
+
+pub fn area(shape: Shape) -> Float {
+  case shape {
+    Circle(radius: r) -> 3.14 *. r *. r
+    Rectangle(width, height) -> width *. height
+    Point -> 0.0
+  }
+}
+
+pub fn width(shape: Shape) -> Float {
+  let assert Rectangle(width: width, ..) = shape
+  width
+}
+
+pub type Size {
+  Size(width: Float, height: Float)
+}
+
+pub fn height(size: Size) -> Float {
+  size.height
+}
+"#
+    );
+}
+
+#[test]
+fn tuples_and_equality() {
+    assert_wat!(
+        r#"
+pub fn same(a: #(Int, String), b: #(Int, String)) -> Bool {
+  a == b && a.0 != 0
+}
+
+pub fn either(a: Bool, b: Bool) -> Bool {
+  a || !b
+}
+"#
+    );
+}
+
+#[test]
+fn alternative_patterns_share_variables() {
+    assert_wat!(
+        r#"
+pub fn first(pair: #(Int, Int)) -> Int {
+  case pair {
+    #(0, x) | #(x, _) -> x
+  }
+}
+"#
+    );
+}
+
+#[test]
+fn constants_and_pipes() {
+    assert_wat!(
+        r#"
+const offset = 10
+
+const origin = #(0, Ok(Nil))
+
+fn double(x: Int) -> Int {
+  x * 2
+}
+
+pub fn shift(x: Int) -> Int {
+  x
+  |> double
+  |> fn_with_offset
+}
+
+fn fn_with_offset(x) {
+  x + offset
+}
+
+pub fn start() -> #(Int, Result(Nil, a)) {
+  origin
+}
+"#
+    );
+}
+
+#[test]
+fn functions_of_other_modules() {
+    let output = compile_modules(&[
+        (
+            "geometry",
+            r#"
+pub type Point {
+  Point(x: Int, y: Int)
+}
+
+pub fn x(point: Point) -> Int {
+  point.x
+}
+
+pub fn unused() -> Int {
+  1
+}
+"#,
+        ),
+        (
+            "my/mod",
+            r#"
+import geometry
+
+pub fn main() -> Int {
+  geometry.x(geometry.Point(1, 2))
+}
+"#,
+        ),
+    ])
+    .expect("compile to wasm");
+    insta::assert_snapshot!(output);
+}
+
+#[test]
+fn anonymous_functions_are_unsupported() {
+    let error = compile_modules(&[("my/mod", "pub fn main() { fn(x) { x } }")])
+        .expect_err("anonymous function");
+    let Error::WasmUnsupported { details } = error else {
+        panic!("unexpected error {error:?}");
+    };
+    assert_eq!(details.feature, "anonymous functions");
+    assert_eq!(details.location, SrcSpan::new(16, 27));
+}
+
+#[test]
+fn external_functions_are_unsupported() {
+    let error = compile_modules(&[(
+        "my/mod",
+        r#"
+@external(erlang, "erlang", "abs")
+fn abs(x: Int) -> Int
+
+pub fn main() {
+  abs(-1)
+}
+"#,
+    )])
+    .expect_err("external function");
+    let Error::WasmUnsupported { details } = error else {
+        panic!("unexpected error {error:?}");
+    };
+    assert_eq!(details.feature, "external functions");
+}
+
+#[test]
+fn guards_are_unsupported() {
+    let error = compile_modules(&[(
+        "my/mod",
+        "pub fn main(x) { case x { y if y > 1 -> 1 _ -> 0 } }",
+    )])
+    .expect_err("guard");
+    let Error::WasmUnsupported { details } = error else {
+        panic!("unexpected error {error:?}");
+    };
+    assert_eq!(details.feature, "case clause guards");
+}
@@ -0,0 +1,111 @@
+// The crash formatter of `gleam run` and `gleam test`, which prints errors
+// raised by Gleam code, such as by `panic`, `todo`, and `let assert`, with the
+// values in them shown as Gleam would show them. The Erlang target has the
+// same formatter in the `gleam@@crash` module, and both format errors in the
+// same way.
+
+import {
+  BitArray,
+  CustomType,
+  List,
+  UtfCodepoint,
+  formatError,
+  setErrorFormatter,
+} from "./prelude.mjs";
+
+// Makes `formatGleamError` the formatter of errors raised by Gleam code. An
+// application can replace it using `setErrorFormatter` from the prelude.
+export function install() {
+  setErrorFormatter(formatGleamError);
+}
+
+export function formatCrash(error) {
+  if (error?.gleam_error === undefined) return error?.stack ?? String(error);
+  return [formatError(error), ...formatStacktrace(error)].join("\n");
+}
+
+export function formatGleamError(error) {
+  let lines = [`error: ${error.message}`];
+  lines.push(`  module: ${error.module}`);
+  lines.push(`  function: ${error.fn}`);
+  lines.push(`  line: ${error.line}`);
+  if ("expression" in error) lines.push(`  expression: ${error.expression}`);
+  if ("value_type" in error) lines.push(`  value type: ${error.value_type}`);
+  if ("value" in error) lines.push(`  value: ${inspect(error.value)}`);
+  if ("counterexample" in error) {
+    lines.push(`  counterexample: ${inspect(error.counterexample)}`);
+  }
+  if ("original" in error) {
+    lines.push(`  original input: ${inspect(error.original)}`);
+  }
+  if ("shrinks" in error) lines.push(`  shrink steps: ${error.shrinks}`);
+  if ("seed" in error) lines.push(`  seed: ${error.seed}`);
+  if ("failure" in error) lines.push(`  failure:${formatFailure(error.failure)}`);
+  return lines.join("\n");
+}
+
+// The error a property failed with for its counterexample, given by property
+// testing libraries in the `failure` field of a `property` error. Errors
+// raised by Gleam code are formatted on the lines after the label.
+function formatFailure(failure) {
+  if (failure?.gleam_error === undefined) {
+    return ` ${failure?.message ?? String(failure)}`;
+  }
+  return formatGleamError(failure)
+    .split("\n")
+    .map((line) => `\n    ${line}`)
+    .join("");
+}
+
+// Leaves out the frames of the prelude, which raises the error, of the code
+// that runs the program, and of the runtime itself.
+function formatStacktrace(error) {
+  let frames = String(error.stack ?? "")
+    .split("\n")
+    .map((line) => line.trim())
+    .filter((line) => line.startsWith("at "))
+    .filter((line) => !/(prelude|crash|test_runner|gleam\.main|gleam\.test)\.mjs|\((node|ext):/.test(line))
+    .map((line) => `  ${line.slice(3)}`);
+  return frames.length ? ["stacktrace:", ...frames] : [];
+}
+
+export function inspect(value) {
+  if (value === true) return "True";
+  if (value === false) return "False";
+  if (value === undefined) return "Nil";
+  if (typeof value === "string") return `"${escape(value)}"`;
+  if (typeof value === "number") return String(value);
+  if (typeof value === "function") {
+    return `//fn(${Array(value.length).fill("_").join(", ")}) { ... }`;
+  }
+  if (Array.isArray(value)) {
+    return `#(${value.map(inspect).join(", ")})`;
+  }
+  if (value instanceof List) {
+    return `[${value.toArray().map(inspect).join(", ")}]`;
+  }
+  if (value instanceof BitArray) {
+    return `<<${Array.from(value.buffer).join(", ")}>>`;
+  }
+  if (value instanceof UtfCodepoint) {
+    return `//utfcodepoint(${value.value})`;
+  }
+  if (value instanceof CustomType) {
+    let name = value.constructor.name;
+    let fields = Object.keys(value).map((label) => {
+      let field = inspect(value[label]);
+      return isNaN(parseInt(label)) ? `${label}: ${field}` : field;
+    });
+    return fields.length ? `${name}(${fields.join(", ")})` : name;
+  }
+  return `//js(${String(value)})`;
+}
+
+function escape(string) {
+  return string
+    .replaceAll("\\", "\\\\")
+    .replaceAll('"', '\\"')
+    .replaceAll("\n", "\\n")
+    .replaceAll("\r", "\\r")
+    .replaceAll("\t", "\\t");
+}
@@ -0,0 +1,170 @@
+-module('gleam@@crash').
+
+% The crash formatter of `gleam run` and `gleam test`, which prints errors
+% raised by Gleam code, such as by `panic`, `todo`, and `let assert`, with the
+% values in them shown as Gleam would show them. The JavaScript target has the
+% same formatter in `crash.mjs`, written next to the prelude, and both format
+% errors in the same way.
+
+-export([install/0, format_crash/3, format_error/1, format_stacktrace/1, inspect/1]).
+
+% Makes `format_error/1` the formatter of errors raised by Gleam code. An
+% application can replace it by storing another function with
+% `persistent_term:put({gleam, error_formatter}, Formatter)`.
+install() ->
+    persistent_term:put({gleam, error_formatter}, fun format_error/1).
+
+format_crash(error, #{gleam_error := _} = Error, StackTrace) ->
+    Formatter = persistent_term:get({gleam, error_formatter}, fun format_error/1),
+    [Formatter(Error), format_stacktrace(StackTrace)];
+format_crash(Class, Reason, StackTrace) ->
+    E = erl_error:format_exception(
+        1, Class, Reason, [], fun stack_filter/3, fun print_stack_frame/2, unicode
+    ),
+    [E, format_stacktrace(StackTrace)].
+
+format_error(#{message := Message} = Error) ->
+    Field = fun(Key, Label, Format) ->
+        case Error of
+            #{Key := Value} -> ["  ", Label, ": ", Format(Value), "\n"];
+            _ -> []
+        end
+    end,
+    Text = fun(Value) -> io_lib:format("~ts", [Value]) end,
+    [
+        io_lib:format("error: ~ts~n", [Message]),
+        Field(module, "module", Text),
+        Field(function, "function", Text),
+        Field(line, "line", fun integer_to_list/1),
+        Field(expression, "expression", Text),
+        Field(value_type, "value type", Text),
+        Field(value, "value", fun inspect/1),
+        Field(counterexample, "counterexample", fun inspect/1),
+        Field(original, "original input", fun inspect/1),
+        Field(shrinks, "shrink steps", fun integer_to_list/1),
+        Field(seed, "seed", fun integer_to_list/1),
+        Field(failure, "failure", fun format_failure/1)
+    ].
+
+% The error a property failed with for its counterexample, given by property
+% testing libraries in the `failure` field of a `property` error. Errors
+% raised by Gleam code are formatted on the lines after the label.
+format_failure(#{gleam_error := _, message := _} = Error) ->
+    Lines = string:split(string:trim(format_error(Error), trailing), "\n", all),
+    [["\n    ", Line] || Line <- Lines];
+format_failure(Reason) ->
+    io_lib:format("~tp", [Reason]).
+
+% Formats a stack trace using the names of Gleam modules and the lines of their
+% source files given by the `-file` attributes of the generated Erlang, leaving
+% out the frames of the code that runs the program.
+format_stacktrace(StackTrace) ->
+    Frames = [
+        format_stack_frame(Frame)
+     || {Module, Function, Arity, _} = Frame <- StackTrace,
+        not stack_filter(Module, Function, Arity)
+    ],
+    case Frames of
+        [] -> [];
+        _ -> ["stacktrace:\n" | Frames]
+    end.
+
+format_stack_frame({Module, Function, Arguments, Location}) ->
+    File = proplists:get_value(file, Location),
+    Name = case File =/= undefined andalso filename:extension(File) of
+        ".gleam" -> string:replace(atom_to_binary(Module), "@", "/", all);
+        _ -> atom_to_binary(Module)
+    end,
+    Arity = case Arguments of
+        _ when is_list(Arguments) -> length(Arguments);
+        _ -> Arguments
+    end,
+    Source = case {File, proplists:get_value(line, Location)} of
+        {undefined, _} -> "";
+        {_, undefined} -> io_lib:format(" (~ts)", [File]);
+        {_, Line} -> io_lib:format(" (~ts:~p)", [File, Line])
+    end,
+    io_lib:format(
+        "  ~ts.~ts/~p~ts~n", [Name, atom_to_binary(Function), Arity, Source]
+    ).
+
+% The frames of the entrypoint module of each package are left out too, which
+% is named after the package with a `@@main` suffix.
+stack_filter(Module, _F, _A) ->
+    case Module of
+        ?MODULE -> true;
+        erl_eval -> true;
+        init -> true;
+        'gleam@@test' -> true;
+        _ -> lists:suffix("@@main", atom_to_list(Module))
+    end.
+
+print_stack_frame(Term, I) ->
+    io_lib:format("~." ++ integer_to_list(I) ++ "tP", [Term, 50]).
+
+%
+% Values
+%
+
+inspect(Value) ->
+    unicode:characters_to_binary(inspect_value(Value)).
+
+inspect_value(nil) ->
+    "Nil";
+inspect_value(true) ->
+    "True";
+inspect_value(false) ->
+    "False";
+inspect_value(Atom) when is_atom(Atom) ->
+    constructor_name(Atom);
+inspect_value(Int) when is_integer(Int) ->
+    integer_to_list(Int);
+inspect_value(Float) when is_float(Float) ->
+    float_to_list(Float, [short]);
+inspect_value(Binary) when is_binary(Binary) ->
+    case unicode:characters_to_binary(Binary) of
+        Binary -> [$", [escape_character(C) || <<C/utf8>> <= Binary], $"];
+        _ -> ["<<", join([integer_to_list(Byte) || <<Byte>> <= Binary]), ">>"]
+    end;
+inspect_value(Bits) when is_bitstring(Bits) ->
+    io_lib:format("~w", [Bits]);
+inspect_value(List) when is_list(List) ->
+    case is_proper_list(List) of
+        true -> ["[", join([inspect_value(Element) || Element <- List]), "]"];
+        false -> io_lib:format("//erl(~tp)", [List])
+    end;
+inspect_value({}) ->
+    "#()";
+inspect_value(Tuple) when is_tuple(Tuple), is_atom(element(1, Tuple)) ->
+    [Tag | Fields] = tuple_to_list(Tuple),
+    [constructor_name(Tag), "(", join([inspect_value(Field) || Field <- Fields]), ")"];
+inspect_value(Tuple) when is_tuple(Tuple) ->
+    ["#(", join([inspect_value(Element) || Element <- tuple_to_list(Tuple)]), ")"];
+inspect_value(Map) when is_map(Map) ->
+    Entries = [
+        ["#(", inspect_value(Key), ", ", inspect_value(Value), ")"]
+     || {Key, Value} <- maps:to_list(Map)
+    ],
+    ["dict.from_list([", join(Entries), "])"];
+inspect_value(Fun) when is_function(Fun) ->
+    {arity, Arity} = erlang:fun_info(Fun, arity),
+    ["//fn(", join(lists:duplicate(Arity, "_")), ") { ... }"];
+inspect_value(Other) ->
+    io_lib:format("//erl(~tp)", [Other]).
+
+constructor_name(Atom) ->
+    [string:titlecase(Part) || Part <- string:split(atom_to_list(Atom), "_", all)].
+
+escape_character($") -> "\\\"";
+escape_character($\\) -> "\\\\";
+escape_character($\n) -> "\\n";
+escape_character($\r) -> "\\r";
+escape_character($\t) -> "\\t";
+escape_character(C) -> C.
+
+is_proper_list([]) -> true;
+is_proper_list([_ | Rest]) -> is_proper_list(Rest);
+is_proper_list(_) -> false.
+
+join(Parts) ->
+    lists:join(", ", Parts).
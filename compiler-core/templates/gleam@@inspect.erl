@@ -0,0 +1,71 @@
+-module('gleam@@inspect').
+
+% Encodes values as JSON for tooling such as `gleam kernel`, which decodes them
+% with the `inspect` module of the compiler and prints them as Gleam would
+% write them using their types. The JavaScript target has the same encoder in
+% `inspect.mjs`, written next to the prelude.
+
+-export([encode/1]).
+
+encode(Value) ->
+    unicode:characters_to_binary(value(Value)).
+
+value(Int) when is_integer(Int) ->
+    object(<<"int">>, [{<<"value">>, string(integer_to_binary(Int))}]);
+value(Float) when is_float(Float) ->
+    object(<<"float">>, [{<<"value">>, float_to_binary(Float, [short])}]);
+value(Atom) when is_atom(Atom) ->
+    object(<<"atom">>, [{<<"name">>, string(atom_to_binary(Atom))}]);
+value(Binary) when is_binary(Binary) ->
+    Bytes = [integer_to_binary(Byte) || <<Byte>> <= Binary],
+    object(<<"bit_array">>, [{<<"bytes">>, ["[", lists:join(",", Bytes), "]"]}]);
+value(List) when is_list(List) ->
+    case is_proper_list(List) of
+        true -> object(<<"list">>, [{<<"elements">>, array(List)}]);
+        false -> other(List)
+    end;
+value(Tuple) when is_tuple(Tuple) ->
+    object(<<"tuple">>, [{<<"elements">>, array(tuple_to_list(Tuple))}]);
+value(Map) when is_map(Map) ->
+    Entries = [["[", value(Key), ",", value(Value), "]"] || {Key, Value} <- maps:to_list(Map)],
+    object(<<"map">>, [{<<"entries">>, ["[", lists:join(",", Entries), "]"]}]);
+value(Fun) when is_function(Fun) ->
+    {arity, Arity} = erlang:fun_info(Fun, arity),
+    object(<<"function">>, [{<<"arity">>, integer_to_binary(Arity)}]);
+value(Other) ->
+    other(Other).
+
+other(Value) ->
+    Text = io_lib:format("//erl(~tp)", [Value]),
+    object(<<"other">>, [{<<"text">>, string(Text)}]).
+
+object(Kind, Members) ->
+    Encoded = [[string(Key), ":", Value] || {Key, Value} <- [{<<"kind">>, string(Kind)} | Members]],
+    ["{", lists:join(",", Encoded), "}"].
+
+array(Values) ->
+    ["[", lists:join(",", [value(Value) || Value <- Values]), "]"].
+
+is_proper_list([]) -> true;
+is_proper_list([_ | Rest]) -> is_proper_list(Rest);
+is_proper_list(_) -> false.
+
+% Only ASCII is written, so that the encoding does not depend on the encoding
+% of the output it is written to.
+string(String) ->
+    [$", [character(C) || C <- unicode:characters_to_list(String)], $"].
+
+character($") ->
+    "\\\"";
+character($\\) ->
+    "\\\\";
+character(C) when C > 16#FFFF ->
+    Offset = C - 16#10000,
+    [unicode_escape(16#D800 + (Offset bsr 10)), unicode_escape(16#DC00 + (Offset band 16#3FF))];
+character(C) when C < 16#20; C > 16#7E ->
+    unicode_escape(C);
+character(C) ->
+    C.
+
+unicode_escape(C) ->
+    io_lib:format("\\u~4.16.0b", [C]).
@@ -0,0 +1,126 @@
+-module('gleam@@test').
+
+% The test runner of `gleam test` when the tests are split into shards, run in
+% parallel, or retried. The tests found by the compiler are read from a file
+% and run in a number of processes at once, with a dot printed for each test
+% that passes, an F for each that fails, and a ? for each that only passes
+% when run again, followed by the errors of the failed and flaky tests. The
+% JavaScript target has the same runner in `test_runner.mjs`, written next to
+% the prelude.
+
+-export([run/3]).
+
+% The options are a map of:
+% - parallel: the number of tests to run at once, or 0 to run as many as
+%   there are schedulers.
+% - retries: the number of times a failing test is run again before it fails.
+% - fail_on_flaky: whether to fail when a test fails and then passes.
+run(Application, Path, Options) ->
+    io:setopts(standard_io, [binary, {encoding, utf8}]),
+    io:setopts(standard_error, [{encoding, utf8}]),
+    'gleam@@crash':install(),
+    {ok, _} = application:ensure_all_started(Application),
+    erlang:process_flag(trap_exit, false),
+    {ok, Tests} = file:consult(Path),
+    #{parallel := Parallel, retries := Retries, fail_on_flaky := FailOnFlaky} = Options,
+    Workers = case Parallel of
+        0 -> erlang:system_info(schedulers_online);
+        _ -> Parallel
+    end,
+    Queue = [{Index, Test, []} || {Index, Test} <- lists:enumerate(Tests)],
+    Started = erlang:monotonic_time(millisecond),
+    Results = run_tests(Queue, Workers, Retries, #{}, []),
+    Duration = erlang:monotonic_time(millisecond) - Started,
+    Status = report(lists:sort(Results), Duration, FailOnFlaky),
+    erlang:halt(Status, [{flush, true}]).
+
+% Each test runs in its own process, which sends its result before exiting.
+% A test that exits in another way, such as by being linked to a process that
+% crashes, fails with the reason it exited. A failing test is put back at the
+% front of the queue with its error until it has been retried enough times.
+run_tests([{Index, Test, Errors} | Queue], Workers, Retries, Running, Results) when
+    map_size(Running) < Workers
+->
+    Parent = self(),
+    {Pid, _} = spawn_monitor(fun() -> Parent ! {gleam_test, self(), run_test(Test)} end),
+    Running1 = Running#{Pid => {Index, Test, Errors}},
+    run_tests(Queue, Workers, Retries, Running1, Results);
+run_tests(Queue, Workers, Retries, Running, Results) when map_size(Running) > 0 ->
+    receive
+        {'DOWN', _, process, Pid, Reason} ->
+            {Index, Test, Errors} = maps:get(Pid, Running),
+            Outcome = receive
+                {gleam_test, Pid, Result} -> Result
+            after 0 ->
+                {failed, 'gleam@@crash':format_crash(exit, Reason, [])}
+            end,
+            Remaining = maps:remove(Pid, Running),
+            case {Outcome, Errors} of
+                {{failed, Error}, _} when length(Errors) < Retries ->
+                    Retry = {Index, Test, [Error | Errors]},
+                    run_tests([Retry | Queue], Workers, Retries, Remaining, Results);
+                {passed, []} ->
+                    io:put_chars("."),
+                    Result1 = {Index, Test, passed},
+                    run_tests(Queue, Workers, Retries, Remaining, [Result1 | Results]);
+                {passed, _} ->
+                    io:put_chars("?"),
+                    Result1 = {Index, Test, {flaky, lists:reverse(Errors)}},
+                    run_tests(Queue, Workers, Retries, Remaining, [Result1 | Results]);
+                {{failed, _}, _} ->
+                    io:put_chars("F"),
+                    Result1 = {Index, Test, Outcome},
+                    run_tests(Queue, Workers, Retries, Remaining, [Result1 | Results])
+            end
+    end;
+run_tests([], _, _, _, Results) ->
+    Results.
+
+run_test({Module, Function}) ->
+    try
+        Module:Function(),
+        passed
+    catch
+        Class:Reason:StackTrace ->
+            {failed, 'gleam@@crash':format_crash(Class, Reason, StackTrace)}
+    end.
+
+% Prints the errors of the failed tests, the errors of the flaky tests before
+% they passed, and how many passed, returning the status to exit with.
+report(Results, Duration, FailOnFlaky) ->
+    Failures = [
+        {name(Test), Error}
+     || {_, Test, {failed, Error}} <- Results
+    ],
+    Flaky = [
+        {name(Test), Errors}
+     || {_, Test, {flaky, Errors}} <- Results
+    ],
+    io:put_chars("\n"),
+    lists:foreach(
+        fun({Name, Error}) -> io:put_chars(["\n", Name, "\n", Error]) end,
+        Failures
+    ),
+    lists:foreach(
+        fun({Name, Errors}) ->
+            io:format("~n~ts passed after ~p retries~n", [Name, length(Errors)]),
+            io:put_chars(lists:last(Errors))
+        end,
+        Flaky
+    ),
+    io:format(
+        "~nFinished in ~.3f seconds~n~p tests, ~p failures",
+        [Duration / 1000, length(Results), length(Failures)]
+    ),
+    case Flaky of
+        [] -> io:put_chars("\n");
+        _ -> io:format(", ~p flaky~n", [length(Flaky)])
+    end,
+    case {Failures, Flaky} of
+        {[_ | _], _} -> 1;
+        {[], [_ | _]} when FailOnFlaky -> 1;
+        {[], _} -> 0
+    end.
+
+name({Module, Function}) ->
+    [string:replace(atom_to_binary(Module), "@", "/", all), ".", atom_to_binary(Function)].
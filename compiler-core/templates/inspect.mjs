@@ -0,0 +1,42 @@
+// Encodes values as JSON for tooling such as `gleam kernel`, which decodes them
+// with the `inspect` module of the compiler and prints them as Gleam would
+// write them using their types. The Erlang target has the same encoder in the
+// `gleam@@inspect` module.
+
+import { BitArray, CustomType, List, UtfCodepoint } from "./prelude.mjs";
+
+export function encode(value) {
+  return JSON.stringify(encodeValue(value));
+}
+
+function encodeValue(value) {
+  if (value === undefined) return { kind: "undefined" };
+  if (typeof value === "boolean") return { kind: "bool", value };
+  if (typeof value === "string") return { kind: "string", value };
+  if (typeof value === "number" && Number.isFinite(value)) {
+    return { kind: "number", value };
+  }
+  if (typeof value === "function") {
+    return { kind: "function", arity: value.length };
+  }
+  if (Array.isArray(value)) {
+    return { kind: "tuple", elements: value.map(encodeValue) };
+  }
+  if (value instanceof List) {
+    return { kind: "list", elements: value.toArray().map(encodeValue) };
+  }
+  if (value instanceof BitArray) {
+    return { kind: "bit_array", bytes: Array.from(value.buffer) };
+  }
+  if (value instanceof UtfCodepoint) {
+    return { kind: "utf_codepoint", value: value.value };
+  }
+  if (value instanceof CustomType) {
+    let fields = Object.keys(value).map((label) => ({
+      label: isNaN(parseInt(label)) ? label : null,
+      value: encodeValue(value[label]),
+    }));
+    return { kind: "record", name: value.constructor.name, fields };
+  }
+  return { kind: "other", text: `//js(${String(value)})` };
+}
@@ -0,0 +1,29 @@
+// Platform shims for web browsers, for use by the JavaScript FFI code of Gleam
+// packages.
+
+export const platform = "browser";
+
+export function randomBytes(length) {
+  const bytes = new Uint8Array(length);
+  // At most 65536 bytes can be filled by each call
+  for (let i = 0; i < length; i += 65536) {
+    globalThis.crypto.getRandomValues(bytes.subarray(i, i + 65536));
+  }
+  return bytes;
+}
+
+export function readFile(path) {
+  throw new globalThis.Error(
+    `Cannot read ${path}: the file system is not available in the browser`,
+  );
+}
+
+export function writeFile(path, _contents) {
+  throw new globalThis.Error(
+    `Cannot write ${path}: the file system is not available in the browser`,
+  );
+}
+
+export function getEnv(_name) {
+  return undefined;
+}
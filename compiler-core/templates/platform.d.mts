@@ -0,0 +1,9 @@
+export const platform: "node" | "browser" | "neutral";
+
+export function randomBytes(length: number): Uint8Array;
+
+export function readFile(path: string): string;
+
+export function writeFile(path: string, contents: string): void;
+
+export function getEnv(name: string): string | undefined;
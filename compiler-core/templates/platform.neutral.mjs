@@ -0,0 +1,42 @@
+// Platform shims for code that may run on any platform, for use by the
+// JavaScript FFI code of Gleam packages. What the platform provides is found
+// when each function is called.
+
+export const platform = "neutral";
+
+export function randomBytes(length) {
+  if (!globalThis.crypto?.getRandomValues) {
+    throw new globalThis.Error("Random bytes are not available on this platform");
+  }
+  const bytes = new Uint8Array(length);
+  // At most 65536 bytes can be filled by each call
+  for (let i = 0; i < length; i += 65536) {
+    globalThis.crypto.getRandomValues(bytes.subarray(i, i + 65536));
+  }
+  return bytes;
+}
+
+function nodeFs(path) {
+  const fs = globalThis.process?.getBuiltinModule?.("node:fs");
+  if (!fs) {
+    throw new globalThis.Error(
+      `Cannot access ${path}: the file system is not available on this platform`,
+    );
+  }
+  return fs;
+}
+
+export function readFile(path) {
+  if (globalThis.Deno) return globalThis.Deno.readTextFileSync(path);
+  return nodeFs(path).readFileSync(path, "utf8");
+}
+
+export function writeFile(path, contents) {
+  if (globalThis.Deno) return globalThis.Deno.writeTextFileSync(path, contents);
+  nodeFs(path).writeFileSync(path, contents);
+}
+
+export function getEnv(name) {
+  if (globalThis.Deno) return globalThis.Deno.env.get(name);
+  return globalThis.process?.env?.[name];
+}
@@ -0,0 +1,29 @@
+// Platform shims for Node.js and the runtimes compatible with it, for use by
+// the JavaScript FFI code of Gleam packages.
+
+import { readFileSync, writeFileSync } from "node:fs";
+import { webcrypto } from "node:crypto";
+import process from "node:process";
+
+export const platform = "node";
+
+export function randomBytes(length) {
+  const bytes = new Uint8Array(length);
+  // At most 65536 bytes can be filled by each call
+  for (let i = 0; i < length; i += 65536) {
+    webcrypto.getRandomValues(bytes.subarray(i, i + 65536));
+  }
+  return bytes;
+}
+
+export function readFile(path) {
+  return readFileSync(path, "utf8");
+}
+
+export function writeFile(path, contents) {
+  writeFileSync(path, contents);
+}
+
+export function getEnv(name) {
+  return process.env[name];
+}
@@ -71,3 +71,9 @@ export function remainderInt(a: number, b: number): number;
 export function divideInt(a: number, b: number): number;
 
 export function divideFloat(a: number, b: number): number;
+
+export function setErrorFormatter(
+  formatter: (error: globalThis.Error) => string,
+): void;
+
+export function formatError(error: globalThis.Error): string;
@@ -358,3 +358,28 @@ export function makeError(variant, module, line, fn, message, extra) {
   for (let k in extra) error[k] = extra[k];
   return error;
 }
+
+let errorFormatter = defaultErrorFormatter;
+
+// Sets the function used by `formatError` to render errors raised by Gleam
+// code, such as by `panic`, `todo`, and `let assert`.
+export function setErrorFormatter(formatter) {
+  errorFormatter = formatter;
+}
+
+export function formatError(error) {
+  return errorFormatter(error);
+}
+
+// Values are not shown, as the prelude does not know how to show them as Gleam
+// would. `gleam run` and `gleam test` use the formatter of `crash.mjs`, which
+// does.
+function defaultErrorFormatter(error) {
+  let lines = [`error: ${error.message}`];
+  lines.push(`  module: ${error.module}`);
+  lines.push(`  function: ${error.fn}`);
+  lines.push(`  line: ${error.line}`);
+  if ("expression" in error) lines.push(`  expression: ${error.expression}`);
+  if ("value_type" in error) lines.push(`  value type: ${error.value_type}`);
+  return lines.join("\n");
+}
@@ -0,0 +1,76 @@
+// The test runner of `gleam test` when the tests are split into shards, run
+// in parallel, or retried. The tests found by the compiler are imported by the
+// `gleam.test.mjs` entrypoint, which runs them with this module a number at a
+// time, so that tests that wait on promises run alongside each other. A dot is
+// printed for each test that passes, an F for each that fails, and a ? for
+// each that only passes when run again, followed by the errors of the failed
+// and flaky tests. The Erlang target has the same runner in the `gleam@@test`
+// module.
+
+import { install, formatCrash } from "./crash.mjs";
+
+// The options are an object of:
+// - parallel: the number of tests to run at once.
+// - retries: the number of times a failing test is run again before it fails.
+// - failOnFlaky: whether to fail when a test fails and then passes.
+export async function run(tests, { parallel, retries, failOnFlaky }) {
+  install();
+  let started = Date.now();
+  let results = [];
+  let next = 0;
+
+  async function worker() {
+    while (next < tests.length) {
+      let index = next++;
+      let [module, name, test] = tests[index];
+      let errors = [];
+      while (true) {
+        try {
+          await test();
+          break;
+        } catch (caught) {
+          errors.push(formatCrash(caught));
+          if (errors.length > retries) break;
+        }
+      }
+      let passed = errors.length <= retries;
+      write(!passed ? "F" : errors.length > 0 ? "?" : ".");
+      results.push({ index, module, name, passed, errors });
+    }
+  }
+
+  let workers = Array.from({ length: Math.max(parallel, 1) }, worker);
+  await Promise.all(workers);
+
+  results.sort((a, b) => a.index - b.index);
+  let failures = results.filter((result) => !result.passed);
+  let flaky = results.filter((r) => r.passed && r.errors.length > 0);
+  write("\n");
+  for (let { module, name, errors } of failures) {
+    write(`\n${module}.${name}\n${errors.at(-1)}\n`);
+  }
+  for (let { module, name, errors } of flaky) {
+    let retried = errors.length;
+    write(`\n${module}.${name} passed after ${retried} retries\n`);
+    write(`${errors.at(-1)}\n`);
+  }
+  let seconds = ((Date.now() - started) / 1000).toFixed(3);
+  write(`\nFinished in ${seconds} seconds\n`);
+  write(`${results.length} tests, ${failures.length} failures`);
+  write(flaky.length === 0 ? "\n" : `, ${flaky.length} flaky\n`);
+  let failed = failures.length > 0 || (failOnFlaky && flaky.length > 0);
+  exit(failed ? 1 : 0);
+}
+
+function write(text) {
+  if (globalThis.Deno) {
+    Deno.stdout.writeSync(new TextEncoder().encode(text));
+  } else {
+    process.stdout.write(text);
+  }
+}
+
+function exit(status) {
+  if (globalThis.Deno) Deno.exit(status);
+  process.exit(status);
+}
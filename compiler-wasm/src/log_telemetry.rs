@@ -1,7 +1,10 @@
-use gleam_core::build::Telemetry;
+use gleam_core::{build::Telemetry, dependency::ResolutionObserver};
+
 #[derive(Debug)]
 pub struct LogTelemetry;
 
+impl ResolutionObserver for LogTelemetry {}
+
 impl Telemetry for LogTelemetry {
     fn compiling_package(&self, name: &str) {
         tracing::info!("Compiling package: {}", name);
@@ -19,7 +22,21 @@ impl Telemetry for LogTelemetry {
         tracing::info!("Resolving package versions");
     }
 
-    fn packages_downloaded(&self, _start: std::time::Instant, count: usize) {
+    fn package_versions_resolved(
+        &self,
+        _start: std::time::Instant,
+        resolution: &gleam_core::dependency::Resolution,
+    ) {
+        tracing::info!("Resolved {} packages", resolution.packages.len());
+    }
+
+    fn package_download_progress(&self, _name: &str, _downloaded: u64, _total: Option<u64>) {}
+
+    fn package_downloaded(&self, name: &str, size: u64) {
+        tracing::info!("Downloaded package: {} ({} bytes)", name, size);
+    }
+
+    fn packages_downloaded(&self, _start: std::time::Instant, count: usize, _size: u64) {
         tracing::info!("Downloaded {} packages", count);
     }
 
@@ -0,0 +1,14 @@
+//! Lexes arbitrary source, checking that the lexer does not panic.
+
+#![no_main]
+
+use gleam_core::{arena::Arena, parse::lexer};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(src) = std::str::from_utf8(data) else {
+        return;
+    };
+    let arena = Arena::new();
+    for _ in lexer::make_tokenizer(src, &arena) {}
+});
@@ -0,0 +1,16 @@
+//! Parses arbitrary source as a module, both as the compiler does and with
+//! the error recovery the language server uses, checking that the parser does
+//! not panic or overflow the stack.
+
+#![no_main]
+
+use gleam_core::parse;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(src) = std::str::from_utf8(data) else {
+        return;
+    };
+    let _ = parse::parse_module(src);
+    let _ = parse::parse_module_with_recovery(src);
+});
@@ -0,0 +1,16 @@
+#!/bin/sh
+# Seeds the corpus of each fuzz target with the Gleam source in this
+# repository and the regression inputs, giving the fuzzer programs to mutate.
+# Files are named by the hash of their contents, as libFuzzer names them.
+set -eu
+
+cd "$(dirname "$0")"
+
+for target in lexer parser; do
+  mkdir -p "corpus/$target"
+  find .. -name '*.gleam' -not -path '../target/*' -not -path '../fuzz/corpus/*' |
+    while read -r file; do
+      hash=$(sha1sum "$file" | cut -d ' ' -f 1)
+      cp "$file" "corpus/$target/$hash"
+    done
+done
@@ -25,6 +25,30 @@ fn duplicate_module() {
     );
 }
 
+#[rustfmt::skip]
+#[test]
+fn env_module() {
+    let output =
+        crate::prepare("./cases/env_module");
+    insta::assert_snapshot!(
+        "env_module",
+        output,
+        "./cases/env_module"
+    );
+}
+
+#[rustfmt::skip]
+#[test]
+fn erlang_app_environment() {
+    let output =
+        crate::prepare("./cases/erlang_app_environment");
+    insta::assert_snapshot!(
+        "erlang_app_environment",
+        output,
+        "./cases/erlang_app_environment"
+    );
+}
+
 #[rustfmt::skip]
 #[test]
 fn erlang_app_generation() {
@@ -37,6 +61,18 @@ fn erlang_app_generation() {
     );
 }
 
+#[rustfmt::skip]
+#[test]
+fn erlang_app_unknown_application() {
+    let output =
+        crate::prepare("./cases/erlang_app_unknown_application");
+    insta::assert_snapshot!(
+        "erlang_app_unknown_application",
+        output,
+        "./cases/erlang_app_unknown_application"
+    );
+}
+
 #[rustfmt::skip]
 #[test]
 fn erlang_bug_752() {
@@ -121,6 +157,30 @@ fn erlang_nested_qualified_constant() {
     );
 }
 
+#[rustfmt::skip]
+#[test]
+fn feature_unknown_attribute() {
+    let output =
+        crate::prepare("./cases/feature_unknown_attribute");
+    insta::assert_snapshot!(
+        "feature_unknown_attribute",
+        output,
+        "./cases/feature_unknown_attribute"
+    );
+}
+
+#[rustfmt::skip]
+#[test]
+fn features() {
+    let output =
+        crate::prepare("./cases/features");
+    insta::assert_snapshot!(
+        "features",
+        output,
+        "./cases/features"
+    );
+}
+
 #[rustfmt::skip]
 #[test]
 fn hello_joe() {
@@ -145,6 +205,18 @@ fn import_cycle() {
     );
 }
 
+#[rustfmt::skip]
+#[test]
+fn import_cycle_multi() {
+    let output =
+        crate::prepare("./cases/import_cycle_multi");
+    insta::assert_snapshot!(
+        "import_cycle_multi",
+        output,
+        "./cases/import_cycle_multi"
+    );
+}
+
 #[rustfmt::skip]
 #[test]
 fn import_shadowed_name_warning() {
@@ -217,6 +289,18 @@ fn javascript_empty() {
     );
 }
 
+#[rustfmt::skip]
+#[test]
+fn javascript_entrypoints() {
+    let output =
+        crate::prepare("./cases/javascript_entrypoints");
+    insta::assert_snapshot!(
+        "javascript_entrypoints",
+        output,
+        "./cases/javascript_entrypoints"
+    );
+}
+
 #[rustfmt::skip]
 #[test]
 fn javascript_import() {
@@ -289,6 +373,42 @@ fn src_importing_test() {
     );
 }
 
+#[rustfmt::skip]
+#[test]
+fn target_specific_definitions() {
+    let output =
+        crate::prepare("./cases/target_specific_definitions");
+    insta::assert_snapshot!(
+        "target_specific_definitions",
+        output,
+        "./cases/target_specific_definitions"
+    );
+}
+
+#[rustfmt::skip]
+#[test]
+fn target_specific_other_target_error() {
+    let output =
+        crate::prepare("./cases/target_specific_other_target_error");
+    insta::assert_snapshot!(
+        "target_specific_other_target_error",
+        output,
+        "./cases/target_specific_other_target_error"
+    );
+}
+
+#[rustfmt::skip]
+#[test]
+fn target_specific_signature_mismatch() {
+    let output =
+        crate::prepare("./cases/target_specific_signature_mismatch");
+    insta::assert_snapshot!(
+        "target_specific_signature_mismatch",
+        output,
+        "./cases/target_specific_signature_mismatch"
+    );
+}
+
 #[rustfmt::skip]
 #[test]
 fn unknown_module_field_in_constant() {
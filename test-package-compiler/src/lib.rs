@@ -9,8 +9,8 @@ mod generated_tests;
 
 use gleam_core::{
     build::{
-        ErlangAppCodegenConfiguration, Mode, NullTelemetry, Outcome, StaleTracker, Target,
-        TargetCodegenConfiguration,
+        env_module, features, ErlangAppCodegenConfiguration, Mode, NullTelemetry, Outcome,
+        StaleTracker, Target, TargetCodegenConfiguration,
     },
     config::PackageConfig,
     io::{memory::InMemoryFileSystem, Content, FileSystemWriter},
@@ -37,11 +37,13 @@ pub fn prepare(path: &str) -> String {
             app_file: Some(ErlangAppCodegenConfiguration {
                 include_dev_deps: true,
                 package_name_overrides: HashMap::new(),
+                project_applications: HashSet::new(),
             }),
         },
         Target::JavaScript => TargetCodegenConfiguration::JavaScript {
             emit_typescript_definitions: config.javascript.typescript_declarations,
             prelude_location: Utf8PathBuf::from("../prelude.mjs"),
+            platform_location: None,
         },
     };
 
@@ -68,6 +70,13 @@ pub fn prepare(path: &str) -> String {
     compiler.write_metadata = true;
     compiler.compile_beam_bytecode = false;
     compiler.copy_native_files = false;
+    compiler.check_other_target = true;
+    compiler.enabled_features = features::resolve(&config, Mode::Dev, &HashMap::new())
+        .unwrap()
+        .remove(&config.name)
+        .unwrap_or_default();
+    let environment = HashMap::from([("GIT_COMMIT".into(), "abc123".into())]);
+    compiler.environment = env_module::variables(&config, &environment);
     let result = compiler.compile(
         &warning_emitter,
         &mut modules,
@@ -8,11 +8,15 @@ import {
   codepointBits,
   divideFloat,
   divideInt,
+  formatError,
   isEqual,
+  makeError,
   stringBits,
   toBitArray,
   toList,
 } from "./prelude.mjs";
+import { formatCrash, inspect as inspectGleam, install } from "./crash.mjs";
+import { encode } from "./inspect.mjs";
 
 let failures = 0;
 let passes = 0;
@@ -455,6 +459,121 @@ assertThrows("Should only construct BitArray from Uint8Array", () => {
   const bs = new BitArray(new ArrayBuffer(8));
 });
 
+//
+// Crash formatter
+//
+
+class Wibble extends CustomType {
+  constructor(name, count) {
+    super();
+    this.name = name;
+    this.count = count;
+  }
+}
+
+assertEqual(inspectGleam(undefined), "Nil");
+assertEqual(inspectGleam(1.5), "1.5");
+assertEqual(inspectGleam('a "b"\n'), '"a \\"b\\"\\n"');
+assertEqual(inspectGleam([1, true]), "#(1, True)");
+assertEqual(inspectGleam(toList([new Ok(1)])), "[Ok(1)]");
+assertEqual(inspectGleam(new Wibble("x", 2)), 'Wibble(name: "x", count: 2)');
+assertEqual(inspectGleam(new BitArray(new Uint8Array([1, 2]))), "<<1, 2>>");
+assertEqual(inspectGleam((a, b) => a), "//fn(_, _) { ... }");
+
+const assertError = makeError("let_assert", "wibble", 3, "main", "No match", {
+  expression: "let assert Ok(x) = y",
+  value_type: "Result(Int, Nil)",
+  value: new Error(undefined),
+});
+assertEqual(
+  formatError(assertError),
+  `error: No match
+  module: wibble
+  function: main
+  line: 3
+  expression: let assert Ok(x) = y
+  value type: Result(Int, Nil)`,
+);
+install();
+assertEqual(
+  formatError(assertError),
+  `error: No match
+  module: wibble
+  function: main
+  line: 3
+  expression: let assert Ok(x) = y
+  value type: Result(Int, Nil)
+  value: Error(Nil)`,
+);
+assertEqual(formatCrash(assertError).split("\n")[0], "error: No match");
+const propertyError = makeError(
+  "property",
+  "wibble",
+  8,
+  "reverse_test",
+  "Property failed",
+  {
+    counterexample: toList([1, 0]),
+    original: toList([5, 3, 0, 8]),
+    shrinks: 4,
+    seed: 42,
+    failure: makeError("panic", "wibble", 10, "reverse_test", "not reversed"),
+  },
+);
+assertEqual(
+  formatError(propertyError),
+  `error: Property failed
+  module: wibble
+  function: reverse_test
+  line: 8
+  counterexample: [1, 0]
+  original input: [5, 3, 0, 8]
+  shrink steps: 4
+  seed: 42
+  failure:
+    error: not reversed
+      module: wibble
+      function: reverse_test
+      line: 10`,
+);
+const otherError = new TypeError("wobble");
+assertEqual(formatCrash(otherError), otherError.stack);
+
+//
+// Value encoding
+//
+
+assertEqual(encode(undefined), '{"kind":"undefined"}');
+assertEqual(encode(true), '{"kind":"bool","value":true}');
+assertEqual(encode(1.5), '{"kind":"number","value":1.5}');
+assertEqual(encode("a"), '{"kind":"string","value":"a"}');
+assertEqual(
+  encode([1, "a"]),
+  '{"kind":"tuple","elements":[{"kind":"number","value":1},{"kind":"string","value":"a"}]}',
+);
+assertEqual(
+  encode(toList([undefined])),
+  '{"kind":"list","elements":[{"kind":"undefined"}]}',
+);
+assertEqual(
+  encode(new BitArray(new Uint8Array([1, 2]))),
+  '{"kind":"bit_array","bytes":[1,2]}',
+);
+assertEqual(
+  encode(new UtfCodepoint(97)),
+  '{"kind":"utf_codepoint","value":97}',
+);
+assertEqual(
+  encode(new Ok(1)),
+  '{"kind":"record","name":"Ok","fields":[{"label":null,"value":{"kind":"number","value":1}}]}',
+);
+assertEqual(
+  encode(new Wibble("x", 2)),
+  '{"kind":"record","name":"Wibble","fields":[{"label":"name","value":{"kind":"string","value":"x"}},{"label":"count","value":{"kind":"number","value":2}}]}',
+);
+assertEqual(encode((a, b) => a), '{"kind":"function","arity":2}');
+assertEqual(encode(NaN), '{"kind":"other","text":"//js(NaN)"}');
+
 //
 // Summary
 //